@@ -188,12 +188,37 @@ pub fn values_with_capacity(datatype: ColumnDataType, capacity: usize) -> Values
     }
 }
 
+/// Returns the values of the timestamps in `unit`.
+///
+/// Timestamps of each unit are in their own field of [Values], conversions of timestamps from
+/// or to [Values] pick the field by this (or [timestamp_values_mut]) so they agree on it.
+pub fn timestamp_values(values: &Values, unit: TimeUnit) -> &Vec<i64> {
+    match unit {
+        TimeUnit::Second => &values.ts_second_values,
+        TimeUnit::Millisecond => &values.ts_millisecond_values,
+        TimeUnit::Microsecond => &values.ts_microsecond_values,
+        TimeUnit::Nanosecond => &values.ts_nanosecond_values,
+    }
+}
+
+/// Returns the mutable values of the timestamps in `unit`, see [timestamp_values].
+pub fn timestamp_values_mut(values: &mut Values, unit: TimeUnit) -> &mut Vec<i64> {
+    match unit {
+        TimeUnit::Second => &mut values.ts_second_values,
+        TimeUnit::Millisecond => &mut values.ts_millisecond_values,
+        TimeUnit::Microsecond => &mut values.ts_microsecond_values,
+        TimeUnit::Nanosecond => &mut values.ts_nanosecond_values,
+    }
+}
+
 // The type of vals must be same.
 pub fn push_vals(column: &mut Column, origin_count: usize, vector: VectorRef) {
     let values = column.values.get_or_insert_with(Values::default);
     let mut null_mask = BitVec::from_slice(&column.null_mask);
+    // Drops the padding bits after the origin rows.
+    null_mask.resize(origin_count, false);
     let len = vector.len();
-    null_mask.reserve_exact(origin_count + len);
+    null_mask.reserve_exact(len);
     null_mask.extend(BitVec::repeat(false, len));
 
     (0..len).for_each(|idx| match vector.get(idx) {
@@ -213,12 +238,7 @@ pub fn push_vals(column: &mut Column, origin_count: usize, vector: VectorRef) {
         Value::Binary(val) => values.binary_values.push(val.to_vec()),
         Value::Date(val) => values.date_values.push(val.val()),
         Value::DateTime(val) => values.datetime_values.push(val.val()),
-        Value::Timestamp(val) => match val.unit() {
-            TimeUnit::Second => values.ts_second_values.push(val.value()),
-            TimeUnit::Millisecond => values.ts_millisecond_values.push(val.value()),
-            TimeUnit::Microsecond => values.ts_microsecond_values.push(val.value()),
-            TimeUnit::Nanosecond => values.ts_nanosecond_values.push(val.value()),
-        },
+        Value::Timestamp(val) => timestamp_values_mut(values, val.unit()).push(val.value()),
         Value::List(_) => unreachable!(),
     });
     column.null_mask = null_mask.into_vec();
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod column_def;
+pub mod row;
 
 pub mod meta {
     pub use greptime_proto::v1::meta::*;
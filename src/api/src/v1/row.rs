@@ -0,0 +1,147 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Row-oriented insert messages, for the clients producing one row at a time, which would
+//! otherwise pivot the rows into the columns of [InsertRequest](crate::v1::InsertRequest).
+//!
+//! The messages are defined in this crate, in the same wire format as they are in the proto:
+//!
+//! ```protobuf
+//! message RowInsertRequests {
+//!   RequestHeader header = 1;
+//!   repeated RowInsertRequest inserts = 2;
+//! }
+//!
+//! message RowInsertRequest {
+//!   string table_name = 1;
+//!   repeated ColumnSchema schema = 2;
+//!   repeated Row rows = 3;
+//!   uint32 region_number = 4;
+//! }
+//!
+//! message ColumnSchema {
+//!   string column_name = 1;
+//!   ColumnDataType datatype = 2;
+//!   Column.SemanticType semantic_type = 3;
+//! }
+//!
+//! message Row { repeated Value values = 1; }
+//!
+//! message Value {
+//!   oneof value_data {
+//!     int32 i8_value = 1;
+//!     ...
+//!     int64 ts_nanosecond_value = 19;
+//!   }
+//! }
+//! ```
+
+use crate::v1::column::SemanticType;
+use crate::v1::{ColumnDataType, RequestHeader};
+
+/// Row inserts of a request. The header is in the same field as the one of a
+/// [GreptimeRequest](crate::v1::GreptimeRequest), so the request is handled like one.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RowInsertRequests {
+    #[prost(message, optional, tag = "1")]
+    pub header: ::core::option::Option<RequestHeader>,
+    #[prost(message, repeated, tag = "2")]
+    pub inserts: ::prost::alloc::vec::Vec<RowInsertRequest>,
+}
+
+/// Rows to insert into a table, each of which has a value for every column of the schema.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RowInsertRequest {
+    #[prost(string, tag = "1")]
+    pub table_name: ::prost::alloc::string::String,
+    /// Columns of the values of the rows, in the same order.
+    #[prost(message, repeated, tag = "2")]
+    pub schema: ::prost::alloc::vec::Vec<ColumnSchema>,
+    #[prost(message, repeated, tag = "3")]
+    pub rows: ::prost::alloc::vec::Vec<Row>,
+    #[prost(uint32, tag = "4")]
+    pub region_number: u32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ColumnSchema {
+    #[prost(string, tag = "1")]
+    pub column_name: ::prost::alloc::string::String,
+    #[prost(enumeration = "ColumnDataType", tag = "2")]
+    pub datatype: i32,
+    #[prost(enumeration = "SemanticType", tag = "3")]
+    pub semantic_type: i32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Row {
+    #[prost(message, repeated, tag = "1")]
+    pub values: ::prost::alloc::vec::Vec<Value>,
+}
+
+/// A value of a row, which is null if `value_data` is absent.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Value {
+    #[prost(
+        oneof = "value::ValueData",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19"
+    )]
+    pub value_data: ::core::option::Option<value::ValueData>,
+}
+
+pub mod value {
+    /// Data of a value, in the same types as the
+    /// [Values](crate::v1::column::Values) of the columns.
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum ValueData {
+        #[prost(int32, tag = "1")]
+        I8Value(i32),
+        #[prost(int32, tag = "2")]
+        I16Value(i32),
+        #[prost(int32, tag = "3")]
+        I32Value(i32),
+        #[prost(int64, tag = "4")]
+        I64Value(i64),
+        #[prost(uint32, tag = "5")]
+        U8Value(u32),
+        #[prost(uint32, tag = "6")]
+        U16Value(u32),
+        #[prost(uint32, tag = "7")]
+        U32Value(u32),
+        #[prost(uint64, tag = "8")]
+        U64Value(u64),
+        #[prost(float, tag = "9")]
+        F32Value(f32),
+        #[prost(double, tag = "10")]
+        F64Value(f64),
+        #[prost(bool, tag = "11")]
+        BoolValue(bool),
+        #[prost(bytes, tag = "12")]
+        BinaryValue(::prost::alloc::vec::Vec<u8>),
+        #[prost(string, tag = "13")]
+        StringValue(::prost::alloc::string::String),
+        #[prost(int32, tag = "14")]
+        DateValue(i32),
+        #[prost(int64, tag = "15")]
+        DatetimeValue(i64),
+        #[prost(int64, tag = "16")]
+        TsSecondValue(i64),
+        #[prost(int64, tag = "17")]
+        TsMillisecondValue(i64),
+        #[prost(int64, tag = "18")]
+        TsMicrosecondValue(i64),
+        #[prost(int64, tag = "19")]
+        TsNanosecondValue(i64),
+    }
+}
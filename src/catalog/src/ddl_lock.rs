@@ -0,0 +1,428 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Locks serializing the DDL on the same tables.
+//!
+//! Every statement changing the schema of existing tables holds the locks of their ids until
+//! it completes, so a DDL always sees the changes of the DDL before it. Statements on several
+//! tables lock them in the order of their ids, which keeps two of them from waiting on each
+//! other.
+//!
+//! The locks are per process. The frontends of a cluster also take the locks of the tables
+//! from the lock service of the metasrv, see [DistDdlLock], so the DDL on the same tables
+//! from different frontends is serialized too.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use common_telemetry::warn;
+use meta_client::client::MetaClient;
+use meta_client::rpc::lock::{LockRequest, UnlockRequest};
+use snafu::ResultExt;
+use table::metadata::TableId;
+use tokio::sync::OwnedMutexGuard;
+
+use crate::error::{DdlBusySnafu, MetaSrvSnafu, Result};
+
+/// Default time a DDL waits for the locks of its tables before it fails.
+pub const DEFAULT_DDL_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time the locks taken from a [DistDdlLock] expire after, so the locks of a frontend failing
+/// amid a DDL are released. A DDL running longer than it isn't serialized with the DDL of the
+/// other frontends anymore.
+const DIST_DDL_LOCK_EXPIRE_SECS: i64 = 60;
+
+pub type DdlLocksRef = Arc<DdlLocks>;
+
+/// Lock shared by the processes running DDL on the same tables, e.g. the frontends of a
+/// cluster.
+#[async_trait]
+pub trait DistDdlLock: Send + Sync {
+    /// Waits for and takes the lock of `name`, returns the key to unlock it.
+    async fn lock(&self, name: Vec<u8>, expire_secs: i64) -> Result<Vec<u8>>;
+
+    async fn unlock(&self, key: Vec<u8>) -> Result<()>;
+}
+
+pub type DistDdlLockRef = Arc<dyn DistDdlLock>;
+
+#[async_trait]
+impl DistDdlLock for MetaClient {
+    async fn lock(&self, name: Vec<u8>, expire_secs: i64) -> Result<Vec<u8>> {
+        let response = MetaClient::lock(self, LockRequest { name, expire_secs })
+            .await
+            .context(MetaSrvSnafu)?;
+        Ok(response.key)
+    }
+
+    async fn unlock(&self, key: Vec<u8>) -> Result<()> {
+        MetaClient::unlock(self, UnlockRequest { key })
+            .await
+            .context(MetaSrvSnafu)
+    }
+}
+
+fn dist_lock_name(table_id: TableId) -> Vec<u8> {
+    format!("__ddl_lock/{table_id}").into_bytes()
+}
+
+/// State of a DDL in [DdlLocks].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DdlState {
+    /// Waiting for the DDL holding the lock of some of its tables.
+    Waiting,
+    /// Holding the locks of all its tables.
+    Running,
+}
+
+impl DdlState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DdlState::Waiting => "Waiting for table lock",
+            DdlState::Running => "Running",
+        }
+    }
+}
+
+/// A point-in-time copy of a DDL waiting for or holding its locks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdlProcess {
+    pub id: u64,
+    pub description: String,
+    pub table_ids: Vec<TableId>,
+    pub state: DdlState,
+    /// Time since the DDL started to wait for its locks.
+    pub elapsed: Duration,
+}
+
+struct DdlEntry {
+    description: String,
+    table_ids: Vec<TableId>,
+    state: DdlState,
+    started: Instant,
+}
+
+/// Per-table locks of the DDL, keyed by table id.
+pub struct DdlLocks {
+    timeout: Duration,
+    next_id: AtomicU64,
+    /// Locks of the tables some DDL holds or waits for, removed when none does.
+    locks: Mutex<HashMap<TableId, Arc<tokio::sync::Mutex<()>>>>,
+    processes: Mutex<BTreeMap<u64, DdlEntry>>,
+    /// Lock shared with the other processes, taken after the locks of this process.
+    dist_lock: Option<DistDdlLockRef>,
+}
+
+impl Default for DdlLocks {
+    fn default() -> Self {
+        Self::new(DEFAULT_DDL_LOCK_TIMEOUT)
+    }
+}
+
+impl DdlLocks {
+    /// Creates the locks, a DDL fails with [DdlBusy](crate::error::Error::DdlBusy) if it can't
+    /// lock its tables in `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            next_id: AtomicU64::new(0),
+            locks: Mutex::new(HashMap::new()),
+            processes: Mutex::new(BTreeMap::new()),
+            dist_lock: None,
+        }
+    }
+
+    /// Also takes the locks of the tables from `dist_lock`, serializing the DDL with the
+    /// other processes sharing it.
+    pub fn with_dist_lock(mut self, dist_lock: DistDdlLockRef) -> Self {
+        self.dist_lock = Some(dist_lock);
+        self
+    }
+
+    /// Locks the tables of the DDL described by `description`. The locks are released when the
+    /// returned guard is dropped.
+    pub async fn lock(
+        self: &Arc<Self>,
+        table_ids: &[TableId],
+        description: impl Into<String>,
+    ) -> Result<DdlGuard> {
+        let mut table_ids = table_ids.to_vec();
+        table_ids.sort_unstable();
+        table_ids.dedup();
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let description = description.into();
+        let _ = self.processes.lock().unwrap().insert(
+            id,
+            DdlEntry {
+                description: description.clone(),
+                table_ids: table_ids.clone(),
+                state: DdlState::Waiting,
+                started: Instant::now(),
+            },
+        );
+        // Created before waiting so the process and the locks taken so far are released if
+        // the wait times out.
+        let mut guard = DdlGuard {
+            locks: self.clone(),
+            id,
+            table_ids: table_ids.clone(),
+            guards: Vec::with_capacity(table_ids.len()),
+            dist_keys: Vec::new(),
+        };
+
+        let acquired = tokio::time::timeout(self.timeout, async {
+            for table_id in &table_ids {
+                let lock = self.table_lock(*table_id);
+                guard.guards.push(lock.lock_owned().await);
+            }
+            if let Some(dist_lock) = &self.dist_lock {
+                for table_id in &table_ids {
+                    let key = dist_lock
+                        .lock(dist_lock_name(*table_id), DIST_DDL_LOCK_EXPIRE_SECS)
+                        .await?;
+                    guard.dist_keys.push(key);
+                }
+            }
+            Ok::<_, crate::error::Error>(())
+        })
+        .await;
+        // A shared lock granted after the timeout is released once it expires.
+        let Ok(acquired) = acquired else {
+            return DdlBusySnafu {
+                description,
+                timeout: self.timeout,
+            }
+            .fail();
+        };
+        acquired?;
+
+        if let Some(entry) = self.processes.lock().unwrap().get_mut(&id) {
+            entry.state = DdlState::Running;
+        }
+        Ok(guard)
+    }
+
+    /// Returns the DDL waiting for or holding their locks, in the order they started.
+    pub fn processes(&self) -> Vec<DdlProcess> {
+        self.processes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, entry)| DdlProcess {
+                id: *id,
+                description: entry.description.clone(),
+                table_ids: entry.table_ids.clone(),
+                state: entry.state,
+                elapsed: entry.started.elapsed(),
+            })
+            .collect()
+    }
+
+    fn table_lock(&self, table_id: TableId) -> Arc<tokio::sync::Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap()
+            .entry(table_id)
+            .or_default()
+            .clone()
+    }
+
+    fn release(&self, id: u64, table_ids: &[TableId]) {
+        let _ = self.processes.lock().unwrap().remove(&id);
+
+        let mut locks = self.locks.lock().unwrap();
+        for table_id in table_ids {
+            // Only the map holds the lock if no other DDL holds or waits for it.
+            if locks
+                .get(table_id)
+                .map(|lock| Arc::strong_count(lock) == 1)
+                .unwrap_or(false)
+            {
+                let _ = locks.remove(table_id);
+            }
+        }
+    }
+}
+
+/// Holds the locks of the tables of a DDL, releases them on drop.
+pub struct DdlGuard {
+    locks: DdlLocksRef,
+    id: u64,
+    table_ids: Vec<TableId>,
+    guards: Vec<OwnedMutexGuard<()>>,
+    /// Keys of the locks taken from the [DistDdlLock].
+    dist_keys: Vec<Vec<u8>>,
+}
+
+impl Drop for DdlGuard {
+    fn drop(&mut self) {
+        self.guards.clear();
+        self.locks.release(self.id, &self.table_ids);
+
+        let Some(dist_lock) = self.locks.dist_lock.clone() else {
+            return;
+        };
+        if self.dist_keys.is_empty() {
+            return;
+        }
+        let dist_keys = std::mem::take(&mut self.dist_keys);
+        common_runtime::spawn_bg(async move {
+            for key in dist_keys {
+                if let Err(e) = dist_lock.unlock(key).await {
+                    warn!("Failed to unlock the DDL lock of a table, error: {e}");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_error::prelude::{ErrorExt, StatusCode};
+    use tokio::sync::oneshot;
+
+    use super::*;
+    use crate::error::Error;
+
+    /// Lock shared by the [DdlLocks] of the tests, like the lock of the metasrv.
+    #[derive(Default)]
+    struct MockDistLock {
+        locks: DdlLocksRef,
+        guards: Mutex<HashMap<Vec<u8>, DdlGuard>>,
+    }
+
+    #[async_trait]
+    impl DistDdlLock for MockDistLock {
+        async fn lock(&self, name: Vec<u8>, _expire_secs: i64) -> Result<Vec<u8>> {
+            let table_id = String::from_utf8(name.clone()).unwrap();
+            let table_id = table_id.trim_start_matches("__ddl_lock/").parse().unwrap();
+            let guard = self.locks.lock(&[table_id], "shared").await?;
+            let _ = self.guards.lock().unwrap().insert(name.clone(), guard);
+            Ok(name)
+        }
+
+        async fn unlock(&self, key: Vec<u8>) -> Result<()> {
+            let _ = self.guards.lock().unwrap().remove(&key);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ddl_on_same_table_serialized() {
+        let locks = Arc::new(DdlLocks::default());
+        let guard = locks.lock(&[1024], "ALTER TABLE demo").await.unwrap();
+
+        let (tx, mut rx) = oneshot::channel();
+        let waiter = {
+            let locks = locks.clone();
+            tokio::spawn(async move {
+                let _guard = locks.lock(&[1024], "DROP TABLE demo").await.unwrap();
+                tx.send(()).unwrap();
+            })
+        };
+        // Waits until the drop is blocked on the lock.
+        while locks.processes().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        let processes = locks.processes();
+        assert_eq!("ALTER TABLE demo", processes[0].description);
+        assert_eq!(DdlState::Running, processes[0].state);
+        assert_eq!("DROP TABLE demo", processes[1].description);
+        assert_eq!(DdlState::Waiting, processes[1].state);
+        assert_eq!(vec![1024], processes[1].table_ids);
+        assert!(rx.try_recv().is_err());
+
+        // Other tables are not blocked.
+        let _other = locks.lock(&[1025], "ALTER TABLE other").await.unwrap();
+
+        drop(guard);
+        waiter.await.unwrap();
+        rx.await.unwrap();
+        assert_eq!(1, locks.processes().len());
+    }
+
+    #[tokio::test]
+    async fn test_ddl_on_multiple_tables() {
+        let locks = Arc::new(DdlLocks::new(Duration::from_secs(5)));
+
+        // Locks the same tables in opposite orders, which would deadlock if they were locked
+        // in the given orders.
+        let handles = (0..10)
+            .map(|i| {
+                let locks = locks.clone();
+                tokio::spawn(async move {
+                    let table_ids = if i % 2 == 0 { [1, 2, 2] } else { [2, 1, 1] };
+                    let _guard = locks.lock(&table_ids, format!("DDL {i}")).await.unwrap();
+                    tokio::task::yield_now().await;
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert!(locks.processes().is_empty());
+        assert!(locks.locks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ddl_lock_timeout() {
+        let locks = Arc::new(DdlLocks::new(Duration::from_millis(100)));
+        let _guard = locks.lock(&[2], "ALTER TABLE b").await.unwrap();
+
+        let err = locks
+            .lock(&[1, 2], "ALTER TABLE a RENAME b")
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::DdlBusy { .. }));
+        assert_eq!(StatusCode::Conflict, err.status_code());
+        assert!(err.status_code().is_retryable());
+        assert!(err.to_string().contains("ALTER TABLE a RENAME b"));
+
+        // The timed out DDL releases the lock of table 1 it took.
+        assert_eq!(1, locks.processes().len());
+        let _guard = locks.lock(&[1], "ALTER TABLE a").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ddl_on_different_processes_serialized() {
+        let dist_lock = Arc::new(MockDistLock::default());
+        let locks = Arc::new(DdlLocks::default().with_dist_lock(dist_lock.clone()));
+        let other_locks =
+            Arc::new(DdlLocks::new(Duration::from_millis(100)).with_dist_lock(dist_lock.clone()));
+
+        let guard = locks.lock(&[1024], "ALTER TABLE demo").await.unwrap();
+        // The DDL of the other process waits for the shared lock.
+        let err = other_locks
+            .lock(&[1024], "DROP TABLE demo")
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::DdlBusy { .. }));
+        // Other tables are not blocked.
+        let _other = other_locks
+            .lock(&[1025], "ALTER TABLE other")
+            .await
+            .unwrap();
+
+        // The shared lock is released in background.
+        drop(guard);
+        let other_locks = Arc::new(DdlLocks::new(Duration::from_secs(5)).with_dist_lock(dist_lock));
+        let _guard = other_locks.lock(&[1024], "DROP TABLE demo").await.unwrap();
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::any::Any;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use common_error::ext::{BoxedError, ErrorExt};
 use common_error::prelude::{Snafu, StatusCode};
@@ -76,6 +77,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid table options of schema {}, source: {}", schema, source))]
+    InvalidSchemaTableOptions {
+        schema: String,
+        #[snafu(backtrace)]
+        source: table::error::Error,
+    },
+
     #[snafu(display("Invalid system catalog key: {:?}", key))]
     InvalidKey {
         key: Option<String>,
@@ -91,6 +99,34 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to serialize value, source: {}", source))]
+    ValueSerialize {
+        source: serde_json::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "System catalog format version {} is newer than the version {} supported, please upgrade to a newer version",
+        version,
+        supported
+    ))]
+    UnsupportedSystemCatalogVersion {
+        version: u32,
+        supported: u32,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Catalog format version {} in metasrv is newer than the version {} supported, please upgrade to a newer version",
+        version,
+        supported
+    ))]
+    UnsupportedRemoteCatalogVersion {
+        version: u32,
+        supported: u32,
+        location: Location,
+    },
+
     #[snafu(display("Table engine not found: {}, source: {}", engine_name, source))]
     TableEngineNotFound {
         engine_name: String,
@@ -117,6 +153,12 @@ pub enum Error {
     #[snafu(display("Table not found: {}", table))]
     TableNotExist { table: String, location: Location },
 
+    #[snafu(display("Table {} is replaying its WAL, retry later", table_name))]
+    TableReplaying {
+        table_name: String,
+        location: Location,
+    },
+
     #[snafu(display("Schema {} already exists", schema))]
     SchemaExists { schema: String, location: Location },
 
@@ -255,11 +297,50 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "DDL busy, `{}` did not get the locks of its tables in {:?} as other DDL on them are running, please retry",
+        description,
+        timeout
+    ))]
+    DdlBusy {
+        description: String,
+        timeout: Duration,
+        location: Location,
+    },
+
     #[snafu(display("Table schema mismatch, source: {}", source))]
     TableSchemaMismatch {
         #[snafu(backtrace)]
         source: table::error::Error,
     },
+
+    #[snafu(display("Failed to scan table {}, source: {}", table, source))]
+    ScanTable {
+        table: String,
+        #[snafu(backtrace)]
+        source: table::error::Error,
+    },
+
+    #[snafu(display("Failed to execute the scan of table {}, source: {}", table, source))]
+    AnalyzeTable {
+        table: String,
+        #[snafu(backtrace)]
+        source: common_query::error::Error,
+    },
+
+    #[snafu(display("Failed to read the scan of table {}, source: {}", table, source))]
+    ReadTableScan {
+        table: String,
+        #[snafu(backtrace)]
+        source: common_recordbatch::error::Error,
+    },
+
+    #[snafu(display("Failed to save the statistics of table {}, source: {}", table, source))]
+    SaveStatistics {
+        table: String,
+        #[snafu(backtrace)]
+        source: table::error::Error,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -273,6 +354,7 @@ impl ErrorExt for Error {
             | Error::IllegalManagerState { .. }
             | Error::CatalogNotFound { .. }
             | Error::InvalidEntryType { .. }
+            | Error::InvalidSchemaTableOptions { .. }
             | Error::InvalidSystemTableDef { .. }
             | Error::ParallelOpenTable { .. } => StatusCode::Unexpected,
 
@@ -280,6 +362,10 @@ impl ErrorExt for Error {
             | Error::EmptyValue { .. }
             | Error::ValueDeserialize { .. } => StatusCode::StorageUnavailable,
 
+            Error::ValueSerialize { .. } => StatusCode::Unexpected,
+            Error::UnsupportedSystemCatalogVersion { .. }
+            | Error::UnsupportedRemoteCatalogVersion { .. } => StatusCode::Unsupported,
+
             Error::SystemCatalogTypeMismatch { .. } => StatusCode::Internal,
 
             Error::ReadSystemCatalog { source, .. } | Error::CreateRecordBatch { source } => {
@@ -291,6 +377,7 @@ impl ErrorExt for Error {
 
             Error::TableExists { .. } => StatusCode::TableAlreadyExists,
             Error::TableNotExist { .. } => StatusCode::TableNotFound,
+            Error::TableReplaying { .. } => StatusCode::StorageUnavailable,
             Error::SchemaExists { .. } | Error::TableEngineNotFound { .. } => {
                 StatusCode::InvalidArguments
             }
@@ -307,6 +394,10 @@ impl ErrorExt for Error {
             Error::MetaSrv { source, .. } => source.status_code(),
             Error::SystemCatalogTableScan { source } => source.status_code(),
             Error::SystemCatalogTableScanExec { source } => source.status_code(),
+            Error::ScanTable { source, .. } => source.status_code(),
+            Error::AnalyzeTable { source, .. } => source.status_code(),
+            Error::ReadTableScan { source, .. } => source.status_code(),
+            Error::SaveStatistics { source, .. } => source.status_code(),
             Error::InvalidTableInfoInCatalog { source } => source.status_code(),
 
             Error::CompileScriptInternal { source }
@@ -315,6 +406,7 @@ impl ErrorExt for Error {
 
             Error::Unimplemented { .. } | Error::NotSupported { .. } => StatusCode::Unsupported,
             Error::QueryAccessDenied { .. } => StatusCode::AccessDenied,
+            Error::DdlBusy { .. } => StatusCode::Conflict,
             Error::Datafusion { .. } => StatusCode::EngineExecuteQuery,
         }
     }
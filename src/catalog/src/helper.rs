@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 
+use api::v1::meta::TableName;
 use common_catalog::error::{
     DeserializeCatalogEntryValueSnafu, Error, InvalidCatalogSnafu, SerializeCatalogEntryValueSnafu,
 };
@@ -23,11 +24,20 @@ use regex::Regex;
 use serde::{Deserialize, Serialize, Serializer};
 use snafu::{ensure, OptionExt, ResultExt};
 use table::metadata::{RawTableInfo, TableId, TableVersion};
+use table::requests::TableOptions;
 
 pub const CATALOG_KEY_PREFIX: &str = "__c";
 pub const SCHEMA_KEY_PREFIX: &str = "__s";
 pub const TABLE_GLOBAL_KEY_PREFIX: &str = "__tg";
 pub const TABLE_REGIONAL_KEY_PREFIX: &str = "__tr";
+pub const DDL_BATCH_KEY_PREFIX: &str = "__ddl_batch";
+pub const REPARTITION_KEY_PREFIX: &str = "__repartition";
+pub const TABLE_INVALIDATION_KEY_PREFIX: &str = "__table_invalidation";
+pub const TABLE_ROUTE_PREFIX: &str = "__meta_table_route";
+/// Key of the format version of the catalog entries in metasrv.
+pub const CATALOG_VERSION_KEY: &str = "__catalog_version";
+/// Prefix of the keys of the datanode leases in metasrv, `{prefix}-{cluster_id}-{node_id}`.
+pub const DN_LEASE_PREFIX: &str = "__meta_dnlease";
 
 const ALPHANUMERICS_NAME_PATTERN: &str = "[a-zA-Z_][a-zA-Z0-9_]*";
 
@@ -90,6 +100,36 @@ pub fn build_table_regional_prefix(
     )
 }
 
+pub fn build_table_route_prefix(catalog: impl AsRef<str>, schema: impl AsRef<str>) -> String {
+    format!(
+        "{}-{}-{}-",
+        TABLE_ROUTE_PREFIX,
+        catalog.as_ref(),
+        schema.as_ref()
+    )
+}
+
+pub fn build_ddl_batch_prefix() -> String {
+    format!("{DDL_BATCH_KEY_PREFIX}-")
+}
+
+pub fn build_repartition_prefix() -> String {
+    format!("{REPARTITION_KEY_PREFIX}-")
+}
+
+pub fn build_table_invalidation_prefix() -> String {
+    format!("{TABLE_INVALIDATION_KEY_PREFIX}-")
+}
+
+/// Builds the key no greater than the keys of the invalidations published at or after
+/// `timestamp_millis`, the start of the range of them.
+pub fn build_table_invalidation_start(timestamp_millis: i64) -> String {
+    format!(
+        "{TABLE_INVALIDATION_KEY_PREFIX}-{:020}",
+        timestamp_millis.max(0)
+    )
+}
+
 /// Table global info has only one key across all datanodes so it does not have `node_id` field.
 #[derive(Clone)]
 pub struct TableGlobalKey {
@@ -251,8 +291,266 @@ impl SchemaKey {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SchemaValue;
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SchemaValue {
+    /// Default options of the tables in the schema, in the canonical format.
+    #[serde(default)]
+    pub table_options: BTreeMap<String, String>,
+}
+
+impl SchemaValue {
+    pub fn new(table_options: &TableOptions) -> Self {
+        Self {
+            table_options: table_options.option_values().into_iter().collect(),
+        }
+    }
+
+    /// Returns the default options of the tables in the schema.
+    pub fn table_options(&self) -> table::Result<TableOptions> {
+        TableOptions::try_from(&HashMap::from_iter(self.table_options.clone()))
+    }
+
+    /// Parses the schema value, which is `null` for the schemas created before they have
+    /// options.
+    pub fn parse(s: impl AsRef<str>) -> Result<Self, Error> {
+        serde_json::from_str::<Option<SchemaValue>>(s.as_ref())
+            .map(Option::unwrap_or_default)
+            .context(DeserializeCatalogEntryValueSnafu { raw: s.as_ref() })
+    }
+
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Result<Self, Error> {
+        Self::parse(&String::from_utf8_lossy(bytes.as_ref()))
+    }
+
+    pub fn as_bytes(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_string(self)
+            .context(SerializeCatalogEntryValueSnafu)?
+            .into_bytes())
+    }
+}
+
+/// Key of the route of a table in the metasrv. The key has the id of the table, so the routes of
+/// the tables of the same name, e.g. during a rename, are kept apart.
+pub struct TableRouteKey<'a> {
+    pub table_id: u64,
+    pub catalog_name: &'a str,
+    pub schema_name: &'a str,
+    pub table_name: &'a str,
+}
+
+impl<'a> TableRouteKey<'a> {
+    pub fn with_table_name(table_id: u64, t: &'a TableName) -> Self {
+        Self {
+            table_id,
+            catalog_name: &t.catalog_name,
+            schema_name: &t.schema_name,
+            table_name: &t.table_name,
+        }
+    }
+
+    pub fn with_table_global_key(table_id: u64, t: &'a TableGlobalKey) -> Self {
+        Self {
+            table_id,
+            catalog_name: &t.catalog_name,
+            schema_name: &t.schema_name,
+            table_name: &t.table_name,
+        }
+    }
+
+    #[inline]
+    pub fn prefix(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            TABLE_ROUTE_PREFIX, self.catalog_name, self.schema_name, self.table_name
+        )
+    }
+
+    #[inline]
+    pub fn key(&self) -> String {
+        format!("{}-{}", self.prefix(), self.table_id)
+    }
+}
+
+/// Key of the journal of a batch of tables created all or nothing, see [DdlBatchValue].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdlBatchKey {
+    pub batch_id: String,
+}
+
+impl Display for DdlBatchKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(DDL_BATCH_KEY_PREFIX)?;
+        f.write_str("-")?;
+        f.write_str(&self.batch_id)
+    }
+}
+
+impl DdlBatchKey {
+    pub fn parse(s: impl AsRef<str>) -> Result<Self, Error> {
+        let key = s.as_ref();
+        let batch_id = key
+            .strip_prefix(&build_ddl_batch_prefix())
+            .filter(|batch_id| !batch_id.is_empty())
+            .context(InvalidCatalogSnafu { key })?;
+        Ok(Self {
+            batch_id: batch_id.to_string(),
+        })
+    }
+}
+
+/// Journal of a batch of tables created all or nothing.
+///
+/// The frontend creating the batch records every table it creates in the journal, and deletes
+/// the journal once the batch is created or rolled back. So the journals left behind are the
+/// batches of the frontends that failed midway, which the metasrv rolls back.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DdlBatchValue {
+    pub tables: Vec<DdlBatchTable>,
+    /// Time the journal was last updated, in milliseconds.
+    pub updated_at_millis: i64,
+}
+
+/// A table created by a batch, identified by its id as the name may be reused by another table
+/// after the table is dropped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DdlBatchTable {
+    pub catalog_name: String,
+    pub schema_name: String,
+    pub table_name: String,
+    pub table_id: TableId,
+}
+
+impl DdlBatchTable {
+    pub fn table_global_key(&self) -> TableGlobalKey {
+        TableGlobalKey {
+            catalog_name: self.catalog_name.clone(),
+            schema_name: self.schema_name.clone(),
+            table_name: self.table_name.clone(),
+        }
+    }
+}
+
+/// Key of the request to repartition a table, see [RepartitionValue]. A table has at most one
+/// repartition in flight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepartitionKey {
+    pub table_id: TableId,
+}
+
+impl Display for RepartitionKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REPARTITION_KEY_PREFIX)?;
+        f.write_str("-")?;
+        f.write_str(&self.table_id.to_string())
+    }
+}
+
+impl RepartitionKey {
+    pub fn parse(s: impl AsRef<str>) -> Result<Self, Error> {
+        let key = s.as_ref();
+        let table_id = key
+            .strip_prefix(&build_repartition_prefix())
+            .and_then(|table_id| table_id.parse().ok())
+            .context(InvalidCatalogSnafu { key })?;
+        Ok(Self { table_id })
+    }
+}
+
+/// Request to repartition a table, put by the frontend executing `ALTER TABLE ... REPARTITION`.
+///
+/// The metasrv leader submits the procedure repartitioning the table for a pending request, and
+/// records the outcome of the procedure in the request. The frontend polls the request until
+/// it's done or failed, then deletes it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RepartitionValue {
+    pub catalog_name: String,
+    pub schema_name: String,
+    pub table_name: String,
+    /// The new partitions of the table in order.
+    pub partitions: Vec<RepartitionPartition>,
+    pub status: RepartitionStatus,
+}
+
+/// A partition of a table, bounded by the serialized exclusive upper bounds of the partition
+/// columns.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RepartitionPartition {
+    pub columns: Vec<String>,
+    pub bounds: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RepartitionStatus {
+    Pending,
+    Running { procedure_id: String },
+    Done,
+    Failed { error: String },
+}
+
+/// Key of the tables invalidated by a DDL, see [TableInvalidationValue]. The keys are ordered by
+/// the time they're published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInvalidationKey {
+    pub timestamp_millis: i64,
+    /// Unique id of the invalidation, keeping apart the ones published at the same time.
+    pub id: String,
+}
+
+impl Display for TableInvalidationKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&build_table_invalidation_start(self.timestamp_millis))?;
+        f.write_str("-")?;
+        f.write_str(&self.id)
+    }
+}
+
+impl TableInvalidationKey {
+    pub fn parse(s: impl AsRef<str>) -> Result<Self, Error> {
+        let key = s.as_ref();
+        let (timestamp_millis, id) = key
+            .strip_prefix(&build_table_invalidation_prefix())
+            .and_then(|key| key.split_once('-'))
+            .filter(|(_, id)| !id.is_empty())
+            .and_then(|(timestamp_millis, id)| Some((timestamp_millis.parse().ok()?, id)))
+            .context(InvalidCatalogSnafu { key })?;
+        Ok(Self {
+            timestamp_millis,
+            id: id.to_string(),
+        })
+    }
+}
+
+/// Tables changed by a DDL, whose cached metadata are stale.
+///
+/// The frontend executing the DDL publishes the tables, and the other frontends, polling the
+/// invalidations published lately, drop the cached metadata of them. The metasrv deletes the
+/// invalidations once they're old enough for every frontend to see them.
+///
+/// The metasrv also publishes the keys of the catalogs, schemas and tables changed through its
+/// store service by any node, whose values cached by the frontends are stale, and the datanodes
+/// removed from the cluster, whose clients the frontends close.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TableInvalidationValue {
+    pub tables: Vec<InvalidatedTable>,
+    #[serde(default)]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub removed_datanodes: Vec<RemovedDatanode>,
+}
+
+/// A datanode whose lease has expired.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RemovedDatanode {
+    pub id: u64,
+    pub addr: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InvalidatedTable {
+    pub catalog_name: String,
+    pub schema_name: String,
+    pub table_name: String,
+}
 
 macro_rules! define_catalog_value {
     ( $($val_ty: ty), *) => {
@@ -281,7 +579,9 @@ define_catalog_value!(
     TableRegionalValue,
     TableGlobalValue,
     CatalogValue,
-    SchemaValue
+    DdlBatchValue,
+    RepartitionValue,
+    TableInvalidationValue
 );
 
 #[cfg(test)]
@@ -300,6 +600,68 @@ mod tests {
         assert_eq!(key, catalog_key.to_string());
     }
 
+    #[test]
+    fn test_ddl_batch_key_value() {
+        let key = DdlBatchKey::parse("__ddl_batch-b1").unwrap();
+        assert_eq!("b1", key.batch_id);
+        assert_eq!("__ddl_batch-b1", key.to_string());
+        assert!(DdlBatchKey::parse("__ddl_batch-").is_err());
+        assert!(DdlBatchKey::parse("__tg-b1").is_err());
+
+        let value = DdlBatchValue {
+            tables: vec![DdlBatchTable {
+                catalog_name: "C".to_string(),
+                schema_name: "S".to_string(),
+                table_name: "T".to_string(),
+                table_id: 1024,
+            }],
+            updated_at_millis: 42,
+        };
+        let bytes = value.as_bytes().unwrap();
+        assert_eq!(value, DdlBatchValue::from_bytes(bytes).unwrap());
+    }
+
+    #[test]
+    fn test_repartition_key_value() {
+        let key = RepartitionKey::parse("__repartition-1024").unwrap();
+        assert_eq!(1024, key.table_id);
+        assert_eq!("__repartition-1024", key.to_string());
+        assert!(RepartitionKey::parse("__repartition-").is_err());
+        assert!(RepartitionKey::parse("__repartition-t").is_err());
+
+        let value = RepartitionValue {
+            catalog_name: "C".to_string(),
+            schema_name: "S".to_string(),
+            table_name: "T".to_string(),
+            partitions: vec![RepartitionPartition {
+                columns: vec!["id".to_string()],
+                bounds: vec!["\"MaxValue\"".to_string()],
+            }],
+            status: RepartitionStatus::Running {
+                procedure_id: "p1".to_string(),
+            },
+        };
+        let bytes = value.as_bytes().unwrap();
+        assert_eq!(value, RepartitionValue::from_bytes(bytes).unwrap());
+    }
+
+    #[test]
+    fn test_table_invalidation_key() {
+        let key = TableInvalidationKey {
+            timestamp_millis: 1024,
+            id: "a-b".to_string(),
+        };
+        let s = key.to_string();
+        assert_eq!("__table_invalidation-00000000000000001024-a-b", s);
+        assert_eq!(key, TableInvalidationKey::parse(&s).unwrap());
+        assert!(s.as_str() >= build_table_invalidation_start(1024).as_str());
+        assert!(s.as_str() < build_table_invalidation_start(1025).as_str());
+
+        assert!(TableInvalidationKey::parse("__table_invalidation-1024").is_err());
+        assert!(TableInvalidationKey::parse("__table_invalidation-1024-").is_err());
+        assert!(TableInvalidationKey::parse("__table_invalidation-t-a").is_err());
+    }
+
     #[test]
     fn test_parse_schema_key() {
         let key = "__s-C-S";
@@ -372,6 +734,23 @@ mod tests {
         assert_eq!(value, deserialized);
     }
 
+    #[test]
+    fn test_schema_value() {
+        // Schemas created before they have options are stored as `null`.
+        assert_eq!(SchemaValue::default(), SchemaValue::parse("null").unwrap());
+
+        let value = SchemaValue::new(&TableOptions {
+            ttl: Some(std::time::Duration::from_secs(3600)),
+            ..Default::default()
+        });
+        let parsed = SchemaValue::from_bytes(value.as_bytes().unwrap()).unwrap();
+        assert_eq!(value, parsed);
+        assert_eq!(
+            Some(std::time::Duration::from_secs(3600)),
+            parsed.table_options().unwrap().ttl
+        );
+    }
+
     #[test]
     fn test_table_global_value_compatibility() {
         let s = r#"{"node_id":1,"regions_id_map":{"1":[0]},"table_info":{"ident":{"table_id":1098,"version":1},"name":"container_cpu_limit","desc":"Created on insertion","catalog_name":"greptime","schema_name":"dd","meta":{"schema":{"column_schemas":[{"name":"container_id","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"container_name","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"docker_image","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"host","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"image_name","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"image_tag","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"interval","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"runtime","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"short_image","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"type","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"dd_value","data_type":{"Float64":{}},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}},{"name":"ts","data_type":{"Timestamp":{"Millisecond":null}},"is_nullable":false,"is_time_index":true,"default_constraint":null,"metadata":{"greptime:time_index":"true"}},{"name":"git.repository_url","data_type":{"String":null},"is_nullable":true,"is_time_index":false,"default_constraint":null,"metadata":{}}],"timestamp_index":11,"version":1},"primary_key_indices":[0,1,2,3,4,5,6,7,8,9,12],"value_indices":[10,11],"engine":"mito","next_column_id":12,"region_numbers":[],"engine_options":{},"options":{},"created_on":"1970-01-01T00:00:00Z"},"table_type":"Base"}}"#;
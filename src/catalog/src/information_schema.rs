@@ -12,33 +12,76 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! The `information_schema` virtual tables of a catalog.
+//!
+//! The tables emit their rows in ascending order of the schema name and the table name (then
+//! the columns in the order of the table schema for the tables listing columns), which is the
+//! order of the names from [schema_names_in_order] and [table_names_in_order]. Drivers paging
+//! through the tables by `LIMIT` and `OFFSET` get each row exactly once, even without an
+//! `ORDER BY`.
+
+mod column_limits;
+mod column_statistics;
+mod columns;
+mod recycled_tables;
+mod table_options;
 mod tables;
 
 use std::any::Any;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use common_catalog::consts::INFORMATION_SCHEMA_NAME;
+use common_time::util::SystemClock;
 use datafusion::datasource::streaming::{PartitionStream, StreamingTable};
 use snafu::ResultExt;
+use table::column_limits::ColumnLimitsOptionsRef;
 use table::table::adapter::TableAdapter;
 use table::TableRef;
 
 use crate::error::{DatafusionSnafu, Result, TableSchemaMismatchSnafu};
+use crate::information_schema::column_limits::InformationSchemaColumnLimits;
+use crate::information_schema::column_statistics::InformationSchemaColumnStatistics;
+use crate::information_schema::columns::InformationSchemaColumns;
+use crate::information_schema::recycled_tables::InformationSchemaRecycledTables;
+use crate::information_schema::table_options::InformationSchemaTableOptions;
 use crate::information_schema::tables::InformationSchemaTables;
-use crate::{CatalogProviderRef, SchemaProvider};
+use crate::recycle_bin::is_recycled_table_name;
+use crate::{CatalogProviderRef, SchemaProvider, SchemaProviderRef};
 
 const TABLES: &str = "tables";
+const COLUMN_STATISTICS: &str = "column_statistics";
+const RECYCLED_TABLES: &str = "recycled_tables";
+const COLUMN_LIMITS: &str = "column_limits";
+const COLUMNS: &str = "columns";
+const TABLE_OPTIONS: &str = "table_options";
+
+/// Names of the tables in the information schema, in ascending order.
+const TABLE_NAMES: [&str; 6] = [
+    COLUMN_LIMITS,
+    COLUMN_STATISTICS,
+    COLUMNS,
+    RECYCLED_TABLES,
+    TABLE_OPTIONS,
+    TABLES,
+];
 
 pub(crate) struct InformationSchemaProvider {
     catalog_name: String,
     catalog_provider: CatalogProviderRef,
+    column_limits: ColumnLimitsOptionsRef,
 }
 
 impl InformationSchemaProvider {
-    pub(crate) fn new(catalog_name: String, catalog_provider: CatalogProviderRef) -> Self {
+    pub(crate) fn new(
+        catalog_name: String,
+        catalog_provider: CatalogProviderRef,
+        column_limits: ColumnLimitsOptionsRef,
+    ) -> Self {
         Self {
             catalog_name,
             catalog_provider,
+            column_limits,
         }
     }
 }
@@ -50,21 +93,48 @@ impl SchemaProvider for InformationSchemaProvider {
     }
 
     async fn table_names(&self) -> Result<Vec<String>> {
-        Ok(vec![TABLES.to_string()])
+        Ok(TABLE_NAMES.iter().map(ToString::to_string).collect())
     }
 
     async fn table(&self, name: &str) -> Result<Option<TableRef>> {
-        let table = if name.eq_ignore_ascii_case(TABLES) {
+        let stream: Arc<dyn PartitionStream> = if name.eq_ignore_ascii_case(TABLES) {
             Arc::new(InformationSchemaTables::new(
                 self.catalog_name.clone(),
                 self.catalog_provider.clone(),
+                Arc::new(SystemClock),
+            ))
+        } else if name.eq_ignore_ascii_case(COLUMN_STATISTICS) {
+            Arc::new(InformationSchemaColumnStatistics::new(
+                self.catalog_name.clone(),
+                self.catalog_provider.clone(),
+            ))
+        } else if name.eq_ignore_ascii_case(RECYCLED_TABLES) {
+            Arc::new(InformationSchemaRecycledTables::new(
+                self.catalog_name.clone(),
+                self.catalog_provider.clone(),
+            ))
+        } else if name.eq_ignore_ascii_case(COLUMN_LIMITS) {
+            Arc::new(InformationSchemaColumnLimits::new(
+                self.catalog_name.clone(),
+                self.catalog_provider.clone(),
+                self.column_limits.clone(),
+            ))
+        } else if name.eq_ignore_ascii_case(COLUMNS) {
+            Arc::new(InformationSchemaColumns::new(
+                self.catalog_name.clone(),
+                self.catalog_provider.clone(),
+            ))
+        } else if name.eq_ignore_ascii_case(TABLE_OPTIONS) {
+            Arc::new(InformationSchemaTableOptions::new(
+                self.catalog_name.clone(),
+                self.catalog_provider.clone(),
             ))
         } else {
             return Ok(None);
         };
 
         let table = Arc::new(
-            StreamingTable::try_new(table.schema().clone(), vec![table]).with_context(|_| {
+            StreamingTable::try_new(stream.schema().clone(), vec![stream]).with_context(|_| {
                 DatafusionSnafu {
                     msg: format!("Failed to get InformationSchema table '{name}'"),
                 }
@@ -75,6 +145,40 @@ impl SchemaProvider for InformationSchemaProvider {
     }
 
     async fn table_exist(&self, name: &str) -> Result<bool> {
-        Ok(matches!(name.to_ascii_lowercase().as_str(), TABLES))
+        Ok(matches!(
+            name.to_ascii_lowercase().as_str(),
+            TABLES | COLUMN_STATISTICS | RECYCLED_TABLES | COLUMN_LIMITS | COLUMNS | TABLE_OPTIONS
+        ))
+    }
+}
+
+/// Returns the names of the schemas of the catalog and the information schema, in ascending
+/// order.
+async fn schema_names_in_order(catalog_provider: &CatalogProviderRef) -> Result<Vec<String>> {
+    let mut schema_names = catalog_provider.schema_names().await?;
+    schema_names.push(INFORMATION_SCHEMA_NAME.to_string());
+    schema_names.sort_unstable();
+    schema_names.dedup();
+    Ok(schema_names)
+}
+
+/// Returns the names of the tables of `schema` in ascending order, except the tables in the
+/// recycle bin.
+async fn table_names_in_order(schema: &SchemaProviderRef) -> Result<Vec<String>> {
+    let mut table_names = schema.table_names().await?;
+    table_names.retain(|table_name| !is_recycled_table_name(table_name));
+    table_names.sort_unstable();
+    Ok(table_names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_names_in_order() {
+        let mut table_names = TABLE_NAMES.to_vec();
+        table_names.sort_unstable();
+        assert_eq!(TABLE_NAMES.to_vec(), table_names);
     }
 }
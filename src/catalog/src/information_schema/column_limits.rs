@@ -0,0 +1,189 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow_schema::SchemaRef as ArrowSchemaRef;
+use common_catalog::consts::INFORMATION_SCHEMA_NAME;
+use common_query::physical_plan::TaskContext;
+use common_recordbatch::RecordBatch;
+use datafusion::datasource::streaming::PartitionStream as DfPartitionStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter as DfRecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
+use datatypes::prelude::{ConcreteDataType, ScalarVectorBuilder, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+use datatypes::vectors::{BooleanVectorBuilder, StringVectorBuilder, UInt32VectorBuilder};
+use snafu::ResultExt;
+use table::column_limits::{ColumnLimitsOptions, ColumnLimitsOptionsRef};
+
+use crate::error::{CreateRecordBatchSnafu, Result};
+use crate::information_schema::{schema_names_in_order, table_names_in_order};
+use crate::CatalogProviderRef;
+
+pub(super) struct InformationSchemaColumnLimits {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_provider: CatalogProviderRef,
+    column_limits: ColumnLimitsOptionsRef,
+}
+
+impl InformationSchemaColumnLimits {
+    pub(super) fn new(
+        catalog_name: String,
+        catalog_provider: CatalogProviderRef,
+        column_limits: ColumnLimitsOptionsRef,
+    ) -> Self {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("table_catalog", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("table_schema", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("table_name", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("column_count", ConcreteDataType::uint32_datatype(), false),
+            ColumnSchema::new("max_columns", ConcreteDataType::uint32_datatype(), false),
+            ColumnSchema::new("near_limit", ConcreteDataType::boolean_datatype(), false),
+        ]));
+        Self {
+            schema,
+            catalog_name,
+            catalog_provider,
+            column_limits,
+        }
+    }
+
+    fn builder(&self) -> InformationSchemaColumnLimitsBuilder {
+        InformationSchemaColumnLimitsBuilder::new(
+            self.schema.clone(),
+            self.catalog_name.clone(),
+            self.catalog_provider.clone(),
+            self.column_limits.clone(),
+        )
+    }
+}
+
+/// Builds the `information_schema.column_limits` table row by row, one row per table with
+/// its number of columns and the max columns of its schema.
+///
+/// `near_limit` flags the tables close to, or already beyond, the max columns, to which
+/// automatic schema evolution will soon be unable to add columns.
+struct InformationSchemaColumnLimitsBuilder {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_provider: CatalogProviderRef,
+    column_limits: ColumnLimitsOptionsRef,
+
+    catalog_names: StringVectorBuilder,
+    schema_names: StringVectorBuilder,
+    table_names: StringVectorBuilder,
+    column_counts: UInt32VectorBuilder,
+    max_columns: UInt32VectorBuilder,
+    near_limits: BooleanVectorBuilder,
+}
+
+impl InformationSchemaColumnLimitsBuilder {
+    fn new(
+        schema: SchemaRef,
+        catalog_name: String,
+        catalog_provider: CatalogProviderRef,
+        column_limits: ColumnLimitsOptionsRef,
+    ) -> Self {
+        Self {
+            schema,
+            catalog_name,
+            catalog_provider,
+            column_limits,
+            catalog_names: StringVectorBuilder::with_capacity(42),
+            schema_names: StringVectorBuilder::with_capacity(42),
+            table_names: StringVectorBuilder::with_capacity(42),
+            column_counts: UInt32VectorBuilder::with_capacity(42),
+            max_columns: UInt32VectorBuilder::with_capacity(42),
+            near_limits: BooleanVectorBuilder::with_capacity(42),
+        }
+    }
+
+    /// Construct the `information_schema.column_limits` virtual table
+    async fn make_column_limits(&mut self) -> Result<RecordBatch> {
+        let catalog_name = self.catalog_name.clone();
+        let options = self.column_limits.clone();
+
+        for schema_name in schema_names_in_order(&self.catalog_provider).await? {
+            if schema_name == INFORMATION_SCHEMA_NAME {
+                continue;
+            }
+
+            let Some(schema) = self.catalog_provider.schema(&schema_name).await? else { continue };
+            for table_name in table_names_in_order(&schema).await? {
+                let Some(table) = schema.table(&table_name).await? else { continue };
+                self.add_table(
+                    &options,
+                    &catalog_name,
+                    &schema_name,
+                    &table_name,
+                    table.schema().num_columns(),
+                );
+            }
+        }
+
+        self.finish()
+    }
+
+    fn add_table(
+        &mut self,
+        options: &ColumnLimitsOptions,
+        catalog_name: &str,
+        schema_name: &str,
+        table_name: &str,
+        column_count: usize,
+    ) {
+        let limits = options.limits_of(schema_name);
+        self.catalog_names.push(Some(catalog_name));
+        self.schema_names.push(Some(schema_name));
+        self.table_names.push(Some(table_name));
+        self.column_counts.push(Some(column_count as u32));
+        self.max_columns.push(Some(limits.max_columns as u32));
+        self.near_limits
+            .push(Some(limits.is_near_limit(column_count)));
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(self.catalog_names.finish()),
+            Arc::new(self.schema_names.finish()),
+            Arc::new(self.table_names.finish()),
+            Arc::new(self.column_counts.finish()),
+            Arc::new(self.max_columns.finish()),
+            Arc::new(self.near_limits.finish()),
+        ];
+        RecordBatch::new(self.schema.clone(), columns).context(CreateRecordBatchSnafu)
+    }
+}
+
+impl DfPartitionStream for InformationSchemaColumnLimits {
+    fn schema(&self) -> &ArrowSchemaRef {
+        self.schema.arrow_schema()
+    }
+
+    fn execute(&self, _: Arc<TaskContext>) -> DfSendableRecordBatchStream {
+        let schema = self.schema().clone();
+        let mut builder = self.builder();
+        Box::pin(DfRecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(async move {
+                builder
+                    .make_column_limits()
+                    .await
+                    .map(|x| x.into_df_record_batch())
+                    .map_err(Into::into)
+            }),
+        ))
+    }
+}
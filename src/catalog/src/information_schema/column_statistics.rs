@@ -0,0 +1,318 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow_schema::SchemaRef as ArrowSchemaRef;
+use common_catalog::consts::INFORMATION_SCHEMA_NAME;
+use common_query::physical_plan::TaskContext;
+use common_recordbatch::RecordBatch;
+use common_telemetry::warn;
+use datafusion::datasource::streaming::PartitionStream as DfPartitionStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter as DfRecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
+use datatypes::prelude::{ConcreteDataType, ScalarVectorBuilder, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+use datatypes::timestamp::TimestampMillisecond;
+use datatypes::vectors::{
+    Float64VectorBuilder, StringVectorBuilder, TimestampMillisecondVectorBuilder,
+    UInt64VectorBuilder,
+};
+use snafu::ResultExt;
+
+use crate::error::{CreateRecordBatchSnafu, Result};
+use crate::information_schema::{schema_names_in_order, table_names_in_order};
+use crate::statistics::{load_statistics, statistics_store, ColumnStatistics};
+use crate::CatalogProviderRef;
+
+pub(super) struct InformationSchemaColumnStatistics {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_provider: CatalogProviderRef,
+}
+
+impl InformationSchemaColumnStatistics {
+    pub(super) fn new(catalog_name: String, catalog_provider: CatalogProviderRef) -> Self {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("table_catalog", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("table_schema", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("table_name", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("column_name", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("min_value", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("max_value", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("approx_distinct", ConcreteDataType::uint64_datatype(), true),
+            ColumnSchema::new("null_fraction", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new(
+                "computed_at",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                true,
+            ),
+        ]));
+        Self {
+            schema,
+            catalog_name,
+            catalog_provider,
+        }
+    }
+
+    fn builder(&self) -> InformationSchemaColumnStatisticsBuilder {
+        InformationSchemaColumnStatisticsBuilder::new(
+            self.schema.clone(),
+            self.catalog_name.clone(),
+            self.catalog_provider.clone(),
+        )
+    }
+}
+
+/// Builds the `information_schema.column_statistics` table row by row, one row per column.
+///
+/// Statistics are only read from the ones saved by `analyze_table`, so building the table never
+/// scans the tables but the one of the persisted statistics. Columns of tables not analyzed have
+/// null statistics.
+struct InformationSchemaColumnStatisticsBuilder {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_provider: CatalogProviderRef,
+
+    catalog_names: StringVectorBuilder,
+    schema_names: StringVectorBuilder,
+    table_names: StringVectorBuilder,
+    column_names: StringVectorBuilder,
+    min_values: StringVectorBuilder,
+    max_values: StringVectorBuilder,
+    approx_distincts: UInt64VectorBuilder,
+    null_fractions: Float64VectorBuilder,
+    computed_ats: TimestampMillisecondVectorBuilder,
+}
+
+impl InformationSchemaColumnStatisticsBuilder {
+    fn new(schema: SchemaRef, catalog_name: String, catalog_provider: CatalogProviderRef) -> Self {
+        Self {
+            schema,
+            catalog_name,
+            catalog_provider,
+            catalog_names: StringVectorBuilder::with_capacity(42),
+            schema_names: StringVectorBuilder::with_capacity(42),
+            table_names: StringVectorBuilder::with_capacity(42),
+            column_names: StringVectorBuilder::with_capacity(42),
+            min_values: StringVectorBuilder::with_capacity(42),
+            max_values: StringVectorBuilder::with_capacity(42),
+            approx_distincts: UInt64VectorBuilder::with_capacity(42),
+            null_fractions: Float64VectorBuilder::with_capacity(42),
+            computed_ats: TimestampMillisecondVectorBuilder::with_capacity(42),
+        }
+    }
+
+    /// Construct the `information_schema.column_statistics` virtual table
+    async fn make_column_statistics(&mut self) -> Result<RecordBatch> {
+        let catalog_name = self.catalog_name.clone();
+        // The statistics computed in this process are still shown if the persisted ones can't
+        // be read.
+        if let Err(e) = load_statistics(&self.catalog_provider).await {
+            warn!("Failed to load the statistics of catalog {catalog_name}, error: {e}");
+        }
+
+        for schema_name in schema_names_in_order(&self.catalog_provider).await? {
+            if schema_name == INFORMATION_SCHEMA_NAME {
+                continue;
+            }
+
+            let Some(schema) = self.catalog_provider.schema(&schema_name).await? else { continue };
+            for table_name in table_names_in_order(&schema).await? {
+                let Some(table) = schema.table(&table_name).await? else { continue };
+                let statistics = statistics_store().get(&table.table_info());
+                for column_schema in table.schema().column_schemas() {
+                    let column_name = &column_schema.name;
+                    let column = statistics
+                        .as_ref()
+                        .and_then(|statistics| statistics.column(column_name));
+                    self.add_column(
+                        &catalog_name,
+                        &schema_name,
+                        &table_name,
+                        column_name,
+                        column,
+                        statistics.as_ref().map(|statistics| statistics.computed_at),
+                    );
+                }
+            }
+        }
+
+        self.finish()
+    }
+
+    fn add_column(
+        &mut self,
+        catalog_name: &str,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        statistics: Option<&ColumnStatistics>,
+        computed_at: Option<i64>,
+    ) {
+        self.catalog_names.push(Some(catalog_name));
+        self.schema_names.push(Some(schema_name));
+        self.table_names.push(Some(table_name));
+        self.column_names.push(Some(column_name));
+        self.min_values
+            .push(statistics.and_then(|s| s.min_value.as_deref()));
+        self.max_values
+            .push(statistics.and_then(|s| s.max_value.as_deref()));
+        self.approx_distincts
+            .push(statistics.map(|s| s.approx_distinct));
+        self.null_fractions
+            .push(statistics.map(|s| s.null_fraction));
+        self.computed_ats
+            .push(statistics.and(computed_at).map(TimestampMillisecond::from));
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(self.catalog_names.finish()),
+            Arc::new(self.schema_names.finish()),
+            Arc::new(self.table_names.finish()),
+            Arc::new(self.column_names.finish()),
+            Arc::new(self.min_values.finish()),
+            Arc::new(self.max_values.finish()),
+            Arc::new(self.approx_distincts.finish()),
+            Arc::new(self.null_fractions.finish()),
+            Arc::new(self.computed_ats.finish()),
+        ];
+        RecordBatch::new(self.schema.clone(), columns).context(CreateRecordBatchSnafu)
+    }
+}
+
+impl DfPartitionStream for InformationSchemaColumnStatistics {
+    fn schema(&self) -> &ArrowSchemaRef {
+        self.schema.arrow_schema()
+    }
+
+    fn execute(&self, _: Arc<TaskContext>) -> DfSendableRecordBatchStream {
+        let schema = self.schema().clone();
+        let mut builder = self.builder();
+        Box::pin(DfRecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(async move {
+                builder
+                    .make_column_statistics()
+                    .await
+                    .map(|x| x.into_df_record_batch())
+                    .map_err(Into::into)
+            }),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use common_query::logical_plan::Expr;
+    use common_query::physical_plan::PhysicalPlanRef;
+    use common_time::Timestamp;
+    use datatypes::value::Value;
+    use table::metadata::TableInfoRef;
+    use table::table::numbers::NumbersTable;
+    use table::{Table, TableRef};
+
+    use super::*;
+    use crate::local::{MemoryCatalogProvider, MemorySchemaProvider};
+    use crate::statistics::TableStatistics;
+
+    /// Table that must not be scanned.
+    struct NoScanTable(NumbersTable);
+
+    #[async_trait::async_trait]
+    impl Table for NoScanTable {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.0.schema()
+        }
+
+        fn table_info(&self) -> TableInfoRef {
+            self.0.table_info()
+        }
+
+        async fn scan(
+            &self,
+            _projection: Option<&Vec<usize>>,
+            _filters: &[Expr],
+            _limit: Option<usize>,
+        ) -> table::Result<PhysicalPlanRef> {
+            unreachable!("reading column statistics must not scan the table")
+        }
+    }
+
+    async fn make_column_statistics(catalog_provider: CatalogProviderRef) -> RecordBatch {
+        InformationSchemaColumnStatistics::new("greptime".to_string(), catalog_provider)
+            .builder()
+            .make_column_statistics()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_column_statistics_without_scan() {
+        let table = NoScanTable(NumbersTable::with_name(
+            1024,
+            "column_statistics_table".to_string(),
+        ));
+        let table_info = table.table_info();
+        let schema_provider = Arc::new(MemorySchemaProvider::new());
+        let _ = schema_provider
+            .register_table_sync(table_info.name.clone(), Arc::new(table) as TableRef)
+            .unwrap();
+        let catalog_provider = Arc::new(MemoryCatalogProvider::new());
+        let _ = catalog_provider
+            .register_schema_sync("public".to_string(), schema_provider)
+            .unwrap();
+
+        // Not analyzed.
+        let batch = make_column_statistics(catalog_provider.clone()).await;
+        assert_eq!(1, batch.num_rows());
+        assert_eq!(Value::from("number"), batch.column(3).get(0));
+        for i in 4..batch.num_columns() {
+            assert!(batch.column(i).is_null(0));
+        }
+
+        statistics_store().insert(
+            &table_info,
+            Arc::new(TableStatistics {
+                table_id: table_info.ident.table_id,
+                columns: vec![ColumnStatistics {
+                    column_name: "number".to_string(),
+                    min_value: Some("0".to_string()),
+                    max_value: Some("9".to_string()),
+                    approx_distinct: 10,
+                    null_fraction: 0.0,
+                }],
+                sampled_rows: 10,
+                computed_at: 1000,
+            }),
+        );
+        let batch = make_column_statistics(catalog_provider).await;
+        assert_eq!(1, batch.num_rows());
+        assert_eq!(Value::from("0"), batch.column(4).get(0));
+        assert_eq!(Value::from("9"), batch.column(5).get(0));
+        assert_eq!(Value::from(10u64), batch.column(6).get(0));
+        assert_eq!(Value::from(0.0f64), batch.column(7).get(0));
+        assert_eq!(
+            Value::Timestamp(Timestamp::new_millisecond(1000)),
+            batch.column(8).get(0)
+        );
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow_schema::SchemaRef as ArrowSchemaRef;
+use common_catalog::consts::INFORMATION_SCHEMA_NAME;
+use common_query::physical_plan::TaskContext;
+use common_recordbatch::RecordBatch;
+use datafusion::datasource::streaming::PartitionStream as DfPartitionStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter as DfRecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
+use datatypes::prelude::{ConcreteDataType, DataType, ScalarVectorBuilder, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+use datatypes::vectors::{StringVectorBuilder, UInt32VectorBuilder};
+use snafu::ResultExt;
+
+use crate::error::{CreateRecordBatchSnafu, Result};
+use crate::information_schema::{schema_names_in_order, table_names_in_order, COLUMNS};
+use crate::CatalogProviderRef;
+
+const SEMANTIC_TYPE_TAG: &str = "tag";
+const SEMANTIC_TYPE_FIELD: &str = "field";
+const SEMANTIC_TYPE_TIMESTAMP: &str = "timestamp";
+const IS_NULLABLE_YES: &str = "YES";
+const IS_NULLABLE_NO: &str = "NO";
+
+pub(super) struct InformationSchemaColumns {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_provider: CatalogProviderRef,
+}
+
+impl InformationSchemaColumns {
+    pub(super) fn new(catalog_name: String, catalog_provider: CatalogProviderRef) -> Self {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("table_catalog", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("table_schema", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("table_name", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("column_name", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ordinal_position",
+                ConcreteDataType::uint32_datatype(),
+                false,
+            ),
+            ColumnSchema::new("data_type", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("is_nullable", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("column_default", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("semantic_type", ConcreteDataType::string_datatype(), false),
+        ]));
+        Self {
+            schema,
+            catalog_name,
+            catalog_provider,
+        }
+    }
+
+    fn builder(&self) -> InformationSchemaColumnsBuilder {
+        InformationSchemaColumnsBuilder::new(
+            self.schema.clone(),
+            self.catalog_name.clone(),
+            self.catalog_provider.clone(),
+        )
+    }
+}
+
+/// Builds the `information_schema.columns` table row by row, one row per column of every table.
+///
+/// `ordinal_position` is the 1-based position of the column in its table. `semantic_type` is
+/// `tag` for the primary key columns, `timestamp` for the time index column and `field` for
+/// the others. `is_nullable` is `YES` or `NO`, as in the SQL standard.
+struct InformationSchemaColumnsBuilder {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_provider: CatalogProviderRef,
+
+    catalog_names: StringVectorBuilder,
+    schema_names: StringVectorBuilder,
+    table_names: StringVectorBuilder,
+    column_names: StringVectorBuilder,
+    ordinal_positions: UInt32VectorBuilder,
+    data_types: StringVectorBuilder,
+    is_nullables: StringVectorBuilder,
+    column_defaults: StringVectorBuilder,
+    semantic_types: StringVectorBuilder,
+}
+
+impl InformationSchemaColumnsBuilder {
+    fn new(schema: SchemaRef, catalog_name: String, catalog_provider: CatalogProviderRef) -> Self {
+        Self {
+            schema,
+            catalog_name,
+            catalog_provider,
+            catalog_names: StringVectorBuilder::with_capacity(42),
+            schema_names: StringVectorBuilder::with_capacity(42),
+            table_names: StringVectorBuilder::with_capacity(42),
+            column_names: StringVectorBuilder::with_capacity(42),
+            ordinal_positions: UInt32VectorBuilder::with_capacity(42),
+            data_types: StringVectorBuilder::with_capacity(42),
+            is_nullables: StringVectorBuilder::with_capacity(42),
+            column_defaults: StringVectorBuilder::with_capacity(42),
+            semantic_types: StringVectorBuilder::with_capacity(42),
+        }
+    }
+
+    /// Construct the `information_schema.columns` virtual table
+    async fn make_columns(&mut self) -> Result<RecordBatch> {
+        let catalog_name = self.catalog_name.clone();
+
+        for schema_name in schema_names_in_order(&self.catalog_provider).await? {
+            if schema_name == INFORMATION_SCHEMA_NAME {
+                self.add_own_columns(&catalog_name);
+                continue;
+            }
+
+            let Some(schema) = self.catalog_provider.schema(&schema_name).await? else { continue };
+            for table_name in table_names_in_order(&schema).await? {
+                let Some(table) = schema.table(&table_name).await? else { continue };
+                let table_info = table.table_info();
+                let primary_key_indices = &table_info.meta.primary_key_indices;
+                let table_schema = &table_info.meta.schema;
+                let timestamp_index = table_schema.timestamp_index();
+                for (idx, column_schema) in table_schema.column_schemas().iter().enumerate() {
+                    let semantic_type = if timestamp_index == Some(idx) {
+                        SEMANTIC_TYPE_TIMESTAMP
+                    } else if primary_key_indices.contains(&idx) {
+                        SEMANTIC_TYPE_TAG
+                    } else {
+                        SEMANTIC_TYPE_FIELD
+                    };
+                    self.add_column(
+                        &catalog_name,
+                        &schema_name,
+                        &table_name,
+                        idx,
+                        column_schema,
+                        semantic_type,
+                    );
+                }
+            }
+        }
+
+        self.finish()
+    }
+
+    /// Describes the columns of this table itself.
+    fn add_own_columns(&mut self, catalog_name: &str) {
+        let own_schema = self.schema.clone();
+        for (idx, column_schema) in own_schema.column_schemas().iter().enumerate() {
+            self.add_column(
+                catalog_name,
+                INFORMATION_SCHEMA_NAME,
+                COLUMNS,
+                idx,
+                column_schema,
+                SEMANTIC_TYPE_FIELD,
+            );
+        }
+    }
+
+    fn add_column(
+        &mut self,
+        catalog_name: &str,
+        schema_name: &str,
+        table_name: &str,
+        idx: usize,
+        column_schema: &ColumnSchema,
+        semantic_type: &str,
+    ) {
+        let column_default = column_schema
+            .default_constraint()
+            .map(|constraint| constraint.to_string());
+        self.catalog_names.push(Some(catalog_name));
+        self.schema_names.push(Some(schema_name));
+        self.table_names.push(Some(table_name));
+        self.column_names.push(Some(&column_schema.name));
+        self.ordinal_positions.push(Some(idx as u32 + 1));
+        self.data_types.push(Some(column_schema.data_type.name()));
+        let is_nullable = if column_schema.is_nullable() {
+            IS_NULLABLE_YES
+        } else {
+            IS_NULLABLE_NO
+        };
+        self.is_nullables.push(Some(is_nullable));
+        self.column_defaults.push(column_default.as_deref());
+        self.semantic_types.push(Some(semantic_type));
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(self.catalog_names.finish()),
+            Arc::new(self.schema_names.finish()),
+            Arc::new(self.table_names.finish()),
+            Arc::new(self.column_names.finish()),
+            Arc::new(self.ordinal_positions.finish()),
+            Arc::new(self.data_types.finish()),
+            Arc::new(self.is_nullables.finish()),
+            Arc::new(self.column_defaults.finish()),
+            Arc::new(self.semantic_types.finish()),
+        ];
+        RecordBatch::new(self.schema.clone(), columns).context(CreateRecordBatchSnafu)
+    }
+}
+
+impl DfPartitionStream for InformationSchemaColumns {
+    fn schema(&self) -> &ArrowSchemaRef {
+        self.schema.arrow_schema()
+    }
+
+    fn execute(&self, _: Arc<TaskContext>) -> DfSendableRecordBatchStream {
+        let schema = self.schema().clone();
+        let mut builder = self.builder();
+        Box::pin(DfRecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(async move {
+                builder
+                    .make_columns()
+                    .await
+                    .map(|x| x.into_df_record_batch())
+                    .map_err(Into::into)
+            }),
+        ))
+    }
+}
@@ -15,30 +15,41 @@
 use std::sync::Arc;
 
 use arrow_schema::SchemaRef as ArrowSchemaRef;
+use async_stream::try_stream;
 use common_catalog::consts::INFORMATION_SCHEMA_NAME;
 use common_query::physical_plan::TaskContext;
 use common_recordbatch::RecordBatch;
+use common_time::util::ClockRef;
 use datafusion::datasource::streaming::PartitionStream as DfPartitionStream;
 use datafusion::physical_plan::stream::RecordBatchStreamAdapter as DfRecordBatchStreamAdapter;
 use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
 use datatypes::prelude::{ConcreteDataType, ScalarVectorBuilder, VectorRef};
 use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
-use datatypes::vectors::{StringVectorBuilder, UInt32VectorBuilder};
+use datatypes::vectors::{Float64VectorBuilder, StringVectorBuilder, UInt32VectorBuilder};
+use futures::{Stream, StreamExt};
 use snafu::ResultExt;
 use table::metadata::TableType;
+use table::table::write_freshness_secs;
 
 use crate::error::{CreateRecordBatchSnafu, Result};
-use crate::information_schema::TABLES;
-use crate::CatalogProviderRef;
+use crate::information_schema::{schema_names_in_order, TABLE_NAMES};
+use crate::recycle_bin::is_recycled_table_name;
+use crate::{table_names_stream, CatalogProviderRef, NAMES_PAGE_SIZE};
 
 pub(super) struct InformationSchemaTables {
     schema: SchemaRef,
     catalog_name: String,
     catalog_provider: CatalogProviderRef,
+    /// Clock of the current time the freshness of the tables is computed at.
+    clock: ClockRef,
 }
 
 impl InformationSchemaTables {
-    pub(super) fn new(catalog_name: String, catalog_provider: CatalogProviderRef) -> Self {
+    pub(super) fn new(
+        catalog_name: String,
+        catalog_provider: CatalogProviderRef,
+        clock: ClockRef,
+    ) -> Self {
         let schema = Arc::new(Schema::new(vec![
             ColumnSchema::new("table_catalog", ConcreteDataType::string_datatype(), false),
             ColumnSchema::new("table_schema", ConcreteDataType::string_datatype(), false),
@@ -46,11 +57,13 @@ impl InformationSchemaTables {
             ColumnSchema::new("table_type", ConcreteDataType::string_datatype(), false),
             ColumnSchema::new("table_id", ConcreteDataType::uint32_datatype(), true),
             ColumnSchema::new("engine", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("freshness", ConcreteDataType::float64_datatype(), true),
         ]));
         Self {
             schema,
             catalog_name,
             catalog_provider,
+            clock,
         }
     }
 
@@ -59,17 +72,20 @@ impl InformationSchemaTables {
             self.schema.clone(),
             self.catalog_name.clone(),
             self.catalog_provider.clone(),
+            self.clock.clone(),
         )
     }
 }
 
-/// Builds the `information_schema.TABLE` table row by row
+/// Builds the `information_schema.TABLE` table row by row, in the order of the schema and
+/// table names.
 ///
 /// Columns are based on <https://www.postgresql.org/docs/current/infoschema-columns.html>
 struct InformationSchemaTablesBuilder {
     schema: SchemaRef,
     catalog_name: String,
     catalog_provider: CatalogProviderRef,
+    clock: ClockRef,
 
     catalog_names: StringVectorBuilder,
     schema_names: StringVectorBuilder,
@@ -77,58 +93,92 @@ struct InformationSchemaTablesBuilder {
     table_types: StringVectorBuilder,
     table_ids: UInt32VectorBuilder,
     engines: StringVectorBuilder,
+    /// Seconds since the last write to the table, null if the table has not been written since
+    /// opened, or the table doesn't keep such stats.
+    freshness: Float64VectorBuilder,
 }
 
 impl InformationSchemaTablesBuilder {
-    fn new(schema: SchemaRef, catalog_name: String, catalog_provider: CatalogProviderRef) -> Self {
+    fn new(
+        schema: SchemaRef,
+        catalog_name: String,
+        catalog_provider: CatalogProviderRef,
+        clock: ClockRef,
+    ) -> Self {
         Self {
             schema,
             catalog_name,
             catalog_provider,
+            clock,
             catalog_names: StringVectorBuilder::with_capacity(42),
             schema_names: StringVectorBuilder::with_capacity(42),
             table_names: StringVectorBuilder::with_capacity(42),
             table_types: StringVectorBuilder::with_capacity(42),
             table_ids: UInt32VectorBuilder::with_capacity(42),
             engines: StringVectorBuilder::with_capacity(42),
+            freshness: Float64VectorBuilder::with_capacity(42),
         }
     }
 
-    /// Construct the `information_schema.tables` virtual table
-    async fn make_tables(&mut self) -> Result<RecordBatch> {
-        let catalog_name = self.catalog_name.clone();
+    /// Construct the `information_schema.tables` virtual table, streaming a record batch for
+    /// every [NAMES_PAGE_SIZE] rows so large catalogs are not built at once.
+    fn make_tables(mut self) -> impl Stream<Item = Result<RecordBatch>> {
+        try_stream!({
+            let catalog_name = self.catalog_name.clone();
+            let now_millis = self.clock.now_millis();
+            let mut rows = 0;
 
-        for schema_name in self.catalog_provider.schema_names().await? {
-            if schema_name == INFORMATION_SCHEMA_NAME {
-                continue;
-            }
+            for schema_name in schema_names_in_order(&self.catalog_provider).await? {
+                if schema_name == INFORMATION_SCHEMA_NAME {
+                    // The information schema tables themselves.
+                    for table_name in TABLE_NAMES {
+                        self.add_table(
+                            &catalog_name,
+                            INFORMATION_SCHEMA_NAME,
+                            table_name,
+                            TableType::View,
+                            None,
+                            None,
+                            None,
+                        );
+                        rows += 1;
+                    }
+                    continue;
+                }
+
+                let Some(schema) = self.catalog_provider.schema(&schema_name).await? else { continue };
+                let mut table_names = Box::pin(table_names_stream(schema.clone()));
+                while let Some(table_name) = table_names.next().await {
+                    let table_name = table_name?;
+                    if is_recycled_table_name(&table_name) {
+                        continue;
+                    }
+                    let Some(table) = schema.table(&table_name).await? else { continue };
+                    let table_info = table.table_info();
+                    let freshness = table
+                        .region_stats()
+                        .ok()
+                        .and_then(|stats| write_freshness_secs(&stats, now_millis));
+                    self.add_table(
+                        &catalog_name,
+                        &schema_name,
+                        &table_name,
+                        table.table_type(),
+                        Some(table_info.ident.table_id),
+                        Some(&table_info.meta.engine),
+                        freshness,
+                    );
+                    rows += 1;
 
-            let Some(schema) = self.catalog_provider.schema(&schema_name).await? else { continue };
-            for table_name in schema.table_names().await? {
-                let Some(table) = schema.table(&table_name).await? else { continue };
-                let table_info = table.table_info();
-                self.add_table(
-                    &catalog_name,
-                    &schema_name,
-                    &table_name,
-                    table.table_type(),
-                    Some(table_info.ident.table_id),
-                    Some(&table_info.meta.engine),
-                );
+                    if rows == NAMES_PAGE_SIZE {
+                        rows = 0;
+                        yield self.finish()?;
+                    }
+                }
             }
-        }
 
-        // Add a final list for the information schema tables themselves
-        self.add_table(
-            &catalog_name,
-            INFORMATION_SCHEMA_NAME,
-            TABLES,
-            TableType::View,
-            None,
-            None,
-        );
-
-        self.finish()
+            yield self.finish()?;
+        })
     }
 
     fn add_table(
@@ -139,17 +189,15 @@ impl InformationSchemaTablesBuilder {
         table_type: TableType,
         table_id: Option<u32>,
         engine: Option<&str>,
+        freshness: Option<f64>,
     ) {
         self.catalog_names.push(Some(catalog_name));
         self.schema_names.push(Some(schema_name));
         self.table_names.push(Some(table_name));
-        self.table_types.push(Some(match table_type {
-            TableType::Base => "BASE TABLE",
-            TableType::View => "VIEW",
-            TableType::Temporary => "LOCAL TEMPORARY",
-        }));
+        self.table_types.push(Some(table_type.as_sql_str()));
         self.table_ids.push(table_id);
         self.engines.push(engine);
+        self.freshness.push(freshness);
     }
 
     fn finish(&mut self) -> Result<RecordBatch> {
@@ -160,6 +208,7 @@ impl InformationSchemaTablesBuilder {
             Arc::new(self.table_types.finish()),
             Arc::new(self.table_ids.finish()),
             Arc::new(self.engines.finish()),
+            Arc::new(self.freshness.finish()),
         ];
         RecordBatch::new(self.schema.clone(), columns).context(CreateRecordBatchSnafu)
     }
@@ -172,16 +221,71 @@ impl DfPartitionStream for InformationSchemaTables {
 
     fn execute(&self, _: Arc<TaskContext>) -> DfSendableRecordBatchStream {
         let schema = self.schema().clone();
-        let mut builder = self.builder();
+        let builder = self.builder();
         Box::pin(DfRecordBatchStreamAdapter::new(
             schema,
-            futures::stream::once(async move {
-                builder
-                    .make_tables()
-                    .await
-                    .map(|x| x.into_df_record_batch())
-                    .map_err(Into::into)
-            }),
+            builder
+                .make_tables()
+                .map(|x| x.map(|x| x.into_df_record_batch()).map_err(Into::into)),
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use datatypes::value::Value;
+    use futures::TryStreamExt;
+    use mito::table::test_util::{self, MockClock, TestEngineComponents, TABLE_NAME};
+
+    use super::*;
+    use crate::local::{MemoryCatalogProvider, MemorySchemaProvider};
+
+    /// Returns the freshness of table `TABLE_NAME` in `information_schema.tables`.
+    async fn freshness_of(tables: &InformationSchemaTables) -> Value {
+        let batches: Vec<RecordBatch> = tables.builder().make_tables().try_collect().await.unwrap();
+        for batch in batches {
+            for i in 0..batch.num_rows() {
+                if batch.column(2).get(i) == Value::from(TABLE_NAME) {
+                    return batch.column(6).get(i);
+                }
+            }
+        }
+        unreachable!("table {TABLE_NAME} is not listed")
+    }
+
+    #[tokio::test]
+    async fn test_tables_freshness() {
+        let clock = Arc::new(MockClock::default());
+        clock.set(10000);
+        let TestEngineComponents {
+            table_ref: table,
+            dir: _dir,
+            ..
+        } = test_util::setup_test_engine_and_table_with_clock(clock.clone()).await;
+        let schema_provider = Arc::new(MemorySchemaProvider::new());
+        let _ = schema_provider
+            .register_table_sync(TABLE_NAME.to_string(), table.clone())
+            .unwrap();
+        let catalog_provider = Arc::new(MemoryCatalogProvider::new());
+        let _ = catalog_provider
+            .register_schema_sync("public".to_string(), schema_provider)
+            .unwrap();
+        let tables =
+            InformationSchemaTables::new("greptime".to_string(), catalog_provider, clock.clone());
+
+        // Not written since opened.
+        assert_eq!(Value::Null, freshness_of(&tables).await);
+
+        test_util::setup_table(table.clone()).await;
+        assert_eq!(Value::from(0.0f64), freshness_of(&tables).await);
+
+        clock.set(12500);
+        assert_eq!(Value::from(2.5f64), freshness_of(&tables).await);
+        clock.set(70000);
+        assert_eq!(Value::from(60.0f64), freshness_of(&tables).await);
+
+        // Written again.
+        test_util::setup_table(table).await;
+        assert_eq!(Value::from(0.0f64), freshness_of(&tables).await);
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interning of catalog, schema and table names.
+//!
+//! With tens of thousands of tables, the same catalog and schema names are otherwise
+//! duplicated in every registration request and in-memory map entry.
+
+use std::collections::HashSet;
+use std::mem;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+
+/// Unused names are purged when the number of names reaches this, or twice the number of
+/// names left by the last purge.
+const MIN_PURGE_THRESHOLD: usize = 1024;
+
+lazy_static! {
+    static ref NAMES: NameInterner = NameInterner::default();
+}
+
+/// Returns the shared copy of `name` from the process wide interner.
+pub fn intern(name: &str) -> Arc<str> {
+    NAMES.intern(name)
+}
+
+/// A set of shared names.
+///
+/// Names that are no longer referenced outside the interner are dropped lazily, when the
+/// set grows past the purge threshold.
+#[derive(Debug)]
+pub struct NameInterner {
+    inner: RwLock<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    names: HashSet<Arc<str>>,
+    purge_threshold: usize,
+}
+
+impl Default for NameInterner {
+    fn default() -> Self {
+        Self {
+            inner: RwLock::new(Inner {
+                names: HashSet::new(),
+                purge_threshold: MIN_PURGE_THRESHOLD,
+            }),
+        }
+    }
+}
+
+impl NameInterner {
+    pub fn intern(&self, name: &str) -> Arc<str> {
+        if let Some(interned) = self.inner.read().unwrap().names.get(name) {
+            return interned.clone();
+        }
+
+        let mut inner = self.inner.write().unwrap();
+        if let Some(interned) = inner.names.get(name) {
+            return interned.clone();
+        }
+        if inner.names.len() >= inner.purge_threshold {
+            inner.names.retain(|name| Arc::strong_count(name) > 1);
+            inner.purge_threshold = (inner.names.len() * 2).max(MIN_PURGE_THRESHOLD);
+        }
+        let interned: Arc<str> = Arc::from(name);
+        inner.names.insert(interned.clone());
+        interned
+    }
+
+    /// Returns the number of interned names, including the unused ones not purged yet.
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Estimates the heap memory held by the interner in bytes: the names with the
+    /// reference counts in front of them, and the slots of the set.
+    pub fn estimated_heap_size(&self) -> usize {
+        let inner = self.inner.read().unwrap();
+        let names = inner
+            .names
+            .iter()
+            .map(|name| name.len() + 2 * mem::size_of::<usize>())
+            .sum::<usize>();
+        names + inner.names.capacity() * mem::size_of::<Arc<str>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let interner = NameInterner::default();
+        let a = interner.intern("public");
+        let b = interner.intern(&String::from("public"));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(1, interner.len());
+
+        let c = interner.intern("private");
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(2, interner.len());
+    }
+
+    #[test]
+    fn test_purge_unused_names() {
+        let interner = NameInterner::default();
+        let kept = interner.intern("kept");
+        for i in 1..MIN_PURGE_THRESHOLD {
+            let _ = interner.intern(&format!("table_{i}"));
+        }
+        assert_eq!(MIN_PURGE_THRESHOLD, interner.len());
+
+        // Reaching the threshold purges the names only referenced by the interner.
+        let _ = interner.intern("another");
+        assert_eq!(2, interner.len());
+        assert!(Arc::ptr_eq(&kept, &interner.intern("kept")));
+    }
+}
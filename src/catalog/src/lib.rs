@@ -15,29 +15,42 @@
 #![feature(assert_matches)]
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
 use api::v1::meta::{RegionStat, TableName};
-use common_telemetry::{info, warn};
+use async_stream::try_stream;
+use common_telemetry::{error, info, warn};
+use futures::{Stream, StreamExt};
 use snafu::ResultExt;
+use table::column_limits::{ColumnLimitsOptions, ColumnLimitsOptionsRef};
 use table::engine::{EngineContext, TableEngineRef};
 use table::metadata::TableId;
-use table::requests::CreateTableRequest;
+use table::requests::{CreateTableRequest, TableOptions};
 use table::TableRef;
 
 use crate::error::{CreateTableSnafu, Result};
-pub use crate::schema::{SchemaProvider, SchemaProviderRef};
+use crate::local::NameResolution;
+use crate::replay::ReplayProgressRef;
+pub use crate::schema::{table_names_stream, SchemaProvider, SchemaProviderRef};
 
+pub mod ddl_lock;
 pub mod error;
 pub mod helper;
 pub(crate) mod information_schema;
+pub mod interner;
 pub mod local;
+mod metrics;
+pub mod recycle_bin;
 pub mod remote;
+pub mod replay;
 pub mod schema;
+pub mod statistics;
 pub mod system;
 pub mod table_source;
 pub mod tables;
+pub mod ttl_purger;
 
 /// Represents a catalog, comprising a number of named schemas.
 #[async_trait::async_trait]
@@ -49,6 +62,20 @@ pub trait CatalogProvider: Sync + Send {
     /// Retrieves the list of available schema names in this catalog.
     async fn schema_names(&self) -> Result<Vec<String>>;
 
+    /// Retrieves at most `limit` schema names in this catalog in ascending order, after the
+    /// name `start_after` if it's given, and whether there are more names after them.
+    ///
+    /// The default implementation sorts all the names from [CatalogProvider::schema_names].
+    async fn schema_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        let mut schema_names = self.schema_names().await?;
+        schema_names.sort_unstable();
+        Ok(paginate(schema_names, start_after, limit))
+    }
+
     /// Registers schema to this catalog.
     async fn register_schema(
         &self,
@@ -58,15 +85,85 @@ pub trait CatalogProvider: Sync + Send {
 
     /// Retrieves a specific schema from the catalog by name, provided it exists.
     async fn schema(&self, name: &str) -> Result<Option<SchemaProviderRef>>;
+
+    /// Returns whether the schema exists in the catalog, for the callers that don't need its
+    /// provider.
+    ///
+    /// The default implementation retrieves the schema by [CatalogProvider::schema].
+    async fn schema_exist(&self, name: &str) -> Result<bool> {
+        Ok(self.schema(name).await?.is_some())
+    }
 }
 
 pub type CatalogProviderRef = Arc<dyn CatalogProvider>;
 
+/// Number of names listed at a time by consumers walking through all the schemas or tables,
+/// which bounds the memory for catalogs of many tables.
+pub const NAMES_PAGE_SIZE: usize = 1024;
+
+/// Returns at most `limit` of the ascending `names` after the name `start_after` if it's
+/// given, and whether there are more names after them.
+pub fn paginate<T: AsRef<str>>(
+    names: impl IntoIterator<Item = T>,
+    start_after: Option<&str>,
+    limit: usize,
+) -> (Vec<T>, bool) {
+    let mut names = names
+        .into_iter()
+        .skip_while(|name| start_after.map_or(false, |start| name.as_ref() <= start));
+    let page = names.by_ref().take(limit).collect();
+    (page, names.next().is_some())
+}
+
+/// Streams the schema names of `catalog` in ascending order, listing [NAMES_PAGE_SIZE] names
+/// at a time.
+pub fn schema_names_stream(catalog: CatalogProviderRef) -> impl Stream<Item = Result<String>> {
+    try_stream!({
+        let mut start_after = None;
+        loop {
+            let (schema_names, more) = catalog
+                .schema_names_paginated(start_after.as_deref(), NAMES_PAGE_SIZE)
+                .await?;
+            start_after = schema_names.last().cloned();
+            for schema_name in schema_names {
+                yield schema_name;
+            }
+            if !more || start_after.is_none() {
+                break;
+            }
+        }
+    })
+}
+
 #[async_trait::async_trait]
 pub trait CatalogManager: Send + Sync {
     /// Starts a catalog manager.
     async fn start(&self) -> Result<()>;
 
+    /// Returns true if [start](CatalogManager::start) has completed, so the tables are
+    /// available.
+    fn is_started(&self) -> bool {
+        true
+    }
+
+    /// Returns the column limits of the tables in the catalogs, which the DDLs, both explicit
+    /// and on insertion, are checked against.
+    fn column_limits(&self) -> ColumnLimitsOptionsRef {
+        Arc::new(ColumnLimitsOptions::default())
+    }
+
+    /// Returns how the names of the schemas and tables of the user catalogs are resolved, the
+    /// DDLs pass the registered names to the engines.
+    fn name_resolution(&self) -> NameResolution {
+        NameResolution::Exact
+    }
+
+    /// Returns the progress of replaying the tables opened by [start](CatalogManager::start),
+    /// or `None` if the manager doesn't replay tables.
+    fn replay_progress(&self) -> Option<ReplayProgressRef> {
+        None
+    }
+
     async fn register_catalog(
         &self,
         name: String,
@@ -98,6 +195,13 @@ pub trait CatalogManager: Send + Sync {
 
     async fn schema(&self, catalog: &str, schema: &str) -> Result<Option<SchemaProviderRef>>;
 
+    /// Returns whether the schema exists in the catalog.
+    ///
+    /// The default implementation retrieves the schema by [CatalogManager::schema].
+    async fn schema_exist(&self, catalog: &str, schema: &str) -> Result<bool> {
+        Ok(self.schema(catalog, schema).await?.is_some())
+    }
+
     /// Returns the table by catalog, schema and table name.
     async fn table(
         &self,
@@ -123,15 +227,34 @@ pub struct RegisterSystemTableRequest {
     pub open_hook: Option<OpenSystemTableHook>,
 }
 
+/// The names are interned, see [RegisterTableRequest::new].
 #[derive(Clone)]
 pub struct RegisterTableRequest {
-    pub catalog: String,
-    pub schema: String,
-    pub table_name: String,
+    pub catalog: Arc<str>,
+    pub schema: Arc<str>,
+    pub table_name: Arc<str>,
     pub table_id: TableId,
     pub table: TableRef,
 }
 
+impl RegisterTableRequest {
+    pub fn new(
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+        table_id: TableId,
+        table: TableRef,
+    ) -> Self {
+        Self {
+            catalog: interner::intern(catalog),
+            schema: interner::intern(schema),
+            table_name: interner::intern(table_name),
+            table_id,
+            table,
+        }
+    }
+}
+
 impl Debug for RegisterTableRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RegisterTableRequest")
@@ -164,6 +287,8 @@ pub struct DeregisterTableRequest {
 pub struct RegisterSchemaRequest {
     pub catalog: String,
     pub schema: String,
+    /// Default options of the tables in the schema.
+    pub table_options: TableOptions,
 }
 
 pub trait CatalogProviderFactory {
@@ -174,96 +299,667 @@ pub trait SchemaProviderFactory {
     fn create(&self, catalog_name: String, schema_name: String) -> SchemaProviderRef;
 }
 
+/// The max number of system table requests handled concurrently.
+const SYSTEM_TABLE_REQUEST_CONCURRENCY: usize = 4;
+
+/// Handles the registered system table requests concurrently.
+///
+/// Failed requests are put back to `sys_table_requests`, so they are retried the next time
+/// the catalog manager starts. For a table that was created but whose open hook failed,
+/// the retry finds the table already registered and reruns the hook.
 pub(crate) async fn handle_system_table_request<'a, M: CatalogManager>(
     manager: &'a M,
     engine: TableEngineRef,
     sys_table_requests: &'a mut Vec<RegisterSystemTableRequest>,
 ) -> Result<()> {
-    for req in sys_table_requests.drain(..) {
-        let catalog_name = &req.create_table_request.catalog_name;
-        let schema_name = &req.create_table_request.schema_name;
-        let table_name = &req.create_table_request.table_name;
-        let table_id = req.create_table_request.id;
-
-        let table = manager.table(catalog_name, schema_name, table_name).await?;
-        let table = if let Some(table) = table {
-            table
-        } else {
-            let table = engine
-                .create_table(&EngineContext::default(), req.create_table_request.clone())
-                .await
-                .with_context(|_| CreateTableSnafu {
-                    table_info: common_catalog::format_full_table_name(
-                        catalog_name,
-                        schema_name,
-                        table_name,
-                    ),
-                })?;
-            manager
-                .register_table(RegisterTableRequest {
+    let results = futures::stream::iter(sys_table_requests.drain(..))
+        .map(|req| {
+            let engine = engine.clone();
+            async move {
+                let result = handle_one_system_table_request(manager, engine, &req).await;
+                (req, result)
+            }
+        })
+        .buffer_unordered(SYSTEM_TABLE_REQUEST_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut first_error = None;
+    for (req, result) in results {
+        if let Err(e) = result {
+            let table_name = &req.create_table_request.table_name;
+            error!(e; "Failed to handle system table request of {table_name}, will retry on next start");
+            sys_table_requests.push(req);
+            let _ = first_error.get_or_insert(e);
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+async fn handle_one_system_table_request<M: CatalogManager>(
+    manager: &M,
+    engine: TableEngineRef,
+    req: &RegisterSystemTableRequest,
+) -> Result<()> {
+    let catalog_name = &req.create_table_request.catalog_name;
+    let schema_name = &req.create_table_request.schema_name;
+    let table_name = &req.create_table_request.table_name;
+    let table_id = req.create_table_request.id;
+
+    let table = manager.table(catalog_name, schema_name, table_name).await?;
+    let table = if let Some(table) = table {
+        table
+    } else {
+        let table = engine
+            .create_table(&EngineContext::default(), req.create_table_request.clone())
+            .await
+            .with_context(|_| CreateTableSnafu {
+                table_info: common_catalog::format_full_table_name(
+                    catalog_name,
+                    schema_name,
+                    table_name,
+                ),
+            })?;
+        let register_result = manager
+            .register_table(RegisterTableRequest::new(
+                catalog_name,
+                schema_name,
+                table_name,
+                table_id,
+                table.clone(),
+            ))
+            .await;
+        if let Err(e) = register_result {
+            // The registration may fail halfway, don't leave a partially registered table.
+            let deregister_result = manager
+                .deregister_table(DeregisterTableRequest {
                     catalog: catalog_name.clone(),
                     schema: schema_name.clone(),
                     table_name: table_name.clone(),
-                    table_id,
-                    table: table.clone(),
                 })
-                .await?;
-            info!("Created and registered system table: {table_name}");
-            table
-        };
-        if let Some(hook) = req.open_hook {
-            (hook)(table)?;
+                .await;
+            if let Err(e) = deregister_result {
+                warn!("Failed to deregister system table {table_name}, err: {e:?}");
+            }
+            return Err(e);
         }
+        info!("Created and registered system table: {table_name}");
+        table
+    };
+    if let Some(hook) = &req.open_hook {
+        (hook)(table)?;
     }
     Ok(())
 }
 
+/// Key in [RegionStat]'s attrs of the max time index value written to the region since it was
+/// opened, in milliseconds.
+pub const REGION_STAT_MAX_TIMESTAMP_KEY: &str = "max_timestamp_millis";
+/// Key in [RegionStat]'s attrs of the wall-clock time of the last write to the region since it
+/// was opened, in milliseconds.
+pub const REGION_STAT_LAST_WRITE_KEY: &str = "last_write_millis";
+/// Key in [RegionStat]'s attrs of the size of memtables that triggers a flush of the region.
+pub const REGION_STAT_FLUSH_THRESHOLD_KEY: &str = "flush_threshold_bytes";
+/// Key in [RegionStat]'s attrs that is `true` if writes to the region skip the WAL, so its
+/// unflushed data is lost on crash.
+pub const REGION_STAT_WAL_DISABLED_KEY: &str = "wal_disabled";
+/// Key in [RegionStat]'s attrs of the bytes of the region's SSTs in the object store, including
+/// the ones pending purge.
+pub const REGION_STAT_STORAGE_BYTES_KEY: &str = "storage_bytes";
+/// Key in [RegionStat]'s attrs of the bytes of the region's memtables, which approximates its
+/// unflushed data in the WAL.
+pub const REGION_STAT_MEMTABLE_BYTES_KEY: &str = "memtable_bytes";
+
+/// Default number of tables whose stats are collected concurrently by [datanode_stat].
+pub const DEFAULT_STAT_CONCURRENCY: usize = 16;
+
 /// The stat of regions in the datanode node.
 /// The number of regions can be got from len of vec.
 ///
+/// Lists the names of all the tables first, then looks up the tables and collects their stats
+/// with at most `concurrency` tables at a time, so a datanode hosting thousands of tables
+/// doesn't delay its heartbeat by looking them up and collecting their stats one by one.
+///
 /// Ignores any errors occurred during iterating regions. The intention of this method is to
 /// collect region stats that will be carried in Datanode's heartbeat to Metasrv, so it's a
 /// "try our best" job.
-pub async fn datanode_stat(catalog_manager: &CatalogManagerRef) -> (u64, Vec<RegionStat>) {
-    let mut region_number: u64 = 0;
-    let mut region_stats = Vec::new();
+pub async fn datanode_stat(
+    catalog_manager: &CatalogManagerRef,
+    concurrency: usize,
+) -> (u64, Vec<RegionStat>) {
+    let mut tables = Vec::new();
 
-    let Ok(catalog_names) = catalog_manager.catalog_names().await else { return (region_number, region_stats) };
+    let Ok(catalog_names) = catalog_manager.catalog_names().await else { return (0, Vec::new()) };
     for catalog_name in catalog_names {
         let Ok(Some(catalog)) = catalog_manager.catalog(&catalog_name).await else { continue };
 
-        let Ok(schema_names) = catalog.schema_names().await else { continue };
-        for schema_name in schema_names {
+        // Lists the names by pages, as a datanode may serve tens of thousands of tables.
+        let mut schema_names = Box::pin(schema_names_stream(catalog.clone()));
+        while let Some(Ok(schema_name)) = schema_names.next().await {
             let Ok(Some(schema)) = catalog.schema(&schema_name).await else { continue };
 
-            let Ok(table_names) = schema.table_names().await else { continue };
-            for table_name in table_names {
-                let Ok(Some(table)) = schema.table(&table_name).await else { continue };
-
-                let region_numbers = &table.table_info().meta.region_numbers;
-                region_number += region_numbers.len() as u64;
-
-                match table.region_stats() {
-                    Ok(stats) => {
-                        let stats = stats.into_iter().map(|stat| RegionStat {
-                            region_id: stat.region_id,
-                            table_name: Some(TableName {
-                                catalog_name: catalog_name.clone(),
-                                schema_name: schema_name.clone(),
-                                table_name: table_name.clone(),
-                            }),
-                            approximate_bytes: stat.disk_usage_bytes as i64,
-                            ..Default::default()
-                        });
-
-                        region_stats.extend(stats);
-                    }
-                    Err(e) => {
-                        warn!("Failed to get region status, err: {:?}", e);
-                    }
-                };
+            let mut table_names = Box::pin(table_names_stream(schema.clone()));
+            while let Some(Ok(table_name)) = table_names.next().await {
+                tables.push((
+                    schema.clone(),
+                    TableName {
+                        catalog_name: catalog_name.clone(),
+                        schema_name: schema_name.clone(),
+                        table_name,
+                    },
+                ));
             }
         }
     }
+
+    let stats = futures::stream::iter(tables)
+        .map(|(schema, table_name)| async move {
+            let Ok(Some(table)) = schema.table(&table_name.table_name).await else { return None };
+            // The stats of a table may be collected from its storage, so they are collected in
+            // the blocking threads, without blocking the lookups and stats of other tables.
+            let stat = common_runtime::spawn_blocking_bg(move || table_stat(table_name, &table));
+            match stat.await {
+                Ok(stat) => Some(stat),
+                Err(e) => {
+                    warn!("Failed to collect the stats of a table, err: {e:?}");
+                    None
+                }
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    // Sums up the tables regardless of the order they complete in.
+    let mut region_number: u64 = 0;
+    let mut region_stats = Vec::new();
+    for (table_region_number, table_region_stats) in stats.into_iter().flatten() {
+        region_number += table_region_number;
+        region_stats.extend(table_region_stats);
+    }
     (region_number, region_stats)
 }
+
+/// Returns the number of regions of the `table` and the stats of them, which are empty if the
+/// table fails to report its stats.
+fn table_stat(table_name: TableName, table: &TableRef) -> (u64, Vec<RegionStat>) {
+    let region_number = table.table_info().meta.region_numbers.len() as u64;
+
+    let stats = match table.region_stats() {
+        Ok(stats) => stats,
+        Err(e) => {
+            warn!("Failed to get region status, err: {:?}", e);
+            return (region_number, Vec::new());
+        }
+    };
+    let stats = stats
+        .into_iter()
+        .map(|stat| {
+            let mut attrs: HashMap<_, _> = [
+                (REGION_STAT_MAX_TIMESTAMP_KEY, stat.max_timestamp_millis),
+                (REGION_STAT_LAST_WRITE_KEY, stat.last_write_millis),
+                (
+                    REGION_STAT_FLUSH_THRESHOLD_KEY,
+                    stat.flush_threshold_bytes.map(|x| x as i64),
+                ),
+                (
+                    REGION_STAT_STORAGE_BYTES_KEY,
+                    Some(stat.storage_bytes as i64),
+                ),
+                (
+                    REGION_STAT_MEMTABLE_BYTES_KEY,
+                    Some(stat.memtable_bytes as i64),
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(k, v)| v.map(|v| (k.to_string(), v.to_string())))
+            .collect();
+            if stat.wal_disabled {
+                attrs.insert(REGION_STAT_WAL_DISABLED_KEY.to_string(), true.to_string());
+            }
+
+            RegionStat {
+                region_id: stat.region_id,
+                table_name: Some(table_name.clone()),
+                approximate_bytes: stat.disk_usage_bytes as i64,
+                approximate_rows: stat.approximate_rows as i64,
+                attrs,
+                ..Default::default()
+            }
+        })
+        .collect();
+    (region_number, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MITO_ENGINE};
+    use datatypes::schema::RawSchema;
+    use table::engine::{TableEngine, TableReference};
+    use table::requests::{AlterTableRequest, DropTableRequest, OpenTableRequest};
+    use table::table::RegionStat as TableRegionStat;
+    use table::test_util::{EmptyTable, MockTableEngine};
+    use table::Table;
+
+    use super::*;
+    use crate::error::IllegalManagerStateSnafu;
+    use crate::local::memory::{new_memory_catalog_list, MemoryCatalogManager};
+
+    fn new_create_table_request(table_name: &str) -> CreateTableRequest {
+        CreateTableRequest {
+            id: 1,
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: table_name.to_string(),
+            desc: None,
+            schema: RawSchema::new(vec![]),
+            region_numbers: vec![0],
+            primary_key_indices: vec![],
+            create_if_not_exists: true,
+            table_options: Default::default(),
+            engine: MITO_ENGINE.to_string(),
+        }
+    }
+
+    async fn table_exists(manager: &dyn CatalogManager, table_name: &str) -> bool {
+        manager
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, table_name)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn test_retry_failed_open_hook() {
+        let manager = new_memory_catalog_list().unwrap();
+        let engine = Arc::new(MockTableEngine::new());
+
+        let failed = Arc::new(AtomicBool::new(false));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let hook: OpenSystemTableHook = {
+            let failed = failed.clone();
+            let calls = calls.clone();
+            Arc::new(move |_| {
+                let _ = calls.fetch_add(1, Ordering::Relaxed);
+                if failed.swap(true, Ordering::Relaxed) {
+                    Ok(())
+                } else {
+                    IllegalManagerStateSnafu { msg: "mock hook" }.fail()
+                }
+            })
+        };
+        let mut requests = vec![RegisterSystemTableRequest {
+            create_table_request: new_create_table_request("scripts"),
+            open_hook: Some(hook),
+        }];
+
+        // The table is created, but the hook fails and the request is kept for retrying.
+        assert!(
+            handle_system_table_request(manager.as_ref(), engine.clone(), &mut requests)
+                .await
+                .is_err()
+        );
+        assert_eq!(1, requests.len());
+        assert!(table_exists(manager.as_ref(), "scripts").await);
+
+        // Retried start reruns the hook on the opened table.
+        handle_system_table_request(manager.as_ref(), engine, &mut requests)
+            .await
+            .unwrap();
+        assert!(requests.is_empty());
+        assert_eq!(2, calls.load(Ordering::Relaxed));
+    }
+
+    /// A catalog manager whose table registration fails after the table is put in memory.
+    struct RegisterFailingManager {
+        inner: Arc<MemoryCatalogManager>,
+    }
+
+    #[async_trait::async_trait]
+    impl CatalogManager for RegisterFailingManager {
+        async fn start(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn register_catalog(
+            &self,
+            name: String,
+            catalog: CatalogProviderRef,
+        ) -> Result<Option<CatalogProviderRef>> {
+            self.inner.register_catalog(name, catalog).await
+        }
+
+        async fn register_table(&self, request: RegisterTableRequest) -> Result<bool> {
+            let _ = self.inner.register_table(request).await?;
+            IllegalManagerStateSnafu {
+                msg: "mock register failure",
+            }
+            .fail()
+        }
+
+        async fn deregister_table(&self, request: DeregisterTableRequest) -> Result<bool> {
+            self.inner.deregister_table(request).await
+        }
+
+        async fn register_schema(&self, request: RegisterSchemaRequest) -> Result<bool> {
+            self.inner.register_schema(request).await
+        }
+
+        async fn rename_table(&self, request: RenameTableRequest) -> Result<bool> {
+            self.inner.rename_table(request).await
+        }
+
+        async fn register_system_table(&self, request: RegisterSystemTableRequest) -> Result<()> {
+            self.inner.register_system_table(request).await
+        }
+
+        async fn catalog_names(&self) -> Result<Vec<String>> {
+            self.inner.catalog_names().await
+        }
+
+        async fn catalog(&self, catalog: &str) -> Result<Option<CatalogProviderRef>> {
+            self.inner.catalog(catalog).await
+        }
+
+        async fn schema(&self, catalog: &str, schema: &str) -> Result<Option<SchemaProviderRef>> {
+            self.inner.schema(catalog, schema).await
+        }
+
+        async fn table(
+            &self,
+            catalog: &str,
+            schema: &str,
+            table_name: &str,
+        ) -> Result<Option<TableRef>> {
+            self.inner.table(catalog, schema, table_name).await
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_no_registration_left_on_failure() {
+        let manager = RegisterFailingManager {
+            inner: new_memory_catalog_list().unwrap(),
+        };
+        let mut requests = vec![RegisterSystemTableRequest {
+            create_table_request: new_create_table_request("scripts"),
+            open_hook: None,
+        }];
+
+        assert!(handle_system_table_request(
+            &manager,
+            Arc::new(MockTableEngine::new()),
+            &mut requests
+        )
+        .await
+        .is_err());
+        assert_eq!(1, requests.len());
+        assert!(!table_exists(&manager, "scripts").await);
+    }
+
+    /// A [MockTableEngine] that takes a while to create a table.
+    #[derive(Default)]
+    struct SlowTableEngine {
+        inner: MockTableEngine,
+    }
+
+    const CREATE_TABLE_DELAY: Duration = Duration::from_millis(500);
+
+    #[async_trait::async_trait]
+    impl TableEngine for SlowTableEngine {
+        fn name(&self) -> &str {
+            "SlowTableEngine"
+        }
+
+        async fn create_table(
+            &self,
+            ctx: &EngineContext,
+            request: CreateTableRequest,
+        ) -> table::Result<TableRef> {
+            tokio::time::sleep(CREATE_TABLE_DELAY).await;
+            self.inner.create_table(ctx, request).await
+        }
+
+        async fn open_table(
+            &self,
+            ctx: &EngineContext,
+            request: OpenTableRequest,
+        ) -> table::Result<Option<TableRef>> {
+            self.inner.open_table(ctx, request).await
+        }
+
+        async fn alter_table(
+            &self,
+            ctx: &EngineContext,
+            request: AlterTableRequest,
+        ) -> table::Result<TableRef> {
+            self.inner.alter_table(ctx, request).await
+        }
+
+        fn get_table(
+            &self,
+            ctx: &EngineContext,
+            table_ref: &TableReference,
+        ) -> table::Result<Option<TableRef>> {
+            self.inner.get_table(ctx, table_ref)
+        }
+
+        fn table_exists(&self, ctx: &EngineContext, table_ref: &TableReference) -> bool {
+            self.inner.table_exists(ctx, table_ref)
+        }
+
+        async fn drop_table(
+            &self,
+            ctx: &EngineContext,
+            request: DropTableRequest,
+        ) -> table::Result<bool> {
+            self.inner.drop_table(ctx, request).await
+        }
+
+        async fn close(&self) -> table::Result<()> {
+            self.inner.close().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_requests_concurrently() {
+        let manager = new_memory_catalog_list().unwrap();
+        let mut requests = vec![
+            RegisterSystemTableRequest {
+                create_table_request: new_create_table_request("foo"),
+                open_hook: None,
+            },
+            RegisterSystemTableRequest {
+                create_table_request: new_create_table_request("bar"),
+                open_hook: None,
+            },
+        ];
+
+        let start = Instant::now();
+        handle_system_table_request(
+            manager.as_ref(),
+            Arc::new(SlowTableEngine::default()),
+            &mut requests,
+        )
+        .await
+        .unwrap();
+        assert!(start.elapsed() < CREATE_TABLE_DELAY * 2);
+
+        assert!(table_exists(manager.as_ref(), "foo").await);
+        assert!(table_exists(manager.as_ref(), "bar").await);
+    }
+
+    /// A table that reports the given stats of its regions.
+    struct StatTable {
+        inner: EmptyTable,
+        stats: fn() -> table::Result<Vec<TableRegionStat>>,
+    }
+
+    impl StatTable {
+        fn new(table_name: &str, stats: fn() -> table::Result<Vec<TableRegionStat>>) -> Self {
+            let request = new_create_table_request(table_name);
+            let mut info = EmptyTable::new(request.clone())
+                .table_info()
+                .as_ref()
+                .clone();
+            info.meta.region_numbers = request.region_numbers;
+            Self {
+                inner: EmptyTable::from_table_info(&info),
+                stats,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Table for StatTable {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> datatypes::schema::SchemaRef {
+            self.inner.schema()
+        }
+
+        fn table_info(&self) -> table::metadata::TableInfoRef {
+            self.inner.table_info()
+        }
+
+        async fn scan(
+            &self,
+            projection: Option<&Vec<usize>>,
+            filters: &[common_query::prelude::Expr],
+            limit: Option<usize>,
+        ) -> table::Result<common_query::physical_plan::PhysicalPlanRef> {
+            self.inner.scan(projection, filters, limit).await
+        }
+
+        fn region_stats(&self) -> table::Result<Vec<TableRegionStat>> {
+            (self.stats)()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_datanode_stat() {
+        let manager: CatalogManagerRef = new_memory_catalog_list().unwrap();
+        let schema = manager
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let full = StatTable::new("full", || {
+            Ok(vec![TableRegionStat {
+                region_id: 1,
+                disk_usage_bytes: 1024,
+                approximate_rows: 100,
+                memtable_bytes: 512,
+                ..Default::default()
+            }])
+        });
+        let _ = schema
+            .register_table("full".to_string(), Arc::new(full))
+            .await
+            .unwrap();
+        // Tables that can't tell the extra stats leave them zero.
+        let partial = StatTable::new("partial", || {
+            Ok(vec![TableRegionStat {
+                region_id: 2,
+                disk_usage_bytes: 2048,
+                ..Default::default()
+            }])
+        });
+        let _ = schema
+            .register_table("partial".to_string(), Arc::new(partial))
+            .await
+            .unwrap();
+        // Tables failing to report stats are skipped.
+        let failing = StatTable::new("failing", || {
+            table::error::UnsupportedSnafu {
+                operation: "REGION_STATS",
+            }
+            .fail()
+        });
+        let _ = schema
+            .register_table("failing".to_string(), Arc::new(failing))
+            .await
+            .unwrap();
+
+        let (region_number, mut stats) = datanode_stat(&manager, DEFAULT_STAT_CONCURRENCY).await;
+        assert_eq!(3, region_number);
+        assert_eq!(2, stats.len());
+        stats.sort_by_key(|stat| stat.region_id);
+
+        let stat = &stats[0];
+        assert_eq!("full", stat.table_name.as_ref().unwrap().table_name);
+        assert_eq!(1024, stat.approximate_bytes);
+        assert_eq!(100, stat.approximate_rows);
+        assert_eq!("512", stat.attrs[REGION_STAT_MEMTABLE_BYTES_KEY]);
+
+        let stat = &stats[1];
+        assert_eq!("partial", stat.table_name.as_ref().unwrap().table_name);
+        assert_eq!(2048, stat.approximate_bytes);
+        assert_eq!(0, stat.approximate_rows);
+        assert_eq!("0", stat.attrs[REGION_STAT_MEMTABLE_BYTES_KEY]);
+    }
+
+    /// Number of the tables whose stats are being collected, and the max of it.
+    static STATS_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+    static MAX_STATS_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+    /// Stats of a table that take a while to collect.
+    fn slow_stats() -> table::Result<Vec<TableRegionStat>> {
+        let in_flight = STATS_IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = MAX_STATS_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(20));
+        let _ = STATS_IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+        Ok(vec![TableRegionStat {
+            approximate_rows: 10,
+            ..Default::default()
+        }])
+    }
+
+    #[tokio::test]
+    async fn test_datanode_stat_concurrently() {
+        let manager: CatalogManagerRef = new_memory_catalog_list().unwrap();
+        let schema = manager
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let num_tables = 8;
+        for i in 0..num_tables {
+            let table_name = format!("table_{i}");
+            let table = StatTable::new(&table_name, slow_stats);
+            let _ = schema
+                .register_table(table_name, Arc::new(table))
+                .await
+                .unwrap();
+        }
+
+        // The stats of a table are collected while the ones of the other tables are.
+        let concurrency = 4;
+        let (region_number, stats) = datanode_stat(&manager, concurrency).await;
+        let max_in_flight = MAX_STATS_IN_FLIGHT.swap(0, Ordering::SeqCst);
+        assert!(
+            max_in_flight > 1 && max_in_flight <= concurrency,
+            "{max_in_flight}"
+        );
+        assert_eq!(num_tables as u64, region_number);
+        assert_eq!(num_tables, stats.len());
+        assert_eq!(
+            num_tables as i64 * 10,
+            stats.iter().map(|stat| stat.approximate_rows).sum::<i64>()
+        );
+
+        // Totals are the same however many tables are collected at a time.
+        let (serial_region_number, serial_stats) = datanode_stat(&manager, 1).await;
+        assert_eq!(1, MAX_STATS_IN_FLIGHT.load(Ordering::SeqCst));
+        assert_eq!(region_number, serial_region_number);
+        assert_eq!(stats.len(), serial_stats.len());
+    }
+}
@@ -14,8 +14,11 @@
 
 pub mod manager;
 pub mod memory;
+pub mod migration;
+pub mod name_resolution;
 
 pub use manager::LocalCatalogManager;
 pub use memory::{
     new_memory_catalog_list, MemoryCatalogManager, MemoryCatalogProvider, MemorySchemaProvider,
 };
+pub use name_resolution::NameResolution;
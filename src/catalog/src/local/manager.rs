@@ -13,7 +13,8 @@
 // limitations under the License.
 
 use std::any::Any;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 
 use common_catalog::consts::{
@@ -23,30 +24,32 @@ use common_catalog::consts::{
 use common_catalog::format_full_table_name;
 use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
 use common_telemetry::{error, info};
+use common_time::util::SystemClock;
 use datatypes::prelude::ScalarVector;
-use datatypes::vectors::{BinaryVector, UInt8Vector};
+use futures::StreamExt;
 use futures_util::lock::Mutex;
 use snafu::{ensure, OptionExt, ResultExt};
+use table::column_limits::{ColumnLimitsOptions, ColumnLimitsOptionsRef};
 use table::engine::manager::TableEngineManagerRef;
 use table::engine::EngineContext;
 use table::metadata::TableId;
-use table::requests::OpenTableRequest;
+use table::requests::{OpenTableRequest, TableOptions};
 use table::table::numbers::NumbersTable;
 use table::table::TableIdProvider;
 use table::TableRef;
 
 use crate::error::{
-    self, CatalogNotFoundSnafu, IllegalManagerStateSnafu, OpenTableSnafu, ReadSystemCatalogSnafu,
-    Result, SchemaExistsSnafu, SchemaNotFoundSnafu, SystemCatalogSnafu,
-    SystemCatalogTypeMismatchSnafu, TableEngineNotFoundSnafu, TableExistsSnafu, TableNotExistSnafu,
-    TableNotFoundSnafu,
+    self, CatalogNotFoundSnafu, IllegalManagerStateSnafu, InvalidSchemaTableOptionsSnafu,
+    OpenTableSnafu, ReadSystemCatalogSnafu, Result, SchemaExistsSnafu, SchemaNotFoundSnafu,
+    TableEngineNotFoundSnafu, TableExistsSnafu, TableNotExistSnafu, TableNotFoundSnafu,
 };
 use crate::local::memory::{MemoryCatalogManager, MemoryCatalogProvider, MemorySchemaProvider};
-use crate::system::{
-    decode_system_catalog, Entry, SystemCatalogTable, TableEntry, ENTRY_TYPE_INDEX, KEY_INDEX,
-    VALUE_INDEX,
-};
+use crate::local::migration::migrate_system_catalog;
+use crate::local::name_resolution::{conflicting_name, NameResolution};
+use crate::replay::{wait_replay, ReplayProgress, ReplayProgressRef, DEFAULT_REPLAY_CONCURRENCY};
+use crate::system::{decode_system_catalog, entry_columns, Entry, SystemCatalogTable, TableEntry};
 use crate::tables::SystemCatalog;
+use crate::ttl_purger::{TtlPurgeOptions, TtlPurger, TtlPurgerRef};
 use crate::{
     handle_system_table_request, CatalogManager, CatalogProviderRef, DeregisterTableRequest,
     RegisterSchemaRequest, RegisterSystemTableRequest, RegisterTableRequest, RenameTableRequest,
@@ -62,6 +65,13 @@ pub struct LocalCatalogManager {
     init_lock: Mutex<bool>,
     register_lock: Mutex<()>,
     system_table_requests: Mutex<Vec<RegisterSystemTableRequest>>,
+    replay_concurrency: usize,
+    replay_progress: ReplayProgressRef,
+    name_resolution: NameResolution,
+    column_limits: ColumnLimitsOptionsRef,
+    /// Whether the initialization, including the system tables, has completed.
+    started: AtomicBool,
+    ttl_purger: TtlPurgerRef,
 }
 
 impl LocalCatalogManager {
@@ -83,11 +93,74 @@ impl LocalCatalogManager {
             init_lock: Mutex::new(false),
             register_lock: Mutex::new(()),
             system_table_requests: Mutex::new(Vec::default()),
+            replay_concurrency: DEFAULT_REPLAY_CONCURRENCY,
+            replay_progress: Arc::new(ReplayProgress::default()),
+            name_resolution: NameResolution::Exact,
+            column_limits: Arc::new(ColumnLimitsOptions::default()),
+            started: AtomicBool::new(false),
+            ttl_purger: Arc::new(TtlPurger::new(
+                TtlPurgeOptions::default(),
+                Arc::new(SystemClock),
+            )),
         })
     }
 
+    /// Sets the max number of tables opened, which replays the WALs of their regions,
+    /// concurrently when the catalog manager starts.
+    pub fn with_replay_concurrency(mut self, concurrency: usize) -> Self {
+        self.replay_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets how the names of the schemas and tables of the user catalogs are resolved, names are
+    /// matched exactly by default.
+    pub fn with_name_resolution(mut self, resolution: NameResolution) -> Self {
+        self.name_resolution = resolution;
+        self
+    }
+
+    /// Sets the column limits of the tables, the default ones if not set.
+    pub fn with_column_limits(mut self, column_limits: ColumnLimitsOptions) -> Self {
+        self.column_limits = Arc::new(column_limits);
+        self
+    }
+
+    /// Sets the options of purging the rows expired by the `ttl` option of the tables.
+    pub fn with_ttl_purge_options(mut self, options: TtlPurgeOptions) -> Self {
+        self.ttl_purger = Arc::new(TtlPurger::new(options, Arc::new(SystemClock)));
+        self
+    }
+
+    /// Returns the purger of the rows expired by the `ttl` option of the registered tables.
+    pub fn ttl_purger(&self) -> TtlPurgerRef {
+        self.ttl_purger.clone()
+    }
+
+    /// Finds the registered table, regardless of the tables replaying.
+    async fn find_table(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Option<TableRef>> {
+        let catalog = self
+            .catalogs
+            .catalog(catalog_name)
+            .await?
+            .context(CatalogNotFoundSnafu { catalog_name })?;
+        let schema = catalog
+            .schema(schema_name)
+            .await?
+            .with_context(|| SchemaNotFoundSnafu {
+                catalog: catalog_name,
+                schema: schema_name,
+            })?;
+        schema.table(table_name).await
+    }
+
     /// Scan all entries from system catalog table
     pub async fn init(&self) -> Result<()> {
+        migrate_system_catalog(&self.system.information_schema.system).await?;
         self.init_system_catalog().await?;
         let system_records = self.system.information_schema.system.records().await?;
         let entries = self.collect_system_catalog_entries(system_records).await?;
@@ -111,6 +184,8 @@ impl LocalCatalogManager {
             })?;
 
         handle_system_table_request(self, engine, &mut sys_table_requests).await?;
+        self.started.store(true, Ordering::Release);
+        self.ttl_purger.start();
         Ok(())
     }
 
@@ -125,8 +200,12 @@ impl LocalCatalogManager {
         self.catalogs
             .register_catalog_sync(SYSTEM_CATALOG_NAME.to_string(), system_catalog)?;
 
-        let default_catalog = Arc::new(MemoryCatalogProvider::new());
-        let default_schema = Arc::new(MemorySchemaProvider::new());
+        let default_catalog = Arc::new(MemoryCatalogProvider::with_name_resolution(
+            self.name_resolution,
+        ));
+        let default_schema = Arc::new(MemorySchemaProvider::with_name_resolution(
+            self.name_resolution,
+        ));
 
         // Add numbers table for test
         let table = Arc::new(NumbersTable::default());
@@ -155,36 +234,7 @@ impl LocalCatalogManager {
 
     /// Convert `RecordBatch` to a vector of `Entry`.
     fn record_batch_to_entry(rb: RecordBatch) -> Result<Vec<Entry>> {
-        ensure!(
-            rb.num_columns() >= 6,
-            SystemCatalogSnafu {
-                msg: format!("Length mismatch: {}", rb.num_columns())
-            }
-        );
-
-        let entry_type = rb
-            .column(ENTRY_TYPE_INDEX)
-            .as_any()
-            .downcast_ref::<UInt8Vector>()
-            .with_context(|| SystemCatalogTypeMismatchSnafu {
-                data_type: rb.column(ENTRY_TYPE_INDEX).data_type(),
-            })?;
-
-        let key = rb
-            .column(KEY_INDEX)
-            .as_any()
-            .downcast_ref::<BinaryVector>()
-            .with_context(|| SystemCatalogTypeMismatchSnafu {
-                data_type: rb.column(KEY_INDEX).data_type(),
-            })?;
-
-        let value = rb
-            .column(VALUE_INDEX)
-            .as_any()
-            .downcast_ref::<BinaryVector>()
-            .with_context(|| SystemCatalogTypeMismatchSnafu {
-                data_type: rb.column(VALUE_INDEX).data_type(),
-            })?;
+        let (entry_type, key, value) = entry_columns(&rb)?;
 
         let mut res = Vec::with_capacity(rb.num_rows());
         for ((t, k), v) in entry_type
@@ -200,19 +250,31 @@ impl LocalCatalogManager {
 
     /// Processes records from system catalog table and returns the max table id persisted
     /// in system catalog table.
+    ///
+    /// Tables are opened concurrently after the catalogs and schemas are registered, and each
+    /// table is registered, so it's queryable, as soon as it's opened. A table failed to open
+    /// is left closed without failing the others.
     async fn handle_system_catalog_entries(&self, entries: Vec<Entry>) -> Result<TableId> {
         let entries = Self::sort_entries(entries);
         let mut max_table_id = 0;
+        let mut tables = Vec::new();
         for entry in entries {
             match entry {
                 Entry::Catalog(c) => {
                     self.catalogs.register_catalog_if_absent(
                         c.catalog_name.clone(),
-                        Arc::new(MemoryCatalogProvider::new()),
+                        Arc::new(MemoryCatalogProvider::with_name_resolution(
+                            self.name_resolution,
+                        )),
                     );
                     info!("Register catalog: {}", c.catalog_name);
                 }
                 Entry::Schema(s) => {
+                    let table_options =
+                        TableOptions::try_from(&HashMap::from_iter(s.table_options.clone()))
+                            .context(InvalidSchemaTableOptionsSnafu {
+                                schema: &s.schema_name,
+                            })?;
                     self.catalogs
                         .catalog(&s.catalog_name)
                         .await?
@@ -221,21 +283,46 @@ impl LocalCatalogManager {
                         })?
                         .register_schema(
                             s.schema_name.clone(),
-                            Arc::new(MemorySchemaProvider::new()),
+                            Arc::new(
+                                MemorySchemaProvider::with_name_resolution(self.name_resolution)
+                                    .with_table_options(table_options),
+                            ),
                         )
                         .await?;
                     info!("Registered schema: {:?}", s);
                 }
                 Entry::Table(t) => {
-                    self.open_and_register_table(&t).await?;
-                    info!("Registered table: {:?}", t);
+                    // The failed tables keep their ids, which can't be reused.
                     max_table_id = max_table_id.max(t.table_id);
+                    tables.push(t);
                 }
+                // The version is checked by the migration before the entries are read.
+                Entry::Version(_) => {}
             }
         }
+
+        self.open_tables(tables).await;
         Ok(max_table_id)
     }
 
+    async fn open_tables(&self, tables: Vec<TableEntry>) {
+        self.replay_progress.add_pending(
+            tables
+                .iter()
+                .map(|t| format_full_table_name(&t.catalog_name, &t.schema_name, &t.table_name)),
+        );
+        self.replay_progress.finish_listing();
+        let opening = futures::stream::iter(tables)
+            .map(|t| async move {
+                let table_name =
+                    format_full_table_name(&t.catalog_name, &t.schema_name, &t.table_name);
+                (table_name, self.open_and_register_table(&t).await)
+            })
+            .buffer_unordered(self.replay_concurrency);
+        wait_replay(&self.replay_progress, opening).await;
+        self.replay_progress.finish();
+    }
+
     /// Sort catalog entries to ensure catalog entries comes first, then schema entries,
     /// and table entries is the last.
     fn sort_entries(mut entries: Vec<Entry>) -> Vec<Entry> {
@@ -289,7 +376,10 @@ impl LocalCatalogManager {
                 ),
             })?;
 
-        schema.register_table(t.table_name.clone(), option).await?;
+        schema
+            .register_table(t.table_name.clone(), option.clone())
+            .await?;
+        self.ttl_purger.track(&option);
         Ok(())
     }
 }
@@ -309,6 +399,22 @@ impl CatalogManager for LocalCatalogManager {
         self.init().await
     }
 
+    fn is_started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    fn column_limits(&self) -> ColumnLimitsOptionsRef {
+        self.column_limits.clone()
+    }
+
+    fn name_resolution(&self) -> NameResolution {
+        self.name_resolution
+    }
+
+    fn replay_progress(&self) -> Option<ReplayProgressRef> {
+        Some(self.replay_progress.clone())
+    }
+
     async fn register_table(&self, request: RegisterTableRequest) -> Result<bool> {
         let started = self.init_lock.lock().await;
 
@@ -319,8 +425,8 @@ impl CatalogManager for LocalCatalogManager {
             }
         );
 
-        let catalog_name = &request.catalog;
-        let schema_name = &request.schema;
+        let catalog_name = request.catalog.as_ref();
+        let schema_name = request.schema.as_ref();
 
         let catalog = self
             .catalogs
@@ -344,11 +450,16 @@ impl CatalogManager for LocalCatalogManager {
                         request,
                         existing.table_info()
                     );
+                    let existing_name = schema
+                        .table_names()
+                        .await?
+                        .into_iter()
+                        .find(|name| self.name_resolution.matches(name, &request.table_name))
+                        .unwrap_or_else(|| request.table_name.to_string());
                     return TableExistsSnafu {
-                        table: format_full_table_name(
-                            catalog_name,
-                            schema_name,
-                            &request.table_name,
+                        table: conflicting_name(
+                            &format_full_table_name(catalog_name, schema_name, &request.table_name),
+                            &format_full_table_name(catalog_name, schema_name, &existing_name),
                         ),
                     }
                     .fail();
@@ -360,16 +471,18 @@ impl CatalogManager for LocalCatalogManager {
                 // table does not exist
                 self.system
                     .register_table(
-                        catalog_name.clone(),
-                        schema_name.clone(),
-                        request.table_name.clone(),
+                        catalog_name.to_string(),
+                        schema_name.to_string(),
+                        request.table_name.to_string(),
                         request.table_id,
                         engine,
                     )
                     .await?;
+                let table = request.table;
                 schema
-                    .register_table(request.table_name, request.table)
+                    .register_table(request.table_name.to_string(), table.clone())
                     .await?;
+                self.ttl_purger.track(&table);
                 Ok(true)
             }
         }
@@ -464,7 +577,9 @@ impl CatalogManager for LocalCatalogManager {
                 return Ok(false);
             }
 
-            self.catalogs.deregister_table(request).await
+            let deregistered = self.catalogs.deregister_table(request).await?;
+            self.ttl_purger.untrack(table_id);
+            Ok(deregistered)
         }
     }
 
@@ -494,10 +609,16 @@ impl CatalogManager for LocalCatalogManager {
                 }
             );
             self.system
-                .register_schema(request.catalog, schema_name.clone())
+                .register_schema(request.catalog, schema_name.clone(), &request.table_options)
                 .await?;
             catalog
-                .register_schema(request.schema, Arc::new(MemorySchemaProvider::new()))
+                .register_schema(
+                    request.schema,
+                    Arc::new(
+                        MemorySchemaProvider::with_name_resolution(self.name_resolution)
+                            .with_table_options(request.table_options),
+                    ),
+                )
                 .await?;
             Ok(true)
         }
@@ -528,25 +649,30 @@ impl CatalogManager for LocalCatalogManager {
             .await
     }
 
+    async fn schema_exist(&self, catalog: &str, schema: &str) -> Result<bool> {
+        self.catalogs
+            .catalog(catalog)
+            .await?
+            .context(CatalogNotFoundSnafu {
+                catalog_name: catalog,
+            })?
+            .schema_exist(schema)
+            .await
+    }
+
     async fn table(
         &self,
         catalog_name: &str,
         schema_name: &str,
         table_name: &str,
     ) -> Result<Option<TableRef>> {
-        let catalog = self
-            .catalogs
-            .catalog(catalog_name)
-            .await?
-            .context(CatalogNotFoundSnafu { catalog_name })?;
-        let schema = catalog
-            .schema(schema_name)
-            .await?
-            .with_context(|| SchemaNotFoundSnafu {
-                catalog: catalog_name,
-                schema: schema_name,
-            })?;
-        schema.table(table_name).await
+        let table = self.find_table(catalog_name, schema_name, table_name).await;
+        if !matches!(table, Ok(Some(_))) {
+            // The table, or its schema, may be not registered yet as it's still replaying.
+            self.replay_progress
+                .ensure_not_pending(catalog_name, schema_name, table_name)?;
+        }
+        table
     }
 
     async fn catalog(&self, catalog: &str) -> Result<Option<CatalogProviderRef>> {
@@ -599,10 +725,12 @@ mod tests {
             Entry::Schema(SchemaEntry {
                 catalog_name: "C1".to_string(),
                 schema_name: "S1".to_string(),
+                table_options: Default::default(),
             }),
             Entry::Schema(SchemaEntry {
                 catalog_name: "C2".to_string(),
                 schema_name: "S2".to_string(),
+                table_options: Default::default(),
             }),
             Entry::Catalog(CatalogEntry {
                 catalog_name: "".to_string(),
@@ -21,14 +21,17 @@ use std::sync::{Arc, RwLock};
 use async_trait::async_trait;
 use common_catalog::consts::MIN_USER_TABLE_ID;
 use common_telemetry::error;
-use snafu::{ensure, OptionExt};
+use snafu::OptionExt;
 use table::metadata::TableId;
+use table::requests::TableOptions;
 use table::table::TableIdProvider;
 use table::TableRef;
 
 use crate::error::{
     self, CatalogNotFoundSnafu, Result, SchemaNotFoundSnafu, TableExistsSnafu, TableNotFoundSnafu,
 };
+use crate::interner::intern;
+use crate::local::name_resolution::{conflicting_name, NameMap, NameResolution};
 use crate::schema::SchemaProvider;
 use crate::{
     CatalogManager, CatalogProvider, CatalogProviderRef, DeregisterTableRequest,
@@ -39,7 +42,7 @@ use crate::{
 /// Simple in-memory list of catalogs
 pub struct MemoryCatalogManager {
     /// Collection of catalogs containing schemas and ultimately Tables
-    pub catalogs: RwLock<HashMap<String, CatalogProviderRef>>,
+    pub catalogs: RwLock<HashMap<Arc<str>, CatalogProviderRef>>,
     pub table_id: AtomicU32,
 }
 
@@ -78,16 +81,16 @@ impl CatalogManager for MemoryCatalogManager {
         let schema = self
             .catalog(&request.catalog)
             .context(CatalogNotFoundSnafu {
-                catalog_name: &request.catalog,
+                catalog_name: request.catalog.as_ref(),
             })?
             .schema(&request.schema)
             .await?
             .context(SchemaNotFoundSnafu {
-                catalog: &request.catalog,
-                schema: &request.schema,
+                catalog: request.catalog.as_ref(),
+                schema: request.schema.as_ref(),
             })?;
         schema
-            .register_table(request.table_name, request.table)
+            .register_table(request.table_name.to_string(), request.table)
             .await
             .map(|v| v.is_none())
     }
@@ -137,7 +140,10 @@ impl CatalogManager for MemoryCatalogManager {
                 catalog_name: &request.catalog,
             })?;
         catalog
-            .register_schema(request.schema, Arc::new(MemorySchemaProvider::new()))
+            .register_schema(
+                request.schema,
+                Arc::new(MemorySchemaProvider::new().with_table_options(request.table_options)),
+            )
             .await?;
         Ok(true)
     }
@@ -155,6 +161,14 @@ impl CatalogManager for MemoryCatalogManager {
         }
     }
 
+    async fn schema_exist(&self, catalog: &str, schema: &str) -> Result<bool> {
+        if let Some(c) = self.catalog(catalog) {
+            c.schema_exist(schema).await
+        } else {
+            Ok(false)
+        }
+    }
+
     async fn table(
         &self,
         catalog: &str,
@@ -172,7 +186,13 @@ impl CatalogManager for MemoryCatalogManager {
     }
 
     async fn catalog_names(&self) -> Result<Vec<String>> {
-        Ok(self.catalogs.read().unwrap().keys().cloned().collect())
+        Ok(self
+            .catalogs
+            .read()
+            .unwrap()
+            .keys()
+            .map(|name| name.to_string())
+            .collect())
     }
 
     async fn register_catalog(
@@ -197,7 +217,7 @@ impl MemoryCatalogManager {
         catalog: CatalogProviderRef,
     ) -> Option<CatalogProviderRef> {
         let mut catalogs = self.catalogs.write().unwrap();
-        let entry = catalogs.entry(name);
+        let entry = catalogs.entry(intern(&name));
         match entry {
             Entry::Occupied(v) => Some(v.get().clone()),
             Entry::Vacant(v) => {
@@ -213,7 +233,7 @@ impl MemoryCatalogManager {
         catalog: CatalogProviderRef,
     ) -> Result<Option<CatalogProviderRef>> {
         let mut catalogs = self.catalogs.write().unwrap();
-        Ok(catalogs.insert(name, catalog))
+        Ok(catalogs.insert(intern(&name), catalog))
     }
 
     fn catalog(&self, catalog_name: &str) -> Option<CatalogProviderRef> {
@@ -229,20 +249,25 @@ impl Default for MemoryCatalogProvider {
 
 /// Simple in-memory implementation of a catalog.
 pub struct MemoryCatalogProvider {
-    schemas: RwLock<HashMap<String, Arc<dyn SchemaProvider>>>,
+    schemas: RwLock<NameMap<Arc<dyn SchemaProvider>>>,
 }
 
 impl MemoryCatalogProvider {
     /// Instantiates a new MemoryCatalogProvider with an empty collection of schemas.
     pub fn new() -> Self {
+        Self::with_name_resolution(NameResolution::Exact)
+    }
+
+    /// Instantiates a new MemoryCatalogProvider whose schemas are looked up by `resolution`.
+    pub fn with_name_resolution(resolution: NameResolution) -> Self {
         Self {
-            schemas: RwLock::new(HashMap::new()),
+            schemas: RwLock::new(NameMap::new(resolution)),
         }
     }
 
     pub fn schema_names_sync(&self) -> Result<Vec<String>> {
         let schemas = self.schemas.read().unwrap();
-        Ok(schemas.keys().cloned().collect())
+        Ok(schemas.keys().map(|name| name.to_string()).collect())
     }
 
     pub fn register_schema_sync(
@@ -251,17 +276,24 @@ impl MemoryCatalogProvider {
         schema: SchemaProviderRef,
     ) -> Result<Option<SchemaProviderRef>> {
         let mut schemas = self.schemas.write().unwrap();
-        ensure!(
-            !schemas.contains_key(&name),
-            error::SchemaExistsSnafu { schema: &name }
-        );
-        Ok(schemas.insert(name, schema))
+        if let Some(existing) = schemas.resolve(&name) {
+            return error::SchemaExistsSnafu {
+                schema: conflicting_name(&name, existing),
+            }
+            .fail();
+        }
+        Ok(schemas.insert(intern(&name), schema))
     }
 
     pub fn schema_sync(&self, name: &str) -> Result<Option<Arc<dyn SchemaProvider>>> {
         let schemas = self.schemas.read().unwrap();
         Ok(schemas.get(name).cloned())
     }
+
+    pub fn schema_exist_sync(&self, name: &str) -> Result<bool> {
+        let schemas = self.schemas.read().unwrap();
+        Ok(schemas.contains_key(name))
+    }
 }
 
 #[async_trait::async_trait]
@@ -274,6 +306,18 @@ impl CatalogProvider for MemoryCatalogProvider {
         self.schema_names_sync()
     }
 
+    async fn schema_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        Ok(self
+            .schemas
+            .read()
+            .unwrap()
+            .keys_paginated(start_after, limit))
+    }
+
     async fn register_schema(
         &self,
         name: String,
@@ -285,24 +329,41 @@ impl CatalogProvider for MemoryCatalogProvider {
     async fn schema(&self, name: &str) -> Result<Option<Arc<dyn SchemaProvider>>> {
         self.schema_sync(name)
     }
+
+    async fn schema_exist(&self, name: &str) -> Result<bool> {
+        self.schema_exist_sync(name)
+    }
 }
 
 /// Simple in-memory implementation of a schema.
 pub struct MemorySchemaProvider {
-    tables: RwLock<HashMap<String, TableRef>>,
+    tables: RwLock<NameMap<TableRef>>,
+    table_options: TableOptions,
 }
 
 impl MemorySchemaProvider {
     /// Instantiates a new MemorySchemaProvider with an empty collection of tables.
     pub fn new() -> Self {
+        Self::with_name_resolution(NameResolution::Exact)
+    }
+
+    /// Instantiates a new MemorySchemaProvider whose tables are looked up by `resolution`.
+    pub fn with_name_resolution(resolution: NameResolution) -> Self {
         Self {
-            tables: RwLock::new(HashMap::new()),
+            tables: RwLock::new(NameMap::new(resolution)),
+            table_options: TableOptions::default(),
         }
     }
 
+    /// Sets the default options of the tables in the schema.
+    pub fn with_table_options(mut self, table_options: TableOptions) -> Self {
+        self.table_options = table_options;
+        self
+    }
+
     pub fn register_table_sync(&self, name: String, table: TableRef) -> Result<Option<TableRef>> {
         let mut tables = self.tables.write().unwrap();
-        if let Some(existing) = tables.get(name.as_str()) {
+        if let Some((existing_name, existing)) = tables.get_key_value(&name) {
             // if table with the same name but different table id exists, then it's a fatal bug
             if existing.table_info().ident.table_id != table.table_info().ident.table_id {
                 error!(
@@ -310,29 +371,32 @@ impl MemorySchemaProvider {
                     table.table_info(),
                     existing.table_info()
                 );
-                return TableExistsSnafu { table: name }.fail()?;
+                return TableExistsSnafu {
+                    table: conflicting_name(&name, existing_name),
+                }
+                .fail()?;
             }
             Ok(Some(existing.clone()))
         } else {
-            Ok(tables.insert(name, table))
+            Ok(tables.insert(intern(&name), table))
         }
     }
 
     pub fn rename_table_sync(&self, name: &str, new_name: String) -> Result<TableRef> {
         let mut tables = self.tables.write().unwrap();
-        let Some(table) = tables.remove(name) else {
+        let Some((_, table)) = tables.remove(name) else {
             return TableNotFoundSnafu {
                 table_info: name.to_string(),
             }
                 .fail()?;
         };
-        let e = match tables.entry(new_name) {
-            Entry::Vacant(e) => e,
-            Entry::Occupied(e) => {
-                return TableExistsSnafu { table: e.key() }.fail();
+        if let Some(existing) = tables.resolve(&new_name) {
+            return TableExistsSnafu {
+                table: conflicting_name(&new_name, existing),
             }
-        };
-        e.insert(table.clone());
+            .fail();
+        }
+        let _ = tables.insert(intern(&new_name), table.clone());
         Ok(table)
     }
 
@@ -343,7 +407,7 @@ impl MemorySchemaProvider {
 
     pub fn deregister_table_sync(&self, name: &str) -> Result<Option<TableRef>> {
         let mut tables = self.tables.write().unwrap();
-        Ok(tables.remove(name))
+        Ok(tables.remove(name).map(|(_, table)| table))
     }
 }
 
@@ -361,7 +425,19 @@ impl SchemaProvider for MemorySchemaProvider {
 
     async fn table_names(&self) -> Result<Vec<String>> {
         let tables = self.tables.read().unwrap();
-        Ok(tables.keys().cloned().collect())
+        Ok(tables.keys().map(|name| name.to_string()).collect())
+    }
+
+    async fn table_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        Ok(self
+            .tables
+            .read()
+            .unwrap()
+            .keys_paginated(start_after, limit))
     }
 
     async fn table(&self, name: &str) -> Result<Option<TableRef>> {
@@ -384,6 +460,10 @@ impl SchemaProvider for MemorySchemaProvider {
     async fn table_exist(&self, name: &str) -> Result<bool> {
         self.table_exist_sync(name)
     }
+
+    async fn default_table_options(&self) -> Result<TableOptions> {
+        Ok(self.table_options.clone())
+    }
 }
 
 /// Create a memory catalog list contains a numbers table for test
@@ -393,6 +473,9 @@ pub fn new_memory_catalog_list() -> Result<Arc<MemoryCatalogManager>> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+    use std::mem;
+
     use common_catalog::consts::*;
     use common_error::ext::ErrorExt;
     use common_error::prelude::StatusCode;
@@ -443,6 +526,48 @@ mod tests {
         assert_eq!(StatusCode::TableAlreadyExists, err.status_code());
     }
 
+    #[tokio::test]
+    async fn test_mem_provider_name_resolution() {
+        let exact = MemorySchemaProvider::with_name_resolution(NameResolution::Exact);
+        let insensitive =
+            MemorySchemaProvider::with_name_resolution(NameResolution::CaseInsensitive);
+        for provider in [&exact, &insensitive] {
+            assert!(provider
+                .register_table_sync("MyTable".to_string(), Arc::new(NumbersTable::new(1)))
+                .unwrap()
+                .is_none());
+            assert!(provider.table("MyTable").await.unwrap().is_some());
+        }
+
+        assert!(exact.table("mytable").await.unwrap().is_none());
+        assert!(exact
+            .register_table_sync("mytable".to_string(), Arc::new(NumbersTable::new(2)))
+            .unwrap()
+            .is_none());
+        let mut table_names = exact.table_names().await.unwrap();
+        table_names.sort();
+        assert_eq!(vec!["MyTable", "mytable"], table_names);
+
+        let table = insensitive.table("mytable").await.unwrap().unwrap();
+        assert_eq!(1, table.table_info().ident.table_id);
+        assert_eq!(vec!["MyTable"], insensitive.table_names().await.unwrap());
+        let err = insensitive
+            .register_table_sync("mytable".to_string(), Arc::new(NumbersTable::new(2)))
+            .unwrap_err();
+        assert_eq!(StatusCode::TableAlreadyExists, err.status_code());
+        assert_eq!(
+            "Table `mytable` as `MyTable` already exists",
+            err.to_string()
+        );
+
+        let catalog = MemoryCatalogProvider::with_name_resolution(NameResolution::CaseInsensitive);
+        catalog
+            .register_schema_sync("MySchema".to_string(), Arc::new(insensitive))
+            .unwrap();
+        assert!(catalog.schema_sync("myschema").unwrap().is_some());
+        assert_eq!(vec!["MySchema"], catalog.schema_names_sync().unwrap());
+    }
+
     #[tokio::test]
     async fn test_mem_provider_rename_table() {
         let provider = MemorySchemaProvider::new();
@@ -495,13 +620,13 @@ mod tests {
         let table_name = "num";
         let table_id = 2333;
         let table: TableRef = Arc::new(NumbersTable::new(table_id));
-        let register_table_req = RegisterTableRequest {
-            catalog: DEFAULT_CATALOG_NAME.to_string(),
-            schema: DEFAULT_SCHEMA_NAME.to_string(),
-            table_name: table_name.to_string(),
+        let register_table_req = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            table_name,
             table_id,
             table,
-        };
+        );
         assert!(catalog.register_table(register_table_req).await.unwrap());
         assert!(schema.table_exist(table_name).await.unwrap());
 
@@ -526,6 +651,100 @@ mod tests {
         assert_eq!(registered_table.table_info().ident.table_id, table_id);
     }
 
+    #[tokio::test]
+    async fn test_register_table_interns_names() {
+        let catalog = MemoryCatalogManager::default();
+        let requests = ["t1", "t2"].map(|name| {
+            RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                name,
+                2333,
+                Arc::new(NumbersTable::default()),
+            )
+        });
+        assert!(Arc::ptr_eq(&requests[0].catalog, &requests[1].catalog));
+        assert!(Arc::ptr_eq(&requests[0].schema, &requests[1].schema));
+        for request in requests.clone() {
+            assert!(catalog.register_table(request).await.unwrap());
+        }
+
+        let schema = catalog
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let schema = schema
+            .as_any()
+            .downcast_ref::<MemorySchemaProvider>()
+            .unwrap();
+        let tables = schema.tables.read().unwrap();
+        for request in &requests {
+            let (name, _) = tables.get_key_value(request.table_name.as_ref()).unwrap();
+            assert!(Arc::ptr_eq(name, &request.table_name));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_tables_name_overhead() {
+        const TABLES: u32 = 10_000;
+
+        let catalog = MemoryCatalogManager::default();
+        let mut requests = Vec::with_capacity(TABLES as usize);
+        for table_id in 0..TABLES {
+            let table_name = format!("table_{table_id}");
+            let table = NumbersTable::with_name(table_id, table_name.clone());
+            let request = RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                &table_name,
+                table_id,
+                Arc::new(table),
+            );
+            assert!(catalog.register_table(request.clone()).await.unwrap());
+            requests.push(request);
+        }
+
+        let schema = catalog
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let schema = schema
+            .as_any()
+            .downcast_ref::<MemorySchemaProvider>()
+            .unwrap();
+        let tables = schema.tables.read().unwrap();
+        assert_eq!(TABLES as usize, tables.keys().count());
+        for request in &requests {
+            assert!(Arc::ptr_eq(&request.catalog, &requests[0].catalog));
+            assert!(Arc::ptr_eq(&request.schema, &requests[0].schema));
+            let (name, _) = tables.get_key_value(&request.table_name).unwrap();
+            assert!(Arc::ptr_eq(name, &request.table_name));
+        }
+
+        // The names held by the requests and the map of the schema, against holding a copy of
+        // each name in a `String`.
+        let names = requests
+            .iter()
+            .flat_map(|request| [&request.catalog, &request.schema, &request.table_name])
+            .chain(tables.keys());
+        let mut allocations = HashSet::new();
+        let (mut interned, mut owned) = (0, 0);
+        for name in names {
+            interned += mem::size_of::<Arc<str>>();
+            if allocations.insert(Arc::as_ptr(name) as *const u8) {
+                // The name with the reference counts in front of it.
+                interned += name.len() + 2 * mem::size_of::<usize>();
+            }
+            owned += mem::size_of::<String>() + name.len();
+        }
+
+        let per_table = interned / TABLES as usize;
+        assert!(per_table < 128, "per table overhead: {per_table} bytes");
+        assert!(interned < owned, "interned: {interned}, owned: {owned}");
+    }
+
     #[test]
     pub fn test_register_if_absent() {
         let list = MemoryCatalogManager::default();
@@ -554,13 +773,13 @@ mod tests {
             .unwrap()
             .unwrap();
 
-        let register_table_req = RegisterTableRequest {
-            catalog: DEFAULT_CATALOG_NAME.to_string(),
-            schema: DEFAULT_SCHEMA_NAME.to_string(),
-            table_name: "numbers".to_string(),
-            table_id: 2333,
-            table: Arc::new(NumbersTable::default()),
-        };
+        let register_table_req = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            "numbers",
+            2333,
+            Arc::new(NumbersTable::default()),
+        );
         catalog.register_table(register_table_req).await.unwrap();
         assert!(schema.table_exist("numbers").await.unwrap());
 
@@ -0,0 +1,401 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migrations of the format of the system catalog.
+//!
+//! The system catalog records its format version in the version entry. When the local catalog
+//! manager starts, the [MIGRATIONS] after the recorded version run in order. The version entry
+//! records the migration running before its entries are rewritten, and the new version after
+//! they are, so a migration interrupted, e.g. by a crash, runs again on the next start. Thus
+//! migrations must be idempotent.
+
+use common_catalog::consts::MITO_ENGINE;
+use common_telemetry::info;
+use datatypes::prelude::ScalarVector;
+use serde_json::{Map, Value};
+use snafu::{ensure, OptionExt, ResultExt};
+use table::Table;
+
+use crate::error::{
+    EmptyValueSnafu, InsertCatalogRecordSnafu, InvalidEntryTypeSnafu, InvalidKeySnafu,
+    ReadSystemCatalogSnafu, Result, UnsupportedSystemCatalogVersionSnafu, ValueDeserializeSnafu,
+    ValueSerializeSnafu,
+};
+use crate::system::{
+    build_insert_request, build_version_insert_request, entry_columns, EntryType,
+    SystemCatalogTable, VersionEntry,
+};
+
+/// The format version of the system catalog this binary supports, which is the version of the
+/// last migration.
+pub const SYSTEM_CATALOG_VERSION: u32 = 1;
+
+/// The migrations in the order of their versions.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "write the engine of table entries explicitly",
+    migrate_entry: explicit_table_engine,
+}];
+
+/// A step migrating the entries of the system catalog from the previous format version.
+pub(crate) struct Migration {
+    /// The format version after the migration, which is one more than the version before.
+    pub(crate) version: u32,
+    pub(crate) description: &'static str,
+    /// Rewrites the entry in place, returns whether the entry is changed.
+    pub(crate) migrate_entry: fn(&mut RawEntry) -> Result<bool>,
+}
+
+/// An entry of the system catalog as it's stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RawEntry {
+    pub(crate) entry_type: u8,
+    pub(crate) key: Vec<u8>,
+    pub(crate) value: Vec<u8>,
+}
+
+/// Migrates the entries of the system catalog `table` to [SYSTEM_CATALOG_VERSION], fails if
+/// the entries are of a newer version.
+pub async fn migrate_system_catalog(table: &SystemCatalogTable) -> Result<()> {
+    migrate(table, MIGRATIONS).await
+}
+
+async fn migrate(table: &SystemCatalogTable, migrations: &[Migration]) -> Result<()> {
+    let supported = migrations.last().map_or(0, |migration| migration.version);
+    let mut entries = read_entries(table).await?;
+    let version_entry = entries
+        .iter()
+        .position(|entry| entry.entry_type == EntryType::Version as u8);
+    let current = match version_entry {
+        Some(index) => {
+            let entry = entries.swap_remove(index);
+            serde_json::from_slice::<VersionEntry>(&entry.value).context(ValueDeserializeSnafu)?
+        }
+        // A new system catalog is created in the supported version.
+        None if entries.is_empty() => {
+            return write_version(table, supported, None).await;
+        }
+        // The system catalog is created before its format is versioned.
+        None => VersionEntry {
+            version: 0,
+            migrating_to: None,
+        },
+    };
+    ensure!(
+        current.version <= supported,
+        UnsupportedSystemCatalogVersionSnafu {
+            version: current.version,
+            supported,
+        }
+    );
+    if let Some(version) = current.migrating_to {
+        info!("Resuming the interrupted migration of system catalog to version {version}");
+    }
+
+    for migration in migrations
+        .iter()
+        .filter(|migration| migration.version > current.version)
+    {
+        info!(
+            "Migrating system catalog to version {}: {}",
+            migration.version, migration.description
+        );
+        write_version(table, migration.version - 1, Some(migration.version)).await?;
+
+        let mut migrated = 0;
+        for entry in &mut entries {
+            if (migration.migrate_entry)(entry)? {
+                let entry_type = EntryType::try_from(entry.entry_type)?;
+                let request = build_insert_request(entry_type, &entry.key, &entry.value);
+                let _ = table
+                    .insert(request)
+                    .await
+                    .context(InsertCatalogRecordSnafu)?;
+                migrated += 1;
+            }
+        }
+
+        write_version(table, migration.version, None).await?;
+        info!(
+            "Migrated {} entries of system catalog to version {}",
+            migrated, migration.version
+        );
+    }
+    Ok(())
+}
+
+async fn read_entries(table: &SystemCatalogTable) -> Result<Vec<RawEntry>> {
+    let records = common_recordbatch::util::collect(table.records().await?)
+        .await
+        .context(ReadSystemCatalogSnafu)?;
+
+    let mut entries = Vec::new();
+    for rb in records {
+        let (entry_type, key, value) = entry_columns(&rb)?;
+        for ((entry_type, key), value) in entry_type
+            .iter_data()
+            .zip(key.iter_data())
+            .zip(value.iter_data())
+        {
+            entries.push(RawEntry {
+                entry_type: entry_type.context(InvalidEntryTypeSnafu { entry_type: None })?,
+                key: key.context(InvalidKeySnafu { key: None })?.to_vec(),
+                value: value.context(EmptyValueSnafu)?.to_vec(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+async fn write_version(
+    table: &SystemCatalogTable,
+    version: u32,
+    migrating_to: Option<u32>,
+) -> Result<()> {
+    let request = build_version_insert_request(&VersionEntry {
+        version,
+        migrating_to,
+    });
+    let _ = table
+        .insert(request)
+        .await
+        .context(InsertCatalogRecordSnafu)?;
+    Ok(())
+}
+
+/// Writes the engine of the table entries written before the engine was recorded, which are
+/// read as mito tables.
+fn explicit_table_engine(entry: &mut RawEntry) -> Result<bool> {
+    if entry.entry_type != EntryType::Table as u8 {
+        return Ok(false);
+    }
+    let mut value: Map<String, Value> =
+        serde_json::from_slice(&entry.value).context(ValueDeserializeSnafu)?;
+    if value.contains_key("engine") {
+        return Ok(false);
+    }
+    let _ = value.insert("engine".to_string(), Value::from(MITO_ENGINE));
+    entry.value = serde_json::to_vec(&value).context(ValueSerializeSnafu)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+
+    use super::*;
+    use crate::error::Error;
+    use crate::system::tests::prepare_table_engine;
+    use crate::system::{
+        build_table_insert_request, decode_system_catalog, format_table_entry_key, Entry,
+        TableEntry,
+    };
+
+    /// Inserts the entry of table `table_name` in the format before the engine was recorded.
+    async fn insert_old_table_entry(table: &SystemCatalogTable, table_name: &str, table_id: u32) {
+        let key = format_table_entry_key(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, table_id);
+        let value = format!("{{\"table_name\":\"{table_name}\"}}");
+        let request = build_insert_request(EntryType::Table, key.as_bytes(), value.as_bytes());
+        let _ = table.insert(request).await.unwrap();
+    }
+
+    async fn read_version(table: &SystemCatalogTable) -> VersionEntry {
+        let entries = read_entries(table).await.unwrap();
+        let entry = entries
+            .iter()
+            .find(|entry| entry.entry_type == EntryType::Version as u8)
+            .unwrap();
+        serde_json::from_slice(&entry.value).unwrap()
+    }
+
+    async fn read_table_values(table: &SystemCatalogTable) -> Vec<Map<String, Value>> {
+        read_entries(table)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.entry_type == EntryType::Table as u8)
+            .map(|entry| serde_json::from_slice(&entry.value).unwrap())
+            .collect()
+    }
+
+    fn version(version: u32, migrating_to: Option<u32>) -> VersionEntry {
+        VersionEntry {
+            version,
+            migrating_to,
+        }
+    }
+
+    #[test]
+    fn test_migrations_in_order() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(i as u32 + 1, migration.version);
+        }
+        assert_eq!(SYSTEM_CATALOG_VERSION, MIGRATIONS.last().unwrap().version);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_new_system_catalog() {
+        let (_dir, table_engine) = prepare_table_engine().await;
+        let table = SystemCatalogTable::new(table_engine).await.unwrap();
+
+        migrate_system_catalog(&table).await.unwrap();
+        assert_eq!(
+            version(SYSTEM_CATALOG_VERSION, None),
+            read_version(&table).await
+        );
+        assert_eq!(1, read_entries(&table).await.unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_old_system_catalog() {
+        let (_dir, table_engine) = prepare_table_engine().await;
+        let table = SystemCatalogTable::new(table_engine).await.unwrap();
+        insert_old_table_entry(&table, "old_table", 1024).await;
+        let request = build_table_insert_request(
+            DEFAULT_CATALOG_NAME.to_string(),
+            DEFAULT_SCHEMA_NAME.to_string(),
+            "new_table".to_string(),
+            1025,
+            "file".to_string(),
+        );
+        let _ = table.insert(request).await.unwrap();
+
+        migrate_system_catalog(&table).await.unwrap();
+        assert_eq!(
+            version(SYSTEM_CATALOG_VERSION, None),
+            read_version(&table).await
+        );
+
+        let mut tables = Vec::new();
+        for entry in read_entries(&table).await.unwrap() {
+            match decode_system_catalog(
+                Some(entry.entry_type),
+                Some(&entry.key),
+                Some(&entry.value),
+            )
+            .unwrap()
+            {
+                Entry::Table(TableEntry {
+                    table_name, engine, ..
+                }) => tables.push((table_name, engine)),
+                Entry::Version(_) => {}
+                entry => panic!("Unexpected entry: {entry:?}"),
+            }
+        }
+        tables.sort();
+        assert_eq!(
+            vec![
+                ("new_table".to_string(), "file".to_string()),
+                ("old_table".to_string(), MITO_ENGINE.to_string()),
+            ],
+            tables
+        );
+        // The engine is stored explicitly.
+        assert!(read_table_values(&table)
+            .await
+            .iter()
+            .all(|value| value.contains_key("engine")));
+
+        // Migrating again changes nothing.
+        migrate_system_catalog(&table).await.unwrap();
+        assert_eq!(3, read_entries(&table).await.unwrap().len());
+    }
+
+    fn add_comment(entry: &mut RawEntry) -> Result<bool> {
+        if entry.entry_type != EntryType::Table as u8 {
+            return Ok(false);
+        }
+        let mut value: Map<String, Value> = serde_json::from_slice(&entry.value).unwrap();
+        if value.contains_key("comment") {
+            return Ok(false);
+        }
+        let _ = value.insert("comment".to_string(), Value::from(""));
+        entry.value = serde_json::to_vec(&value).unwrap();
+        Ok(true)
+    }
+
+    fn interrupt(_: &mut RawEntry) -> Result<bool> {
+        InvalidKeySnafu { key: None }.fail()
+    }
+
+    #[tokio::test]
+    async fn test_resume_interrupted_migration() {
+        let (_dir, table_engine) = prepare_table_engine().await;
+        let table = SystemCatalogTable::new(table_engine).await.unwrap();
+        insert_old_table_entry(&table, "t1", 1024).await;
+        insert_old_table_entry(&table, "t2", 1025).await;
+
+        let mut migrations = vec![
+            Migration {
+                version: 1,
+                description: "explicit engine",
+                migrate_entry: explicit_table_engine,
+            },
+            Migration {
+                version: 2,
+                description: "interrupted",
+                migrate_entry: interrupt,
+            },
+        ];
+        let err = migrate(&table, &migrations).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidKey { .. }));
+        assert_eq!(version(1, Some(2)), read_version(&table).await);
+
+        // The interrupted migration has rewritten the entry of one table.
+        let mut entry = read_entries(&table)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.entry_type == EntryType::Table as u8)
+            .unwrap();
+        assert!(add_comment(&mut entry).unwrap());
+        let request = build_insert_request(EntryType::Table, &entry.key, &entry.value);
+        let _ = table.insert(request).await.unwrap();
+
+        migrations[1].migrate_entry = add_comment;
+        migrate(&table, &migrations).await.unwrap();
+        assert_eq!(version(2, None), read_version(&table).await);
+        let values = read_table_values(&table).await;
+        assert_eq!(2, values.len());
+        for value in values {
+            assert!(value.contains_key("engine"));
+            assert!(value.contains_key("comment"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_newer_version() {
+        let (_dir, table_engine) = prepare_table_engine().await;
+        let table = SystemCatalogTable::new(table_engine).await.unwrap();
+        write_version(&table, SYSTEM_CATALOG_VERSION + 1, None)
+            .await
+            .unwrap();
+
+        let err = migrate_system_catalog(&table).await.unwrap_err();
+        assert!(matches!(err, Error::UnsupportedSystemCatalogVersion { .. }));
+        assert_eq!(
+            format!(
+                "System catalog format version {} is newer than the version {} supported, please upgrade to a newer version",
+                SYSTEM_CATALOG_VERSION + 1,
+                SYSTEM_CATALOG_VERSION
+            ),
+            err.to_string()
+        );
+        // The version is kept.
+        assert_eq!(
+            version(SYSTEM_CATALOG_VERSION + 1, None),
+            read_version(&table).await
+        );
+    }
+}
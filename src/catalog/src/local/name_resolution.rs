@@ -0,0 +1,216 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of schema and table names in the local catalogs.
+//!
+//! Names are always stored and listed as they were registered. The resolution only decides
+//! which registered name a looked up name refers to.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+/// How the names of schemas and tables are matched on lookup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameResolution {
+    /// Names match only if they are equal.
+    #[default]
+    Exact,
+    /// Names match if they are equal ignoring case, like MySQL with `lower_case_table_names`
+    /// set. Two names equal ignoring case can't be registered in the same scope.
+    CaseInsensitive,
+}
+
+impl NameResolution {
+    /// Returns whether `a` and `b` are the names of the same schema or table.
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        self.fold(a) == self.fold(b)
+    }
+
+    fn fold<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            NameResolution::CaseInsensitive if name.chars().any(char::is_uppercase) => {
+                Cow::Owned(name.to_lowercase())
+            }
+            _ => Cow::Borrowed(name),
+        }
+    }
+}
+
+/// Returns the name for the error of registering `name` when `existing` is registered, which
+/// names both if they are different.
+pub(crate) fn conflicting_name(name: &str, existing: &str) -> String {
+    if name == existing {
+        name.to_string()
+    } else {
+        format!("{name}` as `{existing}")
+    }
+}
+
+/// A map keyed by the registered names, whose entries are looked up by the names resolved to
+/// them.
+pub(crate) struct NameMap<V> {
+    resolution: NameResolution,
+    /// Entries in ascending order of the registered names, so they are listed in pages without
+    /// sorting.
+    entries: BTreeMap<Arc<str>, V>,
+    /// Registered names by their folded names, only kept if names are not matched exactly.
+    folded: HashMap<String, Arc<str>>,
+}
+
+impl<V> NameMap<V> {
+    pub(crate) fn new(resolution: NameResolution) -> Self {
+        Self {
+            resolution,
+            entries: BTreeMap::new(),
+            folded: HashMap::new(),
+        }
+    }
+
+    /// Returns the registered name `name` resolves to.
+    pub(crate) fn resolve(&self, name: &str) -> Option<&Arc<str>> {
+        match self.resolution {
+            NameResolution::Exact => self.entries.get_key_value(name).map(|(name, _)| name),
+            NameResolution::CaseInsensitive => self.folded.get(self.resolution.fold(name).as_ref()),
+        }
+    }
+
+    pub(crate) fn get_key_value(&self, name: &str) -> Option<(&Arc<str>, &V)> {
+        let name = self.resolve(name)?;
+        self.entries.get_key_value(name)
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&V> {
+        self.get_key_value(name).map(|(_, value)| value)
+    }
+
+    pub(crate) fn contains_key(&self, name: &str) -> bool {
+        self.resolve(name).is_some()
+    }
+
+    /// Returns the registered names.
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.entries.keys()
+    }
+
+    /// Returns at most `limit` registered names in ascending order after the name
+    /// `start_after` if it's given, and whether there are more names after them.
+    pub(crate) fn keys_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> (Vec<String>, bool) {
+        let start = start_after.map_or(Bound::Unbounded, Bound::Excluded);
+        let mut names = self
+            .entries
+            .range::<str, _>((start, Bound::Unbounded))
+            .map(|(name, _)| name.to_string());
+        let page = names.by_ref().take(limit).collect();
+        (page, names.next().is_some())
+    }
+
+    /// Inserts the entry of `name`, replacing the entry `name` resolves to if any.
+    pub(crate) fn insert(&mut self, name: Arc<str>, value: V) -> Option<V> {
+        let replaced = self.remove(&name).map(|(_, value)| value);
+        if self.resolution != NameResolution::Exact {
+            let _ = self
+                .folded
+                .insert(self.resolution.fold(&name).into_owned(), name.clone());
+        }
+        let _ = self.entries.insert(name, value);
+        replaced
+    }
+
+    /// Removes the entry `name` resolves to, returns its registered name and value.
+    pub(crate) fn remove(&mut self, name: &str) -> Option<(Arc<str>, V)> {
+        let name = self.resolve(name)?.clone();
+        if self.resolution != NameResolution::Exact {
+            let _ = self.folded.remove(self.resolution.fold(&name).as_ref());
+        }
+        self.entries.remove_entry(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_map() {
+        let mut exact = NameMap::new(NameResolution::Exact);
+        assert!(exact.insert("MyTable".into(), 1).is_none());
+        assert_eq!(Some(&1), exact.get("MyTable"));
+        assert!(exact.get("mytable").is_none());
+        assert!(exact.insert("mytable".into(), 2).is_none());
+        assert_eq!(2, exact.keys().count());
+
+        let mut insensitive = NameMap::new(NameResolution::CaseInsensitive);
+        assert!(insensitive.insert("MyTable".into(), 1).is_none());
+        let (name, value) = insensitive.get_key_value("mytable").unwrap();
+        assert_eq!("MyTable", name.as_ref());
+        assert_eq!(1, *value);
+        assert!(insensitive.contains_key("MYTABLE"));
+
+        // Replaces the entry and its registered name.
+        assert_eq!(Some(1), insensitive.insert("mytable".into(), 2));
+        assert_eq!(
+            vec!["mytable"],
+            insensitive
+                .keys()
+                .map(|name| name.as_ref())
+                .collect::<Vec<_>>()
+        );
+
+        let (name, value) = insensitive.remove("MyTable").unwrap();
+        assert_eq!(("mytable", 2), (name.as_ref(), value));
+        assert!(!insensitive.contains_key("mytable"));
+        assert!(insensitive.folded.is_empty());
+    }
+
+    #[test]
+    fn test_name_map_keys_paginated() {
+        let mut names = NameMap::new(NameResolution::CaseInsensitive);
+        for name in ["c", "B", "a", "d"] {
+            assert!(names.insert(name.into(), ()).is_none());
+        }
+
+        assert_eq!(
+            (vec!["B".to_string(), "a".to_string()], true),
+            names.keys_paginated(None, 2)
+        );
+        assert_eq!(
+            (vec!["c".to_string(), "d".to_string()], false),
+            names.keys_paginated(Some("a"), 2)
+        );
+        // The name to start after needn't be registered.
+        assert_eq!(
+            (vec!["d".to_string()], false),
+            names.keys_paginated(Some("cc"), 10)
+        );
+        assert_eq!((vec![], false), names.keys_paginated(Some("d"), 2));
+    }
+
+    #[test]
+    fn test_conflicting_name() {
+        assert_eq!("mytable", conflicting_name("mytable", "mytable"));
+        assert_eq!(
+            "MyTable` as `mytable",
+            conflicting_name("MyTable", "mytable")
+        );
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! catalog metrics
+
+/// Number of tables to open when the catalog manager starts.
+pub(crate) const METRIC_REPLAY_TABLES_TOTAL: &str = "catalog.replay.tables.total";
+/// Number of tables opened, with their WALs replayed, since the catalog manager starts.
+pub(crate) const METRIC_REPLAY_TABLES_REPLAYED: &str = "catalog.replay.tables.replayed";
+/// Number of tables failed to open since the catalog manager starts.
+pub(crate) const METRIC_REPLAY_TABLES_FAILED: &str = "catalog.replay.tables.failed";
+/// Number of tables purged of their expired rows.
+pub(crate) const METRIC_TTL_PURGED_TABLES: &str = "catalog.ttl.purged_tables";
+/// Number of tables failed to purge their expired rows.
+pub(crate) const METRIC_TTL_PURGE_FAILED: &str = "catalog.ttl.purge_failed";
@@ -0,0 +1,110 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Naming of the tables in the recycle bin.
+//!
+//! A soft dropped table is renamed to an internal name carrying its id, the time it was
+//! dropped and its original name, so the recycle bin needs no state besides the catalog.
+//! Tables with such names are hidden from name resolution and listing, and users can't create
+//! or rename tables to them.
+
+pub use common_catalog::naming::{is_recycled_table_name, RECYCLED_TABLE_PREFIX};
+use table::metadata::TableId;
+
+use crate::error::Result;
+use crate::SchemaProviderRef;
+
+/// A table in the recycle bin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecycledTable {
+    /// Name of the table before it was dropped.
+    pub table_name: String,
+    pub table_id: TableId,
+    pub dropped_at_millis: i64,
+    /// Name of the table in the recycle bin.
+    pub recycled_name: String,
+}
+
+impl RecycledTable {
+    pub fn new(table_name: &str, table_id: TableId, dropped_at_millis: i64) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            table_id,
+            dropped_at_millis,
+            recycled_name: format!(
+                "{RECYCLED_TABLE_PREFIX}{table_id}_{dropped_at_millis}_{table_name}"
+            ),
+        }
+    }
+
+    /// Parses the recycled table from its name in the recycle bin, returns `None` if the
+    /// name is not a recycled name.
+    pub fn parse(recycled_name: &str) -> Option<Self> {
+        let rest = recycled_name.strip_prefix(RECYCLED_TABLE_PREFIX)?;
+        let mut parts = rest.splitn(3, '_');
+        let table_id = parts.next()?.parse().ok()?;
+        let dropped_at_millis = parts.next()?.parse().ok()?;
+        let table_name = parts.next().filter(|name| !name.is_empty())?;
+        Some(Self {
+            table_name: table_name.to_string(),
+            table_id,
+            dropped_at_millis,
+            recycled_name: recycled_name.to_string(),
+        })
+    }
+}
+
+/// Lists the tables of `schema` in the recycle bin, in the order they were dropped.
+pub async fn recycled_tables(schema: &SchemaProviderRef) -> Result<Vec<RecycledTable>> {
+    let mut tables = schema
+        .table_names()
+        .await?
+        .iter()
+        .filter_map(|name| RecycledTable::parse(name))
+        .collect::<Vec<_>>();
+    tables.sort_by(|a, b| {
+        a.dropped_at_millis
+            .cmp(&b.dropped_at_millis)
+            .then_with(|| a.recycled_name.cmp(&b.recycled_name))
+    });
+    Ok(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recycled_table_name() {
+        let table = RecycledTable::new("my_table", 1024, 1680000000000);
+        assert_eq!(
+            "__recycled_1024_1680000000000_my_table",
+            table.recycled_name
+        );
+        assert!(is_recycled_table_name(&table.recycled_name));
+        assert_eq!(
+            Some(table.clone()),
+            RecycledTable::parse(&table.recycled_name)
+        );
+
+        assert!(!is_recycled_table_name("my_table"));
+        assert_eq!(None, RecycledTable::parse("my_table"));
+        assert_eq!(None, RecycledTable::parse("__recycled_1024_my_table"));
+        assert_eq!(
+            None,
+            RecycledTable::parse("__recycled_x_1680000000000_my_table")
+        );
+        assert_eq!(None, RecycledTable::parse("__recycled_1024_1680000000000_"));
+    }
+}
@@ -16,15 +16,19 @@ use std::fmt::Debug;
 use std::pin::Pin;
 use std::sync::Arc;
 
-pub use client::MetaKvBackend;
+use async_stream::try_stream;
+pub use client::{CachedMetaKvBackend, MetaKvBackend};
 use futures::Stream;
 use futures_util::StreamExt;
 pub use manager::{RemoteCatalogManager, RemoteCatalogProvider, RemoteSchemaProvider};
+pub use migration::{migrate_remote_catalog, REMOTE_CATALOG_VERSION};
 
 use crate::error::Error;
+use crate::NAMES_PAGE_SIZE;
 
 mod client;
 mod manager;
+mod migration;
 
 #[derive(Debug, Clone)]
 pub struct Kv(pub Vec<u8>, pub Vec<u8>);
@@ -70,10 +74,126 @@ pub trait KvBackend: Send + Sync {
         }
         return Ok(None);
     }
+
+    /// Returns whether the key exists, without fetching its value if the backend can.
+    ///
+    /// Default exists is implemented based on `get` method.
+    async fn exists(&self, key: &[u8]) -> Result<bool, Error> {
+        Ok(self.get(key).await?.is_some())
+    }
+
+    /// Returns the key-values of the `keys` that exist, in no particular order, fetching them
+    /// in one request if the backend can.
+    ///
+    /// Default batch get is implemented based on `get` method.
+    async fn batch_get(&self, keys: &[Vec<u8>]) -> Result<Vec<Kv>, Error> {
+        let mut kvs = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(kv) = self.get(key).await? {
+                kvs.push(kv);
+            }
+        }
+        Ok(kvs)
+    }
+
+    /// Returns at most `limit` key-values whose keys start with `prefix` and are not less than
+    /// `start` in ascending order of keys, and whether there are more key-values after them.
+    ///
+    /// Default range page is implemented based on `range` method.
+    async fn range_page(
+        &self,
+        prefix: &[u8],
+        start: &[u8],
+        limit: usize,
+    ) -> Result<(Vec<Kv>, bool), Error> {
+        let mut iter = self.range(prefix);
+        let mut kvs = Vec::new();
+        while let Some(r) = iter.next().await {
+            let kv = r?;
+            if kv.0.as_slice() < start {
+                continue;
+            }
+            if kvs.len() == limit {
+                return Ok((kvs, true));
+            }
+            kvs.push(kv);
+        }
+        Ok((kvs, false))
+    }
 }
 
 pub type KvBackendRef = Arc<dyn KvBackend>;
 
+/// Streams the names parsed from the keys starting with `prefix` by `parse` in ascending order
+/// of keys, after the name `start_after` if it's given, fetching [NAMES_PAGE_SIZE] key-values
+/// at a time. Names parsed from consecutive keys, like the regional keys of a table on
+/// different nodes, are only yielded once.
+///
+/// The keys are `prefix` followed by the names, maybe with a suffix, so the range starts from
+/// the keys of `start_after` rather than scanning the keys before.
+pub fn range_names(
+    backend: KvBackendRef,
+    prefix: String,
+    start_after: Option<&str>,
+    parse: fn(&str) -> Result<String, Error>,
+) -> impl Stream<Item = Result<String, Error>> + Send {
+    let start_after = start_after.map(|name| name.to_string());
+    try_stream!({
+        let mut start =
+            format!("{prefix}{}", start_after.as_deref().unwrap_or_default()).into_bytes();
+        let mut last_name = start_after;
+        loop {
+            let (kvs, more) = backend
+                .range_page(prefix.as_bytes(), &start, NAMES_PAGE_SIZE)
+                .await?;
+            let Some(Kv(last_key, _)) = kvs.last() else { break };
+            // The smallest key after the last one.
+            start = last_key.clone();
+            start.push(0);
+
+            for Kv(key, _) in kvs {
+                let name = parse(&String::from_utf8_lossy(&key))?;
+                if last_name
+                    .as_ref()
+                    .map_or(true, |last_name| *last_name < name)
+                {
+                    last_name = Some(name.clone());
+                    yield name;
+                }
+            }
+            if !more {
+                break;
+            }
+        }
+    })
+}
+
+/// Returns at most `limit` of the streamed `names`, and whether there are more names after
+/// them.
+pub async fn paginate_names(
+    names: impl Stream<Item = Result<String, Error>>,
+    limit: usize,
+) -> Result<(Vec<String>, bool), Error> {
+    let mut names = Box::pin(names);
+    let mut page = Vec::with_capacity(limit);
+    while let Some(name) = names.next().await {
+        let name = name?;
+        if page.len() == limit {
+            return Ok((page, true));
+        }
+        page.push(name);
+    }
+    Ok((page, false))
+}
+
+/// Invalidates the cached values of keys, e.g. when a table is dropped.
+#[async_trait::async_trait]
+pub trait KvCacheInvalidator: Send + Sync {
+    async fn invalidate_key(&self, key: &[u8]);
+}
+
+pub type KvCacheInvalidatorRef = Arc<dyn KvCacheInvalidator>;
+
 #[cfg(test)]
 mod tests {
     use async_stream::stream;
@@ -128,4 +248,42 @@ mod tests {
         let result = backend.get(3.to_string().as_bytes()).await;
         assert!(result.unwrap().is_none());
     }
+
+    #[tokio::test]
+    async fn test_range_page() {
+        let backend = MockKvBackend {};
+        let (kvs, more) = backend.range_page(b"", b"1", 1).await.unwrap();
+        assert_eq!(
+            vec![b"1".to_vec()],
+            kvs.into_iter().map(|kv| kv.0).collect::<Vec<_>>()
+        );
+        assert!(more);
+        let (kvs, more) = backend.range_page(b"", b"1", 2).await.unwrap();
+        assert_eq!(2, kvs.len());
+        assert!(!more);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_names() {
+        let names = |start_after| {
+            range_names(
+                Arc::new(MockKvBackend {}),
+                String::new(),
+                start_after,
+                |key| Ok(key.to_string()),
+            )
+        };
+        assert_eq!(
+            (vec!["0".to_string(), "1".to_string()], true),
+            paginate_names(names(None), 2).await.unwrap()
+        );
+        assert_eq!(
+            (vec!["2".to_string()], false),
+            paginate_names(names(Some("1")), 2).await.unwrap()
+        );
+        assert_eq!(
+            (vec![], false),
+            paginate_names(names(Some("2")), 2).await.unwrap()
+        );
+    }
 }
@@ -12,17 +12,222 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use async_stream::stream;
 use common_telemetry::info;
 use meta_client::client::MetaClient;
-use meta_client::rpc::{CompareAndPutRequest, DeleteRangeRequest, PutRequest, RangeRequest};
+use meta_client::rpc::util::get_prefix_end_key;
+use meta_client::rpc::{
+    BatchGetRequest, CompareAndPutRequest, DeleteRangeRequest, PutRequest, RangeRequest,
+};
+use moka::future::{Cache, CacheBuilder};
 use snafu::ResultExt;
 
 use crate::error::{Error, MetaSrvSnafu};
-use crate::remote::{Kv, KvBackend, ValueIter};
+use crate::remote::{Kv, KvBackend, KvCacheInvalidator, ValueIter};
+
+const CACHE_MAX_CAPACITY: u64 = 10000;
+const CACHE_TTL_SECOND: u64 = 30 * 60;
+const CACHE_TTI_SECOND: u64 = 5 * 60;
+/// Max number of the keys whose last invalidations are tracked.
+const MAX_TRACKED_INVALIDATIONS: usize = 10000;
+
+/// A [MetaKvBackend] caching the values got by key, to avoid a request to metasrv for each
+/// lookup of the catalog. Keys written by this backend are invalidated, while keys changed by
+/// other nodes are stale until they expire or are invalidated by [KvCacheInvalidator].
+///
+/// Absent keys are not cached, so a newly created catalog, schema or table is visible at once.
+pub struct CachedMetaKvBackend {
+    kv_backend: MetaKvBackend,
+    cache: Cache<Vec<u8>, Kv>,
+    /// A value fetched before an invalidation of its key may be stale, so it's not cached
+    /// after the invalidation.
+    invalidations: Mutex<Invalidations>,
+}
+
+/// Tracks the last invalidation of each key, ordered by sequence.
+#[derive(Debug, Default)]
+struct Invalidations {
+    /// Sequence of the last invalidation.
+    sequence: u64,
+    /// Sequences of the last invalidations of the keys.
+    keys: HashMap<Vec<u8>, u64>,
+    /// All keys are taken as invalidated at this sequence, by invalidating all of them or by
+    /// forgetting the tracked keys once there are too many.
+    all_keys: u64,
+}
+
+impl Invalidations {
+    fn invalidate(&mut self, key: &[u8]) {
+        self.sequence += 1;
+        if self.keys.len() >= MAX_TRACKED_INVALIDATIONS {
+            self.invalidate_all();
+        } else {
+            let _ = self.keys.insert(key.to_vec(), self.sequence);
+        }
+    }
+
+    fn invalidate_all(&mut self) {
+        self.sequence += 1;
+        self.keys.clear();
+        self.all_keys = self.sequence;
+    }
+
+    /// Returns whether the `key` is invalidated after the `sequence`.
+    fn is_invalidated_after(&self, key: &[u8], sequence: u64) -> bool {
+        self.all_keys > sequence || self.keys.get(key).map_or(false, |last| *last > sequence)
+    }
+}
+
+impl CachedMetaKvBackend {
+    pub fn new(client: Arc<MetaClient>) -> Self {
+        Self {
+            kv_backend: MetaKvBackend { client },
+            cache: CacheBuilder::new(CACHE_MAX_CAPACITY)
+                .time_to_live(Duration::from_secs(CACHE_TTL_SECOND))
+                .time_to_idle(Duration::from_secs(CACHE_TTI_SECOND))
+                .build(),
+            invalidations: Mutex::default(),
+        }
+    }
+
+    pub fn client(&self) -> &Arc<MetaClient> {
+        &self.kv_backend.client
+    }
+
+    /// Returns the sequence of the last invalidation, to be passed to [Self::insert_cache]
+    /// along with the key-values fetched after it.
+    pub fn invalidations(&self) -> u64 {
+        self.invalidations.lock().unwrap().sequence
+    }
+
+    /// Puts the key-values fetched in bulk into the cache, e.g. while warming up, except the
+    /// ones whose keys are invalidated after `invalidations` is returned by
+    /// [Self::invalidations], as their values may be stale.
+    pub async fn insert_cache(&self, kvs: impl IntoIterator<Item = Kv>, invalidations: u64) {
+        for kv in kvs {
+            self.insert_if_not_invalidated(kv, invalidations).await;
+        }
+    }
+
+    /// Caches `kv` unless its key is invalidated after `invalidations`.
+    async fn insert_if_not_invalidated(&self, kv: Kv, invalidations: u64) {
+        let key = kv.0.clone();
+        self.cache.insert(key.clone(), kv).await;
+        // Checks after inserting, as an invalidation after the check removes the value itself.
+        let invalidated = self
+            .invalidations
+            .lock()
+            .unwrap()
+            .is_invalidated_after(&key, invalidations);
+        if invalidated {
+            self.cache.invalidate(&key).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl KvBackend for CachedMetaKvBackend {
+    fn range<'a, 'b>(&'a self, key: &[u8]) -> ValueIter<'b, Error>
+    where
+        'a: 'b,
+    {
+        self.kv_backend.range(key)
+    }
+
+    async fn range_page(
+        &self,
+        prefix: &[u8],
+        start: &[u8],
+        limit: usize,
+    ) -> Result<(Vec<Kv>, bool), Error> {
+        self.kv_backend.range_page(prefix, start, limit).await
+    }
+
+    async fn get(&self, key: &[u8]) -> Result<Option<Kv>, Error> {
+        let key = key.to_vec();
+        if let Some(kv) = self.cache.get(&key) {
+            return Ok(Some(kv));
+        }
+
+        let invalidations = self.invalidations();
+        let kv = self.kv_backend.get(&key).await?;
+        if let Some(kv) = &kv {
+            self.insert_if_not_invalidated(kv.clone(), invalidations)
+                .await;
+        }
+        Ok(kv)
+    }
+
+    async fn exists(&self, key: &[u8]) -> Result<bool, Error> {
+        if self.cache.get(&key.to_vec()).is_some() {
+            return Ok(true);
+        }
+        self.kv_backend.exists(key).await
+    }
+
+    async fn batch_get(&self, keys: &[Vec<u8>]) -> Result<Vec<Kv>, Error> {
+        let mut kvs = Vec::with_capacity(keys.len());
+        let mut missed = Vec::new();
+        for key in keys {
+            match self.cache.get(key) {
+                Some(kv) => kvs.push(kv),
+                None => missed.push(key.clone()),
+            }
+        }
+        if missed.is_empty() {
+            return Ok(kvs);
+        }
+
+        let invalidations = self.invalidations();
+        let fetched = self.kv_backend.batch_get(&missed).await?;
+        self.insert_cache(fetched.iter().cloned(), invalidations)
+            .await;
+        kvs.extend(fetched);
+        Ok(kvs)
+    }
+
+    async fn set(&self, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        let result = self.kv_backend.set(key, val).await;
+        self.invalidate_key(key).await;
+        result
+    }
+
+    async fn delete_range(&self, key: &[u8], end: &[u8]) -> Result<(), Error> {
+        let result = self.kv_backend.delete_range(key, end).await;
+        if end.is_empty() {
+            self.invalidate_key(key).await;
+        } else {
+            self.invalidations.lock().unwrap().invalidate_all();
+            self.cache.invalidate_all();
+        }
+        result
+    }
+
+    async fn compare_and_set(
+        &self,
+        key: &[u8],
+        expect: &[u8],
+        val: &[u8],
+    ) -> Result<Result<(), Option<Vec<u8>>>, Error> {
+        let result = self.kv_backend.compare_and_set(key, expect, val).await;
+        self.invalidate_key(key).await;
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl KvCacheInvalidator for CachedMetaKvBackend {
+    async fn invalidate_key(&self, key: &[u8]) {
+        self.invalidations.lock().unwrap().invalidate(key);
+        self.cache.invalidate(&key.to_vec()).await
+    }
+}
+
 #[derive(Debug)]
 pub struct MetaKvBackend {
     pub client: Arc<MetaClient>,
@@ -51,6 +256,24 @@ impl KvBackend for MetaKvBackend {
         }))
     }
 
+    async fn range_page(
+        &self,
+        prefix: &[u8],
+        start: &[u8],
+        limit: usize,
+    ) -> Result<(Vec<Kv>, bool), Error> {
+        let req = RangeRequest::new()
+            .with_range(start.to_vec(), get_prefix_end_key(prefix))
+            .with_limit(limit as i64);
+        let mut resp = self.client.range(req).await.context(MetaSrvSnafu)?;
+        let kvs = resp
+            .take_kvs()
+            .into_iter()
+            .map(|mut kv| Kv(kv.take_key(), kv.take_value()))
+            .collect();
+        Ok((kvs, resp.more()))
+    }
+
     async fn get(&self, key: &[u8]) -> Result<Option<Kv>, Error> {
         let mut response = self
             .client
@@ -63,6 +286,27 @@ impl KvBackend for MetaKvBackend {
             .map(|kv| Kv(kv.take_key(), kv.take_value())))
     }
 
+    async fn batch_get(&self, keys: &[Vec<u8>]) -> Result<Vec<Kv>, Error> {
+        let req = keys
+            .iter()
+            .fold(BatchGetRequest::new(), |req, key| req.add_key(key.clone()));
+        let mut response = self.client.batch_get(req).await.context(MetaSrvSnafu)?;
+        Ok(response
+            .take_kvs()
+            .into_iter()
+            .map(|mut kv| Kv(kv.take_key(), kv.take_value()))
+            .collect())
+    }
+
+    async fn exists(&self, key: &[u8]) -> Result<bool, Error> {
+        let req = RangeRequest::new()
+            .with_key(key)
+            .with_limit(1)
+            .with_keys_only();
+        let mut response = self.client.range(req).await.context(MetaSrvSnafu)?;
+        Ok(!response.take_kvs().is_empty())
+    }
+
     async fn set(&self, key: &[u8], val: &[u8]) -> Result<(), Error> {
         let req = PutRequest::new()
             .with_key(key.to_vec())
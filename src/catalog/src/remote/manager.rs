@@ -15,41 +15,51 @@
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use async_stream::stream;
 use async_trait::async_trait;
-use common_catalog::consts::{MIN_USER_TABLE_ID, MITO_ENGINE};
+use common_catalog::consts::{INFORMATION_SCHEMA_NAME, MIN_USER_TABLE_ID, MITO_ENGINE};
+use common_catalog::format_full_table_name;
 use common_telemetry::{debug, error, info, warn};
+use common_time::util::SystemClock;
 use dashmap::DashMap;
 use futures::Stream;
 use futures_util::{StreamExt, TryStreamExt};
 use parking_lot::RwLock;
 use snafu::{OptionExt, ResultExt};
+use table::column_limits::{ColumnLimitsOptions, ColumnLimitsOptionsRef};
 use table::engine::manager::TableEngineManagerRef;
 use table::engine::{EngineContext, TableReference};
 use table::metadata::TableId;
-use table::requests::{CreateTableRequest, OpenTableRequest};
+use table::requests::{CreateTableRequest, OpenTableRequest, TableOptions};
 use table::TableRef;
 use tokio::sync::Mutex;
 
 use crate::error::{
-    CatalogNotFoundSnafu, CreateTableSnafu, InvalidCatalogValueSnafu, OpenTableSnafu,
-    ParallelOpenTableSnafu, Result, SchemaNotFoundSnafu, TableEngineNotFoundSnafu,
-    TableExistsSnafu, UnimplementedSnafu,
+    CatalogNotFoundSnafu, CreateTableSnafu, Error, InvalidCatalogValueSnafu,
+    InvalidSchemaTableOptionsSnafu, OpenTableSnafu, ParallelOpenTableSnafu, Result,
+    SchemaNotFoundSnafu, TableEngineNotFoundSnafu, TableExistsSnafu, UnimplementedSnafu,
 };
 use crate::helper::{
     build_catalog_prefix, build_schema_prefix, build_table_global_prefix,
     build_table_regional_prefix, CatalogKey, CatalogValue, SchemaKey, SchemaValue, TableGlobalKey,
     TableGlobalValue, TableRegionalKey, TableRegionalValue, CATALOG_KEY_PREFIX,
 };
-use crate::remote::{Kv, KvBackendRef};
+use crate::information_schema::InformationSchemaProvider;
+use crate::remote::{migrate_remote_catalog, paginate_names, range_names, Kv, KvBackendRef};
+use crate::replay::{wait_replay, ReplayProgress, ReplayProgressRef, DEFAULT_REPLAY_CONCURRENCY};
+use crate::ttl_purger::{TtlPurgeOptions, TtlPurger, TtlPurgerRef};
 use crate::{
     handle_system_table_request, CatalogManager, CatalogProvider, CatalogProviderRef,
     DeregisterTableRequest, RegisterSchemaRequest, RegisterSystemTableRequest,
     RegisterTableRequest, RenameTableRequest, SchemaProvider, SchemaProviderRef,
 };
 
+/// A table to open when the catalog manager starts, and the schema to register it to.
+type TableToOpen = (SchemaProviderRef, TableGlobalKey, TableGlobalValue);
+
 /// Catalog manager based on metasrv.
 pub struct RemoteCatalogManager {
     node_id: u64,
@@ -57,6 +67,11 @@ pub struct RemoteCatalogManager {
     catalogs: Arc<RwLock<DashMap<String, CatalogProviderRef>>>,
     engine_manager: TableEngineManagerRef,
     system_table_requests: Mutex<Vec<RegisterSystemTableRequest>>,
+    replay_concurrency: usize,
+    replay_progress: ReplayProgressRef,
+    started: AtomicBool,
+    column_limits: ColumnLimitsOptionsRef,
+    ttl_purger: TtlPurgerRef,
 }
 
 impl RemoteCatalogManager {
@@ -67,15 +82,69 @@ impl RemoteCatalogManager {
             backend,
             catalogs: Default::default(),
             system_table_requests: Default::default(),
+            replay_concurrency: DEFAULT_REPLAY_CONCURRENCY,
+            replay_progress: Arc::new(ReplayProgress::default()),
+            started: AtomicBool::new(false),
+            column_limits: Arc::new(ColumnLimitsOptions::default()),
+            ttl_purger: Arc::new(TtlPurger::new(
+                TtlPurgeOptions::default(),
+                Arc::new(SystemClock),
+            )),
         }
     }
 
+    /// Sets the max number of tables opened, which replays the WALs of their regions,
+    /// concurrently when the catalog manager starts.
+    pub fn with_replay_concurrency(mut self, concurrency: usize) -> Self {
+        self.replay_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the column limits of the tables, the default ones if not set.
+    pub fn with_column_limits(mut self, column_limits: ColumnLimitsOptions) -> Self {
+        self.column_limits = Arc::new(column_limits);
+        self
+    }
+
+    /// Sets the options of purging the rows expired by the `ttl` option of the tables.
+    pub fn with_ttl_purge_options(mut self, options: TtlPurgeOptions) -> Self {
+        self.ttl_purger = Arc::new(TtlPurger::new(options, Arc::new(SystemClock)));
+        self
+    }
+
+    /// Returns the purger of the rows expired by the `ttl` option of the registered tables.
+    pub fn ttl_purger(&self) -> TtlPurgerRef {
+        self.ttl_purger.clone()
+    }
+
+    /// Finds the registered table, regardless of the tables replaying.
+    async fn find_table(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Option<TableRef>> {
+        let catalog = self
+            .catalog(catalog_name)
+            .await?
+            .with_context(|| CatalogNotFoundSnafu { catalog_name })?;
+        let schema = catalog
+            .schema(schema_name)
+            .await?
+            .with_context(|| SchemaNotFoundSnafu {
+                catalog: catalog_name,
+                schema: schema_name,
+            })?;
+        schema.table(table_name).await
+    }
+
     fn new_catalog_provider(&self, catalog_name: &str) -> CatalogProviderRef {
         Arc::new(RemoteCatalogProvider {
             node_id: self.node_id,
             catalog_name: catalog_name.to_string(),
             backend: self.backend.clone(),
             engine_manager: self.engine_manager.clone(),
+            column_limits: self.column_limits.clone(),
         }) as _
     }
 
@@ -175,6 +244,7 @@ impl RemoteCatalogManager {
     /// Fetch catalogs/schemas/tables from remote catalog manager along with max table id allocated.
     async fn initiate_catalogs(&self) -> Result<(HashMap<String, CatalogProviderRef>, TableId)> {
         let mut res = HashMap::new();
+        let mut tables = Vec::new();
         let max_table_id = MIN_USER_TABLE_ID - 1;
 
         let mut catalogs = self.iter_remote_catalogs().await;
@@ -185,10 +255,19 @@ impl RemoteCatalogManager {
                 .entry(catalog_name.clone())
                 .or_insert_with(|| self.new_catalog_provider(&catalog_name))
                 .clone();
+            // Registers the catalog before opening its tables, so the tables are queryable as
+            // soon as they are opened.
+            self.catalogs
+                .read()
+                .insert(catalog_name.clone(), catalog.clone());
 
-            self.initiate_schemas(catalog_name, catalog, max_table_id)
+            self.initiate_schemas(catalog_name, catalog, &mut tables)
                 .await?;
         }
+        // All the tables are listed before any is opened, so the names of the tables not
+        // listed are available to create at once.
+        self.replay_progress.finish_listing();
+        self.open_tables(tables).await;
 
         Ok((res, max_table_id))
     }
@@ -197,7 +276,7 @@ impl RemoteCatalogManager {
         &self,
         catalog_name: String,
         catalog: CatalogProviderRef,
-        max_table_id: TableId,
+        tables: &mut Vec<TableToOpen>,
     ) -> Result<()> {
         let mut schemas = self.iter_remote_schemas(&catalog_name).await;
         while let Some(r) = schemas.next().await {
@@ -223,52 +302,77 @@ impl RemoteCatalogManager {
                 "Fetch schema from metasrv: {}.{}",
                 &catalog_name, &schema_name
             );
-            self.initiate_tables(&catalog_name, &schema_name, schema, max_table_id)
+            self.list_tables(&catalog_name, &schema_name, schema, tables)
                 .await?;
         }
         Ok(())
     }
 
-    /// Initiates all tables inside a catalog by fetching data from metasrv.
-    async fn initiate_tables<'a>(
-        &'a self,
-        catalog_name: &'a str,
-        schema_name: &'a str,
+    /// Lists the tables inside a schema to open by fetching data from metasrv.
+    async fn list_tables(
+        &self,
+        catalog_name: &str,
+        schema_name: &str,
         schema: SchemaProviderRef,
-        mut max_table_id: TableId,
+        tables: &mut Vec<TableToOpen>,
     ) -> Result<()> {
-        info!("initializing tables in {}.{}", catalog_name, schema_name);
-        let mut table_num = 0;
-        let tables = self.iter_remote_tables(catalog_name, schema_name).await;
+        let kvs = self
+            .iter_remote_tables(catalog_name, schema_name)
+            .await
+            .try_collect::<Vec<_>>()
+            .await?;
+        info!(
+            "Found {} tables in {}.{}",
+            kvs.len(),
+            catalog_name,
+            schema_name
+        );
+        self.replay_progress
+            .add_pending(kvs.iter().map(|(table_key, _)| {
+                format_full_table_name(
+                    &table_key.catalog_name,
+                    &table_key.schema_name,
+                    &table_key.table_name,
+                )
+            }));
+        tables.extend(
+            kvs.into_iter()
+                .map(|(table_key, table_value)| (schema.clone(), table_key, table_value)),
+        );
+        Ok(())
+    }
 
-        let kvs = tables.try_collect::<Vec<_>>().await?;
+    /// Opens the listed tables.
+    ///
+    /// Tables are opened concurrently and each table is registered as soon as it's opened. A
+    /// table failed to open is left closed without failing the others.
+    async fn open_tables(&self, tables: Vec<TableToOpen>) {
         let node_id = self.node_id;
-        let joins = kvs
-            .into_iter()
-            .map(|(table_key, table_value)| {
+        let opening = futures::stream::iter(tables)
+            .map(|(schema, table_key, table_value)| {
                 let engine_manager = self.engine_manager.clone();
-                common_runtime::spawn_bg(async move {
-                    open_or_create_table(node_id, engine_manager, &table_key, &table_value).await
-                })
+                let ttl_purger = self.ttl_purger.clone();
+                let table_name = format_full_table_name(
+                    &table_key.catalog_name,
+                    &table_key.schema_name,
+                    &table_key.table_name,
+                );
+                let handle = common_runtime::spawn_bg(async move {
+                    let table_ref =
+                        open_or_create_table(node_id, engine_manager, &table_key, &table_value)
+                            .await?;
+                    let table_name = table_ref.table_info().name.clone();
+                    schema.register_table(table_name, table_ref.clone()).await?;
+                    ttl_purger.track(&table_ref);
+                    Ok::<_, Error>(())
+                });
+                async move {
+                    let result = handle.await.context(ParallelOpenTableSnafu).and_then(|r| r);
+                    (table_name, result)
+                }
             })
-            .collect::<Vec<_>>();
-        let vec = futures::future::join_all(joins).await;
-        for res in vec {
-            let table_ref = res.context(ParallelOpenTableSnafu)??;
-            let table_info = table_ref.table_info();
-            let table_name = &table_info.name;
-            let table_id = table_info.ident.table_id;
-            schema.register_table(table_name.clone(), table_ref).await?;
-            info!("Registered table {}", table_name);
-            max_table_id = max_table_id.max(table_id);
-            table_num += 1;
-        }
-
-        info!(
-            "initialized tables in {}.{}, total: {}",
-            catalog_name, schema_name, table_num
-        );
-        Ok(())
+            .buffer_unordered(self.replay_concurrency);
+        wait_replay(&self.replay_progress, opening).await;
     }
 
     pub async fn create_catalog_and_schema(
@@ -291,7 +395,7 @@ impl RemoteCatalogManager {
         self.backend
             .set(
                 schema_key.as_bytes(),
-                &SchemaValue {}
+                &SchemaValue::default()
                     .as_bytes()
                     .context(InvalidCatalogValueSnafu)?,
             )
@@ -402,19 +506,14 @@ async fn open_or_create_table(
 #[async_trait::async_trait]
 impl CatalogManager for RemoteCatalogManager {
     async fn start(&self) -> Result<()> {
+        migrate_remote_catalog(&self.backend).await?;
         let (catalogs, max_table_id) = self.initiate_catalogs().await?;
+        self.replay_progress.finish();
         info!(
             "Initialized catalogs: {:?}",
             catalogs.keys().cloned().collect::<Vec<_>>()
         );
 
-        {
-            let self_catalogs = self.catalogs.read();
-            catalogs.into_iter().for_each(|(k, v)| {
-                self_catalogs.insert(k, v);
-            });
-        }
-
         info!("Max table id allocated: {}", max_table_id);
 
         let mut system_table_requests = self.system_table_requests.lock().await;
@@ -426,33 +525,46 @@ impl CatalogManager for RemoteCatalogManager {
             })?;
         handle_system_table_request(self, engine, &mut system_table_requests).await?;
         info!("All system table opened");
+        self.started.store(true, Ordering::Release);
+        self.ttl_purger.start();
         Ok(())
     }
 
+    fn is_started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    fn column_limits(&self) -> ColumnLimitsOptionsRef {
+        self.column_limits.clone()
+    }
+
+    fn replay_progress(&self) -> Option<ReplayProgressRef> {
+        Some(self.replay_progress.clone())
+    }
+
     async fn register_table(&self, request: RegisterTableRequest) -> Result<bool> {
-        let catalog_name = request.catalog;
-        let schema_name = request.schema;
+        let catalog_name = request.catalog.as_ref();
+        let schema_name = request.schema.as_ref();
         let schema_provider = self
-            .catalog(&catalog_name)
+            .catalog(catalog_name)
             .await?
-            .context(CatalogNotFoundSnafu {
-                catalog_name: &catalog_name,
-            })?
-            .schema(&schema_name)
+            .context(CatalogNotFoundSnafu { catalog_name })?
+            .schema(schema_name)
             .await?
             .with_context(|| SchemaNotFoundSnafu {
-                catalog: &catalog_name,
-                schema: &schema_name,
+                catalog: catalog_name,
+                schema: schema_name,
             })?;
         if schema_provider.table_exist(&request.table_name).await? {
             return TableExistsSnafu {
-                table: format!("{}.{}.{}", &catalog_name, &schema_name, &request.table_name),
+                table: format!("{}.{}.{}", catalog_name, schema_name, &request.table_name),
             }
             .fail();
         }
         schema_provider
-            .register_table(request.table_name, request.table)
+            .register_table(request.table_name.to_string(), request.table.clone())
             .await?;
+        self.ttl_purger.track(&request.table);
         Ok(true)
     }
 
@@ -468,21 +580,36 @@ impl CatalogManager for RemoteCatalogManager {
             })?
             .deregister_table(&request.table_name)
             .await?;
+        if let Some(table) = &result {
+            self.ttl_purger.untrack(table.table_info().ident.table_id);
+        }
         Ok(result.is_none())
     }
 
     async fn register_schema(&self, request: RegisterSchemaRequest) -> Result<bool> {
         let catalog_name = request.catalog;
         let schema_name = request.schema;
-        let catalog_provider =
-            self.catalog(&catalog_name)
-                .await?
-                .context(CatalogNotFoundSnafu {
-                    catalog_name: &catalog_name,
-                })?;
-        let schema_provider = self.new_schema_provider(&catalog_name, &schema_name);
-        catalog_provider
-            .register_schema(schema_name, schema_provider)
+        let _ = self
+            .catalog(&catalog_name)
+            .await?
+            .context(CatalogNotFoundSnafu {
+                catalog_name: &catalog_name,
+            })?;
+        // Writes the schema key directly instead of registering a schema provider to the
+        // catalog, as the remote schema provider reads its default table options from the
+        // schema key.
+        let schema_key = SchemaKey {
+            catalog_name,
+            schema_name,
+        }
+        .to_string();
+        self.backend
+            .set(
+                schema_key.as_bytes(),
+                &SchemaValue::new(&request.table_options)
+                    .as_bytes()
+                    .context(InvalidCatalogValueSnafu)?,
+            )
             .await?;
         Ok(true)
     }
@@ -529,24 +656,29 @@ impl CatalogManager for RemoteCatalogManager {
             .await
     }
 
+    async fn schema_exist(&self, catalog: &str, schema: &str) -> Result<bool> {
+        self.catalog(catalog)
+            .await?
+            .context(CatalogNotFoundSnafu {
+                catalog_name: catalog,
+            })?
+            .schema_exist(schema)
+            .await
+    }
+
     async fn table(
         &self,
         catalog_name: &str,
         schema_name: &str,
         table_name: &str,
     ) -> Result<Option<TableRef>> {
-        let catalog = self
-            .catalog(catalog_name)
-            .await?
-            .with_context(|| CatalogNotFoundSnafu { catalog_name })?;
-        let schema = catalog
-            .schema(schema_name)
-            .await?
-            .with_context(|| SchemaNotFoundSnafu {
-                catalog: catalog_name,
-                schema: schema_name,
-            })?;
-        schema.table(table_name).await
+        let table = self.find_table(catalog_name, schema_name, table_name).await;
+        if !matches!(table, Ok(Some(_))) {
+            // The table, or its schema, may be not registered yet as it's still replaying.
+            self.replay_progress
+                .ensure_not_pending(catalog_name, schema_name, table_name)?;
+        }
+        table
     }
 
     async fn catalog(&self, catalog: &str) -> Result<Option<CatalogProviderRef>> {
@@ -600,11 +732,13 @@ impl CatalogManager for RemoteCatalogManager {
     }
 }
 
+#[derive(Clone)]
 pub struct RemoteCatalogProvider {
     node_id: u64,
     catalog_name: String,
     backend: KvBackendRef,
     engine_manager: TableEngineManagerRef,
+    column_limits: ColumnLimitsOptionsRef,
 }
 
 impl RemoteCatalogProvider {
@@ -619,9 +753,16 @@ impl RemoteCatalogProvider {
             catalog_name,
             backend,
             engine_manager,
+            column_limits: Arc::new(ColumnLimitsOptions::default()),
         }
     }
 
+    /// Sets the column limits of the tables, which `information_schema.column_limits` reports.
+    pub fn with_column_limits(mut self, column_limits: ColumnLimitsOptionsRef) -> Self {
+        self.column_limits = column_limits;
+        self
+    }
+
     fn build_schema_key(&self, schema_name: impl AsRef<str>) -> SchemaKey {
         SchemaKey {
             catalog_name: self.catalog_name.clone(),
@@ -663,17 +804,30 @@ impl CatalogProvider for RemoteCatalogProvider {
         Ok(res)
     }
 
+    async fn schema_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        let schema_prefix = build_schema_prefix(&self.catalog_name);
+        let schema_names = range_names(self.backend.clone(), schema_prefix, start_after, |key| {
+            let schema_key = SchemaKey::parse(key).context(InvalidCatalogValueSnafu)?;
+            Ok(schema_key.schema_name)
+        });
+        paginate_names(schema_names, limit).await
+    }
+
     async fn register_schema(
         &self,
         name: String,
         schema: SchemaProviderRef,
     ) -> Result<Option<SchemaProviderRef>> {
-        let _ = schema; // we don't care about schema provider
+        let table_options = schema.default_table_options().await?;
         let key = self.build_schema_key(&name).to_string();
         self.backend
             .set(
                 key.as_bytes(),
-                &SchemaValue {}
+                &SchemaValue::new(&table_options)
                     .as_bytes()
                     .context(InvalidCatalogValueSnafu)?,
             )
@@ -683,6 +837,17 @@ impl CatalogProvider for RemoteCatalogProvider {
     }
 
     async fn schema(&self, name: &str) -> Result<Option<SchemaProviderRef>> {
+        if name.eq_ignore_ascii_case(INFORMATION_SCHEMA_NAME) {
+            // The information schema is not stored in the backend, it's built on each access
+            // to list the tables currently in the backend.
+            let catalog_provider = Arc::new(self.clone()) as CatalogProviderRef;
+            return Ok(Some(Arc::new(InformationSchemaProvider::new(
+                self.catalog_name.clone(),
+                catalog_provider,
+                self.column_limits.clone(),
+            ))));
+        }
+
         let key = self.build_schema_key(name).to_string();
         Ok(self
             .backend
@@ -690,6 +855,14 @@ impl CatalogProvider for RemoteCatalogProvider {
             .await?
             .map(|_| self.build_schema_provider(name)))
     }
+
+    async fn schema_exist(&self, name: &str) -> Result<bool> {
+        if name.eq_ignore_ascii_case(INFORMATION_SCHEMA_NAME) {
+            return Ok(true);
+        }
+        let key = self.build_schema_key(name).to_string();
+        self.backend.exists(key.as_bytes()).await
+    }
 }
 
 pub struct RemoteSchemaProvider {
@@ -717,6 +890,13 @@ impl RemoteSchemaProvider {
         }
     }
 
+    fn build_schema_key(&self) -> SchemaKey {
+        SchemaKey {
+            catalog_name: self.catalog_name.clone(),
+            schema_name: self.schema_name.clone(),
+        }
+    }
+
     fn build_regional_table_key(&self, table_name: impl AsRef<str>) -> TableRegionalKey {
         TableRegionalKey {
             catalog_name: self.catalog_name.clone(),
@@ -748,6 +928,32 @@ impl SchemaProvider for RemoteSchemaProvider {
         Ok(table_names)
     }
 
+    async fn table_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        let key_prefix = build_table_regional_prefix(&self.catalog_name, &self.schema_name);
+        let table_names = range_names(self.backend.clone(), key_prefix, start_after, |key| {
+            let regional_key = TableRegionalKey::parse(key).context(InvalidCatalogValueSnafu)?;
+            Ok(regional_key.table_name)
+        });
+        paginate_names(table_names, limit).await
+    }
+
+    async fn default_table_options(&self) -> Result<TableOptions> {
+        let key = self.build_schema_key().to_string();
+        let Some(Kv(_, value)) = self.backend.get(key.as_bytes()).await? else {
+            return Ok(TableOptions::default());
+        };
+        SchemaValue::from_bytes(value)
+            .context(InvalidCatalogValueSnafu)?
+            .table_options()
+            .context(InvalidSchemaTableOptionsSnafu {
+                schema: &self.schema_name,
+            })
+    }
+
     async fn table(&self, name: &str) -> Result<Option<TableRef>> {
         let key = self.build_regional_table_key(name).to_string();
         let table_opt = self
@@ -856,9 +1062,8 @@ impl SchemaProvider for RemoteSchemaProvider {
         Ok(table)
     }
 
-    /// Checks if table exists in schema provider based on locally opened table map.
     async fn table_exist(&self, name: &str) -> Result<bool> {
         let key = self.build_regional_table_key(name).to_string();
-        Ok(self.backend.get(key.as_bytes()).await?.is_some())
+        self.backend.exists(key.as_bytes()).await
     }
 }
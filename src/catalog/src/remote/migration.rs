@@ -0,0 +1,218 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migrations of the format of the catalog entries in metasrv.
+//!
+//! Like the system catalog of the local catalog manager, the catalog in metasrv records its
+//! format version under [CATALOG_VERSION_KEY], and the [MIGRATIONS] after the recorded version
+//! run in order when the remote catalog manager starts. The version records the migration
+//! running before its entries are rewritten, and the new version after they are, so a migration
+//! interrupted, e.g. by a crash, runs again on the next start.
+//!
+//! Datanodes may start at the same time, so the version and the entries are updated by
+//! compare-and-set, and a datanode losing the race continues from the version written by the
+//! other. Thus migrations must be idempotent, as they may run more than once.
+
+use common_catalog::consts::MITO_ENGINE;
+use common_telemetry::info;
+use futures_util::StreamExt;
+use snafu::{ensure, ResultExt};
+
+use crate::error::{
+    InvalidCatalogValueSnafu, Result, UnsupportedRemoteCatalogVersionSnafu, ValueDeserializeSnafu,
+    ValueSerializeSnafu,
+};
+use crate::helper::{
+    build_catalog_prefix, TableRegionalKey, TableRegionalValue, CATALOG_VERSION_KEY,
+    TABLE_REGIONAL_KEY_PREFIX,
+};
+use crate::remote::{Kv, KvBackendRef};
+use crate::system::VersionEntry;
+
+/// The format version of the catalog in metasrv this binary supports, which is the version of
+/// the last migration.
+pub const REMOTE_CATALOG_VERSION: u32 = 1;
+
+/// The migrations in the order of their versions.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "write the engine of regional table values explicitly",
+    prefix: TABLE_REGIONAL_KEY_PREFIX,
+    migrate_entry: explicit_regional_engine,
+}];
+
+/// A step migrating the catalog entries in metasrv from the previous format version.
+struct Migration {
+    /// The format version after the migration, which is one more than the version before.
+    version: u32,
+    description: &'static str,
+    /// Prefix of the keys of the entries to migrate.
+    prefix: &'static str,
+    /// Rewrites the value of the entry in place, returns whether the value is changed.
+    migrate_entry: fn(&str, &mut Vec<u8>) -> Result<bool>,
+}
+
+/// Migrates the catalog entries in metasrv to [REMOTE_CATALOG_VERSION], fails if the entries
+/// are of a newer version.
+pub async fn migrate_remote_catalog(backend: &KvBackendRef) -> Result<()> {
+    let supported = MIGRATIONS.last().map_or(0, |migration| migration.version);
+    let key = CATALOG_VERSION_KEY.as_bytes();
+    let mut raw_version = backend.get(key).await?.map(|Kv(_, value)| value);
+
+    loop {
+        let current = match &raw_version {
+            Some(value) => {
+                serde_json::from_slice::<VersionEntry>(value).context(ValueDeserializeSnafu)?
+            }
+            // A new catalog is created in the supported version.
+            None if is_new_catalog(backend).await? => {
+                match set_version(backend, None, version_entry(supported, None)).await? {
+                    Ok(()) => return Ok(()),
+                    Err(value) => {
+                        raw_version = value;
+                        continue;
+                    }
+                }
+            }
+            // The catalog is created before its format is versioned.
+            None => version_entry(0, None),
+        };
+        ensure!(
+            current.version <= supported,
+            UnsupportedRemoteCatalogVersionSnafu {
+                version: current.version,
+                supported,
+            }
+        );
+        let Some(migration) = MIGRATIONS
+            .iter()
+            .find(|migration| migration.version == current.version + 1) else { return Ok(()) };
+
+        let expect = if current.migrating_to == Some(migration.version) {
+            info!(
+                "Resuming the interrupted migration of catalog in metasrv to version {}",
+                migration.version
+            );
+            raw_version.clone().unwrap_or_default()
+        } else {
+            let migrating = version_entry(current.version, Some(migration.version));
+            if let Err(value) = set_version(backend, raw_version.as_deref(), migrating).await? {
+                raw_version = value;
+                continue;
+            }
+            serde_json::to_vec(&migrating).context(ValueSerializeSnafu)?
+        };
+
+        info!(
+            "Migrating catalog in metasrv to version {}: {}",
+            migration.version, migration.description
+        );
+        let migrated = migrate_entries(backend, migration).await?;
+
+        let done = version_entry(migration.version, None);
+        match set_version(backend, Some(&expect), done).await? {
+            Ok(()) => {
+                info!(
+                    "Migrated {} entries of catalog in metasrv to version {}",
+                    migrated, migration.version
+                );
+                raw_version = Some(serde_json::to_vec(&done).context(ValueSerializeSnafu)?);
+            }
+            // Another datanode has completed the migration.
+            Err(value) => raw_version = value,
+        }
+    }
+}
+
+/// Returns whether the catalog in metasrv has no catalog yet.
+async fn is_new_catalog(backend: &KvBackendRef) -> Result<bool> {
+    let (kvs, _) = backend
+        .range_page(build_catalog_prefix().as_bytes(), &[], 1)
+        .await?;
+    Ok(kvs.is_empty())
+}
+
+/// Sets the version if its current value is `expect`, `None` if it's not set. Returns the
+/// current value otherwise.
+async fn set_version(
+    backend: &KvBackendRef,
+    expect: Option<&[u8]>,
+    version: VersionEntry,
+) -> Result<std::result::Result<(), Option<Vec<u8>>>> {
+    let value = serde_json::to_vec(&version).context(ValueSerializeSnafu)?;
+    backend
+        .compare_and_set(
+            CATALOG_VERSION_KEY.as_bytes(),
+            expect.unwrap_or_default(),
+            &value,
+        )
+        .await
+}
+
+/// Migrates the entries of `migration`, returns the number of entries rewritten.
+async fn migrate_entries(backend: &KvBackendRef, migration: &Migration) -> Result<usize> {
+    let mut kvs = Vec::new();
+    let mut iter = backend.range(migration.prefix.as_bytes());
+    while let Some(kv) = iter.next().await {
+        kvs.push(kv?);
+    }
+
+    let mut migrated = 0;
+    for Kv(key, value) in kvs {
+        let key_str = String::from_utf8_lossy(&key);
+        let mut expect = value;
+        loop {
+            let mut value = expect.clone();
+            if !(migration.migrate_entry)(&key_str, &mut value)? {
+                break;
+            }
+            match backend.compare_and_set(&key, &expect, &value).await? {
+                Ok(()) => {
+                    migrated += 1;
+                    break;
+                }
+                // The entry is updated concurrently, migrates the new value.
+                Err(Some(current)) => expect = current,
+                // The entry is deleted concurrently.
+                Err(None) => break,
+            }
+        }
+    }
+    Ok(migrated)
+}
+
+fn version_entry(version: u32, migrating_to: Option<u32>) -> VersionEntry {
+    VersionEntry {
+        version,
+        migrating_to,
+    }
+}
+
+/// Writes the engine of the regional table values written before the engine was recorded,
+/// which are read as mito tables.
+fn explicit_regional_engine(key: &str, value: &mut Vec<u8>) -> Result<bool> {
+    if TableRegionalKey::parse(key).is_err() {
+        return Ok(false);
+    }
+    let mut regional_value =
+        TableRegionalValue::from_bytes(&value).context(InvalidCatalogValueSnafu)?;
+    if regional_value.engine_name.is_some() {
+        return Ok(false);
+    }
+    regional_value.engine_name = Some(MITO_ENGINE.to_string());
+    *value = regional_value
+        .as_bytes()
+        .context(InvalidCatalogValueSnafu)?;
+    Ok(true)
+}
@@ -0,0 +1,259 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Progress of opening the tables, which replays the WALs of their regions, when the catalog
+//! manager starts.
+//!
+//! The node serves requests while the tables are replaying. A table is queryable as soon as
+//! it's opened, while a request to a table still replaying fails with the retryable
+//! [TableReplaying](crate::error::Error::TableReplaying), instead of seeing the table as missing,
+//! e.g. to create it again.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common_catalog::format_full_table_name;
+use common_telemetry::{error, info};
+use futures::{Stream, StreamExt};
+use metrics::gauge;
+use snafu::ensure;
+
+use crate::error::{Result, TableReplayingSnafu};
+use crate::metrics::{
+    METRIC_REPLAY_TABLES_FAILED, METRIC_REPLAY_TABLES_REPLAYED, METRIC_REPLAY_TABLES_TOTAL,
+};
+
+/// Default number of tables opened concurrently when the catalog manager starts.
+pub const DEFAULT_REPLAY_CONCURRENCY: usize = 8;
+
+/// Interval to log the progress of replaying.
+const REPLAY_LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// State of the tables opened when the catalog manager starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayState {
+    /// Tables are still replaying, the tables already replayed are queryable.
+    Replaying,
+    /// All tables are replayed, or failed to replay.
+    Ready,
+}
+
+/// Progress of replaying the tables, shared with the components reporting the readiness of
+/// the node.
+#[derive(Debug)]
+pub struct ReplayProgress {
+    total: AtomicUsize,
+    replayed: AtomicUsize,
+    failed: AtomicUsize,
+    /// Full names of the tables to replay not opened yet.
+    pending: Mutex<HashSet<String>>,
+    /// Whether all the tables to replay are listed, before which any table may be pending.
+    listed: AtomicBool,
+    finished: AtomicBool,
+    start: Instant,
+}
+
+pub type ReplayProgressRef = Arc<ReplayProgress>;
+
+/// A snapshot of [ReplayProgress].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayProgressSnapshot {
+    pub state: ReplayState,
+    pub total: usize,
+    pub replayed: usize,
+    pub failed: usize,
+}
+
+impl Default for ReplayProgress {
+    fn default() -> Self {
+        Self {
+            total: AtomicUsize::new(0),
+            replayed: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            pending: Mutex::new(HashSet::new()),
+            listed: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl ReplayProgress {
+    pub fn state(&self) -> ReplayState {
+        if self.finished.load(Ordering::Acquire) {
+            ReplayState::Ready
+        } else {
+            ReplayState::Replaying
+        }
+    }
+
+    pub fn snapshot(&self) -> ReplayProgressSnapshot {
+        ReplayProgressSnapshot {
+            state: self.state(),
+            total: self.total.load(Ordering::Relaxed),
+            replayed: self.replayed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns whether the table may be still replaying.
+    pub fn is_pending(&self, catalog: &str, schema: &str, table: &str) -> bool {
+        if self.finished.load(Ordering::Acquire) {
+            return false;
+        }
+        if !self.listed.load(Ordering::Acquire) {
+            return true;
+        }
+        let full_name = format_full_table_name(catalog, schema, table);
+        self.pending.lock().unwrap().contains(&full_name)
+    }
+
+    /// Fails with the retryable [TableReplaying](crate::error::Error::TableReplaying) if the
+    /// table may be still replaying, so a request not finding it doesn't see it as missing.
+    pub fn ensure_not_pending(&self, catalog: &str, schema: &str, table: &str) -> Result<()> {
+        ensure!(
+            !self.is_pending(catalog, schema, table),
+            TableReplayingSnafu {
+                table_name: format_full_table_name(catalog, schema, table),
+            }
+        );
+        Ok(())
+    }
+
+    /// Adds the full names of the tables to replay. Must be called before the tables are
+    /// opened.
+    pub(crate) fn add_pending(&self, tables: impl IntoIterator<Item = String>) {
+        let mut pending = self.pending.lock().unwrap();
+        let n = pending.len();
+        pending.extend(tables);
+        let added = pending.len() - n;
+        drop(pending);
+
+        let total = self.total.fetch_add(added, Ordering::Relaxed) + added;
+        gauge!(METRIC_REPLAY_TABLES_TOTAL, total as f64);
+    }
+
+    /// Marks all the tables to replay as added by [ReplayProgress::add_pending], so the tables
+    /// not added are not pending.
+    pub(crate) fn finish_listing(&self) {
+        self.listed.store(true, Ordering::Release);
+    }
+
+    fn on_opened(&self, table: &str) {
+        let _ = self.pending.lock().unwrap().remove(table);
+    }
+
+    fn on_replayed(&self) {
+        let replayed = self.replayed.fetch_add(1, Ordering::Relaxed) + 1;
+        gauge!(METRIC_REPLAY_TABLES_REPLAYED, replayed as f64);
+    }
+
+    fn on_failed(&self) {
+        let failed = self.failed.fetch_add(1, Ordering::Relaxed) + 1;
+        gauge!(METRIC_REPLAY_TABLES_FAILED, failed as f64);
+    }
+
+    pub(crate) fn finish(&self) {
+        self.finish_listing();
+        self.finished.store(true, Ordering::Release);
+        self.pending.lock().unwrap().clear();
+        self.log();
+    }
+
+    fn log(&self) {
+        let ReplayProgressSnapshot {
+            state,
+            total,
+            replayed,
+            failed,
+        } = self.snapshot();
+        let elapsed = self.start.elapsed();
+        info!(
+            "Replay progress: {:?}, replayed {}/{} tables, failed: {}, elapsed: {:?}, {:.2} tables/s",
+            state,
+            replayed,
+            total,
+            failed,
+            elapsed,
+            (replayed + failed) as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        );
+    }
+}
+
+/// Waits for the tables in `opening` to be opened, logging the progress periodically. Each item
+/// is the full name of a table and the result of opening it, a failed table is left closed.
+pub(crate) async fn wait_replay<S>(progress: &ReplayProgress, opening: S)
+where
+    S: Stream<Item = (String, Result<()>)>,
+{
+    futures::pin_mut!(opening);
+    let mut ticker = tokio::time::interval(REPLAY_LOG_INTERVAL);
+    // The first tick completes immediately.
+    let _ = ticker.tick().await;
+    loop {
+        tokio::select! {
+            opened = opening.next() => {
+                let Some((table, result)) = opened else { break };
+                progress.on_opened(&table);
+                match result {
+                    Ok(()) => {
+                        progress.on_replayed();
+                        info!("Replayed table: {}", table);
+                    }
+                    Err(e) => {
+                        progress.on_failed();
+                        error!(e; "Failed to replay table: {}, leave it closed", table);
+                    }
+                }
+            }
+            _ = ticker.tick() => progress.log(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_error::prelude::{ErrorExt, StatusCode};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pending_tables() {
+        let progress = ReplayProgress::default();
+        // Any table may be pending before the tables are listed.
+        assert!(progress.is_pending("c", "s", "t0"));
+        progress.add_pending(["c.s.t0".to_string(), "c.s.t1".to_string()]);
+        assert!(progress.is_pending("c", "s", "t2"));
+        progress.finish_listing();
+        assert!(!progress.is_pending("c", "s", "t2"));
+        assert!(progress.ensure_not_pending("c", "s", "t2").is_ok());
+
+        let err = progress.ensure_not_pending("c", "s", "t0").unwrap_err();
+        assert_eq!(StatusCode::StorageUnavailable, err.status_code());
+        assert!(err.status_code().is_retryable());
+
+        let opening = futures::stream::iter([("c.s.t0".to_string(), Ok(()))]);
+        wait_replay(&progress, opening).await;
+        assert!(!progress.is_pending("c", "s", "t0"));
+        assert!(progress.is_pending("c", "s", "t1"));
+        assert_eq!(2, progress.snapshot().total);
+        assert_eq!(1, progress.snapshot().replayed);
+
+        progress.finish();
+        assert!(!progress.is_pending("c", "s", "t1"));
+        assert_eq!(ReplayState::Ready, progress.state());
+    }
+}
@@ -15,10 +15,14 @@
 use std::any::Any;
 use std::sync::Arc;
 
+use async_stream::try_stream;
 use async_trait::async_trait;
+use futures::Stream;
+use table::requests::TableOptions;
 use table::TableRef;
 
 use crate::error::{NotSupportedSnafu, Result};
+use crate::{paginate, NAMES_PAGE_SIZE};
 
 /// Represents a schema, comprising a number of named tables.
 #[async_trait]
@@ -30,6 +34,21 @@ pub trait SchemaProvider: Sync + Send {
     /// Retrieves the list of available table names in this schema.
     async fn table_names(&self) -> Result<Vec<String>>;
 
+    /// Retrieves at most `limit` table names in this schema in ascending order, after the name
+    /// `start_after` if it's given, and whether there are more names after them.
+    ///
+    /// The default implementation sorts all the names from [SchemaProvider::table_names], so
+    /// providers of large schemas should override it.
+    async fn table_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, bool)> {
+        let mut table_names = self.table_names().await?;
+        table_names.sort_unstable();
+        Ok(paginate(table_names, start_after, limit))
+    }
+
     /// Retrieves a specific table from the schema by name, provided it exists.
     async fn table(&self, name: &str) -> Result<Option<TableRef>>;
 
@@ -60,10 +79,51 @@ pub trait SchemaProvider: Sync + Send {
         .fail()
     }
 
-    /// If supported by the implementation, checks the table exist in the schema provider or not.
-    /// If no matched table in the schema provider, return false.
-    /// Otherwise, return true.
-    async fn table_exist(&self, name: &str) -> Result<bool>;
+    /// Returns whether the table exists in the schema, for the callers that don't need the
+    /// table itself.
+    ///
+    /// The default implementation retrieves the table by [SchemaProvider::table].
+    async fn table_exist(&self, name: &str) -> Result<bool> {
+        Ok(self.table(name).await?.is_some())
+    }
+
+    /// Retrieves the tables of `names` in the order of the names, `None` for the ones that
+    /// don't exist, for the callers looking up many tables at once.
+    ///
+    /// The default implementation retrieves the tables one by one by [SchemaProvider::table].
+    async fn tables(&self, names: &[String]) -> Result<Vec<Option<TableRef>>> {
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            tables.push(self.table(name).await?);
+        }
+        Ok(tables)
+    }
+
+    /// Returns the default options of the tables in the schema, which the tables created in it
+    /// inherit unless they set the options themselves.
+    async fn default_table_options(&self) -> Result<TableOptions> {
+        Ok(TableOptions::default())
+    }
 }
 
 pub type SchemaProviderRef = Arc<dyn SchemaProvider>;
+
+/// Streams the table names of `schema` in ascending order, listing [NAMES_PAGE_SIZE] names
+/// at a time.
+pub fn table_names_stream(schema: SchemaProviderRef) -> impl Stream<Item = Result<String>> {
+    try_stream!({
+        let mut start_after = None;
+        loop {
+            let (table_names, more) = schema
+                .table_names_paginated(start_after.as_deref(), NAMES_PAGE_SIZE)
+                .await?;
+            start_after = table_names.last().cloned();
+            for table_name in table_names {
+                yield table_name;
+            }
+            if !more || start_after.is_none() {
+                break;
+            }
+        }
+    })
+}
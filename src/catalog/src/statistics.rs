@@ -0,0 +1,372 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Column statistics of tables, computed on demand by [analyze_table] and exposed by
+//! `information_schema.column_statistics`.
+//!
+//! The statistics are persisted to the table `greptime_private.column_statistics` of the catalog
+//! of the analyzed tables, so they are kept after restarts and visible to all the frontends.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, RwLock};
+
+use common_catalog::consts::MITO_ENGINE;
+use common_catalog::format_full_table_name;
+use common_query::physical_plan::SessionContext;
+use common_time::util::current_time_millis;
+use datatypes::prelude::{ScalarVector, VectorRef};
+use datatypes::value::Value;
+use datatypes::vectors::{StringVector, TimestampMillisecondVector};
+use futures::StreamExt;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, ResultExt};
+use table::metadata::{TableId, TableInfo};
+use table::requests::InsertRequest;
+use table::TableRef;
+
+use crate::error::{
+    AnalyzeTableSnafu, NotSupportedSnafu, ReadTableScanSnafu, Result, SaveStatisticsSnafu,
+    ScanTableSnafu, ValueDeserializeSnafu, ValueSerializeSnafu,
+};
+use crate::CatalogProviderRef;
+
+/// Max number of rows sampled to compute the statistics of a table.
+pub const ANALYZE_SAMPLE_ROWS: usize = 100_000;
+
+pub const STATISTICS_SCHEMA_NAME: &str = "greptime_private";
+pub const STATISTICS_TABLE_NAME: &str = "column_statistics";
+
+/// Statements creating the table the statistics are persisted to, one row per analyze of a
+/// table keyed by the full name of the table, the statistics are kept as JSON.
+pub const CREATE_STATISTICS_TABLE_SQLS: [&str; 2] = [
+    "CREATE DATABASE IF NOT EXISTS greptime_private",
+    "CREATE TABLE IF NOT EXISTS greptime_private.column_statistics (
+        table_name STRING,
+        statistics STRING,
+        computed_at TIMESTAMP(3) TIME INDEX,
+        PRIMARY KEY (table_name)
+    )",
+];
+
+lazy_static! {
+    static ref STATISTICS: StatisticsStore = StatisticsStore::default();
+}
+
+/// Returns the process wide store of the computed statistics.
+pub fn statistics_store() -> &'static StatisticsStore {
+    &STATISTICS
+}
+
+/// Statistics of a column, computed from a sample of at most [ANALYZE_SAMPLE_ROWS] rows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnStatistics {
+    pub column_name: String,
+    /// Min non-null value, `None` if all sampled values are null.
+    pub min_value: Option<String>,
+    /// Max non-null value, `None` if all sampled values are null.
+    pub max_value: Option<String>,
+    /// Number of distinct non-null values in the sample.
+    pub approx_distinct: u64,
+    /// Fraction of null values in the sample, 0 for an empty table.
+    pub null_fraction: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableStatistics {
+    pub table_id: TableId,
+    pub columns: Vec<ColumnStatistics>,
+    /// Number of rows the statistics are computed from.
+    pub sampled_rows: usize,
+    /// When the statistics are computed, in milliseconds since the epoch.
+    pub computed_at: i64,
+}
+
+impl TableStatistics {
+    pub fn column(&self, column_name: &str) -> Option<&ColumnStatistics> {
+        self.columns.iter().find(|c| c.column_name == column_name)
+    }
+}
+
+pub type TableStatisticsRef = Arc<TableStatistics>;
+
+/// Statistics of the analyzed tables by full table name, so reading them never scans the tables.
+#[derive(Debug, Default)]
+pub struct StatisticsStore {
+    tables: RwLock<HashMap<String, TableStatisticsRef>>,
+}
+
+impl StatisticsStore {
+    /// Returns the statistics of the table, ignoring the ones of a dropped table of the same name.
+    pub fn get(&self, table_info: &TableInfo) -> Option<TableStatisticsRef> {
+        self.tables
+            .read()
+            .unwrap()
+            .get(&full_table_name(table_info))
+            .filter(|statistics| statistics.table_id == table_info.ident.table_id)
+            .cloned()
+    }
+
+    /// Saves the statistics of the table, unless the ones already saved are computed later.
+    pub fn insert(&self, table_info: &TableInfo, statistics: TableStatisticsRef) {
+        self.insert_by_name(full_table_name(table_info), statistics);
+    }
+
+    /// Forgets the statistics of the table, they are still read from the persisted ones.
+    pub fn remove(&self, table_info: &TableInfo) {
+        let _ = self
+            .tables
+            .write()
+            .unwrap()
+            .remove(&full_table_name(table_info));
+    }
+
+    fn insert_by_name(&self, table_name: String, statistics: TableStatisticsRef) {
+        match self.tables.write().unwrap().entry(table_name) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().computed_at <= statistics.computed_at {
+                    let _ = entry.insert(statistics);
+                }
+            }
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(statistics);
+            }
+        }
+    }
+}
+
+fn full_table_name(table_info: &TableInfo) -> String {
+    format_full_table_name(
+        &table_info.catalog_name,
+        &table_info.schema_name,
+        &table_info.name,
+    )
+}
+
+/// Computes the statistics of the table from a sample of its rows and saves them to the
+/// [statistics_store].
+pub async fn analyze_table(table: &TableRef) -> Result<TableStatisticsRef> {
+    let table_info = table.table_info();
+    let table_name = table_info.name.clone();
+    ensure!(
+        table_info.meta.engine == MITO_ENGINE,
+        NotSupportedSnafu {
+            op: format!(
+                "analyze table {table_name} of engine {}",
+                table_info.meta.engine
+            ),
+        }
+    );
+
+    let schema = table.schema();
+    let mut columns = schema
+        .column_schemas()
+        .iter()
+        .map(|column_schema| ColumnAccumulator::new(&column_schema.name))
+        .collect::<Vec<_>>();
+
+    let plan = table
+        .scan(None, &[], Some(ANALYZE_SAMPLE_ROWS))
+        .await
+        .context(ScanTableSnafu { table: &table_name })?;
+    let task_ctx = SessionContext::default().task_ctx();
+    let mut sampled_rows = 0;
+    'partitions: for partition in 0..plan.output_partitioning().partition_count() {
+        let mut stream = plan
+            .execute(partition, task_ctx.clone())
+            .context(AnalyzeTableSnafu { table: &table_name })?;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.context(ReadTableScanSnafu { table: &table_name })?;
+            let num_rows = batch.num_rows().min(ANALYZE_SAMPLE_ROWS - sampled_rows);
+            for (accumulator, vector) in columns.iter_mut().zip(batch.columns()) {
+                for i in 0..num_rows {
+                    accumulator.update(vector.get(i));
+                }
+            }
+            sampled_rows += num_rows;
+            if sampled_rows >= ANALYZE_SAMPLE_ROWS {
+                break 'partitions;
+            }
+        }
+    }
+
+    let statistics = Arc::new(TableStatistics {
+        table_id: table_info.ident.table_id,
+        columns: columns
+            .into_iter()
+            .map(|accumulator| accumulator.finish(sampled_rows))
+            .collect(),
+        sampled_rows,
+        computed_at: current_time_millis(),
+    });
+    statistics_store().insert(&table_info, statistics.clone());
+    Ok(statistics)
+}
+
+/// Persists the statistics of the table to `statistics_table`, the table created by
+/// [CREATE_STATISTICS_TABLE_SQLS] in the catalog of the table.
+pub async fn persist_statistics(
+    statistics_table: &TableRef,
+    table_info: &TableInfo,
+    statistics: &TableStatistics,
+) -> Result<()> {
+    let json = serde_json::to_string(statistics).context(ValueSerializeSnafu)?;
+    let columns_values: HashMap<String, VectorRef> = HashMap::from([
+        (
+            "table_name".to_string(),
+            Arc::new(StringVector::from(vec![full_table_name(table_info)])) as _,
+        ),
+        (
+            "statistics".to_string(),
+            Arc::new(StringVector::from(vec![json])) as _,
+        ),
+        (
+            "computed_at".to_string(),
+            Arc::new(TimestampMillisecondVector::from_slice([
+                statistics.computed_at
+            ])) as _,
+        ),
+    ]);
+
+    let statistics_table_info = statistics_table.table_info();
+    let _ = statistics_table
+        .insert(InsertRequest {
+            catalog_name: statistics_table_info.catalog_name.clone(),
+            schema_name: statistics_table_info.schema_name.clone(),
+            table_name: statistics_table_info.name.clone(),
+            columns_values,
+            region_number: 0,
+            write_mode: Default::default(),
+        })
+        .await
+        .context(SaveStatisticsSnafu {
+            table: &table_info.name,
+        })?;
+    Ok(())
+}
+
+/// Loads the statistics persisted in the catalog to the [statistics_store], e.g. the ones
+/// computed before a restart or by other frontends. The table of the persisted statistics is
+/// small, one row per analyze, so it's read as a whole.
+pub async fn load_statistics(catalog_provider: &CatalogProviderRef) -> Result<()> {
+    let Some(schema) = catalog_provider.schema(STATISTICS_SCHEMA_NAME).await? else {
+        return Ok(());
+    };
+    let Some(table) = schema.table(STATISTICS_TABLE_NAME).await? else { return Ok(()) };
+    let table_info = table.table_info();
+    let table_name = &table_info.name;
+    let table_schema = table.schema();
+    let (Some(name_index), Some(statistics_index)) = (
+        table_schema.column_index_by_name("table_name"),
+        table_schema.column_index_by_name("statistics"),
+    ) else {
+        return Ok(());
+    };
+
+    let plan = table
+        .scan(None, &[], None)
+        .await
+        .context(ScanTableSnafu { table: table_name })?;
+    let task_ctx = SessionContext::default().task_ctx();
+    for partition in 0..plan.output_partitioning().partition_count() {
+        let mut stream = plan
+            .execute(partition, task_ctx.clone())
+            .context(AnalyzeTableSnafu { table: table_name })?;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.context(ReadTableScanSnafu { table: table_name })?;
+            let names = batch
+                .column(name_index)
+                .as_any()
+                .downcast_ref::<StringVector>();
+            let jsons = batch
+                .column(statistics_index)
+                .as_any()
+                .downcast_ref::<StringVector>();
+            let (Some(names), Some(jsons)) = (names, jsons) else { continue };
+            for (name, json) in names.iter_data().zip(jsons.iter_data()) {
+                let (Some(name), Some(json)) = (name, json) else { continue };
+                let statistics: TableStatistics =
+                    serde_json::from_str(json).context(ValueDeserializeSnafu)?;
+                statistics_store().insert_by_name(name.to_string(), Arc::new(statistics));
+            }
+        }
+    }
+    Ok(())
+}
+
+struct ColumnAccumulator {
+    column_name: String,
+    nulls: usize,
+    distinct: BTreeSet<Value>,
+}
+
+impl ColumnAccumulator {
+    fn new(column_name: &str) -> Self {
+        Self {
+            column_name: column_name.to_string(),
+            nulls: 0,
+            distinct: BTreeSet::new(),
+        }
+    }
+
+    fn update(&mut self, value: Value) {
+        if value.is_null() {
+            self.nulls += 1;
+        } else {
+            let _ = self.distinct.insert(value);
+        }
+    }
+
+    fn finish(self, sampled_rows: usize) -> ColumnStatistics {
+        let null_fraction = if sampled_rows == 0 {
+            0.0
+        } else {
+            self.nulls as f64 / sampled_rows as f64
+        };
+        ColumnStatistics {
+            column_name: self.column_name,
+            min_value: self.distinct.first().map(|v| v.to_string()),
+            max_value: self.distinct.last().map(|v| v.to_string()),
+            approx_distinct: self.distinct.len() as u64,
+            null_fraction,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_accumulator() {
+        let mut accumulator = ColumnAccumulator::new("host");
+        for value in ["b", "a", "c", "a"] {
+            accumulator.update(Value::from(value));
+        }
+        accumulator.update(Value::Null);
+
+        let statistics = accumulator.finish(5);
+        assert_eq!("host", statistics.column_name);
+        assert_eq!(Some("a"), statistics.min_value.as_deref());
+        assert_eq!(Some("c"), statistics.max_value.as_deref());
+        assert_eq!(3, statistics.approx_distinct);
+        assert_eq!(0.2, statistics.null_fraction);
+
+        let statistics = ColumnAccumulator::new("cpu").finish(0);
+        assert_eq!(None, statistics.min_value);
+        assert_eq!(0, statistics.approx_distinct);
+        assert_eq!(0.0, statistics.null_fraction);
+    }
+}
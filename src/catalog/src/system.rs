@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use common_catalog::consts::{
@@ -22,7 +22,7 @@ use common_catalog::consts::{
 };
 use common_query::logical_plan::Expr;
 use common_query::physical_plan::{PhysicalPlanRef, SessionContext};
-use common_recordbatch::SendableRecordBatchStream;
+use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
 use common_telemetry::debug;
 use common_time::util;
 use datatypes::prelude::{ConcreteDataType, ScalarVector, VectorRef};
@@ -39,7 +39,8 @@ use table::{Table, TableRef};
 
 use crate::error::{
     self, CreateSystemCatalogSnafu, EmptyValueSnafu, Error, InvalidEntryTypeSnafu, InvalidKeySnafu,
-    OpenSystemCatalogSnafu, Result, ValueDeserializeSnafu,
+    OpenSystemCatalogSnafu, Result, SystemCatalogSnafu, SystemCatalogTypeMismatchSnafu,
+    ValueDeserializeSnafu,
 };
 use crate::DeregisterTableRequest;
 
@@ -47,6 +48,9 @@ pub const ENTRY_TYPE_INDEX: usize = 0;
 pub const KEY_INDEX: usize = 1;
 pub const VALUE_INDEX: usize = 3;
 
+/// Key of the entry recording the format version of the system catalog.
+pub const VERSION_ENTRY_KEY: &str = "format_version";
+
 pub struct SystemCatalogTable(TableRef);
 
 #[async_trait::async_trait]
@@ -59,6 +63,10 @@ impl Table for SystemCatalogTable {
         self.0.schema()
     }
 
+    fn scan_schema(&self) -> SchemaRef {
+        self.0.scan_schema()
+    }
+
     async fn scan(
         &self,
         projection: Option<&Vec<usize>>,
@@ -235,14 +243,27 @@ fn build_primary_key_columns(entry_type: EntryType, key: &[u8]) -> HashMap<Strin
     m
 }
 
-pub fn build_schema_insert_request(catalog_name: String, schema_name: String) -> InsertRequest {
+pub fn build_schema_insert_request(
+    catalog_name: String,
+    schema_name: String,
+    table_options: &TableOptions,
+) -> InsertRequest {
     let full_schema_name = format!("{catalog_name}.{schema_name}");
+    let value = SchemaEntryValue {
+        table_options: table_options.option_values().into_iter().collect(),
+    };
     build_insert_request(
         EntryType::Schema,
         full_schema_name.as_bytes(),
-        serde_json::to_string(&SchemaEntryValue {})
-            .unwrap()
-            .as_bytes(),
+        serde_json::to_string(&value).unwrap().as_bytes(),
+    )
+}
+
+pub fn build_version_insert_request(entry: &VersionEntry) -> InsertRequest {
+    build_insert_request(
+        EntryType::Version,
+        VERSION_ENTRY_KEY.as_bytes(),
+        serde_json::to_string(entry).unwrap().as_bytes(),
     )
 }
 
@@ -274,6 +295,7 @@ pub fn build_insert_request(entry_type: EntryType, key: &[u8], value: &[u8]) ->
         table_name: SYSTEM_CATALOG_TABLE_NAME.to_string(),
         columns_values,
         region_number: 0, // system catalog table has only one region
+        write_mode: Default::default(),
     }
 }
 
@@ -298,7 +320,8 @@ pub fn decode_system_catalog(
         }
         EntryType::Schema => {
             // As for schema entry, the key is a string with format: `<catalog_name>.<schema_name>`
-            // and the value is current not used.
+            // and the value is a JSON string with format: `{"table_options": {<key>: <value>}}`,
+            // or `null` for the schemas created before they have options.
             let schema_parts = key.split('.').collect::<Vec<_>>();
             ensure!(
                 schema_parts.len() == 2,
@@ -306,9 +329,16 @@ pub fn decode_system_catalog(
                     key: Some(key.to_string())
                 }
             );
+            let schema_value = match value {
+                Some(value) => serde_json::from_slice::<Option<SchemaEntryValue>>(value)
+                    .context(ValueDeserializeSnafu)?
+                    .unwrap_or_default(),
+                None => SchemaEntryValue::default(),
+            };
             Ok(Entry::Schema(SchemaEntry {
                 catalog_name: schema_parts[0].to_string(),
                 schema_name: schema_parts[1].to_string(),
+                table_options: schema_value.table_options,
             }))
         }
 
@@ -335,14 +365,61 @@ pub fn decode_system_catalog(
                 engine: table_meta.engine,
             }))
         }
+
+        EntryType::Version => {
+            // As for version entry, the key is always `VERSION_ENTRY_KEY` and the value is
+            // a JSON string with format: `{"version": <version>, "migrating_to": <version>}`
+            let value = value.context(EmptyValueSnafu)?;
+            let version = serde_json::from_slice(value).context(ValueDeserializeSnafu)?;
+            Ok(Entry::Version(version))
+        }
     }
 }
 
+/// Returns the entry type, key and value columns of a record batch of the system catalog table.
+pub(crate) fn entry_columns(
+    rb: &RecordBatch,
+) -> Result<(&UInt8Vector, &BinaryVector, &BinaryVector)> {
+    ensure!(
+        rb.num_columns() >= 6,
+        SystemCatalogSnafu {
+            msg: format!("Length mismatch: {}", rb.num_columns())
+        }
+    );
+
+    let entry_type = rb
+        .column(ENTRY_TYPE_INDEX)
+        .as_any()
+        .downcast_ref::<UInt8Vector>()
+        .with_context(|| SystemCatalogTypeMismatchSnafu {
+            data_type: rb.column(ENTRY_TYPE_INDEX).data_type(),
+        })?;
+
+    let key = rb
+        .column(KEY_INDEX)
+        .as_any()
+        .downcast_ref::<BinaryVector>()
+        .with_context(|| SystemCatalogTypeMismatchSnafu {
+            data_type: rb.column(KEY_INDEX).data_type(),
+        })?;
+
+    let value = rb
+        .column(VALUE_INDEX)
+        .as_any()
+        .downcast_ref::<BinaryVector>()
+        .with_context(|| SystemCatalogTypeMismatchSnafu {
+            data_type: rb.column(VALUE_INDEX).data_type(),
+        })?;
+
+    Ok((entry_type, key, value))
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EntryType {
     Catalog = 1,
     Schema = 2,
     Table = 3,
+    Version = 4,
 }
 
 impl TryFrom<u8> for EntryType {
@@ -353,6 +430,7 @@ impl TryFrom<u8> for EntryType {
             b if b == Self::Catalog as u8 => Ok(Self::Catalog),
             b if b == Self::Schema as u8 => Ok(Self::Schema),
             b if b == Self::Table as u8 => Ok(Self::Table),
+            b if b == Self::Version as u8 => Ok(Self::Version),
             b => InvalidEntryTypeSnafu {
                 entry_type: Some(b),
             }
@@ -366,6 +444,7 @@ pub enum Entry {
     Catalog(CatalogEntry),
     Schema(SchemaEntry),
     Table(TableEntry),
+    Version(VersionEntry),
 }
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd)]
@@ -377,10 +456,15 @@ pub struct CatalogEntry {
 pub struct SchemaEntry {
     pub catalog_name: String,
     pub schema_name: String,
+    /// Default options of the tables in the schema, in the canonical format.
+    pub table_options: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct SchemaEntryValue;
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SchemaEntryValue {
+    #[serde(default)]
+    pub table_options: BTreeMap<String, String>,
+}
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd)]
 pub struct TableEntry {
@@ -399,12 +483,24 @@ pub struct TableEntryValue {
     pub engine: String,
 }
 
+/// The format version of the system catalog.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Ord, PartialOrd)]
+pub struct VersionEntry {
+    /// Version of the entries, all the migrations up to it are done.
+    pub version: u32,
+    /// Version of the migration running, if the entries are being migrated.
+    #[serde(default)]
+    pub migrating_to: Option<u32>,
+}
+
 fn mito_engine() -> String {
     MITO_ENGINE.to_string()
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use std::assert_matches::assert_matches;
+
     use common_recordbatch::RecordBatches;
     use common_test_util::temp_dir::{create_temp_dir, TempDir};
     use datatypes::value::Value;
@@ -447,9 +543,27 @@ mod tests {
         if let Entry::Schema(e) = entry {
             assert_eq!("some_catalog", e.catalog_name);
             assert_eq!("some_schema", e.schema_name);
+            assert!(e.table_options.is_empty());
         } else {
             panic!("Unexpected type: {entry:?}");
         }
+
+        // Schemas created before they have options.
+        let entry = decode_system_catalog(
+            Some(EntryType::Schema as u8),
+            Some("some_catalog.some_schema".as_bytes()),
+            Some("null".as_bytes()),
+        )
+        .unwrap();
+        assert_matches!(entry, Entry::Schema(e) if e.table_options.is_empty());
+
+        let entry = decode_system_catalog(
+            Some(EntryType::Schema as u8),
+            Some("some_catalog.some_schema".as_bytes()),
+            Some(r#"{"table_options":{"ttl":"7days"}}"#.as_bytes()),
+        )
+        .unwrap();
+        assert_matches!(entry, Entry::Schema(e) if e.table_options["ttl"] == "7days");
     }
 
     #[test]
@@ -471,6 +585,23 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_decode_version_entry() {
+        let entry = decode_system_catalog(
+            Some(EntryType::Version as u8),
+            Some(VERSION_ENTRY_KEY.as_bytes()),
+            Some("{\"version\":1}".as_bytes()),
+        )
+        .unwrap();
+        assert_eq!(
+            Entry::Version(VersionEntry {
+                version: 1,
+                migrating_to: None,
+            }),
+            entry
+        );
+    }
+
     #[test]
     #[should_panic]
     pub fn test_decode_mismatch() {
@@ -487,7 +618,8 @@ mod tests {
         assert_eq!(EntryType::Catalog, EntryType::try_from(1).unwrap());
         assert_eq!(EntryType::Schema, EntryType::try_from(2).unwrap());
         assert_eq!(EntryType::Table, EntryType::try_from(3).unwrap());
-        assert!(EntryType::try_from(4).is_err());
+        assert_eq!(EntryType::Version, EntryType::try_from(4).unwrap());
+        assert!(EntryType::try_from(5).is_err());
     }
 
     pub async fn prepare_table_engine() -> (TempDir, TableEngineRef) {
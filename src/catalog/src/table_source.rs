@@ -28,6 +28,7 @@ use crate::error::{
     CatalogNotFoundSnafu, QueryAccessDeniedSnafu, Result, SchemaNotFoundSnafu, TableNotExistSnafu,
 };
 use crate::information_schema::InformationSchemaProvider;
+use crate::recycle_bin::is_recycled_table_name;
 use crate::CatalogManagerRef;
 
 pub struct DfTableSourceProvider {
@@ -124,14 +125,24 @@ impl DfTableSourceProvider {
             Arc::new(InformationSchemaProvider::new(
                 catalog_name.to_string(),
                 catalog_provider,
+                self.catalog_manager.column_limits(),
             ))
         };
-        let table = schema
-            .table(table_name)
-            .await?
-            .with_context(|| TableNotExistSnafu {
-                table: format_full_table_name(catalog_name, schema_name, table_name),
-            })?;
+        // Tables in the recycle bin are only visible to `RESTORE TABLE`.
+        let table = if is_recycled_table_name(table_name) {
+            None
+        } else {
+            schema.table(table_name).await?
+        };
+        if table.is_none() {
+            // The table is not registered yet if it's still replaying.
+            if let Some(progress) = self.catalog_manager.replay_progress() {
+                progress.ensure_not_pending(catalog_name, schema_name, table_name)?;
+            }
+        }
+        let table = table.with_context(|| TableNotExistSnafu {
+            table: format_full_table_name(catalog_name, schema_name, table_name),
+        })?;
 
         let table = DfTableProviderAdapter::new(table);
         let table = provider_as_source(Arc::new(table));
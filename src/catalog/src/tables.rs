@@ -21,6 +21,7 @@ use async_trait::async_trait;
 use common_catalog::consts::{INFORMATION_SCHEMA_NAME, SYSTEM_CATALOG_TABLE_NAME};
 use snafu::ResultExt;
 use table::metadata::TableId;
+use table::requests::TableOptions;
 use table::{Table, TableRef};
 
 use crate::error::{self, Error, InsertCatalogRecordSnafu, Result as CatalogResult};
@@ -106,8 +107,9 @@ impl SystemCatalog {
         &self,
         catalog: String,
         schema: String,
+        table_options: &TableOptions,
     ) -> crate::error::Result<usize> {
-        let request = build_schema_insert_request(catalog, schema);
+        let request = build_schema_insert_request(catalog, schema, table_options);
         self.information_schema
             .system
             .insert(request)
@@ -141,4 +143,8 @@ impl CatalogProvider for SystemCatalog {
             Ok(None)
         }
     }
+
+    async fn schema_exist(&self, name: &str) -> Result<bool, Error> {
+        Ok(name.eq_ignore_ascii_case(INFORMATION_SCHEMA_NAME))
+    }
 }
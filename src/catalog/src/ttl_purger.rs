@@ -0,0 +1,413 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Background purging of the rows expired by the `ttl` option of the tables, for the engines
+//! relying on the catalog to expire their rows (see [Table::purge_expired](table::Table::purge_expired)).
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common_catalog::format_full_table_name;
+use common_error::ext::ErrorExt;
+use common_error::prelude::StatusCode;
+use common_telemetry::{debug, info, warn};
+use common_time::util::ClockRef;
+use common_time::Timestamp;
+use metrics::increment_counter;
+use rand::Rng;
+use table::metadata::TableId;
+use table::TableRef;
+
+use crate::metrics::{METRIC_TTL_PURGED_TABLES, METRIC_TTL_PURGE_FAILED};
+
+/// Default interval between the iterations of purging.
+pub const DEFAULT_TTL_PURGE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Default max number of tables purged in an iteration.
+pub const DEFAULT_TTL_PURGE_MAX_TABLES: usize = 64;
+
+/// Options of [TtlPurger].
+#[derive(Debug, Clone)]
+pub struct TtlPurgeOptions {
+    /// Interval between the iterations of purging.
+    pub interval: Duration,
+    /// Max number of tables purged in an iteration, the others are purged in the following
+    /// iterations.
+    pub max_tables_per_iteration: usize,
+    /// Max random delay added to each interval, so the nodes started at the same time don't
+    /// purge at the same time.
+    pub max_jitter: Duration,
+}
+
+impl Default for TtlPurgeOptions {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_TTL_PURGE_INTERVAL,
+            max_tables_per_iteration: DEFAULT_TTL_PURGE_MAX_TABLES,
+            max_jitter: DEFAULT_TTL_PURGE_INTERVAL / 10,
+        }
+    }
+}
+
+/// Tracks the registered tables, and periodically asks the ones with a `ttl` option to purge
+/// the rows older than `now - ttl`.
+///
+/// All the tables are tracked, whether they have a `ttl` option or not, as the option is read
+/// on each iteration and may be set or removed by altering the table.
+///
+/// The tables are purged in the order of their ids, an iteration continues from the table
+/// after the last one purged by the previous iteration, so every table is purged in turn even
+/// if there are more tables than an iteration purges.
+pub struct TtlPurger {
+    options: TtlPurgeOptions,
+    clock: ClockRef,
+    state: Mutex<PurgeState>,
+    started: AtomicBool,
+}
+
+pub type TtlPurgerRef = Arc<TtlPurger>;
+
+#[derive(Default)]
+struct PurgeState {
+    /// Registered tables by their ids.
+    tables: BTreeMap<TableId, TableRef>,
+    /// Id of the last table purged.
+    last_purged: Option<TableId>,
+}
+
+impl TtlPurger {
+    pub fn new(options: TtlPurgeOptions, clock: ClockRef) -> Self {
+        Self {
+            options,
+            clock,
+            state: Mutex::new(PurgeState::default()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Tracks the table, it's purged once it has a `ttl` option.
+    pub fn track(&self, table: &TableRef) {
+        let table_info = table.table_info();
+        let _ = self
+            .state
+            .lock()
+            .unwrap()
+            .tables
+            .insert(table_info.ident.table_id, table.clone());
+    }
+
+    /// Stops tracking the table, e.g. when it's deregistered.
+    pub fn untrack(&self, table_id: TableId) {
+        let _ = self.state.lock().unwrap().tables.remove(&table_id);
+    }
+
+    /// Returns the ids of the tracked tables with a `ttl` option in ascending order.
+    pub fn tracked_tables(&self) -> Vec<TableId> {
+        self.state
+            .lock()
+            .unwrap()
+            .tables
+            .iter()
+            .filter(|(_, table)| has_ttl(table))
+            .map(|(table_id, _)| *table_id)
+            .collect()
+    }
+
+    /// Purges the expired rows of at most [TtlPurgeOptions::max_tables_per_iteration] tables,
+    /// returns the number of tables purged.
+    pub async fn purge_once(&self) -> usize {
+        let tables = self.next_tables();
+        let now_millis = self.clock.now_millis();
+
+        let mut purged = 0;
+        for (table_id, table) in tables {
+            let _ = self.state.lock().unwrap().last_purged.insert(table_id);
+
+            let table_info = table.table_info();
+            let table_name = format_full_table_name(
+                &table_info.catalog_name,
+                &table_info.schema_name,
+                &table_info.name,
+            );
+            // The ttl may be removed by altering the table after it's picked.
+            let Some(ttl) = table_info.meta.options.ttl else { continue };
+            let expire_before =
+                Timestamp::new_millisecond(now_millis.saturating_sub(ttl.as_millis() as i64));
+            match table.purge_expired(expire_before).await {
+                Ok(()) => {
+                    purged += 1;
+                    increment_counter!(METRIC_TTL_PURGED_TABLES);
+                }
+                Err(e) if e.status_code() == StatusCode::Unsupported => {
+                    info!(
+                        "Table {} purges its expired rows by itself, stop tracking it",
+                        table_name
+                    );
+                    self.untrack(table_id);
+                }
+                Err(e) => {
+                    increment_counter!(METRIC_TTL_PURGE_FAILED);
+                    warn!(
+                        "Failed to purge the expired rows of table {}, error: {}",
+                        table_name, e
+                    );
+                }
+            }
+        }
+        purged
+    }
+
+    /// Returns the tables to purge in this iteration, which are the ones with a `ttl` option
+    /// after the last table purged, wrapping around to the first table.
+    fn next_tables(&self) -> Vec<(TableId, TableRef)> {
+        let state = self.state.lock().unwrap();
+        let last = state.last_purged.unwrap_or(TableId::MAX);
+        state
+            .tables
+            .range((Bound::Excluded(last), Bound::Unbounded))
+            .chain(state.tables.range(..=last))
+            .filter(|(_, table)| has_ttl(table))
+            .take(self.options.max_tables_per_iteration)
+            .map(|(table_id, table)| (*table_id, table.clone()))
+            .collect()
+    }
+
+    /// Starts purging in the background until the purger is dropped, does nothing if it's
+    /// already started.
+    pub fn start(self: &Arc<Self>) {
+        if self.started.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        let purger = Arc::downgrade(self);
+        let options = self.options.clone();
+        let _handle = common_runtime::spawn_bg(async move {
+            loop {
+                let jitter = rand::thread_rng().gen_range(Duration::ZERO..=options.max_jitter);
+                tokio::time::sleep(options.interval + jitter).await;
+
+                let Some(purger) = purger.upgrade() else { break };
+                let purged = purger.purge_once().await;
+                debug!("Purged the expired rows of {} tables", purged);
+            }
+        });
+    }
+}
+
+fn has_ttl(table: &TableRef) -> bool {
+    table.table_info().meta.options.ttl.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::sync::atomic::AtomicI64;
+
+    use async_trait::async_trait;
+    use common_query::logical_plan::Expr;
+    use common_query::physical_plan::PhysicalPlanRef;
+    use common_recordbatch::RecordBatch;
+    use common_time::util::Clock;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+    use datatypes::vectors::UInt32Vector;
+    use table::metadata::{TableInfo, TableInfoRef};
+    use table::requests::TableOptions;
+    use table::test_util::MemTable;
+    use table::Table;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct MockClock {
+        now_millis: AtomicI64,
+    }
+
+    impl Clock for MockClock {
+        fn now_millis(&self) -> i64 {
+            self.now_millis.load(Ordering::Relaxed)
+        }
+    }
+
+    /// A table recording the purges, or leaving purging unsupported.
+    struct PurgedTable {
+        table: MemTable,
+        /// The `ttl` option, which may be altered.
+        ttl: Mutex<Option<Duration>>,
+        supported: bool,
+        purges: Mutex<Vec<Timestamp>>,
+    }
+
+    impl PurgedTable {
+        fn new(table_id: TableId, ttl: Option<Duration>, supported: bool) -> Arc<Self> {
+            let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+                "n",
+                ConcreteDataType::uint32_datatype(),
+                true,
+            )]));
+            let recordbatch =
+                RecordBatch::new(schema, vec![Arc::new(UInt32Vector::from_slice([1])) as _])
+                    .unwrap();
+            let table = MemTable::new_with_catalog(
+                format!("t{table_id}"),
+                recordbatch,
+                table_id,
+                "greptime".to_string(),
+                "public".to_string(),
+                vec![0],
+            );
+            Arc::new(Self {
+                table,
+                ttl: Mutex::new(ttl),
+                supported,
+                purges: Mutex::new(vec![]),
+            })
+        }
+
+        fn alter_ttl(&self, ttl: Option<Duration>) {
+            *self.ttl.lock().unwrap() = ttl;
+        }
+
+        fn take_purges(&self) -> Vec<Timestamp> {
+            std::mem::take(&mut self.purges.lock().unwrap())
+        }
+    }
+
+    #[async_trait]
+    impl Table for PurgedTable {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.table.schema()
+        }
+
+        fn table_info(&self) -> TableInfoRef {
+            let mut table_info = TableInfo::clone(&self.table.table_info());
+            table_info.meta.options = TableOptions {
+                ttl: *self.ttl.lock().unwrap(),
+                ..Default::default()
+            };
+            Arc::new(table_info)
+        }
+
+        async fn scan(
+            &self,
+            projection: Option<&Vec<usize>>,
+            filters: &[Expr],
+            limit: Option<usize>,
+        ) -> table::Result<PhysicalPlanRef> {
+            self.table.scan(projection, filters, limit).await
+        }
+
+        async fn purge_expired(&self, expire_before: Timestamp) -> table::Result<()> {
+            if !self.supported {
+                return self.table.purge_expired(expire_before).await;
+            }
+            self.purges.lock().unwrap().push(expire_before);
+            Ok(())
+        }
+    }
+
+    fn new_purger(max_tables_per_iteration: usize) -> TtlPurger {
+        let clock = Arc::new(MockClock::default());
+        clock.now_millis.store(10_000, Ordering::Relaxed);
+        let options = TtlPurgeOptions {
+            max_tables_per_iteration,
+            ..Default::default()
+        };
+        TtlPurger::new(options, clock)
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired() {
+        let purger = new_purger(10);
+        let expiring = PurgedTable::new(1, Some(Duration::from_secs(3)), true);
+        let persistent = PurgedTable::new(2, None, true);
+        purger.track(&(expiring.clone() as _));
+        purger.track(&(persistent.clone() as _));
+        assert_eq!(vec![1], purger.tracked_tables());
+
+        assert_eq!(1, purger.purge_once().await);
+        assert_eq!(
+            vec![Timestamp::new_millisecond(7_000)],
+            expiring.take_purges()
+        );
+        assert!(persistent.take_purges().is_empty());
+
+        // Deregistered tables are not purged anymore.
+        purger.untrack(1);
+        assert_eq!(0, purger.purge_once().await);
+        assert!(expiring.take_purges().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_purge_altered_ttl() {
+        let purger = new_purger(10);
+        let table = PurgedTable::new(1, None, true);
+        purger.track(&(table.clone() as _));
+        assert!(purger.tracked_tables().is_empty());
+        assert_eq!(0, purger.purge_once().await);
+
+        // Tables are purged once they gain a ttl by altering.
+        table.alter_ttl(Some(Duration::from_secs(1)));
+        assert_eq!(vec![1], purger.tracked_tables());
+        assert_eq!(1, purger.purge_once().await);
+        assert_eq!(vec![Timestamp::new_millisecond(9_000)], table.take_purges());
+
+        // And not purged anymore once the ttl is removed.
+        table.alter_ttl(None);
+        assert_eq!(0, purger.purge_once().await);
+        assert!(table.take_purges().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_purge_in_turn() {
+        let purger = new_purger(2);
+        let tables = (1..=3)
+            .map(|table_id| PurgedTable::new(table_id, Some(Duration::from_secs(1)), true))
+            .collect::<Vec<_>>();
+        for table in &tables {
+            purger.track(&(table.clone() as _));
+        }
+
+        let purged_tables = || {
+            tables
+                .iter()
+                .filter(|table| !table.take_purges().is_empty())
+                .map(|table| table.table_info().ident.table_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(2, purger.purge_once().await);
+        assert_eq!(vec![1, 2], purged_tables());
+        assert_eq!(2, purger.purge_once().await);
+        assert_eq!(vec![1, 3], purged_tables());
+        assert_eq!(2, purger.purge_once().await);
+        assert_eq!(vec![2, 3], purged_tables());
+    }
+
+    #[tokio::test]
+    async fn test_untrack_unsupported_table() {
+        let purger = new_purger(10);
+        let table = PurgedTable::new(1, Some(Duration::from_secs(1)), false);
+        purger.track(&(table as _));
+        assert_eq!(vec![1], purger.tracked_tables());
+
+        assert_eq!(0, purger.purge_once().await);
+        assert!(purger.tracked_tables().is_empty());
+    }
+}
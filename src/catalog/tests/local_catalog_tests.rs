@@ -14,49 +14,74 @@
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::sync::Arc;
+    use std::time::{Duration, Instant};
 
-    use catalog::local::LocalCatalogManager;
-    use catalog::{CatalogManager, RegisterTableRequest, RenameTableRequest};
-    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use catalog::local::{LocalCatalogManager, NameResolution};
+    use catalog::replay::{ReplayProgressSnapshot, ReplayState};
+    use catalog::{
+        CatalogManager, DeregisterTableRequest, RegisterTableRequest, RenameTableRequest,
+    };
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MITO_ENGINE};
+    use common_catalog::naming::validate_table_name;
+    use common_error::prelude::{ErrorExt, StatusCode};
+    use common_recordbatch::RecordBatch;
     use common_telemetry::{error, info};
-    use common_test_util::temp_dir::TempDir;
+    use common_time::util::current_time_millis;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{StringVector, TimestampMillisecondVector, UInt32Vector, VectorRef};
     use mito::config::EngineConfig;
+    use mito::table::test_util::{
+        new_create_request, new_test_object_store, schema_for_test, MockEngine, MockMitoEngine,
+    };
+    use object_store::ObjectStore;
     use table::engine::manager::MemoryTableEngineManager;
+    use table::engine::{region_name, EngineContext, TableEngine};
+    use table::requests::{InsertRequest, TableOptions};
     use table::table::numbers::NumbersTable;
+    use table::table::TableIdProvider;
+    use table::test_util::{CreateRequestBuilder, MemTable, MemoryTable, MemoryTableEngine};
     use table::TableRef;
     use tokio::sync::Mutex;
 
     async fn create_local_catalog_manager(
-    ) -> Result<(TempDir, LocalCatalogManager), catalog::error::Error> {
-        let (dir, object_store) =
-            mito::table::test_util::new_test_object_store("setup_mock_engine_and_table").await;
-        let mock_engine = Arc::new(mito::table::test_util::MockMitoEngine::new(
-            EngineConfig::default(),
-            mito::table::test_util::MockEngine::default(),
-            object_store,
+    ) -> Result<(MemoryTableEngine, LocalCatalogManager), catalog::error::Error> {
+        create_local_catalog_manager_with_name_resolution(NameResolution::Exact).await
+    }
+
+    async fn create_local_catalog_manager_with_name_resolution(
+        name_resolution: NameResolution,
+    ) -> Result<(MemoryTableEngine, LocalCatalogManager), catalog::error::Error> {
+        let engine = MemoryTableEngine::new();
+        let engine_manager = Arc::new(MemoryTableEngineManager::alias(
+            MITO_ENGINE.to_string(),
+            Arc::new(engine.clone()),
         ));
-        let engine_manager = Arc::new(MemoryTableEngineManager::new(mock_engine.clone()));
-        let catalog_manager = LocalCatalogManager::try_new(engine_manager).await.unwrap();
+        let catalog_manager = LocalCatalogManager::try_new(engine_manager)
+            .await
+            .unwrap()
+            .with_name_resolution(name_resolution);
         catalog_manager.start().await?;
-        Ok((dir, catalog_manager))
+        Ok((engine, catalog_manager))
     }
 
     #[tokio::test]
     async fn test_rename_table() {
         common_telemetry::init_default_ut_logging();
-        let (_dir, catalog_manager) = create_local_catalog_manager().await.unwrap();
+        let (_engine, catalog_manager) = create_local_catalog_manager().await.unwrap();
         // register table
         let table_name = "test_table";
         let table_id = 42;
         let table = Arc::new(NumbersTable::new(table_id));
-        let request = RegisterTableRequest {
-            catalog: DEFAULT_CATALOG_NAME.to_string(),
-            schema: DEFAULT_SCHEMA_NAME.to_string(),
-            table_name: table_name.to_string(),
+        let request = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            table_name,
             table_id,
-            table: table.clone(),
-        };
+            table.clone(),
+        );
         assert!(catalog_manager.register_table(request).await.unwrap());
 
         // rename table
@@ -81,16 +106,84 @@ mod tests {
         assert_eq!(registered_table.table_info().ident.table_id, table_id);
     }
 
+    #[tokio::test]
+    async fn test_name_resolution() {
+        for (name_resolution, case_insensitive) in [
+            (NameResolution::Exact, false),
+            (NameResolution::CaseInsensitive, true),
+        ] {
+            let (_engine, catalog_manager) =
+                create_local_catalog_manager_with_name_resolution(name_resolution)
+                    .await
+                    .unwrap();
+            let request = RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                "MyTable",
+                42,
+                Arc::new(NumbersTable::new(42)),
+            );
+            assert!(catalog_manager.register_table(request).await.unwrap());
+
+            assert!(table_exists(&catalog_manager, "MyTable").await);
+            assert_eq!(
+                case_insensitive,
+                table_exists(&catalog_manager, "mytable").await
+            );
+            let schema = catalog_manager
+                .schema(DEFAULT_CATALOG_NAME, "PUBLIC")
+                .await
+                .unwrap();
+            assert_eq!(case_insensitive, schema.is_some());
+
+            let schema = catalog_manager
+                .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+                .await
+                .unwrap()
+                .unwrap();
+            let table_names = schema.table_names().await.unwrap();
+            assert!(table_names.contains(&"MyTable".to_string()));
+            assert!(!table_names.contains(&"mytable".to_string()));
+
+            let result = catalog_manager
+                .register_table(RegisterTableRequest::new(
+                    DEFAULT_CATALOG_NAME,
+                    DEFAULT_SCHEMA_NAME,
+                    "mytable",
+                    43,
+                    Arc::new(NumbersTable::new(43)),
+                ))
+                .await;
+            if case_insensitive {
+                let err = result.unwrap_err();
+                assert!(
+                    err.to_string().contains(
+                        "Table `greptime.public.mytable` as `greptime.public.MyTable` already exists"
+                    ),
+                    "Actual error message: {err}",
+                );
+            } else {
+                assert!(result.unwrap());
+                let table = catalog_manager
+                    .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "mytable")
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(43, table.table_info().ident.table_id);
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_duplicate_register() {
-        let (_dir, catalog_manager) = create_local_catalog_manager().await.unwrap();
-        let request = RegisterTableRequest {
-            catalog: DEFAULT_CATALOG_NAME.to_string(),
-            schema: DEFAULT_SCHEMA_NAME.to_string(),
-            table_name: "test_table".to_string(),
-            table_id: 42,
-            table: Arc::new(NumbersTable::new(42)),
-        };
+        let (_engine, catalog_manager) = create_local_catalog_manager().await.unwrap();
+        let request = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            "test_table",
+            42,
+            Arc::new(NumbersTable::new(42)),
+        );
         assert!(catalog_manager
             .register_table(request.clone())
             .await
@@ -100,13 +193,13 @@ mod tests {
         assert!(!catalog_manager.register_table(request).await.unwrap());
 
         let err = catalog_manager
-            .register_table(RegisterTableRequest {
-                catalog: DEFAULT_CATALOG_NAME.to_string(),
-                schema: DEFAULT_SCHEMA_NAME.to_string(),
-                table_name: "test_table".to_string(),
-                table_id: 43,
-                table: Arc::new(NumbersTable::new(43)),
-            })
+            .register_table(RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                "test_table",
+                43,
+                Arc::new(NumbersTable::new(43)),
+            ))
             .await
             .unwrap_err();
         assert!(
@@ -116,11 +209,131 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_track_tables_with_ttl() {
+        let (_engine, catalog_manager) = create_local_catalog_manager().await.unwrap();
+        let new_table = |table_id, ttl| {
+            let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+                "n",
+                ConcreteDataType::uint32_datatype(),
+                true,
+            )]));
+            let columns: Vec<VectorRef> = vec![Arc::new(UInt32Vector::from_slice([1]))];
+            let recordbatch = RecordBatch::new(schema, columns).unwrap();
+            MemTable::new_with_catalog(
+                format!("t{table_id}"),
+                recordbatch,
+                table_id,
+                DEFAULT_CATALOG_NAME.to_string(),
+                DEFAULT_SCHEMA_NAME.to_string(),
+                vec![0],
+            )
+            .with_options(TableOptions {
+                ttl,
+                ..Default::default()
+            })
+        };
+        for (table_id, ttl) in [(42, Some(Duration::from_secs(60))), (43, None)] {
+            let request = RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                &format!("t{table_id}"),
+                table_id,
+                Arc::new(new_table(table_id, ttl)),
+            );
+            assert!(catalog_manager.register_table(request).await.unwrap());
+        }
+        let ttl_purger = catalog_manager.ttl_purger();
+        assert_eq!(vec![42], ttl_purger.tracked_tables());
+
+        let request = DeregisterTableRequest {
+            catalog: DEFAULT_CATALOG_NAME.to_string(),
+            schema: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "t42".to_string(),
+        };
+        assert!(catalog_manager.deregister_table(request).await.unwrap());
+        assert!(ttl_purger.tracked_tables().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_rows() {
+        let (engine, catalog_manager) = create_local_catalog_manager().await.unwrap();
+        let request = CreateRequestBuilder::new("monitor")
+            .table_id(42)
+            .tag("host", ConcreteDataType::string_datatype())
+            .time_index("ts")
+            .table_options(TableOptions {
+                ttl: Some(Duration::from_secs(60)),
+                ..Default::default()
+            })
+            .build();
+        let table = engine
+            .create_table(&EngineContext::default(), request)
+            .await
+            .unwrap();
+        let request = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            "monitor",
+            42,
+            table.clone(),
+        );
+        assert!(catalog_manager.register_table(request).await.unwrap());
+
+        let now = current_time_millis();
+        let request = InsertRequest {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "monitor".to_string(),
+            columns_values: HashMap::from([
+                (
+                    "host".to_string(),
+                    Arc::new(StringVector::from(vec!["a", "b"])) as VectorRef,
+                ),
+                (
+                    "ts".to_string(),
+                    Arc::new(TimestampMillisecondVector::from_vec(vec![1000, now])) as _,
+                ),
+            ]),
+            region_number: 0,
+            write_mode: Default::default(),
+        };
+        assert_eq!(2, table.insert(request).await.unwrap());
+
+        // The memory engine relies on the catalog to delete its expired rows.
+        assert_eq!(1, catalog_manager.ttl_purger().purge_once().await);
+        let table = table.as_any().downcast_ref::<MemoryTable>().unwrap();
+        assert_eq!(1, table.num_rows());
+    }
+
+    #[tokio::test]
+    async fn test_register_table_on_system_table_failure() {
+        let (engine, catalog_manager) = create_local_catalog_manager().await.unwrap();
+        let request = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            "test_table",
+            42,
+            Arc::new(NumbersTable::new(42)),
+        );
+
+        // Fails to write the system table, the table is left unregistered.
+        engine.faults().fail_nth(1);
+        assert!(catalog_manager
+            .register_table(request.clone())
+            .await
+            .is_err());
+        assert!(!table_exists(&catalog_manager, "test_table").await);
+
+        assert!(catalog_manager.register_table(request).await.unwrap());
+        assert!(table_exists(&catalog_manager, "test_table").await);
+    }
+
     #[test]
     fn test_concurrent_register() {
         common_telemetry::init_default_ut_logging();
         let rt = Arc::new(tokio::runtime::Builder::new_multi_thread().build().unwrap());
-        let (_dir, catalog_manager) =
+        let (_engine, catalog_manager) =
             rt.block_on(async { create_local_catalog_manager().await.unwrap() });
         let catalog_manager = Arc::new(catalog_manager);
 
@@ -133,13 +346,13 @@ mod tests {
             let handle = rt.spawn(async move {
                 let table_id = 42 + i;
                 let table = Arc::new(NumbersTable::new(table_id));
-                let req = RegisterTableRequest {
-                    catalog: DEFAULT_CATALOG_NAME.to_string(),
-                    schema: DEFAULT_SCHEMA_NAME.to_string(),
-                    table_name: "test_table".to_string(),
+                let req = RegisterTableRequest::new(
+                    DEFAULT_CATALOG_NAME,
+                    DEFAULT_SCHEMA_NAME,
+                    "test_table",
                     table_id,
-                    table: table.clone(),
-                };
+                    table.clone(),
+                );
                 match catalog.register_table(req).await {
                     Ok(res) => {
                         if res {
@@ -173,4 +386,149 @@ mod tests {
             );
         });
     }
+
+    async fn new_catalog_manager_with_storage(
+        storage: MockEngine,
+        object_store: ObjectStore,
+    ) -> (Arc<MockMitoEngine>, LocalCatalogManager) {
+        let engine = Arc::new(MockMitoEngine::new(
+            EngineConfig::default(),
+            storage,
+            object_store,
+        ));
+        let engine_manager = Arc::new(MemoryTableEngineManager::new(engine.clone()));
+        let catalog_manager = LocalCatalogManager::try_new(engine_manager).await.unwrap();
+        (engine, catalog_manager)
+    }
+
+    async fn table_exists(catalog_manager: &LocalCatalogManager, table_name: &str) -> bool {
+        catalog_manager
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, table_name)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn test_replay_tables_concurrently() {
+        common_telemetry::init_default_ut_logging();
+        let (_dir, object_store) = new_test_object_store("test_replay_tables_concurrently").await;
+        let storage = MockEngine::default();
+
+        // Creates the tables to replay.
+        let (engine, catalog_manager) =
+            new_catalog_manager_with_storage(storage.clone(), object_store.clone()).await;
+        catalog_manager.start().await.unwrap();
+        let mut table_ids = Vec::new();
+        for i in 0..4 {
+            let table_id = catalog_manager.next_table_id().await.unwrap();
+            let table_name = format!("t{i}");
+            let mut request = new_create_request(Arc::new(schema_for_test()));
+            request.id = table_id;
+            request.table_name = table_name.clone();
+            let table = engine
+                .create_table(&EngineContext::default(), request)
+                .await
+                .unwrap();
+            let request = RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                &table_name,
+                table_id,
+                table,
+            );
+            assert!(catalog_manager.register_table(request).await.unwrap());
+            table_ids.push(table_id);
+        }
+        // The WAL of the last table is corrupted.
+        storage.corrupt_region(&region_name(table_ids[3], 0));
+
+        // Restarts, each table takes 300ms to replay and 2 tables replay concurrently.
+        let (_engine, catalog_manager) = new_catalog_manager_with_storage(
+            storage.with_replay_delay(Duration::from_millis(300)),
+            object_store,
+        )
+        .await;
+        let catalog_manager = Arc::new(catalog_manager.with_replay_concurrency(2));
+        let progress = catalog_manager.replay_progress().unwrap();
+        assert_eq!(ReplayState::Replaying, progress.state());
+
+        let start = Instant::now();
+        let handle = tokio::spawn({
+            let catalog_manager = catalog_manager.clone();
+            async move { catalog_manager.start().await }
+        });
+
+        // The first 2 tables are replayed and queryable, while the others are replaying.
+        tokio::time::sleep(Duration::from_millis(450)).await;
+        let snapshot = progress.snapshot();
+        assert_eq!(ReplayState::Replaying, snapshot.state);
+        assert_eq!(4, snapshot.total);
+        assert_eq!(2, snapshot.replayed + snapshot.failed);
+        assert!(table_exists(&catalog_manager, "t0").await);
+        // Tables still replaying fail with a retryable error instead of being absent.
+        let err = catalog_manager
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "t3")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::StorageUnavailable, err.status_code());
+        assert!(err.status_code().is_retryable());
+        assert!(!catalog_manager.is_started());
+
+        handle.await.unwrap().unwrap();
+        assert!(catalog_manager.is_started());
+        // Replaying serially takes 1200ms.
+        let elapsed = start.elapsed();
+        assert!(elapsed < Duration::from_millis(1000), "{elapsed:?}");
+
+        // The corrupted table is left closed, without failing the others.
+        assert_eq!(
+            ReplayProgressSnapshot {
+                state: ReplayState::Ready,
+                total: 4,
+                replayed: 3,
+                failed: 1,
+            },
+            progress.snapshot()
+        );
+        for table_name in ["t0", "t1", "t2"] {
+            assert!(table_exists(&catalog_manager, table_name).await);
+        }
+        assert!(!table_exists(&catalog_manager, "t3").await);
+    }
+
+    #[tokio::test]
+    async fn test_reopen_table_with_invalid_name() {
+        // Tables created before their names are validated are still opened after restart.
+        let table_name = "greptime_legacy";
+        assert!(validate_table_name(table_name).is_err());
+
+        let (_dir, object_store) =
+            new_test_object_store("test_reopen_table_with_invalid_name").await;
+        let storage = MockEngine::default();
+        let (engine, catalog_manager) =
+            new_catalog_manager_with_storage(storage.clone(), object_store.clone()).await;
+        catalog_manager.start().await.unwrap();
+        let table_id = catalog_manager.next_table_id().await.unwrap();
+        let mut request = new_create_request(Arc::new(schema_for_test()));
+        request.id = table_id;
+        request.table_name = table_name.to_string();
+        let table = engine
+            .create_table(&EngineContext::default(), request)
+            .await
+            .unwrap();
+        let request = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            table_name,
+            table_id,
+            table,
+        );
+        assert!(catalog_manager.register_table(request).await.unwrap());
+
+        let (_engine, catalog_manager) =
+            new_catalog_manager_with_storage(storage, object_store).await;
+        catalog_manager.start().await.unwrap();
+        assert!(table_exists(&catalog_manager, table_name).await);
+    }
 }
@@ -16,6 +16,7 @@ use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use async_stream::stream;
@@ -31,20 +32,30 @@ use datatypes::vectors::StringVector;
 use serde::Serializer;
 use table::engine::{EngineContext, TableEngine, TableReference};
 use table::metadata::TableId;
-use table::requests::{AlterTableRequest, CreateTableRequest, DropTableRequest, OpenTableRequest};
+use table::requests::{
+    AlterTableRequest, CreateTableRequest, DropTableRequest, OpenTableRequest, TableOptions,
+};
 use table::test_util::MemTable;
 use table::TableRef;
 use tokio::sync::RwLock;
 
 pub struct MockKvBackend {
     map: RwLock<BTreeMap<Vec<u8>, Vec<u8>>>,
+    /// Number of values fetched by `get`.
+    num_gets: AtomicUsize,
+}
+
+impl MockKvBackend {
+    pub fn num_gets(&self) -> usize {
+        self.num_gets.load(Ordering::Relaxed)
+    }
 }
 
 impl Default for MockKvBackend {
     fn default() -> Self {
         let mut map = BTreeMap::default();
         let catalog_value = CatalogValue {}.as_bytes().unwrap();
-        let schema_value = SchemaValue {}.as_bytes().unwrap();
+        let schema_value = SchemaValue::default().as_bytes().unwrap();
 
         let default_catalog_key = CatalogKey {
             catalog_name: DEFAULT_CATALOG_NAME.to_string(),
@@ -62,7 +73,10 @@ impl Default for MockKvBackend {
         map.insert(default_schema_key.into(), schema_value);
 
         let map = RwLock::new(map);
-        Self { map }
+        Self {
+            map,
+            num_gets: AtomicUsize::new(0),
+        }
     }
 }
 
@@ -104,6 +118,16 @@ impl KvBackend for MockKvBackend {
         }))
     }
 
+    async fn get(&self, key: &[u8]) -> Result<Option<Kv>, Error> {
+        let _ = self.num_gets.fetch_add(1, Ordering::Relaxed);
+        let map = self.map.read().await;
+        Ok(map.get(key).map(|value| Kv(key.to_vec(), value.clone())))
+    }
+
+    async fn exists(&self, key: &[u8]) -> Result<bool, Error> {
+        Ok(self.map.read().await.contains_key(key))
+    }
+
     async fn set(&self, key: &[u8], val: &[u8]) -> Result<(), Error> {
         let mut map = self.map.write().await;
         map.insert(key.to_vec(), val.to_vec());
@@ -169,6 +193,7 @@ impl TableEngine for MockTableEngine {
         let table_name = request.table_name.clone();
         let catalog_name = request.catalog_name.clone();
         let schema_name = request.schema_name.clone();
+        let table_key = TableReference::full(&catalog_name, &schema_name, &table_name).to_string();
 
         let default_table_id = "0".to_owned();
         let table_id = TableId::from_str(
@@ -187,17 +212,23 @@ impl TableEngine for MockTableEngine {
 
         let data = vec![Arc::new(StringVector::from(vec!["a", "b", "c"])) as _];
         let record_batch = RecordBatch::new(schema, data).unwrap();
-        let table: TableRef = Arc::new(MemTable::new_with_catalog(
-            &table_name,
-            record_batch,
-            table_id,
-            catalog_name,
-            schema_name,
-            vec![0],
-        )) as Arc<_>;
+        let table: TableRef = Arc::new(
+            MemTable::new_with_catalog(
+                &table_name,
+                record_batch,
+                table_id,
+                catalog_name,
+                schema_name,
+                vec![0],
+            )
+            .with_options(TableOptions {
+                ttl: request.table_options.ttl,
+                ..Default::default()
+            }),
+        ) as Arc<_>;
 
         let mut tables = self.tables.write().await;
-        tables.insert(table_name, table.clone() as TableRef);
+        tables.insert(table_key, table.clone() as TableRef);
         Ok(table)
     }
 
@@ -206,7 +237,17 @@ impl TableEngine for MockTableEngine {
         _ctx: &EngineContext,
         request: OpenTableRequest,
     ) -> table::Result<Option<TableRef>> {
-        Ok(self.tables.read().await.get(&request.table_name).cloned())
+        let table_ref = TableReference::full(
+            &request.catalog_name,
+            &request.schema_name,
+            &request.table_name,
+        );
+        Ok(self
+            .tables
+            .read()
+            .await
+            .get(&table_ref.to_string())
+            .cloned())
     }
 
     async fn alter_table(
@@ -19,20 +19,35 @@ mod mock;
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
     use std::sync::Arc;
+    use std::time::Duration;
 
-    use catalog::helper::{CatalogKey, CatalogValue, SchemaKey, SchemaValue};
+    use catalog::error::Error;
+    use catalog::helper::{
+        CatalogKey, CatalogValue, SchemaKey, SchemaValue, TableRegionalKey, TableRegionalValue,
+        CATALOG_VERSION_KEY,
+    };
     use catalog::remote::{
-        KvBackend, KvBackendRef, RemoteCatalogManager, RemoteCatalogProvider, RemoteSchemaProvider,
+        migrate_remote_catalog, KvBackend, KvBackendRef, RemoteCatalogManager,
+        RemoteCatalogProvider, RemoteSchemaProvider, REMOTE_CATALOG_VERSION,
+    };
+    use catalog::system::VersionEntry;
+    use catalog::{
+        table_names_stream, CatalogManager, CatalogProvider, DeregisterTableRequest,
+        RegisterTableRequest, SchemaProvider,
     };
-    use catalog::{CatalogManager, RegisterTableRequest};
-    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MITO_ENGINE};
+    use common_catalog::consts::{
+        DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, INFORMATION_SCHEMA_NAME, MITO_ENGINE,
+    };
+    use common_query::physical_plan::SessionContext;
+    use common_recordbatch::util;
     use datatypes::schema::RawSchema;
-    use futures_util::StreamExt;
+    use datatypes::value::Value;
+    use futures_util::{StreamExt, TryStreamExt};
     use table::engine::manager::{MemoryTableEngineManager, TableEngineManagerRef};
     use table::engine::{EngineContext, TableEngineRef};
-    use table::requests::CreateTableRequest;
+    use table::requests::{CreateTableRequest, TableOptions};
 
     use crate::mock::{MockKvBackend, MockTableEngine};
 
@@ -60,7 +75,10 @@ mod tests {
         }
         .to_string();
         backend
-            .set(schema_key.as_bytes(), &SchemaValue {}.as_bytes().unwrap())
+            .set(
+                schema_key.as_bytes(),
+                &SchemaValue::default().as_bytes().unwrap(),
+            )
             .await
             .unwrap();
 
@@ -152,13 +170,7 @@ mod tests {
             )
             .await
             .unwrap();
-        let reg_req = RegisterTableRequest {
-            catalog: catalog_name,
-            schema: schema_name,
-            table_name,
-            table_id: 1,
-            table,
-        };
+        let reg_req = RegisterTableRequest::new(&catalog_name, &schema_name, &table_name, 1, table);
         let res = catalog_manager.register_table(reg_req).await;
 
         // because nonexistent_catalog does not exist yet.
@@ -214,13 +226,8 @@ mod tests {
             )
             .await
             .unwrap();
-        let reg_req = RegisterTableRequest {
-            catalog: catalog_name,
-            schema: schema_name,
-            table_name: table_name.clone(),
-            table_id,
-            table,
-        };
+        let reg_req =
+            RegisterTableRequest::new(&catalog_name, &schema_name, &table_name, table_id, table);
         assert!(catalog_manager.register_table(reg_req).await.unwrap());
         assert_eq!(
             vec![table_name],
@@ -228,6 +235,57 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_track_tables_with_ttl() {
+        let node_id = 42;
+        let (_, table_engine, catalog_manager, _) = prepare_components(node_id).await;
+        for (table_id, ttl) in [(1, Some(Duration::from_secs(60))), (2, None)] {
+            let table_name = format!("t{table_id}");
+            let table_options = TableOptions {
+                ttl,
+                extra_options: HashMap::from([("table_id".to_string(), table_id.to_string())]),
+                ..Default::default()
+            };
+            let table = table_engine
+                .create_table(
+                    &EngineContext {},
+                    CreateTableRequest {
+                        id: table_id,
+                        catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                        schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                        table_name: table_name.clone(),
+                        desc: None,
+                        schema: RawSchema::new(vec![]),
+                        region_numbers: vec![0],
+                        primary_key_indices: vec![],
+                        create_if_not_exists: false,
+                        table_options,
+                        engine: MITO_ENGINE.to_string(),
+                    },
+                )
+                .await
+                .unwrap();
+            let request = RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                &table_name,
+                table_id,
+                table,
+            );
+            assert!(catalog_manager.register_table(request).await.unwrap());
+        }
+        let ttl_purger = catalog_manager.ttl_purger();
+        assert_eq!(vec![1], ttl_purger.tracked_tables());
+
+        let request = DeregisterTableRequest {
+            catalog: DEFAULT_CATALOG_NAME.to_string(),
+            schema: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "t1".to_string(),
+        };
+        let _ = catalog_manager.deregister_table(request).await.unwrap();
+        assert!(ttl_purger.tracked_tables().is_empty());
+    }
+
     #[tokio::test]
     async fn test_register_catalog_schema_table() {
         let node_id = 42;
@@ -274,13 +332,13 @@ mod tests {
             .await
             .unwrap();
 
-        let reg_req = RegisterTableRequest {
-            catalog: catalog_name.clone(),
-            schema: schema_name.clone(),
-            table_name: " fail_table".to_string(),
-            table_id: 2,
-            table: table_to_register,
-        };
+        let reg_req = RegisterTableRequest::new(
+            &catalog_name,
+            &schema_name,
+            " fail_table",
+            2,
+            table_to_register,
+        );
         // this register will fail since schema does not exist yet
         assert_matches!(
             catalog_manager
@@ -320,4 +378,401 @@ mod tests {
                 .collect()
         )
     }
+
+    #[tokio::test]
+    async fn test_remote_information_schema_tables() {
+        let node_id = 42;
+        let (_, table_engine, catalog_manager, _) = prepare_components(node_id).await;
+
+        let table_name = "test_table".to_string();
+        let table = table_engine
+            .create_table(
+                &EngineContext {},
+                CreateTableRequest {
+                    id: 1,
+                    catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                    schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                    table_name: table_name.clone(),
+                    desc: None,
+                    schema: RawSchema::new(vec![]),
+                    region_numbers: vec![0],
+                    primary_key_indices: vec![],
+                    create_if_not_exists: false,
+                    table_options: Default::default(),
+                    engine: MITO_ENGINE.to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        let reg_req = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            &table_name,
+            1,
+            table,
+        );
+        assert!(catalog_manager.register_table(reg_req).await.unwrap());
+
+        // The information schema is not listed as a schema of the catalog, but resolvable.
+        let default_catalog = catalog_manager
+            .catalog(DEFAULT_CATALOG_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            vec![DEFAULT_SCHEMA_NAME.to_string()],
+            default_catalog.schema_names().await.unwrap()
+        );
+        let tables = catalog_manager
+            .table(DEFAULT_CATALOG_NAME, INFORMATION_SCHEMA_NAME, "tables")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let session_ctx = SessionContext::new();
+        let stream = tables.scan(None, &[], None).await.unwrap();
+        let stream = stream.execute(0, session_ctx.task_ctx()).unwrap();
+        let batches = util::collect(stream).await.unwrap();
+        let user_tables = batches
+            .iter()
+            .flat_map(|batch| batch.rows())
+            .filter_map(|row| match (&row[1], &row[2]) {
+                (Value::String(schema), Value::String(table))
+                    if schema.as_utf8() != INFORMATION_SCHEMA_NAME =>
+                {
+                    Some(format!("{}.{}", schema.as_utf8(), table.as_utf8()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![format!("{DEFAULT_SCHEMA_NAME}.{table_name}")],
+            user_tables
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_table_names_paginated() {
+        let node_id = 42;
+        let (_, table_engine, catalog_manager, _) = prepare_components(node_id).await;
+
+        // Registers the tables out of order.
+        for (table_id, table_name) in ["t3", "t1", "t4", "t0", "t2"].into_iter().enumerate() {
+            let table_id = table_id as u32 + 1;
+            let table = table_engine
+                .create_table(
+                    &EngineContext {},
+                    CreateTableRequest {
+                        id: table_id,
+                        catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                        schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                        table_name: table_name.to_string(),
+                        desc: None,
+                        schema: RawSchema::new(vec![]),
+                        region_numbers: vec![0],
+                        primary_key_indices: vec![],
+                        create_if_not_exists: false,
+                        table_options: Default::default(),
+                        engine: MITO_ENGINE.to_string(),
+                    },
+                )
+                .await
+                .unwrap();
+            let reg_req = RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                table_name,
+                table_id,
+                table,
+            );
+            assert!(catalog_manager.register_table(reg_req).await.unwrap());
+        }
+
+        let default_catalog = catalog_manager
+            .catalog(DEFAULT_CATALOG_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (vec![DEFAULT_SCHEMA_NAME.to_string()], false),
+            default_catalog
+                .schema_names_paginated(None, 10)
+                .await
+                .unwrap()
+        );
+
+        let default_schema = default_catalog
+            .schema(DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (vec!["t1".to_string(), "t2".to_string()], true),
+            default_schema
+                .table_names_paginated(Some("t0"), 2)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            (vec!["t3".to_string(), "t4".to_string()], false),
+            default_schema
+                .table_names_paginated(Some("t2"), 2)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            vec!["t0", "t1", "t2", "t3", "t4"],
+            table_names_stream(default_schema)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_schema_and_table_exist() {
+        let node_id = 42;
+        let backend = Arc::new(MockKvBackend::default());
+        let table_engine = Arc::new(MockTableEngine::default());
+        let engine_manager = Arc::new(MemoryTableEngineManager::alias(
+            MITO_ENGINE.to_string(),
+            table_engine.clone(),
+        ));
+        let catalog_manager =
+            RemoteCatalogManager::new(engine_manager.clone(), node_id, backend.clone());
+        catalog_manager.start().await.unwrap();
+
+        let table_name = "test_table";
+        let table_id = 1;
+        let table = table_engine
+            .create_table(
+                &EngineContext {},
+                CreateTableRequest {
+                    id: table_id,
+                    catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                    schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                    table_name: table_name.to_string(),
+                    desc: None,
+                    schema: RawSchema::new(vec![]),
+                    region_numbers: vec![0],
+                    primary_key_indices: vec![],
+                    create_if_not_exists: false,
+                    table_options: Default::default(),
+                    engine: MITO_ENGINE.to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        let reg_req = RegisterTableRequest::new(
+            DEFAULT_CATALOG_NAME,
+            DEFAULT_SCHEMA_NAME,
+            table_name,
+            table_id,
+            table,
+        );
+        assert!(catalog_manager.register_table(reg_req).await.unwrap());
+
+        let catalog_provider = RemoteCatalogProvider::new(
+            DEFAULT_CATALOG_NAME.to_string(),
+            backend.clone(),
+            engine_manager.clone(),
+            node_id,
+        );
+        let schema_provider = RemoteSchemaProvider::new(
+            DEFAULT_CATALOG_NAME.to_string(),
+            DEFAULT_SCHEMA_NAME.to_string(),
+            node_id,
+            engine_manager,
+            backend.clone(),
+        );
+
+        // The existence checks never fetch the values to build the providers or open the tables.
+        let num_gets = backend.num_gets();
+        assert!(catalog_provider
+            .schema_exist(DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap());
+        assert!(catalog_provider
+            .schema_exist(INFORMATION_SCHEMA_NAME)
+            .await
+            .unwrap());
+        assert!(!catalog_provider.schema_exist("nonexistent").await.unwrap());
+        assert!(schema_provider.table_exist(table_name).await.unwrap());
+        assert!(!schema_provider.table_exist("nonexistent").await.unwrap());
+        assert_eq!(num_gets, backend.num_gets());
+
+        assert!(catalog_provider
+            .schema(DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(num_gets + 1, backend.num_gets());
+    }
+
+    fn regional_key(table_name: &str) -> Vec<u8> {
+        TableRegionalKey {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: table_name.to_string(),
+            node_id: 42,
+        }
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Puts the regional value of table `table_name` in the format before the engine was
+    /// recorded.
+    async fn put_old_regional_value(backend: &KvBackendRef, table_name: &str) {
+        backend
+            .set(
+                &regional_key(table_name),
+                br#"{"version":0,"regions_ids":[0]}"#,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn read_engine(backend: &KvBackendRef, table_name: &str) -> Option<String> {
+        let kv = backend
+            .get(&regional_key(table_name))
+            .await
+            .unwrap()
+            .unwrap();
+        TableRegionalValue::from_bytes(kv.1).unwrap().engine_name
+    }
+
+    async fn read_version(backend: &KvBackendRef) -> VersionEntry {
+        let kv = backend
+            .get(CATALOG_VERSION_KEY.as_bytes())
+            .await
+            .unwrap()
+            .unwrap();
+        serde_json::from_slice(&kv.1).unwrap()
+    }
+
+    async fn set_version(backend: &KvBackendRef, version: u32, migrating_to: Option<u32>) {
+        let version = VersionEntry {
+            version,
+            migrating_to,
+        };
+        backend
+            .set(
+                CATALOG_VERSION_KEY.as_bytes(),
+                &serde_json::to_vec(&version).unwrap(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_migrate_remote_catalog() {
+        let backend = Arc::new(MockKvBackend::default()) as KvBackendRef;
+        put_old_regional_value(&backend, "old_table").await;
+        let value = TableRegionalValue {
+            version: 0,
+            regions_ids: vec![0],
+            engine_name: Some("file".to_string()),
+        };
+        backend
+            .set(&regional_key("new_table"), &value.as_bytes().unwrap())
+            .await
+            .unwrap();
+
+        migrate_remote_catalog(&backend).await.unwrap();
+        let expected = VersionEntry {
+            version: REMOTE_CATALOG_VERSION,
+            migrating_to: None,
+        };
+        assert_eq!(expected, read_version(&backend).await);
+        assert_eq!(
+            Some(MITO_ENGINE.to_string()),
+            read_engine(&backend, "old_table").await
+        );
+        assert_eq!(
+            Some("file".to_string()),
+            read_engine(&backend, "new_table").await
+        );
+
+        // Migrating again changes nothing.
+        migrate_remote_catalog(&backend).await.unwrap();
+        assert_eq!(expected, read_version(&backend).await);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_new_remote_catalog() {
+        let backend = Arc::new(MockKvBackend::default()) as KvBackendRef;
+        backend.delete_range(b"__", b"__~").await.unwrap();
+
+        migrate_remote_catalog(&backend).await.unwrap();
+        assert_eq!(
+            VersionEntry {
+                version: REMOTE_CATALOG_VERSION,
+                migrating_to: None,
+            },
+            read_version(&backend).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_interrupted_remote_migration() {
+        let backend = Arc::new(MockKvBackend::default()) as KvBackendRef;
+        put_old_regional_value(&backend, "t1").await;
+        put_old_regional_value(&backend, "t2").await;
+        // The interrupted migration has rewritten the value of one table.
+        let value = TableRegionalValue {
+            version: 0,
+            regions_ids: vec![0],
+            engine_name: Some(MITO_ENGINE.to_string()),
+        };
+        backend
+            .set(&regional_key("t1"), &value.as_bytes().unwrap())
+            .await
+            .unwrap();
+        set_version(&backend, 0, Some(1)).await;
+
+        migrate_remote_catalog(&backend).await.unwrap();
+        assert_eq!(
+            VersionEntry {
+                version: REMOTE_CATALOG_VERSION,
+                migrating_to: None,
+            },
+            read_version(&backend).await
+        );
+        for table_name in ["t1", "t2"] {
+            assert_eq!(
+                Some(MITO_ENGINE.to_string()),
+                read_engine(&backend, table_name).await
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_newer_remote_catalog_version() {
+        let backend = Arc::new(MockKvBackend::default()) as KvBackendRef;
+        set_version(&backend, REMOTE_CATALOG_VERSION + 1, None).await;
+
+        let err = migrate_remote_catalog(&backend).await.unwrap_err();
+        assert_matches!(err, Error::UnsupportedRemoteCatalogVersion { .. });
+        assert_eq!(
+            format!(
+                "Catalog format version {} in metasrv is newer than the version {} supported, please upgrade to a newer version",
+                REMOTE_CATALOG_VERSION + 1,
+                REMOTE_CATALOG_VERSION
+            ),
+            err.to_string()
+        );
+
+        // The catalog manager refuses to start.
+        let engine_manager = Arc::new(MemoryTableEngineManager::alias(
+            MITO_ENGINE.to_string(),
+            Arc::new(MockTableEngine::default()),
+        ));
+        let catalog_manager = RemoteCatalogManager::new(engine_manager, 42, backend.clone());
+        assert!(catalog_manager.start().await.is_err());
+        assert!(!catalog_manager.is_started());
+        assert_eq!(
+            REMOTE_CATALOG_VERSION + 1,
+            read_version(&backend).await.version
+        );
+    }
 }
@@ -12,23 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use api::v1::auth_header::AuthScheme;
 use api::v1::ddl_request::Expr as DdlExpr;
 use api::v1::greptime_request::Request;
 use api::v1::query_request::Query;
+use api::v1::row::{RowInsertRequest, RowInsertRequests};
 use api::v1::{
     greptime_response, AffectedRows, AlterExpr, AuthHeader, CreateTableExpr, DdlRequest,
     DeleteRequest, DropTableExpr, FlushTableExpr, GreptimeRequest, InsertRequest, PromRangeQuery,
     QueryRequest, RequestHeader,
 };
-use arrow_flight::{FlightData, Ticket};
+use arrow_flight::{Action, FlightData, Ticket};
+use common_base::commit_token::{CommitToken, COMMIT_TOKEN_HEADER};
+use common_base::validation_mode::{ValidationMode, VALIDATION_MODE_HEADER};
+use common_base::write_mode::{WriteMode, WRITE_MODE_HEADER};
+use common_base::AS_OF_HEADER;
 use common_error::prelude::*;
-use common_grpc::flight::{flight_messages_to_recordbatches, FlightDecoder, FlightMessage};
+use common_grpc::flight::{
+    flight_messages_to_recordbatches, FlightDecoder, FlightMessage, CREATE_TABLES_ACTION,
+    REGION_ACTION, ROW_INSERTS_ACTION,
+};
 use common_query::Output;
 use common_telemetry::{logging, timer};
 use futures_util::{TryFutureExt, TryStreamExt};
+use parking_lot::Mutex;
 use prost::Message;
 use snafu::{ensure, ResultExt};
+use table::requests::RegionRequest;
+use tonic::metadata::MetadataMap;
 
 use crate::error::{
     ConvertFlightDataSnafu, IllegalDatabaseResponseSnafu, IllegalFlightMessagesSnafu,
@@ -48,6 +61,17 @@ pub struct Database {
 
     client: Client,
     ctx: FlightContext,
+    // Commit token of the writes made through this client, shared by its clones. It's carried
+    // by every request so queries read these writes.
+    commit_token: Arc<Mutex<CommitToken>>,
+    // How the inserts through this client write the rows whose primary key and timestamp
+    // already exist.
+    write_mode: WriteMode,
+    // Whether the inserts through this client report all the invalid rows or only the first.
+    validation_mode: ValidationMode,
+    // Wall-clock time in millis the queries through this client read the snapshots of tables
+    // as of, or the latest snapshots if `None`.
+    as_of: Option<i64>,
 }
 
 impl Database {
@@ -106,6 +130,75 @@ impl Database {
         });
     }
 
+    pub fn set_write_mode(&mut self, write_mode: WriteMode) {
+        self.write_mode = write_mode;
+    }
+
+    pub fn set_validation_mode(&mut self, validation_mode: ValidationMode) {
+        self.validation_mode = validation_mode;
+    }
+
+    pub fn set_as_of(&mut self, as_of: Option<i64>) {
+        self.as_of = as_of;
+    }
+
+    /// Sets the commit token carried by the requests, e.g. the one of a session in the
+    /// frontend, so the queries read the writes in it. Stops sharing the token with the
+    /// clones made before.
+    pub fn set_commit_token(&mut self, commit_token: CommitToken) {
+        self.commit_token = Arc::new(Mutex::new(commit_token));
+    }
+
+    /// Returns the commit token of the writes made through this client so far.
+    pub fn commit_token(&self) -> CommitToken {
+        self.commit_token.lock().clone()
+    }
+
+    fn attach_commit_token(&self, metadata: &mut MetadataMap) {
+        let token = self.commit_token.lock();
+        if token.is_empty() {
+            return;
+        }
+        if let Ok(value) = token.to_string().parse() {
+            let _ = metadata.insert(COMMIT_TOKEN_HEADER, value);
+        }
+    }
+
+    fn attach_write_mode(&self, metadata: &mut MetadataMap) {
+        // Servers upsert if the write mode is absent, which keeps working with older servers.
+        if self.write_mode == WriteMode::Upsert {
+            return;
+        }
+        if let Ok(value) = self.write_mode.to_string().parse() {
+            let _ = metadata.insert(WRITE_MODE_HEADER, value);
+        }
+    }
+
+    fn attach_as_of(&self, metadata: &mut MetadataMap) {
+        if let Some(as_of) = self.as_of {
+            let _ = metadata.insert(AS_OF_HEADER, as_of.into());
+        }
+    }
+
+    fn attach_validation_mode(&self, metadata: &mut MetadataMap) {
+        if self.validation_mode == ValidationMode::Strict {
+            return;
+        }
+        if let Ok(value) = self.validation_mode.to_string().parse() {
+            let _ = metadata.insert(VALIDATION_MODE_HEADER, value);
+        }
+    }
+
+    fn merge_commit_token(&self, metadata: &MetadataMap) {
+        let token = metadata
+            .get(COMMIT_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<CommitToken>().ok());
+        if let Some(token) = token {
+            self.commit_token.lock().merge(&token);
+        }
+    }
+
     pub async fn insert(&self, request: InsertRequest) -> Result<u32> {
         let _timer = timer!(metrics::METRIC_GRPC_INSERT);
         self.handle(Request::Insert(request)).await
@@ -118,18 +211,15 @@ impl Database {
 
     async fn handle(&self, request: Request) -> Result<u32> {
         let mut client = self.client.make_database_client()?.inner;
-        let request = GreptimeRequest {
-            header: Some(RequestHeader {
-                catalog: self.catalog.clone(),
-                schema: self.schema.clone(),
-                authorization: self.ctx.auth_header.clone(),
-                dbname: self.dbname.clone(),
-            }),
-            request: Some(request),
-        };
-        let response = client
-            .handle(request)
-            .await?
+        let request = self.to_greptime_request(request);
+        let mut request = tonic::Request::new(request);
+        self.attach_commit_token(request.metadata_mut());
+        self.attach_write_mode(request.metadata_mut());
+        self.attach_validation_mode(request.metadata_mut());
+
+        let response = client.handle(request).await?;
+        self.merge_commit_token(response.metadata());
+        let response = response
             .into_inner()
             .response
             .context(IllegalDatabaseResponseSnafu {
@@ -206,21 +296,140 @@ impl Database {
         .await
     }
 
+    /// Creates the tables of the `CREATE TABLE` statements in `sql` all or nothing. Returns
+    /// the creation status of each table, a JSON object of its `table`, `status` and `error`.
+    pub async fn create_tables(&self, sql: &str) -> Result<Vec<serde_json::Value>> {
+        let request = self.to_greptime_request(Request::Query(QueryRequest {
+            query: Some(Query::Sql(sql.to_string())),
+        }));
+        let action = Action {
+            r#type: CREATE_TABLES_ACTION.to_string(),
+            body: request.encode_to_vec().into(),
+        };
+
+        let mut client = self.client.make_flight_client()?;
+        let results: Vec<arrow_flight::Result> = client
+            .mut_inner()
+            .do_action(action)
+            .and_then(|response| response.into_inner().try_collect())
+            .await
+            .map_err(|e| flight_error(e, client.addr(), "action"))?;
+
+        results
+            .into_iter()
+            .map(|result| {
+                serde_json::from_slice(&result.body).map_err(|e| {
+                    error::IllegalDatabaseResponseSnafu {
+                        err_msg: format!("invalid table creation: {e}"),
+                    }
+                    .build()
+                })
+            })
+            .collect()
+    }
+
+    /// Inserts the rows of `requests`, the same way as [insert](Self::insert) inserts the
+    /// columns. Returns the number of rows inserted.
+    pub async fn row_insert(&self, requests: Vec<RowInsertRequest>) -> Result<u32> {
+        let _timer = timer!(metrics::METRIC_GRPC_INSERT);
+        let request = RowInsertRequests {
+            header: Some(self.request_header()),
+            inserts: requests,
+        };
+        let mut request = tonic::Request::new(Action {
+            r#type: ROW_INSERTS_ACTION.to_string(),
+            body: request.encode_to_vec().into(),
+        });
+        self.attach_commit_token(request.metadata_mut());
+        self.attach_write_mode(request.metadata_mut());
+        self.attach_validation_mode(request.metadata_mut());
+
+        let mut client = self.client.make_flight_client()?;
+        let results: Vec<arrow_flight::Result> = client
+            .mut_inner()
+            .do_action(request)
+            .and_then(|response| {
+                self.merge_commit_token(response.metadata());
+                response.into_inner().try_collect()
+            })
+            .await
+            .map_err(|e| flight_error(e, client.addr(), "action"))?;
+
+        let [result] = results.as_slice() else {
+            return IllegalDatabaseResponseSnafu {
+                err_msg: format!("expect one result of row inserts, got {}", results.len()),
+            }
+            .fail();
+        };
+        serde_json::from_slice(&result.body).map_err(|e| {
+            IllegalDatabaseResponseSnafu {
+                err_msg: format!("invalid affected rows: {e}"),
+            }
+            .build()
+        })
+    }
+
+    /// Sends the `request` to a region of a table, to the datanode serving the region. Returns
+    /// the watermark of the region if the request returns one.
+    pub async fn region_request(&self, request: &RegionRequest) -> Result<Option<u64>> {
+        let body = serde_json::to_vec(request).map_err(|e| {
+            error::IllegalDatabaseResponseSnafu {
+                err_msg: format!("invalid region request: {e}"),
+            }
+            .build()
+        })?;
+        let action = Action {
+            r#type: REGION_ACTION.to_string(),
+            body: body.into(),
+        };
+
+        let mut client = self.client.make_flight_client()?;
+        let results: Vec<arrow_flight::Result> = client
+            .mut_inner()
+            .do_action(action)
+            .and_then(|response| response.into_inner().try_collect())
+            .await
+            .map_err(|e| flight_error(e, client.addr(), "action"))?;
+
+        let [result] = results.as_slice() else {
+            return IllegalDatabaseResponseSnafu {
+                err_msg: format!("expect one result of region request, got {}", results.len()),
+            }
+            .fail();
+        };
+        serde_json::from_slice(&result.body).map_err(|e| {
+            IllegalDatabaseResponseSnafu {
+                err_msg: format!("invalid watermark: {e}"),
+            }
+            .build()
+        })
+    }
+
+    fn to_greptime_request(&self, request: Request) -> GreptimeRequest {
+        GreptimeRequest {
+            header: Some(self.request_header()),
+            request: Some(request),
+        }
+    }
+
+    fn request_header(&self) -> RequestHeader {
+        RequestHeader {
+            catalog: self.catalog.clone(),
+            schema: self.schema.clone(),
+            authorization: self.ctx.auth_header.clone(),
+            dbname: self.dbname.clone(),
+        }
+    }
+
     async fn do_get(&self, request: Request) -> Result<Output> {
         // FIXME(paomian): should be added some labels for metrics
         let _timer = timer!(metrics::METRIC_GRPC_DO_GET);
-        let request = GreptimeRequest {
-            header: Some(RequestHeader {
-                catalog: self.catalog.clone(),
-                schema: self.schema.clone(),
-                authorization: self.ctx.auth_header.clone(),
-                dbname: self.dbname.clone(),
-            }),
-            request: Some(request),
-        };
-        let request = Ticket {
+        let request = self.to_greptime_request(request);
+        let mut request = tonic::Request::new(Ticket {
             ticket: request.encode_to_vec().into(),
-        };
+        });
+        self.attach_commit_token(request.metadata_mut());
+        self.attach_as_of(request.metadata_mut());
 
         let mut client = self.client.make_flight_client()?;
 
@@ -228,31 +437,12 @@ impl Database {
         let flight_data: Vec<FlightData> = client
             .mut_inner()
             .do_get(request)
-            .and_then(|response| response.into_inner().try_collect())
+            .and_then(|response| {
+                self.merge_commit_token(response.metadata());
+                response.into_inner().try_collect()
+            })
             .await
-            .map_err(|e| {
-                let tonic_code = e.code();
-                let e: error::Error = e.into();
-                let code = e.status_code();
-                let msg = e.to_string();
-                error::ServerSnafu { code, msg }
-                    .fail::<()>()
-                    .map_err(BoxedError::new)
-                    .context(error::FlightGetSnafu {
-                        tonic_code,
-                        addr: client.addr(),
-                    })
-                    .map_err(|error| {
-                        logging::error!(
-                            "Failed to do Flight get, addr: {}, code: {}, source: {}",
-                            client.addr(),
-                            tonic_code,
-                            error
-                        );
-                        error
-                    })
-                    .unwrap_err()
-            })?;
+            .map_err(|e| flight_error(e, client.addr(), "get"))?;
 
         let decoder = &mut FlightDecoder::default();
         let flight_messages = flight_data
@@ -277,6 +467,27 @@ impl Database {
     }
 }
 
+/// Converts the status of a failed Flight `call` to the error.
+fn flight_error(e: tonic::Status, addr: &str, call: &str) -> error::Error {
+    let tonic_code = e.code();
+    let e: error::Error = e.into();
+    let code = e.status_code();
+    let msg = e.to_string();
+    let error = error::ServerSnafu { code, msg }
+        .fail::<()>()
+        .map_err(BoxedError::new)
+        .context(error::FlightGetSnafu { tonic_code, addr })
+        .unwrap_err();
+    logging::error!(
+        "Failed to do Flight {}, addr: {}, code: {}, source: {}",
+        call,
+        addr,
+        tonic_code,
+        error
+    );
+    error
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct FlightContext {
     auth_header: Option<AuthHeader>,
@@ -289,6 +500,10 @@ mod tests {
     use api::helper::ColumnDataTypeWrapper;
     use api::v1::auth_header::AuthScheme;
     use api::v1::{AuthHeader, Basic, Column};
+    use common_base::commit_token::COMMIT_TOKEN_HEADER;
+    use common_base::validation_mode::{ValidationMode, VALIDATION_MODE_HEADER};
+    use common_base::write_mode::{WriteMode, WRITE_MODE_HEADER};
+    use common_base::AS_OF_HEADER;
     use common_grpc::select::{null_mask, values};
     use common_grpc_expr::column_to_vector;
     use datatypes::prelude::{Vector, VectorRef};
@@ -297,8 +512,9 @@ mod tests {
         Int16Vector, Int32Vector, Int64Vector, Int8Vector, StringVector, UInt16Vector,
         UInt32Vector, UInt64Vector, UInt8Vector,
     };
+    use tonic::metadata::MetadataMap;
 
-    use crate::database::FlightContext;
+    use crate::database::{Database, FlightContext};
 
     #[test]
     fn test_column_to_vector() {
@@ -405,4 +621,61 @@ mod tests {
             })
         ))
     }
+
+    #[test]
+    fn test_commit_token_metadata() {
+        let db = Database::default();
+        let mut metadata = MetadataMap::new();
+        db.attach_commit_token(&mut metadata);
+        assert!(metadata.get(COMMIT_TOKEN_HEADER).is_none());
+
+        let mut response = MetadataMap::new();
+        let _ = response.insert(COMMIT_TOKEN_HEADER, "1:3,2:1".parse().unwrap());
+        db.merge_commit_token(&response);
+        let _ = response.insert(COMMIT_TOKEN_HEADER, "1:2".parse().unwrap());
+        // Clones share the commit token.
+        db.clone().merge_commit_token(&response);
+        let _ = response.insert(COMMIT_TOKEN_HEADER, "invalid".parse().unwrap());
+        db.merge_commit_token(&response);
+        assert_eq!("1:3,2:1", db.commit_token().to_string());
+
+        db.attach_commit_token(&mut metadata);
+        assert_eq!("1:3,2:1", metadata.get(COMMIT_TOKEN_HEADER).unwrap());
+    }
+
+    #[test]
+    fn test_write_mode_metadata() {
+        let mut db = Database::default();
+        let mut metadata = MetadataMap::new();
+        db.attach_write_mode(&mut metadata);
+        assert!(metadata.get(WRITE_MODE_HEADER).is_none());
+
+        db.set_write_mode(WriteMode::InsertIgnore);
+        db.attach_write_mode(&mut metadata);
+        assert_eq!("insert_ignore", metadata.get(WRITE_MODE_HEADER).unwrap());
+    }
+
+    #[test]
+    fn test_validation_mode_metadata() {
+        let mut db = Database::default();
+        let mut metadata = MetadataMap::new();
+        db.attach_validation_mode(&mut metadata);
+        assert!(metadata.get(VALIDATION_MODE_HEADER).is_none());
+
+        db.set_validation_mode(ValidationMode::Lenient);
+        db.attach_validation_mode(&mut metadata);
+        assert_eq!("lenient", metadata.get(VALIDATION_MODE_HEADER).unwrap());
+    }
+
+    #[test]
+    fn test_as_of_metadata() {
+        let mut db = Database::default();
+        let mut metadata = MetadataMap::new();
+        db.attach_as_of(&mut metadata);
+        assert!(metadata.get(AS_OF_HEADER).is_none());
+
+        db.set_as_of(Some(1681293600123));
+        db.attach_as_of(&mut metadata);
+        assert_eq!("1681293600123", metadata.get(AS_OF_HEADER).unwrap());
+    }
 }
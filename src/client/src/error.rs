@@ -16,8 +16,10 @@ use std::any::Any;
 use std::str::FromStr;
 
 use common_error::prelude::*;
+use common_grpc_expr::validation::{RowErrors, INVALID_ROWS_REASON, ROW_ERRORS_KEY};
 use snafu::Location;
 use tonic::{Code, Status};
+use tonic_types::StatusExt;
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub))]
@@ -63,7 +65,12 @@ pub enum Error {
 
     // Server error carried in Tonic Status's metadata.
     #[snafu(display("{}", msg))]
-    Server { code: StatusCode, msg: String },
+    Server {
+        code: StatusCode,
+        msg: String,
+        // Errors of the rows if the insertion is rejected for its invalid rows.
+        row_errors: Option<RowErrors>,
+    },
 
     #[snafu(display("Illegal Database response: {err_msg}"))]
     IllegalDatabaseResponse { err_msg: String },
@@ -93,6 +100,16 @@ impl ErrorExt for Error {
     }
 }
 
+impl Error {
+    /// Returns the errors of the rows if the insertion is rejected for its invalid rows.
+    pub fn row_errors(&self) -> Option<&RowErrors> {
+        match self {
+            Error::Server { row_errors, .. } => row_errors.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 impl From<Status> for Error {
     fn from(e: Status) -> Self {
         fn get_metadata_value(e: &Status, key: &str) -> Option<String> {
@@ -107,6 +124,54 @@ impl From<Status> for Error {
 
         let msg = get_metadata_value(&e, INNER_ERROR_MSG).unwrap_or(e.to_string());
 
-        Self::Server { code, msg }
+        let row_errors = e
+            .get_details_error_info()
+            .filter(|info| info.reason == INVALID_ROWS_REASON)
+            .and_then(|info| {
+                let row_errors = info.metadata.get(ROW_ERRORS_KEY)?;
+                serde_json::from_str::<RowErrors>(row_errors).ok()
+            });
+
+        Self::Server {
+            code,
+            msg,
+            row_errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common_grpc_expr::validation::{RowError, ERROR_INFO_DOMAIN};
+    use tonic_types::ErrorDetails;
+
+    use super::*;
+
+    #[test]
+    fn test_row_errors_from_status() {
+        let row_errors = RowErrors {
+            errors: vec![RowError::new(
+                1,
+                "host",
+                None,
+                "null value in not null column",
+            )],
+            incomplete: false,
+        };
+        let details = ErrorDetails::with_error_info(
+            INVALID_ROWS_REASON,
+            ERROR_INFO_DOMAIN,
+            HashMap::from([(
+                ROW_ERRORS_KEY.to_string(),
+                serde_json::to_string(&row_errors).unwrap(),
+            )]),
+        );
+        let status = Status::with_error_details(Code::Internal, "invalid rows", details);
+        assert_eq!(Some(&row_errors), Error::from(status).row_errors());
+
+        let status = Status::new(Code::Internal, "other error");
+        assert!(Error::from(status).row_errors().is_none());
     }
 }
@@ -24,7 +24,10 @@ use servers::Mode;
 use snafu::ResultExt;
 
 use crate::error::{Error, MissingConfigSnafu, Result, ShutdownDatanodeSnafu, StartDatanodeSnafu};
-use crate::toml_loader;
+use crate::options::{load_options, print_config_sample};
+
+/// Prefix of the environment variables overriding the options of datanode.
+const ENV_PREFIX: &str = "GREPTIMEDB_DATANODE";
 
 pub struct Instance {
     datanode: Datanode,
@@ -92,10 +95,19 @@ struct StartCommand {
     http_addr: Option<String>,
     #[clap(long)]
     http_timeout: Option<u64>,
+    /// Prints a sample config file of the default options and exits.
+    #[clap(long)]
+    print_config_sample: bool,
+    /// Ignores the unknown keys in the config file instead of rejecting them.
+    #[clap(long)]
+    allow_unknown_config: bool,
 }
 
 impl StartCommand {
     async fn build(self) -> Result<Instance> {
+        if self.print_config_sample {
+            print_config_sample::<DatanodeOptions>()?;
+        }
         logging::info!("Datanode start command: {:#?}", self);
 
         let opts: DatanodeOptions = self.try_into()?;
@@ -111,38 +123,59 @@ impl StartCommand {
 impl TryFrom<StartCommand> for DatanodeOptions {
     type Error = Error;
     fn try_from(cmd: StartCommand) -> Result<Self> {
-        let mut opts: DatanodeOptions = if let Some(path) = cmd.config_file {
-            toml_loader::from_file!(&path)?
-        } else {
-            DatanodeOptions::default()
-        };
-
-        if let Some(addr) = cmd.rpc_addr {
-            opts.rpc_addr = addr;
-        }
-
-        if cmd.rpc_hostname.is_some() {
-            opts.rpc_hostname = cmd.rpc_hostname;
-        }
-
-        if let Some(addr) = cmd.mysql_addr {
-            opts.mysql_addr = addr;
-        }
-
-        if let Some(node_id) = cmd.node_id {
-            opts.node_id = Some(node_id);
-        }
-
-        if let Some(meta_addr) = cmd.metasrv_addr {
-            opts.meta_client_options
-                .get_or_insert_with(MetaClientOptions::default)
-                .metasrv_addrs = meta_addr
-                .split(',')
-                .map(&str::trim)
-                .map(&str::to_string)
-                .collect::<_>();
-            opts.mode = Mode::Distributed;
-        }
+        let opts: DatanodeOptions = load_options(
+            ENV_PREFIX,
+            cmd.config_file.as_deref(),
+            cmd.allow_unknown_config,
+            |opts: &mut DatanodeOptions| {
+                if let Some(addr) = cmd.rpc_addr {
+                    opts.rpc_addr = addr;
+                }
+
+                if cmd.rpc_hostname.is_some() {
+                    opts.rpc_hostname = cmd.rpc_hostname;
+                }
+
+                if let Some(addr) = cmd.mysql_addr {
+                    opts.mysql_addr = addr;
+                }
+
+                if let Some(node_id) = cmd.node_id {
+                    opts.node_id = Some(node_id);
+                }
+
+                if let Some(meta_addr) = cmd.metasrv_addr {
+                    opts.meta_client_options
+                        .get_or_insert_with(MetaClientOptions::default)
+                        .metasrv_addrs = meta_addr
+                        .split(',')
+                        .map(&str::trim)
+                        .map(&str::to_string)
+                        .collect::<_>();
+                    opts.mode = Mode::Distributed;
+                }
+
+                if let Some(data_dir) = cmd.data_dir {
+                    opts.storage.store = ObjectStoreConfig::File(FileConfig { data_dir });
+                }
+
+                if let Some(wal_dir) = cmd.wal_dir {
+                    opts.wal.dir = wal_dir;
+                }
+                if let Some(procedure_dir) = cmd.procedure_dir {
+                    opts.procedure = ProcedureConfig::from_file_path(procedure_dir);
+                }
+                if let Some(http_addr) = cmd.http_addr {
+                    opts.http_opts.addr = http_addr
+                }
+                if let Some(http_timeout) = cmd.http_timeout {
+                    opts.http_opts.timeout = Duration::from_secs(http_timeout)
+                }
+
+                // Disable dashboard in datanode.
+                opts.http_opts.disable_dashboard = true;
+            },
+        )?;
 
         if let (Mode::Distributed, None) = (&opts.mode, &opts.node_id) {
             return MissingConfigSnafu {
@@ -151,26 +184,6 @@ impl TryFrom<StartCommand> for DatanodeOptions {
             .fail();
         }
 
-        if let Some(data_dir) = cmd.data_dir {
-            opts.storage.store = ObjectStoreConfig::File(FileConfig { data_dir });
-        }
-
-        if let Some(wal_dir) = cmd.wal_dir {
-            opts.wal.dir = wal_dir;
-        }
-        if let Some(procedure_dir) = cmd.procedure_dir {
-            opts.procedure = ProcedureConfig::from_file_path(procedure_dir);
-        }
-        if let Some(http_addr) = cmd.http_addr {
-            opts.http_opts.addr = http_addr
-        }
-        if let Some(http_timeout) = cmd.http_timeout {
-            opts.http_opts.timeout = Duration::from_secs(http_timeout)
-        }
-
-        // Disable dashboard in datanode.
-        opts.http_opts.disable_dashboard = true;
-
         Ok(opts)
     }
 }
@@ -183,7 +196,10 @@ mod tests {
 
     use common_base::readable_size::ReadableSize;
     use common_test_util::temp_dir::create_named_temp_file;
-    use datanode::datanode::{CompactionConfig, ObjectStoreConfig, RegionManifestConfig};
+    use datanode::datanode::{
+        CompactionConfig, FlushConfig, ObjectStoreConfig, OrphanGcConfig, RecycleBinConfig,
+        RegionManifestConfig, StorageUsageConfig, TagDictionaryConfig, TimeTravelConfig,
+    };
     use servers::Mode;
 
     use super::*;
@@ -214,6 +230,7 @@ mod tests {
             purge_interval = "10m"
             read_batch_size = 128
             sync_write = false
+            allow_disabling = false
 
             [storage]
             type = "File"
@@ -228,6 +245,32 @@ mod tests {
             checkpoint_margin = 9
             gc_duration = '7s'
             checkpoint_on_startup = true
+
+            [storage.flush]
+            adaptive = true
+            target_interval = '5m'
+            max_age = '30m'
+            max_write_stall = '5s'
+
+            [storage.usage]
+            sample_interval = '1m'
+
+            [storage.recycle_bin]
+            enable = true
+            retention = '1d'
+
+            [storage.orphan_gc]
+            enable = true
+            safety_age = '1d'
+            max_deletions_per_sec = 10
+
+            [storage.tag_dictionary]
+            enable = true
+            max_values_per_column = 1024
+            encode_scans = true
+
+            [storage.time_travel]
+            retention = '5m'
         "#;
         write!(file, "{}", toml_str).unwrap();
 
@@ -245,18 +288,21 @@ mod tests {
         assert_eq!(1024 * 1024 * 1024, options.wal.file_size.0);
         assert_eq!(1024 * 1024 * 1024 * 50, options.wal.purge_threshold.0);
         assert!(!options.wal.sync_write);
+        assert!(!options.wal.allow_disabling);
 
         let MetaClientOptions {
             metasrv_addrs: metasrv_addr,
             timeout_millis,
             connect_timeout_millis,
             tcp_nodelay,
+            max_retries,
         } = options.meta_client_options.unwrap();
 
         assert_eq!(vec!["127.0.0.1:3002".to_string()], metasrv_addr);
         assert_eq!(5000, connect_timeout_millis);
         assert_eq!(3000, timeout_millis);
         assert!(tcp_nodelay);
+        assert_eq!(3, max_retries);
 
         match &options.storage.store {
             ObjectStoreConfig::File(FileConfig { data_dir, .. }) => {
@@ -283,6 +329,55 @@ mod tests {
             },
             options.storage.manifest,
         );
+        assert_eq!(
+            FlushConfig {
+                adaptive: true,
+                target_interval: Duration::from_secs(300),
+                min_write_buffer_size: ReadableSize::mb(4),
+                max_write_buffer_size: ReadableSize::mb(256),
+                max_age: Duration::from_secs(1800),
+                age_check_interval: Duration::from_secs(60),
+                max_write_stall: Some(Duration::from_secs(5)),
+            },
+            options.storage.flush,
+        );
+        assert_eq!(
+            StorageUsageConfig {
+                sample_interval: Duration::from_secs(60),
+            },
+            options.storage.usage,
+        );
+        assert_eq!(
+            RecycleBinConfig {
+                enable: true,
+                retention: Duration::from_secs(24 * 60 * 60),
+                reap_interval: Duration::from_secs(60 * 60),
+            },
+            options.storage.recycle_bin,
+        );
+        assert_eq!(
+            OrphanGcConfig {
+                enable: true,
+                interval: Duration::from_secs(24 * 60 * 60),
+                safety_age: Duration::from_secs(24 * 60 * 60),
+                max_deletions_per_sec: Some(10),
+            },
+            options.storage.orphan_gc,
+        );
+        assert_eq!(
+            TagDictionaryConfig {
+                enable: true,
+                max_values_per_column: 1024,
+                encode_scans: true,
+            },
+            options.storage.tag_dictionary,
+        );
+        assert_eq!(
+            TimeTravelConfig {
+                retention: Duration::from_secs(5 * 60),
+            },
+            options.storage.time_travel,
+        );
     }
 
     #[test]
@@ -316,4 +411,70 @@ mod tests {
         })
         .unwrap();
     }
+    #[test]
+    fn test_invalid_config_file() {
+        let mut file = create_named_temp_file();
+        let toml_str = r#"
+            rpc_addr = "127.0.0.1:3001"
+
+            [storage]
+            type = "S3"
+            bucket = "greptimedb"
+            access_key_id = "access_key_id"
+        "#;
+        write!(file, "{}", toml_str).unwrap();
+        let path = file.path().to_str().unwrap().to_string();
+
+        let err = DatanodeOptions::try_from(StartCommand {
+            config_file: Some(path.clone()),
+            ..Default::default()
+        })
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("storage.secret_access_key"), "{err}");
+        assert!(err.contains(&path), "{err}");
+
+        let mut file = create_named_temp_file();
+        write!(
+            file,
+            "rpc_addr = \"127.0.0.1:3001\"\nrpc_adr = \"127.0.0.1:3001\"\n"
+        )
+        .unwrap();
+        let cmd = StartCommand {
+            config_file: Some(file.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let err = DatanodeOptions::try_from(cmd).unwrap_err().to_string();
+        assert!(err.contains("rpc_adr"), "{err}");
+
+        let cmd = StartCommand {
+            config_file: Some(file.path().to_str().unwrap().to_string()),
+            allow_unknown_config: true,
+            ..Default::default()
+        };
+        DatanodeOptions::try_from(cmd).unwrap();
+
+        // The keys of the object store flattened into the storage options.
+        let mut file = create_named_temp_file();
+        write!(
+            file,
+            "[storage]\ntype = \"File\"\ndata_dir = \"/tmp/greptimedb/data\"\ndatadir = \"/tmp\"\n"
+        )
+        .unwrap();
+        let cmd = StartCommand {
+            config_file: Some(file.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let err = DatanodeOptions::try_from(cmd).unwrap_err().to_string();
+        assert!(err.contains("storage.datadir"), "{err}");
+
+        // The command line arguments are validated too.
+        let err = DatanodeOptions::try_from(StartCommand {
+            http_addr: Some("127.0.0.1:70000".to_string()),
+            ..Default::default()
+        })
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("http_opts.addr"), "{err}");
+    }
 }
@@ -63,17 +63,16 @@ pub enum Error {
         source: meta_srv::error::Error,
     },
 
-    #[snafu(display("Failed to read config file: {}, source: {}", path, source))]
-    ReadConfig {
-        path: String,
-        source: std::io::Error,
-        location: Location,
+    #[snafu(display("Failed to load config, source: {}", source))]
+    LoadConfig {
+        #[snafu(backtrace)]
+        source: common_config::error::Error,
     },
 
-    #[snafu(display("Failed to parse config, source: {}", source))]
-    ParseConfig {
-        source: toml::de::Error,
-        location: Location,
+    #[snafu(display("Failed to generate config sample, source: {}", source))]
+    GenerateConfigSample {
+        #[snafu(backtrace)]
+        source: common_config::error::Error,
     },
 
     #[snafu(display("Missing config, msg: {}", msg))]
@@ -168,9 +167,10 @@ impl ErrorExt for Error {
             Error::ShutdownMetaServer { source } => source.status_code(),
             Error::BuildMetaServer { source } => source.status_code(),
             Error::UnsupportedSelectorType { source, .. } => source.status_code(),
-            Error::ReadConfig { .. } | Error::ParseConfig { .. } | Error::MissingConfig { .. } => {
-                StatusCode::InvalidArguments
+            Error::LoadConfig { source } | Error::GenerateConfigSample { source } => {
+                source.status_code()
             }
+            Error::MissingConfig { .. } => StatusCode::InvalidArguments,
             Error::IllegalConfig { .. } | Error::InvalidReplCommand { .. } => {
                 StatusCode::InvalidArguments
             }
@@ -25,13 +25,18 @@ use frontend::opentsdb::OpentsdbOptions;
 use frontend::postgres::PostgresOptions;
 use frontend::prom::PromOptions;
 use meta_client::MetaClientOptions;
-use servers::auth::UserProviderRef;
+use query::query_engine::options::QueryOptions;
+use servers::auth::authorizer::{StatementAuthorizerOptions, StatementAuthorizerRef};
+use servers::auth::{authorizer, UserProviderRef};
 use servers::tls::{TlsMode, TlsOption};
 use servers::{auth, Mode};
 use snafu::ResultExt;
 
 use crate::error::{self, IllegalAuthConfigSnafu, Result};
-use crate::toml_loader;
+use crate::options::{load_options, print_config_sample};
+
+/// Prefix of the environment variables overriding the options of frontend.
+const ENV_PREFIX: &str = "GREPTIMEDB_FRONTEND";
 
 pub struct Instance {
     frontend: FeInstance,
@@ -108,12 +113,28 @@ pub struct StartCommand {
     user_provider: Option<String>,
     #[clap(long)]
     disable_dashboard: Option<bool>,
+    /// Prints a sample config file of the default options and exits.
+    #[clap(long)]
+    print_config_sample: bool,
+    /// Ignores the unknown keys in the config file instead of rejecting them.
+    #[clap(long)]
+    allow_unknown_config: bool,
 }
 
 impl StartCommand {
     async fn build(self) -> Result<Instance> {
-        let plugins = Arc::new(load_frontend_plugins(&self.user_provider)?);
+        if self.print_config_sample {
+            print_config_sample::<FrontendOptions>()?;
+        }
+        let user_provider = self.user_provider.clone();
         let opts: FrontendOptions = self.try_into()?;
+        let mut plugins =
+            load_frontend_plugins(&user_provider, opts.statement_authorizer.as_ref())?;
+        plugins.insert(QueryOptions {
+            reject_expired_ranges: opts.reject_expired_ranges,
+            ..Default::default()
+        });
+        let plugins = Arc::new(plugins);
 
         let mut instance = FeInstance::try_new_distributed(&opts, plugins.clone())
             .await
@@ -128,13 +149,21 @@ impl StartCommand {
     }
 }
 
-pub fn load_frontend_plugins(user_provider: &Option<String>) -> Result<Plugins> {
+pub fn load_frontend_plugins(
+    user_provider: &Option<String>,
+    statement_authorizer: Option<&StatementAuthorizerOptions>,
+) -> Result<Plugins> {
     let mut plugins = Plugins::new();
 
     if let Some(provider) = user_provider {
         let provider = auth::user_provider_from_option(provider).context(IllegalAuthConfigSnafu)?;
         plugins.insert::<UserProviderRef>(provider);
     }
+    if let Some(opts) = statement_authorizer {
+        let authorizer =
+            authorizer::statement_authorizer_from_options(opts).context(IllegalAuthConfigSnafu)?;
+        plugins.insert::<StatementAuthorizerRef>(authorizer);
+    }
     Ok(plugins)
 }
 
@@ -142,68 +171,71 @@ impl TryFrom<StartCommand> for FrontendOptions {
     type Error = error::Error;
 
     fn try_from(cmd: StartCommand) -> Result<Self> {
-        let mut opts: FrontendOptions = if let Some(path) = cmd.config_file {
-            toml_loader::from_file!(&path)?
-        } else {
-            FrontendOptions::default()
-        };
-
-        let tls_option = TlsOption::new(cmd.tls_mode, cmd.tls_cert_path, cmd.tls_key_path);
-
-        if let Some(addr) = cmd.http_addr {
-            opts.http_options.get_or_insert_with(Default::default).addr = addr;
-        }
-
-        if let Some(disable_dashboard) = cmd.disable_dashboard {
-            opts.http_options
-                .get_or_insert_with(Default::default)
-                .disable_dashboard = disable_dashboard;
-        }
-
-        if let Some(addr) = cmd.grpc_addr {
-            opts.grpc_options = Some(GrpcOptions {
-                addr,
-                ..Default::default()
-            });
-        }
-
-        if let Some(addr) = cmd.mysql_addr {
-            opts.mysql_options = Some(MysqlOptions {
-                addr,
-                tls: tls_option.clone(),
-                ..Default::default()
-            });
-        }
-        if let Some(addr) = cmd.prom_addr {
-            opts.prom_options = Some(PromOptions { addr });
-        }
-        if let Some(addr) = cmd.postgres_addr {
-            opts.postgres_options = Some(PostgresOptions {
-                addr,
-                tls: tls_option,
-                ..Default::default()
-            });
-        }
-        if let Some(addr) = cmd.opentsdb_addr {
-            opts.opentsdb_options = Some(OpentsdbOptions {
-                addr,
-                ..Default::default()
-            });
-        }
-        if let Some(enable) = cmd.influxdb_enable {
-            opts.influxdb_options = Some(InfluxdbOptions { enable });
-        }
-        if let Some(metasrv_addr) = cmd.metasrv_addr {
-            opts.meta_client_options
-                .get_or_insert_with(MetaClientOptions::default)
-                .metasrv_addrs = metasrv_addr
-                .split(',')
-                .map(&str::trim)
-                .map(&str::to_string)
-                .collect::<Vec<_>>();
-            opts.mode = Mode::Distributed;
-        }
-        Ok(opts)
+        load_options(
+            ENV_PREFIX,
+            cmd.config_file.as_deref(),
+            cmd.allow_unknown_config,
+            |opts: &mut FrontendOptions| {
+                let tls_option = TlsOption::new(cmd.tls_mode, cmd.tls_cert_path, cmd.tls_key_path);
+
+                if let Some(addr) = cmd.http_addr {
+                    opts.http_options.get_or_insert_with(Default::default).addr = addr;
+                }
+
+                if let Some(disable_dashboard) = cmd.disable_dashboard {
+                    opts.http_options
+                        .get_or_insert_with(Default::default)
+                        .disable_dashboard = disable_dashboard;
+                }
+
+                if let Some(addr) = cmd.grpc_addr {
+                    opts.grpc_options = Some(GrpcOptions {
+                        addr,
+                        ..Default::default()
+                    });
+                }
+
+                if let Some(addr) = cmd.mysql_addr {
+                    opts.mysql_options = Some(MysqlOptions {
+                        addr,
+                        tls: tls_option.clone(),
+                        ..Default::default()
+                    });
+                }
+                if let Some(addr) = cmd.prom_addr {
+                    opts.prom_options = Some(PromOptions {
+                        addr,
+                        ..Default::default()
+                    });
+                }
+                if let Some(addr) = cmd.postgres_addr {
+                    opts.postgres_options = Some(PostgresOptions {
+                        addr,
+                        tls: tls_option,
+                        ..Default::default()
+                    });
+                }
+                if let Some(addr) = cmd.opentsdb_addr {
+                    opts.opentsdb_options = Some(OpentsdbOptions {
+                        addr,
+                        ..Default::default()
+                    });
+                }
+                if let Some(enable) = cmd.influxdb_enable {
+                    opts.influxdb_options = Some(InfluxdbOptions { enable });
+                }
+                if let Some(metasrv_addr) = cmd.metasrv_addr {
+                    opts.meta_client_options
+                        .get_or_insert_with(MetaClientOptions::default)
+                        .metasrv_addrs = metasrv_addr
+                        .split(',')
+                        .map(&str::trim)
+                        .map(&str::to_string)
+                        .collect::<Vec<_>>();
+                    opts.mode = Mode::Distributed;
+                }
+            },
+        )
     }
 }
 
@@ -234,6 +266,8 @@ mod tests {
             tls_key_path: None,
             user_provider: None,
             disable_dashboard: Some(false),
+            print_config_sample: false,
+            allow_unknown_config: false,
         };
 
         let opts: FrontendOptions = command.try_into().unwrap();
@@ -279,6 +313,12 @@ mod tests {
             [http_options]
             addr = "127.0.0.1:4000"
             timeout = "30s"
+
+            [column_limits]
+            max_columns = 2048
+
+            [column_limits.schemas.metrics]
+            max_added_columns = 16
         "#;
         write!(file, "{}", toml_str).unwrap();
 
@@ -297,6 +337,8 @@ mod tests {
             tls_key_path: None,
             user_provider: None,
             disable_dashboard: Some(false),
+            print_config_sample: false,
+            allow_unknown_config: false,
         };
 
         let fe_opts = FrontendOptions::try_from(command).unwrap();
@@ -309,6 +351,13 @@ mod tests {
             Duration::from_secs(30),
             fe_opts.http_options.as_ref().unwrap().timeout
         );
+
+        let limits = fe_opts.column_limits.limits_of("public");
+        assert_eq!(2048, limits.max_columns);
+        assert_eq!(256, limits.max_added_columns);
+        let limits = fe_opts.column_limits.limits_of("metrics");
+        assert_eq!(2048, limits.max_columns);
+        assert_eq!(16, limits.max_added_columns);
     }
 
     #[tokio::test]
@@ -328,9 +377,11 @@ mod tests {
             tls_key_path: None,
             user_provider: Some("static_user_provider:cmd:test=test".to_string()),
             disable_dashboard: Some(false),
+            print_config_sample: false,
+            allow_unknown_config: false,
         };
 
-        let plugins = load_frontend_plugins(&command.user_provider);
+        let plugins = load_frontend_plugins(&command.user_provider, None);
         assert!(plugins.is_ok());
         let plugins = plugins.unwrap();
         let provider = plugins.get::<UserProviderRef>();
@@ -19,5 +19,5 @@ pub mod datanode;
 pub mod error;
 pub mod frontend;
 pub mod metasrv;
+mod options;
 pub mod standalone;
-mod toml_loader;
@@ -18,10 +18,14 @@ use clap::Parser;
 use common_telemetry::{info, logging, warn};
 use meta_srv::bootstrap::MetaSrvInstance;
 use meta_srv::metasrv::MetaSrvOptions;
+use meta_srv::selector::SelectorType;
 use snafu::ResultExt;
 
-use crate::error::{Error, Result};
-use crate::{error, toml_loader};
+use crate::error::{self, Error, Result};
+use crate::options::{load_options, print_config_sample};
+
+/// Prefix of the environment variables overriding the options of metasrv.
+const ENV_PREFIX: &str = "GREPTIMEDB_METASRV";
 
 pub struct Instance {
     instance: MetaSrvInstance,
@@ -86,10 +90,19 @@ struct StartCommand {
     http_addr: Option<String>,
     #[clap(long)]
     http_timeout: Option<u64>,
+    /// Prints a sample config file of the default options and exits.
+    #[clap(long)]
+    print_config_sample: bool,
+    /// Ignores the unknown keys in the config file instead of rejecting them.
+    #[clap(long)]
+    allow_unknown_config: bool,
 }
 
 impl StartCommand {
     async fn build(self) -> Result<Instance> {
+        if self.print_config_sample {
+            print_config_sample::<MetaSrvOptions>()?;
+        }
         logging::info!("MetaSrv start command: {:#?}", self);
 
         let opts: MetaSrvOptions = self.try_into()?;
@@ -107,44 +120,50 @@ impl TryFrom<StartCommand> for MetaSrvOptions {
     type Error = Error;
 
     fn try_from(cmd: StartCommand) -> Result<Self> {
-        let mut opts: MetaSrvOptions = if let Some(path) = cmd.config_file {
-            toml_loader::from_file!(&path)?
-        } else {
-            MetaSrvOptions::default()
-        };
-
-        if let Some(addr) = cmd.bind_addr {
-            opts.bind_addr = addr;
-        }
-        if let Some(addr) = cmd.server_addr {
-            opts.server_addr = addr;
-        }
-        if let Some(addr) = cmd.store_addr {
-            opts.store_addr = addr;
-        }
-        if let Some(selector_type) = &cmd.selector {
-            opts.selector = selector_type[..]
-                .try_into()
-                .context(error::UnsupportedSelectorTypeSnafu { selector_type })?;
-            info!("Using {} selector", selector_type);
-        }
-
-        if cmd.use_memory_store {
-            warn!("Using memory store for Meta. Make sure you are in running tests.");
-            opts.use_memory_store = true;
-        }
-
-        if let Some(http_addr) = cmd.http_addr {
-            opts.http_opts.addr = http_addr;
-        }
-        if let Some(http_timeout) = cmd.http_timeout {
-            opts.http_opts.timeout = Duration::from_secs(http_timeout);
-        }
-
-        // Disable dashboard in metasrv.
-        opts.http_opts.disable_dashboard = true;
-
-        Ok(opts)
+        let selector = cmd
+            .selector
+            .as_ref()
+            .map(|selector_type| {
+                SelectorType::try_from(&selector_type[..])
+                    .context(error::UnsupportedSelectorTypeSnafu { selector_type })
+            })
+            .transpose()?;
+
+        load_options(
+            ENV_PREFIX,
+            cmd.config_file.as_deref(),
+            cmd.allow_unknown_config,
+            |opts: &mut MetaSrvOptions| {
+                if let Some(addr) = cmd.bind_addr {
+                    opts.bind_addr = addr;
+                }
+                if let Some(addr) = cmd.server_addr {
+                    opts.server_addr = addr;
+                }
+                if let Some(addr) = cmd.store_addr {
+                    opts.store_addr = addr;
+                }
+                if let Some(selector) = selector {
+                    info!("Using {:?} selector", selector);
+                    opts.selector = selector;
+                }
+
+                if cmd.use_memory_store {
+                    warn!("Using memory store for Meta. Make sure you are in running tests.");
+                    opts.use_memory_store = true;
+                }
+
+                if let Some(http_addr) = cmd.http_addr {
+                    opts.http_opts.addr = http_addr;
+                }
+                if let Some(http_timeout) = cmd.http_timeout {
+                    opts.http_opts.timeout = Duration::from_secs(http_timeout);
+                }
+
+                // Disable dashboard in metasrv.
+                opts.http_opts.disable_dashboard = true;
+            },
+        )
     }
 }
 
@@ -168,6 +187,8 @@ mod tests {
             use_memory_store: false,
             http_addr: None,
             http_timeout: None,
+            print_config_sample: false,
+            allow_unknown_config: false,
         };
         let options: MetaSrvOptions = cmd.try_into().unwrap();
         assert_eq!("127.0.0.1:3002".to_string(), options.bind_addr);
@@ -198,6 +219,8 @@ mod tests {
             use_memory_store: false,
             http_addr: None,
             http_timeout: None,
+            print_config_sample: false,
+            allow_unknown_config: false,
         };
         let options: MetaSrvOptions = cmd.try_into().unwrap();
         assert_eq!("127.0.0.1:3002".to_string(), options.bind_addr);
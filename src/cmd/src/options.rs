@@ -0,0 +1,46 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_config::{config_sample, ConfigLoader, Validate};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use snafu::ResultExt;
+
+use crate::error::{GenerateConfigSampleSnafu, LoadConfigSnafu, Result};
+
+/// Loads the options of a component from the config file, overridden by the environment
+/// variables prefixed by `env_prefix` and then by the command line arguments applied by
+/// `overrides`, and validates the final options.
+pub(crate) fn load_options<T, F>(
+    env_prefix: &str,
+    config_file: Option<&str>,
+    allow_unknown_config: bool,
+    overrides: F,
+) -> Result<T>
+where
+    T: Default + Serialize + DeserializeOwned + Validate,
+    F: FnOnce(&mut T),
+{
+    ConfigLoader::new(env_prefix)
+        .allow_unknown_keys(allow_unknown_config)
+        .load(config_file, overrides)
+        .context(LoadConfigSnafu)
+}
+
+/// Prints the sample config file of the default options and exits.
+pub(crate) fn print_config_sample<T: Default + Serialize>() -> Result<()> {
+    let sample = config_sample::<T>().context(GenerateConfigSampleSnafu)?;
+    println!("{sample}");
+    std::process::exit(0)
+}
@@ -14,10 +14,14 @@
 
 use std::sync::Arc;
 
+use catalog::local::NameResolution;
 use clap::Parser;
 use common_base::Plugins;
+use common_config::{FieldError, Validate};
 use common_telemetry::info;
-use datanode::datanode::{Datanode, DatanodeOptions, ProcedureConfig, StorageConfig, WalConfig};
+use datanode::datanode::{
+    Datanode, DatanodeOptions, ProcedureConfig, QueryConfig, StorageConfig, WalConfig,
+};
 use datanode::instance::InstanceRef;
 use frontend::frontend::FrontendOptions;
 use frontend::grpc::GrpcOptions;
@@ -27,7 +31,7 @@ use frontend::mysql::MysqlOptions;
 use frontend::opentsdb::OpentsdbOptions;
 use frontend::postgres::PostgresOptions;
 use frontend::prom::PromOptions;
-use frontend::prometheus::PrometheusOptions;
+use frontend::script::ScriptOptions;
 use serde::{Deserialize, Serialize};
 use servers::http::HttpOptions;
 use servers::tls::{TlsMode, TlsOption};
@@ -39,7 +43,10 @@ use crate::error::{
     StartDatanodeSnafu, StartFrontendSnafu,
 };
 use crate::frontend::load_frontend_plugins;
-use crate::toml_loader;
+use crate::options::{load_options, print_config_sample};
+
+/// Prefix of the environment variables overriding the options of standalone mode.
+const ENV_PREFIX: &str = "GREPTIMEDB_STANDALONE";
 
 #[derive(Parser)]
 pub struct Command {
@@ -66,57 +73,26 @@ impl SubCommand {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Options of standalone mode, which are the options of the frontend plus the storage
+/// options of the datanode.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct StandaloneOptions {
-    pub mode: Mode,
+    #[serde(flatten)]
+    pub frontend: FrontendOptions,
     pub enable_memory_catalog: bool,
-    pub http_options: Option<HttpOptions>,
-    pub grpc_options: Option<GrpcOptions>,
-    pub mysql_options: Option<MysqlOptions>,
-    pub postgres_options: Option<PostgresOptions>,
-    pub opentsdb_options: Option<OpentsdbOptions>,
-    pub influxdb_options: Option<InfluxdbOptions>,
-    pub prometheus_options: Option<PrometheusOptions>,
-    pub prom_options: Option<PromOptions>,
     pub wal: WalConfig,
     pub storage: StorageConfig,
     pub procedure: ProcedureConfig,
-}
-
-impl Default for StandaloneOptions {
-    fn default() -> Self {
-        Self {
-            mode: Mode::Standalone,
-            enable_memory_catalog: false,
-            http_options: Some(HttpOptions::default()),
-            grpc_options: Some(GrpcOptions::default()),
-            mysql_options: Some(MysqlOptions::default()),
-            postgres_options: Some(PostgresOptions::default()),
-            opentsdb_options: Some(OpentsdbOptions::default()),
-            influxdb_options: Some(InfluxdbOptions::default()),
-            prometheus_options: Some(PrometheusOptions::default()),
-            prom_options: Some(PromOptions::default()),
-            wal: WalConfig::default(),
-            storage: StorageConfig::default(),
-            procedure: ProcedureConfig::default(),
-        }
-    }
+    pub name_resolution: NameResolution,
 }
 
 impl StandaloneOptions {
     fn frontend_options(self) -> FrontendOptions {
         FrontendOptions {
-            mode: self.mode,
-            http_options: self.http_options,
-            grpc_options: self.grpc_options,
-            mysql_options: self.mysql_options,
-            postgres_options: self.postgres_options,
-            opentsdb_options: self.opentsdb_options,
-            influxdb_options: self.influxdb_options,
-            prometheus_options: self.prometheus_options,
-            prom_options: self.prom_options,
+            mode: Mode::Standalone,
             meta_client_options: None,
+            ..self.frontend
         }
     }
 
@@ -126,11 +102,24 @@ impl StandaloneOptions {
             wal: self.wal,
             storage: self.storage,
             procedure: self.procedure,
+            column_limits: self.frontend.column_limits,
+            name_resolution: self.name_resolution,
+            query: QueryConfig {
+                reject_expired_ranges: self.frontend.reject_expired_ranges,
+                ..Default::default()
+            },
             ..Default::default()
         }
     }
 }
 
+impl Validate for StandaloneOptions {
+    fn validate(&self) -> std::result::Result<(), FieldError> {
+        self.frontend.validate()?;
+        self.storage.validate().map_err(|e| e.nested("storage"))
+    }
+}
+
 pub struct Instance {
     datanode: Datanode,
     frontend: FeInstance,
@@ -193,23 +182,27 @@ struct StartCommand {
     tls_key_path: Option<String>,
     #[clap(long)]
     user_provider: Option<String>,
+    /// Prints a sample config file of the default options and exits.
+    #[clap(long)]
+    print_config_sample: bool,
+    /// Ignores the unknown keys in the config file instead of rejecting them.
+    #[clap(long)]
+    allow_unknown_config: bool,
 }
 
 impl StartCommand {
     async fn build(self) -> Result<Instance> {
-        let enable_memory_catalog = self.enable_memory_catalog;
-        let config_file = self.config_file.clone();
-        let plugins = Arc::new(load_frontend_plugins(&self.user_provider)?);
-        let fe_opts = FrontendOptions::try_from(self)?;
-        let dn_opts: DatanodeOptions = {
-            let mut opts: StandaloneOptions = if let Some(path) = config_file {
-                toml_loader::from_file!(&path)?
-            } else {
-                StandaloneOptions::default()
-            };
-            opts.enable_memory_catalog = enable_memory_catalog;
-            opts.datanode_options()
-        };
+        if self.print_config_sample {
+            print_config_sample::<StandaloneOptions>()?;
+        }
+        let user_provider = self.user_provider.clone();
+        let opts = StandaloneOptions::try_from(self)?;
+        let fe_opts = opts.clone().frontend_options();
+        let plugins = Arc::new(load_frontend_plugins(
+            &user_provider,
+            fe_opts.statement_authorizer.as_ref(),
+        )?);
+        let dn_opts = opts.datanode_options();
 
         info!(
             "Standalone frontend options: {:#?}, datanode options: {:#?}",
@@ -220,7 +213,10 @@ impl StartCommand {
             .await
             .context(StartDatanodeSnafu)?;
 
-        let mut frontend = build_frontend(plugins.clone(), datanode.get_instance()).await?;
+        let mut frontend =
+            build_frontend(plugins.clone(), datanode.get_instance(), &fe_opts.script).await?;
+        frontend.set_auto_create_ts_default(fe_opts.auto_create_ts_default.clone());
+        frontend.set_promql_max_points(fe_opts.promql_max_points());
 
         frontend
             .build_servers(&fe_opts)
@@ -235,38 +231,24 @@ impl StartCommand {
 async fn build_frontend(
     plugins: Arc<Plugins>,
     datanode_instance: InstanceRef,
+    script_opts: &ScriptOptions,
 ) -> Result<FeInstance> {
-    let mut frontend_instance = FeInstance::try_new_standalone(datanode_instance.clone())
-        .await
-        .context(StartFrontendSnafu)?;
+    let mut frontend_instance =
+        FeInstance::try_new_standalone(datanode_instance.clone(), script_opts)
+            .await
+            .context(StartFrontendSnafu)?;
     frontend_instance.set_plugins(plugins.clone());
     Ok(frontend_instance)
 }
 
-impl TryFrom<StartCommand> for FrontendOptions {
+impl TryFrom<StartCommand> for StandaloneOptions {
     type Error = Error;
 
     fn try_from(cmd: StartCommand) -> std::result::Result<Self, Self::Error> {
-        let opts: StandaloneOptions = if let Some(path) = cmd.config_file {
-            toml_loader::from_file!(&path)?
-        } else {
-            StandaloneOptions::default()
-        };
-
-        let mut opts = opts.frontend_options();
-
-        opts.mode = Mode::Standalone;
-
-        if let Some(addr) = cmd.http_addr {
-            opts.http_options = Some(HttpOptions {
-                addr,
-                ..Default::default()
-            });
-        }
-        if let Some(addr) = cmd.rpc_addr {
+        if let Some(addr) = &cmd.rpc_addr {
             // frontend grpc addr conflict with datanode default grpc addr
             let datanode_grpc_addr = DatanodeOptions::default().rpc_addr;
-            if addr == datanode_grpc_addr {
+            if *addr == datanode_grpc_addr {
                 return IllegalConfigSnafu {
                     msg: format!(
                         "gRPC listen address conflicts with datanode reserved gRPC addr: {datanode_grpc_addr}",
@@ -274,54 +256,74 @@ impl TryFrom<StartCommand> for FrontendOptions {
                 }
                 .fail();
             }
-            opts.grpc_options = Some(GrpcOptions {
-                addr,
-                ..Default::default()
-            });
         }
 
-        if let Some(addr) = cmd.mysql_addr {
-            opts.mysql_options = Some(MysqlOptions {
-                addr,
-                ..Default::default()
-            })
-        }
+        load_options(
+            ENV_PREFIX,
+            cmd.config_file.as_deref(),
+            cmd.allow_unknown_config,
+            |opts: &mut StandaloneOptions| {
+                if cmd.enable_memory_catalog {
+                    opts.enable_memory_catalog = true;
+                }
 
-        if let Some(addr) = cmd.prom_addr {
-            opts.prom_options = Some(PromOptions { addr })
-        }
+                let opts = &mut opts.frontend;
+                if let Some(addr) = cmd.http_addr {
+                    opts.http_options = Some(HttpOptions {
+                        addr,
+                        ..Default::default()
+                    });
+                }
+                if let Some(addr) = cmd.rpc_addr {
+                    opts.grpc_options = Some(GrpcOptions {
+                        addr,
+                        ..Default::default()
+                    });
+                }
 
-        if let Some(addr) = cmd.postgres_addr {
-            opts.postgres_options = Some(PostgresOptions {
-                addr,
-                ..Default::default()
-            })
-        }
+                if let Some(addr) = cmd.mysql_addr {
+                    opts.mysql_options = Some(MysqlOptions {
+                        addr,
+                        ..Default::default()
+                    })
+                }
 
-        if let Some(addr) = cmd.opentsdb_addr {
-            opts.opentsdb_options = Some(OpentsdbOptions {
-                addr,
-                ..Default::default()
-            });
-        }
+                if let Some(addr) = cmd.prom_addr {
+                    opts.prom_options = Some(PromOptions {
+                        addr,
+                        ..Default::default()
+                    })
+                }
 
-        if cmd.influxdb_enable {
-            opts.influxdb_options = Some(InfluxdbOptions { enable: true });
-        }
+                if let Some(addr) = cmd.postgres_addr {
+                    opts.postgres_options = Some(PostgresOptions {
+                        addr,
+                        ..Default::default()
+                    })
+                }
 
-        let tls_option = TlsOption::new(cmd.tls_mode, cmd.tls_cert_path, cmd.tls_key_path);
+                if let Some(addr) = cmd.opentsdb_addr {
+                    opts.opentsdb_options = Some(OpentsdbOptions {
+                        addr,
+                        ..Default::default()
+                    });
+                }
 
-        if let Some(mut mysql_options) = opts.mysql_options {
-            mysql_options.tls = tls_option.clone();
-            opts.mysql_options = Some(mysql_options);
-        }
+                if cmd.influxdb_enable {
+                    opts.influxdb_options = Some(InfluxdbOptions { enable: true });
+                }
 
-        if let Some(mut postgres_options) = opts.postgres_options {
-            postgres_options.tls = tls_option;
-            opts.postgres_options = Some(postgres_options);
-        }
+                let tls_option = TlsOption::new(cmd.tls_mode, cmd.tls_cert_path, cmd.tls_key_path);
+
+                if let Some(mysql_options) = &mut opts.mysql_options {
+                    mysql_options.tls = tls_option.clone();
+                }
 
-        Ok(opts)
+                if let Some(postgres_options) = &mut opts.postgres_options {
+                    postgres_options.tls = tls_option;
+                }
+            },
+        )
     }
 }
 
@@ -352,9 +354,11 @@ mod tests {
             tls_cert_path: None,
             tls_key_path: None,
             user_provider: None,
+            print_config_sample: false,
+            allow_unknown_config: false,
         };
 
-        let fe_opts = FrontendOptions::try_from(cmd).unwrap();
+        let fe_opts = StandaloneOptions::try_from(cmd).unwrap().frontend_options();
         assert_eq!(Mode::Standalone, fe_opts.mode);
         assert_eq!(
             "127.0.0.1:4000".to_string(),
@@ -396,9 +400,11 @@ mod tests {
             tls_cert_path: None,
             tls_key_path: None,
             user_provider: Some("static_user_provider:cmd:test=test".to_string()),
+            print_config_sample: false,
+            allow_unknown_config: false,
         };
 
-        let plugins = load_frontend_plugins(&command.user_provider);
+        let plugins = load_frontend_plugins(&command.user_provider, None);
         assert!(plugins.is_ok());
         let plugins = plugins.unwrap();
         let provider = plugins.get::<UserProviderRef>();
@@ -412,8 +418,11 @@ mod tests {
 
     #[test]
     fn test_toml() {
-        let opts = StandaloneOptions::default();
-        let toml_string = toml::to_string(&opts).unwrap();
-        let _parsed: StandaloneOptions = toml::from_str(&toml_string).unwrap();
+        let sample = common_config::config_sample::<StandaloneOptions>().unwrap();
+        let parsed: StandaloneOptions = toml::from_str(&sample).unwrap();
+        assert_eq!(
+            StandaloneOptions::default().frontend.http_options,
+            parsed.frontend.http_options
+        );
     }
 }
@@ -0,0 +1,131 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commit token for read-your-writes consistency.
+//!
+//! Responses of writes carry a commit token, which records the sequences of the writes in
+//! each written region. Queries carrying the token wait until the regions they scan have
+//! applied the writes.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Name of the gRPC metadata and the HTTP header carrying the commit token.
+pub const COMMIT_TOKEN_HEADER: &str = "x-greptime-commit-token";
+
+/// Sequences of writes by region id, formatted as `region_id:sequence,...`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommitToken {
+    sequences: BTreeMap<u64, u64>,
+}
+
+impl CommitToken {
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    /// Records that the region has accepted writes up to `sequence`.
+    pub fn record(&mut self, region_id: u64, sequence: u64) {
+        let current = self.sequences.entry(region_id).or_default();
+        *current = (*current).max(sequence);
+    }
+
+    /// Merges `other` into this token, keeping the larger sequence of each region.
+    pub fn merge(&mut self, other: &CommitToken) {
+        for (region_id, sequence) in other.iter() {
+            self.record(region_id, sequence);
+        }
+    }
+
+    /// Returns the sequence to wait for in the region, if any.
+    pub fn sequence(&self, region_id: u64) -> Option<u64> {
+        self.sequences.get(&region_id).copied()
+    }
+
+    /// Iterates over `(region_id, sequence)` pairs in the order of region ids.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.sequences
+            .iter()
+            .map(|(region_id, sequence)| (*region_id, *sequence))
+    }
+}
+
+impl fmt::Display for CommitToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (region_id, sequence)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{region_id}:{sequence}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CommitToken {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CommitToken, String> {
+        let mut token = CommitToken::default();
+        for entry in s.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            let parsed = entry
+                .split_once(':')
+                .and_then(|(region_id, sequence)| {
+                    Some((region_id.trim().parse().ok()?, sequence.trim().parse().ok()?))
+                });
+            match parsed {
+                Some((region_id, sequence)) => token.record(region_id, sequence),
+                None => return Err(format!("{entry:?} is not a valid commit token entry.")),
+            }
+        }
+        Ok(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_token_format_and_parse() {
+        let mut token = CommitToken::default();
+        assert!(token.is_empty());
+        assert_eq!("", token.to_string());
+        assert_eq!(token, "".parse().unwrap());
+
+        token.record(4294967297, 10);
+        token.record(4294967296, 3);
+        assert_eq!("4294967296:3,4294967297:10", token.to_string());
+        assert_eq!(token, token.to_string().parse().unwrap());
+        assert_eq!(token, " 4294967297:10, 4294967296 : 3 ".parse().unwrap());
+
+        assert!("4294967296".parse::<CommitToken>().is_err());
+        assert!("4294967296:a".parse::<CommitToken>().is_err());
+        assert!("-1:3".parse::<CommitToken>().is_err());
+    }
+
+    #[test]
+    fn test_commit_token_merge() {
+        let mut token: CommitToken = "1:5,2:7".parse().unwrap();
+        token.merge(&"2:6,3:1".parse().unwrap());
+        assert_eq!("1:5,2:7,3:1", token.to_string());
+
+        token.record(1, 9);
+        token.record(3, 0);
+        assert_eq!(Some(9), token.sequence(1));
+        assert_eq!(Some(1), token.sequence(3));
+        assert_eq!(None, token.sequence(4));
+    }
+}
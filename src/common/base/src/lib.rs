@@ -15,9 +15,16 @@
 pub mod bit_vec;
 pub mod buffer;
 pub mod bytes;
+pub mod commit_token;
 #[allow(clippy::all)]
 pub mod readable_size;
+pub mod validation_mode;
+pub mod write_mode;
 
 pub use bit_vec::BitVec;
 
+/// Name of the gRPC metadata carrying the wall-clock time in millis the queries in a request
+/// read the snapshots of tables as of.
+pub const AS_OF_HEADER: &str = "x-greptime-as-of";
+
 pub type Plugins = anymap::Map<dyn core::any::Any + Send + Sync>;
@@ -0,0 +1,71 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation mode of inserts, which decides how many of the invalid rows of a batch are
+//! reported before the batch is rejected.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Name of the gRPC metadata and HTTP parameter carrying the validation mode of the inserts
+/// in a request.
+pub const VALIDATION_MODE_HEADER: &str = "x-greptime-validation-mode";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Stops at the first invalid row.
+    #[default]
+    Strict,
+    /// Validates the remaining rows after an invalid one, so the errors of multiple rows are
+    /// reported, up to a cap.
+    Lenient,
+}
+
+impl fmt::Display for ValidationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationMode::Strict => write!(f, "strict"),
+            ValidationMode::Lenient => write!(f, "lenient"),
+        }
+    }
+}
+
+impl FromStr for ValidationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ValidationMode, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "strict" => Ok(ValidationMode::Strict),
+            "lenient" => Ok(ValidationMode::Lenient),
+            _ => Err(format!(
+                "{s:?} is not a valid validation mode, expecting \"strict\" or \"lenient\"."
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_mode_format_and_parse() {
+        assert_eq!(ValidationMode::Strict, ValidationMode::default());
+        for mode in [ValidationMode::Strict, ValidationMode::Lenient] {
+            assert_eq!(mode, mode.to_string().parse().unwrap());
+        }
+        assert_eq!(ValidationMode::Lenient, " Lenient".parse().unwrap());
+        assert!("loose".parse::<ValidationMode>().is_err());
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Write mode of inserts, which decides what happens to the rows whose primary key and
+//! timestamp already exist in the table.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Name of the gRPC metadata carrying the write mode of the inserts in a request.
+pub const WRITE_MODE_HEADER: &str = "x-greptime-write-mode";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Overwrites the existing rows.
+    #[default]
+    Upsert,
+    /// Keeps the existing rows and skips the inserted ones.
+    InsertIgnore,
+}
+
+impl fmt::Display for WriteMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteMode::Upsert => write!(f, "upsert"),
+            WriteMode::InsertIgnore => write!(f, "insert_ignore"),
+        }
+    }
+}
+
+impl FromStr for WriteMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<WriteMode, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "upsert" => Ok(WriteMode::Upsert),
+            "insert_ignore" => Ok(WriteMode::InsertIgnore),
+            _ => Err(format!(
+                "{s:?} is not a valid write mode, expecting \"upsert\" or \"insert_ignore\"."
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_mode_format_and_parse() {
+        assert_eq!(WriteMode::Upsert, WriteMode::default());
+        for mode in [WriteMode::Upsert, WriteMode::InsertIgnore] {
+            assert_eq!(mode, mode.to_string().parse().unwrap());
+        }
+        assert_eq!(WriteMode::InsertIgnore, " Insert_Ignore".parse().unwrap());
+        assert!("ignore".parse::<WriteMode>().is_err());
+    }
+}
@@ -39,6 +39,13 @@ pub enum Error {
 
     #[snafu(display("Failed to parse node id: {}", key))]
     ParseNodeId { key: String, location: Location },
+
+    #[snafu(display("Invalid table name '{}', {}", name, reason))]
+    InvalidTableName {
+        name: String,
+        reason: String,
+        location: Location,
+    },
 }
 
 impl ErrorExt for Error {
@@ -47,7 +54,9 @@ impl ErrorExt for Error {
             Error::InvalidCatalog { .. }
             | Error::DeserializeCatalogEntryValue { .. }
             | Error::SerializeCatalogEntryValue { .. } => StatusCode::Unexpected,
-            Error::ParseNodeId { .. } => StatusCode::InvalidArguments,
+            Error::ParseNodeId { .. } | Error::InvalidTableName { .. } => {
+                StatusCode::InvalidArguments
+            }
         }
     }
 
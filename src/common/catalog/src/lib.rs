@@ -14,6 +14,7 @@
 
 pub mod consts;
 pub mod error;
+pub mod naming;
 
 /// Formats table fully-qualified name
 #[inline]
@@ -0,0 +1,191 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rules of the names of the tables created, shared by all the paths creating tables, like
+//! SQL, auto-creation on insertion and the ingestion protocols.
+//!
+//! A valid table name:
+//! - has 1 to [MAX_TABLE_NAME_LEN] characters, counted in unicode scalar values;
+//! - consists of unicode letters and digits, like `温度` or `données`, and `_`, `-`, `:`
+//!   and `.`. Other characters, like whitespaces, quotes, slashes and emojis, are not allowed;
+//! - starts with a letter, a digit or `_`;
+//! - doesn't start with a reserved prefix in [RESERVED_TABLE_NAME_PREFIXES], ignoring case.
+//!
+//! The rules apply to the tables being created or renamed, the existing tables with invalid
+//! names are still opened, queried and written by SQL as usual. The ingestion protocols, which
+//! may create the tables they write to, reject the invalid names before knowing whether the
+//! tables exist.
+
+use snafu::ensure;
+
+use crate::error::{InvalidTableNameSnafu, Result};
+
+/// Max number of characters of a table name.
+pub const MAX_TABLE_NAME_LEN: usize = 255;
+
+/// Prefix of the names of the tables in the recycle bin, which only the soft drop renames
+/// tables to.
+pub const RECYCLED_TABLE_PREFIX: &str = "__recycled_";
+
+/// Prefixes of the names reserved for the tables of the system.
+pub const RESERVED_TABLE_NAME_PREFIXES: [&str; 3] =
+    ["greptime_", "information_schema", RECYCLED_TABLE_PREFIX];
+
+/// Punctuations allowed in table names besides letters and digits.
+const ALLOWED_PUNCTUATIONS: [char; 4] = ['_', '-', ':', '.'];
+
+/// Validates the name of the table to create by the rules of this module.
+pub fn validate_table_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return InvalidTableNameSnafu {
+            name,
+            reason: "it's empty",
+        }
+        .fail();
+    };
+    ensure!(
+        first.is_alphanumeric() || first == '_',
+        InvalidTableNameSnafu {
+            name,
+            reason: "it must start with a letter, a digit or '_'",
+        }
+    );
+    if let Some(c) = chars.find(|c| !c.is_alphanumeric() && !ALLOWED_PUNCTUATIONS.contains(c)) {
+        return InvalidTableNameSnafu {
+            name,
+            reason: format!(
+                "character {c:?} is not allowed, only letters, digits, '_', '-', ':' and '.' are"
+            ),
+        }
+        .fail();
+    }
+
+    let len = name.chars().count();
+    ensure!(
+        len <= MAX_TABLE_NAME_LEN,
+        InvalidTableNameSnafu {
+            name,
+            reason: format!("it has {len} characters, more than the max {MAX_TABLE_NAME_LEN}"),
+        }
+    );
+
+    let lowercase = name.to_lowercase();
+    if let Some(prefix) = RESERVED_TABLE_NAME_PREFIXES
+        .iter()
+        .find(|prefix| lowercase.starts_with(*prefix))
+    {
+        return InvalidTableNameSnafu {
+            name,
+            reason: format!("prefix '{prefix}' is reserved by the system"),
+        }
+        .fail();
+    }
+    Ok(())
+}
+
+/// Returns whether `table_name` is the name of a table in the recycle bin.
+pub fn is_recycled_table_name(table_name: &str) -> bool {
+    table_name.starts_with(RECYCLED_TABLE_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_table_names() {
+        let max_len = "a".repeat(MAX_TABLE_NAME_LEN);
+        let max_len_unicode = "温".repeat(MAX_TABLE_NAME_LEN);
+        for name in [
+            "monitor",
+            "_monitor",
+            "1m_cpu",
+            "node:cpu:rate5m",
+            "sys.cpu.user",
+            "http-requests",
+            "温度",
+            "données",
+            "greptime",
+            "my_greptime_table",
+            &max_len,
+            &max_len_unicode,
+        ] {
+            assert!(validate_table_name(name).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_table_names() {
+        let too_long = "a".repeat(MAX_TABLE_NAME_LEN + 1);
+        let cases = [
+            ("", "Invalid table name '', it's empty"),
+            (
+                "-cpu",
+                "Invalid table name '-cpu', it must start with a letter, a digit or '_'",
+            ),
+            (
+                ".hidden",
+                "Invalid table name '.hidden', it must start with a letter, a digit or '_'",
+            ),
+            (
+                "a/b",
+                "Invalid table name 'a/b', character '/' is not allowed, only letters, digits, \
+                 '_', '-', ':' and '.' are",
+            ),
+            (
+                "cpu usage",
+                "Invalid table name 'cpu usage', character ' ' is not allowed, only letters, \
+                 digits, '_', '-', ':' and '.' are",
+            ),
+            (
+                "a`b",
+                "Invalid table name 'a`b', character '`' is not allowed, only letters, digits, \
+                 '_', '-', ':' and '.' are",
+            ),
+            (
+                "cpu🔥",
+                "Invalid table name 'cpu🔥', character '🔥' is not allowed, only letters, \
+                 digits, '_', '-', ':' and '.' are",
+            ),
+            (
+                "Greptime_Metrics",
+                "Invalid table name 'Greptime_Metrics', prefix 'greptime_' is reserved by the \
+                 system",
+            ),
+            (
+                "information_schema_tables",
+                "Invalid table name 'information_schema_tables', prefix 'information_schema' \
+                 is reserved by the system",
+            ),
+            (
+                "__recycled_1024_1680000000000_cpu",
+                "Invalid table name '__recycled_1024_1680000000000_cpu', prefix '__recycled_' \
+                 is reserved by the system",
+            ),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(
+                expected,
+                validate_table_name(name).unwrap_err().to_string(),
+                "{name}"
+            );
+        }
+
+        assert!(validate_table_name(&too_long)
+            .unwrap_err()
+            .to_string()
+            .ends_with("it has 256 characters, more than the max 255"));
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+
+use common_error::prelude::*;
+use snafu::Location;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Failed to read config file: {}, source: {}", path, source))]
+    ReadConfig {
+        path: String,
+        source: std::io::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to parse config file: {}, source: {}", path, source))]
+    ParseConfig {
+        path: String,
+        source: toml::de::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Invalid config `{}` in {}: {}", field, path, msg))]
+    InvalidConfig {
+        path: String,
+        field: String,
+        msg: String,
+        location: Location,
+    },
+
+    #[snafu(display("Unknown config `{}` in {}", field, path))]
+    UnknownConfig {
+        path: String,
+        field: String,
+        location: Location,
+    },
+
+    #[snafu(display("Invalid environment variable {}: {}", name, msg))]
+    InvalidEnvOverride {
+        name: String,
+        msg: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to serialize options, source: {}", source))]
+    SerializeOptions {
+        source: toml::ser::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to generate config sample, source: {}", source))]
+    GenerateSample {
+        source: toml::ser::Error,
+        location: Location,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl ErrorExt for Error {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Error::ReadConfig { .. }
+            | Error::ParseConfig { .. }
+            | Error::InvalidConfig { .. }
+            | Error::UnknownConfig { .. }
+            | Error::InvalidEnvOverride { .. } => StatusCode::InvalidArguments,
+            Error::SerializeOptions { .. } | Error::GenerateSample { .. } => StatusCode::Unexpected,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading of the options of the server components.
+//!
+//! The options are loaded with the precedence, from low to high:
+//! 1. the defaults of the options;
+//! 2. the TOML config file;
+//! 3. the environment variables, like `GREPTIMEDB_DATANODE__HTTP_OPTS__ADDR` for the option
+//!    `http_opts.addr` of the datanode. The keys of the nested options are separated by `__`,
+//!    and the values are parsed as the types of the options;
+//! 4. the command line arguments, applied by the commands as the overrides of the loader.
+//!
+//! Unknown keys in the config file and the environment variables are rejected unless
+//! allowed, and the options are validated after all the layers are applied.
+
+pub mod error;
+mod loader;
+mod validate;
+
+pub use loader::{config_sample, ConfigLoader, ENV_SEPARATOR};
+pub use validate::{validate_addr, FieldError, Validate};
@@ -0,0 +1,430 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use snafu::{ensure, ResultExt};
+use toml::value::{Table, Value};
+
+use crate::error::{
+    GenerateSampleSnafu, InvalidConfigSnafu, InvalidEnvOverrideSnafu, ParseConfigSnafu,
+    ReadConfigSnafu, Result, SerializeOptionsSnafu, UnknownConfigSnafu,
+};
+use crate::validate::Validate;
+
+/// Separator of the prefix and the keys of the nested options in the environment variables.
+pub const ENV_SEPARATOR: &str = "__";
+
+/// Source name of the options in errors when there is no config file.
+const DEFAULT_SOURCE: &str = "<default config>";
+
+/// Loads options from a config file, overridden by environment variables.
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    env_prefix: String,
+    allow_unknown_keys: bool,
+}
+
+impl ConfigLoader {
+    /// Creates a loader of the options overridden by the environment variables prefixed by
+    /// `env_prefix`, e.g. `GREPTIMEDB_DATANODE`.
+    pub fn new(env_prefix: impl Into<String>) -> Self {
+        Self {
+            env_prefix: env_prefix.into(),
+            allow_unknown_keys: false,
+        }
+    }
+
+    /// Ignores the unknown keys instead of rejecting them, e.g. to read a config file written
+    /// for another version.
+    pub fn allow_unknown_keys(mut self, allow: bool) -> Self {
+        self.allow_unknown_keys = allow;
+        self
+    }
+
+    /// Loads the options from the file at `path`, or the defaults if there is no file, then
+    /// applies the `overrides` of the caller, e.g. the command line arguments, and validates
+    /// the final options.
+    pub fn load<T, F>(&self, path: Option<&str>, overrides: F) -> Result<T>
+    where
+        T: Default + Serialize + DeserializeOwned + Validate,
+        F: FnOnce(&mut T),
+    {
+        let content = path
+            .map(|path| std::fs::read_to_string(path).context(ReadConfigSnafu { path }))
+            .transpose()?;
+        self.load_from_str(path, content.as_deref(), std::env::vars(), overrides)
+    }
+
+    fn load_from_str<T, F>(
+        &self,
+        path: Option<&str>,
+        content: Option<&str>,
+        vars: impl IntoIterator<Item = (String, String)>,
+        overrides: F,
+    ) -> Result<T>
+    where
+        T: Default + Serialize + DeserializeOwned + Validate,
+        F: FnOnce(&mut T),
+    {
+        let source = path.unwrap_or(DEFAULT_SOURCE);
+        let mut table: Table = match content {
+            Some(content) => toml::from_str(content).context(ParseConfigSnafu { path: source })?,
+            None => Table::new(),
+        };
+        let defaults = Value::try_from(T::default()).context(SerializeOptionsSnafu)?;
+        self.apply_env_overrides(&mut table, &defaults, vars)?;
+
+        let mut options: T = serde_path_to_error::deserialize(Value::Table(table.clone()))
+            .map_err(|e| {
+                InvalidConfigSnafu {
+                    path: source,
+                    field: e.path().to_string(),
+                    msg: e.inner().to_string(),
+                }
+                .build()
+            })?;
+        if !self.allow_unknown_keys {
+            // The keys dropped by deserializing, including the ones of the flattened options
+            // which serde doesn't report as ignored.
+            let known = Value::try_from(&options).context(SerializeOptionsSnafu)?;
+            let mut unknown_keys = vec![];
+            collect_unknown_keys("", &table, &known, &mut unknown_keys);
+            ensure!(
+                unknown_keys.is_empty(),
+                UnknownConfigSnafu {
+                    path: source,
+                    field: unknown_keys.join(", "),
+                }
+            );
+        }
+
+        overrides(&mut options);
+        options.validate().map_err(|e| {
+            InvalidConfigSnafu {
+                path: source,
+                field: e.field,
+                msg: e.msg,
+            }
+            .build()
+        })?;
+        Ok(options)
+    }
+
+    /// Sets the options named by the environment variables like `<prefix>__HTTP_OPTS__ADDR`,
+    /// parsing the values as the types of the options in the `table` or the `defaults`.
+    fn apply_env_overrides(
+        &self,
+        table: &mut Table,
+        defaults: &Value,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<()> {
+        let prefix = format!("{}{ENV_SEPARATOR}", self.env_prefix);
+        for (name, value) in vars {
+            let Some(keys) = name.strip_prefix(&prefix) else { continue };
+            let keys = keys
+                .split(ENV_SEPARATOR)
+                .map(|key| key.to_lowercase())
+                .collect::<Vec<_>>();
+            ensure!(
+                keys.iter().all(|key| !key.is_empty()),
+                InvalidEnvOverrideSnafu {
+                    name: &name,
+                    msg: "empty key",
+                }
+            );
+
+            let (last, parents) = keys.split_last().unwrap();
+            let mut current = &mut *table;
+            let mut default = Some(defaults);
+            for key in parents {
+                default = default.and_then(|value| value.get(key));
+                let entry = current
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Table(Table::new()));
+                let Value::Table(nested) = entry else {
+                    return InvalidEnvOverrideSnafu {
+                        name: &name,
+                        msg: format!("`{key}` is not a table"),
+                    }
+                    .fail();
+                };
+                current = nested;
+            }
+            let expected = current
+                .get(last)
+                .or_else(|| default.and_then(|value| value.get(last)));
+            let value = parse_env_value(&name, &value, expected)?;
+            let _ = current.insert(last.clone(), value);
+        }
+        Ok(())
+    }
+}
+
+/// Parses the value of the environment variable `name` as the type of the `expected` value.
+///
+/// The value of a string option is taken as is, the others are parsed as TOML values, like a
+/// number or an array. If the type of the option is unknown, e.g. the option is absent by
+/// default, the value is parsed as a TOML value if possible or taken as a string, leaving
+/// the type check to the deserialization.
+fn parse_env_value(name: &str, value: &str, expected: Option<&Value>) -> Result<Value> {
+    let parsed = toml::from_str::<Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"));
+    match (expected, parsed) {
+        (Some(Value::String(_)), _) => Ok(Value::String(value.to_string())),
+        (Some(Value::Float(_)), Some(Value::Integer(v))) => Ok(Value::Float(v as f64)),
+        (Some(expected), Some(parsed)) if parsed.same_type(expected) => Ok(parsed),
+        (Some(expected), _) => InvalidEnvOverrideSnafu {
+            name,
+            msg: format!("expect a {} but got `{value}`", expected.type_str()),
+        }
+        .fail(),
+        (None, parsed) => Ok(parsed.unwrap_or_else(|| Value::String(value.to_string()))),
+    }
+}
+
+/// Collects the keys of the `input` under `prefix` that the `known` options don't have.
+fn collect_unknown_keys(
+    prefix: &str,
+    input: &Table,
+    known: &Value,
+    unknown_keys: &mut Vec<String>,
+) {
+    for (key, value) in input {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (value, known.get(key)) {
+            (_, None) => unknown_keys.push(path),
+            (Value::Table(nested), Some(known)) => {
+                collect_unknown_keys(&path, nested, known, unknown_keys)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Generates a sample config file from the default options.
+pub fn config_sample<T: Default + Serialize>() -> Result<String> {
+    // Serializes to a value first, which puts the tables after the other values as TOML
+    // requires.
+    let value = Value::try_from(T::default()).context(GenerateSampleSnafu)?;
+    toml::to_string_pretty(&value).context(GenerateSampleSnafu)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use common_test_util::temp_dir::create_named_temp_file;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::error::Error;
+    use crate::validate::{validate_addr, FieldError};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct MockOptions {
+        name: String,
+        runtime_size: usize,
+        http_options: Option<MockHttpOptions>,
+        storage: MockStorageOptions,
+    }
+
+    impl Default for MockOptions {
+        fn default() -> Self {
+            Self {
+                name: "mock".to_string(),
+                runtime_size: 8,
+                http_options: Some(MockHttpOptions::default()),
+                storage: MockStorageOptions::default(),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct MockStorageOptions {
+        #[serde(flatten)]
+        store: MockStoreOptions,
+        ratio: f64,
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct MockStoreOptions {
+        data_dir: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    struct MockHttpOptions {
+        addr: String,
+    }
+
+    impl Default for MockHttpOptions {
+        fn default() -> Self {
+            Self {
+                addr: "127.0.0.1:4000".to_string(),
+            }
+        }
+    }
+
+    impl Validate for MockOptions {
+        fn validate(&self) -> std::result::Result<(), FieldError> {
+            if let Some(http_options) = &self.http_options {
+                validate_addr("http_options.addr", &http_options.addr)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn load(content: &str, vars: Vec<(&str, &str)>) -> Result<MockOptions> {
+        ConfigLoader::new("GREPTIMEDB_MOCK").load_from_str(
+            Some("mock.toml"),
+            Some(content),
+            vars.into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+            |_| {},
+        )
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let mut file = create_named_temp_file();
+        write!(
+            file,
+            "name = 'test'\n[http_options]\naddr = '0.0.0.0:4000'\n"
+        )
+        .unwrap();
+
+        let options: MockOptions = ConfigLoader::new("GREPTIMEDB_TEST_LOAD_FROM_FILE")
+            .load(Some(file.path().to_str().unwrap()), |_| {})
+            .unwrap();
+        assert_eq!("test", options.name);
+        assert_eq!(8, options.runtime_size);
+        assert_eq!("0.0.0.0:4000", options.http_options.unwrap().addr);
+
+        let options: MockOptions = ConfigLoader::new("GREPTIMEDB_TEST_LOAD_FROM_FILE")
+            .load(None, |_| {})
+            .unwrap();
+        assert_eq!(MockOptions::default(), options);
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let content = "name = 'test'\n[http_options]\naddr = '127.0.0.1:4000'\ntimeout = '30s'\n";
+        let err = load(content, vec![]).unwrap_err();
+        assert!(matches!(err, Error::UnknownConfig { .. }), "{err}");
+        let msg = err.to_string();
+        assert!(
+            msg.contains("http_options") && msg.contains("timeout"),
+            "{msg}"
+        );
+        assert!(msg.contains("mock.toml"), "{msg}");
+
+        // The keys of the flattened options are checked too.
+        let err = load("[storage]\ndata_dir = '/tmp'\ndatadir = '/tmp'\n", vec![]).unwrap_err();
+        let Error::UnknownConfig { field, .. } = &err else { panic!("{err}") };
+        assert_eq!("storage.datadir", field);
+
+        // The escape hatch.
+        let options: MockOptions = ConfigLoader::new("GREPTIMEDB_MOCK")
+            .allow_unknown_keys(true)
+            .load_from_str(Some("mock.toml"), Some(content), vec![], |_| {})
+            .unwrap();
+        assert_eq!("test", options.name);
+    }
+
+    #[test]
+    fn test_validate_after_overrides() {
+        let load = |addr: &'static str| -> Result<MockOptions> {
+            ConfigLoader::new("GREPTIMEDB_MOCK").load_from_str(
+                Some("mock.toml"),
+                Some("[http_options]\naddr = '127.0.0.1:4000'\n"),
+                vec![],
+                |options: &mut MockOptions| {
+                    options.http_options.as_mut().unwrap().addr = addr.to_string()
+                },
+            )
+        };
+        let options = load("0.0.0.0:4000").unwrap();
+        assert_eq!("0.0.0.0:4000", options.http_options.unwrap().addr);
+
+        let err = load("127.0.0.1:70000").unwrap_err();
+        let Error::InvalidConfig { field, .. } = &err else { panic!("{err}") };
+        assert_eq!("http_options.addr", field);
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let err = load("[http_options]\naddr = '127.0.0.1:70000'\n", vec![]).unwrap_err();
+        let Error::InvalidConfig { path, field, .. } = &err else { panic!("{err}") };
+        assert_eq!("mock.toml", path);
+        assert_eq!("http_options.addr", field);
+
+        // A string where a number is expected.
+        let err = load("runtime_size = 'eight'\n", vec![]).unwrap_err();
+        let Error::InvalidConfig { field, .. } = &err else { panic!("{err}") };
+        assert_eq!("runtime_size", field);
+    }
+
+    #[test]
+    fn test_env_override() {
+        let content = "name = 'test'\nruntime_size = 4\n";
+        let options = load(
+            content,
+            vec![
+                ("GREPTIMEDB_MOCK__RUNTIME_SIZE", "16"),
+                ("GREPTIMEDB_MOCK__HTTP_OPTIONS__ADDR", "0.0.0.0:4000"),
+                ("GREPTIMEDB_OTHER__NAME", "other"),
+            ],
+        )
+        .unwrap();
+        assert_eq!("test", options.name);
+        assert_eq!(16, options.runtime_size);
+        assert_eq!("0.0.0.0:4000", options.http_options.unwrap().addr);
+
+        let err = load(content, vec![("GREPTIMEDB_MOCK__NAME__FIRST", "a")]).unwrap_err();
+        assert!(matches!(err, Error::InvalidEnvOverride { .. }), "{err}");
+
+        // The values are parsed as the types of the options.
+        let options = load(
+            content,
+            vec![
+                ("GREPTIMEDB_MOCK__NAME", "123"),
+                ("GREPTIMEDB_MOCK__STORAGE__RATIO", "2"),
+                ("GREPTIMEDB_MOCK__STORAGE__DATA_DIR", "true"),
+            ],
+        )
+        .unwrap();
+        assert_eq!("123", options.name);
+        assert_eq!(2.0, options.storage.ratio);
+        assert_eq!("true", options.storage.data_dir);
+
+        let err = load(content, vec![("GREPTIMEDB_MOCK__RUNTIME_SIZE", "many")]).unwrap_err();
+        let Error::InvalidEnvOverride { name, .. } = &err else { panic!("{err}") };
+        assert_eq!("GREPTIMEDB_MOCK__RUNTIME_SIZE", name);
+    }
+
+    #[test]
+    fn test_config_sample() {
+        let sample = config_sample::<MockOptions>().unwrap();
+        assert_eq!(MockOptions::default(), load(&sample, vec![]).unwrap());
+    }
+}
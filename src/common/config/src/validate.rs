@@ -0,0 +1,89 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Error of an invalid option, named by its path like `http_options.addr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub msg: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, msg: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            msg: msg.into(),
+        }
+    }
+
+    /// Prefixes the path of the field by the path of the options containing it.
+    pub fn nested(self, parent: &str) -> Self {
+        Self {
+            field: format!("{parent}.{}", self.field),
+            msg: self.msg,
+        }
+    }
+}
+
+/// Options validated after loading, for the constraints serde can't express, like the
+/// ranges of values or the options required together.
+pub trait Validate {
+    fn validate(&self) -> Result<(), FieldError> {
+        Ok(())
+    }
+}
+
+/// Validates the address `host:port` of the option `field`.
+pub fn validate_addr(field: &str, addr: &str) -> Result<(), FieldError> {
+    let Some((host, port)) = addr.rsplit_once(':') else {
+        return Err(FieldError::new(
+            field,
+            format!("missing port in address {addr}"),
+        ));
+    };
+    if host.is_empty() {
+        return Err(FieldError::new(
+            field,
+            format!("missing host in address {addr}"),
+        ));
+    }
+    if port.parse::<u16>().is_err() {
+        return Err(FieldError::new(
+            field,
+            format!("invalid port {port} in address {addr}, should be in 0..=65535"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_addr() {
+        validate_addr("addr", "127.0.0.1:4000").unwrap();
+        validate_addr("addr", "datanode-0.greptime:3001").unwrap();
+        validate_addr("addr", "[::1]:4000").unwrap();
+
+        let err = validate_addr("http_options.addr", "127.0.0.1:70000").unwrap_err();
+        assert_eq!("http_options.addr", err.field);
+        assert!(err.msg.contains("70000"), "{}", err.msg);
+        assert!(validate_addr("addr", "127.0.0.1").is_err());
+        assert!(validate_addr("addr", ":4000").is_err());
+
+        let err = FieldError::new("addr", "msg").nested("http_options");
+        assert_eq!("http_options.addr", err.field);
+    }
+}
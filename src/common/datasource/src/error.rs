@@ -74,6 +74,12 @@ pub enum Error {
         source: datafusion::parquet::errors::ParquetError,
     },
 
+    #[snafu(display("Failed to read orc file, source: {}", source))]
+    ReadOrc {
+        location: Location,
+        source: orc_rust::error::Error,
+    },
+
     #[snafu(display("Failed to convert parquet to schema: {}", source))]
     ParquetToSchema {
         location: Location,
@@ -118,6 +124,13 @@ pub enum Error {
 
     #[snafu(display("Missing required field: {}", name))]
     MissingRequiredField { name: String, location: Location },
+
+    #[snafu(display("Failed to encode record batch as {}, source: {}", format, source))]
+    EncodeRecordBatch {
+        format: String,
+        source: arrow_schema::ArrowError,
+        location: Location,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -139,12 +152,15 @@ impl ErrorExt for Error {
             | InvalidPath { .. }
             | InferSchema { .. }
             | ReadParquetSnafu { .. }
+            | ReadOrc { .. }
             | ParquetToSchema { .. }
             | ParseFormat { .. }
             | MergeSchema { .. }
             | MissingRequiredField { .. } => StatusCode::InvalidArguments,
 
-            Decompression { .. } | JoinHandle { .. } => StatusCode::Unexpected,
+            Decompression { .. } | JoinHandle { .. } | EncodeRecordBatch { .. } => {
+                StatusCode::Unexpected
+            }
         }
     }
 
@@ -160,12 +176,14 @@ impl ErrorExt for Error {
             ListObjects { location, .. } => Some(*location),
             InferSchema { location, .. } => Some(*location),
             ReadParquetSnafu { location, .. } => Some(*location),
+            ReadOrc { location, .. } => Some(*location),
             ParquetToSchema { location, .. } => Some(*location),
             Decompression { location, .. } => Some(*location),
             JoinHandle { location, .. } => Some(*location),
             ParseFormat { location, .. } => Some(*location),
             MergeSchema { location, .. } => Some(*location),
             MissingRequiredField { location, .. } => Some(*location),
+            EncodeRecordBatch { location, .. } => Some(*location),
 
             UnsupportedBackendProtocol { location, .. } => Some(*location),
             EmptyHostPath { location, .. } => Some(*location),
@@ -14,6 +14,7 @@
 
 pub mod csv;
 pub mod json;
+pub mod orc;
 pub mod parquet;
 #[cfg(test)]
 pub mod tests;
@@ -37,21 +38,26 @@ use snafu::ResultExt;
 
 use self::csv::CsvFormat;
 use self::json::JsonFormat;
+use self::orc::OrcFormat;
 use self::parquet::ParquetFormat;
 use crate::compression::CompressionType;
 use crate::error::{self, Result};
 
 pub const FORMAT_COMPRESSION_TYPE: &str = "COMPRESSION_TYPE";
+pub const FORMAT_DELIMITER: &str = "DELIMITER";
+/// The misspelled key of the delimiter, which is still accepted for compatibility.
 pub const FORMAT_DELIMTERL: &str = "DELIMTERL";
 pub const FORMAT_SCHEMA_INFER_MAX_RECORD: &str = "SCHEMA_INFER_MAX_RECORD";
 pub const FORMAT_HAS_HEADER: &str = "FORMAT_HAS_HEADER";
+pub const FORMAT_COLUMN_TYPES: &str = "FORMAT_COLUMN_TYPES";
 pub const FORMAT_TYPE: &str = "FORMAT";
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Format {
     Csv(CsvFormat),
     Json(JsonFormat),
     Parquet(ParquetFormat),
+    Orc(OrcFormat),
 }
 
 impl TryFrom<&HashMap<String, String>> for Format {
@@ -67,11 +73,48 @@ impl TryFrom<&HashMap<String, String>> for Format {
             "CSV" => Ok(Self::Csv(CsvFormat::try_from(options)?)),
             "JSON" => Ok(Self::Json(JsonFormat::try_from(options)?)),
             "PARQUET" => Ok(Self::Parquet(ParquetFormat::default())),
+            "ORC" => Ok(Self::Orc(OrcFormat::default())),
             _ => error::UnsupportedFormatSnafu { format: &format }.fail(),
         }
     }
 }
 
+impl Format {
+    /// Returns the options in canonical keys, from which the same format can be parsed.
+    pub fn to_options(&self) -> HashMap<String, String> {
+        let (format, mut options) = match self {
+            Self::Csv(format) => ("csv", format.to_options()),
+            Self::Json(format) => ("json", format.to_options()),
+            Self::Parquet(format) => ("parquet", format.to_options()),
+            Self::Orc(format) => ("orc", format.to_options()),
+        };
+        let _ = options.insert(FORMAT_TYPE.to_string(), format.to_string());
+        options
+    }
+}
+
+/// Returns whether `key` is an option of the file formats, including the deprecated ones.
+pub fn is_format_option(key: &str) -> bool {
+    matches!(
+        key,
+        FORMAT_TYPE
+            | FORMAT_COMPRESSION_TYPE
+            | FORMAT_DELIMITER
+            | FORMAT_DELIMTERL
+            | FORMAT_SCHEMA_INFER_MAX_RECORD
+            | FORMAT_HAS_HEADER
+            | FORMAT_COLUMN_TYPES
+    )
+}
+
+/// Replaces the format options in `options` with the canonical ones of the parsed format.
+pub fn normalize_format_options(options: &mut HashMap<String, String>) -> Result<Format> {
+    let format = Format::try_from(&*options)?;
+    options.retain(|key, _| !is_format_option(key));
+    options.extend(format.to_options());
+    Ok(format)
+}
+
 #[async_trait]
 pub trait FileFormat: Send + Sync + std::fmt::Debug {
     async fn infer_schema(&self, store: &ObjectStore, path: String) -> Result<ArrowSchema>;
@@ -18,7 +18,8 @@ use std::sync::Arc;
 
 use arrow::csv;
 use arrow::csv::reader::infer_reader_schema as infer_csv_schema;
-use arrow_schema::{Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
 use async_trait::async_trait;
 use common_runtime;
 use datafusion::error::Result as DataFusionResult;
@@ -32,12 +33,14 @@ use crate::compression::CompressionType;
 use crate::error::{self, Result};
 use crate::file_format::{self, open_with_decoder, FileFormat};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CsvFormat {
     pub has_header: bool,
     pub delimiter: u8,
     pub schema_infer_max_record: Option<usize>,
     pub compression_type: CompressionType,
+    /// Types of the columns that take precedence over the inferred ones.
+    pub column_types: Vec<(String, DataType)>,
 }
 
 impl TryFrom<&HashMap<String, String>> for CsvFormat {
@@ -45,11 +48,14 @@ impl TryFrom<&HashMap<String, String>> for CsvFormat {
 
     fn try_from(value: &HashMap<String, String>) -> Result<Self> {
         let mut format = CsvFormat::default();
-        if let Some(delimiter) = value.get(file_format::FORMAT_DELIMTERL) {
+        let delimiter = [file_format::FORMAT_DELIMITER, file_format::FORMAT_DELIMTERL]
+            .into_iter()
+            .find_map(|key| value.get(key).map(|delimiter| (key, delimiter)));
+        if let Some((key, delimiter)) = delimiter {
             // TODO(weny): considers to support parse like "\t" (not only b'\t')
             format.delimiter = u8::from_str(delimiter).map_err(|_| {
                 error::ParseFormatSnafu {
-                    key: file_format::FORMAT_DELIMTERL,
+                    key,
                     value: delimiter,
                 }
                 .build()
@@ -79,10 +85,96 @@ impl TryFrom<&HashMap<String, String>> for CsvFormat {
                 .build()
             })?;
         }
+        if let Some(column_types) = value.get(file_format::FORMAT_COLUMN_TYPES) {
+            format.column_types = parse_column_types(column_types)?;
+        }
         Ok(format)
     }
 }
 
+/// Parses the column types like `id:string,ts:timestamp_ms`.
+fn parse_column_types(value: &str) -> Result<Vec<(String, DataType)>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|column_type| !column_type.is_empty())
+        .map(|column_type| {
+            column_type
+                .split_once(':')
+                .and_then(|(name, data_type)| {
+                    let name = name.trim();
+                    let data_type = parse_data_type(data_type.trim())?;
+                    (!name.is_empty()).then(|| (name.to_string(), data_type))
+                })
+                .ok_or_else(|| {
+                    error::ParseFormatSnafu {
+                        key: file_format::FORMAT_COLUMN_TYPES,
+                        value: column_type,
+                    }
+                    .build()
+                })
+        })
+        .collect()
+}
+
+/// Formats the column types in the same way as [parse_column_types] parses them, the types
+/// that can't be parsed from the options are skipped.
+fn format_column_types(column_types: &[(String, DataType)]) -> String {
+    column_types
+        .iter()
+        .filter_map(|(name, data_type)| Some(format!("{name}:{}", data_type_name(data_type)?)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_data_type(name: &str) -> Option<DataType> {
+    let data_type = match name.to_ascii_lowercase().as_str() {
+        "string" => DataType::Utf8,
+        "boolean" => DataType::Boolean,
+        "int8" => DataType::Int8,
+        "int16" => DataType::Int16,
+        "int32" => DataType::Int32,
+        "int64" => DataType::Int64,
+        "uint8" => DataType::UInt8,
+        "uint16" => DataType::UInt16,
+        "uint32" => DataType::UInt32,
+        "uint64" => DataType::UInt64,
+        "float32" => DataType::Float32,
+        "float64" => DataType::Float64,
+        "date" => DataType::Date32,
+        "timestamp_s" => DataType::Timestamp(TimeUnit::Second, None),
+        "timestamp_ms" => DataType::Timestamp(TimeUnit::Millisecond, None),
+        "timestamp_us" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "timestamp_ns" => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        _ => return None,
+    };
+    Some(data_type)
+}
+
+fn data_type_name(data_type: &DataType) -> Option<&'static str> {
+    let name = match data_type {
+        DataType::Utf8 => "string",
+        DataType::Boolean => "boolean",
+        DataType::Int8 => "int8",
+        DataType::Int16 => "int16",
+        DataType::Int32 => "int32",
+        DataType::Int64 => "int64",
+        DataType::UInt8 => "uint8",
+        DataType::UInt16 => "uint16",
+        DataType::UInt32 => "uint32",
+        DataType::UInt64 => "uint64",
+        DataType::Float32 => "float32",
+        DataType::Float64 => "float64",
+        DataType::Date32 => "date",
+        DataType::Timestamp(TimeUnit::Second, _) => "timestamp_s",
+        DataType::Timestamp(TimeUnit::Millisecond, _) => "timestamp_ms",
+        DataType::Timestamp(TimeUnit::Microsecond, _) => "timestamp_us",
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => "timestamp_ns",
+        _ => return None,
+    };
+    Some(name)
+}
+
 impl Default for CsvFormat {
     fn default() -> Self {
         Self {
@@ -90,10 +182,82 @@ impl Default for CsvFormat {
             delimiter: b',',
             schema_infer_max_record: Some(file_format::DEFAULT_SCHEMA_INFER_MAX_RECORD),
             compression_type: CompressionType::UNCOMPRESSED,
+            column_types: vec![],
         }
     }
 }
 
+impl CsvFormat {
+    /// Returns the options in canonical keys, from which the same format can be parsed.
+    pub fn to_options(&self) -> HashMap<String, String> {
+        let mut options = HashMap::new();
+        let _ = options.insert(
+            file_format::FORMAT_HAS_HEADER.to_string(),
+            self.has_header.to_string(),
+        );
+        let _ = options.insert(
+            file_format::FORMAT_DELIMITER.to_string(),
+            self.delimiter.to_string(),
+        );
+        if let Some(schema_infer_max_record) = self.schema_infer_max_record {
+            let _ = options.insert(
+                file_format::FORMAT_SCHEMA_INFER_MAX_RECORD.to_string(),
+                schema_infer_max_record.to_string(),
+            );
+        }
+        if self.compression_type.is_compressed() {
+            let _ = options.insert(
+                file_format::FORMAT_COMPRESSION_TYPE.to_string(),
+                self.compression_type.to_string(),
+            );
+        }
+        if !self.column_types.is_empty() {
+            let _ = options.insert(
+                file_format::FORMAT_COLUMN_TYPES.to_string(),
+                format_column_types(&self.column_types),
+            );
+        }
+        options
+    }
+
+    /// Encodes `batch` as CSV rows, preceded by the header if the format has one and
+    /// `first_batch` is true. The rows are not compressed.
+    pub fn encode(&self, batch: &RecordBatch, first_batch: bool) -> Result<Vec<u8>> {
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(self.has_header && first_batch)
+            .with_delimiter(self.delimiter)
+            .build(Vec::new());
+        writer
+            .write(batch)
+            .context(error::EncodeRecordBatchSnafu { format: "csv" })?;
+        Ok(writer.into_inner())
+    }
+
+    /// Replaces the types of the columns in `schema` with the ones in `column_types`.
+    fn override_column_types(&self, schema: Schema) -> Schema {
+        if self.column_types.is_empty() {
+            return schema;
+        }
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                match self
+                    .column_types
+                    .iter()
+                    .find(|(name, _)| name == field.name())
+                {
+                    Some((_, data_type)) => {
+                        Field::new(field.name(), data_type.clone(), field.is_nullable())
+                    }
+                    None => field.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        Schema::new_with_metadata(fields, schema.metadata().clone())
+    }
+}
+
 #[derive(Debug, Clone, Builder)]
 pub struct CsvConfig {
     batch_size: usize,
@@ -179,6 +343,7 @@ impl FileFormat for CsvFormat {
         })
         .await
         .context(error::JoinHandleSnafu)?
+        .map(|schema| self.override_column_types(schema))
     }
 }
 
@@ -187,8 +352,8 @@ mod tests {
 
     use super::*;
     use crate::file_format::{
-        FileFormat, FORMAT_COMPRESSION_TYPE, FORMAT_DELIMTERL, FORMAT_HAS_HEADER,
-        FORMAT_SCHEMA_INFER_MAX_RECORD,
+        infer_schemas, FileFormat, FORMAT_COLUMN_TYPES, FORMAT_COMPRESSION_TYPE, FORMAT_DELIMITER,
+        FORMAT_DELIMTERL, FORMAT_HAS_HEADER, FORMAT_SCHEMA_INFER_MAX_RECORD,
     };
     use crate::test_util::{self, format_schema, test_store};
 
@@ -293,7 +458,128 @@ mod tests {
                 schema_infer_max_record: Some(2000),
                 delimiter: b'\t',
                 has_header: false,
+                column_types: vec![],
             }
         );
     }
+
+    #[test]
+    fn test_try_from_delimiter() {
+        let mut map = HashMap::new();
+        map.insert(FORMAT_DELIMTERL.to_string(), b'\t'.to_string());
+        assert_eq!(b'\t', CsvFormat::try_from(&map).unwrap().delimiter);
+
+        // The canonical key takes precedence over the misspelled one.
+        map.insert(FORMAT_DELIMITER.to_string(), b'|'.to_string());
+        assert_eq!(b'|', CsvFormat::try_from(&map).unwrap().delimiter);
+
+        map.insert(FORMAT_DELIMITER.to_string(), "|".to_string());
+        let err = CsvFormat::try_from(&map).unwrap_err();
+        assert!(
+            matches!(&err, error::Error::ParseFormat { key, .. } if *key == FORMAT_DELIMITER),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_try_from_column_types() {
+        let mut map = HashMap::new();
+        map.insert(
+            FORMAT_COLUMN_TYPES.to_string(),
+            "id:string, ts:Timestamp_MS,".to_string(),
+        );
+        let format = CsvFormat::try_from(&map).unwrap();
+        assert_eq!(
+            vec![
+                ("id".to_string(), DataType::Utf8),
+                (
+                    "ts".to_string(),
+                    DataType::Timestamp(TimeUnit::Millisecond, None)
+                ),
+            ],
+            format.column_types
+        );
+
+        for (value, offending) in [
+            ("id:string,ts:timestamp_xs", "ts:timestamp_xs"),
+            ("id", "id"),
+            (":string", ":string"),
+        ] {
+            map.insert(FORMAT_COLUMN_TYPES.to_string(), value.to_string());
+            let err = CsvFormat::try_from(&map).unwrap_err();
+            assert!(
+                matches!(&err, error::Error::ParseFormat { value, .. } if value == offending),
+                "{err:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("host", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(arrow::array::Int64Array::from(vec![1, 2])),
+                Arc::new(arrow::array::StringArray::from(vec![Some("a"), None])),
+            ],
+        )
+        .unwrap();
+
+        let csv = CsvFormat {
+            delimiter: b'|',
+            ..Default::default()
+        };
+        let encoded = csv.encode(&batch, true).unwrap();
+        assert_eq!("id|host\n1|a\n2|\n", String::from_utf8(encoded).unwrap());
+        // Only the first batch of a file has the header.
+        let encoded = csv.encode(&batch, false).unwrap();
+        assert_eq!("1|a\n2|\n", String::from_utf8(encoded).unwrap());
+
+        let csv = CsvFormat {
+            has_header: false,
+            ..Default::default()
+        };
+        let encoded = csv.encode(&batch, true).unwrap();
+        assert_eq!("1,a\n2,\n", String::from_utf8(encoded).unwrap());
+    }
+
+    #[tokio::test]
+    async fn infer_schema_with_column_types() {
+        let store = test_store(&test_data_root());
+        let files = vec![
+            "column_types_1.csv".to_string(),
+            "column_types_2.csv".to_string(),
+        ];
+
+        // The id column is inferred as Int64 in the first file but as Utf8 in the second one.
+        let csv = CsvFormat::default();
+        let schema = csv.infer_schema(&store, files[0].clone()).await.unwrap();
+        assert_eq!("id: Int64: NULL", format_schema(schema)[0]);
+        assert!(infer_schemas(&store, &files, &csv).await.is_err());
+
+        let csv = CsvFormat {
+            column_types: vec![
+                ("id".to_string(), DataType::Utf8),
+                (
+                    "ts".to_string(),
+                    DataType::Timestamp(TimeUnit::Millisecond, None),
+                ),
+            ],
+            ..CsvFormat::default()
+        };
+        let schema = csv.infer_schema(&store, files[0].clone()).await.unwrap();
+        let expected = vec![
+            "id: Utf8: NULL",
+            "ts: Timestamp(Millisecond, None): NULL",
+            "value: Float64: NULL",
+        ];
+        assert_eq!(expected, format_schema(schema));
+
+        let merged = infer_schemas(&store, &files, &csv).await.unwrap();
+        assert_eq!(expected, format_schema(merged));
+    }
 }
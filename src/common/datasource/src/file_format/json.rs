@@ -19,7 +19,8 @@ use std::sync::Arc;
 
 use arrow::datatypes::SchemaRef;
 use arrow::json::reader::{infer_json_schema_from_iterator, ValueIter};
-use arrow::json::RawReaderBuilder;
+use arrow::json::{LineDelimitedWriter, RawReaderBuilder};
+use arrow::record_batch::RecordBatch;
 use arrow_schema::Schema;
 use async_trait::async_trait;
 use common_runtime;
@@ -63,6 +64,36 @@ impl TryFrom<&HashMap<String, String>> for JsonFormat {
     }
 }
 
+impl JsonFormat {
+    /// Returns the options in canonical keys, from which the same format can be parsed.
+    pub fn to_options(&self) -> HashMap<String, String> {
+        let mut options = HashMap::new();
+        if let Some(schema_infer_max_record) = self.schema_infer_max_record {
+            let _ = options.insert(
+                file_format::FORMAT_SCHEMA_INFER_MAX_RECORD.to_string(),
+                schema_infer_max_record.to_string(),
+            );
+        }
+        if self.compression_type.is_compressed() {
+            let _ = options.insert(
+                file_format::FORMAT_COMPRESSION_TYPE.to_string(),
+                self.compression_type.to_string(),
+            );
+        }
+        options
+    }
+
+    /// Encodes `batch` as line delimited JSON objects. The rows are not compressed.
+    pub fn encode(&self, batch: &RecordBatch) -> Result<Vec<u8>> {
+        let mut writer = LineDelimitedWriter::new(Vec::new());
+        writer
+            .write(batch.clone())
+            .and_then(|_| writer.finish())
+            .context(error::EncodeRecordBatchSnafu { format: "json" })?;
+        Ok(writer.into_inner())
+    }
+}
+
 impl Default for JsonFormat {
     fn default() -> Self {
         Self {
@@ -214,4 +245,27 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_encode() {
+        let schema = Arc::new(Schema::new(vec![
+            arrow_schema::Field::new("id", arrow_schema::DataType::Int64, false),
+            arrow_schema::Field::new("host", arrow_schema::DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(arrow::array::Int64Array::from(vec![1, 2])),
+                Arc::new(arrow::array::StringArray::from(vec![Some("a"), None])),
+            ],
+        )
+        .unwrap();
+
+        let encoded = String::from_utf8(JsonFormat::default().encode(&batch).unwrap()).unwrap();
+        let lines = encoded.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        assert!(lines[0].contains(r#""id":1"#) && lines[0].contains(r#""host":"a""#));
+        // Nulls are left out.
+        assert_eq!(r#"{"id":2}"#, lines[1]);
+    }
 }
@@ -0,0 +1,168 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use arrow::record_batch::RecordBatch;
+use arrow_schema::{ArrowError, Schema, SchemaRef};
+use async_trait::async_trait;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::physical_plan::file_format::{FileMeta, FileOpenFuture, FileOpener};
+use futures::{Stream, StreamExt};
+use object_store::ObjectStore;
+use orc_rust::arrow_reader::{create_arrow_schema, Cursor};
+use orc_rust::async_arrow_reader::ArrowStreamReader;
+use orc_rust::reader::Reader;
+use snafu::ResultExt;
+use tokio::io::{AsyncRead, AsyncSeek};
+
+use crate::error::{self, Result};
+use crate::file_format::FileFormat;
+
+/// ORC files compress their stripes by themselves, so there is no compression type to set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OrcFormat {}
+
+impl OrcFormat {
+    /// Returns the options in canonical keys, there is no option besides the format itself.
+    pub fn to_options(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+}
+
+async fn new_orc_cursor<R: AsyncRead + AsyncSeek + Unpin + Send + 'static>(
+    reader: R,
+) -> Result<Cursor<R>> {
+    let reader = Reader::new_async(reader)
+        .await
+        .context(error::ReadOrcSnafu)?;
+    Cursor::root(reader).context(error::ReadOrcSnafu)
+}
+
+/// Returns a stream of the record batches in the ORC file read by `reader`.
+pub async fn new_orc_stream_reader<R: AsyncRead + AsyncSeek + Unpin + Send + 'static>(
+    reader: R,
+) -> Result<OrcArrowStreamReaderAdapter<R>> {
+    let cursor = new_orc_cursor(reader).await?;
+    Ok(OrcArrowStreamReaderAdapter {
+        stream: ArrowStreamReader::new(cursor, None),
+    })
+}
+
+/// Reads the schema from the footer of the ORC file read by `reader`.
+pub async fn infer_orc_schema<R: AsyncRead + AsyncSeek + Unpin + Send + 'static>(
+    reader: R,
+) -> Result<Schema> {
+    let cursor = new_orc_cursor(reader).await?;
+    Ok(create_arrow_schema(&cursor))
+}
+
+/// Adapts [ArrowStreamReader] to a stream of arrow [RecordBatch]es.
+pub struct OrcArrowStreamReaderAdapter<R: AsyncRead + AsyncSeek + Unpin + Send + 'static> {
+    stream: ArrowStreamReader<R>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin + Send + 'static> OrcArrowStreamReaderAdapter<R> {
+    pub fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin + Send + 'static> Stream for OrcArrowStreamReaderAdapter<R> {
+    type Item = std::result::Result<RecordBatch, ArrowError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let batch = futures::ready!(Pin::new(&mut self.stream).poll_next(cx))
+            .map(|batch| batch.map_err(|e| ArrowError::ExternalError(Box::new(e))));
+        Poll::Ready(batch)
+    }
+}
+
+#[async_trait]
+impl FileFormat for OrcFormat {
+    async fn infer_schema(&self, store: &ObjectStore, path: String) -> Result<Schema> {
+        let reader = store
+            .reader(&path)
+            .await
+            .context(error::ReadObjectSnafu { path: &path })?;
+
+        infer_orc_schema(reader).await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OrcOpener {
+    object_store: Arc<ObjectStore>,
+    projection: Option<Vec<usize>>,
+}
+
+impl OrcOpener {
+    /// Return a new [`OrcOpener`], the record batches are projected by `projection` if any.
+    pub fn new(object_store: ObjectStore, projection: Option<Vec<usize>>) -> Self {
+        Self {
+            object_store: Arc::new(object_store),
+            projection,
+        }
+    }
+}
+
+impl FileOpener for OrcOpener {
+    fn open(&self, meta: FileMeta) -> DataFusionResult<FileOpenFuture> {
+        let object_store = self.object_store.clone();
+        let projection = self.projection.clone();
+        Ok(Box::pin(async move {
+            let reader = object_store
+                .reader(&meta.location().to_string())
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+            let stream = new_orc_stream_reader(reader)
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+            let stream = stream.map(move |batch| match &projection {
+                Some(projection) => batch?.project(projection),
+                None => batch,
+            });
+
+            Ok(stream.boxed())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, format_schema, test_store};
+
+    fn test_data_root() -> String {
+        test_util::get_data_dir("tests/orc").display().to_string()
+    }
+
+    #[tokio::test]
+    async fn infer_schema_basic() {
+        let orc = OrcFormat::default();
+        let store = test_store(&test_data_root());
+        let schema = orc
+            .infer_schema(&store, "basic.orc".to_string())
+            .await
+            .unwrap();
+        let formatted: Vec<_> = format_schema(schema);
+
+        assert_eq!(vec!["num: Int64: NULL", "str: Utf8: NULL"], formatted);
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::result;
 use std::sync::Arc;
 
@@ -34,6 +35,13 @@ use crate::file_format::FileFormat;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct ParquetFormat {}
 
+impl ParquetFormat {
+    /// Returns the options in canonical keys, there is no option besides the format itself.
+    pub fn to_options(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+}
+
 #[async_trait]
 impl FileFormat for ParquetFormat {
     async fn infer_schema(&self, store: &ObjectStore, path: String) -> Result<Schema> {
@@ -28,11 +28,15 @@ use datafusion::physical_plan::ExecutionPlan;
 use datafusion::prelude::SessionContext;
 use futures::StreamExt;
 
-use super::FORMAT_TYPE;
+use super::{
+    normalize_format_options, FORMAT_COLUMN_TYPES, FORMAT_COMPRESSION_TYPE, FORMAT_DELIMITER,
+    FORMAT_DELIMTERL, FORMAT_HAS_HEADER, FORMAT_SCHEMA_INFER_MAX_RECORD, FORMAT_TYPE,
+};
 use crate::compression::CompressionType;
 use crate::error;
 use crate::file_format::csv::{CsvConfigBuilder, CsvOpener};
 use crate::file_format::json::JsonOpener;
+use crate::file_format::orc::OrcOpener;
 use crate::file_format::parquet::DefaultParquetFileReaderFactory;
 use crate::file_format::Format;
 use crate::test_util::{self, test_basic_schema, test_store};
@@ -210,6 +214,60 @@ async fn test_parquet_exec() {
     );
 }
 
+#[tokio::test]
+async fn test_orc_opener() {
+    let store = test_store("/");
+
+    let schema = test_basic_schema();
+    let path = &test_util::get_data_dir("tests/orc/basic.orc")
+        .display()
+        .to_string();
+
+    let tests = [
+        Test {
+            config: scan_config(schema.clone(), None, path),
+            opener: OrcOpener::new(store.clone(), None),
+            expected: vec![
+                "+-----+-------+",
+                "| num | str   |",
+                "+-----+-------+",
+                "| 5   | test  |",
+                "| 2   | hello |",
+                "| 4   | foo   |",
+                "+-----+-------+",
+            ],
+        },
+        Test {
+            config: scan_config(schema.clone(), Some(1), path),
+            opener: OrcOpener::new(store.clone(), None),
+            expected: vec![
+                "+-----+------+",
+                "| num | str  |",
+                "+-----+------+",
+                "| 5   | test |",
+                "+-----+------+",
+            ],
+        },
+        Test {
+            config: scan_config(schema.clone(), None, path),
+            opener: OrcOpener::new(store.clone(), Some(vec![1])),
+            expected: vec![
+                "+-------+",
+                "| str   |",
+                "+-------+",
+                "| test  |",
+                "| hello |",
+                "| foo   |",
+                "+-------+",
+            ],
+        },
+    ];
+
+    for test in tests {
+        test.run().await;
+    }
+}
+
 #[test]
 fn test_format() {
     let value = [(FORMAT_TYPE.to_string(), "csv".to_string())]
@@ -230,6 +288,12 @@ fn test_format() {
 
     assert_matches!(Format::try_from(&value).unwrap(), Format::Json(_));
 
+    let value = [(FORMAT_TYPE.to_string(), "ORC".to_string())]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    assert_matches!(Format::try_from(&value).unwrap(), Format::Orc(_));
+
     let value = [(FORMAT_TYPE.to_string(), "Foobar".to_string())]
         .into_iter()
         .collect::<HashMap<_, _>>();
@@ -243,3 +307,73 @@ fn test_format() {
 
     assert_matches!(Format::try_from(&value).unwrap(), Format::Parquet(_));
 }
+
+fn options(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_format_options_round_trip() {
+    let cases = [
+        options(&[
+            (FORMAT_TYPE, "csv"),
+            (FORMAT_HAS_HEADER, "false"),
+            (FORMAT_DELIMITER, "9"),
+            (FORMAT_SCHEMA_INFER_MAX_RECORD, "2000"),
+            (FORMAT_COMPRESSION_TYPE, "ZSTD"),
+            (FORMAT_COLUMN_TYPES, "id:string,ts:timestamp_ms,v:float64"),
+        ]),
+        options(&[
+            (FORMAT_TYPE, "csv"),
+            (FORMAT_HAS_HEADER, "true"),
+            (FORMAT_DELIMITER, "44"),
+            (FORMAT_SCHEMA_INFER_MAX_RECORD, "1000"),
+        ]),
+        options(&[
+            (FORMAT_TYPE, "json"),
+            (FORMAT_SCHEMA_INFER_MAX_RECORD, "10"),
+            (FORMAT_COMPRESSION_TYPE, "GZIP"),
+        ]),
+        options(&[
+            (FORMAT_TYPE, "json"),
+            (FORMAT_SCHEMA_INFER_MAX_RECORD, "1000"),
+        ]),
+        options(&[(FORMAT_TYPE, "parquet")]),
+        options(&[(FORMAT_TYPE, "orc")]),
+    ];
+
+    for expected in cases {
+        let format = Format::try_from(&expected).unwrap();
+        let options = format.to_options();
+        assert_eq!(expected, options);
+        assert_eq!(format, Format::try_from(&options).unwrap());
+    }
+}
+
+#[test]
+fn test_normalize_format_options() {
+    let mut value = options(&[
+        ("LOCATION", "/data/"),
+        (FORMAT_TYPE, "CSV"),
+        (FORMAT_DELIMTERL, "9"),
+        (FORMAT_COMPRESSION_TYPE, "gz"),
+    ]);
+
+    let format = normalize_format_options(&mut value).unwrap();
+
+    assert_matches!(&format, Format::Csv(csv) if csv.delimiter == b'\t');
+    assert_eq!(
+        options(&[
+            ("LOCATION", "/data/"),
+            (FORMAT_TYPE, "csv"),
+            (FORMAT_HAS_HEADER, "true"),
+            (FORMAT_DELIMITER, "9"),
+            (FORMAT_SCHEMA_INFER_MAX_RECORD, "1000"),
+            (FORMAT_COMPRESSION_TYPE, "GZIP"),
+        ]),
+        value
+    );
+}
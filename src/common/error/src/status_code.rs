@@ -33,6 +33,11 @@ pub enum StatusCode {
     Internal = 1003,
     /// Invalid arguments.
     InvalidArguments = 1004,
+    /// The request is cancelled, e.g. it exceeds its deadline.
+    Cancelled = 1005,
+    /// The request conflicts with a concurrent one, e.g. the DDL on the same table, and may
+    /// succeed if retried later.
+    Conflict = 1006,
     // ====== End of common status code ================
 
     // ====== Begin of SQL related status code =========
@@ -91,7 +96,9 @@ impl StatusCode {
         match self {
             StatusCode::StorageUnavailable
             | StatusCode::RuntimeResourcesExhausted
-            | StatusCode::Internal => true,
+            | StatusCode::Internal
+            | StatusCode::Cancelled
+            | StatusCode::Conflict => true,
 
             StatusCode::Success
             | StatusCode::Unknown
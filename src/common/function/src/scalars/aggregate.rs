@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod approx_percentile;
 mod argmax;
 mod argmin;
+pub mod counter;
 mod diff;
 mod mean;
 mod percentile;
@@ -23,9 +25,11 @@ mod scipy_stats_norm_pdf;
 
 use std::sync::Arc;
 
+pub use approx_percentile::ApproxPercentileAccumulatorCreator;
 pub use argmax::ArgmaxAccumulatorCreator;
 pub use argmin::ArgminAccumulatorCreator;
 use common_query::logical_plan::AggregateFunctionCreatorRef;
+pub use counter::{DeltaAccumulatorCreator, IncreaseAccumulatorCreator, RateAccumulatorCreator};
 pub use diff::DiffAccumulatorCreator;
 pub use mean::MeanAccumulatorCreator;
 pub use percentile::PercentileAccumulatorCreator;
@@ -92,7 +96,11 @@ impl AggregateFunctions {
         register_aggr_func!("argmax", 1, ArgmaxAccumulatorCreator);
         register_aggr_func!("argmin", 1, ArgminAccumulatorCreator);
         register_aggr_func!("percentile", 2, PercentileAccumulatorCreator);
+        register_aggr_func!("approx_percentile", 2, ApproxPercentileAccumulatorCreator);
         register_aggr_func!("scipystatsnormcdf", 2, ScipyStatsNormCdfAccumulatorCreator);
         register_aggr_func!("scipystatsnormpdf", 2, ScipyStatsNormPdfAccumulatorCreator);
+        register_aggr_func!("rate", 2, RateAccumulatorCreator);
+        register_aggr_func!("increase", 2, IncreaseAccumulatorCreator);
+        register_aggr_func!("delta", 2, DeltaAccumulatorCreator);
     }
 }
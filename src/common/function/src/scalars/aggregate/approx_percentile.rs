@@ -0,0 +1,382 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use common_function_macro::{as_aggr_func_creator, AggrFuncTypeStore};
+use common_query::error::{
+    self, BadAccumulatorImplSnafu, CreateAccumulatorSnafu, FromArrowArraySnafu,
+    InvalidFuncArgsSnafu, InvalidInputColSnafu, Result, TypeCastSnafu,
+};
+use common_query::logical_plan::{Accumulator, AggregateFunctionCreator};
+use common_query::prelude::*;
+use datatypes::arrow::compute;
+use datatypes::arrow::datatypes::DataType as ArrowDataType;
+use datatypes::prelude::*;
+use datatypes::value::{ListValue, OrderedFloat};
+use datatypes::vectors::{Float64Vector, Helper};
+use datatypes::with_match_primitive_type_id;
+use snafu::{ensure, ResultExt};
+
+/// Compression of the digest, which keeps about this number of centroids after compressed.
+const COMPRESSION: f64 = 100.0;
+
+/// The digest is compressed once it has more centroids than this.
+const MAX_CENTROIDS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A t-digest (<https://arxiv.org/abs/1902.04023>), which summarizes a distribution by
+/// clusters of values (centroids) to estimate its percentiles.
+///
+/// Centroids near the tails of the distribution are kept small by the scale function, so
+/// the extreme percentiles are estimated precisely. A digest of no more than [MAX_CENTROIDS]
+/// values is never compressed and gives the exact percentiles.
+#[derive(Debug, Default)]
+struct TDigest {
+    centroids: Vec<Centroid>,
+}
+
+impl TDigest {
+    fn add(&mut self, mean: f64, weight: f64) {
+        self.centroids.push(Centroid { mean, weight });
+        if self.centroids.len() > MAX_CENTROIDS {
+            self.compress();
+        }
+    }
+
+    /// Merges the adjacent centroids as long as a centroid spans no more than one unit of
+    /// the scale function `k(q) = COMPRESSION / 2π * asin(2q - 1)`.
+    fn compress(&mut self) {
+        self.centroids.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = self.centroids.iter().map(|c| c.weight).sum();
+        let scale = |weight: f64| COMPRESSION / (2.0 * PI) * (2.0 * weight / total - 1.0).asin();
+
+        let mut centroids = self.centroids.iter().copied();
+        let Some(mut current) = centroids.next() else { return };
+        let mut compressed = Vec::new();
+        let mut weight_before = 0.0;
+        for centroid in centroids {
+            let weight = current.weight + centroid.weight;
+            if scale(weight_before + weight) - scale(weight_before) <= 1.0 {
+                current.mean += (centroid.mean - current.mean) * centroid.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_before += current.weight;
+                compressed.push(current);
+                current = centroid;
+            }
+        }
+        compressed.push(current);
+        self.centroids = compressed;
+    }
+
+    /// Estimates the `p`-th percentile, by linear interpolation between the centroids like
+    /// the `PERCENTILE` function.
+    fn percentile(&self, p: f64) -> Option<f64> {
+        let mut centroids = self.centroids.clone();
+        centroids.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let rank = (total - 1.0) * p / 100.0;
+
+        let mut weight_before = 0.0;
+        let mut prev: Option<(f64, f64)> = None;
+        for centroid in centroids {
+            // The rank of the center of the centroid, which is the rank of the only value of a
+            // centroid of weight 1.
+            let position = weight_before + (centroid.weight - 1.0) / 2.0;
+            if rank <= position {
+                let Some((prev_position, prev_mean)) = prev else { return Some(centroid.mean) };
+                let fract = (rank - prev_position) / (position - prev_position);
+                return Some(prev_mean * (1.0 - fract) + centroid.mean * fract);
+            }
+            prev = Some((position, centroid.mean));
+            weight_before += centroid.weight;
+        }
+        prev.map(|(_, mean)| mean)
+    }
+}
+
+/// Approximate percentile by a [TDigest], whose states can be merged across nodes, so the
+/// aggregation can be distributed.
+#[derive(Debug, Default)]
+pub struct ApproxPercentile {
+    digest: TDigest,
+    p: Option<f64>,
+}
+
+impl ApproxPercentile {
+    fn set_p(&mut self, p: f64) -> Result<()> {
+        ensure!(
+            (0.0..=100.0).contains(&p),
+            InvalidFuncArgsSnafu {
+                err_msg: format!(
+                    "expecting \"APPROX_PERCENTILE\" function's percentile in [0, 100], got {p}"
+                ),
+            }
+        );
+        if let Some(old) = self.p {
+            ensure!(old == p, InvalidInputColSnafu);
+        } else {
+            self.p = Some(p);
+        }
+        Ok(())
+    }
+}
+
+impl Accumulator for ApproxPercentile {
+    fn state(&self) -> Result<Vec<Value>> {
+        let (means, weights) = self
+            .digest
+            .centroids
+            .iter()
+            .map(|c| (Value::from(c.mean), Value::from(c.weight)))
+            .unzip();
+        Ok(vec![
+            Value::List(ListValue::new(
+                Some(Box::new(means)),
+                ConcreteDataType::float64_datatype(),
+            )),
+            Value::List(ListValue::new(
+                Some(Box::new(weights)),
+                ConcreteDataType::float64_datatype(),
+            )),
+            self.p.into(),
+        ])
+    }
+
+    fn update_batch(&mut self, values: &[VectorRef]) -> Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        ensure!(values.len() == 2, InvalidInputStateSnafu);
+        ensure!(values[0].len() == values[1].len(), InvalidInputStateSnafu);
+        if values[0].len() == 0 {
+            return Ok(());
+        }
+
+        let p = &values[1];
+        let p = Helper::check_get_scalar::<f64>(p).context(error::InvalidInputTypeSnafu {
+            err_msg: "expecting \"APPROX_PERCENTILE\" function's second argument to be float64",
+        })?;
+        // `get(0)` is safe because we have checked `values[1].len() == values[0].len() != 0`
+        let first = p.get(0);
+        for i in 1..p.len() {
+            ensure!(first == p.get(i), InvalidInputColSnafu);
+        }
+        let Value::Float64(OrderedFloat(first)) = first else {
+            return InvalidInputColSnafu.fail();
+        };
+        self.set_p(first)?;
+
+        let column = values[0].to_arrow_array();
+        let column = compute::cast(&column, &ArrowDataType::Float64).context(TypeCastSnafu {
+            typ: ArrowDataType::Float64,
+        })?;
+        let column = Float64Vector::try_from_arrow_array(column).context(FromArrowArraySnafu)?;
+        for v in column.iter_data().flatten() {
+            self.digest.add(v, 1.0);
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[VectorRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        ensure!(
+            states.len() == 3,
+            BadAccumulatorImplSnafu {
+                err_msg: "expect 3 states in `merge_batch`",
+            }
+        );
+
+        for i in 0..states[0].len() {
+            if let Value::Float64(OrderedFloat(p)) = states[2].get(i) {
+                self.set_p(p)?;
+            }
+            let (means, weights) = (states[0].get(i), states[1].get(i));
+            let (Value::List(means), Value::List(weights)) = (means, weights) else { continue };
+            let (Some(means), Some(weights)) = (means.items(), weights.items()) else { continue };
+            for (mean, weight) in means.iter().zip(weights.iter()) {
+                if let (Value::Float64(mean), Value::Float64(weight)) = (mean, weight) {
+                    self.digest.add(mean.0, weight.0);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<Value> {
+        let Some(p) = self.p else { return Ok(Value::Null) };
+        Ok(self.digest.percentile(p).into())
+    }
+}
+
+#[as_aggr_func_creator]
+#[derive(Debug, Default, AggrFuncTypeStore)]
+pub struct ApproxPercentileAccumulatorCreator {}
+
+impl AggregateFunctionCreator for ApproxPercentileAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        let creator: AccumulatorCreatorFunction = Arc::new(move |types: &[ConcreteDataType]| {
+            let input_type = &types[0];
+            with_match_primitive_type_id!(
+                input_type.logical_type_id(),
+                |$S| { Ok(Box::<ApproxPercentile>::default()) },
+                {
+                    let err_msg = format!(
+                        "\"APPROX_PERCENTILE\" aggregate function not support data type {:?}",
+                        input_type.logical_type_id(),
+                    );
+                    CreateAccumulatorSnafu { err_msg }.fail()?
+                }
+            )
+        });
+        creator
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        let input_types = self.input_types()?;
+        ensure!(input_types.len() == 2, InvalidInputStateSnafu);
+        Ok(ConcreteDataType::float64_datatype())
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        let input_types = self.input_types()?;
+        ensure!(input_types.len() == 2, InvalidInputStateSnafu);
+        Ok(vec![
+            ConcreteDataType::list_datatype(ConcreteDataType::float64_datatype()),
+            ConcreteDataType::list_datatype(ConcreteDataType::float64_datatype()),
+            ConcreteDataType::float64_datatype(),
+        ])
+    }
+
+    fn supports_distributed(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datatypes::vectors::Int32Vector;
+
+    use super::*;
+
+    fn update(accumulator: &mut ApproxPercentile, values: Vec<Option<i32>>, p: f64) {
+        let len = values.len();
+        let values: Vec<VectorRef> = vec![
+            Arc::new(Int32Vector::from(values)),
+            Arc::new(Float64Vector::from_vec(vec![p; len])),
+        ];
+        accumulator.update_batch(&values).unwrap();
+    }
+
+    #[test]
+    fn test_update_batch() {
+        let mut percentile = ApproxPercentile::default();
+        assert!(percentile.update_batch(&[]).is_ok());
+        assert_eq!(Value::Null, percentile.evaluate().unwrap());
+
+        let mut percentile = ApproxPercentile::default();
+        update(&mut percentile, vec![None], 50.0);
+        assert_eq!(Value::Null, percentile.evaluate().unwrap());
+
+        // Same as the result of numpy.percentile as the digest is not compressed.
+        let cases = [
+            (0.0, 4.0),
+            (40.0, 6.4),
+            (50.0, 7.0),
+            (95.0, 9.700_000_000_000_001),
+        ];
+        for (p, expect) in cases {
+            let mut percentile = ApproxPercentile::default();
+            update(&mut percentile, vec![Some(10), None, Some(7), Some(4)], p);
+            assert_eq!(Value::from(expect), percentile.evaluate().unwrap(), "{p}");
+        }
+
+        // The percentile is out of range.
+        let mut percentile = ApproxPercentile::default();
+        let values: Vec<VectorRef> = vec![
+            Arc::new(Int32Vector::from_slice([1])),
+            Arc::new(Float64Vector::from_slice([101.0])),
+        ];
+        assert!(percentile.update_batch(&values).is_err());
+    }
+
+    #[test]
+    fn test_merge_batch() {
+        let values = (0..100).map(Some).collect::<Vec<_>>();
+
+        let mut expect = ApproxPercentile::default();
+        update(&mut expect, values.clone(), 90.0);
+
+        let creator = ApproxPercentileAccumulatorCreator::default();
+        creator
+            .set_input_types(vec![
+                ConcreteDataType::int32_datatype(),
+                ConcreteDataType::float64_datatype(),
+            ])
+            .unwrap();
+        let state_types = creator.state_types().unwrap();
+
+        let mut merged = ApproxPercentile::default();
+        for chunk in values.chunks(30) {
+            let mut partial = ApproxPercentile::default();
+            update(&mut partial, chunk.to_vec(), 90.0);
+
+            let states = partial
+                .state()
+                .unwrap()
+                .into_iter()
+                .zip(state_types.iter())
+                .map(|(value, data_type)| {
+                    let mut builder = data_type.create_mutable_vector(1);
+                    builder.push_value_ref(value.as_value_ref());
+                    builder.to_vector()
+                })
+                .collect::<Vec<_>>();
+            merged.merge_batch(&states).unwrap();
+        }
+        assert_eq!(Value::from(89.1), expect.evaluate().unwrap());
+        assert_eq!(expect.evaluate().unwrap(), merged.evaluate().unwrap());
+    }
+
+    #[test]
+    fn test_compressed_digest() {
+        let mut percentile = ApproxPercentile::default();
+        for chunk in (0..100_000).collect::<Vec<_>>().chunks(1000) {
+            let values = chunk.iter().map(|&v| Some(v)).collect();
+            update(&mut percentile, values, 99.0);
+        }
+        assert!(percentile.digest.centroids.len() <= MAX_CENTROIDS);
+
+        let expects = [
+            (0.0, 0.0),
+            (1.0, 999.99),
+            (50.0, 49_999.5),
+            (99.0, 98_999.01),
+            (100.0, 99_999.0),
+        ];
+        for (p, expect) in expects {
+            let estimate = percentile.digest.percentile(p).unwrap();
+            assert!((estimate - expect).abs() <= 100.0, "{p}: {estimate}");
+        }
+    }
+}
@@ -0,0 +1,446 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counter aware `rate`, `increase` and `delta` aggregate functions over `(ts, value)`.
+//!
+//! They follow the definitions of the same named functions in Prometheus: a decreasing
+//! value is treated as a counter reset by `rate` and `increase`, while `delta` is meant
+//! for gauges and takes the values as they are. As there is no range selector in SQL,
+//! the result is not extrapolated and the window is the one covered by the samples.
+
+use std::ops::{Add, Sub};
+use std::sync::Arc;
+
+use common_function_macro::{as_aggr_func_creator, AggrFuncTypeStore};
+use common_query::error::{
+    BadAccumulatorImplSnafu, CreateAccumulatorSnafu, DowncastVectorSnafu, FromScalarValueSnafu,
+    Result,
+};
+use common_query::logical_plan::{Accumulator, AggregateFunctionCreator};
+use common_query::prelude::*;
+use common_time::timestamp::TimeUnit;
+use datatypes::prelude::*;
+use datatypes::types::{LogicalPrimitiveType, WrapperType};
+use datatypes::value::ListValue;
+use datatypes::vectors::{ConstantVector, Helper, Int64Vector, ListVector};
+use datatypes::with_match_primitive_type_id;
+use num_traits::AsPrimitive;
+use snafu::{ensure, OptionExt, ResultExt};
+
+/// Native types of counter samples.
+///
+/// Samples are widened before any arithmetic, integers to `i128` and floats to `f64`,
+/// so neither the difference of two `u64` samples nor the sum of the increments
+/// across resets can wrap around.
+pub trait CounterNative: Copy + PartialOrd {
+    type Wide: Copy
+        + PartialOrd
+        + Add<Output = Self::Wide>
+        + Sub<Output = Self::Wide>
+        + AsPrimitive<f64>;
+
+    fn widen(self) -> Self::Wide;
+
+    fn to_f64(self) -> f64 {
+        self.widen().as_()
+    }
+}
+
+macro_rules! impl_counter_native {
+    ($wide: ty, $($native: ty),+) => {
+        $(
+            impl CounterNative for $native {
+                type Wide = $wide;
+
+                fn widen(self) -> $wide {
+                    self as $wide
+                }
+            }
+        )+
+    };
+}
+
+impl_counter_native!(i128, i8, i16, i32, i64, u8, u16, u32, u64);
+impl_counter_native!(f64, f32, f64);
+
+/// Increase of a counter over `values`, i.e. `last - first` plus the value before every
+/// reset. Returns `None` if there are less than two samples.
+pub fn counter_increase<T: CounterNative>(values: &[T]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mut result = values[values.len() - 1].widen() - values[0].widen();
+    for window in values.windows(2) {
+        let (prev, curr) = (window[0].widen(), window[1].widen());
+        if curr < prev {
+            result = result + prev;
+        }
+    }
+    Some(result.as_())
+}
+
+/// Difference between the last and the first of `values`, without detecting resets.
+/// Returns `None` if there are less than two samples.
+pub fn gauge_delta<T: CounterNative>(values: &[T]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    Some((values[values.len() - 1].widen() - values[0].widen()).as_())
+}
+
+pub type Delta<T> = Counter<T, false, false>;
+pub type Rate<T> = Counter<T, true, true>;
+pub type Increase<T> = Counter<T, true, false>;
+
+#[derive(Debug)]
+pub struct Counter<T, const IS_COUNTER: bool, const IS_RATE: bool> {
+    /// Samples as `(timestamp in millisecond, value)`, in the order they are received.
+    samples: Vec<(i64, T)>,
+}
+
+impl<T, const IS_COUNTER: bool, const IS_RATE: bool> Default for Counter<T, IS_COUNTER, IS_RATE> {
+    fn default() -> Self {
+        Self {
+            samples: Vec::new(),
+        }
+    }
+}
+
+impl<T, const IS_COUNTER: bool, const IS_RATE: bool> Counter<T, IS_COUNTER, IS_RATE>
+where
+    T: WrapperType,
+{
+    fn push(&mut self, ts: Value, value: Option<T>) {
+        let ts = match ts {
+            Value::Timestamp(ts) => ts.convert_to(TimeUnit::Millisecond).map(|ts| ts.value()),
+            Value::Int64(ts) => Some(ts),
+            _ => None,
+        };
+        if let (Some(ts), Some(value)) = (ts, value) {
+            self.samples.push((ts, value));
+        }
+    }
+}
+
+impl<T, const IS_COUNTER: bool, const IS_RATE: bool> Accumulator for Counter<T, IS_COUNTER, IS_RATE>
+where
+    T: WrapperType,
+    T::Native: CounterNative,
+{
+    fn state(&self) -> Result<Vec<Value>> {
+        let (timestamps, values): (Vec<Value>, Vec<Value>) = self
+            .samples
+            .iter()
+            .map(|&(ts, value)| (Value::Int64(ts), value.into()))
+            .unzip();
+        Ok(vec![
+            Value::List(ListValue::new(
+                Some(Box::new(timestamps)),
+                ConcreteDataType::int64_datatype(),
+            )),
+            Value::List(ListValue::new(
+                Some(Box::new(values)),
+                T::LogicalType::build_data_type(),
+            )),
+        ])
+    }
+
+    fn update_batch(&mut self, values: &[VectorRef]) -> Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        ensure!(values.len() == 2, InvalidInputStateSnafu);
+        ensure!(values[0].len() == values[1].len(), InvalidInputStateSnafu);
+
+        let timestamps = &values[0];
+        let column = &values[1];
+        let is_const = column.is_const();
+        let column: &<T as Scalar>::VectorType = if is_const {
+            let column: &ConstantVector = unsafe { Helper::static_cast(column) };
+            unsafe { Helper::static_cast(column.inner()) }
+        } else {
+            unsafe { Helper::static_cast(column) }
+        };
+        for i in 0..timestamps.len() {
+            let value = column.get_data(if is_const { 0 } else { i });
+            self.push(timestamps.get(i), value);
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[VectorRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+
+        ensure!(
+            states.len() == 2,
+            BadAccumulatorImplSnafu {
+                err_msg: "expect 2 states in `merge_batch`",
+            }
+        );
+
+        let timestamps = states[0]
+            .as_any()
+            .downcast_ref::<ListVector>()
+            .with_context(|| DowncastVectorSnafu {
+                err_msg: format!(
+                    "expect ListVector, got vector type {}",
+                    states[0].vector_type_name()
+                ),
+            })?;
+        let values = states[1]
+            .as_any()
+            .downcast_ref::<ListVector>()
+            .with_context(|| DowncastVectorSnafu {
+                err_msg: format!(
+                    "expect ListVector, got vector type {}",
+                    states[1].vector_type_name()
+                ),
+            })?;
+        for (timestamps, values) in timestamps.values_iter().zip(values.values_iter()) {
+            let (Some(timestamps), Some(values)) = (
+                timestamps.context(FromScalarValueSnafu)?,
+                values.context(FromScalarValueSnafu)?,
+            ) else {
+                continue;
+            };
+            let timestamps: &Int64Vector = unsafe { Helper::static_cast(&timestamps) };
+            let values: &<T as Scalar>::VectorType = unsafe { Helper::static_cast(&values) };
+            for (ts, value) in timestamps.iter_data().zip(values.iter_data()) {
+                if let (Some(ts), Some(value)) = (ts, value) {
+                    self.samples.push((ts, value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<Value> {
+        let mut samples = self.samples.clone();
+        samples.sort_by_key(|&(ts, _)| ts);
+        let values = samples
+            .iter()
+            .map(|&(_, value)| value.into_native())
+            .collect::<Vec<_>>();
+
+        let result = if IS_COUNTER {
+            counter_increase(&values)
+        } else {
+            gauge_delta(&values)
+        };
+        let Some(mut result) = result else {
+            return Ok(Value::Null);
+        };
+
+        if IS_RATE {
+            // `result` is only present with at least two samples.
+            let interval = samples[samples.len() - 1].0 - samples[0].0;
+            if interval == 0 {
+                return Ok(Value::Null);
+            }
+            result /= interval as f64 / 1000.0;
+        }
+        Ok(result.into())
+    }
+}
+
+fn counter_creator<const IS_COUNTER: bool, const IS_RATE: bool>(
+    name: &'static str,
+) -> AccumulatorCreatorFunction {
+    Arc::new(move |types: &[ConcreteDataType]| {
+        ensure!(types.len() == 2, InvalidInputStateSnafu);
+        ensure!(
+            matches!(
+                types[0],
+                ConcreteDataType::Timestamp(_) | ConcreteDataType::Int64(_)
+            ),
+            CreateAccumulatorSnafu {
+                err_msg: format!(
+                    "\"{name}\" aggregate function expects a timestamp as the first argument, found {:?}",
+                    types[0].logical_type_id(),
+                ),
+            }
+        );
+        let input_type = &types[1];
+        with_match_primitive_type_id!(
+            input_type.logical_type_id(),
+            |$S| {
+                Ok(Box::new(Counter::<<$S as LogicalPrimitiveType>::Wrapper, IS_COUNTER, IS_RATE>::default()))
+            },
+            {
+                let err_msg = format!(
+                    "\"{name}\" aggregate function not support data type {:?}",
+                    input_type.logical_type_id(),
+                );
+                CreateAccumulatorSnafu { err_msg }.fail()?
+            }
+        )
+    })
+}
+
+fn counter_state_types(input_types: Vec<ConcreteDataType>) -> Result<Vec<ConcreteDataType>> {
+    ensure!(input_types.len() == 2, InvalidInputStateSnafu);
+    Ok(vec![
+        ConcreteDataType::list_datatype(ConcreteDataType::int64_datatype()),
+        ConcreteDataType::list_datatype(input_types.into_iter().nth(1).unwrap()),
+    ])
+}
+
+#[as_aggr_func_creator]
+#[derive(Debug, Default, AggrFuncTypeStore)]
+pub struct RateAccumulatorCreator {}
+
+impl AggregateFunctionCreator for RateAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        counter_creator::<true, true>("RATE")
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::float64_datatype())
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        counter_state_types(self.input_types()?)
+    }
+}
+
+#[as_aggr_func_creator]
+#[derive(Debug, Default, AggrFuncTypeStore)]
+pub struct IncreaseAccumulatorCreator {}
+
+impl AggregateFunctionCreator for IncreaseAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        counter_creator::<true, false>("INCREASE")
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::float64_datatype())
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        counter_state_types(self.input_types()?)
+    }
+}
+
+#[as_aggr_func_creator]
+#[derive(Debug, Default, AggrFuncTypeStore)]
+pub struct DeltaAccumulatorCreator {}
+
+impl AggregateFunctionCreator for DeltaAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        counter_creator::<false, false>("DELTA")
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::float64_datatype())
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        counter_state_types(self.input_types()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datatypes::vectors::{TimestampMillisecondVector, UInt64Vector};
+
+    use super::*;
+
+    #[test]
+    fn test_counter_increase() {
+        assert_eq!(None, counter_increase::<u64>(&[]));
+        assert_eq!(None, counter_increase(&[1u64]));
+        assert_eq!(Some(4.0), counter_increase(&[1u64, 2, 3, 4, 5]));
+        // two resets: 1 -> 4, 1 -> 3, 2 -> 6
+        assert_eq!(Some(12.0), counter_increase(&[1u64, 4, 1, 3, 2, 6]));
+        assert_eq!(
+            Some(12.0),
+            counter_increase(&[1.0f64, 4.0, 1.0, 3.0, 2.0, 6.0])
+        );
+        // values near the max of u64 neither underflow nor overflow
+        assert_eq!(Some(2.0), counter_increase(&[u64::MAX - 2, u64::MAX]));
+        // the counter restarts from zero after the reset
+        assert_eq!(Some(2.0), counter_increase(&[u64::MAX - 1, u64::MAX, 0, 1]));
+    }
+
+    #[test]
+    fn test_gauge_delta() {
+        assert_eq!(None, gauge_delta::<i64>(&[1]));
+        assert_eq!(Some(-3.0), gauge_delta(&[4i64, 8, 1]));
+        assert_eq!(Some(-(u64::MAX as f64)), gauge_delta(&[u64::MAX, 0]));
+    }
+
+    fn update(counter: &mut impl Accumulator, timestamps: Vec<i64>, values: Vec<u64>) {
+        let v: Vec<VectorRef> = vec![
+            Arc::new(TimestampMillisecondVector::from_vec(timestamps)),
+            Arc::new(UInt64Vector::from_vec(values)),
+        ];
+        counter.update_batch(&v).unwrap();
+    }
+
+    #[test]
+    fn test_update_batch() {
+        let mut increase = Increase::<u64>::default();
+        assert!(increase.update_batch(&[]).is_ok());
+        assert_eq!(Value::Null, increase.evaluate().unwrap());
+
+        // a single sample has no increase
+        update(&mut increase, vec![1000], vec![1]);
+        assert_eq!(Value::Null, increase.evaluate().unwrap());
+
+        // samples are sorted by timestamps before evaluating
+        update(&mut increase, vec![6000, 3000], vec![6, 1]);
+        update(&mut increase, vec![2000, 5000, 4000], vec![4, 2, 3]);
+        assert_eq!(Value::Float64(12.0.into()), increase.evaluate().unwrap());
+
+        let mut rate = Rate::<u64>::default();
+        update(
+            &mut rate,
+            vec![1000, 2000, 3000, 4000, 5000, 6000],
+            vec![1, 4, 1, 3, 2, 6],
+        );
+        assert_eq!(Value::Float64(2.4.into()), rate.evaluate().unwrap());
+
+        let mut delta = Delta::<u64>::default();
+        update(&mut delta, vec![1000, 2000, 3000], vec![u64::MAX, 0, 1]);
+        assert_eq!(
+            Value::Float64((1.0 - u64::MAX as f64).into()),
+            delta.evaluate().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_batch() {
+        let mut first = Increase::<u64>::default();
+        update(&mut first, vec![1000, 2000], vec![u64::MAX - 1, u64::MAX]);
+        let mut second = Increase::<u64>::default();
+        update(&mut second, vec![3000, 4000], vec![0, 1]);
+
+        let mut merged = Increase::<u64>::default();
+        for state in [first.state().unwrap(), second.state().unwrap()] {
+            let states = state
+                .into_iter()
+                .map(|value| {
+                    let mut builder = value.data_type().create_mutable_vector(1);
+                    builder.push_value_ref(value.as_value_ref());
+                    builder.to_vector()
+                })
+                .collect::<Vec<_>>();
+            merged.merge_batch(&states).unwrap();
+        }
+        assert_eq!(Value::Float64(2.0.into()), merged.evaluate().unwrap());
+    }
+}
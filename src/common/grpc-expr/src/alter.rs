@@ -19,7 +19,8 @@ use datatypes::schema::{ColumnSchema, RawSchema};
 use snafu::{ensure, OptionExt, ResultExt};
 use table::metadata::TableId;
 use table::requests::{
-    AddColumnRequest, AlterKind, AlterTableRequest, CreateTableRequest, TableOptions,
+    with_column_storage_options, AddColumnRequest, AlterKind, AlterTableRequest,
+    CreateTableRequest, TableOptions,
 };
 
 use crate::error::{
@@ -116,13 +117,15 @@ pub fn create_table_schema(expr: &CreateTableExpr, require_time_index: bool) ->
     let column_schemas = column_schemas
         .into_iter()
         .map(|column_schema| {
-            if column_schema.name == expr.time_index {
+            let column_schema = if column_schema.name == expr.time_index {
                 column_schema.with_time_index(true)
             } else {
                 column_schema
-            }
+            };
+            with_column_storage_options(column_schema, &expr.table_options)
+                .context(UnrecognizedTableOptionSnafu)
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>>>()?;
 
     Ok(RawSchema::new(column_schemas))
 }
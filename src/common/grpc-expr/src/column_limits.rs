@@ -0,0 +1,108 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of the [ColumnLimits] of the tables created or altered, either explicitly or on
+//! insertion.
+
+use snafu::ensure;
+use table::column_limits::ColumnLimits;
+
+use crate::error::{ColumnLimitExceededSnafu, Result, TooManyColumnsSnafu, TooManyNewColumnsSnafu};
+
+/// Checks a table of `num_columns` columns can be created.
+pub fn check_new_table(limits: &ColumnLimits, num_columns: usize) -> Result<()> {
+    ensure!(
+        num_columns <= limits.max_columns,
+        TooManyColumnsSnafu {
+            num_columns,
+            max_columns: limits.max_columns,
+        }
+    );
+    Ok(())
+}
+
+/// Checks `new_columns` can be added to a table of `num_columns` columns.
+///
+/// Tables already beyond the max columns still work, but no columns can be added to them.
+pub fn check_new_columns(
+    limits: &ColumnLimits,
+    num_columns: usize,
+    new_columns: &[&str],
+) -> Result<()> {
+    ensure!(
+        new_columns.len() <= limits.max_added_columns,
+        TooManyNewColumnsSnafu {
+            num_new_columns: new_columns.len(),
+            max_added_columns: limits.max_added_columns,
+        }
+    );
+    ensure!(
+        num_columns + new_columns.len() <= limits.max_columns,
+        ColumnLimitExceededSnafu {
+            columns: new_columns.join(", "),
+            num_columns,
+            max_columns: limits.max_columns,
+        }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_check_column_limits() {
+        let limits = ColumnLimits {
+            max_columns: 4,
+            max_added_columns: 2,
+        };
+
+        check_new_table(&limits, 4).unwrap();
+        let err = check_new_table(&limits, 5).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::TooManyColumns {
+                    num_columns: 5,
+                    max_columns: 4,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        check_new_columns(&limits, 2, &["a", "b"]).unwrap();
+        let err = check_new_columns(&limits, 0, &["a", "b", "c"]).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::TooManyNewColumns {
+                    num_new_columns: 3,
+                    max_added_columns: 2,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        // A table beyond the limit can't have more columns.
+        let err = check_new_columns(&limits, 6, &["a"]).unwrap_err();
+        assert_eq!(
+            "Adding columns [a] to a table of 6 columns exceeds the limit max_columns = 4",
+            err.to_string()
+        );
+    }
+}
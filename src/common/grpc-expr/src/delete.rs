@@ -14,18 +14,38 @@
 
 use std::collections::HashMap;
 
-use api::helper::ColumnDataTypeWrapper;
 use api::v1::{Column, DeleteRequest as GrpcDeleteRequest};
-use datatypes::data_type::DataType;
-use datatypes::prelude::ConcreteDataType;
-use snafu::{ensure, ResultExt};
+use common_catalog::format_full_table_name;
+use snafu::ensure;
+use table::metadata::TableMeta;
 use table::requests::DeleteRequest;
 
-use crate::error::{ColumnDataTypeSnafu, IllegalDeleteRequestSnafu, Result};
-use crate::insert::add_values_to_builder;
+use crate::error::{IllegalDeleteRequestSnafu, Result};
+use crate::insert::{check_columns_values, values_to_vector};
 
-pub fn to_table_delete_request(request: GrpcDeleteRequest) -> Result<DeleteRequest> {
+/// Converts the delete request on the wire to the delete request of the table.
+///
+/// Rows are deleted by their keys, so the request must have all the primary key columns and
+/// the time index of the table in `table_meta`. Other columns of the table are kept, as the
+/// distributed table routes deletes by its partition columns, which may be fields.
+pub fn to_table_delete_request(
+    catalog_name: &str,
+    schema_name: &str,
+    request: GrpcDeleteRequest,
+    table_meta: &TableMeta,
+) -> Result<DeleteRequest> {
+    let table_name = format_full_table_name(catalog_name, schema_name, &request.table_name);
     let row_count = request.row_count as usize;
+    check_columns_values(&request.key_columns, row_count)?;
+
+    let time_index = table_meta
+        .schema
+        .timestamp_column()
+        .map(|column| &column.name);
+    let key_column_names = table_meta
+        .row_key_column_names()
+        .chain(time_index)
+        .collect::<Vec<_>>();
 
     let mut key_column_values = HashMap::with_capacity(request.key_columns.len());
     for Column {
@@ -36,19 +56,23 @@ pub fn to_table_delete_request(request: GrpcDeleteRequest) -> Result<DeleteReque
         ..
     } in request.key_columns
     {
-        let Some(values) = values else { continue };
-
-        let datatype: ConcreteDataType = ColumnDataTypeWrapper::try_new(datatype)
-            .context(ColumnDataTypeSnafu)?
-            .into();
-
-        let vector_builder = &mut datatype.create_mutable_vector(row_count);
-
-        add_values_to_builder(vector_builder, values, row_count, null_mask)?;
+        ensure!(
+            table_meta.schema.contains_column(&column_name),
+            IllegalDeleteRequestSnafu {
+                reason: format!("Column '{column_name}' not found in table '{table_name}'.")
+            }
+        );
 
+        let vector = values_to_vector(
+            &column_name,
+            datatype,
+            values.as_ref(),
+            &null_mask,
+            row_count,
+        )?;
         ensure!(
             key_column_values
-                .insert(column_name.clone(), vector_builder.to_vector())
+                .insert(column_name.clone(), vector)
                 .is_none(),
             IllegalDeleteRequestSnafu {
                 reason: format!("Duplicated column '{column_name}' in delete request.")
@@ -56,6 +80,27 @@ pub fn to_table_delete_request(request: GrpcDeleteRequest) -> Result<DeleteReque
         );
     }
 
+    ensure!(
+        key_column_names
+            .iter()
+            .any(|column_name| key_column_values.contains_key(*column_name)),
+        IllegalDeleteRequestSnafu {
+            reason: format!(
+                "No key columns of table '{table_name}' in delete request, rows are deleted by the primary key and the time index."
+            )
+        }
+    );
+    for column_name in key_column_names {
+        ensure!(
+            key_column_values.contains_key(column_name),
+            IllegalDeleteRequestSnafu {
+                reason: format!(
+                    "Missing key column '{column_name}' of table '{table_name}' in delete request."
+                )
+            }
+        );
+    }
+
     Ok(DeleteRequest { key_column_values })
 }
 
@@ -63,51 +108,156 @@ pub fn to_table_delete_request(request: GrpcDeleteRequest) -> Result<DeleteReque
 mod tests {
     use std::sync::Arc;
 
-    use api::v1::column::Values;
+    use api::v1::column::{SemanticType, Values};
     use api::v1::ColumnDataType;
-    use datatypes::prelude::{ScalarVector, VectorRef};
-    use datatypes::vectors::{Int32Vector, StringVector};
+    use datatypes::prelude::{ConcreteDataType, ScalarVector, VectorRef};
+    use datatypes::schema::{ColumnSchema, SchemaBuilder};
+    use datatypes::vectors::{StringVector, TimestampMillisecondVector};
+    use table::metadata::TableMetaBuilder;
 
     use super::*;
+    use crate::error::Error;
 
-    #[test]
-    fn test_to_table_delete_request() {
-        let grpc_request = GrpcDeleteRequest {
-            table_name: "foo".to_string(),
+    fn demo_table_meta() -> TableMeta {
+        let column_schemas = vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ];
+        let schema = Arc::new(
+            SchemaBuilder::try_from(column_schemas)
+                .unwrap()
+                .build()
+                .unwrap(),
+        );
+        TableMetaBuilder::default()
+            .schema(schema)
+            .primary_key_indices(vec![0])
+            .engine("mito")
+            .next_column_id(3)
+            .build()
+            .unwrap()
+    }
+
+    fn mock_delete_batch() -> (Vec<Column>, u32) {
+        let row_count = 2;
+
+        let host_column = Column {
+            column_name: "host".to_string(),
+            semantic_type: SemanticType::Tag as i32,
+            values: Some(Values {
+                string_values: vec!["host1".to_string(), "host2".to_string()],
+                ..Default::default()
+            }),
+            null_mask: vec![0],
+            datatype: ColumnDataType::String as i32,
+        };
+
+        let ts_column = Column {
+            column_name: "ts".to_string(),
+            semantic_type: SemanticType::Timestamp as i32,
+            values: Some(Values {
+                ts_millisecond_values: vec![100, 101],
+                ..Default::default()
+            }),
+            null_mask: vec![0],
+            datatype: ColumnDataType::TimestampMillisecond as i32,
+        };
+
+        (vec![host_column, ts_column], row_count)
+    }
+
+    fn mock_cpu_column() -> Column {
+        Column {
+            column_name: "cpu".to_string(),
+            semantic_type: SemanticType::Field as i32,
+            values: Some(Values {
+                f64_values: vec![0.1, 0.2],
+                ..Default::default()
+            }),
+            null_mask: vec![0],
+            datatype: ColumnDataType::Float64 as i32,
+        }
+    }
+
+    fn delete_request(key_columns: Vec<Column>, row_count: u32) -> Result<DeleteRequest> {
+        let request = GrpcDeleteRequest {
+            table_name: "demo".to_string(),
             region_number: 0,
-            key_columns: vec![
-                Column {
-                    column_name: "id".to_string(),
-                    values: Some(Values {
-                        i32_values: vec![1, 2, 3],
-                        ..Default::default()
-                    }),
-                    datatype: ColumnDataType::Int32 as i32,
-                    ..Default::default()
-                },
-                Column {
-                    column_name: "name".to_string(),
-                    values: Some(Values {
-                        string_values: vec!["a".to_string(), "b".to_string(), "c".to_string()],
-                        ..Default::default()
-                    }),
-                    datatype: ColumnDataType::String as i32,
-                    ..Default::default()
-                },
-            ],
-            row_count: 3,
+            key_columns,
+            row_count,
         };
+        to_table_delete_request("greptime", "public", request, &demo_table_meta())
+    }
 
-        let mut request = to_table_delete_request(grpc_request).unwrap();
+    #[test]
+    fn test_to_table_delete_request() {
+        let (key_columns, row_count) = mock_delete_batch();
+        let mut request = delete_request(key_columns, row_count).unwrap();
 
         assert_eq!(
-            Arc::new(Int32Vector::from_slice(vec![1, 2, 3])) as VectorRef,
-            request.key_column_values.remove("id").unwrap()
+            Arc::new(StringVector::from_slice(&["host1", "host2"])) as VectorRef,
+            request.key_column_values.remove("host").unwrap()
         );
         assert_eq!(
-            Arc::new(StringVector::from_slice(&["a", "b", "c"])) as VectorRef,
-            request.key_column_values.remove("name").unwrap()
+            Arc::new(TimestampMillisecondVector::from_slice([100, 101])) as VectorRef,
+            request.key_column_values.remove("ts").unwrap()
         );
         assert!(request.key_column_values.is_empty());
     }
+
+    #[test]
+    fn test_delete_request_missing_key_columns() {
+        let (mut key_columns, row_count) = mock_delete_batch();
+        let _ = key_columns.pop();
+        let err = delete_request(key_columns, row_count).unwrap_err();
+        assert!(matches!(err, Error::IllegalDeleteRequest { .. }));
+        assert!(err
+            .to_string()
+            .contains("Missing key column 'ts' of table 'greptime.public.demo'"));
+
+        let (mut key_columns, row_count) = mock_delete_batch();
+        let _ = key_columns.remove(0);
+        let err = delete_request(key_columns, row_count).unwrap_err();
+        assert!(err.to_string().contains("Missing key column 'host'"));
+    }
+
+    #[test]
+    fn test_delete_request_with_field_columns() {
+        let err = delete_request(vec![mock_cpu_column()], 2).unwrap_err();
+        assert!(matches!(err, Error::IllegalDeleteRequest { .. }));
+        assert!(err
+            .to_string()
+            .contains("No key columns of table 'greptime.public.demo' in delete request"));
+
+        // Fields along with the keys are kept for routing the delete.
+        let (mut key_columns, row_count) = mock_delete_batch();
+        key_columns.push(mock_cpu_column());
+        let request = delete_request(key_columns, row_count).unwrap();
+        assert_eq!(3, request.key_column_values.len());
+
+        let (mut key_columns, row_count) = mock_delete_batch();
+        let mut column = mock_cpu_column();
+        column.column_name = "memory".to_string();
+        key_columns.push(column);
+        let err = delete_request(key_columns, row_count).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Column 'memory' not found in table 'greptime.public.demo'"));
+    }
+
+    #[test]
+    fn test_delete_request_duplicated_columns() {
+        let (mut key_columns, row_count) = mock_delete_batch();
+        key_columns.push(key_columns[0].clone());
+        let err = delete_request(key_columns, row_count).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Duplicated column 'host' in delete request"));
+    }
 }
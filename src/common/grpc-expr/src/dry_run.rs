@@ -0,0 +1,402 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry run of insert requests, which validates the rows of a request and finds the DDL the
+//! insertion would issue, without executing anything.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use api::helper::ColumnDataTypeWrapper;
+use api::v1::{AddColumns, ColumnDef, CreateTableExpr, InsertRequest as GrpcInsertRequest};
+use common_base::validation_mode::ValidationMode;
+use datatypes::data_type::{ConcreteDataType, DataType};
+use datatypes::schema::{Schema, SchemaRef};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::alter::create_table_schema;
+use crate::error::{BuildTableSchemaSnafu, ColumnDataTypeSnafu, Result};
+use crate::insert::{columns_to_vectors, validate_rows};
+pub use crate::validation::RowError;
+
+/// Result of the dry run of an insert request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InsertDryRun {
+    pub table_name: String,
+    /// Whether the table doesn't exist and would be created by the insertion.
+    pub would_create_table: bool,
+    /// Columns the table would be created with, or added to the existing table.
+    pub columns_to_add: Vec<NewColumn>,
+    /// Number of rows without errors.
+    pub rows_accepted: usize,
+    /// Errors of the rows, ordered by the row index. The insertion of a request with any row
+    /// error is rejected as a whole.
+    pub row_errors: Vec<RowError>,
+}
+
+/// A column created or added by an insertion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewColumn {
+    pub name: String,
+    pub data_type: String,
+    pub is_key: bool,
+}
+
+/// Dry runs the insertion of `request` into a table to be created by `create_expr`, which is
+/// the expr the table would be created with on insertion.
+pub fn dry_run_create_table(
+    create_expr: &CreateTableExpr,
+    request: &GrpcInsertRequest,
+) -> Result<InsertDryRun> {
+    let columns_to_add = create_expr
+        .column_defs
+        .iter()
+        .map(|column_def| {
+            new_column(
+                column_def,
+                create_expr.primary_keys.contains(&column_def.name),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let schema = create_table_schema(create_expr, true)?;
+    let schema = Schema::try_from(schema).context(BuildTableSchemaSnafu {
+        table_name: &create_expr.table_name,
+    })?;
+    let row_errors = validate_request(&Arc::new(schema), request)?;
+
+    Ok(InsertDryRun::new(request, true, columns_to_add, row_errors))
+}
+
+/// Dry runs the insertion of `request` into an existing table of `schema`, with the columns
+/// in `add_columns` that the insertion would add to the table.
+pub fn dry_run_insert_into(
+    schema: &SchemaRef,
+    add_columns: Option<&AddColumns>,
+    request: &GrpcInsertRequest,
+) -> Result<InsertDryRun> {
+    let columns_to_add = add_columns
+        .map(|add_columns| {
+            add_columns
+                .add_columns
+                .iter()
+                .filter_map(|add_column| {
+                    let column_def = add_column.column_def.as_ref()?;
+                    Some(new_column(column_def, add_column.is_key))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let row_errors = validate_request(schema, request)?;
+
+    Ok(InsertDryRun::new(
+        request,
+        false,
+        columns_to_add,
+        row_errors,
+    ))
+}
+
+/// Converts the columns and validates the rows of `request` as the conversion to the insert
+/// request of a table of `schema` does, see [to_table_insert_request]. The errors failing the
+/// conversion fail the dry run, the row errors are returned up to the cap of
+/// [ValidationMode::Lenient].
+///
+/// [to_table_insert_request]: crate::insert::to_table_insert_request
+fn validate_request(schema: &SchemaRef, request: &GrpcInsertRequest) -> Result<Vec<RowError>> {
+    let row_count = request.row_count as usize;
+    let (column_names, columns_values) = columns_to_vectors(&request.columns, row_count, schema)?;
+    let row_errors = match validate_rows(
+        &column_names,
+        &columns_values,
+        schema,
+        row_count,
+        ValidationMode::Lenient,
+    ) {
+        Ok(()) => Vec::new(),
+        Err(row_errors) => row_errors.errors,
+    };
+    Ok(row_errors)
+}
+
+impl InsertDryRun {
+    fn new(
+        request: &GrpcInsertRequest,
+        would_create_table: bool,
+        columns_to_add: Vec<NewColumn>,
+        mut row_errors: Vec<RowError>,
+    ) -> Self {
+        row_errors.sort_by_key(|error| error.row);
+        let rejected_rows = row_errors
+            .iter()
+            .map(|error| error.row)
+            .collect::<BTreeSet<_>>()
+            .len();
+        Self {
+            table_name: request.table_name.clone(),
+            would_create_table,
+            columns_to_add,
+            rows_accepted: request.row_count as usize - rejected_rows,
+            row_errors,
+        }
+    }
+}
+
+fn column_data_type(datatype: i32) -> Result<ConcreteDataType> {
+    Ok(ColumnDataTypeWrapper::try_new(datatype)
+        .context(ColumnDataTypeSnafu)?
+        .into())
+}
+
+fn new_column(column_def: &ColumnDef, is_key: bool) -> Result<NewColumn> {
+    Ok(NewColumn {
+        name: column_def.name.clone(),
+        data_type: column_data_type(column_def.datatype)?.name().to_string(),
+        is_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use api::v1::column::{SemanticType, Values};
+    use api::v1::{Column, ColumnDataType};
+    use datatypes::schema::{ColumnSchema, Schema};
+    use table::column_limits::ColumnLimits;
+
+    use super::*;
+    use crate::{build_create_expr_from_insertion, find_new_columns};
+
+    fn new_grpc_column(
+        name: &str,
+        semantic_type: SemanticType,
+        datatype: ColumnDataType,
+        values: Values,
+        null_mask: Vec<u8>,
+    ) -> Column {
+        Column {
+            column_name: name.to_string(),
+            semantic_type: semantic_type as i32,
+            values: Some(values),
+            null_mask,
+            datatype: datatype as i32,
+        }
+    }
+
+    fn ts_column(ts: Vec<i64>, null_mask: Vec<u8>) -> Column {
+        new_grpc_column(
+            "ts",
+            SemanticType::Timestamp,
+            ColumnDataType::TimestampMillisecond,
+            Values {
+                ts_millisecond_values: ts,
+                ..Default::default()
+            },
+            null_mask,
+        )
+    }
+
+    fn new_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ]))
+    }
+
+    #[test]
+    fn test_dry_run_create_table() {
+        let request = GrpcInsertRequest {
+            table_name: "monitor".to_string(),
+            columns: vec![
+                new_grpc_column(
+                    "host",
+                    SemanticType::Tag,
+                    ColumnDataType::String,
+                    Values {
+                        string_values: vec!["h1".to_string(), "h2".to_string()],
+                        ..Default::default()
+                    },
+                    vec![0],
+                ),
+                ts_column(vec![1], vec![0b10]),
+            ],
+            row_count: 2,
+            ..Default::default()
+        };
+        let create_expr = build_create_expr_from_insertion(
+            "",
+            "",
+            None,
+            "monitor",
+            &request.columns,
+            "",
+            &ColumnLimits::default(),
+            None,
+        )
+        .unwrap();
+
+        let dry_run = dry_run_create_table(&create_expr, &request).unwrap();
+        assert_eq!(
+            InsertDryRun {
+                table_name: "monitor".to_string(),
+                would_create_table: true,
+                columns_to_add: vec![
+                    NewColumn {
+                        name: "host".to_string(),
+                        data_type: "String".to_string(),
+                        is_key: true,
+                    },
+                    NewColumn {
+                        name: "ts".to_string(),
+                        data_type: "TimestampMillisecond".to_string(),
+                        is_key: false,
+                    },
+                ],
+                rows_accepted: 1,
+                row_errors: vec![RowError {
+                    row: 1,
+                    column: "ts".to_string(),
+                    value: None,
+                    reason: "null value in not null column".to_string(),
+                }],
+            },
+            dry_run
+        );
+    }
+
+    #[test]
+    fn test_dry_run_insert_into() {
+        let schema = new_schema();
+        let request = GrpcInsertRequest {
+            table_name: "monitor".to_string(),
+            columns: vec![
+                // The value of the second row is a string, the first and the third are null.
+                new_grpc_column(
+                    "cpu",
+                    SemanticType::Field,
+                    ColumnDataType::String,
+                    Values {
+                        string_values: vec!["high".to_string()],
+                        ..Default::default()
+                    },
+                    vec![0b101],
+                ),
+                new_grpc_column(
+                    "memory",
+                    SemanticType::Field,
+                    ColumnDataType::Float64,
+                    Values {
+                        f64_values: vec![1.0, 2.0, 3.0],
+                        ..Default::default()
+                    },
+                    vec![0],
+                ),
+                ts_column(vec![1, 2], vec![0b100]),
+            ],
+            row_count: 3,
+            ..Default::default()
+        };
+        let add_columns =
+            find_new_columns(&schema, &request.columns, &ColumnLimits::default()).unwrap();
+
+        let dry_run = dry_run_insert_into(&schema, add_columns.as_ref(), &request).unwrap();
+        assert!(!dry_run.would_create_table);
+        assert_eq!(
+            vec![NewColumn {
+                name: "memory".to_string(),
+                data_type: "Float64".to_string(),
+                is_key: false,
+            }],
+            dry_run.columns_to_add
+        );
+        assert_eq!(1, dry_run.rows_accepted);
+        assert_eq!(
+            vec![
+                RowError {
+                    row: 1,
+                    column: "cpu".to_string(),
+                    value: Some("high".to_string()),
+                    reason: "column expects type Float64, got String".to_string(),
+                },
+                RowError {
+                    row: 2,
+                    column: "ts".to_string(),
+                    value: None,
+                    reason: "null value in not null column".to_string(),
+                },
+            ],
+            dry_run.row_errors
+        );
+    }
+
+    #[test]
+    fn test_dry_run_invalid_request() {
+        let schema = new_schema();
+
+        // Duplicated columns.
+        let request = GrpcInsertRequest {
+            table_name: "monitor".to_string(),
+            columns: vec![ts_column(vec![1], vec![0]), ts_column(vec![1], vec![0])],
+            row_count: 1,
+            ..Default::default()
+        };
+        assert!(dry_run_insert_into(&schema, None, &request).is_err());
+
+        // The null mask leaves no value for the second row.
+        let request = GrpcInsertRequest {
+            table_name: "monitor".to_string(),
+            columns: vec![ts_column(vec![1], vec![0])],
+            row_count: 2,
+            ..Default::default()
+        };
+        assert!(dry_run_insert_into(&schema, None, &request).is_err());
+
+        // Missing the time index column, which is not null.
+        let request = GrpcInsertRequest {
+            table_name: "monitor".to_string(),
+            columns: vec![new_grpc_column(
+                "host",
+                SemanticType::Tag,
+                ColumnDataType::String,
+                Values {
+                    string_values: vec!["h1".to_string(), "h2".to_string()],
+                    ..Default::default()
+                },
+                vec![0],
+            )],
+            row_count: 2,
+            ..Default::default()
+        };
+        let dry_run = dry_run_insert_into(&schema, None, &request).unwrap();
+        assert_eq!(0, dry_run.rows_accepted);
+        assert_eq!(
+            vec![0, 1],
+            dry_run
+                .row_errors
+                .iter()
+                .map(|error| error.row)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::any::Any;
 
+use api::v1::ColumnDataType;
 use api::DecodeError;
 use common_error::ext::ErrorExt;
 use common_error::prelude::{Snafu, StatusCode};
@@ -59,12 +60,170 @@ pub enum Error {
 
     #[snafu(display("Invalid column proto: {}", err_msg))]
     InvalidColumnProto { err_msg: String, location: Location },
+
+    #[snafu(display(
+        "Null mask of column {} covers {} of {} rows, but only {} values are present for the rest",
+        column,
+        mask_rows,
+        row_count,
+        value_count
+    ))]
+    NullMaskTooShort {
+        column: String,
+        mask_rows: usize,
+        row_count: usize,
+        value_count: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Null mask of column {} marks row {} as null, but there are only {} rows",
+        column,
+        row,
+        row_count
+    ))]
+    NullMaskTooLong {
+        column: String,
+        row: usize,
+        row_count: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Row count {} of the request is not backed by the values of any column",
+        row_count
+    ))]
+    RowCountWithoutValues {
+        row_count: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Null mask of column {} marks {} of {} rows as null, but {} values are present",
+        column,
+        null_count,
+        row_count,
+        value_count
+    ))]
+    InconsistentNullMask {
+        column: String,
+        null_count: usize,
+        row_count: usize,
+        value_count: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Values of column {} are expected to be {:?}, but {} of the {} values present are of other types",
+        column,
+        expected_type,
+        actual_len - expected_len,
+        actual_len
+    ))]
+    InvalidColumnValues {
+        column: String,
+        expected_type: ColumnDataType,
+        expected_len: usize,
+        actual_len: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Row {} has {} values, but the schema of the rows has {} columns",
+        row,
+        actual,
+        expected
+    ))]
+    RowWidthMismatch {
+        row: usize,
+        expected: usize,
+        actual: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Value of column {} in row {} is expected to be {:?}",
+        column,
+        row,
+        expected_type
+    ))]
+    InvalidRowValue {
+        row: usize,
+        column: String,
+        expected_type: ColumnDataType,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Column {} is not nullable, but its values are absent in the insert request",
+        column
+    ))]
+    ColumnValuesAbsent { column: String, location: Location },
+
+    #[snafu(display(
+        "Table of {} columns exceeds the limit max_columns = {}",
+        num_columns,
+        max_columns
+    ))]
+    TooManyColumns {
+        num_columns: usize,
+        max_columns: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Adding {} columns at once exceeds the limit max_added_columns = {}",
+        num_new_columns,
+        max_added_columns
+    ))]
+    TooManyNewColumns {
+        num_new_columns: usize,
+        max_added_columns: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Adding columns [{}] to a table of {} columns exceeds the limit max_columns = {}",
+        columns,
+        num_columns,
+        max_columns
+    ))]
+    ColumnLimitExceeded {
+        columns: String,
+        num_columns: usize,
+        max_columns: usize,
+        location: Location,
+    },
+
+    #[snafu(display("{}", source))]
+    InvalidRows {
+        source: crate::validation::RowErrors,
+        location: Location,
+    },
+
+    #[snafu(display("{}", source))]
+    InvalidTableName {
+        source: common_catalog::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Failed to create vector, source: {}", source))]
     CreateVector {
         #[snafu(backtrace)]
         source: datatypes::error::Error,
     },
 
+    #[snafu(display(
+        "Failed to build the schema of table {}, source: {}",
+        table_name,
+        source
+    ))]
+    BuildTableSchema {
+        table_name: String,
+        #[snafu(backtrace)]
+        source: datatypes::error::Error,
+    },
+
     #[snafu(display("Missing required field in protobuf, field: {}", field))]
     MissingField { field: String, location: Location },
 
@@ -107,10 +266,24 @@ impl ErrorExt for Error {
             Error::DuplicatedTimestampColumn { .. } | Error::MissingTimestampColumn { .. } => {
                 StatusCode::InvalidArguments
             }
-            Error::InvalidColumnProto { .. } => StatusCode::InvalidArguments,
+            Error::InvalidColumnProto { .. }
+            | Error::NullMaskTooShort { .. }
+            | Error::NullMaskTooLong { .. }
+            | Error::RowCountWithoutValues { .. }
+            | Error::InconsistentNullMask { .. }
+            | Error::InvalidColumnValues { .. }
+            | Error::RowWidthMismatch { .. }
+            | Error::InvalidRowValue { .. }
+            | Error::ColumnValuesAbsent { .. }
+            | Error::InvalidRows { .. }
+            | Error::InvalidTableName { .. } => StatusCode::InvalidArguments,
+            Error::TooManyColumns { .. }
+            | Error::TooManyNewColumns { .. }
+            | Error::ColumnLimitExceeded { .. } => StatusCode::InvalidArguments,
             Error::CreateVector { .. } => StatusCode::InvalidArguments,
             Error::MissingField { .. } => StatusCode::InvalidArguments,
-            Error::ColumnDefaultConstraint { source, .. } => source.status_code(),
+            Error::BuildTableSchema { source, .. }
+            | Error::ColumnDefaultConstraint { source, .. } => source.status_code(),
             Error::InvalidColumnDef { source, .. } => source.status_code(),
             Error::UnrecognizedTableOption { .. } => StatusCode::InvalidArguments,
         }
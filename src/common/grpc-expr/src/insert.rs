@@ -14,29 +14,33 @@
 
 use std::collections::{HashMap, HashSet};
 
-use api::helper::ColumnDataTypeWrapper;
+use api::helper::{timestamp_values, ColumnDataTypeWrapper};
 use api::v1::column::{SemanticType, Values};
 use api::v1::{
     AddColumn, AddColumns, Column, ColumnDataType, ColumnDef, CreateTableExpr,
     InsertRequest as GrpcInsertRequest,
 };
+use common_base::validation_mode::ValidationMode;
 use common_base::BitVec;
-use common_time::timestamp::Timestamp;
+use common_catalog::naming::validate_table_name;
+use common_time::timestamp::{TimeUnit, Timestamp};
 use common_time::{Date, DateTime};
 use datatypes::data_type::{ConcreteDataType, DataType};
 use datatypes::prelude::{ValueRef, VectorRef};
-use datatypes::schema::SchemaRef;
-use datatypes::types::TimestampType;
-use datatypes::value::Value;
-use datatypes::vectors::MutableVector;
-use snafu::{ensure, OptionExt, ResultExt};
+use datatypes::schema::{ColumnDefaultConstraint, SchemaRef};
+use snafu::{ensure, ResultExt};
+use table::column_limits::ColumnLimits;
 use table::metadata::TableId;
-use table::requests::InsertRequest;
+use table::requests::{InsertRequest, WriteMode};
 
+use crate::column_limits;
 use crate::error::{
-    ColumnDataTypeSnafu, CreateVectorSnafu, DuplicatedTimestampColumnSnafu, IllegalInsertDataSnafu,
-    InvalidColumnProtoSnafu, MissingTimestampColumnSnafu, Result,
+    ColumnDataTypeSnafu, ColumnDefaultConstraintSnafu, ColumnValuesAbsentSnafu, CreateVectorSnafu,
+    DuplicatedTimestampColumnSnafu, IllegalInsertDataSnafu, InconsistentNullMaskSnafu,
+    InvalidColumnValuesSnafu, InvalidRowsSnafu, InvalidTableNameSnafu, MissingTimestampColumnSnafu,
+    NullMaskTooLongSnafu, NullMaskTooShortSnafu, Result, RowCountWithoutValuesSnafu,
 };
+use crate::validation::{vector_value, RowError, RowErrorCollector, RowErrors};
 const TAG_SEMANTIC_TYPE: i32 = SemanticType::Tag as i32;
 const TIMESTAMP_SEMANTIC_TYPE: i32 = SemanticType::Timestamp as i32;
 
@@ -50,7 +54,12 @@ fn build_column_def(column_name: &str, datatype: i32, nullable: bool) -> ColumnD
     }
 }
 
-pub fn find_new_columns(schema: &SchemaRef, columns: &[Column]) -> Result<Option<AddColumns>> {
+/// Finds the columns absent in `schema`, which are added to the table within `limits`.
+pub fn find_new_columns(
+    schema: &SchemaRef,
+    columns: &[Column],
+    limits: &ColumnLimits,
+) -> Result<Option<AddColumns>> {
     let mut columns_to_add = Vec::default();
     let mut new_columns: HashSet<String> = HashSet::default();
 
@@ -73,52 +82,157 @@ pub fn find_new_columns(schema: &SchemaRef, columns: &[Column]) -> Result<Option
     }
 
     if columns_to_add.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(AddColumns {
-            add_columns: columns_to_add,
-        }))
+        return Ok(None);
     }
+
+    let new_columns = columns_to_add
+        .iter()
+        .filter_map(|column| Some(column.column_def.as_ref()?.name.as_str()))
+        .collect::<Vec<_>>();
+    column_limits::check_new_columns(limits, schema.num_columns(), &new_columns)?;
+    Ok(Some(AddColumns {
+        add_columns: columns_to_add,
+    }))
 }
 
 pub fn column_to_vector(column: &Column, rows: u32) -> Result<VectorRef> {
-    let wrapper = ColumnDataTypeWrapper::try_new(column.datatype).context(ColumnDataTypeSnafu)?;
+    values_to_vector(
+        &column.column_name,
+        column.datatype,
+        column.values.as_ref(),
+        &column.null_mask,
+        rows as usize,
+    )
+}
+
+/// Builds the vector of the `row_count` rows of a column from its values on the wire.
+///
+/// The null mask marks the null rows, and there is a value for each of the others. A column
+/// without values is all null, which is different from a column absent in the request, to
+/// which the table fills the defaults.
+pub(crate) fn values_to_vector(
+    column_name: &str,
+    datatype: i32,
+    values: Option<&Values>,
+    null_mask: &[u8],
+    row_count: usize,
+) -> Result<VectorRef> {
+    let wrapper = ColumnDataTypeWrapper::try_new(datatype).context(ColumnDataTypeSnafu)?;
     let column_datatype = wrapper.datatype();
+    // Checks the values before the vector of `row_count` rows is allocated.
+    check_column_values(column_name, column_datatype, values, null_mask, row_count)?;
+    let mut vector = ConcreteDataType::from(wrapper).create_mutable_vector(row_count);
 
-    let rows = rows as usize;
-    let mut vector = ConcreteDataType::from(wrapper).create_mutable_vector(rows);
-
-    if let Some(values) = &column.values {
-        let values = collect_column_values(column_datatype, values);
-        let mut values_iter = values.into_iter();
-
-        let null_mask = BitVec::from_slice(&column.null_mask);
-        let mut nulls_iter = null_mask.iter().by_vals().fuse();
-
-        for i in 0..rows {
-            if let Some(true) = nulls_iter.next() {
-                vector.push_null();
-            } else {
-                let value_ref = values_iter
-                    .next()
-                    .with_context(|| InvalidColumnProtoSnafu {
-                        err_msg: format!(
-                            "value not found at position {} of column {}",
-                            i, &column.column_name
-                        ),
-                    })?;
-                vector
-                    .try_push_value_ref(value_ref)
-                    .context(CreateVectorSnafu)?;
-            }
+    let Some(values) = values else {
+        (0..row_count).for_each(|_| vector.push_null());
+        return Ok(vector.to_vector());
+    };
+
+    let null_mask = rows_null_mask(null_mask, row_count);
+    let mut values_iter = collect_column_values(column_datatype, values).into_iter();
+    for is_null in null_mask.iter().by_vals() {
+        if is_null {
+            vector.push_null();
+        } else {
+            // The mask is checked to leave a value for each non-null row.
+            let value_ref = values_iter.next().unwrap();
+            vector
+                .try_push_value_ref(value_ref)
+                .context(CreateVectorSnafu)?;
         }
-    } else {
-        (0..rows).for_each(|_| vector.push_null());
     }
     Ok(vector.to_vector())
 }
 
-fn collect_column_values(column_datatype: ColumnDataType, values: &Values) -> Vec<ValueRef> {
+/// Checks the values of a column on the wire are consistent with the `row_count` rows.
+///
+/// Only the lengths are checked, so it's cheap however large the column is: all the values
+/// must be of the datatype of the column, the null mask must not mark the rows beyond
+/// `row_count`, and there must be exactly one value for each of the non-null rows.
+pub(crate) fn check_column_values(
+    column_name: &str,
+    column_datatype: ColumnDataType,
+    values: Option<&Values>,
+    null_mask: &[u8],
+    row_count: usize,
+) -> Result<()> {
+    // The null mask of a column without values is ignored.
+    let Some(values) = values else {
+        return Ok(());
+    };
+
+    let expected_len = typed_values_len(column_datatype, values);
+    let actual_len = values_len(values);
+    // Values of other types would be dropped by `collect_column_values`, which otherwise looks
+    // like a column of fewer values, or even a column of nulls if the null mask covers all rows.
+    ensure!(
+        expected_len == actual_len,
+        InvalidColumnValuesSnafu {
+            column: column_name,
+            expected_type: column_datatype,
+            expected_len,
+            actual_len,
+        }
+    );
+    check_value_count(column_name, null_mask, row_count, expected_len)
+}
+
+/// Returns the number of the values of `column_datatype`, see [collect_column_values].
+fn typed_values_len(column_datatype: ColumnDataType, values: &Values) -> usize {
+    match column_datatype {
+        ColumnDataType::Boolean => values.bool_values.len(),
+        ColumnDataType::Int8 => values.i8_values.len(),
+        ColumnDataType::Int16 => values.i16_values.len(),
+        ColumnDataType::Int32 => values.i32_values.len(),
+        ColumnDataType::Int64 => values.i64_values.len(),
+        ColumnDataType::Uint8 => values.u8_values.len(),
+        ColumnDataType::Uint16 => values.u16_values.len(),
+        ColumnDataType::Uint32 => values.u32_values.len(),
+        ColumnDataType::Uint64 => values.u64_values.len(),
+        ColumnDataType::Float32 => values.f32_values.len(),
+        ColumnDataType::Float64 => values.f64_values.len(),
+        ColumnDataType::Binary => values.binary_values.len(),
+        ColumnDataType::String => values.string_values.len(),
+        ColumnDataType::Date => values.date_values.len(),
+        ColumnDataType::Datetime => values.datetime_values.len(),
+        ColumnDataType::TimestampSecond => timestamp_values(values, TimeUnit::Second).len(),
+        ColumnDataType::TimestampMillisecond => {
+            timestamp_values(values, TimeUnit::Millisecond).len()
+        }
+        ColumnDataType::TimestampMicrosecond => {
+            timestamp_values(values, TimeUnit::Microsecond).len()
+        }
+        ColumnDataType::TimestampNanosecond => timestamp_values(values, TimeUnit::Nanosecond).len(),
+    }
+}
+
+/// Returns the number of the values of all types.
+fn values_len(values: &Values) -> usize {
+    values.bool_values.len()
+        + values.i8_values.len()
+        + values.i16_values.len()
+        + values.i32_values.len()
+        + values.i64_values.len()
+        + values.u8_values.len()
+        + values.u16_values.len()
+        + values.u32_values.len()
+        + values.u64_values.len()
+        + values.f32_values.len()
+        + values.f64_values.len()
+        + values.binary_values.len()
+        + values.string_values.len()
+        + values.date_values.len()
+        + values.datetime_values.len()
+        + values.ts_second_values.len()
+        + values.ts_millisecond_values.len()
+        + values.ts_microsecond_values.len()
+        + values.ts_nanosecond_values.len()
+}
+
+pub(crate) fn collect_column_values(
+    column_datatype: ColumnDataType,
+    values: &Values,
+) -> Vec<ValueRef> {
     macro_rules! collect_values {
         ($value: expr, $mapper: expr) => {
             $value.iter().map($mapper).collect::<Vec<ValueRef>>()
@@ -165,30 +279,32 @@ fn collect_column_values(column_datatype: ColumnDataType, values: &Values) -> Ve
                 DateTime::new(*v)
             ))
         }
-        ColumnDataType::TimestampSecond => {
-            collect_values!(values.ts_second_values, |v| ValueRef::Timestamp(
-                Timestamp::new_second(*v)
-            ))
-        }
+        ColumnDataType::TimestampSecond => collect_timestamp_values(values, TimeUnit::Second),
         ColumnDataType::TimestampMillisecond => {
-            collect_values!(values.ts_millisecond_values, |v| ValueRef::Timestamp(
-                Timestamp::new_millisecond(*v)
-            ))
+            collect_timestamp_values(values, TimeUnit::Millisecond)
         }
         ColumnDataType::TimestampMicrosecond => {
-            collect_values!(values.ts_millisecond_values, |v| ValueRef::Timestamp(
-                Timestamp::new_microsecond(*v)
-            ))
+            collect_timestamp_values(values, TimeUnit::Microsecond)
         }
         ColumnDataType::TimestampNanosecond => {
-            collect_values!(values.ts_millisecond_values, |v| ValueRef::Timestamp(
-                Timestamp::new_nanosecond(*v)
-            ))
+            collect_timestamp_values(values, TimeUnit::Nanosecond)
         }
     }
 }
 
-/// Try to build create table request from insert data.
+fn collect_timestamp_values(values: &Values, unit: TimeUnit) -> Vec<ValueRef> {
+    timestamp_values(values, unit)
+        .iter()
+        .map(|v| ValueRef::Timestamp(Timestamp::new(*v, unit)))
+        .collect()
+}
+
+/// Try to build create table request from insert data, the columns of which are within
+/// `limits`. The table name must be valid by [common_catalog::naming].
+///
+/// The time index has the default `timestamp_default` if it's given, like
+/// `current_timestamp()`, so the rows inserted later may omit it.
+#[allow(clippy::too_many_arguments)]
 pub fn build_create_expr_from_insertion(
     catalog_name: &str,
     schema_name: &str,
@@ -196,7 +312,11 @@ pub fn build_create_expr_from_insertion(
     table_name: &str,
     columns: &[Column],
     engine: &str,
+    limits: &ColumnLimits,
+    timestamp_default: Option<&ColumnDefaultConstraint>,
 ) -> Result<CreateTableExpr> {
+    validate_table_name(table_name).context(InvalidTableNameSnafu)?;
+
     let mut new_columns: HashSet<String> = HashSet::default();
     let mut column_defs = Vec::default();
     let mut primary_key_indices = Vec::default();
@@ -238,6 +358,20 @@ pub fn build_create_expr_from_insertion(
         timestamp_index != usize::MAX,
         MissingTimestampColumnSnafu { msg: table_name }
     );
+    column_limits::check_new_table(limits, column_defs.len())?;
+    if let Some(constraint) = timestamp_default {
+        let column_def = &mut column_defs[timestamp_index];
+        let data_type = ColumnDataTypeWrapper::try_new(column_def.datatype)
+            .context(ColumnDataTypeSnafu)?
+            .into();
+        constraint
+            .validate(&data_type, column_def.is_nullable)
+            .context(ColumnDefaultConstraintSnafu)?;
+        column_def.default_constraint = constraint
+            .clone()
+            .try_into()
+            .context(ColumnDefaultConstraintSnafu)?;
+    }
     let timestamp_field_name = columns[timestamp_index].column_name.clone();
 
     let primary_keys = primary_key_indices
@@ -263,188 +397,303 @@ pub fn build_create_expr_from_insertion(
     Ok(expr)
 }
 
+/// Converts the insert request on the wire to the insert request of the table.
+///
+/// The columns are checked by [columns_to_vectors] and the rows by [validate_rows]. The
+/// `write_mode` of the request is carried in the metadata of the gRPC request rather than the
+/// insert itself.
 pub fn to_table_insert_request(
     catalog_name: &str,
     schema_name: &str,
     request: GrpcInsertRequest,
+    table_schema: &SchemaRef,
+    write_mode: WriteMode,
+    validation_mode: ValidationMode,
 ) -> Result<InsertRequest> {
-    let table_name = &request.table_name;
     let row_count = request.row_count as usize;
+    let (column_names, columns_values) =
+        columns_to_vectors(&request.columns, row_count, table_schema)?;
+    validate_rows(
+        &column_names,
+        &columns_values,
+        table_schema,
+        row_count,
+        validation_mode,
+    )
+    .context(InvalidRowsSnafu)?;
 
-    let mut columns_values = HashMap::with_capacity(request.columns.len());
+    Ok(InsertRequest {
+        catalog_name: catalog_name.to_string(),
+        schema_name: schema_name.to_string(),
+        table_name: request.table_name,
+        columns_values,
+        region_number: request.region_number,
+        write_mode,
+    })
+}
+
+/// Converts the `columns` of an insert request of `row_count` rows to vectors, checking them as
+/// the insertion into a table of `table_schema` does. Returns the names of the columns in the
+/// order of the request, and the vectors by the names.
+///
+/// A column whose values are absent is all null in the request, so it's rejected if it's not
+/// nullable in `table_schema`.
+pub(crate) fn columns_to_vectors(
+    columns: &[Column],
+    row_count: usize,
+    table_schema: &SchemaRef,
+) -> Result<(Vec<String>, HashMap<String, VectorRef>)> {
+    check_columns_values(columns, row_count)?;
+
+    let mut columns_values = HashMap::with_capacity(columns.len());
+    let mut column_names = Vec::with_capacity(columns.len());
     for Column {
         column_name,
         values,
         null_mask,
         datatype,
         ..
-    } in request.columns
+    } in columns
     {
-        let Some(values) = values else { continue };
-
-        let datatype: ConcreteDataType = ColumnDataTypeWrapper::try_new(datatype)
-            .context(ColumnDataTypeSnafu)?
-            .into();
-
-        let vector_builder = &mut datatype.create_mutable_vector(row_count);
-
-        add_values_to_builder(vector_builder, values, row_count, null_mask)?;
+        if values.is_none() && row_count > 0 {
+            let column_schema = table_schema.column_schema_by_name(column_name);
+            ensure!(
+                column_schema.map_or(true, |column| column.is_nullable()),
+                ColumnValuesAbsentSnafu {
+                    column: column_name,
+                }
+            );
+        }
 
+        let vector = values_to_vector(
+            column_name,
+            *datatype,
+            values.as_ref(),
+            null_mask,
+            row_count,
+        )?;
+        column_names.push(column_name.clone());
         ensure!(
-            columns_values
-                .insert(column_name, vector_builder.to_vector())
-                .is_none(),
+            columns_values.insert(column_name.clone(), vector).is_none(),
             IllegalInsertDataSnafu
         );
     }
+    Ok((column_names, columns_values))
+}
 
-    Ok(InsertRequest {
-        catalog_name: catalog_name.to_string(),
-        schema_name: schema_name.to_string(),
-        table_name: table_name.to_string(),
-        columns_values,
-        region_number: request.region_number,
-    })
+/// Checks the values of all the `columns` are consistent with the `row_count` rows, before
+/// any of the vectors is built, so a malformed request is rejected without allocating for its
+/// rows.
+///
+/// A column without values is consistent with any number of rows, so the rows must be backed
+/// by the values of at least one column, which bounds them by the size of the request.
+pub(crate) fn check_columns_values(columns: &[Column], row_count: usize) -> Result<()> {
+    ensure!(
+        row_count == 0 || columns.iter().any(|column| column.values.is_some()),
+        RowCountWithoutValuesSnafu { row_count }
+    );
+    for column in columns {
+        let wrapper =
+            ColumnDataTypeWrapper::try_new(column.datatype).context(ColumnDataTypeSnafu)?;
+        check_column_values(
+            &column.column_name,
+            wrapper.datatype(),
+            column.values.as_ref(),
+            &column.null_mask,
+            row_count,
+        )?;
+    }
+    Ok(())
 }
 
-pub(crate) fn add_values_to_builder(
-    builder: &mut Box<dyn MutableVector>,
-    values: Values,
+/// Validates the rows against `table_schema` as the table does on insertion, so all the invalid
+/// rows are reported instead of the table rejecting the insertion at the first one. A row is
+/// invalid if:
+/// - it has no value of a not null column without default value, i.e. the column is absent,
+/// - its value is not of the type of the column in the table, or
+/// - its value of a not null column is null.
+///
+/// The rows are validated in their order and then the order of the columns named by
+/// `column_names`, followed by the columns absent. The columns not in `table_schema` are
+/// skipped, they are added to the table before the insertion. The validation stops at the cap
+/// of the row errors of `validation_mode`.
+pub(crate) fn validate_rows(
+    column_names: &[String],
+    columns_values: &HashMap<String, VectorRef>,
+    table_schema: &SchemaRef,
     row_count: usize,
-    null_mask: Vec<u8>,
-) -> Result<()> {
-    let data_type = builder.data_type();
-    let values = convert_values(&data_type, values);
-
-    if null_mask.is_empty() {
-        ensure!(values.len() == row_count, IllegalInsertDataSnafu);
-
-        values.iter().try_for_each(|value| {
-            builder
-                .try_push_value_ref(value.as_value_ref())
-                .context(CreateVectorSnafu)
-        })?;
-    } else {
-        let null_mask = BitVec::from_vec(null_mask);
-        ensure!(
-            null_mask.count_ones() + values.len() == row_count,
-            IllegalInsertDataSnafu
-        );
+    validation_mode: ValidationMode,
+) -> std::result::Result<(), RowErrors> {
+    let present_columns = column_names.iter().filter_map(|column_name| {
+        let column_schema = table_schema.column_schema_by_name(column_name)?;
+        let vector = columns_values.get(column_name)?;
+        let data_type = vector.data_type();
+        // An all null vector may be of the null type, which the table takes for any column.
+        let type_mismatch =
+            (!data_type.is_null() && data_type != column_schema.data_type).then(|| {
+                format!(
+                    "column expects type {}, got {}",
+                    column_schema.data_type.name(),
+                    data_type.name()
+                )
+            });
+        let check = ColumnCheck {
+            name: column_name,
+            vector: Some(vector),
+            nullable: column_schema.is_nullable(),
+            type_mismatch,
+        };
+        check.is_needed().then_some(check)
+    });
+    let absent_columns = table_schema
+        .column_schemas()
+        .iter()
+        .filter(|column_schema| {
+            !column_schema.is_nullable()
+                && column_schema.default_constraint().is_none()
+                && !columns_values.contains_key(&column_schema.name)
+        })
+        .map(|column_schema| ColumnCheck {
+            name: &column_schema.name,
+            vector: None,
+            nullable: false,
+            type_mismatch: None,
+        });
+    let checks = present_columns.chain(absent_columns).collect::<Vec<_>>();
+    if checks.is_empty() {
+        return Ok(());
+    }
 
-        let mut idx_of_values = 0;
-        for idx in 0..row_count {
-            match is_null(&null_mask, idx) {
-                Some(true) => builder.push_null(),
-                _ => {
-                    builder
-                        .try_push_value_ref(values[idx_of_values].as_value_ref())
-                        .context(CreateVectorSnafu)?;
-                    idx_of_values += 1
+    let mut collector = RowErrorCollector::new(validation_mode);
+    for row in 0..row_count {
+        for check in &checks {
+            if let Some(error) = check.row_error(row) {
+                collector.push(error);
+                if collector.is_full() {
+                    return collector.finish();
                 }
             }
         }
     }
-    Ok(())
+    collector.finish()
+}
+
+/// Checks of the values of a column in [validate_rows].
+struct ColumnCheck<'a> {
+    name: &'a str,
+    /// Values of the column, `None` if it's absent.
+    vector: Option<&'a VectorRef>,
+    nullable: bool,
+    /// Reason of the values not of the type of the column, if they are not.
+    type_mismatch: Option<String>,
 }
 
-fn convert_values(data_type: &ConcreteDataType, values: Values) -> Vec<Value> {
-    // TODO(fys): use macros to optimize code
-    match data_type {
-        ConcreteDataType::Int64(_) => values
-            .i64_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::Float64(_) => values
-            .f64_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::String(_) => values
-            .string_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::Boolean(_) => values
-            .bool_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::Int8(_) => values.i8_values.into_iter().map(|val| val.into()).collect(),
-        ConcreteDataType::Int16(_) => values
-            .i16_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::Int32(_) => values
-            .i32_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::UInt8(_) => values.u8_values.into_iter().map(|val| val.into()).collect(),
-        ConcreteDataType::UInt16(_) => values
-            .u16_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::UInt32(_) => values
-            .u32_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::UInt64(_) => values
-            .u64_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::Float32(_) => values
-            .f32_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::Binary(_) => values
-            .binary_values
-            .into_iter()
-            .map(|val| val.into())
-            .collect(),
-        ConcreteDataType::DateTime(_) => values
-            .i64_values
-            .into_iter()
-            .map(|v| Value::DateTime(v.into()))
-            .collect(),
-        ConcreteDataType::Date(_) => values
-            .i32_values
-            .into_iter()
-            .map(|v| Value::Date(v.into()))
-            .collect(),
-        ConcreteDataType::Timestamp(TimestampType::Second(_)) => values
-            .ts_second_values
-            .into_iter()
-            .map(|v| Value::Timestamp(Timestamp::new_second(v)))
-            .collect(),
-        ConcreteDataType::Timestamp(TimestampType::Millisecond(_)) => values
-            .ts_millisecond_values
-            .into_iter()
-            .map(|v| Value::Timestamp(Timestamp::new_millisecond(v)))
-            .collect(),
-        ConcreteDataType::Timestamp(TimestampType::Microsecond(_)) => values
-            .ts_microsecond_values
-            .into_iter()
-            .map(|v| Value::Timestamp(Timestamp::new_microsecond(v)))
-            .collect(),
-        ConcreteDataType::Timestamp(TimestampType::Nanosecond(_)) => values
-            .ts_nanosecond_values
-            .into_iter()
-            .map(|v| Value::Timestamp(Timestamp::new_nanosecond(v)))
-            .collect(),
-        ConcreteDataType::Null(_) | ConcreteDataType::List(_) | ConcreteDataType::Dictionary(_) => {
-            unreachable!()
+impl ColumnCheck<'_> {
+    /// Returns true if any row could be invalid in this column.
+    fn is_needed(&self) -> bool {
+        match self.vector {
+            Some(vector) => {
+                self.type_mismatch.is_some() || (!self.nullable && vector.null_count() > 0)
+            }
+            None => true,
         }
     }
+
+    fn row_error(&self, row: usize) -> Option<RowError> {
+        let Some(vector) = self.vector else {
+            return Some(RowError::new(
+                row,
+                self.name,
+                None,
+                "missing value for not null column without default value",
+            ));
+        };
+        if vector.is_null(row) {
+            return (!self.nullable)
+                .then(|| RowError::new(row, self.name, None, "null value in not null column"));
+        }
+        let reason = self.type_mismatch.as_ref()?;
+        let value = vector_value(&**vector, row);
+        Some(RowError::new(row, self.name, value.as_deref(), reason))
+    }
+}
+
+/// Returns the null mask of the first `row_count` rows of a column.
+///
+/// The null mask is padded to whole bytes on the wire, and some clients set the padding bits,
+/// so the bits beyond `row_count` are dropped. The rows not covered by a short mask are not
+/// null.
+pub fn rows_null_mask(null_mask: &[u8], row_count: usize) -> BitVec {
+    let mut null_mask = BitVec::from_slice(null_mask);
+    null_mask.resize(row_count, false);
+    null_mask
 }
 
-fn is_null(null_mask: &BitVec, idx: usize) -> Option<bool> {
-    null_mask.get(idx).as_deref().copied()
+/// Returns the number of the null rows in the first `row_count` rows of a column, the same as
+/// the null mask returned by [rows_null_mask] but without building it.
+fn null_count(null_mask: &[u8], row_count: usize) -> usize {
+    let full_bytes = (row_count / 8).min(null_mask.len());
+    let mut null_count = null_mask[..full_bytes]
+        .iter()
+        .map(|byte| byte.count_ones() as usize)
+        .sum();
+    let padding_bits = row_count % 8;
+    if padding_bits > 0 {
+        if let Some(byte) = null_mask.get(full_bytes) {
+            null_count += (byte & ((1 << padding_bits) - 1)).count_ones() as usize;
+        }
+    }
+    null_count
+}
+
+/// Checks there is exactly a value for each of the non-null rows of the `row_count` rows of a
+/// column.
+///
+/// The padding bits of the last byte of the rows are dropped as [rows_null_mask] does, but a
+/// null mask marking the rows of the bytes after it as null is rejected, as it's the mask of
+/// more rows, even if it happens to be consistent with the values within `row_count`.
+fn check_value_count(
+    column_name: &str,
+    null_mask: &[u8],
+    row_count: usize,
+    value_count: usize,
+) -> Result<()> {
+    let row_bytes = (row_count + 7) / 8;
+    if let Some((i, byte)) = null_mask
+        .iter()
+        .enumerate()
+        .skip(row_bytes)
+        .find(|(_, byte)| **byte != 0)
+    {
+        return NullMaskTooLongSnafu {
+            column: column_name,
+            row: i * 8 + byte.trailing_zeros() as usize,
+            row_count,
+        }
+        .fail();
+    }
+
+    let mask_rows = null_mask.len() * 8;
+    let null_count = null_count(null_mask, row_count);
+    ensure!(
+        mask_rows >= row_count || null_count + value_count >= row_count,
+        NullMaskTooShortSnafu {
+            column: column_name,
+            mask_rows,
+            row_count,
+            value_count,
+        }
+    );
+    ensure!(
+        null_count + value_count == row_count,
+        InconsistentNullMaskSnafu {
+            column: column_name,
+            null_count,
+            row_count,
+            value_count,
+        }
+    );
+    Ok(())
 }
 
 #[cfg(test)]
@@ -453,18 +702,21 @@ mod tests {
     use std::sync::Arc;
     use std::{assert_eq, unimplemented, vec};
 
-    use api::helper::ColumnDataTypeWrapper;
+    use api::helper::{push_vals, ColumnDataTypeWrapper};
     use api::v1::column::{self, SemanticType, Values};
     use api::v1::{Column, ColumnDataType};
-    use common_base::BitVec;
     use common_catalog::consts::MITO_ENGINE;
+    use common_error::prelude::{ErrorExt, StatusCode};
     use common_query::physical_plan::PhysicalPlanRef;
     use common_query::prelude::Expr;
     use common_time::timestamp::Timestamp;
     use datatypes::data_type::ConcreteDataType;
-    use datatypes::schema::{ColumnSchema, SchemaBuilder, SchemaRef};
-    use datatypes::types::{TimestampMillisecondType, TimestampSecondType, TimestampType};
+    use datatypes::schema::{ColumnSchema, Schema, SchemaBuilder, SchemaRef};
     use datatypes::value::Value;
+    use datatypes::vectors::{
+        TimestampMicrosecondVector, TimestampMillisecondVector, TimestampNanosecondVector,
+        TimestampSecondVector,
+    };
     use snafu::ResultExt;
     use table::error::Result as TableResult;
     use table::metadata::TableInfoRef;
@@ -496,10 +748,19 @@ mod tests {
         let table_id = Some(10);
         let table_name = "test_metric";
 
-        assert!(
-            build_create_expr_from_insertion("", "", table_id, table_name, &[], MITO_ENGINE)
-                .is_err()
-        );
+        let limits = ColumnLimits::default();
+
+        assert!(build_create_expr_from_insertion(
+            "",
+            "",
+            table_id,
+            table_name,
+            &[],
+            MITO_ENGINE,
+            &limits,
+            None,
+        )
+        .is_err());
 
         let insert_batch = mock_insert_batch();
 
@@ -510,6 +771,8 @@ mod tests {
             table_name,
             &insert_batch.0,
             MITO_ENGINE,
+            &limits,
+            None,
         )
         .unwrap();
 
@@ -594,11 +857,14 @@ mod tests {
 
         let schema = Arc::new(SchemaBuilder::try_from(columns).unwrap().build().unwrap());
 
-        assert!(find_new_columns(&schema, &[]).unwrap().is_none());
+        let limits = ColumnLimits::default();
+        assert!(find_new_columns(&schema, &[], &limits).unwrap().is_none());
 
         let insert_batch = mock_insert_batch();
 
-        let add_columns = find_new_columns(&schema, &insert_batch.0).unwrap().unwrap();
+        let add_columns = find_new_columns(&schema, &insert_batch.0, &limits)
+            .unwrap()
+            .unwrap();
 
         assert_eq!(2, add_columns.add_columns.len());
         let host_column = &add_columns.add_columns[0];
@@ -624,6 +890,148 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_invalid_table_name_on_insertion() {
+        let (columns, _) = mock_insert_batch();
+        for name in ["", "cpu usage", "greptime_metrics"] {
+            let err = build_create_expr_from_insertion(
+                "",
+                "",
+                None,
+                name,
+                &columns,
+                MITO_ENGINE,
+                &ColumnLimits::default(),
+                None,
+            )
+            .unwrap_err();
+            assert_eq!(
+                common_catalog::naming::validate_table_name(name)
+                    .unwrap_err()
+                    .to_string(),
+                err.to_string()
+            );
+            assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        }
+    }
+
+    #[test]
+    fn test_column_limits_on_insertion() {
+        let (columns, _) = mock_insert_batch();
+        let limits = ColumnLimits {
+            max_columns: 3,
+            max_added_columns: 2,
+        };
+
+        // The batch has 4 columns.
+        let err = build_create_expr_from_insertion(
+            "",
+            "",
+            None,
+            "demo",
+            &columns,
+            MITO_ENGINE,
+            &limits,
+            None,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::TooManyColumns {
+                    num_columns: 4,
+                    max_columns: 3,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        // Adding "host" and "memory" to the table of "cpu" and "ts".
+        let schema = Arc::new(
+            SchemaBuilder::try_from(vec![
+                ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+                ColumnSchema::new(
+                    "ts",
+                    ConcreteDataType::timestamp_millisecond_datatype(),
+                    false,
+                )
+                .with_time_index(true),
+            ])
+            .unwrap()
+            .build()
+            .unwrap(),
+        );
+        let err = find_new_columns(&schema, &columns, &limits).unwrap_err();
+        assert_eq!(
+            "Adding columns [host, memory] to a table of 2 columns exceeds the limit max_columns = 3",
+            err.to_string()
+        );
+
+        // Columns of the table are still writable.
+        assert!(find_new_columns(&schema, &columns[1..2], &limits)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_timestamp_default_on_insertion() {
+        let (columns, _) = mock_insert_batch();
+        let build = |constraint: ColumnDefaultConstraint| {
+            build_create_expr_from_insertion(
+                "",
+                "",
+                None,
+                "demo",
+                &columns,
+                MITO_ENGINE,
+                &ColumnLimits::default(),
+                Some(&constraint),
+            )
+        };
+
+        let constraint = ColumnDefaultConstraint::Function("current_timestamp()".to_string());
+        let create_expr = build(constraint.clone()).unwrap();
+        let schema = crate::create_table_schema(&create_expr, true).unwrap();
+        let ts = schema
+            .column_schemas
+            .iter()
+            .find(|column| column.name == "ts")
+            .unwrap();
+        assert_eq!(Some(&constraint), ts.default_constraint());
+        let host = schema
+            .column_schemas
+            .iter()
+            .find(|column| column.name == "host")
+            .unwrap();
+        assert!(host.default_constraint().is_none());
+
+        let err = build(ColumnDefaultConstraint::Function("bogus()".to_string())).unwrap_err();
+        assert!(
+            matches!(err, error::Error::ColumnDefaultConstraint { .. }),
+            "{err}"
+        );
+        assert!(err
+            .to_string()
+            .contains("Unsupported column default constraint expression: bogus()"));
+
+        // The table created by clients encoding the function themselves is validated too.
+        let mut create_expr = build(constraint).unwrap();
+        for column_def in &mut create_expr.column_defs {
+            if column_def.name == "ts" {
+                column_def.default_constraint =
+                    ColumnDefaultConstraint::Function("bogus()".to_string())
+                        .try_into()
+                        .unwrap();
+            }
+        }
+        let err = crate::create_table_schema(&create_expr, true).unwrap_err();
+        assert!(
+            matches!(err, error::Error::InvalidColumnDef { .. }),
+            "{err}"
+        );
+    }
+
     #[test]
     fn test_to_table_insert_request() {
         let (columns, row_count) = mock_insert_batch();
@@ -633,7 +1041,15 @@ mod tests {
             row_count,
             region_number: 0,
         };
-        let insert_req = to_table_insert_request("greptime", "public", request).unwrap();
+        let insert_req = to_table_insert_request(
+            "greptime",
+            "public",
+            request,
+            &DemoTable.schema(),
+            WriteMode::default(),
+            ValidationMode::default(),
+        )
+        .unwrap();
 
         assert_eq!("greptime", insert_req.catalog_name);
         assert_eq!("public", insert_req.schema_name);
@@ -654,73 +1070,652 @@ mod tests {
         let ts = insert_req.columns_values.get("ts").unwrap();
         assert_eq!(Value::Timestamp(Timestamp::new_millisecond(100)), ts.get(0));
         assert_eq!(Value::Timestamp(Timestamp::new_millisecond(101)), ts.get(1));
+        assert_eq!(WriteMode::Upsert, insert_req.write_mode);
     }
 
     #[test]
-    fn test_convert_values() {
-        let data_type = ConcreteDataType::float64_datatype();
+    fn test_to_table_insert_request_with_write_mode() {
+        let (columns, row_count) = mock_insert_batch();
+        let request = GrpcInsertRequest {
+            table_name: "demo".to_string(),
+            columns,
+            row_count,
+            region_number: 0,
+        };
+        let insert_req = to_table_insert_request(
+            "greptime",
+            "public",
+            request,
+            &DemoTable.schema(),
+            WriteMode::InsertIgnore,
+            ValidationMode::default(),
+        )
+        .unwrap();
+        assert_eq!(WriteMode::InsertIgnore, insert_req.write_mode);
+        assert_eq!(4, insert_req.columns_values.len());
+    }
+
+    #[test]
+    fn test_collect_column_values() {
         let values = Values {
             f64_values: vec![0.1, 0.2, 0.3],
             ..Default::default()
         };
 
-        let result = convert_values(&data_type, values);
+        let result = collect_column_values(ColumnDataType::Float64, &values);
 
         assert_eq!(
             vec![
-                Value::Float64(0.1.into()),
-                Value::Float64(0.2.into()),
-                Value::Float64(0.3.into())
+                ValueRef::Float64(0.1.into()),
+                ValueRef::Float64(0.2.into()),
+                ValueRef::Float64(0.3.into())
             ],
             result
         );
     }
 
     #[test]
-    fn test_convert_timestamp_values() {
-        // second
-        let actual = convert_values(
-            &ConcreteDataType::Timestamp(TimestampType::Second(TimestampSecondType)),
-            Values {
-                ts_second_values: vec![1_i64, 2_i64, 3_i64],
-                ..Default::default()
-            },
-        );
-        let expect = vec![
-            Value::Timestamp(Timestamp::new_second(1_i64)),
-            Value::Timestamp(Timestamp::new_second(2_i64)),
-            Value::Timestamp(Timestamp::new_second(3_i64)),
+    fn test_collect_timestamp_values() {
+        let values = Values {
+            ts_second_values: vec![1],
+            ts_millisecond_values: vec![2],
+            ts_microsecond_values: vec![3],
+            ts_nanosecond_values: vec![4],
+            ..Default::default()
+        };
+        let cases = [
+            (ColumnDataType::TimestampSecond, Timestamp::new_second(1)),
+            (
+                ColumnDataType::TimestampMillisecond,
+                Timestamp::new_millisecond(2),
+            ),
+            (
+                ColumnDataType::TimestampMicrosecond,
+                Timestamp::new_microsecond(3),
+            ),
+            (
+                ColumnDataType::TimestampNanosecond,
+                Timestamp::new_nanosecond(4),
+            ),
         ];
-        assert_eq!(expect, actual);
+        for (datatype, expect) in cases {
+            assert_eq!(
+                vec![ValueRef::Timestamp(expect)],
+                collect_column_values(datatype, &values)
+            );
+        }
+    }
 
-        // millisecond
-        let actual = convert_values(
-            &ConcreteDataType::Timestamp(TimestampType::Millisecond(TimestampMillisecondType)),
-            Values {
-                ts_millisecond_values: vec![1_i64, 2_i64, 3_i64],
+    #[test]
+    fn test_timestamp_values_round_trip() {
+        let vectors: Vec<VectorRef> = vec![
+            Arc::new(TimestampSecondVector::from(vec![Some(1), None, Some(3)])),
+            Arc::new(TimestampMillisecondVector::from(vec![
+                Some(1),
+                None,
+                Some(3),
+            ])),
+            Arc::new(TimestampMicrosecondVector::from(vec![
+                Some(1),
+                None,
+                Some(3),
+            ])),
+            Arc::new(TimestampNanosecondVector::from(vec![
+                Some(1),
+                None,
+                Some(3),
+            ])),
+        ];
+        for vector in vectors {
+            let datatype = ColumnDataTypeWrapper::try_from(vector.data_type()).unwrap();
+            let mut column = Column {
+                column_name: "ts".to_string(),
+                semantic_type: SemanticType::Timestamp as i32,
+                datatype: datatype.datatype() as i32,
                 ..Default::default()
-            },
+            };
+            push_vals(&mut column, 0, vector.clone());
+
+            assert_eq!(vector, column_to_vector(&column, 3).unwrap());
+
+            let request = GrpcInsertRequest {
+                table_name: "demo".to_string(),
+                columns: vec![column],
+                row_count: 3,
+                region_number: 0,
+            };
+            let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+                "ts",
+                vector.data_type(),
+                true,
+            )
+            .with_time_index(true)]));
+            let mut insert_req = to_table_insert_request(
+                "greptime",
+                "public",
+                request,
+                &schema,
+                WriteMode::default(),
+                ValidationMode::default(),
+            )
+            .unwrap();
+            assert_eq!(vector, insert_req.columns_values.remove("ts").unwrap());
+        }
+    }
+
+    #[test]
+    fn test_rows_null_mask() {
+        let null_mask = rows_null_mask(&[0b0000_0001, 0b0000_1000], 12);
+        assert_eq!(12, null_mask.len());
+        assert!(null_mask[0]);
+        assert!(!null_mask[1]);
+        assert!(!null_mask[10]);
+        assert!(null_mask[11]);
+
+        // Padding bits are dropped.
+        let null_mask = rows_null_mask(&[0b1111_1010], 3);
+        assert_eq!(3, null_mask.len());
+        assert_eq!(1, null_mask.count_ones());
+
+        // Rows not covered by the mask are not null.
+        let null_mask = rows_null_mask(&[0b0000_0001], 10);
+        assert_eq!(10, null_mask.len());
+        assert_eq!(1, null_mask.count_ones());
+    }
+
+    fn new_cpu_column(values: Vec<f64>, null_mask: Vec<u8>) -> Column {
+        Column {
+            column_name: "cpu".to_string(),
+            semantic_type: SemanticType::Field as i32,
+            values: Some(Values {
+                f64_values: values,
+                ..Default::default()
+            }),
+            null_mask,
+            datatype: ColumnDataType::Float64 as i32,
+        }
+    }
+
+    fn insert_cpu_column(column: Column, row_count: u32) -> error::Result<VectorRef> {
+        insert_columns(vec![column], row_count)
+    }
+
+    /// Inserts the columns into a table of the nullable column "cpu", returning its vector.
+    fn insert_columns(columns: Vec<Column>, row_count: u32) -> error::Result<VectorRef> {
+        let request = GrpcInsertRequest {
+            table_name: "demo".to_string(),
+            columns,
+            row_count,
+            region_number: 0,
+        };
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "cpu",
+            ConcreteDataType::float64_datatype(),
+            true,
+        )]));
+        let mut insert_req = to_table_insert_request(
+            "greptime",
+            "public",
+            request,
+            &schema,
+            WriteMode::default(),
+            ValidationMode::default(),
+        )?;
+        Ok(insert_req.columns_values.remove("cpu").unwrap())
+    }
+
+    #[test]
+    fn test_null_mask_with_padding_bits() {
+        // Row 1 is null, and the padding bits after row 2 are set.
+        let column = new_cpu_column(vec![0.1, 0.3], vec![0b1111_1010]);
+
+        for vector in [
+            column_to_vector(&column, 3).unwrap(),
+            insert_cpu_column(column, 3).unwrap(),
+        ] {
+            assert_eq!(3, vector.len());
+            assert_eq!(Value::Float64(0.1.into()), vector.get(0));
+            assert_eq!(Value::Null, vector.get(1));
+            assert_eq!(Value::Float64(0.3.into()), vector.get(2));
+        }
+
+        // A mask not covering all the rows, with values for the rest.
+        let column = new_cpu_column(vec![0.0; 9], vec![0b0000_0001]);
+        let vector = insert_cpu_column(column, 10).unwrap();
+        assert_eq!(10, vector.len());
+        assert_eq!(1, vector.null_count());
+    }
+
+    #[test]
+    fn test_invalid_null_mask() {
+        // The mask covers 8 of the 10 rows, but there are only 7 values for the 9 rows left.
+        let column = new_cpu_column(vec![0.0; 7], vec![0b0000_0001]);
+        for err in [
+            column_to_vector(&column, 10).unwrap_err(),
+            insert_cpu_column(column, 10).unwrap_err(),
+        ] {
+            assert!(
+                matches!(
+                    err,
+                    error::Error::NullMaskTooShort {
+                        mask_rows: 8,
+                        row_count: 10,
+                        value_count: 7,
+                        ..
+                    }
+                ),
+                "{err}"
+            );
+        }
+
+        // No mask, and missing values.
+        let column = new_cpu_column(vec![0.0; 2], vec![]);
+        let err = insert_cpu_column(column, 3).unwrap_err();
+        assert!(
+            matches!(err, error::Error::NullMaskTooShort { mask_rows: 0, .. }),
+            "{err}"
         );
-        let expect = vec![
-            Value::Timestamp(Timestamp::new_millisecond(1_i64)),
-            Value::Timestamp(Timestamp::new_millisecond(2_i64)),
-            Value::Timestamp(Timestamp::new_millisecond(3_i64)),
-        ];
-        assert_eq!(expect, actual);
+
+        // The nulls and the values are more than the rows.
+        let column = new_cpu_column(vec![0.1, 0.2, 0.3], vec![0b0000_0010]);
+        for err in [
+            column_to_vector(&column, 3).unwrap_err(),
+            insert_cpu_column(column, 3).unwrap_err(),
+        ] {
+            assert!(
+                matches!(
+                    err,
+                    error::Error::InconsistentNullMask {
+                        null_count: 1,
+                        row_count: 3,
+                        value_count: 3,
+                        ..
+                    }
+                ),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_values_of_other_types() {
+        // Float64 column with its values in `i64_values`.
+        let mut column = new_cpu_column(vec![], vec![]);
+        column.values = Some(Values {
+            i64_values: vec![1, 2, 3],
+            ..Default::default()
+        });
+        for err in [
+            column_to_vector(&column, 3).unwrap_err(),
+            insert_cpu_column(column.clone(), 3).unwrap_err(),
+        ] {
+            assert!(
+                matches!(
+                    err,
+                    error::Error::InvalidColumnValues {
+                        expected_type: ColumnDataType::Float64,
+                        expected_len: 0,
+                        actual_len: 3,
+                        ..
+                    }
+                ),
+                "{err}"
+            );
+        }
+
+        // It's not taken as a column of nulls even if the null mask covers all the rows.
+        column.null_mask = vec![0b0000_0111];
+        let err = insert_cpu_column(column, 3).unwrap_err();
+        assert!(
+            matches!(err, error::Error::InvalidColumnValues { .. }),
+            "{err}"
+        );
+
+        // Values of the type along with values of other types.
+        let mut column = new_cpu_column(vec![0.1, 0.2], vec![]);
+        column.values.as_mut().unwrap().string_values = vec!["0.3".to_string()];
+        let err = insert_cpu_column(column, 3).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::InvalidColumnValues {
+                    expected_len: 2,
+                    actual_len: 3,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+        assert!(err.to_string().contains("cpu"), "{err}");
+    }
+
+    #[test]
+    fn test_mismatched_value_count() {
+        // More values than the non-null rows.
+        let column = new_cpu_column(vec![0.0; 5], vec![0b0000_0100]);
+        let err = insert_cpu_column(column, 4).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::InconsistentNullMask {
+                    null_count: 1,
+                    row_count: 4,
+                    value_count: 5,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        // Fewer values than the non-null rows.
+        let column = new_cpu_column(vec![0.0; 2], vec![0b0000_0100]);
+        let err = insert_cpu_column(column, 4).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::InconsistentNullMask {
+                    null_count: 1,
+                    row_count: 4,
+                    value_count: 2,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        // A bogus row count is rejected before the rows are allocated.
+        let column = new_cpu_column(vec![0.0; 2], vec![]);
+        let err = insert_cpu_column(column, u32::MAX).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::NullMaskTooShort {
+                    mask_rows: 0,
+                    value_count: 2,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+    }
+
+    #[test]
+    fn test_null_mask_too_long() {
+        // Row 1 is null in the 4 rows, and so are rows 8 and 9 of the second byte, which looks
+        // consistent with the 3 values within the rows.
+        let column = new_cpu_column(vec![0.0; 3], vec![0b0000_0010, 0b0000_0011]);
+        for err in [
+            column_to_vector(&column, 4).unwrap_err(),
+            insert_cpu_column(column, 4).unwrap_err(),
+        ] {
+            assert!(
+                matches!(
+                    err,
+                    error::Error::NullMaskTooLong {
+                        row: 8,
+                        row_count: 4,
+                        ..
+                    }
+                ),
+                "{err}"
+            );
+            assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        }
+
+        // The bytes after the rows are allowed if they mark no rows as null.
+        let column = new_cpu_column(vec![0.0; 3], vec![0b0000_0010, 0, 0]);
+        let vector = insert_cpu_column(column, 4).unwrap();
+        assert_eq!(4, vector.len());
+        assert_eq!(1, vector.null_count());
+    }
+
+    #[test]
+    fn test_random_columns() {
+        // A xorshift generator, so a failure is reproducible.
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+
+        for _ in 0..10000 {
+            let mut row_count = next(40) as u32;
+            let values = (0..next(40)).map(|i| i as f64).collect::<Vec<_>>();
+            let null_mask = (0..next(8)).map(|_| next(256) as u8).collect::<Vec<_>>();
+            let mut column = new_cpu_column(values, null_mask);
+            match next(8) {
+                0 => column.values = None,
+                1 => column.values.as_mut().unwrap().i64_values = vec![1],
+                2 => {
+                    // A bogus row count of the column without values is rejected before the
+                    // rows are allocated.
+                    column.values = None;
+                    row_count = u32::MAX;
+                }
+                _ => {}
+            }
+
+            let value_count = column.values.as_ref().map_or(0, |v| v.f64_values.len());
+            let desc = format!("{column:?}, row_count: {row_count}");
+            match insert_cpu_column(column.clone(), row_count) {
+                Ok(vector) => {
+                    assert_eq!(row_count as usize, vector.len(), "{desc}");
+                    assert_eq!(value_count, vector.len() - vector.null_count(), "{desc}");
+                    assert_eq!(vector, column_to_vector(&column, row_count).unwrap());
+                }
+                Err(err) => {
+                    assert!(
+                        matches!(
+                            err,
+                            error::Error::NullMaskTooShort { .. }
+                                | error::Error::NullMaskTooLong { .. }
+                                | error::Error::InconsistentNullMask { .. }
+                                | error::Error::InvalidColumnValues { .. }
+                                | error::Error::RowCountWithoutValues { .. }
+                        ),
+                        "{desc}: {err}"
+                    );
+                    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+                    // A column without values is consistent with any number of rows on its
+                    // own, it's the request without any values that is rejected.
+                    if column.values.is_some() {
+                        assert!(column_to_vector(&column, row_count).is_err(), "{desc}");
+                    } else {
+                        assert!(
+                            matches!(err, error::Error::RowCountWithoutValues { .. }),
+                            "{desc}: {err}"
+                        );
+                    }
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_is_null() {
-        let null_mask = BitVec::from_slice(&[0b0000_0001, 0b0000_1000]);
+    fn test_all_null_column() {
+        // The values of an all null column are absent.
+        let mut column = new_cpu_column(vec![], vec![]);
+        column.values = None;
+        let mut memory = new_cpu_column(vec![0.0; 3], vec![]);
+        memory.column_name = "memory".to_string();
+
+        for vector in [
+            column_to_vector(&column, 3).unwrap(),
+            insert_columns(vec![column.clone(), memory], 3).unwrap(),
+        ] {
+            assert_eq!(3, vector.len());
+            assert_eq!(3, vector.null_count());
+            assert_eq!(ConcreteDataType::float64_datatype(), vector.data_type());
+        }
+    }
 
-        assert_eq!(Some(true), is_null(&null_mask, 0));
-        assert_eq!(Some(false), is_null(&null_mask, 1));
-        assert_eq!(Some(false), is_null(&null_mask, 10));
-        assert_eq!(Some(true), is_null(&null_mask, 11));
-        assert_eq!(Some(false), is_null(&null_mask, 12));
+    #[test]
+    fn test_rows_without_values() {
+        // The rows of a request without any values are bounded by nothing.
+        let mut column = new_cpu_column(vec![], vec![]);
+        column.values = None;
+        let err = insert_cpu_column(column.clone(), u32::MAX).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::RowCountWithoutValues { row_count, .. }
+                    if row_count == u32::MAX as usize
+            ),
+            "{err}"
+        );
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+
+        let vector = insert_cpu_column(column, 0).unwrap();
+        assert!(vector.is_empty());
+    }
+
+    #[test]
+    fn test_absent_values_of_not_null_column() {
+        let (mut columns, row_count) = mock_insert_batch();
+        // "host" is not nullable.
+        columns[0].values = None;
+        let request = GrpcInsertRequest {
+            table_name: "demo".to_string(),
+            columns,
+            row_count,
+            region_number: 0,
+        };
 
-        assert_eq!(None, is_null(&null_mask, 16));
-        assert_eq!(None, is_null(&null_mask, 99));
+        let err = to_table_insert_request(
+            "greptime",
+            "public",
+            request,
+            &DemoTable.schema(),
+            WriteMode::default(),
+            ValidationMode::default(),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&err, error::Error::ColumnValuesAbsent { column, .. } if column == "host"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_null_values_of_not_null_column() {
+        let (mut columns, _) = mock_insert_batch();
+        // Rows 1, 4 and 7 of "host", which is not nullable, are null.
+        columns[0].values = Some(column::Values {
+            string_values: (0..5).map(|i| format!("host{i}")).collect(),
+            ..Default::default()
+        });
+        columns[0].null_mask = vec![0b1001_0010];
+        columns.truncate(1);
+        let request = GrpcInsertRequest {
+            table_name: "demo".to_string(),
+            columns,
+            row_count: 8,
+            region_number: 0,
+        };
+
+        let row_errors = |validation_mode| {
+            let err = to_table_insert_request(
+                "greptime",
+                "public",
+                request.clone(),
+                &DemoTable.schema(),
+                WriteMode::default(),
+                validation_mode,
+            )
+            .unwrap_err();
+            assert_eq!(StatusCode::InvalidArguments, err.status_code());
+            let error::Error::InvalidRows { source, .. } = err else {
+                unreachable!("{err}")
+            };
+            source
+        };
+
+        let lenient = row_errors(ValidationMode::Lenient);
+        assert_eq!(
+            vec![1, 4, 7],
+            lenient.errors.iter().map(|e| e.row).collect::<Vec<_>>()
+        );
+        assert!(lenient
+            .errors
+            .iter()
+            .all(|e| e.column == "host" && e.value.is_none()));
+        assert!(!lenient.incomplete);
+
+        let strict = row_errors(ValidationMode::Strict);
+        assert_eq!(vec![lenient.errors[0].clone()], strict.errors);
+        assert_eq!(
+            "Invalid rows in insertion: row 1, column host, value NULL: null value in not null column",
+            strict.to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_rows_of_insertion() {
+        let (mut columns, _) = mock_insert_batch();
+        // "cpu" is of type String, with the second row null, and "host", which is not null
+        // without default value, is absent.
+        columns[1] = Column {
+            column_name: "cpu".to_string(),
+            semantic_type: SemanticType::Field as i32,
+            values: Some(column::Values {
+                string_values: vec!["high".to_string()],
+                ..Default::default()
+            }),
+            null_mask: vec![0b10],
+            datatype: ColumnDataType::String as i32,
+        };
+        let _ = columns.remove(0);
+        let request = GrpcInsertRequest {
+            table_name: "demo".to_string(),
+            columns,
+            row_count: 2,
+            region_number: 0,
+        };
+
+        let row_errors = |validation_mode| {
+            let err = to_table_insert_request(
+                "greptime",
+                "public",
+                request.clone(),
+                &DemoTable.schema(),
+                WriteMode::default(),
+                validation_mode,
+            )
+            .unwrap_err();
+            let error::Error::InvalidRows { source, .. } = err else {
+                unreachable!("{err}")
+            };
+            source.errors
+        };
+
+        let lenient = row_errors(ValidationMode::Lenient);
+        assert_eq!(
+            vec![
+                RowError::new(
+                    0,
+                    "cpu",
+                    Some("high"),
+                    "column expects type Float64, got String"
+                ),
+                RowError::new(
+                    0,
+                    "host",
+                    None,
+                    "missing value for not null column without default value"
+                ),
+                RowError::new(
+                    1,
+                    "host",
+                    None,
+                    "missing value for not null column without default value"
+                ),
+            ],
+            lenient
+        );
+        assert_eq!(lenient[..1], row_errors(ValidationMode::Strict));
     }
 
     struct DemoTable;
@@ -13,9 +13,16 @@
 // limitations under the License.
 
 mod alter;
+pub mod column_limits;
 pub mod delete;
+pub mod dry_run;
 pub mod error;
 pub mod insert;
+pub mod row_insert;
+pub mod validation;
 
 pub use alter::{alter_expr_to_request, create_expr_to_request, create_table_schema};
-pub use insert::{build_create_expr_from_insertion, column_to_vector, find_new_columns};
+pub use insert::{
+    build_create_expr_from_insertion, column_to_vector, find_new_columns, rows_null_mask,
+};
+pub use row_insert::{build_create_expr_from_row_insertion, rows_to_insert_request};
@@ -0,0 +1,555 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions of the row-oriented [RowInsertRequest], the counterparts of the ones of the
+//! columnar insert request in [crate::insert].
+
+use std::collections::{HashMap, HashSet};
+
+use api::helper::{push_vals, ColumnDataTypeWrapper};
+use api::v1::row::value::ValueData;
+use api::v1::row::{ColumnSchema, RowInsertRequest};
+use api::v1::{Column, ColumnDataType, CreateTableExpr, InsertRequest as GrpcInsertRequest};
+use common_base::validation_mode::ValidationMode;
+use common_time::timestamp::{TimeUnit, Timestamp};
+use common_time::{Date, DateTime};
+use datatypes::data_type::{ConcreteDataType, DataType};
+use datatypes::prelude::ValueRef;
+use datatypes::schema::{ColumnDefaultConstraint, SchemaRef};
+use datatypes::vectors::VectorRef;
+use snafu::{ensure, OptionExt, ResultExt};
+use table::column_limits::ColumnLimits;
+use table::metadata::TableId;
+use table::requests::{InsertRequest, WriteMode};
+
+use crate::error::{
+    ColumnDataTypeSnafu, CreateVectorSnafu, IllegalInsertDataSnafu, InvalidRowValueSnafu,
+    InvalidRowsSnafu, Result, RowWidthMismatchSnafu,
+};
+use crate::insert::{build_create_expr_from_insertion, validate_rows};
+
+/// Converts the row insert request on the wire to the insert request of the table, the same as
+/// [to_table_insert_request](crate::insert::to_table_insert_request) does for the columnar one.
+///
+/// Every row must have a value for each column of the schema of the request, where a value
+/// without data is null.
+pub fn rows_to_table_insert_request(
+    catalog_name: &str,
+    schema_name: &str,
+    request: RowInsertRequest,
+    table_schema: &SchemaRef,
+    write_mode: WriteMode,
+    validation_mode: ValidationMode,
+) -> Result<InsertRequest> {
+    let row_count = request.rows.len();
+    let vectors = rows_to_vectors(&request)?;
+
+    let mut columns_values = HashMap::with_capacity(vectors.len());
+    let mut column_names = Vec::with_capacity(vectors.len());
+    for (column, vector) in request.schema.into_iter().zip(vectors) {
+        column_names.push(column.column_name.clone());
+        ensure!(
+            columns_values.insert(column.column_name, vector).is_none(),
+            IllegalInsertDataSnafu
+        );
+    }
+    validate_rows(
+        &column_names,
+        &columns_values,
+        table_schema,
+        row_count,
+        validation_mode,
+    )
+    .context(InvalidRowsSnafu)?;
+
+    Ok(InsertRequest {
+        catalog_name: catalog_name.to_string(),
+        schema_name: schema_name.to_string(),
+        table_name: request.table_name,
+        columns_values,
+        region_number: request.region_number,
+        write_mode,
+    })
+}
+
+/// Converts the row insert request on the wire to the columnar insert request on the wire,
+/// so the rows are inserted the same way as the columns are, including creating or altering
+/// the table on demand.
+pub fn rows_to_insert_request(request: RowInsertRequest) -> Result<GrpcInsertRequest> {
+    let row_count = request.rows.len();
+    let vectors = rows_to_vectors(&request)?;
+
+    let mut column_names = HashSet::with_capacity(vectors.len());
+    let mut columns = Vec::with_capacity(vectors.len());
+    for (column, vector) in request.schema.into_iter().zip(vectors) {
+        ensure!(
+            column_names.insert(column.column_name.clone()),
+            IllegalInsertDataSnafu
+        );
+        let mut grpc_column = Column {
+            column_name: column.column_name,
+            semantic_type: column.semantic_type,
+            datatype: column.datatype,
+            ..Default::default()
+        };
+        push_vals(&mut grpc_column, 0, vector);
+        columns.push(grpc_column);
+    }
+
+    Ok(GrpcInsertRequest {
+        table_name: request.table_name,
+        columns,
+        row_count: row_count as u32,
+        region_number: request.region_number,
+    })
+}
+
+/// Pivots the rows of the request into a vector for each column of its schema.
+fn rows_to_vectors(request: &RowInsertRequest) -> Result<Vec<VectorRef>> {
+    let row_count = request.rows.len();
+    let width = request.schema.len();
+
+    let mut datatypes = Vec::with_capacity(width);
+    let mut vectors = Vec::with_capacity(width);
+    for column in &request.schema {
+        let wrapper =
+            ColumnDataTypeWrapper::try_new(column.datatype).context(ColumnDataTypeSnafu)?;
+        datatypes.push(wrapper.datatype());
+        vectors.push(ConcreteDataType::from(wrapper).create_mutable_vector(row_count));
+    }
+
+    for (row_index, row) in request.rows.iter().enumerate() {
+        ensure!(
+            row.values.len() == width,
+            RowWidthMismatchSnafu {
+                row: row_index,
+                expected: width,
+                actual: row.values.len(),
+            }
+        );
+        for (column_index, value) in row.values.iter().enumerate() {
+            let vector = &mut vectors[column_index];
+            let Some(data) = &value.value_data else {
+                vector.push_null();
+                continue;
+            };
+            let datatype = datatypes[column_index];
+            let value_ref = value_ref(datatype, data).with_context(|| InvalidRowValueSnafu {
+                row: row_index,
+                column: &request.schema[column_index].column_name,
+                expected_type: datatype,
+            })?;
+            vector
+                .try_push_value_ref(value_ref)
+                .context(CreateVectorSnafu)?;
+        }
+    }
+
+    Ok(vectors
+        .iter_mut()
+        .map(|vector| vector.to_vector())
+        .collect())
+}
+
+/// Returns the value of `data` if it's of `datatype`, and in the range of the type for the
+/// types narrower than the ones on the wire.
+fn value_ref(datatype: ColumnDataType, data: &ValueData) -> Option<ValueRef> {
+    let value_ref = match (datatype, data) {
+        (ColumnDataType::Boolean, ValueData::BoolValue(v)) => ValueRef::from(*v),
+        (ColumnDataType::Int8, ValueData::I8Value(v)) => ValueRef::from(i8::try_from(*v).ok()?),
+        (ColumnDataType::Int16, ValueData::I16Value(v)) => ValueRef::from(i16::try_from(*v).ok()?),
+        (ColumnDataType::Int32, ValueData::I32Value(v)) => ValueRef::from(*v),
+        (ColumnDataType::Int64, ValueData::I64Value(v)) => ValueRef::from(*v),
+        (ColumnDataType::Uint8, ValueData::U8Value(v)) => ValueRef::from(u8::try_from(*v).ok()?),
+        (ColumnDataType::Uint16, ValueData::U16Value(v)) => ValueRef::from(u16::try_from(*v).ok()?),
+        (ColumnDataType::Uint32, ValueData::U32Value(v)) => ValueRef::from(*v),
+        (ColumnDataType::Uint64, ValueData::U64Value(v)) => ValueRef::from(*v),
+        (ColumnDataType::Float32, ValueData::F32Value(v)) => ValueRef::from(*v),
+        (ColumnDataType::Float64, ValueData::F64Value(v)) => ValueRef::from(*v),
+        (ColumnDataType::Binary, ValueData::BinaryValue(v)) => ValueRef::from(v.as_slice()),
+        (ColumnDataType::String, ValueData::StringValue(v)) => ValueRef::from(v.as_str()),
+        (ColumnDataType::Date, ValueData::DateValue(v)) => ValueRef::Date(Date::new(*v)),
+        (ColumnDataType::Datetime, ValueData::DatetimeValue(v)) => {
+            ValueRef::DateTime(DateTime::new(*v))
+        }
+        (ColumnDataType::TimestampSecond, ValueData::TsSecondValue(v)) => {
+            ValueRef::Timestamp(Timestamp::new(*v, TimeUnit::Second))
+        }
+        (ColumnDataType::TimestampMillisecond, ValueData::TsMillisecondValue(v)) => {
+            ValueRef::Timestamp(Timestamp::new(*v, TimeUnit::Millisecond))
+        }
+        (ColumnDataType::TimestampMicrosecond, ValueData::TsMicrosecondValue(v)) => {
+            ValueRef::Timestamp(Timestamp::new(*v, TimeUnit::Microsecond))
+        }
+        (ColumnDataType::TimestampNanosecond, ValueData::TsNanosecondValue(v)) => {
+            ValueRef::Timestamp(Timestamp::new(*v, TimeUnit::Nanosecond))
+        }
+        _ => return None,
+    };
+    Some(value_ref)
+}
+
+/// Try to build create table request from the schema of the rows of a row insert request, the
+/// same as [build_create_expr_from_insertion] does from the columns of an insert request.
+#[allow(clippy::too_many_arguments)]
+pub fn build_create_expr_from_row_insertion(
+    catalog_name: &str,
+    schema_name: &str,
+    table_id: Option<TableId>,
+    table_name: &str,
+    row_schema: &[ColumnSchema],
+    engine: &str,
+    limits: &ColumnLimits,
+    timestamp_default: Option<&ColumnDefaultConstraint>,
+) -> Result<CreateTableExpr> {
+    let columns = row_schema
+        .iter()
+        .map(|column| Column {
+            column_name: column.column_name.clone(),
+            semantic_type: column.semantic_type,
+            datatype: column.datatype,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    build_create_expr_from_insertion(
+        catalog_name,
+        schema_name,
+        table_id,
+        table_name,
+        &columns,
+        engine,
+        limits,
+        timestamp_default,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use api::v1::column::SemanticType;
+    use api::v1::row::{Row, Value};
+    use common_catalog::consts::MITO_ENGINE;
+    use common_error::prelude::{ErrorExt, StatusCode};
+    use datatypes::schema::{ColumnSchema as TableColumnSchema, SchemaBuilder};
+    use datatypes::value::Value as DtValue;
+
+    use super::*;
+    use crate::error::Error;
+    use crate::insert::to_table_insert_request;
+
+    fn column_schema(name: &str, datatype: ColumnDataType, semantic: SemanticType) -> ColumnSchema {
+        ColumnSchema {
+            column_name: name.to_string(),
+            datatype: datatype as i32,
+            semantic_type: semantic as i32,
+        }
+    }
+
+    fn row_schema() -> Vec<ColumnSchema> {
+        vec![
+            column_schema("host", ColumnDataType::String, SemanticType::Tag),
+            column_schema("cpu", ColumnDataType::Float64, SemanticType::Field),
+            column_schema("memory", ColumnDataType::Float64, SemanticType::Field),
+            column_schema(
+                "ts",
+                ColumnDataType::TimestampMillisecond,
+                SemanticType::Timestamp,
+            ),
+        ]
+    }
+
+    fn table_schema() -> SchemaRef {
+        let column_schemas = vec![
+            TableColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            TableColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            TableColumnSchema::new("memory", ConcreteDataType::float64_datatype(), true),
+            TableColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ];
+        Arc::new(
+            SchemaBuilder::try_from(column_schemas)
+                .unwrap()
+                .build()
+                .unwrap(),
+        )
+    }
+
+    fn value(data: Option<ValueData>) -> Value {
+        Value { value_data: data }
+    }
+
+    fn cpu_row(host: &str, cpu: Option<f64>, memory: Option<f64>, ts: i64) -> Row {
+        Row {
+            values: vec![
+                value(Some(ValueData::StringValue(host.to_string()))),
+                value(cpu.map(ValueData::F64Value)),
+                value(memory.map(ValueData::F64Value)),
+                value(Some(ValueData::TsMillisecondValue(ts))),
+            ],
+        }
+    }
+
+    fn to_request(request: RowInsertRequest) -> Result<InsertRequest> {
+        rows_to_table_insert_request(
+            "greptime",
+            "public",
+            request,
+            &table_schema(),
+            WriteMode::default(),
+            ValidationMode::default(),
+        )
+    }
+
+    #[test]
+    fn test_rows_to_table_insert_request() {
+        let request = RowInsertRequest {
+            table_name: "demo".to_string(),
+            schema: row_schema(),
+            rows: vec![
+                cpu_row("host1", Some(0.31), None, 100),
+                cpu_row("host2", None, Some(0.1), 101),
+            ],
+            region_number: 1,
+        };
+        let insert_req = to_request(request).unwrap();
+
+        assert_eq!("greptime", insert_req.catalog_name);
+        assert_eq!("public", insert_req.schema_name);
+        assert_eq!("demo", insert_req.table_name);
+        assert_eq!(1, insert_req.region_number);
+        assert_eq!(4, insert_req.columns_values.len());
+
+        let host = insert_req.columns_values.get("host").unwrap();
+        assert_eq!(DtValue::String("host1".into()), host.get(0));
+        assert_eq!(DtValue::String("host2".into()), host.get(1));
+
+        let cpu = insert_req.columns_values.get("cpu").unwrap();
+        assert_eq!(DtValue::Float64(0.31.into()), cpu.get(0));
+        assert_eq!(DtValue::Null, cpu.get(1));
+
+        let memory = insert_req.columns_values.get("memory").unwrap();
+        assert_eq!(DtValue::Null, memory.get(0));
+        assert_eq!(DtValue::Float64(0.1.into()), memory.get(1));
+
+        let ts = insert_req.columns_values.get("ts").unwrap();
+        assert_eq!(
+            DtValue::Timestamp(Timestamp::new_millisecond(100)),
+            ts.get(0)
+        );
+        assert_eq!(
+            DtValue::Timestamp(Timestamp::new_millisecond(101)),
+            ts.get(1)
+        );
+    }
+
+    #[test]
+    fn test_many_rows() {
+        let row_count = 5000;
+        let request = RowInsertRequest {
+            table_name: "demo".to_string(),
+            schema: row_schema(),
+            rows: (0..row_count)
+                .map(|i| {
+                    let cpu = (i % 3 != 0).then_some(i as f64);
+                    cpu_row(&format!("host{}", i % 10), cpu, Some(0.5), i as i64)
+                })
+                .collect(),
+            region_number: 0,
+        };
+        let insert_req = to_request(request).unwrap();
+
+        let cpu = insert_req.columns_values.get("cpu").unwrap();
+        assert_eq!(row_count, cpu.len());
+        assert_eq!((row_count + 2) / 3, cpu.null_count());
+        assert_eq!(DtValue::Float64(4999.0.into()), cpu.get(4999));
+        let host = insert_req.columns_values.get("host").unwrap();
+        assert_eq!(DtValue::String("host7".into()), host.get(4997));
+        let ts = insert_req.columns_values.get("ts").unwrap();
+        assert_eq!(
+            DtValue::Timestamp(Timestamp::new_millisecond(4999)),
+            ts.get(4999)
+        );
+    }
+
+    #[test]
+    fn test_invalid_rows() {
+        let new_request = |rows| RowInsertRequest {
+            table_name: "demo".to_string(),
+            schema: row_schema(),
+            rows,
+            region_number: 0,
+        };
+
+        let mut short_row = cpu_row("host1", None, None, 100);
+        let _ = short_row.values.pop();
+        let rows = vec![cpu_row("host1", None, None, 100), short_row];
+        let err = to_request(new_request(rows)).unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        assert!(
+            matches!(
+                err,
+                Error::RowWidthMismatch {
+                    row: 1,
+                    expected: 4,
+                    actual: 3,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        let mut row = cpu_row("host1", None, None, 100);
+        row.values[1] = value(Some(ValueData::StringValue("0.1".to_string())));
+        let err = to_request(new_request(vec![row])).unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        assert_eq!(
+            "Value of column cpu in row 0 is expected to be Float64",
+            err.to_string()
+        );
+
+        // Nulls in the not null columns.
+        let mut row = cpu_row("host1", None, None, 100);
+        row.values[0] = value(None);
+        let err = to_request(new_request(vec![row])).unwrap_err();
+        assert!(matches!(err, Error::InvalidRows { .. }), "{err}");
+
+        let mut schema = row_schema();
+        schema[1].column_name = "host".to_string();
+        let mut request = new_request(vec![cpu_row("host1", None, None, 100)]);
+        request.schema = schema;
+        assert!(matches!(
+            to_request(request).unwrap_err(),
+            Error::IllegalInsertData { .. }
+        ));
+    }
+
+    #[test]
+    fn test_rows_to_insert_request() {
+        let request = RowInsertRequest {
+            table_name: "demo".to_string(),
+            schema: row_schema(),
+            rows: vec![
+                cpu_row("host1", Some(0.31), None, 100),
+                cpu_row("host2", None, Some(0.1), 101),
+                cpu_row("host3", Some(0.5), Some(0.2), 102),
+            ],
+            region_number: 1,
+        };
+        let grpc_request = rows_to_insert_request(request.clone()).unwrap();
+        assert_eq!("demo", grpc_request.table_name);
+        assert_eq!(3, grpc_request.row_count);
+        assert_eq!(1, grpc_request.region_number);
+        let cpu = &grpc_request.columns[1];
+        assert_eq!("cpu", cpu.column_name);
+        assert_eq!(SemanticType::Field as i32, cpu.semantic_type);
+        assert_eq!(vec![0.31, 0.5], cpu.values.as_ref().unwrap().f64_values);
+        assert_eq!(vec![0b010], cpu.null_mask);
+
+        // The columnar request converts to the same insert request of the table.
+        let from_columns = to_table_insert_request(
+            "greptime",
+            "public",
+            grpc_request,
+            &table_schema(),
+            WriteMode::default(),
+            ValidationMode::default(),
+        )
+        .unwrap();
+        let from_rows = to_request(request).unwrap();
+        assert_eq!(from_rows.columns_values, from_columns.columns_values);
+    }
+
+    #[test]
+    fn test_out_of_range_values() {
+        let cases = [
+            (ColumnDataType::Int8, ValueData::I8Value(128)),
+            (ColumnDataType::Int16, ValueData::I16Value(-32769)),
+            (ColumnDataType::Uint8, ValueData::U8Value(256)),
+            (ColumnDataType::Uint16, ValueData::U16Value(65536)),
+        ];
+        for (datatype, data) in cases {
+            let request = RowInsertRequest {
+                table_name: "demo".to_string(),
+                schema: vec![column_schema("v", datatype, SemanticType::Field)],
+                rows: vec![Row {
+                    values: vec![value(Some(data))],
+                }],
+                region_number: 0,
+            };
+            let err = rows_to_insert_request(request).unwrap_err();
+            assert!(matches!(err, Error::InvalidRowValue { .. }), "{err}");
+        }
+
+        assert_eq!(
+            Some(ValueRef::from(i8::MIN)),
+            value_ref(ColumnDataType::Int8, &ValueData::I8Value(-128))
+        );
+        assert_eq!(
+            Some(ValueRef::from(u16::MAX)),
+            value_ref(ColumnDataType::Uint16, &ValueData::U16Value(65535))
+        );
+    }
+
+    #[test]
+    fn test_build_create_expr_from_row_insertion() {
+        let expr = build_create_expr_from_row_insertion(
+            "greptime",
+            "public",
+            Some(10),
+            "demo",
+            &row_schema(),
+            MITO_ENGINE,
+            &ColumnLimits::default(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!("demo", expr.table_name);
+        assert_eq!("ts", expr.time_index);
+        assert_eq!(vec!["host".to_string()], expr.primary_keys);
+        let columns = expr
+            .column_defs
+            .iter()
+            .map(|c| (c.name.as_str(), c.datatype, c.is_nullable))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("host", ColumnDataType::String as i32, true),
+                ("cpu", ColumnDataType::Float64 as i32, true),
+                ("memory", ColumnDataType::Float64 as i32, true),
+                ("ts", ColumnDataType::TimestampMillisecond as i32, false),
+            ],
+            columns
+        );
+
+        let mut schema = row_schema();
+        let _ = schema.pop();
+        assert!(build_create_expr_from_row_insertion(
+            "greptime",
+            "public",
+            None,
+            "demo",
+            &schema,
+            MITO_ENGINE,
+            &ColumnLimits::default(),
+            None,
+        )
+        .is_err());
+    }
+}
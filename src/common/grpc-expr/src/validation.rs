@@ -0,0 +1,279 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors of the rows of insertions, shared by the conversions of all ingestion protocols so
+//! clients are told which values of a batch are rejected.
+
+use std::fmt;
+
+use common_base::validation_mode::ValidationMode;
+use datatypes::vectors::Vector;
+use serde::{Deserialize, Serialize};
+
+/// Reason of the `google.rpc.ErrorInfo` in the details of the gRPC status of an insertion
+/// rejected for its invalid rows.
+pub const INVALID_ROWS_REASON: &str = "INVALID_ROWS";
+/// Domain of the `google.rpc.ErrorInfo` of the errors of GreptimeDB.
+pub const ERROR_INFO_DOMAIN: &str = "greptime.io";
+/// Key of the [RowErrors] in JSON in the metadata of the `google.rpc.ErrorInfo` of
+/// [INVALID_ROWS_REASON].
+pub const ROW_ERRORS_KEY: &str = "row_errors";
+
+/// Max number of row errors collected in [ValidationMode::Lenient].
+pub const MAX_ROW_ERRORS: usize = 100;
+
+/// Max number of chars of a value in a [RowError], the rest of the value is truncated.
+pub const MAX_VALUE_CHARS: usize = 64;
+
+/// Error of a value in a row of an insertion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowError {
+    /// Index of the row in the request.
+    pub row: usize,
+    pub column: String,
+    /// The offending value rendered by [render_value], `None` if it's null.
+    #[serde(default)]
+    pub value: Option<String>,
+    pub reason: String,
+}
+
+impl RowError {
+    /// Creates the error of `value` in the `column` of `row`, the value is rendered by
+    /// [render_value].
+    pub fn new(
+        row: usize,
+        column: impl Into<String>,
+        value: Option<&str>,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self {
+            row,
+            column: column.into(),
+            value: value.map(render_value),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}, column {}, value ", self.row, self.column)?;
+        match &self.value {
+            Some(value) => write!(f, "\"{value}\"")?,
+            None => write!(f, "NULL")?,
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// Renders `value` to be safely put in messages: control chars are escaped and the value is
+/// truncated to [MAX_VALUE_CHARS] chars, followed by `...` if it's longer.
+pub fn render_value(value: &str) -> String {
+    let mut rendered = String::with_capacity(value.len().min(MAX_VALUE_CHARS + 3));
+    for (i, c) in value.chars().enumerate() {
+        if i == MAX_VALUE_CHARS {
+            rendered.push_str("...");
+            break;
+        }
+        if c.is_control() {
+            rendered.extend(c.escape_default());
+        } else {
+            rendered.push(c);
+        }
+    }
+    rendered
+}
+
+/// Returns the value of `row` in `vector` to be put in a [RowError], `None` if it's null.
+pub fn vector_value(vector: &dyn Vector, row: usize) -> Option<String> {
+    let value = vector.get(row);
+    (!value.is_null()).then(|| value.to_string())
+}
+
+/// Errors of the rows of an insertion, which is rejected as a whole if any of its rows is
+/// invalid.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowErrors {
+    /// Errors ordered by the row index.
+    pub errors: Vec<RowError>,
+    /// Whether the errors reach the cap of [ValidationMode::Lenient], so the rows after the
+    /// last error are not validated. The validation in [ValidationMode::Strict] always stops at
+    /// the first error.
+    pub incomplete: bool,
+}
+
+impl fmt::Display for RowErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid rows in insertion: ")?;
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{error}")?;
+        }
+        if self.incomplete {
+            write!(f, "; the rest of the rows are not validated")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RowErrors {}
+
+/// Returns the row errors in the chain of `err`, if any.
+///
+/// The row errors are always the source of the errors wrapping them, so they are found even
+/// if the error is boxed by the layers between the conversion and the server.
+pub fn find_row_errors<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a RowErrors> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(row_errors) = err.downcast_ref::<RowErrors>() {
+            return Some(row_errors);
+        }
+        current = err.source();
+    }
+    None
+}
+
+/// Collects the errors of the rows of an insertion, up to the cap of the [ValidationMode].
+#[derive(Debug)]
+pub struct RowErrorCollector {
+    mode: ValidationMode,
+    max_errors: usize,
+    errors: Vec<RowError>,
+}
+
+impl RowErrorCollector {
+    pub fn new(mode: ValidationMode) -> Self {
+        let max_errors = match mode {
+            ValidationMode::Strict => 1,
+            ValidationMode::Lenient => MAX_ROW_ERRORS,
+        };
+        Self {
+            mode,
+            max_errors,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Adds the error of a row, unless the collector is full.
+    pub fn push(&mut self, error: RowError) {
+        if !self.is_full() {
+            self.errors.push(error);
+        }
+    }
+
+    /// Returns true if the collector reaches the cap, so the validation should stop.
+    pub fn is_full(&self) -> bool {
+        self.errors.len() >= self.max_errors
+    }
+
+    /// Returns the collected errors ordered by the row index, if any.
+    pub fn finish(mut self) -> Result<(), RowErrors> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        let incomplete = self.mode == ValidationMode::Lenient && self.is_full();
+        self.errors.sort_by_key(|error| error.row);
+        Err(RowErrors {
+            errors: self.errors,
+            incomplete,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_value() {
+        assert_eq!("host1", render_value("host1"));
+        assert_eq!("a\\nb\\u{0}", render_value("a\nb\0"));
+        assert_eq!("中文", render_value("中文"));
+
+        let long = "x".repeat(MAX_VALUE_CHARS + 10);
+        let rendered = render_value(&long);
+        assert_eq!(format!("{}...", "x".repeat(MAX_VALUE_CHARS)), rendered);
+        let exact = "中".repeat(MAX_VALUE_CHARS);
+        assert_eq!(exact, render_value(&exact));
+    }
+
+    #[test]
+    fn test_collect_row_errors() {
+        let errors = |mode, rows: &[usize]| {
+            let mut collector = RowErrorCollector::new(mode);
+            for row in rows {
+                if collector.is_full() {
+                    break;
+                }
+                collector.push(RowError::new(*row, "cpu", None, "null value"));
+            }
+            collector.finish()
+        };
+
+        assert_eq!(Ok(()), errors(ValidationMode::Strict, &[]));
+        let strict = errors(ValidationMode::Strict, &[1, 4, 7]).unwrap_err();
+        assert_eq!(vec![1], rows_of(&strict));
+        assert!(!strict.incomplete);
+
+        let lenient = errors(ValidationMode::Lenient, &[1, 4, 7]).unwrap_err();
+        assert_eq!(vec![1, 4, 7], rows_of(&lenient));
+        assert!(!lenient.incomplete);
+        assert_eq!(
+            "Invalid rows in insertion: row 1, column cpu, value NULL: null value; \
+             row 4, column cpu, value NULL: null value; row 7, column cpu, value NULL: null value",
+            lenient.to_string()
+        );
+
+        let rows = (0..MAX_ROW_ERRORS * 2).collect::<Vec<_>>();
+        let capped = errors(ValidationMode::Lenient, &rows).unwrap_err();
+        assert_eq!(MAX_ROW_ERRORS, capped.errors.len());
+        assert!(capped.incomplete);
+    }
+
+    #[test]
+    fn test_find_row_errors() {
+        #[derive(Debug)]
+        struct Wrapper(RowErrors);
+
+        impl fmt::Display for Wrapper {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "wrapper, source: {}", self.0)
+            }
+        }
+
+        impl std::error::Error for Wrapper {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let row_errors = RowErrors {
+            errors: vec![RowError::new(3, "host", Some("a\tb"), "too long")],
+            incomplete: false,
+        };
+        let err = Wrapper(row_errors.clone());
+        assert_eq!(Some(&row_errors), find_row_errors(&err));
+        assert_eq!(
+            "row 3, column host, value \"a\\tb\": too long",
+            row_errors.errors[0].to_string()
+        );
+        assert_eq!(None, find_row_errors(&std::fmt::Error));
+    }
+
+    fn rows_of(errors: &RowErrors) -> Vec<usize> {
+        errors.errors.iter().map(|error| error.row).collect()
+    }
+}
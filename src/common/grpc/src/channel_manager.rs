@@ -12,21 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
+use common_time::util::{Clock, ClockRef};
 use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
+use metrics::{counter, decrement_gauge, increment_counter, increment_gauge};
 use snafu::{OptionExt, ResultExt};
+use tonic::body::BoxBody;
+use tonic::codegen::http::Request;
 use tonic::transport::{
     Certificate, Channel as InnerChannel, ClientTlsConfig, Endpoint, Identity, Uri,
 };
 use tower::make::MakeConnection;
+use tower::Service;
 
 use crate::error::{CreateChannelSnafu, InvalidConfigFilePathSnafu, InvalidTlsConfigSnafu, Result};
+use crate::metrics::{
+    EVICTION_REASON_LABEL, METRIC_GRPC_CHANNELS, METRIC_GRPC_CHANNEL_CREATIONS,
+    METRIC_GRPC_CHANNEL_EVICTIONS,
+};
 
 const RECYCLE_CHANNEL_INTERVAL_SECS: u64 = 60;
+const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 60;
 
 #[derive(Clone, Debug)]
 pub struct ChannelManager {
@@ -48,11 +59,13 @@ impl ChannelManager {
 
     pub fn with_config(config: ChannelConfig) -> Self {
         let pool = Arc::new(Pool::default());
-        let cloned_pool = pool.clone();
 
-        common_runtime::spawn_bg(async {
-            recycle_channel_in_loop(cloned_pool, RECYCLE_CHANNEL_INTERVAL_SECS).await;
-        });
+        if let Some(idle_timeout) = config.idle_timeout.filter(|timeout| !timeout.is_zero()) {
+            let cloned_pool = pool.clone();
+            common_runtime::spawn_bg(async move {
+                recycle_channel_in_loop(cloned_pool, idle_timeout).await;
+            });
+        }
 
         Self {
             config,
@@ -99,24 +112,42 @@ impl ChannelManager {
         }
 
         // It will acquire the write lock.
-        let entry = match self.pool.entry(addr.to_string()) {
-            Entry::Occupied(entry) => {
-                entry.get().increase_access();
-                entry.into_ref()
+        let inner_channel = match self.pool.entry(addr.to_string()) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().needs_recreation() {
+                    // The users still holding the failed channel keep seeing the failure.
+                    let _ = entry.insert(self.new_channel(addr)?);
+                    increment_counter!(
+                        METRIC_GRPC_CHANNEL_EVICTIONS,
+                        EVICTION_REASON_LABEL => "failed"
+                    );
+                    increment_counter!(METRIC_GRPC_CHANNEL_CREATIONS);
+                }
+                let channel = entry.get();
+                channel.touch(self.pool.clock.now_millis());
+                channel.channel.clone()
             }
             Entry::Vacant(entry) => {
-                let endpoint = self.build_endpoint(addr)?;
-                let inner_channel = endpoint.connect_lazy();
-
-                let channel = Channel {
-                    channel: inner_channel,
-                    access: AtomicUsize::new(1),
-                    use_default_connector: true,
-                };
-                entry.insert(channel)
+                let channel = self.new_channel(addr)?;
+                increment_gauge!(METRIC_GRPC_CHANNELS, 1.0);
+                increment_counter!(METRIC_GRPC_CHANNEL_CREATIONS);
+                entry.insert(channel).channel.clone()
             }
         };
-        Ok(entry.channel.clone())
+
+        if let Some(max_channels) = self.config.max_channels {
+            self.pool.evict_lru(max_channels, addr);
+        }
+        Ok(inner_channel)
+    }
+
+    /// Closes the channel to `addr`, e.g. once the peer is removed from the cluster. Returns
+    /// false if there is no channel to `addr`.
+    ///
+    /// The users still holding the channel can finish their requests, the connection is
+    /// closed once all of them drop the channel.
+    pub fn close(&self, addr: impl AsRef<str>) -> bool {
+        self.pool.remove(addr.as_ref(), "closed")
     }
 
     pub fn reset_with_connector<C>(
@@ -133,11 +164,7 @@ impl ChannelManager {
         let addr = addr.as_ref();
         let endpoint = self.build_endpoint(addr)?;
         let inner_channel = endpoint.connect_with_connector_lazy(connector);
-        let channel = Channel {
-            channel: inner_channel.clone(),
-            access: AtomicUsize::new(1),
-            use_default_connector: false,
-        };
+        let channel = Channel::new(inner_channel.clone(), false, self.pool.clock.now_millis());
         self.pool.put(addr, channel);
 
         Ok(inner_channel)
@@ -150,6 +177,15 @@ impl ChannelManager {
         self.pool.retain_channel(f);
     }
 
+    fn new_channel(&self, addr: &str) -> Result<Channel> {
+        let endpoint = self.build_endpoint(addr)?;
+        Ok(Channel::new(
+            endpoint.connect_lazy(),
+            true,
+            self.pool.clock.now_millis(),
+        ))
+    }
+
     fn build_endpoint(&self, addr: &str) -> Result<Endpoint> {
         let mut endpoint = Endpoint::new(format!("http://{addr}")).context(CreateChannelSnafu)?;
 
@@ -219,6 +255,8 @@ pub struct ChannelConfig {
     pub tcp_keepalive: Option<Duration>,
     pub tcp_nodelay: bool,
     pub client_tls: Option<ClientTlsOption>,
+    pub idle_timeout: Option<Duration>,
+    pub max_channels: Option<usize>,
 }
 
 impl Default for ChannelConfig {
@@ -237,6 +275,8 @@ impl Default for ChannelConfig {
             tcp_keepalive: None,
             tcp_nodelay: true,
             client_tls: None,
+            idle_timeout: Some(Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS)),
+            max_channels: None,
         }
     }
 }
@@ -363,16 +403,48 @@ impl ChannelConfig {
             ..self
         }
     }
+
+    /// Set how long a channel can stay unused before it's closed, None to keep it forever.
+    ///
+    /// Default is 60 seconds.
+    pub fn idle_timeout(self, timeout: Option<Duration>) -> Self {
+        Self {
+            idle_timeout: timeout,
+            ..self
+        }
+    }
+
+    /// Set the max number of the channels, the least recently used one is closed if a new
+    /// channel exceeds it.
+    ///
+    /// Unbounded by default.
+    pub fn max_channels(self, max_channels: usize) -> Self {
+        Self {
+            max_channels: Some(max_channels),
+            ..self
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Channel {
     channel: InnerChannel,
     access: AtomicUsize,
+    /// Time of the last access, in milliseconds of the [Clock] of the pool.
+    last_access_millis: AtomicI64,
     use_default_connector: bool,
 }
 
 impl Channel {
+    fn new(channel: InnerChannel, use_default_connector: bool, now_millis: i64) -> Self {
+        Self {
+            channel,
+            access: AtomicUsize::new(1),
+            last_access_millis: AtomicI64::new(now_millis),
+            use_default_connector,
+        }
+    }
+
     #[inline]
     pub fn access(&self) -> usize {
         self.access.load(Ordering::Relaxed)
@@ -387,20 +459,79 @@ impl Channel {
     pub fn increase_access(&self) {
         self.access.fetch_add(1, Ordering::Relaxed);
     }
+
+    #[inline]
+    fn touch(&self, now_millis: i64) {
+        self.increase_access();
+        self.last_access_millis.store(now_millis, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn last_access_millis(&self) -> i64 {
+        self.last_access_millis.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the channel has failed and can't serve any request anymore, so it
+    /// should be recreated. Channels with a custom connector are left to their creators.
+    fn needs_recreation(&self) -> bool {
+        self.use_default_connector && is_failed(&self.channel)
+    }
+}
+
+/// Returns true if the background worker of the `channel` has failed, after which all the
+/// requests to the channel fail.
+fn is_failed(channel: &InnerChannel) -> bool {
+    // Polls a clone so no buffer slot is held after returning.
+    let mut channel = channel.clone();
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    matches!(
+        Service::<Request<BoxBody>>::poll_ready(&mut channel, &mut cx),
+        Poll::Ready(Err(_))
+    )
+}
+
+/// Monotonic clock counting from its creation, the source of the current time of the pool
+/// unless replaced in tests.
+#[derive(Debug)]
+struct MonotonicClock {
+    start: Instant,
+}
+
+impl Clock for MonotonicClock {
+    fn now_millis(&self) -> i64 {
+        self.start.elapsed().as_millis() as i64
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct Pool {
     channels: DashMap<String, Channel>,
+    clock: ClockRef,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self::with_clock(Arc::new(MonotonicClock {
+            start: Instant::now(),
+        }))
+    }
 }
 
 impl Pool {
+    fn with_clock(clock: ClockRef) -> Self {
+        Self {
+            channels: DashMap::default(),
+            clock,
+        }
+    }
+
     fn get(&self, addr: &str) -> Option<InnerChannel> {
-        let channel = self.channels.get(addr);
-        channel.map(|ch| {
-            ch.increase_access();
-            ch.channel.clone()
-        })
+        let channel = self.channels.get(addr)?;
+        if channel.needs_recreation() {
+            return None;
+        }
+        channel.touch(self.clock.now_millis());
+        Some(channel.channel.clone())
     }
 
     fn entry(&self, addr: String) -> Entry<String, Channel> {
@@ -414,23 +545,78 @@ impl Pool {
     }
 
     fn put(&self, addr: &str, channel: Channel) {
-        self.channels.insert(addr.to_string(), channel);
+        if self.channels.insert(addr.to_string(), channel).is_none() {
+            increment_gauge!(METRIC_GRPC_CHANNELS, 1.0);
+        }
+        increment_counter!(METRIC_GRPC_CHANNEL_CREATIONS);
+    }
+
+    fn remove(&self, addr: &str, reason: &'static str) -> bool {
+        let removed = self.channels.remove(addr).is_some();
+        if removed {
+            decrement_gauge!(METRIC_GRPC_CHANNELS, 1.0);
+            increment_counter!(METRIC_GRPC_CHANNEL_EVICTIONS, EVICTION_REASON_LABEL => reason);
+        }
+        removed
     }
 
     fn retain_channel<F>(&self, f: F)
     where
         F: FnMut(&String, &mut Channel) -> bool,
     {
+        self.retain_with_reason(f, "retain");
+    }
+
+    fn retain_with_reason<F>(&self, f: F, reason: &'static str)
+    where
+        F: FnMut(&String, &mut Channel) -> bool,
+    {
+        let before = self.channels.len();
         self.channels.retain(f);
+        let evicted = before.saturating_sub(self.channels.len());
+        if evicted > 0 {
+            decrement_gauge!(METRIC_GRPC_CHANNELS, evicted as f64);
+            counter!(
+                METRIC_GRPC_CHANNEL_EVICTIONS,
+                evicted as u64,
+                EVICTION_REASON_LABEL => reason
+            );
+        }
+    }
+
+    /// Evicts the channels unused for longer than `idle_timeout`.
+    fn evict_idle(&self, idle_timeout: Duration) {
+        let now = self.clock.now_millis();
+        let idle_timeout = idle_timeout.as_millis() as i64;
+        self.retain_with_reason(
+            |_, channel| now - channel.last_access_millis() <= idle_timeout,
+            "idle",
+        );
+    }
+
+    /// Evicts the least recently used channels, except the one to `keep`, until there are no
+    /// more than `max_channels` channels.
+    fn evict_lru(&self, max_channels: usize, keep: &str) {
+        while self.channels.len() > max_channels {
+            let lru = self
+                .channels
+                .iter()
+                .filter(|entry| entry.key() != keep)
+                .min_by_key(|entry| entry.last_access_millis())
+                .map(|entry| entry.key().clone());
+            let Some(addr) = lru else { break };
+            let _ = self.remove(&addr, "lru");
+        }
     }
 }
 
-async fn recycle_channel_in_loop(pool: Arc<Pool>, interval_secs: u64) {
-    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+async fn recycle_channel_in_loop(pool: Arc<Pool>, idle_timeout: Duration) {
+    let interval = idle_timeout.min(Duration::from_secs(RECYCLE_CHANNEL_INTERVAL_SECS));
+    let mut interval = tokio::time::interval(interval);
 
     loop {
         interval.tick().await;
-        pool.retain_channel(|_, c| c.access.swap(0, Ordering::Relaxed) != 0)
+        pool.evict_idle(idle_timeout);
     }
 }
 
@@ -440,6 +626,46 @@ mod tests {
 
     use super::*;
 
+    #[derive(Debug, Default)]
+    struct MockClock {
+        now_millis: AtomicI64,
+    }
+
+    impl MockClock {
+        fn advance(&self, duration: Duration) {
+            let _ = self
+                .now_millis
+                .fetch_add(duration.as_millis() as i64, Ordering::Relaxed);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now_millis(&self) -> i64 {
+            self.now_millis.load(Ordering::Relaxed)
+        }
+    }
+
+    fn new_manager_with_clock(config: ChannelConfig) -> (ChannelManager, Arc<MockClock>) {
+        let clock = Arc::new(MockClock::default());
+        let mgr = ChannelManager {
+            pool: Arc::new(Pool::with_clock(clock.clone())),
+            config,
+            client_tls_config: None,
+        };
+        (mgr, clock)
+    }
+
+    fn pooled_addrs(mgr: &ChannelManager) -> Vec<String> {
+        let mut addrs = mgr
+            .pool
+            .channels
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<_>>();
+        addrs.sort();
+        addrs
+    }
+
     #[should_panic]
     #[test]
     fn test_invalid_addr() {
@@ -504,6 +730,8 @@ mod tests {
                 tcp_keepalive: None,
                 tcp_nodelay: true,
                 client_tls: None,
+                idle_timeout: Some(Duration::from_secs(60)),
+                max_channels: None,
             },
             default_cfg
         );
@@ -525,7 +753,9 @@ mod tests {
                 server_ca_cert_path: "some_server_path".to_string(),
                 client_cert_path: "some_cert_path".to_string(),
                 client_key_path: "some_key_path".to_string(),
-            });
+            })
+            .idle_timeout(None)
+            .max_channels(16);
 
         assert_eq!(
             ChannelConfig {
@@ -546,6 +776,8 @@ mod tests {
                     client_cert_path: "some_cert_path".to_string(),
                     client_key_path: "some_key_path".to_string(),
                 }),
+                idle_timeout: None,
+                max_channels: Some(16),
             },
             cfg
         );
@@ -580,11 +812,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_channel_with_connector() {
-        let pool = Pool {
-            channels: DashMap::default(),
-        };
-
-        let pool = Arc::new(pool);
+        let pool = Arc::new(Pool::default());
 
         let config = ChannelConfig::new();
         let mgr = ChannelManager {
@@ -621,4 +849,61 @@ mod tests {
             true
         });
     }
+
+    #[tokio::test]
+    async fn test_evict_idle_channels() {
+        let (mgr, clock) = new_manager_with_clock(ChannelConfig::new());
+        let _ = mgr.get("addr_a").unwrap();
+        clock.advance(Duration::from_secs(30));
+        let _ = mgr.get("addr_b").unwrap();
+        clock.advance(Duration::from_secs(40));
+
+        mgr.pool.evict_idle(Duration::from_secs(60));
+        assert_eq!(vec!["addr_b"], pooled_addrs(&mgr));
+
+        // Accessing the channel keeps it alive.
+        let _ = mgr.get("addr_b").unwrap();
+        clock.advance(Duration::from_secs(50));
+        mgr.pool.evict_idle(Duration::from_secs(60));
+        assert_eq!(vec!["addr_b"], pooled_addrs(&mgr));
+
+        clock.advance(Duration::from_secs(20));
+        mgr.pool.evict_idle(Duration::from_secs(60));
+        assert!(pooled_addrs(&mgr).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_removed_peer() {
+        let (mgr, _clock) = new_manager_with_clock(ChannelConfig::new());
+        let _ = mgr.get("addr_a").unwrap();
+        let _ = mgr.get("addr_b").unwrap();
+
+        assert!(mgr.close("addr_a"));
+        assert!(!mgr.close("addr_a"));
+        assert_eq!(vec!["addr_b"], pooled_addrs(&mgr));
+
+        // The channel is recreated if the peer comes back.
+        let _ = mgr.get("addr_a").unwrap();
+        assert_eq!(vec!["addr_a", "addr_b"], pooled_addrs(&mgr));
+    }
+
+    #[tokio::test]
+    async fn test_evict_lru_channels() {
+        let (mgr, clock) = new_manager_with_clock(ChannelConfig::new().max_channels(2));
+        let _ = mgr.get("addr_a").unwrap();
+        clock.advance(Duration::from_secs(1));
+        let in_flight = mgr.get("addr_b").unwrap();
+        clock.advance(Duration::from_secs(1));
+        let _ = mgr.get("addr_a").unwrap();
+        clock.advance(Duration::from_secs(1));
+
+        let _ = mgr.get("addr_c").unwrap();
+        assert_eq!(vec!["addr_a", "addr_c"], pooled_addrs(&mgr));
+        // The evicted channel still works for the users holding it.
+        assert!(!is_failed(&in_flight));
+
+        clock.advance(Duration::from_secs(1));
+        let _ = mgr.get("addr_b").unwrap();
+        assert_eq!(vec!["addr_b", "addr_c"], pooled_addrs(&mgr));
+    }
 }
@@ -33,6 +33,22 @@ use crate::error::{
     Result,
 };
 
+/// Type of the flight action creating the tables of the `CREATE TABLE` statements all or
+/// nothing. The body of the action is an encoded `GreptimeRequest` of the SQL query, and each
+/// result of the action is the JSON encoded creation status of a table.
+pub const CREATE_TABLES_ACTION: &str = "create_tables";
+
+/// Type of the flight action inserting rows. The body of the action is encoded
+/// `RowInsertRequests`, and the only result of the action is the number of rows inserted, in
+/// JSON. The rows are inserted the same way as the inserts of a `GreptimeRequest` are.
+pub const ROW_INSERTS_ACTION: &str = "row_inserts";
+
+/// Type of the flight action operating a region of a table on its datanode, for changing the
+/// partitions of the table. The body of the action is a JSON encoded `RegionRequest`, and the
+/// only result of the action is the JSON encoded watermark of the region, `null` if the request
+/// doesn't return one.
+pub const REGION_ACTION: &str = "region";
+
 #[derive(Debug, Clone)]
 pub enum FlightMessage {
     Schema(SchemaRef),
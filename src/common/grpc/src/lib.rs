@@ -15,6 +15,7 @@
 pub mod channel_manager;
 pub mod error;
 pub mod flight;
+mod metrics;
 pub mod select;
 pub mod writer;
 
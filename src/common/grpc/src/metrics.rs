@@ -0,0 +1,19 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Channel manager metrics
+pub(crate) const METRIC_GRPC_CHANNELS: &str = "grpc.channel_manager.channels";
+pub(crate) const METRIC_GRPC_CHANNEL_CREATIONS: &str = "grpc.channel_manager.creations";
+pub(crate) const METRIC_GRPC_CHANNEL_EVICTIONS: &str = "grpc.channel_manager.evictions";
+pub(crate) const EVICTION_REASON_LABEL: &str = "reason";
@@ -173,6 +173,13 @@ impl LinesWriter {
         Ok(())
     }
 
+    /// Returns the type of the column written by the previous lines, `None` if there's no such
+    /// column.
+    pub fn column_datatype(&self, column_name: &str) -> Option<ColumnDataType> {
+        let idx = self.column_name_index.get(column_name)?;
+        ColumnDataType::from_i32(self.batch.0[*idx].datatype)
+    }
+
     pub fn commit(&mut self) {
         let batch = &mut self.batch;
         batch.1 += 1;
@@ -284,6 +291,8 @@ mod tests {
             .write_ts("ts", (103011002, Precision::Millisecond))
             .unwrap();
         writer.commit();
+        assert_eq!(Some(ColumnDataType::Float64), writer.column_datatype("cpu"));
+        assert_eq!(None, writer.column_datatype("disk"));
 
         let insert_batch = writer.finish();
         assert_eq!(3, insert_batch.1);
@@ -172,6 +172,21 @@ pub enum Error {
 
     #[snafu(display("Invalid function args: {}", err_msg))]
     InvalidFuncArgs { err_msg: String, location: Location },
+
+    #[snafu(display("Failed to encode accumulator state, source: {}", source))]
+    EncodeAccumulatorState {
+        source: ArrowError,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to decode accumulator state, source: {}", source))]
+    DecodeAccumulatorState {
+        source: ArrowError,
+        location: Location,
+    },
+
+    #[snafu(display("Invalid accumulator state: {}", err_msg))]
+    InvalidAccumulatorState { err_msg: String, location: Location },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -190,7 +205,10 @@ impl ErrorExt for Error {
             | Error::BadAccumulatorImpl { .. }
             | Error::ToScalarValue { .. }
             | Error::GetScalarVector { .. }
-            | Error::ArrowCompute { .. } => StatusCode::EngineExecuteQuery,
+            | Error::ArrowCompute { .. }
+            | Error::EncodeAccumulatorState { .. }
+            | Error::DecodeAccumulatorState { .. }
+            | Error::InvalidAccumulatorState { .. } => StatusCode::EngineExecuteQuery,
 
             Error::InvalidInputType { source, .. }
             | Error::IntoVector { source, .. }
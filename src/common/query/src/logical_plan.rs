@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod accumulator;
+pub mod dist_aggr;
 mod expr;
 mod udaf;
 mod udf;
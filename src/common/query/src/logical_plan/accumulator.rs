@@ -69,6 +69,17 @@ pub trait AggregateFunctionCreator: AggrFuncTypeStore {
 
     /// Get the Accumulator's state data types.
     fn state_types(&self) -> Result<Vec<ConcreteDataType>>;
+
+    /// Whether the aggregation can be split into partial aggregations next to the distributed
+    /// data and a final aggregation merging their states.
+    ///
+    /// The states of such accumulators are serialized in the schema given by `state_types`
+    /// (see [encode_state](crate::logical_plan::dist_aggr::encode_state)) to be sent to where
+    /// they are merged by `merge_batch`, so `merge_batch` must accept the states produced by
+    /// `state` of accumulators of the same input types elsewhere.
+    fn supports_distributed(&self) -> bool {
+        false
+    }
 }
 
 /// `AggrFuncTypeStore` stores the aggregate function's input data's types.
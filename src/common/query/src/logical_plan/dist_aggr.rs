@@ -0,0 +1,486 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Distributed aggregation of user-defined aggregate functions.
+//!
+//! An aggregation of a UDAF whose creator
+//! [supports distributed](AggregateFunctionCreator::supports_distributed) can be split into a
+//! partial aggregation evaluated next to each part of the data, and a final aggregation merging
+//! the partial results. The partial aggregation outputs the states of its
+//! accumulators serialized by [encode_state], which are deserialized by [decode_state] and merged
+//! by the final aggregation.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use datatypes::arrow::datatypes::{Field, Schema as ArrowSchema};
+use datatypes::arrow::ipc::reader::StreamReader;
+use datatypes::arrow::ipc::writer::StreamWriter;
+use datatypes::arrow::record_batch::RecordBatch as DfRecordBatch;
+use datatypes::prelude::*;
+use datatypes::vectors::{BinaryVector, Helper as VectorHelper};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::error::{
+    BadAccumulatorImplSnafu, DecodeAccumulatorStateSnafu, DowncastVectorSnafu,
+    EncodeAccumulatorStateSnafu, FromScalarValueSnafu, InvalidAccumulatorStateSnafu,
+    InvalidInputStateSnafu, Result, ToScalarValueSnafu,
+};
+use crate::function::AccumulatorCreatorFunction;
+use crate::logical_plan::accumulator::AggrFuncTypeStore;
+use crate::logical_plan::{
+    create_aggregate_function, Accumulator, AggregateFunction, AggregateFunctionCreator,
+    AggregateFunctionCreatorRef,
+};
+
+/// Returns the name of the partial aggregate function of the UDAF `name`.
+pub fn partial_function_name(name: &str) -> String {
+    format!("__{name}_partial")
+}
+
+/// Returns the name of the UDAF whose partial aggregate function is `name`, the reverse of
+/// [partial_function_name].
+pub fn partial_function_base_name(name: &str) -> Option<&str> {
+    name.strip_prefix("__")?.strip_suffix("_partial")
+}
+
+/// Returns the name of the merge aggregate function of the UDAF `name`.
+pub fn merge_function_name(name: &str) -> String {
+    format!("__{name}_merge")
+}
+
+/// Serializes the `state` of an accumulator, whose values are of `state_types`, to a one-row
+/// Arrow IPC stream.
+pub fn encode_state(state: Vec<Value>, state_types: &[ConcreteDataType]) -> Result<Vec<u8>> {
+    ensure!(
+        state.len() == state_types.len(),
+        BadAccumulatorImplSnafu {
+            err_msg: format!(
+                "expect {} state values, got {}",
+                state_types.len(),
+                state.len()
+            ),
+        }
+    );
+
+    let arrays = state
+        .into_iter()
+        .zip(state_types)
+        .map(|(value, data_type)| {
+            let scalar_value = value
+                .try_to_scalar_value(data_type)
+                .context(ToScalarValueSnafu)?;
+            Ok(scalar_value.to_array())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let fields = arrays
+        .iter()
+        .enumerate()
+        .map(|(i, array)| Field::new(format!("state_{i}"), array.data_type().clone(), true))
+        .collect::<Vec<_>>();
+    let batch = DfRecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), arrays)
+        .context(EncodeAccumulatorStateSnafu)?;
+
+    let mut bytes = Vec::new();
+    {
+        let mut writer = StreamWriter::try_new(&mut bytes, &batch.schema())
+            .context(EncodeAccumulatorStateSnafu)?;
+        writer.write(&batch).context(EncodeAccumulatorStateSnafu)?;
+        writer.finish().context(EncodeAccumulatorStateSnafu)?;
+    }
+    Ok(bytes)
+}
+
+/// Deserializes the state encoded by [encode_state] to vectors of one row, which can be merged
+/// by [Accumulator::merge_batch].
+pub fn decode_state(bytes: &[u8], state_types: &[ConcreteDataType]) -> Result<Vec<VectorRef>> {
+    let mut reader =
+        StreamReader::try_new(Cursor::new(bytes), None).context(DecodeAccumulatorStateSnafu)?;
+    let batch = reader
+        .next()
+        .context(InvalidAccumulatorStateSnafu {
+            err_msg: "no state in the stream",
+        })?
+        .context(DecodeAccumulatorStateSnafu)?;
+    ensure!(
+        batch.num_rows() == 1 && batch.num_columns() == state_types.len(),
+        InvalidAccumulatorStateSnafu {
+            err_msg: format!(
+                "expect 1 row of {} state values, got {} rows of {} values",
+                state_types.len(),
+                batch.num_rows(),
+                batch.num_columns()
+            ),
+        }
+    );
+
+    let vectors = VectorHelper::try_into_vectors(batch.columns()).context(FromScalarValueSnafu)?;
+    for (vector, data_type) in vectors.iter().zip(state_types) {
+        ensure!(
+            vector.data_type() == *data_type,
+            InvalidAccumulatorStateSnafu {
+                err_msg: format!(
+                    "expect state of type {:?}, got {:?}",
+                    data_type,
+                    vector.data_type()
+                ),
+            }
+        );
+    }
+    Ok(vectors)
+}
+
+/// Creates the aggregate function evaluating the partial stage of the UDAF `name`, which
+/// outputs the serialized states of the accumulators of `creator`.
+pub fn create_partial_aggregate_function(
+    name: &str,
+    args_count: u8,
+    creator: AggregateFunctionCreatorRef,
+) -> AggregateFunction {
+    create_aggregate_function(
+        partial_function_name(name),
+        args_count,
+        Arc::new(PartialAccumulatorCreator { inner: creator }),
+    )
+}
+
+/// Creates the aggregate function evaluating the final stage of the UDAF `name`, which merges
+/// the serialized states output by the partial stage.
+///
+/// The input types of `creator` must have been set to the ones of the original aggregation, as
+/// the only input of the final stage is the serialized states.
+pub fn create_merge_aggregate_function(
+    name: &str,
+    creator: AggregateFunctionCreatorRef,
+) -> AggregateFunction {
+    create_aggregate_function(
+        merge_function_name(name),
+        1,
+        Arc::new(MergeAccumulatorCreator { inner: creator }),
+    )
+}
+
+#[derive(Debug)]
+struct PartialAccumulatorCreator {
+    inner: AggregateFunctionCreatorRef,
+}
+
+impl AggrFuncTypeStore for PartialAccumulatorCreator {
+    fn input_types(&self) -> Result<Vec<ConcreteDataType>> {
+        self.inner.input_types()
+    }
+
+    fn set_input_types(&self, input_types: Vec<ConcreteDataType>) -> Result<()> {
+        self.inner.set_input_types(input_types)
+    }
+}
+
+impl AggregateFunctionCreator for PartialAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        let inner = self.inner.clone();
+        Arc::new(move |types: &[ConcreteDataType]| {
+            let accumulator = inner.creator()(types)?;
+            Ok(Box::new(PartialAccumulator {
+                inner: accumulator,
+                state_types: inner.state_types()?,
+            }))
+        })
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::binary_datatype())
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        self.inner.state_types()
+    }
+}
+
+/// Accumulates the input rows like the inner accumulator, but evaluates to its serialized state.
+#[derive(Debug)]
+struct PartialAccumulator {
+    inner: Box<dyn Accumulator>,
+    state_types: Vec<ConcreteDataType>,
+}
+
+impl Accumulator for PartialAccumulator {
+    fn state(&self) -> Result<Vec<Value>> {
+        self.inner.state()
+    }
+
+    fn update_batch(&mut self, values: &[VectorRef]) -> Result<()> {
+        self.inner.update_batch(values)
+    }
+
+    fn merge_batch(&mut self, states: &[VectorRef]) -> Result<()> {
+        self.inner.merge_batch(states)
+    }
+
+    fn evaluate(&self) -> Result<Value> {
+        let state = encode_state(self.inner.state()?, &self.state_types)?;
+        Ok(Value::from(state))
+    }
+}
+
+#[derive(Debug)]
+struct MergeAccumulatorCreator {
+    inner: AggregateFunctionCreatorRef,
+}
+
+impl AggrFuncTypeStore for MergeAccumulatorCreator {
+    fn input_types(&self) -> Result<Vec<ConcreteDataType>> {
+        Ok(vec![ConcreteDataType::binary_datatype()])
+    }
+
+    fn set_input_types(&self, input_types: Vec<ConcreteDataType>) -> Result<()> {
+        ensure!(
+            input_types == [ConcreteDataType::binary_datatype()],
+            InvalidInputStateSnafu
+        );
+        Ok(())
+    }
+}
+
+impl AggregateFunctionCreator for MergeAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        let inner = self.inner.clone();
+        Arc::new(move |_: &[ConcreteDataType]| {
+            let accumulator = inner.creator()(&inner.input_types()?)?;
+            Ok(Box::new(MergeAccumulator {
+                inner: accumulator,
+                state_types: inner.state_types()?,
+            }))
+        })
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        self.inner.output_type()
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        self.inner.state_types()
+    }
+}
+
+/// Merges the serialized states of the input rows into the inner accumulator.
+#[derive(Debug)]
+struct MergeAccumulator {
+    inner: Box<dyn Accumulator>,
+    state_types: Vec<ConcreteDataType>,
+}
+
+impl Accumulator for MergeAccumulator {
+    fn state(&self) -> Result<Vec<Value>> {
+        self.inner.state()
+    }
+
+    fn update_batch(&mut self, values: &[VectorRef]) -> Result<()> {
+        ensure!(values.len() == 1, InvalidInputStateSnafu);
+        let states = values[0]
+            .as_any()
+            .downcast_ref::<BinaryVector>()
+            .with_context(|| DowncastVectorSnafu {
+                err_msg: format!(
+                    "expect BinaryVector, got vector type {}",
+                    values[0].vector_type_name()
+                ),
+            })?;
+        for state in states.iter_data().flatten() {
+            let state = decode_state(state, &self.state_types)?;
+            self.inner.merge_batch(&state)?;
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[VectorRef]) -> Result<()> {
+        self.inner.merge_batch(states)
+    }
+
+    fn evaluate(&self) -> Result<Value> {
+        self.inner.evaluate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datatypes::value::ListValue;
+    use datatypes::vectors::{Float64Vector, Int64Vector};
+
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        let state_types = vec![
+            ConcreteDataType::list_datatype(ConcreteDataType::float64_datatype()),
+            ConcreteDataType::float64_datatype(),
+            ConcreteDataType::uint64_datatype(),
+        ];
+        let state = vec![
+            Value::List(ListValue::new(
+                Some(Box::new(vec![Value::from(1.5_f64), Value::from(-2.0_f64)])),
+                ConcreteDataType::float64_datatype(),
+            )),
+            Value::Null,
+            Value::from(42_u64),
+        ];
+
+        let bytes = encode_state(state.clone(), &state_types).unwrap();
+        let vectors = decode_state(&bytes, &state_types).unwrap();
+        let decoded = vectors.iter().map(|v| v.get(0)).collect::<Vec<_>>();
+        assert_eq!(state, decoded);
+
+        // The state doesn't match the state types.
+        assert!(encode_state(vec![Value::Null], &state_types).is_err());
+        assert!(decode_state(&bytes, &state_types[1..]).is_err());
+        assert!(decode_state(&bytes[1..], &state_types).is_err());
+    }
+
+    /// Sum of the inputs, states are the sum and whether there are any inputs.
+    #[derive(Debug, Default)]
+    struct SumAccumulator {
+        sum: i64,
+        any: bool,
+    }
+
+    impl Accumulator for SumAccumulator {
+        fn state(&self) -> Result<Vec<Value>> {
+            Ok(vec![Value::from(self.sum), Value::from(self.any)])
+        }
+
+        fn update_batch(&mut self, values: &[VectorRef]) -> Result<()> {
+            let values = values[0].as_any().downcast_ref::<Int64Vector>().unwrap();
+            for v in values.iter_data().flatten() {
+                self.sum += v;
+                self.any = true;
+            }
+            Ok(())
+        }
+
+        fn merge_batch(&mut self, states: &[VectorRef]) -> Result<()> {
+            for i in 0..states[0].len() {
+                if let (Value::Int64(sum), Value::Boolean(any)) =
+                    (states[0].get(i), states[1].get(i))
+                {
+                    self.sum += sum;
+                    self.any |= any;
+                }
+            }
+            Ok(())
+        }
+
+        fn evaluate(&self) -> Result<Value> {
+            Ok(if self.any {
+                Value::from(self.sum as f64)
+            } else {
+                Value::Null
+            })
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct SumAccumulatorCreator {
+        input_types: std::sync::Mutex<Option<Vec<ConcreteDataType>>>,
+    }
+
+    impl AggrFuncTypeStore for SumAccumulatorCreator {
+        fn input_types(&self) -> Result<Vec<ConcreteDataType>> {
+            self.input_types
+                .lock()
+                .unwrap()
+                .clone()
+                .context(InvalidInputStateSnafu)
+        }
+
+        fn set_input_types(&self, input_types: Vec<ConcreteDataType>) -> Result<()> {
+            *self.input_types.lock().unwrap() = Some(input_types);
+            Ok(())
+        }
+    }
+
+    impl AggregateFunctionCreator for SumAccumulatorCreator {
+        fn creator(&self) -> AccumulatorCreatorFunction {
+            Arc::new(|_| Ok(Box::<SumAccumulator>::default()))
+        }
+
+        fn output_type(&self) -> Result<ConcreteDataType> {
+            Ok(ConcreteDataType::float64_datatype())
+        }
+
+        fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+            Ok(vec![
+                ConcreteDataType::int64_datatype(),
+                ConcreteDataType::boolean_datatype(),
+            ])
+        }
+
+        fn supports_distributed(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_partial_and_merge() {
+        let input_types = [ConcreteDataType::int64_datatype()];
+        let partial = create_partial_aggregate_function(
+            "my_sum",
+            1,
+            Arc::new(SumAccumulatorCreator::default()),
+        );
+        assert_eq!("__my_sum_partial", partial.name);
+        assert_eq!(Some("my_sum"), partial_function_base_name(&partial.name));
+        assert_eq!(None, partial_function_base_name("my_sum"));
+        assert_eq!(
+            Arc::new(ConcreteDataType::binary_datatype()),
+            (partial.return_type)(&input_types).unwrap()
+        );
+
+        // Each part of the data is aggregated by a partial accumulator.
+        let parts = [vec![Some(1_i64), Some(2)], vec![], vec![None, Some(-7)]];
+        let states = parts
+            .into_iter()
+            .map(|part| {
+                let mut accumulator = (partial.accumulator)().unwrap();
+                let values: VectorRef = Arc::new(Int64Vector::from(part));
+                accumulator.update_batch(&[values]).unwrap();
+                let Value::Binary(state) = accumulator.evaluate().unwrap() else { unreachable!() };
+                Some(state.to_vec())
+            })
+            .collect::<Vec<_>>();
+
+        let creator = Arc::new(SumAccumulatorCreator::default());
+        creator.set_input_types(input_types.to_vec()).unwrap();
+        let merge = create_merge_aggregate_function("my_sum", creator);
+        assert_eq!("__my_sum_merge", merge.name);
+        assert_eq!(
+            Arc::new(ConcreteDataType::float64_datatype()),
+            (merge.return_type)(&[ConcreteDataType::binary_datatype()]).unwrap()
+        );
+        assert!((merge.return_type)(&input_types).is_err());
+
+        let mut accumulator = (merge.accumulator)().unwrap();
+        let states: VectorRef = Arc::new(BinaryVector::from(states));
+        accumulator.update_batch(&[states]).unwrap();
+        assert_eq!(Value::from(-4.0_f64), accumulator.evaluate().unwrap());
+
+        // Merges nothing.
+        let mut accumulator = (merge.accumulator)().unwrap();
+        let states: VectorRef = Arc::new(BinaryVector::from(vec![None]));
+        accumulator.update_batch(&[states]).unwrap();
+        assert_eq!(Value::Null, accumulator.evaluate().unwrap());
+
+        // Not a state.
+        let values: VectorRef = Arc::new(Float64Vector::from_slice([1.0]));
+        assert!(accumulator.update_batch(&[values]).is_err());
+    }
+}
@@ -17,7 +17,10 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use datafusion::arrow::datatypes::SchemaRef as DfSchemaRef;
+use datafusion::arrow::compute;
+use datafusion::arrow::datatypes::{
+    DataType as ArrowDataType, Field, Schema as ArrowSchema, SchemaRef as DfSchemaRef,
+};
 use datafusion::error::Result as DfResult;
 use datafusion::physical_plan::RecordBatchStream as DfRecordBatchStream;
 use datafusion_common::DataFusionError;
@@ -77,17 +80,76 @@ impl Stream for DfRecordBatchStreamAdapter {
 }
 
 /// DataFusion SendableRecordBatchStream -> Greptime RecordBatchStream
+///
+/// Adapts the record batches of DataFusion to the ones leaving the query engine, which have
+/// the dictionary encoded columns, e.g. the tags scanned with dictionary, unpacked.
 pub struct RecordBatchStreamAdapter {
     schema: SchemaRef,
+    /// Arrow schema of the batches with the dictionary encoded columns unpacked, `None` if the
+    /// stream has no such column.
+    unpacked_schema: Option<DfSchemaRef>,
     stream: DfSendableRecordBatchStream,
 }
 
 impl RecordBatchStreamAdapter {
     pub fn try_new(stream: DfSendableRecordBatchStream) -> Result<Self> {
+        let unpacked_schema = unpack_dictionary_schema(&stream.schema());
+        let arrow_schema = unpacked_schema.clone().unwrap_or_else(|| stream.schema());
         let schema =
-            Arc::new(Schema::try_from(stream.schema()).context(error::SchemaConversionSnafu)?);
-        Ok(Self { schema, stream })
+            Arc::new(Schema::try_from(arrow_schema).context(error::SchemaConversionSnafu)?);
+        Ok(Self {
+            schema,
+            unpacked_schema,
+            stream,
+        })
+    }
+}
+
+/// Returns the `schema` with the dictionary encoded columns replaced by the type of their
+/// values, `None` if there is no such column.
+fn unpack_dictionary_schema(schema: &DfSchemaRef) -> Option<DfSchemaRef> {
+    if !schema
+        .fields()
+        .iter()
+        .any(|field| matches!(field.data_type(), ArrowDataType::Dictionary(..)))
+    {
+        return None;
     }
+
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| match field.data_type() {
+            ArrowDataType::Dictionary(_, value_type) => Field::new(
+                field.name(),
+                value_type.as_ref().clone(),
+                field.is_nullable(),
+            )
+            .with_metadata(field.metadata().clone()),
+            _ => field.clone(),
+        })
+        .collect::<Vec<_>>();
+    Some(Arc::new(ArrowSchema::new_with_metadata(
+        fields,
+        schema.metadata().clone(),
+    )))
+}
+
+/// Unpacks the dictionary encoded columns of the `batch` to the types of the `schema`.
+fn unpack_dictionary_columns(batch: DfRecordBatch, schema: &DfSchemaRef) -> Result<DfRecordBatch> {
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| {
+            if column.data_type() == field.data_type() {
+                Ok(column.clone())
+            } else {
+                compute::cast(column, field.data_type()).context(error::UnpackDictionarySnafu)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    DfRecordBatch::try_new(schema.clone(), columns).context(error::NewDfRecordBatchSnafu)
 }
 
 impl RecordBatchStream for RecordBatchStreamAdapter {
@@ -103,7 +165,10 @@ impl Stream for RecordBatchStreamAdapter {
         match Pin::new(&mut self.stream).poll_next(cx) {
             Poll::Pending => Poll::Pending,
             Poll::Ready(Some(df_record_batch)) => {
-                let df_record_batch = df_record_batch.context(error::PollStreamSnafu)?;
+                let mut df_record_batch = df_record_batch.context(error::PollStreamSnafu)?;
+                if let Some(unpacked_schema) = &self.unpacked_schema {
+                    df_record_batch = unpack_dictionary_columns(df_record_batch, unpacked_schema)?;
+                }
                 Poll::Ready(Some(RecordBatch::try_from_df_record_batch(
                     self.schema(),
                     df_record_batch,
@@ -189,7 +254,7 @@ mod test {
     use common_error::prelude::{BoxedError, StatusCode};
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::ColumnSchema;
-    use datatypes::vectors::Int32Vector;
+    use datatypes::vectors::{Int32Vector, StringDictionaryBuilder, StringVector, VectorRef};
 
     use super::*;
     use crate::RecordBatches;
@@ -282,4 +347,50 @@ mod test {
             "Failed to init Recordbatch stream, source: External error: External error, source: Internal"
         );
     }
+    #[tokio::test]
+    async fn test_unpack_dictionary_columns() {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new(
+                "host",
+                ConcreteDataType::dictionary_datatype(
+                    ConcreteDataType::uint32_datatype(),
+                    ConcreteDataType::string_datatype(),
+                ),
+                false,
+            ),
+            ColumnSchema::new("a", ConcreteDataType::int32_datatype(), false),
+        ]));
+        let hosts = StringVector::from(vec!["host1", "host2", "host1"]);
+        let encoded = StringDictionaryBuilder::default().encode(&hosts).unwrap();
+        let batch = RecordBatch::new(
+            schema.clone(),
+            vec![
+                Arc::new(encoded) as _,
+                Arc::new(Int32Vector::from_slice([1, 2, 3])) as _,
+            ],
+        )
+        .unwrap();
+        // The column is kept encoded in the batch.
+        assert!(matches!(
+            batch.df_record_batch().column(0).data_type(),
+            ArrowDataType::Dictionary(..)
+        ));
+
+        let stream = RecordBatches::try_new(schema, vec![batch])
+            .unwrap()
+            .as_stream();
+        let adapter =
+            RecordBatchStreamAdapter::try_new(Box::pin(DfRecordBatchStreamAdapter::new(stream)))
+                .unwrap();
+        let unpacked_schema = adapter.schema();
+        assert_eq!(
+            ConcreteDataType::string_datatype(),
+            unpacked_schema.column_schemas()[0].data_type
+        );
+        let batches = crate::util::collect(Box::pin(adapter)).await.unwrap();
+        assert_eq!(1, batches.len());
+        assert_eq!(unpacked_schema, batches[0].schema);
+        let expect: VectorRef = Arc::new(hosts);
+        assert_eq!(expect, *batches[0].column(0));
+    }
 }
@@ -58,6 +58,12 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to unpack dictionary encoded column, source: {}", source))]
+    UnpackDictionary {
+        source: datatypes::arrow::error::ArrowError,
+        location: Location,
+    },
+
     #[snafu(display("Fail to format record batch, source: {}", source))]
     Format {
         source: datatypes::arrow::error::ArrowError,
@@ -100,6 +106,7 @@ impl ErrorExt for Error {
             | Error::CreateRecordBatches { .. }
             | Error::PollStream { .. }
             | Error::Format { .. }
+            | Error::UnpackDictionary { .. }
             | Error::InitRecordbatchStream { .. }
             | Error::ColumnNotExists { .. } => StatusCode::Internal,
 
@@ -16,7 +16,7 @@ use std::collections::HashMap;
 
 use datatypes::schema::SchemaRef;
 use datatypes::value::Value;
-use datatypes::vectors::{Helper, VectorRef};
+use datatypes::vectors::{DictionaryVector, Helper, VectorRef};
 use serde::ser::{Error, SerializeStruct};
 use serde::{Serialize, Serializer};
 use snafu::{OptionExt, ResultExt};
@@ -39,7 +39,19 @@ impl RecordBatch {
         columns: I,
     ) -> Result<RecordBatch> {
         let columns: Vec<_> = columns.into_iter().collect();
-        let arrow_arrays = columns.iter().map(|v| v.to_arrow_array()).collect();
+        let arrow_arrays = columns
+            .iter()
+            .zip(schema.column_schemas())
+            .map(|(vector, column_schema)| {
+                // Keeps the dictionary encoded vectors encoded if the schema asks for.
+                match vector.as_any().downcast_ref::<DictionaryVector>() {
+                    Some(vector) if column_schema.data_type.is_dictionary() => {
+                        vector.to_dictionary_array()
+                    }
+                    _ => vector.to_arrow_array(),
+                }
+            })
+            .collect();
 
         let df_record_batch = DfRecordBatch::try_new(schema.arrow_schema().clone(), arrow_arrays)
             .context(error::NewDfRecordBatchSnafu)?;
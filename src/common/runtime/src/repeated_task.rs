@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Duration;
 
 use common_error::prelude::ErrorExt;
@@ -35,7 +35,9 @@ pub trait TaskFunction<E: ErrorExt> {
 pub type TaskFunctionRef<E> = Arc<dyn TaskFunction<E> + Send + Sync>;
 
 pub struct RepeatedTask<E> {
-    cancel_token: Mutex<Option<CancellationToken>>,
+    /// Token to cancel the running task. Guarded by a std mutex that is never held across an
+    /// await, so [RepeatedTask::cancel] can always take it without waiting.
+    cancel_token: StdMutex<Option<CancellationToken>>,
     task_handle: Mutex<Option<JoinHandle<()>>>,
     started: AtomicBool,
     interval: Duration,
@@ -59,7 +61,7 @@ impl<E: ErrorExt> std::fmt::Debug for RepeatedTask<E> {
 impl<E: ErrorExt + 'static> RepeatedTask<E> {
     pub fn new(interval: Duration, task_fn: TaskFunctionRef<E>) -> Self {
         Self {
-            cancel_token: Mutex::new(None),
+            cancel_token: StdMutex::new(None),
             task_handle: Mutex::new(None),
             started: AtomicBool::new(false),
             interval,
@@ -90,7 +92,7 @@ impl<E: ErrorExt + 'static> RepeatedTask<E> {
                 }
             }
         });
-        *self.cancel_token.lock().await = Some(token);
+        *self.cancel_token.lock().unwrap() = Some(token);
         *self.task_handle.lock().await = Some(handle);
         self.started.store(true, Ordering::Relaxed);
 
@@ -114,7 +116,7 @@ impl<E: ErrorExt + 'static> RepeatedTask<E> {
         let token = self
             .cancel_token
             .lock()
-            .await
+            .unwrap()
             .take()
             .context(IllegalStateSnafu { name })?;
         let handle = self
@@ -130,6 +132,15 @@ impl<E: ErrorExt + 'static> RepeatedTask<E> {
         logging::debug!("Repeated task {} stopped", name);
         Ok(())
     }
+
+    /// Cancels the task without waiting for it to stop, for owners that can't wait, e.g. on
+    /// drop. Does nothing if the task is not started.
+    pub fn cancel(&self) {
+        if let Some(token) = self.cancel_token.lock().unwrap().take() {
+            token.cancel();
+            self.started.store(false, Ordering::Relaxed);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -171,4 +182,21 @@ mod tests {
 
         assert_eq!(task_fn.n.load(Ordering::Relaxed), 5);
     }
+
+    #[tokio::test]
+    async fn test_cancel_repeated_task() {
+        let task_fn = Arc::new(TickTask {
+            n: AtomicI32::new(0),
+        });
+        let task = RepeatedTask::new(Duration::from_millis(100), task_fn.clone());
+        task.cancel();
+
+        task.start(crate::bg_runtime()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        task.cancel();
+        assert!(!task.started());
+        let n = task_fn.n.load(Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert_eq!(n, task_fn.n.load(Ordering::Relaxed));
+    }
 }
@@ -26,7 +26,7 @@ use snafu::ResultExt;
 use substrait_proto::proto::Plan;
 
 use crate::error::{DecodeDfPlanSnafu, DecodeRelSnafu, EncodeDfPlanSnafu, EncodeRelSnafu, Error};
-use crate::SubstraitPlan;
+use crate::{df_udaf, SubstraitPlan};
 
 pub struct DFLogicalSubstraitConvertor;
 
@@ -42,18 +42,30 @@ impl SubstraitPlan for DFLogicalSubstraitConvertor {
         catalog_list: Arc<dyn CatalogList>,
     ) -> Result<Self::Plan, Self::Error> {
         let mut context = SessionContext::new();
-        let plan = Plan::decode(message).context(DecodeRelSnafu)?;
+        let mut plan = Plan::decode(message).context(DecodeRelSnafu)?;
+        let udafs = df_udaf::unmark_udafs(&mut plan)?;
         context.register_catalog_list(catalog_list);
         let df_plan = from_substrait_plan(&mut context, &plan)
             .await
             .context(DecodeDfPlanSnafu)?;
-        Ok(df_plan)
+        if udafs.iter().all(|udafs| udafs.is_empty()) {
+            return Ok(df_plan);
+        }
+        df_udaf::restore_udafs(&df_plan, &mut udafs.into_iter())
     }
 
     fn encode(&self, plan: Self::Plan) -> Result<Bytes, Self::Error> {
         let mut buf = BytesMut::new();
 
-        let substrait_plan = to_substrait_plan(&plan).context(EncodeDfPlanSnafu)?;
+        let substrait_plan = if df_udaf::has_udafs(&plan) {
+            let mut udafs = Vec::new();
+            let plan = df_udaf::replace_udafs(&plan, &mut udafs)?;
+            let mut substrait_plan = to_substrait_plan(&plan).context(EncodeDfPlanSnafu)?;
+            df_udaf::mark_udafs(&mut substrait_plan, udafs)?;
+            substrait_plan
+        } else {
+            to_substrait_plan(&plan).context(EncodeDfPlanSnafu)?
+        };
         substrait_plan.encode(&mut buf).context(EncodeRelSnafu)?;
 
         Ok(buf.freeze())
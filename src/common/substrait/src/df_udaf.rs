@@ -0,0 +1,538 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Carries the partial stages of the UDAFs pushed down to the datanodes in substrait plans.
+//!
+//! The substrait plans of DataFusion only carry the built-in aggregate functions. Before a plan
+//! is encoded, the partial UDAFs of its aggregations are replaced by `COUNT` placeholders, and
+//! their arguments are projected to columns of the input of the aggregation. The placeholders
+//! are then pointed to the names of the partial UDAFs, taking the argument columns. Decoding
+//! does the reverse, resolving the UDAFs by their names in the [FUNCTION_REGISTRY].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_function::scalars::FUNCTION_REGISTRY;
+use common_query::logical_plan::dist_aggr::{
+    create_partial_aggregate_function, partial_function_base_name,
+};
+use datafusion::common::tree_node::{TreeNode, VisitRecursion};
+use datafusion::common::DFSchema;
+use datafusion_expr::logical_plan::{Aggregate, Projection};
+use datafusion_expr::utils::from_plan;
+use datafusion_expr::{count, lit, Expr, LogicalPlan};
+use snafu::{ensure, OptionExt, ResultExt};
+use substrait_proto::proto::expression::field_reference::ReferenceType as FieldReferenceType;
+use substrait_proto::proto::expression::reference_segment::{
+    ReferenceType as SegReferenceType, StructField,
+};
+use substrait_proto::proto::expression::{FieldReference, ReferenceSegment, RexType};
+use substrait_proto::proto::extensions::simple_extension_declaration::{
+    ExtensionFunction, MappingType,
+};
+use substrait_proto::proto::extensions::SimpleExtensionDeclaration;
+use substrait_proto::proto::function_argument::ArgType;
+use substrait_proto::proto::plan_rel::RelType as PlanRelType;
+use substrait_proto::proto::rel::RelType;
+use substrait_proto::proto::{
+    AggregateFunction as SubstraitAggregateFunction, AggregateRel, Expression, FunctionArgument,
+    Plan, Rel,
+};
+
+use crate::error::{DFInternalSnafu, InvalidParametersSnafu, Result, UnsupportedExprSnafu};
+
+/// Name of the built-in aggregate function of the placeholders.
+const PLACEHOLDER_FUNCTION: &str = "count";
+
+/// A partial UDAF of an aggregation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PartialUdaf {
+    /// Index of the UDAF in the aggregate expressions.
+    measure: usize,
+    /// Name of the partial UDAF, see [partial_function_base_name].
+    name: String,
+    /// Indices of the argument columns in the input of the aggregation.
+    args: Vec<usize>,
+}
+
+/// Partial UDAFs of the aggregations of a plan, in the pre-order of the aggregations.
+pub(crate) type PartialUdafs = Vec<Vec<PartialUdaf>>;
+
+/// Returns whether any aggregation of the plan has UDAFs.
+pub(crate) fn has_udafs(plan: &LogicalPlan) -> bool {
+    let mut found = false;
+    // The visitor never fails.
+    let _ = plan.apply(&mut |plan| {
+        if let LogicalPlan::Aggregate(aggregate) = plan {
+            found = aggregate
+                .aggr_expr
+                .iter()
+                .any(|expr| matches!(unalias(expr), Expr::AggregateUDF { .. }));
+        }
+        Ok(if found {
+            VisitRecursion::Stop
+        } else {
+            VisitRecursion::Continue
+        })
+    });
+    found
+}
+
+/// Replaces the UDAFs of the aggregations of the plan by placeholders, see the module docs.
+pub(crate) fn replace_udafs(plan: &LogicalPlan, udafs: &mut PartialUdafs) -> Result<LogicalPlan> {
+    let LogicalPlan::Aggregate(aggregate) = plan else {
+        let inputs = plan
+            .inputs()
+            .into_iter()
+            .map(|input| replace_udafs(input, udafs))
+            .collect::<Result<Vec<_>>>()?;
+        return from_plan(plan, &plan.expressions(), &inputs).context(DFInternalSnafu);
+    };
+
+    let index = udafs.len();
+    udafs.push(Vec::new());
+    let input = replace_udafs(&aggregate.input, udafs)?;
+
+    let input_columns = input
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| Expr::Column(field.qualified_column()))
+        .collect::<Vec<_>>();
+    let mut projection = input_columns.clone();
+    let mut aggr_expr = Vec::with_capacity(aggregate.aggr_expr.len());
+    for (measure, expr) in aggregate.aggr_expr.iter().enumerate() {
+        let Expr::AggregateUDF { fun, args, filter } = unalias(expr) else {
+            aggr_expr.push(expr.clone());
+            continue;
+        };
+        ensure!(
+            partial_function_base_name(&fun.name).is_some() && filter.is_none() && !args.is_empty(),
+            UnsupportedExprSnafu {
+                name: format!("UDAF {expr}"),
+            }
+        );
+
+        let args = args
+            .iter()
+            .map(|arg| match projection.iter().position(|expr| expr == arg) {
+                Some(i) => i,
+                None => {
+                    projection.push(arg.clone());
+                    projection.len() - 1
+                }
+            })
+            .collect();
+        udafs[index].push(PartialUdaf {
+            measure,
+            name: fun.name.clone(),
+            args,
+        });
+        let placeholder = count(lit(1_i64));
+        aggr_expr.push(match expr {
+            Expr::Alias(_, name) => placeholder.alias(name),
+            _ => placeholder,
+        });
+    }
+
+    let input = if projection.len() > input_columns.len() {
+        LogicalPlan::Projection(
+            Projection::try_new(projection, Arc::new(input)).context(DFInternalSnafu)?,
+        )
+    } else {
+        input
+    };
+    let aggregate = Aggregate::try_new(Arc::new(input), aggregate.group_expr.clone(), aggr_expr)
+        .context(DFInternalSnafu)?;
+    Ok(LogicalPlan::Aggregate(aggregate))
+}
+
+/// Points the placeholders of the encoded plan to the UDAFs replaced by [replace_udafs].
+pub(crate) fn mark_udafs(plan: &mut Plan, udafs: PartialUdafs) -> Result<()> {
+    let mut anchors = FunctionAnchors::new(plan);
+    let mut udafs = udafs.into_iter();
+    for_each_aggregate_rel(plan, &mut |aggregate_rel| {
+        let udafs = udafs.next().context(InvalidParametersSnafu {
+            reason: "more aggregations are encoded than planned",
+        })?;
+        for udaf in udafs {
+            let function = aggregate_function(aggregate_rel, udaf.measure)?;
+            function.function_reference = anchors.anchor(&udaf.name);
+            function.arguments = udaf.args.iter().map(|i| field_argument(*i)).collect();
+        }
+        Ok(())
+    })?;
+    plan.extensions.extend(anchors.into_extensions());
+    Ok(())
+}
+
+/// Replaces the UDAFs of the aggregations of the plan to decode by placeholders, which the
+/// substrait consumer of DataFusion decodes, returns the UDAFs replaced.
+pub(crate) fn unmark_udafs(plan: &mut Plan) -> Result<PartialUdafs> {
+    let mut anchors = FunctionAnchors::new(plan);
+    let udaf_names = anchors
+        .names
+        .iter()
+        .filter(|(name, _)| partial_function_base_name(name).is_some())
+        .map(|(name, anchor)| (*anchor, name.clone()))
+        .collect::<HashMap<_, _>>();
+
+    let mut udafs = Vec::new();
+    for_each_aggregate_rel(plan, &mut |aggregate_rel| {
+        let mut aggregate_udafs = Vec::new();
+        for measure in 0..aggregate_rel.measures.len() {
+            let function = aggregate_function(aggregate_rel, measure)?;
+            let Some(name) = udaf_names.get(&function.function_reference) else { continue };
+            let args = function
+                .arguments
+                .iter()
+                .map(argument_field)
+                .collect::<Result<Vec<_>>>()?;
+            ensure!(
+                !args.is_empty(),
+                InvalidParametersSnafu {
+                    reason: format!("UDAF {name} without arguments"),
+                }
+            );
+            function.function_reference = anchors.anchor(PLACEHOLDER_FUNCTION);
+            function.arguments.truncate(1);
+            aggregate_udafs.push(PartialUdaf {
+                measure,
+                name: name.clone(),
+                args,
+            });
+        }
+        udafs.push(aggregate_udafs);
+        Ok(())
+    })?;
+    plan.extensions.extend(anchors.into_extensions());
+    Ok(udafs)
+}
+
+/// Replaces the placeholders of the aggregations of the decoded plan by the UDAFs returned by
+/// [unmark_udafs].
+pub(crate) fn restore_udafs(
+    plan: &LogicalPlan,
+    udafs: &mut impl Iterator<Item = Vec<PartialUdaf>>,
+) -> Result<LogicalPlan> {
+    let LogicalPlan::Aggregate(aggregate) = plan else {
+        let inputs = plan
+            .inputs()
+            .into_iter()
+            .map(|input| restore_udafs(input, udafs))
+            .collect::<Result<Vec<_>>>()?;
+        return from_plan(plan, &plan.expressions(), &inputs).context(DFInternalSnafu);
+    };
+
+    let aggregate_udafs = udafs.next().unwrap_or_default();
+    let input = restore_udafs(&aggregate.input, udafs)?;
+    let mut aggr_expr = aggregate.aggr_expr.clone();
+    for udaf in aggregate_udafs {
+        let expr = udaf.to_expr(input.schema())?;
+        let placeholder = aggr_expr
+            .get_mut(udaf.measure)
+            .context(InvalidParametersSnafu {
+                reason: format!("UDAF {} of measure {} not found", udaf.name, udaf.measure),
+            })?;
+        *placeholder = match &*placeholder {
+            Expr::Alias(_, name) => expr.alias(name.as_str()),
+            _ => expr,
+        };
+    }
+    let aggregate = Aggregate::try_new(Arc::new(input), aggregate.group_expr.clone(), aggr_expr)
+        .context(DFInternalSnafu)?;
+    Ok(LogicalPlan::Aggregate(aggregate))
+}
+
+impl PartialUdaf {
+    fn to_expr(&self, input_schema: &DFSchema) -> Result<Expr> {
+        let func = partial_function_base_name(&self.name)
+            .and_then(|name| FUNCTION_REGISTRY.get_aggr_function(name))
+            .with_context(|| UnsupportedExprSnafu {
+                name: format!("UDAF {}", self.name),
+            })?;
+        let args = self
+            .args
+            .iter()
+            .map(|i| {
+                ensure!(
+                    *i < input_schema.fields().len(),
+                    InvalidParametersSnafu {
+                        reason: format!("argument {i} of UDAF {} out of the input", self.name),
+                    }
+                );
+                Ok(Expr::Column(input_schema.field(*i).qualified_column()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let partial =
+            create_partial_aggregate_function(&func.name(), func.args_count(), func.create());
+        Ok(Expr::AggregateUDF {
+            fun: Arc::new(partial.into()),
+            args,
+            filter: None,
+        })
+    }
+}
+
+/// Anchors of the functions declared in a plan, and the ones to declare.
+struct FunctionAnchors {
+    names: HashMap<String, u32>,
+    next_anchor: u32,
+    new_extensions: Vec<SimpleExtensionDeclaration>,
+}
+
+impl FunctionAnchors {
+    fn new(plan: &Plan) -> Self {
+        let names = plan
+            .extensions
+            .iter()
+            .filter_map(|extension| match &extension.mapping_type {
+                Some(MappingType::ExtensionFunction(function)) => {
+                    Some((function.name.clone(), function.function_anchor))
+                }
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+        let next_anchor = names.values().max().map_or(0, |anchor| anchor + 1);
+        Self {
+            names,
+            next_anchor,
+            new_extensions: Vec::new(),
+        }
+    }
+
+    /// Returns the anchor of the function `name`, declaring it if it's not yet.
+    fn anchor(&mut self, name: &str) -> u32 {
+        if let Some(anchor) = self.names.get(name) {
+            return *anchor;
+        }
+        let anchor = self.next_anchor;
+        self.next_anchor += 1;
+        let _ = self.names.insert(name.to_string(), anchor);
+        self.new_extensions.push(SimpleExtensionDeclaration {
+            mapping_type: Some(MappingType::ExtensionFunction(ExtensionFunction {
+                extension_uri_reference: 0,
+                function_anchor: anchor,
+                name: name.to_string(),
+            })),
+        });
+        anchor
+    }
+
+    fn into_extensions(self) -> Vec<SimpleExtensionDeclaration> {
+        self.new_extensions
+    }
+}
+
+fn unalias(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Alias(expr, _) => unalias(expr),
+        expr => expr,
+    }
+}
+
+fn aggregate_function(
+    aggregate_rel: &mut AggregateRel,
+    measure: usize,
+) -> Result<&mut SubstraitAggregateFunction> {
+    aggregate_rel
+        .measures
+        .get_mut(measure)
+        .and_then(|measure| measure.measure.as_mut())
+        .context(InvalidParametersSnafu {
+            reason: format!("measure {measure} not found in aggregate relation"),
+        })
+}
+
+fn field_argument(field: usize) -> FunctionArgument {
+    let field_reference = FieldReference {
+        reference_type: Some(FieldReferenceType::DirectReference(ReferenceSegment {
+            reference_type: Some(SegReferenceType::StructField(Box::new(StructField {
+                field: field as _,
+                child: None,
+            }))),
+        })),
+        root_type: None,
+    };
+    FunctionArgument {
+        arg_type: Some(ArgType::Value(Expression {
+            rex_type: Some(RexType::Selection(Box::new(field_reference))),
+        })),
+    }
+}
+
+/// Returns the index of the input column the argument refers to.
+fn argument_field(argument: &FunctionArgument) -> Result<usize> {
+    if let Some(ArgType::Value(Expression {
+        rex_type: Some(RexType::Selection(field_reference)),
+    })) = &argument.arg_type
+    && let Some(FieldReferenceType::DirectReference(ReferenceSegment {
+        reference_type: Some(SegReferenceType::StructField(field)),
+    })) = &field_reference.reference_type
+    && field.child.is_none()
+    {
+        return Ok(field.field as usize);
+    }
+    InvalidParametersSnafu {
+        reason: format!("UDAF argument {argument:?} is not a column"),
+    }
+    .fail()
+}
+
+/// Calls `f` on the aggregate relations of the plan, in pre-order.
+fn for_each_aggregate_rel(
+    plan: &mut Plan,
+    f: &mut dyn FnMut(&mut AggregateRel) -> Result<()>,
+) -> Result<()> {
+    for relation in &mut plan.relations {
+        match &mut relation.rel_type {
+            Some(PlanRelType::Rel(rel)) => visit_aggregate_rels(rel, f)?,
+            Some(PlanRelType::Root(root)) => {
+                if let Some(rel) = &mut root.input {
+                    visit_aggregate_rels(rel, f)?;
+                }
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+fn visit_aggregate_rels(
+    rel: &mut Rel,
+    f: &mut dyn FnMut(&mut AggregateRel) -> Result<()>,
+) -> Result<()> {
+    let inputs: Vec<&mut Rel> = match &mut rel.rel_type {
+        Some(RelType::Aggregate(aggregate_rel)) => {
+            f(aggregate_rel)?;
+            aggregate_rel.input.as_deref_mut().into_iter().collect()
+        }
+        Some(RelType::Filter(rel)) => rel.input.as_deref_mut().into_iter().collect(),
+        Some(RelType::Project(rel)) => rel.input.as_deref_mut().into_iter().collect(),
+        Some(RelType::Sort(rel)) => rel.input.as_deref_mut().into_iter().collect(),
+        Some(RelType::Fetch(rel)) => rel.input.as_deref_mut().into_iter().collect(),
+        Some(RelType::Join(rel)) => rel
+            .left
+            .as_deref_mut()
+            .into_iter()
+            .chain(rel.right.as_deref_mut())
+            .collect(),
+        Some(RelType::Cross(rel)) => rel
+            .left
+            .as_deref_mut()
+            .into_iter()
+            .chain(rel.right.as_deref_mut())
+            .collect(),
+        Some(RelType::Set(rel)) => rel.inputs.iter_mut().collect(),
+        _ => Vec::new(),
+    };
+    for input in inputs {
+        visit_aggregate_rels(input, f)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::arrow::array::Float64Array;
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use datafusion::arrow::record_batch::RecordBatch;
+    use datafusion::catalog::catalog::{
+        CatalogList, CatalogProvider, MemoryCatalogList, MemoryCatalogProvider,
+    };
+    use datafusion::catalog::schema::{MemorySchemaProvider, SchemaProvider};
+    use datafusion::datasource::{provider_as_source, MemTable};
+    use datafusion_expr::{col, LogicalPlanBuilder};
+
+    use super::*;
+    use crate::{DFLogicalSubstraitConvertor, SubstraitPlan};
+
+    fn partial_udaf(name: &str, args: Vec<Expr>) -> Expr {
+        let func = FUNCTION_REGISTRY.get_aggr_function(name).unwrap();
+        let partial =
+            create_partial_aggregate_function(&func.name(), func.args_count(), func.create());
+        Expr::AggregateUDF {
+            fun: Arc::new(partial.into()),
+            args,
+            filter: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_udaf_round_trip() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Float64,
+            true,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0]))],
+        )
+        .unwrap();
+        let table = Arc::new(MemTable::try_new(schema, vec![vec![batch]]).unwrap());
+        let schema_provider = MemorySchemaProvider::new();
+        let _ = schema_provider
+            .register_table("numbers".to_string(), table.clone())
+            .unwrap();
+        let catalog_provider = MemoryCatalogProvider::new();
+        let _ = catalog_provider
+            .register_schema("public", Arc::new(schema_provider))
+            .unwrap();
+        let catalog_list = Arc::new(MemoryCatalogList::new());
+        let _ = catalog_list.register_catalog("datafusion".to_string(), Arc::new(catalog_provider));
+
+        let plan = LogicalPlanBuilder::scan("numbers", provider_as_source(table), None)
+            .unwrap()
+            .aggregate(
+                Vec::<Expr>::new(),
+                vec![
+                    count(col("value")),
+                    partial_udaf("approx_percentile", vec![col("value"), lit(0.5)]).alias("s"),
+                ],
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(has_udafs(&plan));
+
+        let bytes = DFLogicalSubstraitConvertor.encode(plan).unwrap();
+        let decoded = DFLogicalSubstraitConvertor
+            .decode(bytes, catalog_list)
+            .await
+            .unwrap();
+
+        let LogicalPlan::Aggregate(aggregate) = decoded else { unreachable!() };
+        assert!(matches!(
+            &aggregate.aggr_expr[0],
+            Expr::AggregateFunction(function) if function.args.len() == 1
+        ));
+        let Expr::AggregateUDF { fun, args, .. } = unalias(&aggregate.aggr_expr[1]) else {
+            unreachable!()
+        };
+        assert_eq!("__approx_percentile_partial", fun.name);
+        // The literal argument is projected to the second column of the input.
+        let input_columns = aggregate
+            .input
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect::<Vec<_>>();
+        assert_eq!(2, input_columns.len());
+        assert_eq!(&input_columns, args);
+    }
+}
@@ -20,6 +20,7 @@ mod df_expr;
 #[allow(unused)]
 mod df_logical;
 mod df_substrait;
+mod df_udaf;
 pub mod error;
 mod schema;
 mod types;
@@ -17,14 +17,23 @@
 use std::sync::{Arc, Once, RwLock};
 use std::time::{Duration, Instant};
 
-use metrics::histogram;
+use dashmap::DashSet;
+use metrics::{gauge, histogram};
 use metrics_exporter_prometheus::PrometheusBuilder;
 pub use metrics_exporter_prometheus::PrometheusHandle;
 use once_cell::sync::Lazy;
 
+/// Prefix of the names of all the metrics.
+const GLOBAL_PREFIX: &str = "greptime";
+
 static PROMETHEUS_HANDLE: Lazy<Arc<RwLock<Option<PrometheusHandle>>>> =
     Lazy::new(|| Arc::new(RwLock::new(None)));
 
+/// Removed series of the removable gauges, as the recorder renders them, e.g.
+/// `greptime_foo{table="bar"}`. The recorder never forgets a series once recorded, so the
+/// series of the objects gone away, e.g. closed tables, are left out when rendering instead.
+static REMOVED_GAUGES: Lazy<DashSet<String>> = Lazy::new(Default::default);
+
 pub fn init_default_metrics_recorder() {
     static START: Once = Once::new();
     START.call_once(init_prometheus_recorder)
@@ -33,7 +42,7 @@ pub fn init_default_metrics_recorder() {
 /// Init prometheus recorder.
 fn init_prometheus_recorder() {
     let recorder = PrometheusBuilder::new()
-        .add_global_prefix("greptime".to_string())
+        .add_global_prefix(GLOBAL_PREFIX.to_string())
         .build_recorder();
     let mut h = PROMETHEUS_HANDLE.as_ref().write().unwrap();
     *h = Some(recorder.handle());
@@ -57,6 +66,73 @@ pub fn try_handle() -> Option<PrometheusHandle> {
     PROMETHEUS_HANDLE.as_ref().read().unwrap().clone()
 }
 
+/// Sets the series of the removable gauge `name` with the `labels` to `value`, through the
+/// recorder.
+pub fn set_removable_gauge(name: &'static str, labels: &[(&'static str, String)], value: f64) {
+    // Nothing to bring back in the common case that no gauge is removed.
+    if !REMOVED_GAUGES.is_empty() {
+        let _ = REMOVED_GAUGES.remove(&series_name(name, labels));
+    }
+    gauge!(name, value, &labels.to_vec());
+}
+
+/// Removes the series of the removable gauge `name` with the `labels` from the rendered
+/// metrics, until it's set again.
+pub fn remove_removable_gauge(name: &str, labels: &[(&str, String)]) {
+    let _ = REMOVED_GAUGES.insert(series_name(name, labels));
+}
+
+/// Renders the metrics of the recorder in the text format of Prometheus, without the removed
+/// gauges. `None` if the recorder is not initialized.
+pub fn render() -> Option<String> {
+    let text = try_handle()?.render();
+    if REMOVED_GAUGES.is_empty() {
+        return Some(text);
+    }
+
+    let is_removed = |line: &str| {
+        line.rsplit_once(' ')
+            .map_or(false, |(series, _)| REMOVED_GAUGES.contains(series))
+    };
+    Some(
+        text.split_inclusive('\n')
+            .filter(|line| !is_removed(line.trim_end()))
+            .collect(),
+    )
+}
+
+/// Returns the series of the metric `name` with the `labels` as the recorder renders it: the
+/// name prefixed and with the characters invalid in Prometheus replaced by `_`, then the
+/// labels in the given order.
+fn series_name(name: &str, labels: &[(&str, String)]) -> String {
+    let name = format!("{GLOBAL_PREFIX}_{name}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if labels.is_empty() {
+        return name;
+    }
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape_label_value(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{name}{{{labels}}}")
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 #[must_use = "Timer should be kept in a variable otherwise it cannot observe duration"]
 #[derive(Debug)]
 pub struct Timer {
@@ -136,6 +212,34 @@ mod tests {
         assert!(text.contains("test_elapsed_timer_b"));
     }
 
+    #[test]
+    fn test_removable_gauges() {
+        init_default_metrics_recorder();
+        let labels = |table: &str| {
+            [
+                ("schema", "public".to_string()),
+                ("table", table.to_string()),
+            ]
+        };
+        set_removable_gauge("test.removable_gauge", &labels("a"), 1.0);
+        set_removable_gauge("test.removable_gauge", &labels("b\"c"), f64::NAN);
+        let text = render().unwrap();
+        assert!(text.contains("greptime_test_removable_gauge{schema=\"public\",table=\"a\"} 1\n"));
+        assert!(text
+            .contains("greptime_test_removable_gauge{schema=\"public\",table=\"b\\\"c\"} NaN\n"));
+
+        remove_removable_gauge("test.removable_gauge", &labels("a"));
+        let text = render().unwrap();
+        assert!(!text.contains("table=\"a\""));
+        assert!(text.contains("table=\"b\\\"c\""));
+        // The raw rendering of the recorder still has the series.
+        assert!(try_handle().unwrap().render().contains("table=\"a\""));
+
+        set_removable_gauge("test.removable_gauge", &labels("a"), 2.0);
+        let text = render().unwrap();
+        assert!(text.contains("greptime_test_removable_gauge{schema=\"public\",table=\"a\"} 2\n"));
+    }
+
     #[test]
     fn test_elapsed_timer_with_label() {
         init_default_metrics_recorder();
@@ -91,6 +91,15 @@ impl DateTime {
     pub fn to_chrono_datetime(&self) -> Option<NaiveDateTime> {
         NaiveDateTime::from_timestamp_millis(self.0)
     }
+
+    /// Format the datetime to `YYYY-MM-DD HH:MM:SS` in `time_zone`, falls back to the
+    /// [Display] of it if it's out of range.
+    pub fn to_string_in_time_zone(&self, time_zone: &crate::TimeZone) -> String {
+        match NaiveDateTime::from_timestamp_opt(self.0, 0) {
+            Some(v) => time_zone.from_utc(&v).format(DATETIME_FORMAT).to_string(),
+            None => self.to_string(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -136,4 +145,13 @@ mod tests {
             .val();
         assert_eq!(28800, ts);
     }
+
+    #[test]
+    fn test_to_string_in_time_zone() {
+        let tz: crate::TimeZone = "-05:30".parse().unwrap();
+        assert_eq!(
+            "1969-12-31 18:30:00",
+            DateTime::new(0).to_string_in_time_zone(&tz)
+        );
+    }
 }
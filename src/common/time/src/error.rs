@@ -20,6 +20,8 @@ use common_error::ext::ErrorExt;
 use common_error::prelude::StatusCode;
 use snafu::{Location, Snafu};
 
+use crate::timestamp::TimeUnit;
+
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub))]
 pub enum Error {
@@ -40,6 +42,27 @@ pub enum Error {
 
     #[snafu(display("Timestamp arithmetic overflow, msg: {}", msg))]
     ArithmeticOverflow { msg: String, location: Location },
+
+    #[snafu(display(
+        "Invalid timestamp string '{}', expect RFC3339 like '2023-04-01T10:00:00Z', \
+         'YYYY-MM-DD HH:MM:SS[.fff]', 'YYYY-MM-DD' or an integer of the epoch in the unit \
+         of the timestamp",
+        raw
+    ))]
+    InvalidTimestampStr { raw: String, location: Location },
+
+    #[snafu(display("Timestamp '{}' is out of the range of unit {}", raw, unit))]
+    TimestampOutOfRange {
+        raw: String,
+        unit: TimeUnit,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Invalid time zone '{}', expect 'SYSTEM', 'UTC' or an offset like '+08:00'",
+        raw
+    ))]
+    InvalidTimeZone { raw: String, location: Location },
 }
 
 impl ErrorExt for Error {
@@ -49,9 +72,11 @@ impl ErrorExt for Error {
                 StatusCode::InvalidArguments
             }
             Error::TimestampOverflow { .. } => StatusCode::Internal,
-            Error::InvalidDateStr { .. } | Error::ArithmeticOverflow { .. } => {
-                StatusCode::InvalidArguments
-            }
+            Error::InvalidDateStr { .. }
+            | Error::ArithmeticOverflow { .. }
+            | Error::InvalidTimestampStr { .. }
+            | Error::TimestampOutOfRange { .. }
+            | Error::InvalidTimeZone { .. } => StatusCode::InvalidArguments,
         }
     }
 
@@ -63,7 +88,10 @@ impl ErrorExt for Error {
         match self {
             Error::ParseTimestamp { location, .. }
             | Error::TimestampOverflow { location, .. }
-            | Error::ArithmeticOverflow { location, .. } => Some(*location),
+            | Error::ArithmeticOverflow { location, .. }
+            | Error::InvalidTimestampStr { location, .. }
+            | Error::TimestampOutOfRange { location, .. }
+            | Error::InvalidTimeZone { location, .. } => Some(*location),
             Error::ParseDateStr { .. } => None,
             Error::InvalidDateStr { location, .. } => Some(*location),
         }
@@ -20,12 +20,15 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use chrono::offset::Local;
-use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ResultExt};
 
 use crate::error;
-use crate::error::{ArithmeticOverflowSnafu, Error, ParseTimestampSnafu, TimestampOverflowSnafu};
+use crate::error::{
+    ArithmeticOverflowSnafu, Error, InvalidTimestampStrSnafu, ParseTimestampSnafu,
+    TimestampOutOfRangeSnafu, TimestampOverflowSnafu,
+};
 use crate::util::div_ceil;
 
 #[derive(Debug, Clone, Default, Copy, Serialize, Deserialize)]
@@ -175,10 +178,84 @@ impl Timestamp {
         }
     }
 
+    /// Format timestamp to `YYYY-MM-DD HH:MM:SS[.fff]` in `time_zone`, the text format of
+    /// datetimes in MySQL. The digits of the fraction are decided by the unit, like 3 digits
+    /// for milliseconds. Falls back to [Timestamp::to_iso8601_string] if the timestamp exceeds
+    /// what chrono timestamp can represent.
+    pub fn to_string_in_time_zone(&self, time_zone: &crate::TimeZone) -> String {
+        let Some(v) = self.to_chrono_datetime() else {
+            return self.to_iso8601_string();
+        };
+        let format = match self.unit {
+            TimeUnit::Second => "%Y-%m-%d %H:%M:%S",
+            TimeUnit::Millisecond => "%Y-%m-%d %H:%M:%S%.3f",
+            TimeUnit::Microsecond => "%Y-%m-%d %H:%M:%S%.6f",
+            TimeUnit::Nanosecond => "%Y-%m-%d %H:%M:%S%.9f",
+        };
+        time_zone.from_utc(&v).format(format).to_string()
+    }
+
     pub fn to_chrono_datetime(&self) -> Option<NaiveDateTime> {
         let (sec, nsec) = self.split();
         NaiveDateTime::from_timestamp_opt(sec, nsec)
     }
+
+    /// Parses a string to a timestamp in `unit`, for the strings written to timestamp columns.
+    ///
+    /// Accepts:
+    /// - RFC3339 with or without the offset, like `2023-04-01T10:00:00Z`,
+    ///   `2023-04-01T10:00:00.123+08:00` or `2023-04-01T10:00:00`
+    /// - `YYYY-MM-DD HH:MM:SS[.fff]`, optionally followed by `Z` or an offset
+    /// - `YYYY-MM-DD`, the start of the date
+    /// - an integer, the epoch in `unit`
+    ///
+    /// Strings without an offset are in the local timezone. Fractions of seconds finer than
+    /// `unit` are truncated. Unlike [Timestamp::from_str], the string is converted to `unit`
+    /// directly, so dates out of the range of nanosecond timestamps are fine in coarser units.
+    pub fn parse_in_unit(s: &str, unit: TimeUnit) -> error::Result<Timestamp> {
+        let s = s.trim();
+        if let Ok(value) = s.parse::<i64>() {
+            return Ok(Timestamp::new(value, unit));
+        }
+
+        let datetime = parse_datetime(s).context(InvalidTimestampStrSnafu { raw: s })?;
+        let units_per_sec = (TimeUnit::Second.factor() / unit.factor()) as i64;
+        let subsec_units = (datetime.timestamp_subsec_nanos() / unit.factor()) as i64;
+        let value = datetime
+            .timestamp()
+            .checked_mul(units_per_sec)
+            .and_then(|v| v.checked_add(subsec_units))
+            .context(TimestampOutOfRangeSnafu { raw: s, unit })?;
+        Ok(Timestamp::new(value, unit))
+    }
+}
+
+/// Parses the datetime strings accepted by [Timestamp::parse_in_unit].
+fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    if let Ok(datetime) = DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%:z") {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    if let Ok(datetime) = Utc.datetime_from_str(s, "%Y-%m-%d %H:%M:%S%.fZ") {
+        return Some(datetime);
+    }
+
+    let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => {
+            Some(datetime.with_timezone(&Utc))
+        }
+        LocalResult::None => None,
+    }
 }
 
 impl FromStr for Timestamp {
@@ -662,6 +739,40 @@ mod tests {
         assert_eq!("1970-01-01 07:59:58.999+0800", ts.to_iso8601_string());
     }
 
+    #[test]
+    fn test_to_string_in_time_zone() {
+        let tz: crate::TimeZone = "+08:00".parse().unwrap();
+        let cases = [
+            (Timestamp::new_second(1668070237), "2022-11-10 16:50:37"),
+            (
+                Timestamp::new_millisecond(1668070237042),
+                "2022-11-10 16:50:37.042",
+            ),
+            (
+                Timestamp::new(1668070237000042, TimeUnit::Microsecond),
+                "2022-11-10 16:50:37.000042",
+            ),
+            (
+                Timestamp::new(1668070237000000042, TimeUnit::Nanosecond),
+                "2022-11-10 16:50:37.000000042",
+            ),
+            (Timestamp::new_millisecond(-1), "1970-01-01 07:59:59.999"),
+        ];
+        for (ts, expected) in cases {
+            assert_eq!(expected, ts.to_string_in_time_zone(&tz));
+        }
+
+        let tz: crate::TimeZone = "-05:30".parse().unwrap();
+        let ts = Timestamp::new_millisecond(1668070237042);
+        assert_eq!("2022-11-10 03:20:37.042", ts.to_string_in_time_zone(&tz));
+
+        std::env::set_var("TZ", "Asia/Shanghai");
+        assert_eq!(
+            "2022-11-10 16:50:37.042",
+            ts.to_string_in_time_zone(&crate::TimeZone::System)
+        );
+    }
+
     #[test]
     fn test_serialize_to_json_value() {
         std::env::set_var("TZ", "Asia/Shanghai");
@@ -876,6 +987,46 @@ mod tests {
         assert_eq!(TimeUnit::Second, res.unit);
     }
 
+    #[test]
+    fn test_parse_in_unit() {
+        std::env::set_var("TZ", "Asia/Shanghai");
+        // Strings without an offset are in the local timezone.
+        for s in [
+            "2023-04-01 18:00:00",
+            "2023-04-01T18:00:00",
+            "2023-04-01 18:00:00+08:00",
+            "2023-04-01T10:00:00Z",
+        ] {
+            assert_eq!(
+                Timestamp::new_millisecond(1680343200000),
+                Timestamp::parse_in_unit(s, TimeUnit::Millisecond).unwrap(),
+                "{s}"
+            );
+        }
+        assert_eq!(
+            Timestamp::new_second(1680278400),
+            Timestamp::parse_in_unit("2023-04-01", TimeUnit::Second).unwrap()
+        );
+        assert_eq!(
+            Timestamp::new_microsecond(1680343200123456),
+            Timestamp::parse_in_unit("2023-04-01 18:00:00.123456789", TimeUnit::Microsecond)
+                .unwrap()
+        );
+        assert_eq!(
+            Timestamp::new_nanosecond(-1),
+            Timestamp::parse_in_unit(" -1 ", TimeUnit::Nanosecond).unwrap()
+        );
+
+        assert!(matches!(
+            Timestamp::parse_in_unit("1677-01-01", TimeUnit::Nanosecond),
+            Err(Error::TimestampOutOfRange { .. })
+        ));
+        assert!(matches!(
+            Timestamp::parse_in_unit("2023-04-01 18:00", TimeUnit::Second),
+            Err(Error::InvalidTimestampStr { .. })
+        ));
+    }
+
     #[test]
     fn test_parse_in_time_zone() {
         std::env::set_var("TZ", "Asia/Shanghai");
@@ -0,0 +1,112 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{FixedOffset, Local, NaiveDateTime, Offset, TimeZone as _};
+
+use crate::error::{Error, InvalidTimeZoneSnafu, Result};
+
+/// Time zone of a session to show the times in, like the `time_zone` variable of MySQL.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeZone {
+    /// The local time zone of the server.
+    #[default]
+    System,
+    /// A fixed offset from UTC.
+    Offset(FixedOffset),
+}
+
+impl TimeZone {
+    /// Converts the UTC `datetime` to the wall-clock time in this time zone.
+    pub fn from_utc(&self, datetime: &NaiveDateTime) -> NaiveDateTime {
+        let offset = match self {
+            TimeZone::System => Local.offset_from_utc_datetime(datetime).fix(),
+            TimeZone::Offset(offset) => *offset,
+        };
+        *datetime + offset
+    }
+}
+
+impl Display for TimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeZone::System => write!(f, "SYSTEM"),
+            TimeZone::Offset(offset) => write!(f, "{offset}"),
+        }
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = Error;
+
+    /// Parses `SYSTEM`, `UTC` or an offset in `[+-]HH:MM` like MySQL does.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("SYSTEM") {
+            return Ok(TimeZone::System);
+        }
+        if s.eq_ignore_ascii_case("UTC") || s == "Z" {
+            return Ok(TimeZone::Offset(FixedOffset::east_opt(0).unwrap()));
+        }
+
+        let invalid = || InvalidTimeZoneSnafu { raw: s }.build();
+        let (sign, hhmm) = match s.as_bytes().first() {
+            Some(b'+') => (1, &s[1..]),
+            Some(b'-') => (-1, &s[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = hhmm.split_once(':').ok_or_else(invalid)?;
+        let hours = hours.parse::<i32>().map_err(|_| invalid())?;
+        let minutes = minutes.parse::<i32>().map_err(|_| invalid())?;
+        // MySQL accepts offsets in `-13:59..=+14:00`.
+        if !(0..60).contains(&minutes) || hours > 14 || (hours == 14 && minutes > 0) {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(TimeZone::Offset)
+            .ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_zone() {
+        assert_eq!(TimeZone::System, "SYSTEM".parse().unwrap());
+        assert_eq!(TimeZone::System, "system".parse().unwrap());
+        assert_eq!("+00:00", "UTC".parse::<TimeZone>().unwrap().to_string());
+        assert_eq!("+08:00", "+08:00".parse::<TimeZone>().unwrap().to_string());
+        assert_eq!("-05:30", "-5:30".parse::<TimeZone>().unwrap().to_string());
+        assert_eq!("+14:00", "+14:00".parse::<TimeZone>().unwrap().to_string());
+        assert_eq!("SYSTEM", TimeZone::default().to_string());
+
+        for s in ["", "08:00", "+8", "+14:30", "+08:60", "Asia/Shanghai"] {
+            assert!(s.parse::<TimeZone>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_from_utc() {
+        let utc = NaiveDateTime::from_timestamp_opt(0, 0).unwrap();
+        let tz: TimeZone = "-05:30".parse().unwrap();
+        assert_eq!(
+            "1969-12-31 18:30:00",
+            tz.from_utc(&utc).format("%Y-%m-%d %H:%M:%S").to_string()
+        );
+    }
+}
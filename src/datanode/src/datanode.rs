@@ -15,14 +15,24 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use catalog::local::NameResolution;
+use catalog::replay::DEFAULT_REPLAY_CONCURRENCY;
+use catalog::DEFAULT_STAT_CONCURRENCY;
 use common_base::readable_size::ReadableSize;
+use common_config::{validate_addr, FieldError, Validate};
 use common_telemetry::info;
 use meta_client::MetaClientOptions;
+use query::rebatch::{RebatchOptions, DEFAULT_TARGET_BYTES, DEFAULT_TARGET_ROWS};
 use serde::{Deserialize, Serialize};
 use servers::http::HttpOptions;
 use servers::Mode;
-use storage::config::EngineConfig as StorageEngineConfig;
+use storage::config::{
+    AdaptiveFlushConfig, EngineConfig as StorageEngineConfig,
+    TagDictionaryConfig as StorageTagDictionaryConfig,
+};
 use storage::scheduler::SchedulerConfig;
+use storage::sst::{parse_compression, parse_encoding};
+use table::column_limits::ColumnLimitsOptions;
 
 use crate::error::Result;
 use crate::instance::{Instance, InstanceRef};
@@ -47,6 +57,65 @@ pub struct StorageConfig {
     pub store: ObjectStoreConfig,
     pub compaction: CompactionConfig,
     pub manifest: RegionManifestConfig,
+    pub flush: FlushConfig,
+    pub usage: StorageUsageConfig,
+    pub recycle_bin: RecycleBinConfig,
+    pub orphan_gc: OrphanGcConfig,
+    pub tag_dictionary: TagDictionaryConfig,
+    pub time_travel: TimeTravelConfig,
+    pub sst: SstConfig,
+}
+
+impl Validate for StorageConfig {
+    fn validate(&self) -> std::result::Result<(), FieldError> {
+        self.sst.validate().map_err(|e| e.nested("sst"))?;
+        self.orphan_gc
+            .validate()
+            .map_err(|e| e.nested("orphan_gc"))?;
+        match &self.store {
+            ObjectStoreConfig::File(_) => Ok(()),
+            ObjectStoreConfig::S3(s3) => {
+                validate_required("bucket", &s3.bucket)?;
+                validate_credentials(
+                    ("access_key_id", &s3.access_key_id),
+                    ("secret_access_key", &s3.secret_access_key),
+                )
+            }
+            ObjectStoreConfig::Oss(oss) => {
+                validate_required("bucket", &oss.bucket)?;
+                validate_required("endpoint", &oss.endpoint)?;
+                validate_credentials(
+                    ("access_key_id", &oss.access_key_id),
+                    ("access_key_secret", &oss.access_key_secret),
+                )
+            }
+        }
+    }
+}
+
+fn validate_required(field: &str, value: &str) -> std::result::Result<(), FieldError> {
+    if value.is_empty() {
+        return Err(FieldError::new(field, "required by the object store"));
+    }
+    Ok(())
+}
+
+/// The key id and the secret of the object store must be set together.
+fn validate_credentials(
+    (id_field, id): (&str, &str),
+    (secret_field, secret): (&str, &str),
+) -> std::result::Result<(), FieldError> {
+    match (id.is_empty(), secret.is_empty()) {
+        (true, false) => Err(FieldError::new(
+            id_field,
+            format!("required with {secret_field}"),
+        )),
+        (false, true) => Err(FieldError::new(
+            secret_field,
+            format!("required with {id_field}"),
+        )),
+        _ => Ok(()),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Default, Deserialize)]
@@ -104,6 +173,10 @@ pub struct WalConfig {
     pub read_batch_size: usize,
     // whether to sync log file after every write
     pub sync_write: bool,
+    // whether tables can skip the wal by the `wal = 'disabled'` option
+    pub allow_disabling: bool,
+    // max number of tables replaying their wal concurrently on startup
+    pub replay_concurrency: usize,
 }
 
 impl Default for WalConfig {
@@ -115,6 +188,8 @@ impl Default for WalConfig {
             purge_interval: Duration::from_secs(600),
             read_batch_size: 128,
             sync_write: false,
+            allow_disabling: true,
+            replay_concurrency: DEFAULT_REPLAY_CONCURRENCY,
         }
     }
 }
@@ -168,6 +243,208 @@ impl Default for CompactionConfig {
     }
 }
 
+/// Options of the SST files written by flushes and compactions.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct SstConfig {
+    /// Compression of the columns without their own `COMPRESSION` option.
+    pub compression: String,
+    /// Encoding of the columns without their own `ENCODING` option.
+    pub encoding: String,
+}
+
+impl Default for SstConfig {
+    fn default() -> Self {
+        Self {
+            compression: "zstd".to_string(),
+            encoding: "plain".to_string(),
+        }
+    }
+}
+
+impl Validate for SstConfig {
+    fn validate(&self) -> std::result::Result<(), FieldError> {
+        if parse_compression(&self.compression).is_none() {
+            return Err(FieldError::new("compression", "unknown compression"));
+        }
+        if parse_encoding(&self.encoding).is_none() {
+            return Err(FieldError::new("encoding", "unknown encoding"));
+        }
+        Ok(())
+    }
+}
+
+/// Options for flushing regions of tables without `write_buffer_size`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct FlushConfig {
+    /// Whether to size the flush threshold of each region by its ingest rate, which is the
+    /// default. Uses a fixed write buffer size otherwise.
+    pub adaptive: bool,
+    /// Expected interval between two flushes of a region.
+    #[serde(with = "humantime_serde")]
+    pub target_interval: Duration,
+    /// Lower bound of the adaptive flush threshold.
+    pub min_write_buffer_size: ReadableSize,
+    /// Upper bound of the adaptive flush threshold.
+    pub max_write_buffer_size: ReadableSize,
+    /// Regions holding unflushed data older than this are flushed even if they are idle, so
+    /// the WAL can be purged.
+    #[serde(with = "humantime_serde")]
+    pub max_age: Duration,
+    /// Interval to check the age of unflushed data.
+    #[serde(with = "humantime_serde")]
+    pub age_check_interval: Duration,
+    /// Max time a write waits for the previous flush of its region before it's rejected as
+    /// throttled, so the clients can back off and retry. Writes wait for the flush if unset.
+    #[serde(with = "humantime_serde")]
+    pub max_write_stall: Option<Duration>,
+}
+
+impl Default for FlushConfig {
+    fn default() -> Self {
+        let adaptive_flush = AdaptiveFlushConfig::default();
+        Self {
+            adaptive: true,
+            target_interval: adaptive_flush.target_flush_interval,
+            min_write_buffer_size: adaptive_flush.min_write_buffer_size,
+            max_write_buffer_size: adaptive_flush.max_write_buffer_size,
+            max_age: adaptive_flush.max_memtable_age,
+            age_check_interval: adaptive_flush.age_check_interval,
+            max_write_stall: None,
+        }
+    }
+}
+
+/// Options for accounting the bytes stored in the object store by the tables.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct StorageUsageConfig {
+    /// Interval to sample the bytes stored by each table, the growth is reported against the
+    /// previous sample.
+    #[serde(with = "humantime_serde")]
+    pub sample_interval: Duration,
+}
+
+impl Default for StorageUsageConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Options of the recycle bin keeping the dropped tables.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct RecycleBinConfig {
+    /// Whether `DROP TABLE` moves the table to the recycle bin instead of deleting it, so it can
+    /// be brought back by `RESTORE TABLE`.
+    pub enable: bool,
+    /// Tables stay in the recycle bin for this long after being dropped, then they are deleted.
+    #[serde(with = "humantime_serde")]
+    pub retention: Duration,
+    /// Interval to delete the tables staying in the recycle bin longer than `retention`.
+    #[serde(with = "humantime_serde")]
+    pub reap_interval: Duration,
+}
+
+impl Default for RecycleBinConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            retention: Duration::from_secs(7 * 24 * 60 * 60),
+            reap_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// Options of the garbage collection of the orphans, the objects in the storage of the tables
+/// that no region references.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct OrphanGcConfig {
+    /// Whether to delete the orphans of all tables periodically. Orphans can still be found
+    /// and deleted per table by the admin API if disabled.
+    pub enable: bool,
+    /// Interval to delete the orphans of all tables.
+    #[serde(with = "humantime_serde")]
+    pub interval: Duration,
+    /// Min age of the orphans to delete, so the files written by in-progress flushes and
+    /// compactions are spared.
+    #[serde(with = "humantime_serde")]
+    pub safety_age: Duration,
+    /// Max number of orphans to delete per second across all regions of the datanode,
+    /// unlimited if not set.
+    pub max_deletions_per_sec: Option<u32>,
+}
+
+impl Default for OrphanGcConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            interval: Duration::from_secs(24 * 60 * 60),
+            safety_age: Duration::from_secs(6 * 60 * 60),
+            max_deletions_per_sec: Some(100),
+        }
+    }
+}
+
+impl Validate for OrphanGcConfig {
+    fn validate(&self) -> std::result::Result<(), FieldError> {
+        if self.safety_age.is_zero() {
+            return Err(FieldError::new("safety_age", "must be positive"));
+        }
+        Ok(())
+    }
+}
+
+/// Options of the tag dictionary shared by the SSTs of each region.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct TagDictionaryConfig {
+    /// Whether to write the string tags of new SSTs by the dictionary. SSTs written by the
+    /// dictionary are still readable after it's disabled.
+    pub enable: bool,
+    /// Max number of values of each tag in the dictionary, the others are stored in SSTs.
+    pub max_values_per_column: usize,
+    /// Whether the scans of tables encode the string tags with dictionaries, which are kept
+    /// encoded until the results leave the query engine.
+    pub encode_scans: bool,
+}
+
+impl Default for TagDictionaryConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            max_values_per_column: StorageTagDictionaryConfig::default().max_values_per_column,
+            encode_scans: false,
+        }
+    }
+}
+
+/// Options of the time-travel reads of the snapshots of regions.
+///
+/// The snapshots are kept as long as the versions of their rows, which only memtables keep: a
+/// flush drops the snapshots since the previous flush except the one the memtables are frozen
+/// at, and a compaction drops all the snapshots before it. So the retention is an upper bound.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct TimeTravelConfig {
+    /// How long the snapshots of regions are readable by time-travel reads, which are disabled
+    /// if it's zero.
+    #[serde(with = "humantime_serde")]
+    pub retention: Duration,
+}
+
+impl Default for TimeTravelConfig {
+    fn default() -> Self {
+        Self {
+            retention: StorageEngineConfig::default().time_travel_retention,
+        }
+    }
+}
+
 impl From<&DatanodeOptions> for SchedulerConfig {
     fn from(value: &DatanodeOptions) -> Self {
         Self {
@@ -178,6 +455,7 @@ impl From<&DatanodeOptions> for SchedulerConfig {
 
 impl From<&DatanodeOptions> for StorageEngineConfig {
     fn from(value: &DatanodeOptions) -> Self {
+        let default_config = StorageEngineConfig::default();
         Self {
             manifest_checkpoint_on_startup: value.storage.manifest.checkpoint_on_startup,
             manifest_checkpoint_margin: value.storage.manifest.checkpoint_margin,
@@ -185,6 +463,26 @@ impl From<&DatanodeOptions> for StorageEngineConfig {
             max_files_in_l0: value.storage.compaction.max_files_in_level0,
             max_purge_tasks: value.storage.compaction.max_purge_tasks,
             sst_write_buffer_size: value.storage.compaction.sst_write_buffer_size,
+            // The options are validated on loading.
+            sst_compression: parse_compression(&value.storage.sst.compression)
+                .unwrap_or(default_config.sst_compression),
+            sst_encoding: parse_encoding(&value.storage.sst.encoding)
+                .unwrap_or(default_config.sst_encoding),
+            adaptive_flush: value.storage.flush.adaptive.then(|| AdaptiveFlushConfig {
+                target_flush_interval: value.storage.flush.target_interval,
+                min_write_buffer_size: value.storage.flush.min_write_buffer_size,
+                max_write_buffer_size: value.storage.flush.max_write_buffer_size,
+                max_memtable_age: value.storage.flush.max_age,
+                age_check_interval: value.storage.flush.age_check_interval,
+            }),
+            allow_wal_disabled: value.wal.allow_disabling,
+            tag_dictionary: value.storage.tag_dictionary.enable.then(|| {
+                StorageTagDictionaryConfig {
+                    max_values_per_column: value.storage.tag_dictionary.max_values_per_column,
+                }
+            }),
+            time_travel_retention: value.storage.time_travel.retention,
+            max_write_stall: value.storage.flush.max_write_stall,
         }
     }
 }
@@ -222,6 +520,50 @@ impl ProcedureConfig {
     }
 }
 
+/// Options of the queries the datanode executes for the frontends.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct QueryConfig {
+    /// Target number of rows of the result batches sent to the frontends.
+    pub result_batch_rows: usize,
+    /// Target size of the result batches sent to the frontends.
+    pub result_batch_size: ReadableSize,
+    /// Rejects the queries of the time ranges before the retention boundaries of the tables,
+    /// instead of only warning about them.
+    pub reject_expired_ranges: bool,
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        Self {
+            result_batch_rows: DEFAULT_TARGET_ROWS,
+            result_batch_size: ReadableSize(DEFAULT_TARGET_BYTES as u64),
+            reject_expired_ranges: false,
+        }
+    }
+}
+
+impl QueryConfig {
+    pub fn rebatch_options(&self) -> RebatchOptions {
+        RebatchOptions {
+            target_rows: self.result_batch_rows,
+            target_bytes: self.result_batch_size.as_bytes() as usize,
+        }
+    }
+}
+
+impl Validate for QueryConfig {
+    fn validate(&self) -> std::result::Result<(), FieldError> {
+        if self.result_batch_rows == 0 {
+            return Err(FieldError::new("result_batch_rows", "must be positive"));
+        }
+        if self.result_batch_size.as_bytes() == 0 {
+            return Err(FieldError::new("result_batch_size", "must be positive"));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct DatanodeOptions {
@@ -238,6 +580,14 @@ pub struct DatanodeOptions {
     pub wal: WalConfig,
     pub storage: StorageConfig,
     pub procedure: ProcedureConfig,
+    pub query: QueryConfig,
+    /// Max number of tables whose stats are collected concurrently for heartbeats.
+    pub stat_concurrency: usize,
+    /// Column limits of the tables, checked on table creation and alteration.
+    pub column_limits: ColumnLimitsOptions,
+    /// How the names of schemas and tables are resolved by the local catalog of standalone
+    /// mode.
+    pub name_resolution: NameResolution,
 }
 
 impl Default for DatanodeOptions {
@@ -256,10 +606,24 @@ impl Default for DatanodeOptions {
             wal: WalConfig::default(),
             storage: StorageConfig::default(),
             procedure: ProcedureConfig::default(),
+            query: QueryConfig::default(),
+            stat_concurrency: DEFAULT_STAT_CONCURRENCY,
+            column_limits: ColumnLimitsOptions::default(),
+            name_resolution: NameResolution::default(),
         }
     }
 }
 
+impl Validate for DatanodeOptions {
+    fn validate(&self) -> std::result::Result<(), FieldError> {
+        validate_addr("rpc_addr", &self.rpc_addr)?;
+        validate_addr("mysql_addr", &self.mysql_addr)?;
+        validate_addr("http_opts.addr", &self.http_opts.addr)?;
+        self.storage.validate().map_err(|e| e.nested("storage"))?;
+        self.query.validate().map_err(|e| e.nested("query"))
+    }
+}
+
 /// Datanode service.
 pub struct Datanode {
     opts: DatanodeOptions,
@@ -283,8 +647,12 @@ impl Datanode {
 
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting datanode instance...");
-        self.start_instance().await?;
-        self.start_services().await
+        // Services start while the tables are replaying their WALs, so the tables already
+        // replayed are queryable before the whole instance is started. Requests to the tables
+        // still replaying fail with a retryable error.
+        let instance = self.instance.clone();
+        futures::try_join!(instance.start(), self.start_services())?;
+        Ok(())
     }
 
     /// Start only the internal component of datanode.
@@ -334,4 +702,14 @@ mod tests {
         let toml_string = toml::to_string(&opts).unwrap();
         let _parsed: DatanodeOptions = toml::from_str(&toml_string).unwrap();
     }
+
+    #[test]
+    fn test_validate_orphan_gc() {
+        let mut opts = StorageConfig::default();
+        opts.validate().unwrap();
+
+        opts.orphan_gc.safety_age = Duration::ZERO;
+        let err = opts.validate().unwrap_err();
+        assert_eq!("orphan_gc.safety_age", err.field);
+    }
 }
@@ -19,7 +19,9 @@ use common_procedure::ProcedureId;
 use serde_json::error::Error as JsonError;
 use snafu::Location;
 use storage::error::Error as StorageError;
+use store_api::storage::RegionNumber;
 use table::error::Error as TableError;
+use table::metadata::TableId;
 
 use crate::datanode::ObjectStoreConfig;
 
@@ -124,6 +126,31 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Table {} has id {} instead of the expected id {}",
+        table_name,
+        actual,
+        expected
+    ))]
+    TableIdMismatch {
+        table_name: String,
+        expected: TableId,
+        actual: TableId,
+        location: Location,
+    },
+
+    #[snafu(display("Table {} not found in the recycle bin", table_name))]
+    RecycledTableNotFound {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Table {} already exists", table_name))]
+    TableExists {
+        table_name: String,
+        location: Location,
+    },
+
     #[snafu(display("Column {} not found in table {}", column_name, table_name))]
     ColumnNotFound {
         column_name: String,
@@ -149,6 +176,19 @@ pub enum Error {
     #[snafu(display("Missing insert body"))]
     MissingInsertBody { location: Location },
 
+    #[snafu(display(
+        "Region {} not found in table {}, valid regions: {:?}",
+        region_number,
+        table_name,
+        regions
+    ))]
+    InvalidRegionNumber {
+        table_name: String,
+        region_number: RegionNumber,
+        regions: Vec<RegionNumber>,
+        location: Location,
+    },
+
     #[snafu(display("Failed to insert value to table: {}, source: {}", table_name, source))]
     Insert {
         table_name: String,
@@ -174,6 +214,20 @@ pub enum Error {
         source: TableError,
     },
 
+    #[snafu(display(
+        "Failed to collect orphans of table: {}, source: {}",
+        table_name,
+        source
+    ))]
+    GcOrphans {
+        table_name: String,
+        #[snafu(backtrace)]
+        source: TableError,
+    },
+
+    #[snafu(display("Safety age of the orphans to delete must be positive"))]
+    InvalidSafetyAge { location: Location },
+
     #[snafu(display("Failed to start server, source: {}", source))]
     StartServer {
         #[snafu(backtrace)]
@@ -217,6 +271,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to list objects under {}, source: {}", path, source))]
+    ListStorage {
+        path: String,
+        source: object_store::Error,
+        location: Location,
+    },
+
     #[snafu(display("Runtime resource error, source: {}", source))]
     RuntimeResource {
         #[snafu(backtrace)]
@@ -343,6 +404,12 @@ pub enum Error {
         source: common_grpc_expr::error::Error,
     },
 
+    #[snafu(display("Failed to check column limits: {}", source))]
+    CheckColumnLimits {
+        #[snafu(backtrace)]
+        source: common_grpc_expr::error::Error,
+    },
+
     #[snafu(display(
         "Table id provider not found, cannot execute SQL directly on datanode in distributed mode"
     ))]
@@ -372,23 +439,12 @@ pub enum Error {
     #[snafu(display("Cannot find requested database: {}-{}", catalog, schema))]
     DatabaseNotFound { catalog: String, schema: String },
 
-    #[snafu(display(
-        "Failed to build default value, column: {}, source: {}",
-        column,
-        source
-    ))]
-    ColumnDefaultValue {
-        column: String,
-        #[snafu(backtrace)]
-        source: datatypes::error::Error,
+    #[snafu(display("{}", source))]
+    InvalidInsertRows {
+        source: common_grpc_expr::validation::RowErrors,
+        location: Location,
     },
 
-    #[snafu(display(
-        "No valid default value can be built automatically, column: {}",
-        column,
-    ))]
-    ColumnNoneDefaultValue { column: String, location: Location },
-
     #[snafu(display("Failed to describe schema for given statement, source: {}", source))]
     DescribeStatement {
         #[snafu(backtrace)]
@@ -439,6 +495,60 @@ pub enum Error {
         source: BoxedError,
     },
 
+    #[snafu(display(
+        "Failed to operate region {} of table {}, source: {}",
+        region_number,
+        table_name,
+        source
+    ))]
+    OperateRegion {
+        table_name: String,
+        region_number: RegionNumber,
+        #[snafu(backtrace)]
+        source: TableError,
+    },
+
+    #[snafu(display(
+        "Failed to read region {} of table {}, source: {}",
+        region_number,
+        table_name,
+        source
+    ))]
+    ReadRegion {
+        table_name: String,
+        region_number: RegionNumber,
+        #[snafu(backtrace)]
+        source: common_recordbatch::error::Error,
+    },
+
+    #[snafu(display(
+        "Failed to copy rows of table {} to region {} on {}, source: {}",
+        table_name,
+        region_number,
+        addr,
+        source
+    ))]
+    CopyRegionRows {
+        table_name: String,
+        region_number: RegionNumber,
+        addr: String,
+        #[snafu(backtrace)]
+        source: client::Error,
+    },
+
+    #[snafu(display("Failed to convert column data type, source: {}", source))]
+    ColumnDataType {
+        #[snafu(backtrace)]
+        source: api::error::Error,
+    },
+
+    #[snafu(display("Invalid partition bound {}, source: {}", bound, source))]
+    InvalidPartitionBound {
+        bound: String,
+        location: Location,
+        source: JsonError,
+    },
+
     #[snafu(display("Failed to encode object into json, source: {}", source))]
     EncodeJson {
         location: Location,
@@ -459,20 +569,25 @@ impl ErrorExt for Error {
             | DescribeStatement { source } => source.status_code(),
 
             DecodeLogicalPlan { source } => source.status_code(),
-            NewCatalog { source } | RegisterSchema { source } => source.status_code(),
+            NewCatalog { source } | RegisterSchema { source } | Catalog { source } => {
+                source.status_code()
+            }
             FindTable { source, .. } => source.status_code(),
             CreateTable { source, .. } | GetTable { source, .. } | AlterTable { source, .. } => {
                 source.status_code()
             }
             DropTable { source, .. } => source.status_code(),
-            FlushTable { source, .. } => source.status_code(),
+            FlushTable { source, .. } | GcOrphans { source, .. } => source.status_code(),
 
             Insert { source, .. } => source.status_code(),
             Delete { source, .. } => source.status_code(),
             TableEngineNotFound { source, .. } | EngineProcedureNotFound { source, .. } => {
                 source.status_code()
             }
-            TableNotFound { .. } => StatusCode::TableNotFound,
+            TableNotFound { .. } | RecycledTableNotFound { .. } | TableIdMismatch { .. } => {
+                StatusCode::TableNotFound
+            }
+            TableExists { .. } => StatusCode::TableAlreadyExists,
             ColumnNotFound { .. } => StatusCode::TableColumnNotFound,
 
             ParseSqlValue { source, .. } | ParseSql { source, .. } => source.status_code(),
@@ -480,7 +595,8 @@ impl ErrorExt for Error {
             AlterExprToRequest { source, .. }
             | CreateExprToRequest { source }
             | DeleteExprToRequest { source }
-            | InsertData { source } => source.status_code(),
+            | InsertData { source }
+            | CheckColumnLimits { source } => source.status_code(),
 
             ConvertSchema { source, .. } | VectorComputation { source } => source.status_code(),
 
@@ -498,14 +614,22 @@ impl ErrorExt for Error {
             | SchemaExists { .. }
             | ParseTimestamp { .. }
             | MissingInsertBody { .. }
+            | InvalidRegionNumber { .. }
             | DatabaseNotFound { .. }
             | MissingNodeId { .. }
             | MissingMetasrvOpts { .. }
-            | ColumnNoneDefaultValue { .. }
+            | InvalidInsertRows { .. }
+            | InvalidSafetyAge { .. }
             | PrepareImmutableTable { .. } => StatusCode::InvalidArguments,
 
             EncodeJson { .. } => StatusCode::Unexpected,
 
+            OperateRegion { source, .. } => source.status_code(),
+            ReadRegion { source, .. } => source.status_code(),
+            CopyRegionRows { source, .. } => source.status_code(),
+            ColumnDataType { source } => source.status_code(),
+            InvalidPartitionBound { .. } => StatusCode::InvalidArguments,
+
             // TODO(yingwen): Further categorize http error.
             StartServer { .. }
             | ParseAddr { .. }
@@ -515,14 +639,13 @@ impl ErrorExt for Error {
             | RemoveDir { .. }
             | InsertSystemCatalog { .. }
             | RenameTable { .. }
-            | Catalog { .. }
             | MissingRequiredField { .. }
             | IncorrectInternalState { .. }
             | ShutdownServer { .. }
             | ShutdownInstance { .. }
             | CloseTableEngine { .. } => StatusCode::Internal,
 
-            InitBackend { .. } => StatusCode::StorageUnavailable,
+            InitBackend { .. } | ListStorage { .. } => StatusCode::StorageUnavailable,
 
             OpenLogStore { source } => source.status_code(),
             OpenStorageEngine { source } => source.status_code(),
@@ -530,7 +653,6 @@ impl ErrorExt for Error {
             MetaClientInit { source, .. } => source.status_code(),
             TableIdProviderNotFound { .. } => StatusCode::Unsupported,
             BumpTableId { source, .. } => source.status_code(),
-            ColumnDefaultValue { source, .. } => source.status_code(),
             UnrecognizedTableOption { .. } => StatusCode::InvalidArguments,
             RecoverProcedure { source, .. } | SubmitProcedure { source, .. } => {
                 source.status_code()
@@ -17,7 +17,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use api::v1::meta::{HeartbeatRequest, HeartbeatResponse, NodeStat, Peer};
-use catalog::{datanode_stat, CatalogManagerRef};
+use catalog::{datanode_stat, CatalogManagerRef, DEFAULT_STAT_CONCURRENCY};
 use common_telemetry::{error, info, trace, warn};
 use meta_client::client::{HeartbeatSender, MetaClient};
 use snafu::ResultExt;
@@ -32,6 +32,7 @@ pub struct HeartbeatTask {
     meta_client: Arc<MetaClient>,
     catalog_manager: CatalogManagerRef,
     interval: u64,
+    stat_concurrency: usize,
 }
 
 impl Drop for HeartbeatTask {
@@ -57,9 +58,16 @@ impl HeartbeatTask {
             meta_client,
             catalog_manager,
             interval: 5_000, // default interval is set to 5 secs
+            stat_concurrency: DEFAULT_STAT_CONCURRENCY,
         }
     }
 
+    /// Sets the max number of tables whose stats are collected concurrently.
+    pub fn with_stat_concurrency(mut self, concurrency: usize) -> Self {
+        self.stat_concurrency = concurrency.max(1);
+        self
+    }
+
     pub async fn create_streams(
         meta_client: &MetaClient,
         running: Arc<AtomicBool>,
@@ -101,12 +109,14 @@ impl HeartbeatTask {
         let node_id = self.node_id;
         let addr = resolve_addr(&self.server_addr, &self.server_hostname);
         let meta_client = self.meta_client.clone();
+        let stat_concurrency = self.stat_concurrency;
 
         let catalog_manager_clone = self.catalog_manager.clone();
         let mut tx = Self::create_streams(&meta_client, running.clone()).await?;
         common_runtime::spawn_bg(async move {
             while running.load(Ordering::Acquire) {
-                let (region_num, region_stats) = datanode_stat(&catalog_manager_clone).await;
+                let (region_num, region_stats) =
+                    datanode_stat(&catalog_manager_clone, stat_concurrency).await;
 
                 let req = HeartbeatRequest {
                     peer: Some(Peer {
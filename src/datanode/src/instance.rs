@@ -18,8 +18,10 @@ use std::time::Duration;
 use std::{fs, path};
 
 use catalog::remote::MetaKvBackend;
+use catalog::replay::ReplayProgressRef;
 use catalog::{CatalogManager, CatalogManagerRef, RegisterTableRequest};
 use common_base::readable_size::ReadableSize;
+use common_base::Plugins;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MIN_USER_TABLE_ID};
 use common_error::prelude::BoxedError;
 use common_grpc::channel_manager::{ChannelConfig, ChannelManager};
@@ -38,9 +40,10 @@ use object_store::cache_policy::LruCacheLayer;
 use object_store::layers::{LoggingLayer, MetricsLayer, RetryLayer, TracingLayer};
 use object_store::services::{Fs as FsBuilder, Oss as OSSBuilder, S3 as S3Builder};
 use object_store::{util, ObjectStore, ObjectStoreBuilder};
+use query::query_engine::options::QueryOptions;
 use query::query_engine::{QueryEngineFactory, QueryEngineRef};
 use servers::Mode;
-use session::context::QueryContext;
+use session::context::{QueryContext, QueryOrigin};
 use snafu::prelude::*;
 use storage::compaction::{CompactionHandler, CompactionSchedulerRef, SimplePicker};
 use storage::config::EngineConfig as StorageEngineConfig;
@@ -48,7 +51,7 @@ use storage::scheduler::{LocalScheduler, SchedulerConfig};
 use storage::EngineImpl;
 use store_api::logstore::LogStore;
 use table::engine::manager::MemoryTableEngineManager;
-use table::engine::{TableEngine, TableEngineProcedureRef};
+use table::engine::{TableEngine, TableEngineProcedureRef, TableEngineRef};
 use table::requests::FlushTableRequest;
 use table::table::numbers::NumbersTable;
 use table::table::TableIdProviderRef;
@@ -62,7 +65,10 @@ use crate::error::{
     NewCatalogSnafu, OpenLogStoreSnafu, RecoverProcedureSnafu, Result, ShutdownInstanceSnafu,
 };
 use crate::heartbeat::HeartbeatTask;
+use crate::orphan_gc::{OrphanCollector, OrphanCollectorRef};
+use crate::recycle_bin::{RecycleBinReaper, RecycleBinReaperRef};
 use crate::sql::{SqlHandler, SqlRequest};
+use crate::storage_usage::{StorageUsageTracker, StorageUsageTrackerRef};
 
 mod grpc;
 pub mod sql;
@@ -76,7 +82,13 @@ pub struct Instance {
     pub(crate) catalog_manager: CatalogManagerRef,
     pub(crate) table_id_provider: Option<TableIdProviderRef>,
     pub(crate) heartbeat_task: Option<HeartbeatTask>,
+    pub(crate) storage_usage: StorageUsageTrackerRef,
+    /// Reaper of the recycle bin, `None` if tables are dropped without the recycle bin.
+    pub(crate) recycle_bin_reaper: Option<RecycleBinReaperRef>,
+    pub(crate) orphan_collector: OrphanCollectorRef,
     procedure_manager: ProcedureManagerRef,
+    /// In standalone mode, the instance owns all the regions of its tables.
+    pub(crate) mode: Mode,
 }
 
 pub type InstanceRef = Arc<Instance>;
@@ -111,7 +123,9 @@ impl Instance {
         let log_store = Arc::new(create_log_store(&opts.wal).await?);
 
         let mito_engine = Arc::new(DefaultEngine::new(
-            TableEngineConfig::default(),
+            TableEngineConfig {
+                dictionary_tags: opts.storage.tag_dictionary.encode_scans,
+            },
             EngineImpl::new(
                 StorageEngineConfig::from(opts),
                 log_store.clone(),
@@ -144,6 +158,35 @@ impl Instance {
             .with_engine_procedures(engine_procedures),
         );
 
+        let procedure_manager = create_procedure_manager(&opts.procedure).await?;
+        // Register procedures of the mito engine.
+        mito_engine.register_procedure_loaders(&*procedure_manager);
+        // Register procedures of the file table engine.
+        immutable_file_engine.register_procedure_loaders(&*procedure_manager);
+
+        Self::new_with_engines(
+            opts,
+            meta_client,
+            object_store,
+            engine_manager,
+            mito_engine.clone(),
+            mito_engine,
+            procedure_manager,
+        )
+        .await
+    }
+
+    /// Creates the instance on the engines of the `engine_manager`, whose DDLs are executed by
+    /// the procedures of the `default_engine`.
+    pub(crate) async fn new_with_engines(
+        opts: &DatanodeOptions,
+        meta_client: Option<Arc<MetaClient>>,
+        object_store: ObjectStore,
+        engine_manager: Arc<MemoryTableEngineManager>,
+        default_engine: TableEngineRef,
+        default_engine_procedure: TableEngineProcedureRef,
+        procedure_manager: ProcedureManagerRef,
+    ) -> Result<Self> {
         // create remote catalog manager
         let (catalog_manager, table_id_provider) = match opts.mode {
             Mode::Standalone => {
@@ -152,13 +195,13 @@ impl Instance {
                     let table = NumbersTable::new(MIN_USER_TABLE_ID);
 
                     catalog
-                        .register_table(RegisterTableRequest {
-                            table_id: MIN_USER_TABLE_ID,
-                            table_name: table.table_info().name.to_string(),
-                            table: Arc::new(table),
-                            catalog: DEFAULT_CATALOG_NAME.to_string(),
-                            schema: DEFAULT_SCHEMA_NAME.to_string(),
-                        })
+                        .register_table(RegisterTableRequest::new(
+                            DEFAULT_CATALOG_NAME,
+                            DEFAULT_SCHEMA_NAME,
+                            &table.table_info().name,
+                            MIN_USER_TABLE_ID,
+                            Arc::new(table),
+                        ))
                         .await
                         .expect("Failed to register numbers");
 
@@ -170,7 +213,10 @@ impl Instance {
                     let catalog = Arc::new(
                         catalog::local::LocalCatalogManager::try_new(engine_manager.clone())
                             .await
-                            .context(CatalogSnafu)?,
+                            .context(CatalogSnafu)?
+                            .with_replay_concurrency(opts.wal.replay_concurrency)
+                            .with_column_limits(opts.column_limits.clone())
+                            .with_name_resolution(opts.name_resolution),
                     );
 
                     (
@@ -181,56 +227,92 @@ impl Instance {
             }
 
             Mode::Distributed => {
-                let catalog = Arc::new(catalog::remote::RemoteCatalogManager::new(
-                    engine_manager.clone(),
-                    opts.node_id.context(MissingNodeIdSnafu)?,
-                    Arc::new(MetaKvBackend {
-                        client: meta_client.as_ref().unwrap().clone(),
-                    }),
-                ));
+                let catalog = Arc::new(
+                    catalog::remote::RemoteCatalogManager::new(
+                        engine_manager.clone(),
+                        opts.node_id.context(MissingNodeIdSnafu)?,
+                        Arc::new(MetaKvBackend {
+                            client: meta_client.as_ref().unwrap().clone(),
+                        }),
+                    )
+                    .with_replay_concurrency(opts.wal.replay_concurrency)
+                    .with_column_limits(opts.column_limits.clone()),
+                );
                 (catalog as CatalogManagerRef, None)
             }
         };
 
-        let factory = QueryEngineFactory::new(catalog_manager.clone());
+        let storage_usage = Arc::new(StorageUsageTracker::new(
+            catalog_manager.clone(),
+            object_store.clone(),
+            opts.storage.usage.sample_interval,
+        ));
+        let orphan_collector = Arc::new(OrphanCollector::new(
+            catalog_manager.clone(),
+            &opts.storage.orphan_gc,
+        ));
+
+        let mut plugins = Plugins::new();
+        plugins.insert(QueryOptions {
+            // Only the results sent to the frontends over the network are worth re-batching.
+            result_rebatch: (opts.mode == Mode::Distributed).then(|| opts.query.rebatch_options()),
+            reject_expired_ranges: opts.query.reject_expired_ranges,
+            ..Default::default()
+        });
+        let factory =
+            QueryEngineFactory::new_with_plugins(catalog_manager.clone(), Arc::new(plugins));
         let query_engine = factory.query_engine();
 
         let heartbeat_task = match opts.mode {
             Mode::Standalone => None,
-            Mode::Distributed => Some(HeartbeatTask::new(
-                opts.node_id.context(MissingNodeIdSnafu)?,
-                opts.rpc_addr.clone(),
-                opts.rpc_hostname.clone(),
-                meta_client.as_ref().unwrap().clone(),
-                catalog_manager.clone(),
-            )),
+            Mode::Distributed => Some(
+                HeartbeatTask::new(
+                    opts.node_id.context(MissingNodeIdSnafu)?,
+                    opts.rpc_addr.clone(),
+                    opts.rpc_hostname.clone(),
+                    meta_client.as_ref().unwrap().clone(),
+                    catalog_manager.clone(),
+                )
+                .with_stat_concurrency(opts.stat_concurrency),
+            ),
         };
 
-        let procedure_manager = create_procedure_manager(&opts.procedure).await?;
-        // Register all procedures.
-        // Register procedures of the mito engine.
-        mito_engine.register_procedure_loaders(&*procedure_manager);
-        // Register procedures of the file table engine.
-        immutable_file_engine.register_procedure_loaders(&*procedure_manager);
         // Register procedures in table-procedure crate.
         table_procedure::register_procedure_loaders(
             catalog_manager.clone(),
-            mito_engine.clone(),
-            mito_engine.clone(),
+            default_engine_procedure,
+            default_engine,
             &*procedure_manager,
         );
 
+        // The frontends move the tables to the recycle bin and reap it in distributed mode, the
+        // datanodes drop the tables they are asked to.
+        let soft_drop = opts.storage.recycle_bin.enable && opts.mode == Mode::Standalone;
+        let sql_handler = SqlHandler::new(
+            engine_manager,
+            catalog_manager.clone(),
+            procedure_manager.clone(),
+        )
+        .with_soft_drop(soft_drop);
+        let recycle_bin_reaper = soft_drop.then(|| {
+            Arc::new(RecycleBinReaper::new(
+                catalog_manager.clone(),
+                Arc::new(sql_handler.clone()),
+                &opts.storage.recycle_bin,
+            ))
+        });
+
         Ok(Self {
             query_engine: query_engine.clone(),
-            sql_handler: SqlHandler::new(
-                engine_manager,
-                catalog_manager.clone(),
-                procedure_manager.clone(),
-            ),
+            sql_handler,
             catalog_manager,
             heartbeat_task,
+            storage_usage,
+            recycle_bin_reaper,
+            orphan_collector,
             table_id_provider,
             procedure_manager,
+            mode: opts.mode.clone(),
         })
     }
 
@@ -242,6 +324,11 @@ impl Instance {
         if let Some(task) = &self.heartbeat_task {
             task.start().await?;
         }
+        self.storage_usage.start();
+        if let Some(reaper) = &self.recycle_bin_reaper {
+            reaper.start();
+        }
+        self.orphan_collector.start();
 
         // Recover procedures after the catalog manager is started, so we can
         // ensure we can access all tables from the catalog manager.
@@ -252,6 +339,12 @@ impl Instance {
         Ok(())
     }
 
+    /// Returns the progress of replaying the tables when the instance starts, or `None` if the
+    /// tables are in memory.
+    pub fn replay_progress(&self) -> Option<ReplayProgressRef> {
+        self.catalog_manager.replay_progress()
+    }
+
     pub async fn shutdown(&self) -> Result<()> {
         if let Some(heartbeat_task) = &self.heartbeat_task {
             heartbeat_task
@@ -260,6 +353,11 @@ impl Instance {
                 .map_err(BoxedError::new)
                 .context(ShutdownInstanceSnafu)?;
         }
+        self.storage_usage.stop();
+        if let Some(reaper) = &self.recycle_bin_reaper {
+            reaper.stop();
+        }
+        self.orphan_collector.stop();
 
         self.flush_tables().await?;
 
@@ -295,14 +393,14 @@ impl Instance {
                 })
             })
             .collect::<Vec<_>>();
-        let flush_result = futures::future::try_join_all(
-            flush_requests
-                .into_iter()
-                .map(|request| self.sql_handler.execute(request, QueryContext::arc())),
-        )
-        .await
-        .map_err(BoxedError::new)
-        .context(ShutdownInstanceSnafu);
+        let flush_result =
+            futures::future::try_join_all(flush_requests.into_iter().map(|request| {
+                let query_ctx = QueryContext::new().with_origin(QueryOrigin::System);
+                self.sql_handler.execute(request, Arc::new(query_ctx))
+            }))
+            .await
+            .map_err(BoxedError::new)
+            .context(ShutdownInstanceSnafu);
         info!("Flushed all tables result: {}", flush_result.is_ok());
         flush_result?;
 
@@ -508,6 +606,7 @@ async fn new_metasrv_client(node_id: u64, meta_config: &MetaClientOptions) -> Re
         .enable_router()
         .enable_store()
         .channel_manager(channel_manager)
+        .router_max_retries(meta_config.max_retries)
         .build();
     meta_client
         .start(&meta_config.metasrv_addrs)
@@ -31,18 +31,20 @@ use query::parser::{PromQuery, QueryLanguageParser, QueryStatement};
 use query::plan::LogicalPlan;
 use query::query_engine::SqlStatementExecutor;
 use servers::query_handler::grpc::GrpcQueryHandler;
-use session::context::{QueryContext, QueryContextRef};
+use servers::Mode;
+use session::context::QueryContextRef;
 use snafu::prelude::*;
 use sql::statements::statement::Statement;
+use store_api::storage::RegionNumber;
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
 use table::engine::TableReference;
-use table::requests::CreateDatabaseRequest;
+use table::requests::{CreateDatabaseRequest, TableOptions};
 use table::table::adapter::DfTableProviderAdapter;
 
 use crate::error::{
     self, CatalogNotFoundSnafu, CatalogSnafu, DecodeLogicalPlanSnafu, DeleteExprToRequestSnafu,
-    DeleteSnafu, ExecuteLogicalPlanSnafu, ExecuteSqlSnafu, InsertSnafu, PlanStatementSnafu, Result,
-    SchemaNotFoundSnafu, TableNotFoundSnafu,
+    DeleteSnafu, ExecuteLogicalPlanSnafu, ExecuteSqlSnafu, InsertSnafu, InvalidRegionNumberSnafu,
+    PlanStatementSnafu, Result, SchemaNotFoundSnafu, TableNotFoundSnafu,
 };
 use crate::instance::Instance;
 
@@ -55,6 +57,8 @@ impl Instance {
         let req = CreateDatabaseRequest {
             db_name: expr.database_name,
             create_if_not_exists: expr.create_if_not_exists,
+            // Creating databases with default table options is only supported by SQL.
+            table_options: TableOptions::default(),
         };
         self.sql_handler.create_database(req, query_ctx).await
     }
@@ -77,7 +81,7 @@ impl Instance {
             .context(DecodeLogicalPlanSnafu)?;
 
         self.query_engine
-            .execute(LogicalPlan::DfPlan(logical_plan), QueryContext::arc())
+            .execute(LogicalPlan::DfPlan(logical_plan), ctx)
             .await
             .context(ExecuteLogicalPlanSnafu)
     }
@@ -138,12 +142,29 @@ impl Instance {
                 table_name: table_ref.to_string(),
             })?;
 
-        let request = common_grpc_expr::insert::to_table_insert_request(catalog, schema, request)
-            .context(error::InsertDataSnafu)?;
+        let mut request = common_grpc_expr::insert::to_table_insert_request(
+            catalog,
+            schema,
+            request,
+            &table.schema(),
+            ctx.write_mode(),
+            ctx.validation_mode(),
+        )
+        .context(error::InsertDataSnafu)?;
+        request.region_number = insert_region(
+            &self.mode,
+            &table_ref.to_string(),
+            &table.table_info().meta.region_numbers,
+            request.region_number,
+        )?;
 
         let affected_rows = table.insert(request).await.with_context(|_| InsertSnafu {
             table_name: table_ref.to_string(),
         })?;
+        let token = table.commit_token().with_context(|_| InsertSnafu {
+            table_name: table_ref.to_string(),
+        })?;
+        ctx.merge_commit_token(&token);
         Ok(Output::AffectedRows(affected_rows))
     }
 
@@ -162,12 +183,21 @@ impl Instance {
                 table_name: table_ref.to_string(),
             })?;
 
-        let request = common_grpc_expr::delete::to_table_delete_request(request)
-            .context(DeleteExprToRequestSnafu)?;
+        let request = common_grpc_expr::delete::to_table_delete_request(
+            catalog,
+            schema,
+            request,
+            &table.table_info().meta,
+        )
+        .context(DeleteExprToRequestSnafu)?;
 
         let affected_rows = table.delete(request).await.with_context(|_| DeleteSnafu {
             table_name: table_ref.to_string(),
         })?;
+        let token = table.commit_token().with_context(|_| DeleteSnafu {
+            table_name: table_ref.to_string(),
+        })?;
+        ctx.merge_commit_token(&token);
         Ok(Output::AffectedRows(affected_rows))
     }
 
@@ -266,6 +296,33 @@ impl SchemaProvider for DummySchemaProvider {
     }
 }
 
+/// Returns the region of the table to insert into for the `region_number` from the client,
+/// which must be one of the `regions` of the table.
+///
+/// The standalone instance owns the regions of its tables, so it ignores the region number from
+/// the client, which is only meaningful to a distributed frontend.
+fn insert_region(
+    mode: &Mode,
+    table_name: &str,
+    regions: &[RegionNumber],
+    region_number: RegionNumber,
+) -> Result<RegionNumber> {
+    if *mode == Mode::Standalone {
+        return Ok(regions.first().copied().unwrap_or(region_number));
+    }
+
+    // Tables without regions don't route the rows by region numbers.
+    ensure!(
+        regions.is_empty() || regions.contains(&region_number),
+        InvalidRegionNumberSnafu {
+            table_name,
+            region_number,
+            regions,
+        }
+    );
+    Ok(region_number)
+}
+
 async fn new_dummy_catalog_list(
     catalog_name: &str,
     schema_name: &str,
@@ -297,12 +354,14 @@ mod test {
         CreateDatabaseExpr, CreateTableExpr, QueryRequest,
     };
     use common_catalog::consts::MITO_ENGINE;
+    use common_error::prelude::{ErrorExt, StatusCode};
     use common_recordbatch::RecordBatches;
     use datatypes::prelude::*;
     use query::parser::QueryLanguageParser;
     use session::context::QueryContext;
 
     use super::*;
+    use crate::error::Error;
     use crate::tests::test_util::{self, MockInstance};
 
     async fn exec_selection(instance: &Instance, sql: &str) -> Output {
@@ -447,6 +506,8 @@ mod test {
                 },
             ],
             row_count: 3,
+            // The standalone instance ignores the region number from clients.
+            region_number: 42,
             ..Default::default()
         };
 
@@ -468,6 +529,35 @@ mod test {
         assert_eq!(recordbatches.pretty_print().unwrap(), expected);
     }
 
+    #[test]
+    fn test_insert_region() {
+        let regions = [0, 1, 2];
+        assert_eq!(
+            1,
+            insert_region(&Mode::Distributed, "demo", &regions, 1).unwrap()
+        );
+
+        let err = insert_region(&Mode::Distributed, "demo", &regions, 3).unwrap_err();
+        assert!(matches!(err, Error::InvalidRegionNumber { .. }), "{err:?}");
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        assert_eq!(
+            "Region 3 not found in table demo, valid regions: [0, 1, 2]",
+            err.to_string()
+        );
+
+        // Tables without regions accept any region number.
+        assert_eq!(
+            3,
+            insert_region(&Mode::Distributed, "demo", &[], 3).unwrap()
+        );
+
+        // The standalone instance overrides the region number from clients.
+        assert_eq!(
+            0,
+            insert_region(&Mode::Standalone, "demo", &regions, 3).unwrap()
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_handle_delete() {
         let instance = MockInstance::new("test_handle_delete").await;
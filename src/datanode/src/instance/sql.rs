@@ -20,18 +20,19 @@ use common_query::Output;
 use common_telemetry::logging::info;
 use common_telemetry::timer;
 use query::error::QueryExecutionSnafu;
-use query::parser::{PromQuery, QueryLanguageParser, QueryStatement};
+use query::parser::{PromQuery, QueryLanguageParser, QueryStatement, DEFAULT_MAX_POINTS};
 use query::query_engine::SqlStatementExecutor;
 use session::context::QueryContextRef;
 use snafu::prelude::*;
 use sql::ast::ObjectName;
 use sql::statements::statement::Statement;
+use sql::util::to_lowercase_options_map;
 use table::engine::TableReference;
-use table::requests::{CreateDatabaseRequest, DropTableRequest};
+use table::requests::{CreateDatabaseRequest, DropTableRequest, RestoreTableRequest, TableOptions};
 
 use crate::error::{
     self, BumpTableIdSnafu, ExecuteSqlSnafu, ExecuteStatementSnafu, NotSupportSqlSnafu,
-    PlanStatementSnafu, Result, TableIdProviderNotFoundSnafu,
+    PlanStatementSnafu, Result, TableIdProviderNotFoundSnafu, UnrecognizedTableOptionSnafu,
 };
 use crate::instance::Instance;
 use crate::metrics;
@@ -41,15 +42,23 @@ impl Instance {
     async fn do_execute_sql(&self, stmt: Statement, query_ctx: QueryContextRef) -> Result<Output> {
         match stmt {
             Statement::Insert(insert) => {
-                let request =
-                    SqlHandler::insert_to_request(self.catalog_manager.clone(), *insert, query_ctx)
-                        .await?;
-                self.sql_handler.insert(request).await
+                let request = SqlHandler::insert_to_request(
+                    self.catalog_manager.clone(),
+                    *insert,
+                    query_ctx.clone(),
+                )
+                .await?;
+                self.sql_handler.insert(request, &query_ctx).await
             }
             Statement::CreateDatabase(create_database) => {
+                let table_options = TableOptions::try_from_schema_options(
+                    &to_lowercase_options_map(&create_database.options),
+                )
+                .context(UnrecognizedTableOptionSnafu)?;
                 let request = CreateDatabaseRequest {
                     db_name: create_database.name.to_string(),
                     create_if_not_exists: create_database.if_not_exists,
+                    table_options,
                 };
 
                 info!("Creating a new database: {}", request.db_name);
@@ -123,13 +132,27 @@ impl Instance {
                     .execute(SqlRequest::DropTable(req), query_ctx)
                     .await
             }
+            Statement::RestoreTable(restore_table) => {
+                let (catalog_name, schema_name, table_name) =
+                    table_idents_to_full_name(restore_table.table_name(), query_ctx.clone())?;
+                let req = RestoreTableRequest {
+                    catalog_name,
+                    schema_name,
+                    table_name,
+                    new_table_name: restore_table.new_table_name().map(ToString::to_string),
+                };
+                self.sql_handler
+                    .execute(SqlRequest::RestoreTable(req), query_ctx)
+                    .await
+            }
             Statement::ShowCreateTable(show) => {
                 let (catalog, schema, table) =
                     table_idents_to_full_name(&show.table_name, query_ctx.clone())?;
                 let table_ref = TableReference::full(&catalog, &schema, &table);
                 let table = self.sql_handler.get_table(&table_ref).await?;
 
-                query::sql::show_create_table(table, None).context(ExecuteStatementSnafu)
+                query::sql::show_create_table(table, None, show.for_mysql)
+                    .context(ExecuteStatementSnafu)
             }
             _ => NotSupportSqlSnafu {
                 msg: format!("not supported to execute {stmt:?}"),
@@ -145,7 +168,8 @@ impl Instance {
     ) -> Result<Output> {
         let _timer = timer!(metrics::HANDLE_PROMQL_ELAPSED);
 
-        let stmt = QueryLanguageParser::parse_promql(promql).context(ExecuteSqlSnafu)?;
+        let stmt = QueryLanguageParser::parse_promql(promql, DEFAULT_MAX_POINTS)
+            .context(ExecuteSqlSnafu)?;
 
         let engine = self.query_engine();
         let plan = engine
@@ -175,7 +199,8 @@ impl Instance {
             end: "0".to_string(),
             step: "5m".to_string(),
         };
-        let mut stmt = QueryLanguageParser::parse_promql(&query).context(ExecuteSqlSnafu)?;
+        let mut stmt = QueryLanguageParser::parse_promql(&query, DEFAULT_MAX_POINTS)
+            .context(ExecuteSqlSnafu)?;
         match &mut stmt {
             QueryStatement::Sql(_) => unreachable!(),
             QueryStatement::Promql(eval_stmt) => {
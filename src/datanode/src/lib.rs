@@ -21,7 +21,11 @@ mod heartbeat;
 pub mod instance;
 pub mod metrics;
 mod mock;
+mod orphan_gc;
+pub mod recycle_bin;
+mod region;
 pub mod server;
 pub mod sql;
+mod storage_usage;
 #[cfg(test)]
 mod tests;
@@ -12,15 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use common_catalog::consts::MITO_ENGINE;
 use meta_client::client::{MetaClient, MetaClientBuilder};
 use meta_srv::mocks::MockInfo;
 use storage::compaction::noop::NoopCompactionScheduler;
+use table::engine::manager::MemoryTableEngineManager;
+use table::engine::{TableEngine, TableEngineProcedure};
 
 use crate::datanode::DatanodeOptions;
 use crate::error::Result;
-use crate::instance::Instance;
+use crate::instance::{create_procedure_manager, new_object_store, Instance};
 
 impl Instance {
     pub async fn with_mock_meta_client(opts: &DatanodeOptions) -> Result<Self> {
@@ -33,6 +37,33 @@ impl Instance {
         let compaction_scheduler = Arc::new(NoopCompactionScheduler::default());
         Instance::new_with(opts, Some(meta_client), compaction_scheduler).await
     }
+
+    /// Creates a standalone instance keeping all the tables in the `table_engine`, which
+    /// takes the place of mito so the DDLs and the system catalog work unchanged.
+    pub async fn with_table_engine<E>(opts: &DatanodeOptions, table_engine: Arc<E>) -> Result<Self>
+    where
+        E: TableEngine + TableEngineProcedure + 'static,
+    {
+        let object_store = new_object_store(&opts.storage.store).await?;
+        let engine_manager = Arc::new(
+            MemoryTableEngineManager::alias(MITO_ENGINE.to_string(), table_engine.clone())
+                .with_engine_procedures(HashMap::from([(
+                    MITO_ENGINE.to_string(),
+                    table_engine.clone() as _,
+                )])),
+        );
+        let procedure_manager = create_procedure_manager(&opts.procedure).await?;
+        Instance::new_with_engines(
+            opts,
+            None,
+            object_store,
+            engine_manager,
+            table_engine.clone(),
+            table_engine,
+            procedure_manager,
+        )
+        .await
+    }
 }
 
 async fn mock_meta_client(mock_info: MockInfo, node_id: u64) -> MetaClient {
@@ -0,0 +1,305 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Garbage collection of the orphans of the tables, the objects in the storage of the tables
+//! that no region references, like the SSTs left by crashed flushes and failed compactions.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use catalog::CatalogManagerRef;
+use common_catalog::format_full_table_name;
+use common_error::prelude::BoxedError;
+use common_telemetry::{error, info, warn};
+use servers::query_handler::{Orphan, OrphanGcHandler, OrphanGcReport};
+use snafu::{ensure, OptionExt, ResultExt};
+use store_api::storage::{
+    DeletionPacer, DeletionPacerRef, OrphanFile, OrphanGcRequest, OrphanKind,
+};
+
+use crate::datanode::OrphanGcConfig;
+use crate::error::{
+    CatalogSnafu, GcOrphansSnafu, InvalidSafetyAgeSnafu, Result, TableNotFoundSnafu,
+};
+use crate::instance::Instance;
+
+/// Finds the orphans of the tables on demand, and deletes the orphans of all tables
+/// periodically if enabled.
+pub struct OrphanCollector {
+    catalog_manager: CatalogManagerRef,
+    enable: bool,
+    interval: Duration,
+    safety_age: Duration,
+    /// Shared by the requests to all regions, so the deletions are paced across them.
+    deletion_pacer: Option<DeletionPacerRef>,
+    running: AtomicBool,
+}
+
+pub type OrphanCollectorRef = Arc<OrphanCollector>;
+
+impl OrphanCollector {
+    pub fn new(catalog_manager: CatalogManagerRef, config: &OrphanGcConfig) -> Self {
+        Self {
+            catalog_manager,
+            enable: config.enable,
+            interval: config.interval,
+            safety_age: config.safety_age,
+            deletion_pacer: config
+                .max_deletions_per_sec
+                .filter(|n| *n > 0)
+                .map(|n| Arc::new(DeletionPacer::new(n))),
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// Starts deleting the orphans in background if enabled, until the collector is stopped
+    /// or dropped.
+    pub fn start(self: &Arc<Self>) {
+        if !self.enable {
+            return;
+        }
+        if self
+            .running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            warn!("Orphan collector started multiple times");
+            return;
+        }
+
+        let collector = Arc::downgrade(self);
+        let mut interval = tokio::time::interval(self.interval);
+        common_runtime::spawn_bg(async move {
+            loop {
+                interval.tick().await;
+                let Some(collector) = collector.upgrade() else { break };
+                if !collector.running.load(Ordering::Acquire) {
+                    break;
+                }
+                let _ = collector.collect().await;
+            }
+            info!("Orphan collector stopped");
+        });
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Release);
+    }
+
+    fn new_request(&self, delete: bool, safety_age: Option<Duration>) -> OrphanGcRequest {
+        OrphanGcRequest {
+            delete,
+            safety_age: safety_age.unwrap_or(self.safety_age),
+            deletion_pacer: self.deletion_pacer.clone(),
+        }
+    }
+
+    /// Finds the orphans of the table, and deletes the ones older than `safety_age` if
+    /// `delete` is true. The configured safety age is used if `safety_age` is `None`.
+    pub async fn gc_table(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+        delete: bool,
+        safety_age: Option<Duration>,
+    ) -> Result<OrphanGcReport> {
+        ensure!(
+            !delete || safety_age != Some(Duration::ZERO),
+            InvalidSafetyAgeSnafu
+        );
+        let full_table_name = format_full_table_name(catalog, schema, table_name);
+        let table = self
+            .catalog_manager
+            .table(catalog, schema, table_name)
+            .await
+            .context(CatalogSnafu)?
+            .with_context(|| TableNotFoundSnafu {
+                table_name: &full_table_name,
+            })?;
+
+        let request = self.new_request(delete, safety_age);
+        let orphans = table.gc_orphans(&request).await.context(GcOrphansSnafu {
+            table_name: full_table_name,
+        })?;
+
+        Ok(OrphanGcReport {
+            catalog: catalog.to_string(),
+            schema: schema.to_string(),
+            table: table_name.to_string(),
+            orphans: orphans.into_iter().map(to_orphan).collect(),
+        })
+    }
+
+    /// Deletes the orphans of all tables older than the configured safety age, returns the
+    /// number of orphans deleted. Tables without regions, like the system tables, are skipped,
+    /// and tables failed to collect are left to the next run.
+    pub async fn collect(&self) -> usize {
+        let request = self.new_request(true, None);
+        let mut deleted = 0;
+
+        let Ok(catalog_names) = self.catalog_manager.catalog_names().await else { return deleted };
+        for catalog_name in catalog_names {
+            let Ok(Some(catalog)) = self.catalog_manager.catalog(&catalog_name).await else { continue };
+
+            let Ok(schema_names) = catalog.schema_names().await else { continue };
+            for schema_name in schema_names {
+                let Ok(Some(schema)) = catalog.schema(&schema_name).await else { continue };
+
+                let Ok(table_names) = schema.table_names().await else { continue };
+                for table_name in table_names {
+                    let Ok(Some(table)) = schema.table(&table_name).await else { continue };
+                    if table.region_stats().is_err() {
+                        continue;
+                    }
+
+                    match table.gc_orphans(&request).await {
+                        Ok(orphans) => {
+                            deleted += orphans.iter().filter(|orphan| orphan.deleted).count();
+                        }
+                        Err(e) => {
+                            error!(e; "Failed to collect orphans of table {}.{}.{}", catalog_name, schema_name, table_name)
+                        }
+                    }
+                }
+            }
+        }
+
+        info!("Deleted {} orphans of the tables", deleted);
+        deleted
+    }
+}
+
+fn to_orphan(orphan: OrphanFile) -> Orphan {
+    let kind = match orphan.kind {
+        OrphanKind::Sst => "sst",
+        OrphanKind::Manifest => "manifest",
+    };
+    Orphan {
+        path: orphan.path,
+        kind: kind.to_string(),
+        size: orphan.size,
+        age_secs: orphan.age.map(|age| age.as_secs()),
+        deleted: orphan.deleted,
+    }
+}
+
+#[async_trait]
+impl OrphanGcHandler for Instance {
+    async fn gc_orphans(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table: &str,
+        delete: bool,
+        safety_age: Option<Duration>,
+    ) -> servers::error::Result<OrphanGcReport> {
+        self.orphan_collector
+            .gc_table(catalog, schema, table, delete, safety_age)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::GcOrphansSnafu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_error::prelude::{ErrorExt, StatusCode};
+    use common_query::Output;
+    use query::parser::{QueryLanguageParser, QueryStatement};
+    use query::query_engine::SqlStatementExecutor;
+    use session::context::QueryContext;
+    use table::engine::{region_name, table_dir};
+
+    use super::*;
+    use crate::tests::test_util::MockInstance;
+
+    const ORPHAN: &str = "2b3b6e4f-7d6a-4c8e-9d2f-0e5c1a7b9f3d.parquet";
+
+    async fn execute_sql(instance: &Instance, sql: &str) -> Output {
+        let QueryStatement::Sql(stmt) = QueryLanguageParser::parse_sql(sql).unwrap() else { unreachable!() };
+        instance
+            .execute_sql(stmt, QueryContext::arc())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gc_orphans() {
+        let mock = MockInstance::new("test_gc_orphans").await;
+        let instance = mock.inner();
+        execute_sql(
+            instance,
+            "CREATE TABLE metrics(host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX, PRIMARY KEY(host))",
+        )
+        .await;
+        execute_sql(
+            instance,
+            "INSERT INTO metrics VALUES ('host1', 1.0, 1000), ('host2', 2.0, 2000)",
+        )
+        .await;
+        instance.flush_tables().await.unwrap();
+
+        let table = instance
+            .catalog_manager()
+            .table("greptime", "public", "metrics")
+            .await
+            .unwrap()
+            .unwrap();
+        let table_id = table.table_info().ident.table_id;
+        let orphan_path = mock
+            .data_dir()
+            .join(table_dir("greptime", "public", table_id))
+            .join(region_name(table_id, 0))
+            .join(ORPHAN);
+        std::fs::write(&orphan_path, b"orphan").unwrap();
+
+        let report = instance
+            .gc_orphans("greptime", "public", "metrics", false, None)
+            .await
+            .unwrap();
+        assert_eq!(1, report.orphans.len());
+        let orphan = &report.orphans[0];
+        assert!(orphan.path.ends_with(ORPHAN));
+        assert_eq!("sst", orphan.kind);
+        assert_eq!(6, orphan.size);
+        assert!(!orphan.deleted);
+
+        // The orphan is younger than the default safety age.
+        assert_eq!(0, instance.orphan_collector.collect().await);
+        assert!(orphan_path.exists());
+
+        let report = instance
+            .gc_orphans("greptime", "public", "metrics", true, Some(Duration::ZERO))
+            .await
+            .unwrap();
+        assert_eq!(1, report.orphans.len());
+        assert!(report.orphans[0].deleted);
+        assert!(!orphan_path.exists());
+
+        let report = instance
+            .gc_orphans("greptime", "public", "metrics", false, None)
+            .await
+            .unwrap();
+        assert!(report.orphans.is_empty());
+
+        let err = instance
+            .gc_orphans("greptime", "public", "not_exist", false, None)
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code());
+    }
+}
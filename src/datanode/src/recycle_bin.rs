@@ -0,0 +1,308 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reaping of the tables staying in the recycle bin longer than the retention.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use catalog::recycle_bin::recycled_tables;
+use catalog::CatalogManagerRef;
+use common_error::prelude::BoxedError;
+use common_telemetry::{error, info, warn};
+use common_time::util::current_time_millis;
+use table::requests::DropTableRequest;
+
+use crate::datanode::RecycleBinConfig;
+use crate::sql::SqlHandler;
+
+/// Drops the tables reaped from the recycle bin, by the [SqlHandler] in standalone mode and by
+/// the frontend in distributed mode.
+#[async_trait]
+pub trait RecycledTableDropper: Send + Sync {
+    async fn drop_recycled_table(&self, req: DropTableRequest) -> Result<(), BoxedError>;
+}
+
+pub type RecycledTableDropperRef = Arc<dyn RecycledTableDropper>;
+
+#[async_trait]
+impl RecycledTableDropper for SqlHandler {
+    async fn drop_recycled_table(&self, req: DropTableRequest) -> Result<(), BoxedError> {
+        self.drop_table(req)
+            .await
+            .map(|_| ())
+            .map_err(BoxedError::new)
+    }
+}
+
+/// Drops the tables in the recycle bin periodically once they are older than the retention.
+pub struct RecycleBinReaper {
+    catalog_manager: CatalogManagerRef,
+    dropper: RecycledTableDropperRef,
+    retention: Duration,
+    interval: Duration,
+    running: AtomicBool,
+}
+
+pub type RecycleBinReaperRef = Arc<RecycleBinReaper>;
+
+impl RecycleBinReaper {
+    pub fn new(
+        catalog_manager: CatalogManagerRef,
+        dropper: RecycledTableDropperRef,
+        config: &RecycleBinConfig,
+    ) -> Self {
+        Self {
+            catalog_manager,
+            dropper,
+            retention: config.retention,
+            interval: config.reap_interval,
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// Starts reaping in background, until the reaper is stopped or dropped.
+    pub fn start(self: &Arc<Self>) {
+        if self
+            .running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            warn!("Recycle bin reaper started multiple times");
+            return;
+        }
+
+        let reaper = Arc::downgrade(self);
+        let mut interval = tokio::time::interval(self.interval);
+        common_runtime::spawn_bg(async move {
+            loop {
+                interval.tick().await;
+                let Some(reaper) = reaper.upgrade() else { break };
+                if !reaper.running.load(Ordering::Acquire) {
+                    break;
+                }
+                let _ = reaper.reap(current_time_millis()).await;
+            }
+            info!("Recycle bin reaper stopped");
+        });
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Release);
+    }
+
+    /// Drops the tables dropped more than the retention before `now_millis`, returns the number
+    /// of tables dropped. Tables failed to drop are left to the next reaping.
+    pub async fn reap(&self, now_millis: i64) -> usize {
+        let retention_millis = self.retention.as_millis() as i64;
+        let mut reaped = 0;
+
+        let Ok(catalog_names) = self.catalog_manager.catalog_names().await else { return reaped };
+        for catalog_name in catalog_names {
+            let Ok(Some(catalog)) = self.catalog_manager.catalog(&catalog_name).await else { continue };
+
+            let Ok(schema_names) = catalog.schema_names().await else { continue };
+            for schema_name in schema_names {
+                let Ok(Some(schema)) = catalog.schema(&schema_name).await else { continue };
+
+                let Ok(tables) = recycled_tables(&schema).await else { continue };
+                for recycled in tables {
+                    if now_millis - recycled.dropped_at_millis < retention_millis {
+                        continue;
+                    }
+                    let req = DropTableRequest {
+                        catalog_name: catalog_name.clone(),
+                        schema_name: schema_name.clone(),
+                        table_name: recycled.recycled_name.clone(),
+                    };
+                    match self.dropper.drop_recycled_table(req).await {
+                        Ok(_) => {
+                            info!(
+                                "Reaped table {} dropped at {} from the recycle bin",
+                                recycled.recycled_name, recycled.dropped_at_millis
+                            );
+                            reaped += 1;
+                        }
+                        Err(e) => {
+                            error!(e; "Failed to reap table {} from the recycle bin", recycled.recycled_name)
+                        }
+                    }
+                }
+            }
+        }
+        reaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::greptime_request::Request as GrpcRequest;
+    use api::v1::query_request::Query;
+    use api::v1::QueryRequest;
+    use catalog::CatalogManager;
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use common_error::prelude::{ErrorExt, StatusCode};
+    use common_query::Output;
+    use common_recordbatch::RecordBatches;
+    use servers::query_handler::grpc::GrpcQueryHandler;
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::error::Result;
+    use crate::instance::Instance;
+    use crate::tests::test_util::MockInstance;
+
+    async fn execute_sql(instance: &Instance, sql: &str) -> Result<Output> {
+        let query = GrpcRequest::Query(QueryRequest {
+            query: Some(Query::Sql(sql.to_string())),
+        });
+        instance.do_query(query, QueryContext::arc()).await
+    }
+
+    async fn query(instance: &Instance, sql: &str) -> String {
+        let Output::Stream(stream) = execute_sql(instance, sql).await.unwrap() else { unreachable!() };
+        RecordBatches::try_collect(stream)
+            .await
+            .unwrap()
+            .pretty_print()
+            .unwrap()
+    }
+
+    async fn create_and_drop_table(instance: &Instance) {
+        execute_sql(
+            instance,
+            "CREATE TABLE metrics(host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX, PRIMARY KEY(host))",
+        )
+        .await
+        .unwrap();
+        execute_sql(
+            instance,
+            "INSERT INTO metrics VALUES ('host1', 1.0, 1000), ('host2', 2.0, 2000)",
+        )
+        .await
+        .unwrap();
+
+        let output = execute_sql(instance, "DROP TABLE metrics").await.unwrap();
+        assert!(matches!(output, Output::AffectedRows(1)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_drop_and_restore_table() {
+        let mock = MockInstance::new_with("test_drop_and_restore_table", |opts| {
+            opts.storage.recycle_bin.enable = true;
+        })
+        .await;
+        let instance = mock.inner();
+        create_and_drop_table(instance).await;
+
+        // The dropped table is invisible to queries but stays in the recycle bin.
+        let err = execute_sql(instance, "SELECT * FROM metrics")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+        let tables = query(
+            instance,
+            "SELECT table_name FROM information_schema.tables WHERE table_schema = 'public' ORDER BY table_name",
+        )
+        .await;
+        assert!(!tables.contains("metrics"), "{tables}");
+        let recycled = query(
+            instance,
+            "SELECT table_schema, table_name FROM information_schema.recycled_tables",
+        )
+        .await;
+        let expected = "\
++--------------+------------+
+| table_schema | table_name |
++--------------+------------+
+| public       | metrics    |
++--------------+------------+";
+        assert_eq!(expected, recycled);
+
+        // The table in the recycle bin can't be written.
+        let schema = instance
+            .catalog_manager()
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let recycled_name = recycled_tables(&schema).await.unwrap()[0]
+            .recycled_name
+            .clone();
+        let err = execute_sql(
+            instance,
+            &format!("INSERT INTO {recycled_name} VALUES ('host3', 3.0, 3000)"),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+        assert!(err.to_string().contains("is in the recycle bin"), "{err}");
+
+        execute_sql(instance, "RESTORE TABLE metrics AS restored")
+            .await
+            .unwrap();
+        let expected = "\
++-------+-----+
+| host  | cpu |
++-------+-----+
+| host1 | 1.0 |
+| host2 | 2.0 |
++-------+-----+";
+        assert_eq!(
+            expected,
+            query(instance, "SELECT host, cpu FROM restored ORDER BY host").await
+        );
+        let recycled = query(
+            instance,
+            "SELECT table_name FROM information_schema.recycled_tables",
+        )
+        .await;
+        assert!(!recycled.contains("metrics"), "{recycled}");
+
+        // Nothing left to restore.
+        let err = execute_sql(instance, "RESTORE TABLE metrics")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reap_recycle_bin() {
+        let mock = MockInstance::new_with("test_reap_recycle_bin", |opts| {
+            opts.storage.recycle_bin.enable = true;
+        })
+        .await;
+        let instance = mock.inner();
+        create_and_drop_table(instance).await;
+
+        let reaper = instance.recycle_bin_reaper.as_ref().unwrap();
+        // Tables within the retention are kept.
+        assert_eq!(0, reaper.reap(current_time_millis()).await);
+
+        let expired_at = current_time_millis() + reaper.retention.as_millis() as i64;
+        assert_eq!(1, reaper.reap(expired_at).await);
+        let recycled = query(
+            instance,
+            "SELECT table_name FROM information_schema.recycled_tables",
+        )
+        .await;
+        assert!(!recycled.contains("metrics"), "{recycled}");
+        let err = execute_sql(instance, "RESTORE TABLE metrics")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+    }
+}
@@ -0,0 +1,370 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handles the requests of the metasrv to the regions of tables, for changing the partitions
+//! of the tables.
+
+use api::helper::{push_vals, ColumnDataTypeWrapper};
+use api::v1::column::SemanticType;
+use api::v1::{Column, InsertRequest as GrpcInsertRequest};
+use async_trait::async_trait;
+use client::{Client, Database};
+use common_error::prelude::BoxedError;
+use common_recordbatch::RecordBatch;
+use common_telemetry::info;
+use datatypes::vectors::BooleanVector;
+use futures::StreamExt;
+use partition::partition::PartitionBound;
+use servers::query_handler::RegionHandler;
+use snafu::{ensure, OptionExt, ResultExt};
+use store_api::storage::RegionNumber;
+use table::engine::EngineContext;
+use table::metadata::TableInfo;
+use table::requests::{RegionRequest, RegionRequestKind};
+use table::TableRef;
+
+use crate::error::{
+    CatalogSnafu, ColumnDataTypeSnafu, ColumnNotFoundSnafu, CopyRegionRowsSnafu,
+    InvalidPartitionBoundSnafu, OperateRegionSnafu, ReadRegionSnafu, Result, TableNotFoundSnafu,
+    VectorComputationSnafu,
+};
+use crate::instance::Instance;
+
+#[async_trait]
+impl RegionHandler for Instance {
+    async fn handle_region_request(
+        &self,
+        request: RegionRequest,
+    ) -> servers::error::Result<Option<u64>> {
+        self.handle_region_request_inner(request)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::HandleRegionRequestSnafu)
+    }
+}
+
+impl Instance {
+    async fn handle_region_request_inner(&self, request: RegionRequest) -> Result<Option<u64>> {
+        let table_name = request.table_ref().to_string();
+        let region_number = request.region_number;
+        let table = self
+            .catalog_manager
+            .table(
+                &request.catalog_name,
+                &request.schema_name,
+                &request.table_name,
+            )
+            .await
+            .context(CatalogSnafu)?
+            .filter(|table| table.table_info().ident.table_id == request.table_id);
+        let Some(table) = table else {
+            // The region is dropped along with its table.
+            ensure!(
+                request.kind == RegionRequestKind::Drop,
+                TableNotFoundSnafu {
+                    table_name: &table_name,
+                }
+            );
+            return Ok(None);
+        };
+
+        let watermark = match &request.kind {
+            RegionRequestKind::Create => {
+                self.sql_handler
+                    .table_engine(table.clone())?
+                    .create_region(
+                        &EngineContext::default(),
+                        &request.table_ref(),
+                        region_number,
+                    )
+                    .await
+                    .context(OperateRegionSnafu {
+                        table_name: &table_name,
+                        region_number,
+                    })?;
+                info!("Created region {region_number} of table {table_name}");
+                None
+            }
+            RegionRequestKind::CopyTo {
+                target_addr,
+                target_region,
+                partition_columns,
+                lower,
+                upper,
+            } => {
+                let range = KeyRange::try_new(partition_columns, lower.as_deref(), upper)?;
+                let (watermark, rows) =
+                    copy_rows(&table, region_number, &range, target_addr, *target_region).await?;
+                info!(
+                    "Copied {} rows of table {} from region {} to region {} on {}",
+                    rows, table_name, region_number, target_region, target_addr
+                );
+                Some(watermark)
+            }
+            RegionRequestKind::Watermark => {
+                let watermark =
+                    table
+                        .committed_sequence(region_number)
+                        .context(OperateRegionSnafu {
+                            table_name: &table_name,
+                            region_number,
+                        })?;
+                Some(watermark)
+            }
+            RegionRequestKind::SetWritable { writable } => {
+                table
+                    .set_region_writable(region_number, *writable)
+                    .context(OperateRegionSnafu {
+                        table_name: &table_name,
+                        region_number,
+                    })?;
+                None
+            }
+            RegionRequestKind::Publish => {
+                table
+                    .publish_region(region_number)
+                    .await
+                    .context(OperateRegionSnafu {
+                        table_name: &table_name,
+                        region_number,
+                    })?;
+                None
+            }
+            RegionRequestKind::Drop => {
+                let dropped = self
+                    .sql_handler
+                    .table_engine(table.clone())?
+                    .drop_region(
+                        &EngineContext::default(),
+                        &request.table_ref(),
+                        region_number,
+                    )
+                    .await
+                    .context(OperateRegionSnafu {
+                        table_name: &table_name,
+                        region_number,
+                    })?;
+                if dropped {
+                    info!("Dropped region {region_number} of table {table_name}");
+                }
+                None
+            }
+        };
+        Ok(watermark)
+    }
+}
+
+/// Copies the rows of the region in the `range` into the region `target_region` of the table
+/// on the datanode at `target_addr`. Returns the sequence of the region the rows are copied up
+/// to, and the number of rows copied.
+async fn copy_rows(
+    table: &TableRef,
+    region_number: RegionNumber,
+    range: &KeyRange,
+    target_addr: &str,
+    target_region: RegionNumber,
+) -> Result<(u64, usize)> {
+    let table_info = table.table_info();
+    let database = Database::new(
+        &table_info.catalog_name,
+        &table_info.schema_name,
+        Client::with_urls([target_addr]),
+    );
+    let (mut stream, sequence) =
+        table
+            .scan_region(region_number)
+            .await
+            .context(OperateRegionSnafu {
+                table_name: &table_info.name,
+                region_number,
+            })?;
+
+    let mut copied = 0;
+    while let Some(batch) = stream.next().await {
+        let batch = batch.context(ReadRegionSnafu {
+            table_name: &table_info.name,
+            region_number,
+        })?;
+        let (columns, row_count) = rows_in_range(&batch, range, &table_info)?;
+        if row_count == 0 {
+            continue;
+        }
+
+        let request = GrpcInsertRequest {
+            table_name: table_info.name.clone(),
+            columns,
+            row_count: row_count as u32,
+            region_number: target_region,
+        };
+        let _ = database
+            .insert(request)
+            .await
+            .context(CopyRegionRowsSnafu {
+                table_name: &table_info.name,
+                region_number: target_region,
+                addr: target_addr,
+            })?;
+        copied += row_count;
+    }
+    Ok((sequence, copied))
+}
+
+/// Returns the columns of the rows of `batch` in the `range`, and the number of the rows.
+fn rows_in_range(
+    batch: &RecordBatch,
+    range: &KeyRange,
+    table_info: &TableInfo,
+) -> Result<(Vec<Column>, usize)> {
+    let schema = &table_info.meta.schema;
+    let key_vectors = range
+        .columns
+        .iter()
+        .map(|name| {
+            batch
+                .column_by_name(name)
+                .with_context(|| ColumnNotFoundSnafu {
+                    column_name: name,
+                    table_name: &table_info.name,
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let in_range = (0..batch.num_rows())
+        .map(|row| {
+            let key = key_vectors
+                .iter()
+                .map(|v| PartitionBound::Value(v.get(row)))
+                .collect::<Vec<_>>();
+            range.contains(&key)
+        })
+        .collect::<Vec<_>>();
+    let row_count = in_range.iter().filter(|x| **x).count();
+    if row_count == 0 {
+        return Ok((Vec::new(), 0));
+    }
+    let in_range = BooleanVector::from(in_range);
+
+    let columns = batch
+        .schema
+        .column_schemas()
+        .iter()
+        .zip(batch.columns())
+        .map(|(column_schema, vector)| {
+            let index = schema.column_index_by_name(&column_schema.name);
+            let semantic_type = if index.is_some() && index == schema.timestamp_index() {
+                SemanticType::Timestamp
+            } else if index.map_or(false, |i| table_info.meta.primary_key_indices.contains(&i)) {
+                SemanticType::Tag
+            } else {
+                SemanticType::Field
+            };
+            let datatype = ColumnDataTypeWrapper::try_from(vector.data_type())
+                .context(ColumnDataTypeSnafu)?
+                .datatype();
+            let mut column = Column {
+                column_name: column_schema.name.clone(),
+                semantic_type: semantic_type.into(),
+                datatype: datatype as i32,
+                ..Default::default()
+            };
+            let vector = vector.filter(&in_range).context(VectorComputationSnafu)?;
+            push_vals(&mut column, 0, vector);
+            Ok(column)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((columns, row_count))
+}
+
+/// The range of partition keys of a region, see [RegionRequestKind::CopyTo].
+struct KeyRange {
+    columns: Vec<String>,
+    lower: Option<Vec<PartitionBound>>,
+    upper: Vec<PartitionBound>,
+}
+
+impl KeyRange {
+    fn try_new(columns: &[String], lower: Option<&[String]>, upper: &[String]) -> Result<Self> {
+        let decode = |bounds: &[String]| {
+            bounds
+                .iter()
+                .map(|bound| {
+                    serde_json::from_str(bound).context(InvalidPartitionBoundSnafu { bound })
+                })
+                .collect::<Result<Vec<PartitionBound>>>()
+        };
+        Ok(Self {
+            columns: columns.to_vec(),
+            lower: lower.map(decode).transpose()?,
+            upper: decode(upper)?,
+        })
+    }
+
+    /// Whether the key is in the range, comparing the values of the partition columns in
+    /// order, the same as the partition rule of the table.
+    fn contains(&self, key: &[PartitionBound]) -> bool {
+        let above_lower = match &self.lower {
+            Some(lower) => key >= lower.as_slice(),
+            None => true,
+        };
+        above_lower && key < self.upper.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datatypes::value::Value;
+
+    use super::*;
+
+    fn bounds(values: &[Option<i32>]) -> Vec<String> {
+        values
+            .iter()
+            .map(|v| {
+                let bound = match v {
+                    Some(v) => PartitionBound::Value(Value::from(*v)),
+                    None => PartitionBound::MaxValue,
+                };
+                serde_json::to_string(&bound).unwrap()
+            })
+            .collect()
+    }
+
+    fn key(values: &[i32]) -> Vec<PartitionBound> {
+        values
+            .iter()
+            .map(|v| PartitionBound::Value(Value::from(*v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_key_range_contains() {
+        let columns = vec!["a".to_string(), "b".to_string()];
+        let range = KeyRange::try_new(
+            &columns,
+            Some(&bounds(&[Some(10), Some(5)])),
+            &bounds(&[Some(20), None]),
+        )
+        .unwrap();
+        assert!(!range.contains(&key(&[10, 4])));
+        assert!(range.contains(&key(&[10, 5])));
+        assert!(range.contains(&key(&[15, 0])));
+        assert!(range.contains(&key(&[20, 100])));
+        assert!(!range.contains(&key(&[21, 0])));
+
+        let range = KeyRange::try_new(&columns[..1], None, &bounds(&[Some(10)])).unwrap();
+        assert!(range.contains(&key(&[i32::MIN])));
+        assert!(!range.contains(&key(&[10])));
+
+        assert!(KeyRange::try_new(&columns, None, &["invalid".to_string()]).is_err());
+    }
+}
@@ -51,13 +51,16 @@ impl Services {
 
         Ok(Self {
             grpc_server: GrpcServer::new(
-                ServerGrpcQueryHandlerAdaptor::arc(instance),
+                ServerGrpcQueryHandlerAdaptor::arc(instance.clone()),
                 None,
                 None,
                 grpc_runtime,
-            ),
+            )
+            .with_region_handler(instance.clone()),
             http_server: HttpServerBuilder::new(opts.http_opts.clone())
                 .with_metrics_handler(MetricsHandler)
+                .with_storage_usage_handler(instance.clone())
+                .with_orphan_gc_handler(instance)
                 .build(),
         })
     }
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
+use catalog::ddl_lock::{DdlGuard, DdlLocks, DdlLocksRef};
+use catalog::local::NameResolution;
 use catalog::CatalogManagerRef;
 use common_error::prelude::BoxedError;
 use common_procedure::ProcedureManagerRef;
@@ -21,6 +25,7 @@ use session::context::QueryContextRef;
 use snafu::{OptionExt, ResultExt};
 use table::engine::manager::TableEngineManagerRef;
 use table::engine::{TableEngineProcedureRef, TableEngineRef, TableReference};
+use table::metadata::TableId;
 use table::requests::*;
 use table::TableRef;
 
@@ -36,6 +41,7 @@ mod create_external;
 mod drop_table;
 mod flush_table;
 pub(crate) mod insert;
+mod restore_table;
 
 #[derive(Debug)]
 pub enum SqlRequest {
@@ -43,6 +49,7 @@ pub enum SqlRequest {
     CreateDatabase(CreateDatabaseRequest),
     Alter(AlterTableRequest),
     DropTable(DropTableRequest),
+    RestoreTable(RestoreTableRequest),
     FlushTable(FlushTableRequest),
 }
 
@@ -52,6 +59,9 @@ pub struct SqlHandler {
     table_engine_manager: TableEngineManagerRef,
     catalog_manager: CatalogManagerRef,
     procedure_manager: ProcedureManagerRef,
+    /// Whether `DROP TABLE` moves the table to the recycle bin.
+    soft_drop: bool,
+    ddl_locks: DdlLocksRef,
 }
 
 impl SqlHandler {
@@ -64,27 +74,110 @@ impl SqlHandler {
             table_engine_manager,
             catalog_manager,
             procedure_manager,
+            soft_drop: false,
+            ddl_locks: Arc::new(DdlLocks::default()),
         }
     }
 
+    pub fn with_soft_drop(mut self, soft_drop: bool) -> Self {
+        self.soft_drop = soft_drop;
+        self
+    }
+
     // TODO(LFC): Refactor consideration: a context awareness "Planner".
     // Now we have some query related state (like current using database in session context), maybe
     // we could create a new struct called `Planner` that stores context and handle these queries
     // there, instead of executing here in a "static" fashion.
     pub async fn execute(&self, request: SqlRequest, query_ctx: QueryContextRef) -> Result<Output> {
-        let result = match request {
-            SqlRequest::CreateTable(req) => self.create_table(req).await,
-            SqlRequest::CreateDatabase(req) => self.create_database(req, query_ctx.clone()).await,
-            SqlRequest::Alter(req) => self.alter_table(req).await,
-            SqlRequest::DropTable(req) => self.drop_table(req).await,
-            SqlRequest::FlushTable(req) => self.flush_table(req).await,
-        };
+        let result = self.execute_resolved(request, query_ctx.clone()).await;
         if let Err(e) = &result {
             error!(e; "{query_ctx}");
         }
         result
     }
 
+    async fn execute_resolved(
+        &self,
+        request: SqlRequest,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        match request {
+            SqlRequest::CreateTable(mut req) => {
+                self.resolve_schema_name(&req.catalog_name, &mut req.schema_name)
+                    .await?;
+                self.create_table(req).await
+            }
+            SqlRequest::CreateDatabase(req) => self.create_database(req, query_ctx).await,
+            SqlRequest::Alter(mut req) => {
+                self.resolve_table_name(
+                    &req.catalog_name,
+                    &mut req.schema_name,
+                    &mut req.table_name,
+                )
+                .await?;
+                self.alter_table(req).await
+            }
+            SqlRequest::DropTable(mut req) => {
+                self.resolve_table_name(
+                    &req.catalog_name,
+                    &mut req.schema_name,
+                    &mut req.table_name,
+                )
+                .await?;
+                self.drop_table(req).await
+            }
+            SqlRequest::RestoreTable(req) => self.restore_table(req).await,
+            SqlRequest::FlushTable(req) => self.flush_table(req).await,
+        }
+    }
+
+    /// Replaces the name of the schema with the one it's registered as, which differs from the
+    /// given one if the catalog doesn't match names exactly, as the engines know the tables by
+    /// the registered names.
+    async fn resolve_schema_name(&self, catalog: &str, schema: &mut String) -> Result<()> {
+        let resolution = self.catalog_manager.name_resolution();
+        if resolution == NameResolution::Exact {
+            return Ok(());
+        }
+        let catalog = self
+            .catalog_manager
+            .catalog(catalog)
+            .await
+            .context(error::CatalogSnafu)?;
+        let Some(catalog) = catalog else { return Ok(()) };
+        let schema_names = catalog.schema_names().await.context(error::CatalogSnafu)?;
+        if let Some(name) = schema_names
+            .into_iter()
+            .find(|name| resolution.matches(name, schema))
+        {
+            *schema = name;
+        }
+        Ok(())
+    }
+
+    /// Replaces the names of the schema and the table with the ones they are registered as,
+    /// see [SqlHandler::resolve_schema_name]. The names are kept if the table is not found.
+    async fn resolve_table_name(
+        &self,
+        catalog: &str,
+        schema: &mut String,
+        table: &mut String,
+    ) -> Result<()> {
+        if self.catalog_manager.name_resolution() == NameResolution::Exact {
+            return Ok(());
+        }
+        self.resolve_schema_name(catalog, schema).await?;
+        if let Some(found) = self
+            .catalog_manager
+            .table(catalog, schema, table)
+            .await
+            .context(error::CatalogSnafu)?
+        {
+            *table = found.table_info().name.clone();
+        }
+        Ok(())
+    }
+
     pub async fn get_table(&self, table_ref: &TableReference<'_>) -> Result<TableRef> {
         let TableReference {
             catalog,
@@ -102,6 +195,24 @@ impl SqlHandler {
         Ok(table)
     }
 
+    /// Returns the locks held by the DDL on existing tables.
+    pub fn ddl_locks(&self) -> &DdlLocksRef {
+        &self.ddl_locks
+    }
+
+    /// Locks the tables of the DDL described by `description`, waits for the DDL running on
+    /// them to complete.
+    pub(crate) async fn lock_ddl(
+        &self,
+        table_ids: &[TableId],
+        description: String,
+    ) -> Result<DdlGuard> {
+        self.ddl_locks
+            .lock(table_ids, description)
+            .await
+            .context(error::CatalogSnafu)
+    }
+
     pub fn table_engine_manager(&self) -> TableEngineManagerRef {
         self.table_engine_manager.clone()
     }
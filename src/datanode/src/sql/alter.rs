@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_grpc_expr::column_limits;
 use common_procedure::{watcher, ProcedureWithId};
 use common_query::Output;
 use common_telemetry::logging::info;
@@ -27,14 +28,33 @@ use crate::sql::SqlHandler;
 
 impl SqlHandler {
     pub(crate) async fn alter_table(&self, req: AlterTableRequest) -> Result<Output> {
-        let table_name = req.table_name.clone();
-        let table_ref = TableReference {
-            catalog: &req.catalog_name,
-            schema: &req.schema_name,
-            table: &table_name,
-        };
+        let table = self.get_table(&req.table_ref()).await?;
+        let _guard = self
+            .lock_ddl(
+                &[table.table_info().ident.table_id],
+                format!("ALTER TABLE {}", req.table_ref()),
+            )
+            .await?;
+        self.alter_table_locked(req).await
+    }
 
-        let table = self.get_table(&table_ref).await?;
+    /// Alters the table by procedure, the caller holds the DDL lock of the table.
+    pub(crate) async fn alter_table_locked(&self, req: AlterTableRequest) -> Result<Output> {
+        let table_name = req.table_name.clone();
+        // Gets the table again as the one before locking may have been altered or dropped.
+        let table = self.get_table(&req.table_ref()).await?;
+        if let AlterKind::AddColumns { columns } = &req.alter_kind {
+            let limits = self
+                .catalog_manager
+                .column_limits()
+                .limits_of(&req.schema_name);
+            let new_columns = columns
+                .iter()
+                .map(|column| column.column_schema.name.as_str())
+                .collect::<Vec<_>>();
+            column_limits::check_new_columns(&limits, table.schema().num_columns(), &new_columns)
+                .context(error::CheckColumnLimitsSnafu)?;
+        }
         let engine_procedure = self.engine_procedure(table)?;
 
         let procedure =
@@ -82,6 +102,21 @@ impl SqlHandler {
             AlterTableOperation::RenameTable { new_table_name } => AlterKind::RenameTable {
                 new_table_name: new_table_name.clone(),
             },
+            AlterTableOperation::SetColumnOptions {
+                name,
+                encoding,
+                compression,
+            } => AlterKind::SetColumnOptions {
+                name: name.value.clone(),
+                encoding: encoding.clone(),
+                compression: compression.clone(),
+            },
+            AlterTableOperation::Repartition { .. } => {
+                return error::InvalidSqlSnafu {
+                    msg: "only distributed tables can be repartitioned",
+                }
+                .fail()
+            }
         };
         Ok(AlterTableRequest {
             catalog_name: table_ref.catalog.to_string(),
@@ -167,6 +202,30 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_alter_to_request_with_setting_column_options() {
+        let alter_table = parse_sql("ALTER TABLE test_table ALTER host SET ENCODING 'dictionary'");
+        let req = SqlHandler::alter_to_request(
+            alter_table,
+            TableReference::full("greptime", "public", "test_table"),
+        )
+        .unwrap();
+        assert_eq!(req.table_name, "test_table");
+
+        match req.alter_kind {
+            AlterKind::SetColumnOptions {
+                name,
+                encoding,
+                compression,
+            } => {
+                assert_eq!("host", name);
+                assert_eq!(Some("dictionary".to_string()), encoding);
+                assert_eq!(None, compression);
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_alter_table_by_procedure() {
         let instance = MockInstance::new("alter_table_by_procedure").await;
@@ -202,5 +261,70 @@ mod tests {
             .await
             .unwrap();
         assert!(matches!(output, Output::AffectedRows(0)));
+
+        // Set the storage options of a column.
+        let sql = r#"alter table test_alter alter column cpu set compression('ZSTD')"#;
+        let stmt = match QueryLanguageParser::parse_sql(sql).unwrap() {
+            QueryStatement::Sql(sql) => sql,
+            _ => unreachable!(),
+        };
+        let output = instance
+            .inner()
+            .execute_sql(stmt, QueryContext::arc())
+            .await
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(0)));
+        let table = instance
+            .inner()
+            .sql_handler()
+            .get_table(&TableReference::full("greptime", "public", "test_alter"))
+            .await
+            .unwrap();
+        let cpu = table.schema().column_schema_by_name("cpu").unwrap().clone();
+        assert_eq!(Some("zstd"), cpu.compression());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_alter_table() {
+        let instance = MockInstance::new("concurrent_alter_table").await;
+        let instance = instance.inner();
+        let execute_sql = move |sql: &str| {
+            let stmt = match QueryLanguageParser::parse_sql(sql).unwrap() {
+                QueryStatement::Sql(sql) => sql,
+                _ => unreachable!(),
+            };
+            instance.execute_sql(stmt, QueryContext::arc())
+        };
+
+        let output = execute_sql(
+            r#"create table test_alter(
+                    host string,
+                    ts timestamp,
+                    cpu double default 0,
+                    TIME INDEX (ts),
+                    PRIMARY KEY(host)
+                ) engine=mito with(regions=1);"#,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(output, Output::AffectedRows(0)));
+
+        // Both alters compute the new schema from the one altered by the other.
+        let (memory, disk) = tokio::join!(
+            execute_sql("alter table test_alter add column memory double"),
+            execute_sql("alter table test_alter add column disk double"),
+        );
+        assert!(matches!(memory.unwrap(), Output::AffectedRows(0)));
+        assert!(matches!(disk.unwrap(), Output::AffectedRows(0)));
+
+        let sql_handler = instance.sql_handler();
+        let table = sql_handler
+            .get_table(&TableReference::full("greptime", "public", "test_alter"))
+            .await
+            .unwrap();
+        let schema = table.schema();
+        assert!(schema.column_schema_by_name("memory").is_some());
+        assert!(schema.column_schema_by_name("disk").is_some());
+        assert!(sql_handler.ddl_locks().processes().is_empty());
     }
 }
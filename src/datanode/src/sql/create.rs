@@ -15,6 +15,7 @@
 use std::collections::HashMap;
 
 use catalog::RegisterSchemaRequest;
+use common_grpc_expr::column_limits;
 use common_procedure::{watcher, ProcedureWithId};
 use common_query::Output;
 use common_telemetry::tracing::info;
@@ -31,9 +32,9 @@ use table::requests::*;
 use table_procedure::CreateTableProcedure;
 
 use crate::error::{
-    self, CatalogSnafu, ConstraintNotSupportedSnafu, EngineProcedureNotFoundSnafu,
-    IllegalPrimaryKeysDefSnafu, KeyColumnNotFoundSnafu, RegisterSchemaSnafu, Result,
-    SchemaExistsSnafu, SubmitProcedureSnafu, TableEngineNotFoundSnafu,
+    self, CatalogSnafu, CheckColumnLimitsSnafu, ConstraintNotSupportedSnafu,
+    EngineProcedureNotFoundSnafu, IllegalPrimaryKeysDefSnafu, KeyColumnNotFoundSnafu,
+    RegisterSchemaSnafu, Result, SchemaExistsSnafu, SubmitProcedureSnafu, TableEngineNotFoundSnafu,
     UnrecognizedTableOptionSnafu, WaitProcedureSnafu,
 };
 use crate::sql::SqlHandler;
@@ -63,6 +64,7 @@ impl SqlHandler {
         let reg_req = RegisterSchemaRequest {
             catalog,
             schema: schema.clone(),
+            table_options: req.table_options,
         };
         self.catalog_manager
             .register_schema(reg_req)
@@ -73,7 +75,33 @@ impl SqlHandler {
         Ok(Output::AffectedRows(1))
     }
 
-    pub(crate) async fn create_table(&self, req: CreateTableRequest) -> Result<Output> {
+    pub(crate) async fn create_table(&self, mut req: CreateTableRequest) -> Result<Output> {
+        // A table still replaying isn't registered yet, it must not be created again.
+        if let Some(progress) = self.catalog_manager.replay_progress() {
+            progress
+                .ensure_not_pending(&req.catalog_name, &req.schema_name, &req.table_name)
+                .context(CatalogSnafu)?;
+        }
+
+        if let Some(schema) = self
+            .catalog_manager
+            .schema(&req.catalog_name, &req.schema_name)
+            .await
+            .context(CatalogSnafu)?
+        {
+            let schema_options = schema.default_table_options().await.context(CatalogSnafu)?;
+            req.table_options
+                .inherit(&schema_options)
+                .context(UnrecognizedTableOptionSnafu)?;
+        }
+
+        let limits = self
+            .catalog_manager
+            .column_limits()
+            .limits_of(&req.schema_name);
+        column_limits::check_new_table(&limits, req.schema.column_schemas.len())
+            .context(CheckColumnLimitsSnafu)?;
+
         let table_name = req.table_name.clone();
         let table_engine =
             self.table_engine_manager
@@ -256,6 +284,7 @@ mod tests {
     use sql::dialect::GenericDialect;
     use sql::parser::ParserContext;
     use sql::statements::statement::Statement;
+    use table::column_limits::ColumnLimitsOptions;
 
     use super::*;
     use crate::error::Error;
@@ -499,4 +528,44 @@ mod tests {
             .unwrap();
         assert!(matches!(output, Output::AffectedRows(0)));
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_column_limits() {
+        let instance = MockInstance::new_with("test_column_limits", |opts| {
+            opts.column_limits = ColumnLimitsOptions {
+                max_columns: 3,
+                max_added_columns: 2,
+                ..Default::default()
+            };
+        })
+        .await;
+        let execute_sql = |sql: &str| {
+            let Ok(QueryStatement::Sql(stmt)) = QueryLanguageParser::parse_sql(sql) else { unreachable!() };
+            instance.inner().execute_sql(stmt, QueryContext::arc())
+        };
+
+        // The datanode checks the DDLs itself, as they may come from frontends configured
+        // differently.
+        let err = execute_sql(
+            "CREATE TABLE demo(host STRING, cpu DOUBLE, memory DOUBLE, ts TIMESTAMP TIME INDEX)",
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("Failed to check column limits"),
+            "{err}"
+        );
+
+        let _ = execute_sql("CREATE TABLE demo(host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX)")
+            .await
+            .unwrap();
+        let err = execute_sql("ALTER TABLE demo ADD COLUMN memory DOUBLE")
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Adding columns [memory] to a table of 3 columns"),
+            "{err}"
+        );
+    }
 }
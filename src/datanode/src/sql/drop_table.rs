@@ -12,27 +12,103 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use catalog::recycle_bin::{is_recycled_table_name, RecycledTable};
+use common_catalog::consts::MITO_ENGINE;
 use common_procedure::{watcher, ProcedureWithId};
 use common_query::Output;
 use common_telemetry::info;
-use snafu::ResultExt;
-use table::engine::TableReference;
-use table::requests::DropTableRequest;
+use common_time::util::current_time_millis;
+use snafu::{ensure, ResultExt};
+use table::metadata::TableId;
+use table::requests::{AlterKind, AlterTableRequest, DropTableRequest};
+use table::TableRef;
 use table_procedure::DropTableProcedure;
 
 use crate::error::{self, Result};
 use crate::sql::SqlHandler;
 
 impl SqlHandler {
+    /// Drops the table, or moves it to the recycle bin if soft drop is enabled. Tables already
+    /// in the recycle bin and the external tables, which only reference their files, are always
+    /// dropped.
     pub(crate) async fn drop_table(&self, req: DropTableRequest) -> Result<Output> {
-        let table_name = req.table_name.clone();
-        let table_ref = TableReference {
-            catalog: &req.catalog_name,
-            schema: &req.schema_name,
-            table: &table_name,
-        };
+        self.drop_table_with_id(req, None).await
+    }
+
+    /// Drops the table only if its id is `table_id`, so a table created by another DDL under
+    /// the same name after the table of `table_id` was dropped is left alone.
+    pub async fn drop_table_by_id(
+        &self,
+        req: DropTableRequest,
+        table_id: TableId,
+    ) -> Result<Output> {
+        self.drop_table_with_id(req, Some(table_id)).await
+    }
+
+    async fn drop_table_with_id(
+        &self,
+        req: DropTableRequest,
+        expected_table_id: Option<TableId>,
+    ) -> Result<Output> {
+        let table = self.get_table(&req.table_ref()).await?;
+        let _guard = self
+            .lock_ddl(
+                &[table.table_info().ident.table_id],
+                format!("DROP TABLE {}", req.table_ref()),
+            )
+            .await?;
+        // Gets the table again as it may have been altered or dropped while waiting for the lock.
+        let table = self.get_table(&req.table_ref()).await?;
+        if let Some(expected) = expected_table_id {
+            let actual = table.table_info().ident.table_id;
+            ensure!(
+                actual == expected,
+                error::TableIdMismatchSnafu {
+                    table_name: req.table_ref().to_string(),
+                    expected,
+                    actual,
+                }
+            );
+        }
+        if self.soft_drop
+            && table.table_info().meta.engine == MITO_ENGINE
+            && !is_recycled_table_name(&req.table_name)
+        {
+            return self.recycle_table(req, table).await;
+        }
+        self.purge_table(req, table).await
+    }
+
+    /// Renames the table to its name in the recycle bin, which keeps its data until it's
+    /// restored or reaped.
+    async fn recycle_table(&self, req: DropTableRequest, table: TableRef) -> Result<Output> {
+        let recycled = RecycledTable::new(
+            &req.table_name,
+            table.table_info().ident.table_id,
+            current_time_millis(),
+        );
+        info!(
+            "Move table {} to the recycle bin as {}",
+            req.table_ref(),
+            recycled.recycled_name
+        );
+
+        let _ = self
+            .alter_table_locked(AlterTableRequest {
+                catalog_name: req.catalog_name,
+                schema_name: req.schema_name,
+                table_name: req.table_name,
+                alter_kind: AlterKind::RenameTable {
+                    new_table_name: recycled.recycled_name,
+                },
+            })
+            .await?;
+        Ok(Output::AffectedRows(1))
+    }
 
-        let table = self.get_table(&table_ref).await?;
+    /// Drops the table and deletes its data.
+    async fn purge_table(&self, req: DropTableRequest, table: TableRef) -> Result<Output> {
+        let table_name = req.table_name.clone();
         let engine_procedure = self.engine_procedure(table)?;
 
         let procedure =
@@ -59,9 +135,12 @@ impl SqlHandler {
 
 #[cfg(test)]
 mod tests {
+    use catalog::ddl_lock::DdlState;
+    use catalog::local::NameResolution;
     use query::parser::{QueryLanguageParser, QueryStatement};
     use query::query_engine::SqlStatementExecutor;
     use session::context::QueryContext;
+    use table::engine::{EngineContext, TableReference};
 
     use super::*;
     use crate::tests::test_util::MockInstance;
@@ -102,4 +181,98 @@ mod tests {
             .unwrap();
         assert!(matches!(output, Output::AffectedRows(1)));
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_drop_table_waits_for_alter() {
+        let instance = MockInstance::new("drop_table_waits_for_alter").await;
+
+        let sql = r#"create table test_drop(
+                            host string,
+                            ts timestamp,
+                            cpu double default 0,
+                            TIME INDEX (ts),
+                            PRIMARY KEY(host)
+                        ) engine=mito with(regions=1);"#;
+        let stmt = match QueryLanguageParser::parse_sql(sql).unwrap() {
+            QueryStatement::Sql(sql) => sql,
+            _ => unreachable!(),
+        };
+        let _ = instance
+            .inner()
+            .execute_sql(stmt, QueryContext::arc())
+            .await
+            .unwrap();
+
+        let sql_handler = instance.inner().sql_handler();
+        let table_ref = TableReference::full("greptime", "public", "test_drop");
+        let table = sql_handler.get_table(&table_ref).await.unwrap();
+        // Holds the lock of the table like an in-flight alter.
+        let alter_guard = sql_handler
+            .ddl_locks()
+            .lock(
+                &[table.table_info().ident.table_id],
+                "ALTER TABLE test_drop",
+            )
+            .await
+            .unwrap();
+
+        let stmt = match QueryLanguageParser::parse_sql("drop table test_drop").unwrap() {
+            QueryStatement::Sql(sql) => sql,
+            _ => unreachable!(),
+        };
+        let drop_table = instance.inner().execute_sql(stmt, QueryContext::arc());
+        let complete_alter = async move {
+            while sql_handler.ddl_locks().processes().len() < 2 {
+                tokio::task::yield_now().await;
+            }
+            let processes = sql_handler.ddl_locks().processes();
+            assert_eq!(
+                "DROP TABLE greptime.public.test_drop",
+                processes[1].description
+            );
+            assert_eq!(DdlState::Waiting, processes[1].state);
+            // The table is not dropped until the alter completes.
+            assert!(sql_handler.get_table(&table_ref).await.is_ok());
+
+            drop(alter_guard);
+        };
+        let (output, _) = tokio::join!(drop_table, complete_alter);
+        assert!(matches!(output.unwrap(), Output::AffectedRows(1)));
+        assert!(sql_handler.get_table(&table_ref).await.is_err());
+        assert!(sql_handler.ddl_locks().processes().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ddl_resolves_table_names() {
+        let instance = MockInstance::new_with("ddl_resolves_table_names", |opts| {
+            opts.name_resolution = NameResolution::CaseInsensitive;
+        })
+        .await;
+
+        for sql in [
+            "create table MyTable(host string, ts timestamp time index, primary key(host))",
+            "alter table mytable add column cpu double",
+            "drop table MYTABLE",
+        ] {
+            let stmt = match QueryLanguageParser::parse_sql(sql).unwrap() {
+                QueryStatement::Sql(sql) => sql,
+                _ => unreachable!(),
+            };
+            let _ = instance
+                .inner()
+                .execute_sql(stmt, QueryContext::arc())
+                .await
+                .unwrap();
+        }
+
+        // The engine dropped the table registered as `MyTable`.
+        let table_ref = TableReference::full("greptime", "public", "MyTable");
+        let engine = instance
+            .inner()
+            .sql_handler()
+            .table_engine_manager()
+            .engine(MITO_ENGINE)
+            .unwrap();
+        assert!(!engine.table_exists(&EngineContext::default(), &table_ref));
+    }
 }
@@ -13,10 +13,13 @@
 // limitations under the License.
 
 use catalog::CatalogManagerRef;
+use common_base::validation_mode::ValidationMode;
 use common_catalog::format_full_table_name;
+use common_grpc_expr::validation::{RowError, RowErrorCollector};
 use common_query::Output;
 use datatypes::data_type::DataType;
 use datatypes::schema::ColumnSchema;
+use datatypes::value::Value;
 use datatypes::vectors::MutableVector;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
@@ -28,16 +31,19 @@ use table::requests::*;
 use table::TableRef;
 
 use crate::error::{
-    CatalogSnafu, ColumnDefaultValueSnafu, ColumnNoneDefaultValueSnafu, ColumnNotFoundSnafu,
-    ColumnValuesNumberMismatchSnafu, InsertSnafu, MissingInsertBodySnafu, ParseSqlSnafu,
-    ParseSqlValueSnafu, Result, TableNotFoundSnafu,
+    CatalogSnafu, ColumnNotFoundSnafu, ColumnValuesNumberMismatchSnafu, InsertSnafu,
+    InvalidInsertRowsSnafu, MissingInsertBodySnafu, ParseSqlValueSnafu, Result, TableNotFoundSnafu,
 };
 use crate::sql::{table_idents_to_full_name, SqlHandler};
 
 const DEFAULT_PLACEHOLDER_VALUE: &str = "default";
 
 impl SqlHandler {
-    pub(crate) async fn insert(&self, req: InsertRequest) -> Result<Output> {
+    pub(crate) async fn insert(
+        &self,
+        req: InsertRequest,
+        query_ctx: &QueryContextRef,
+    ) -> Result<Output> {
         // FIXME(dennis): table_ref is used in InsertSnafu and the req is consumed
         // in `insert`, so we have to clone catalog_name etc.
         let table_ref = TableReference {
@@ -51,14 +57,21 @@ impl SqlHandler {
         let affected_rows = table.insert(req).await.with_context(|_| InsertSnafu {
             table_name: table_ref.to_string(),
         })?;
+        let token = table.commit_token().with_context(|_| InsertSnafu {
+            table_name: table_ref.to_string(),
+        })?;
+        query_ctx.merge_commit_token(&token);
 
         Ok(Output::AffectedRows(affected_rows))
     }
 
-    fn build_request_from_values(
+    /// Builds the request of the `INSERT ... VALUES` statement into the resolved `table`. The
+    /// invalid values are reported as row errors, up to the cap of `validation_mode`.
+    pub fn build_request_from_values(
         table_ref: TableReference,
         table: &TableRef,
         stmt: Insert,
+        validation_mode: ValidationMode,
     ) -> Result<InsertRequest> {
         let values = stmt
             .values_body()
@@ -97,7 +110,8 @@ impl SqlHandler {
         }
 
         // Convert rows into columns
-        for row in values {
+        let mut row_errors = RowErrorCollector::new(validation_mode);
+        for (row_index, row) in values.iter().enumerate() {
             ensure!(
                 row.len() == columns_num,
                 ColumnValuesNumberMismatchSnafu {
@@ -107,9 +121,27 @@ impl SqlHandler {
             );
 
             for (sql_val, (column_schema, builder)) in row.iter().zip(columns_builders.iter_mut()) {
-                add_row_to_vector(column_schema, sql_val, builder)?;
+                match row_value(column_schema, sql_val) {
+                    Ok(value) => builder.push_value_ref(value.as_value_ref()),
+                    Err(reason) => {
+                        let value =
+                            (!matches!(sql_val, SqlValue::Null)).then(|| sql_val.to_string());
+                        row_errors.push(RowError::new(
+                            row_index,
+                            &column_schema.name,
+                            value.as_deref(),
+                            reason,
+                        ));
+                        // The request is rejected anyway, the null keeps the columns aligned.
+                        builder.push_null();
+                    }
+                }
+            }
+            if row_errors.is_full() {
+                break;
             }
         }
+        row_errors.finish().context(InvalidInsertRowsSnafu)?;
 
         Ok(InsertRequest {
             catalog_name: table_ref.catalog.to_string(),
@@ -120,6 +152,7 @@ impl SqlHandler {
                 .map(|(cs, mut b)| (cs.name.to_string(), b.to_vector()))
                 .collect(),
             region_number: 0,
+            write_mode: Default::default(),
         })
     }
 
@@ -140,30 +173,29 @@ impl SqlHandler {
             })?;
 
         let table_ref = TableReference::full(&catalog_name, &schema_name, &table_name);
-        Self::build_request_from_values(table_ref, &table, stmt)
+        Self::build_request_from_values(table_ref, &table, stmt, query_ctx.validation_mode())
     }
 }
 
-fn add_row_to_vector(
+/// Returns the value of `sql_val` in the column of `column_schema`, or the reason why it's
+/// invalid.
+fn row_value(
     column_schema: &ColumnSchema,
     sql_val: &SqlValue,
-    builder: &mut Box<dyn MutableVector>,
-) -> Result<()> {
+) -> std::result::Result<Value, String> {
     let value = if replace_default(sql_val) {
         column_schema
             .create_default()
-            .context(ColumnDefaultValueSnafu {
-                column: column_schema.name.to_string(),
-            })?
-            .context(ColumnNoneDefaultValueSnafu {
-                column: column_schema.name.to_string(),
-            })?
+            .map_err(|e| format!("Failed to build default value, source: {e}"))?
+            .ok_or_else(|| "No valid default value can be built automatically".to_string())?
     } else {
         statements::sql_value_to_value(&column_schema.name, &column_schema.data_type, sql_val)
-            .context(ParseSqlSnafu)?
+            .map_err(|e| e.to_string())?
     };
-    builder.push_value_ref(value.as_value_ref());
-    Ok(())
+    if value.is_null() && !column_schema.is_nullable() {
+        return Err("null value in not null column".to_string());
+    }
+    Ok(value)
 }
 
 fn replace_default(sql_val: &SqlValue) -> bool {
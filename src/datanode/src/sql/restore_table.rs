@@ -0,0 +1,91 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use catalog::recycle_bin::recycled_tables;
+use common_catalog::format_full_table_name;
+use common_query::Output;
+use common_telemetry::info;
+use snafu::{ensure, OptionExt, ResultExt};
+use table::requests::{AlterKind, AlterTableRequest, RestoreTableRequest};
+
+use crate::error::{self, RecycledTableNotFoundSnafu, Result, SchemaNotFoundSnafu};
+use crate::sql::SqlHandler;
+
+impl SqlHandler {
+    /// Restores the table named `table_name` dropped last from the recycle bin, by renaming it
+    /// back to its name or the new name.
+    pub(crate) async fn restore_table(&self, req: RestoreTableRequest) -> Result<Output> {
+        let schema = self
+            .catalog_manager
+            .schema(&req.catalog_name, &req.schema_name)
+            .await
+            .context(error::CatalogSnafu)?
+            .context(SchemaNotFoundSnafu {
+                name: &req.schema_name,
+            })?;
+        let recycled = recycled_tables(&schema)
+            .await
+            .context(error::CatalogSnafu)?
+            .into_iter()
+            .rev()
+            .find(|recycled| recycled.table_name == req.table_name)
+            .with_context(|| RecycledTableNotFoundSnafu {
+                table_name: format_full_table_name(
+                    &req.catalog_name,
+                    &req.schema_name,
+                    &req.table_name,
+                ),
+            })?;
+
+        let _guard = self
+            .lock_ddl(
+                &[recycled.table_id],
+                format!(
+                    "RESTORE TABLE {}",
+                    format_full_table_name(&req.catalog_name, &req.schema_name, &req.table_name)
+                ),
+            )
+            .await?;
+
+        let new_table_name = req.new_table_name.unwrap_or(req.table_name);
+        let exists = schema
+            .table_exist(&new_table_name)
+            .await
+            .context(error::CatalogSnafu)?;
+        ensure!(
+            !exists,
+            error::TableExistsSnafu {
+                table_name: format_full_table_name(
+                    &req.catalog_name,
+                    &req.schema_name,
+                    &new_table_name
+                ),
+            }
+        );
+
+        info!(
+            "Restore table {} from the recycle bin as {}",
+            recycled.recycled_name, new_table_name
+        );
+        let _ = self
+            .alter_table_locked(AlterTableRequest {
+                catalog_name: req.catalog_name,
+                schema_name: req.schema_name,
+                table_name: recycled.recycled_name,
+                alter_kind: AlterKind::RenameTable { new_table_name },
+            })
+            .await?;
+        Ok(Output::AffectedRows(0))
+    }
+}
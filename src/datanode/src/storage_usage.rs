@@ -0,0 +1,422 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Accounting of the bytes stored in the object store by the tables of the datanode.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use catalog::CatalogManagerRef;
+use common_error::prelude::BoxedError;
+use common_telemetry::{info, warn};
+use common_time::util::current_time_millis;
+use futures::StreamExt;
+use object_store::{EntryMode, ErrorKind, Metakey, ObjectStore};
+use servers::query_handler::{
+    ListedStorageUsage, SchemaStorageUsage, StorageUsage, StorageUsageHandler, TableStorageUsage,
+};
+use snafu::ResultExt;
+use table::engine::table_dir;
+use table::metadata::TableId;
+
+use crate::error::{ListStorageSnafu, Result};
+use crate::instance::Instance;
+
+/// Full name of a table, `(catalog, schema, table)`.
+type TableKey = (String, String, String);
+
+#[derive(Debug, Clone, Copy)]
+struct TableSample {
+    table_id: TableId,
+    storage_bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Sample {
+    timestamp_millis: i64,
+    tables: BTreeMap<TableKey, TableSample>,
+}
+
+#[derive(Debug, Default)]
+struct Samples {
+    previous: Option<Sample>,
+    current: Option<Sample>,
+}
+
+/// Samples the bytes stored in the object store by the tables periodically.
+///
+/// The bytes of a table are its manifest and the SSTs and manifests tracked by its regions,
+/// including the SSTs removed by compaction but not purged yet, so sampling doesn't list the
+/// object store. The objects of
+/// the tables are only listed on demand.
+pub struct StorageUsageTracker {
+    catalog_manager: CatalogManagerRef,
+    object_store: ObjectStore,
+    interval: Duration,
+    samples: Mutex<Samples>,
+    running: AtomicBool,
+}
+
+pub type StorageUsageTrackerRef = Arc<StorageUsageTracker>;
+
+impl StorageUsageTracker {
+    pub fn new(
+        catalog_manager: CatalogManagerRef,
+        object_store: ObjectStore,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            catalog_manager,
+            object_store,
+            interval,
+            samples: Mutex::new(Samples::default()),
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// Starts sampling in background, until the tracker is stopped or dropped.
+    pub fn start(self: &Arc<Self>) {
+        if self
+            .running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            warn!("Storage usage tracker started multiple times");
+            return;
+        }
+
+        let tracker = Arc::downgrade(self);
+        let mut interval = tokio::time::interval(self.interval);
+        common_runtime::spawn_bg(async move {
+            loop {
+                interval.tick().await;
+                let Some(tracker) = tracker.upgrade() else { break };
+                if !tracker.running.load(Ordering::Acquire) {
+                    break;
+                }
+                tracker.sample().await;
+            }
+            info!("Storage usage tracker stopped");
+        });
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Release);
+    }
+
+    /// Samples the bytes stored by each table, which becomes the base of the deltas of the
+    /// next sample.
+    pub async fn sample(&self) {
+        let tables = self.collect_table_bytes().await;
+        let sample = Sample {
+            timestamp_millis: current_time_millis(),
+            tables,
+        };
+
+        let mut samples = self.samples.lock().unwrap();
+        samples.previous = samples.current.replace(sample);
+    }
+
+    /// Returns the usage as of the last sample, sampling first if there isn't any. Lists the
+    /// objects of each table if `full` is true.
+    pub async fn usage(&self, full: bool) -> Result<StorageUsage> {
+        let (current, previous) = {
+            let samples = self.samples.lock().unwrap();
+            (samples.current.clone(), samples.previous.clone())
+        };
+        let (current, previous) = match current {
+            Some(current) => (current, previous),
+            None => {
+                self.sample().await;
+                let samples = self.samples.lock().unwrap();
+                (samples.current.clone().unwrap(), samples.previous.clone())
+            }
+        };
+
+        let mut usage = build_usage(&current, previous.as_ref());
+        if full {
+            for (table, ((catalog, schema, _), sample)) in
+                usage.tables.iter_mut().zip(current.tables.iter())
+            {
+                let listed = self.list_table(catalog, schema, sample.table_id).await?;
+                table.listed = Some(listed);
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Sums the bytes stored by each table and its regions. Tables without region stats, like
+    /// the system tables, are skipped.
+    async fn collect_table_bytes(&self) -> BTreeMap<TableKey, TableSample> {
+        let mut tables = BTreeMap::new();
+
+        let Ok(catalog_names) = self.catalog_manager.catalog_names().await else { return tables };
+        for catalog_name in catalog_names {
+            let Ok(Some(catalog)) = self.catalog_manager.catalog(&catalog_name).await else { continue };
+
+            let Ok(schema_names) = catalog.schema_names().await else { continue };
+            for schema_name in schema_names {
+                let Ok(Some(schema)) = catalog.schema(&schema_name).await else { continue };
+
+                let Ok(table_names) = schema.table_names().await else { continue };
+                for table_name in table_names {
+                    let Ok(Some(table)) = schema.table(&table_name).await else { continue };
+                    let Ok(stats) = table.region_stats() else { continue };
+
+                    let sample = TableSample {
+                        table_id: table.table_info().ident.table_id,
+                        storage_bytes: table.manifest_bytes()
+                            + stats.iter().map(|stat| stat.storage_bytes).sum::<u64>(),
+                    };
+                    let key = (catalog_name.clone(), schema_name.clone(), table_name);
+                    tables.insert(key, sample);
+                }
+            }
+        }
+        tables
+    }
+
+    /// Lists the objects under the directory of the table.
+    async fn list_table(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_id: TableId,
+    ) -> Result<ListedStorageUsage> {
+        let path = table_dir(catalog, schema, table_id);
+        let mut listed = ListedStorageUsage::default();
+
+        let mut lister = match self.object_store.scan(&path).await {
+            Ok(lister) => lister,
+            // Nothing is written by the table yet.
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(listed),
+            Err(e) => return Err(e).context(ListStorageSnafu { path }),
+        };
+        while let Some(entry) = lister.next().await {
+            let entry = entry.context(ListStorageSnafu { path: &path })?;
+            let metadata = self
+                .object_store
+                .metadata(&entry, Metakey::Mode | Metakey::ContentLength)
+                .await
+                .context(ListStorageSnafu { path: entry.path() })?;
+            if !matches!(metadata.mode(), EntryMode::FILE) {
+                continue;
+            }
+
+            let bytes = metadata.content_length();
+            if entry.path().contains("/manifest/") {
+                listed.manifest_bytes += bytes;
+            } else if entry.path().ends_with(".parquet") {
+                listed.sst_bytes += bytes;
+            } else {
+                listed.other_bytes += bytes;
+            }
+        }
+        Ok(listed)
+    }
+}
+
+/// Builds the usage of the tables in `current` and the rollups of their schemas, with the
+/// deltas against `previous`.
+fn build_usage(current: &Sample, previous: Option<&Sample>) -> StorageUsage {
+    let tables = current
+        .tables
+        .iter()
+        .map(|((catalog, schema, table), sample)| {
+            let delta_bytes = previous
+                .and_then(|previous| {
+                    previous
+                        .tables
+                        .get(&(catalog.clone(), schema.clone(), table.clone()))
+                })
+                .map(|previous| sample.storage_bytes as i64 - previous.storage_bytes as i64);
+            TableStorageUsage {
+                catalog: catalog.clone(),
+                schema: schema.clone(),
+                table: table.clone(),
+                storage_bytes: sample.storage_bytes,
+                delta_bytes,
+                listed: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut schemas: BTreeMap<(&str, &str), SchemaStorageUsage> = BTreeMap::new();
+    for table in &tables {
+        let schema = schemas
+            .entry((&table.catalog, &table.schema))
+            .or_insert_with(|| SchemaStorageUsage {
+                catalog: table.catalog.clone(),
+                schema: table.schema.clone(),
+                ..Default::default()
+            });
+        schema.table_num += 1;
+        schema.storage_bytes += table.storage_bytes;
+    }
+    if let Some(previous) = previous {
+        // Compares against all the tables of the schema in the previous sample, so dropping
+        // a table shrinks its schema.
+        let mut previous_bytes: BTreeMap<(&str, &str), u64> = BTreeMap::new();
+        for ((catalog, schema, _), sample) in &previous.tables {
+            *previous_bytes
+                .entry((catalog.as_str(), schema.as_str()))
+                .or_default() += sample.storage_bytes;
+        }
+        for (key, schema) in schemas.iter_mut() {
+            let previous_bytes = previous_bytes.get(key).copied().unwrap_or_default();
+            schema.delta_bytes = Some(schema.storage_bytes as i64 - previous_bytes as i64);
+        }
+    }
+
+    StorageUsage {
+        sampled_at_millis: current.timestamp_millis,
+        previous_sampled_at_millis: previous.map(|previous| previous.timestamp_millis),
+        schemas: schemas.into_values().collect(),
+        tables,
+    }
+}
+
+#[async_trait]
+impl StorageUsageHandler for Instance {
+    async fn storage_usage(&self, full: bool) -> servers::error::Result<StorageUsage> {
+        self.storage_usage
+            .usage(full)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::CollectStorageUsageSnafu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use common_query::Output;
+    use query::parser::{QueryLanguageParser, QueryStatement};
+    use query::query_engine::SqlStatementExecutor;
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::tests::test_util::MockInstance;
+
+    async fn execute_sql(instance: &Instance, sql: &str) -> Output {
+        let QueryStatement::Sql(stmt) = QueryLanguageParser::parse_sql(sql).unwrap() else { unreachable!() };
+        instance
+            .execute_sql(stmt, QueryContext::arc())
+            .await
+            .unwrap()
+    }
+
+    /// Sums the bytes of the SSTs and the manifest files under `dir`, read from the file
+    /// system under the object store.
+    fn stored_bytes_in_dir(dir: &Path, in_manifest: bool) -> u64 {
+        let Ok(entries) = std::fs::read_dir(dir) else { return 0 };
+        entries
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    stored_bytes_in_dir(&path, in_manifest || path.ends_with("manifest"))
+                } else if in_manifest || path.extension().map_or(false, |ext| ext == "parquet") {
+                    std::fs::metadata(&path).unwrap().len()
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
+    async fn table_stored_bytes(instance: &MockInstance, table_name: &str) -> u64 {
+        let table = instance
+            .inner()
+            .catalog_manager()
+            .table("greptime", "public", table_name)
+            .await
+            .unwrap()
+            .unwrap();
+        let dir = table_dir("greptime", "public", table.table_info().ident.table_id);
+        stored_bytes_in_dir(&instance.data_dir().join(dir), false)
+    }
+
+    fn table_usage<'a>(usage: &'a StorageUsage, table: &str) -> &'a TableStorageUsage {
+        usage.tables.iter().find(|t| t.table == table).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_storage_usage() {
+        let mock = MockInstance::new("test_storage_usage").await;
+        let instance = mock.inner();
+        for table in ["usage_a", "usage_b"] {
+            execute_sql(
+                instance,
+                &format!(
+                    "CREATE TABLE {table}(host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX, PRIMARY KEY(host))"
+                ),
+            )
+            .await;
+        }
+        execute_sql(
+            instance,
+            "INSERT INTO usage_a VALUES ('host1', 1.0, 1000), ('host2', 2.0, 2000)",
+        )
+        .await;
+        execute_sql(instance, "INSERT INTO usage_b VALUES ('host1', 1.0, 1000)").await;
+        instance.flush_tables().await.unwrap();
+
+        instance.storage_usage.sample().await;
+        let usage = instance.storage_usage.usage(true).await.unwrap();
+        assert_eq!(None, usage.previous_sampled_at_millis);
+        for table in ["usage_a", "usage_b"] {
+            let expect = table_stored_bytes(&mock, table).await;
+            let table_usage = table_usage(&usage, table);
+            assert_eq!(expect, table_usage.storage_bytes);
+            assert_eq!(None, table_usage.delta_bytes);
+            let listed = table_usage.listed.as_ref().unwrap();
+            assert!(listed.sst_bytes > 0);
+            assert!(listed.manifest_bytes > 0);
+            assert_eq!(expect, listed.sst_bytes + listed.manifest_bytes);
+        }
+        let schema = usage
+            .schemas
+            .iter()
+            .find(|s| s.catalog == "greptime" && s.schema == "public")
+            .unwrap();
+        assert_eq!(2, schema.table_num);
+        assert_eq!(
+            table_usage(&usage, "usage_a").storage_bytes
+                + table_usage(&usage, "usage_b").storage_bytes,
+            schema.storage_bytes
+        );
+        assert_eq!(None, schema.delta_bytes);
+
+        // Only usage_a grows.
+        let before = table_usage(&usage, "usage_a").storage_bytes;
+        execute_sql(instance, "INSERT INTO usage_a VALUES ('host3', 3.0, 3000)").await;
+        instance.flush_tables().await.unwrap();
+        instance.storage_usage.sample().await;
+
+        let usage = instance.storage_usage.usage(false).await.unwrap();
+        assert!(usage.previous_sampled_at_millis.is_some());
+        let table_a = table_usage(&usage, "usage_a");
+        let expect = table_stored_bytes(&mock, "usage_a").await;
+        assert_eq!(expect, table_a.storage_bytes);
+        assert_eq!(Some((expect - before) as i64), table_a.delta_bytes);
+        assert!(table_a.listed.is_none());
+        assert_eq!(Some(0), table_usage(&usage, "usage_b").delta_bytes);
+        let schema = usage.schemas.iter().find(|s| s.schema == "public").unwrap();
+        assert_eq!(table_a.delta_bytes, schema.delta_bytes);
+    }
+}
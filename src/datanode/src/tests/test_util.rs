@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+
 use common_catalog::consts::{
     DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MIN_USER_TABLE_ID, MITO_ENGINE,
 };
@@ -31,27 +33,40 @@ use crate::instance::Instance;
 
 pub(crate) struct MockInstance {
     instance: Instance,
-    _guard: TestGuard,
+    guard: TestGuard,
 }
 
 impl MockInstance {
     pub(crate) async fn new(name: &str) -> Self {
-        let (opts, _guard) = create_tmp_dir_and_datanode_opts(name);
+        Self::new_with(name, |_| {}).await
+    }
+
+    /// Creates an instance with the options modified by `update_opts`.
+    pub(crate) async fn new_with(
+        name: &str,
+        update_opts: impl FnOnce(&mut DatanodeOptions),
+    ) -> Self {
+        let (mut opts, guard) = create_tmp_dir_and_datanode_opts(name);
+        update_opts(&mut opts);
 
         let instance = Instance::with_mock_meta_client(&opts).await.unwrap();
         instance.start().await.unwrap();
 
-        MockInstance { instance, _guard }
+        MockInstance { instance, guard }
     }
 
     pub(crate) fn inner(&self) -> &Instance {
         &self.instance
     }
+
+    pub(crate) fn data_dir(&self) -> &Path {
+        self.guard.data_tmp_dir.path()
+    }
 }
 
 struct TestGuard {
     _wal_tmp_dir: TempDir,
-    _data_tmp_dir: TempDir,
+    data_tmp_dir: TempDir,
     _procedure_tmp_dir: TempDir,
 }
 
@@ -80,7 +95,7 @@ fn create_tmp_dir_and_datanode_opts(name: &str) -> (DatanodeOptions, TestGuard)
         opts,
         TestGuard {
             _wal_tmp_dir: wal_tmp_dir,
-            _data_tmp_dir: data_tmp_dir,
+            data_tmp_dir,
             _procedure_tmp_dir: procedure_tmp_dir,
         },
     )
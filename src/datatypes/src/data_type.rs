@@ -136,6 +136,10 @@ impl ConcreteDataType {
         matches!(self, ConcreteDataType::Null(NullType))
     }
 
+    pub fn is_dictionary(&self) -> bool {
+        matches!(self, ConcreteDataType::Dictionary(_))
+    }
+
     /// Try to cast the type as a [`ListType`].
     pub fn as_list(&self) -> Option<&ListType> {
         match self {
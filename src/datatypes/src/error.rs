@@ -109,6 +109,9 @@ pub enum Error {
 
     #[snafu(display("Invalid timestamp precision: {}", precision))]
     InvalidTimestampPrecision { precision: u64, location: Location },
+
+    #[snafu(display("Invalid storage option, reason: {}", reason))]
+    InvalidStorageOption { reason: String, location: Location },
 }
 
 impl ErrorExt for Error {
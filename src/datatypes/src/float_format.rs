@@ -0,0 +1,227 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Formatting of floats for the output of query results, shared by all the output formats so
+//! the same value is rendered to the same string in the same [FloatPrecision].
+
+/// Max digits of [FloatPrecision::Significant].
+///
+/// A decimal with at most 15 significant digits parses to a `f64` whose shortest representation
+/// has the same digits, so formats writing floats by their shortest representation (like json)
+/// render a rounded float to the same string as [format_f64].
+pub const MAX_SIGNIFICANT_DIGITS: u8 = 15;
+
+/// Max number of digits before the decimal point of floats not written in exponent notation.
+const MAX_INTEGER_DIGITS: i32 = 16;
+
+/// Min (exclusive) position of the decimal point of floats not written in exponent notation,
+/// e.g. `0.00001` is written as is but `1e-6` isn't.
+const MIN_POINT_POSITION: i32 = -5;
+
+/// Precision of the floats in query results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatPrecision {
+    /// The shortest representation that parses back to the same float.
+    #[default]
+    Roundtrip,
+    /// Rounds to the given number of significant digits, trailing zeros are omitted.
+    Significant(u8),
+}
+
+impl FloatPrecision {
+    /// Returns the precision of `digits` significant digits, or `None` if `digits` is not in
+    /// `1..=MAX_SIGNIFICANT_DIGITS`.
+    pub fn significant(digits: u8) -> Option<FloatPrecision> {
+        (1..=MAX_SIGNIFICANT_DIGITS)
+            .contains(&digits)
+            .then_some(FloatPrecision::Significant(digits))
+    }
+}
+
+/// Formats `v` in `precision`.
+///
+/// Finite floats are written like `1.5`, `100.0`, `0.001` or `1.5e20`, same as json serializes
+/// them. Non-finite floats are written as `NaN`, `Infinity` and `-Infinity`.
+pub fn format_f64(v: f64, precision: FloatPrecision) -> String {
+    if let Some(name) = non_finite_name(v) {
+        return name.to_string();
+    }
+    match precision {
+        FloatPrecision::Roundtrip => layout(&format!("{v:e}")),
+        FloatPrecision::Significant(digits) => {
+            layout(&format!("{:.*e}", digits.saturating_sub(1) as usize, v))
+        }
+    }
+}
+
+/// Formats `v` in `precision`, see [format_f64].
+///
+/// [FloatPrecision::Roundtrip] writes the shortest representation parsing back to the same
+/// `f32`, which is shorter than the one of `v` as a `f64`.
+pub fn format_f32(v: f32, precision: FloatPrecision) -> String {
+    if let Some(name) = non_finite_name(v.into()) {
+        return name.to_string();
+    }
+    match precision {
+        FloatPrecision::Roundtrip => layout(&format!("{v:e}")),
+        FloatPrecision::Significant(digits) => {
+            layout(&format!("{:.*e}", digits.saturating_sub(1) as usize, v))
+        }
+    }
+}
+
+fn non_finite_name(v: f64) -> Option<&'static str> {
+    if v.is_nan() {
+        Some("NaN")
+    } else if v.is_infinite() && v.is_sign_positive() {
+        Some("Infinity")
+    } else if v.is_infinite() {
+        Some("-Infinity")
+    } else {
+        None
+    }
+}
+
+/// Lays out the float written in exponent notation by `LowerExp`, like `-1.25e-3`.
+fn layout(exp_notation: &str) -> String {
+    let (sign, unsigned) = match exp_notation.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", exp_notation),
+    };
+    // Safety: `LowerExp` always writes the exponent of a finite float.
+    let (mantissa, exponent) = unsigned.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    let digits = mantissa.replace('.', "");
+    let digits = match digits.trim_end_matches('0') {
+        "" => "0",
+        digits => digits,
+    };
+    let num_digits = digits.len() as i32;
+    // Position of the decimal point after the first `point` digits.
+    let point = exponent + 1;
+
+    if num_digits <= point && point <= MAX_INTEGER_DIGITS {
+        let zeros = "0".repeat((point - num_digits) as usize);
+        format!("{sign}{digits}{zeros}.0")
+    } else if 0 < point && point <= MAX_INTEGER_DIGITS {
+        let (integer, fraction) = digits.split_at(point as usize);
+        format!("{sign}{integer}.{fraction}")
+    } else if MIN_POINT_POSITION < point && point <= 0 {
+        let zeros = "0".repeat(-point as usize);
+        format!("{sign}0.{zeros}{digits}")
+    } else if num_digits == 1 {
+        format!("{sign}{digits}e{exponent}")
+    } else {
+        let (first, rest) = digits.split_at(1);
+        format!("{sign}{first}.{rest}e{exponent}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_roundtrip() {
+        let precision = FloatPrecision::Roundtrip;
+        let cases = [
+            (0.1 + 0.2, "0.30000000000000004"),
+            (0.0, "0.0"),
+            (-0.0, "-0.0"),
+            (1.0, "1.0"),
+            (-12.5, "-12.5"),
+            (100.0, "100.0"),
+            (0.001, "0.001"),
+            (1e-5, "0.00001"),
+            (1e-6, "1e-6"),
+            (1.5e-7, "1.5e-7"),
+            (1e15, "1000000000000000.0"),
+            (1e16, "1e16"),
+            (1.25e300, "1.25e300"),
+            (f64::MAX, "1.7976931348623157e308"),
+            (f64::NAN, "NaN"),
+            (f64::INFINITY, "Infinity"),
+            (f64::NEG_INFINITY, "-Infinity"),
+        ];
+        for (v, expect) in cases {
+            assert_eq!(expect, format_f64(v, precision), "{v:?}");
+        }
+
+        assert_eq!("0.1", format_f32(0.1, precision));
+        assert_eq!("3.4028235e38", format_f32(f32::MAX, precision));
+        assert_eq!("NaN", format_f32(f32::NAN, precision));
+    }
+
+    #[test]
+    fn test_format_significant() {
+        let precision = FloatPrecision::significant(6).unwrap();
+        let cases = [
+            (0.1 + 0.2, "0.3"),
+            (1.0 / 3.0, "0.333333"),
+            (-2.0 / 3.0, "-0.666667"),
+            (123456789.0, "123457000.0"),
+            (0.0, "0.0"),
+            (1.23456789e-10, "1.23457e-10"),
+            (9.9999999, "10.0"),
+            (f64::NEG_INFINITY, "-Infinity"),
+        ];
+        for (v, expect) in cases {
+            assert_eq!(expect, format_f64(v, precision), "{v:?}");
+        }
+
+        assert_eq!("0.1", format_f32(0.1, precision));
+        assert_eq!("3.14159", format_f32(std::f32::consts::PI, precision));
+        assert_eq!(
+            "3.0",
+            format_f64(
+                std::f64::consts::PI,
+                FloatPrecision::significant(1).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_significant_digits_range() {
+        assert!(FloatPrecision::significant(0).is_none());
+        assert!(FloatPrecision::significant(MAX_SIGNIFICANT_DIGITS + 1).is_none());
+        assert_eq!(
+            Some(FloatPrecision::Significant(MAX_SIGNIFICANT_DIGITS)),
+            FloatPrecision::significant(MAX_SIGNIFICANT_DIGITS)
+        );
+    }
+
+    #[test]
+    fn test_format_same_as_json() {
+        let values = [
+            0.1 + 0.2,
+            -0.0,
+            1e-5,
+            1e-6,
+            1e15,
+            1e16,
+            123.456e200,
+            f64::MIN_POSITIVE,
+        ];
+        for v in values {
+            let json = serde_json::to_string(&v).unwrap();
+            assert_eq!(json, format_f64(v, FloatPrecision::Roundtrip), "{v:?}");
+
+            let precision = FloatPrecision::significant(MAX_SIGNIFICANT_DIGITS).unwrap();
+            let rounded = format_f64(v, precision);
+            let json = serde_json::to_string(&rounded.parse::<f64>().unwrap()).unwrap();
+            assert_eq!(json, rounded, "{v:?}");
+        }
+    }
+}
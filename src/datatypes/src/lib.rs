@@ -15,6 +15,7 @@
 pub mod arrow_array;
 pub mod data_type;
 pub mod error;
+pub mod float_format;
 pub mod macros;
 pub mod prelude;
 pub mod scalars;
@@ -29,6 +29,10 @@ pub type Metadata = HashMap<String, String>;
 /// Key used to store whether the column is time index in arrow field's metadata.
 pub const TIME_INDEX_KEY: &str = "greptime:time_index";
 pub const COMMENT_KEY: &str = "greptime:storage:comment";
+/// Key used to store the encoding of the column in SST files.
+pub const ENCODING_KEY: &str = "greptime:storage:encoding";
+/// Key used to store the compression of the column in SST files.
+pub const COMPRESSION_KEY: &str = "greptime:storage:compression";
 /// Key used to store default constraint in arrow field's metadata.
 const DEFAULT_CONSTRAINT_KEY: &str = "greptime:default_constraint";
 
@@ -113,6 +117,64 @@ impl ColumnSchema {
         self
     }
 
+    /// Sets the encoding of the column in SST files, which must be one of the encodings
+    /// supported by the data type of the column.
+    pub fn with_encoding(mut self, encoding: &str) -> Result<Self> {
+        let encoding = encoding.to_lowercase();
+        let supported = match encoding.as_str() {
+            "plain" => true,
+            "dictionary" => !self.data_type.is_boolean(),
+            "delta_binary_packed" => self.data_type.is_signed() || self.data_type.is_unsigned(),
+            "delta_length_byte_array" | "delta_byte_array" => matches!(
+                self.data_type,
+                ConcreteDataType::String(_) | ConcreteDataType::Binary(_)
+            ),
+            "byte_stream_split" => self.data_type.is_float(),
+            _ => false,
+        };
+        ensure!(
+            supported,
+            error::InvalidStorageOptionSnafu {
+                reason: format!(
+                    "encoding {encoding} is not supported by type {:?}",
+                    self.data_type
+                ),
+            }
+        );
+
+        let _ = self.metadata.insert(ENCODING_KEY.to_string(), encoding);
+        Ok(self)
+    }
+
+    /// Sets the compression of the column in SST files.
+    pub fn with_compression(mut self, compression: &str) -> Result<Self> {
+        let compression = compression.to_lowercase();
+        ensure!(
+            matches!(
+                compression.as_str(),
+                "uncompressed" | "snappy" | "gzip" | "lz4" | "zstd"
+            ),
+            error::InvalidStorageOptionSnafu {
+                reason: format!("unknown compression {compression}"),
+            }
+        );
+
+        let _ = self
+            .metadata
+            .insert(COMPRESSION_KEY.to_string(), compression);
+        Ok(self)
+    }
+
+    /// Returns the encoding of the column in SST files, if any.
+    pub fn encoding(&self) -> Option<&str> {
+        self.metadata.get(ENCODING_KEY).map(String::as_str)
+    }
+
+    /// Returns the compression of the column in SST files, if any.
+    pub fn compression(&self) -> Option<&str> {
+        self.metadata.get(COMPRESSION_KEY).map(String::as_str)
+    }
+
     /// Creates a vector with default value for this column.
     ///
     /// If the column is `NOT NULL` but doesn't has `DEFAULT` value supplied, returns `Ok(None)`.
@@ -241,6 +303,37 @@ mod tests {
         assert_eq!(column_schema, new_column_schema);
     }
 
+    #[test]
+    fn test_column_schema_with_storage_options() {
+        let column_schema = ColumnSchema::new("host", ConcreteDataType::string_datatype(), true)
+            .with_encoding("DICTIONARY")
+            .unwrap()
+            .with_compression("Zstd")
+            .unwrap();
+        assert_eq!(Some("dictionary"), column_schema.encoding());
+        assert_eq!(Some("zstd"), column_schema.compression());
+
+        let field = Field::try_from(&column_schema).unwrap();
+        let new_column_schema = ColumnSchema::try_from(&field).unwrap();
+        assert_eq!(column_schema, new_column_schema);
+
+        let column_schema = ColumnSchema::new("ts", ConcreteDataType::int64_datatype(), true);
+        assert!(column_schema
+            .clone()
+            .with_encoding("delta_binary_packed")
+            .is_ok());
+        assert!(column_schema
+            .clone()
+            .with_encoding("byte_stream_split")
+            .is_err());
+        assert!(column_schema.clone().with_encoding("unknown").is_err());
+        assert!(column_schema.with_compression("brotli").is_err());
+
+        let column_schema = ColumnSchema::new("flag", ConcreteDataType::boolean_datatype(), true);
+        assert!(column_schema.clone().with_encoding("plain").is_ok());
+        assert!(column_schema.with_encoding("dictionary").is_err());
+    }
+
     #[test]
     fn test_column_schema_with_default_constraint() {
         let column_schema = ColumnSchema::new("test", ConcreteDataType::int32_datatype(), true)
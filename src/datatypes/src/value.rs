@@ -30,6 +30,7 @@ use snafu::ensure;
 
 use crate::error;
 use crate::error::Result;
+use crate::float_format::{format_f32, format_f64, FloatPrecision};
 use crate::prelude::*;
 use crate::type_id::LogicalTypeId;
 use crate::types::ListType;
@@ -425,7 +426,14 @@ impl TryFrom<Value> for serde_json::Value {
     type Error = serde_json::Error;
 
     fn try_from(value: Value) -> serde_json::Result<serde_json::Value> {
-        let json_value = match value {
+        value.try_into_json(FloatPrecision::default())
+    }
+}
+
+impl Value {
+    /// Converts the value to json, with floats in `precision`.
+    pub fn try_into_json(self, precision: FloatPrecision) -> serde_json::Result<serde_json::Value> {
+        let json_value = match self {
             Value::Null => serde_json::Value::Null,
             Value::Boolean(v) => serde_json::Value::Bool(v),
             Value::UInt8(v) => serde_json::Value::from(v),
@@ -436,8 +444,8 @@ impl TryFrom<Value> for serde_json::Value {
             Value::Int16(v) => serde_json::Value::from(v),
             Value::Int32(v) => serde_json::Value::from(v),
             Value::Int64(v) => serde_json::Value::from(v),
-            Value::Float32(v) => serde_json::Value::from(v.0),
-            Value::Float64(v) => serde_json::Value::from(v.0),
+            Value::Float32(v) => float_to_json_value(format_f32(v.0, precision)),
+            Value::Float64(v) => float_to_json_value(format_f64(v.0, precision)),
             Value::String(bytes) => serde_json::Value::String(bytes.as_utf8().to_string()),
             Value::Binary(bytes) => serde_json::to_value(bytes)?,
             Value::Date(v) => serde_json::Value::Number(v.val().into()),
@@ -450,6 +458,20 @@ impl TryFrom<Value> for serde_json::Value {
     }
 }
 
+/// Converts a float formatted by [format_f64] or [format_f32] to a json number, which is
+/// serialized to the same string, or to the strings `"NaN"`, `"Infinity"` and `"-Infinity"` for
+/// non-finite values which json numbers can't represent.
+fn float_to_json_value(formatted: String) -> serde_json::Value {
+    match formatted
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        Some(number) => serde_json::Value::Number(number),
+        None => serde_json::Value::String(formatted),
+    }
+}
+
 // TODO(yingwen): Consider removing the `datatype` field from `ListValue`.
 /// List value.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1202,6 +1224,18 @@ mod tests {
             serde_json::Value::from(125.0f64),
             to_json(Value::Float64(125.0.into()))
         );
+        assert_eq!(
+            serde_json::Value::String(String::from("NaN")),
+            to_json(Value::Float64(f64::NAN.into()))
+        );
+        assert_eq!(
+            serde_json::Value::String(String::from("Infinity")),
+            to_json(Value::Float32(f32::INFINITY.into()))
+        );
+        assert_eq!(
+            serde_json::Value::String(String::from("-Infinity")),
+            to_json(Value::Float64(f64::NEG_INFINITY.into()))
+        );
         assert_eq!(
             serde_json::Value::String(String::from("hello")),
             to_json(Value::String(StringBytes::from("hello")))
@@ -1235,6 +1269,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_json_float_precision() {
+        let to_json_string = |value: Value, precision| {
+            serde_json::to_string(&value.try_into_json(precision).unwrap()).unwrap()
+        };
+
+        let value = Value::Float64((0.1 + 0.2).into());
+        assert_eq!(
+            "0.30000000000000004",
+            to_json_string(value.clone(), FloatPrecision::Roundtrip)
+        );
+        let precision = FloatPrecision::significant(3).unwrap();
+        assert_eq!("0.3", to_json_string(value, precision));
+        assert_eq!(
+            "3.14",
+            to_json_string(Value::Float64(std::f64::consts::PI.into()), precision)
+        );
+
+        // f32 is written by its own shortest representation.
+        assert_eq!(
+            "0.1",
+            to_json_string(Value::Float32(0.1.into()), FloatPrecision::Roundtrip)
+        );
+        assert_eq!(
+            "\"NaN\"",
+            to_json_string(Value::Float32(f32::NAN.into()), precision)
+        );
+    }
+
     #[test]
     fn test_null_value() {
         assert!(Value::Null.is_null());
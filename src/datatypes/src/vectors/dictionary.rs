@@ -0,0 +1,356 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, DictionaryArray};
+use arrow::datatypes::UInt32Type;
+use snafu::{OptionExt, ResultExt};
+
+use crate::data_type::ConcreteDataType;
+use crate::error::{self, Result, SerializeSnafu};
+use crate::scalars::{ScalarVector, ScalarVectorBuilder};
+use crate::serialize::Serializable;
+use crate::value::{Value, ValueRef};
+use crate::vectors::operations::VectorOp;
+use crate::vectors::{
+    BooleanVector, StringVector, UInt32Vector, UInt32VectorBuilder, Validity, Vector, VectorRef,
+};
+
+/// Vector that stores the index of each value in a dictionary of values.
+///
+/// Like [ConstantVector](crate::vectors::ConstantVector), it's transparent to users: its data
+/// type is the type of the values and the arrow array it converts to is unpacked. Use
+/// [DictionaryVector::to_dictionary_array()] to get the dictionary encoded array.
+#[derive(Debug, Clone)]
+pub struct DictionaryVector {
+    keys: UInt32Vector,
+    values: VectorRef,
+}
+
+impl DictionaryVector {
+    /// Create a new [DictionaryVector].
+    ///
+    /// # Panics
+    /// Panics if a key is out of the bounds of `values`.
+    pub fn new(keys: UInt32Vector, values: VectorRef) -> Self {
+        let num_values = values.len();
+        assert!(
+            keys.iter_data()
+                .flatten()
+                .all(|k| (k as usize) < num_values),
+            "Dictionary key out of bounds, the dictionary has {num_values} values"
+        );
+
+        Self { keys, values }
+    }
+
+    pub fn keys(&self) -> &UInt32Vector {
+        &self.keys
+    }
+
+    pub fn values(&self) -> &VectorRef {
+        &self.values
+    }
+
+    /// Returns the values of the vector as a plain vector.
+    pub fn unpack(&self) -> VectorRef {
+        // Keys are checked in `new()` so `take()` won't fail.
+        self.values.take(&self.keys).unwrap()
+    }
+
+    /// Returns the vector as an arrow [DictionaryArray].
+    pub fn to_dictionary_array(&self) -> ArrayRef {
+        let values = self.values.to_arrow_array();
+        // Keys are checked in `new()` so the dictionary is always valid.
+        Arc::new(
+            DictionaryArray::<UInt32Type>::try_new(self.keys.as_arrow(), values.as_ref()).unwrap(),
+        )
+    }
+
+    fn with_keys(&self, keys: VectorRef) -> VectorRef {
+        let keys = keys
+            .as_any()
+            .downcast_ref::<UInt32Vector>()
+            .unwrap()
+            .clone();
+        Arc::new(Self {
+            keys,
+            values: self.values.clone(),
+        })
+    }
+
+    pub(crate) fn replicate_vector(&self, offsets: &[usize]) -> VectorRef {
+        self.with_keys(self.keys.replicate(offsets))
+    }
+
+    pub(crate) fn filter_vector(&self, filter: &BooleanVector) -> Result<VectorRef> {
+        Ok(self.with_keys(self.keys.filter(filter)?))
+    }
+
+    pub(crate) fn take_vector(&self, indices: &UInt32Vector) -> Result<VectorRef> {
+        Ok(self.with_keys(self.keys.take(indices)?))
+    }
+}
+
+impl Vector for DictionaryVector {
+    fn data_type(&self) -> ConcreteDataType {
+        self.values.data_type()
+    }
+
+    fn vector_type_name(&self) -> String {
+        "DictionaryVector".to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn to_arrow_array(&self) -> ArrayRef {
+        self.unpack().to_arrow_array()
+    }
+
+    fn to_boxed_arrow_array(&self) -> Box<dyn Array> {
+        self.unpack().to_boxed_arrow_array()
+    }
+
+    fn validity(&self) -> Validity {
+        if self.values.null_count() == 0 {
+            self.keys.validity()
+        } else {
+            self.unpack().validity()
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        self.keys.memory_size() + self.values.memory_size()
+    }
+
+    fn null_count(&self) -> usize {
+        if self.values.null_count() == 0 {
+            self.keys.null_count()
+        } else {
+            (0..self.len()).filter(|i| self.is_null(*i)).count()
+        }
+    }
+
+    fn is_null(&self, row: usize) -> bool {
+        match self.keys.get_data(row) {
+            Some(key) => self.values.is_null(key as usize),
+            None => true,
+        }
+    }
+
+    fn slice(&self, offset: usize, length: usize) -> VectorRef {
+        self.with_keys(self.keys.slice(offset, length))
+    }
+
+    fn get(&self, index: usize) -> Value {
+        match self.keys.get_data(index) {
+            Some(key) => self.values.get(key as usize),
+            None => Value::Null,
+        }
+    }
+
+    fn get_ref(&self, index: usize) -> ValueRef {
+        match self.keys.get_data(index) {
+            Some(key) => self.values.get_ref(key as usize),
+            None => ValueRef::Null,
+        }
+    }
+}
+
+impl Serializable for DictionaryVector {
+    fn serialize_to_json(&self) -> Result<Vec<serde_json::Value>> {
+        (0..self.len())
+            .map(|i| serde_json::Value::try_from(self.get(i)))
+            .collect::<serde_json::Result<_>>()
+            .context(SerializeSnafu)
+    }
+}
+
+/// Builds [DictionaryVector]s of strings sharing the same dictionary.
+///
+/// The values vector is only rebuilt when new values are added, so vectors built between two
+/// additions share the storage of their values. Rebuilding copies all values, so the
+/// dictionary is reset once the values copied outnumber the rows encoded, e.g. for a column
+/// with few repeated values, which bounds the copies by the rows encoded.
+#[derive(Debug, Default)]
+pub struct StringDictionaryBuilder {
+    /// Keys of the values, sharing the strings with `values`.
+    keys: HashMap<Arc<str>, u32>,
+    values: Vec<Arc<str>>,
+    /// Values vector of the dictionary, `None` if it's outdated.
+    values_vector: Option<VectorRef>,
+    /// Rows encoded since the dictionary is reset.
+    rows_encoded: usize,
+    /// Values copied to build the values vectors since the dictionary is reset.
+    values_copied: usize,
+}
+
+impl StringDictionaryBuilder {
+    /// Encodes the `vector` with the dictionary.
+    ///
+    /// Returns an error if `vector` is not a string vector.
+    pub fn encode(&mut self, vector: &dyn Vector) -> Result<DictionaryVector> {
+        let strings = vector
+            .as_any()
+            .downcast_ref::<StringVector>()
+            .with_context(|| error::UnsupportedOperationSnafu {
+                op: "dictionary encoding",
+                vector_type: vector.vector_type_name(),
+            })?;
+
+        if self.values_copied > self.rows_encoded {
+            self.reset();
+        }
+        let mut keys = UInt32VectorBuilder::with_capacity(strings.len());
+        for value in strings.iter_data() {
+            keys.push(value.map(|value| self.key_of(value)));
+        }
+        self.rows_encoded += strings.len();
+
+        let values = match &self.values_vector {
+            Some(values) => values.clone(),
+            None => {
+                let values: VectorRef = Arc::new(StringVector::from_iterator(
+                    self.values.iter().map(|value| value.as_ref()),
+                ));
+                self.values_copied += self.values.len();
+                self.values_vector = Some(values.clone());
+                values
+            }
+        };
+
+        Ok(DictionaryVector {
+            keys: keys.finish(),
+            values,
+        })
+    }
+
+    /// Returns the number of distinct values in the dictionary.
+    pub fn num_values(&self) -> usize {
+        self.values.len()
+    }
+
+    fn key_of(&mut self, value: &str) -> u32 {
+        if let Some(key) = self.keys.get(value) {
+            return *key;
+        }
+
+        let key = self.values.len() as u32;
+        let value: Arc<str> = Arc::from(value);
+        let _ = self.keys.insert(value.clone(), key);
+        self.values.push(value);
+        self.values_vector = None;
+        key
+    }
+
+    fn reset(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+        self.values_vector = None;
+        self.rows_encoded = 0;
+        self.values_copied = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::DataType as ArrowDataType;
+
+    use super::*;
+
+    #[test]
+    fn test_dictionary_vector_misc() {
+        let mut builder = StringDictionaryBuilder::default();
+        let input = StringVector::from(vec![Some("a"), None, Some("b"), Some("a")]);
+        let v = builder.encode(&input).unwrap();
+
+        assert_eq!("DictionaryVector", v.vector_type_name());
+        assert_eq!(ConcreteDataType::string_datatype(), v.data_type());
+        assert_eq!(4, v.len());
+        assert_eq!(1, v.null_count());
+        assert!(v.is_null(1));
+        for i in 0..input.len() {
+            assert_eq!(input.get(i), v.get(i));
+            assert_eq!(input.get_ref(i), v.get_ref(i));
+        }
+        assert_eq!(input.to_arrow_array(), v.to_arrow_array());
+        assert_eq!(
+            input.serialize_to_json().unwrap(),
+            v.serialize_to_json().unwrap()
+        );
+
+        let dict = v.to_dictionary_array();
+        assert_eq!(
+            &ArrowDataType::Dictionary(
+                Box::new(ArrowDataType::UInt32),
+                Box::new(ArrowDataType::Utf8)
+            ),
+            dict.data_type()
+        );
+
+        let sliced = v.slice(2, 2);
+        assert_eq!(Value::from("b"), sliced.get(0));
+        assert_eq!(Value::from("a"), sliced.get(1));
+        let taken = v.take(&UInt32Vector::from_slice([3, 0])).unwrap();
+        assert_eq!("DictionaryVector", taken.vector_type_name());
+        assert_eq!(Value::from("a"), taken.get(0));
+    }
+
+    #[test]
+    fn test_reuse_dictionary_values() {
+        let mut builder = StringDictionaryBuilder::default();
+        let first = builder
+            .encode(&StringVector::from(vec!["host1", "host2"]))
+            .unwrap();
+        let second = builder
+            .encode(&StringVector::from(vec!["host2", "host1", "host1"]))
+            .unwrap();
+        assert_eq!(2, builder.num_values());
+        assert!(Arc::ptr_eq(first.values(), second.values()));
+        assert_eq!(Value::from("host2"), second.get(0));
+
+        let third = builder.encode(&StringVector::from(vec!["host3"])).unwrap();
+        assert_eq!(3, builder.num_values());
+        assert!(!Arc::ptr_eq(first.values(), third.values()));
+        assert_eq!(Value::from("host3"), third.get(0));
+    }
+
+    #[test]
+    fn test_reset_distinct_values() {
+        let mut builder = StringDictionaryBuilder::default();
+        for i in 0..100 {
+            let input = StringVector::from(vec![format!("host{i}"), format!("host{}", i + 1000)]);
+            let v = builder.encode(&input).unwrap();
+            assert_eq!(Value::from(format!("host{i}")), v.get(0));
+            assert_eq!(Value::from(format!("host{}", i + 1000)), v.get(1));
+            // The dictionary doesn't grow with the distinct values.
+            assert!(builder.num_values() <= 4, "{}", builder.num_values());
+        }
+    }
+
+    #[test]
+    fn test_encode_non_string() {
+        let mut builder = StringDictionaryBuilder::default();
+        assert!(builder.encode(&UInt32Vector::from_slice([1])).is_err());
+    }
+}
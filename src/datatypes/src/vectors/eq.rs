@@ -17,6 +17,7 @@ use std::sync::Arc;
 use crate::data_type::DataType;
 use crate::types::TimestampType;
 use crate::vectors::constant::ConstantVector;
+use crate::vectors::dictionary::DictionaryVector;
 use crate::vectors::{
     BinaryVector, BooleanVector, DateTimeVector, DateVector, ListVector, PrimitiveVector,
     StringVector, TimestampMicrosecondVector, TimestampMillisecondVector,
@@ -69,6 +70,18 @@ fn equal(lhs: &dyn Vector, rhs: &dyn Vector) -> bool {
         );
     }
 
+    let lhs_dict = lhs.as_any().downcast_ref::<DictionaryVector>();
+    let rhs_dict = rhs.as_any().downcast_ref::<DictionaryVector>();
+    if lhs_dict.is_some() || rhs_dict.is_some() {
+        // Dictionaries may differ, so compare the values.
+        let lhs_values = lhs_dict.map(DictionaryVector::unpack);
+        let rhs_values = rhs_dict.map(DictionaryVector::unpack);
+        return equal(
+            lhs_values.as_deref().unwrap_or(lhs),
+            rhs_values.as_deref().unwrap_or(rhs),
+        );
+    }
+
     use crate::data_type::ConcreteDataType::*;
 
     let lhs_type = lhs.data_type();
@@ -23,6 +23,7 @@ use common_base::BitVec;
 use crate::error::{self, Result};
 use crate::types::LogicalPrimitiveType;
 use crate::vectors::constant::ConstantVector;
+use crate::vectors::dictionary::DictionaryVector;
 use crate::vectors::{
     BinaryVector, BooleanVector, ConcreteDataType, ListVector, NullVector, PrimitiveVector,
     StringVector, UInt32Vector, Vector, VectorRef,
@@ -174,3 +175,30 @@ impl VectorOp for ConstantVector {
         self.take_vector(indices)
     }
 }
+
+impl VectorOp for DictionaryVector {
+    fn replicate(&self, offsets: &[usize]) -> VectorRef {
+        self.replicate_vector(offsets)
+    }
+
+    fn find_unique(&self, selected: &mut BitVec, prev_vector: Option<&dyn Vector>) {
+        // Vectors may have different dictionaries, so compare the values instead of the keys.
+        let prev_values = prev_vector
+            .and_then(|pv| pv.as_any().downcast_ref::<DictionaryVector>())
+            .map(DictionaryVector::unpack);
+        let prev_vector = prev_values.as_deref().or(prev_vector);
+        self.unpack().find_unique(selected, prev_vector);
+    }
+
+    fn filter(&self, filter: &BooleanVector) -> Result<VectorRef> {
+        self.filter_vector(filter)
+    }
+
+    fn cast(&self, to_type: &ConcreteDataType) -> Result<VectorRef> {
+        self.unpack().cast(to_type)
+    }
+
+    fn take(&self, indices: &UInt32Vector) -> Result<VectorRef> {
+        self.take_vector(indices)
+    }
+}
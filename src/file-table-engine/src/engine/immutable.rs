@@ -22,22 +22,26 @@ use common_procedure::{BoxedProcedure, ProcedureManager};
 use common_telemetry::{debug, logging};
 use datatypes::schema::Schema;
 use object_store::ObjectStore;
-use snafu::ResultExt;
+use snafu::{ensure, OptionExt, ResultExt};
 use table::engine::{table_dir, EngineContext, TableEngine, TableEngineProcedure, TableReference};
 use table::error::TableOperationSnafu;
-use table::metadata::{TableInfo, TableInfoBuilder, TableMetaBuilder, TableType};
-use table::requests::{AlterTableRequest, CreateTableRequest, DropTableRequest, OpenTableRequest};
+use table::metadata::{RawTableInfo, TableInfo, TableInfoBuilder, TableMetaBuilder, TableType};
+use table::requests::{
+    AlterKind, AlterTableRequest, CreateTableRequest, DropTableRequest, OpenTableRequest,
+};
 use table::{error as table_error, Result as TableResult, Table, TableRef};
 use tokio::sync::Mutex;
 
 use crate::config::EngineConfig;
-use crate::engine::procedure::{self, CreateImmutableFileTable, DropImmutableFileTable};
+use crate::engine::procedure::{
+    self, AlterImmutableFileTable, CreateImmutableFileTable, DropImmutableFileTable,
+};
 use crate::engine::INIT_TABLE_VERSION;
 use crate::error::{
-    BuildTableInfoSnafu, BuildTableMetaSnafu, DropTableSnafu, InvalidRawSchemaSnafu, Result,
-    TableExistsSnafu,
+    AlterTableSnafu, BuildTableInfoSnafu, BuildTableMetaSnafu, DropTableSnafu,
+    InvalidRawSchemaSnafu, Result, TableExistsSnafu, TableNotFoundSnafu,
 };
-use crate::manifest::immutable::{delete_table_manifest, ImmutableMetadata};
+use crate::manifest::immutable::{delete_table_manifest, update_table_manifest, ImmutableMetadata};
 use crate::manifest::table_manifest_dir;
 use crate::table::immutable::{ImmutableFileTable, ImmutableFileTableRef};
 
@@ -80,12 +84,13 @@ impl TableEngine for ImmutableFileTableEngine {
     async fn alter_table(
         &self,
         _ctx: &EngineContext,
-        _req: AlterTableRequest,
+        req: AlterTableRequest,
     ) -> TableResult<TableRef> {
-        table_error::UnsupportedSnafu {
-            operation: "ALTER TABLE",
-        }
-        .fail()
+        self.inner
+            .alter_table(req)
+            .await
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)
     }
 
     fn get_table(
@@ -131,12 +136,14 @@ impl TableEngineProcedure for ImmutableFileTableEngine {
     fn alter_table_procedure(
         &self,
         _ctx: &EngineContext,
-        _request: AlterTableRequest,
+        request: AlterTableRequest,
     ) -> TableResult<BoxedProcedure> {
-        table_error::UnsupportedSnafu {
-            operation: "ALTER TABLE",
-        }
-        .fail()
+        let procedure = Box::new(
+            AlterImmutableFileTable::new(request, self.clone())
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?,
+        );
+        Ok(procedure)
     }
 
     fn drop_table_procedure(
@@ -363,6 +370,90 @@ impl EngineInner {
         Ok(table)
     }
 
+    async fn alter_table(&self, req: AlterTableRequest) -> Result<TableRef> {
+        let table_ref = req.table_ref();
+        let table_full_name = table_ref.to_string();
+
+        let _lock = self.table_mutex.lock().await;
+        if let AlterKind::RenameTable { new_table_name } = &req.alter_kind {
+            let new_table_ref = TableReference {
+                table: new_table_name,
+                ..table_ref
+            };
+            ensure!(
+                self.get_table(&new_table_ref).is_none(),
+                TableExistsSnafu {
+                    table_name: new_table_ref.to_string(),
+                }
+            );
+        }
+        let table = self
+            .tables
+            .read()
+            .unwrap()
+            .get(&table_full_name)
+            .cloned()
+            .context(TableNotFoundSnafu {
+                table_name: &table_full_name,
+            })?;
+
+        let table_info = table.table_info();
+        let mut new_info = TableInfo::clone(&table_info);
+        match &req.alter_kind {
+            AlterKind::RenameTable { new_table_name } => new_info.name = new_table_name.clone(),
+            // Columns missing in the files are filled with nulls and the storage options only
+            // apply to SSTs, so the files don't change.
+            AlterKind::AddColumns { .. }
+            | AlterKind::DropColumns { .. }
+            | AlterKind::SetColumnOptions { .. } => {
+                new_info.meta = table_info
+                    .meta
+                    .builder_with_alter_kind(&table_info.name, &req.alter_kind)
+                    .context(AlterTableSnafu {
+                        table_name: &table_full_name,
+                    })?
+                    .build()
+                    .context(BuildTableMetaSnafu {
+                        table_name: &table_full_name,
+                    })?;
+            }
+        }
+        new_info.ident.version = table_info.ident.version + 1;
+
+        let table_dir = table_dir(
+            &table_info.catalog_name,
+            &table_info.schema_name,
+            table_info.ident.table_id,
+        );
+        let metadata = update_table_manifest(
+            &table_full_name,
+            &table_manifest_dir(&table_dir),
+            &self.object_store,
+            table.metadata().version,
+            RawTableInfo::from(new_info.clone()),
+        )
+        .await?;
+        table.set_metadata(new_info, metadata);
+
+        if let AlterKind::RenameTable { new_table_name } = &req.alter_kind {
+            let new_table_ref = TableReference {
+                table: new_table_name,
+                ..table_ref
+            };
+            let mut tables = self.tables.write().unwrap();
+            tables.remove(&table_full_name);
+            tables.insert(new_table_ref.to_string(), table.clone());
+        }
+
+        logging::info!(
+            "Immutable file engine altered table: {}, alter kind: {:?}",
+            table_full_name,
+            req.alter_kind
+        );
+
+        Ok(table)
+    }
+
     async fn drop_table(&self, req: DropTableRequest) -> Result<bool> {
         let table_ref = TableReference {
             catalog: &req.catalog_name,
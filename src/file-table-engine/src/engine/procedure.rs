@@ -14,12 +14,14 @@
 
 //! Procedures for immutable file table engine.
 
+mod alter;
 mod create;
 mod drop;
 
 use common_procedure::ProcedureManager;
 
 use crate::engine::immutable::ImmutableFileTableEngine;
+pub(crate) use crate::engine::procedure::alter::AlterImmutableFileTable;
 pub(crate) use crate::engine::procedure::create::CreateImmutableFileTable;
 pub(crate) use crate::engine::procedure::drop::DropImmutableFileTable;
 
@@ -33,5 +35,6 @@ pub(crate) fn register_procedure_loaders(
 ) {
     // The procedure names are expected to be unique, so we just panic on error.
     CreateImmutableFileTable::register_loader(engine.clone(), procedure_manager);
+    AlterImmutableFileTable::register_loader(engine.clone(), procedure_manager);
     DropImmutableFileTable::register_loader(engine, procedure_manager);
 }
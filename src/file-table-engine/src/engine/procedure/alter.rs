@@ -0,0 +1,154 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedure to alter an immutable file table.
+
+use async_trait::async_trait;
+use common_procedure::error::{FromJsonSnafu, ToJsonSnafu};
+use common_procedure::{Context, Error, LockKey, Procedure, ProcedureManager, Result, Status};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use table::engine::{EngineContext, TableEngine, TableReference};
+use table::metadata::TableVersion;
+use table::requests::{AlterKind, AlterTableRequest};
+
+use crate::engine::immutable::ImmutableFileTableEngine;
+use crate::error::TableNotFoundSnafu;
+
+/// Procedure to alter an immutable file table.
+pub(crate) struct AlterImmutableFileTable {
+    data: AlterTableData,
+    engine: ImmutableFileTableEngine,
+}
+
+#[async_trait]
+impl Procedure for AlterImmutableFileTable {
+    fn type_name(&self) -> &str {
+        Self::TYPE_NAME
+    }
+
+    async fn execute(&mut self, _ctx: &Context) -> Result<Status> {
+        let engine_ctx = EngineContext::default();
+        // The table is altered if its version is bumped, which happens when the procedure
+        // is retried after altering the table.
+        if let Ok(Some(table)) = self
+            .engine
+            .get_table(&engine_ctx, &self.data.altered_table_ref())
+        {
+            if table.table_info().ident.version > self.data.table_version {
+                return Ok(Status::Done);
+            }
+        }
+
+        self.engine
+            .alter_table(&engine_ctx, self.data.request.clone())
+            .await
+            .map_err(Error::from_error_ext)?;
+
+        Ok(Status::Done)
+    }
+
+    fn dump(&self) -> Result<String> {
+        let json = serde_json::to_string(&self.data).context(ToJsonSnafu)?;
+        Ok(json)
+    }
+
+    fn lock_key(&self) -> LockKey {
+        // We don't need to support multiple region so we only lock region-0.
+        let table_ref = self.data.table_ref();
+        let mut keys = vec![format!("{table_ref}/region-0")];
+        // If alter kind is rename, we also need to lock the table with another name.
+        if let AlterKind::RenameTable { new_table_name } = &self.data.request.alter_kind {
+            let new_table_ref = TableReference {
+                table: new_table_name,
+                ..table_ref
+            };
+            keys.push(format!("{new_table_ref}/region-0"));
+        }
+        LockKey::new(keys)
+    }
+}
+
+impl AlterImmutableFileTable {
+    const TYPE_NAME: &str = "file-table-engine:AlterImmutableFileTable";
+
+    pub(crate) fn new(
+        request: AlterTableRequest,
+        engine: ImmutableFileTableEngine,
+    ) -> crate::error::Result<Self> {
+        let table_ref = request.table_ref();
+        let table = engine
+            .get_table(&EngineContext::default(), &table_ref)
+            .ok()
+            .flatten()
+            .context(TableNotFoundSnafu {
+                table_name: table_ref.to_string(),
+            })?;
+        let table_version = table.table_info().ident.version;
+
+        Ok(AlterImmutableFileTable {
+            data: AlterTableData {
+                request,
+                table_version,
+            },
+            engine,
+        })
+    }
+
+    pub(crate) fn register_loader(
+        engine: ImmutableFileTableEngine,
+        procedure_manager: &dyn ProcedureManager,
+    ) {
+        procedure_manager
+            .register_loader(
+                Self::TYPE_NAME,
+                Box::new(move |data| {
+                    Self::from_json(data, engine.clone()).map(|p| Box::new(p) as _)
+                }),
+            )
+            .unwrap()
+    }
+
+    fn from_json(json: &str, engine: ImmutableFileTableEngine) -> Result<Self> {
+        let data: AlterTableData = serde_json::from_str(json).context(FromJsonSnafu)?;
+
+        Ok(AlterImmutableFileTable { data, engine })
+    }
+}
+
+/// Serializable data of [AlterImmutableFileTable].
+#[derive(Debug, Serialize, Deserialize)]
+struct AlterTableData {
+    request: AlterTableRequest,
+    /// Version of the table before alteration.
+    table_version: TableVersion,
+}
+
+impl AlterTableData {
+    fn table_ref(&self) -> TableReference {
+        self.request.table_ref()
+    }
+
+    /// Returns the reference of the table after alteration.
+    fn altered_table_ref(&self) -> TableReference {
+        let table_ref = self.table_ref();
+        match &self.request.alter_kind {
+            AlterKind::RenameTable { new_table_name } => TableReference {
+                table: new_table_name,
+                ..table_ref
+            },
+            _ => table_ref,
+        }
+    }
+}
@@ -16,8 +16,16 @@ use std::assert_matches::assert_matches;
 use std::sync::Arc;
 
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, IMMUTABLE_FILE_ENGINE};
+use common_datasource::file_format::{
+    FORMAT_DELIMITER, FORMAT_DELIMTERL, FORMAT_HAS_HEADER, FORMAT_SCHEMA_INFER_MAX_RECORD,
+    FORMAT_TYPE,
+};
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::ColumnSchema;
 use table::engine::{EngineContext, TableEngine, TableEngineProcedure, TableReference};
-use table::requests::{AlterKind, AlterTableRequest, DropTableRequest, OpenTableRequest};
+use table::requests::{
+    AddColumnRequest, AlterKind, AlterTableRequest, DropTableRequest, OpenTableRequest,
+};
 use table::{error as table_error, Table};
 
 use crate::config::EngineConfig;
@@ -96,6 +104,40 @@ async fn test_open_table() {
     assert_eq!(left, right);
 }
 
+#[tokio::test]
+async fn test_create_table_with_canonical_format_options() {
+    let (_dir, object_store) = test_util::new_test_object_store("canonical_format_options");
+    let table_engine = ImmutableFileTableEngine::new(EngineConfig::default(), object_store);
+
+    let mut request = test_util::new_create_request(Arc::new(test_util::test_schema()));
+    let _ = request
+        .table_options
+        .extra_options
+        .insert(FORMAT_DELIMTERL.to_string(), b'\t'.to_string());
+
+    let table = table_engine
+        .create_table(&EngineContext::default(), request)
+        .await
+        .unwrap();
+    let table = table.as_any().downcast_ref::<ImmutableFileTable>().unwrap();
+
+    let expected = [
+        (FORMAT_TYPE, "csv"),
+        (FORMAT_HAS_HEADER, "true"),
+        (FORMAT_DELIMITER, "9"),
+        (FORMAT_SCHEMA_INFER_MAX_RECORD, "1000"),
+    ];
+    for options in [
+        &table.table_info().meta.options.extra_options,
+        &table.metadata().table_info.meta.options.extra_options,
+    ] {
+        assert!(!options.contains_key(FORMAT_DELIMTERL));
+        for (key, value) in expected {
+            assert_eq!(Some(value), options.get(key).map(String::as_str), "{key}");
+        }
+    }
+}
+
 #[tokio::test]
 async fn test_close_all_table() {
     common_telemetry::init_default_ut_logging();
@@ -127,8 +169,31 @@ async fn test_alter_table() {
         dir: _dir,
         ..
     } = test_util::setup_test_engine_and_table("test_alter_table").await;
+    let ctx = EngineContext::default();
 
-    let alter_req = AlterTableRequest {
+    let add_column_req = AlterTableRequest {
+        catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+        schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+        table_name: TEST_TABLE_NAME.to_string(),
+        alter_kind: AlterKind::AddColumns {
+            columns: vec![AddColumnRequest {
+                column_schema: ColumnSchema::new(
+                    "extra",
+                    ConcreteDataType::string_datatype(),
+                    true,
+                ),
+                is_key: false,
+            }],
+        },
+    };
+    let table = table_engine
+        .alter_table(&ctx, add_column_req)
+        .await
+        .unwrap();
+    assert!(table.schema().column_schema_by_name("extra").is_some());
+    assert_eq!(1, table.table_info().ident.version);
+
+    let rename_req = AlterTableRequest {
         catalog_name: DEFAULT_CATALOG_NAME.to_string(),
         schema_name: DEFAULT_SCHEMA_NAME.to_string(),
         table_name: TEST_TABLE_NAME.to_string(),
@@ -136,14 +201,42 @@ async fn test_alter_table() {
             new_table_name: "foo".to_string(),
         },
     };
+    let table = table_engine
+        .alter_table(&ctx, rename_req.clone())
+        .await
+        .unwrap();
+    assert_eq!("foo", table.table_info().name);
+    let renamed_ref = TableReference {
+        catalog: DEFAULT_CATALOG_NAME,
+        schema: DEFAULT_SCHEMA_NAME,
+        table: "foo",
+    };
+    assert!(table_engine.table_exists(&ctx, &renamed_ref));
+    assert!(!table_engine.table_exists(&ctx, &rename_req.table_ref()));
 
-    let unsupported = table_engine
-        .alter_table(&EngineContext::default(), alter_req)
+    // The alterations are persisted in the manifest.
+    table_engine.close_table(&renamed_ref).await.unwrap();
+    let reopened = table_engine
+        .open_table(
+            &ctx,
+            OpenTableRequest {
+                catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "foo".to_string(),
+                table_id: 1,
+            },
+        )
         .await
-        .err()
+        .unwrap()
         .unwrap();
+    assert_eq!(table.table_info(), reopened.table_info());
 
-    assert_matches!(unsupported, table_error::Error::Unsupported { .. })
+    // The table is not found under the old name.
+    let not_found = table_engine
+        .alter_table(&ctx, rename_req)
+        .await
+        .unwrap_err();
+    assert_matches!(not_found, table_error::Error::TableOperation { .. });
 }
 
 #[tokio::test]
@@ -192,7 +285,7 @@ async fn test_drop_table() {
 }
 
 #[tokio::test]
-async fn test_create_drop_table_procedure() {
+async fn test_create_alter_drop_table_procedure() {
     let (_dir, object_store) = test_util::new_test_object_store("procedure");
 
     let table_engine = ImmutableFileTableEngine::new(EngineConfig::default(), object_store.clone());
@@ -212,11 +305,37 @@ async fn test_create_drop_table_procedure() {
         .unwrap()
         .is_some());
 
+    // Test alter table by procedure.
+    let alter_request = AlterTableRequest {
+        catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+        schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+        table_name: TEST_TABLE_NAME.to_string(),
+        alter_kind: AlterKind::RenameTable {
+            new_table_name: "foo".to_string(),
+        },
+    };
+    let mut procedure = table_engine
+        .alter_table_procedure(&engine_ctx, alter_request.clone())
+        .unwrap();
+    common_procedure_test::execute_procedure_until_done(&mut procedure).await;
+    // Executing the procedure again is a no-op.
+    common_procedure_test::execute_procedure_until_done(&mut procedure).await;
+
+    let renamed_ref = TableReference {
+        catalog: DEFAULT_CATALOG_NAME,
+        schema: DEFAULT_SCHEMA_NAME,
+        table: "foo",
+    };
+    assert!(table_engine
+        .get_table(&engine_ctx, &renamed_ref)
+        .unwrap()
+        .is_some());
+
     // Test drop table by procedure.
     let drop_request = DropTableRequest {
         catalog_name: DEFAULT_CATALOG_NAME.to_string(),
         schema_name: DEFAULT_SCHEMA_NAME.to_string(),
-        table_name: TEST_TABLE_NAME.to_string(),
+        table_name: "foo".to_string(),
     };
     let mut procedure = table_engine
         .drop_table_procedure(&engine_ctx, drop_request)
@@ -21,6 +21,8 @@ use serde_json::error::Error as JsonError;
 use snafu::Location;
 use table::metadata::{TableInfoBuilderError, TableMetaBuilderError};
 
+use crate::manifest::immutable::MetadataVersion;
+
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub))]
 pub enum Error {
@@ -82,6 +84,49 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Failed to list table manifests, table: {}, source: {}",
+        table_name,
+        source,
+    ))]
+    ListTableManifest {
+        source: object_store::Error,
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Manifest of table {} has changed, expected version: {}, actual version: {}",
+        table_name,
+        expected,
+        actual
+    ))]
+    ManifestVersionChanged {
+        table_name: String,
+        expected: MetadataVersion,
+        actual: MetadataVersion,
+        location: Location,
+    },
+
+    #[snafu(display("Table not found: {}", table_name))]
+    TableNotFound {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to alter table {}, source: {}", table_name, source))]
+    AlterTable {
+        table_name: String,
+        #[snafu(backtrace)]
+        source: table::error::Error,
+    },
+
+    #[snafu(display("No previous manifest of table {} to roll back to", table_name))]
+    NoPreviousManifest {
+        table_name: String,
+        location: Location,
+    },
+
     #[snafu(display(
         "Failed to build table meta for table: {}, source: {}",
         table_name,
@@ -178,6 +223,20 @@ pub enum Error {
         source: datatypes::error::Error,
         location: Location,
     },
+
+    #[snafu(display("Invalid value of table option {}: {}", key, value))]
+    InvalidTableOption {
+        key: String,
+        value: String,
+        location: Location,
+    },
+
+    #[snafu(display("File {} of table {} is missing", path, table_name))]
+    MissingFile {
+        path: String,
+        table_name: String,
+        location: Location,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -195,7 +254,13 @@ impl ErrorExt for Error {
             | BuildCsvConfig { .. }
             | ProjectSchema { .. }
             | MissingRequiredField { .. }
-            | ConvertSchema { .. } => StatusCode::InvalidArguments,
+            | ConvertSchema { .. }
+            | InvalidTableOption { .. }
+            | NoPreviousManifest { .. } => StatusCode::InvalidArguments,
+
+            TableNotFound { .. } => StatusCode::TableNotFound,
+
+            AlterTable { source, .. } => source.status_code(),
 
             BuildBackend { source, .. } => source.status_code(),
             BuildStreamAdapter { source, .. } => source.status_code(),
@@ -204,13 +269,16 @@ impl ErrorExt for Error {
             WriteTableManifest { .. }
             | DeleteTableManifest { .. }
             | ReadTableManifest { .. }
-            | CheckObject { .. } => StatusCode::StorageUnavailable,
+            | ListTableManifest { .. }
+            | CheckObject { .. }
+            | MissingFile { .. } => StatusCode::StorageUnavailable,
 
             EncodeJson { .. }
             | DecodeJson { .. }
             | ConvertRaw { .. }
             | DropTable { .. }
             | WriteImmutableManifest { .. }
+            | ManifestVersionChanged { .. }
             | BuildStream { .. }
             | ParquetScanPlan { .. } => StatusCode::Unexpected,
         }
@@ -18,6 +18,7 @@ pub mod config;
 pub mod engine;
 pub mod error;
 pub mod manifest;
+mod metrics;
 pub mod table;
 #[cfg(any(test, feature = "test"))]
 pub(crate) mod test_util;
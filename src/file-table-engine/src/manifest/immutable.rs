@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_telemetry::logging;
+use futures::TryStreamExt;
 use object_store::ObjectStore;
 use serde::{Deserialize, Serialize};
 use snafu::{ensure, ResultExt};
@@ -19,13 +21,17 @@ use table::metadata::RawTableInfo;
 
 use crate::error::{
     CheckObjectSnafu, DecodeJsonSnafu, DeleteTableManifestSnafu, EncodeJsonSnafu,
+    ListTableManifestSnafu, ManifestVersionChangedSnafu, NoPreviousManifestSnafu,
     ReadTableManifestSnafu, Result, WriteImmutableManifestSnafu, WriteTableManifestSnafu,
 };
 
 pub type MetadataVersion = u32;
 pub const INIT_META_VERSION: MetadataVersion = 0;
 
+/// The manifest written on creating the table, whose version is [INIT_META_VERSION]. Later
+/// versions are written to `_immutable_manifest.<version>`.
 const IMMUTABLE_MANIFEST_FILE: &str = "_immutable_manifest";
+const TMP_MANIFEST_INFIX: &str = ".tmp.";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ImmutableMetadata {
@@ -45,11 +51,81 @@ pub fn manifest_path(dir: &str) -> String {
     format!("{}{}", dir, IMMUTABLE_MANIFEST_FILE)
 }
 
+fn versioned_manifest_path(dir: &str, version: MetadataVersion) -> String {
+    if version == INIT_META_VERSION {
+        manifest_path(dir)
+    } else {
+        format!("{}{}.{}", dir, IMMUTABLE_MANIFEST_FILE, version)
+    }
+}
+
+fn tmp_manifest_path(dir: &str, version: MetadataVersion) -> String {
+    format!(
+        "{}{}{}{}",
+        dir, IMMUTABLE_MANIFEST_FILE, TMP_MANIFEST_INFIX, version
+    )
+}
+
+/// Returns the version of the manifest file named `file_name`, or `None` if it's not a
+/// manifest, e.g. a temporary manifest whose swap is not done.
+fn manifest_version(file_name: &str) -> Option<MetadataVersion> {
+    let suffix = file_name.strip_prefix(IMMUTABLE_MANIFEST_FILE)?;
+    if suffix.is_empty() {
+        return Some(INIT_META_VERSION);
+    }
+    suffix
+        .strip_prefix('.')?
+        .parse()
+        .ok()
+        .filter(|version| *version != INIT_META_VERSION)
+}
+
+/// Lists the versions of the manifests in `dir`, in descending order.
+async fn list_manifest_versions(
+    table_name: &str,
+    dir: &str,
+    object_store: &ObjectStore,
+) -> Result<Vec<MetadataVersion>> {
+    let lister = object_store
+        .list(dir)
+        .await
+        .context(ListTableManifestSnafu { table_name })?;
+    let mut versions = lister
+        .try_filter_map(|entry| async move { Ok(manifest_version(entry.name())) })
+        .try_collect::<Vec<_>>()
+        .await
+        .context(ListTableManifestSnafu { table_name })?;
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(versions)
+}
+
 pub(crate) async fn delete_table_manifest(
     table_name: &str,
     dir: &str,
     object_store: &ObjectStore,
 ) -> Result<()> {
+    let lister = object_store
+        .list(dir)
+        .await
+        .context(ListTableManifestSnafu { table_name })?;
+    let paths = lister
+        .try_filter_map(|entry| async move {
+            Ok(entry
+                .name()
+                .starts_with(IMMUTABLE_MANIFEST_FILE)
+                .then(|| entry.path().to_string()))
+        })
+        .try_collect::<Vec<_>>()
+        .await
+        .context(ListTableManifestSnafu { table_name })?;
+
+    // Deletes the base manifest at last, so the table is still readable if the deletion fails.
+    for path in paths.iter().filter(|path| **path != manifest_path(dir)) {
+        object_store
+            .delete(path)
+            .await
+            .context(DeleteTableManifestSnafu { table_name })?;
+    }
     object_store
         .delete(&manifest_path(dir))
         .await
@@ -78,14 +154,158 @@ pub(crate) async fn write_table_manifest(
         .context(WriteTableManifestSnafu { table_name })
 }
 
+/// Writes `table_info` as the next version of the manifest in `dir` and returns it. The
+/// latest version must still be `expected_version`, otherwise another writer has updated the
+/// manifest and the update fails.
+///
+/// The manifest is written to a temporary object first and then swapped in, so readers
+/// never see a partial manifest. The base manifest and the previous version are kept, the
+/// latter for [rollback_table_manifest], and the versions between them are deleted.
+pub async fn update_table_manifest(
+    table_name: &str,
+    dir: &str,
+    object_store: &ObjectStore,
+    expected_version: MetadataVersion,
+    table_info: RawTableInfo,
+) -> Result<ImmutableMetadata> {
+    let current = read_table_manifest(table_name, dir, object_store).await?;
+    ensure!(
+        current.version == expected_version,
+        ManifestVersionChangedSnafu {
+            table_name,
+            expected: expected_version,
+            actual: current.version,
+        }
+    );
+    let metadata = ImmutableMetadata {
+        table_info,
+        version: current.version + 1,
+    };
+
+    write_tmp_manifest(table_name, dir, object_store, &metadata).await?;
+    swap_tmp_manifest(table_name, dir, object_store, metadata.version).await?;
+
+    // The base manifest marks the table exists, and the previous version is kept for rollback.
+    let versions = list_manifest_versions(table_name, dir, object_store).await?;
+    for version in versions
+        .into_iter()
+        .filter(|v| *v != INIT_META_VERSION && *v < current.version)
+    {
+        object_store
+            .delete(&versioned_manifest_path(dir, version))
+            .await
+            .context(DeleteTableManifestSnafu { table_name })?;
+    }
+
+    Ok(metadata)
+}
+
+async fn write_tmp_manifest(
+    table_name: &str,
+    dir: &str,
+    object_store: &ObjectStore,
+    metadata: &ImmutableMetadata,
+) -> Result<()> {
+    let bs = encode_metadata(metadata)?;
+    object_store
+        .write(&tmp_manifest_path(dir, metadata.version), bs)
+        .await
+        .context(WriteTableManifestSnafu { table_name })
+}
+
+/// Publishes the temporary manifest of `version` as the manifest of the version. Writing an
+/// object is atomic, so the versioned manifest is either absent or complete.
+async fn swap_tmp_manifest(
+    table_name: &str,
+    dir: &str,
+    object_store: &ObjectStore,
+    version: MetadataVersion,
+) -> Result<()> {
+    let tmp_path = tmp_manifest_path(dir, version);
+    let bs = object_store
+        .read(&tmp_path)
+        .await
+        .context(ReadTableManifestSnafu { table_name })?;
+    // Makes sure the temporary manifest is complete before publishing it.
+    let _ = decode_metadata(&bs)?;
+
+    // Another writer has published the version since we read the manifest.
+    let path = versioned_manifest_path(dir, version);
+    let exist = object_store
+        .is_exist(&path)
+        .await
+        .context(CheckObjectSnafu { path: &path })?;
+    ensure!(
+        !exist,
+        ManifestVersionChangedSnafu {
+            table_name,
+            expected: version - 1,
+            actual: version,
+        }
+    );
+
+    object_store
+        .write(&path, bs)
+        .await
+        .context(WriteTableManifestSnafu { table_name })?;
+    object_store
+        .delete(&tmp_path)
+        .await
+        .context(DeleteTableManifestSnafu { table_name })
+}
+
+/// Deletes the latest version of the manifest in `dir` and returns the previous version,
+/// which becomes the latest.
+pub async fn rollback_table_manifest(
+    table_name: &str,
+    dir: &str,
+    object_store: &ObjectStore,
+) -> Result<ImmutableMetadata> {
+    let versions = list_manifest_versions(table_name, dir, object_store).await?;
+    ensure!(versions.len() > 1, NoPreviousManifestSnafu { table_name });
+
+    object_store
+        .delete(&versioned_manifest_path(dir, versions[0]))
+        .await
+        .context(DeleteTableManifestSnafu { table_name })?;
+
+    read_table_manifest(table_name, dir, object_store).await
+}
+
+/// Reads the manifest of the highest complete version in `dir`. Temporary manifests left by
+/// unfinished updates are ignored.
 pub(crate) async fn read_table_manifest(
     table_name: &str,
     dir: &str,
     object_store: &ObjectStore,
 ) -> Result<ImmutableMetadata> {
-    let path = manifest_path(dir);
+    let versions = list_manifest_versions(table_name, dir, object_store).await?;
+    for version in versions {
+        let path = versioned_manifest_path(dir, version);
+        let bs = object_store
+            .read(&path)
+            .await
+            .context(ReadTableManifestSnafu { table_name })?;
+        match decode_metadata(&bs) {
+            Ok(metadata) if metadata.version == version => return Ok(metadata),
+            Ok(metadata) => logging::warn!(
+                "Ignore manifest {} of table {}, its version is {}",
+                path,
+                table_name,
+                metadata.version
+            ),
+            Err(e) => logging::warn!(
+                "Ignore incomplete manifest {} of table {}, error: {}",
+                path,
+                table_name,
+                e
+            ),
+        }
+    }
+
+    // Reports the missing base manifest.
     let bs = object_store
-        .read(&path)
+        .read(&manifest_path(dir))
         .await
         .context(ReadTableManifestSnafu { table_name })?;
 
@@ -189,4 +409,200 @@ mod tests {
 
         assert!(!exist);
     }
+
+    fn renamed_table_info(metadata: &ImmutableMetadata, name: &str) -> RawTableInfo {
+        let mut table_info = metadata.table_info.clone();
+        table_info.name = name.to_string();
+        table_info
+    }
+
+    #[tokio::test]
+    async fn test_update_table_manifest() {
+        let (_dir, store) = new_test_object_store("test_update_table_manifest");
+        let metadata = build_test_table_metadata();
+        let table_dir = &table_manifest_dir(TEST_TABLE_NAME);
+        write_table_manifest(TEST_TABLE_NAME, table_dir, &store, &metadata)
+            .await
+            .unwrap();
+
+        let updated = update_table_manifest(
+            TEST_TABLE_NAME,
+            table_dir,
+            &store,
+            0,
+            renamed_table_info(&metadata, "demo1"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(1, updated.version);
+        assert_eq!("demo1", updated.table_info.name);
+        let read = read_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap();
+        assert_eq!(updated, read);
+
+        let updated = update_table_manifest(
+            TEST_TABLE_NAME,
+            table_dir,
+            &store,
+            1,
+            renamed_table_info(&metadata, "demo2"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(2, updated.version);
+        let read = read_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap();
+        assert_eq!(updated, read);
+
+        let _ = update_table_manifest(
+            TEST_TABLE_NAME,
+            table_dir,
+            &store,
+            2,
+            renamed_table_info(&metadata, "demo3"),
+        )
+        .await
+        .unwrap();
+        // Only the base manifest and the previous version are kept.
+        assert_eq!(
+            vec![3, 2, 0],
+            list_manifest_versions(TEST_TABLE_NAME, table_dir, &store)
+                .await
+                .unwrap()
+        );
+
+        // The update based on a stale version fails.
+        let changed = update_table_manifest(
+            TEST_TABLE_NAME,
+            table_dir,
+            &store,
+            2,
+            renamed_table_info(&metadata, "demo4"),
+        )
+        .await
+        .unwrap_err();
+        assert_matches!(
+            changed,
+            Error::ManifestVersionChanged {
+                expected: 2,
+                actual: 3,
+                ..
+            }
+        );
+
+        delete_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap();
+        assert!(list_manifest_versions(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_table_manifest_crash_before_swap() {
+        let (_dir, store) = new_test_object_store("test_update_table_manifest_crash_before_swap");
+        let metadata = build_test_table_metadata();
+        let table_dir = &table_manifest_dir(TEST_TABLE_NAME);
+        write_table_manifest(TEST_TABLE_NAME, table_dir, &store, &metadata)
+            .await
+            .unwrap();
+
+        // Crashes after writing the temporary manifest, before swapping it in.
+        let staged = ImmutableMetadata {
+            table_info: renamed_table_info(&metadata, "demo1"),
+            version: 1,
+        };
+        write_tmp_manifest(TEST_TABLE_NAME, table_dir, &store, &staged)
+            .await
+            .unwrap();
+        assert!(store
+            .is_exist(&tmp_manifest_path(table_dir, 1))
+            .await
+            .unwrap());
+        // An incomplete manifest of a higher version is ignored too.
+        store
+            .write(
+                &versioned_manifest_path(table_dir, 2),
+                b"{\"table_info".to_vec(),
+            )
+            .await
+            .unwrap();
+
+        let read = read_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap();
+        assert_eq!(metadata, read);
+
+        // The next update overwrites the stray temporary manifest.
+        store
+            .delete(&versioned_manifest_path(table_dir, 2))
+            .await
+            .unwrap();
+        let updated = update_table_manifest(
+            TEST_TABLE_NAME,
+            table_dir,
+            &store,
+            0,
+            renamed_table_info(&metadata, "demo2"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(1, updated.version);
+        assert_eq!("demo2", updated.table_info.name);
+        assert!(!store
+            .is_exist(&tmp_manifest_path(table_dir, 1))
+            .await
+            .unwrap());
+        let read = read_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap();
+        assert_eq!(updated, read);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_table_manifest() {
+        let (_dir, store) = new_test_object_store("test_rollback_table_manifest");
+        let metadata = build_test_table_metadata();
+        let table_dir = &table_manifest_dir(TEST_TABLE_NAME);
+        write_table_manifest(TEST_TABLE_NAME, table_dir, &store, &metadata)
+            .await
+            .unwrap();
+
+        let no_previous = rollback_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap_err();
+        assert_matches!(no_previous, Error::NoPreviousManifest { .. });
+
+        let _ = update_table_manifest(
+            TEST_TABLE_NAME,
+            table_dir,
+            &store,
+            0,
+            renamed_table_info(&metadata, "demo1"),
+        )
+        .await
+        .unwrap();
+
+        let rolled_back = rollback_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap();
+        assert_eq!(metadata, rolled_back);
+        let read = read_table_manifest(TEST_TABLE_NAME, table_dir, &store)
+            .await
+            .unwrap();
+        assert_eq!(metadata, read);
+    }
+
+    #[test]
+    fn test_manifest_version() {
+        assert_eq!(Some(0), manifest_version("_immutable_manifest"));
+        assert_eq!(Some(3), manifest_version("_immutable_manifest.3"));
+        assert_eq!(None, manifest_version("_immutable_manifest.0"));
+        assert_eq!(None, manifest_version("_immutable_manifest.tmp.3"));
+        assert_eq!(None, manifest_version("_immutable_manifest.x"));
+        assert_eq!(None, manifest_version("data.csv"));
+    }
 }
@@ -12,35 +12,148 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use common_datasource::file_format::csv::{CsvConfigBuilder, CsvFormat, CsvOpener};
 use common_datasource::file_format::json::{JsonFormat, JsonOpener};
+use common_datasource::file_format::orc::{OrcFormat, OrcOpener};
 use common_datasource::file_format::parquet::{DefaultParquetFileReaderFactory, ParquetFormat};
 use common_datasource::file_format::Format;
 use common_query::physical_plan::{PhysicalPlanAdapter, PhysicalPlanRef};
 use common_query::prelude::Expr;
 use common_recordbatch::adapter::RecordBatchStreamAdapter;
+use common_telemetry::warn;
 use datafusion::common::ToDFSchema;
 use datafusion::datasource::listing::PartitionedFile;
 use datafusion::datasource::object_store::ObjectStoreUrl;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
 use datafusion::optimizer::utils::conjunction;
 use datafusion::physical_expr::create_physical_expr;
 use datafusion::physical_expr::execution_props::ExecutionProps;
-use datafusion::physical_plan::file_format::{FileOpener, FileScanConfig, FileStream, ParquetExec};
+use datafusion::physical_plan::file_format::{
+    FileMeta, FileOpenFuture, FileOpener, FileScanConfig, FileStream, ParquetExec,
+};
 use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
 use datatypes::arrow::datatypes::Schema as ArrowSchema;
 use datatypes::schema::{Schema, SchemaRef};
-use object_store::ObjectStore;
+use futures::StreamExt;
+use metrics::increment_counter;
+use object_store::{ErrorKind, ObjectStore};
 use snafu::ResultExt;
 use table::table::scan::SimpleTableScan;
 
 use crate::error::{self, Result};
+use crate::metrics::SCAN_SKIPPED_FILES;
 
 const DEFAULT_BATCH_SIZE: usize = 8192;
 
-#[derive(Debug, Clone, Copy, Default)]
-pub struct CreateScanPlanContext {}
+/// How a scan handles the files that are missing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnMissingFile {
+    /// Fails the scan with an error naming the missing file.
+    #[default]
+    Error,
+    /// Logs a warning, counts the file in [ScanMetrics] and continues with the other files.
+    Skip,
+}
+
+#[derive(Debug, Default)]
+pub struct ScanMetrics {
+    skipped_files: AtomicUsize,
+}
+
+impl ScanMetrics {
+    /// Returns the number of missing files skipped by the scans so far.
+    pub fn skipped_files(&self) -> usize {
+        self.skipped_files.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CreateScanPlanContext {
+    pub table_name: String,
+    pub on_missing_file: OnMissingFile,
+    pub metrics: Arc<ScanMetrics>,
+}
+
+impl CreateScanPlanContext {
+    /// Handles the missing file at `path`, returns an error if the scan should fail.
+    pub fn handle_missing_file(&self, path: &str) -> Result<()> {
+        match self.on_missing_file {
+            OnMissingFile::Error => error::MissingFileSnafu {
+                path,
+                table_name: &self.table_name,
+            }
+            .fail(),
+            OnMissingFile::Skip => {
+                warn!("Skip missing file {path} of table {}", self.table_name);
+                self.metrics.skipped_files.fetch_add(1, Ordering::Relaxed);
+                increment_counter!(SCAN_SKIPPED_FILES);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the files that still exist, handling the missing ones.
+    pub async fn existing_files(
+        &self,
+        store: &ObjectStore,
+        files: &[String],
+    ) -> Result<Vec<String>> {
+        let exists = futures::future::try_join_all(files.iter().map(|path| async move {
+            store
+                .is_exist(path)
+                .await
+                .context(error::CheckObjectSnafu { path })
+        }))
+        .await?;
+
+        let mut existing = Vec::with_capacity(files.len());
+        for (path, exist) in files.iter().zip(exists) {
+            if exist {
+                existing.push(path.clone());
+            } else {
+                self.handle_missing_file(path)?;
+            }
+        }
+        Ok(existing)
+    }
+}
+
+/// Handles the files that disappear after the scan plan is built, when they are opened.
+struct MissingFileOpener<T> {
+    inner: T,
+    ctx: CreateScanPlanContext,
+}
+
+impl<T: FileOpener> FileOpener for MissingFileOpener<T> {
+    fn open(&self, meta: FileMeta) -> DataFusionResult<FileOpenFuture> {
+        let path = meta.location().to_string();
+        let open = self.inner.open(meta)?;
+        let ctx = self.ctx.clone();
+        Ok(Box::pin(async move {
+            match open.await {
+                Err(e) if is_not_found(&e) => {
+                    ctx.handle_missing_file(&path)
+                        .map_err(|e| DataFusionError::External(Box::new(e)))?;
+                    Ok(futures::stream::empty().boxed())
+                }
+                result => result,
+            }
+        }))
+    }
+}
+
+fn is_not_found(e: &DataFusionError) -> bool {
+    match e {
+        DataFusionError::External(e) => e
+            .downcast_ref::<object_store::Error>()
+            .map(|e| e.kind() == ErrorKind::NotFound)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
 
 fn build_csv_opener(
     file_schema: Arc<ArrowSchema>,
@@ -85,12 +198,17 @@ fn build_json_opener(
 }
 
 fn build_scan_plan<T: FileOpener + Send + 'static>(
+    ctx: &CreateScanPlanContext,
     opener: T,
     file_schema: Arc<ArrowSchema>,
     files: &[String],
     projection: Option<&Vec<usize>>,
     limit: Option<usize>,
 ) -> Result<PhysicalPlanRef> {
+    let opener = MissingFileOpener {
+        inner: opener,
+        ctx: ctx.clone(),
+    };
     let stream = FileStream::new(
         &FileScanConfig {
             object_store_url: ObjectStoreUrl::parse("empty://").unwrap(), // won't be used
@@ -117,13 +235,14 @@ fn build_scan_plan<T: FileOpener + Send + 'static>(
 }
 
 fn new_csv_scan_plan(
-    _ctx: &CreateScanPlanContext,
+    ctx: &CreateScanPlanContext,
     config: &ScanPlanConfig,
     format: &CsvFormat,
 ) -> Result<PhysicalPlanRef> {
     let file_schema = config.file_schema.arrow_schema().clone();
     let opener = build_csv_opener(file_schema.clone(), config, format)?;
     build_scan_plan(
+        ctx,
         opener,
         file_schema,
         config.files,
@@ -133,13 +252,31 @@ fn new_csv_scan_plan(
 }
 
 fn new_json_scan_plan(
-    _ctx: &CreateScanPlanContext,
+    ctx: &CreateScanPlanContext,
     config: &ScanPlanConfig,
     format: &JsonFormat,
 ) -> Result<PhysicalPlanRef> {
     let file_schema = config.file_schema.arrow_schema().clone();
     let opener = build_json_opener(file_schema.clone(), config, format)?;
     build_scan_plan(
+        ctx,
+        opener,
+        file_schema,
+        config.files,
+        config.projection,
+        config.limit,
+    )
+}
+
+fn new_orc_scan_plan(
+    ctx: &CreateScanPlanContext,
+    config: &ScanPlanConfig,
+    _format: &OrcFormat,
+) -> Result<PhysicalPlanRef> {
+    let file_schema = config.file_schema.arrow_schema().clone();
+    let opener = OrcOpener::new(config.store.clone(), config.projection.cloned());
+    build_scan_plan(
+        ctx,
         opener,
         file_schema,
         config.files,
@@ -196,6 +333,8 @@ fn new_parquet_scan_plan(
         None
     };
 
+    // The opener of `ParquetExec` can't be wrapped, so the missing files are only handled
+    // when `REFRESH_FILES` checks them before the scan.
     let exec = ParquetExec::new(scan_config, filters, None).with_parquet_file_reader_factory(
         Arc::new(DefaultParquetFileReaderFactory::new(store.clone())),
     );
@@ -237,5 +376,6 @@ pub fn create_physical_plan(
         Format::Csv(format) => new_csv_scan_plan(ctx, config, format),
         Format::Json(format) => new_json_scan_plan(ctx, config, format),
         Format::Parquet(format) => new_parquet_scan_plan(ctx, config, format),
+        Format::Orc(format) => new_orc_scan_plan(ctx, config, format),
     }
 }
@@ -13,14 +13,15 @@
 // limitations under the License.
 
 use std::any::Any;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use async_trait::async_trait;
-use common_datasource::file_format::Format;
+use common_datasource::file_format::{normalize_format_options, Format};
 use common_datasource::object_store::build_backend;
 use common_error::prelude::BoxedError;
 use common_query::physical_plan::PhysicalPlanRef;
 use common_query::prelude::Expr;
+use common_time::Timestamp;
 use datatypes::schema::SchemaRef;
 use object_store::ObjectStore;
 use serde::{Deserialize, Serialize};
@@ -35,7 +36,9 @@ use crate::manifest::immutable::{
     read_table_manifest, write_table_manifest, ImmutableMetadata, INIT_META_VERSION,
 };
 use crate::manifest::table_manifest_dir;
-use crate::table::format::{create_physical_plan, CreateScanPlanContext, ScanPlanConfig};
+use crate::table::format::{
+    create_physical_plan, CreateScanPlanContext, OnMissingFile, ScanMetrics, ScanPlanConfig,
+};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default)]
@@ -44,12 +47,16 @@ pub struct ImmutableFileTableOptions {
 }
 
 pub struct ImmutableFileTable {
-    metadata: ImmutableMetadata,
-    // currently, it's immutable
-    table_info: Arc<TableInfo>,
+    /// The manifest the table info is read from, replaced with the table info on alteration.
+    metadata: RwLock<ImmutableMetadata>,
+    table_info: RwLock<Arc<TableInfo>>,
     object_store: ObjectStore,
     files: Vec<String>,
     format: Format,
+    on_missing_file: OnMissingFile,
+    /// Whether to check the files exist before each scan.
+    refresh_files: bool,
+    scan_metrics: Arc<ScanMetrics>,
 }
 
 pub type ImmutableFileTableRef = Arc<ImmutableFileTable>;
@@ -67,7 +74,7 @@ impl Table for ImmutableFileTable {
     }
 
     fn table_info(&self) -> TableInfoRef {
-        self.table_info.clone()
+        self.table_info.read().unwrap().clone()
     }
 
     fn table_type(&self) -> TableType {
@@ -80,12 +87,26 @@ impl Table for ImmutableFileTable {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> TableResult<PhysicalPlanRef> {
+        let ctx = CreateScanPlanContext {
+            table_name: self.table_info().name.clone(),
+            on_missing_file: self.on_missing_file,
+            metrics: self.scan_metrics.clone(),
+        };
+        let files = if self.refresh_files {
+            ctx.existing_files(&self.object_store, &self.files)
+                .await
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?
+        } else {
+            self.files.clone()
+        };
+
         create_physical_plan(
             &self.format,
-            &CreateScanPlanContext::default(),
+            &ctx,
             &ScanPlanConfig {
                 file_schema: self.schema(),
-                files: &self.files,
+                files: &files,
                 projection,
                 filters,
                 limit,
@@ -108,15 +129,36 @@ impl Table for ImmutableFileTable {
     async fn close(&self) -> TableResult<()> {
         Ok(())
     }
+
+    async fn purge_expired(&self, _expire_before: Timestamp) -> TableResult<()> {
+        // The rows of the files can't be deleted.
+        table_error::UnsupportedSnafu {
+            operation: "PURGE_EXPIRED",
+        }
+        .fail()?
+    }
 }
 
 impl ImmutableFileTable {
-    #[inline]
-    pub fn metadata(&self) -> &ImmutableMetadata {
-        &self.metadata
+    pub fn metadata(&self) -> ImmutableMetadata {
+        self.metadata.read().unwrap().clone()
     }
 
-    pub(crate) fn new(table_info: TableInfo, metadata: ImmutableMetadata) -> Result<Self> {
+    /// Replaces the table info with the one of the `metadata` written on alteration.
+    pub(crate) fn set_metadata(&self, table_info: TableInfo, metadata: ImmutableMetadata) {
+        *self.table_info.write().unwrap() = Arc::new(table_info);
+        *self.metadata.write().unwrap() = metadata;
+    }
+
+    /// Returns the metrics of the scans on this table.
+    pub fn scan_metrics(&self) -> &Arc<ScanMetrics> {
+        &self.scan_metrics
+    }
+
+    pub(crate) fn new(mut table_info: TableInfo, metadata: ImmutableMetadata) -> Result<Self> {
+        // Surfaces the format options in canonical keys, so the table can be recreated from them.
+        let format = normalize_format_options(&mut table_info.meta.options.extra_options)
+            .context(error::ParseFileFormatSnafu)?;
         let table_info = Arc::new(table_info);
         let options = &table_info.meta.options.extra_options;
 
@@ -134,25 +176,52 @@ impl ImmutableFileTable {
 
         let meta: ImmutableFileTableOptions =
             serde_json::from_str(meta).context(error::DecodeJsonSnafu)?;
-        let format = Format::try_from(options).context(error::ParseFileFormatSnafu)?;
 
         let object_store = build_backend(url, options).context(error::BuildBackendSnafu)?;
 
+        let on_missing_file = match options.get(requests::IMMUTABLE_TABLE_ON_MISSING_FILE_KEY) {
+            None => OnMissingFile::default(),
+            Some(value) if value.eq_ignore_ascii_case("error") => OnMissingFile::Error,
+            Some(value) if value.eq_ignore_ascii_case("skip") => OnMissingFile::Skip,
+            Some(value) => {
+                return error::InvalidTableOptionSnafu {
+                    key: requests::IMMUTABLE_TABLE_ON_MISSING_FILE_KEY,
+                    value,
+                }
+                .fail()
+            }
+        };
+        let refresh_files = match options.get(requests::IMMUTABLE_TABLE_REFRESH_FILES_KEY) {
+            None => false,
+            Some(value) => value.to_lowercase().parse::<bool>().ok().context(
+                error::InvalidTableOptionSnafu {
+                    key: requests::IMMUTABLE_TABLE_REFRESH_FILES_KEY,
+                    value,
+                },
+            )?,
+        };
+
         Ok(Self {
-            metadata,
-            table_info,
+            metadata: RwLock::new(metadata),
+            table_info: RwLock::new(table_info),
             object_store,
             files: meta.files,
             format,
+            on_missing_file,
+            refresh_files,
+            scan_metrics: Arc::new(ScanMetrics::default()),
         })
     }
 
     pub async fn create(
         table_name: &str,
         table_dir: &str,
-        table_info: TableInfo,
+        mut table_info: TableInfo,
         object_store: ObjectStore,
     ) -> Result<ImmutableFileTable> {
+        // Stores the format options in canonical keys in the manifest.
+        let _ = normalize_format_options(&mut table_info.meta.options.extra_options)
+            .context(error::ParseFileFormatSnafu)?;
         let metadata = ImmutableMetadata {
             table_info: RawTableInfo::from(table_info.clone()),
             version: INIT_META_VERSION,
@@ -181,3 +250,154 @@ impl ImmutableFileTable {
         Ok((metadata, table_info))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use common_query::physical_plan::SessionContext;
+    use common_recordbatch::util;
+    use common_test_util::temp_dir::{create_temp_dir, TempDir};
+
+    use super::*;
+    use crate::test_util::build_test_table_info;
+
+    const NUM_FILES: usize = 3;
+
+    /// Writes the csv files and returns a table on them with the `options`.
+    async fn new_csv_table(
+        prefix: &str,
+        options: &[(&str, &str)],
+    ) -> (TempDir, ImmutableFileTable) {
+        let dir = create_temp_dir(prefix);
+        let files = (0..NUM_FILES)
+            .map(|i| {
+                let path = dir.path().join(format!("{i}.csv"));
+                std::fs::write(
+                    &path,
+                    format!("host,cpu,memory,ts\nhost{i},1.0,1024.0,2023-04-01 00:00:0{i}\n"),
+                )
+                .unwrap();
+                path.to_string_lossy().to_string()
+            })
+            .collect::<Vec<_>>();
+
+        let mut table_info = build_test_table_info();
+        let extra_options = &mut table_info.meta.options.extra_options;
+        extra_options.insert(
+            requests::IMMUTABLE_TABLE_LOCATION_KEY.to_string(),
+            dir.path().to_string_lossy().to_string(),
+        );
+        extra_options.insert(
+            requests::IMMUTABLE_TABLE_META_KEY.to_string(),
+            serde_json::to_string(&ImmutableFileTableOptions { files }).unwrap(),
+        );
+        extra_options.insert(
+            requests::IMMUTABLE_TABLE_FORMAT_KEY.to_string(),
+            "csv".to_string(),
+        );
+        for (key, value) in options {
+            extra_options.insert(key.to_string(), value.to_string());
+        }
+
+        let metadata = ImmutableMetadata {
+            table_info: RawTableInfo::from(table_info.clone()),
+            version: INIT_META_VERSION,
+        };
+        let table = ImmutableFileTable::new(table_info, metadata).unwrap();
+        (dir, table)
+    }
+
+    async fn scan_rows(table: &ImmutableFileTable) -> TableResult<usize> {
+        let plan = table.scan(None, &[], None).await?;
+        let stream = plan
+            .execute(0, SessionContext::default().task_ctx())
+            .unwrap();
+        let batches = util::collect(stream)
+            .await
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)?;
+        Ok(batches.iter().map(|batch| batch.num_rows()).sum())
+    }
+
+    #[tokio::test]
+    async fn test_skip_missing_file() {
+        let (dir, table) = new_csv_table(
+            "test_skip_missing_file",
+            &[(requests::IMMUTABLE_TABLE_ON_MISSING_FILE_KEY, "skip")],
+        )
+        .await;
+        assert_eq!(NUM_FILES, scan_rows(&table).await.unwrap());
+
+        // The file disappears after the table is created.
+        std::fs::remove_file(dir.path().join("1.csv")).unwrap();
+        assert_eq!(NUM_FILES - 1, scan_rows(&table).await.unwrap());
+        assert_eq!(1, table.scan_metrics().skipped_files());
+    }
+
+    #[tokio::test]
+    async fn test_error_on_missing_file() {
+        for refresh_files in ["false", "true"] {
+            let (dir, table) = new_csv_table(
+                "test_error_on_missing_file",
+                &[(requests::IMMUTABLE_TABLE_REFRESH_FILES_KEY, refresh_files)],
+            )
+            .await;
+            std::fs::remove_file(dir.path().join("1.csv")).unwrap();
+
+            let err = scan_rows(&table).await.unwrap_err();
+            let msg = format!("{err:?}");
+            assert!(msg.contains("1.csv"), "{msg}");
+            assert!(msg.contains("MissingFile"), "{msg}");
+            assert!(msg.contains(&table.table_info.name), "{msg}");
+            assert_eq!(0, table.scan_metrics().skipped_files());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_and_skip_missing_file() {
+        let (dir, table) = new_csv_table(
+            "test_refresh_and_skip_missing_file",
+            &[
+                (requests::IMMUTABLE_TABLE_ON_MISSING_FILE_KEY, "SKIP"),
+                (requests::IMMUTABLE_TABLE_REFRESH_FILES_KEY, "true"),
+            ],
+        )
+        .await;
+        std::fs::remove_file(dir.path().join("0.csv")).unwrap();
+        std::fs::remove_file(dir.path().join("2.csv")).unwrap();
+
+        assert_eq!(1, scan_rows(&table).await.unwrap());
+        assert_eq!(2, table.scan_metrics().skipped_files());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_missing_file_options() {
+        let dir = create_temp_dir("test_invalid_missing_file_options");
+        let options = [
+            (requests::IMMUTABLE_TABLE_ON_MISSING_FILE_KEY, "ignore"),
+            (requests::IMMUTABLE_TABLE_REFRESH_FILES_KEY, "yes"),
+        ];
+        for (key, value) in options {
+            let mut table_info = build_test_table_info();
+            let extra_options = &mut table_info.meta.options.extra_options;
+            extra_options.insert(
+                requests::IMMUTABLE_TABLE_LOCATION_KEY.to_string(),
+                dir.path().to_string_lossy().to_string(),
+            );
+            extra_options.insert(
+                requests::IMMUTABLE_TABLE_META_KEY.to_string(),
+                serde_json::to_string(&ImmutableFileTableOptions::default()).unwrap(),
+            );
+            extra_options.insert(key.to_string(), value.to_string());
+
+            let metadata = ImmutableMetadata {
+                table_info: RawTableInfo::from(table_info.clone()),
+                version: INIT_META_VERSION,
+            };
+            let err = ImmutableFileTable::new(table_info, metadata).err().unwrap();
+            assert!(
+                matches!(err, error::Error::InvalidTableOption { .. }),
+                "{err:?}"
+            );
+        }
+    }
+}
@@ -12,34 +12,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub(crate) mod invalidation;
+pub mod warm_up;
+
 use std::any::Any;
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::sync::Arc;
 
 use api::v1::CreateTableExpr;
+use async_stream::try_stream;
 use async_trait::async_trait;
 use catalog::error::{
-    self as catalog_err, InternalSnafu, InvalidCatalogValueSnafu, InvalidSystemTableDefSnafu,
-    Result as CatalogResult, UnimplementedSnafu,
+    self as catalog_err, InternalSnafu, InvalidCatalogValueSnafu, InvalidSchemaTableOptionsSnafu,
+    InvalidSystemTableDefSnafu, Result as CatalogResult, UnimplementedSnafu,
 };
 use catalog::helper::{
     build_catalog_prefix, build_schema_prefix, build_table_global_prefix, CatalogKey, SchemaKey,
-    TableGlobalKey, TableGlobalValue,
+    SchemaValue, TableGlobalKey, TableGlobalValue,
 };
-use catalog::remote::{Kv, KvBackendRef};
+use catalog::interner::intern;
+use catalog::remote::{paginate_names, range_names, Kv, KvBackendRef, KvCacheInvalidatorRef};
 use catalog::{
     CatalogManager, CatalogProvider, CatalogProviderRef, DeregisterTableRequest,
     RegisterSchemaRequest, RegisterSystemTableRequest, RegisterTableRequest, RenameTableRequest,
     SchemaProvider, SchemaProviderRef,
 };
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_catalog::format_full_table_name;
 use common_error::prelude::BoxedError;
 use common_telemetry::warn;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use futures_util::TryStreamExt;
 use meta_client::rpc::TableName;
+use moka::sync::Cache;
 use partition::manager::PartitionRuleManagerRef;
 use snafu::prelude::*;
+use table::column_limits::{ColumnLimitsOptions, ColumnLimitsOptionsRef};
+use table::metadata::{RawTableInfo, TableInfoRef};
+use table::requests::TableOptions;
 use table::table::numbers::NumbersTable;
 use table::TableRef;
 
@@ -51,8 +63,14 @@ use crate::table::DistTable;
 #[derive(Clone)]
 pub struct FrontendCatalogManager {
     backend: KvBackendRef,
+    backend_cache_invalidator: KvCacheInvalidatorRef,
     partition_manager: PartitionRuleManagerRef,
     datanode_clients: Arc<DatanodeClients>,
+    table_infos: Arc<TableInfoCache>,
+    column_limits: ColumnLimitsOptionsRef,
+    /// Set once [start](CatalogManager::start) has reached the catalogs in metasrv, shared by
+    /// the clones.
+    started: Arc<AtomicBool>,
 
     // TODO(LFC): Remove this field.
     // DistInstance in FrontendCatalogManager is only used for creating distributed script table now.
@@ -64,17 +82,28 @@ pub struct FrontendCatalogManager {
 impl FrontendCatalogManager {
     pub fn new(
         backend: KvBackendRef,
+        backend_cache_invalidator: KvCacheInvalidatorRef,
         partition_manager: PartitionRuleManagerRef,
         datanode_clients: Arc<DatanodeClients>,
     ) -> Self {
         Self {
             backend,
+            backend_cache_invalidator,
             partition_manager,
             datanode_clients,
+            table_infos: Arc::new(TableInfoCache::default()),
+            column_limits: Arc::new(ColumnLimitsOptions::default()),
+            started: Arc::new(AtomicBool::new(false)),
             dist_instance: None,
         }
     }
 
+    /// Sets the column limits of the tables, the default ones if not set.
+    pub fn with_column_limits(mut self, column_limits: ColumnLimitsOptions) -> Self {
+        self.column_limits = Arc::new(column_limits);
+        self
+    }
+
     pub(crate) fn set_dist_instance(&mut self, dist_instance: Arc<DistInstance>) {
         self.dist_instance = Some(dist_instance)
     }
@@ -90,16 +119,54 @@ impl FrontendCatalogManager {
     pub(crate) fn datanode_clients(&self) -> Arc<DatanodeClients> {
         self.datanode_clients.clone()
     }
+
+    /// Drops everything cached of the table: its info, its global value and its route. Called
+    /// after each DDL of the table, so the following statements, e.g. the ones in the same
+    /// request, see the table as the DDL left it instead of the cached one.
+    pub(crate) async fn invalidate_table(&self, table_name: &TableName) {
+        self.table_infos.invalidate(format_full_table_name(
+            &table_name.catalog_name,
+            &table_name.schema_name,
+            &table_name.table_name,
+        ));
+        let table_global_key = TableGlobalKey {
+            catalog_name: table_name.catalog_name.clone(),
+            schema_name: table_name.schema_name.clone(),
+            table_name: table_name.table_name.clone(),
+        };
+        self.backend_cache_invalidator
+            .invalidate_key(table_global_key.to_string().as_bytes())
+            .await;
+        self.partition_manager
+            .table_routes()
+            .invalidate_table_route(table_name)
+            .await;
+    }
 }
 
 // FIXME(hl): Frontend only needs a CatalogList, should replace with trait upcasting
 // as soon as it's stable: https://github.com/rust-lang/rust/issues/65991
 #[async_trait::async_trait]
 impl CatalogManager for FrontendCatalogManager {
+    /// Checks the catalogs in metasrv are readable, the tables are fetched on demand or by the
+    /// warm up afterwards.
     async fn start(&self) -> catalog::error::Result<()> {
+        let key = CatalogKey {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+        };
+        let _ = self.backend.get(key.to_string().as_bytes()).await?;
+        self.started.store(true, AtomicOrdering::Release);
         Ok(())
     }
 
+    fn is_started(&self) -> bool {
+        self.started.load(AtomicOrdering::Acquire)
+    }
+
+    fn column_limits(&self) -> ColumnLimitsOptionsRef {
+        self.column_limits.clone()
+    }
+
     async fn register_catalog(
         &self,
         _name: String,
@@ -115,10 +182,7 @@ impl CatalogManager for FrontendCatalogManager {
 
     async fn deregister_table(&self, request: DeregisterTableRequest) -> CatalogResult<bool> {
         let table_name = TableName::new(request.catalog, request.schema, request.table_name);
-        self.partition_manager
-            .table_routes()
-            .invalidate_table_route(&table_name)
-            .await;
+        self.invalidate_table(&table_name).await;
         Ok(true)
     }
 
@@ -248,10 +312,11 @@ impl CatalogManager for FrontendCatalogManager {
         .to_string();
         Ok(self.backend.get(key.as_bytes()).await?.map(|_| {
             Arc::new(FrontendCatalogProvider {
-                catalog_name: catalog.to_string(),
+                catalog_name: intern(catalog),
                 backend: self.backend.clone(),
                 partition_manager: self.partition_manager.clone(),
                 datanode_clients: self.datanode_clients.clone(),
+                table_infos: self.table_infos.clone(),
             }) as Arc<_>
         }))
     }
@@ -270,6 +335,16 @@ impl CatalogManager for FrontendCatalogManager {
             .await
     }
 
+    async fn schema_exist(&self, catalog: &str, schema: &str) -> catalog::error::Result<bool> {
+        self.catalog(catalog)
+            .await?
+            .context(catalog::error::CatalogNotFoundSnafu {
+                catalog_name: catalog,
+            })?
+            .schema_exist(schema)
+            .await
+    }
+
     async fn table(
         &self,
         catalog: &str,
@@ -289,10 +364,11 @@ impl CatalogManager for FrontendCatalogManager {
 }
 
 pub struct FrontendCatalogProvider {
-    catalog_name: String,
+    catalog_name: Arc<str>,
     backend: KvBackendRef,
     partition_manager: PartitionRuleManagerRef,
     datanode_clients: Arc<DatanodeClients>,
+    table_infos: Arc<TableInfoCache>,
 }
 
 #[async_trait::async_trait]
@@ -314,6 +390,19 @@ impl CatalogProvider for FrontendCatalogProvider {
         Ok(res.into_iter().collect())
     }
 
+    async fn schema_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> CatalogResult<(Vec<String>, bool)> {
+        let key = build_schema_prefix(&self.catalog_name);
+        let schema_names = range_names(self.backend.clone(), key, start_after, |key| {
+            let key = SchemaKey::parse(key).context(InvalidCatalogValueSnafu)?;
+            Ok(key.schema_name)
+        });
+        paginate_names(schema_names, limit).await
+    }
+
     async fn register_schema(
         &self,
         _name: String,
@@ -323,27 +412,40 @@ impl CatalogProvider for FrontendCatalogProvider {
     }
 
     async fn schema(&self, name: &str) -> catalog::error::Result<Option<SchemaProviderRef>> {
-        let all_schemas = self.schema_names().await?;
-        if all_schemas.contains(&name.to_string()) {
-            Ok(Some(Arc::new(FrontendSchemaProvider {
+        let key = SchemaKey {
+            catalog_name: self.catalog_name.to_string(),
+            schema_name: name.to_string(),
+        }
+        .to_string();
+        Ok(self.backend.get(key.as_bytes()).await?.map(|_| {
+            Arc::new(FrontendSchemaProvider {
                 catalog_name: self.catalog_name.clone(),
-                schema_name: name.to_string(),
+                schema_name: intern(name),
                 backend: self.backend.clone(),
                 partition_manager: self.partition_manager.clone(),
                 datanode_clients: self.datanode_clients.clone(),
-            })))
-        } else {
-            Ok(None)
+                table_infos: self.table_infos.clone(),
+            }) as Arc<_>
+        }))
+    }
+
+    async fn schema_exist(&self, name: &str) -> catalog::error::Result<bool> {
+        let key = SchemaKey {
+            catalog_name: self.catalog_name.to_string(),
+            schema_name: name.to_string(),
         }
+        .to_string();
+        self.backend.exists(key.as_bytes()).await
     }
 }
 
 pub struct FrontendSchemaProvider {
-    catalog_name: String,
-    schema_name: String,
+    catalog_name: Arc<str>,
+    schema_name: Arc<str>,
     backend: KvBackendRef,
     partition_manager: PartitionRuleManagerRef,
     datanode_clients: Arc<DatanodeClients>,
+    table_infos: Arc<TableInfoCache>,
 }
 
 #[async_trait]
@@ -354,7 +456,8 @@ impl SchemaProvider for FrontendSchemaProvider {
 
     async fn table_names(&self) -> catalog::error::Result<Vec<String>> {
         let mut tables = vec![];
-        if self.catalog_name == DEFAULT_CATALOG_NAME && self.schema_name == DEFAULT_SCHEMA_NAME {
+        if &*self.catalog_name == DEFAULT_CATALOG_NAME && &*self.schema_name == DEFAULT_SCHEMA_NAME
+        {
             tables.push("numbers".to_string());
         }
         let key = build_table_global_prefix(&self.catalog_name, &self.schema_name);
@@ -372,45 +475,226 @@ impl SchemaProvider for FrontendSchemaProvider {
         Ok(tables)
     }
 
-    async fn table(&self, name: &str) -> catalog::error::Result<Option<TableRef>> {
-        if self.catalog_name == DEFAULT_CATALOG_NAME
-            && self.schema_name == DEFAULT_SCHEMA_NAME
-            && name == "numbers"
+    async fn table_names_paginated(
+        &self,
+        start_after: Option<&str>,
+        limit: usize,
+    ) -> CatalogResult<(Vec<String>, bool)> {
+        let key = build_table_global_prefix(&self.catalog_name, &self.schema_name);
+        let table_names = range_names(self.backend.clone(), key, start_after, |key| {
+            let key = TableGlobalKey::parse(key).context(InvalidCatalogValueSnafu)?;
+            Ok(key.table_name)
+        });
+        if &*self.catalog_name == DEFAULT_CATALOG_NAME && &*self.schema_name == DEFAULT_SCHEMA_NAME
         {
+            paginate_names(with_numbers_table(table_names, start_after), limit).await
+        } else {
+            paginate_names(table_names, limit).await
+        }
+    }
+
+    async fn default_table_options(&self) -> catalog::error::Result<TableOptions> {
+        let key = SchemaKey {
+            catalog_name: self.catalog_name.to_string(),
+            schema_name: self.schema_name.to_string(),
+        }
+        .to_string();
+        let Some(Kv(_, value)) = self.backend.get(key.as_bytes()).await? else {
+            return Ok(TableOptions::default());
+        };
+        SchemaValue::from_bytes(value)
+            .context(InvalidCatalogValueSnafu)?
+            .table_options()
+            .context(InvalidSchemaTableOptionsSnafu {
+                schema: &*self.schema_name,
+            })
+    }
+
+    async fn table(&self, name: &str) -> catalog::error::Result<Option<TableRef>> {
+        if is_numbers_table(&self.catalog_name, &self.schema_name, name) {
             return Ok(Some(Arc::new(NumbersTable::default())));
         }
 
         let table_global_key = TableGlobalKey {
-            catalog_name: self.catalog_name.clone(),
-            schema_name: self.schema_name.clone(),
+            catalog_name: self.catalog_name.to_string(),
+            schema_name: self.schema_name.to_string(),
             table_name: name.to_string(),
         };
-        let Some(kv) = self.backend.get(table_global_key.to_string().as_bytes()).await? else { return Ok(None) };
-        let v = TableGlobalValue::from_bytes(kv.1).context(InvalidCatalogValueSnafu)?;
-        let table_info = Arc::new(
-            v.table_info
-                .try_into()
-                .context(catalog_err::InvalidTableInfoInCatalogSnafu)?,
-        );
-        let table = Arc::new(DistTable::new(
-            TableName::new(&self.catalog_name, &self.schema_name, name),
+        let Some(kv) = self.backend.get(table_global_key.to_string().as_bytes()).await? else {
+            self.evict_table_info(name);
+            return Ok(None);
+        };
+        self.dist_table(name, kv.1).map(Some)
+    }
+
+    async fn tables(&self, names: &[String]) -> catalog::error::Result<Vec<Option<TableRef>>> {
+        let keys = names
+            .iter()
+            .map(|name| {
+                TableGlobalKey {
+                    catalog_name: self.catalog_name.to_string(),
+                    schema_name: self.schema_name.to_string(),
+                    table_name: name.clone(),
+                }
+                .to_string()
+                .into_bytes()
+            })
+            .collect::<Vec<_>>();
+        // Fetches the tables in one request instead of one per table.
+        let mut values = self
+            .backend
+            .batch_get(&keys)
+            .await?
+            .into_iter()
+            .map(|Kv(key, value)| (key, value))
+            .collect::<HashMap<_, _>>();
+
+        let mut tables = Vec::with_capacity(names.len());
+        for (name, key) in names.iter().zip(keys) {
+            let table = if is_numbers_table(&self.catalog_name, &self.schema_name, name) {
+                Some(Arc::new(NumbersTable::default()) as _)
+            } else if let Some(value) = values.remove(&key) {
+                Some(self.dist_table(name, value)?)
+            } else {
+                self.evict_table_info(name);
+                None
+            };
+            tables.push(table);
+        }
+        Ok(tables)
+    }
+
+    async fn table_exist(&self, name: &str) -> catalog::error::Result<bool> {
+        if is_numbers_table(&self.catalog_name, &self.schema_name, name) {
+            return Ok(true);
+        }
+
+        let table_global_key = TableGlobalKey {
+            catalog_name: self.catalog_name.to_string(),
+            schema_name: self.schema_name.to_string(),
+            table_name: name.to_string(),
+        };
+        self.backend
+            .exists(table_global_key.to_string().as_bytes())
+            .await
+    }
+}
+
+impl FrontendSchemaProvider {
+    /// Evicts the cached info of the table `name`, which is gone from metasrv. It may have been
+    /// dropped or renamed by another frontend, before this frontend polls the invalidation.
+    fn evict_table_info(&self, name: &str) {
+        self.table_infos.invalidate(format_full_table_name(
+            &self.catalog_name,
+            &self.schema_name,
+            name,
+        ));
+    }
+
+    /// Returns the distributed table `name` of the table global value `value`.
+    fn dist_table(&self, name: &str, value: Vec<u8>) -> catalog::error::Result<TableRef> {
+        let v = TableGlobalValue::from_bytes(value).context(InvalidCatalogValueSnafu)?;
+        let table_info = self.table_infos.get_or_insert(
+            format_full_table_name(&self.catalog_name, &self.schema_name, name),
+            v.table_info,
+        )?;
+        Ok(Arc::new(DistTable::new(
+            TableName::new(self.catalog_name.as_ref(), self.schema_name.as_ref(), name),
             table_info,
             self.partition_manager.clone(),
             self.datanode_clients.clone(),
             self.backend.clone(),
-        ));
-        Ok(Some(table))
+        )))
     }
+}
 
-    async fn table_exist(&self, name: &str) -> catalog::error::Result<bool> {
-        Ok(self.table_names().await?.contains(&name.to_string()))
+fn is_numbers_table(catalog_name: &str, schema_name: &str, table_name: &str) -> bool {
+    catalog_name == DEFAULT_CATALOG_NAME
+        && schema_name == DEFAULT_SCHEMA_NAME
+        && table_name == "numbers"
+}
+
+/// Max number of the table infos cached by a frontend.
+const TABLE_INFO_CACHE_CAPACITY: u64 = 10000;
+
+/// Shares the [TableInfo](table::metadata::TableInfo) of a table between lookups, until the
+/// table is altered, renamed or dropped, instead of decoding a new copy for each lookup. The
+/// infos used the least are evicted beyond the capacity.
+struct TableInfoCache {
+    /// Keyed by the full table name.
+    infos: Cache<String, TableInfoRef>,
+}
+
+impl Default for TableInfoCache {
+    fn default() -> Self {
+        Self::with_capacity(TABLE_INFO_CACHE_CAPACITY)
+    }
+}
+
+/// Merges the name of the `numbers` table into the ascending `table_names` of the default schema.
+fn with_numbers_table(
+    table_names: impl Stream<Item = CatalogResult<String>>,
+    start_after: Option<&str>,
+) -> impl Stream<Item = CatalogResult<String>> {
+    let numbers = Some("numbers".to_string())
+        .filter(|numbers| start_after.map_or(true, |start| start < numbers.as_str()));
+    try_stream!({
+        let mut numbers = numbers;
+        for await table_name in table_names {
+            let table_name = table_name?;
+            match numbers.as_deref().map(|name| name.cmp(table_name.as_str())) {
+                Some(Ordering::Less) => yield numbers.take().unwrap(),
+                // The `numbers` table shadows the table of the same name.
+                Some(Ordering::Equal) => numbers = None,
+                _ => {}
+            }
+            yield table_name;
+        }
+        if let Some(name) = numbers {
+            yield name;
+        }
+    })
+}
+
+impl TableInfoCache {
+    fn with_capacity(capacity: u64) -> Self {
+        Self {
+            infos: Cache::new(capacity),
+        }
+    }
+
+    fn get_or_insert(
+        &self,
+        full_table_name: String,
+        raw_info: RawTableInfo,
+    ) -> CatalogResult<TableInfoRef> {
+        if let Some(info) = self.infos.get(&full_table_name) {
+            if info.ident == raw_info.ident {
+                return Ok(info);
+            }
+        }
+
+        let info: TableInfoRef = Arc::new(
+            raw_info
+                .try_into()
+                .context(catalog_err::InvalidTableInfoInCatalogSnafu)?,
+        );
+        self.infos.insert(full_table_name, info.clone());
+        Ok(info)
+    }
+
+    fn invalidate(&self, full_table_name: String) {
+        self.infos.invalidate(&full_table_name);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MITO_ENGINE};
+    use moka::sync::ConcurrentCacheExt;
     use script::table::{build_scripts_schema, SCRIPTS_TABLE_NAME};
+    use servers::query_handler::sql::SqlQueryHandler;
+    use session::context::QueryContext;
     use table::requests::{CreateTableRequest, TableOptions};
 
     use super::*;
@@ -473,4 +757,132 @@ mod tests {
             "system table should be actually created at one and only one datanode"
         )
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_batch_get_tables() {
+        let instance = crate::tests::create_distributed_instance("test_batch_get_tables").await;
+        for name in ["a", "b"] {
+            let sql = format!("CREATE TABLE {name} (ts TIMESTAMP TIME INDEX)");
+            let _ = instance
+                .frontend
+                .do_query(&sql, QueryContext::arc())
+                .await
+                .remove(0)
+                .unwrap();
+        }
+
+        let schema = instance
+            .catalog_manager
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let names = ["b", "missing", "numbers", "a"].map(|name| name.to_string());
+        let tables = schema
+            .tables(&names)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|table| table.map(|table| table.table_info().name.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some("b".to_string()),
+                None,
+                Some("numbers".to_string()),
+                Some("a".to_string())
+            ],
+            tables
+        );
+    }
+
+    #[test]
+    fn test_table_info_cache() {
+        let raw_info =
+            |table_id| RawTableInfo::from((*NumbersTable::new(table_id).table_info()).clone());
+        let cache = TableInfoCache::with_capacity(16);
+        let name = "greptime.public.t0".to_string();
+        let info = cache.get_or_insert(name.clone(), raw_info(0)).unwrap();
+        let cached = cache.get_or_insert(name.clone(), raw_info(0)).unwrap();
+        assert!(Arc::ptr_eq(&info, &cached));
+
+        cache.invalidate(name.clone());
+        let decoded = cache.get_or_insert(name, raw_info(0)).unwrap();
+        assert!(!Arc::ptr_eq(&info, &decoded));
+
+        for table_id in 0..100 {
+            let name = format!("greptime.public.t{table_id}");
+            let _ = cache.get_or_insert(name, raw_info(table_id)).unwrap();
+        }
+        cache.infos.sync();
+        assert!(cache.infos.entry_count() <= 16);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_evict_table_info_of_removed_table() {
+        let instance =
+            crate::tests::create_distributed_instance("test_evict_table_info_of_removed_table")
+                .await;
+        let sql = "CREATE TABLE removed (ts TIMESTAMP TIME INDEX)";
+        let _ = instance
+            .frontend
+            .do_query(sql, QueryContext::arc())
+            .await
+            .remove(0)
+            .unwrap();
+
+        let catalog_manager = &instance.catalog_manager;
+        let schema = catalog_manager
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let full_table_name =
+            format_full_table_name(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "removed");
+        assert!(schema.table("removed").await.unwrap().is_some());
+        assert!(catalog_manager
+            .table_infos
+            .infos
+            .contains_key(&full_table_name));
+
+        // Removes the table behind the frontend, like a drop or a rename by another frontend
+        // whose invalidation is not polled yet.
+        let key = TableGlobalKey {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "removed".to_string(),
+        };
+        catalog_manager
+            .backend()
+            .delete(key.to_string().as_bytes())
+            .await
+            .unwrap();
+        assert!(schema.table("removed").await.unwrap().is_none());
+        assert!(!catalog_manager
+            .table_infos
+            .infos
+            .contains_key(&full_table_name));
+    }
+
+    #[tokio::test]
+    async fn test_with_numbers_table() {
+        async fn merge(table_names: &[&str], start_after: Option<&str>) -> Vec<String> {
+            let table_names = table_names
+                .iter()
+                .map(|name| Ok(name.to_string()))
+                .collect::<Vec<_>>();
+            with_numbers_table(futures::stream::iter(table_names), start_after)
+                .try_collect()
+                .await
+                .unwrap()
+        }
+
+        assert_eq!(vec!["numbers"], merge(&[], None).await);
+        assert_eq!(vec!["a", "numbers", "z"], merge(&["a", "z"], None).await);
+        assert_eq!(vec!["a", "numbers"], merge(&["a", "numbers"], None).await);
+        assert_eq!(vec!["a", "b", "numbers"], merge(&["a", "b"], None).await);
+        // The names streamed are already after `start_after`.
+        assert_eq!(vec!["numbers", "z"], merge(&["z"], Some("a")).await);
+        assert_eq!(vec!["z"], merge(&["z"], Some("numbers")).await);
+    }
 }
@@ -0,0 +1,271 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Invalidation of the metadata of the tables cached by the other frontends after a DDL, and of
+//! the catalog entries changed through the store of metasrv, see [TableInvalidationValue].
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use catalog::error::Result;
+use catalog::helper::{
+    build_table_invalidation_prefix, build_table_invalidation_start, InvalidatedTable,
+    TableGlobalKey, TableInvalidationKey, TableInvalidationValue,
+};
+use catalog::remote::KvBackend;
+use common_telemetry::warn;
+use common_time::util::current_time_millis;
+use meta_client::rpc::{Peer, TableName};
+
+use crate::catalog::FrontendCatalogManager;
+
+/// Interval of polling the invalidations published by the other frontends.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How far before the last poll the invalidations are polled again, covering the ones
+/// published meanwhile by a frontend whose clock is behind.
+const POLL_LOOKBACK_MILLIS: i64 = 10_000;
+const POLL_PAGE_SIZE: usize = 100;
+
+impl FrontendCatalogManager {
+    /// Publishes the tables changed by a DDL, for the other frontends to drop the cached
+    /// metadata of them.
+    pub(crate) async fn publish_table_invalidation(&self, tables: &[&TableName]) -> Result<()> {
+        let key = TableInvalidationKey {
+            timestamp_millis: current_time_millis(),
+            id: uuid::Uuid::new_v4().to_string(),
+        };
+        let value = TableInvalidationValue {
+            tables: tables
+                .iter()
+                .map(|table| InvalidatedTable {
+                    catalog_name: table.catalog_name.clone(),
+                    schema_name: table.schema_name.clone(),
+                    table_name: table.table_name.clone(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        self.backend
+            .set(key.to_string().as_bytes(), &value.as_bytes()?)
+            .await
+    }
+}
+
+/// Polls the invalidations published by the frontends in background, until the catalog
+/// manager is dropped.
+pub(crate) fn start_invalidation_listener(catalog_manager: &Arc<FrontendCatalogManager>) {
+    let catalog_manager = Arc::downgrade(catalog_manager);
+    common_runtime::spawn_bg(async move {
+        let mut listener = InvalidationListener::new(current_time_millis());
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let Some(catalog_manager) = Weak::upgrade(&catalog_manager) else {
+                return;
+            };
+            if let Err(e) = listener.poll(&catalog_manager).await {
+                warn!("Failed to poll the invalidations of tables, error: {e}");
+            }
+        }
+    });
+}
+
+/// Invalidates the tables of the invalidations published after the listener starts, each
+/// once.
+struct InvalidationListener {
+    /// Time of the last poll.
+    polled_at_millis: i64,
+    /// Keys of the invalidations seen within the lookback of the last poll.
+    seen: BTreeSet<String>,
+}
+
+impl InvalidationListener {
+    fn new(now_millis: i64) -> Self {
+        Self {
+            polled_at_millis: now_millis,
+            seen: BTreeSet::new(),
+        }
+    }
+
+    /// Invalidates the tables of the invalidations not seen yet, returns the number of them.
+    async fn poll(&mut self, catalog_manager: &FrontendCatalogManager) -> Result<usize> {
+        let now = current_time_millis();
+        let start = build_table_invalidation_start(self.polled_at_millis - POLL_LOOKBACK_MILLIS);
+        // Forgets the invalidations no more polled.
+        self.seen = self.seen.split_off(&start);
+
+        let prefix = build_table_invalidation_prefix();
+        let mut start = start.into_bytes();
+        let mut invalidations = 0;
+        loop {
+            let (kvs, more) = catalog_manager
+                .backend
+                .range_page(prefix.as_bytes(), &start, POLL_PAGE_SIZE)
+                .await?;
+            for kv in &kvs {
+                let key = String::from_utf8_lossy(kv.0.as_slice()).to_string();
+                if !self.seen.insert(key) {
+                    continue;
+                }
+                let value = TableInvalidationValue::from_bytes(&kv.1)?;
+                for table in value.tables {
+                    let table_name =
+                        TableName::new(table.catalog_name, table.schema_name, table.table_name);
+                    catalog_manager.invalidate_table(&table_name).await;
+                }
+                // The keys changed through the store of metasrv.
+                for key in value.keys {
+                    match TableGlobalKey::parse(&key) {
+                        Ok(key) => {
+                            let table_name =
+                                TableName::new(key.catalog_name, key.schema_name, key.table_name);
+                            catalog_manager.invalidate_table(&table_name).await;
+                        }
+                        Err(_) => {
+                            catalog_manager
+                                .backend_cache_invalidator
+                                .invalidate_key(key.as_bytes())
+                                .await
+                        }
+                    }
+                }
+                // The datanodes removed from the cluster, whose clients are no more used.
+                for datanode in value.removed_datanodes {
+                    let peer = Peer::new(datanode.id, datanode.addr);
+                    catalog_manager
+                        .datanode_clients()
+                        .remove_datanode(&peer)
+                        .await;
+                }
+                invalidations += 1;
+            }
+            match kvs.last() {
+                Some(kv) if more => {
+                    start = kv.0.clone();
+                    start.push(0);
+                }
+                _ => break,
+            }
+        }
+        self.polled_at_millis = now;
+        Ok(invalidations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use catalog::helper::{RemovedDatanode, SchemaKey};
+    use catalog::remote::CachedMetaKvBackend;
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use meta_client::client::MetaClientBuilder;
+    use meta_srv::mocks::MockInfo;
+    use partition::manager::PartitionRuleManager;
+    use partition::route::TableRoutes;
+
+    use super::*;
+    use crate::datanode::DatanodeClients;
+
+    async fn new_catalog_manager(mock_info: &MockInfo) -> FrontendCatalogManager {
+        let mut meta_client = MetaClientBuilder::new(1000, 0)
+            .enable_router()
+            .enable_store()
+            .channel_manager(mock_info.channel_manager.clone())
+            .build();
+        meta_client.start(&[&mock_info.server_addr]).await.unwrap();
+        let meta_client = Arc::new(meta_client);
+
+        let backend = Arc::new(CachedMetaKvBackend::new(meta_client.clone()));
+        let table_routes = Arc::new(TableRoutes::new(meta_client));
+        FrontendCatalogManager::new(
+            backend.clone(),
+            backend,
+            Arc::new(PartitionRuleManager::new(table_routes)),
+            Arc::new(DatanodeClients::default()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_poll_invalidations() {
+        let mock_info = meta_srv::mocks::mock_with_memstore().await;
+        let publisher = new_catalog_manager(&mock_info).await;
+        let subscriber = new_catalog_manager(&mock_info).await;
+        let mut listener = InvalidationListener::new(current_time_millis());
+        assert_eq!(0, listener.poll(&subscriber).await.unwrap());
+
+        let table_name = TableName::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "t");
+        publisher
+            .publish_table_invalidation(&[&table_name])
+            .await
+            .unwrap();
+        assert_eq!(1, listener.poll(&subscriber).await.unwrap());
+        // Each invalidation is seen once.
+        assert_eq!(0, listener.poll(&subscriber).await.unwrap());
+
+        // The keys published by the metasrv.
+        let key = TableInvalidationKey {
+            timestamp_millis: current_time_millis(),
+            id: "metasrv".to_string(),
+        };
+        let value = TableInvalidationValue {
+            keys: vec![
+                TableGlobalKey {
+                    catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                    schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                    table_name: "t".to_string(),
+                }
+                .to_string(),
+                SchemaKey {
+                    catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                    schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                }
+                .to_string(),
+            ],
+            ..Default::default()
+        };
+        publisher
+            .backend
+            .set(key.to_string().as_bytes(), &value.as_bytes().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(1, listener.poll(&subscriber).await.unwrap());
+
+        // The clients of the datanodes removed are dropped.
+        let peer = Peer::new(1, "127.0.0.1:3001");
+        let _ = subscriber.datanode_clients().get_client(&peer).await;
+        assert!(subscriber.datanode_clients().contains_client(&peer));
+        let key = TableInvalidationKey {
+            timestamp_millis: current_time_millis(),
+            id: "metasrv-datanodes".to_string(),
+        };
+        let value = TableInvalidationValue {
+            removed_datanodes: vec![RemovedDatanode {
+                id: peer.id,
+                addr: peer.addr.clone(),
+            }],
+            ..Default::default()
+        };
+        publisher
+            .backend
+            .set(key.to_string().as_bytes(), &value.as_bytes().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(1, listener.poll(&subscriber).await.unwrap());
+        assert!(!subscriber.datanode_clients().contains_client(&peer));
+
+        // The invalidations published before the listener starts are skipped.
+        let mut listener =
+            InvalidationListener::new(current_time_millis() + POLL_LOOKBACK_MILLIS + 1);
+        assert_eq!(0, listener.poll(&subscriber).await.unwrap());
+    }
+}
@@ -0,0 +1,543 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Warms up the metadata caches of a frontend at startup, so the first queries of each table
+//! don't have to fetch its metadata from metasrv one request at a time.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use catalog::helper::{
+    build_catalog_prefix, build_schema_prefix, build_table_global_prefix, CatalogKey, SchemaKey,
+    TableGlobalKey,
+};
+use catalog::remote::{CachedMetaKvBackend, Kv};
+use common_telemetry::{info, warn};
+use meta_client::rpc::{KeyValue, RouteRequest, TableName};
+use partition::route::TableRoutes;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::error::{self, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetadataWarmUpOptions {
+    pub enable: bool,
+    /// Max time the warm up delays serving, the rest of the metadata is fetched on demand.
+    pub timeout_millis: u64,
+    /// Max number of keys fetched from metasrv per request.
+    pub page_size: usize,
+}
+
+impl Default for MetadataWarmUpOptions {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            timeout_millis: 10_000,
+            page_size: 128,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct WarmUpStats {
+    pub(crate) catalogs: usize,
+    pub(crate) schemas: usize,
+    pub(crate) tables: usize,
+    pub(crate) routes: usize,
+}
+
+/// Warms up the caches of a frontend by [warm_up] before its servers start.
+pub(crate) struct MetadataWarmUp {
+    opts: MetadataWarmUpOptions,
+    backend: Arc<CachedMetaKvBackend>,
+    table_routes: Arc<TableRoutes>,
+}
+
+pub(crate) type MetadataWarmUpRef = Arc<MetadataWarmUp>;
+
+impl MetadataWarmUp {
+    pub(crate) fn new(
+        opts: MetadataWarmUpOptions,
+        backend: Arc<CachedMetaKvBackend>,
+        table_routes: Arc<TableRoutes>,
+    ) -> Self {
+        Self {
+            opts,
+            backend,
+            table_routes,
+        }
+    }
+
+    /// Warms up the caches for at most the timeout of the options, the metadata not warmed up
+    /// by then is fetched on demand.
+    pub(crate) async fn run(&self) -> WarmUpStats {
+        warm_up(&self.opts, &self.backend, &self.table_routes).await
+    }
+}
+
+/// Fetches the catalogs, schemas, tables and table routes from metasrv in bulk, and puts them
+/// into the caches of the catalog `backend` and the `table_routes`.
+///
+/// Failures are tolerated: a page failed to fetch is logged and skipped, and its metadata is
+/// fetched on demand like without warming up. The values of the keys invalidated meanwhile are
+/// not cached, see [CachedMetaKvBackend::insert_cache].
+pub(crate) async fn warm_up(
+    opts: &MetadataWarmUpOptions,
+    backend: &CachedMetaKvBackend,
+    table_routes: &TableRoutes,
+) -> WarmUpStats {
+    let mut stats = WarmUpStats::default();
+    if !opts.enable {
+        return stats;
+    }
+
+    let start = Instant::now();
+    let timeout = Duration::from_millis(opts.timeout_millis);
+    let warmer = Warmer {
+        page_size: opts.page_size,
+        backend,
+        table_routes,
+    };
+    match tokio::time::timeout(timeout, warmer.warm_up(&mut stats)).await {
+        Ok(()) => info!(
+            "Warmed up metadata caches in {:?}, {:?}",
+            start.elapsed(),
+            stats
+        ),
+        Err(_) => warn!(
+            "Metadata caches are not fully warmed up in {:?}, {:?}",
+            timeout, stats
+        ),
+    }
+    stats
+}
+
+struct Warmer<'a> {
+    page_size: usize,
+    backend: &'a CachedMetaKvBackend,
+    table_routes: &'a TableRoutes,
+}
+
+impl Warmer<'_> {
+    async fn warm_up(&self, stats: &mut WarmUpStats) {
+        let invalidations = self.backend.invalidations();
+        let catalogs = match self.fetch_all(build_catalog_prefix()).await {
+            Ok(kvs) => kvs,
+            Err(e) => {
+                warn!("Failed to warm up catalogs, error: {e}");
+                return;
+            }
+        };
+        let catalog_names = parse_keys(&catalogs, |key| {
+            CatalogKey::parse(key).map(|key| key.catalog_name)
+        });
+        stats.catalogs += catalogs.len();
+        self.backend.insert_cache(catalogs, invalidations).await;
+
+        for catalog_name in catalog_names {
+            let invalidations = self.backend.invalidations();
+            let schemas = match self.fetch_all(build_schema_prefix(&catalog_name)).await {
+                Ok(kvs) => kvs,
+                Err(e) => {
+                    warn!("Failed to warm up schemas of catalog {catalog_name}, error: {e}");
+                    continue;
+                }
+            };
+            let schema_names = parse_keys(&schemas, |key| {
+                SchemaKey::parse(key).map(|key| key.schema_name)
+            });
+            stats.schemas += schemas.len();
+            self.backend.insert_cache(schemas, invalidations).await;
+
+            for schema_name in schema_names {
+                self.warm_up_tables(&catalog_name, &schema_name, stats)
+                    .await;
+            }
+        }
+    }
+
+    async fn warm_up_tables(&self, catalog_name: &str, schema_name: &str, stats: &mut WarmUpStats) {
+        let mut pager = self.backend.client().range_pages(
+            build_table_global_prefix(catalog_name, schema_name),
+            self.page_size,
+        );
+        loop {
+            let invalidations = self.backend.invalidations();
+            let page = match pager.next_page().await {
+                Ok(Some(page)) => page,
+                Ok(None) => return,
+                Err(e) => {
+                    warn!("Failed to warm up tables of {catalog_name}.{schema_name}, error: {e}");
+                    return;
+                }
+            };
+            let tables = page.into_iter().map(to_kv).collect::<Vec<_>>();
+            let table_names = parse_keys(&tables, |key| {
+                TableGlobalKey::parse(key)
+                    .map(|key| TableName::new(key.catalog_name, key.schema_name, key.table_name))
+            });
+            stats.tables += tables.len();
+            self.backend.insert_cache(tables, invalidations).await;
+
+            // Routes the tables of the page in one request.
+            match self.fetch_routes(table_names).await {
+                Ok(routes) => stats.routes += routes,
+                Err(e) => {
+                    warn!("Failed to warm up routes of {catalog_name}.{schema_name}, error: {e}")
+                }
+            }
+        }
+    }
+
+    async fn fetch_routes(&self, table_names: Vec<TableName>) -> Result<usize> {
+        let resp = self
+            .backend
+            .client()
+            .route(RouteRequest { table_names })
+            .await
+            .context(error::RequestMetaSnafu)?;
+        let routes = resp.table_routes.len();
+        for route in resp.table_routes {
+            self.table_routes
+                .insert_table_route(route.table.table_name.clone(), Arc::new(route))
+                .await;
+        }
+        Ok(routes)
+    }
+
+    async fn fetch_all(&self, prefix: String) -> Result<Vec<Kv>> {
+        let mut pager = self.backend.client().range_pages(prefix, self.page_size);
+        let mut kvs = vec![];
+        while let Some(page) = pager.next_page().await.context(error::RequestMetaSnafu)? {
+            kvs.extend(page.into_iter().map(to_kv));
+        }
+        Ok(kvs)
+    }
+}
+
+fn to_kv(mut kv: KeyValue) -> Kv {
+    Kv(kv.take_key(), kv.take_value())
+}
+
+fn parse_keys<T, E>(kvs: &[Kv], parse: impl Fn(&str) -> std::result::Result<T, E>) -> Vec<T> {
+    kvs.iter()
+        .filter_map(|kv| {
+            let key = String::from_utf8_lossy(&kv.0);
+            let parsed = parse(&key).ok();
+            if parsed.is_none() {
+                warn!("Invalid catalog key: {key}");
+            }
+            parsed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use api::v1::meta::{
+        BatchDeleteRequest, BatchDeleteResponse, BatchGetRequest, BatchGetResponse,
+        BatchPutRequest, BatchPutResponse, CompareAndPutRequest, CompareAndPutResponse,
+        DeleteRangeRequest, DeleteRangeResponse, MoveValueRequest, MoveValueResponse, Peer,
+        PutRequest, PutResponse, RangeRequest, RangeResponse, Region, RegionRoute, Table,
+        TableName as PbTableName, TableRoute, TableRouteValue,
+    };
+    use catalog::helper::TableGlobalValue;
+    use catalog::CatalogManager;
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use meta_client::client::MetaClientBuilder;
+    use meta_srv::keys::TableRouteKey;
+    use meta_srv::metasrv::MetaSrvOptions;
+    use meta_srv::mocks::MockInfo;
+    use meta_srv::service::store::kv::{KvStore, KvStoreRef};
+    use meta_srv::service::store::memory::MemStore;
+    use partition::manager::PartitionRuleManager;
+    use table::metadata::RawTableInfo;
+    use table::test_util::MemTable;
+
+    use super::*;
+    use crate::catalog::FrontendCatalogManager;
+    use crate::datanode::DatanodeClients;
+
+    const TABLES: usize = 1000;
+
+    /// Records the requests reading the catalog metadata.
+    #[derive(Default)]
+    struct CountingKvStore {
+        inner: MemStore,
+        /// The limit of each range request of table global keys.
+        table_range_limits: Mutex<Vec<i64>>,
+        metadata_reads: Mutex<usize>,
+    }
+
+    impl CountingKvStore {
+        fn record(&self, key: &[u8]) {
+            let key = String::from_utf8_lossy(key);
+            if ["__c-", "__s-", "__tg-"]
+                .iter()
+                .any(|prefix| key.starts_with(prefix))
+            {
+                *self.metadata_reads.lock().unwrap() += 1;
+            }
+        }
+
+        fn metadata_reads(&self) -> usize {
+            *self.metadata_reads.lock().unwrap()
+        }
+
+        fn reset(&self) {
+            *self.metadata_reads.lock().unwrap() = 0;
+            self.table_range_limits.lock().unwrap().clear();
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl KvStore for CountingKvStore {
+        async fn range(&self, req: RangeRequest) -> meta_srv::Result<RangeResponse> {
+            self.record(&req.key);
+            if req.key.starts_with(b"__tg-") {
+                self.table_range_limits.lock().unwrap().push(req.limit);
+            }
+            self.inner.range(req).await
+        }
+
+        async fn put(&self, req: PutRequest) -> meta_srv::Result<PutResponse> {
+            self.inner.put(req).await
+        }
+
+        async fn batch_get(&self, req: BatchGetRequest) -> meta_srv::Result<BatchGetResponse> {
+            if let Some(key) = req.keys.first() {
+                self.record(key);
+            }
+            self.inner.batch_get(req).await
+        }
+
+        async fn batch_put(&self, req: BatchPutRequest) -> meta_srv::Result<BatchPutResponse> {
+            self.inner.batch_put(req).await
+        }
+
+        async fn batch_delete(
+            &self,
+            req: BatchDeleteRequest,
+        ) -> meta_srv::Result<BatchDeleteResponse> {
+            self.inner.batch_delete(req).await
+        }
+
+        async fn compare_and_put(
+            &self,
+            req: CompareAndPutRequest,
+        ) -> meta_srv::Result<CompareAndPutResponse> {
+            self.inner.compare_and_put(req).await
+        }
+
+        async fn delete_range(
+            &self,
+            req: DeleteRangeRequest,
+        ) -> meta_srv::Result<DeleteRangeResponse> {
+            self.inner.delete_range(req).await
+        }
+
+        async fn move_value(&self, req: MoveValueRequest) -> meta_srv::Result<MoveValueResponse> {
+            self.inner.move_value(req).await
+        }
+    }
+
+    fn table_name(i: usize) -> String {
+        format!("table_{i}")
+    }
+
+    async fn put_table(kv_store: &KvStoreRef, i: usize) {
+        let table_id = 1024 + i as u32;
+        let mut table_info = (*MemTable::default_numbers_table().table_info()).clone();
+        table_info.ident.table_id = table_id;
+        table_info.name = table_name(i);
+
+        let key = TableGlobalKey {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: table_name(i),
+        };
+        let value = TableGlobalValue {
+            node_id: 1,
+            regions_id_map: HashMap::from([(1, vec![0])]),
+            table_info: RawTableInfo::from(table_info),
+        };
+        let route = TableRouteValue {
+            peers: vec![Peer {
+                id: 1,
+                addr: "127.0.0.1:3001".to_string(),
+            }],
+            table_route: Some(TableRoute {
+                table: Some(Table {
+                    id: table_id as u64,
+                    table_name: Some(PbTableName {
+                        catalog_name: key.catalog_name.clone(),
+                        schema_name: key.schema_name.clone(),
+                        table_name: key.table_name.clone(),
+                    }),
+                    ..Default::default()
+                }),
+                region_routes: vec![RegionRoute {
+                    region: Some(Region {
+                        id: 0,
+                        ..Default::default()
+                    }),
+                    leader_peer_index: 0,
+                    follower_peer_indexes: vec![],
+                }],
+            }),
+        };
+        let _ = kv_store
+            .batch_put(BatchPutRequest {
+                kvs: vec![
+                    api::v1::meta::KeyValue {
+                        key: key.to_string().into_bytes(),
+                        value: value.as_bytes().unwrap(),
+                    },
+                    api::v1::meta::KeyValue {
+                        key: TableRouteKey::with_table_global_key(table_id as u64, &key)
+                            .key()
+                            .into_bytes(),
+                        value: route.into(),
+                    },
+                ],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn new_backend(
+        kv_store: Arc<CountingKvStore>,
+    ) -> (Arc<CachedMetaKvBackend>, Arc<TableRoutes>) {
+        let MockInfo {
+            server_addr,
+            channel_manager,
+        } = meta_srv::mocks::mock(MetaSrvOptions::default(), kv_store, None).await;
+        let mut meta_client = MetaClientBuilder::new(1000, 0)
+            .enable_router()
+            .enable_store()
+            .channel_manager(channel_manager)
+            .build();
+        meta_client.start(&[&server_addr]).await.unwrap();
+        let meta_client = Arc::new(meta_client);
+
+        let backend = Arc::new(CachedMetaKvBackend::new(meta_client.clone()));
+        let table_routes = Arc::new(TableRoutes::new(meta_client));
+        (backend, table_routes)
+    }
+
+    #[tokio::test]
+    async fn test_warm_up() {
+        let kv_store = Arc::new(CountingKvStore::default());
+        let kv_store_ref: KvStoreRef = kv_store.clone();
+        for i in 0..TABLES {
+            put_table(&kv_store_ref, i).await;
+        }
+        let (backend, table_routes) = new_backend(kv_store.clone()).await;
+        kv_store.reset();
+
+        let opts = MetadataWarmUpOptions {
+            page_size: 100,
+            ..Default::default()
+        };
+        let stats = warm_up(&opts, &backend, &table_routes).await;
+        assert_eq!(
+            WarmUpStats {
+                catalogs: 1,
+                schemas: 1,
+                tables: TABLES,
+                routes: TABLES,
+            },
+            stats
+        );
+        // One request per page of tables.
+        let limits = kv_store.table_range_limits.lock().unwrap().clone();
+        assert_eq!(vec![100; TABLES / 100], limits);
+
+        // Looking up the tables and their routes doesn't request metasrv anymore.
+        kv_store.reset();
+        let catalog_manager = FrontendCatalogManager::new(
+            backend.clone(),
+            backend,
+            Arc::new(PartitionRuleManager::new(table_routes.clone())),
+            Arc::new(DatanodeClients::default()),
+        );
+        for i in [0, TABLES / 2, TABLES - 1] {
+            let table = catalog_manager
+                .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, &table_name(i))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(table_name(i), table.table_info().name);
+
+            let name = TableName::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, table_name(i));
+            let route = table_routes.get_route(&name).await.unwrap();
+            assert_eq!(1024 + i as u64, route.table.id);
+        }
+        assert_eq!(0, kv_store.metadata_reads());
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_tolerates_failures() {
+        let kv_store = Arc::new(CountingKvStore::default());
+        let kv_store_ref: KvStoreRef = kv_store.clone();
+        for i in 0..TABLES {
+            put_table(&kv_store_ref, i).await;
+        }
+        // The route of "table_0" is lost, so routing the first page of tables fails.
+        let _ = kv_store
+            .delete_range(DeleteRangeRequest {
+                key: TableRouteKey {
+                    table_id: 1024,
+                    catalog_name: DEFAULT_CATALOG_NAME,
+                    schema_name: DEFAULT_SCHEMA_NAME,
+                    table_name: &table_name(0),
+                }
+                .key()
+                .into_bytes(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let (backend, table_routes) = new_backend(kv_store.clone()).await;
+
+        let opts = MetadataWarmUpOptions {
+            page_size: 100,
+            ..Default::default()
+        };
+        let stats = warm_up(&opts, &backend, &table_routes).await;
+        assert_eq!(TABLES, stats.tables);
+        assert_eq!(TABLES - 100, stats.routes);
+
+        let opts = MetadataWarmUpOptions {
+            enable: false,
+            ..Default::default()
+        };
+        let stats = warm_up(&opts, &backend, &table_routes).await;
+        assert_eq!(WarmUpStats::default(), stats);
+
+        // The warm up delaying the servers gives up at the timeout.
+        let opts = MetadataWarmUpOptions {
+            timeout_millis: 0,
+            ..Default::default()
+        };
+        let stats = MetadataWarmUp::new(opts, backend, table_routes).run().await;
+        assert!(stats.tables < TABLES);
+    }
+}
@@ -48,6 +48,18 @@ impl DatanodeClients {
             .await
     }
 
+    /// Drops the client of the `datanode` and closes its channel, once the datanode is
+    /// removed from the cluster.
+    pub async fn remove_datanode(&self, datanode: &Peer) {
+        self.clients.invalidate(datanode).await;
+        let _ = self.channel_manager.close(&datanode.addr);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn contains_client(&self, datanode: &Peer) -> bool {
+        self.clients.contains_key(datanode)
+    }
+
     #[cfg(test)]
     pub(crate) async fn insert_client(&self, datanode: Peer, client: Client) {
         self.clients.insert(datanode, client).await
@@ -18,7 +18,8 @@ use common_error::prelude::*;
 use datafusion::parquet;
 use datatypes::value::Value;
 use snafu::Location;
-use store_api::storage::RegionId;
+use store_api::storage::{RegionId, RegionNumber};
+use table::metadata::TableId;
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub))]
@@ -106,6 +107,13 @@ pub enum Error {
     #[snafu(display("Invalid SQL, error: {}", err_msg))]
     InvalidSql { err_msg: String, location: Location },
 
+    #[snafu(display(
+        "Invalid float precision {}, must be between 1 and {}",
+        digits,
+        datatypes::float_format::MAX_SIGNIFICANT_DIGITS
+    ))]
+    InvalidFloatPrecision { digits: u8, location: Location },
+
     #[snafu(display("Illegal Frontend state: {}", err_msg))]
     IllegalFrontendState { err_msg: String, location: Location },
 
@@ -121,12 +129,44 @@ pub enum Error {
     #[snafu(display("Invalid InsertRequest, reason: {}", reason))]
     InvalidInsertRequest { reason: String, location: Location },
 
+    #[snafu(display(
+        "Region {} not found in table {}, valid regions: {:?}",
+        region_number,
+        table_name,
+        regions
+    ))]
+    InvalidRegionNumber {
+        table_name: String,
+        region_number: RegionNumber,
+        regions: Vec<RegionNumber>,
+        location: Location,
+    },
+
     #[snafu(display("Table not found: {}", table_name))]
     TableNotFound {
         table_name: String,
         location: Location,
     },
 
+    #[snafu(display("Table {} not found in the recycle bin", table_name))]
+    RecycledTableNotFound {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Table {} has id {} instead of the expected id {}",
+        table_name,
+        actual,
+        expected
+    ))]
+    TableIdMismatch {
+        table_name: String,
+        expected: TableId,
+        actual: TableId,
+        location: Location,
+    },
+
     #[snafu(display("Column {} not found in table {}", column_name, table_name))]
     ColumnNotFound {
         column_name: String,
@@ -203,12 +243,31 @@ pub enum Error {
         source: partition::error::Error,
     },
 
+    #[snafu(display(
+        "Failed to rename table route of table {} to {}, source: {}",
+        table_name,
+        new_table_name,
+        source
+    ))]
+    RenameTableRoute {
+        table_name: String,
+        new_table_name: String,
+        #[snafu(backtrace)]
+        source: partition::error::Error,
+    },
+
     #[snafu(display("Failed to create table info, source: {}", source))]
     CreateTableInfo {
         #[snafu(backtrace)]
         source: datatypes::error::Error,
     },
 
+    #[snafu(display("{}", source))]
+    InvalidTableName {
+        source: common_catalog::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Failed to build CreateExpr on insertion: {}", source))]
     BuildCreateExprOnInsertion {
         #[snafu(backtrace)]
@@ -248,6 +307,17 @@ pub enum Error {
     #[snafu(display("Schema {} already exists", name))]
     SchemaExists { name: String, location: Location },
 
+    #[snafu(display(
+        "Session is pinned to schema {}, can't switch to schema {}",
+        current,
+        schema
+    ))]
+    SchemaPinned {
+        current: String,
+        schema: String,
+        location: Location,
+    },
+
     #[snafu(display("Table occurs error, source: {}", source))]
     Table {
         #[snafu(backtrace)]
@@ -337,12 +407,38 @@ pub enum Error {
     #[snafu(display("Not supported: {}", feat))]
     NotSupported { feat: String },
 
+    #[snafu(display("Table {} is being repartitioned", table_name))]
+    RepartitionInProgress {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to repartition table {}, reason: {}", table_name, reason))]
+    RepartitionTable {
+        table_name: String,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to check column limits: {}", source))]
+    CheckColumnLimits {
+        #[snafu(backtrace)]
+        source: common_grpc_expr::error::Error,
+    },
+
     #[snafu(display("Failed to find new columns on insertion: {}", source))]
     FindNewColumnsOnInsertion {
         #[snafu(backtrace)]
         source: common_grpc_expr::error::Error,
     },
 
+    #[snafu(display("Failed to dry run insertion into table {}: {}", table_name, source))]
+    DryRunInsert {
+        table_name: String,
+        #[snafu(backtrace)]
+        source: common_grpc_expr::error::Error,
+    },
+
     #[snafu(display("Failed to convert into vectors, source: {}", source))]
     IntoVectors {
         #[snafu(backtrace)]
@@ -361,6 +457,12 @@ pub enum Error {
         source: BoxedError,
     },
 
+    #[snafu(display("Failed to authorize statement, source: {}", source))]
+    AuthorizeStatement {
+        #[snafu(backtrace)]
+        source: servers::auth::Error,
+    },
+
     #[snafu(display(
         "Failed to deserialize partition in meta to partition def, source: {}",
         source
@@ -429,6 +531,26 @@ pub enum Error {
         source: common_query::error::Error,
     },
 
+    #[snafu(display(
+        "Datanode returned incompatible schema for table {}, reason: {}",
+        table_name,
+        reason
+    ))]
+    IncompatibleDatanodeSchema {
+        table_name: String,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Failed to align record batches to the plan schema, source: {}",
+        source
+    ))]
+    AlignRecordBatches {
+        #[snafu(backtrace)]
+        source: common_recordbatch::error::Error,
+    },
+
     #[snafu(display("Failed to parse data source url, source: {}", source))]
     ParseUrl {
         #[snafu(backtrace)]
@@ -447,6 +569,18 @@ pub enum Error {
         source: common_datasource::error::Error,
     },
 
+    #[snafu(display("Failed to parse file format, source: {}", source))]
+    ParseFileFormat {
+        #[snafu(backtrace)]
+        source: common_datasource::error::Error,
+    },
+
+    #[snafu(display("Failed to create record batches, source: {}", source))]
+    CreateRecordBatches {
+        #[snafu(backtrace)]
+        source: common_recordbatch::error::Error,
+    },
+
     #[snafu(display("Failed to read object in path: {}, source: {}", path, source))]
     ReadObject {
         path: String,
@@ -460,6 +594,18 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to read orc file, source: {}", source))]
+    ReadOrc {
+        #[snafu(backtrace)]
+        source: common_datasource::error::Error,
+    },
+
+    #[snafu(display("Failed to read record batch from orc file, source: {}", source))]
+    ReadOrcRecordBatch {
+        source: datatypes::arrow::error::ArrowError,
+        location: Location,
+    },
+
     #[snafu(display("Failed to build parquet record batch stream, source: {}", source))]
     BuildParquetRecordBatchStream {
         location: Location,
@@ -472,6 +618,19 @@ pub enum Error {
         source: storage::error::Error,
     },
 
+    #[snafu(display("Failed to encode record batch, source: {}", source))]
+    EncodeRecordBatch {
+        #[snafu(backtrace)]
+        source: common_datasource::error::Error,
+    },
+
+    #[snafu(display("Failed to write object in path: {}, source: {}", path, source))]
+    WriteObject {
+        path: String,
+        location: Location,
+        source: object_store::Error,
+    },
+
     #[snafu(display(
         "Schema datatypes not match at index {}, expected table schema: {}, actual file schema: {}",
         index,
@@ -485,12 +644,35 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Failed to parse timestamp strings of column {}, source: {}",
+        column_name,
+        source
+    ))]
+    ParseTimestamp {
+        column_name: String,
+        #[snafu(backtrace)]
+        source: common_time::error::Error,
+    },
+
     #[snafu(display("Failed to encode object into json, source: {}", source))]
     EncodeJson {
         source: serde_json::error::Error,
         location: Location,
     },
 
+    #[snafu(display("Failed to decode object from json, source: {}", source))]
+    DecodeJson {
+        source: serde_json::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to collect record batches, source: {}", source))]
+    CollectRecordBatches {
+        #[snafu(backtrace)]
+        source: common_recordbatch::error::Error,
+    },
+
     #[snafu(display("Failed to prepare immutable table: {}", source))]
     PrepareImmutableTable {
         #[snafu(backtrace)]
@@ -504,8 +686,10 @@ impl ErrorExt for Error {
     fn status_code(&self) -> StatusCode {
         match self {
             Error::ParseAddr { .. }
+            | Error::InvalidFloatPrecision { .. }
             | Error::InvalidSql { .. }
             | Error::InvalidInsertRequest { .. }
+            | Error::InvalidRegionNumber { .. }
             | Error::ColumnValuesNumberMismatch { .. }
             | Error::IllegalPrimaryKeysDef { .. }
             | Error::CatalogNotFound { .. }
@@ -519,12 +703,20 @@ impl ErrorExt for Error {
             | Error::InvalidSchema { .. }
             | Error::PrepareImmutableTable { .. } => StatusCode::InvalidArguments,
 
+            Error::ParseTimestamp { source, .. } => source.status_code(),
+
             Error::NotSupported { .. } => StatusCode::Unsupported,
 
+            Error::RepartitionInProgress { .. } => StatusCode::InvalidArguments,
+            Error::RepartitionTable { .. } => StatusCode::Internal,
+
+            Error::SchemaPinned { .. } => StatusCode::AccessDenied,
+
             Error::RuntimeResource { source, .. } => source.status_code(),
             Error::ExecutePromql { source, .. } => source.status_code(),
 
             Error::SqlExecIntercepted { source, .. } => source.status_code(),
+            Error::AuthorizeStatement { source } => source.status_code(),
             Error::StartServer { source, .. } => source.status_code(),
             Error::ShutdownServer { source, .. } => source.status_code(),
 
@@ -555,14 +747,19 @@ impl ErrorExt for Error {
             Error::IllegalFrontendState { .. }
             | Error::IncompleteGrpcResult { .. }
             | Error::ContextValueNotFound { .. }
-            | Error::EncodeJson { .. } => StatusCode::Unexpected,
+            | Error::EncodeJson { .. }
+            | Error::DecodeJson { .. } => StatusCode::Unexpected,
 
-            Error::TableNotFound { .. } => StatusCode::TableNotFound,
+            Error::TableNotFound { .. }
+            | Error::RecycledTableNotFound { .. }
+            | Error::TableIdMismatch { .. } => StatusCode::TableNotFound,
             Error::ColumnNotFound { .. } => StatusCode::TableColumnNotFound,
 
             Error::JoinTask { .. } => StatusCode::Unexpected,
             Error::Catalog { source, .. } => source.status_code(),
-            Error::CatalogEntrySerde { source, .. } => source.status_code(),
+            Error::CatalogEntrySerde { source, .. } | Error::InvalidTableName { source, .. } => {
+                source.status_code()
+            }
 
             Error::StartMetaClient { source } | Error::RequestMeta { source } => {
                 source.status_code()
@@ -570,7 +767,9 @@ impl ErrorExt for Error {
             Error::BuildCreateExprOnInsertion { source }
             | Error::ToTableInsertRequest { source }
             | Error::ToTableDeleteRequest { source }
-            | Error::FindNewColumnsOnInsertion { source } => source.status_code(),
+            | Error::FindNewColumnsOnInsertion { source }
+            | Error::CheckColumnLimits { source }
+            | Error::DryRunInsert { source, .. } => source.status_code(),
 
             Error::ExecuteStatement { source, .. }
             | Error::PlanStatement { source }
@@ -587,20 +786,30 @@ impl ErrorExt for Error {
             Error::External { source } => source.status_code(),
             Error::DeserializePartition { source, .. }
             | Error::FindTablePartitionRule { source, .. }
-            | Error::FindTableRoute { source, .. } => source.status_code(),
+            | Error::FindTableRoute { source, .. }
+            | Error::RenameTableRoute { source, .. } => source.status_code(),
             Error::UnrecognizedTableOption { .. } => StatusCode::InvalidArguments,
 
             Error::StartScriptManager { source } => source.status_code(),
 
             Error::TableScanExec { source, .. } => source.status_code(),
+            Error::IncompatibleDatanodeSchema { .. } => StatusCode::Unexpected,
+            Error::AlignRecordBatches { source }
+            | Error::CreateRecordBatches { source }
+            | Error::CollectRecordBatches { source } => source.status_code(),
 
             Error::ReadObject { .. }
+            | Error::WriteObject { .. }
             | Error::ReadParquet { .. }
-            | Error::BuildParquetRecordBatchStream { .. } => StatusCode::StorageUnavailable,
+            | Error::BuildParquetRecordBatchStream { .. }
+            | Error::ReadOrcRecordBatch { .. } => StatusCode::StorageUnavailable,
 
             Error::ListObjects { source }
+            | Error::ParseFileFormat { source }
+            | Error::ReadOrc { source }
             | Error::ParseUrl { source }
-            | Error::BuildBackend { source } => source.status_code(),
+            | Error::BuildBackend { source }
+            | Error::EncodeRecordBatch { source } => source.status_code(),
 
             Error::WriteParquet { source, .. } => source.status_code(),
         }
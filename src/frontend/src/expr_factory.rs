@@ -16,19 +16,24 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::helper::ColumnDataTypeWrapper;
-use api::v1::{Column, ColumnDataType, CreateTableExpr};
+use api::v1::ddl_request::Expr as DdlExpr;
+use api::v1::{alter_expr, AlterExpr, Column, ColumnDataType, CreateTableExpr};
+use common_catalog::naming;
 use common_error::prelude::BoxedError;
 use datanode::instance::sql::table_idents_to_full_name;
-use datatypes::schema::ColumnSchema;
+use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema};
 use file_table_engine::table::immutable::ImmutableFileTableOptions;
 use query::sql::prepare_immutable_file_table_files_and_schema;
 use session::context::QueryContextRef;
 use snafu::{ensure, ResultExt};
 use sql::ast::{ColumnDef, ColumnOption, TableConstraint};
+use sql::statements::alter::AlterTableOperation;
 use sql::statements::column_def_to_schema;
 use sql::statements::create::{CreateExternalTable, CreateTable, TIME_INDEX};
+use sql::statements::statement::Statement;
 use sql::util::to_lowercase_options_map;
-use table::requests::{TableOptions, IMMUTABLE_TABLE_META_KEY};
+use table::column_limits::ColumnLimits;
+use table::requests::{column_storage_options, TableOptions, IMMUTABLE_TABLE_META_KEY};
 
 use crate::error::{
     self, BuildCreateExprOnInsertionSnafu, ColumnDataTypeSnafu,
@@ -40,6 +45,7 @@ pub type CreateExprFactoryRef = Arc<dyn CreateExprFactory + Send + Sync>;
 
 #[async_trait::async_trait]
 pub trait CreateExprFactory {
+    #[allow(clippy::too_many_arguments)]
     async fn create_expr_by_columns(
         &self,
         catalog_name: &str,
@@ -47,6 +53,8 @@ pub trait CreateExprFactory {
         table_name: &str,
         columns: &[Column],
         engine: &str,
+        limits: &ColumnLimits,
+        timestamp_default: Option<&ColumnDefaultConstraint>,
     ) -> crate::error::Result<CreateTableExpr>;
 }
 
@@ -62,7 +70,10 @@ impl CreateExprFactory for DefaultCreateExprFactory {
         table_name: &str,
         columns: &[Column],
         engine: &str,
+        limits: &ColumnLimits,
+        timestamp_default: Option<&ColumnDefaultConstraint>,
     ) -> Result<CreateTableExpr> {
+        validate_table_name(table_name)?;
         let table_id = None;
         let create_expr = common_grpc_expr::build_create_expr_from_insertion(
             catalog_name,
@@ -71,6 +82,8 @@ impl CreateExprFactory for DefaultCreateExprFactory {
             table_name,
             columns,
             engine,
+            limits,
+            timestamp_default,
         )
         .context(BuildCreateExprOnInsertionSnafu)?;
 
@@ -86,6 +99,7 @@ pub(crate) async fn create_external_expr(
         table_idents_to_full_name(&create.name, query_ctx)
             .map_err(BoxedError::new)
             .context(error::ExternalSnafu)?;
+    validate_table_name(&table_name)?;
 
     let mut options = create.options;
 
@@ -125,18 +139,22 @@ pub(crate) fn create_to_expr(
         table_idents_to_full_name(&create.name, query_ctx)
             .map_err(BoxedError::new)
             .context(error::ExternalSnafu)?;
+    validate_table_name(&table_name)?;
 
     let time_index = find_time_index(&create.constraints)?;
-    let table_options = HashMap::from(
+    let column_schemas = columns_to_column_schemas(&create.columns, &time_index)?;
+    let mut table_options = HashMap::from(
         &TableOptions::try_from(&to_lowercase_options_map(&create.options))
             .context(error::UnrecognizedTableOptionSnafu)?,
     );
+    // The gRPC column definitions don't carry the storage options of columns.
+    table_options.extend(column_storage_options(&column_schemas));
     let expr = CreateTableExpr {
         catalog_name,
         schema_name,
         table_name,
         desc: "".to_string(),
-        column_defs: columns_to_expr(&create.columns, &time_index)?,
+        column_defs: column_schemas_to_defs(column_schemas)?,
         time_index,
         primary_keys: find_primary_keys(&create.columns, &create.constraints)?,
         create_if_not_exists: create.if_not_exists,
@@ -148,6 +166,46 @@ pub(crate) fn create_to_expr(
     Ok(expr)
 }
 
+/// Validates the name of the table to create by the rules of [naming].
+fn validate_table_name(table_name: &str) -> Result<()> {
+    naming::validate_table_name(table_name).context(error::InvalidTableNameSnafu)
+}
+
+/// Validates the new name of the table created, renamed or restored by `stmt`, if it names
+/// one.
+pub(crate) fn check_new_table_name(stmt: &Statement, query_ctx: &QueryContextRef) -> Result<()> {
+    let name = match stmt {
+        Statement::CreateTable(create) => &create.name,
+        Statement::CreateExternalTable(create) => &create.name,
+        Statement::Alter(alter) => match alter.alter_operation() {
+            AlterTableOperation::RenameTable { new_table_name } => {
+                return validate_table_name(new_table_name)
+            }
+            _ => return Ok(()),
+        },
+        Statement::RestoreTable(restore) => {
+            return restore.new_table_name().map_or(Ok(()), validate_table_name)
+        }
+        _ => return Ok(()),
+    };
+    let (_, _, table_name) = table_idents_to_full_name(name, query_ctx.clone())
+        .map_err(BoxedError::new)
+        .context(error::ExternalSnafu)?;
+    validate_table_name(&table_name)
+}
+
+/// Validates the new name of the table created or renamed by the DDL request of gRPC.
+pub(crate) fn check_new_table_name_of_ddl(expr: &DdlExpr) -> Result<()> {
+    match expr {
+        DdlExpr::CreateTable(create) => validate_table_name(&create.table_name),
+        DdlExpr::Alter(AlterExpr {
+            kind: Some(alter_expr::Kind::RenameTable(rename)),
+            ..
+        }) => validate_table_name(&rename.new_table_name),
+        _ => Ok(()),
+    }
+}
+
 fn find_primary_keys(
     columns: &[ColumnDef],
     constraints: &[TableConstraint],
@@ -227,15 +285,14 @@ pub fn find_time_index(constraints: &[TableConstraint]) -> crate::error::Result<
     Ok(time_index.first().unwrap().to_string())
 }
 
-fn columns_to_expr(
+fn columns_to_column_schemas(
     column_defs: &[ColumnDef],
     time_index: &str,
-) -> crate::error::Result<Vec<api::v1::ColumnDef>> {
-    let column_schemas = column_defs
+) -> crate::error::Result<Vec<ColumnSchema>> {
+    column_defs
         .iter()
         .map(|c| column_def_to_schema(c, c.name.to_string() == time_index).context(ParseSqlSnafu))
-        .collect::<Result<Vec<ColumnSchema>>>()?;
-    column_schemas_to_defs(column_schemas)
+        .collect()
 }
 
 pub(crate) fn column_schemas_to_defs(
@@ -275,10 +332,10 @@ pub(crate) fn column_schemas_to_defs(
 
 #[cfg(test)]
 mod tests {
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MITO_ENGINE};
     use session::context::QueryContext;
     use sql::dialect::GenericDialect;
     use sql::parser::ParserContext;
-    use sql::statements::statement::Statement;
 
     use super::*;
 
@@ -298,4 +355,89 @@ mod tests {
             expr.table_options.get("write_buffer_size").unwrap()
         );
     }
+
+    #[tokio::test]
+    async fn test_invalid_table_name() {
+        let query_ctx = Arc::new(QueryContext::default());
+        for name in [
+            "\"cpu usage\"",
+            "greptime_metrics",
+            "\"a/b\"",
+            "__recycled_1024_1680000000000_cpu",
+        ] {
+            let sql = format!("CREATE TABLE {name} (ts TIMESTAMP TIME INDEX)");
+            let stmt = ParserContext::create_with_dialect(&sql, &GenericDialect {})
+                .unwrap()
+                .pop()
+                .unwrap();
+            let table_name = name.trim_matches('"');
+            let expected = naming::validate_table_name(table_name)
+                .unwrap_err()
+                .to_string();
+
+            let err = check_new_table_name(&stmt, &query_ctx).unwrap_err();
+            assert_eq!(expected, err.to_string());
+            let Statement::CreateTable(create_table) = stmt else { unreachable!() };
+            let err = create_to_expr(&create_table, query_ctx.clone()).unwrap_err();
+            assert_eq!(expected, err.to_string());
+
+            let err = DefaultCreateExprFactory
+                .create_expr_by_columns(
+                    DEFAULT_CATALOG_NAME,
+                    DEFAULT_SCHEMA_NAME,
+                    table_name,
+                    &[],
+                    MITO_ENGINE,
+                    &ColumnLimits::default(),
+                    None,
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(expected, err.to_string());
+
+            // Tables can't be renamed or restored to the invalid names either.
+            for sql in [
+                format!("ALTER TABLE cpu RENAME {name}"),
+                format!("RESTORE TABLE cpu AS {name}"),
+            ] {
+                let stmt = ParserContext::create_with_dialect(&sql, &GenericDialect {})
+                    .unwrap()
+                    .pop()
+                    .unwrap();
+                let err = check_new_table_name(&stmt, &query_ctx).unwrap_err();
+                assert_eq!(expected, err.to_string(), "{sql}");
+            }
+            let rename = DdlExpr::Alter(AlterExpr {
+                table_name: "cpu".to_string(),
+                kind: Some(alter_expr::Kind::RenameTable(api::v1::RenameTable {
+                    new_table_name: table_name.to_string(),
+                })),
+                ..Default::default()
+            });
+            let err = check_new_table_name_of_ddl(&rename).unwrap_err();
+            assert_eq!(expected, err.to_string());
+        }
+    }
+
+    #[test]
+    fn test_create_to_expr_with_storage_options() {
+        let sql = "CREATE TABLE monitor (host STRING ENCODING('dictionary'), cpu DOUBLE COMPRESSION('zstd'), ts TIMESTAMP TIME INDEX, PRIMARY KEY(host)) ENGINE=mito";
+        let stmt = ParserContext::create_with_dialect(sql, &GenericDialect {})
+            .unwrap()
+            .pop()
+            .unwrap();
+
+        let Statement::CreateTable(create_table) = stmt else { unreachable!() };
+        let expr = create_to_expr(&create_table, Arc::new(QueryContext::default())).unwrap();
+        assert_eq!(
+            "dictionary",
+            expr.table_options.get("column.host.encoding").unwrap()
+        );
+
+        let request = common_grpc_expr::create_expr_to_request(1024, expr, true).unwrap();
+        let column_schemas = &request.schema.column_schemas;
+        assert_eq!(Some("dictionary"), column_schemas[0].encoding());
+        assert_eq!(Some("zstd"), column_schemas[1].compression());
+        assert!(request.table_options.extra_options.is_empty());
+    }
 }
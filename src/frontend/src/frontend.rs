@@ -12,11 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_config::{validate_addr, FieldError, Validate};
+use datanode::datanode::RecycleBinConfig;
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::ColumnDefaultConstraint;
 use meta_client::MetaClientOptions;
+use query::parser::DEFAULT_MAX_POINTS;
 use serde::{Deserialize, Serialize};
+use servers::auth::authorizer::StatementAuthorizerOptions;
 use servers::http::HttpOptions;
 use servers::Mode;
+use table::column_limits::ColumnLimitsOptions;
 
+use crate::catalog::warm_up::MetadataWarmUpOptions;
 use crate::grpc::GrpcOptions;
 use crate::influxdb::InfluxdbOptions;
 use crate::mysql::MysqlOptions;
@@ -24,6 +32,7 @@ use crate::opentsdb::OpentsdbOptions;
 use crate::postgres::PostgresOptions;
 use crate::prom::PromOptions;
 use crate::prometheus::PrometheusOptions;
+use crate::script::ScriptOptions;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
@@ -38,6 +47,19 @@ pub struct FrontendOptions {
     pub prometheus_options: Option<PrometheusOptions>,
     pub prom_options: Option<PromOptions>,
     pub meta_client_options: Option<MetaClientOptions>,
+    pub metadata_warm_up: MetadataWarmUpOptions,
+    pub column_limits: ColumnLimitsOptions,
+    /// Function the time index of the tables created on insertion defaults to, like
+    /// `current_timestamp()`. The rows inserted must carry the time index if it's not set.
+    pub auto_create_ts_default: Option<String>,
+    pub script: ScriptOptions,
+    /// External authorizer asked whether the statements are allowed, none by default.
+    pub statement_authorizer: Option<StatementAuthorizerOptions>,
+    /// Recycle bin of the distributed tables, the standalone mode takes the one of the storage.
+    pub recycle_bin: RecycleBinConfig,
+    /// Rejects the queries of the time ranges before the retention boundaries of the tables,
+    /// instead of only warning about them.
+    pub reject_expired_ranges: bool,
 }
 
 impl Default for FrontendOptions {
@@ -53,10 +75,61 @@ impl Default for FrontendOptions {
             prometheus_options: Some(PrometheusOptions::default()),
             prom_options: Some(PromOptions::default()),
             meta_client_options: None,
+            metadata_warm_up: MetadataWarmUpOptions::default(),
+            column_limits: ColumnLimitsOptions::default(),
+            auto_create_ts_default: None,
+            script: ScriptOptions::default(),
+            statement_authorizer: None,
+            recycle_bin: RecycleBinConfig::default(),
+            reject_expired_ranges: false,
         }
     }
 }
 
+impl FrontendOptions {
+    /// Returns the max number of points per timeseries of the PromQL range queries, set by
+    /// the options of the Prometheus HTTP API.
+    pub fn promql_max_points(&self) -> usize {
+        self.prom_options
+            .as_ref()
+            .map(|o| o.max_points)
+            .unwrap_or(DEFAULT_MAX_POINTS)
+    }
+}
+
+impl Validate for FrontendOptions {
+    fn validate(&self) -> Result<(), FieldError> {
+        let addrs = [
+            ("http_options", self.http_options.as_ref().map(|o| &o.addr)),
+            ("grpc_options", self.grpc_options.as_ref().map(|o| &o.addr)),
+            (
+                "mysql_options",
+                self.mysql_options.as_ref().map(|o| &o.addr),
+            ),
+            (
+                "postgres_options",
+                self.postgres_options.as_ref().map(|o| &o.addr),
+            ),
+            (
+                "opentsdb_options",
+                self.opentsdb_options.as_ref().map(|o| &o.addr),
+            ),
+            ("prom_options", self.prom_options.as_ref().map(|o| &o.addr)),
+        ];
+        for (options, addr) in addrs {
+            if let Some(addr) = addr {
+                validate_addr("addr", addr).map_err(|e| e.nested(options))?;
+            }
+        }
+        if let Some(function) = &self.auto_create_ts_default {
+            ColumnDefaultConstraint::Function(function.clone())
+                .validate(&ConcreteDataType::timestamp_millisecond_datatype(), false)
+                .map_err(|e| FieldError::new("auto_create_ts_default", e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +140,24 @@ mod tests {
         let toml_string = toml::to_string(&opts).unwrap();
         let _parsed: FrontendOptions = toml::from_str(&toml_string).unwrap();
     }
+
+    #[test]
+    fn test_validate() {
+        let mut opts = FrontendOptions::default();
+        opts.validate().unwrap();
+
+        opts.mysql_options.as_mut().unwrap().addr = "127.0.0.1:65536".to_string();
+        let err = opts.validate().unwrap_err();
+        assert_eq!("mysql_options.addr", err.field);
+
+        let mut opts = FrontendOptions {
+            auto_create_ts_default: Some("current_timestamp()".to_string()),
+            ..Default::default()
+        };
+        opts.validate().unwrap();
+        opts.auto_create_ts_default = Some("bogus()".to_string());
+        let err = opts.validate().unwrap_err();
+        assert_eq!("auto_create_ts_default", err.field);
+        assert!(err.msg.contains("bogus()"), "{}", err.msg);
+    }
 }
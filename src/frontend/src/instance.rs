@@ -12,11 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod ddl_batch;
 pub(crate) mod distributed;
+mod dry_run;
 mod grpc;
 mod influxdb;
+mod jobs;
+mod json_ingest;
 mod opentsdb;
 mod prometheus;
+mod queries;
+mod readiness;
 mod script;
 mod standalone;
 
@@ -29,27 +35,29 @@ use api::v1::ddl_request::Expr as DdlExpr;
 use api::v1::greptime_request::Request;
 use api::v1::{AddColumns, AlterExpr, Column, DdlRequest, InsertRequest};
 use async_trait::async_trait;
-use catalog::remote::MetaKvBackend;
+use catalog::remote::CachedMetaKvBackend;
 use catalog::CatalogManagerRef;
 use common_base::Plugins;
 use common_catalog::consts::MITO_ENGINE;
 use common_error::ext::BoxedError;
 use common_grpc::channel_manager::{ChannelConfig, ChannelManager};
+use common_grpc_expr::{column_limits, rows_null_mask};
 use common_query::Output;
 use common_telemetry::logging::{debug, info};
 use common_telemetry::timer;
 use datafusion::sql::sqlparser::ast::ObjectName;
 use datanode::instance::sql::table_idents_to_full_name;
 use datanode::instance::InstanceRef as DnInstanceRef;
-use datatypes::schema::Schema;
+use datanode::recycle_bin::{RecycleBinReaper, RecycleBinReaperRef};
+use datatypes::schema::{ColumnDefaultConstraint, Schema};
 use distributed::DistInstance;
 use meta_client::client::{MetaClient, MetaClientBuilder};
-use meta_client::MetaClientOptions;
 use partition::manager::PartitionRuleManager;
 use partition::route::TableRoutes;
 use query::parser::{PromQuery, QueryLanguageParser, QueryStatement};
 use query::query_engine::options::{validate_catalog_and_schema, QueryOptions};
 use query::{QueryEngineFactory, QueryEngineRef};
+use servers::auth::authorizer::StatementAuthorizerRef;
 use servers::error as server_error;
 use servers::error::{ExecuteQuerySnafu, ParsePromQLSnafu};
 use servers::interceptor::{SqlQueryInterceptor, SqlQueryInterceptorRef};
@@ -57,26 +65,31 @@ use servers::prom::PromHandler;
 use servers::query_handler::grpc::{GrpcQueryHandler, GrpcQueryHandlerRef};
 use servers::query_handler::sql::SqlQueryHandler;
 use servers::query_handler::{
-    InfluxdbLineProtocolHandler, OpentsdbProtocolHandler, PrometheusProtocolHandler, ScriptHandler,
+    DdlBatchHandler, InfluxdbLineProtocolHandler, JobHandler, JsonIngestHandler,
+    OpentsdbProtocolHandler, PrometheusProtocolHandler, ScriptHandler,
 };
 use session::context::QueryContextRef;
 use snafu::prelude::*;
 use sql::dialect::GenericDialect;
 use sql::parser::ParserContext;
+use sql::statements::alter::AlterTableOperation;
 use sql::statements::copy::CopyTable;
 use sql::statements::statement::Statement;
 
+use crate::catalog::invalidation::start_invalidation_listener;
+use crate::catalog::warm_up::{MetadataWarmUp, MetadataWarmUpRef};
 use crate::catalog::FrontendCatalogManager;
 use crate::datanode::DatanodeClients;
 use crate::error::{
     self, Error, ExecutePromqlSnafu, ExternalSnafu, InvalidInsertRequestSnafu,
-    MissingMetasrvOptsSnafu, ParseSqlSnafu, PlanStatementSnafu, Result, SqlExecInterceptedSnafu,
+    MissingMetasrvOptsSnafu, ParseSqlSnafu, Result, SqlExecInterceptedSnafu,
 };
-use crate::expr_factory::{CreateExprFactoryRef, DefaultCreateExprFactory};
+use crate::expr_factory::{check_new_table_name, CreateExprFactoryRef, DefaultCreateExprFactory};
 use crate::frontend::FrontendOptions;
 use crate::instance::standalone::StandaloneGrpcQueryHandler;
 use crate::metrics;
-use crate::script::ScriptExecutor;
+use crate::progress::ProgressSnapshot;
+use crate::script::{ScriptExecutor, ScriptOptions};
 use crate::server::{start_server, ServerHandlers, Services};
 use crate::statement::StatementExecutor;
 
@@ -86,6 +99,9 @@ pub trait FrontendInstance:
     + SqlQueryHandler<Error = Error>
     + OpentsdbProtocolHandler
     + InfluxdbLineProtocolHandler
+    + JsonIngestHandler
+    + DdlBatchHandler
+    + JobHandler
     + PrometheusProtocolHandler
     + ScriptHandler
     + PromHandler
@@ -107,12 +123,29 @@ pub struct Instance {
     grpc_query_handler: GrpcQueryHandlerRef<Error>,
 
     create_expr_factory: CreateExprFactoryRef,
+    /// Default of the time index of the tables created on insertion.
+    auto_create_ts_default: Option<ColumnDefaultConstraint>,
 
     /// plugins: this map holds extensions to customize query or auth
     /// behaviours.
     plugins: Arc<Plugins>,
 
     servers: Arc<ServerHandlers>,
+
+    /// The instance handling the requests in distributed mode, `None` in standalone mode.
+    dist_instance: Option<Arc<DistInstance>>,
+
+    /// The datanode instance handling the requests in standalone mode, `None` in distributed
+    /// mode.
+    dn_instance: Option<DnInstanceRef>,
+
+    /// The reaper of the recycle bin of the distributed tables, `None` if the recycle bin is
+    /// disabled or in standalone mode, where the datanode instance reaps it.
+    recycle_bin_reaper: Option<RecycleBinReaperRef>,
+
+    /// The warm up of the metadata caches, run before the servers start, `None` if it's
+    /// disabled or in standalone mode.
+    metadata_warm_up: Option<MetadataWarmUpRef>,
 }
 
 impl Instance {
@@ -122,63 +155,95 @@ impl Instance {
     ) -> Result<Self> {
         let meta_client = Self::create_meta_client(opts).await?;
 
-        let meta_backend = Arc::new(MetaKvBackend {
-            client: meta_client.clone(),
-        });
+        let meta_backend = Arc::new(CachedMetaKvBackend::new(meta_client.clone()));
         let table_routes = Arc::new(TableRoutes::new(meta_client.clone()));
+        let metadata_warm_up = opts.metadata_warm_up.enable.then(|| {
+            Arc::new(MetadataWarmUp::new(
+                opts.metadata_warm_up.clone(),
+                meta_backend.clone(),
+                table_routes.clone(),
+            ))
+        });
         let partition_manager = Arc::new(PartitionRuleManager::new(table_routes));
         let datanode_clients = Arc::new(DatanodeClients::default());
 
-        let mut catalog_manager =
-            FrontendCatalogManager::new(meta_backend, partition_manager, datanode_clients.clone());
+        let mut catalog_manager = FrontendCatalogManager::new(
+            meta_backend.clone(),
+            meta_backend,
+            partition_manager,
+            datanode_clients.clone(),
+        )
+        .with_column_limits(opts.column_limits.clone());
 
         let dist_instance = DistInstance::new(
             meta_client,
             Arc::new(catalog_manager.clone()),
             datanode_clients,
-        );
+        )
+        .with_soft_drop(opts.recycle_bin.enable);
         let dist_instance = Arc::new(dist_instance);
 
         catalog_manager.set_dist_instance(dist_instance.clone());
         let catalog_manager = Arc::new(catalog_manager);
+        start_invalidation_listener(&catalog_manager);
+        let recycle_bin_reaper = opts.recycle_bin.enable.then(|| {
+            Arc::new(RecycleBinReaper::new(
+                catalog_manager.clone(),
+                dist_instance.clone(),
+                &opts.recycle_bin,
+            ))
+        });
 
         let query_engine =
             QueryEngineFactory::new_with_plugins(catalog_manager.clone(), plugins.clone())
                 .query_engine();
 
-        let script_executor =
-            Arc::new(ScriptExecutor::new(catalog_manager.clone(), query_engine.clone()).await?);
+        let script_executor = Arc::new(
+            ScriptExecutor::new(catalog_manager.clone(), query_engine.clone(), &opts.script)
+                .await?,
+        );
 
-        let statement_executor = Arc::new(StatementExecutor::new(
+        let mut statement_executor = StatementExecutor::new(
             catalog_manager.clone(),
             query_engine.clone(),
             dist_instance.clone(),
-        ));
+            dist_instance.ddl_locks().clone(),
+        );
+        statement_executor.set_authorizer(plugins.get::<StatementAuthorizerRef>());
+        statement_executor.set_promql_max_points(opts.promql_max_points());
+        let statement_executor = Arc::new(statement_executor);
 
         Ok(Instance {
             catalog_manager,
             script_executor,
             create_expr_factory: Arc::new(DefaultCreateExprFactory),
+            auto_create_ts_default: opts
+                .auto_create_ts_default
+                .clone()
+                .map(ColumnDefaultConstraint::Function),
             statement_executor,
             query_engine,
-            grpc_query_handler: dist_instance,
+            grpc_query_handler: dist_instance.clone(),
             plugins: plugins.clone(),
             servers: Arc::new(HashMap::new()),
+            dist_instance: Some(dist_instance),
+            dn_instance: None,
+            recycle_bin_reaper,
+            metadata_warm_up,
         })
     }
 
     async fn create_meta_client(opts: &FrontendOptions) -> Result<Arc<MetaClient>> {
-        let metasrv_addr = &opts
+        let meta_config = opts
             .meta_client_options
             .as_ref()
-            .context(MissingMetasrvOptsSnafu)?
-            .metasrv_addrs;
+            .context(MissingMetasrvOptsSnafu)?;
+        let metasrv_addr = &meta_config.metasrv_addrs;
         info!(
             "Creating Frontend instance in distributed mode with Meta server addr {:?}",
             metasrv_addr
         );
 
-        let meta_config = MetaClientOptions::default();
         let channel_config = ChannelConfig::new()
             .timeout(Duration::from_millis(meta_config.timeout_millis))
             .connect_timeout(Duration::from_millis(meta_config.connect_timeout_millis))
@@ -188,7 +253,9 @@ impl Instance {
         let mut meta_client = MetaClientBuilder::new(0, 0)
             .enable_router()
             .enable_store()
+            .enable_lock()
             .channel_manager(channel_manager)
+            .router_max_retries(meta_config.max_retries)
             .build();
         meta_client
             .start(metasrv_addr)
@@ -197,27 +264,37 @@ impl Instance {
         Ok(Arc::new(meta_client))
     }
 
-    pub async fn try_new_standalone(dn_instance: DnInstanceRef) -> Result<Self> {
+    pub async fn try_new_standalone(
+        dn_instance: DnInstanceRef,
+        script_opts: &ScriptOptions,
+    ) -> Result<Self> {
         let catalog_manager = dn_instance.catalog_manager();
         let query_engine = dn_instance.query_engine();
-        let script_executor =
-            Arc::new(ScriptExecutor::new(catalog_manager.clone(), query_engine.clone()).await?);
+        let script_executor = Arc::new(
+            ScriptExecutor::new(catalog_manager.clone(), query_engine.clone(), script_opts).await?,
+        );
 
         let statement_executor = Arc::new(StatementExecutor::new(
             catalog_manager.clone(),
             query_engine.clone(),
             dn_instance.clone(),
+            dn_instance.sql_handler().ddl_locks().clone(),
         ));
 
         Ok(Instance {
             catalog_manager: catalog_manager.clone(),
             script_executor,
             create_expr_factory: Arc::new(DefaultCreateExprFactory),
+            auto_create_ts_default: None,
             statement_executor,
             query_engine,
             grpc_query_handler: StandaloneGrpcQueryHandler::arc(dn_instance.clone()),
             plugins: Default::default(),
             servers: Arc::new(HashMap::new()),
+            dist_instance: None,
+            dn_instance: Some(dn_instance),
+            recycle_bin_reaper: None,
+            metadata_warm_up: None,
         })
     }
 
@@ -235,15 +312,20 @@ impl Instance {
     ) -> Self {
         let query_engine = QueryEngineFactory::new(catalog_manager.clone()).query_engine();
         let script_executor = Arc::new(
-            ScriptExecutor::new(catalog_manager.clone(), query_engine.clone())
-                .await
-                .unwrap(),
+            ScriptExecutor::new(
+                catalog_manager.clone(),
+                query_engine.clone(),
+                &ScriptOptions::default(),
+            )
+            .await
+            .unwrap(),
         );
 
         let statement_executor = Arc::new(StatementExecutor::new(
             catalog_manager.clone(),
             query_engine.clone(),
             dist_instance.clone(),
+            dist_instance.ddl_locks().clone(),
         ));
 
         Instance {
@@ -252,9 +334,14 @@ impl Instance {
             statement_executor,
             query_engine,
             create_expr_factory: Arc::new(DefaultCreateExprFactory),
-            grpc_query_handler: dist_instance,
+            auto_create_ts_default: None,
+            grpc_query_handler: dist_instance.clone(),
             plugins: Default::default(),
             servers: Arc::new(HashMap::new()),
+            dist_instance: Some(dist_instance),
+            dn_instance: None,
+            recycle_bin_reaper: None,
+            metadata_warm_up: None,
         }
     }
 
@@ -262,12 +349,20 @@ impl Instance {
         &self.catalog_manager
     }
 
-    /// Handle batch inserts
+    /// Handle batch inserts, which are dry runs reporting the [InsertDryRun]s as records if the
+    /// `ctx` is in dry run.
+    ///
+    /// [InsertDryRun]: common_grpc_expr::dry_run::InsertDryRun
     pub async fn handle_inserts(
         &self,
         requests: Vec<InsertRequest>,
         ctx: QueryContextRef,
     ) -> Result<Output> {
+        if ctx.is_dry_run() {
+            let dry_runs = self.dry_run_inserts(&requests, &ctx).await?;
+            return dry_run::dry_runs_to_output(&dry_runs);
+        }
+
         let mut success = 0;
         for request in requests {
             match self.handle_insert(request, ctx.clone()).await? {
@@ -322,8 +417,10 @@ impl Instance {
 
                 validate_insert_request(schema.as_ref(), request)?;
 
-                if let Some(add_columns) = common_grpc_expr::find_new_columns(&schema, columns)
-                    .context(error::FindNewColumnsOnInsertionSnafu)?
+                let limits = self.catalog_manager.column_limits().limits_of(schema_name);
+                if let Some(add_columns) =
+                    common_grpc_expr::find_new_columns(&schema, columns, &limits)
+                        .context(error::FindNewColumnsOnInsertionSnafu)?
                 {
                     info!(
                         "Find new columns {:?} on insertion, try to alter table: {}.{}.{}",
@@ -353,9 +450,18 @@ impl Instance {
         let schema_name = &ctx.current_schema();
 
         // Create table automatically, build schema from data.
+        let limits = self.catalog_manager.column_limits().limits_of(schema_name);
         let create_expr = self
             .create_expr_factory
-            .create_expr_by_columns(catalog_name, schema_name, table_name, columns, engine)
+            .create_expr_by_columns(
+                catalog_name,
+                schema_name,
+                table_name,
+                columns,
+                engine,
+                &limits,
+                self.auto_create_ts_default.as_ref(),
+            )
             .await?;
 
         info!(
@@ -401,6 +507,8 @@ impl Instance {
     }
 
     pub fn set_plugins(&mut self, map: Arc<Plugins>) {
+        Arc::make_mut(&mut self.statement_executor)
+            .set_authorizer(map.get::<StatementAuthorizerRef>());
         self.plugins = map;
     }
 
@@ -408,7 +516,81 @@ impl Instance {
         self.plugins.clone()
     }
 
+    /// Sets the function, like `current_timestamp()`, the time index of the tables created on
+    /// insertion defaults to, so the rows inserted later may omit it.
+    pub fn set_auto_create_ts_default(&mut self, function: Option<String>) {
+        self.auto_create_ts_default = function.map(ColumnDefaultConstraint::Function);
+    }
+
+    /// Sets the max number of points per timeseries of the PromQL range queries, from all
+    /// the protocols and TQL.
+    pub fn set_promql_max_points(&mut self, max_points: usize) {
+        Arc::make_mut(&mut self.statement_executor).set_promql_max_points(max_points);
+    }
+
+    /// Checks the table created, or the column added, by `stmt` is within the column limits of
+    /// its schema.
+    async fn check_column_limits(
+        &self,
+        stmt: &Statement,
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        match stmt {
+            Statement::CreateTable(create) => {
+                let (_, schema_name, _) =
+                    table_idents_to_full_name(&create.name, query_ctx.clone())
+                        .map_err(BoxedError::new)
+                        .context(ExternalSnafu)?;
+                let limits = self.catalog_manager.column_limits().limits_of(&schema_name);
+                column_limits::check_new_table(&limits, create.columns.len())
+                    .context(error::CheckColumnLimitsSnafu)
+            }
+            Statement::Alter(alter) => {
+                let AlterTableOperation::AddColumn { column_def } = alter.alter_operation() else {
+                    return Ok(());
+                };
+                let (catalog_name, schema_name, table_name) =
+                    table_idents_to_full_name(alter.table_name(), query_ctx.clone())
+                        .map_err(BoxedError::new)
+                        .context(ExternalSnafu)?;
+                let table = self
+                    .catalog_manager
+                    .table(&catalog_name, &schema_name, &table_name)
+                    .await
+                    .context(error::CatalogSnafu)?;
+                // The alteration fails later if the table doesn't exist.
+                let Some(table) = table else { return Ok(()) };
+                let limits = self.catalog_manager.column_limits().limits_of(&schema_name);
+                column_limits::check_new_columns(
+                    &limits,
+                    table.schema().num_columns(),
+                    &[&column_def.name.value],
+                )
+                .context(error::CheckColumnLimitsSnafu)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the progress of the running long-running statements, like `COPY`.
+    pub fn running_statements(&self) -> Vec<ProgressSnapshot> {
+        self.statement_executor.progress_registry().snapshots()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn ddl_locks(&self) -> &catalog::ddl_lock::DdlLocksRef {
+        self.statement_executor.ddl_locks()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn progress_registry(&self) -> &crate::progress::ProgressRegistryRef {
+        self.statement_executor.progress_registry()
+    }
+
     pub async fn shutdown(&self) -> Result<()> {
+        if let Some(reaper) = &self.recycle_bin_reaper {
+            reaper.stop();
+        }
         futures::future::try_join_all(self.servers.values().map(|server| server.0.shutdown()))
             .await
             .context(error::ShutdownServerSnafu)
@@ -425,6 +607,20 @@ impl Instance {
 impl FrontendInstance for Instance {
     async fn start(&mut self) -> Result<()> {
         // TODO(hl): Frontend init should move to here
+        // The catalog manager of the standalone mode is the datanode's, which has started.
+        if self.dist_instance.is_some() {
+            self.catalog_manager
+                .start()
+                .await
+                .context(error::CatalogSnafu)?;
+        }
+        if let Some(reaper) = &self.recycle_bin_reaper {
+            reaper.start();
+        }
+        // The invalidation listener has started, so the values changed meanwhile aren't cached.
+        if let Some(warm_up) = &self.metadata_warm_up {
+            let _ = warm_up.run().await;
+        }
 
         futures::future::try_join_all(self.servers.values().map(start_server))
             .await
@@ -439,7 +635,11 @@ fn parse_stmt(sql: &str) -> Result<Vec<Statement>> {
 
 impl Instance {
     async fn query_statement(&self, stmt: Statement, query_ctx: QueryContextRef) -> Result<Output> {
+        query_ctx.clear_warnings();
+        query_ctx.set_result_tables(Vec::new());
         check_permission(self.plugins.clone(), &stmt, &query_ctx)?;
+        check_new_table_name(&stmt, &query_ctx)?;
+        self.check_column_limits(&stmt, &query_ctx).await?;
 
         let stmt = QueryStatement::Sql(stmt);
         self.statement_executor.execute_stmt(stmt, query_ctx).await
@@ -451,7 +651,10 @@ impl SqlQueryHandler for Instance {
     type Error = Error;
 
     async fn do_query(&self, query: &str, query_ctx: QueryContextRef) -> Vec<Result<Output>> {
-        let _timer = timer!(metrics::METRIC_HANDLE_SQL_ELAPSED);
+        let _timer = timer!(
+            metrics::METRIC_HANDLE_SQL_ELAPSED,
+            &[(metrics::LABEL_ORIGIN, query_ctx.origin().label())]
+        );
 
         let query_interceptor = self.plugins.get::<SqlQueryInterceptorRef<Error>>();
         let query = match query_interceptor.pre_parsing(query, query_ctx.clone()) {
@@ -511,12 +714,11 @@ impl SqlQueryHandler for Instance {
         query_ctx: QueryContextRef,
     ) -> Result<Option<Schema>> {
         if let Statement::Query(_) = stmt {
+            let action = stmt.kind();
             let plan = self
-                .query_engine
-                .planner()
-                .plan(QueryStatement::Sql(stmt), query_ctx)
-                .await
-                .context(PlanStatementSnafu)?;
+                .statement_executor
+                .plan(QueryStatement::Sql(stmt), action, &query_ctx)
+                .await?;
             self.query_engine
                 .describe(plan)
                 .await
@@ -529,9 +731,8 @@ impl SqlQueryHandler for Instance {
 
     async fn is_valid_schema(&self, catalog: &str, schema: &str) -> Result<bool> {
         self.catalog_manager
-            .schema(catalog, schema)
+            .schema_exist(catalog, schema)
             .await
-            .map(|s| s.is_some())
             .context(error::CatalogSnafu)
     }
 }
@@ -543,9 +744,12 @@ impl PromHandler for Instance {
         query: &PromQuery,
         query_ctx: QueryContextRef,
     ) -> server_error::Result<Output> {
-        let stmt = QueryLanguageParser::parse_promql(query).with_context(|_| ParsePromQLSnafu {
-            query: query.clone(),
-        })?;
+        let stmt = self
+            .statement_executor
+            .parse_promql(query)
+            .with_context(|_| ParsePromQLSnafu {
+                query: query.clone(),
+            })?;
         self.statement_executor
             .execute_stmt(stmt, query_ctx)
             .await
@@ -571,10 +775,21 @@ pub fn check_permission(
     }
 
     match stmt {
+        // `analyze_table` is not executed by query engine.
+        Statement::Query(query) => {
+            if let Some(table_name) = query.analyze_table_name() {
+                validate_param(&table_name, query_ctx)?;
+            }
+        }
         // These are executed by query engine, and will be checked there.
-        Statement::Query(_) | Statement::Explain(_) | Statement::Tql(_) | Statement::Delete(_) => {}
+        Statement::Explain(_)
+        | Statement::Tql(_)
+        | Statement::Delete(_)
+        | Statement::CopyQueryTo(_) => {}
         // database ops won't be checked
         Statement::CreateDatabase(_) | Statement::ShowDatabases(_) | Statement::Use(_) => {}
+        // the processes of the instance are not bound to any schema
+        Statement::ShowProcesslist(_) => {}
         // show create table and alter are not supported yet
         Statement::ShowCreateTable(_) | Statement::CreateExternalTable(_) | Statement::Alter(_) => {
         }
@@ -588,6 +803,9 @@ pub fn check_permission(
         Statement::DropTable(drop_stmt) => {
             validate_param(drop_stmt.table_name(), query_ctx)?;
         }
+        Statement::RestoreTable(restore_stmt) => {
+            validate_param(restore_stmt.table_name(), query_ctx)?;
+        }
         Statement::ShowTables(stmt) => {
             if let Some(database) = &stmt.database {
                 validate_catalog_and_schema(&query_ctx.current_catalog(), database, query_ctx)
@@ -598,7 +816,7 @@ pub fn check_permission(
         Statement::DescribeTable(stmt) => {
             validate_param(stmt.name(), query_ctx)?;
         }
-        Statement::Copy(stmd) => match stmd {
+        Statement::Copy(stmd) | Statement::ExplainCopy(stmd) => match stmd {
             CopyTable::To(copy_table_to) => validate_param(&copy_table_to.table_name, query_ctx)?,
             CopyTable::From(copy_table_from) => {
                 validate_param(&copy_table_from.table_name, query_ctx)?
@@ -627,7 +845,10 @@ fn validate_insert_request(schema: &Schema, request: &InsertRequest) -> Result<(
             .columns
             .iter()
             .find(|x| x.column_name == column_schema.name)
-            .map(|column| column.null_mask.is_empty() || column.null_mask.iter().all(|x| *x == 0));
+            .map(|column| {
+                // Only the bits of the rows count, the padding bits may be set by clients.
+                rows_null_mask(&column.null_mask, request.row_count as usize).not_any()
+            });
         ensure!(
             not_null == Some(true),
             InvalidInsertRequestSnafu {
@@ -647,14 +868,18 @@ mod tests {
     use std::collections::HashMap;
     use std::sync::atomic::AtomicU32;
 
-    use api::v1::column::Values;
+    use api::v1::column::{SemanticType, Values};
+    use api::v1::ColumnDataType;
     use catalog::helper::{TableGlobalKey, TableGlobalValue};
+    use common_error::prelude::{ErrorExt, StatusCode};
     use common_recordbatch::RecordBatches;
     use datatypes::prelude::{ConcreteDataType, Value};
     use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema};
     use query::query_engine::options::QueryOptions;
+    use servers::auth::authorizer::StatementAuthorizer;
     use session::context::QueryContext;
     use strfmt::Format;
+    use table::column_limits::ColumnLimitsOptions;
 
     use super::*;
     use crate::table::DistTable;
@@ -724,6 +949,23 @@ mod tests {
         };
         // Neither of the above cases.
         assert!(validate_insert_request(&schema, &request).is_err());
+
+        let new_request = |null_mask| InsertRequest {
+            columns: vec![Column {
+                column_name: "a".to_string(),
+                values: Some(Values {
+                    i32_values: vec![1, 2, 3],
+                    ..Default::default()
+                }),
+                null_mask,
+                ..Default::default()
+            }],
+            row_count: 3,
+            ..Default::default()
+        };
+        // The padding bits after the rows are ignored.
+        assert!(validate_insert_request(&schema, &new_request(vec![0b1111_1000])).is_ok());
+        assert!(validate_insert_request(&schema, &new_request(vec![0b0000_0010])).is_err());
     }
 
     #[test]
@@ -732,6 +974,7 @@ mod tests {
         let mut plugins = Plugins::new();
         plugins.insert(QueryOptions {
             disallow_cross_schema_query: true,
+            ..Default::default()
         });
         let plugins = Arc::new(plugins);
 
@@ -837,7 +1080,8 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_standalone_exec_sql() {
-        let standalone = tests::create_standalone_instance("test_standalone_exec_sql").await;
+        let (standalone, _engine) =
+            tests::create_memory_standalone_instance("test_standalone_exec_sql").await;
         let instance = standalone.instance.as_ref();
 
         let sql = r#"
@@ -857,6 +1101,207 @@ mod tests {
         drop_table(instance).await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_column_limits() {
+        let (standalone, _engine) = tests::create_memory_standalone_instance_with_column_limits(
+            "test_column_limits",
+            ColumnLimitsOptions {
+                max_columns: 3,
+                max_added_columns: 2,
+                ..Default::default()
+            },
+        )
+        .await;
+        let instance = standalone.instance.as_ref();
+        let ctx = QueryContext::arc();
+
+        let sql =
+            "CREATE TABLE demo(host STRING, cpu DOUBLE, memory DOUBLE, ts TIMESTAMP TIME INDEX)";
+        let err = SqlQueryHandler::do_query(instance, sql, ctx.clone())
+            .await
+            .remove(0)
+            .unwrap_err();
+        assert!(matches!(err, Error::CheckColumnLimits { .. }), "{err}");
+
+        create_table(
+            instance,
+            "CREATE TABLE demo(host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX)",
+        )
+        .await;
+        let sql = "ALTER TABLE demo ADD COLUMN memory DOUBLE";
+        let err = SqlQueryHandler::do_query(instance, sql, ctx.clone())
+            .await
+            .remove(0)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Adding columns [memory] to a table of 3 columns"),
+            "{err}"
+        );
+
+        let column = |name: &str, semantic_type: SemanticType| Column {
+            column_name: name.to_string(),
+            semantic_type: semantic_type as i32,
+            values: Some(Values {
+                f64_values: vec![1.0],
+                ..Default::default()
+            }),
+            datatype: ColumnDataType::Float64 as i32,
+            ..Default::default()
+        };
+        let ts = Column {
+            column_name: "ts".to_string(),
+            semantic_type: SemanticType::Timestamp as i32,
+            values: Some(Values {
+                ts_millisecond_values: vec![1],
+                ..Default::default()
+            }),
+            datatype: ColumnDataType::TimestampMillisecond as i32,
+            ..Default::default()
+        };
+        let request = |table_name: &str, columns: Vec<Column>| InsertRequest {
+            table_name: table_name.to_string(),
+            columns,
+            row_count: 1,
+            ..Default::default()
+        };
+
+        // Creates a table of 4 columns on insertion.
+        let columns = vec![
+            column("a", SemanticType::Tag),
+            column("b", SemanticType::Field),
+            column("c", SemanticType::Field),
+            ts.clone(),
+        ];
+        let err = instance
+            .handle_inserts(vec![request("auto", columns)], ctx.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::BuildCreateExprOnInsertion { .. }),
+            "{err}"
+        );
+
+        // Adds columns to the table of 3 columns on insertion.
+        let columns = vec![
+            column("cpu", SemanticType::Field),
+            column("disk", SemanticType::Field),
+            ts.clone(),
+        ];
+        let err = instance
+            .handle_inserts(vec![request("demo", columns)], ctx.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Adding columns [disk] to a table of 3 columns"),
+            "{err}"
+        );
+
+        // The existing columns are still writable.
+        let columns = vec![column("cpu", SemanticType::Field), ts];
+        let output = instance
+            .handle_inserts(vec![request("demo", columns)], ctx)
+            .await
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(1)));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_auto_create_ts_default() {
+        let (mut standalone, _engine) =
+            tests::create_memory_standalone_instance("test_auto_create_ts_default").await;
+        Arc::get_mut(&mut standalone.instance)
+            .unwrap()
+            .set_auto_create_ts_default(Some("current_timestamp()".to_string()));
+        let instance = standalone.instance.as_ref();
+        let ctx = QueryContext::arc();
+
+        let request = InsertRequest {
+            table_name: "auto".to_string(),
+            columns: vec![
+                Column {
+                    column_name: "host".to_string(),
+                    semantic_type: SemanticType::Tag as i32,
+                    values: Some(Values {
+                        string_values: vec!["host1".to_string()],
+                        ..Default::default()
+                    }),
+                    datatype: ColumnDataType::String as i32,
+                    ..Default::default()
+                },
+                Column {
+                    column_name: "ts".to_string(),
+                    semantic_type: SemanticType::Timestamp as i32,
+                    values: Some(Values {
+                        ts_millisecond_values: vec![1],
+                        ..Default::default()
+                    }),
+                    datatype: ColumnDataType::TimestampMillisecond as i32,
+                    ..Default::default()
+                },
+            ],
+            row_count: 1,
+            ..Default::default()
+        };
+        let output = instance
+            .handle_inserts(vec![request], ctx.clone())
+            .await
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(1)));
+
+        let Output::RecordBatches(recordbatches) = query(instance, "SHOW CREATE TABLE auto").await else { unreachable!() };
+        let show_create = recordbatches.pretty_print().unwrap();
+        assert!(
+            show_create.contains("TIMESTAMP(3) NOT NULL DEFAULT current_timestamp()"),
+            "{show_create}"
+        );
+
+        // The rows inserted later may omit the time index.
+        let output = query(instance, "INSERT INTO auto(host) VALUES ('host2')").await;
+        assert!(matches!(output, Output::AffectedRows(1)));
+        let Output::Stream(stream) = query(instance, "SELECT host FROM auto ORDER BY host").await else { unreachable!() };
+        let recordbatches = RecordBatches::try_collect(stream).await.unwrap();
+        let expected = "\
++-------+
+| host  |
++-------+
+| host1 |
+| host2 |
++-------+";
+        assert_eq!(expected, recordbatches.pretty_print().unwrap());
+
+        // The function is validated when the table is created.
+        Arc::get_mut(&mut standalone.instance)
+            .unwrap()
+            .set_auto_create_ts_default(Some("bogus()".to_string()));
+        let instance = standalone.instance.as_ref();
+        let request = InsertRequest {
+            table_name: "bogus".to_string(),
+            columns: vec![Column {
+                column_name: "ts".to_string(),
+                semantic_type: SemanticType::Timestamp as i32,
+                values: Some(Values {
+                    ts_millisecond_values: vec![1],
+                    ..Default::default()
+                }),
+                datatype: ColumnDataType::TimestampMillisecond as i32,
+                ..Default::default()
+            }],
+            row_count: 1,
+            ..Default::default()
+        };
+        let err = instance
+            .handle_inserts(vec![request], ctx)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unsupported column default constraint expression: bogus()"),
+            "{err}"
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_distributed_exec_sql() {
         let distributed = tests::create_distributed_instance("test_distributed_exec_sql").await;
@@ -931,6 +1376,161 @@ mod tests {
         verify_table_is_dropped(&distributed).await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_distributed_ddl_in_multi_statement_query() {
+        let distributed =
+            tests::create_distributed_instance("test_distributed_ddl_in_multi_statement_query")
+                .await;
+        let instance = distributed.frontend.as_ref();
+
+        let sql = r#"
+            CREATE TABLE demo(host STRING, ts TIMESTAMP, cpu DOUBLE, TIME INDEX (ts), PRIMARY KEY(host));
+            INSERT INTO demo(host, cpu, ts) VALUES ('host1', 1.0, 1000), ('host2', 2.0, 2000);
+            ALTER TABLE demo ADD COLUMN memory DOUBLE;
+            INSERT INTO demo(host, cpu, memory, ts) VALUES ('host3', 3.0, 30.0, 3000);
+            SELECT host, cpu, memory FROM demo ORDER BY host"#;
+        let mut results = SqlQueryHandler::do_query(instance, sql, QueryContext::arc()).await;
+        assert_eq!(5, results.len());
+        let Output::Stream(s) = results.pop().unwrap().unwrap() else { unreachable!() };
+        let batches = common_recordbatch::util::collect_batches(s).await.unwrap();
+        let expected = "\
++-------+-----+--------+
+| host  | cpu | memory |
++-------+-----+--------+
+| host1 | 1.0 |        |
+| host2 | 2.0 |        |
+| host3 | 3.0 | 30.0   |
++-------+-----+--------+";
+        assert_eq!(expected, batches.pretty_print().unwrap());
+        for result in results {
+            let _ = result.unwrap();
+        }
+
+        // Recreates the table with another schema, whose route and info are not the cached ones.
+        let sql = r#"
+            DROP TABLE demo;
+            CREATE TABLE demo(host STRING, ts TIMESTAMP, disk DOUBLE, TIME INDEX (ts), PRIMARY KEY(host));
+            INSERT INTO demo(host, disk, ts) VALUES ('host4', 4.0, 4000);
+            SELECT * FROM demo"#;
+        let mut results = SqlQueryHandler::do_query(instance, sql, QueryContext::arc()).await;
+        assert_eq!(4, results.len());
+        let Output::Stream(s) = results.pop().unwrap().unwrap() else { unreachable!() };
+        let batches = common_recordbatch::util::collect_batches(s).await.unwrap();
+        let expected = "\
++-------+---------------------+------+
+| host  | ts                  | disk |
++-------+---------------------+------+
+| host4 | 1970-01-01T00:00:04 | 4.0  |
++-------+---------------------+------+";
+        assert_eq!(expected, batches.pretty_print().unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_distributed_table_dropped_by_another_frontend() {
+        let distributed = tests::create_distributed_instance(
+            "test_distributed_table_dropped_by_another_frontend",
+        )
+        .await;
+        let instance = distributed.frontend.as_ref();
+
+        // Caches the table and its route.
+        let sql = r#"
+            CREATE TABLE demo(host STRING, ts TIMESTAMP, cpu DOUBLE, TIME INDEX (ts), PRIMARY KEY(host));
+            INSERT INTO demo(host, cpu, ts) VALUES ('host1', 1.0, 1000);
+            SELECT * FROM demo"#;
+        for result in SqlQueryHandler::do_query(instance, sql, QueryContext::arc()).await {
+            let _ = result.unwrap();
+        }
+
+        let another = distributed.create_another_frontend().await;
+        drop_table(&another).await;
+
+        let sql = "INSERT INTO demo(host, cpu, ts) VALUES ('host2', 2.0, 2000)";
+        let err = SqlQueryHandler::do_query(instance, sql, QueryContext::arc())
+            .await
+            .remove(0)
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+
+        // The stale cache is dropped by the failed insert.
+        let err = SqlQueryHandler::do_query(instance, "SELECT * FROM demo", QueryContext::arc())
+            .await
+            .remove(0)
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_distributed_write_while_table_recreated() {
+        let distributed =
+            tests::create_distributed_instance("test_distributed_write_while_table_recreated")
+                .await;
+        let instance = distributed.frontend.clone();
+        let create = "CREATE TABLE demo(host STRING, ts TIMESTAMP, cpu DOUBLE, TIME INDEX (ts), PRIMARY KEY(host))";
+        let _ = query(&instance, create).await;
+
+        // Writes of all kinds run while another frontend drops and recreates the table, each of
+        // them either succeeds or fails cleanly as the table is not found.
+        let writers = (0..4)
+            .map(|i| {
+                let instance = instance.clone();
+                tokio::spawn(async move {
+                    let sqls = [
+                        format!("INSERT INTO demo(host, cpu, ts) VALUES ('host{i}', 1.0, {i})"),
+                        format!("INSERT INTO demo SELECT * FROM demo WHERE host = 'host{i}'"),
+                        format!("DELETE FROM demo WHERE host = 'host{i}'"),
+                    ];
+                    for _ in 0..10 {
+                        for sql in &sqls {
+                            let result = SqlQueryHandler::do_query(
+                                instance.as_ref(),
+                                sql,
+                                QueryContext::arc(),
+                            )
+                            .await
+                            .remove(0);
+                            if let Err(e) = result {
+                                assert_eq!(
+                                    StatusCode::TableNotFound,
+                                    e.status_code(),
+                                    "{sql}: {e}"
+                                );
+                            }
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let another = distributed.create_another_frontend().await;
+        for _ in 0..5 {
+            drop_table(&another).await;
+            let _ = query(&another, create).await;
+        }
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        // A write refreshes the cache of the table if it's stale, so at most one write fails.
+        let sql = "INSERT INTO demo(host, cpu, ts) VALUES ('host9', 9.0, 9000)";
+        let result = SqlQueryHandler::do_query(instance.as_ref(), sql, QueryContext::arc())
+            .await
+            .remove(0);
+        if let Err(e) = result {
+            assert_eq!(StatusCode::TableNotFound, e.status_code(), "{e}");
+            let _ = query(&instance, sql).await;
+        }
+        let output = query(&instance, "SELECT host, cpu FROM demo WHERE host = 'host9'").await;
+        let Output::Stream(s) = output else { unreachable!() };
+        let batches = common_recordbatch::util::collect_batches(s).await.unwrap();
+        let expected = "\
++-------+-----+
+| host  | cpu |
++-------+-----+
+| host9 | 9.0 |
++-------+-----+";
+        assert_eq!(expected, batches.pretty_print().unwrap());
+    }
+
     async fn query(instance: &Instance, sql: &str) -> Output {
         SqlQueryHandler::do_query(instance, sql, QueryContext::arc())
             .await
@@ -1095,7 +1695,7 @@ mod tests {
             }
         }
 
-        let standalone = tests::create_standalone_instance("test_hook").await;
+        let (standalone, _engine) = tests::create_memory_standalone_instance("test_hook").await;
         let mut instance = standalone.instance;
 
         let mut plugins = Plugins::new();
@@ -1155,7 +1755,7 @@ mod tests {
 
         let query_ctx = Arc::new(QueryContext::new());
 
-        let standalone = tests::create_standalone_instance("test_db_hook").await;
+        let (standalone, _engine) = tests::create_memory_standalone_instance("test_db_hook").await;
         let mut instance = standalone.instance;
 
         let mut plugins = Plugins::new();
@@ -1202,4 +1802,99 @@ mod tests {
             unreachable!();
         }
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_statement_authorizer_plugin() {
+        struct NoDropAuthorizer {
+            requests: std::sync::Mutex<Vec<(String, String, Vec<String>)>>,
+        }
+
+        #[async_trait]
+        impl StatementAuthorizer for NoDropAuthorizer {
+            async fn authorize(
+                &self,
+                user_info: &session::context::UserInfo,
+                action: &str,
+                objects: &[String],
+                _query_ctx: &QueryContextRef,
+            ) -> servers::auth::Result<servers::auth::authorizer::Decision> {
+                self.requests.lock().unwrap().push((
+                    user_info.username().to_string(),
+                    action.to_string(),
+                    objects.to_vec(),
+                ));
+                Ok(if action == "DROP TABLE" {
+                    servers::auth::authorizer::Decision::Deny {
+                        reason: "no drop".to_string(),
+                    }
+                } else {
+                    servers::auth::authorizer::Decision::Allow
+                })
+            }
+        }
+
+        let (standalone, _engine) =
+            tests::create_memory_standalone_instance("test_statement_authorizer").await;
+        let mut instance = standalone.instance;
+        let authorizer = Arc::new(NoDropAuthorizer {
+            requests: Default::default(),
+        });
+        let mut plugins = Plugins::new();
+        plugins.insert::<StatementAuthorizerRef>(authorizer.clone());
+        Arc::make_mut(&mut instance).set_plugins(Arc::new(plugins));
+
+        let query_ctx = Arc::new(QueryContext::new());
+        query_ctx.set_current_user(session::context::UserInfo::new("alice"));
+
+        let sql = "CREATE TABLE demo(host STRING, ts TIMESTAMP TIME INDEX, val DOUBLE, PRIMARY KEY(host))";
+        let output = SqlQueryHandler::do_query(&*instance, sql, query_ctx.clone())
+            .await
+            .remove(0)
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(0)));
+
+        let sql = "SELECT * FROM demo WHERE host IN (SELECT host FROM public.demo)";
+        let output = SqlQueryHandler::do_query(&*instance, sql, query_ctx.clone())
+            .await
+            .remove(0)
+            .unwrap();
+        assert!(matches!(
+            output,
+            Output::Stream(_) | Output::RecordBatches(_)
+        ));
+
+        // TQL names no table, it's authorized on the tables of its plan.
+        let sql = "TQL EVAL (0, 10, '5s') demo";
+        let output = SqlQueryHandler::do_query(&*instance, sql, query_ctx.clone())
+            .await
+            .remove(0)
+            .unwrap();
+        assert!(matches!(
+            output,
+            Output::Stream(_) | Output::RecordBatches(_)
+        ));
+
+        let err = SqlQueryHandler::do_query(&*instance, "DROP TABLE demo", query_ctx.clone())
+            .await
+            .remove(0)
+            .unwrap_err();
+        assert_eq!(StatusCode::AccessDenied, err.status_code());
+        assert!(err.to_string().contains("no drop"), "{err}");
+
+        let requests = authorizer.requests.lock().unwrap().clone();
+        let demo = vec!["greptime.public.demo".to_string()];
+        assert_eq!(
+            vec![
+                (
+                    "alice".to_string(),
+                    "CREATE TABLE".to_string(),
+                    demo.clone()
+                ),
+                ("alice".to_string(), "SELECT".to_string(), demo.clone()),
+                ("alice".to_string(), "TQL".to_string(), demo.clone()),
+                ("alice".to_string(), "DROP TABLE".to_string(), demo),
+            ],
+            requests
+        );
+    }
 }
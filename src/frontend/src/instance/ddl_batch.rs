@@ -0,0 +1,612 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Creation of a batch of tables all or nothing.
+//!
+//! All the tables of a batch are validated before creating any of them. If a table fails to be
+//! created, the tables created before it in the batch are dropped as compensation. The tables
+//! are dropped by their ids, so a table of the same name created by another DDL meanwhile is
+//! left alone.
+//!
+//! In distributed mode, the tables created are also recorded in the journal of the batch in the
+//! meta server, which rolls back the batch if the frontend fails midway, see
+//! [DdlBatchValue](catalog::helper::DdlBatchValue).
+
+use std::collections::HashSet;
+
+use api::v1::DropTableExpr;
+use async_trait::async_trait;
+use catalog::helper::{DdlBatchKey, DdlBatchTable, DdlBatchValue};
+use catalog::remote::KvBackendRef;
+use common_catalog::format_full_table_name;
+use common_error::prelude::{BoxedError, ErrorExt, StatusCode};
+use common_telemetry::{error, info, warn};
+use common_time::util::current_time_millis;
+use datanode::instance::sql::table_idents_to_full_name;
+use meta_client::rpc::TableName;
+use servers::query_handler::{DdlBatchHandler, TableCreation, TableCreationStatus};
+use session::context::QueryContextRef;
+use snafu::{ensure, OptionExt, ResultExt};
+use sql::statements::create::CreateTable;
+use sql::statements::statement::Statement;
+use table::metadata::TableId;
+use table::requests::DropTableRequest;
+
+use crate::error::{
+    CatalogEntrySerdeSnafu, CatalogSnafu, InvalidSqlSnafu, InvokeDatanodeSnafu, NotSupportedSnafu,
+    Result, SchemaNotFoundSnafu, TableAlreadyExistSnafu,
+};
+use crate::expr_factory;
+use crate::instance::{check_permission, parse_stmt, Instance};
+
+/// A validated table of the batch.
+struct BatchTable {
+    /// Full name of the table.
+    name: String,
+    stmt: Statement,
+    drop_expr: DropTableExpr,
+    /// Whether the table already exists and is created with `IF NOT EXISTS`.
+    existed: bool,
+}
+
+/// A table created by the batch.
+struct CreatedTable {
+    /// Index of the table in the creations.
+    index: usize,
+    table: DdlBatchTable,
+}
+
+/// Journal of the batch in the meta server.
+struct BatchJournal {
+    backend: KvBackendRef,
+    key: String,
+    value: DdlBatchValue,
+}
+
+impl BatchJournal {
+    fn new(backend: KvBackendRef) -> Self {
+        let key = DdlBatchKey {
+            batch_id: uuid::Uuid::new_v4().to_string(),
+        };
+        Self {
+            backend,
+            key: key.to_string(),
+            value: DdlBatchValue::default(),
+        }
+    }
+
+    /// Records the table created by the batch.
+    async fn record(&mut self, table: DdlBatchTable) -> Result<()> {
+        self.value.tables.push(table);
+        self.value.updated_at_millis = current_time_millis();
+        let value = self.value.as_bytes().context(CatalogEntrySerdeSnafu)?;
+        self.backend
+            .set(self.key.as_bytes(), &value)
+            .await
+            .context(CatalogSnafu)
+    }
+
+    /// Removes the journal once the batch is created or rolled back.
+    async fn remove(&self) -> Result<()> {
+        if self.value.tables.is_empty() {
+            return Ok(());
+        }
+        self.backend
+            .delete(self.key.as_bytes())
+            .await
+            .context(CatalogSnafu)
+    }
+}
+
+impl Instance {
+    async fn create_tables_in_batch(
+        &self,
+        sql: &str,
+        ctx: QueryContextRef,
+    ) -> Result<Vec<TableCreation>> {
+        let stmts = parse_stmt(sql)?;
+        ensure!(
+            !stmts.is_empty(),
+            InvalidSqlSnafu {
+                err_msg: "no CREATE TABLE statement in the batch",
+            }
+        );
+
+        let mut names = HashSet::with_capacity(stmts.len());
+        let mut tables = Vec::with_capacity(stmts.len());
+        for (index, stmt) in stmts.into_iter().enumerate() {
+            tables.push(
+                self.validate_batch_table(index, stmt, &ctx, &mut names)
+                    .await,
+            );
+        }
+        if tables.iter().any(|table| table.is_err()) {
+            return Ok(tables
+                .into_iter()
+                .map(|table| match table {
+                    Ok(table) => TableCreation::new(table.name, TableCreationStatus::Skipped),
+                    Err(creation) => creation,
+                })
+                .collect());
+        }
+
+        let tables = tables.into_iter().flatten().collect::<Vec<_>>();
+        let mut journal = self
+            .dist_instance
+            .as_ref()
+            .map(|dist_instance| BatchJournal::new(dist_instance.kv_backend()));
+        let mut creations = Vec::with_capacity(tables.len());
+        let mut created = Vec::new();
+        let mut tables = tables.into_iter();
+        for table in tables.by_ref() {
+            if table.existed {
+                creations.push(TableCreation::new(table.name, TableCreationStatus::Existed));
+                continue;
+            }
+
+            let (mut result, table_id) = self.create_batch_table(&table, ctx.clone()).await;
+            if let Some(table_id) = table_id {
+                let created_table = DdlBatchTable {
+                    catalog_name: table.drop_expr.catalog_name,
+                    schema_name: table.drop_expr.schema_name,
+                    table_name: table.drop_expr.table_name,
+                    table_id,
+                };
+                if let Some(journal) = &mut journal {
+                    let recorded = journal.record(created_table.clone()).await;
+                    result = result.and(recorded);
+                }
+                created.push(CreatedTable {
+                    index: creations.len(),
+                    table: created_table,
+                });
+            }
+            match result {
+                Ok(()) => {
+                    creations.push(TableCreation::new(table.name, TableCreationStatus::Created))
+                }
+                Err(e) => {
+                    error!(e; "Failed to create table {} in batch, rolling back", table.name);
+                    creations.push(TableCreation::failed(table.name, e.to_string()));
+                    break;
+                }
+            }
+        }
+        creations.extend(
+            tables.map(|table| TableCreation::new(table.name, TableCreationStatus::Skipped)),
+        );
+
+        let mut failed = creations
+            .iter()
+            .any(|creation| creation.status == TableCreationStatus::Failed);
+        if !failed {
+            // The batch is committed by removing its journal, or the meta server would roll
+            // back the batch later.
+            let committed = match &journal {
+                Some(journal) => journal.remove().await,
+                None => Ok(()),
+            };
+            if let Err(e) = committed {
+                error!(e; "Failed to commit the batch, rolling back");
+                if let Some(last) = created.last() {
+                    let creation = &mut creations[last.index];
+                    creation.status = TableCreationStatus::Failed;
+                    creation.error = Some(format!("failed to commit the batch: {e}"));
+                }
+                failed = true;
+            }
+        }
+        if failed {
+            let rolled_back = self.rollback_tables(&mut creations, created).await;
+            if let (true, Some(journal)) = (rolled_back, &journal) {
+                if let Err(e) = journal.remove().await {
+                    warn!("Failed to remove the journal of the rolled back batch, error: {e}");
+                }
+            }
+        }
+        Ok(creations)
+    }
+
+    /// Validates the table of the `index`-th statement, which must be a `CREATE TABLE`.
+    async fn validate_batch_table(
+        &self,
+        index: usize,
+        stmt: Statement,
+        ctx: &QueryContextRef,
+        names: &mut HashSet<String>,
+    ) -> std::result::Result<BatchTable, TableCreation> {
+        let Statement::CreateTable(create) = &stmt else {
+            return Err(TableCreation::failed(
+                format!("statement #{index}"),
+                "only CREATE TABLE statements are allowed in the batch".to_string(),
+            ));
+        };
+        let name = table_idents_to_full_name(&create.name, ctx.clone())
+            .map(|(catalog, schema, table)| format_full_table_name(&catalog, &schema, &table))
+            .unwrap_or_else(|_| create.name.to_string());
+        self.validate_create_table(&name, create, &stmt, ctx, names)
+            .await
+            .map_err(|e| TableCreation::failed(name, e.to_string()))
+    }
+
+    async fn validate_create_table(
+        &self,
+        name: &str,
+        create: &CreateTable,
+        stmt: &Statement,
+        ctx: &QueryContextRef,
+        names: &mut HashSet<String>,
+    ) -> Result<BatchTable> {
+        check_permission(self.plugins.clone(), stmt, ctx)?;
+        self.check_column_limits(stmt, ctx).await?;
+
+        let expr = expr_factory::create_to_expr(create, ctx.clone())?;
+        ensure!(
+            names.insert(name.to_string()),
+            InvalidSqlSnafu {
+                err_msg: format!("table {name} is created more than once in the batch"),
+            }
+        );
+
+        ensure!(
+            self.catalog_manager
+                .schema_exist(&expr.catalog_name, &expr.schema_name)
+                .await
+                .context(CatalogSnafu)?,
+            SchemaNotFoundSnafu {
+                schema_info: format!("{}.{}", expr.catalog_name, expr.schema_name),
+            }
+        );
+
+        let drop_expr = DropTableExpr {
+            catalog_name: expr.catalog_name,
+            schema_name: expr.schema_name,
+            table_name: expr.table_name,
+        };
+        let existed = self.table_id(&drop_expr).await.is_some();
+        ensure!(
+            !existed || expr.create_if_not_exists,
+            TableAlreadyExistSnafu { table: name }
+        );
+
+        // The table doesn't exist, so it must be created by the batch, instead of taking the
+        // table created by another DDL meanwhile as created by the batch.
+        let mut create = create.clone();
+        create.if_not_exists = false;
+        Ok(BatchTable {
+            name: name.to_string(),
+            stmt: Statement::CreateTable(create),
+            drop_expr,
+            existed,
+        })
+    }
+
+    /// Returns the id of the table, or `None` if the table doesn't exist.
+    async fn table_id(&self, expr: &DropTableExpr) -> Option<TableId> {
+        match self
+            .catalog_manager
+            .table(&expr.catalog_name, &expr.schema_name, &expr.table_name)
+            .await
+        {
+            Ok(Some(table)) => Some(table.table_info().ident.table_id),
+            _ => None,
+        }
+    }
+
+    /// Creates the table, returns the result and the id of the table if it's created, even
+    /// partially.
+    async fn create_batch_table(
+        &self,
+        table: &BatchTable,
+        ctx: QueryContextRef,
+    ) -> (Result<()>, Option<TableId>) {
+        let Statement::CreateTable(create) = &table.stmt else {
+            unreachable!("validated as CREATE TABLE");
+        };
+
+        if let Some(dist_instance) = &self.dist_instance {
+            let mut expr = match expr_factory::create_to_expr(create, ctx) {
+                Ok(expr) => expr,
+                Err(e) => return (Err(e), None),
+            };
+            // The id of the table is assigned to the expression once the table is created in
+            // the meta server, from which point the table has to be rolled back.
+            let result = dist_instance
+                .create_table(&mut expr, create.partitions.clone())
+                .await
+                .map(|_| ());
+            return (result, expr.table_id.map(|table_id| table_id.id));
+        }
+
+        // A table of the local catalog is only registered after it's created by the engine, so
+        // there is nothing to roll back if the creation fails.
+        match self.query_statement(table.stmt.clone(), ctx).await {
+            Ok(_) => {
+                let table_id = self.table_id(&table.drop_expr).await;
+                (Ok(()), table_id)
+            }
+            Err(e) => (Err(e), None),
+        }
+    }
+
+    /// Drops the `created` tables in the reverse order of creation, returns true if all of
+    /// them are dropped.
+    async fn rollback_tables(
+        &self,
+        creations: &mut [TableCreation],
+        created: Vec<CreatedTable>,
+    ) -> bool {
+        let mut rolled_back = true;
+        for CreatedTable { index, table } in created.into_iter().rev() {
+            let creation = &mut creations[index];
+            match self.drop_batch_table(&table).await {
+                Ok(()) => info!("Rolled back the creation of table {}", creation.table),
+                // Dropped or replaced by another DDL, either way the table created is gone.
+                Err(e) if e.status_code() == StatusCode::TableNotFound => {
+                    info!(
+                        "Table {} created by the batch is already dropped: {e}",
+                        creation.table
+                    );
+                }
+                Err(e) => {
+                    error!(e; "Failed to roll back the creation of table {}", creation.table);
+                    let rollback_error = format!("failed to roll back: {e}");
+                    creation.error = Some(match creation.error.take() {
+                        Some(error) => format!("{error}; {rollback_error}"),
+                        None => rollback_error,
+                    });
+                    rolled_back = false;
+                    continue;
+                }
+            }
+            if creation.status == TableCreationStatus::Created {
+                creation.status = TableCreationStatus::RolledBack;
+            }
+        }
+        rolled_back
+    }
+
+    /// Drops the table created by the batch, only if the table of the name still has the id.
+    async fn drop_batch_table(&self, table: &DdlBatchTable) -> Result<()> {
+        if let Some(dist_instance) = &self.dist_instance {
+            let table_name =
+                TableName::new(&table.catalog_name, &table.schema_name, &table.table_name);
+            let _ = dist_instance
+                .drop_table(table_name, Some(table.table_id))
+                .await?;
+            return Ok(());
+        }
+
+        let dn_instance = self.dn_instance.as_ref().context(NotSupportedSnafu {
+            feat: "rolling back tables without a datanode",
+        })?;
+        let request = DropTableRequest {
+            catalog_name: table.catalog_name.clone(),
+            schema_name: table.schema_name.clone(),
+            table_name: table.table_name.clone(),
+        };
+        let _ = dn_instance
+            .sql_handler()
+            .drop_table_by_id(request, table.table_id)
+            .await
+            .context(InvokeDatanodeSnafu)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DdlBatchHandler for Instance {
+    async fn create_tables(
+        &self,
+        sql: &str,
+        ctx: QueryContextRef,
+    ) -> servers::error::Result<Vec<TableCreation>> {
+        self.create_tables_in_batch(sql, ctx)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::ExecuteQuerySnafu { query: sql })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use catalog::helper::build_ddl_batch_prefix;
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use futures::TryStreamExt;
+    use servers::query_handler::sql::SqlQueryHandler;
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::tests;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_standalone_create_tables() {
+        let standalone = tests::create_standalone_instance("test_standalone_create_tables").await;
+        let instance = &standalone.instance;
+
+        test_create_tables(instance).await;
+        test_rollback_by_id(instance).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_distributed_create_tables() {
+        let instance = tests::create_distributed_instance("test_distributed_create_tables").await;
+        let frontend = &instance.frontend;
+
+        test_create_tables(frontend).await;
+        test_rollback_by_id(frontend).await;
+
+        // The journals of the batches are removed once they are created or rolled back.
+        let backend = frontend.dist_instance.as_ref().unwrap().kv_backend();
+        let journals = backend
+            .range(build_ddl_batch_prefix().as_bytes())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(journals.is_empty());
+    }
+
+    fn create_table_sql(name: &str, options: &str) -> String {
+        format!(
+            "CREATE TABLE {name} (host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX, \
+             PRIMARY KEY(host)) {options};"
+        )
+    }
+
+    async fn table_exists(instance: &Arc<Instance>, name: &str) -> bool {
+        instance
+            .catalog_manager()
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, name)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    async fn table_id(instance: &Arc<Instance>, name: &str) -> TableId {
+        instance
+            .catalog_manager()
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, name)
+            .await
+            .unwrap()
+            .unwrap()
+            .table_info()
+            .ident
+            .table_id
+    }
+
+    /// Rolling back a table created by a batch leaves the table of the same name created by
+    /// another DDL alone.
+    async fn test_rollback_by_id(instance: &Arc<Instance>) {
+        let creations = instance
+            .create_tables(&create_table_sql("t5", ""), QueryContext::arc())
+            .await
+            .unwrap();
+        assert_eq!(vec![TableCreationStatus::Created], statuses(&creations));
+        let batch_table = DdlBatchTable {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "t5".to_string(),
+            table_id: table_id(instance, "t5").await,
+        };
+
+        for sql in ["DROP TABLE t5", &create_table_sql("t5", "")] {
+            let _ = instance
+                .do_query(sql, QueryContext::arc())
+                .await
+                .remove(0)
+                .unwrap();
+        }
+        let new_table_id = table_id(instance, "t5").await;
+        assert_ne!(batch_table.table_id, new_table_id);
+
+        let err = instance.drop_batch_table(&batch_table).await.unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code());
+        assert_eq!(new_table_id, table_id(instance, "t5").await);
+    }
+
+    fn statuses(creations: &[TableCreation]) -> Vec<TableCreationStatus> {
+        creations.iter().map(|creation| creation.status).collect()
+    }
+
+    async fn test_create_tables(instance: &Arc<Instance>) {
+        // The third table has an invalid option.
+        let sql = [
+            create_table_sql("t1", ""),
+            create_table_sql("t2", "WITH(ttl='7d')"),
+            create_table_sql("t3", "WITH(foo='bar')"),
+            create_table_sql("t4", ""),
+        ]
+        .concat();
+        let creations = instance
+            .create_tables(&sql, QueryContext::arc())
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![
+                TableCreationStatus::Skipped,
+                TableCreationStatus::Skipped,
+                TableCreationStatus::Failed,
+                TableCreationStatus::Skipped,
+            ],
+            statuses(&creations)
+        );
+        assert_eq!("greptime.public.t3", creations[2].table);
+        assert!(creations[2].error.is_some());
+        for name in ["t1", "t2", "t3", "t4"] {
+            assert!(!table_exists(instance, name).await, "{name}");
+        }
+
+        // The third table fails to be created, after the first two are created.
+        let sql = [
+            create_table_sql("t1", ""),
+            create_table_sql("t2", ""),
+            create_table_sql("t3", "ENGINE=no_such_engine"),
+            create_table_sql("t4", ""),
+        ]
+        .concat();
+        let creations = instance
+            .create_tables(&sql, QueryContext::arc())
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![
+                TableCreationStatus::RolledBack,
+                TableCreationStatus::RolledBack,
+                TableCreationStatus::Failed,
+                TableCreationStatus::Skipped,
+            ],
+            statuses(&creations)
+        );
+        for name in ["t1", "t2", "t3", "t4"] {
+            assert!(!table_exists(instance, name).await, "{name}");
+        }
+
+        let sql = [
+            create_table_sql("t1", ""),
+            create_table_sql("t2", "WITH(ttl='7d')"),
+            create_table_sql("t3", ""),
+        ]
+        .concat();
+        let creations = instance
+            .create_tables(&sql, QueryContext::arc())
+            .await
+            .unwrap();
+        assert_eq!(vec![TableCreationStatus::Created; 3], statuses(&creations));
+        for name in ["t1", "t2", "t3"] {
+            assert!(table_exists(instance, name).await, "{name}");
+        }
+
+        // Existing tables are rejected unless created with `IF NOT EXISTS`.
+        let creations = instance
+            .create_tables(&create_table_sql("t1", ""), QueryContext::arc())
+            .await
+            .unwrap();
+        assert_eq!(vec![TableCreationStatus::Failed], statuses(&creations));
+        let sql = format!(
+            "{}{}",
+            create_table_sql("IF NOT EXISTS t1", ""),
+            create_table_sql("t4", "")
+        );
+        let creations = instance
+            .create_tables(&sql, QueryContext::arc())
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![TableCreationStatus::Existed, TableCreationStatus::Created],
+            statuses(&creations)
+        );
+    }
+}
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 mod grpc;
+mod recycle_bin;
+mod repartition;
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -23,15 +25,18 @@ use api::v1::{
     FlushTableExpr, InsertRequest, TableId,
 };
 use async_trait::async_trait;
-use catalog::helper::{SchemaKey, SchemaValue};
+use catalog::ddl_lock::{DdlLocks, DdlLocksRef};
+use catalog::helper::{SchemaKey, SchemaValue, DN_LEASE_PREFIX};
+use catalog::recycle_bin::is_recycled_table_name;
+use catalog::remote::KvBackendRef;
 use catalog::{CatalogManager, DeregisterTableRequest, RegisterTableRequest};
 use chrono::DateTime;
 use client::Database;
-use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MITO_ENGINE};
 use common_catalog::format_full_table_name;
-use common_error::prelude::BoxedError;
+use common_error::prelude::{BoxedError, ErrorExt, StatusCode};
 use common_query::Output;
-use common_telemetry::debug;
+use common_telemetry::{debug, warn};
 use datanode::instance::sql::table_idents_to_full_name;
 use datanode::sql::SqlHandler;
 use datatypes::prelude::ConcreteDataType;
@@ -40,7 +45,7 @@ use meta_client::client::MetaClient;
 use meta_client::rpc::router::DeleteRequest as MetaDeleteRequest;
 use meta_client::rpc::{
     CompareAndPutRequest, CreateRequest as MetaCreateRequest, Partition as MetaPartition,
-    RouteRequest, RouteResponse, TableName,
+    RangeRequest, RouteRequest, RouteResponse, TableName,
 };
 use partition::manager::PartitionInfo;
 use partition::partition::{PartitionBound, PartitionDef};
@@ -49,12 +54,17 @@ use query::query_engine::SqlStatementExecutor;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
 use sql::ast::{Ident, Value as SqlValue};
+use sql::statements::alter::AlterTableOperation;
 use sql::statements::create::{PartitionEntry, Partitions};
 use sql::statements::statement::Statement;
 use sql::statements::{self, sql_value_to_value};
+use sql::util::to_lowercase_options_map;
 use table::engine::TableReference;
 use table::metadata::{RawTableInfo, RawTableMeta, TableIdent, TableType};
-use table::requests::TableOptions;
+use table::requests::{
+    with_column_storage_options, AlterKind, DeleteRequest as TableDeleteRequest,
+    InsertRequest as TableInsertRequest, TableOptions,
+};
 use table::table::AlterContext;
 use table::TableRef;
 
@@ -71,12 +81,17 @@ use crate::expr_factory;
 use crate::table::DistTable;
 
 const MAX_VALUE: &str = "MAXVALUE";
+/// Key read from the meta server to check whether it's reachable.
+const READINESS_PROBE_KEY: &[u8] = b"__frontend_readiness_probe";
 
 #[derive(Clone)]
 pub(crate) struct DistInstance {
     meta_client: Arc<MetaClient>,
     catalog_manager: Arc<FrontendCatalogManager>,
     datanode_clients: Arc<DatanodeClients>,
+    ddl_locks: DdlLocksRef,
+    /// Whether `DROP TABLE` moves the tables to the recycle bin.
+    soft_drop: bool,
 }
 
 impl DistInstance {
@@ -85,10 +100,58 @@ impl DistInstance {
         catalog_manager: Arc<FrontendCatalogManager>,
         datanode_clients: Arc<DatanodeClients>,
     ) -> Self {
+        // The DDL on the same tables from the other frontends is serialized by the lock of
+        // the metasrv.
+        let ddl_locks = Arc::new(DdlLocks::default().with_dist_lock(meta_client.clone()));
         Self {
             meta_client,
             catalog_manager,
             datanode_clients,
+            ddl_locks,
+            soft_drop: false,
+        }
+    }
+
+    pub(crate) fn with_soft_drop(mut self, soft_drop: bool) -> Self {
+        self.soft_drop = soft_drop;
+        self
+    }
+
+    /// Returns the locks held by the DDL on existing tables.
+    pub(crate) fn ddl_locks(&self) -> &DdlLocksRef {
+        &self.ddl_locks
+    }
+
+    /// Returns the backend of the catalog in the meta server.
+    pub(crate) fn kv_backend(&self) -> KvBackendRef {
+        self.catalog_manager.backend()
+    }
+
+    /// Returns true if the meta server answers a read.
+    pub(crate) async fn is_meta_connected(&self) -> bool {
+        let request = RangeRequest::new().with_key(READINESS_PROBE_KEY);
+        match self.meta_client.range(request).await {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Meta server is unreachable, err: {e}");
+                false
+            }
+        }
+    }
+
+    /// Returns the number of the datanodes holding a lease in the meta server, `None` if the
+    /// meta server is unreachable. The expired leases are removed by the meta server, so the
+    /// count is the alive datanodes, whether the frontend has sent them a request or not.
+    pub(crate) async fn num_datanodes(&self) -> Option<u64> {
+        let request = RangeRequest::new()
+            .with_prefix(format!("{DN_LEASE_PREFIX}-"))
+            .with_keys_only();
+        match self.meta_client.range(request).await {
+            Ok(mut response) => Some(response.take_kvs().len() as u64),
+            Err(e) => {
+                warn!("Failed to get the datanode leases from meta server, err: {e}");
+                None
+            }
         }
     }
 
@@ -125,6 +188,19 @@ impl DistInstance {
         }
 
         let mut table_info = create_table_info(create_table)?;
+        if let Some(schema) = self
+            .catalog_manager
+            .schema(&table_name.catalog_name, &table_name.schema_name)
+            .await
+            .context(CatalogSnafu)?
+        {
+            let schema_options = schema.default_table_options().await.context(CatalogSnafu)?;
+            table_info
+                .meta
+                .options
+                .inherit(&schema_options)
+                .context(UnrecognizedTableOptionSnafu)?;
+        }
 
         let response = self
             .create_table_in_meta(create_table, partitions, &table_info)
@@ -164,13 +240,13 @@ impl DistInstance {
             self.catalog_manager.backend(),
         ));
 
-        let request = RegisterTableRequest {
-            catalog: table_name.catalog_name.clone(),
-            schema: table_name.schema_name.clone(),
-            table_name: table_name.table_name.clone(),
+        let request = RegisterTableRequest::new(
+            &table_name.catalog_name,
+            &table_name.schema_name,
+            &table_name.table_name,
             table_id,
-            table: table.clone(),
-        };
+            table.clone(),
+        );
         ensure!(
             self.catalog_manager
                 .register_table(request)
@@ -200,11 +276,33 @@ impl DistInstance {
                 .await
                 .context(RequestDatanodeSnafu)?;
         }
+
+        // Drops the route and the value cached by a previous table of the same name.
+        self.catalog_manager.invalidate_table(&table_name).await;
         Ok(table)
     }
 
-    async fn drop_table(&self, table_name: TableName) -> Result<Output> {
-        let _ = self
+    /// Publishes the tables changed by a committed DDL to the other frontends. A failure only
+    /// leaves the other frontends with the stale metadata until their caches expire, so it's
+    /// logged rather than failing the DDL.
+    pub(crate) async fn publish_table_invalidation(&self, tables: &[&TableName]) {
+        if let Err(e) = self
+            .catalog_manager
+            .publish_table_invalidation(tables)
+            .await
+        {
+            warn!("Failed to publish the invalidation of tables {tables:?}, error: {e}");
+        }
+    }
+
+    /// Drops the table, only if its id is `expected_table_id` if it's given. The table is moved
+    /// to the recycle bin instead if soft drop is enabled, like in standalone mode.
+    pub(crate) async fn drop_table(
+        &self,
+        table_name: TableName,
+        expected_table_id: Option<table::metadata::TableId>,
+    ) -> Result<Output> {
+        let table = self
             .catalog_manager
             .table(
                 &table_name.catalog_name,
@@ -216,6 +314,44 @@ impl DistInstance {
             .with_context(|| TableNotFoundSnafu {
                 table_name: table_name.to_string(),
             })?;
+        let _guard = self
+            .ddl_locks
+            .lock(
+                &[table.table_info().ident.table_id],
+                format!("DROP TABLE {table_name}"),
+            )
+            .await
+            .context(CatalogSnafu)?;
+        // Checks again as the table may have been dropped or renamed while waiting for the lock.
+        let table = self
+            .catalog_manager
+            .table(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+            )
+            .await
+            .context(CatalogSnafu)?
+            .with_context(|| TableNotFoundSnafu {
+                table_name: table_name.to_string(),
+            })?;
+        if let Some(expected) = expected_table_id {
+            let actual = table.table_info().ident.table_id;
+            ensure!(
+                actual == expected,
+                error::TableIdMismatchSnafu {
+                    table_name: table_name.to_string(),
+                    expected,
+                    actual,
+                }
+            );
+        }
+        if self.soft_drop
+            && table.table_info().meta.engine == MITO_ENGINE
+            && !is_recycled_table_name(&table_name.table_name)
+        {
+            return self.recycle_table(table_name, &table).await;
+        }
 
         let route_response = self
             .meta_client
@@ -257,6 +393,7 @@ impl DistInstance {
                     .context(RequestDatanodeSnafu)?;
             }
         }
+        self.publish_table_invalidation(&[&table_name]).await;
 
         Ok(Output::AffectedRows(1))
     }
@@ -323,11 +460,15 @@ impl DistInstance {
     ) -> Result<Output> {
         match stmt {
             Statement::CreateDatabase(stmt) => {
+                let table_options =
+                    TableOptions::try_from_schema_options(&to_lowercase_options_map(&stmt.options))
+                        .context(UnrecognizedTableOptionSnafu)?;
                 let expr = CreateDatabaseExpr {
                     database_name: stmt.name.to_string(),
                     create_if_not_exists: stmt.if_not_exists,
                 };
-                self.handle_create_database(expr, query_ctx).await
+                self.handle_create_database(expr, table_options, query_ctx)
+                    .await
             }
             Statement::CreateTable(stmt) => {
                 let create_expr = &mut expr_factory::create_to_expr(&stmt, query_ctx)?;
@@ -340,6 +481,16 @@ impl DistInstance {
                 Ok(Output::AffectedRows(0))
             }
             Statement::Alter(alter_table) => {
+                if let AlterTableOperation::Repartition { partitions } =
+                    alter_table.alter_operation()
+                {
+                    let (catalog, schema, table) =
+                        table_idents_to_full_name(alter_table.table_name(), query_ctx)
+                            .map_err(BoxedError::new)
+                            .context(error::ExternalSnafu)?;
+                    let table_name = TableName::new(catalog, schema, table);
+                    return self.repartition_table(table_name, partitions).await;
+                }
                 let expr = grpc::to_alter_expr(alter_table, query_ctx)?;
                 self.handle_alter_table(expr).await
             }
@@ -349,29 +500,48 @@ impl DistInstance {
                         .map_err(BoxedError::new)
                         .context(error::ExternalSnafu)?;
                 let table_name = TableName::new(catalog, schema, table);
-                self.drop_table(table_name).await
+                self.drop_table(table_name, None).await
             }
-            Statement::Insert(insert) => {
+            Statement::RestoreTable(stmt) => {
                 let (catalog, schema, table) =
+                    table_idents_to_full_name(stmt.table_name(), query_ctx)
+                        .map_err(BoxedError::new)
+                        .context(error::ExternalSnafu)?;
+                let table_name = TableName::new(catalog, schema, table);
+                self.restore_table(table_name, stmt.new_table_name().map(ToString::to_string))
+                    .await
+            }
+            Statement::Insert(insert) => {
+                let validation_mode = query_ctx.validation_mode();
+                let (catalog, schema, table_name) =
                     table_idents_to_full_name(insert.table_name(), query_ctx.clone())
                         .map_err(BoxedError::new)
                         .context(error::ExternalSnafu)?;
 
+                let table_ref = TableReference::full(&catalog, &schema, &table_name);
                 let table = self
                     .catalog_manager
-                    .table(&catalog, &schema, &table)
+                    .table(&catalog, &schema, &table_name)
                     .await
                     .context(CatalogSnafu)?
-                    .context(TableNotFoundSnafu { table_name: table })?;
-
-                let insert_request =
-                    SqlHandler::insert_to_request(self.catalog_manager.clone(), *insert, query_ctx)
-                        .await
-                        .context(InvokeDatanodeSnafu)?;
-
-                Ok(Output::AffectedRows(
-                    table.insert(insert_request).await.context(TableSnafu)?,
-                ))
+                    .with_context(|| TableNotFoundSnafu {
+                        table_name: table_ref.to_string(),
+                    })?;
+
+                // Builds the request from the table just found rather than looking it up
+                // again, so the whole statement sees the same schema even if the table is
+                // altered or dropped in the meantime.
+                let insert_request = SqlHandler::build_request_from_values(
+                    table_ref,
+                    &table,
+                    *insert,
+                    validation_mode,
+                )
+                .context(InvokeDatanodeSnafu)?;
+
+                let table_name = TableName::new(catalog, schema, table_name);
+                self.insert_into(&table_name, table, insert_request, &query_ctx)
+                    .await
             }
             Statement::ShowCreateTable(show) => {
                 let (catalog, schema, table) =
@@ -387,7 +557,8 @@ impl DistInstance {
                     .context(TableNotFoundSnafu { table_name: &table })?;
                 let table_name = TableName::new(catalog, schema, table);
 
-                self.show_create_table(table_name, table_ref).await
+                self.show_create_table(table_name, table_ref, show.for_mysql)
+                    .await
             }
             _ => error::NotSupportedSnafu {
                 feat: format!("{stmt:?}"),
@@ -396,7 +567,12 @@ impl DistInstance {
         }
     }
 
-    async fn show_create_table(&self, table_name: TableName, table: TableRef) -> Result<Output> {
+    async fn show_create_table(
+        &self,
+        table_name: TableName,
+        table: TableRef,
+        for_mysql: bool,
+    ) -> Result<Output> {
         let partitions = self
             .catalog_manager
             .partition_manager()
@@ -408,22 +584,24 @@ impl DistInstance {
 
         let partitions = create_partitions_stmt(partitions)?;
 
-        query::sql::show_create_table(table, partitions).context(error::ExecuteStatementSnafu)
+        query::sql::show_create_table(table, partitions, for_mysql)
+            .context(error::ExecuteStatementSnafu)
     }
 
-    /// Handles distributed database creation
+    /// Handles distributed database creation, `table_options` are the default options of the
+    /// tables created in the database.
     async fn handle_create_database(
         &self,
         expr: CreateDatabaseExpr,
+        table_options: TableOptions,
         query_ctx: QueryContextRef,
     ) -> Result<Output> {
         let catalog = query_ctx.current_catalog();
         if self
             .catalog_manager
-            .schema(&catalog, &expr.database_name)
+            .schema_exist(&catalog, &expr.database_name)
             .await
             .context(CatalogSnafu)?
-            .is_some()
         {
             return if expr.create_if_not_exists {
                 Ok(Output::AffectedRows(1))
@@ -439,7 +617,7 @@ impl DistInstance {
             catalog_name: catalog,
             schema_name: expr.database_name,
         };
-        let value = SchemaValue {};
+        let value = SchemaValue::new(&table_options);
         let client = self
             .meta_client
             .store_client()
@@ -474,13 +652,42 @@ impl DistInstance {
             expr.schema_name.as_str()
         };
         let table_name = expr.table_name.as_str();
+        let full_table_name = format_full_table_name(catalog_name, schema_name, table_name);
         let table = self
             .catalog_manager
             .table(catalog_name, schema_name, table_name)
             .await
             .context(CatalogSnafu)?
             .context(TableNotFoundSnafu {
-                table_name: format_full_table_name(catalog_name, schema_name, table_name),
+                table_name: &full_table_name,
+            })?;
+        let _guard = self
+            .ddl_locks
+            .lock(
+                &[table.table_info().ident.table_id],
+                format!("ALTER TABLE {full_table_name}"),
+            )
+            .await
+            .context(CatalogSnafu)?;
+        let table_name = TableName::new(catalog_name, schema_name, table_name);
+        self.alter_table_locked(table_name, expr).await
+    }
+
+    /// Alters the table by `expr`, the caller holds the DDL lock of the table.
+    async fn alter_table_locked(&self, table_name: TableName, expr: AlterExpr) -> Result<Output> {
+        // Gets the table again so the alter applies to the table info the alter before it
+        // leaves, rather than the one before locking.
+        let table = self
+            .catalog_manager
+            .table(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+            )
+            .await
+            .context(CatalogSnafu)?
+            .with_context(|| TableNotFoundSnafu {
+                table_name: table_name.to_string(),
             })?;
 
         let request = common_grpc_expr::alter_expr_to_request(expr.clone())
@@ -491,6 +698,23 @@ impl DistInstance {
 
         table.alter(context, &request).await.context(TableSnafu)?;
 
+        self.catalog_manager.invalidate_table(&table_name).await;
+        let new_table_name = match &request.alter_kind {
+            AlterKind::RenameTable { new_table_name } => {
+                let new_table_name = TableName::new(
+                    &table_name.catalog_name,
+                    &table_name.schema_name,
+                    new_table_name,
+                );
+                self.catalog_manager.invalidate_table(&new_table_name).await;
+                Some(new_table_name)
+            }
+            _ => None,
+        };
+        let tables = [Some(&table_name), new_table_name.as_ref()];
+        self.publish_table_invalidation(&tables.into_iter().flatten().collect::<Vec<_>>())
+            .await;
+
         Ok(Output::AffectedRows(0))
     }
 
@@ -546,11 +770,45 @@ impl DistInstance {
                 table_name: table_ref.to_string(),
             })?;
 
-        let request = common_grpc_expr::insert::to_table_insert_request(catalog, schema, request)
-            .context(ToTableInsertRequestSnafu)?;
+        let table_name = TableName::new(catalog, schema, table_name);
+        let request = common_grpc_expr::insert::to_table_insert_request(
+            catalog,
+            schema,
+            request,
+            &table.schema(),
+            ctx.write_mode(),
+            ctx.validation_mode(),
+        )
+        .context(ToTableInsertRequestSnafu)?;
+
+        self.insert_into(&table_name, table, request, &ctx).await
+    }
 
-        let affected_rows = table.insert(request).await.context(TableSnafu)?;
-        Ok(Output::AffectedRows(affected_rows))
+    /// Inserts into the `table` found in the catalog. If the datanodes don't find the table, it
+    /// has been dropped by another frontend since it was cached, so the cache of the table is
+    /// refreshed for the following statements.
+    ///
+    /// The writes are recorded in the commit token of the session.
+    async fn insert_into(
+        &self,
+        table_name: &TableName,
+        table: TableRef,
+        request: TableInsertRequest,
+        ctx: &QueryContextRef,
+    ) -> Result<Output> {
+        let table = table.with_commit_token(ctx.commit_token()).unwrap_or(table);
+        match table.insert(request).await.context(TableSnafu) {
+            Ok(affected_rows) => {
+                ctx.merge_commit_token(&table.commit_token().context(TableSnafu)?);
+                Ok(Output::AffectedRows(affected_rows))
+            }
+            Err(e) => {
+                if e.status_code() == StatusCode::TableNotFound {
+                    self.catalog_manager.invalidate_table(table_name).await;
+                }
+                Err(e)
+            }
+        }
     }
 
     async fn handle_dist_delete(
@@ -572,11 +830,40 @@ impl DistInstance {
                 table_name: table_ref.to_string(),
             })?;
 
-        let request = common_grpc_expr::delete::to_table_delete_request(request)
-            .context(ToTableDeleteRequestSnafu)?;
+        let table_name = TableName::new(catalog, schema, table_name);
+        let request = common_grpc_expr::delete::to_table_delete_request(
+            catalog,
+            schema,
+            request,
+            &table.table_info().meta,
+        )
+        .context(ToTableDeleteRequestSnafu)?;
 
-        let affected_rows = table.delete(request).await.context(TableSnafu)?;
-        Ok(Output::AffectedRows(affected_rows))
+        self.delete_from(&table_name, table, request, &ctx).await
+    }
+
+    /// Deletes from the `table` found in the catalog, refreshing the cache of the table if the
+    /// datanodes don't find it, like [Self::insert_into].
+    async fn delete_from(
+        &self,
+        table_name: &TableName,
+        table: TableRef,
+        request: TableDeleteRequest,
+        ctx: &QueryContextRef,
+    ) -> Result<Output> {
+        let table = table.with_commit_token(ctx.commit_token()).unwrap_or(table);
+        match table.delete(request).await.context(TableSnafu) {
+            Ok(affected_rows) => {
+                ctx.merge_commit_token(&table.commit_token().context(TableSnafu)?);
+                Ok(Output::AffectedRows(affected_rows))
+            }
+            Err(e) => {
+                if e.status_code() == StatusCode::TableNotFound {
+                    self.catalog_manager.invalidate_table(table_name).await;
+                }
+                Err(e)
+            }
+        }
     }
 
     #[cfg(test)]
@@ -648,7 +935,11 @@ fn create_table_info(create_table: &CreateTableExpr) -> Result<RawTableInfo> {
             column_def::try_as_column_schema(column).context(error::InvalidColumnDefSnafu {
                 column: &column.name,
             })?;
-        let schema = schema.with_time_index(column.name == create_table.time_index);
+        let schema = with_column_storage_options(
+            schema.with_time_index(column.name == create_table.time_index),
+            &create_table.table_options,
+        )
+        .context(UnrecognizedTableOptionSnafu)?;
 
         column_schemas.push(schema);
         column_name_to_index_map.insert(column.name.clone(), idx);
@@ -758,13 +1049,7 @@ fn find_partition_entries(
             for (i, v) in e.value_list.iter().enumerate() {
                 // indexing is safe here because we have checked that "value_list" and "column_list" are matched in size
                 let (column_name, data_type) = &column_name_and_type[i];
-                let v = match v {
-                    SqlValue::Number(n, _) if n == MAX_VALUE => PartitionBound::MaxValue,
-                    _ => PartitionBound::Value(
-                        sql_value_to_value(column_name, data_type, v).context(ParseSqlSnafu)?,
-                    ),
-                };
-                values.push(v);
+                values.push(to_partition_bound(column_name, data_type, v)?);
             }
             entries.push(values);
         }
@@ -775,6 +1060,20 @@ fn find_partition_entries(
     Ok(entries)
 }
 
+fn to_partition_bound(
+    column_name: &str,
+    data_type: &ConcreteDataType,
+    value: &SqlValue,
+) -> Result<PartitionBound> {
+    let bound = match value {
+        SqlValue::Number(n, _) if n == MAX_VALUE => PartitionBound::MaxValue,
+        _ => PartitionBound::Value(
+            sql_value_to_value(column_name, data_type, value).context(ParseSqlSnafu)?,
+        ),
+    };
+    Ok(bound)
+}
+
 fn find_partition_columns(
     create_table: &CreateTableExpr,
     partitions: &Option<Partitions>,
@@ -26,6 +26,7 @@ use session::context::QueryContextRef;
 use snafu::{OptionExt, ResultExt};
 use sql::statements::alter::{AlterTable, AlterTableOperation};
 use sql::statements::sql_column_def_to_grpc_column_def;
+use table::requests::TableOptions;
 
 use crate::error::{self, ExternalSnafu, Result};
 use crate::instance::distributed::DistInstance;
@@ -46,7 +47,11 @@ impl GrpcQueryHandler for DistInstance {
                     err_msg: "Missing 'expr' in DDL request",
                 })?;
                 match expr {
-                    DdlExpr::CreateDatabase(expr) => self.handle_create_database(expr, ctx).await,
+                    DdlExpr::CreateDatabase(expr) => {
+                        // Creating databases with default table options is only supported by SQL.
+                        self.handle_create_database(expr, TableOptions::default(), ctx)
+                            .await
+                    }
                     DdlExpr::CreateTable(mut expr) => {
                         // TODO(LFC): Support creating distributed table through GRPC interface.
                         // Currently only SQL supports it; how to design the fields in CreateTableExpr?
@@ -57,7 +62,7 @@ impl GrpcQueryHandler for DistInstance {
                     DdlExpr::DropTable(expr) => {
                         let table_name =
                             TableName::new(&expr.catalog_name, &expr.schema_name, &expr.table_name);
-                        self.drop_table(table_name).await
+                        self.drop_table(table_name, None).await
                     }
                     DdlExpr::FlushTable(expr) => {
                         let table_name =
@@ -104,6 +109,20 @@ pub(crate) fn to_alter_expr(
         AlterTableOperation::RenameTable { new_table_name } => Kind::RenameTable(RenameTable {
             new_table_name: new_table_name.to_string(),
         }),
+        // The alter expr can't carry the storage options of columns.
+        AlterTableOperation::SetColumnOptions { .. } => {
+            return error::NotSupportedSnafu {
+                feat: "ALTER COLUMN SET in distributed mode",
+            }
+            .fail();
+        }
+        // Served by the metasrv, see `DistInstance::repartition_table`.
+        AlterTableOperation::Repartition { .. } => {
+            return error::NotSupportedSnafu {
+                feat: "REPARTITION in alter expr",
+            }
+            .fail();
+        }
     };
 
     Ok(AlterExpr {
@@ -0,0 +1,273 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recycle bin of the distributed tables.
+//!
+//! A soft dropped table is renamed to its name in the recycle bin, in the metadata of the
+//! metasrv and on the datanodes, under the DDL lock of the metasrv like the other renames. So
+//! any frontend sees it in the recycle bin and can restore it, and each frontend's reaper
+//! drops the tables staying there longer than the retention.
+
+use api::v1::alter_expr::Kind;
+use api::v1::{AlterExpr, RenameTable};
+use async_trait::async_trait;
+use catalog::recycle_bin::{recycled_tables, RecycledTable};
+use catalog::CatalogManager;
+use common_error::prelude::BoxedError;
+use common_query::Output;
+use common_telemetry::info;
+use common_time::util::current_time_millis;
+use datanode::recycle_bin::RecycledTableDropper;
+use meta_client::rpc::TableName;
+use snafu::{ensure, OptionExt, ResultExt};
+use table::requests::DropTableRequest;
+use table::TableRef;
+
+use crate::error::{
+    CatalogSnafu, RecycledTableNotFoundSnafu, Result, SchemaNotFoundSnafu, TableAlreadyExistSnafu,
+};
+use crate::instance::distributed::DistInstance;
+
+impl DistInstance {
+    /// Renames the table to its name in the recycle bin, the caller holds the DDL lock of the
+    /// table.
+    pub(super) async fn recycle_table(
+        &self,
+        table_name: TableName,
+        table: &TableRef,
+    ) -> Result<Output> {
+        let recycled = RecycledTable::new(
+            &table_name.table_name,
+            table.table_info().ident.table_id,
+            current_time_millis(),
+        );
+        info!(
+            "Move table {table_name} to the recycle bin as {}",
+            recycled.recycled_name
+        );
+
+        let _ = self
+            .rename_table_locked(table_name, recycled.recycled_name)
+            .await?;
+        Ok(Output::AffectedRows(1))
+    }
+
+    /// Restores the table named `table_name` dropped last from the recycle bin, by renaming it
+    /// back to its name or `new_table_name`.
+    pub(super) async fn restore_table(
+        &self,
+        table_name: TableName,
+        new_table_name: Option<String>,
+    ) -> Result<Output> {
+        let schema = self
+            .catalog_manager
+            .schema(&table_name.catalog_name, &table_name.schema_name)
+            .await
+            .context(CatalogSnafu)?
+            .with_context(|| SchemaNotFoundSnafu {
+                schema_info: &table_name.schema_name,
+            })?;
+        let recycled = recycled_tables(&schema)
+            .await
+            .context(CatalogSnafu)?
+            .into_iter()
+            .rev()
+            .find(|recycled| recycled.table_name == table_name.table_name)
+            .with_context(|| RecycledTableNotFoundSnafu {
+                table_name: table_name.to_string(),
+            })?;
+
+        let _guard = self
+            .ddl_locks
+            .lock(&[recycled.table_id], format!("RESTORE TABLE {table_name}"))
+            .await
+            .context(CatalogSnafu)?;
+
+        let new_table_name = TableName::new(
+            &table_name.catalog_name,
+            &table_name.schema_name,
+            new_table_name.unwrap_or(table_name.table_name),
+        );
+        let exists = self
+            .catalog_manager
+            .table(
+                &new_table_name.catalog_name,
+                &new_table_name.schema_name,
+                &new_table_name.table_name,
+            )
+            .await
+            .context(CatalogSnafu)?
+            .is_some();
+        ensure!(
+            !exists,
+            TableAlreadyExistSnafu {
+                table: new_table_name.to_string(),
+            }
+        );
+
+        info!(
+            "Restore table {} from the recycle bin as {new_table_name}",
+            recycled.recycled_name
+        );
+        let recycled_name = TableName::new(
+            new_table_name.catalog_name,
+            new_table_name.schema_name,
+            recycled.recycled_name,
+        );
+        let _ = self
+            .rename_table_locked(recycled_name, new_table_name.table_name)
+            .await?;
+        Ok(Output::AffectedRows(0))
+    }
+
+    async fn rename_table_locked(
+        &self,
+        table_name: TableName,
+        new_table_name: String,
+    ) -> Result<Output> {
+        let expr = AlterExpr {
+            catalog_name: table_name.catalog_name.clone(),
+            schema_name: table_name.schema_name.clone(),
+            table_name: table_name.table_name.clone(),
+            kind: Some(Kind::RenameTable(RenameTable { new_table_name })),
+        };
+        self.alter_table_locked(table_name, expr).await
+    }
+}
+
+#[async_trait]
+impl RecycledTableDropper for DistInstance {
+    async fn drop_recycled_table(
+        &self,
+        req: DropTableRequest,
+    ) -> std::result::Result<(), BoxedError> {
+        let table_name = TableName::new(req.catalog_name, req.schema_name, req.table_name);
+        self.drop_table(table_name, None)
+            .await
+            .map(|_| ())
+            .map_err(BoxedError::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use common_error::prelude::{ErrorExt, StatusCode};
+    use common_recordbatch::RecordBatches;
+    use datanode::datanode::RecycleBinConfig;
+    use datanode::recycle_bin::RecycleBinReaper;
+    use servers::query_handler::sql::SqlQueryHandler;
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::instance::Instance;
+    use crate::tests;
+
+    async fn execute_sql(instance: &Instance, sql: &str) -> Result<Output> {
+        instance.do_query(sql, QueryContext::arc()).await.remove(0)
+    }
+
+    async fn query(instance: &Instance, sql: &str) -> String {
+        let Output::Stream(stream) = execute_sql(instance, sql).await.unwrap() else { unreachable!() };
+        RecordBatches::try_collect(stream)
+            .await
+            .unwrap()
+            .pretty_print()
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_dist_drop_and_restore_table() {
+        let instance = tests::create_distributed_instance("test_dist_drop_and_restore_table").await;
+        let frontend = instance.frontend.as_ref();
+        let dist_instance =
+            Arc::new(DistInstance::clone(&instance.dist_instance).with_soft_drop(true));
+
+        execute_sql(
+            frontend,
+            "CREATE TABLE metrics(host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX, PRIMARY KEY(host))",
+        )
+        .await
+        .unwrap();
+        execute_sql(
+            frontend,
+            "INSERT INTO metrics VALUES ('host1', 1.0, 1000), ('host2', 2.0, 2000)",
+        )
+        .await
+        .unwrap();
+
+        let table_name = TableName::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "metrics");
+        let output = dist_instance
+            .drop_table(table_name.clone(), None)
+            .await
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(1)));
+        let err = execute_sql(frontend, "SELECT * FROM metrics")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+
+        // The table in the recycle bin can't be written, nor can a table be renamed into it.
+        let schema = instance
+            .catalog_manager
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let recycled = recycled_tables(&schema).await.unwrap();
+        assert_eq!(1, recycled.len());
+        assert_eq!("metrics", recycled[0].table_name);
+        let recycled_name = &recycled[0].recycled_name;
+        let sql = format!("INSERT INTO {recycled_name} VALUES ('host3', 3.0, 3000)");
+        assert!(execute_sql(frontend, &sql).await.is_err());
+        let sql = format!("ALTER TABLE metrics RENAME {recycled_name}");
+        let err = execute_sql(frontend, &sql).await.unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code(), "{err}");
+
+        // Any frontend restores the table.
+        let output = execute_sql(frontend, "RESTORE TABLE metrics")
+            .await
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(0)));
+        let expected = "\
++-------+-----+
+| host  | cpu |
++-------+-----+
+| host1 | 1.0 |
+| host2 | 2.0 |
++-------+-----+";
+        assert_eq!(
+            expected,
+            query(frontend, "SELECT host, cpu FROM metrics ORDER BY host").await
+        );
+
+        // The reaper drops the table staying in the recycle bin longer than the retention.
+        let _ = dist_instance.drop_table(table_name, None).await.unwrap();
+        let config = RecycleBinConfig::default();
+        let reaper = RecycleBinReaper::new(
+            instance.catalog_manager.clone(),
+            dist_instance.clone(),
+            &config,
+        );
+        assert_eq!(0, reaper.reap(current_time_millis()).await);
+        let expired_at = current_time_millis() + config.retention.as_millis() as i64;
+        assert_eq!(1, reaper.reap(expired_at).await);
+        let err = execute_sql(frontend, "RESTORE TABLE metrics")
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::TableNotFound, err.status_code(), "{err}");
+    }
+}
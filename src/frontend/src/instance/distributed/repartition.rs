@@ -0,0 +1,248 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `ALTER TABLE ... REPARTITION` of distributed tables.
+//!
+//! The frontend puts a request to repartition the table in the meta server, which the metasrv
+//! leader serves by the procedure changing the regions of the table, see [RepartitionValue].
+//! The statement returns after the procedure finishes, with the caches of the table in the
+//! frontend invalidated, so the following statements route to the new regions.
+
+use std::time::Duration;
+
+use catalog::helper::{RepartitionKey, RepartitionPartition, RepartitionStatus, RepartitionValue};
+use common_query::Output;
+use common_telemetry::info;
+use meta_client::rpc::{
+    CompareAndPutRequest, DeleteRangeRequest, Partition as MetaPartition, RangeRequest, TableName,
+};
+use partition::partition::PartitionDef;
+use snafu::{ensure, OptionExt, ResultExt};
+use sql::statements::create::Partitions;
+use table::metadata::TableInfo;
+
+use crate::error::{
+    CatalogEntrySerdeSnafu, CatalogSnafu, DeserializePartitionSnafu, InvalidSqlSnafu,
+    RepartitionInProgressSnafu, RepartitionTableSnafu, RequestMetaSnafu, Result,
+    TableNotFoundSnafu,
+};
+use crate::instance::distributed::{to_partition_bound, DistInstance};
+
+/// Interval of polling the status of the request to repartition a table.
+const REPARTITION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+impl DistInstance {
+    pub(super) async fn repartition_table(
+        &self,
+        table_name: TableName,
+        partitions: &Partitions,
+    ) -> Result<Output> {
+        let full_table_name = table_name.to_string();
+        let table = self
+            .catalog_manager
+            .table(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+            )
+            .await
+            .context(CatalogSnafu)?
+            .context(TableNotFoundSnafu {
+                table_name: &full_table_name,
+            })?;
+        let table_info = table.table_info();
+        let partitions = to_repartitions(&table_info, partitions)?;
+
+        let _guard = self
+            .ddl_locks
+            .lock(
+                &[table_info.ident.table_id],
+                format!("ALTER TABLE {full_table_name} REPARTITION"),
+            )
+            .await
+            .context(CatalogSnafu)?;
+
+        let key = RepartitionKey {
+            table_id: table_info.ident.table_id,
+        }
+        .to_string();
+        let request = RepartitionValue {
+            catalog_name: table_name.catalog_name.clone(),
+            schema_name: table_name.schema_name.clone(),
+            table_name: table_name.table_name.clone(),
+            partitions,
+            status: RepartitionStatus::Pending,
+        };
+        self.put_repartition_request(&key, &request, &full_table_name)
+            .await?;
+        info!("Requested to repartition table {full_table_name}");
+
+        let result = self.wait_repartition_request(&key, &full_table_name).await;
+
+        let _ = self
+            .meta_client
+            .delete_range(DeleteRangeRequest::new().with_key(key))
+            .await
+            .context(RequestMetaSnafu)?;
+        // The table info and the route of the table are changed by the procedure, even if it
+        // fails after switching the route.
+        self.catalog_manager.invalidate_table(&table_name).await;
+        self.publish_table_invalidation(&[&table_name]).await;
+
+        result.map(|_| Output::AffectedRows(0))
+    }
+
+    /// Puts the request to repartition a table, failing if another one of the table is in
+    /// flight. The request left by a frontend failing midway is replaced once it's finished.
+    async fn put_repartition_request(
+        &self,
+        key: &str,
+        request: &RepartitionValue,
+        table_name: &str,
+    ) -> Result<()> {
+        let expect = match self.get_repartition_request(key).await? {
+            Some((value, existing)) => {
+                ensure!(
+                    matches!(
+                        existing.status,
+                        RepartitionStatus::Done | RepartitionStatus::Failed { .. }
+                    ),
+                    RepartitionInProgressSnafu { table_name }
+                );
+                value
+            }
+            None => Vec::new(),
+        };
+        let request = CompareAndPutRequest::new()
+            .with_key(key)
+            .with_expect(expect)
+            .with_value(request.as_bytes().context(CatalogEntrySerdeSnafu)?);
+        let response = self
+            .meta_client
+            .compare_and_put(request)
+            .await
+            .context(RequestMetaSnafu)?;
+        ensure!(
+            response.is_success(),
+            RepartitionInProgressSnafu { table_name }
+        );
+        Ok(())
+    }
+
+    /// Waits for the request to repartition a table to finish, polling the meta server
+    /// directly as the cached backend of the catalog doesn't see the updates of the metasrv.
+    async fn wait_repartition_request(&self, key: &str, table_name: &str) -> Result<()> {
+        loop {
+            tokio::time::sleep(REPARTITION_POLL_INTERVAL).await;
+            let (_, request) = self.get_repartition_request(key).await?.with_context(|| {
+                RepartitionTableSnafu {
+                    table_name,
+                    reason: "the request is removed",
+                }
+            })?;
+            match request.status {
+                RepartitionStatus::Done => return Ok(()),
+                RepartitionStatus::Failed { error } => {
+                    return RepartitionTableSnafu {
+                        table_name,
+                        reason: error,
+                    }
+                    .fail()
+                }
+                RepartitionStatus::Pending | RepartitionStatus::Running { .. } => {}
+            }
+        }
+    }
+
+    /// Returns the request in raw bytes and decoded.
+    async fn get_repartition_request(
+        &self,
+        key: &str,
+    ) -> Result<Option<(Vec<u8>, RepartitionValue)>> {
+        let mut response = self
+            .meta_client
+            .range(RangeRequest::new().with_key(key))
+            .await
+            .context(RequestMetaSnafu)?;
+        let Some(mut kv) = response.take_kvs().pop() else {
+            return Ok(None);
+        };
+        let value = kv.take_value();
+        let request = RepartitionValue::from_bytes(&value).context(CatalogEntrySerdeSnafu)?;
+        Ok(Some((value, request)))
+    }
+}
+
+/// Converts the partitions of the statement to the ones of the request, bounded by the values
+/// of the partition columns in the table.
+fn to_repartitions(
+    table_info: &TableInfo,
+    partitions: &Partitions,
+) -> Result<Vec<RepartitionPartition>> {
+    let schema = &table_info.meta.schema;
+    let columns = partitions
+        .column_list
+        .iter()
+        .map(|column| {
+            schema
+                .column_schema_by_name(&column.value)
+                .with_context(|| InvalidSqlSnafu {
+                    err_msg: format!(
+                        "Partition column {} not found in table {}",
+                        column.value, table_info.name
+                    ),
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut bounds = Vec::with_capacity(partitions.entries.len());
+    for entry in &partitions.entries {
+        let values = entry
+            .value_list
+            .iter()
+            .zip(&columns)
+            .map(|(value, column)| to_partition_bound(&column.name, &column.data_type, value))
+            .collect::<Result<Vec<_>>>()?;
+        bounds.push(values);
+    }
+    ensure!(
+        bounds.windows(2).all(|w| w[0] < w[1]),
+        InvalidSqlSnafu {
+            err_msg: "VALUES LESS THAN value must be strictly increasing for each partition.",
+        }
+    );
+
+    let column_names = columns
+        .iter()
+        .map(|column| column.name.clone())
+        .collect::<Vec<_>>();
+    bounds
+        .into_iter()
+        .map(|bounds| {
+            let partition =
+                MetaPartition::try_from(PartitionDef::new(column_names.clone(), bounds))
+                    .context(DeserializePartitionSnafu)?;
+            let to_strings = |values: Vec<Vec<u8>>| {
+                values
+                    .into_iter()
+                    .map(|v| String::from_utf8_lossy(&v).to_string())
+                    .collect()
+            };
+            Ok(RepartitionPartition {
+                columns: to_strings(partition.column_list),
+                bounds: to_strings(partition.value_list),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,369 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry runs of inserts, which run the conversion and validation of the insert requests and
+//! find the tables to create and the columns to add, without executing the DDL or writing the
+//! rows.
+
+use std::sync::Arc;
+
+use api::v1::InsertRequest;
+use common_catalog::consts::MITO_ENGINE;
+use common_grpc_expr::dry_run::{dry_run_create_table, dry_run_insert_into, InsertDryRun};
+use common_query::Output;
+use common_recordbatch::RecordBatches;
+use datatypes::prelude::{ConcreteDataType, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{BooleanVector, StringVector, UInt64Vector};
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+
+use crate::error::{self, Result};
+use crate::instance::Instance;
+
+impl Instance {
+    /// Dry runs the inserts of `requests`, the table of a request is checked as it is before
+    /// the requests, as nothing is created or altered.
+    pub async fn dry_run_inserts(
+        &self,
+        requests: &[InsertRequest],
+        ctx: &QueryContextRef,
+    ) -> Result<Vec<InsertDryRun>> {
+        let mut dry_runs = Vec::with_capacity(requests.len());
+        for request in requests {
+            dry_runs.push(self.dry_run_insert(request, ctx).await?);
+        }
+        Ok(dry_runs)
+    }
+
+    async fn dry_run_insert(
+        &self,
+        request: &InsertRequest,
+        ctx: &QueryContextRef,
+    ) -> Result<InsertDryRun> {
+        let catalog_name = &ctx.current_catalog();
+        let schema_name = &ctx.current_schema();
+        let table_name = &request.table_name;
+
+        let table = self
+            .catalog_manager
+            .table(catalog_name, schema_name, table_name)
+            .await
+            .context(error::CatalogSnafu)?;
+        let limits = self.catalog_manager.column_limits().limits_of(schema_name);
+        match table {
+            None => {
+                let create_expr = self
+                    .create_expr_factory
+                    .create_expr_by_columns(
+                        catalog_name,
+                        schema_name,
+                        table_name,
+                        &request.columns,
+                        MITO_ENGINE,
+                        &limits,
+                        self.auto_create_ts_default.as_ref(),
+                    )
+                    .await?;
+                dry_run_create_table(&create_expr, request)
+            }
+            Some(table) => {
+                let schema = table.schema();
+                let add_columns =
+                    common_grpc_expr::find_new_columns(&schema, &request.columns, &limits)
+                        .context(error::FindNewColumnsOnInsertionSnafu)?;
+                dry_run_insert_into(&schema, add_columns.as_ref(), request)
+            }
+        }
+        .context(error::DryRunInsertSnafu { table_name })
+    }
+}
+
+/// Returns the dry runs as records, with a row for each row error of a table, or a row of null
+/// error if the table has no row errors.
+pub(crate) fn dry_runs_to_output(dry_runs: &[InsertDryRun]) -> Result<Output> {
+    let mut table_names = Vec::new();
+    let mut would_create_tables = Vec::new();
+    let mut columns_to_add = Vec::new();
+    let mut rows_accepted = Vec::new();
+    let mut error_rows = Vec::new();
+    let mut error_columns = Vec::new();
+    let mut error_reasons = Vec::new();
+
+    for dry_run in dry_runs {
+        let new_columns = dry_run
+            .columns_to_add
+            .iter()
+            .map(|column| {
+                if column.is_key {
+                    format!("{} {} PRIMARY KEY", column.name, column.data_type)
+                } else {
+                    format!("{} {}", column.name, column.data_type)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let errors = if dry_run.row_errors.is_empty() {
+            vec![None]
+        } else {
+            dry_run.row_errors.iter().map(Some).collect()
+        };
+        for error in errors {
+            table_names.push(dry_run.table_name.clone());
+            would_create_tables.push(dry_run.would_create_table);
+            columns_to_add.push(new_columns.clone());
+            rows_accepted.push(dry_run.rows_accepted as u64);
+            error_rows.push(error.map(|error| error.row as u64));
+            error_columns.push(error.map(|error| error.column.clone()));
+            error_reasons.push(error.map(|error| error.reason.clone()));
+        }
+    }
+
+    let schema = Arc::new(Schema::new(vec![
+        ColumnSchema::new("table_name", ConcreteDataType::string_datatype(), false),
+        ColumnSchema::new(
+            "would_create_table",
+            ConcreteDataType::boolean_datatype(),
+            false,
+        ),
+        ColumnSchema::new("columns_to_add", ConcreteDataType::string_datatype(), false),
+        ColumnSchema::new("rows_accepted", ConcreteDataType::uint64_datatype(), false),
+        ColumnSchema::new("error_row", ConcreteDataType::uint64_datatype(), true),
+        ColumnSchema::new("error_column", ConcreteDataType::string_datatype(), true),
+        ColumnSchema::new("error_reason", ConcreteDataType::string_datatype(), true),
+    ]));
+    let columns: Vec<VectorRef> = vec![
+        Arc::new(StringVector::from(table_names)),
+        Arc::new(BooleanVector::from(would_create_tables)),
+        Arc::new(StringVector::from(columns_to_add)),
+        Arc::new(UInt64Vector::from_vec(rows_accepted)),
+        Arc::new(UInt64Vector::from(error_rows)),
+        Arc::new(StringVector::from(error_columns)),
+        Arc::new(StringVector::from(error_reasons)),
+    ];
+    let batches = RecordBatches::try_from_columns(schema, columns)
+        .context(error::CreateRecordBatchesSnafu)?;
+    Ok(Output::RecordBatches(batches))
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::column::{SemanticType, Values};
+    use api::v1::{Column, ColumnDataType};
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use common_grpc_expr::dry_run::{NewColumn, RowError};
+    use servers::query_handler::sql::SqlQueryHandler;
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::tests;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_standalone_dry_run_inserts() {
+        let standalone = tests::create_standalone_instance("test_standalone_dry_run_inserts").await;
+        let instance = &standalone.instance;
+
+        test_dry_run_inserts(instance).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_distributed_dry_run_inserts() {
+        let instance = tests::create_distributed_instance("test_distributed_dry_run_inserts").await;
+        let instance = &instance.frontend;
+
+        test_dry_run_inserts(instance).await;
+    }
+
+    fn new_column(
+        name: &str,
+        semantic_type: SemanticType,
+        datatype: ColumnDataType,
+        values: Values,
+        null_mask: Vec<u8>,
+    ) -> Column {
+        Column {
+            column_name: name.to_string(),
+            semantic_type: semantic_type as i32,
+            values: Some(values),
+            null_mask,
+            datatype: datatype as i32,
+        }
+    }
+
+    fn new_request(ts: Vec<i64>, mut columns: Vec<Column>) -> InsertRequest {
+        let row_count = ts.len() as u32;
+        columns.push(new_column(
+            "host",
+            SemanticType::Tag,
+            ColumnDataType::String,
+            Values {
+                string_values: vec!["host1".to_string(); ts.len()],
+                ..Default::default()
+            },
+            vec![0],
+        ));
+        columns.push(new_column(
+            "ts",
+            SemanticType::Timestamp,
+            ColumnDataType::TimestampMillisecond,
+            Values {
+                ts_millisecond_values: ts,
+                ..Default::default()
+            },
+            vec![0],
+        ));
+        InsertRequest {
+            table_name: "monitor".to_string(),
+            columns,
+            row_count,
+            ..Default::default()
+        }
+    }
+
+    async fn query_monitor(instance: &Arc<Instance>) -> String {
+        let output = instance
+            .do_query(
+                "SELECT host, cpu, ts FROM monitor ORDER BY ts",
+                QueryContext::arc(),
+            )
+            .await
+            .remove(0)
+            .unwrap();
+        let Output::Stream(stream) = output else { unreachable!() };
+        let recordbatches = RecordBatches::try_collect(stream).await.unwrap();
+        recordbatches.pretty_print().unwrap()
+    }
+
+    async fn test_dry_run_inserts(instance: &Arc<Instance>) {
+        let dry_run_ctx = QueryContext::arc();
+        dry_run_ctx.set_dry_run(true);
+
+        let cpu = new_column(
+            "cpu",
+            SemanticType::Field,
+            ColumnDataType::Float64,
+            Values {
+                f64_values: vec![0.5, 0.6],
+                ..Default::default()
+            },
+            vec![0],
+        );
+        let request = new_request(vec![1000, 2000], vec![cpu]);
+
+        // The table to create is reported, but not created.
+        let dry_runs = instance
+            .dry_run_inserts(&[request.clone()], &dry_run_ctx)
+            .await
+            .unwrap();
+        assert_eq!(1, dry_runs.len());
+        assert!(dry_runs[0].would_create_table);
+        assert_eq!(2, dry_runs[0].rows_accepted);
+        assert!(dry_runs[0].row_errors.is_empty());
+        let table = instance
+            .catalog_manager()
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "monitor")
+            .await
+            .unwrap();
+        assert!(table.is_none());
+
+        let output = instance
+            .handle_inserts(vec![request], QueryContext::arc())
+            .await
+            .unwrap();
+        assert!(matches!(output, Output::AffectedRows(2)));
+        let expected = "\
++-------+-----+---------------------+
+| host  | cpu | ts                  |
++-------+-----+---------------------+
+| host1 | 0.5 | 1970-01-01T00:00:01 |
+| host1 | 0.6 | 1970-01-01T00:00:02 |
++-------+-----+---------------------+";
+        assert_eq!(expected, query_monitor(instance).await);
+
+        // The value of cpu in the second row is a string, the first row is null.
+        let cpu = new_column(
+            "cpu",
+            SemanticType::Field,
+            ColumnDataType::String,
+            Values {
+                string_values: vec!["high".to_string()],
+                ..Default::default()
+            },
+            vec![0b01],
+        );
+        let memory = new_column(
+            "memory",
+            SemanticType::Field,
+            ColumnDataType::Float64,
+            Values {
+                f64_values: vec![1024.0, 2048.0],
+                ..Default::default()
+            },
+            vec![0],
+        );
+        let request = new_request(vec![3000, 4000], vec![cpu, memory]);
+
+        let dry_runs = instance
+            .dry_run_inserts(&[request.clone()], &dry_run_ctx)
+            .await
+            .unwrap();
+        assert_eq!(1, dry_runs.len());
+        assert!(!dry_runs[0].would_create_table);
+        assert_eq!(
+            vec![NewColumn {
+                name: "memory".to_string(),
+                data_type: "Float64".to_string(),
+                is_key: false,
+            }],
+            dry_runs[0].columns_to_add
+        );
+        assert_eq!(1, dry_runs[0].rows_accepted);
+        assert_eq!(
+            vec![RowError {
+                row: 1,
+                column: "cpu".to_string(),
+                value: Some("high".to_string()),
+                reason: "column expects type Float64, got String".to_string(),
+            }],
+            dry_runs[0].row_errors
+        );
+
+        // Inserts in the dry run context are dry runs reported as records.
+        let output = instance
+            .handle_inserts(vec![request], dry_run_ctx)
+            .await
+            .unwrap();
+        let Output::RecordBatches(recordbatches) = output else { unreachable!() };
+        assert_eq!(
+            "\
++------------+--------------------+----------------+---------------+-----------+--------------+-----------------------------------------+
+| table_name | would_create_table | columns_to_add | rows_accepted | error_row | error_column | error_reason                            |
++------------+--------------------+----------------+---------------+-----------+--------------+-----------------------------------------+
+| monitor    | false              | memory Float64 | 1             | 1         | cpu          | column expects type Float64, got String |
++------------+--------------------+----------------+---------------+-----------+--------------+-----------------------------------------+",
+            recordbatches.pretty_print().unwrap()
+        );
+
+        // Neither the column is added nor the rows are written.
+        let table = instance
+            .catalog_manager()
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "monitor")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(table.schema().column_schema_by_name("memory").is_none());
+        assert_eq!(expected, query_monitor(instance).await);
+    }
+}
@@ -23,6 +23,7 @@ use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt};
 
 use crate::error::{self, Result};
+use crate::expr_factory::check_new_table_name_of_ddl;
 use crate::instance::Instance;
 
 #[async_trait]
@@ -31,7 +32,7 @@ impl GrpcQueryHandler for Instance {
 
     async fn do_query(&self, request: Request, ctx: QueryContextRef) -> Result<Output> {
         let output = match request {
-            Request::Insert(request) => self.handle_insert(request, ctx).await?,
+            Request::Insert(request) => self.handle_inserts(vec![request], ctx).await?,
             Request::Query(query_request) => {
                 let query = query_request
                     .query
@@ -74,7 +75,18 @@ impl GrpcQueryHandler for Instance {
                     }
                 }
             }
-            Request::Ddl(_) | Request::Delete(_) => {
+            Request::Ddl(ddl_request) => {
+                if let Some(expr) = &ddl_request.expr {
+                    check_new_table_name_of_ddl(expr)?;
+                }
+                GrpcQueryHandler::do_query(
+                    self.grpc_query_handler.as_ref(),
+                    Request::Ddl(ddl_request),
+                    ctx,
+                )
+                .await?
+            }
+            Request::Delete(_) => {
                 GrpcQueryHandler::do_query(self.grpc_query_handler.as_ref(), request, ctx).await?
             }
         };
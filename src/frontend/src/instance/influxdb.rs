@@ -13,7 +13,9 @@
 // limitations under the License.
 
 use async_trait::async_trait;
+use common_base::validation_mode::ValidationMode;
 use common_error::prelude::BoxedError;
+use common_grpc_expr::dry_run::InsertDryRun;
 use servers::influxdb::InfluxdbRequest;
 use servers::query_handler::InfluxdbLineProtocolHandler;
 use session::context::QueryContextRef;
@@ -28,13 +30,25 @@ impl InfluxdbLineProtocolHandler for Instance {
         request: &InfluxdbRequest,
         ctx: QueryContextRef,
     ) -> servers::error::Result<()> {
-        let requests = request.try_into()?;
+        let requests = request.to_insert_requests(ctx.validation_mode())?;
         self.handle_inserts(requests, ctx)
             .await
             .map_err(BoxedError::new)
             .context(servers::error::ExecuteGrpcQuerySnafu)?;
         Ok(())
     }
+
+    async fn dry_run(
+        &self,
+        request: &InfluxdbRequest,
+        ctx: QueryContextRef,
+    ) -> servers::error::Result<Vec<InsertDryRun>> {
+        let requests = request.to_insert_requests(ValidationMode::Lenient)?;
+        self.dry_run_inserts(&requests, &ctx)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::ExecuteGrpcQuerySnafu)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,42 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use common_error::prelude::BoxedError;
+use servers::error::ExecuteStatementSnafu;
+use servers::query_handler::{JobHandler, JobStatus};
+use snafu::ResultExt;
+
+use crate::instance::Instance;
+
+#[async_trait]
+impl JobHandler for Instance {
+    async fn job(&self, id: u64) -> servers::error::Result<Option<JobStatus>> {
+        self.statement_executor
+            .job_registry()
+            .status(id)
+            .await
+            .map_err(BoxedError::new)
+            .context(ExecuteStatementSnafu)
+    }
+
+    async fn cancel_job(&self, id: u64) -> servers::error::Result<Option<JobStatus>> {
+        self.statement_executor
+            .job_registry()
+            .cancel(id)
+            .await
+            .map_err(BoxedError::new)
+            .context(ExecuteStatementSnafu)
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use api::v1::InsertRequest;
+use async_trait::async_trait;
+use common_error::prelude::BoxedError;
+use common_grpc_expr::dry_run::InsertDryRun;
+use servers::query_handler::JsonIngestHandler;
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+use table::TableRef;
+
+use crate::instance::Instance;
+
+#[async_trait]
+impl JsonIngestHandler for Instance {
+    async fn table(
+        &self,
+        table_name: &str,
+        ctx: QueryContextRef,
+    ) -> servers::error::Result<Option<TableRef>> {
+        self.catalog_manager
+            .table(&ctx.current_catalog(), &ctx.current_schema(), table_name)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::ExecuteGrpcQuerySnafu)
+    }
+
+    async fn ingest(
+        &self,
+        request: InsertRequest,
+        ctx: QueryContextRef,
+    ) -> servers::error::Result<()> {
+        self.handle_inserts(vec![request], ctx)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::ExecuteGrpcQuerySnafu)?;
+        Ok(())
+    }
+
+    async fn dry_run(
+        &self,
+        request: InsertRequest,
+        ctx: QueryContextRef,
+    ) -> servers::error::Result<InsertDryRun> {
+        let mut dry_runs = self
+            .dry_run_inserts(&[request], &ctx)
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::ExecuteGrpcQuerySnafu)?;
+        // Safety: there is a dry run for each request.
+        Ok(dry_runs.pop().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use common_query::Output;
+    use common_recordbatch::RecordBatches;
+    use serde_json::json;
+    use servers::json_ingest::{documents_to_insert_request, JsonIngestOptions};
+    use servers::query_handler::sql::SqlQueryHandler;
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::tests;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_standalone_ingest_json() {
+        let standalone = tests::create_standalone_instance("test_standalone_ingest_json").await;
+        let instance = &standalone.instance;
+
+        test_ingest_json(instance).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_distributed_ingest_json() {
+        let instance = tests::create_distributed_instance("test_distributed_ingest_json").await;
+        let instance = &instance.frontend;
+
+        test_ingest_json(instance).await;
+    }
+
+    async fn ingest(instance: &Arc<Instance>, documents: &[serde_json::Value]) -> Vec<usize> {
+        let options = JsonIngestOptions {
+            tags: HashSet::from(["host".to_string()]),
+            ..Default::default()
+        };
+        let table = instance
+            .table("json_logs", QueryContext::arc())
+            .await
+            .unwrap();
+        let table_info = table.as_ref().map(|table| table.table_info());
+        let (request, errors) = documents_to_insert_request(
+            "json_logs",
+            documents,
+            &options,
+            table_info.as_ref().map(|table_info| &table_info.meta),
+            0,
+        )
+        .unwrap();
+        if let Some(request) = request {
+            instance.ingest(request, QueryContext::arc()).await.unwrap();
+        }
+        errors.into_iter().map(|e| e.index).collect()
+    }
+
+    async fn query(instance: &Arc<Instance>, sql: &str) -> String {
+        let output = instance
+            .do_query(sql, QueryContext::arc())
+            .await
+            .remove(0)
+            .unwrap();
+        let Output::Stream(stream) = output else { unreachable!() };
+        let recordbatches = RecordBatches::try_collect(stream).await.unwrap();
+        recordbatches.pretty_print().unwrap()
+    }
+
+    async fn test_ingest_json(instance: &Arc<Instance>) {
+        let errors = ingest(
+            instance,
+            &[
+                json!({"ts": 1000, "host": "host1", "cpu": 0.5}),
+                json!({"ts": "bad time", "host": "host2", "cpu": 0.6}),
+            ],
+        )
+        .await;
+        assert_eq!(vec![1], errors);
+
+        // The new keys add columns to the table.
+        let errors = ingest(
+            instance,
+            &[json!({"ts": 2000, "host": "host2", "cpu": 0.7, "msg": "ok", "labels": ["a"]})],
+        )
+        .await;
+        assert!(errors.is_empty());
+
+        // The documents conflicting with the columns of the table are skipped.
+        let errors = ingest(
+            instance,
+            &[json!({"ts": 3000, "host": "host3", "cpu": "high"})],
+        )
+        .await;
+        assert_eq!(vec![0], errors);
+
+        assert_eq!(
+            query(
+                instance,
+                "SELECT ts, host, cpu, msg, _overflow FROM json_logs ORDER BY ts"
+            )
+            .await,
+            "\
++---------------------+-------+-----+-----+------------------+
+| ts                  | host  | cpu | msg | _overflow        |
++---------------------+-------+-----+-----+------------------+
+| 1970-01-01T00:00:01 | host1 | 0.5 |     |                  |
+| 1970-01-01T00:00:02 | host2 | 0.7 | ok  | {\"labels\":[\"a\"]} |
++---------------------+-------+-----+-----+------------------+"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use servers::query_handler::{RunningQuery, RunningQueryHandler};
+
+use crate::instance::Instance;
+
+impl RunningQueryHandler for Instance {
+    fn running_queries(&self) -> Vec<RunningQuery> {
+        self.running_statements()
+            .into_iter()
+            .map(|snapshot| RunningQuery {
+                id: snapshot.id,
+                description: snapshot.description,
+                origin: snapshot.origin.to_string(),
+                start_time: snapshot.start_time,
+                elapsed_secs: snapshot.elapsed.as_secs(),
+                done: snapshot.done,
+                total: snapshot.total,
+                current_item: snapshot.current_item,
+                eta_secs: snapshot.eta.map(|eta| eta.as_secs()),
+            })
+            .collect()
+    }
+}
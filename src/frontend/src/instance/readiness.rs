@@ -0,0 +1,77 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use servers::query_handler::{ComponentStatus, ReadinessHandler};
+
+use crate::instance::Instance;
+
+#[async_trait]
+impl ReadinessHandler for Instance {
+    async fn component_status(&self) -> ComponentStatus {
+        let catalog_manager_started = self.catalog_manager.is_started();
+        match &self.dist_instance {
+            Some(dist_instance) => ComponentStatus {
+                catalog_manager_started,
+                meta_client_connected: Some(dist_instance.is_meta_connected().await),
+                datanodes: dist_instance.num_datanodes().await,
+            },
+            None => ComponentStatus {
+                catalog_manager_started,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use catalog::CatalogManager;
+
+    use super::*;
+    use crate::tests;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_component_status() {
+        let standalone = tests::create_standalone_instance("test_component_status").await;
+        let status = standalone.instance.component_status().await;
+        assert_eq!(
+            ComponentStatus {
+                catalog_manager_started: true,
+                meta_client_connected: None,
+                datanodes: None,
+            },
+            status
+        );
+        assert!(status.unready_component().is_none());
+
+        let distributed = tests::create_distributed_instance("test_component_status").await;
+        // The datanodes are counted by their leases in metasrv.
+        let status = distributed.frontend.component_status().await;
+        assert_eq!(
+            ComponentStatus {
+                catalog_manager_started: false,
+                meta_client_connected: Some(true),
+                datanodes: Some(4),
+            },
+            status
+        );
+        assert_eq!(Some("catalog_manager"), status.unready_component());
+
+        distributed.catalog_manager.start().await.unwrap();
+        let status = distributed.frontend.component_status().await;
+        assert!(status.catalog_manager_started);
+        assert!(status.unready_component().is_none());
+    }
+}
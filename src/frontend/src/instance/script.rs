@@ -47,4 +47,8 @@ impl ScriptHandler for Instance {
             .execute_script(schema, name, params)
             .await
     }
+
+    async fn script_states(&self) -> servers::error::Result<Output> {
+        self.script_executor.script_states()
+    }
 }
@@ -0,0 +1,358 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Asynchronous jobs of long-running statements, like `COPY TO` with `async = 'true'`.
+//!
+//! The statement returns the id of the job once it's spawned, and the client polls the status
+//! of the job or cancels it by the id. The status of each job is persisted in the jobs table,
+//! see [JobsTable], while it's running and once it finishes, so it's also visible to the other
+//! frontends and after a restart. A job is only cancelled by the frontend running it.
+
+mod table;
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use common_runtime::JoinHandle;
+use common_telemetry::warn;
+use common_time::util::current_time_millis;
+use futures::future::{AbortHandle, Abortable};
+use servers::query_handler::{JobState, JobStatus};
+pub(crate) use table::JobsTable;
+
+use crate::error::Result;
+
+/// Max number of the finished jobs kept for the clients to query, the oldest ones are removed
+/// beyond it.
+const MAX_FINISHED_JOBS: usize = 256;
+/// Interval of persisting the status of a running job.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(1);
+/// Bits of the id of a job below the time it's created.
+const JOB_ID_SEQUENCE_BITS: u32 = 12;
+
+pub type JobRef = Arc<Job>;
+pub type JobRegistryRef = Arc<JobRegistry>;
+
+/// A job running in background, updated by the job itself.
+#[derive(Debug)]
+pub struct Job {
+    id: u64,
+    job_type: &'static str,
+    description: String,
+    created_at_millis: i64,
+    rows: AtomicU64,
+    bytes: AtomicU64,
+    files: AtomicU64,
+    outcome: Mutex<Outcome>,
+    abort_handle: AbortHandle,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[derive(Debug)]
+struct Outcome {
+    state: JobState,
+    file_urls: Vec<String>,
+    error: Option<String>,
+}
+
+impl Job {
+    fn new(
+        id: u64,
+        job_type: &'static str,
+        description: String,
+        created_at_millis: i64,
+        abort_handle: AbortHandle,
+    ) -> Self {
+        Self {
+            id,
+            job_type,
+            description,
+            created_at_millis,
+            rows: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            files: AtomicU64::new(0),
+            outcome: Mutex::new(Outcome {
+                state: JobState::Running,
+                file_urls: Vec::new(),
+                error: None,
+            }),
+            abort_handle,
+            task: Mutex::new(None),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn inc_rows(&self, rows: u64) {
+        let _ = self.rows.fetch_add(rows, Ordering::Relaxed);
+    }
+
+    pub fn inc_bytes(&self, bytes: u64) {
+        let _ = self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn inc_files(&self, files: u64) {
+        let _ = self.files.fetch_add(files, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.outcome.lock().unwrap().state != JobState::Running
+    }
+
+    pub fn status(&self) -> JobStatus {
+        let outcome = self.outcome.lock().unwrap();
+        JobStatus {
+            id: self.id,
+            job_type: self.job_type.to_string(),
+            description: self.description.clone(),
+            state: outcome.state,
+            rows: self.rows.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            files: self.files.load(Ordering::Relaxed),
+            file_urls: outcome.file_urls.clone(),
+            error: outcome.error.clone(),
+        }
+    }
+
+    fn finish(&self, state: JobState, file_urls: Vec<String>, error: Option<String>) {
+        *self.outcome.lock().unwrap() = Outcome {
+            state,
+            file_urls,
+            error,
+        };
+    }
+}
+
+/// The running jobs and the recently finished ones.
+pub struct JobRegistry {
+    next_sequence: AtomicU64,
+    jobs: RwLock<BTreeMap<u64, JobRef>>,
+    /// Table persisting the status of the jobs, the jobs are only kept in memory if it's `None`.
+    table: Option<Arc<JobsTable>>,
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self {
+            // Starts randomly, so the frontends unlikely assign the same ids at the same time.
+            next_sequence: AtomicU64::new(uuid::Uuid::new_v4().as_u64_pair().0),
+            jobs: RwLock::new(BTreeMap::new()),
+            table: None,
+        }
+    }
+}
+
+impl JobRegistry {
+    /// Persists the status of the jobs in `table`.
+    pub(crate) fn with_table(mut self, table: JobsTable) -> Self {
+        self.table = Some(Arc::new(table));
+        self
+    }
+
+    /// Returns a new job id, which is the time in milliseconds followed by a sequence, small
+    /// enough to be a precise number in JSON.
+    fn next_id(&self) -> u64 {
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        ((current_time_millis() as u64) << JOB_ID_SEQUENCE_BITS)
+            | (sequence & ((1 << JOB_ID_SEQUENCE_BITS) - 1))
+    }
+
+    /// Spawns the future `task` builds as a job in background. The task returns the URLs of
+    /// the files it produces. If the job is cancelled, the task is dropped at its next await
+    /// point and `on_cancel` runs to remove what the task has partially written.
+    pub fn spawn<T, F, C>(
+        &self,
+        job_type: &'static str,
+        description: impl Into<String>,
+        task: T,
+        on_cancel: C,
+    ) -> JobRef
+    where
+        T: FnOnce(JobRef) -> F,
+        F: Future<Output = Result<Vec<String>>> + Send + 'static,
+        C: Future<Output = ()> + Send + 'static,
+    {
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let job = Arc::new(Job::new(
+            self.next_id(),
+            job_type,
+            description.into(),
+            current_time_millis(),
+            abort_handle,
+        ));
+        self.insert(job.clone());
+
+        let task = Abortable::new(task(job.clone()), abort_registration);
+        let table = self.table.clone();
+        // Holds the lock of the task while spawning it, so cancelling the job always waits for
+        // the task, even if it's cancelled right after being spawned.
+        let mut task_handle = job.task.lock().unwrap();
+        *task_handle = Some(common_runtime::spawn_bg({
+            let job = job.clone();
+            async move {
+                let result = match &table {
+                    Some(table) => {
+                        table.persist(&job).await;
+                        tokio::pin!(task);
+                        loop {
+                            tokio::select! {
+                                result = &mut task => break result,
+                                _ = tokio::time::sleep(PERSIST_INTERVAL) => {
+                                    table.persist(&job).await
+                                }
+                            }
+                        }
+                    }
+                    None => task.await,
+                };
+                match result {
+                    Ok(Ok(file_urls)) => job.finish(JobState::Completed, file_urls, None),
+                    Ok(Err(e)) => job.finish(JobState::Failed, Vec::new(), Some(e.to_string())),
+                    Err(_) => {
+                        on_cancel.await;
+                        job.finish(JobState::Cancelled, Vec::new(), None);
+                    }
+                }
+                if let Some(table) = &table {
+                    table.persist(&job).await;
+                }
+            }
+        }));
+        drop(task_handle);
+        job
+    }
+
+    pub fn get(&self, id: u64) -> Option<JobRef> {
+        self.jobs.read().unwrap().get(&id).cloned()
+    }
+
+    /// Returns the status of the job, which is read from the jobs table if the job is not in
+    /// this registry, e.g. it's run by another frontend or before a restart.
+    pub async fn status(&self, id: u64) -> Result<Option<JobStatus>> {
+        if let Some(job) = self.get(id) {
+            return Ok(Some(job.status()));
+        }
+        match &self.table {
+            Some(table) => table.read(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Cancels the job if it's running and waits until it stops, returns the status of the job
+    /// afterwards. A finished job, or a job not run by this registry, is left as it is.
+    pub async fn cancel(&self, id: u64) -> Result<Option<JobStatus>> {
+        let Some(job) = self.get(id) else {
+            return self.status(id).await;
+        };
+        job.abort_handle.abort();
+        let task = job.task.lock().unwrap().take();
+        if let Some(task) = task {
+            if let Err(e) = task.await {
+                warn!("Failed to wait for the cancelled job {id}, error: {e}");
+            }
+        }
+        Ok(Some(job.status()))
+    }
+
+    fn insert(&self, job: JobRef) {
+        let mut jobs = self.jobs.write().unwrap();
+        let finished = jobs
+            .values()
+            .filter(|job| job.is_finished())
+            .map(|job| job.id)
+            .collect::<Vec<_>>();
+        let excess = finished.len().saturating_sub(MAX_FINISHED_JOBS);
+        for id in &finished[..excess] {
+            let _ = jobs.remove(id);
+        }
+        let _ = jobs.insert(job.id, job);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::error::NotSupportedSnafu;
+
+    pub(crate) async fn wait_finished(job: &Job) -> JobStatus {
+        while !job.is_finished() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        job.status()
+    }
+
+    #[tokio::test]
+    async fn test_job_states() {
+        let registry = Arc::new(JobRegistry::default());
+
+        let job = registry.spawn(
+            "test",
+            "completed",
+            |job| async move {
+                job.inc_rows(10);
+                job.inc_files(1);
+                Ok(vec!["/tmp/a.parquet".to_string()])
+            },
+            async {},
+        );
+        let status = wait_finished(&job).await;
+        assert_eq!(JobState::Completed, status.state);
+        assert_eq!(10, status.rows);
+        assert_eq!(vec!["/tmp/a.parquet".to_string()], status.file_urls);
+
+        let job = registry.spawn(
+            "test",
+            "failed",
+            |_| async { NotSupportedSnafu { feat: "test" }.fail() },
+            async {},
+        );
+        let status = wait_finished(&job).await;
+        assert_eq!(JobState::Failed, status.state);
+        assert!(status.error.is_some());
+        assert!(status.file_urls.is_empty());
+        // Cancelling a finished job keeps its state.
+        let status = registry.cancel(job.id()).await.unwrap().unwrap();
+        assert_eq!(JobState::Failed, status.state);
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let job = registry.spawn(
+            "test",
+            "cancelled",
+            |_| async {
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+                Ok(Vec::new())
+            },
+            async move {
+                tx.send(()).unwrap();
+            },
+        );
+        assert_eq!(
+            JobState::Running,
+            registry.get(job.id()).unwrap().status().state
+        );
+        let status = registry.cancel(job.id()).await.unwrap().unwrap();
+        assert_eq!(JobState::Cancelled, status.state);
+        rx.await.unwrap();
+
+        assert!(registry.get(job.id() + 1).is_none());
+        assert!(registry.cancel(job.id() + 1).await.unwrap().is_none());
+    }
+}
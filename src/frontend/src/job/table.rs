@@ -0,0 +1,290 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The jobs table `greptime.greptime_private.jobs`, keeping the status of the jobs of all the
+//! frontends, one row per job keyed by its id.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use catalog::CatalogManagerRef;
+use common_catalog::consts::DEFAULT_CATALOG_NAME;
+use common_catalog::format_full_table_name;
+use common_query::Output;
+use common_recordbatch::RecordBatches;
+use common_telemetry::warn;
+use datatypes::prelude::VectorRef;
+use datatypes::vectors::{StringVector, TimestampMillisecondVector, UInt64Vector};
+use query::parser::{QueryLanguageParser, QueryStatement};
+use query::query_engine::SqlStatementExecutorRef;
+use query::QueryEngineRef;
+use servers::query_handler::JobStatus;
+use session::context::{QueryContext, QueryContextRef, QueryOrigin};
+use snafu::{OptionExt, ResultExt};
+use table::requests::InsertRequest;
+use table::TableRef;
+use tokio::sync::OnceCell;
+
+use crate::error::{
+    CatalogSnafu, CollectRecordBatchesSnafu, DecodeJsonSnafu, EncodeJsonSnafu,
+    ExecLogicalPlanSnafu, ExecuteStatementSnafu, InsertSnafu, ParseQuerySnafu, PlanStatementSnafu,
+    Result, TableNotFoundSnafu,
+};
+use crate::job::Job;
+
+const JOBS_SCHEMA_NAME: &str = "greptime_private";
+const JOBS_TABLE_NAME: &str = "jobs";
+
+/// Statements creating the jobs table, the status of a job is kept as JSON.
+const CREATE_JOBS_TABLE_SQLS: [&str; 2] = [
+    "CREATE DATABASE IF NOT EXISTS greptime_private",
+    "CREATE TABLE IF NOT EXISTS greptime_private.jobs (
+        id BIGINT UNSIGNED,
+        job_type STRING,
+        state STRING,
+        status STRING,
+        created_at TIMESTAMP(3) TIME INDEX,
+        PRIMARY KEY (id)
+    )",
+];
+
+pub(crate) struct JobsTable {
+    catalog_manager: CatalogManagerRef,
+    query_engine: QueryEngineRef,
+    sql_stmt_executor: SqlStatementExecutorRef,
+    /// Set once the table is created, which is on the first write.
+    created: OnceCell<()>,
+}
+
+impl JobsTable {
+    pub(crate) fn new(
+        catalog_manager: CatalogManagerRef,
+        query_engine: QueryEngineRef,
+        sql_stmt_executor: SqlStatementExecutorRef,
+    ) -> Self {
+        Self {
+            catalog_manager,
+            query_engine,
+            sql_stmt_executor,
+            created: OnceCell::new(),
+        }
+    }
+
+    /// Writes the current status of the job over the one written before. A failure is only
+    /// logged, the job goes on without being persisted.
+    pub(crate) async fn persist(&self, job: &Job) {
+        if let Err(e) = self.write(job).await {
+            warn!(
+                "Failed to persist the status of job {}, error: {e}",
+                job.id()
+            );
+        }
+    }
+
+    async fn write(&self, job: &Job) -> Result<()> {
+        let table = self.table().await?;
+        let status = job.status();
+        let state = serde_json::to_value(status.state).context(EncodeJsonSnafu)?;
+        let json = serde_json::to_string(&status).context(EncodeJsonSnafu)?;
+        let columns_values: HashMap<String, VectorRef> = HashMap::from([
+            (
+                "id".to_string(),
+                Arc::new(UInt64Vector::from_slice([status.id])) as _,
+            ),
+            (
+                "job_type".to_string(),
+                Arc::new(StringVector::from(vec![status.job_type.as_str()])) as _,
+            ),
+            (
+                "state".to_string(),
+                Arc::new(StringVector::from(vec![state.as_str().unwrap_or_default()])) as _,
+            ),
+            (
+                "status".to_string(),
+                Arc::new(StringVector::from(vec![json])) as _,
+            ),
+            (
+                "created_at".to_string(),
+                Arc::new(TimestampMillisecondVector::from_slice([
+                    job.created_at_millis
+                ])) as _,
+            ),
+        ]);
+
+        let _ = table
+            .insert(InsertRequest {
+                catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                schema_name: JOBS_SCHEMA_NAME.to_string(),
+                table_name: JOBS_TABLE_NAME.to_string(),
+                columns_values,
+                region_number: 0,
+                write_mode: Default::default(),
+            })
+            .await
+            .context(InsertSnafu {
+                table_name: full_table_name(),
+            })?;
+        Ok(())
+    }
+
+    /// Reads the status of the job `id`, `None` if there is no such job.
+    pub(crate) async fn read(&self, id: u64) -> Result<Option<JobStatus>> {
+        if self.find_table().await?.is_none() {
+            return Ok(None);
+        }
+
+        let sql = format!("SELECT status FROM {} WHERE id = {id}", full_table_name());
+        let stmt = QueryLanguageParser::parse_sql(&sql).context(ParseQuerySnafu)?;
+        let query_ctx = system_query_ctx();
+        let plan = self
+            .query_engine
+            .planner()
+            .plan(stmt, query_ctx.clone())
+            .await
+            .context(PlanStatementSnafu)?;
+        let batches = match self
+            .query_engine
+            .execute(plan, query_ctx)
+            .await
+            .context(ExecLogicalPlanSnafu)?
+        {
+            Output::Stream(stream) => RecordBatches::try_collect(stream)
+                .await
+                .context(CollectRecordBatchesSnafu)?,
+            Output::RecordBatches(batches) => batches,
+            Output::AffectedRows(_) => return Ok(None),
+        };
+
+        for batch in batches.iter() {
+            let Some(statuses) = batch.column(0).as_any().downcast_ref::<StringVector>() else {
+                continue;
+            };
+            if let Some(status) = statuses.get_data(0) {
+                return serde_json::from_str(status)
+                    .context(DecodeJsonSnafu)
+                    .map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the table, creating it if it doesn't exist.
+    async fn table(&self) -> Result<TableRef> {
+        let _ = self.created.get_or_try_init(|| self.create_table()).await?;
+        self.find_table()
+            .await?
+            .with_context(|| TableNotFoundSnafu {
+                table_name: full_table_name(),
+            })
+    }
+
+    async fn find_table(&self) -> Result<Option<TableRef>> {
+        self.catalog_manager
+            .table(DEFAULT_CATALOG_NAME, JOBS_SCHEMA_NAME, JOBS_TABLE_NAME)
+            .await
+            .context(CatalogSnafu)
+    }
+
+    async fn create_table(&self) -> Result<()> {
+        for sql in CREATE_JOBS_TABLE_SQLS {
+            let stmt = QueryLanguageParser::parse_sql(sql).context(ParseQuerySnafu)?;
+            let QueryStatement::Sql(stmt) = stmt else {
+                unreachable!("the statements creating the jobs table are SQL")
+            };
+            let _ = self
+                .sql_stmt_executor
+                .execute_sql(stmt, system_query_ctx())
+                .await
+                .context(ExecuteStatementSnafu)?;
+        }
+        Ok(())
+    }
+}
+
+fn full_table_name() -> String {
+    format_full_table_name(DEFAULT_CATALOG_NAME, JOBS_SCHEMA_NAME, JOBS_TABLE_NAME)
+}
+
+fn system_query_ctx() -> QueryContextRef {
+    Arc::new(QueryContext::new().with_origin(QueryOrigin::System))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use servers::query_handler::JobState;
+
+    use super::*;
+    use crate::job::JobRegistry;
+    use crate::tests;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_persist_jobs() {
+        let standalone = tests::create_standalone_instance("test_persist_jobs").await;
+        let executor = standalone.instance.statement_executor();
+        let new_table = || executor.new_jobs_table();
+        assert!(new_table().read(0).await.unwrap().is_none());
+
+        let registry = JobRegistry::default().with_table(new_table());
+        let job = registry.spawn(
+            "test",
+            "persisted",
+            |job| async move {
+                job.inc_rows(10);
+                // Lasts long enough for the progress to be persisted.
+                tokio::time::sleep(Duration::from_millis(1500)).await;
+                job.inc_rows(10);
+                Ok(vec!["/tmp/a.parquet".to_string()])
+            },
+            async {},
+        );
+
+        // The progress is visible to another frontend while the job is running.
+        let table = new_table();
+        let status = loop {
+            if let Some(status) = table.read(job.id()).await.unwrap() {
+                break status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert_eq!(JobState::Running, status.state);
+        assert_eq!("test", status.job_type);
+        let status = loop {
+            let status = table.read(job.id()).await.unwrap().unwrap();
+            if status.rows == 10 {
+                break status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert_eq!(JobState::Running, status.state);
+
+        // The status of the finished job is kept after a restart.
+        let expected = crate::job::tests::wait_finished(&job).await;
+        let registry = JobRegistry::default().with_table(new_table());
+        let status = loop {
+            let status = registry.status(job.id()).await.unwrap().unwrap();
+            if status.state != JobState::Running {
+                break status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert_eq!(expected, status);
+        assert_eq!(JobState::Completed, status.state);
+        assert_eq!(20, status.rows);
+        assert_eq!(vec!["/tmp/a.parquet".to_string()], status.file_urls);
+        // Only the frontend running the job cancels it.
+        assert_eq!(Some(status), registry.cancel(job.id()).await.unwrap());
+    }
+}
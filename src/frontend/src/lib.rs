@@ -23,13 +23,15 @@ pub mod frontend;
 pub mod grpc;
 pub mod influxdb;
 pub mod instance;
+pub mod job;
 pub(crate) mod metrics;
 pub mod mysql;
 pub mod opentsdb;
 pub mod postgres;
+pub mod progress;
 pub mod prom;
 pub mod prometheus;
-mod script;
+pub mod script;
 mod server;
 pub(crate) mod statement;
 mod table;
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 pub(crate) const METRIC_HANDLE_SQL_ELAPSED: &str = "frontend.handle_sql_elapsed";
+/// Label of the origin of a query, see [session::context::QueryOrigin].
+pub(crate) const LABEL_ORIGIN: &str = "origin";
 pub(crate) const METRIC_HANDLE_SCRIPTS_ELAPSED: &str = "frontend.handle_scripts_elapsed";
 pub(crate) const METRIC_RUN_SCRIPT_ELAPSED: &str = "frontend.run_script_elapsed";
 
@@ -21,3 +23,6 @@ pub(crate) const METRIC_RUN_SCRIPT_ELAPSED: &str = "frontend.run_script_elapsed"
 pub const DIST_CREATE_TABLE: &str = "frontend.dist.create_table";
 pub const DIST_CREATE_TABLE_IN_META: &str = "frontend.dist.create_table.update_meta";
 pub const DIST_CREATE_TABLE_IN_DATANODE: &str = "frontend.dist.create_table.invoke_datanode";
+/// Counter of the regions the frontend splits the rows of inserts to that are not in the route
+/// of the table.
+pub const DIST_INSERT_REGION_MISMATCH: &str = "frontend.dist.insert.region_mismatch";
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::connection::ConnectionOptions;
 use servers::tls::TlsOption;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -22,6 +23,17 @@ pub struct MysqlOptions {
     #[serde(default = "Default::default")]
     pub tls: TlsOption,
     pub reject_no_database: Option<bool>,
+    /// Max number of rows returned by the `SELECT`s without a `LIMIT` of new sessions,
+    /// which can be changed by `SET SQL_SELECT_LIMIT`. Unlimited if not set.
+    #[serde(default)]
+    pub sql_select_limit: Option<usize>,
+    /// Significant digits of the floats written in the text protocol. Floats are written in
+    /// the shortest form parsing back to the same value if not set.
+    #[serde(default)]
+    pub float_precision: Option<u8>,
+    /// Keepalive, idle timeout and max age of the connections.
+    #[serde(flatten)]
+    pub connection: ConnectionOptions,
 }
 
 impl Default for MysqlOptions {
@@ -31,6 +43,9 @@ impl Default for MysqlOptions {
             runtime_size: 2,
             tls: TlsOption::default(),
             reject_no_database: None,
+            sql_select_limit: None,
+            float_precision: None,
+            connection: ConnectionOptions::default(),
         }
     }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::connection::ConnectionOptions;
 use servers::tls::TlsOption;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,6 +22,9 @@ pub struct PostgresOptions {
     pub runtime_size: usize,
     #[serde(default = "Default::default")]
     pub tls: TlsOption,
+    /// Keepalive, idle timeout and max age of the connections.
+    #[serde(flatten)]
+    pub connection: ConnectionOptions,
 }
 
 impl Default for PostgresOptions {
@@ -29,6 +33,7 @@ impl Default for PostgresOptions {
             addr: "127.0.0.1:4003".to_string(),
             runtime_size: 2,
             tls: Default::default(),
+            connection: ConnectionOptions::default(),
         }
     }
 }
@@ -0,0 +1,264 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Progress of long-running statements, like `COPY`.
+//!
+//! Only statements that may run for a long time register their progress, so fast queries
+//! don't pay for it.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use common_time::util::current_time_millis;
+use session::context::QueryOrigin;
+
+pub type ProgressRef = Arc<Progress>;
+pub type ProgressRegistryRef = Arc<ProgressRegistry>;
+
+/// Progress of a running statement, updated by the statement itself.
+#[derive(Debug)]
+pub struct Progress {
+    id: u64,
+    description: String,
+    origin: QueryOrigin,
+    /// Start time in milliseconds since the epoch.
+    start_time: i64,
+    started: Instant,
+    /// Number of units done, e.g. the files imported.
+    done: AtomicU64,
+    /// Total number of units, 0 if unknown.
+    total: AtomicU64,
+    /// The item being processed, e.g. the file being read.
+    current_item: Mutex<Option<String>>,
+}
+
+impl Progress {
+    fn new(id: u64, description: String, origin: QueryOrigin) -> Self {
+        Self {
+            id,
+            description,
+            origin,
+            start_time: current_time_millis(),
+            started: Instant::now(),
+            done: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            current_item: Mutex::new(None),
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn inc_done(&self, units: u64) {
+        let _ = self.done.fetch_add(units, Ordering::Relaxed);
+    }
+
+    pub fn set_current_item(&self, item: impl Into<String>) {
+        *self.current_item.lock().unwrap() = Some(item.into());
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let done = self.done.load(Ordering::Relaxed);
+        let total = self.total.load(Ordering::Relaxed);
+        let elapsed = self.started.elapsed();
+        // Estimates the remaining time by the average time of the units done.
+        let eta = (done > 0 && total > done).then(|| {
+            Duration::from_secs_f64(elapsed.as_secs_f64() * (total - done) as f64 / done as f64)
+        });
+
+        ProgressSnapshot {
+            id: self.id,
+            description: self.description.clone(),
+            origin: self.origin.clone(),
+            start_time: self.start_time,
+            elapsed,
+            done,
+            total: (total > 0).then_some(total),
+            current_item: self.current_item.lock().unwrap().clone(),
+            eta,
+        }
+    }
+}
+
+/// A point-in-time copy of a [Progress].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressSnapshot {
+    pub id: u64,
+    pub description: String,
+    /// Who issues the statement, e.g. a background job.
+    pub origin: QueryOrigin,
+    /// Start time in milliseconds since the epoch.
+    pub start_time: i64,
+    pub elapsed: Duration,
+    pub done: u64,
+    /// Total number of units, `None` if unknown.
+    pub total: Option<u64>,
+    pub current_item: Option<String>,
+    /// Estimated remaining time, `None` if nothing is done yet or the total is unknown.
+    pub eta: Option<Duration>,
+}
+
+impl ProgressSnapshot {
+    /// Renders the units done, out of the total if it's known, and the estimated remaining
+    /// time, e.g. `2/3 done, ETA 10s`. `Running` if nothing is done yet.
+    pub fn state(&self) -> String {
+        let mut state = match self.total {
+            Some(total) => format!("{}/{total} done", self.done),
+            None if self.done > 0 => format!("{} done", self.done),
+            None => "Running".to_string(),
+        };
+        if let Some(eta) = self.eta {
+            state.push_str(&format!(", ETA {}s", eta.as_secs()));
+        }
+        state
+    }
+}
+
+/// Progress of the running statements.
+#[derive(Debug, Default)]
+pub struct ProgressRegistry {
+    next_id: AtomicU64,
+    entries: RwLock<BTreeMap<u64, ProgressRef>>,
+}
+
+impl ProgressRegistry {
+    /// Registers the progress of a statement. The entry is removed when the returned guard
+    /// is dropped, i.e. when the statement completes or fails.
+    pub fn register(
+        self: &Arc<Self>,
+        description: impl Into<String>,
+        origin: QueryOrigin,
+    ) -> ProgressGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let progress = Arc::new(Progress::new(id, description.into(), origin));
+        let _ = self.entries.write().unwrap().insert(id, progress.clone());
+
+        ProgressGuard {
+            registry: self.clone(),
+            progress,
+        }
+    }
+
+    /// Returns the progress of the running statements, in the order they are registered.
+    pub fn snapshots(&self) -> Vec<ProgressSnapshot> {
+        self.entries
+            .read()
+            .unwrap()
+            .values()
+            .map(|progress| progress.snapshot())
+            .collect()
+    }
+
+    fn remove(&self, id: u64) {
+        let _ = self.entries.write().unwrap().remove(&id);
+    }
+}
+
+/// Removes the progress from the registry on drop.
+#[derive(Debug)]
+pub struct ProgressGuard {
+    registry: ProgressRegistryRef,
+    progress: ProgressRef,
+}
+
+impl ProgressGuard {
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+}
+
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        self.registry.remove(self.progress.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_between_files() {
+        let registry = Arc::new(ProgressRegistry::default());
+        assert!(registry.snapshots().is_empty());
+
+        let files = ["a.parquet", "b.parquet", "c.parquet"];
+        let guard = registry.register("COPY demo FROM '/tmp/'", QueryOrigin::User);
+        guard.progress().set_total(files.len() as u64);
+        for (i, file) in files.iter().enumerate() {
+            guard.progress().set_current_item(*file);
+
+            let snapshots = registry.snapshots();
+            assert_eq!(1, snapshots.len());
+            let snapshot = &snapshots[0];
+            assert_eq!("COPY demo FROM '/tmp/'", snapshot.description);
+            assert_eq!(i as u64, snapshot.done);
+            assert_eq!(Some(3), snapshot.total);
+            assert_eq!(Some(*file), snapshot.current_item.as_deref());
+            assert_eq!(i == 0, snapshot.eta.is_none());
+            if i == 0 {
+                assert_eq!("0/3 done", snapshot.state());
+            } else {
+                assert!(snapshot.state().starts_with(&format!("{i}/3 done, ETA ")));
+            }
+
+            guard.progress().inc_done(1);
+        }
+        let snapshot = &registry.snapshots()[0];
+        assert_eq!(3, snapshot.done);
+        assert!(snapshot.eta.is_none());
+        assert_eq!("3/3 done", snapshot.state());
+
+        // Completion clears the entry.
+        drop(guard);
+        assert!(registry.snapshots().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_total() {
+        let registry = Arc::new(ProgressRegistry::default());
+        let first = registry.register("first", QueryOrigin::User);
+        let second = registry.register("second", QueryOrigin::Background("job".to_string()));
+        second.progress().inc_done(10);
+
+        let snapshots = registry.snapshots();
+        assert_eq!(
+            vec!["first", "second"],
+            snapshots
+                .iter()
+                .map(|s| s.description.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(None, snapshots[1].total);
+        assert_eq!(None, snapshots[1].eta);
+        assert_eq!("Running", snapshots[0].state());
+        assert_eq!("10 done", snapshots[1].state());
+        assert_eq!(
+            QueryOrigin::Background("job".to_string()),
+            snapshots[1].origin
+        );
+
+        drop(first);
+        let snapshots = registry.snapshots();
+        assert_eq!(1, snapshots.len());
+        assert_eq!("second", snapshots[0].description);
+    }
+}
@@ -12,17 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use query::parser::DEFAULT_MAX_POINTS;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PromOptions {
     pub addr: String,
+    /// Max number of points per timeseries of a range query, also applied to the PromQL
+    /// queries of the other protocols and TQL.
+    pub max_points: usize,
 }
 
 impl Default for PromOptions {
     fn default() -> Self {
         Self {
             addr: "127.0.0.1:4004".to_string(),
+            max_points: DEFAULT_MAX_POINTS,
         }
     }
 }
@@ -35,5 +41,6 @@ mod tests {
     fn test_prometheus_options() {
         let default = PromOptions::default();
         assert_eq!(default.addr, "127.0.0.1:4004".to_string());
+        assert_eq!(default.max_points, 11_000);
     }
 }
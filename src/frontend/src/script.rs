@@ -17,9 +17,29 @@ use std::collections::HashMap;
 use catalog::CatalogManagerRef;
 use common_query::Output;
 use query::QueryEngineRef;
+use serde::{Deserialize, Serialize};
 
 use crate::error::Result;
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptOptions {
+    /// Recompiles the stored scripts before the start completes and fails the start on any
+    /// compile error, instead of recompiling them in background.
+    pub strict_recompile: bool,
+    /// Max number of scripts recompiled concurrently in background.
+    pub recompile_parallelism: usize,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> Self {
+        Self {
+            strict_recompile: false,
+            recompile_parallelism: 4,
+        }
+    }
+}
+
 #[cfg(not(feature = "python"))]
 mod dummy {
     use super::*;
@@ -30,6 +50,7 @@ mod dummy {
         pub async fn new(
             _catalog_manager: CatalogManagerRef,
             _query_engine: QueryEngineRef,
+            _opts: &ScriptOptions,
         ) -> Result<Self> {
             Ok(Self {})
         }
@@ -51,14 +72,24 @@ mod dummy {
         ) -> servers::error::Result<Output> {
             servers::error::NotSupportedSnafu { feat: "script" }.fail()
         }
+
+        pub fn script_states(&self) -> servers::error::Result<Output> {
+            servers::error::NotSupportedSnafu { feat: "script" }.fail()
+        }
     }
 }
 
 #[cfg(feature = "python")]
 mod python {
+    use std::sync::Arc;
+
     use common_error::prelude::BoxedError;
+    use common_recordbatch::RecordBatches;
     use common_telemetry::logging::error;
-    use script::manager::ScriptManager;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::StringVector;
+    use script::manager::{RecompileOptions, ScriptManager, ScriptState};
     use snafu::ResultExt;
 
     use super::*;
@@ -71,11 +102,20 @@ mod python {
         pub async fn new(
             catalog_manager: CatalogManagerRef,
             query_engine: QueryEngineRef,
+            opts: &ScriptOptions,
         ) -> Result<Self> {
+            let recompile_options = RecompileOptions {
+                strict: opts.strict_recompile,
+                parallelism: opts.recompile_parallelism,
+            };
             Ok(Self {
-                script_manager: ScriptManager::new(catalog_manager, query_engine)
-                    .await
-                    .context(crate::error::StartScriptManagerSnafu)?,
+                script_manager: ScriptManager::new(
+                    catalog_manager,
+                    query_engine,
+                    recompile_options,
+                )
+                .await
+                .context(crate::error::StartScriptManagerSnafu)?,
             })
         }
 
@@ -113,6 +153,45 @@ mod python {
                 })
                 .context(servers::error::ExecuteScriptSnafu { name })
         }
+
+        /// Returns the compile states of the scripts, with the errors of the ones failed to
+        /// compile.
+        pub fn script_states(&self) -> servers::error::Result<Output> {
+            let states = self.script_manager.script_states();
+            let names = states
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>();
+            let errors = states
+                .iter()
+                .map(|(_, state)| match state {
+                    ScriptState::Failed(e) => Some(e.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let states = states
+                .iter()
+                .map(|(_, state)| match state {
+                    ScriptState::Pending => "pending",
+                    ScriptState::Compiled => "compiled",
+                    ScriptState::Failed(_) => "failed",
+                })
+                .collect::<Vec<_>>();
+
+            let schema = Arc::new(Schema::new(vec![
+                ColumnSchema::new("name", ConcreteDataType::string_datatype(), false),
+                ColumnSchema::new("state", ConcreteDataType::string_datatype(), false),
+                ColumnSchema::new("error", ConcreteDataType::string_datatype(), true),
+            ]));
+            let columns = vec![
+                Arc::new(StringVector::from(names)) as _,
+                Arc::new(StringVector::from(states)) as _,
+                Arc::new(StringVector::from(errors)) as _,
+            ];
+            let records = RecordBatches::try_from_columns(schema, columns)
+                .context(servers::error::CollectRecordbatchSnafu)?;
+            Ok(Output::RecordBatches(records))
+        }
     }
 }
 
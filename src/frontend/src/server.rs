@@ -19,6 +19,7 @@ use std::sync::Arc;
 use common_base::Plugins;
 use common_runtime::Builder as RuntimeBuilder;
 use common_telemetry::info;
+use datatypes::float_format::FloatPrecision;
 use servers::auth::UserProviderRef;
 use servers::error::Error::InternalIo;
 use servers::grpc::GrpcServer;
@@ -31,6 +32,7 @@ use servers::prom::PromServer;
 use servers::query_handler::grpc::ServerGrpcQueryHandlerAdaptor;
 use servers::query_handler::sql::ServerSqlQueryHandlerAdaptor;
 use servers::server::Server;
+use session::registry::SessionRegistry;
 use snafu::ResultExt;
 
 use crate::error::Error::StartServer;
@@ -57,6 +59,7 @@ impl Services {
     {
         let mut result = Vec::<ServerHandler>::with_capacity(plugins.len());
         let user_provider = plugins.get::<UserProviderRef>().cloned();
+        let sessions = Arc::new(SessionRegistry::default());
 
         if let Some(opts) = &opts.grpc_options {
             let grpc_addr = parse_addr(&opts.addr)?;
@@ -74,7 +77,8 @@ impl Services {
                 Some(instance.clone()),
                 user_provider.clone(),
                 grpc_runtime,
-            );
+            )
+            .with_ddl_batch_handler(instance.clone());
 
             result.push((Box::new(grpc_server), grpc_addr));
         };
@@ -94,6 +98,7 @@ impl Services {
                 Arc::new(MysqlSpawnRef::new(
                     ServerSqlQueryHandlerAdaptor::arc(instance.clone()),
                     user_provider.clone(),
+                    sessions.clone(),
                 )),
                 Arc::new(MysqlSpawnConfig::new(
                     opts.tls.should_force_tls(),
@@ -103,7 +108,11 @@ impl Services {
                             source: InternalIo { source: e },
                         })?
                         .map(Arc::new),
+                    opts.tls.client_cert_mapping(),
                     opts.reject_no_database.unwrap_or(false),
+                    opts.sql_select_limit,
+                    float_precision(opts.float_precision)?,
+                    opts.connection.clone(),
                 )),
             );
             result.push((mysql_server, mysql_addr));
@@ -125,6 +134,8 @@ impl Services {
                 opts.tls.clone(),
                 pg_io_runtime,
                 user_provider.clone(),
+                opts.connection.clone(),
+                sessions.clone(),
             )) as Box<dyn Server>;
 
             result.push((pg_server, pg_addr));
@@ -179,6 +190,12 @@ impl Services {
             }
             http_server_builder.with_metrics_handler(MetricsHandler);
             http_server_builder.with_script_handler(instance.clone());
+            http_server_builder.with_json_ingest_handler(instance.clone());
+            http_server_builder.with_ddl_batch_handler(instance.clone());
+            http_server_builder.with_job_handler(instance.clone());
+            http_server_builder.with_running_query_handler(instance.clone());
+            http_server_builder.with_readiness_handler(instance.clone());
+            http_server_builder.with_session_registry(sessions.clone());
             let http_server = http_server_builder.build();
             result.push((Box::new(http_server), http_addr));
         }
@@ -205,6 +222,14 @@ fn parse_addr(addr: &str) -> Result<SocketAddr> {
     addr.parse().context(error::ParseAddrSnafu { addr })
 }
 
+fn float_precision(digits: Option<u8>) -> Result<FloatPrecision> {
+    match digits {
+        Some(digits) => FloatPrecision::significant(digits)
+            .context(error::InvalidFloatPrecisionSnafu { digits }),
+        None => Ok(FloatPrecision::Roundtrip),
+    }
+}
+
 pub async fn start_server(
     server_and_addr: &(Box<dyn Server>, SocketAddr),
 ) -> servers::error::Result<Option<SocketAddr>> {
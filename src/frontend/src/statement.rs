@@ -12,20 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod analyze;
+mod authorize;
+mod copy_query_to;
 mod copy_table_from;
 mod copy_table_to;
 mod describe;
+mod explain;
+mod select_limit;
 mod show;
 mod tql;
 
+use std::sync::Arc;
+
+use catalog::ddl_lock::DdlLocksRef;
 use catalog::CatalogManagerRef;
-use common_error::prelude::BoxedError;
+use common_error::prelude::{BoxedError, ErrorExt, StatusCode};
 use common_query::Output;
 use common_recordbatch::RecordBatches;
+use datafusion_expr::LogicalPlan as DfLogicalPlan;
 use datanode::instance::sql::table_idents_to_full_name;
-use query::parser::QueryStatement;
+use meta_client::rpc::TableName;
+use query::parser::{PromQuery, QueryLanguageParser, QueryStatement, DEFAULT_MAX_POINTS};
+use query::plan::LogicalPlan;
 use query::query_engine::SqlStatementExecutorRef;
 use query::QueryEngineRef;
+use servers::auth::authorizer::StatementAuthorizerRef;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
 use sql::statements::copy::{CopyTable, CopyTableArgument};
@@ -34,16 +46,41 @@ use table::engine::TableReference;
 use table::requests::{CopyDirection, CopyTableRequest};
 use table::TableRef;
 
+use crate::catalog::FrontendCatalogManager;
 use crate::error::{
-    CatalogSnafu, ExecLogicalPlanSnafu, ExecuteStatementSnafu, ExternalSnafu, PlanStatementSnafu,
-    Result, SchemaNotFoundSnafu, TableNotFoundSnafu,
+    CatalogSnafu, ExecLogicalPlanSnafu, ExecuteStatementSnafu, ExternalSnafu, Result,
+    SchemaNotFoundSnafu, SchemaPinnedSnafu, TableNotFoundSnafu,
 };
+use crate::job::{JobRegistry, JobRegistryRef, JobsTable};
+use crate::progress::{ProgressRegistry, ProgressRegistryRef};
+use crate::statement::authorize::{is_planned, query_statement_action};
+use crate::statement::select_limit::is_select_limit_applicable;
+
+/// Returns the table written by the `plan`, if it's an `INSERT` or a `DELETE`.
+fn written_table(plan: &LogicalPlan, query_ctx: &QueryContextRef) -> Option<TableName> {
+    let LogicalPlan::DfPlan(DfLogicalPlan::Dml(dml)) = plan else { return None };
+    let table_name = dml
+        .table_name
+        .clone()
+        .resolve(&query_ctx.current_catalog(), &query_ctx.current_schema());
+    Some(TableName::new(
+        table_name.catalog,
+        table_name.schema,
+        table_name.table,
+    ))
+}
 
 #[derive(Clone)]
 pub(crate) struct StatementExecutor {
     catalog_manager: CatalogManagerRef,
     query_engine: QueryEngineRef,
     sql_stmt_executor: SqlStatementExecutorRef,
+    progress_registry: ProgressRegistryRef,
+    job_registry: JobRegistryRef,
+    ddl_locks: DdlLocksRef,
+    authorizer: Option<StatementAuthorizerRef>,
+    /// Max number of points per timeseries of the PromQL range queries.
+    promql_max_points: usize,
 }
 
 impl StatementExecutor {
@@ -51,14 +88,64 @@ impl StatementExecutor {
         catalog_manager: CatalogManagerRef,
         query_engine: QueryEngineRef,
         sql_stmt_executor: SqlStatementExecutorRef,
+        ddl_locks: DdlLocksRef,
     ) -> Self {
+        let jobs_table = JobsTable::new(
+            catalog_manager.clone(),
+            query_engine.clone(),
+            sql_stmt_executor.clone(),
+        );
         Self {
             catalog_manager,
             query_engine,
             sql_stmt_executor,
+            progress_registry: Arc::new(ProgressRegistry::default()),
+            job_registry: Arc::new(JobRegistry::default().with_table(jobs_table)),
+            ddl_locks,
+            authorizer: None,
+            promql_max_points: DEFAULT_MAX_POINTS,
         }
     }
 
+    /// Sets the authorizer asked whether the statements are allowed, see [authorize].
+    pub(crate) fn set_authorizer(&mut self, authorizer: Option<StatementAuthorizerRef>) {
+        self.authorizer = authorizer;
+    }
+
+    pub(crate) fn set_promql_max_points(&mut self, max_points: usize) {
+        self.promql_max_points = max_points;
+    }
+
+    /// Parses the PromQL query, see [QueryLanguageParser::parse_promql].
+    pub(crate) fn parse_promql(&self, query: &PromQuery) -> query::error::Result<QueryStatement> {
+        QueryLanguageParser::parse_promql(query, self.promql_max_points)
+    }
+
+    /// Returns the progress of the long-running statements.
+    pub(crate) fn progress_registry(&self) -> &ProgressRegistryRef {
+        &self.progress_registry
+    }
+
+    /// Returns the asynchronous jobs of the statements, like `COPY TO` with `async = 'true'`.
+    pub(crate) fn job_registry(&self) -> &JobRegistryRef {
+        &self.job_registry
+    }
+
+    /// Returns a jobs table of its own, like the one of another frontend.
+    #[cfg(test)]
+    pub(crate) fn new_jobs_table(&self) -> JobsTable {
+        JobsTable::new(
+            self.catalog_manager.clone(),
+            self.query_engine.clone(),
+            self.sql_stmt_executor.clone(),
+        )
+    }
+
+    /// Returns the locks held by the DDL on existing tables.
+    pub(crate) fn ddl_locks(&self) -> &DdlLocksRef {
+        &self.ddl_locks
+    }
+
     pub(crate) async fn execute_stmt(
         &self,
         stmt: QueryStatement,
@@ -71,8 +158,44 @@ impl StatementExecutor {
     }
 
     async fn execute_sql(&self, stmt: Statement, query_ctx: QueryContextRef) -> Result<Output> {
+        // The planned statements are authorized on the tables of their plans instead.
+        if !is_planned(&stmt) {
+            self.authorize_statement(&stmt, &query_ctx).await?;
+        }
+
         match stmt {
-            Statement::Query(_) | Statement::Explain(_) | Statement::Delete(_) => {
+            // Plain inserts and deletes are not planned by the query engine, so they are explained
+            // by the routing of their rows instead.
+            Statement::Explain(explain) => match explain.write_statement() {
+                Some(Statement::Insert(insert)) if !insert.is_insert_select() => {
+                    self.explain_insert(*insert, query_ctx).await
+                }
+                Some(Statement::Delete(delete)) => self.explain_delete(*delete, query_ctx).await,
+                _ => {
+                    self.plan_exec(QueryStatement::Sql(Statement::Explain(explain)), query_ctx)
+                        .await
+                }
+            },
+
+            Statement::Query(query) => match query.analyze_table_name() {
+                Some(table_name) => self.analyze_table(table_name, query_ctx).await,
+                None => match query_ctx.select_limit() {
+                    Some(limit) if is_select_limit_applicable(&query) => {
+                        self.select_with_limit(query, limit, query_ctx).await
+                    }
+                    _ => {
+                        self.plan_exec(QueryStatement::Sql(Statement::Query(query)), query_ctx)
+                            .await
+                    }
+                },
+            },
+
+            Statement::Delete(ref delete) => {
+                // A delete with predicates on non-key columns scans the table before deleting
+                // anything, so it may run for a long time.
+                let _progress_guard = self
+                    .progress_registry
+                    .register(delete.inner.to_string(), query_ctx.origin().clone());
                 self.plan_exec(QueryStatement::Sql(stmt), query_ctx).await
             }
 
@@ -92,20 +215,31 @@ impl StatementExecutor {
 
             Statement::ShowTables(stmt) => self.show_tables(stmt, query_ctx).await,
 
+            Statement::ShowProcesslist(stmt) => self.show_processlist(stmt),
+
             Statement::Copy(stmt) => {
+                let origin = query_ctx.origin().clone();
                 let req = to_copy_table_request(stmt, query_ctx)?;
                 match req.direction {
-                    CopyDirection::Export => self.copy_table_to(req).await,
-                    CopyDirection::Import => self.copy_table_from(req).await,
+                    CopyDirection::Export => self.copy_table_to(req, origin).await,
+                    CopyDirection::Import => self.copy_table_from(req, origin).await,
                 }
             }
 
+            Statement::CopyQueryTo(stmt) => self.copy_query_to(stmt, query_ctx).await,
+
+            Statement::ExplainCopy(stmt) => {
+                let req = to_copy_table_request(stmt, query_ctx)?;
+                self.explain_copy(req).await
+            }
+
             Statement::CreateDatabase(_)
             | Statement::CreateTable(_)
             | Statement::CreateExternalTable(_)
             | Statement::Insert(_)
             | Statement::Alter(_)
             | Statement::DropTable(_)
+            | Statement::RestoreTable(_)
             | Statement::ShowCreateTable(_) => self
                 .sql_stmt_executor
                 .execute_sql(stmt, query_ctx)
@@ -115,25 +249,48 @@ impl StatementExecutor {
     }
 
     async fn plan_exec(&self, stmt: QueryStatement, query_ctx: QueryContextRef) -> Result<Output> {
-        let planner = self.query_engine.planner();
-        let plan = planner
-            .plan(stmt, query_ctx.clone())
-            .await
-            .context(PlanStatementSnafu)?;
-        self.query_engine
+        let action = query_statement_action(&stmt);
+        let plan = self.plan(stmt, action, &query_ctx).await?;
+        let written_table = written_table(&plan, &query_ctx);
+        let result = self
+            .query_engine
             .execute(plan, query_ctx)
             .await
-            .context(ExecLogicalPlanSnafu)
+            .context(ExecLogicalPlanSnafu);
+        // If the datanodes don't find the table written, it has been dropped by another
+        // frontend since it was cached, so the cache of the table is refreshed for the
+        // following statements.
+        if let (Err(e), Some(table_name)) = (&result, written_table) {
+            if e.status_code() == StatusCode::TableNotFound {
+                if let Some(catalog_manager) = self
+                    .catalog_manager
+                    .as_any()
+                    .downcast_ref::<FrontendCatalogManager>()
+                {
+                    catalog_manager.invalidate_table(&table_name).await;
+                }
+            }
+        }
+        result
     }
 
     async fn handle_use(&self, db: String, query_ctx: QueryContextRef) -> Result<Output> {
         let catalog = &query_ctx.current_catalog();
+        if query_ctx.is_schema_pinned() {
+            let current = query_ctx.current_schema();
+            ensure!(
+                current == db,
+                SchemaPinnedSnafu {
+                    current,
+                    schema: db
+                }
+            );
+        }
         ensure!(
             self.catalog_manager
-                .schema(catalog, &db)
+                .schema_exist(catalog, &db)
                 .await
-                .context(CatalogSnafu)?
-                .is_some(),
+                .context(CatalogSnafu)?,
             SchemaNotFoundSnafu { schema_info: &db }
         );
 
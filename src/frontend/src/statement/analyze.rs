@@ -0,0 +1,108 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use catalog::statistics::{
+    CREATE_STATISTICS_TABLE_SQLS, STATISTICS_SCHEMA_NAME, STATISTICS_TABLE_NAME,
+};
+use common_catalog::consts::DEFAULT_SCHEMA_NAME;
+use common_catalog::format_full_table_name;
+use common_error::prelude::BoxedError;
+use common_query::Output;
+use datanode::instance::sql::table_idents_to_full_name;
+use query::parser::{QueryLanguageParser, QueryStatement};
+use session::context::{QueryContext, QueryContextRef, QueryOrigin};
+use snafu::{OptionExt, ResultExt};
+use sql::ast::ObjectName;
+use table::engine::TableReference;
+use table::TableRef;
+
+use crate::error::{
+    CatalogSnafu, ExecuteStatementSnafu, ExternalSnafu, ParseQuerySnafu, Result, TableNotFoundSnafu,
+};
+use crate::statement::StatementExecutor;
+
+impl StatementExecutor {
+    /// Executes `SELECT analyze_table('<table>')`, which computes and persists the column
+    /// statistics of the table, returning the number of rows sampled.
+    pub(super) async fn analyze_table(
+        &self,
+        table_name: ObjectName,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let origin = query_ctx.origin().clone();
+        let (catalog, schema, table) = table_idents_to_full_name(&table_name, query_ctx)
+            .map_err(BoxedError::new)
+            .context(ExternalSnafu)?;
+        let table_ref = TableReference::full(&catalog, &schema, &table);
+        let table = self.get_table(&table_ref).await?;
+
+        let _progress_guard = self
+            .progress_registry()
+            .register(format!("ANALYZE {table_ref}"), origin);
+        let statistics = catalog::statistics::analyze_table(&table)
+            .await
+            .context(CatalogSnafu)?;
+
+        let statistics_table = self.statistics_table(&catalog).await?;
+        catalog::statistics::persist_statistics(
+            &statistics_table,
+            &table.table_info(),
+            &statistics,
+        )
+        .await
+        .context(CatalogSnafu)?;
+        Ok(Output::AffectedRows(statistics.sampled_rows))
+    }
+
+    /// Returns the table the statistics of the tables of `catalog` are persisted to, creating it
+    /// if it doesn't exist.
+    async fn statistics_table(&self, catalog: &str) -> Result<TableRef> {
+        if let Some(table) = self.find_statistics_table(catalog).await? {
+            return Ok(table);
+        }
+
+        let query_ctx = Arc::new(
+            QueryContext::with(catalog, DEFAULT_SCHEMA_NAME).with_origin(QueryOrigin::System),
+        );
+        for sql in CREATE_STATISTICS_TABLE_SQLS {
+            let stmt = QueryLanguageParser::parse_sql(sql).context(ParseQuerySnafu)?;
+            let QueryStatement::Sql(stmt) = stmt else {
+                unreachable!("the statements creating the statistics table are SQL")
+            };
+            let _ = self
+                .sql_stmt_executor
+                .execute_sql(stmt, query_ctx.clone())
+                .await
+                .context(ExecuteStatementSnafu)?;
+        }
+        self.find_statistics_table(catalog)
+            .await?
+            .with_context(|| TableNotFoundSnafu {
+                table_name: format_full_table_name(
+                    catalog,
+                    STATISTICS_SCHEMA_NAME,
+                    STATISTICS_TABLE_NAME,
+                ),
+            })
+    }
+
+    async fn find_statistics_table(&self, catalog: &str) -> Result<Option<TableRef>> {
+        self.catalog_manager
+            .table(catalog, STATISTICS_SCHEMA_NAME, STATISTICS_TABLE_NAME)
+            .await
+            .context(CatalogSnafu)
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Authorization of the statements by the [StatementAuthorizer] plugin.
+//!
+//! The statements planned by the query engine, including TQL and PromQL, are authorized on the
+//! tables their plans scan or write, subqueries included, when they are planned by
+//! [StatementExecutor::plan]. The other statements are authorized on the tables they name
+//! before they are executed.
+
+use common_catalog::format_full_table_name;
+use common_error::prelude::BoxedError;
+use datafusion_common::tree_node::{TreeNode, VisitRecursion};
+use datafusion_common::OwnedTableReference;
+use datafusion_expr::{Expr, LogicalPlan as DfLogicalPlan};
+use datanode::instance::sql::table_idents_to_full_name;
+use query::parser::QueryStatement;
+use query::plan::LogicalPlan;
+use servers::auth::authorizer::StatementAuthorizer;
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+use sql::statements::statement::Statement;
+
+use crate::error::{AuthorizeStatementSnafu, ExternalSnafu, PlanStatementSnafu, Result};
+use crate::statement::StatementExecutor;
+
+/// The action of a statement planned by the query engine.
+pub(super) fn query_statement_action(stmt: &QueryStatement) -> &'static str {
+    match stmt {
+        QueryStatement::Sql(stmt) => stmt.kind(),
+        QueryStatement::Promql(_) => "TQL",
+    }
+}
+
+/// Returns whether the statement is executed by the plan of the query engine, so it's
+/// authorized by [StatementExecutor::plan].
+pub(super) fn is_planned(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Query(query) => query.analyze_table_name().is_none(),
+        Statement::Explain(explain) => !matches!(
+            explain.write_statement(),
+            Some(Statement::Insert(insert)) if !insert.is_insert_select()
+        ),
+        Statement::Insert(insert) => insert.is_insert_select(),
+        Statement::Delete(_) | Statement::Tql(_) | Statement::CopyQueryTo(_) => true,
+        _ => false,
+    }
+}
+
+impl StatementExecutor {
+    /// Plans the statement, then authorizes the `action` on the tables of the plan.
+    pub(crate) async fn plan(
+        &self,
+        stmt: QueryStatement,
+        action: &str,
+        query_ctx: &QueryContextRef,
+    ) -> Result<LogicalPlan> {
+        let plan = self
+            .query_engine
+            .planner()
+            .plan(stmt, query_ctx.clone())
+            .await
+            .context(PlanStatementSnafu)?;
+        if let Some(authorizer) = &self.authorizer {
+            let objects = plan_tables(&plan, query_ctx);
+            authorize(authorizer.as_ref(), action, &objects, query_ctx).await?;
+        }
+        Ok(plan)
+    }
+
+    /// Authorizes the statement not planned by the query engine on the tables it names.
+    pub(super) async fn authorize_statement(
+        &self,
+        stmt: &Statement,
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let Some(authorizer) = &self.authorizer else { return Ok(()) };
+        let objects = stmt
+            .referenced_tables()
+            .iter()
+            .map(|name| {
+                let (catalog, schema, table) = table_idents_to_full_name(name, query_ctx.clone())
+                    .map_err(BoxedError::new)
+                    .context(ExternalSnafu)?;
+                Ok(format_full_table_name(&catalog, &schema, &table))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        authorize(authorizer.as_ref(), stmt.kind(), &objects, query_ctx).await
+    }
+}
+
+async fn authorize(
+    authorizer: &dyn StatementAuthorizer,
+    action: &str,
+    objects: &[String],
+    query_ctx: &QueryContextRef,
+) -> Result<()> {
+    authorizer
+        .check(&query_ctx.current_user(), action, objects, query_ctx)
+        .await
+        .context(AuthorizeStatementSnafu)
+}
+
+/// Returns the full names of the tables the plan scans or writes, including the tables of its
+/// subqueries, in the order they appear and without duplicates.
+fn plan_tables(plan: &LogicalPlan, query_ctx: &QueryContextRef) -> Vec<String> {
+    let LogicalPlan::DfPlan(plan) = plan;
+    let mut tables = Vec::new();
+    collect_plan_tables(plan, &mut |table_name| {
+        let table_name = table_name
+            .clone()
+            .resolve(&query_ctx.current_catalog(), &query_ctx.current_schema());
+        let table_name =
+            format_full_table_name(&table_name.catalog, &table_name.schema, &table_name.table);
+        if !tables.contains(&table_name) {
+            tables.push(table_name);
+        }
+    });
+    tables
+}
+
+fn collect_plan_tables(plan: &DfLogicalPlan, f: &mut dyn FnMut(&OwnedTableReference)) {
+    // The visitors never fail.
+    let _ = plan.apply(&mut |plan| {
+        match plan {
+            DfLogicalPlan::TableScan(scan) => f(&scan.table_name),
+            DfLogicalPlan::Dml(dml) => f(&dml.table_name),
+            _ => {}
+        }
+        // The subqueries in the expressions are not the inputs of the plan.
+        for expr in plan.expressions() {
+            let _ = expr.apply(&mut |expr| {
+                match expr {
+                    Expr::ScalarSubquery(subquery)
+                    | Expr::InSubquery { subquery, .. }
+                    | Expr::Exists { subquery, .. } => collect_plan_tables(&subquery.subquery, f),
+                    _ => {}
+                }
+                Ok(VisitRecursion::Continue)
+            });
+        }
+        Ok(VisitRecursion::Continue)
+    });
+}
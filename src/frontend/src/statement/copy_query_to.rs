@@ -0,0 +1,321 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_datasource::file_format::Format;
+use common_datasource::object_store::{build_backend, parse_url};
+use common_query::Output;
+use common_recordbatch::{DfRecordBatch, SendableRecordBatchStream};
+use common_telemetry::warn;
+use futures_util::StreamExt;
+use object_store::ObjectStore;
+use query::parser::QueryStatement;
+use session::context::{QueryContextRef, QueryOrigin};
+use snafu::{ensure, ResultExt};
+use sql::statements::copy::CopyQueryTo;
+use sql::statements::statement::Statement;
+use storage::sst::SstInfo;
+use storage::{ParquetWriter, Source, WriteProgress};
+
+use crate::error::{self, Result, WriteParquetSnafu};
+use crate::statement::copy_table_to::{
+    copy_to_job_progress, is_async, job_id_output, spawn_copy_to_job, COPY_TO_JOB,
+};
+use crate::statement::StatementExecutor;
+
+impl StatementExecutor {
+    /// Executes `COPY (<query>) TO 'location'`, streaming the result of the query into a
+    /// parquet, CSV or JSON file at the location. Compressed files are not supported. Returns
+    /// the number of rows exported, or the id of the job exporting them in background with
+    /// `async = 'true'`.
+    pub(crate) async fn copy_query_to(
+        &self,
+        stmt: CopyQueryTo,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let format = Format::try_from(&stmt.with).context(error::ParseFileFormatSnafu)?;
+        ensure_writable(&format)?;
+
+        let (_schema, _host, path) = parse_url(&stmt.location).context(error::ParseUrlSnafu)?;
+        let object_store =
+            build_backend(&stmt.location, &stmt.connection).context(error::BuildBackendSnafu)?;
+        let description = format!("COPY ({}) TO '{}'", stmt.query.inner, stmt.location);
+        let run_async = is_async(&stmt.with);
+        let origin = query_ctx.origin().clone();
+
+        // The query is planned and executed like any other query, so it's subject to the same
+        // limits, but authorized as a `COPY TO`.
+        let plan = self
+            .plan(
+                QueryStatement::Sql(Statement::Query(stmt.query)),
+                "COPY TO",
+                &query_ctx,
+            )
+            .await?;
+        // The result of an asynchronous export is read by the job, not by the user.
+        let exec_ctx = if run_async {
+            Arc::new(query_ctx.fork(QueryOrigin::Background(COPY_TO_JOB.to_string())))
+        } else {
+            query_ctx
+        };
+        let output = self
+            .query_engine
+            .execute(plan, exec_ctx)
+            .await
+            .context(error::ExecLogicalPlanSnafu)?;
+        let stream = match output {
+            Output::Stream(stream) => stream,
+            Output::RecordBatches(batches) => batches.as_stream(),
+            Output::AffectedRows(_) => {
+                return error::NotSupportedSnafu {
+                    feat: "exporting the result of a statement without output rows",
+                }
+                .fail();
+            }
+        };
+
+        if run_async {
+            let job = spawn_copy_to_job(
+                self.job_registry(),
+                copy_to_job_progress(self.progress_registry(), description),
+                stmt.location,
+                path,
+                format,
+                stream,
+                object_store,
+            );
+            return job_id_output(job.id());
+        }
+
+        let progress_guard = self.progress_registry().register(description, origin);
+        progress_guard.progress().set_current_item(&path);
+
+        let rows_copied = write_file(&path, &format, stream, object_store, None)
+            .await?
+            .unwrap_or(0);
+        Ok(Output::AffectedRows(rows_copied))
+    }
+}
+
+/// Checks that files of `format` can be written. Files are written in parquet, or in CSV
+/// and JSON without compression.
+fn ensure_writable(format: &Format) -> Result<()> {
+    let compression_type = match format {
+        Format::Parquet(_) => return Ok(()),
+        Format::Csv(format) => format.compression_type,
+        Format::Json(format) => format.compression_type,
+        Format::Orc(_) => {
+            return error::NotSupportedSnafu {
+                feat: "exporting to orc files",
+            }
+            .fail()
+        }
+    };
+    ensure!(
+        !compression_type.is_compressed(),
+        error::NotSupportedSnafu {
+            feat: "exporting to compressed files",
+        }
+    );
+    Ok(())
+}
+
+/// Writes the stream to a file of `format` at `path`, removing the partially written file if
+/// the stream or the writer fails. Returns the number of rows written, `None` if the stream is
+/// empty, in which case no file is written. The bytes written are reported to
+/// `write_progress` as they are flushed.
+pub(super) async fn write_file(
+    path: &str,
+    format: &Format,
+    stream: SendableRecordBatchStream,
+    object_store: ObjectStore,
+    write_progress: Option<WriteProgress>,
+) -> Result<Option<usize>> {
+    ensure_writable(format)?;
+    let result = match format {
+        Format::Csv(format) => {
+            write_rows(
+                path,
+                stream,
+                &object_store,
+                write_progress,
+                |batch, first_batch| format.encode(batch, first_batch),
+            )
+            .await
+        }
+        Format::Json(format) => {
+            write_rows(path, stream, &object_store, write_progress, |batch, _| {
+                format.encode(batch)
+            })
+            .await
+        }
+        Format::Parquet(_) | Format::Orc(_) => {
+            return Ok(write_parquet(path, stream, object_store, write_progress)
+                .await?
+                .map(|SstInfo { num_rows, .. }| num_rows));
+        }
+    };
+    if result.is_err() {
+        remove_partial_file(&object_store, path).await;
+    }
+    result
+}
+
+/// Writes the rows of the stream encoded by `encode`, which is told whether the batch is the
+/// first one of the file. The file is created on the first non-empty batch.
+async fn write_rows(
+    path: &str,
+    mut stream: SendableRecordBatchStream,
+    object_store: &ObjectStore,
+    write_progress: Option<WriteProgress>,
+    encode: impl Fn(&DfRecordBatch, bool) -> common_datasource::error::Result<Vec<u8>>,
+) -> Result<Option<usize>> {
+    let mut object_writer = None;
+    let mut num_rows = 0;
+    while let Some(batch) = stream.next().await {
+        let batch = batch.context(error::CollectRecordBatchesSnafu)?;
+        if batch.num_rows() == 0 {
+            continue;
+        }
+        let bytes = encode(batch.df_record_batch(), num_rows == 0)
+            .context(error::EncodeRecordBatchSnafu)?;
+        let size = bytes.len() as u64;
+        let writer = match object_writer.as_mut() {
+            Some(writer) => writer,
+            None => object_writer.insert(
+                object_store
+                    .writer(path)
+                    .await
+                    .context(error::WriteObjectSnafu { path })?,
+            ),
+        };
+        writer
+            .append(bytes)
+            .await
+            .context(error::WriteObjectSnafu { path })?;
+        if let Some(write_progress) = &write_progress {
+            write_progress(size);
+        }
+        num_rows += batch.num_rows();
+    }
+
+    let Some(mut writer) = object_writer else {
+        return Ok(None);
+    };
+    writer
+        .close()
+        .await
+        .context(error::WriteObjectSnafu { path })?;
+    Ok(Some(num_rows))
+}
+
+/// Writes the stream to a parquet file at `path`, removing the partially written file if
+/// the stream or the writer fails. Returns `None` if the stream is empty, in which case no
+/// file is written. The bytes written are reported to `write_progress` as they are flushed.
+pub(super) async fn write_parquet(
+    path: &str,
+    stream: SendableRecordBatchStream,
+    object_store: ObjectStore,
+    write_progress: Option<WriteProgress>,
+) -> Result<Option<SstInfo>> {
+    let mut writer = ParquetWriter::new(path, Source::Stream(stream), object_store.clone());
+    if let Some(write_progress) = write_progress {
+        writer = writer.with_write_progress(write_progress);
+    }
+    match writer
+        .write_sst(&storage::sst::WriteOptions::default())
+        .await
+    {
+        Ok(sst_info) => Ok(sst_info),
+        Err(e) => {
+            remove_partial_file(&object_store, path).await;
+            Err(e).context(WriteParquetSnafu)
+        }
+    }
+}
+
+pub(super) async fn remove_partial_file(object_store: &ObjectStore, path: &str) {
+    if let Err(e) = object_store.delete(path).await {
+        warn!("Failed to remove partially exported file {path}, error: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use common_datasource::object_store::fs::build_fs_backend;
+    use common_recordbatch::error::CreateRecordBatchesSnafu;
+    use common_recordbatch::{RecordBatch, RecordBatchStream};
+    use common_test_util::temp_dir::create_temp_dir;
+    use datatypes::prelude::{ConcreteDataType, VectorRef};
+    use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+    use datatypes::vectors::Int64Vector;
+    use futures_util::Stream;
+
+    use super::*;
+
+    /// Yields `batches` batches and then fails.
+    struct FailingStream {
+        schema: SchemaRef,
+        batches: usize,
+    }
+
+    impl RecordBatchStream for FailingStream {
+        fn schema(&self) -> SchemaRef {
+            self.schema.clone()
+        }
+    }
+
+    impl Stream for FailingStream {
+        type Item = common_recordbatch::error::Result<RecordBatch>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            if self.batches == 0 {
+                return Poll::Ready(Some(
+                    CreateRecordBatchesSnafu {
+                        reason: "injected failure",
+                    }
+                    .fail(),
+                ));
+            }
+            self.batches -= 1;
+            let column: VectorRef = Arc::new(Int64Vector::from_values(0..4096));
+            Poll::Ready(Some(RecordBatch::new(self.schema.clone(), vec![column])))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_partial_file_on_failure() {
+        let dir = create_temp_dir("copy_query_to");
+        let path = format!("{}/export.parquet", dir.path().to_str().unwrap());
+        let object_store = build_fs_backend("/").unwrap();
+
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "v",
+            ConcreteDataType::int64_datatype(),
+            false,
+        )]));
+        let stream = Box::pin(FailingStream {
+            schema,
+            batches: 1024,
+        });
+        let result = write_parquet(&path, stream, object_store.clone(), None).await;
+        assert!(result.is_err());
+        assert!(!object_store.is_exist(&path).await.unwrap());
+    }
+}
@@ -17,15 +17,22 @@ use std::future::Future;
 
 use async_compat::CompatExt;
 use common_base::readable_size::ReadableSize;
+use common_datasource::file_format::orc::new_orc_stream_reader;
+use common_datasource::file_format::Format;
 use common_datasource::lister::{Lister, Source};
 use common_datasource::object_store::{build_backend, parse_url};
 use common_datasource::util::find_dir_and_filename;
 use common_query::Output;
+use common_time::Timestamp;
 use datafusion::parquet::arrow::ParquetRecordBatchStreamBuilder;
 use datatypes::arrow::datatypes::{DataType, SchemaRef};
-use datatypes::vectors::Helper;
+use datatypes::prelude::{ConcreteDataType, ValueRef};
+use datatypes::schema::ColumnSchema;
+use datatypes::vectors::{Helper, VectorRef};
 use futures_util::StreamExt;
+use object_store::{Entry, ObjectStore};
 use regex::Regex;
+use session::context::QueryOrigin;
 use snafu::ResultExt;
 use table::engine::TableReference;
 use table::requests::{CopyTableRequest, InsertRequest};
@@ -35,7 +42,11 @@ use crate::error::{self, IntoVectorsSnafu, Result};
 use crate::statement::StatementExecutor;
 
 impl StatementExecutor {
-    pub(crate) async fn copy_table_from(&self, req: CopyTableRequest) -> Result<Output> {
+    pub(crate) async fn copy_table_from(
+        &self,
+        req: CopyTableRequest,
+        origin: QueryOrigin,
+    ) -> Result<Output> {
         let table_ref = TableReference {
             catalog: &req.catalog_name,
             schema: &req.schema_name,
@@ -43,60 +54,68 @@ impl StatementExecutor {
         };
         let table = self.get_table(&table_ref).await?;
 
-        let (_schema, _host, path) = parse_url(&req.location).context(error::ParseUrlSnafu)?;
-
+        let format = Format::try_from(&req.with).context(error::ParseFileFormatSnafu)?;
         let object_store =
             build_backend(&req.location, &req.connection).context(error::BuildBackendSnafu)?;
 
-        let (dir, filename) = find_dir_and_filename(&path);
-        let regex = req
-            .pattern
-            .as_ref()
-            .map(|x| Regex::new(x))
-            .transpose()
-            .context(error::BuildRegexSnafu)?;
-
-        let source = if let Some(filename) = filename {
-            Source::Filename(filename)
-        } else {
-            Source::Dir
-        };
-
-        let lister = Lister::new(object_store.clone(), source, dir, regex);
+        let entries = list_source_files(&req, &object_store).await?;
 
-        let entries = lister.list().await.context(error::ListObjectsSnafu)?;
+        let progress_guard = self
+            .progress_registry()
+            .register(format!("COPY {table_ref} FROM '{}'", req.location), origin);
+        let progress = progress_guard.progress();
+        progress.set_total(entries.len() as u64);
 
-        let fields = table
-            .schema()
-            .arrow_schema()
-            .fields()
-            .iter()
-            .map(|f| f.name().to_string())
-            .collect::<Vec<_>>();
+        let table_schema = table.schema();
+        let columns = table_schema.column_schemas();
 
         let mut rows_inserted = 0;
         for entry in entries.iter() {
             let path = entry.path();
-            // skips directories.
-            if entry.path().ends_with('/') {
-                continue;
-            }
+            progress.set_current_item(path);
             let reader = object_store
                 .reader(path)
                 .await
                 .context(error::ReadObjectSnafu { path })?;
 
-            let buf_reader = BufReader::new(reader.compat());
-
-            let builder = ParquetRecordBatchStreamBuilder::new(buf_reader)
-                .await
-                .context(error::ReadParquetSnafu)?;
-
-            ensure_schema_matches_ignore_timezone(builder.schema(), table.schema().arrow_schema())?;
-
-            let mut stream = builder
-                .build()
-                .context(error::BuildParquetRecordBatchStreamSnafu)?;
+            let mut stream = match format {
+                Format::Orc(_) => {
+                    let stream = new_orc_stream_reader(reader)
+                        .await
+                        .context(error::ReadOrcSnafu)?;
+                    ensure_schema_matches_ignore_timezone(
+                        &stream.schema(),
+                        table.schema().arrow_schema(),
+                    )?;
+                    stream
+                        .map(|r| r.context(error::ReadOrcRecordBatchSnafu))
+                        .boxed()
+                }
+                Format::Parquet(_) => {
+                    let buf_reader = BufReader::new(reader.compat());
+
+                    let builder = ParquetRecordBatchStreamBuilder::new(buf_reader)
+                        .await
+                        .context(error::ReadParquetSnafu)?;
+
+                    ensure_schema_matches_ignore_timezone(
+                        builder.schema(),
+                        table.schema().arrow_schema(),
+                    )?;
+
+                    builder
+                        .build()
+                        .context(error::BuildParquetRecordBatchStreamSnafu)?
+                        .map(|r| r.context(error::ReadParquetSnafu))
+                        .boxed()
+                }
+                Format::Csv(_) | Format::Json(_) => {
+                    return error::NotSupportedSnafu {
+                        feat: format!("COPY FROM {format:?} files"),
+                    }
+                    .fail()
+                }
+            };
 
             // TODO(hl): make this configurable through options.
             let pending_mem_threshold = ReadableSize::mb(32).as_bytes();
@@ -104,17 +123,20 @@ impl StatementExecutor {
             let mut pending = vec![];
 
             while let Some(r) = stream.next().await {
-                let record_batch = r.context(error::ReadParquetSnafu)?;
+                let record_batch = r?;
                 let vectors =
                     Helper::try_into_vectors(record_batch.columns()).context(IntoVectorsSnafu)?;
 
                 pending_mem_size += vectors.iter().map(|v| v.memory_size()).sum::<usize>();
 
-                let columns_values = fields
+                let columns_values = columns
                     .iter()
-                    .cloned()
                     .zip(vectors.into_iter())
-                    .collect::<HashMap<_, _>>();
+                    .map(|(column, vector)| {
+                        let vector = parse_timestamp_strings(vector, column)?;
+                        Ok((column.name.clone(), vector))
+                    })
+                    .collect::<Result<HashMap<_, _>>>()?;
 
                 pending.push(table.insert(InsertRequest {
                     catalog_name: req.catalog_name.to_string(),
@@ -123,6 +145,7 @@ impl StatementExecutor {
                     columns_values,
                     //TODO: support multi-regions
                     region_number: 0,
+                    write_mode: Default::default(),
                 }));
 
                 if pending_mem_size as u64 >= pending_mem_threshold {
@@ -135,12 +158,44 @@ impl StatementExecutor {
                 rows_inserted +=
                     batch_insert(&mut pending, &mut pending_mem_size, &req.table_name).await?;
             }
+            progress.inc_done(1);
         }
 
         Ok(Output::AffectedRows(rows_inserted))
     }
 }
 
+/// Lists the files to import by `req`, without reading them.
+pub(super) async fn list_source_files(
+    req: &CopyTableRequest,
+    object_store: &ObjectStore,
+) -> Result<Vec<Entry>> {
+    let (_schema, _host, path) = parse_url(&req.location).context(error::ParseUrlSnafu)?;
+
+    let (dir, filename) = find_dir_and_filename(&path);
+    let regex = req
+        .pattern
+        .as_ref()
+        .map(|x| Regex::new(x))
+        .transpose()
+        .context(error::BuildRegexSnafu)?;
+
+    let source = if let Some(filename) = filename {
+        Source::Filename(filename)
+    } else {
+        Source::Dir
+    };
+
+    let lister = Lister::new(object_store.clone(), source, dir, regex);
+
+    let entries = lister.list().await.context(error::ListObjectsSnafu)?;
+    // skips directories.
+    Ok(entries
+        .into_iter()
+        .filter(|entry| !entry.path().ends_with('/'))
+        .collect())
+}
+
 /// Executes all pending inserts all at once, drain pending requests and reset pending bytes.
 async fn batch_insert(
     pending: &mut Vec<impl Future<Output = table::error::Result<usize>>>,
@@ -157,6 +212,33 @@ async fn batch_insert(
     Ok(res)
 }
 
+/// Parses the strings in `vector` to timestamps in the unit of the timestamp `column`, in any
+/// form [Timestamp::parse_in_unit] accepts. Returns `vector` as is if it isn't a string vector
+/// imported to a timestamp column.
+fn parse_timestamp_strings(vector: VectorRef, column: &ColumnSchema) -> Result<VectorRef> {
+    let ConcreteDataType::Timestamp(timestamp_type) = &column.data_type else { return Ok(vector) };
+    if vector.data_type() != ConcreteDataType::string_datatype() {
+        return Ok(vector);
+    }
+
+    let unit = timestamp_type.unit();
+    let mut builder = column.data_type.create_mutable_vector(vector.len());
+    for i in 0..vector.len() {
+        match vector.get_ref(i) {
+            ValueRef::String(s) => {
+                let ts = Timestamp::parse_in_unit(s, unit).context(error::ParseTimestampSnafu {
+                    column_name: &column.name,
+                })?;
+                builder.push_value_ref(ValueRef::Timestamp(ts));
+            }
+            _ => builder.push_null(),
+        }
+    }
+    Ok(builder.to_vector())
+}
+
+/// Ensures the `left` schema of the file matches the `right` schema of the table. Strings in the
+/// file also match timestamp columns, and are parsed to timestamps on import.
 fn ensure_schema_matches_ignore_timezone(left: &SchemaRef, right: &SchemaRef) -> Result<()> {
     let not_match = left
         .fields
@@ -164,7 +246,10 @@ fn ensure_schema_matches_ignore_timezone(left: &SchemaRef, right: &SchemaRef) ->
         .zip(right.fields.iter())
         .map(|(l, r)| (l.data_type(), r.data_type()))
         .enumerate()
-        .find(|(_, (l, r))| !data_type_equals_ignore_timezone(l, r));
+        .find(|(_, (l, r))| {
+            !matches!((l, r), (DataType::Utf8, DataType::Timestamp(..)))
+                && !data_type_equals_ignore_timezone(l, r)
+        });
 
     if let Some((index, _)) = not_match {
         error::InvalidSchemaSnafu {
@@ -210,6 +295,7 @@ mod tests {
     use std::sync::Arc;
 
     use datatypes::arrow::datatypes::{Field, Schema};
+    use datatypes::vectors::{Int64Vector, StringVector, TimestampMillisecondVector};
 
     use super::*;
 
@@ -288,5 +374,59 @@ mod tests {
         test_schema_matches((DataType::Int8, true), (DataType::Int8, true), true);
 
         test_schema_matches((DataType::Int8, true), (DataType::Int16, true), false);
+
+        test_schema_matches(
+            (DataType::Utf8, true),
+            (
+                DataType::Timestamp(datatypes::arrow::datatypes::TimeUnit::Millisecond, None),
+                true,
+            ),
+            true,
+        );
+
+        test_schema_matches(
+            (
+                DataType::Timestamp(datatypes::arrow::datatypes::TimeUnit::Millisecond, None),
+                true,
+            ),
+            (DataType::Utf8, true),
+            false,
+        );
+    }
+
+    #[test]
+    fn test_parse_timestamp_strings() {
+        let column = ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            true,
+        );
+        let vector: VectorRef = Arc::new(StringVector::from(vec![
+            Some("2023-04-01T12:00:00Z"),
+            None,
+            Some("2023-04-01T12:00:00.5+08:00"),
+            Some("1680350400000"),
+        ]));
+        let parsed = parse_timestamp_strings(vector, &column).unwrap();
+        let expect: VectorRef = Arc::new(TimestampMillisecondVector::from(vec![
+            Some(1680350400000),
+            None,
+            Some(1680321600500),
+            Some(1680350400000),
+        ]));
+        assert_eq!(expect, parsed);
+
+        let vector: VectorRef = Arc::new(StringVector::from(vec!["2023-04-01 12:00"]));
+        let err = parse_timestamp_strings(vector, &column).unwrap_err();
+        assert!(err.to_string().contains("column ts"), "{err}");
+
+        // Vectors of other types or imported to other columns are kept as is.
+        let vector: VectorRef = Arc::new(Int64Vector::from(vec![1, 2]));
+        let parsed = parse_timestamp_strings(vector.clone(), &column).unwrap();
+        assert_eq!(vector, parsed);
+        let column = ColumnSchema::new("host", ConcreteDataType::string_datatype(), true);
+        let vector: VectorRef = Arc::new(StringVector::from(vec!["2023-04-01T12:00:00Z"]));
+        let parsed = parse_timestamp_strings(vector.clone(), &column).unwrap();
+        assert_eq!(vector, parsed);
     }
 }
@@ -12,20 +12,50 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use common_datasource::file_format::parquet::ParquetFormat;
+use common_datasource::file_format::Format;
 use common_datasource::object_store::{build_backend, parse_url};
 use common_query::physical_plan::SessionContext;
 use common_query::Output;
+use common_recordbatch::{
+    RecordBatch, RecordBatchStream, RecordBatches, SendableRecordBatchStream,
+};
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+use datatypes::vectors::{UInt64Vector, VectorRef};
+use futures_util::Stream;
+use object_store::ObjectStore;
+use session::context::QueryOrigin;
 use snafu::ResultExt;
 use storage::sst::SstInfo;
-use storage::{ParquetWriter, Source};
+use storage::WriteProgress;
 use table::engine::TableReference;
 use table::requests::CopyTableRequest;
 
-use crate::error::{self, Result, WriteParquetSnafu};
+use crate::error::{self, Result};
+use crate::job::{JobRef, JobRegistry};
+use crate::progress::{ProgressGuard, ProgressRegistryRef};
+use crate::statement::copy_query_to::{remove_partial_file, write_file, write_parquet};
 use crate::statement::StatementExecutor;
 
+/// Key of the option to run `COPY TO` as an asynchronous job.
+const ASYNC_KEY: &str = "ASYNC";
+/// Type of the jobs of `COPY TO`.
+pub(super) const COPY_TO_JOB: &str = "copy_to";
+
 impl StatementExecutor {
-    pub(crate) async fn copy_table_to(&self, req: CopyTableRequest) -> Result<Output> {
+    /// Executes `COPY <table> TO 'location'`. With `async = 'true'`, the export runs as a job
+    /// in background and the id of the job is returned at once.
+    pub(crate) async fn copy_table_to(
+        &self,
+        req: CopyTableRequest,
+        origin: QueryOrigin,
+    ) -> Result<Output> {
         let table_ref = TableReference {
             catalog: &req.catalog_name,
             schema: &req.schema_name,
@@ -47,16 +77,256 @@ impl StatementExecutor {
         let (_schema, _host, path) = parse_url(&req.location).context(error::ParseUrlSnafu)?;
         let object_store =
             build_backend(&req.location, &req.connection).context(error::BuildBackendSnafu)?;
+        let description = format!("COPY {table_ref} TO '{}'", req.location);
 
-        let writer = ParquetWriter::new(&path, Source::Stream(stream), object_store);
+        if is_async(&req.with) {
+            let job = spawn_copy_to_job(
+                self.job_registry(),
+                copy_to_job_progress(self.progress_registry(), description),
+                req.location.clone(),
+                path,
+                Format::Parquet(ParquetFormat::default()),
+                stream,
+                object_store,
+            );
+            return job_id_output(job.id());
+        }
 
-        let rows_copied = writer
-            .write_sst(&storage::sst::WriteOptions::default())
-            .await
-            .context(WriteParquetSnafu)?
+        let progress_guard = self.progress_registry().register(description, origin);
+        progress_guard.progress().set_current_item(&path);
+
+        let rows_copied = write_parquet(&path, stream, object_store, None)
+            .await?
             .map(|SstInfo { num_rows, .. }| num_rows)
             .unwrap_or(0);
 
         Ok(Output::AffectedRows(rows_copied))
     }
 }
+
+/// Returns whether the `COPY TO` runs as an asynchronous job by its `with` options.
+pub(super) fn is_async(with: &HashMap<String, String>) -> bool {
+    with.get(ASYNC_KEY)
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Spawns a job exporting the stream to a file of `format` at `path` of the object store,
+/// `url` is the location of the file reported once the job completes. The `progress` of the
+/// job, see [copy_to_job_progress], is listed until the job stops.
+pub(super) fn spawn_copy_to_job(
+    registry: &JobRegistry,
+    progress: ProgressGuard,
+    url: String,
+    path: String,
+    format: Format,
+    stream: SendableRecordBatchStream,
+    object_store: ObjectStore,
+) -> JobRef {
+    let on_cancel = {
+        let object_store = object_store.clone();
+        let path = path.clone();
+        async move { remove_partial_file(&object_store, &path).await }
+    };
+    progress.progress().set_current_item(&path);
+    let description = progress.progress().description().to_string();
+
+    registry.spawn(
+        COPY_TO_JOB,
+        description,
+        move |job| async move {
+            let _progress = progress;
+            let stream = Box::pin(JobProgressStream {
+                stream,
+                job: job.clone(),
+            });
+            let write_progress: WriteProgress = {
+                let job = job.clone();
+                Box::new(move |bytes| job.inc_bytes(bytes))
+            };
+            match write_file(&path, &format, stream, object_store, Some(write_progress)).await? {
+                Some(_) => {
+                    job.inc_files(1);
+                    Ok(vec![url])
+                }
+                None => Ok(Vec::new()),
+            }
+        },
+        on_cancel,
+    )
+}
+
+/// Registers the progress of a `COPY TO` job described by `description`, as a statement of the
+/// `copy_to` background task.
+pub(super) fn copy_to_job_progress(
+    registry: &ProgressRegistryRef,
+    description: String,
+) -> ProgressGuard {
+    registry.register(
+        description,
+        QueryOrigin::Background(COPY_TO_JOB.to_string()),
+    )
+}
+
+pub(super) fn job_id_output(id: u64) -> Result<Output> {
+    let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+        "job_id",
+        ConcreteDataType::uint64_datatype(),
+        false,
+    )]));
+    let columns: Vec<VectorRef> = vec![Arc::new(UInt64Vector::from_vec(vec![id]))];
+    let batches = RecordBatches::try_from_columns(schema, columns)
+        .context(error::CreateRecordBatchesSnafu)?;
+    Ok(Output::RecordBatches(batches))
+}
+
+/// Counts the rows read from the stream into the job.
+struct JobProgressStream {
+    stream: SendableRecordBatchStream,
+    job: JobRef,
+}
+
+impl RecordBatchStream for JobProgressStream {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+}
+
+impl Stream for JobProgressStream {
+    type Item = common_recordbatch::error::Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = Pin::new(&mut self.stream).poll_next(cx);
+        if let Poll::Ready(Some(Ok(batch))) = &poll {
+            self.job.inc_rows(batch.num_rows() as u64);
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::task::ready;
+    use std::time::Duration;
+
+    use common_datasource::object_store::fs::build_fs_backend;
+    use common_test_util::temp_dir::create_temp_dir;
+    use datatypes::vectors::Int64Vector;
+    use servers::query_handler::JobState;
+    use tokio::time::{Instant, Sleep};
+
+    use super::*;
+    use crate::job::tests::wait_finished;
+
+    /// Yields `batches` batches of 1024 rows, sleeping `delay` before each of them.
+    struct SlowStream {
+        schema: SchemaRef,
+        batches: usize,
+        delay: Duration,
+        sleep: Pin<Box<Sleep>>,
+    }
+
+    impl SlowStream {
+        fn new(batches: usize, delay: Duration) -> SendableRecordBatchStream {
+            Box::pin(Self {
+                schema: Arc::new(Schema::new(vec![ColumnSchema::new(
+                    "v",
+                    ConcreteDataType::int64_datatype(),
+                    false,
+                )])),
+                batches,
+                delay,
+                sleep: Box::pin(tokio::time::sleep(delay)),
+            })
+        }
+    }
+
+    impl RecordBatchStream for SlowStream {
+        fn schema(&self) -> SchemaRef {
+            self.schema.clone()
+        }
+    }
+
+    impl Stream for SlowStream {
+        type Item = common_recordbatch::error::Result<RecordBatch>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            if self.batches == 0 {
+                return Poll::Ready(None);
+            }
+            ready!(self.sleep.as_mut().poll(cx));
+            let deadline = Instant::now() + self.delay;
+            self.sleep.as_mut().reset(deadline);
+            self.batches -= 1;
+            let column: VectorRef = Arc::new(Int64Vector::from_values(0..1024));
+            Poll::Ready(Some(RecordBatch::new(self.schema.clone(), vec![column])))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_copy_to_job() {
+        let dir = create_temp_dir("copy_table_to");
+        let path = format!("{}/export.parquet", dir.path().to_str().unwrap());
+        let object_store = build_fs_backend("/").unwrap();
+        let registry = JobRegistry::default();
+        let progress_registry = ProgressRegistryRef::default();
+
+        let job = spawn_copy_to_job(
+            &registry,
+            copy_to_job_progress(&progress_registry, "COPY demo TO".to_string()),
+            path.clone(),
+            path.clone(),
+            Format::Parquet(ParquetFormat::default()),
+            SlowStream::new(1000, Duration::from_millis(20)),
+            object_store.clone(),
+        );
+        // Waits until the export is in progress.
+        while job.status().rows < 2048 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let rows = job.status().rows;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(job.status().rows > rows);
+        assert_eq!(JobState::Running, job.status().state);
+        // The running job is listed as a statement of the background task.
+        let snapshots = progress_registry.snapshots();
+        assert_eq!(1, snapshots.len());
+        assert_eq!("COPY demo TO", snapshots[0].description);
+        assert_eq!("background(copy_to)", snapshots[0].origin.to_string());
+        assert_eq!(Some(path.as_str()), snapshots[0].current_item.as_deref());
+
+        let status = registry.cancel(job.id()).await.unwrap().unwrap();
+        assert_eq!(JobState::Cancelled, status.state);
+        assert!(status.file_urls.is_empty());
+        assert!(!object_store.is_exist(&path).await.unwrap());
+        assert!(progress_registry.snapshots().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_complete_copy_to_job() {
+        let dir = create_temp_dir("copy_table_to");
+        let path = format!("{}/export.csv", dir.path().to_str().unwrap());
+        let object_store = build_fs_backend("/").unwrap();
+        let registry = JobRegistry::default();
+        let progress_registry = ProgressRegistryRef::default();
+
+        let job = spawn_copy_to_job(
+            &registry,
+            copy_to_job_progress(&progress_registry, "COPY demo TO".to_string()),
+            format!("file://{path}"),
+            path.clone(),
+            Format::Csv(Default::default()),
+            SlowStream::new(3, Duration::from_millis(1)),
+            object_store.clone(),
+        );
+        let status = wait_finished(&job).await;
+        assert_eq!(JobState::Completed, status.state);
+        assert_eq!(3072, status.rows);
+        assert_eq!(1, status.files);
+        assert_eq!(vec![format!("file://{path}")], status.file_urls);
+        let file_size = object_store.stat(&path).await.unwrap().content_length();
+        assert_eq!(file_size, status.bytes);
+        assert!(progress_registry.snapshots().is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `EXPLAIN` of the statements that are not planned by the query engine: where a plain
+//! `INSERT` or a `DELETE` is routed to, and which files and columns a `COPY` reads or writes.
+//! Nothing is written, and no file is read.
+
+use std::sync::Arc;
+
+use common_datasource::file_format::Format;
+use common_datasource::object_store::build_backend;
+use common_query::logical_plan::Expr;
+use common_query::Output;
+use common_recordbatch::RecordBatches;
+use datafusion_common::tree_node::{TreeNode, VisitRecursion};
+use datafusion_expr::utils::split_conjunction;
+use datafusion_expr::LogicalPlan as DfLogicalPlan;
+use datanode::sql::SqlHandler;
+use datatypes::prelude::{ConcreteDataType, DataType};
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{StringVector, VectorRef};
+use object_store::ObjectStore;
+use query::parser::QueryStatement;
+use query::plan::LogicalPlan;
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+use sql::statements::delete::Delete;
+use sql::statements::insert::Insert;
+use sql::statements::statement::Statement;
+use table::engine::TableReference;
+use table::requests::{CopyDirection, CopyTableRequest};
+use table::TableRef;
+
+use crate::error::{self, InvokeDatanodeSnafu, Result};
+use crate::statement::copy_table_from::list_source_files;
+use crate::statement::StatementExecutor;
+use crate::table::{DistTable, RegionTarget};
+
+/// Rows of an `EXPLAIN` output, in the same `plan_type` and `plan` columns as the query
+/// engine's.
+#[derive(Debug, Default)]
+pub(crate) struct ExplainRows {
+    plan_types: Vec<String>,
+    plans: Vec<String>,
+}
+
+impl ExplainRows {
+    fn push(&mut self, plan_type: &str, plan: impl Into<String>) {
+        self.plan_types.push(plan_type.to_string());
+        self.plans.push(plan.into());
+    }
+
+    fn into_output(self) -> Result<Output> {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("plan_type", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("plan", ConcreteDataType::string_datatype(), false),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(self.plan_types)),
+            Arc::new(StringVector::from(self.plans)),
+        ];
+        let batches = RecordBatches::try_from_columns(schema, columns)
+            .context(error::CreateRecordBatchesSnafu)?;
+        Ok(Output::RecordBatches(batches))
+    }
+}
+
+impl StatementExecutor {
+    /// Explains where the rows of a plain `INSERT` are routed to.
+    pub(super) async fn explain_insert(
+        &self,
+        insert: Insert,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let request =
+            SqlHandler::insert_to_request(self.catalog_manager.clone(), insert, query_ctx)
+                .await
+                .context(InvokeDatanodeSnafu)?;
+        let table_ref = TableReference {
+            catalog: &request.catalog_name,
+            schema: &request.schema_name,
+            table: &request.table_name,
+        };
+        let table = self.get_table(&table_ref).await?;
+        let table_name = table_ref.to_string();
+        let local_rows = request.columns_values.values().next().map(|v| v.len());
+
+        let targets = match table.as_any().downcast_ref::<DistTable>() {
+            Some(dist_table) => Some(dist_table.route_insert(request).await?),
+            None => None,
+        };
+        let rows = request_rows(&table_name, &table, targets, local_rows).await?;
+        rows.into_output()
+    }
+
+    /// Explains which regions a `DELETE` is routed to, by the partition columns in its
+    /// `WHERE` clause.
+    pub(super) async fn explain_delete(
+        &self,
+        delete: Delete,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        // Authorized as the `EXPLAIN` it's executed for.
+        let plan = self
+            .plan(
+                QueryStatement::Sql(Statement::Delete(Box::new(delete))),
+                "EXPLAIN",
+                &query_ctx,
+            )
+            .await?;
+        let dml = match plan {
+            LogicalPlan::DfPlan(DfLogicalPlan::Dml(dml)) => dml,
+            _ => {
+                return error::NotSupportedSnafu {
+                    feat: format!("EXPLAIN of {plan:?}"),
+                }
+                .fail()
+            }
+        };
+
+        let default_catalog = query_ctx.current_catalog();
+        let default_schema = query_ctx.current_schema();
+        let table_name = dml.table_name.resolve(&default_catalog, &default_schema);
+        let table_ref = TableReference {
+            catalog: table_name.catalog.as_ref(),
+            schema: table_name.schema.as_ref(),
+            table: table_name.table.as_ref(),
+        };
+        let table = self.get_table(&table_ref).await?;
+
+        let targets = match table.as_any().downcast_ref::<DistTable>() {
+            Some(dist_table) => {
+                let filters = collect_filters(&dml.input);
+                Some(dist_table.route_delete(&filters).await?)
+            }
+            None => None,
+        };
+        let rows = request_rows(&table_ref.to_string(), &table, targets, None).await?;
+        rows.into_output()
+    }
+
+    /// Explains the files and columns a `COPY` reads or writes. The files to import are
+    /// listed, but not read.
+    pub(super) async fn explain_copy(&self, req: CopyTableRequest) -> Result<Output> {
+        let table_ref = TableReference {
+            catalog: &req.catalog_name,
+            schema: &req.schema_name,
+            table: &req.table_name,
+        };
+        let table = self.get_table(&table_ref).await?;
+
+        let rows = match req.direction {
+            CopyDirection::Import => {
+                let object_store = build_backend(&req.location, &req.connection)
+                    .context(error::BuildBackendSnafu)?;
+                explain_copy_table_from(&req, &table.schema(), &object_store).await?
+            }
+            CopyDirection::Export => explain_copy_table_to(&req, &table.schema()),
+        };
+        rows.into_output()
+    }
+}
+
+/// Renders the regions a write is routed to. The `targets` are `None` for tables that are not
+/// partitioned across datanodes, which write all `local_rows` to one region.
+async fn request_rows(
+    table_name: &str,
+    table: &TableRef,
+    targets: Option<Vec<RegionTarget>>,
+    local_rows: Option<usize>,
+) -> Result<ExplainRows> {
+    let mut rows = ExplainRows::default();
+    rows.push("target", table_name);
+
+    let targets = match targets {
+        Some(targets) => targets,
+        None => {
+            let mut plan = "region: 0".to_string();
+            if let Some(local_rows) = local_rows {
+                plan.push_str(&format!(", rows: {local_rows}"));
+            }
+            rows.push("region_target", plan);
+            return Ok(rows);
+        }
+    };
+
+    // Safety: `targets` are only found for dist tables.
+    let dist_table = table.as_any().downcast_ref::<DistTable>().unwrap();
+    for (region, bounds) in dist_table.partition_bounds().await? {
+        rows.push(
+            "partition_rule",
+            format!("region: {region}, range: {bounds}"),
+        );
+    }
+    for target in targets {
+        let mut plan = format!("region: {}, range: {}", target.region, target.bounds);
+        if let Some(target_rows) = target.rows {
+            plan.push_str(&format!(", rows: {target_rows}"));
+        }
+        plan.push_str(&format!(", datanode: {}", target.datanode));
+        rows.push("region_target", plan);
+    }
+    Ok(rows)
+}
+
+/// Collects the predicates of the `WHERE` clause, split by `AND`.
+fn collect_filters(plan: &DfLogicalPlan) -> Vec<Expr> {
+    let mut filters = Vec::new();
+    // The visitor never fails.
+    let _ = plan.apply(&mut |plan| {
+        match plan {
+            DfLogicalPlan::Filter(filter) => {
+                filters.extend(split_conjunction(&filter.predicate).into_iter().cloned())
+            }
+            DfLogicalPlan::TableScan(scan) => filters.extend(scan.filters.iter().cloned()),
+            _ => {}
+        }
+        Ok(VisitRecursion::Continue)
+    });
+    filters.into_iter().map(Expr::from).collect()
+}
+
+fn describe_format(format: &Format) -> String {
+    match format {
+        Format::Csv(format) => format!("CSV, compression: {}", format.compression_type),
+        Format::Json(format) => format!("JSON, compression: {}", format.compression_type),
+        Format::Parquet(_) => "PARQUET".to_string(),
+        Format::Orc(_) => "ORC".to_string(),
+    }
+}
+
+pub(super) async fn explain_copy_table_from(
+    req: &CopyTableRequest,
+    table_schema: &Schema,
+    object_store: &ObjectStore,
+) -> Result<ExplainRows> {
+    let mut rows = ExplainRows::default();
+    let mut source = req.location.clone();
+    if let Some(pattern) = &req.pattern {
+        source.push_str(&format!(", pattern: {pattern}"));
+    }
+    rows.push("source", source);
+
+    let format = Format::try_from(&req.with).context(error::ParseFileFormatSnafu)?;
+    rows.push("format", describe_format(&format));
+
+    for entry in list_source_files(req, object_store).await? {
+        rows.push("file", entry.path());
+    }
+
+    // The columns of the files are imported to the table columns at the same position.
+    for (i, column) in table_schema.column_schemas().iter().enumerate() {
+        rows.push(
+            "column_mapping",
+            format!(
+                "file column {i} -> {} {}",
+                column.name,
+                column.data_type.name()
+            ),
+        );
+    }
+    rows.push(
+        "target",
+        format!(
+            "{}.{}.{}",
+            req.catalog_name, req.schema_name, req.table_name
+        ),
+    );
+    Ok(rows)
+}
+
+fn explain_copy_table_to(req: &CopyTableRequest, table_schema: &Schema) -> ExplainRows {
+    let mut rows = ExplainRows::default();
+    rows.push(
+        "source",
+        format!(
+            "{}.{}.{}",
+            req.catalog_name, req.schema_name, req.table_name
+        ),
+    );
+    // Tables are always exported as parquet.
+    rows.push("format", "PARQUET");
+    for (i, column) in table_schema.column_schemas().iter().enumerate() {
+        rows.push(
+            "column_mapping",
+            format!(
+                "{} {} -> file column {i}",
+                column.name,
+                column.data_type.name()
+            ),
+        );
+    }
+    rows.push("target", req.location.clone());
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common_test_util::temp_dir::create_temp_dir;
+    use object_store::services::Fs;
+    use object_store::test_util::RecordingLayer;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_explain_copy_table_from_only_lists_files() {
+        let dir = create_temp_dir("explain-copy-from");
+        // Not valid csv files: explaining must not read them.
+        std::fs::write(dir.path().join("a.csv"), b"\x00\x01garbage").unwrap();
+        std::fs::write(dir.path().join("b.csv"), b"\x02\x03garbage").unwrap();
+        std::fs::write(dir.path().join("c.json"), b"\x04\x05garbage").unwrap();
+
+        let recording = RecordingLayer::default();
+        let mut builder = Fs::default();
+        builder.root("/");
+        let object_store = ObjectStore::new(builder)
+            .unwrap()
+            .finish()
+            .layer(recording.clone());
+
+        let location = format!("{}/", dir.path().to_string_lossy());
+        let req = CopyTableRequest {
+            catalog_name: "greptime".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "demo".to_string(),
+            location: location.clone(),
+            with: HashMap::from([("FORMAT".to_string(), "csv".to_string())]),
+            connection: HashMap::new(),
+            pattern: Some(".*\\.csv".to_string()),
+            direction: CopyDirection::Import,
+        };
+        let schema = Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+        ]);
+
+        let rows = explain_copy_table_from(&req, &schema, &object_store)
+            .await
+            .unwrap();
+
+        let mut files = rows
+            .plan_types
+            .iter()
+            .zip(rows.plans.iter())
+            .filter(|(plan_type, _)| *plan_type == "file")
+            .map(|(_, plan)| plan.clone())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(2, files.len());
+        assert!(files[0].ends_with("a.csv"));
+        assert!(files[1].ends_with("b.csv"));
+        assert!(rows
+            .plans
+            .contains(&"file column 1 -> cpu Float64".to_string()));
+        assert!(rows.plans.contains(&"greptime.public.demo".to_string()));
+
+        let operations = recording.operations();
+        assert!(!operations.is_empty());
+        assert!(
+            operations.iter().all(|(op, _)| *op == "list"),
+            "{operations:?}"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use common_query::Output;
+use common_recordbatch::{RecordBatch, RecordBatchStream, SendableRecordBatchStream};
+use datatypes::schema::SchemaRef;
+use futures_util::Stream;
+use query::parser::QueryStatement;
+use session::context::QueryContextRef;
+use sql::ast::{Expr, Value};
+use sql::statements::query::Query;
+use sql::statements::statement::Statement;
+
+use crate::error::Result;
+use crate::statement::StatementExecutor;
+
+impl StatementExecutor {
+    /// Executes the `SELECT` without a `LIMIT` under the select limit of the session: at most
+    /// `limit` rows are returned, and a warning is added to the context if there are more.
+    pub(crate) async fn select_with_limit(
+        &self,
+        mut query: Box<Query>,
+        limit: usize,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        // Asks for one more row to tell whether the result is truncated.
+        query.inner.limit = Some(Expr::Value(Value::Number(
+            limit.saturating_add(1).to_string(),
+            false,
+        )));
+        let output = self
+            .plan_exec(
+                QueryStatement::Sql(Statement::Query(query)),
+                query_ctx.clone(),
+            )
+            .await?;
+
+        let stream = match output {
+            Output::Stream(stream) => stream,
+            Output::RecordBatches(batches) => batches.as_stream(),
+            Output::AffectedRows(_) => return Ok(output),
+        };
+        Ok(Output::Stream(Box::pin(SelectLimitStream {
+            stream,
+            limit,
+            remaining: limit,
+            truncated: false,
+            query_ctx,
+        })))
+    }
+}
+
+/// Returns whether the select limit of the session applies to the query, which is a `SELECT`
+/// without a `LIMIT` or `FETCH` of its own.
+pub(crate) fn is_select_limit_applicable(query: &Query) -> bool {
+    query.inner.limit.is_none() && query.inner.fetch.is_none()
+}
+
+/// Stops the stream after `limit` rows, and warns about the truncation if the stream has more.
+struct SelectLimitStream {
+    stream: SendableRecordBatchStream,
+    limit: usize,
+    remaining: usize,
+    truncated: bool,
+    query_ctx: QueryContextRef,
+}
+
+impl RecordBatchStream for SelectLimitStream {
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+}
+
+impl Stream for SelectLimitStream {
+    type Item = common_recordbatch::error::Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.truncated {
+                return Poll::Ready(None);
+            }
+            let batch = match futures_util::ready!(Pin::new(&mut self.stream).poll_next(cx)) {
+                Some(Ok(batch)) if batch.num_rows() == 0 => continue,
+                Some(Ok(batch)) => batch,
+                other => return Poll::Ready(other),
+            };
+            if batch.num_rows() <= self.remaining {
+                self.remaining -= batch.num_rows();
+                return Poll::Ready(Some(Ok(batch)));
+            }
+
+            self.truncated = true;
+            self.query_ctx.add_warning(format!(
+                "Result is truncated to {} rows by sql_select_limit, add a LIMIT to the query to return more rows",
+                self.limit
+            ));
+            if self.remaining == 0 {
+                return Poll::Ready(None);
+            }
+            let sliced = batch.df_record_batch().slice(0, self.remaining);
+            return Poll::Ready(Some(RecordBatch::try_from_df_record_batch(
+                batch.schema.clone(),
+                sliced,
+            )));
+        }
+    }
+}
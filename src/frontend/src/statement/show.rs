@@ -12,14 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use common_query::Output;
-use session::context::QueryContextRef;
+use common_recordbatch::RecordBatches;
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{StringVector, UInt64Vector, VectorRef};
+use session::context::{QueryContextRef, QueryOrigin};
 use snafu::ResultExt;
-use sql::statements::show::{ShowDatabases, ShowTables};
+use sql::statements::show::{ShowDatabases, ShowProcesslist, ShowTables};
 
-use crate::error::{ExecuteStatementSnafu, Result};
+use crate::error::{self, ExecuteStatementSnafu, Result};
 use crate::statement::StatementExecutor;
 
+/// Max chars of the statement shown in `SHOW PROCESSLIST` without `FULL`, same as MySQL.
+const PROCESSLIST_INFO_LEN: usize = 100;
+
 impl StatementExecutor {
     pub(super) async fn show_databases(&self, stmt: ShowDatabases) -> Result<Output> {
         query::sql::show_databases(stmt, self.catalog_manager.clone())
@@ -36,4 +45,67 @@ impl StatementExecutor {
             .await
             .context(ExecuteStatementSnafu)
     }
+
+    /// Shows the DDL waiting for or holding the locks of their tables, then the long-running
+    /// statements with their progress, in the columns of MySQL's `SHOW PROCESSLIST` and the
+    /// origin of the statements.
+    pub(super) fn show_processlist(&self, stmt: ShowProcesslist) -> Result<Output> {
+        let mut ids = Vec::new();
+        let mut commands = Vec::new();
+        let mut times = Vec::new();
+        let mut states = Vec::new();
+        let mut infos = Vec::new();
+        let mut current_items = Vec::new();
+        let mut origins = Vec::new();
+        // DDL are only executed on behalf of the users.
+        for process in self.ddl_locks.processes() {
+            ids.push(process.id);
+            commands.push("DDL");
+            times.push(process.elapsed.as_secs());
+            states.push(process.state.as_str().to_string());
+            infos.push(process.description);
+            current_items.push(None);
+            origins.push(QueryOrigin::User.to_string());
+        }
+        for snapshot in self.progress_registry.snapshots() {
+            ids.push(snapshot.id);
+            commands.push("Query");
+            times.push(snapshot.elapsed.as_secs());
+            states.push(snapshot.state());
+            infos.push(snapshot.description);
+            current_items.push(snapshot.current_item);
+            origins.push(snapshot.origin.to_string());
+        }
+        for (info, current_item) in infos.iter_mut().zip(current_items) {
+            if !stmt.full {
+                if let Some((end, _)) = info.char_indices().nth(PROCESSLIST_INFO_LEN) {
+                    info.truncate(end);
+                }
+            }
+            // The item being processed is kept after the truncated statement.
+            if let Some(item) = current_item {
+                info.push_str(&format!(" (current: {item})"));
+            }
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("Id", ConcreteDataType::uint64_datatype(), false),
+            ColumnSchema::new("Command", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("Time", ConcreteDataType::uint64_datatype(), false),
+            ColumnSchema::new("State", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("Info", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("Origin", ConcreteDataType::string_datatype(), false),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(UInt64Vector::from_vec(ids)),
+            Arc::new(StringVector::from(commands)),
+            Arc::new(UInt64Vector::from_vec(times)),
+            Arc::new(StringVector::from(states)),
+            Arc::new(StringVector::from(infos)),
+            Arc::new(StringVector::from(origins)),
+        ];
+        let batches = RecordBatches::try_from_columns(schema, columns)
+            .context(error::CreateRecordBatchesSnafu)?;
+        Ok(Output::RecordBatches(batches))
+    }
 }
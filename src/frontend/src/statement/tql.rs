@@ -13,14 +13,13 @@
 // limitations under the License.
 
 use common_query::Output;
-use query::parser::{PromQuery, QueryLanguageParser};
+use query::parser::PromQuery;
 use session::context::QueryContextRef;
 use snafu::ResultExt;
 use sql::statements::tql::Tql;
 
-use crate::error::{
-    ExecLogicalPlanSnafu, NotSupportedSnafu, ParseQuerySnafu, PlanStatementSnafu, Result,
-};
+use crate::error::{ExecLogicalPlanSnafu, NotSupportedSnafu, ParseQuerySnafu, Result};
+use crate::statement::authorize::query_statement_action;
 use crate::statement::StatementExecutor;
 
 impl StatementExecutor {
@@ -33,12 +32,9 @@ impl StatementExecutor {
                     step: eval.step,
                     query: eval.query,
                 };
-                let stmt = QueryLanguageParser::parse_promql(&promql).context(ParseQuerySnafu)?;
-                self.query_engine
-                    .planner()
-                    .plan(stmt, query_ctx.clone())
-                    .await
-                    .context(PlanStatementSnafu)?
+                let stmt = self.parse_promql(&promql).context(ParseQuerySnafu)?;
+                let action = query_statement_action(&stmt);
+                self.plan(stmt, action, &query_ctx).await?
             }
             Tql::Explain(_) => {
                 return NotSupportedSnafu {
@@ -13,42 +13,50 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::iter;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use api::v1::AlterExpr;
 use async_trait::async_trait;
 use catalog::helper::{TableGlobalKey, TableGlobalValue};
 use catalog::remote::KvBackendRef;
-use client::Database;
+use client::{Client, Database};
+use common_base::commit_token::CommitToken;
 use common_error::prelude::BoxedError;
 use common_query::error::Result as QueryResult;
 use common_query::logical_plan::Expr;
 use common_query::physical_plan::{PhysicalPlan, PhysicalPlanRef};
 use common_query::Output;
 use common_recordbatch::adapter::AsyncRecordBatchStreamAdapter;
-use common_recordbatch::{RecordBatches, SendableRecordBatchStream};
-use common_telemetry::debug;
+use common_recordbatch::{RecordBatch, RecordBatches, SendableRecordBatchStream};
+use common_telemetry::{debug, warn};
 use datafusion::execution::context::TaskContext;
 use datafusion::physical_plan::{
     Partitioning, SendableRecordBatchStream as DfSendableRecordBatchStream,
 };
 use datafusion_common::DataFusionError;
 use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+use datatypes::value::Value;
+use itertools::Itertools;
 use meta_client::rpc::TableName;
+use metrics::increment_counter;
 use partition::manager::PartitionRuleManagerRef;
+use partition::partition::{PartitionBound, PartitionDef};
 use partition::splitter::WriteSplitter;
 use snafu::prelude::*;
 use store_api::storage::RegionNumber;
 use table::error::TableOperationSnafu;
 use table::metadata::{FilterPushDownType, TableInfo, TableInfoRef};
 use table::requests::{AlterKind, AlterTableRequest, DeleteRequest, InsertRequest};
-use table::table::AlterContext;
-use table::{meter_insert_request, Table};
+use table::table::{AlterContext, PartialAggregate, PartialTopK};
+use table::{meter_insert_request, Table, TableRef};
 use tokio::sync::RwLock;
 
 use crate::datanode::DatanodeClients;
-use crate::error::{self, FindDatanodeSnafu, FindTableRouteSnafu, Result};
+use crate::error::{
+    self, FindDatanodeSnafu, FindTableRouteSnafu, InvalidRegionNumberSnafu, Result,
+};
 use crate::table::delete::to_grpc_delete_request;
 use crate::table::insert::to_grpc_insert_request;
 use crate::table::scan::{DatanodeInstance, TableScanPlan};
@@ -57,6 +65,17 @@ mod delete;
 pub mod insert;
 pub(crate) mod scan;
 
+/// A region that a write is routed to, found without executing the write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RegionTarget {
+    pub region: RegionNumber,
+    /// The value range of the region, in the syntax of `PARTITION BY RANGE COLUMNS`.
+    pub bounds: String,
+    pub datanode: u64,
+    /// Number of rows routed to the region, `None` if it's unknown until the write is executed.
+    pub rows: Option<usize>,
+}
+
 #[derive(Clone)]
 pub struct DistTable {
     table_name: TableName,
@@ -64,6 +83,12 @@ pub struct DistTable {
     partition_manager: PartitionRuleManagerRef,
     datanode_clients: Arc<DatanodeClients>,
     backend: KvBackendRef,
+    /// Commit token of the session the table is viewed by, see [Table::with_commit_token].
+    /// It's carried by the requests to the datanodes, and records the writes they accept.
+    commit_token: Option<Arc<Mutex<CommitToken>>>,
+    /// Wall-clock time in millis the scans read the snapshots of the regions as of, see
+    /// [Table::scan_as_of].
+    as_of: Option<i64>,
 }
 
 #[async_trait]
@@ -83,6 +108,7 @@ impl Table for DistTable {
     async fn insert(&self, request: InsertRequest) -> table::Result<usize> {
         meter_insert_request!(request);
 
+        let write_mode = request.write_mode;
         let splits = self
             .partition_manager
             .split_insert_request(&self.table_name, request)
@@ -90,6 +116,11 @@ impl Table for DistTable {
             .map_err(BoxedError::new)
             .context(TableOperationSnafu)?;
 
+        self.check_split_regions(splits.keys())
+            .await
+            .map_err(BoxedError::new)
+            .context(TableOperationSnafu)?;
+
         let inserts = splits
             .into_iter()
             .map(|(region_number, insert)| to_grpc_insert_request(region_number, insert))
@@ -98,7 +129,7 @@ impl Table for DistTable {
             .context(TableOperationSnafu)?;
 
         let output = self
-            .dist_insert(inserts)
+            .dist_insert(inserts, write_mode)
             .await
             .map_err(BoxedError::new)
             .context(TableOperationSnafu)?;
@@ -112,49 +143,34 @@ impl Table for DistTable {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> table::Result<PhysicalPlanRef> {
-        let partition_rule = self
-            .partition_manager
-            .find_table_partition_rule(&self.table_name)
-            .await
-            .map_err(BoxedError::new)
-            .context(TableOperationSnafu)?;
-
-        let regions = self
-            .partition_manager
-            .find_regions_by_filters(partition_rule, filters)
-            .map_err(BoxedError::new)
-            .context(TableOperationSnafu)?;
-        let datanodes = self
-            .partition_manager
-            .find_region_datanodes(&self.table_name, regions)
-            .await
-            .map_err(BoxedError::new)
-            .context(TableOperationSnafu)?;
-
-        let table_name = &self.table_name;
-        let mut partition_execs = Vec::with_capacity(datanodes.len());
-        for (datanode, _regions) in datanodes.iter() {
-            let client = self.datanode_clients.get_client(datanode).await;
-            let db = Database::new(&table_name.catalog_name, &table_name.schema_name, client);
-            let datanode_instance = DatanodeInstance::new(Arc::new(self.clone()) as _, db);
-
-            partition_execs.push(Arc::new(PartitionExec {
-                table_name: table_name.clone(),
-                datanode_instance,
-                projection: projection.cloned(),
-                filters: filters.to_vec(),
-                limit,
-                batches: Arc::new(RwLock::new(None)),
-            }));
-        }
+        let schema = project_schema(self.schema(), projection);
+        let partition_execs = self
+            .partition_execs(schema.clone(), projection, filters, limit, None, None)
+            .await?;
 
         let dist_scan = DistTableScan {
-            schema: project_schema(self.schema(), projection),
+            schema,
             partition_execs,
         };
         Ok(Arc::new(dist_scan))
     }
 
+    fn supports_time_travel(&self) -> bool {
+        true
+    }
+
+    async fn scan_as_of(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+        as_of: i64,
+    ) -> table::Result<PhysicalPlanRef> {
+        let mut table = self.clone();
+        table.as_of = Some(as_of);
+        table.scan(projection, filters, limit).await
+    }
+
     fn supports_filters_pushdown(
         &self,
         filters: &[&Expr],
@@ -162,6 +178,69 @@ impl Table for DistTable {
         Ok(vec![FilterPushDownType::Inexact; filters.len()])
     }
 
+    fn supports_aggregate_pushdown(&self) -> bool {
+        true
+    }
+
+    // The partial UDAFs are carried by the substrait plans sent to datanodes, which resolve
+    // them in their function registry.
+    fn supports_udaf_pushdown(&self) -> bool {
+        true
+    }
+
+    async fn scan_partial_aggregate(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        aggregate: &PartialAggregate,
+    ) -> table::Result<PhysicalPlanRef> {
+        let partition_execs = self
+            .partition_execs(
+                aggregate.schema.clone(),
+                projection,
+                filters,
+                None,
+                Some(aggregate.clone()),
+                None,
+            )
+            .await?;
+
+        let dist_scan = DistTableScan {
+            schema: aggregate.schema.clone(),
+            partition_execs,
+        };
+        Ok(Arc::new(dist_scan))
+    }
+
+    fn supports_topk_pushdown(&self) -> bool {
+        true
+    }
+
+    async fn scan_topk(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        topk: &PartialTopK,
+    ) -> table::Result<PhysicalPlanRef> {
+        let schema = project_schema(self.schema(), projection);
+        let partition_execs = self
+            .partition_execs(
+                schema.clone(),
+                projection,
+                filters,
+                None,
+                None,
+                Some(topk.clone()),
+            )
+            .await?;
+
+        let dist_scan = DistTableScan {
+            schema,
+            partition_execs,
+        };
+        Ok(Arc::new(dist_scan))
+    }
+
     async fn alter(&self, context: AlterContext, request: &AlterTableRequest) -> table::Result<()> {
         self.handle_alter(context, request)
             .await
@@ -214,6 +293,20 @@ impl Table for DistTable {
         let Output::AffectedRows(rows) = output else { unreachable!() };
         Ok(rows)
     }
+
+    fn commit_token(&self) -> table::Result<CommitToken> {
+        Ok(self
+            .commit_token
+            .as_ref()
+            .map(|token| token.lock().unwrap().clone())
+            .unwrap_or_default())
+    }
+
+    fn with_commit_token(&self, commit_token: CommitToken) -> Option<TableRef> {
+        let mut table = self.clone();
+        table.commit_token = Some(Arc::new(Mutex::new(commit_token)));
+        Some(Arc::new(table))
+    }
 }
 
 impl DistTable {
@@ -230,7 +323,135 @@ impl DistTable {
             partition_manager,
             datanode_clients,
             backend,
+            commit_token: None,
+            as_of: None,
+        }
+    }
+
+    /// Creates the client of a datanode, which carries the commit token of the session and
+    /// the time the scans read the snapshots as of.
+    fn datanode_database(&self, client: Client) -> Database {
+        let mut db = Database::new(
+            &self.table_name.catalog_name,
+            &self.table_name.schema_name,
+            client,
+        );
+        if let Some(token) = &self.commit_token {
+            db.set_commit_token(token.lock().unwrap().clone());
+        }
+        db.set_as_of(self.as_of);
+        db
+    }
+
+    /// Records the writes accepted by a datanode in the commit token of the session.
+    fn record_commit_token(&self, token: &CommitToken) {
+        if let Some(commit_token) = &self.commit_token {
+            commit_token.lock().unwrap().merge(token);
+        }
+    }
+
+    /// Creates one [PartitionExec] for each datanode that holds the regions to be scanned.
+    /// The results of the datanodes are aligned to `schema`, the output schema of the scan.
+    async fn partition_execs(
+        &self,
+        schema: SchemaRef,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+        aggregate: Option<PartialAggregate>,
+        topk: Option<PartialTopK>,
+    ) -> table::Result<Vec<Arc<PartitionExec>>> {
+        let partition_rule = self
+            .partition_manager
+            .find_table_partition_rule(&self.table_name)
+            .await
+            .map_err(BoxedError::new)
+            .context(TableOperationSnafu)?;
+
+        let regions = self
+            .partition_manager
+            .find_regions_by_filters(partition_rule, filters)
+            .map_err(BoxedError::new)
+            .context(TableOperationSnafu)?;
+        let datanodes = self
+            .partition_manager
+            .find_region_datanodes(&self.table_name, regions)
+            .await
+            .map_err(BoxedError::new)
+            .context(TableOperationSnafu)?;
+
+        let table_name = &self.table_name;
+        let mut partition_execs = Vec::with_capacity(datanodes.len());
+        for (datanode, _regions) in datanodes.iter() {
+            let client = self.datanode_clients.get_client(datanode).await;
+            let db = self.datanode_database(client);
+            let datanode_instance = DatanodeInstance::new(Arc::new(self.clone()) as _, db);
+
+            partition_execs.push(Arc::new(PartitionExec {
+                table_name: table_name.clone(),
+                schema: schema.clone(),
+                datanode_instance,
+                projection: projection.cloned(),
+                filters: filters.to_vec(),
+                limit,
+                aggregate: aggregate.clone(),
+                topk: topk.clone(),
+                batches: Arc::new(RwLock::new(None)),
+            }));
         }
+        Ok(partition_execs)
+    }
+
+    /// Returns the value range of each region, ordered by the bounds.
+    pub(crate) async fn partition_bounds(&self) -> Result<Vec<(RegionNumber, String)>> {
+        let partitions = self
+            .partition_manager
+            .find_table_partitions(&self.table_name)
+            .await
+            .context(error::FindTablePartitionRuleSnafu {
+                table_name: self.table_name.to_string(),
+            })?;
+        Ok(partitions
+            .iter()
+            .map(|info| (info.id as RegionNumber, format_bounds(&info.partition)))
+            .collect())
+    }
+
+    /// Describes the regions in `rows`, which are pairs of the region and the number of rows
+    /// routed to it.
+    async fn region_targets(
+        &self,
+        rows: Vec<(RegionNumber, Option<usize>)>,
+    ) -> Result<Vec<RegionTarget>> {
+        let bounds = self
+            .partition_bounds()
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let regions = rows.iter().map(|(region, _)| *region).collect();
+        let datanodes = self
+            .partition_manager
+            .find_region_datanodes(&self.table_name, regions)
+            .await
+            .context(FindTableRouteSnafu {
+                table_name: self.table_name.to_string(),
+            })?
+            .into_iter()
+            .flat_map(|(peer, regions)| regions.into_iter().map(move |region| (region, peer.id)))
+            .collect::<HashMap<_, _>>();
+
+        let mut targets = rows
+            .into_iter()
+            .map(|(region, rows)| RegionTarget {
+                region,
+                bounds: bounds.get(&region).cloned().unwrap_or_default(),
+                // Safety: `find_region_datanodes` fails if the datanode of any region is not found.
+                datanode: datanodes[&region],
+                rows,
+            })
+            .collect::<Vec<_>>();
+        targets.sort_by_key(|target| target.region);
+        Ok(targets)
     }
 
     pub(crate) async fn table_global_value(
@@ -310,8 +531,40 @@ impl DistTable {
                 schema_name: alter_expr.schema_name.clone(),
                 table_name: new_table_name.clone(),
             };
-            self.set_table_global_value(new_key, value).await?;
-            self.delete_table_global_value(key).await
+            // The route is put under the new name before the rename commits and removed from
+            // the old name after, so an insert finding the table by either name during the
+            // rename is routed to its regions, and one by the old name after is TableNotFound.
+            let table_id = table_info.ident.table_id as u64;
+            let table_routes = self.partition_manager.table_routes();
+            let context = || error::RenameTableRouteSnafu {
+                table_name: self.table_name.to_string(),
+                new_table_name: new_table_name.clone(),
+            };
+            table_routes
+                .rename_table_route(table_id, &self.table_name, new_table_name)
+                .await
+                .with_context(|_| context())?;
+
+            if let Err(e) = self.set_table_global_value(new_key, value).await {
+                // The table is not renamed, so the route put under the new name is removed,
+                // for the name not to be routed to the regions of the table.
+                if let Err(rollback_err) = table_routes
+                    .rollback_renamed_table_route(table_id, &self.table_name, new_table_name)
+                    .await
+                {
+                    warn!(
+                        "Failed to rollback the route of table {} renamed to {}, error: {}",
+                        self.table_name, new_table_name, rollback_err
+                    );
+                }
+                return Err(e);
+            }
+            self.delete_table_global_value(key).await?;
+
+            table_routes
+                .remove_renamed_table_route(table_id, &self.table_name)
+                .await
+                .with_context(|_| context())
         } else {
             self.set_table_global_value(key, value).await
         }
@@ -351,6 +604,49 @@ impl DistTable {
         Ok(())
     }
 
+    /// Checks the `regions` the rows of an insert are split to are the regions in the route of
+    /// the table, so a stale partition rule never sends rows to a region the table doesn't have.
+    ///
+    /// The route may change between splitting the rows and checking them, e.g. the table is
+    /// repartitioned meanwhile, so a mismatch is rejected as an error and counted in the
+    /// metrics.
+    async fn check_split_regions(
+        &self,
+        regions: impl Iterator<Item = &RegionNumber>,
+    ) -> Result<()> {
+        let table_name = &self.table_name;
+        let route = self
+            .partition_manager
+            .find_table_route(table_name)
+            .await
+            .with_context(|_| FindTableRouteSnafu {
+                table_name: table_name.to_string(),
+            })?;
+
+        for region_number in regions {
+            let valid = route
+                .region_routes
+                .iter()
+                .any(|x| x.region.id == *region_number as u64);
+            if !valid {
+                increment_counter!(crate::metrics::DIST_INSERT_REGION_MISMATCH);
+            }
+            ensure!(
+                valid,
+                InvalidRegionNumberSnafu {
+                    table_name: table_name.to_string(),
+                    region_number: *region_number,
+                    regions: route
+                        .region_routes
+                        .iter()
+                        .map(|x| x.region.id as RegionNumber)
+                        .collect::<Vec<_>>(),
+                }
+            );
+        }
+        Ok(())
+    }
+
     async fn find_datanode_instances(
         &self,
         regions: &[RegionNumber],
@@ -385,13 +681,29 @@ impl DistTable {
         let mut instances = Vec::with_capacity(datanodes.len());
         for datanode in datanodes {
             let client = self.datanode_clients.get_client(&datanode).await;
-            let db = Database::new(&table_name.catalog_name, &table_name.schema_name, client);
+            let db = self.datanode_database(client);
             instances.push(DatanodeInstance::new(Arc::new(self.clone()) as _, db));
         }
         Ok(instances)
     }
 }
 
+fn format_bounds(partition: &PartitionDef) -> String {
+    let values = partition
+        .partition_bounds()
+        .iter()
+        .map(|bound| match bound {
+            PartitionBound::Value(Value::String(v)) => format!("'{}'", v.as_utf8()),
+            PartitionBound::Value(v) => v.to_string(),
+            PartitionBound::MaxValue => "MAXVALUE".to_string(),
+        })
+        .join(", ");
+    format!(
+        "({}) VALUES LESS THAN ({values})",
+        partition.partition_columns().join(", ")
+    )
+}
+
 fn project_schema(table_schema: SchemaRef, projection: Option<&Vec<usize>>) -> SchemaRef {
     if let Some(projection) = projection {
         let columns = table_schema.column_schemas();
@@ -452,10 +764,14 @@ impl PhysicalPlan for DistTableScan {
 #[derive(Debug)]
 struct PartitionExec {
     table_name: TableName,
+    /// The schema the frontend plan expects from this partition.
+    schema: SchemaRef,
     datanode_instance: DatanodeInstance,
     projection: Option<Vec<usize>>,
     filters: Vec<Expr>,
     limit: Option<usize>,
+    aggregate: Option<PartialAggregate>,
+    topk: Option<PartialTopK>,
     batches: Arc<RwLock<Option<RecordBatches>>>,
 }
 
@@ -475,8 +791,11 @@ impl PartitionExec {
             projection: self.projection.clone(),
             filters: self.filters.clone(),
             limit: self.limit,
+            aggregate: self.aggregate.clone(),
+            topk: self.topk.clone(),
         };
         let result = self.datanode_instance.grpc_table_scan(plan).await?;
+        let result = align_batches(&self.table_name, &self.schema, result)?;
         let _ = batches.insert(result);
         Ok(())
     }
@@ -491,6 +810,78 @@ impl PartitionExec {
     }
 }
 
+/// Aligns the batches returned by a datanode to the `expected` schema of the frontend plan.
+///
+/// Columns are matched by name, or by position when the datanode names them differently,
+/// e.g. the aggregations qualified by another table alias. The batches are rebuilt with the
+/// `expected` schema so the column order and names of the frontend plan always win.
+fn align_batches(
+    table_name: &TableName,
+    expected: &SchemaRef,
+    batches: RecordBatches,
+) -> Result<RecordBatches> {
+    let actual = batches.schema();
+    if actual == *expected {
+        return Ok(batches);
+    }
+
+    let actual_columns = actual.column_schemas();
+    // The expected column each actual column is matched to.
+    let mut matched_by: Vec<Option<&str>> = vec![None; actual_columns.len()];
+    let mut indices = Vec::with_capacity(expected.num_columns());
+    for (i, column) in expected.column_schemas().iter().enumerate() {
+        let index = actual
+            .column_index_by_name(&column.name)
+            .or_else(|| (actual.num_columns() == expected.num_columns()).then_some(i))
+            .with_context(|| error::IncompatibleDatanodeSchemaSnafu {
+                table_name: table_name.to_string(),
+                reason: format!(
+                    "column {} not found in [{}]",
+                    column.name,
+                    actual_columns.iter().map(|c| &c.name).join(", ")
+                ),
+            })?;
+        // A column matched by position may be the one another column is matched to by name.
+        if let Some(other) = matched_by[index] {
+            return error::IncompatibleDatanodeSchemaSnafu {
+                table_name: table_name.to_string(),
+                reason: format!(
+                    "columns {other} and {} both match column {}",
+                    column.name, actual_columns[index].name
+                ),
+            }
+            .fail();
+        }
+        matched_by[index] = Some(&column.name);
+
+        let actual_type = &actual_columns[index].data_type;
+        ensure!(
+            *actual_type == column.data_type,
+            error::IncompatibleDatanodeSchemaSnafu {
+                table_name: table_name.to_string(),
+                reason: format!(
+                    "column {} is {:?}, expected {:?}",
+                    column.name, actual_type, column.data_type
+                ),
+            }
+        );
+        indices.push(index);
+    }
+
+    let batches = batches
+        .take()
+        .into_iter()
+        .map(|batch| {
+            RecordBatch::new(
+                expected.clone(),
+                indices.iter().map(|i| batch.column(*i).clone()),
+            )
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context(error::AlignRecordBatchesSnafu)?;
+    RecordBatches::try_new(expected.clone(), batches).context(error::AlignRecordBatchesSnafu)
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
@@ -499,7 +890,7 @@ mod test {
     use api::v1::column::SemanticType;
     use api::v1::{column, Column, ColumnDataType, InsertRequest as GrpcInsertRequest};
     use catalog::error::Result;
-    use catalog::remote::{KvBackend, ValueIter};
+    use catalog::remote::{CachedMetaKvBackend, Kv, KvBackend, KvCacheInvalidator, ValueIter};
     use common_query::physical_plan::DfPhysicalPlanAdapter;
     use common_query::DfPhysicalPlan;
     use common_recordbatch::adapter::RecordBatchStreamAdapter;
@@ -514,10 +905,13 @@ mod test {
     use datatypes::arrow::compute::SortOptions;
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{Int32Vector, VectorRef};
     use itertools::Itertools;
-    use meta_client::client::MetaClient;
+    use meta_client::client::{MetaClient, MetaClientBuilder};
     use meta_client::rpc::router::RegionRoute;
-    use meta_client::rpc::{Region, Table, TableRoute};
+    use meta_client::rpc::util::get_prefix_end_key;
+    use meta_client::rpc::{PutRequest, Region, Table, TableRoute};
+    use meta_srv::mocks::MockInfo;
     use meter_core::collect::Collect;
     use meter_core::data::{ReadRecord, WriteRecord};
     use meter_core::global::global_registry;
@@ -533,6 +927,7 @@ mod test {
     use sql::statements::statement::Statement;
     use store_api::storage::RegionNumber;
     use table::metadata::{TableInfoBuilder, TableMetaBuilder};
+    use table::predicate::MAX_PRUNING_DISJUNCTS;
     use table::{meter_insert_request, TableRef};
 
     use super::*;
@@ -905,6 +1300,28 @@ mod test {
         assert_eq!(recordbatches.pretty_print().unwrap(), expected_output);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_check_split_regions() {
+        let table = new_dist_table("test_check_split_regions").await;
+        table
+            .check_split_regions([0, 1, 2, 3].iter())
+            .await
+            .unwrap();
+
+        // The rows split by a stale partition rule are rejected.
+        let err = table.check_split_regions([1, 4].iter()).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::InvalidRegionNumber {
+                    region_number: 4,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
+
     async fn new_dist_table(test_name: &str) -> DistTable {
         let column_schemas = vec![
             ColumnSchema::new("ts", ConcreteDataType::int64_datatype(), false),
@@ -1007,6 +1424,8 @@ mod test {
             partition_manager,
             datanode_clients,
             backend: catalog_manager.backend(),
+            commit_token: None,
+            as_of: None,
         }
     }
 
@@ -1165,6 +1584,27 @@ mod test {
             vec![0, 1],
         );
 
+        // test "IN" and "BETWEEN" filters
+        test(
+            vec![col("a").in_list(vec![lit(5), lit(45)], false).into()], // a IN (5, 45)
+            vec![0, 2],
+        );
+        test(
+            vec![col("a").in_list(vec![lit(5), lit(45)], true).into()], // a NOT IN (5, 45)
+            vec![0, 1, 2, 3],
+        );
+        test(
+            vec![col("a").between(lit(12), lit(30)).into()], // a BETWEEN 12 AND 30
+            vec![1, 2],
+        );
+        // test finding all regions when the "IN" list is too long
+        test(
+            vec![col("a")
+                .in_list(vec![lit(5); MAX_PRUNING_DISJUNCTS + 1], false)
+                .into()],
+            vec![0, 1, 2, 3],
+        );
+
         // test failed to find regions by contradictory filters
         let regions = partition_manager.find_regions_by_filters(
             partition_rule,
@@ -1181,6 +1621,93 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_align_batches() {
+        let table_name = TableName::new("greptime", "public", "dist_numbers");
+        let datanode_schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("row_id", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("a", ConcreteDataType::int32_datatype(), true),
+        ]));
+        let batches = || {
+            RecordBatches::try_from_columns(
+                datanode_schema.clone(),
+                vec![
+                    Arc::new(Int32Vector::from_slice([1, 2])) as VectorRef,
+                    Arc::new(Int32Vector::from_slice([10, 20])) as VectorRef,
+                ],
+            )
+            .unwrap()
+        };
+
+        // Reordered columns are matched by name.
+        let expected = Arc::new(Schema::new(vec![
+            ColumnSchema::new("a", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("row_id", ConcreteDataType::int32_datatype(), true),
+        ]));
+        let aligned = align_batches(&table_name, &expected, batches()).unwrap();
+        assert_eq!(aligned.schema(), expected);
+        let expected_output = "\
++----+--------+
+| a  | row_id |
++----+--------+
+| 10 | 1      |
+| 20 | 2      |
++----+--------+";
+        assert_eq!(aligned.pretty_print().unwrap(), expected_output);
+
+        // Columns named differently by the datanode are matched by position.
+        let expected = Arc::new(Schema::new(vec![
+            ColumnSchema::new("x", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("a", ConcreteDataType::int32_datatype(), true),
+        ]));
+        let aligned = align_batches(&table_name, &expected, batches()).unwrap();
+        assert_eq!(aligned.schema(), expected);
+        let expected_output = "\
++---+----+
+| x | a  |
++---+----+
+| 1 | 10 |
+| 2 | 20 |
++---+----+";
+        assert_eq!(aligned.pretty_print().unwrap(), expected_output);
+
+        // A datanode of another version may return a different schema.
+        let expected = Arc::new(Schema::new(vec![
+            ColumnSchema::new("a", ConcreteDataType::int64_datatype(), true),
+            ColumnSchema::new("row_id", ConcreteDataType::int32_datatype(), true),
+        ]));
+        let err = align_batches(&table_name, &expected, batches()).unwrap_err();
+        assert!(
+            matches!(err, error::Error::IncompatibleDatanodeSchema { .. }),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("column a is"), "{err}");
+
+        let expected = Arc::new(Schema::new(vec![
+            ColumnSchema::new("a", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("b", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("row_id", ConcreteDataType::int32_datatype(), true),
+        ]));
+        let err = align_batches(&table_name, &expected, batches()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("column b not found in [row_id, a]"),
+            "{err}"
+        );
+
+        // A column matched by position can't be the one another column is matched to by name.
+        let expected = Arc::new(Schema::new(vec![
+            ColumnSchema::new("x", ConcreteDataType::int32_datatype(), true),
+            ColumnSchema::new("row_id", ConcreteDataType::int32_datatype(), true),
+        ]));
+        let err = align_batches(&table_name, &expected, batches()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("columns x and row_id both match column row_id"),
+            "{err}"
+        );
+    }
+
     #[derive(Default)]
     struct MockCollector {
         pub write_sum: AtomicU32,
@@ -1218,10 +1745,82 @@ mod test {
             table_name: "numbers".to_string(),
             columns_values: Default::default(),
             region_number: 0,
+            write_mode: Default::default(),
         };
         meter_insert_request!(req);
 
         let re = collector.write_sum.load(Ordering::Relaxed);
         assert_eq!(re, 1024 * 10);
     }
+
+    #[tokio::test]
+    async fn test_invalidated_values_not_cached() {
+        let MockInfo {
+            server_addr,
+            channel_manager,
+        } = meta_srv::mocks::mock_with_memstore().await;
+        let mut meta_client = MetaClientBuilder::new(1000, 0)
+            .enable_store()
+            .channel_manager(channel_manager)
+            .build();
+        meta_client.start(&[&server_addr]).await.unwrap();
+        let meta_client = Arc::new(meta_client);
+        let backend = CachedMetaKvBackend::new(meta_client.clone());
+
+        let key = TableGlobalKey {
+            catalog_name: "greptime".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "test".to_string(),
+        }
+        .to_string()
+        .into_bytes();
+        // Puts the value bypassing the cache of the backend, like another frontend.
+        let put = |value: &str| {
+            let req = PutRequest::new()
+                .with_key(key.clone())
+                .with_value(value.as_bytes().to_vec());
+            let meta_client = meta_client.clone();
+            async move {
+                let _ = meta_client.put(req).await.unwrap();
+            }
+        };
+
+        put("v1").await;
+        let kv = backend.get(&key).await.unwrap().unwrap();
+        backend.invalidate_key(&key).await;
+
+        // The value fetched before the key is invalidated may be stale.
+        let invalidations = backend.invalidations();
+        backend.invalidate_key(&key).await;
+        backend.insert_cache([kv.clone()], invalidations).await;
+        put("v2").await;
+        let Kv(_, value) = backend.get(&key).await.unwrap().unwrap();
+        assert_eq!(b"v2".to_vec(), value);
+
+        // Invalidating other keys doesn't prevent caching the values fetched before.
+        backend.invalidate_key(&key).await;
+        let other_key = b"__s-greptime-other".to_vec();
+        let other_kv = Kv(other_key.clone(), b"other".to_vec());
+        let invalidations = backend.invalidations();
+        backend.invalidate_key(&other_key).await;
+        backend
+            .insert_cache([kv.clone(), other_kv], invalidations)
+            .await;
+        put("v3").await;
+        let Kv(_, value) = backend.get(&key).await.unwrap().unwrap();
+        assert_eq!(b"v1".to_vec(), value);
+        // The other key is absent in metasrv, so it's only got from the cache if cached.
+        assert!(backend.get(&other_key).await.unwrap().is_none());
+
+        // Invalidating a range invalidates all keys.
+        backend.invalidate_key(&key).await;
+        let invalidations = backend.invalidations();
+        backend
+            .delete_range(b"__s-", &get_prefix_end_key(b"__s-"))
+            .await
+            .unwrap();
+        backend.insert_cache([kv], invalidations).await;
+        let Kv(_, value) = backend.get(&key).await.unwrap().unwrap();
+        assert_eq!(b"v3".to_vec(), value);
+    }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use api::v1::DeleteRequest as GrpcDeleteRequest;
+use common_query::logical_plan::Expr;
 use common_query::Output;
 use futures::future;
 use meta_client::rpc::TableName;
@@ -20,9 +21,9 @@ use snafu::ResultExt;
 use store_api::storage::RegionNumber;
 use table::requests::DeleteRequest;
 
-use crate::error::{JoinTaskSnafu, RequestDatanodeSnafu, Result};
+use crate::error::{self, JoinTaskSnafu, RequestDatanodeSnafu, Result};
 use crate::table::insert::to_grpc_columns;
-use crate::table::DistTable;
+use crate::table::{DistTable, RegionTarget};
 
 impl DistTable {
     pub(super) async fn dist_delete(&self, requests: Vec<GrpcDeleteRequest>) -> Result<Output> {
@@ -32,19 +33,45 @@ impl DistTable {
         let results = future::try_join_all(instances.into_iter().zip(requests.into_iter()).map(
             |(instance, request)| {
                 common_runtime::spawn_write(async move {
-                    instance
+                    let rows = instance
                         .grpc_delete(request)
                         .await
-                        .context(RequestDatanodeSnafu)
+                        .context(RequestDatanodeSnafu)?;
+                    Ok::<_, error::Error>((rows, instance.commit_token()))
                 })
             },
         ))
         .await
         .context(JoinTaskSnafu)?;
 
-        let affected_rows = results.into_iter().sum::<Result<u32>>()?;
+        let mut affected_rows = 0;
+        for result in results {
+            let (rows, token) = result?;
+            self.record_commit_token(&token);
+            affected_rows += rows;
+        }
         Ok(Output::AffectedRows(affected_rows as _))
     }
+
+    /// Returns the regions a delete with the `filters` is routed to, without deleting.
+    ///
+    /// The number of rows is unknown, as the rows to delete are found by scanning the regions.
+    pub(crate) async fn route_delete(&self, filters: &[Expr]) -> Result<Vec<RegionTarget>> {
+        let table_name = self.table_name.to_string();
+        let partition_rule = self
+            .partition_manager
+            .find_table_partition_rule(&self.table_name)
+            .await
+            .context(error::FindTablePartitionRuleSnafu {
+                table_name: &table_name,
+            })?;
+        let regions = self
+            .partition_manager
+            .find_regions_by_filters(partition_rule, filters)
+            .context(error::FindTablePartitionRuleSnafu { table_name })?;
+        self.region_targets(regions.into_iter().map(|region| (region, None)).collect())
+            .await
+    }
 }
 
 pub(super) fn to_grpc_delete_request(
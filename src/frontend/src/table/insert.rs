@@ -22,33 +22,67 @@ use datatypes::prelude::{ConcreteDataType, VectorRef};
 use futures::future;
 use snafu::{ensure, ResultExt};
 use store_api::storage::RegionNumber;
-use table::requests::InsertRequest;
+use table::requests::{InsertRequest, WriteMode};
 
-use super::DistTable;
+use super::{DistTable, RegionTarget};
 use crate::error;
 use crate::error::{JoinTaskSnafu, RequestDatanodeSnafu, Result};
 
 impl DistTable {
-    pub async fn dist_insert(&self, inserts: Vec<GrpcInsertRequest>) -> Result<Output> {
+    /// Sends the `inserts` to the datanodes of their regions, which write the existing rows as
+    /// `write_mode` says.
+    pub async fn dist_insert(
+        &self,
+        inserts: Vec<GrpcInsertRequest>,
+        write_mode: WriteMode,
+    ) -> Result<Output> {
         let regions = inserts.iter().map(|x| x.region_number).collect::<Vec<_>>();
         let instances = self.find_datanode_instances(&regions).await?;
 
         let results = future::try_join_all(instances.into_iter().zip(inserts.into_iter()).map(
             |(instance, request)| {
                 common_runtime::spawn_write(async move {
-                    instance
-                        .grpc_insert(request)
+                    let rows = instance
+                        .grpc_insert(request, write_mode)
                         .await
-                        .context(RequestDatanodeSnafu)
+                        .context(RequestDatanodeSnafu)?;
+                    Ok::<_, error::Error>((rows, instance.commit_token()))
                 })
             },
         ))
         .await
         .context(JoinTaskSnafu)?;
 
-        let affected_rows = results.into_iter().sum::<Result<u32>>()?;
+        let mut affected_rows = 0;
+        for result in results {
+            let (rows, token) = result?;
+            self.record_commit_token(&token);
+            affected_rows += rows;
+        }
         Ok(Output::AffectedRows(affected_rows as _))
     }
+
+    /// Splits the `request` by the partition rule like [Table::insert], but only returns the
+    /// regions the rows are routed to, without writing them.
+    ///
+    /// [Table::insert]: table::Table::insert
+    pub(crate) async fn route_insert(&self, request: InsertRequest) -> Result<Vec<RegionTarget>> {
+        let splits = self
+            .partition_manager
+            .split_insert_request(&self.table_name, request)
+            .await
+            .context(error::FindTablePartitionRuleSnafu {
+                table_name: self.table_name.to_string(),
+            })?;
+        let rows = splits
+            .iter()
+            .map(|(region, insert)| {
+                let rows = insert.columns_values.values().next().map(|v| v.len());
+                (*region, Some(rows.unwrap_or(0)))
+            })
+            .collect();
+        self.region_targets(rows).await
+    }
 }
 
 pub fn insert_request_to_insert_batch(insert: &InsertRequest) -> Result<(Vec<Column>, u32)> {
@@ -160,6 +194,7 @@ mod tests {
             table_name: "demo".to_string(),
             columns_values,
             region_number: 0,
+            write_mode: Default::default(),
         }
     }
 
@@ -17,6 +17,7 @@ use std::sync::Arc;
 
 use api::v1::{DeleteRequest, InsertRequest};
 use client::Database;
+use common_base::commit_token::CommitToken;
 use common_query::prelude::Expr;
 use common_query::Output;
 use common_recordbatch::RecordBatches;
@@ -25,7 +26,9 @@ use datafusion_expr::{LogicalPlan, LogicalPlanBuilder};
 use meta_client::rpc::TableName;
 use snafu::ResultExt;
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
+use table::requests::WriteMode;
 use table::table::adapter::DfTableProviderAdapter;
+use table::table::{PartialAggregate, PartialTopK};
 use table::TableRef;
 
 use crate::error::{self, Result};
@@ -47,8 +50,19 @@ impl DatanodeInstance {
         Self { table, db }
     }
 
-    pub(crate) async fn grpc_insert(&self, request: InsertRequest) -> client::Result<u32> {
-        self.db.insert(request).await
+    pub(crate) async fn grpc_insert(
+        &self,
+        request: InsertRequest,
+        write_mode: WriteMode,
+    ) -> client::Result<u32> {
+        let mut db = self.db.clone();
+        db.set_write_mode(write_mode);
+        db.insert(request).await
+    }
+
+    /// Returns the commit token of the datanode, updated by the writes sent to it.
+    pub(crate) fn commit_token(&self) -> CommitToken {
+        self.db.commit_token()
     }
 
     pub(crate) async fn grpc_delete(&self, request: DeleteRequest) -> client::Result<u32> {
@@ -98,6 +112,23 @@ impl DatanodeInstance {
                 .context(error::BuildDfLogicalPlanSnafu)?;
         }
 
+        if let Some(aggregate) = &table_scan.aggregate {
+            builder = builder
+                .aggregate(
+                    aggregate.group_expr.iter().map(|x| x.df_expr().clone()),
+                    aggregate.aggr_expr.iter().map(|x| x.df_expr().clone()),
+                )
+                .context(error::BuildDfLogicalPlanSnafu)?;
+        }
+
+        if let Some(topk) = &table_scan.topk {
+            builder = builder
+                .sort(topk.sort_expr.iter().map(|x| x.df_expr().clone()))
+                .context(error::BuildDfLogicalPlanSnafu)?
+                .limit(0, Some(topk.fetch))
+                .context(error::BuildDfLogicalPlanSnafu)?;
+        }
+
         if table_scan.limit.is_some() {
             builder = builder
                 .limit(0, table_scan.limit)
@@ -114,4 +145,12 @@ pub(crate) struct TableScanPlan {
     pub projection: Option<Vec<usize>>,
     pub filters: Vec<Expr>,
     pub limit: Option<usize>,
+    /// Partial aggregation evaluated on the datanode, see [Table::scan_partial_aggregate].
+    ///
+    /// [Table::scan_partial_aggregate]: table::Table::scan_partial_aggregate
+    pub aggregate: Option<PartialAggregate>,
+    /// Partial top-k evaluated on the datanode, see [Table::scan_topk].
+    ///
+    /// [Table::scan_topk]: table::Table::scan_topk
+    pub topk: Option<PartialTopK>,
 }
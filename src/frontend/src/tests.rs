@@ -21,7 +21,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use catalog::local::{MemoryCatalogProvider, MemorySchemaProvider};
-use catalog::remote::{MetaKvBackend, RemoteCatalogManager};
+use catalog::remote::{CachedMetaKvBackend, RemoteCatalogManager};
 use client::Client;
 use common_grpc::channel_manager::ChannelManager;
 use common_runtime::Builder as RuntimeBuilder;
@@ -30,7 +30,7 @@ use datanode::datanode::{
     DatanodeOptions, FileConfig, ObjectStoreConfig, ProcedureConfig, StorageConfig, WalConfig,
 };
 use datanode::instance::Instance as DatanodeInstance;
-use meta_client::client::MetaClientBuilder;
+use meta_client::client::{MetaClient, MetaClientBuilder};
 use meta_client::rpc::Peer;
 use meta_srv::metasrv::MetaSrvOptions;
 use meta_srv::mocks::MockInfo;
@@ -41,7 +41,9 @@ use partition::route::TableRoutes;
 use servers::grpc::GrpcServer;
 use servers::query_handler::grpc::ServerGrpcQueryHandlerAdaptor;
 use servers::Mode;
+use table::column_limits::ColumnLimitsOptions;
 use table::engine::{region_name, table_dir};
+use table::test_util::MemoryTableEngine;
 use tonic::transport::Server;
 use tower::service_fn;
 
@@ -49,6 +51,7 @@ use crate::catalog::FrontendCatalogManager;
 use crate::datanode::DatanodeClients;
 use crate::instance::distributed::DistInstance;
 use crate::instance::Instance;
+use crate::script::ScriptOptions;
 
 /// Guard against the `TempDir`s that used in unit tests.
 /// (The `TempDir` will be deleted once it goes out of scope.)
@@ -63,6 +66,8 @@ pub(crate) struct MockDistributedInstance {
     pub(crate) dist_instance: Arc<DistInstance>,
     pub(crate) datanodes: HashMap<u64, Arc<DatanodeInstance>>,
     pub(crate) catalog_manager: Arc<FrontendCatalogManager>,
+    meta_client: Arc<MetaClient>,
+    datanode_clients: Arc<DatanodeClients>,
     _guards: Vec<TestGuard>,
 }
 
@@ -70,6 +75,14 @@ impl MockDistributedInstance {
     pub fn data_tmp_dirs(&self) -> Vec<&TempDir> {
         self._guards.iter().map(|g| &g._data_tmp_dir).collect()
     }
+
+    /// Creates another frontend of the same cluster, with caches of its own.
+    pub(crate) async fn create_another_frontend(&self) -> Arc<Instance> {
+        let (frontend, _, _) =
+            create_distributed_frontend(self.meta_client.clone(), self.datanode_clients.clone())
+                .await;
+        frontend
+    }
 }
 
 pub(crate) struct MockStandaloneInstance {
@@ -86,9 +99,40 @@ impl MockStandaloneInstance {
 pub(crate) async fn create_standalone_instance(test_name: &str) -> MockStandaloneInstance {
     let (opts, guard) = create_tmp_dir_and_datanode_opts(test_name);
     let dn_instance = Arc::new(DatanodeInstance::new(&opts).await.unwrap());
-    let frontend_instance = Instance::try_new_standalone(dn_instance.clone())
-        .await
-        .unwrap();
+    start_standalone_instance(dn_instance, guard).await
+}
+
+/// Creates a standalone instance keeping all the tables in the returned [MemoryTableEngine],
+/// for the tests that don't care how the tables are stored.
+pub(crate) async fn create_memory_standalone_instance(
+    test_name: &str,
+) -> (MockStandaloneInstance, MemoryTableEngine) {
+    create_memory_standalone_instance_with_column_limits(test_name, Default::default()).await
+}
+
+pub(crate) async fn create_memory_standalone_instance_with_column_limits(
+    test_name: &str,
+    column_limits: ColumnLimitsOptions,
+) -> (MockStandaloneInstance, MemoryTableEngine) {
+    let (mut opts, guard) = create_tmp_dir_and_datanode_opts(test_name);
+    opts.column_limits = column_limits;
+    let engine = MemoryTableEngine::new();
+    let dn_instance = Arc::new(
+        DatanodeInstance::with_table_engine(&opts, Arc::new(engine.clone()))
+            .await
+            .unwrap(),
+    );
+    (start_standalone_instance(dn_instance, guard).await, engine)
+}
+
+async fn start_standalone_instance(
+    dn_instance: Arc<DatanodeInstance>,
+    guard: TestGuard,
+) -> MockStandaloneInstance {
+    let frontend_instance =
+        Instance::try_new_standalone(dn_instance.clone(), &ScriptOptions::default())
+            .await
+            .unwrap();
 
     // create another catalog and schema for testing
     let another_catalog = Arc::new(MemoryCatalogProvider::new());
@@ -300,26 +344,51 @@ pub(crate) async fn create_distributed_instance(test_name: &str) -> MockDistribu
     let mut meta_client = MetaClientBuilder::new(1000, 0)
         .enable_router()
         .enable_store()
+        .enable_lock()
         .channel_manager(channel_manager)
         .build();
     meta_client.start(&[&server_addr]).await.unwrap();
     let meta_client = Arc::new(meta_client);
 
-    let meta_backend = Arc::new(MetaKvBackend {
-        client: meta_client.clone(),
-    });
+    wait_datanodes_alive(kv_store).await;
+
+    let (frontend, dist_instance, catalog_manager) =
+        create_distributed_frontend(meta_client.clone(), datanode_clients.clone()).await;
+
+    MockDistributedInstance {
+        frontend,
+        dist_instance,
+        datanodes: datanode_instances,
+        catalog_manager,
+        meta_client,
+        datanode_clients,
+        _guards: test_guards,
+    }
+}
+
+async fn create_distributed_frontend(
+    meta_client: Arc<MetaClient>,
+    datanode_clients: Arc<DatanodeClients>,
+) -> (
+    Arc<Instance>,
+    Arc<DistInstance>,
+    Arc<FrontendCatalogManager>,
+) {
+    let meta_backend = Arc::new(CachedMetaKvBackend::new(meta_client.clone()));
     let partition_manager = Arc::new(PartitionRuleManager::new(Arc::new(TableRoutes::new(
         meta_client.clone(),
     ))));
-    let mut catalog_manager =
-        FrontendCatalogManager::new(meta_backend, partition_manager, datanode_clients.clone());
-
-    wait_datanodes_alive(kv_store).await;
+    let mut catalog_manager = FrontendCatalogManager::new(
+        meta_backend.clone(),
+        meta_backend,
+        partition_manager,
+        datanode_clients.clone(),
+    );
 
     let dist_instance = DistInstance::new(
-        meta_client.clone(),
+        meta_client,
         Arc::new(catalog_manager.clone()),
-        datanode_clients.clone(),
+        datanode_clients,
     );
     let dist_instance = Arc::new(dist_instance);
 
@@ -327,14 +396,7 @@ pub(crate) async fn create_distributed_instance(test_name: &str) -> MockDistribu
     let catalog_manager = Arc::new(catalog_manager);
 
     let frontend = Instance::new_distributed(catalog_manager.clone(), dist_instance.clone()).await;
-
-    MockDistributedInstance {
-        frontend: Arc::new(frontend),
-        dist_instance,
-        datanodes: datanode_instances,
-        catalog_manager,
-        _guards: test_guards,
-    }
+    (Arc::new(frontend), dist_instance, catalog_manager)
 }
 
 pub fn test_region_dir(
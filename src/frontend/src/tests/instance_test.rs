@@ -14,16 +14,24 @@
 
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 
-use common_catalog::consts::DEFAULT_CATALOG_NAME;
+use common_base::commit_token::CommitToken;
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_query::Output;
-use common_recordbatch::util;
+use common_recordbatch::{util, RecordBatch, RecordBatches};
 use common_telemetry::logging;
+use common_test_util::temp_dir::create_temp_dir;
+use common_time::util::current_time_millis;
+use datafusion::parquet::arrow::ParquetRecordBatchStreamBuilder;
+use datatypes::schema::Schema;
+use datatypes::value::Value;
 use datatypes::vectors::{Int64Vector, StringVector, UInt64Vector, VectorRef};
+use futures_util::TryStreamExt;
 use rstest::rstest;
 use rstest_reuse::apply;
 use servers::query_handler::sql::SqlQueryHandler;
-use session::context::{QueryContext, QueryContextRef};
+use session::context::{QueryContext, QueryContextRef, QueryOrigin, UserInfo};
 
 use crate::error::{Error, Result};
 use crate::instance::Instance;
@@ -309,6 +317,167 @@ async fn test_execute_insert_by_select(instance: Arc<dyn MockInstance>) {
     check_output_stream(output, expected).await;
 }
 
+#[apply(both_instances_cases)]
+async fn test_read_your_writes(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    execute_sql(
+        &instance,
+        "create table ryw(host string, cpu double, ts timestamp time index);",
+    )
+    .await;
+    let query_ctx = QueryContext::arc();
+    let output = execute_sql_with(
+        &instance,
+        "insert into ryw(host, cpu, ts) values ('host1', 1.0, 1000)",
+        query_ctx.clone(),
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(1)));
+    // The writes are recorded in the commit token of the session, including the ones the
+    // frontend sends to the datanodes.
+    let token = query_ctx.commit_token();
+    assert!(!token.is_empty());
+
+    let output = execute_sql_with(&instance, "select host, cpu from ryw", query_ctx.clone()).await;
+    let expected = "\
++-------+-----+
+| host  | cpu |
++-------+-----+
+| host1 | 1.0 |
++-------+-----+";
+    check_output_stream(output, expected).await;
+
+    // Other sessions don't carry the writes of this session.
+    let other_ctx = QueryContext::arc();
+    let _ = execute_sql_with(&instance, "select host from ryw", other_ctx.clone()).await;
+    assert!(other_ctx.commit_token().is_empty());
+
+    // The scans wait for the writes in the token, and time out on the ones never applied.
+    let mut unapplied = CommitToken::default();
+    for (region_id, sequence) in token.iter() {
+        unapplied.record(region_id, sequence + 1_000_000);
+    }
+    let unapplied_ctx = QueryContext::arc();
+    unapplied_ctx.merge_commit_token(&unapplied);
+    let result = try_execute_sql_with(&instance, "select host from ryw", unapplied_ctx).await;
+    let result = match result {
+        Ok(Output::Stream(stream)) => util::collect(stream)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    };
+    let err = result.unwrap_err();
+    assert!(err.contains("Timeout waiting for region"), "{err}");
+}
+
+#[apply(both_instances_cases)]
+async fn test_execute_query_with_select_limit(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    execute_sql(
+        &instance,
+        "create table limit_src(host string, cpu double, ts timestamp time index);",
+    )
+    .await;
+    execute_sql(
+        &instance,
+        "create table limit_dst(host string, cpu double, ts timestamp time index);",
+    )
+    .await;
+    let output = execute_sql(
+        &instance,
+        r#"insert into limit_src(host, cpu, ts) values
+                           ('host1', 1.0, 1000),
+                           ('host2', 2.0, 2000),
+                           ('host3', 3.0, 3000),
+                           ('host4', 4.0, 4000),
+                           ('host5', 5.0, 5000)
+                           "#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(5)));
+
+    let query_ctx = QueryContext::arc();
+    query_ctx.set_select_limit(Some(3));
+
+    // The SELECT without a LIMIT is truncated, with a warning.
+    let output = execute_sql_with(
+        &instance,
+        "select host from limit_src order by ts",
+        query_ctx.clone(),
+    )
+    .await;
+    let expected = "\
++-------+
+| host  |
++-------+
+| host1 |
+| host2 |
+| host3 |
++-------+";
+    check_output_stream(output, expected).await;
+    let warnings = query_ctx.warnings();
+    assert_eq!(1, warnings.len());
+    assert!(warnings[0].contains("truncated to 3 rows"));
+
+    // The LIMIT of the query itself is honored, and the warnings of the last statement are
+    // cleared.
+    let output = execute_sql_with(
+        &instance,
+        "select host from limit_src order by ts limit 4",
+        query_ctx.clone(),
+    )
+    .await;
+    let expected = "\
++-------+
+| host  |
++-------+
+| host1 |
+| host2 |
+| host3 |
+| host4 |
++-------+";
+    check_output_stream(output, expected).await;
+    assert!(query_ctx.warnings().is_empty());
+
+    // No warning if all the rows are returned.
+    let output = execute_sql_with(
+        &instance,
+        "select host from limit_src where cpu > 3 order by ts",
+        query_ctx.clone(),
+    )
+    .await;
+    let expected = "\
++-------+
+| host  |
++-------+
+| host4 |
+| host5 |
++-------+";
+    check_output_stream(output, expected).await;
+    assert!(query_ctx.warnings().is_empty());
+
+    // INSERT ... SELECT isn't limited.
+    let output = execute_sql_with(
+        &instance,
+        "insert into limit_dst select * from limit_src",
+        query_ctx.clone(),
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(5)));
+    let output = execute_sql(&instance, "select count(*) from limit_dst").await;
+    let expected = "\
++-----------------+
+| COUNT(UInt8(1)) |
++-----------------+
+| 5               |
++-----------------+";
+    check_output_stream(output, expected).await;
+}
+
 #[apply(both_instances_cases)]
 async fn test_execute_insert_query_with_i64_timestamp(instance: Arc<dyn MockInstance>) {
     let instance = instance.frontend();
@@ -381,6 +550,246 @@ async fn test_execute_query(instance: Arc<dyn MockInstance>) {
     }
 }
 
+#[apply(both_instances_cases)]
+async fn test_execute_group_by_aggregate(instance: Arc<dyn MockInstance>) {
+    let is_distributed_mode = instance.is_distributed_mode();
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        r#"create table demo(
+            host string,
+            cpu double,
+            ts timestamp time index,
+            primary key(host)
+        )
+        partition by range columns (host) (
+            partition r0 values less than ('b'),
+            partition r1 values less than ('d'),
+            partition r2 values less than (maxvalue),
+        )"#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(0)));
+
+    let output = execute_sql(
+        &instance,
+        r#"insert into demo(host, cpu, ts) values
+            ('a1', 1.0, 1),
+            ('a1', 3.0, 2),
+            ('b1', 2.0, 3),
+            ('c1', null, 4),
+            ('e1', 5.0, 5),
+            ('e1', 7.0, 6)"#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(6)));
+
+    let sql = "select host, count(cpu) as c, sum(cpu) as s, min(cpu) as mi, max(cpu) as ma, avg(cpu) as av from demo group by host order by host";
+    let output = execute_sql(&instance, sql).await;
+    let expected = "\
++------+---+------+-----+-----+-----+
+| host | c | s    | mi  | ma  | av  |
++------+---+------+-----+-----+-----+
+| a1   | 2 | 4.0  | 1.0 | 3.0 | 2.0 |
+| b1   | 1 | 2.0  | 2.0 | 2.0 | 2.0 |
+| c1   | 0 |      |     |     |     |
+| e1   | 2 | 12.0 | 5.0 | 7.0 | 6.0 |
++------+---+------+-----+-----+-----+";
+    check_output_stream(output, expected).await;
+
+    // In distributed mode, the partial aggregation is pushed down to datanodes.
+    let output = execute_sql(&instance, &format!("explain {sql}")).await;
+    let plan = match output {
+        Output::Stream(stream) => util::collect_batches(stream).await.unwrap(),
+        Output::RecordBatches(recordbatches) => recordbatches,
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        plan.pretty_print().unwrap().contains("TableAggregateScan"),
+        is_distributed_mode
+    );
+
+    // Regions without rows contribute nothing to the final aggregation.
+    let output = execute_sql(&instance, "select count(*) from demo where host > 'z'").await;
+    let expected = "\
++-----------------+
+| COUNT(UInt8(1)) |
++-----------------+
+| 0               |
++-----------------+";
+    check_output_stream(output, expected).await;
+
+    // Distinct aggregation is not decomposable.
+    let output = execute_sql(&instance, "select count(distinct host) as c from demo").await;
+    let expected = "\
++---+
+| c |
++---+
+| 4 |
++---+";
+    check_output_stream(output, expected).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_execute_query_with_aliases(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        r#"create table demo(
+            host string,
+            cpu double,
+            memory double,
+            ts timestamp time index,
+            primary key(host)
+        )
+        partition by range columns (host) (
+            partition r0 values less than ('b'),
+            partition r1 values less than ('d'),
+            partition r2 values less than (maxvalue),
+        )"#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(0)));
+
+    let output = execute_sql(
+        &instance,
+        r#"insert into demo(host, cpu, memory, ts) values
+            ('a1', 1.0, 10.0, 1),
+            ('c1', 2.0, 20.0, 2),
+            ('e1', 3.0, 30.0, 3)"#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(3)));
+
+    // Aliased columns.
+    let output = execute_sql(&instance, "select host as h, cpu as c from demo order by h").await;
+    let expected = "\
++----+-----+
+| h  | c   |
++----+-----+
+| a1 | 1.0 |
+| c1 | 2.0 |
+| e1 | 3.0 |
++----+-----+";
+    check_output_stream(output, expected).await;
+
+    // Columns in another order than the table.
+    let output = execute_sql(
+        &instance,
+        "select memory, ts, host as h from demo order by h",
+    )
+    .await;
+    let expected = "\
++--------+-------------------------+----+
+| memory | ts                      | h  |
++--------+-------------------------+----+
+| 10.0   | 1970-01-01T00:00:00.001 | a1 |
+| 20.0   | 1970-01-01T00:00:00.002 | c1 |
+| 30.0   | 1970-01-01T00:00:00.003 | e1 |
++--------+-------------------------+----+";
+    check_output_stream(output, expected).await;
+
+    // Computed columns.
+    let output = execute_sql(
+        &instance,
+        "select cpu * 2 as double_cpu, host, memory + cpu as total from demo order by host",
+    )
+    .await;
+    let expected = "\
++------------+------+-------+
+| double_cpu | host | total |
++------------+------+-------+
+| 2.0        | a1   | 11.0  |
+| 4.0        | c1   | 22.0  |
+| 6.0        | e1   | 33.0  |
++------------+------+-------+";
+    check_output_stream(output, expected).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_explain_insert_and_delete(instance: Arc<dyn MockInstance>) {
+    let is_distributed_mode = instance.is_distributed_mode();
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        r#"create table demo(
+            host string,
+            cpu double,
+            ts timestamp time index,
+            primary key(host)
+        )
+        partition by range columns (host) (
+            partition r0 values less than ('b'),
+            partition r1 values less than ('d'),
+            partition r2 values less than (maxvalue),
+        )"#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(0)));
+
+    let output = execute_sql(
+        &instance,
+        r#"explain insert into demo(host, cpu, ts) values
+            ('a1', 1.0, 1),
+            ('a2', 2.0, 2),
+            ('e1', 3.0, 3)"#,
+    )
+    .await;
+    let targets = explained_region_targets(output);
+    let expected = if is_distributed_mode {
+        vec![
+            "region: 0, range: (host) VALUES LESS THAN ('b'), rows: 2",
+            "region: 2, range: (host) VALUES LESS THAN (MAXVALUE), rows: 1",
+        ]
+    } else {
+        vec!["region: 0, rows: 3"]
+    };
+    assert_eq!(expected, targets);
+
+    // Nothing is inserted.
+    let output = execute_sql(&instance, "select count(*) as c from demo").await;
+    let expected = "\
++---+
+| c |
++---+
+| 0 |
++---+";
+    check_output_stream(output, expected).await;
+
+    let output = execute_sql(&instance, "explain delete from demo where host = 'c1'").await;
+    let targets = explained_region_targets(output);
+    let expected = if is_distributed_mode {
+        vec!["region: 1, range: (host) VALUES LESS THAN ('d')"]
+    } else {
+        vec!["region: 0"]
+    };
+    assert_eq!(expected, targets);
+}
+
+/// Returns the explained region targets, without the datanodes as the regions are assigned
+/// to datanodes randomly.
+fn explained_region_targets(output: Output) -> Vec<String> {
+    let Output::RecordBatches(batches) = output else { unreachable!() };
+    let mut targets = Vec::new();
+    for batch in batches.take() {
+        for i in 0..batch.num_rows() {
+            if batch.column(0).get(i).to_string() != "region_target" {
+                continue;
+            }
+            let plan = batch.column(1).get(i).to_string();
+            let plan = match plan.split_once(", datanode: ") {
+                Some((plan, _)) => plan.to_string(),
+                None => plan,
+            };
+            targets.push(plan);
+        }
+    }
+    targets
+}
+
 #[apply(both_instances_cases)]
 async fn test_execute_show_databases_tables(instance: Arc<dyn MockInstance>) {
     let instance = instance.frontend();
@@ -445,19 +854,14 @@ async fn test_execute_show_databases_tables(instance: Arc<dyn MockInstance>) {
 
     // show tables like [string]
     let output = execute_sql(&instance, "show tables like 'de%'").await;
-    match output {
-        Output::RecordBatches(databases) => {
-            let databases = databases.take();
-            assert_eq!(1, databases[0].num_columns());
-            assert_eq!(databases[0].column(0).len(), 1);
-
-            assert_eq!(
-                *databases[0].column(0),
-                Arc::new(StringVector::from(vec![Some("demo")])) as VectorRef
-            );
-        }
-        _ => unreachable!(),
-    }
+    let expected = "\
++--------+
+| Tables |
++--------+
+| demo   |
++--------+\
+";
+    check_output_stream(output, expected).await;
 }
 
 #[apply(both_instances_cases)]
@@ -582,6 +986,60 @@ async fn test_execute_query_external_table_parquet(instance: Arc<dyn MockInstanc
     check_output_stream(output, expect).await;
 }
 
+#[apply(both_instances_cases)]
+async fn test_execute_query_external_table_orc(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+    let format = "orc";
+    let location = get_data_dir("../common/datasource/tests/orc/basic.orc")
+        .canonicalize()
+        .unwrap()
+        .display()
+        .to_string();
+
+    let table_name = "basic_orc";
+
+    let output = execute_sql(
+        &instance,
+        &format!(
+            r#"create external table {table_name} with (location='{location}', format='{format}');"#,
+        ),
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(0)));
+
+    let output = execute_sql(&instance, &format!("desc table {table_name};")).await;
+    let expect = "\
++-------+--------+------+---------+---------------+
+| Field | Type   | Null | Default | Semantic Type |
++-------+--------+------+---------+---------------+
+| num   | Int64  | YES  |         | FIELD         |
+| str   | String | YES  |         | FIELD         |
++-------+--------+------+---------+---------------+";
+    check_output_stream(output, expect).await;
+
+    let output = execute_sql(&instance, &format!("select * from {table_name};")).await;
+    let expect = "\
++-----+-------+
+| num | str   |
++-----+-------+
+| 5   | test  |
+| 2   | hello |
+| 4   | foo   |
++-----+-------+";
+    check_output_stream(output, expect).await;
+
+    let output = execute_sql(&instance, &format!("select str from {table_name};")).await;
+    let expect = "\
++-------+
+| str   |
++-------+
+| test  |
+| hello |
+| foo   |
++-------+";
+    check_output_stream(output, expect).await;
+}
+
 #[apply(both_instances_cases)]
 async fn test_execute_query_external_table_csv(instance: Arc<dyn MockInstance>) {
     let instance = instance.frontend();
@@ -950,6 +1408,162 @@ async fn test_alter_table(instance: Arc<dyn MockInstance>) {
     check_output_stream(output, expected).await;
 }
 
+#[apply(both_instances_cases)]
+async fn test_concurrent_alter_table(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    execute_sql(
+        &instance,
+        "create table demo(host string, cpu double, ts timestamp time index);",
+    )
+    .await;
+
+    // The alters run one after the other, so neither column is lost.
+    let (memory, disk) = tokio::join!(
+        execute_sql(&instance, "alter table demo add memory double null"),
+        execute_sql(&instance, "alter table demo add disk double null"),
+    );
+    assert!(matches!(memory, Output::AffectedRows(0)));
+    assert!(matches!(disk, Output::AffectedRows(0)));
+
+    let output = execute_sql(
+        &instance,
+        "insert into demo(host, cpu, memory, disk, ts) values ('host1', 1.1, 100, 200, 1000)",
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(1)));
+
+    let output = execute_sql(&instance, "select host, memory, disk from demo").await;
+    let expected = "\
++-------+--------+-------+
+| host  | memory | disk  |
++-------+--------+-------+
+| host1 | 100.0  | 200.0 |
++-------+--------+-------+";
+    check_output_stream(output, expected).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_drop_table_waits_for_alter(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    execute_sql(
+        &instance,
+        "create table demo(host string, cpu double, ts timestamp time index);",
+    )
+    .await;
+    assert!(show_processlist(&instance).await.is_empty());
+
+    let table = instance
+        .catalog_manager()
+        .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "demo")
+        .await
+        .unwrap()
+        .unwrap();
+    // Holds the lock of the table like an in-flight alter.
+    let alter_guard = instance
+        .ddl_locks()
+        .lock(
+            &[table.table_info().ident.table_id],
+            "ALTER TABLE greptime.public.demo",
+        )
+        .await
+        .unwrap();
+
+    let drop_table = execute_sql(&instance, "drop table demo");
+    let complete_alter = async {
+        let mut processes = show_processlist(&instance).await;
+        while processes.len() < 2 {
+            tokio::task::yield_now().await;
+            processes = show_processlist(&instance).await;
+        }
+        assert_eq!(
+            vec![
+                (
+                    "DDL".to_string(),
+                    "Running".to_string(),
+                    "ALTER TABLE greptime.public.demo".to_string(),
+                    "user".to_string()
+                ),
+                (
+                    "DDL".to_string(),
+                    "Waiting for table lock".to_string(),
+                    "DROP TABLE greptime.public.demo".to_string(),
+                    "user".to_string()
+                ),
+            ],
+            processes
+        );
+        // The table is not dropped until the alter completes.
+        assert!(instance
+            .catalog_manager()
+            .table(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "demo")
+            .await
+            .unwrap()
+            .is_some());
+
+        drop(alter_guard);
+    };
+    let (output, _) = tokio::join!(drop_table, complete_alter);
+    assert!(matches!(output, Output::AffectedRows(1)));
+    assert!(show_processlist(&instance).await.is_empty());
+}
+
+#[apply(both_instances_cases)]
+async fn test_processlist_origin(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let export = instance.progress_registry().register(
+        "COPY demo TO '/tmp/demo.parquet'",
+        QueryOrigin::Background("copy_to".to_string()),
+    );
+    export.progress().set_current_item("/tmp/demo.parquet");
+    let import = instance
+        .progress_registry()
+        .register("COPY demo FROM '/tmp/'", QueryOrigin::User);
+    import.progress().set_total(3);
+    import.progress().inc_done(1);
+
+    let processes = show_processlist(&instance).await;
+    assert_eq!(2, processes.len());
+    assert_eq!(
+        (
+            "Query".to_string(),
+            "Running".to_string(),
+            "COPY demo TO '/tmp/demo.parquet' (current: /tmp/demo.parquet)".to_string(),
+            "background(copy_to)".to_string()
+        ),
+        processes[0]
+    );
+    assert_eq!("COPY demo FROM '/tmp/'", processes[1].2);
+    assert!(
+        processes[1].1.starts_with("1/3 done, ETA "),
+        "{}",
+        processes[1].1
+    );
+    assert_eq!("user", processes[1].3);
+
+    drop(export);
+    drop(import);
+    assert!(show_processlist(&instance).await.is_empty());
+}
+
+/// Returns the command, state, info and origin of the processes in `SHOW PROCESSLIST`.
+async fn show_processlist(instance: &Arc<Instance>) -> Vec<(String, String, String, String)> {
+    let Output::RecordBatches(batches) = execute_sql(instance, "show processlist").await else { unreachable!() };
+    batches
+        .iter()
+        .flat_map(|batch| batch.rows())
+        .map(|row| {
+            let field = |i: usize| match &row[i] {
+                Value::String(s) => s.as_utf8().to_string(),
+                v => unreachable!("{v:?}"),
+            };
+            (field(1), field(3), field(4), field(5))
+        })
+        .collect()
+}
+
 async fn test_insert_with_default_value_for_type(instance: Arc<Instance>, type_name: &str) {
     let table_name = format!("test_table_with_{type_name}");
     let create_sql = format!(
@@ -1052,6 +1666,51 @@ async fn test_use_database(instance: Arc<dyn MockInstance>) {
     check_output_stream(output, expected).await;
 }
 
+#[apply(both_instances_cases)]
+async fn test_use_default_schema_of_user(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    for db in ["tenant_a", "tenant_b"] {
+        let output = execute_sql(&instance, &format!("create database {db}")).await;
+        assert!(matches!(output, Output::AffectedRows(1)));
+    }
+    let output = execute_sql(
+        &instance,
+        "create table tenant_a.tb1(col_i32 int, ts bigint, TIME INDEX(ts))",
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(0)));
+
+    // The user connects without specifying a database.
+    let user_info = UserInfo::new("tenant").with_default_schema(None, "tenant_a", true);
+    let query_ctx = QueryContext::arc();
+    query_ctx.use_default_schema_of(&user_info);
+
+    let output = execute_sql_with(&instance, "show tables", query_ctx.clone()).await;
+    let expected = "\
++--------+
+| Tables |
++--------+
+| tb1    |
++--------+";
+    check_output_stream(output, expected).await;
+
+    let output = execute_sql_with(
+        &instance,
+        "insert into tb1(col_i32, ts) values (1, 1655276557000)",
+        query_ctx.clone(),
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(1)));
+
+    // The user is pinned to the default schema.
+    let output = execute_sql_with(&instance, "use tenant_a", query_ctx.clone()).await;
+    assert!(matches!(output, Output::RecordBatches(_)));
+    let result = try_execute_sql_with(&instance, "use tenant_b", query_ctx.clone()).await;
+    assert!(matches!(result, Err(Error::SchemaPinned { .. })));
+    assert_eq!("tenant_a", query_ctx.current_schema());
+}
+
 #[apply(both_instances_cases)]
 async fn test_delete(instance: Arc<dyn MockInstance>) {
     let instance = instance.frontend();
@@ -1099,6 +1758,260 @@ async fn test_delete(instance: Arc<dyn MockInstance>) {
     check_output_stream(output, expect).await;
 }
 
+#[apply(both_instances_cases)]
+async fn test_delete_with_predicates(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        r#"create table test_table(
+                            host string,
+                            ts timestamp,
+                            cpu double default 0,
+                            memory double,
+                            TIME INDEX (ts),
+                            PRIMARY KEY(host)
+                        ) engine=mito with(regions=1);"#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(0)));
+
+    let output = execute_sql(
+        &instance,
+        r#"insert into test_table(host, cpu, memory, ts) values
+                           ('host1', 66.6, 1024, 1655276557000),
+                           ('host1', 77.7, 2048, 1655276558000),
+                           ('host1', 88.8, 3072, 1655276559000),
+                           ('host2', 11.1, 1024, 1655276557000),
+                           ('host2', 22.2, 2048, 1655276558000)
+                           "#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(5)));
+
+    // predicates on primary key and time index only, deleted by a range tombstone in the
+    // standalone mode, whose deleted rows are unknown
+    let output = execute_sql(
+        &instance,
+        "delete from test_table where host = 'host1' and ts < 1655276559000",
+    )
+    .await;
+    assert!(matches!(
+        output,
+        Output::AffectedRows(0) | Output::AffectedRows(2)
+    ));
+
+    // predicates on a field column fall back to scan then delete by key
+    let output = execute_sql(&instance, "delete from test_table where cpu > 20").await;
+    assert!(matches!(output, Output::AffectedRows(2)));
+
+    let output = execute_sql(&instance, "select * from test_table").await;
+    let expect = "\
++-------+---------------------+------+--------+
+| host  | ts                  | cpu  | memory |
++-------+---------------------+------+--------+
+| host2 | 2022-06-15T07:02:37 | 11.1 | 1024.0 |
++-------+---------------------+------+--------+";
+    check_output_stream(output, expect).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_execute_copy_query_to(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    execute_sql(
+        &instance,
+        "create table demo(host string, cpu double, memory double, ts timestamp time index);",
+    )
+    .await;
+    let output = execute_sql(
+        &instance,
+        r#"insert into demo(host, cpu, memory, ts) values
+                        ('host1', 66.6, 1024, 1655276557000),
+                        ('host2', 88.8, 333.3, 1655276558000),
+                        ('host1', 11.1, 2048, 1655276559000)
+                        "#,
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(3)));
+
+    let query =
+        "select host, sum(cpu) as total_cpu, count(*) as cnt from demo group by host order by host";
+    let dir = create_temp_dir("copy_query_to");
+    let path = format!("{}/export.parquet", dir.path().to_str().unwrap());
+    let output = execute_sql(&instance, &format!("copy ({query}) to '{path}'")).await;
+    assert!(matches!(output, Output::AffectedRows(2)));
+
+    let file = tokio::fs::File::open(&path).await.unwrap();
+    let stream = ParquetRecordBatchStreamBuilder::new(file)
+        .await
+        .unwrap()
+        .build()
+        .unwrap();
+    let df_batches = stream.try_collect::<Vec<_>>().await.unwrap();
+    let schema = Arc::new(Schema::try_from(df_batches[0].schema()).unwrap());
+    let batches = df_batches
+        .into_iter()
+        .map(|batch| RecordBatch::try_from_df_record_batch(schema.clone(), batch).unwrap())
+        .collect();
+    let exported = RecordBatches::try_new(schema, batches)
+        .unwrap()
+        .pretty_print()
+        .unwrap();
+
+    let output = execute_sql(&instance, query).await;
+    check_output_stream(output, &exported).await;
+
+    let query = "select host, count(*) as cnt from demo group by host order by host";
+    let csv_path = format!("{}/export.csv", dir.path().to_str().unwrap());
+    let output = execute_sql(
+        &instance,
+        &format!("copy ({query}) to '{csv_path}' with (format = 'csv')"),
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(2)));
+    let exported = tokio::fs::read_to_string(&csv_path).await.unwrap();
+    assert_eq!("host,cnt\nhost1,2\nhost2,1\n", exported);
+
+    let json_path = format!("{}/export.json", dir.path().to_str().unwrap());
+    let output = execute_sql(
+        &instance,
+        &format!("copy ({query}) to '{json_path}' with (format = 'json')"),
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(2)));
+    let exported = tokio::fs::read_to_string(&json_path).await.unwrap();
+    let rows = exported
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            serde_json::json!({"host": "host1", "cnt": 2}),
+            serde_json::json!({"host": "host2", "cnt": 1}),
+        ],
+        rows
+    );
+
+    // Compressed files and orc files are not supported, and nothing is written.
+    for (file, with) in [
+        ("export.csv.gz", "format = 'csv', compression_type = 'gzip'"),
+        ("export.orc", "format = 'orc'"),
+    ] {
+        let path = format!("{}/{file}", dir.path().to_str().unwrap());
+        let output = try_execute_sql(
+            &instance,
+            &format!("copy ({query}) to '{path}' with ({with})"),
+        )
+        .await;
+        assert!(output.is_err());
+        assert!(!std::path::Path::new(&path).exists());
+    }
+}
+
+#[apply(both_instances_cases)]
+async fn test_execute_copy_from_orc(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    // The file has no time index, the rows take the default one.
+    execute_sql(
+        &instance,
+        "create table demo(num bigint, str string, ts timestamp default current_timestamp() time index, primary key(num));",
+    )
+    .await;
+
+    let location = get_data_dir("../common/datasource/tests/orc/basic.orc")
+        .canonicalize()
+        .unwrap()
+        .display()
+        .to_string();
+    let output = execute_sql(
+        &instance,
+        &format!("copy demo from '{location}' with (format = 'orc')"),
+    )
+    .await;
+    assert!(matches!(output, Output::AffectedRows(3)));
+
+    let output = execute_sql(&instance, "select num, str from demo order by num").await;
+    let expected = "\
++-----+-------+
+| num | str   |
++-----+-------+
+| 2   | hello |
+| 4   | foo   |
+| 5   | test  |
++-----+-------+";
+    check_output_stream(output, expected).await;
+
+    let location = get_data_dir("../common/datasource/tests/csv/basic.csv")
+        .canonicalize()
+        .unwrap()
+        .display()
+        .to_string();
+    let output = try_execute_sql(
+        &instance,
+        &format!("copy demo from '{location}' with (format = 'csv')"),
+    )
+    .await;
+    assert!(output.is_err());
+}
+
+#[apply(both_instances_cases)]
+async fn test_select_for_system_time_as_of(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    execute_sql(
+        &instance,
+        "create table demo(host string, cpu double, ts timestamp time index, primary key(host))",
+    )
+    .await;
+    execute_sql(&instance, "insert into demo values('host1', 66.6, 0)").await;
+    let pause = Duration::from_millis(200);
+    tokio::time::sleep(pause).await;
+    let before_overwrite = current_time_millis();
+    tokio::time::sleep(pause).await;
+    execute_sql(&instance, "insert into demo values('host1', 88.8, 0)").await;
+
+    let output = execute_sql(
+        &instance,
+        &format!("select cpu from demo for system_time as of {before_overwrite}"),
+    )
+    .await;
+    let expected = "\
++------+
+| cpu  |
++------+
+| 66.6 |
++------+";
+    check_output_stream(output, expected).await;
+
+    let output = execute_sql(&instance, "select cpu from demo").await;
+    let expected = "\
++------+
+| cpu  |
++------+
+| 88.8 |
++------+";
+    check_output_stream(output, expected).await;
+
+    // The tables without versions are read as is.
+    let output = execute_sql(
+        &instance,
+        &format!(
+            "select table_name from information_schema.tables where table_name = 'demo' \
+            for system_time as of {before_overwrite}"
+        ),
+    )
+    .await;
+    let expected = "\
++------------+
+| table_name |
++------------+
+| demo       |
++------------+";
+    check_output_stream(output, expected).await;
+}
+
 #[apply(both_instances_cases)]
 async fn test_execute_copy_to_s3(instance: Arc<dyn MockInstance>) {
     if let Ok(bucket) = env::var("GT_S3_BUCKET") {
@@ -1254,50 +2167,163 @@ async fn test_information_schema(instance: Arc<dyn MockInstance>) {
     let expected = match is_distributed_mode {
         true => {
             "\
-+---------------+--------------------+------------+------------+----------+-------------+
-| table_catalog | table_schema       | table_name | table_type | table_id | engine      |
-+---------------+--------------------+------------+------------+----------+-------------+
-| greptime      | public             | numbers    | BASE TABLE | 1        | test_engine |
-| greptime      | public             | scripts    | BASE TABLE | 1024     | mito        |
-| greptime      | information_schema | tables     | VIEW       |          |             |
-+---------------+--------------------+------------+------------+----------+-------------+"
++---------------+--------------------+-------------------+------------+----------+-------------+
+| table_catalog | table_schema       | table_name        | table_type | table_id | engine      |
++---------------+--------------------+-------------------+------------+----------+-------------+
+| greptime      | information_schema | column_limits     | VIEW       |          |             |
+| greptime      | information_schema | column_statistics | VIEW       |          |             |
+| greptime      | information_schema | columns           | VIEW       |          |             |
+| greptime      | public             | numbers           | BASE TABLE | 1        | test_engine |
+| greptime      | information_schema | recycled_tables   | VIEW       |          |             |
+| greptime      | public             | scripts           | BASE TABLE | 1024     | mito        |
+| greptime      | information_schema | table_options     | VIEW       |          |             |
+| greptime      | information_schema | tables            | VIEW       |          |             |
++---------------+--------------------+-------------------+------------+----------+-------------+"
         }
         false => {
             "\
-+---------------+--------------------+------------+------------+----------+-------------+
-| table_catalog | table_schema       | table_name | table_type | table_id | engine      |
-+---------------+--------------------+------------+------------+----------+-------------+
-| greptime      | public             | numbers    | BASE TABLE | 1        | test_engine |
-| greptime      | public             | scripts    | BASE TABLE | 1        | mito        |
-| greptime      | information_schema | tables     | VIEW       |          |             |
-+---------------+--------------------+------------+------------+----------+-------------+"
++---------------+--------------------+-------------------+------------+----------+-------------+
+| table_catalog | table_schema       | table_name        | table_type | table_id | engine      |
++---------------+--------------------+-------------------+------------+----------+-------------+
+| greptime      | information_schema | column_limits     | VIEW       |          |             |
+| greptime      | information_schema | column_statistics | VIEW       |          |             |
+| greptime      | information_schema | columns           | VIEW       |          |             |
+| greptime      | public             | numbers           | BASE TABLE | 1        | test_engine |
+| greptime      | information_schema | recycled_tables   | VIEW       |          |             |
+| greptime      | public             | scripts           | BASE TABLE | 1        | mito        |
+| greptime      | information_schema | table_options     | VIEW       |          |             |
+| greptime      | information_schema | tables            | VIEW       |          |             |
++---------------+--------------------+-------------------+------------+----------+-------------+"
         }
     };
 
     check_output_stream(output, expected).await;
 
-    let output = execute_sql_with(&instance, sql, query_ctx).await;
+    let output = execute_sql_with(&instance, sql, query_ctx.clone()).await;
     let expected = match is_distributed_mode {
         true => {
             "\
-+-----------------+--------------------+---------------+------------+----------+--------+
-| table_catalog   | table_schema       | table_name    | table_type | table_id | engine |
-+-----------------+--------------------+---------------+------------+----------+--------+
-| another_catalog | another_schema     | another_table | BASE TABLE | 1025     | mito   |
-| another_catalog | information_schema | tables        | VIEW       |          |        |
-+-----------------+--------------------+---------------+------------+----------+--------+"
++-----------------+--------------------+-------------------+------------+----------+--------+
+| table_catalog   | table_schema       | table_name        | table_type | table_id | engine |
++-----------------+--------------------+-------------------+------------+----------+--------+
+| another_catalog | another_schema     | another_table     | BASE TABLE | 1025     | mito   |
+| another_catalog | information_schema | column_limits     | VIEW       |          |        |
+| another_catalog | information_schema | column_statistics | VIEW       |          |        |
+| another_catalog | information_schema | columns           | VIEW       |          |        |
+| another_catalog | information_schema | recycled_tables   | VIEW       |          |        |
+| another_catalog | information_schema | table_options     | VIEW       |          |        |
+| another_catalog | information_schema | tables            | VIEW       |          |        |
++-----------------+--------------------+-------------------+------------+----------+--------+"
         }
         false => {
             "\
-+-----------------+--------------------+---------------+------------+----------+--------+
-| table_catalog   | table_schema       | table_name    | table_type | table_id | engine |
-+-----------------+--------------------+---------------+------------+----------+--------+
-| another_catalog | another_schema     | another_table | BASE TABLE | 1024     | mito   |
-| another_catalog | information_schema | tables        | VIEW       |          |        |
-+-----------------+--------------------+---------------+------------+----------+--------+"
++-----------------+--------------------+-------------------+------------+----------+--------+
+| table_catalog   | table_schema       | table_name        | table_type | table_id | engine |
++-----------------+--------------------+-------------------+------------+----------+--------+
+| another_catalog | another_schema     | another_table     | BASE TABLE | 1024     | mito   |
+| another_catalog | information_schema | column_limits     | VIEW       |          |        |
+| another_catalog | information_schema | column_statistics | VIEW       |          |        |
+| another_catalog | information_schema | columns           | VIEW       |          |        |
+| another_catalog | information_schema | recycled_tables   | VIEW       |          |        |
+| another_catalog | information_schema | table_options     | VIEW       |          |        |
+| another_catalog | information_schema | tables            | VIEW       |          |        |
++-----------------+--------------------+-------------------+------------+----------+--------+"
         }
     };
     check_output_stream(output, expected).await;
+
+    let sql = "select table_name, column_name, ordinal_position, data_type, is_nullable, semantic_type from information_schema.columns where table_schema = 'another_schema' order by ordinal_position";
+    let output = execute_sql_with(&instance, sql, query_ctx.clone()).await;
+    let expected = "\
++---------------+-------------+------------------+-----------+-------------+---------------+
+| table_name    | column_name | ordinal_position | data_type | is_nullable | semantic_type |
++---------------+-------------+------------------+-----------+-------------+---------------+
+| another_table | i           | 1                | Int64     | NO          | timestamp     |
++---------------+-------------+------------------+-----------+-------------+---------------+";
+    check_output_stream(output, expected).await;
+}
+
+#[apply(standalone_instance_case)]
+async fn test_information_schema_freshness(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let sql = "create table freshness_table(host string, ts timestamp time index)";
+    execute_sql(&instance, sql).await;
+
+    let sql = "select table_name, freshness from information_schema.tables where table_name = 'freshness_table'";
+    let output = execute_sql(&instance, sql).await;
+    let expected = "\
++-----------------+-----------+
+| table_name      | freshness |
++-----------------+-----------+
+| freshness_table |           |
++-----------------+-----------+";
+    check_output_stream(output, expected).await;
+
+    let sql = "insert into freshness_table(host, ts) values ('host1', 1655276557000)";
+    execute_sql(&instance, sql).await;
+
+    let sql = "select table_name from information_schema.tables where table_name = 'freshness_table' and freshness >= 0 and freshness < 60";
+    let output = execute_sql(&instance, sql).await;
+    let expected = "\
++-----------------+
+| table_name      |
++-----------------+
+| freshness_table |
++-----------------+";
+    check_output_stream(output, expected).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_analyze_table(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let sql = "create table statistics_table(host string, cpu double, ts timestamp time index)";
+    execute_sql(&instance, sql).await;
+    let sql = "create table not_analyzed_table(host string, ts timestamp time index)";
+    execute_sql(&instance, sql).await;
+
+    let sql = "insert into statistics_table(host, cpu, ts) values ('b', 1.0, 1000), ('a', null, 2000), ('c', 2.5, 3000), ('a', 3.0, 4000)";
+    execute_sql(&instance, sql).await;
+
+    let output = execute_sql(&instance, "select analyze_table('statistics_table')").await;
+    assert!(matches!(output, Output::AffectedRows(4)));
+
+    let sql = "select column_name, min_value, max_value, approx_distinct, null_fraction from information_schema.column_statistics where table_name = 'statistics_table' and column_name != 'ts' and computed_at is not null order by column_name";
+    let output = execute_sql(&instance, sql).await;
+    let expected = "\
++-------------+-----------+-----------+-----------------+---------------+
+| column_name | min_value | max_value | approx_distinct | null_fraction |
++-------------+-----------+-----------+-----------------+---------------+
+| cpu         | 1         | 3         | 3               | 0.25          |
+| host        | a         | c         | 3               | 0.0           |
++-------------+-----------+-----------+-----------------+---------------+";
+    check_output_stream(output, expected).await;
+
+    // The statistics are read from the persisted ones once forgotten, e.g. after a restart.
+    let table = instance
+        .catalog_manager()
+        .table("greptime", "public", "statistics_table")
+        .await
+        .unwrap()
+        .unwrap();
+    catalog::statistics::statistics_store().remove(&table.table_info());
+    let output = execute_sql(&instance, sql).await;
+    check_output_stream(output, expected).await;
+
+    let sql = "select column_name, min_value, max_value, approx_distinct, null_fraction, computed_at from information_schema.column_statistics where table_name = 'not_analyzed_table' order by column_name";
+    let output = execute_sql(&instance, sql).await;
+    let expected = "\
++-------------+-----------+-----------+-----------------+---------------+-------------+
+| column_name | min_value | max_value | approx_distinct | null_fraction | computed_at |
++-------------+-----------+-----------+-----------------+---------------+-------------+
+| host        |           |           |                 |               |             |
+| ts          |           |           |                 |               |             |
++-------------+-----------+-----------+-----------------+---------------+-------------+";
+    check_output_stream(output, expected).await;
+
+    let output = try_execute_sql(&instance, "select analyze_table('numbers')").await;
+    assert!(output.is_err());
 }
 
 async fn execute_sql(instance: &Arc<Instance>, sql: &str) -> Output {
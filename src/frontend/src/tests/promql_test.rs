@@ -15,7 +15,10 @@
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use query::parser::{PromQuery, QueryLanguageParser, QueryStatement};
+use common_query::Output;
+use common_recordbatch::util;
+use datatypes::prelude::{ConcreteDataType, Value};
+use query::parser::{PromQuery, QueryLanguageParser, QueryStatement, DEFAULT_MAX_POINTS};
 use rstest::rstest;
 use rstest_reuse::apply;
 use servers::query_handler::sql::SqlQueryHandler;
@@ -46,7 +49,7 @@ async fn create_insert_query_assert(
         end: "0".to_string(),
         step: "5m".to_string(),
     };
-    let QueryStatement::Promql(mut eval_stmt) = QueryLanguageParser::parse_promql(&query).unwrap() else { unreachable!() };
+    let QueryStatement::Promql(mut eval_stmt) = QueryLanguageParser::parse_promql(&query, DEFAULT_MAX_POINTS).unwrap() else { unreachable!() };
     eval_stmt.start = start;
     eval_stmt.end = end;
     eval_stmt.interval = interval;
@@ -127,6 +130,81 @@ async fn sql_insert_tql_query_ceil(instance: Arc<dyn MockInstance>) {
     .await;
 }
 
+// should apply to both instances. tracked in #1296
+#[apply(standalone_instance_case)]
+async fn sql_insert_tql_query_subsecond_step(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    // Points are at exact 500ms boundaries, each with the latest sample before it.
+    create_insert_tql_assert(
+        instance,
+        r#"create table cpu_usage (
+            host string,
+            cpu double,
+            ts timestamp TIME INDEX,
+            PRIMARY KEY (host),
+        );"#,
+        r#"insert into cpu_usage(host, cpu, ts) values
+            ('host1', 1.1, 0),
+            ('host1', 2.1, 700),
+            ('host1', 3.1, 1200),
+            ('host1', 4.1, 1800);
+        "#,
+        "TQL EVAL (0, 2, '500ms') ceil(cpu_usage{host=\"host1\"})",
+        "+-------------------------+-----------+-------+\
+        \n| ts                      | ceil(cpu) | host  |\
+        \n+-------------------------+-----------+-------+\
+        \n| 1970-01-01T00:00:00     | 2.0       | host1 |\
+        \n| 1970-01-01T00:00:00.500 | 2.0       | host1 |\
+        \n| 1970-01-01T00:00:01     | 3.0       | host1 |\
+        \n| 1970-01-01T00:00:01.500 | 4.0       | host1 |\
+        \n| 1970-01-01T00:00:02     | 5.0       | host1 |\
+        \n+-------------------------+-----------+-------+",
+    )
+    .await;
+}
+
+// should apply to both instances. tracked in #1296
+#[apply(standalone_instance_case)]
+async fn tql_and_promql_max_points(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+    instance
+        .do_query(
+            "create table max_points (host string, cpu double, ts timestamp TIME INDEX, PRIMARY KEY (host))",
+            QueryContext::arc(),
+        )
+        .await
+        .remove(0)
+        .unwrap();
+
+    // 20000 steps of 1s, over the default max points.
+    let err = instance
+        .do_query("TQL EVAL (0, 20000, '1s') max_points", QueryContext::arc())
+        .await
+        .remove(0)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("exceeded maximum resolution"),
+        "{err}"
+    );
+
+    let query = PromQuery {
+        query: "max_points".to_string(),
+        start: "0".to_string(),
+        end: "20000".to_string(),
+        step: "1s".to_string(),
+    };
+    let err = instance
+        .do_promql_query(&query, QueryContext::arc())
+        .await
+        .remove(0)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("exceeded maximum resolution"),
+        "{err}"
+    );
+}
+
 // should apply to both instances. tracked in #1296
 #[apply(standalone_instance_case)]
 async fn sql_insert_promql_query_ceil(instance: Arc<dyn MockInstance>) {
@@ -509,3 +587,81 @@ async fn binary_op_plain_columns(instance: Arc<dyn MockInstance>) {
     )
     .await;
 }
+
+async fn query_float64(instance: &Instance, query: &str) -> f64 {
+    let output = instance
+        .do_query(query, QueryContext::arc())
+        .await
+        .remove(0)
+        .unwrap();
+    let recordbatches = match output {
+        Output::Stream(stream) => util::collect_batches(stream).await.unwrap(),
+        Output::RecordBatches(recordbatches) => recordbatches,
+        _ => unreachable!(),
+    };
+    let batch = recordbatches.take().remove(0);
+    let column = batch
+        .columns()
+        .iter()
+        .find(|column| column.data_type() == ConcreteDataType::float64_datatype())
+        .unwrap();
+    let Value::Float64(value) = column.get(0) else { unreachable!() };
+    value.0
+}
+
+// UInt64 counters near the max with two resets, the range of the PromQL query covers
+// exactly the samples so there is nothing to extrapolate.
+#[apply(standalone_instance_case)]
+async fn sql_and_promql_counter_functions_agree(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    instance
+        .do_query(
+            r#"create table counters (
+                host string,
+                val bigint unsigned,
+                ts timestamp TIME INDEX,
+                PRIMARY KEY (host),
+            );"#,
+            QueryContext::arc(),
+        )
+        .await;
+    instance
+        .do_query(
+            r#"insert into counters(host, val, ts) values
+                ('host1', 18446744073709551595, 0),
+                ('host1', 18446744073709551605, 10000),
+                ('host1', 5, 20000),
+                ('host1', 15, 30000),
+                ('host1', 2, 40000),
+                ('host1', 12, 50000);
+            "#,
+            QueryContext::arc(),
+        )
+        .await;
+
+    for (function, expected) in [
+        ("increase", 37.0),
+        ("rate", 0.74),
+        ("delta", 12.0 - (u64::MAX - 20) as f64),
+    ] {
+        let sql = query_float64(
+            &instance,
+            &format!("select {function}(ts, val) from counters"),
+        )
+        .await;
+        let promql = query_float64(
+            &instance,
+            &format!("TQL EVAL (50, 50, '10s') {function}(counters[50s])"),
+        )
+        .await;
+        assert!(
+            (sql - expected).abs() <= expected.abs() * 1e-9,
+            "{function}: expected {expected}, sql {sql}"
+        );
+        assert!(
+            (sql - promql).abs() <= expected.abs() * 1e-9,
+            "{function}: sql {sql}, promql {promql}"
+        );
+    }
+}
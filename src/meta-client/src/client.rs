@@ -27,15 +27,16 @@ use snafu::OptionExt;
 use store::Client as StoreClient;
 
 pub use self::heartbeat::{HeartbeatSender, HeartbeatStream};
+pub use self::router::DEFAULT_MAX_RETRIES as DEFAULT_ROUTER_MAX_RETRIES;
 use crate::error;
 use crate::error::Result;
 use crate::rpc::lock::{LockRequest, LockResponse, UnlockRequest};
-use crate::rpc::router::DeleteRequest;
+use crate::rpc::router::{DeleteRequest, RenameRequest};
 use crate::rpc::{
-    BatchDeleteRequest, BatchDeleteResponse, BatchGetRequest, BatchGetResponse, BatchPutRequest,
-    BatchPutResponse, CompareAndPutRequest, CompareAndPutResponse, CreateRequest,
-    DeleteRangeRequest, DeleteRangeResponse, MoveValueRequest, MoveValueResponse, PutRequest,
-    PutResponse, RangeRequest, RangeResponse, RouteRequest, RouteResponse,
+    util, BatchDeleteRequest, BatchDeleteResponse, BatchGetRequest, BatchGetResponse,
+    BatchPutRequest, BatchPutResponse, CompareAndPutRequest, CompareAndPutResponse, CreateRequest,
+    DeleteRangeRequest, DeleteRangeResponse, KeyValue, MoveValueRequest, MoveValueResponse,
+    PutRequest, PutResponse, RangeRequest, RangeResponse, RouteRequest, RouteResponse,
 };
 
 pub type Id = (u64, u64);
@@ -48,6 +49,7 @@ pub struct MetaClientBuilder {
     enable_store: bool,
     enable_lock: bool,
     channel_manager: Option<ChannelManager>,
+    router_max_retries: Option<usize>,
 }
 
 impl MetaClientBuilder {
@@ -93,6 +95,14 @@ impl MetaClientBuilder {
         }
     }
 
+    /// Sets the max number of retries of the router requests on unavailable peers.
+    pub fn router_max_retries(self, max_retries: usize) -> Self {
+        Self {
+            router_max_retries: Some(max_retries),
+            ..self
+        }
+    }
+
     pub fn build(self) -> MetaClient {
         let mut client = if let Some(mgr) = self.channel_manager {
             MetaClient::with_channel_manager(self.id, mgr)
@@ -110,7 +120,10 @@ impl MetaClientBuilder {
             client.heartbeat = Some(HeartbeatClient::new(self.id, mgr.clone()));
         }
         if self.enable_router {
-            client.router = Some(RouterClient::new(self.id, mgr.clone()));
+            let max_retries = self
+                .router_max_retries
+                .unwrap_or(router::DEFAULT_MAX_RETRIES);
+            client.router = Some(RouterClient::new(self.id, mgr.clone(), max_retries));
         }
         if self.enable_store {
             client.store = Some(StoreClient::new(self.id, mgr.clone()));
@@ -236,6 +249,13 @@ impl MetaClient {
         self.router_client()?.delete(req.into()).await?.try_into()
     }
 
+    /// Puts the route of a table under its new name before the table is renamed, keeping the
+    /// route of the old name, which is removed by the caller once the rename commits. Retrying
+    /// it is a no-op, fails if the route of the new name is changed concurrently.
+    pub async fn rename_route(&self, req: RenameRequest) -> Result<()> {
+        self.router_client()?.rename(req).await
+    }
+
     /// Range gets the keys in the range from the key-value store.
     pub async fn range(&self, req: RangeRequest) -> Result<RangeResponse> {
         self.store_client()?.range(req.into()).await?.try_into()
@@ -292,6 +312,20 @@ impl MetaClient {
             .try_into()
     }
 
+    /// Returns a [RangePager] range getting the keys with the given prefix page by page, at
+    /// most `page_size` keys per request. Useful to fetch a large amount of metadata, e.g. all
+    /// the tables of a catalog, without oversized requests.
+    pub fn range_pages(&self, prefix: impl Into<Vec<u8>>, page_size: usize) -> RangePager<'_> {
+        let next_key = prefix.into();
+        RangePager {
+            client: self,
+            range_end: util::get_prefix_end_key(&next_key),
+            next_key,
+            page_size,
+            finished: false,
+        }
+    }
+
     pub async fn lock(&self, req: LockRequest) -> Result<LockResponse> {
         self.lock_client()?.lock(req.into()).await.map(Into::into)
     }
@@ -340,12 +374,45 @@ impl MetaClient {
     }
 }
 
+/// Range gets the keys with a prefix page by page, see [MetaClient::range_pages].
+pub struct RangePager<'a> {
+    client: &'a MetaClient,
+    next_key: Vec<u8>,
+    range_end: Vec<u8>,
+    page_size: usize,
+    finished: bool,
+}
+
+impl RangePager<'_> {
+    /// Fetches the next page of keys, returns `None` if all the keys are fetched.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<KeyValue>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let req = RangeRequest::new()
+            .with_range(self.next_key.clone(), self.range_end.clone())
+            .with_limit(self.page_size as i64);
+        let mut res = self.client.range(req).await?;
+        let kvs = res.take_kvs();
+        match kvs.last() {
+            Some(kv) if res.more() => {
+                // The smallest key after the last fetched one.
+                self.next_key = kv.key().iter().copied().chain([0]).collect();
+            }
+            _ => self.finished = true,
+        }
+
+        Ok(if kvs.is_empty() { None } else { Some(kvs) })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
     use std::sync::Arc;
 
-    use api::v1::meta::{HeartbeatRequest, Peer};
+    use api::v1::meta::{HeartbeatRequest, Peer, TableName as PbTableName, TableRouteValue};
     use chrono::DateTime;
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::{ColumnSchema, RawSchema};
@@ -582,6 +649,25 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_create_route_with_invalid_table_name() {
+        let selector = Arc::new(MockSelector {});
+        let client = mocks::mock_client_with_memorystore_and_selector(selector).await;
+
+        let table_info = new_table_info();
+        let req = CreateRequest::new(
+            TableName::new("test_catalog", "test_schema", "greptime_table"),
+            &table_info,
+        );
+        let err = client.create_route(req).await.unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "Invalid table name 'greptime_table', prefix 'greptime_' is reserved by the system"
+            ),
+            "{err}"
+        );
+    }
+
     #[tokio::test]
     async fn test_route() {
         let selector = Arc::new(MockSelector {});
@@ -613,6 +699,81 @@ mod tests {
         assert!(res.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_rename_route() {
+        let selector = Arc::new(MockSelector {});
+        let client = mocks::mock_client_with_memorystore_and_selector(selector).await;
+
+        let table_name = TableName::new("test_catalog", "test_schema", "test_table");
+        let table_info = new_table_info();
+        let req = CreateRequest::new(table_name.clone(), &table_info);
+        let res = client.create_route(req).await.unwrap();
+        let table_id = res.table_routes[0].table.id;
+
+        let new_table_name = TableName::new("test_catalog", "test_schema", "new_table");
+        let route_key = |table_name: &TableName| {
+            meta_srv::keys::TableRouteKey::with_table_name(table_id, &table_name.clone().into())
+                .key()
+                .into_bytes()
+        };
+        let (from_key, to_key) = (route_key(&table_name), route_key(&new_table_name));
+        let req = RenameRequest::new(
+            table_name.clone(),
+            new_table_name.clone(),
+            from_key.clone(),
+            to_key.clone(),
+        );
+        let client = &client;
+        let route_name = move |key: Vec<u8>| async move {
+            let mut res = client
+                .range(RangeRequest::new().with_key(key))
+                .await
+                .unwrap();
+            let kv = res.take_kvs().pop()?;
+            let route_value = TableRouteValue::try_from(kv.value()).unwrap();
+            let table = route_value.table_route.unwrap().table.unwrap();
+            assert_eq!(table_id, table.id);
+            table.table_name
+        };
+
+        // The table is routed by both names until the rename commits.
+        client.rename_route(req.clone()).await.unwrap();
+        assert_eq!(
+            Some(PbTableName::from(table_name.clone())),
+            route_name(from_key.clone()).await
+        );
+        assert_eq!(
+            Some(PbTableName::from(new_table_name.clone())),
+            route_name(to_key.clone()).await
+        );
+        // Retrying the rename is a no-op.
+        client.rename_route(req.clone()).await.unwrap();
+
+        // Retries after the rename commits are no-ops too.
+        let _ = client
+            .delete_range(DeleteRangeRequest::new().with_key(from_key))
+            .await
+            .unwrap();
+        client.rename_route(req.clone()).await.unwrap();
+        assert_eq!(
+            Some(PbTableName::from(new_table_name)),
+            route_name(to_key).await
+        );
+
+        let another_table_name = TableName::new("test_catalog", "test_schema", "another_table");
+        let req = RenameRequest::new(
+            table_name,
+            another_table_name.clone(),
+            req.route_key,
+            route_key(&another_table_name),
+        );
+        let err = client.rename_route(req).await.unwrap_err();
+        assert!(
+            matches!(err, error::Error::TableRouteNotFound { .. }),
+            "{err}"
+        );
+    }
+
     #[tokio::test]
     async fn test_range_get() {
         let tc = new_client("test_range_get").await;
@@ -661,6 +822,28 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_range_pages() {
+        let tc = new_client("test_range_pages").await;
+        tc.gen_data().await;
+
+        for page_size in [1, 3, 10, 20] {
+            let mut pager = tc.client.range_pages(tc.key("key-"), page_size);
+            let mut keys = vec![];
+            while let Some(kvs) = pager.next_page().await.unwrap() {
+                assert!(kvs.len() <= page_size);
+                keys.extend(kvs.into_iter().map(|mut kv| kv.take_key()));
+            }
+            let expected = (0..10)
+                .map(|i| tc.key(&format!("key-{i}")))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, keys);
+        }
+
+        let mut pager = tc.client.range_pages(tc.key("absent-"), 3);
+        assert!(pager.next_page().await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_range_keys_only() {
         let tc = new_client("test_range_keys_only").await;
@@ -13,19 +13,33 @@
 // limitations under the License.
 
 use std::collections::HashSet;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use api::v1::meta::router_client::RouterClient;
-use api::v1::meta::{CreateRequest, DeleteRequest, RouteRequest, RouteResponse};
+use api::v1::meta::store_client::StoreClient;
+use api::v1::meta::{
+    CompareAndPutRequest, CreateRequest, DeleteRequest, RangeRequest, RouteRequest, RouteResponse,
+    TableName as PbTableName, TableRouteValue,
+};
 use common_grpc::channel_manager::ChannelManager;
-use snafu::{ensure, Location, OptionExt, ResultExt};
+use common_telemetry::warn;
+use snafu::{ensure, OptionExt, ResultExt};
 use tokio::sync::RwLock;
 use tonic::transport::Channel;
+use tonic::{Code, Response, Status};
 
 use crate::client::{load_balance as lb, Id};
 use crate::error;
-use crate::error::Error::TonicStatus;
 use crate::error::Result;
+use crate::rpc::router::RenameRequest;
+use crate::rpc::util;
+
+/// Default max number of retries of a request on transient failures.
+pub const DEFAULT_MAX_RETRIES: usize = 3;
+/// Backoff before the first retry, growing linearly with the retries.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
 
 #[derive(Clone, Debug)]
 pub struct Client {
@@ -33,11 +47,12 @@ pub struct Client {
 }
 
 impl Client {
-    pub fn new(id: Id, channel_manager: ChannelManager) -> Self {
+    pub fn new(id: Id, channel_manager: ChannelManager, max_retries: usize) -> Self {
         let inner = Arc::new(RwLock::new(Inner {
             id,
             channel_manager,
             peers: vec![],
+            max_retries,
         }));
 
         Self { inner }
@@ -71,6 +86,11 @@ impl Client {
         let inner = self.inner.read().await;
         inner.delete(req).await
     }
+
+    pub async fn rename(&self, req: RenameRequest) -> Result<()> {
+        let inner = self.inner.read().await;
+        inner.rename(req).await
+    }
 }
 
 #[derive(Debug)]
@@ -78,6 +98,7 @@ struct Inner {
     id: Id,
     channel_manager: ChannelManager,
     peers: Vec<String>,
+    max_retries: usize,
 }
 
 impl Inner {
@@ -105,54 +126,173 @@ impl Inner {
     }
 
     async fn create(&self, mut req: CreateRequest) -> Result<RouteResponse> {
-        let mut client = self.random_client()?;
         req.set_header(self.id);
-        let res = client.create(req).await.context(error::TonicStatusSnafu)?;
-
-        Ok(res.into_inner())
+        self.call_with_retry(req, is_unavailable, |channel, req| async move {
+            RouterClient::new(channel).create(req).await
+        })
+        .await
     }
 
     async fn route(&self, mut req: RouteRequest) -> Result<RouteResponse> {
-        let mut client = self.random_client()?;
         req.set_header(self.id);
-        let res = client.route(req).await.context(error::TonicStatusSnafu)?;
-
-        Ok(res.into_inner())
+        self.call_with_retry(req, is_retriable, |channel, req| async move {
+            RouterClient::new(channel).route(req).await
+        })
+        .await
     }
 
     async fn delete(&self, mut req: DeleteRequest) -> Result<RouteResponse> {
-        let mut client = self.random_client()?;
         req.set_header(self.id);
-        let res = client.delete(req).await.map_err(|mut source| {
-            // FIXME(hl): here intentionally clear the metadata field so that error date does not changes which will break sqlness test.
-            // we can remove this hack as soon as either: sqlness supports regex result match or greptimedb supports renaming table routes
-            source.metadata_mut().clear();
-            TonicStatus {
-                source,
-                location: Location::default(),
+        self.call_with_retry(req, is_unavailable, |channel, req| async move {
+            RouterClient::new(channel)
+                .delete(req)
+                .await
+                .map_err(|mut status| {
+                    // FIXME(hl): here intentionally clear the metadata field so that error date does not changes which will break sqlness test.
+                    // we can remove this hack as soon as either: sqlness supports regex result match or greptimedb supports renaming table routes
+                    status.metadata_mut().clear();
+                    status
+                })
+        })
+        .await
+    }
+
+    /// Puts the route of the table under its new name, with the table name in the route
+    /// updated. The route of the old name is kept, for the table to be routed by whichever name
+    /// it's found by until the rename commits, then it's removed by the caller.
+    ///
+    /// The router service has no rename RPC, so the route is renamed by the store service of the
+    /// same peers. The route of the new name is put only if it's not changed since it's read, as
+    /// it may be left by a failed attempt of the rename. The rename is idempotent, so it's safe
+    /// to retry, and a retried request applied before is not failed as a conflict.
+    async fn rename(&self, req: RenameRequest) -> Result<()> {
+        let RenameRequest {
+            table_name,
+            new_table_name,
+            route_key,
+            new_route_key,
+        } = req;
+        let Some(value) = self.get(route_key).await? else {
+            // The route of the old name is removed once the rename commits.
+            ensure!(
+                self.get(new_route_key).await?.is_some(),
+                error::TableRouteNotFoundSnafu {
+                    table_name: table_name.to_string(),
+                }
+            );
+            return Ok(());
+        };
+
+        let mut route_value =
+            TableRouteValue::try_from(value.as_slice()).context(error::DecodeTableRouteSnafu {
+                table_name: table_name.to_string(),
+            })?;
+        let table = route_value
+            .table_route
+            .as_mut()
+            .and_then(|route| route.table.as_mut())
+            .context(error::RouteInfoCorruptedSnafu {
+                err_msg: "table required",
+            })?;
+        table.table_name = Some(PbTableName::from(new_table_name.clone()));
+        let value: Vec<u8> = route_value.into();
+
+        let expect = self.get(new_route_key.clone()).await?.unwrap_or_default();
+        let mut req = CompareAndPutRequest {
+            key: new_route_key,
+            expect,
+            value: value.clone(),
+            ..Default::default()
+        };
+        req.set_header(self.id);
+        let res = self
+            .call_with_retry(req, is_retriable, |channel, req| async move {
+                StoreClient::new(channel).compare_and_put(req).await
+            })
+            .await?;
+        util::check_response_header(res.header.as_ref())?;
+        let applied = res.success || res.prev_kv.map_or(false, |kv| kv.value == value);
+        ensure!(
+            applied,
+            error::TableRouteChangedSnafu {
+                table_name: new_table_name.to_string(),
             }
-        })?;
-        Ok(res.into_inner())
+        );
+        Ok(())
+    }
+
+    /// Gets the value of `key` from the store service.
+    async fn get(&self, key: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        let mut req = RangeRequest {
+            key,
+            ..Default::default()
+        };
+        req.set_header(self.id);
+        let res = self
+            .call_with_retry(req, is_retriable, |channel, req| async move {
+                StoreClient::new(channel).range(req).await
+            })
+            .await?;
+        util::check_response_header(res.header.as_ref())?;
+        Ok(res.kvs.into_iter().next().map(|kv| kv.value))
     }
 
-    fn random_client(&self) -> Result<RouterClient<Channel>> {
-        let len = self.peers.len();
-        let peer = lb::random_get(len, |i| Some(&self.peers[i])).context(
+    /// Calls a random peer with the request, and retries on another peer not tried yet if the
+    /// failure is `retriable`, until it succeeds or it has been retried `max_retries` times.
+    async fn call_with_retry<Req, Res, F, Fut>(
+        &self,
+        req: Req,
+        retriable: fn(&Status) -> bool,
+        call: F,
+    ) -> Result<Res>
+    where
+        Req: Clone,
+        F: Fn(Channel, Req) -> Fut,
+        Fut: Future<Output = std::result::Result<Response<Res>, Status>>,
+    {
+        let mut tried = HashSet::new();
+        let mut retries = 0;
+        loop {
+            let peer = self.random_peer(&tried)?;
+            let channel = self.make_channel(peer)?;
+            match call(channel, req.clone()).await {
+                Ok(res) => return Ok(res.into_inner()),
+                Err(status) if retriable(&status) && retries < self.max_retries => {
+                    retries += 1;
+                    warn!(
+                        "Failed to call router of peer {}, retry {}/{} on another peer, status: {}",
+                        peer, retries, self.max_retries, status
+                    );
+                    let _ = tried.insert(peer);
+                    if tried.len() == self.peers.len() {
+                        // All peers have failed once, give them another chance.
+                        tried.clear();
+                    }
+                    tokio::time::sleep(RETRY_BACKOFF * retries as u32).await;
+                }
+                Err(status) => return Err(status).context(error::TonicStatusSnafu),
+            }
+        }
+    }
+
+    /// Picks a random peer out of the ones not in `tried`.
+    fn random_peer(&self, tried: &HashSet<&str>) -> Result<&str> {
+        let candidates = self
+            .peers
+            .iter()
+            .filter(|peer| !tried.contains(peer.as_str()))
+            .collect::<Vec<_>>();
+        lb::random_get(candidates.len(), |i| Some(candidates[i].as_str())).context(
             error::IllegalGrpcClientStateSnafu {
                 err_msg: "Empty peers, router client may not start yet",
             },
-        )?;
-
-        self.make_client(peer)
+        )
     }
 
-    fn make_client(&self, addr: impl AsRef<str>) -> Result<RouterClient<Channel>> {
-        let channel = self
-            .channel_manager
+    fn make_channel(&self, addr: impl AsRef<str>) -> Result<Channel> {
+        self.channel_manager
             .get(addr)
-            .context(error::CreateChannelSnafu)?;
-
-        Ok(RouterClient::new(channel))
+            .context(error::CreateChannelSnafu)
     }
 
     #[inline]
@@ -161,13 +301,234 @@ impl Inner {
     }
 }
 
+/// Returns true if the failure is transient and the request may succeed on another peer.
+fn is_retriable(status: &Status) -> bool {
+    matches!(status.code(), Code::Unavailable | Code::DeadlineExceeded)
+}
+
+/// Returns true if the peer is unavailable, so it hasn't served the request. The requests not
+/// idempotent, like creating or deleting a route, are retried only on it, as a request timing
+/// out may have been applied by the peer.
+fn is_unavailable(status: &Status) -> bool {
+    status.code() == Code::Unavailable
+}
+
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use api::v1::meta::router_server::{self, RouterServer};
+    use tonic::Request;
+    use tower::service_fn;
+
     use super::*;
 
+    /// Router failing the first `failures` requests with `code`.
+    struct MockRouter {
+        calls: Arc<AtomicUsize>,
+        failures: usize,
+        code: Code,
+    }
+
+    impl MockRouter {
+        fn respond(&self) -> std::result::Result<Response<RouteResponse>, Status> {
+            if self.calls.fetch_add(1, Ordering::Relaxed) < self.failures {
+                Err(Status::new(self.code, "mock failure"))
+            } else {
+                Ok(Response::new(RouteResponse::default()))
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl router_server::Router for MockRouter {
+        async fn create(
+            &self,
+            _req: Request<CreateRequest>,
+        ) -> std::result::Result<Response<RouteResponse>, Status> {
+            self.respond()
+        }
+
+        async fn route(
+            &self,
+            _req: Request<RouteRequest>,
+        ) -> std::result::Result<Response<RouteResponse>, Status> {
+            self.respond()
+        }
+
+        async fn delete(
+            &self,
+            _req: Request<DeleteRequest>,
+        ) -> std::result::Result<Response<RouteResponse>, Status> {
+            self.respond()
+        }
+    }
+
+    /// Serves a [MockRouter] as the peer `addr`, returning the counter of its calls.
+    fn mock_peer(
+        channel_manager: &ChannelManager,
+        addr: &str,
+        failures: usize,
+        code: Code,
+    ) -> Arc<AtomicUsize> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let router = MockRouter {
+            calls: calls.clone(),
+            failures,
+            code,
+        };
+        let (client, server) = tokio::io::duplex(1024);
+        let _handle = tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(RouterServer::new(router))
+                .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+                .await
+        });
+
+        let mut client = Some(client);
+        let res = channel_manager.reset_with_connector(
+            addr,
+            service_fn(move |_| {
+                let client = client.take();
+                async move {
+                    client.ok_or_else(|| {
+                        std::io::Error::new(std::io::ErrorKind::Other, "Client already taken")
+                    })
+                }
+            }),
+        );
+        assert!(res.is_ok());
+        calls
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_another_peer() {
+        let channel_manager = ChannelManager::default();
+        let down = mock_peer(
+            &channel_manager,
+            "127.0.0.1:3001",
+            usize::MAX,
+            Code::Unavailable,
+        );
+        let up = mock_peer(&channel_manager, "127.0.0.1:3002", 0, Code::Unavailable);
+
+        let mut client = Client::new((0, 0), channel_manager, 1);
+        client
+            .start(&["127.0.0.1:3001", "127.0.0.1:3002"])
+            .await
+            .unwrap();
+
+        for _ in 0..10 {
+            client.route(RouteRequest::default()).await.unwrap();
+        }
+        assert_eq!(10, up.load(Ordering::Relaxed));
+        // The down peer is never retried by the same request.
+        assert!(down.load(Ordering::Relaxed) <= 10);
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let channel_manager = ChannelManager::default();
+        let first = mock_peer(
+            &channel_manager,
+            "127.0.0.1:3001",
+            2,
+            Code::DeadlineExceeded,
+        );
+        let second = mock_peer(&channel_manager, "127.0.0.1:3002", 2, Code::Unavailable);
+
+        let mut client = Client::new((0, 0), channel_manager, 4);
+        client
+            .start(&["127.0.0.1:3001", "127.0.0.1:3002"])
+            .await
+            .unwrap();
+
+        // Both peers fail twice, so the 5th attempt succeeds.
+        client.route(RouteRequest::default()).await.unwrap();
+        let calls = first.load(Ordering::Relaxed) + second.load(Ordering::Relaxed);
+        assert_eq!(5, calls);
+    }
+
+    #[tokio::test]
+    async fn test_not_retry_create_on_timeout() {
+        let channel_manager = ChannelManager::default();
+        let first = mock_peer(
+            &channel_manager,
+            "127.0.0.1:3001",
+            1,
+            Code::DeadlineExceeded,
+        );
+        let second = mock_peer(
+            &channel_manager,
+            "127.0.0.1:3002",
+            1,
+            Code::DeadlineExceeded,
+        );
+
+        let mut client = Client::new((0, 0), channel_manager, 3);
+        client
+            .start(&["127.0.0.1:3001", "127.0.0.1:3002"])
+            .await
+            .unwrap();
+
+        // The timed out create may have been applied, so it's not retried.
+        let err = client.create(CreateRequest::default()).await.unwrap_err();
+        let error::Error::TonicStatus { source, .. } = err else { unreachable!() };
+        assert_eq!(Code::DeadlineExceeded, source.code());
+        let calls = first.load(Ordering::Relaxed) + second.load(Ordering::Relaxed);
+        assert_eq!(1, calls);
+    }
+
+    #[tokio::test]
+    async fn test_exhaust_retries() {
+        let channel_manager = ChannelManager::default();
+        let first = mock_peer(
+            &channel_manager,
+            "127.0.0.1:3001",
+            usize::MAX,
+            Code::Unavailable,
+        );
+        let second = mock_peer(
+            &channel_manager,
+            "127.0.0.1:3002",
+            usize::MAX,
+            Code::Unavailable,
+        );
+
+        let mut client = Client::new((0, 0), channel_manager, 2);
+        client
+            .start(&["127.0.0.1:3001", "127.0.0.1:3002"])
+            .await
+            .unwrap();
+
+        let err = client.delete(DeleteRequest::default()).await.unwrap_err();
+        assert!(matches!(err, error::Error::TonicStatus { .. }));
+        let calls = first.load(Ordering::Relaxed) + second.load(Ordering::Relaxed);
+        assert_eq!(3, calls);
+    }
+
+    #[tokio::test]
+    async fn test_not_retry_invalid_argument() {
+        let channel_manager = ChannelManager::default();
+        let first = mock_peer(&channel_manager, "127.0.0.1:3001", 1, Code::InvalidArgument);
+        let second = mock_peer(&channel_manager, "127.0.0.1:3002", 1, Code::InvalidArgument);
+
+        let mut client = Client::new((0, 0), channel_manager, 3);
+        client
+            .start(&["127.0.0.1:3001", "127.0.0.1:3002"])
+            .await
+            .unwrap();
+
+        let err = client.route(RouteRequest::default()).await.unwrap_err();
+        let error::Error::TonicStatus { source, .. } = err else { unreachable!() };
+        assert_eq!(Code::InvalidArgument, source.code());
+        let calls = first.load(Ordering::Relaxed) + second.load(Ordering::Relaxed);
+        assert_eq!(1, calls);
+    }
+
     #[tokio::test]
     async fn test_start_client() {
-        let mut client = Client::new((0, 0), ChannelManager::default());
+        let mut client = Client::new((0, 0), ChannelManager::default(), DEFAULT_MAX_RETRIES);
         assert!(!client.is_started().await);
         client
             .start(&["127.0.0.1:1000", "127.0.0.1:1001"])
@@ -178,7 +539,7 @@ mod test {
 
     #[tokio::test]
     async fn test_already_start() {
-        let mut client = Client::new((0, 0), ChannelManager::default());
+        let mut client = Client::new((0, 0), ChannelManager::default(), DEFAULT_MAX_RETRIES);
         client
             .start(&["127.0.0.1:1000", "127.0.0.1:1001"])
             .await
@@ -194,7 +555,7 @@ mod test {
 
     #[tokio::test]
     async fn test_start_with_duplicate_peers() {
-        let mut client = Client::new((0, 0), ChannelManager::default());
+        let mut client = Client::new((0, 0), ChannelManager::default(), DEFAULT_MAX_RETRIES);
         client
             .start(&["127.0.0.1:1000", "127.0.0.1:1000", "127.0.0.1:1000"])
             .await
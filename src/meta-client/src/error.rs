@@ -58,6 +58,25 @@ pub enum Error {
     #[snafu(display("Route info corrupted: {}", err_msg))]
     RouteInfoCorrupted { err_msg: String, location: Location },
 
+    #[snafu(display("Failed to decode route of table {}, source: {}", table_name, source))]
+    DecodeTableRoute {
+        table_name: String,
+        source: api::DecodeError,
+        location: Location,
+    },
+
+    #[snafu(display("Route of table {} not found", table_name))]
+    TableRouteNotFound {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Route of table {} is changed concurrently", table_name))]
+    TableRouteChanged {
+        table_name: String,
+        location: Location,
+    },
+
     #[snafu(display("Illegal state from server, code: {}, error: {}", code, err_msg))]
     IllegalServerState {
         code: i32,
@@ -92,8 +111,12 @@ impl ErrorExt for Error {
             | Error::CreateHeartbeatStream { .. }
             | Error::CreateChannel { .. }
             | Error::IllegalServerState { .. }
-            | Error::SerdeJson { .. } => StatusCode::Internal,
-            Error::RouteInfoCorrupted { .. } => StatusCode::Unexpected,
+            | Error::SerdeJson { .. }
+            | Error::TableRouteChanged { .. } => StatusCode::Internal,
+            Error::RouteInfoCorrupted { .. } | Error::DecodeTableRoute { .. } => {
+                StatusCode::Unexpected
+            }
+            Error::TableRouteNotFound { .. } => StatusCode::TableNotFound,
         }
     }
 }
@@ -22,11 +22,14 @@ pub mod rpc;
 
 // Options for meta client in datanode instance.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MetaClientOptions {
     pub metasrv_addrs: Vec<String>,
     pub timeout_millis: u64,
     pub connect_timeout_millis: u64,
     pub tcp_nodelay: bool,
+    /// Max number of retries of a router request on another metasrv.
+    pub max_retries: usize,
 }
 
 impl Default for MetaClientOptions {
@@ -36,6 +39,7 @@ impl Default for MetaClientOptions {
             timeout_millis: 3_000u64,
             connect_timeout_millis: 5_000u64,
             tcp_nodelay: true,
+            max_retries: client::DEFAULT_ROUTER_MAX_RETRIES,
         }
     }
 }
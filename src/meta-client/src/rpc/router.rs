@@ -114,6 +114,36 @@ impl DeleteRequest {
     }
 }
 
+/// Request putting the route of a table under its new name, before the table is renamed.
+///
+/// The keys of the routes are given by the caller, see `TableRouteKey` of the catalog.
+#[derive(Debug, Clone)]
+pub struct RenameRequest {
+    pub table_name: TableName,
+    pub new_table_name: TableName,
+    /// Key of the route of the table under its current name.
+    pub route_key: Vec<u8>,
+    /// Key of the route of the table under its new name.
+    pub new_route_key: Vec<u8>,
+}
+
+impl RenameRequest {
+    #[inline]
+    pub fn new(
+        table_name: TableName,
+        new_table_name: TableName,
+        route_key: impl Into<Vec<u8>>,
+        new_route_key: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            table_name,
+            new_table_name,
+            route_key: route_key.into(),
+            new_route_key: new_route_key.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RouteResponse {
     pub table_routes: Vec<TableRoute>,
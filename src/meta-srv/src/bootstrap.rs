@@ -33,8 +33,10 @@ use tonic::transport::server::Router;
 use crate::cluster::MetaPeerClientBuilder;
 use crate::election::etcd::EtcdElection;
 use crate::lock::etcd::EtcdLock;
+use crate::lock::memory::MemLock;
 use crate::metasrv::builder::MetaSrvBuilder;
 use crate::metasrv::{MetaSrv, MetaSrvOptions, SelectorRef};
+use crate::procedure::region_operator::DatanodeRegionOperator;
 use crate::selector::lease_based::LeaseBasedSelector;
 use crate::selector::load_based::LoadBasedSelector;
 use crate::selector::SelectorType;
@@ -152,7 +154,11 @@ pub fn router(meta_srv: MetaSrv) -> Router {
 
 pub async fn build_meta_srv(opts: &MetaSrvOptions) -> Result<MetaSrv> {
     let (kv_store, election, lock) = if opts.use_memory_store {
-        (Arc::new(MemStore::new()) as _, None, None)
+        (
+            Arc::new(MemStore::new()) as _,
+            None,
+            Some(MemLock::new_ref()),
+        )
     } else {
         let etcd_endpoints = [&opts.store_addr];
         let etcd_client = Client::connect(etcd_endpoints, None)
@@ -189,6 +195,7 @@ pub async fn build_meta_srv(opts: &MetaSrvOptions) -> Result<MetaSrv> {
         .election(election)
         .meta_peer_client(meta_peer_client)
         .lock(lock)
+        .region_operator(Some(Arc::new(DatanodeRegionOperator::default())))
         .build()
         .await;
 
@@ -139,6 +139,12 @@ pub enum Error {
         source: common_catalog::error::Error,
     },
 
+    #[snafu(display("{}", source))]
+    InvalidTableName {
+        source: common_catalog::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Unexcepted sequence value: {}", err_msg))]
     UnexceptedSequenceValue { err_msg: String, location: Location },
 
@@ -286,11 +292,48 @@ pub enum Error {
         source: common_procedure::Error,
     },
 
+    #[snafu(display("Route of table {table_name} changed while repartitioning it"))]
+    TableRouteChanged {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Global value of table {table_name} changed while repartitioning it"))]
+    TableGlobalValueChanged {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Region operator is not configured"))]
+    RegionOperatorNotConfig { location: Location },
+
+    #[snafu(display("Failed to submit procedure, source: {source}"))]
+    SubmitProcedure {
+        #[snafu(backtrace)]
+        source: common_procedure::Error,
+    },
+
+    #[snafu(display("Failed to query procedure state, source: {source}"))]
+    QueryProcedure {
+        #[snafu(backtrace)]
+        source: common_procedure::Error,
+    },
+
     #[snafu(display("Schema already exists, name: {schema_name}"))]
     SchemaAlreadyExists {
         schema_name: String,
         location: Location,
     },
+
+    #[snafu(display("Heartbeat handler not found, name: {name}"))]
+    HeartbeatHandlerNotFound { name: String, location: Location },
+
+    #[snafu(display("Failed to request datanode {peer}, source: {source}"))]
+    RequestDatanode {
+        peer: String,
+        #[snafu(backtrace)]
+        source: client::Error,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -328,6 +371,7 @@ impl ErrorExt for Error {
             | Error::Unlock { .. }
             | Error::LeaseGrant { .. }
             | Error::LockNotConfig { .. }
+            | Error::RegionOperatorNotConfig { .. }
             | Error::ExceededRetryLimit { .. }
             | Error::SendShutdownSignal { .. }
             | Error::ParseAddr { .. }
@@ -340,6 +384,7 @@ impl ErrorExt for Error {
             | Error::InvalidStatKey { .. }
             | Error::ParseNum { .. }
             | Error::UnsupportedSelectorType { .. }
+            | Error::HeartbeatHandlerNotFound { .. }
             | Error::InvalidArguments { .. } => StatusCode::InvalidArguments,
             Error::LeaseKeyFromUtf8 { .. }
             | Error::LeaseValueFromUtf8 { .. }
@@ -347,6 +392,8 @@ impl ErrorExt for Error {
             | Error::StatValueFromUtf8 { .. }
             | Error::UnexceptedSequenceValue { .. }
             | Error::TableRouteNotFound { .. }
+            | Error::TableRouteChanged { .. }
+            | Error::TableGlobalValueChanged { .. }
             | Error::NextSequence { .. }
             | Error::MoveValue { .. }
             | Error::InvalidKvsLength { .. }
@@ -354,9 +401,14 @@ impl ErrorExt for Error {
             | Error::InvalidUtf8Value { .. }
             | Error::Unexpected { .. } => StatusCode::Unexpected,
             Error::TableNotFound { .. } => StatusCode::TableNotFound,
-            Error::InvalidCatalogValue { source, .. } => source.status_code(),
+            Error::InvalidCatalogValue { source, .. } | Error::InvalidTableName { source, .. } => {
+                source.status_code()
+            }
             Error::MetaInternal { source } => source.status_code(),
-            Error::RecoverProcedure { source } => source.status_code(),
+            Error::RequestDatanode { source, .. } => source.status_code(),
+            Error::RecoverProcedure { source }
+            | Error::SubmitProcedure { source }
+            | Error::QueryProcedure { source } => source.status_code(),
             Error::ShutdownServer { source, .. } | Error::StartHttp { source } => {
                 source.status_code()
             }
@@ -34,23 +34,55 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use api::v1::meta::{HeartbeatRequest, HeartbeatResponse, ResponseHeader};
-use common_telemetry::info;
+use common_telemetry::{info, timer, warn};
+use metrics::increment_counter;
+use snafu::OptionExt;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::RwLock;
 
 use self::instruction::Instruction;
 use self::node_stat::Stat;
-use crate::error::Result;
+use crate::error::{HeartbeatHandlerNotFoundSnafu, Result};
 use crate::metasrv::Context;
+use crate::metrics::{
+    METRIC_META_HEARTBEAT_HANDLER_ELAPSED, METRIC_META_HEARTBEAT_HANDLER_FAILURE,
+    METRIC_META_HEARTBEAT_HANDLER_LABEL,
+};
+
+/// What the [HeartbeatHandlerGroup] does after a handler handles a heartbeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleControl {
+    /// Passes the heartbeat to the next handler.
+    Continue,
+    /// Skips the rest handlers for this heartbeat, the response is built from what is
+    /// accumulated so far.
+    Skip,
+    /// Like [HandleControl::Skip], and also marks the context to skip all, so the handlers
+    /// ignore the later heartbeats of the same stream.
+    Stop,
+}
 
 #[async_trait::async_trait]
 pub trait HeartbeatHandler: Send + Sync {
+    /// Name of the handler in logs and metrics, which also locates the handler in the
+    /// [HeartbeatHandlerGroup].
+    fn name(&self) -> &'static str {
+        let type_name = std::any::type_name::<Self>();
+        type_name.rsplit("::").next().unwrap_or(type_name)
+    }
+
+    /// Whether an error of the handler fails the whole heartbeat. Errors of the handlers that
+    /// are not critical are logged, and the rest handlers still handle the heartbeat.
+    fn is_critical(&self) -> bool {
+        false
+    }
+
     async fn handle(
         &self,
         req: &HeartbeatRequest,
         ctx: &mut Context,
         acc: &mut HeartbeatAccumulator,
-    ) -> Result<()>;
+    ) -> Result<HandleControl>;
 }
 
 #[derive(Debug, Default)]
@@ -76,11 +108,49 @@ pub struct HeartbeatHandlerGroup {
 }
 
 impl HeartbeatHandlerGroup {
+    /// Appends the handler to the end of the handlers.
     pub async fn add_handler(&self, handler: impl HeartbeatHandler + 'static) {
         let mut handlers = self.handlers.write().await;
         handlers.push(Box::new(handler));
     }
 
+    /// Inserts the handler right before the handler named `name`.
+    pub async fn add_handler_before(
+        &self,
+        name: &str,
+        handler: impl HeartbeatHandler + 'static,
+    ) -> Result<()> {
+        let mut handlers = self.handlers.write().await;
+        let index = Self::position(&handlers, name)?;
+        handlers.insert(index, Box::new(handler));
+        Ok(())
+    }
+
+    /// Inserts the handler right after the handler named `name`.
+    pub async fn add_handler_after(
+        &self,
+        name: &str,
+        handler: impl HeartbeatHandler + 'static,
+    ) -> Result<()> {
+        let mut handlers = self.handlers.write().await;
+        let index = Self::position(&handlers, name)?;
+        handlers.insert(index + 1, Box::new(handler));
+        Ok(())
+    }
+
+    /// Returns the names of the handlers, in the order they handle heartbeats.
+    pub async fn handler_names(&self) -> Vec<&'static str> {
+        let handlers = self.handlers.read().await;
+        handlers.iter().map(|h| h.name()).collect()
+    }
+
+    fn position(handlers: &[Box<dyn HeartbeatHandler>], name: &str) -> Result<usize> {
+        handlers
+            .iter()
+            .position(|h| h.name() == name)
+            .context(HeartbeatHandlerNotFoundSnafu { name })
+    }
+
     pub async fn register(&self, key: impl AsRef<str>, pusher: Pusher) {
         let mut pushers = self.pushers.write().await;
         let key = key.as_ref();
@@ -103,7 +173,36 @@ impl HeartbeatHandlerGroup {
         let mut acc = HeartbeatAccumulator::default();
         let handlers = self.handlers.read().await;
         for h in handlers.iter() {
-            h.handle(&req, &mut ctx, &mut acc).await?;
+            let name = h.name();
+            let control = {
+                let _timer = timer!(
+                    METRIC_META_HEARTBEAT_HANDLER_ELAPSED,
+                    &[(METRIC_META_HEARTBEAT_HANDLER_LABEL, name)]
+                );
+                h.handle(&req, &mut ctx, &mut acc).await
+            };
+            let control = match control {
+                Ok(control) => control,
+                Err(e) if h.is_critical() => return Err(e),
+                Err(e) => {
+                    warn!(
+                        "Heartbeat handler {name} failed, continue with the rest handlers: {e:?}"
+                    );
+                    increment_counter!(
+                        METRIC_META_HEARTBEAT_HANDLER_FAILURE,
+                        METRIC_META_HEARTBEAT_HANDLER_LABEL => name
+                    );
+                    HandleControl::Continue
+                }
+            };
+            match control {
+                HandleControl::Continue => {}
+                HandleControl::Skip => break,
+                HandleControl::Stop => {
+                    ctx.set_skip_all();
+                    break;
+                }
+            }
         }
         let header = std::mem::take(&mut acc.header);
         let res = HeartbeatResponse {
@@ -113,3 +212,254 @@ impl HeartbeatHandlerGroup {
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use api::v1::meta::{Peer, RangeRequest, RequestHeader};
+
+    use super::*;
+    use crate::error::{self, Error};
+    use crate::keys::LeaseKey;
+    use crate::service::store::kv::KvStoreRef;
+    use crate::service::store::memory::MemStore;
+
+    fn new_context(kv_store: KvStoreRef) -> Context {
+        Context {
+            datanode_lease_secs: 30,
+            server_addr: "127.0.0.1:0000".to_string(),
+            in_memory: Arc::new(MemStore::new()),
+            kv_store,
+            election: None,
+            skip_all: Arc::new(AtomicBool::new(false)),
+            catalog: None,
+            schema: None,
+            table: None,
+            is_infancy: false,
+        }
+    }
+
+    fn new_request() -> HeartbeatRequest {
+        HeartbeatRequest {
+            header: Some(RequestHeader::new((1, 2))),
+            peer: Some(Peer {
+                id: 42,
+                addr: "127.0.0.1:3001".to_string(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Records its name when it handles a heartbeat, then returns the `control` or fails.
+    struct TestHandler {
+        name: &'static str,
+        control: Option<HandleControl>,
+        critical: bool,
+        handled: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl TestHandler {
+        fn new(name: &'static str, handled: &Arc<Mutex<Vec<&'static str>>>) -> Self {
+            Self {
+                name,
+                control: Some(HandleControl::Continue),
+                critical: false,
+                handled: handled.clone(),
+            }
+        }
+
+        fn failing(name: &'static str, critical: bool) -> Self {
+            Self {
+                name,
+                control: None,
+                critical,
+                handled: Arc::default(),
+            }
+        }
+
+        fn with_control(mut self, control: HandleControl) -> Self {
+            self.control = Some(control);
+            self
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HeartbeatHandler for TestHandler {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn is_critical(&self) -> bool {
+            self.critical
+        }
+
+        async fn handle(
+            &self,
+            _req: &HeartbeatRequest,
+            _ctx: &mut Context,
+            _acc: &mut HeartbeatAccumulator,
+        ) -> Result<HandleControl> {
+            self.handled.lock().unwrap().push(self.name);
+            match self.control {
+                Some(control) => Ok(control),
+                None => error::UnexpectedSnafu {
+                    violated: format!("{} failed", self.name),
+                }
+                .fail(),
+            }
+        }
+    }
+
+    async fn wait_for_lease(kv_store: &KvStoreRef) -> bool {
+        let key: Vec<u8> = LeaseKey {
+            cluster_id: 1,
+            node_id: 42,
+        }
+        .try_into()
+        .unwrap();
+        for _ in 0..50 {
+            let req = RangeRequest {
+                key: key.clone(),
+                ..Default::default()
+            };
+            if !kv_store.range(req).await.unwrap().kvs.is_empty() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_handler_order() {
+        let handled = Arc::default();
+        let group = HeartbeatHandlerGroup::default();
+        group.add_handler(TestHandler::new("a", &handled)).await;
+        group.add_handler(TestHandler::new("c", &handled)).await;
+        group
+            .add_handler_before("a", TestHandler::new("first", &handled))
+            .await
+            .unwrap();
+        group
+            .add_handler_after("a", TestHandler::new("b", &handled))
+            .await
+            .unwrap();
+        group
+            .add_handler_after("c", TestHandler::new("last", &handled))
+            .await
+            .unwrap();
+        let err = group
+            .add_handler_before("absent", TestHandler::new("x", &handled))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::HeartbeatHandlerNotFound { .. }));
+
+        let expected = vec!["first", "a", "b", "c", "last"];
+        assert_eq!(expected, group.handler_names().await);
+
+        let ctx = new_context(Arc::new(MemStore::new()));
+        let _ = group.handle(new_request(), ctx).await.unwrap();
+        assert_eq!(expected, *handled.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_handle_control() {
+        let handled = Arc::default();
+        let group = HeartbeatHandlerGroup::default();
+        group.add_handler(TestHandler::new("a", &handled)).await;
+        group
+            .add_handler(TestHandler::new("skip", &handled).with_control(HandleControl::Skip))
+            .await;
+        group.add_handler(TestHandler::new("b", &handled)).await;
+
+        let ctx = new_context(Arc::new(MemStore::new()));
+        let _ = group.handle(new_request(), ctx.clone()).await.unwrap();
+        assert_eq!(vec!["a", "skip"], *handled.lock().unwrap());
+        assert!(!ctx.is_skip_all());
+
+        let handled = Arc::default();
+        let group = HeartbeatHandlerGroup::default();
+        group
+            .add_handler(TestHandler::new("stop", &handled).with_control(HandleControl::Stop))
+            .await;
+        group.add_handler(TestHandler::new("b", &handled)).await;
+
+        let _ = group.handle(new_request(), ctx.clone()).await.unwrap();
+        assert_eq!(vec!["stop"], *handled.lock().unwrap());
+        assert!(ctx.is_skip_all());
+    }
+
+    #[tokio::test]
+    async fn test_non_critical_handler_failure() {
+        let kv_store: KvStoreRef = Arc::new(MemStore::new());
+        let group = HeartbeatHandlerGroup::default();
+        group.add_handler(ResponseHeaderHandler::default()).await;
+        group.add_handler(TestHandler::failing("fail", false)).await;
+        group
+            .add_handler(KeepLeaseHandler::new(kv_store.clone()))
+            .await;
+
+        let ctx = new_context(kv_store.clone());
+        let res = group.handle(new_request(), ctx).await.unwrap();
+        assert_eq!(1, res.header.unwrap().cluster_id);
+        assert!(wait_for_lease(&kv_store).await);
+    }
+
+    #[tokio::test]
+    async fn test_critical_handler_failure() {
+        let kv_store: KvStoreRef = Arc::new(MemStore::new());
+        let group = HeartbeatHandlerGroup::default();
+        group.add_handler(ResponseHeaderHandler::default()).await;
+        group.add_handler(TestHandler::failing("fail", true)).await;
+        group
+            .add_handler(KeepLeaseHandler::new(kv_store.clone()))
+            .await;
+
+        let ctx = new_context(kv_store.clone());
+        let err = group.handle(new_request(), ctx).await.unwrap_err();
+        assert!(matches!(err, Error::Unexpected { .. }));
+        assert!(!wait_for_lease(&kv_store).await);
+    }
+
+    #[tokio::test]
+    async fn test_handler_metrics() {
+        common_telemetry::init_default_metrics_recorder();
+        let handled = Arc::default();
+        let group = HeartbeatHandlerGroup::default();
+        group
+            .add_handler(TestHandler::new("metrics_test_ok", &handled))
+            .await;
+        group
+            .add_handler(TestHandler::failing("metrics_test_fail", false))
+            .await;
+
+        let ctx = new_context(Arc::new(MemStore::new()));
+        let _ = group.handle(new_request(), ctx).await.unwrap();
+
+        let text = common_telemetry::metric::try_handle().unwrap().render();
+        let has_metric = |metric: &str, handler: &str| {
+            let label = format!("handler=\"{handler}\"");
+            text.lines()
+                .any(|line| line.contains(metric) && line.contains(&label))
+        };
+        assert!(has_metric(
+            "meta_heartbeat_handler_elapsed",
+            "metrics_test_ok"
+        ));
+        assert!(has_metric(
+            "meta_heartbeat_handler_elapsed",
+            "metrics_test_fail"
+        ));
+        assert!(has_metric(
+            "meta_heartbeat_handler_failure",
+            "metrics_test_fail"
+        ));
+        assert!(!has_metric(
+            "meta_heartbeat_handler_failure",
+            "metrics_test_ok"
+        ));
+    }
+}
@@ -15,7 +15,7 @@
 use api::v1::meta::{Error, HeartbeatRequest};
 
 use crate::error::Result;
-use crate::handler::{HeartbeatAccumulator, HeartbeatHandler};
+use crate::handler::{HandleControl, HeartbeatAccumulator, HeartbeatHandler};
 use crate::metasrv::Context;
 
 #[derive(Default)]
@@ -23,21 +23,25 @@ pub struct CheckLeaderHandler;
 
 #[async_trait::async_trait]
 impl HeartbeatHandler for CheckLeaderHandler {
+    fn is_critical(&self) -> bool {
+        true
+    }
+
     async fn handle(
         &self,
         _req: &HeartbeatRequest,
         ctx: &mut Context,
         acc: &mut HeartbeatAccumulator,
-    ) -> Result<()> {
+    ) -> Result<HandleControl> {
         if let Some(election) = &ctx.election {
             if election.is_leader() {
-                return Ok(());
+                return Ok(HandleControl::Continue);
             }
             if let Some(header) = &mut acc.header {
                 header.error = Some(Error::is_not_leader());
-                ctx.set_skip_all();
+                return Ok(HandleControl::Stop);
             }
         }
-        Ok(())
+        Ok(HandleControl::Continue)
     }
 }
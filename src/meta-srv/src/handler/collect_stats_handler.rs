@@ -17,7 +17,7 @@ use common_telemetry::debug;
 
 use super::node_stat::Stat;
 use crate::error::Result;
-use crate::handler::{HeartbeatAccumulator, HeartbeatHandler};
+use crate::handler::{HandleControl, HeartbeatAccumulator, HeartbeatHandler};
 use crate::metasrv::Context;
 
 pub struct CollectStatsHandler;
@@ -29,9 +29,9 @@ impl HeartbeatHandler for CollectStatsHandler {
         req: &HeartbeatRequest,
         ctx: &mut Context,
         acc: &mut HeartbeatAccumulator,
-    ) -> Result<()> {
+    ) -> Result<HandleControl> {
         if ctx.is_skip_all() {
-            return Ok(());
+            return Ok(HandleControl::Continue);
         }
 
         match Stat::try_from(req.clone()) {
@@ -43,6 +43,6 @@ impl HeartbeatHandler for CollectStatsHandler {
             }
         };
 
-        Ok(())
+        Ok(HandleControl::Continue)
     }
 }
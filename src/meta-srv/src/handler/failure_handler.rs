@@ -19,7 +19,7 @@ use async_trait::async_trait;
 
 use crate::error::Result;
 use crate::handler::failure_handler::runner::{FailureDetectControl, FailureDetectRunner};
-use crate::handler::{HeartbeatAccumulator, HeartbeatHandler};
+use crate::handler::{HandleControl, HeartbeatAccumulator, HeartbeatHandler};
 use crate::metasrv::{Context, ElectionRef};
 
 #[derive(Eq, Hash, PartialEq, Clone)]
@@ -63,7 +63,7 @@ impl HeartbeatHandler for RegionFailureHandler {
         _: &HeartbeatRequest,
         ctx: &mut Context,
         acc: &mut HeartbeatAccumulator,
-    ) -> Result<()> {
+    ) -> Result<HandleControl> {
         if ctx.is_infancy {
             self.failure_detect_runner
                 .send_control(FailureDetectControl::Purge)
@@ -71,10 +71,10 @@ impl HeartbeatHandler for RegionFailureHandler {
         }
 
         if ctx.is_skip_all() {
-            return Ok(());
+            return Ok(HandleControl::Continue);
         }
 
-        let Some(stat) = acc.stat.as_ref() else { return Ok(()) };
+        let Some(stat) = acc.stat.as_ref() else { return Ok(HandleControl::Continue) };
 
         let heartbeat = DatanodeHeartbeat {
             cluster_id: stat.cluster_id,
@@ -93,7 +93,7 @@ impl HeartbeatHandler for RegionFailureHandler {
         };
 
         self.failure_detect_runner.send_heartbeat(heartbeat).await;
-        Ok(())
+        Ok(HandleControl::Continue)
     }
 }
 
@@ -126,6 +126,12 @@ mod tests {
                 wcus: 0,
                 approximate_bytes: 0,
                 approximate_rows: 0,
+                max_timestamp_millis: None,
+                last_write_millis: None,
+                flush_threshold_bytes: None,
+                wal_disabled: false,
+                storage_bytes: None,
+                memtable_bytes: None,
             }
         }
         acc.stat = Some(Stat {
@@ -18,7 +18,7 @@ use common_time::util as time_util;
 use tokio::sync::mpsc::{self, Sender};
 
 use crate::error::Result;
-use crate::handler::{HeartbeatAccumulator, HeartbeatHandler};
+use crate::handler::{HandleControl, HeartbeatAccumulator, HeartbeatHandler};
 use crate::keys::{LeaseKey, LeaseValue};
 use crate::metasrv::Context;
 use crate::service::store::kv::KvStoreRef;
@@ -55,14 +55,18 @@ impl KeepLeaseHandler {
 
 #[async_trait::async_trait]
 impl HeartbeatHandler for KeepLeaseHandler {
+    fn is_critical(&self) -> bool {
+        true
+    }
+
     async fn handle(
         &self,
         req: &HeartbeatRequest,
         ctx: &mut Context,
         _acc: &mut HeartbeatAccumulator,
-    ) -> Result<()> {
+    ) -> Result<HandleControl> {
         if ctx.is_skip_all() {
-            return Ok(());
+            return Ok(HandleControl::Continue);
         }
 
         let HeartbeatRequest { header, peer, .. } = req;
@@ -86,6 +90,6 @@ impl HeartbeatHandler for KeepLeaseHandler {
             }
         }
 
-        Ok(())
+        Ok(HandleControl::Continue)
     }
 }
@@ -13,6 +13,10 @@
 // limitations under the License.
 
 use api::v1::meta::HeartbeatRequest;
+use catalog::{
+    REGION_STAT_FLUSH_THRESHOLD_KEY, REGION_STAT_LAST_WRITE_KEY, REGION_STAT_MAX_TIMESTAMP_KEY,
+    REGION_STAT_MEMTABLE_BYTES_KEY, REGION_STAT_STORAGE_BYTES_KEY, REGION_STAT_WAL_DISABLED_KEY,
+};
 use common_time::util as time_util;
 use serde::{Deserialize, Serialize};
 
@@ -58,6 +62,24 @@ pub struct RegionStat {
     pub approximate_bytes: i64,
     /// Approximate number of rows in this region
     pub approximate_rows: i64,
+    /// Max time index value written to this region since it was opened, in milliseconds
+    #[serde(default)]
+    pub max_timestamp_millis: Option<i64>,
+    /// Wall-clock time of the last write to this region since it was opened, in milliseconds
+    #[serde(default)]
+    pub last_write_millis: Option<i64>,
+    /// Size of memtables that triggers a flush of this region
+    #[serde(default)]
+    pub flush_threshold_bytes: Option<i64>,
+    /// Whether writes to this region skip the WAL, so its unflushed data is lost on crash
+    #[serde(default)]
+    pub wal_disabled: bool,
+    /// Bytes of the SSTs of this region in the object store, including the ones pending purge
+    #[serde(default)]
+    pub storage_bytes: Option<i64>,
+    /// Bytes of the memtables of this region, which approximates its unflushed data in the WAL
+    #[serde(default)]
+    pub memtable_bytes: Option<i64>,
 }
 
 impl Stat {
@@ -114,6 +136,7 @@ impl TryFrom<HeartbeatRequest> for Stat {
 impl From<api::v1::meta::RegionStat> for RegionStat {
     fn from(value: api::v1::meta::RegionStat) -> Self {
         let table = value.table_name.as_ref();
+        let attr = |key: &str| value.attrs.get(key).and_then(|v| v.parse().ok());
         Self {
             id: value.region_id,
             catalog: table.map_or("", |t| &t.catalog_name).to_string(),
@@ -123,13 +146,30 @@ impl From<api::v1::meta::RegionStat> for RegionStat {
             wcus: value.wcus,
             approximate_bytes: value.approximate_bytes,
             approximate_rows: value.approximate_rows,
+            max_timestamp_millis: attr(REGION_STAT_MAX_TIMESTAMP_KEY),
+            last_write_millis: attr(REGION_STAT_LAST_WRITE_KEY),
+            flush_threshold_bytes: attr(REGION_STAT_FLUSH_THRESHOLD_KEY),
+            storage_bytes: attr(REGION_STAT_STORAGE_BYTES_KEY),
+            memtable_bytes: attr(REGION_STAT_MEMTABLE_BYTES_KEY),
+            wal_disabled: value
+                .attrs
+                .get(REGION_STAT_WAL_DISABLED_KEY)
+                .map_or(false, |v| v == "true"),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::handler::node_stat::Stat;
+    use std::collections::HashMap;
+
+    use catalog::{
+        REGION_STAT_FLUSH_THRESHOLD_KEY, REGION_STAT_LAST_WRITE_KEY, REGION_STAT_MAX_TIMESTAMP_KEY,
+        REGION_STAT_MEMTABLE_BYTES_KEY, REGION_STAT_STORAGE_BYTES_KEY,
+        REGION_STAT_WAL_DISABLED_KEY,
+    };
+
+    use crate::handler::node_stat::{RegionStat, Stat};
 
     #[test]
     fn test_stat_key() {
@@ -145,4 +185,49 @@ mod tests {
         assert_eq!(3, stat_key.cluster_id);
         assert_eq!(101, stat_key.node_id);
     }
+
+    #[test]
+    fn test_region_stat_write_attrs() {
+        let region_stat = RegionStat::from(api::v1::meta::RegionStat {
+            region_id: 1,
+            attrs: HashMap::from([
+                (
+                    REGION_STAT_MAX_TIMESTAMP_KEY.to_string(),
+                    "1000".to_string(),
+                ),
+                (REGION_STAT_LAST_WRITE_KEY.to_string(), "2000".to_string()),
+                (
+                    REGION_STAT_FLUSH_THRESHOLD_KEY.to_string(),
+                    "33554432".to_string(),
+                ),
+                (REGION_STAT_WAL_DISABLED_KEY.to_string(), "true".to_string()),
+                (
+                    REGION_STAT_STORAGE_BYTES_KEY.to_string(),
+                    "4096".to_string(),
+                ),
+                (
+                    REGION_STAT_MEMTABLE_BYTES_KEY.to_string(),
+                    "2048".to_string(),
+                ),
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(Some(1000), region_stat.max_timestamp_millis);
+        assert_eq!(Some(2000), region_stat.last_write_millis);
+        assert_eq!(Some(33554432), region_stat.flush_threshold_bytes);
+        assert!(region_stat.wal_disabled);
+        assert_eq!(Some(4096), region_stat.storage_bytes);
+        assert_eq!(Some(2048), region_stat.memtable_bytes);
+
+        // Regions not written since opened.
+        let region_stat = RegionStat::from(api::v1::meta::RegionStat {
+            region_id: 1,
+            ..Default::default()
+        });
+        assert_eq!(None, region_stat.max_timestamp_millis);
+        assert_eq!(None, region_stat.last_write_millis);
+        assert!(!region_stat.wal_disabled);
+        assert_eq!(None, region_stat.storage_bytes);
+        assert_eq!(None, region_stat.memtable_bytes);
+    }
 }
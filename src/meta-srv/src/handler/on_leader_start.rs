@@ -15,7 +15,7 @@
 use api::v1::meta::HeartbeatRequest;
 
 use crate::error::Result;
-use crate::handler::{HeartbeatAccumulator, HeartbeatHandler};
+use crate::handler::{HandleControl, HeartbeatAccumulator, HeartbeatHandler};
 use crate::metasrv::Context;
 
 #[derive(Default)]
@@ -28,13 +28,13 @@ impl HeartbeatHandler for OnLeaderStartHandler {
         _req: &HeartbeatRequest,
         ctx: &mut Context,
         _acc: &mut HeartbeatAccumulator,
-    ) -> Result<()> {
+    ) -> Result<HandleControl> {
         if let Some(election) = &ctx.election {
             if election.in_infancy() {
                 ctx.is_infancy = true;
                 ctx.reset_in_memory();
             }
         }
-        Ok(())
+        Ok(HandleControl::Continue)
     }
 }
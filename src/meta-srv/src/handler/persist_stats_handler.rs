@@ -17,7 +17,7 @@ use dashmap::DashMap;
 
 use crate::error::Result;
 use crate::handler::node_stat::Stat;
-use crate::handler::{HeartbeatAccumulator, HeartbeatHandler};
+use crate::handler::{HandleControl, HeartbeatAccumulator, HeartbeatHandler};
 use crate::keys::{StatKey, StatValue};
 use crate::metasrv::Context;
 
@@ -35,12 +35,12 @@ impl HeartbeatHandler for PersistStatsHandler {
         _req: &HeartbeatRequest,
         ctx: &mut Context,
         acc: &mut HeartbeatAccumulator,
-    ) -> Result<()> {
+    ) -> Result<HandleControl> {
         if ctx.is_skip_all() {
-            return Ok(());
+            return Ok(HandleControl::Continue);
         }
 
-        let Some(stat) = acc.stat.take() else { return Ok(()) };
+        let Some(stat) = acc.stat.take() else { return Ok(HandleControl::Continue) };
 
         let key = stat.stat_key();
         let mut entry = self
@@ -51,7 +51,7 @@ impl HeartbeatHandler for PersistStatsHandler {
         stats.push(stat);
 
         if stats.len() < MAX_CACHED_STATS_PER_KEY {
-            return Ok(());
+            return Ok(HandleControl::Continue);
         }
 
         let stats = stats.drain(..).collect();
@@ -66,7 +66,7 @@ impl HeartbeatHandler for PersistStatsHandler {
 
         ctx.in_memory.put(put).await?;
 
-        Ok(())
+        Ok(HandleControl::Continue)
     }
 }
 
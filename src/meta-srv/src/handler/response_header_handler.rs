@@ -15,7 +15,7 @@
 use api::v1::meta::{HeartbeatRequest, ResponseHeader, PROTOCOL_VERSION};
 
 use crate::error::Result;
-use crate::handler::{HeartbeatAccumulator, HeartbeatHandler};
+use crate::handler::{HandleControl, HeartbeatAccumulator, HeartbeatHandler};
 use crate::metasrv::Context;
 
 #[derive(Default)]
@@ -23,12 +23,16 @@ pub struct ResponseHeaderHandler;
 
 #[async_trait::async_trait]
 impl HeartbeatHandler for ResponseHeaderHandler {
+    fn is_critical(&self) -> bool {
+        true
+    }
+
     async fn handle(
         &self,
         req: &HeartbeatRequest,
         _ctx: &mut Context,
         acc: &mut HeartbeatAccumulator,
-    ) -> Result<()> {
+    ) -> Result<HandleControl> {
         let HeartbeatRequest { header, .. } = req;
         let res_header = ResponseHeader {
             protocol_version: PROTOCOL_VERSION,
@@ -36,7 +40,7 @@ impl HeartbeatHandler for ResponseHeaderHandler {
             ..Default::default()
         };
         acc.header = Some(res_header);
-        Ok(())
+        Ok(HandleControl::Continue)
     }
 }
 
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use api::v1::meta::RangeRequest;
+use api::v1::meta::{BatchDeleteRequest, RangeRequest};
 
 use crate::error::Result;
 use crate::keys::{LeaseKey, LeaseValue, DN_LEASE_PREFIX};
@@ -51,7 +51,108 @@ where
     Ok(lease_kvs)
 }
 
+/// Deletes the leases of the datanodes of all clusters, which have expired for `lease_secs`
+/// at `now_millis`, and returns them. Such datanodes are regarded as removed from the cluster,
+/// each returned once, while a datanode coming back puts its lease again by the heartbeat.
+pub async fn take_expired_datanodes(
+    kv_store: &KvStoreRef,
+    lease_secs: i64,
+    now_millis: i64,
+) -> Result<Vec<(LeaseKey, LeaseValue)>> {
+    let key = format!("{DN_LEASE_PREFIX}-").into_bytes();
+    let range_end = util::get_prefix_end_key(&key);
+    let req = RangeRequest {
+        key,
+        range_end,
+        ..Default::default()
+    };
+
+    let res = kv_store.range(req).await?;
+
+    let mut keys = vec![];
+    let mut lease_kvs = vec![];
+    for kv in res.kvs {
+        let lease_value: LeaseValue = kv.value.try_into()?;
+        if now_millis - lease_value.timestamp_millis <= lease_secs * 1000 {
+            continue;
+        }
+        let lease_key: LeaseKey = kv.key.clone().try_into()?;
+        keys.push(kv.key);
+        lease_kvs.push((lease_key, lease_value));
+    }
+
+    if !keys.is_empty() {
+        let req = BatchDeleteRequest {
+            keys,
+            ..Default::default()
+        };
+        let _ = kv_store.batch_delete(req).await?;
+    }
+
+    Ok(lease_kvs)
+}
+
 #[inline]
 pub fn get_lease_prefix(cluster_id: u64) -> Vec<u8> {
     format!("{DN_LEASE_PREFIX}-{cluster_id}").into_bytes()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use api::v1::meta::PutRequest;
+
+    use super::*;
+    use crate::service::store::memory::MemStore;
+
+    async fn put_lease(
+        kv_store: &KvStoreRef,
+        cluster_id: u64,
+        node_id: u64,
+        timestamp_millis: i64,
+    ) {
+        let key = LeaseKey {
+            cluster_id,
+            node_id,
+        };
+        let value = LeaseValue {
+            timestamp_millis,
+            node_addr: format!("127.0.0.1:300{node_id}"),
+        };
+        let req = PutRequest {
+            key: key.try_into().unwrap(),
+            value: value.try_into().unwrap(),
+            ..Default::default()
+        };
+        let _ = kv_store.put(req).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_take_expired_datanodes() {
+        let kv_store: KvStoreRef = Arc::new(MemStore::default());
+        put_lease(&kv_store, 1, 1, 1_000).await;
+        put_lease(&kv_store, 1, 2, 20_000).await;
+        put_lease(&kv_store, 2, 3, 1_000).await;
+
+        let expired = take_expired_datanodes(&kv_store, 15, 20_000).await.unwrap();
+        let mut nodes = expired
+            .iter()
+            .map(|(k, v)| (k.cluster_id, k.node_id, v.node_addr.as_str()))
+            .collect::<Vec<_>>();
+        nodes.sort();
+        assert_eq!(
+            vec![(1, 1, "127.0.0.1:3001"), (2, 3, "127.0.0.1:3003")],
+            nodes
+        );
+
+        // Each expired datanode is taken once.
+        assert!(take_expired_datanodes(&kv_store, 15, 20_000)
+            .await
+            .unwrap()
+            .is_empty());
+        let alive = alive_datanodes(1, &kv_store, |_, _| true).await.unwrap();
+        assert_eq!(1, alive.len());
+        assert_eq!(2, alive[0].0.node_id);
+    }
+}
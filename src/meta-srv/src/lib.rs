@@ -29,10 +29,12 @@ pub mod metasrv;
 mod metrics;
 #[cfg(feature = "mock")]
 pub mod mocks;
-mod procedure;
+pub mod procedure;
 pub mod selector;
 mod sequence;
 pub mod service;
+pub mod table_id_audit;
+pub mod table_invalidation;
 pub mod util;
 
 pub use crate::error::Result;
@@ -0,0 +1,153 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::OwnedMutexGuard;
+
+use super::{DistLock, DistLockRef, Opts, DEFAULT_EXPIRE_TIME_SECS};
+use crate::error::Result;
+
+/// A implementation of distributed lock in memory, for the metasrv with the memory store,
+/// which runs as a single node. The locks expire like the ones of etcd.
+#[derive(Default)]
+pub struct MemLock {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: AtomicU64,
+    /// Mutexes of the names some caller holds or waits for.
+    mutexes: Mutex<HashMap<Vec<u8>, Arc<tokio::sync::Mutex<()>>>>,
+    /// Guards of the held locks, by their keys, with the names locked.
+    guards: Mutex<HashMap<Vec<u8>, (Vec<u8>, OwnedMutexGuard<()>)>>,
+}
+
+impl MemLock {
+    pub fn new_ref() -> DistLockRef {
+        Arc::new(MemLock::default())
+    }
+}
+
+impl Inner {
+    fn release(&self, key: &[u8]) {
+        let Some((name, guard)) = self.guards.lock().unwrap().remove(key) else {
+            return;
+        };
+        drop(guard);
+
+        let mut mutexes = self.mutexes.lock().unwrap();
+        // Only the map holds the mutex if no other caller holds or waits for it.
+        if mutexes
+            .get(&name)
+            .map(|mutex| Arc::strong_count(mutex) == 1)
+            .unwrap_or(false)
+        {
+            let _ = mutexes.remove(&name);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DistLock for MemLock {
+    async fn lock(&self, name: Vec<u8>, opts: Opts) -> Result<Vec<u8>> {
+        let expire = opts.expire_secs.unwrap_or(DEFAULT_EXPIRE_TIME_SECS);
+
+        let mutex = self
+            .inner
+            .mutexes
+            .lock()
+            .unwrap()
+            .entry(name.clone())
+            .or_default()
+            .clone();
+        let guard = mutex.lock_owned().await;
+
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut key = name.clone();
+        key.extend_from_slice(format!("/{id}").as_bytes());
+        let _ = self
+            .inner
+            .guards
+            .lock()
+            .unwrap()
+            .insert(key.clone(), (name, guard));
+
+        // Releases the lock once it expires, if it's still held.
+        let inner = self.inner.clone();
+        let expired_key = key.clone();
+        common_runtime::spawn_bg(async move {
+            tokio::time::sleep(Duration::from_secs(expire)).await;
+            inner.release(&expired_key);
+        });
+
+        Ok(key)
+    }
+
+    async fn unlock(&self, key: Vec<u8>) -> Result<()> {
+        self.inner.release(&key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mem_lock() {
+        let lock = MemLock::default();
+        let key = lock
+            .lock(b"test".to_vec(), Opts { expire_secs: None })
+            .await
+            .unwrap();
+
+        // The lock is held until it's unlocked.
+        let locked = tokio::time::timeout(
+            Duration::from_millis(100),
+            lock.lock(b"test".to_vec(), Opts { expire_secs: None }),
+        )
+        .await;
+        assert!(locked.is_err());
+        // Other names are not blocked.
+        let other = lock
+            .lock(b"other".to_vec(), Opts { expire_secs: None })
+            .await
+            .unwrap();
+
+        lock.unlock(key).await.unwrap();
+        lock.unlock(other).await.unwrap();
+        let key = lock
+            .lock(
+                b"test".to_vec(),
+                Opts {
+                    expire_secs: Some(1),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, lock.inner.guards.lock().unwrap().len());
+
+        // The lock is released once it expires.
+        let _ = lock
+            .lock(b"test".to_vec(), Opts { expire_secs: None })
+            .await
+            .unwrap();
+        assert!(!lock.inner.guards.lock().unwrap().contains_key(&key));
+    }
+}
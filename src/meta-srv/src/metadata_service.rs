@@ -93,7 +93,7 @@ impl MetadataService for DefaultMetadataService {
         let req = CompareAndPutRequest {
             key: schema_key.into(),
             expect: vec![],
-            value: SchemaValue {}
+            value: SchemaValue::default()
                 .as_bytes()
                 .context(error::InvalidCatalogValueSnafu)?,
             ..Default::default()
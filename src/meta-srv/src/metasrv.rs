@@ -16,9 +16,11 @@ pub mod builder;
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use api::v1::meta::Peer;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_config::{validate_addr, FieldError, Validate};
 use common_procedure::ProcedureManagerRef;
 use common_telemetry::{error, info, warn};
 use serde::{Deserialize, Serialize};
@@ -32,12 +34,21 @@ use crate::error::{RecoverProcedureSnafu, Result};
 use crate::handler::HeartbeatHandlerGroup;
 use crate::lock::DistLockRef;
 use crate::metadata_service::MetadataServiceRef;
+use crate::procedure::ddl_batch::{rollback_expired_ddl_batches, DDL_BATCH_EXPIRE_MILLIS};
+use crate::procedure::repartition::{dispatch_repartition_requests, RegionOperatorRef};
 use crate::selector::{Selector, SelectorType};
 use crate::sequence::SequenceRef;
 use crate::service::store::kv::{KvStoreRef, ResettableKvStoreRef};
+use crate::table_id_audit::audit_table_ids;
+use crate::table_invalidation::{delete_expired_table_invalidations, publish_expired_datanodes};
 
 pub const TABLE_ID_SEQ: &str = "table_id";
 
+/// Interval of checking the requests to repartition tables.
+const REPARTITION_DISPATCH_INTERVAL: Duration = Duration::from_secs(1);
+/// Interval of deleting the expired invalidations of tables.
+const TABLE_INVALIDATION_REAP_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct MetaSrvOptions {
@@ -64,6 +75,17 @@ impl Default for MetaSrvOptions {
     }
 }
 
+impl Validate for MetaSrvOptions {
+    fn validate(&self) -> std::result::Result<(), FieldError> {
+        validate_addr("bind_addr", &self.bind_addr)?;
+        validate_addr("server_addr", &self.server_addr)?;
+        if !self.use_memory_store {
+            validate_addr("store_addr", &self.store_addr)?;
+        }
+        validate_addr("http_opts.addr", &self.http_opts.addr)
+    }
+}
+
 #[derive(Clone)]
 pub struct Context {
     pub datanode_lease_secs: i64,
@@ -113,6 +135,7 @@ pub struct MetaSrv {
     lock: Option<DistLockRef>,
     procedure_manager: ProcedureManagerRef,
     metadata_service: MetadataServiceRef,
+    region_operator: Option<RegionOperatorRef>,
 }
 
 impl MetaSrv {
@@ -130,6 +153,8 @@ impl MetaSrv {
 
         if let Some(election) = self.election() {
             let procedure_manager = self.procedure_manager.clone();
+            let kv_store = self.kv_store.clone();
+            let table_id_sequence = self.table_id_sequence.clone();
             let mut rx = election.subscribe_leader_change();
             common_runtime::spawn_bg(async move {
                 loop {
@@ -140,6 +165,11 @@ impl MetaSrv {
                                     if let Err(e) = procedure_manager.recover().await {
                                         error!("Failed to recover procedures, error: {e}");
                                     }
+                                    if let Err(e) =
+                                        audit_table_ids(&kv_store, &table_id_sequence).await
+                                    {
+                                        error!("Failed to audit table ids, error: {e}");
+                                    }
                                 }
                                 LeaderChangeMessage::StepDown(leader) => {
                                     // TODO(LFC): TBC
@@ -176,12 +206,104 @@ impl MetaSrv {
                 .recover()
                 .await
                 .context(RecoverProcedureSnafu)?;
+            if let Err(e) = audit_table_ids(&self.kv_store, &self.table_id_sequence).await {
+                error!("Failed to audit table ids, error: {e}");
+            }
         }
 
+        self.start_ddl_batch_reaper();
+        self.start_repartition_dispatcher();
+        self.start_table_invalidation_reaper();
+
         info!("MetaSrv started");
         Ok(())
     }
 
+    /// Rolls back the DDL batches abandoned by the frontends periodically, on the leader only.
+    fn start_ddl_batch_reaper(&self) {
+        let started = self.started.clone();
+        let election = self.election.clone();
+        let kv_store = self.kv_store.clone();
+        let region_operator = self.region_operator.clone();
+        let procedure_manager = self.procedure_manager.clone();
+        common_runtime::spawn_bg(async move {
+            let period = Duration::from_millis(DDL_BATCH_EXPIRE_MILLIS as u64);
+            while started.load(Ordering::Relaxed) {
+                tokio::time::sleep(period).await;
+                if election
+                    .as_ref()
+                    .map_or(false, |election| !election.is_leader())
+                {
+                    continue;
+                }
+                if let Err(e) = rollback_expired_ddl_batches(
+                    &kv_store,
+                    region_operator.clone(),
+                    &procedure_manager,
+                )
+                .await
+                {
+                    error!("Failed to roll back the abandoned DDL batches, error: {e}");
+                }
+            }
+        });
+    }
+
+    /// Serves the requests of the frontends to repartition tables, on the leader only.
+    fn start_repartition_dispatcher(&self) {
+        let Some(region_operator) = self.region_operator.clone() else {
+            return;
+        };
+        let started = self.started.clone();
+        let election = self.election.clone();
+        let kv_store = self.kv_store.clone();
+        let procedure_manager = self.procedure_manager.clone();
+        common_runtime::spawn_bg(async move {
+            while started.load(Ordering::Relaxed) {
+                tokio::time::sleep(REPARTITION_DISPATCH_INTERVAL).await;
+                if election
+                    .as_ref()
+                    .map_or(false, |election| !election.is_leader())
+                {
+                    continue;
+                }
+                if let Err(e) =
+                    dispatch_repartition_requests(&kv_store, &region_operator, &procedure_manager)
+                        .await
+                {
+                    error!("Failed to dispatch the requests to repartition tables, error: {e}");
+                }
+            }
+        });
+    }
+
+    /// Deletes the expired invalidations of tables published by the frontends periodically, on
+    /// the leader only, and publishes the datanodes whose leases have expired as removed.
+    fn start_table_invalidation_reaper(&self) {
+        let started = self.started.clone();
+        let election = self.election.clone();
+        let kv_store = self.kv_store.clone();
+        let lease_secs = self.options.datanode_lease_secs;
+        common_runtime::spawn_bg(async move {
+            while started.load(Ordering::Relaxed) {
+                tokio::time::sleep(TABLE_INVALIDATION_REAP_INTERVAL).await;
+                if election
+                    .as_ref()
+                    .map_or(false, |election| !election.is_leader())
+                {
+                    continue;
+                }
+                let now = common_time::util::current_time_millis();
+                if let Err(e) = delete_expired_table_invalidations(&kv_store, now).await {
+                    error!("Failed to delete the expired invalidations of tables, error: {e}");
+                }
+                if let Err(e) = publish_expired_datanodes(&kv_store, lease_secs, now).await {
+                    error!("Failed to publish the removed datanodes, error: {e}");
+                }
+            }
+        });
+    }
+
     async fn create_default_schema_if_not_exist(&self) -> Result<()> {
         self.metadata_service
             .create_schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, true)
@@ -237,6 +359,16 @@ impl MetaSrv {
         self.lock.clone()
     }
 
+    #[inline]
+    pub fn procedure_manager(&self) -> ProcedureManagerRef {
+        self.procedure_manager.clone()
+    }
+
+    #[inline]
+    pub fn region_operator(&self) -> Option<RegionOperatorRef> {
+        self.region_operator.clone()
+    }
+
     #[inline]
     pub fn new_ctx(&self) -> Context {
         let datanode_lease_secs = self.options().datanode_lease_secs;
@@ -25,6 +25,8 @@ use crate::handler::{
 use crate::lock::DistLockRef;
 use crate::metadata_service::{DefaultMetadataService, MetadataServiceRef};
 use crate::metasrv::{ElectionRef, MetaSrv, MetaSrvOptions, SelectorRef, TABLE_ID_SEQ};
+use crate::procedure::ddl_batch::RollbackDdlBatchProcedure;
+use crate::procedure::repartition::{RegionOperatorRef, RepartitionTableProcedure};
 use crate::procedure::state_store::MetaStateStore;
 use crate::selector::lease_based::LeaseBasedSelector;
 use crate::sequence::Sequence;
@@ -42,6 +44,7 @@ pub struct MetaSrvBuilder {
     meta_peer_client: Option<MetaPeerClient>,
     lock: Option<DistLockRef>,
     metadata_service: Option<MetadataServiceRef>,
+    region_operator: Option<RegionOperatorRef>,
 }
 
 impl MetaSrvBuilder {
@@ -56,6 +59,7 @@ impl MetaSrvBuilder {
             options: None,
             lock: None,
             metadata_service: None,
+            region_operator: None,
         }
     }
 
@@ -104,6 +108,11 @@ impl MetaSrvBuilder {
         self
     }
 
+    pub fn region_operator(mut self, region_operator: Option<RegionOperatorRef>) -> Self {
+        self.region_operator = region_operator;
+        self
+    }
+
     pub async fn build(self) -> MetaSrv {
         let started = Arc::new(AtomicBool::new(false));
 
@@ -117,6 +126,7 @@ impl MetaSrvBuilder {
             handler_group,
             lock,
             metadata_service,
+            region_operator,
         } = self;
 
         let options = options.unwrap_or_default();
@@ -154,6 +164,18 @@ impl MetaSrvBuilder {
         let config = ManagerConfig::default();
         let state_store = Arc::new(MetaStateStore::new(kv_store.clone()));
         let procedure_manager = Arc::new(LocalManager::new(config, state_store));
+        RollbackDdlBatchProcedure::register_loader(
+            kv_store.clone(),
+            region_operator.clone(),
+            procedure_manager.as_ref(),
+        );
+        if let Some(region_operator) = &region_operator {
+            RepartitionTableProcedure::register_loader(
+                kv_store.clone(),
+                region_operator.clone(),
+                procedure_manager.as_ref(),
+            );
+        }
 
         let metadata_service = metadata_service
             .unwrap_or_else(|| Arc::new(DefaultMetadataService::new(kv_store.clone())));
@@ -171,6 +193,7 @@ impl MetaSrvBuilder {
             lock,
             procedure_manager,
             metadata_service,
+            region_operator,
         }
     }
 }
@@ -14,3 +14,6 @@
 
 pub(crate) const METRIC_META_CREATE_CATALOG: &str = "meta.create_catalog";
 pub(crate) const METRIC_META_CREATE_SCHEMA: &str = "meta.create_schema";
+pub(crate) const METRIC_META_HEARTBEAT_HANDLER_ELAPSED: &str = "meta.heartbeat_handler_elapsed";
+pub(crate) const METRIC_META_HEARTBEAT_HANDLER_FAILURE: &str = "meta.heartbeat_handler_failure";
+pub(crate) const METRIC_META_HEARTBEAT_HANDLER_LABEL: &str = "handler";
@@ -16,12 +16,14 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use api::v1::meta::heartbeat_server::HeartbeatServer;
+use api::v1::meta::lock_server::LockServer;
 use api::v1::meta::router_server::RouterServer;
 use api::v1::meta::store_server::StoreServer;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_grpc::channel_manager::{ChannelConfig, ChannelManager};
 use tower::service_fn;
 
+use crate::lock::memory::MemLock;
 use crate::metadata_service::{DefaultMetadataService, MetadataService};
 use crate::metasrv::builder::MetaSrvBuilder;
 use crate::metasrv::{MetaSrvOptions, SelectorRef};
@@ -64,7 +66,10 @@ pub async fn mock(
         .await
         .unwrap();
 
-    let builder = MetaSrvBuilder::new().options(opts).kv_store(kv_store);
+    let builder = MetaSrvBuilder::new()
+        .options(opts)
+        .kv_store(kv_store)
+        .lock(Some(MemLock::new_ref()));
 
     let builder = match selector {
         Some(s) => builder.selector(s),
@@ -79,6 +84,7 @@ pub async fn mock(
             .add_service(HeartbeatServer::new(meta_srv.clone()))
             .add_service(RouterServer::new(meta_srv.clone()))
             .add_service(StoreServer::new(meta_srv.clone()))
+            .add_service(LockServer::new(meta_srv.clone()))
             .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
             .await
     });
@@ -12,4 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod ddl_batch;
+pub mod region_operator;
+pub mod repartition;
 pub(crate) mod state_store;
@@ -0,0 +1,526 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedure to roll back the batches of tables left behind by the frontends failing midway.
+//!
+//! A frontend creating a batch of tables all or nothing records every table it creates in the
+//! journal of the batch ([DdlBatchValue]), and deletes the journal once the batch is created or
+//! rolled back. A journal not updated for [DDL_BATCH_EXPIRE_MILLIS] is left by a frontend that
+//! crashed or lost the meta server, so the tables in it are dropped by their ids, in the reverse
+//! order of creation, then the journal is deleted.
+
+use api::v1::meta::{
+    CompareAndPutRequest, DeleteRangeRequest, MoveValueRequest, RangeRequest, TableName,
+};
+use async_trait::async_trait;
+use catalog::helper::{
+    build_ddl_batch_prefix, DdlBatchKey, DdlBatchTable, DdlBatchValue, TableGlobalValue,
+};
+use common_catalog::format_full_table_name;
+use common_procedure::error::{FromJsonSnafu, ToJsonSnafu};
+use common_procedure::{
+    Context, Error as ProcedureError, LockKey, Procedure, ProcedureManager, ProcedureManagerRef,
+    ProcedureWithId, Result as ProcedureResult, Status,
+};
+use common_telemetry::{info, warn};
+use common_time::util::current_time_millis;
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+
+use crate::error::{self, Result};
+use crate::keys::{to_removed_key, TableRouteKey};
+use crate::procedure::repartition::{
+    decode_route, RegionOperatorRef, RegionPlacement, RegionTable,
+};
+use crate::service::router::get_table_global_value;
+use crate::service::store::ext::KvStoreExt;
+use crate::service::store::kv::KvStoreRef;
+use crate::util;
+
+/// Time since the last update of the journal of a batch, after which the batch is considered
+/// abandoned by its frontend.
+pub const DDL_BATCH_EXPIRE_MILLIS: i64 = 5 * 60 * 1000;
+
+/// Procedure to drop the tables created by an abandoned batch, see the [module](self) docs.
+pub struct RollbackDdlBatchProcedure {
+    data: RollbackDdlBatchData,
+    kv_store: KvStoreRef,
+    region_operator: Option<RegionOperatorRef>,
+}
+
+#[async_trait]
+impl Procedure for RollbackDdlBatchProcedure {
+    fn type_name(&self) -> &str {
+        Self::TYPE_NAME
+    }
+
+    async fn execute(&mut self, _ctx: &Context) -> ProcedureResult<Status> {
+        self.on_rollback()
+            .await
+            .map_err(ProcedureError::retry_later)
+    }
+
+    fn dump(&self) -> ProcedureResult<String> {
+        let json = serde_json::to_string(&self.data).context(ToJsonSnafu)?;
+        Ok(json)
+    }
+
+    fn lock_key(&self) -> LockKey {
+        LockKey::new(self.data.tables.iter().map(|table| {
+            format_full_table_name(&table.catalog_name, &table.schema_name, &table.table_name)
+        }))
+    }
+}
+
+impl RollbackDdlBatchProcedure {
+    const TYPE_NAME: &str = "metasrv-procedure::RollbackDdlBatchProcedure";
+
+    pub fn new(
+        batch_id: String,
+        tables: Vec<DdlBatchTable>,
+        kv_store: KvStoreRef,
+        region_operator: Option<RegionOperatorRef>,
+    ) -> Self {
+        Self {
+            data: RollbackDdlBatchData {
+                batch_id,
+                tables,
+                rolled_back: 0,
+            },
+            kv_store,
+            region_operator,
+        }
+    }
+
+    /// Register the loader of this procedure to the `procedure_manager`.
+    ///
+    /// # Panics
+    /// Panics on error.
+    pub fn register_loader(
+        kv_store: KvStoreRef,
+        region_operator: Option<RegionOperatorRef>,
+        procedure_manager: &dyn ProcedureManager,
+    ) {
+        procedure_manager
+            .register_loader(
+                Self::TYPE_NAME,
+                Box::new(move |data| {
+                    Self::from_json(data, kv_store.clone(), region_operator.clone())
+                        .map(|p| Box::new(p) as _)
+                }),
+            )
+            .unwrap()
+    }
+
+    /// Recover the procedure from json.
+    fn from_json(
+        json: &str,
+        kv_store: KvStoreRef,
+        region_operator: Option<RegionOperatorRef>,
+    ) -> ProcedureResult<Self> {
+        let data: RollbackDdlBatchData = serde_json::from_str(json).context(FromJsonSnafu)?;
+        Ok(Self {
+            data,
+            kv_store,
+            region_operator,
+        })
+    }
+
+    /// Drops the next table in the reverse order of creation, then deletes the journal once
+    /// all the tables are dropped.
+    async fn on_rollback(&mut self) -> Result<Status> {
+        let tables = &self.data.tables;
+        if self.data.rolled_back < tables.len() {
+            let table = &tables[tables.len() - 1 - self.data.rolled_back];
+            self.drop_table(table).await?;
+            self.data.rolled_back += 1;
+            return Ok(Status::executing(true));
+        }
+
+        let key = DdlBatchKey {
+            batch_id: self.data.batch_id.clone(),
+        };
+        let _ = self
+            .kv_store
+            .delete_range(DeleteRangeRequest {
+                key: key.to_string().into_bytes(),
+                ..Default::default()
+            })
+            .await?;
+        info!(
+            "Rolled back {} tables of the abandoned batch {}",
+            self.data.tables.len(),
+            self.data.batch_id
+        );
+        Ok(Status::Done)
+    }
+
+    /// Drops the table if it's still the one created by the batch, i.e. the table of the name
+    /// has the id of the created table.
+    async fn drop_table(&self, table: &DdlBatchTable) -> Result<()> {
+        let table_key = table.table_global_key();
+        let table_ref =
+            format_full_table_name(&table.catalog_name, &table.schema_name, &table.table_name);
+        let route_key = TableRouteKey::with_table_global_key(table.table_id as u64, &table_key);
+
+        // The route is keyed by the table id, so it's only the route of the created table.
+        if let Some(route) = self.kv_store.get(route_key.key().into_bytes()).await? {
+            let (peers, region_routes) = decode_route(&route.value)?;
+            match &self.region_operator {
+                Some(region_operator) => {
+                    let region_table = RegionTable {
+                        table_id: table.table_id,
+                        table_name: TableName {
+                            catalog_name: table.catalog_name.clone(),
+                            schema_name: table.schema_name.clone(),
+                            table_name: table.table_name.clone(),
+                        },
+                    };
+                    for route in &region_routes {
+                        let (Some(region), Some(peer)) = (
+                            route.region.as_ref(),
+                            peers.get(route.leader_peer_index as usize),
+                        ) else {
+                            continue;
+                        };
+                        region_operator
+                            .drop_region(&region_table, &RegionPlacement::new(region.id, peer))
+                            .await?;
+                    }
+                }
+                None => warn!(
+                    "No region operator to drop the regions of table {table_ref}, they are left \
+                     to the orphan region collector of the datanodes"
+                ),
+            }
+            let key = route_key.key();
+            let removed_key = to_removed_key(&key);
+            move_value(&self.kv_store, key, removed_key).await?;
+        }
+
+        let global_key = table_key.to_string();
+        let Some(tgv) = get_table_global_value(&self.kv_store, &table_key).await? else {
+            info!("Table {table_ref} of the batch is already dropped");
+            return Ok(());
+        };
+        if tgv.table_id() != table.table_id {
+            info!(
+                "Table {table_ref} of the batch is already dropped, the table of the name has id {}",
+                tgv.table_id()
+            );
+            return Ok(());
+        }
+        let removed_key = to_removed_key(&global_key);
+        let Some(moved) = move_value(&self.kv_store, global_key.clone(), removed_key.clone()).await?
+        else {
+            return Ok(());
+        };
+        let moved_tgv =
+            TableGlobalValue::from_bytes(&moved).context(error::InvalidCatalogValueSnafu)?;
+        if moved_tgv.table_id() != table.table_id {
+            // Another table of the name was created since the value was read, puts it back.
+            warn!("Table {table_ref} was recreated while rolling back the batch, restores it");
+            let _ = self
+                .kv_store
+                .compare_and_put(CompareAndPutRequest {
+                    key: global_key.into_bytes(),
+                    expect: vec![],
+                    value: moved,
+                    ..Default::default()
+                })
+                .await?;
+        } else {
+            info!(
+                "Dropped table {table_ref} (id {}) of the abandoned batch {}",
+                table.table_id, self.data.batch_id
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Moves the value of `from_key` to `to_key`, returns the value moved if any.
+async fn move_value(
+    kv_store: &KvStoreRef,
+    from_key: String,
+    to_key: String,
+) -> Result<Option<Vec<u8>>> {
+    let resp = kv_store
+        .move_value(MoveValueRequest {
+            from_key: from_key.into_bytes(),
+            to_key: to_key.into_bytes(),
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.kv.map(|kv| kv.value))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RollbackDdlBatchData {
+    batch_id: String,
+    tables: Vec<DdlBatchTable>,
+    /// Number of the tables dropped, from the last created one.
+    rolled_back: usize,
+}
+
+/// Submits the procedures to roll back the batches whose journals are not updated for
+/// [DDL_BATCH_EXPIRE_MILLIS], returns the number of the procedures submitted.
+pub async fn rollback_expired_ddl_batches(
+    kv_store: &KvStoreRef,
+    region_operator: Option<RegionOperatorRef>,
+    procedure_manager: &ProcedureManagerRef,
+) -> Result<usize> {
+    let prefix = build_ddl_batch_prefix().into_bytes();
+    let req = RangeRequest {
+        range_end: util::get_prefix_end_key(&prefix),
+        key: prefix,
+        ..Default::default()
+    };
+    let kvs = kv_store.range(req).await?.kvs;
+
+    let now = current_time_millis();
+    let mut submitted = 0;
+    for kv in kvs {
+        let key = String::from_utf8(kv.key).context(error::InvalidUtf8ValueSnafu)?;
+        let key = DdlBatchKey::parse(key).context(error::InvalidCatalogValueSnafu)?;
+        let value = DdlBatchValue::from_bytes(kv.value).context(error::InvalidCatalogValueSnafu)?;
+        if now - value.updated_at_millis < DDL_BATCH_EXPIRE_MILLIS {
+            continue;
+        }
+
+        let procedure = RollbackDdlBatchProcedure::new(
+            key.batch_id.clone(),
+            value.tables,
+            kv_store.clone(),
+            region_operator.clone(),
+        );
+        let procedure_with_id = ProcedureWithId::with_random_id(Box::new(procedure));
+        let procedure_id = procedure_with_id.id;
+        info!(
+            "Roll back the abandoned batch {} by procedure {procedure_id}",
+            key.batch_id
+        );
+        // The journal is deleted by the procedure, so a batch is rolled back by another
+        // procedure if the metasrv restarts before the procedure completes, which is harmless
+        // as the tables are dropped by their ids.
+        let _ = procedure_manager
+            .submit(procedure_with_id)
+            .await
+            .context(error::SubmitProcedureSnafu)?;
+        submitted += 1;
+    }
+    Ok(submitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use api::v1::meta::{
+        Peer, PutRequest, Region, RegionRoute, Table, TableName, TableRoute, TableRouteValue,
+    };
+    use catalog::helper::TableGlobalKey;
+    use common_procedure::ProcedureId;
+    use table::metadata::{RawTableInfo, TableId};
+    use table::test_util::MemTable;
+    use table::Table as _;
+
+    use super::*;
+    use crate::procedure::repartition::{KeyRange, RegionOperator};
+    use crate::service::store::memory::MemStore;
+
+    #[derive(Default)]
+    struct MockRegionOperator {
+        dropped: Mutex<Vec<(TableId, u64)>>,
+    }
+
+    #[async_trait]
+    impl RegionOperator for MockRegionOperator {
+        async fn create_region(
+            &self,
+            _: &RegionTable,
+            _: &RegionPlacement,
+            _: &api::v1::meta::Partition,
+        ) -> Result<()> {
+            unreachable!()
+        }
+
+        async fn copy_rows(
+            &self,
+            _: &RegionTable,
+            _: &RegionPlacement,
+            _: &RegionPlacement,
+            _: &KeyRange,
+        ) -> Result<u64> {
+            unreachable!()
+        }
+
+        async fn watermark(&self, _: &RegionTable, _: &RegionPlacement) -> Result<u64> {
+            unreachable!()
+        }
+
+        async fn set_writable(&self, _: &RegionTable, _: &RegionPlacement, _: bool) -> Result<()> {
+            unreachable!()
+        }
+
+        async fn publish_region(&self, _: &RegionTable, _: &RegionPlacement) -> Result<()> {
+            unreachable!()
+        }
+
+        async fn drop_region(&self, table: &RegionTable, region: &RegionPlacement) -> Result<()> {
+            self.dropped
+                .lock()
+                .unwrap()
+                .push((table.table_id, region.region_id));
+            Ok(())
+        }
+    }
+
+    struct MockContextProvider;
+
+    #[async_trait]
+    impl common_procedure::ContextProvider for MockContextProvider {
+        async fn procedure_state(
+            &self,
+            _: ProcedureId,
+        ) -> ProcedureResult<Option<common_procedure::ProcedureState>> {
+            Ok(None)
+        }
+    }
+
+    fn context() -> Context {
+        Context {
+            procedure_id: ProcedureId::random(),
+            provider: Arc::new(MockContextProvider),
+        }
+    }
+
+    fn batch_table(table_name: &str, table_id: TableId) -> DdlBatchTable {
+        DdlBatchTable {
+            catalog_name: "greptime".to_string(),
+            schema_name: "public".to_string(),
+            table_name: table_name.to_string(),
+            table_id,
+        }
+    }
+
+    async fn put_table(kv_store: &KvStoreRef, table: &DdlBatchTable) {
+        let mut table_info = (*MemTable::default_numbers_table().table_info()).clone();
+        table_info.ident.table_id = table.table_id;
+        table_info.name = table.table_name.clone();
+        let key = table.table_global_key();
+        let value = TableGlobalValue {
+            node_id: 1,
+            regions_id_map: Default::default(),
+            table_info: RawTableInfo::from(table_info),
+        };
+        kv_store
+            .put(PutRequest {
+                key: key.to_string().into_bytes(),
+                value: value.as_bytes().unwrap(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let route = TableRouteValue {
+            peers: vec![Peer {
+                id: 1,
+                addr: "127.0.0.1:3001".to_string(),
+            }],
+            table_route: Some(TableRoute {
+                table: Some(Table {
+                    id: table.table_id as u64,
+                    table_name: Some(TableName {
+                        catalog_name: table.catalog_name.clone(),
+                        schema_name: table.schema_name.clone(),
+                        table_name: table.table_name.clone(),
+                    }),
+                    table_schema: vec![],
+                }),
+                region_routes: vec![RegionRoute {
+                    region: Some(Region {
+                        id: 0,
+                        ..Default::default()
+                    }),
+                    leader_peer_index: 0,
+                    follower_peer_indexes: vec![],
+                }],
+            }),
+        };
+        let route_key = TableRouteKey::with_table_global_key(table.table_id as u64, &key);
+        kv_store
+            .put(PutRequest {
+                key: route_key.key().into_bytes(),
+                value: route.into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn table_id_of(kv_store: &KvStoreRef, table: &DdlBatchTable) -> Option<TableId> {
+        let key: TableGlobalKey = table.table_global_key();
+        get_table_global_value(kv_store, &key)
+            .await
+            .unwrap()
+            .map(|tgv| tgv.table_id())
+    }
+
+    #[tokio::test]
+    async fn test_rollback_ddl_batch() {
+        let kv_store = Arc::new(MemStore::new()) as KvStoreRef;
+        let t1 = batch_table("t1", 1024);
+        let t2 = batch_table("t2", 1025);
+        put_table(&kv_store, &t1).await;
+        // `t2` of the batch is dropped, and another `t2` is created.
+        let another_t2 = batch_table("t2", 1026);
+        put_table(&kv_store, &another_t2).await;
+
+        let key = DdlBatchKey {
+            batch_id: "b1".to_string(),
+        };
+        let value = DdlBatchValue {
+            tables: vec![t1.clone(), t2.clone()],
+            updated_at_millis: 0,
+        };
+        kv_store
+            .put(PutRequest {
+                key: key.to_string().into_bytes(),
+                value: value.as_bytes().unwrap(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let region_operator = Arc::new(MockRegionOperator::default());
+        let mut procedure = RollbackDdlBatchProcedure::new(
+            key.batch_id.clone(),
+            value.tables,
+            kv_store.clone(),
+            Some(region_operator.clone()),
+        );
+        let ctx = context();
+        while !matches!(procedure.execute(&ctx).await.unwrap(), Status::Done) {}
+
+        assert_eq!(None, table_id_of(&kv_store, &t1).await);
+        assert_eq!(Some(1026), table_id_of(&kv_store, &t2).await);
+        assert_eq!(vec![(1024, 0)], *region_operator.dropped.lock().unwrap());
+        assert!(kv_store
+            .get(key.to_string().into_bytes())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [RegionOperator] sending the requests to the regions to the datanodes serving them.
+
+use api::v1::meta::Partition;
+use async_trait::async_trait;
+use client::{Client, Database};
+use common_grpc::channel_manager::ChannelManager;
+use snafu::{OptionExt, ResultExt};
+use table::requests::{RegionRequest, RegionRequestKind};
+
+use crate::error::{self, Result};
+use crate::procedure::repartition::{KeyRange, RegionOperator, RegionPlacement, RegionTable};
+
+/// Operates the regions through the flight services of the datanodes. The rows of a region are
+/// copied by the datanode of the source region, which writes them to the target region.
+#[derive(Default)]
+pub struct DatanodeRegionOperator {
+    channel_manager: ChannelManager,
+}
+
+impl DatanodeRegionOperator {
+    pub fn new(channel_manager: ChannelManager) -> Self {
+        Self { channel_manager }
+    }
+
+    async fn request(
+        &self,
+        table: &RegionTable,
+        region: &RegionPlacement,
+        kind: RegionRequestKind,
+    ) -> Result<Option<u64>> {
+        let table_name = &table.table_name;
+        let client =
+            Client::with_manager_and_urls(self.channel_manager.clone(), [&region.peer_addr]);
+        let database = Database::new(&table_name.catalog_name, &table_name.schema_name, client);
+        let request = RegionRequest {
+            catalog_name: table_name.catalog_name.clone(),
+            schema_name: table_name.schema_name.clone(),
+            table_name: table_name.table_name.clone(),
+            table_id: table.table_id,
+            region_number: region.region_id as u32,
+            kind,
+        };
+        database
+            .region_request(&request)
+            .await
+            .context(error::RequestDatanodeSnafu {
+                peer: &region.peer_addr,
+            })
+    }
+
+    async fn watermark_of(
+        &self,
+        table: &RegionTable,
+        region: &RegionPlacement,
+        kind: RegionRequestKind,
+    ) -> Result<u64> {
+        let watermark = self.request(table, region, kind).await?;
+        watermark.context(error::UnexpectedSnafu {
+            violated: format!("datanode should return the watermark of region {region:?}"),
+        })
+    }
+}
+
+#[async_trait]
+impl RegionOperator for DatanodeRegionOperator {
+    async fn create_region(
+        &self,
+        table: &RegionTable,
+        region: &RegionPlacement,
+        _partition: &Partition,
+    ) -> Result<()> {
+        let _ = self
+            .request(table, region, RegionRequestKind::Create)
+            .await?;
+        Ok(())
+    }
+
+    async fn copy_rows(
+        &self,
+        table: &RegionTable,
+        source: &RegionPlacement,
+        target: &RegionPlacement,
+        range: &KeyRange,
+    ) -> Result<u64> {
+        let to_strings = |values: &[Vec<u8>]| {
+            values
+                .iter()
+                .map(|v| String::from_utf8_lossy(v).to_string())
+                .collect::<Vec<_>>()
+        };
+        let kind = RegionRequestKind::CopyTo {
+            target_addr: target.peer_addr.clone(),
+            target_region: target.region_id as u32,
+            partition_columns: to_strings(&range.columns),
+            lower: range.lower.as_deref().map(to_strings),
+            upper: to_strings(&range.upper),
+        };
+        self.watermark_of(table, source, kind).await
+    }
+
+    async fn watermark(&self, table: &RegionTable, region: &RegionPlacement) -> Result<u64> {
+        self.watermark_of(table, region, RegionRequestKind::Watermark)
+            .await
+    }
+
+    async fn set_writable(
+        &self,
+        table: &RegionTable,
+        region: &RegionPlacement,
+        writable: bool,
+    ) -> Result<()> {
+        let _ = self
+            .request(table, region, RegionRequestKind::SetWritable { writable })
+            .await?;
+        Ok(())
+    }
+
+    async fn publish_region(&self, table: &RegionTable, region: &RegionPlacement) -> Result<()> {
+        let _ = self
+            .request(table, region, RegionRequestKind::Publish)
+            .await?;
+        Ok(())
+    }
+
+    async fn drop_region(&self, table: &RegionTable, region: &RegionPlacement) -> Result<()> {
+        let _ = self.request(table, region, RegionRequestKind::Drop).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1276 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedure to change the partition rule of a distributed table.
+//!
+//! The procedure creates the regions of the new rule, copies the rows of the old regions into
+//! them by key range, then switches the table route to the new regions and drops the old ones.
+//! The old regions keep serving reads and writes while the rows are copied. Writes to them are
+//! only paused for the catch-up copy right before the route is switched, which copies the new
+//! regions again if their sources are written since the first copy.
+//!
+//! Nothing is visible to the users of the table before the route is switched, so a failure
+//! before that drops the new regions and leaves the old layout intact. After the switch, the
+//! procedure retries until the new regions are published and the old ones are dropped.
+
+use std::sync::Arc;
+
+use api::v1::meta::{
+    CompareAndPutRequest, KeyValue, Partition, Peer, RangeRequest, Region, RegionRoute, TableName,
+    TableRouteValue,
+};
+use async_trait::async_trait;
+use catalog::helper::{
+    build_repartition_prefix, RepartitionStatus, RepartitionValue, TableGlobalKey, TableGlobalValue,
+};
+use common_procedure::error::{FromJsonSnafu, ToJsonSnafu};
+use common_procedure::{
+    Context, Error as ProcedureError, LockKey, Procedure, ProcedureId, ProcedureManager,
+    ProcedureManagerRef, ProcedureState, ProcedureWithId, Result as ProcedureResult, Status,
+};
+use common_telemetry::{info, warn};
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt};
+use table::metadata::TableId;
+
+use crate::error::{self, Error, Result};
+use crate::keys::TableRouteKey;
+use crate::service::router::{create_table_global_value, get_table_global_value};
+use crate::service::store::ext::KvStoreExt;
+use crate::service::store::kv::KvStoreRef;
+use crate::util;
+
+pub type RegionOperatorRef = Arc<dyn RegionOperator>;
+
+/// Operates the regions of tables on datanodes for the [RepartitionTableProcedure].
+///
+/// All the operations may be retried after a failure or a restart of the metasrv, so they
+/// should be idempotent.
+#[async_trait]
+pub trait RegionOperator: Send + Sync {
+    /// Creates the region of the table holding the rows in `partition`. The scans of
+    /// the table don't read the region until it's published.
+    async fn create_region(
+        &self,
+        table: &RegionTable,
+        region: &RegionPlacement,
+        partition: &Partition,
+    ) -> Result<()>;
+
+    /// Bulk copies the rows of `source` in `range` to `target`. Returns the watermark of
+    /// `source` the rows are copied up to.
+    async fn copy_rows(
+        &self,
+        table: &RegionTable,
+        source: &RegionPlacement,
+        target: &RegionPlacement,
+        range: &KeyRange,
+    ) -> Result<u64>;
+
+    /// Returns the watermark of the region, which changes whenever the region is written.
+    async fn watermark(&self, table: &RegionTable, region: &RegionPlacement) -> Result<u64>;
+
+    /// Pauses or resumes the writes to the region.
+    async fn set_writable(
+        &self,
+        table: &RegionTable,
+        region: &RegionPlacement,
+        writable: bool,
+    ) -> Result<()>;
+
+    /// Makes the scans of the table read the region.
+    async fn publish_region(&self, table: &RegionTable, region: &RegionPlacement) -> Result<()>;
+
+    /// Drops the region, succeeds if the region doesn't exist.
+    async fn drop_region(&self, table: &RegionTable, region: &RegionPlacement) -> Result<()>;
+}
+
+/// The table whose regions are operated by a [RegionOperator].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionTable {
+    pub table_id: TableId,
+    pub table_name: TableName,
+}
+
+/// A region of a table and the datanode serving it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionPlacement {
+    pub region_id: u64,
+    pub peer_id: u64,
+    pub peer_addr: String,
+}
+
+impl RegionPlacement {
+    pub(crate) fn new(region_id: u64, peer: &Peer) -> Self {
+        Self {
+            region_id,
+            peer_id: peer.id,
+            peer_addr: peer.addr.clone(),
+        }
+    }
+}
+
+/// The range of partition keys of a region.
+///
+/// Bounds are the serialized values of the partition columns, same as the `value_list` of a
+/// [Partition].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRange {
+    pub columns: Vec<Vec<u8>>,
+    /// Inclusive lower bound, `None` for the first region of the table.
+    pub lower: Option<Vec<Vec<u8>>>,
+    /// Exclusive upper bound.
+    pub upper: Vec<Vec<u8>>,
+}
+
+impl KeyRange {
+    fn partition(&self) -> Partition {
+        Partition {
+            column_list: self.columns.clone(),
+            value_list: self.upper.clone(),
+        }
+    }
+}
+
+/// Procedure to change the partition rule of a table to the given partitions.
+pub struct RepartitionTableProcedure {
+    data: RepartitionData,
+    kv_store: KvStoreRef,
+    region_operator: RegionOperatorRef,
+}
+
+#[async_trait]
+impl Procedure for RepartitionTableProcedure {
+    fn type_name(&self) -> &str {
+        Self::TYPE_NAME
+    }
+
+    async fn execute(&mut self, _ctx: &Context) -> ProcedureResult<Status> {
+        let result = match self.data.state {
+            RepartitionState::Prepare => self.on_prepare().await,
+            RepartitionState::CreateRegions => self.on_create_regions().await,
+            RepartitionState::CopyData => self.on_copy_data().await,
+            RepartitionState::SwitchRoute => self.on_switch_route().await,
+            // The route is switched, the procedure has to move on to serve the new regions and
+            // clean up the old ones.
+            RepartitionState::PublishRegions => {
+                return self
+                    .on_publish_regions()
+                    .await
+                    .map_err(ProcedureError::retry_later)
+            }
+            RepartitionState::DropOldRegions => {
+                return self
+                    .on_drop_old_regions()
+                    .await
+                    .map_err(ProcedureError::retry_later)
+            }
+        };
+        match result {
+            Ok(status) => Ok(status),
+            Err(e) => self.abort(e).await,
+        }
+    }
+
+    fn dump(&self) -> ProcedureResult<String> {
+        let json = serde_json::to_string(&self.data).context(ToJsonSnafu)?;
+        Ok(json)
+    }
+
+    fn lock_key(&self) -> LockKey {
+        LockKey::single(self.data.table_ref())
+    }
+}
+
+impl RepartitionTableProcedure {
+    const TYPE_NAME: &str = "metasrv-procedure::RepartitionTableProcedure";
+
+    /// Returns a new [RepartitionTableProcedure] to repartition the table by `partitions`,
+    /// whose columns must be the partition columns of the table.
+    pub fn new(
+        table_name: TableName,
+        partitions: Vec<Partition>,
+        kv_store: KvStoreRef,
+        region_operator: RegionOperatorRef,
+    ) -> Self {
+        let mut new_ranges = Vec::with_capacity(partitions.len());
+        let mut lower = None;
+        for partition in partitions {
+            new_ranges.push(KeyRange {
+                columns: partition.column_list,
+                lower,
+                upper: partition.value_list.clone(),
+            });
+            lower = Some(partition.value_list);
+        }
+
+        Self {
+            data: RepartitionData {
+                state: RepartitionState::Prepare,
+                catalog_name: table_name.catalog_name,
+                schema_name: table_name.schema_name,
+                table_name: table_name.table_name,
+                new_ranges,
+                table_id: 0,
+                old_route: Vec::new(),
+                old_regions: Vec::new(),
+                new_regions: Vec::new(),
+                copies: Vec::new(),
+                writes_paused: false,
+            },
+            kv_store,
+            region_operator,
+        }
+    }
+
+    /// Register the loader of this procedure to the `procedure_manager`.
+    ///
+    /// # Panics
+    /// Panics on error.
+    pub fn register_loader(
+        kv_store: KvStoreRef,
+        region_operator: RegionOperatorRef,
+        procedure_manager: &dyn ProcedureManager,
+    ) {
+        procedure_manager
+            .register_loader(
+                Self::TYPE_NAME,
+                Box::new(move |data| {
+                    Self::from_json(data, kv_store.clone(), region_operator.clone())
+                        .map(|p| Box::new(p) as _)
+                }),
+            )
+            .unwrap()
+    }
+
+    /// Recover the procedure from json.
+    fn from_json(
+        json: &str,
+        kv_store: KvStoreRef,
+        region_operator: RegionOperatorRef,
+    ) -> ProcedureResult<Self> {
+        let data: RepartitionData = serde_json::from_str(json).context(FromJsonSnafu)?;
+        Ok(Self {
+            data,
+            kv_store,
+            region_operator,
+        })
+    }
+
+    async fn on_prepare(&mut self) -> Result<Status> {
+        let table_ref = self.data.table_ref();
+        ensure!(
+            !self.data.new_ranges.is_empty(),
+            error::InvalidArgumentsSnafu {
+                err_msg: format!("No partitions to repartition table {table_ref} by"),
+            }
+        );
+
+        let table_key = self.data.table_key();
+        let tgv = get_table_global_value(&self.kv_store, &table_key)
+            .await?
+            .with_context(|| error::TableNotFoundSnafu { name: &table_ref })?;
+        let table_id = tgv.table_id();
+        let route_key = TableRouteKey::with_table_global_key(table_id as u64, &table_key).key();
+        let old_route = self
+            .kv_store
+            .get(route_key.clone().into_bytes())
+            .await?
+            .context(error::TableRouteNotFoundSnafu { key: &route_key })?
+            .value;
+        let (peers, region_routes) = decode_route(&old_route)?;
+        ensure!(
+            !peers.is_empty(),
+            error::UnexpectedSnafu {
+                violated: format!("Table {table_ref} has no peers"),
+            }
+        );
+
+        let mut old_regions = Vec::with_capacity(region_routes.len());
+        for route in &region_routes {
+            let region = route.region.as_ref().context(error::UnexpectedSnafu {
+                violated: "region should have been set",
+            })?;
+            if let Some(partition) = &region.partition {
+                ensure!(
+                    self.data
+                        .new_ranges
+                        .iter()
+                        .all(|range| range.columns == partition.column_list),
+                    error::InvalidArgumentsSnafu {
+                        err_msg: format!("Can't change the partition columns of table {table_ref}"),
+                    }
+                );
+            }
+            let peer =
+                peers
+                    .get(route.leader_peer_index as usize)
+                    .context(error::UnexpectedSnafu {
+                        violated: "leader peer should have been set",
+                    })?;
+            old_regions.push(RegionPlacement::new(region.id, peer));
+        }
+
+        // New regions take the ids after the old ones, placed on the peers of the table in
+        // turn like a new table.
+        let next_region_id = old_regions
+            .iter()
+            .map(|r| r.region_id + 1)
+            .max()
+            .unwrap_or(0);
+        let new_regions = (0..self.data.new_ranges.len())
+            .map(|i| RegionPlacement::new(next_region_id + i as u64, &peers[i % peers.len()]))
+            .collect::<Vec<_>>();
+        let copies = (0..new_regions.len())
+            .flat_map(|target| {
+                (0..old_regions.len()).map(move |source| CopyTask {
+                    source,
+                    target,
+                    watermark: None,
+                })
+            })
+            .collect();
+
+        info!(
+            "Repartition table {table_ref} from regions {:?} to regions {:?}",
+            old_regions, new_regions
+        );
+        self.data.table_id = table_id;
+        self.data.old_route = old_route;
+        self.data.old_regions = old_regions;
+        self.data.new_regions = new_regions;
+        self.data.copies = copies;
+        self.data.state = RepartitionState::CreateRegions;
+        Ok(Status::executing(true))
+    }
+
+    async fn on_create_regions(&mut self) -> Result<Status> {
+        let table = self.data.region_table();
+        for (region, range) in self.data.new_regions.iter().zip(&self.data.new_ranges) {
+            self.region_operator
+                .create_region(&table, region, &range.partition())
+                .await?;
+        }
+
+        self.data.state = RepartitionState::CopyData;
+        Ok(Status::executing(true))
+    }
+
+    async fn on_copy_data(&mut self) -> Result<Status> {
+        let table_ref = self.data.table_ref();
+        let table = self.data.region_table();
+        let num_copies = self.data.copies.len();
+        for (i, copy) in self.data.copies.iter_mut().enumerate() {
+            // Skips the copies done before the procedure is recovered.
+            if copy.watermark.is_some() {
+                continue;
+            }
+            let source = &self.data.old_regions[copy.source];
+            let target = &self.data.new_regions[copy.target];
+            let watermark = self
+                .region_operator
+                .copy_rows(&table, source, target, &self.data.new_ranges[copy.target])
+                .await?;
+            copy.watermark = Some(watermark);
+            info!(
+                "Repartition table {}, copied region {} to region {} ({}/{})",
+                table_ref,
+                source.region_id,
+                target.region_id,
+                i + 1,
+                num_copies
+            );
+        }
+
+        self.data.state = RepartitionState::SwitchRoute;
+        Ok(Status::executing(true))
+    }
+
+    async fn on_switch_route(&mut self) -> Result<Status> {
+        let table_ref = self.data.table_ref();
+        let table = self.data.region_table();
+        let route_key = self.route_key();
+        let new_route = self.new_route()?;
+
+        let current = self.kv_store.get(route_key.clone().into_bytes()).await?;
+        let switched = current.map(|kv| kv.value) == Some(new_route.clone());
+        // The route may have been switched before the procedure is recovered.
+        if !switched {
+            self.data.writes_paused = true;
+            for region in &self.data.old_regions {
+                self.region_operator
+                    .set_writable(&table, region, false)
+                    .await?;
+            }
+            self.catch_up().await?;
+
+            let resp = self
+                .kv_store
+                .compare_and_put(CompareAndPutRequest {
+                    key: route_key.into_bytes(),
+                    expect: self.data.old_route.clone(),
+                    value: new_route,
+                    ..Default::default()
+                })
+                .await?;
+            ensure!(
+                resp.success,
+                error::TableRouteChangedSnafu {
+                    table_name: &table_ref,
+                }
+            );
+            info!("Repartition table {table_ref}, switched the route to the new regions");
+        }
+
+        self.data.state = RepartitionState::PublishRegions;
+        Ok(Status::executing(true))
+    }
+
+    /// Copies the rows written to the old regions since they are copied, with the writes
+    /// paused.
+    ///
+    /// A new region is created again and copied from all its sources if any of them is written
+    /// since copied, so the rows deleted from the sources are gone from the new region too.
+    async fn catch_up(&mut self) -> Result<()> {
+        let table_ref = self.data.table_ref();
+        let table = self.data.region_table();
+        let RepartitionData {
+            old_regions,
+            new_regions,
+            new_ranges,
+            copies,
+            ..
+        } = &mut self.data;
+
+        let mut watermarks = Vec::with_capacity(old_regions.len());
+        for region in old_regions.iter() {
+            watermarks.push(self.region_operator.watermark(&table, region).await?);
+        }
+        for (target, (region, range)) in new_regions.iter().zip(new_ranges.iter()).enumerate() {
+            let is_stale = copies.iter().any(|copy| {
+                copy.target == target && copy.watermark != Some(watermarks[copy.source])
+            });
+            if !is_stale {
+                continue;
+            }
+
+            self.region_operator.drop_region(&table, region).await?;
+            self.region_operator
+                .create_region(&table, region, &range.partition())
+                .await?;
+            for copy in copies.iter_mut().filter(|copy| copy.target == target) {
+                let watermark = self
+                    .region_operator
+                    .copy_rows(&table, &old_regions[copy.source], region, range)
+                    .await?;
+                copy.watermark = Some(watermark);
+            }
+            info!(
+                "Repartition table {table_ref}, copied region {} again to catch up",
+                region.region_id
+            );
+        }
+        Ok(())
+    }
+
+    /// Publishes the new regions and updates the regions in the global value of the table,
+    /// which follow the route.
+    async fn on_publish_regions(&mut self) -> Result<Status> {
+        let table = self.data.region_table();
+        for region in &self.data.new_regions {
+            self.region_operator.publish_region(&table, region).await?;
+        }
+
+        let table_ref = self.data.table_ref();
+        let tgv_key = self.data.table_key().to_string().into_bytes();
+        let current = self
+            .kv_store
+            .get(tgv_key.clone())
+            .await?
+            .with_context(|| error::TableNotFoundSnafu { name: &table_ref })?
+            .value;
+        let tgv =
+            TableGlobalValue::from_bytes(&current).context(error::InvalidCatalogValueSnafu)?;
+        let new_trv: TableRouteValue = self
+            .new_route()?
+            .as_slice()
+            .try_into()
+            .context(error::DecodeTableRouteSnafu)?;
+        let mut table_info = tgv.table_info;
+        table_info.meta.region_numbers = self
+            .data
+            .new_regions
+            .iter()
+            .map(|r| r.region_id as u32)
+            .collect();
+        let new_tgv = create_table_global_value(&new_trv, table_info)?
+            .as_bytes()
+            .context(error::InvalidCatalogValueSnafu)?;
+        // Only updates the value read above, the table may be altered meanwhile.
+        if new_tgv != current {
+            let resp = self
+                .kv_store
+                .compare_and_put(CompareAndPutRequest {
+                    key: tgv_key,
+                    expect: current,
+                    value: new_tgv,
+                    ..Default::default()
+                })
+                .await?;
+            ensure!(
+                resp.success,
+                error::TableGlobalValueChangedSnafu {
+                    table_name: &table_ref,
+                }
+            );
+        }
+
+        self.data.state = RepartitionState::DropOldRegions;
+        Ok(Status::executing(true))
+    }
+
+    async fn on_drop_old_regions(&mut self) -> Result<Status> {
+        let table = self.data.region_table();
+        for region in &self.data.old_regions {
+            self.region_operator.drop_region(&table, region).await?;
+        }
+        info!(
+            "Repartition table {} done, dropped the old regions",
+            self.data.table_ref()
+        );
+
+        Ok(Status::Done)
+    }
+
+    /// Cleans up after a failure before the route is switched, then fails the procedure.
+    async fn abort(&mut self, error: Error) -> ProcedureResult<Status> {
+        let table = self.data.region_table();
+        warn!(
+            "Abort repartitioning table {}, state: {:?}, error: {}",
+            self.data.table_ref(),
+            self.data.state,
+            error
+        );
+        if self.data.writes_paused {
+            for region in &self.data.old_regions {
+                if let Err(e) = self
+                    .region_operator
+                    .set_writable(&table, region, true)
+                    .await
+                {
+                    warn!("Failed to resume writes to region {region:?}, error: {e}");
+                }
+            }
+        }
+        if self.data.state != RepartitionState::Prepare {
+            for region in &self.data.new_regions {
+                if let Err(e) = self.region_operator.drop_region(&table, region).await {
+                    warn!("Failed to drop new region {region:?}, error: {e}");
+                }
+            }
+        }
+
+        Err(ProcedureError::external(error))
+    }
+
+    fn route_key(&self) -> String {
+        TableRouteKey::with_table_global_key(self.data.table_id as u64, &self.data.table_key())
+            .key()
+    }
+
+    /// Returns the encoded route of the table over the new regions.
+    fn new_route(&self) -> Result<Vec<u8>> {
+        let mut trv: TableRouteValue = self
+            .data
+            .old_route
+            .as_slice()
+            .try_into()
+            .context(error::DecodeTableRouteSnafu)?;
+        let table_route = trv.table_route.as_mut().context(error::UnexpectedSnafu {
+            violated: "table route should have been set",
+        })?;
+
+        let mut region_routes = Vec::with_capacity(self.data.new_regions.len());
+        for (region, range) in self.data.new_regions.iter().zip(&self.data.new_ranges) {
+            let leader_peer_index = trv
+                .peers
+                .iter()
+                .position(|peer| peer.id == region.peer_id)
+                .context(error::UnexpectedSnafu {
+                    violated: format!("peer of region {region:?} should be in the route"),
+                })?;
+            region_routes.push(RegionRoute {
+                region: Some(Region {
+                    id: region.region_id,
+                    partition: Some(range.partition()),
+                    ..Default::default()
+                }),
+                leader_peer_index: leader_peer_index as u64,
+                follower_peer_indexes: vec![],
+            });
+        }
+        table_route.region_routes = region_routes;
+
+        Ok(trv.into())
+    }
+}
+
+/// Submits the procedures for the pending requests to repartition tables put by the frontends,
+/// and records the outcomes of the submitted ones in the requests, see [RepartitionValue].
+/// Returns the number of the requests updated.
+pub async fn dispatch_repartition_requests(
+    kv_store: &KvStoreRef,
+    region_operator: &RegionOperatorRef,
+    procedure_manager: &ProcedureManagerRef,
+) -> Result<usize> {
+    let prefix = build_repartition_prefix().into_bytes();
+    let req = RangeRequest {
+        range_end: util::get_prefix_end_key(&prefix),
+        key: prefix,
+        ..Default::default()
+    };
+    let kvs = kv_store.range(req).await?.kvs;
+
+    let mut updated = 0;
+    for kv in kvs {
+        let request =
+            RepartitionValue::from_bytes(&kv.value).context(error::InvalidCatalogValueSnafu)?;
+        let table_ref = format!(
+            "{}.{}.{}",
+            request.catalog_name, request.schema_name, request.table_name
+        );
+        let status = match &request.status {
+            RepartitionStatus::Pending => {
+                let table_name = TableName {
+                    catalog_name: request.catalog_name.clone(),
+                    schema_name: request.schema_name.clone(),
+                    table_name: request.table_name.clone(),
+                };
+                let partitions = request
+                    .partitions
+                    .iter()
+                    .map(|p| Partition {
+                        column_list: p.columns.iter().map(|c| c.clone().into_bytes()).collect(),
+                        value_list: p.bounds.iter().map(|b| b.clone().into_bytes()).collect(),
+                    })
+                    .collect();
+                let procedure = RepartitionTableProcedure::new(
+                    table_name,
+                    partitions,
+                    kv_store.clone(),
+                    region_operator.clone(),
+                );
+                let procedure = ProcedureWithId::with_random_id(Box::new(procedure));
+                let procedure_id = procedure.id;
+
+                // Claims the request before submitting the procedure, so only one procedure is
+                // ever submitted for it.
+                let running = RepartitionStatus::Running {
+                    procedure_id: procedure_id.to_string(),
+                };
+                let Some(kv) = update_request_status(kv_store, kv, &request, running).await? else {
+                    continue;
+                };
+                updated += 1;
+                info!("Repartition table {table_ref} by procedure {procedure_id}");
+                if let Err(e) = procedure_manager.submit(procedure).await {
+                    let failed = RepartitionStatus::Failed {
+                        error: e.to_string(),
+                    };
+                    let _ = update_request_status(kv_store, kv, &request, failed).await?;
+                }
+                continue;
+            }
+            RepartitionStatus::Running { procedure_id } => {
+                let id = ProcedureId::parse_str(procedure_id).ok().with_context(|| {
+                    error::UnexpectedSnafu {
+                        violated: format!("Invalid procedure id: {procedure_id}"),
+                    }
+                })?;
+                let state = procedure_manager
+                    .procedure_state(id)
+                    .await
+                    .context(error::QueryProcedureSnafu)?;
+                match state {
+                    Some(ProcedureState::Done) => RepartitionStatus::Done,
+                    Some(ProcedureState::Failed { error }) => RepartitionStatus::Failed {
+                        error: error.to_string(),
+                    },
+                    Some(ProcedureState::Running) | Some(ProcedureState::Retrying { .. }) => {
+                        continue
+                    }
+                    // The states of the finished procedures are not recovered after the metasrv
+                    // restarts.
+                    None => RepartitionStatus::Failed {
+                        error: format!(
+                            "Procedure {procedure_id} is not found, it may be finished before the \
+                             metasrv restarted, check the partitions of table {table_ref}"
+                        ),
+                    },
+                }
+            }
+            // Deleted by the frontend requesting it.
+            RepartitionStatus::Done | RepartitionStatus::Failed { .. } => continue,
+        };
+        if update_request_status(kv_store, kv, &request, status)
+            .await?
+            .is_some()
+        {
+            updated += 1;
+        }
+    }
+    Ok(updated)
+}
+
+/// Updates the status of the repartition request in `kv` if it's not changed, returns the
+/// updated key value, or `None` if the request is changed.
+async fn update_request_status(
+    kv_store: &KvStoreRef,
+    kv: KeyValue,
+    request: &RepartitionValue,
+    status: RepartitionStatus,
+) -> Result<Option<KeyValue>> {
+    let value = RepartitionValue {
+        status,
+        ..request.clone()
+    }
+    .as_bytes()
+    .context(error::InvalidCatalogValueSnafu)?;
+    let resp = kv_store
+        .compare_and_put(CompareAndPutRequest {
+            key: kv.key.clone(),
+            expect: kv.value,
+            value: value.clone(),
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.success.then_some(KeyValue { key: kv.key, value }))
+}
+
+pub(crate) fn decode_route(route: &[u8]) -> Result<(Vec<Peer>, Vec<RegionRoute>)> {
+    let trv: TableRouteValue = route.try_into().context(error::DecodeTableRouteSnafu)?;
+    let table_route = trv.table_route.context(error::UnexpectedSnafu {
+        violated: "table route should have been set",
+    })?;
+    Ok((trv.peers, table_route.region_routes))
+}
+
+/// Represents each step of repartitioning a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum RepartitionState {
+    /// Validate the partitions and plan the new regions.
+    Prepare,
+    /// Create the new regions on datanodes.
+    CreateRegions,
+    /// Copy the rows of the old regions into the new regions.
+    CopyData,
+    /// Catch up the copies with the writes paused, and switch the route to the new regions.
+    SwitchRoute,
+    /// Publish the new regions and update the global value of the table.
+    PublishRegions,
+    /// Drop the old regions.
+    DropOldRegions,
+}
+
+/// Copy of the rows of an old region in the key range of a new region.
+#[derive(Debug, Serialize, Deserialize)]
+struct CopyTask {
+    /// Index of the source in the old regions.
+    source: usize,
+    /// Index of the target in the new regions.
+    target: usize,
+    /// Watermark of the source the rows are copied up to, `None` if not copied yet.
+    watermark: Option<u64>,
+}
+
+/// Serializable data of [RepartitionTableProcedure].
+#[derive(Debug, Serialize, Deserialize)]
+struct RepartitionData {
+    state: RepartitionState,
+    catalog_name: String,
+    schema_name: String,
+    table_name: String,
+    /// Key ranges of the new regions.
+    new_ranges: Vec<KeyRange>,
+    /// Id of the table, available after [RepartitionState::Prepare].
+    table_id: TableId,
+    /// Encoded route of the table before repartitioning, the route is only switched if it's
+    /// still this one.
+    old_route: Vec<u8>,
+    old_regions: Vec<RegionPlacement>,
+    new_regions: Vec<RegionPlacement>,
+    copies: Vec<CopyTask>,
+    /// Whether the writes to the old regions are paused.
+    writes_paused: bool,
+}
+
+impl RepartitionData {
+    fn table_ref(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.catalog_name, self.schema_name, self.table_name
+        )
+    }
+
+    fn region_table(&self) -> RegionTable {
+        RegionTable {
+            table_id: self.table_id,
+            table_name: TableName {
+                catalog_name: self.catalog_name.clone(),
+                schema_name: self.schema_name.clone(),
+                table_name: self.table_name.clone(),
+            },
+        }
+    }
+
+    fn table_key(&self) -> TableGlobalKey {
+        TableGlobalKey {
+            catalog_name: self.catalog_name.clone(),
+            schema_name: self.schema_name.clone(),
+            table_name: self.table_name.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    use api::v1::meta::TableRoute;
+    use catalog::helper::TableGlobalValue;
+    use common_procedure::{ContextProvider, ProcedureId, ProcedureState};
+    use table::metadata::RawTableInfo;
+    use table::test_util::MemTable;
+    use table::Table;
+
+    use super::*;
+    use crate::service::store::memory::MemStore;
+
+    const TABLE_ID: TableId = 1024;
+
+    struct MockContextProvider;
+
+    #[async_trait]
+    impl ContextProvider for MockContextProvider {
+        async fn procedure_state(&self, _: ProcedureId) -> ProcedureResult<Option<ProcedureState>> {
+            Ok(None)
+        }
+    }
+
+    #[derive(Default)]
+    struct MockRegion {
+        /// Rows of the region, as (sequence, key).
+        rows: Vec<(u64, i64)>,
+        paused: bool,
+        published: bool,
+    }
+
+    /// Datanodes keeping the rows of regions in memory, the key of a row is its only partition
+    /// column.
+    #[derive(Default)]
+    struct MockRegionOperator {
+        regions: Mutex<HashMap<u64, MockRegion>>,
+        next_sequence: Mutex<u64>,
+        fail_copy: AtomicBool,
+    }
+
+    impl MockRegionOperator {
+        fn write(&self, region_id: u64, key: i64) {
+            let mut sequence = self.next_sequence.lock().unwrap();
+            *sequence += 1;
+            let mut regions = self.regions.lock().unwrap();
+            let region = regions.entry(region_id).or_default();
+            assert!(!region.paused);
+            region.rows.push((*sequence, key));
+        }
+
+        fn region_ids(&self) -> Vec<u64> {
+            let mut ids = self
+                .regions
+                .lock()
+                .unwrap()
+                .keys()
+                .copied()
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        }
+
+        fn keys(&self, region_id: u64) -> Vec<i64> {
+            let regions = self.regions.lock().unwrap();
+            let mut keys = regions[&region_id]
+                .rows
+                .iter()
+                .map(|(_, key)| *key)
+                .collect::<Vec<_>>();
+            keys.sort();
+            keys
+        }
+
+        fn is_paused(&self, region_id: u64) -> bool {
+            self.regions.lock().unwrap()[&region_id].paused
+        }
+
+        fn is_published(&self, region_id: u64) -> bool {
+            self.regions.lock().unwrap()[&region_id].published
+        }
+    }
+
+    fn bound(value: Option<i64>) -> Vec<u8> {
+        match value {
+            Some(v) => format!(r#"{{"Value":{{"Int64":{v}}}}}"#).into_bytes(),
+            None => br#""MaxValue""#.to_vec(),
+        }
+    }
+
+    fn decode_bound(bound: &[u8]) -> Option<i64> {
+        let value: serde_json::Value = serde_json::from_slice(bound).unwrap();
+        value["Value"]["Int64"].as_i64()
+    }
+
+    fn in_range(key: i64, range: &KeyRange) -> bool {
+        let above_lower = match &range.lower {
+            Some(lower) => decode_bound(&lower[0]).map_or(false, |v| key >= v),
+            None => true,
+        };
+        let below_upper = decode_bound(&range.upper[0]).map_or(true, |v| key < v);
+        above_lower && below_upper
+    }
+
+    #[async_trait]
+    impl RegionOperator for MockRegionOperator {
+        async fn create_region(
+            &self,
+            _: &RegionTable,
+            region: &RegionPlacement,
+            _: &Partition,
+        ) -> Result<()> {
+            let _ = self
+                .regions
+                .lock()
+                .unwrap()
+                .entry(region.region_id)
+                .or_default();
+            Ok(())
+        }
+
+        async fn copy_rows(
+            &self,
+            _: &RegionTable,
+            source: &RegionPlacement,
+            target: &RegionPlacement,
+            range: &KeyRange,
+        ) -> Result<u64> {
+            ensure!(
+                !self.fail_copy.load(Ordering::Relaxed),
+                error::UnexpectedSnafu {
+                    violated: "injected copy failure",
+                }
+            );
+            let mut regions = self.regions.lock().unwrap();
+            let source = &regions[&source.region_id];
+            let rows = source
+                .rows
+                .iter()
+                .filter(|(_, key)| in_range(*key, range))
+                .copied()
+                .collect::<Vec<_>>();
+            let watermark = source.rows.iter().map(|(s, _)| *s).max().unwrap_or(0);
+            regions
+                .get_mut(&target.region_id)
+                .unwrap()
+                .rows
+                .extend(rows);
+            Ok(watermark)
+        }
+
+        async fn watermark(&self, _: &RegionTable, region: &RegionPlacement) -> Result<u64> {
+            let regions = self.regions.lock().unwrap();
+            let rows = &regions[&region.region_id].rows;
+            Ok(rows.iter().map(|(s, _)| *s).max().unwrap_or(0))
+        }
+
+        async fn set_writable(
+            &self,
+            _: &RegionTable,
+            region: &RegionPlacement,
+            writable: bool,
+        ) -> Result<()> {
+            self.regions
+                .lock()
+                .unwrap()
+                .get_mut(&region.region_id)
+                .unwrap()
+                .paused = !writable;
+            Ok(())
+        }
+
+        async fn publish_region(&self, _: &RegionTable, region: &RegionPlacement) -> Result<()> {
+            self.regions
+                .lock()
+                .unwrap()
+                .get_mut(&region.region_id)
+                .unwrap()
+                .published = true;
+            Ok(())
+        }
+
+        async fn drop_region(&self, _: &RegionTable, region: &RegionPlacement) -> Result<()> {
+            let _ = self.regions.lock().unwrap().remove(&region.region_id);
+            Ok(())
+        }
+    }
+
+    fn peers() -> Vec<Peer> {
+        vec![
+            Peer {
+                id: 1,
+                addr: "127.0.0.1:4001".to_string(),
+            },
+            Peer {
+                id: 2,
+                addr: "127.0.0.1:4002".to_string(),
+            },
+        ]
+    }
+
+    fn table_name() -> TableName {
+        TableName {
+            catalog_name: "greptime".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "my_table".to_string(),
+        }
+    }
+
+    fn partitions(bounds: &[Option<i64>]) -> Vec<Partition> {
+        bounds
+            .iter()
+            .map(|v| Partition {
+                column_list: vec![b"id".to_vec()],
+                value_list: vec![bound(*v)],
+            })
+            .collect()
+    }
+
+    fn table_key() -> TableGlobalKey {
+        let table_name = table_name();
+        TableGlobalKey {
+            catalog_name: table_name.catalog_name,
+            schema_name: table_name.schema_name,
+            table_name: table_name.table_name,
+        }
+    }
+
+    fn route_key() -> String {
+        TableRouteKey::with_table_global_key(TABLE_ID as u64, &table_key()).key()
+    }
+
+    /// Creates a table of two regions split at 100 on two datanodes.
+    async fn create_table(kv_store: &KvStoreRef) -> Vec<u8> {
+        let region_routes = partitions(&[Some(100), None])
+            .into_iter()
+            .enumerate()
+            .map(|(i, partition)| RegionRoute {
+                region: Some(Region {
+                    id: i as u64,
+                    partition: Some(partition),
+                    ..Default::default()
+                }),
+                leader_peer_index: i as u64,
+                follower_peer_indexes: vec![],
+            })
+            .collect();
+        let trv = TableRouteValue {
+            peers: peers(),
+            table_route: Some(TableRoute {
+                region_routes,
+                ..Default::default()
+            }),
+        };
+
+        let mut table_info = (*MemTable::default_numbers_table().table_info()).clone();
+        table_info.ident.table_id = TABLE_ID;
+        table_info.name = "my_table".to_string();
+        let tgv = create_table_global_value(&trv, RawTableInfo::from(table_info)).unwrap();
+
+        let route: Vec<u8> = trv.into();
+        for (key, value) in [
+            (table_key().to_string(), tgv.as_bytes().unwrap()),
+            (route_key(), route.clone()),
+        ] {
+            kv_store
+                .put(PutRequest {
+                    key: key.into_bytes(),
+                    value,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+        route
+    }
+
+    async fn get_value(kv_store: &KvStoreRef, key: String) -> Vec<u8> {
+        kv_store.get(key.into_bytes()).await.unwrap().unwrap().value
+    }
+
+    fn context() -> Context {
+        Context {
+            procedure_id: ProcedureId::random(),
+            provider: Arc::new(MockContextProvider),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repartition_two_to_four() {
+        let kv_store = Arc::new(MemStore::new()) as KvStoreRef;
+        let _ = create_table(&kv_store).await;
+        let operator = Arc::new(MockRegionOperator::default());
+        for (region_id, key) in [(0, 10), (0, 60), (0, 99), (1, 100), (1, 150), (1, 500)] {
+            operator.write(region_id, key);
+        }
+
+        let mut procedure = RepartitionTableProcedure::new(
+            table_name(),
+            partitions(&[Some(50), Some(100), Some(150), None]),
+            kv_store.clone(),
+            operator.clone(),
+        );
+        let ctx = context();
+        let mut dump_before_switch = None;
+        loop {
+            if procedure.data.state == RepartitionState::SwitchRoute && dump_before_switch.is_none()
+            {
+                // Written after the rows are copied, moved by the catch-up copy.
+                operator.write(0, 70);
+                dump_before_switch = Some(procedure.dump().unwrap());
+            }
+            if let Status::Done = procedure.execute(&ctx).await.unwrap() {
+                break;
+            }
+        }
+
+        // Old regions are dropped.
+        assert_eq!(vec![2, 3, 4, 5], operator.region_ids());
+        assert!((2..6).all(|region_id| operator.is_published(region_id)));
+        assert_eq!(vec![10], operator.keys(2));
+        assert_eq!(vec![60, 70, 99], operator.keys(3));
+        assert_eq!(vec![100], operator.keys(4));
+        assert_eq!(vec![150, 500], operator.keys(5));
+
+        let route = get_value(&kv_store, route_key()).await;
+        let (route_peers, region_routes) = decode_route(&route).unwrap();
+        assert_eq!(peers(), route_peers);
+        let regions = region_routes
+            .iter()
+            .map(|r| {
+                let region = r.region.as_ref().unwrap();
+                let partition = region.partition.as_ref().unwrap();
+                (
+                    region.id,
+                    r.leader_peer_index,
+                    decode_bound(&partition.value_list[0]),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (2, 0, Some(50)),
+                (3, 1, Some(100)),
+                (4, 0, Some(150)),
+                (5, 1, None)
+            ],
+            regions
+        );
+
+        let tgv = TableGlobalValue::from_bytes(get_value(&kv_store, table_key().to_string()).await)
+            .unwrap();
+        assert_eq!(
+            HashMap::from([(1, vec![2, 4]), (2, vec![3, 5])]),
+            tgv.regions_id_map
+        );
+        assert_eq!(vec![2, 3, 4, 5], tgv.table_info.meta.region_numbers);
+
+        // Recovering the procedure from before the switch finds the route switched, and
+        // doesn't switch or copy again.
+        let mut procedure = RepartitionTableProcedure::from_json(
+            &dump_before_switch.unwrap(),
+            kv_store.clone(),
+            operator.clone(),
+        )
+        .unwrap();
+        while !matches!(procedure.execute(&ctx).await.unwrap(), Status::Done) {}
+        assert_eq!(route, get_value(&kv_store, route_key()).await);
+        assert_eq!(vec![60, 70, 99], operator.keys(3));
+        assert_eq!(vec![150, 500], operator.keys(5));
+    }
+
+    #[tokio::test]
+    async fn test_repartition_abort_on_copy_failure() {
+        let kv_store = Arc::new(MemStore::new()) as KvStoreRef;
+        let route = create_table(&kv_store).await;
+        let tgv = get_value(&kv_store, table_key().to_string()).await;
+        let operator = Arc::new(MockRegionOperator::default());
+        operator.write(0, 10);
+        operator.write(1, 100);
+        operator.fail_copy.store(true, Ordering::Relaxed);
+
+        let mut procedure = RepartitionTableProcedure::new(
+            table_name(),
+            partitions(&[Some(50), Some(100), Some(150), None]),
+            kv_store.clone(),
+            operator.clone(),
+        );
+        let ctx = context();
+        let err = loop {
+            match procedure.execute(&ctx).await {
+                Ok(Status::Done) => panic!("repartition should fail"),
+                Ok(_) => (),
+                Err(e) => break e,
+            }
+        };
+        assert!(!err.is_retry_later());
+        assert!(err.to_string().contains("injected copy failure"), "{err}");
+
+        // New regions are dropped, the old layout is intact.
+        assert_eq!(vec![0, 1], operator.region_ids());
+        assert!(!operator.is_published(0) && !operator.is_published(1));
+        assert_eq!(vec![10], operator.keys(0));
+        assert_eq!(vec![100], operator.keys(1));
+        assert!(!operator.is_paused(0) && !operator.is_paused(1));
+        assert_eq!(route, get_value(&kv_store, route_key()).await);
+        assert_eq!(tgv, get_value(&kv_store, table_key().to_string()).await);
+    }
+
+    #[tokio::test]
+    async fn test_repartition_partition_columns_unchanged() {
+        let kv_store = Arc::new(MemStore::new()) as KvStoreRef;
+        let _ = create_table(&kv_store).await;
+        let operator = Arc::new(MockRegionOperator::default());
+
+        let mut procedure = RepartitionTableProcedure::new(
+            table_name(),
+            vec![Partition {
+                column_list: vec![b"host".to_vec()],
+                value_list: vec![bound(None)],
+            }],
+            kv_store.clone(),
+            operator.clone(),
+        );
+        assert!(procedure.execute(&context()).await.is_err());
+        assert!(operator.region_ids().is_empty());
+    }
+}
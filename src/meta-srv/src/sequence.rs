@@ -21,6 +21,7 @@ use tokio::sync::Mutex;
 
 use crate::error::{self, Result};
 use crate::keys;
+use crate::service::store::ext::KvStoreExt;
 use crate::service::store::kv::KvStoreRef;
 
 pub type SequenceRef = Arc<Sequence>;
@@ -50,6 +51,20 @@ impl Sequence {
         let mut inner = self.inner.lock().await;
         inner.next().await
     }
+
+    /// Returns the persisted value of the sequence, i.e. the start of the next range
+    /// to be fetched from the generator.
+    pub async fn persisted(&self) -> Result<u64> {
+        let inner = self.inner.lock().await;
+        Ok(inner.persisted().await?.unwrap_or(inner.initial))
+    }
+
+    /// Makes sure the sequence never hands out values less than `min` again. Returns
+    /// `true` if the persisted value had to be bumped.
+    pub async fn bump_to(&self, min: u64) -> Result<bool> {
+        let mut inner = self.inner.lock().await;
+        inner.bump_to(min).await
+    }
 }
 
 struct Inner {
@@ -97,6 +112,59 @@ impl Inner {
         .fail()
     }
 
+    pub async fn persisted(&self) -> Result<Option<u64>> {
+        let kv = self.generator.get(self.name.as_bytes().to_vec()).await?;
+        kv.map(|kv| self.decode(kv.value)).transpose()
+    }
+
+    /// 1. returns directly if the persisted value is not less than `min`
+    /// 2. CAS the persisted value to `min`
+    /// 3. drop the local cache so that the next value comes from a new range
+    pub async fn bump_to(&mut self, min: u64) -> Result<bool> {
+        for _ in 0..self.force_quit {
+            let persisted = self.persisted().await?;
+            let current = persisted.unwrap_or(self.initial);
+            let bumped = if current >= min {
+                false
+            } else {
+                let req = CompareAndPutRequest {
+                    key: self.name.as_bytes().to_vec(),
+                    expect: persisted
+                        .map(|v| u64::to_le_bytes(v).to_vec())
+                        .unwrap_or_default(),
+                    value: u64::to_le_bytes(min).to_vec(),
+                    ..Default::default()
+                };
+                if !self.generator.compare_and_put(req).await?.success {
+                    continue;
+                }
+                true
+            };
+
+            // Values in the local cache may be less than `min`.
+            if self.next < min {
+                self.range = None;
+                self.next = min.max(current);
+            }
+            return Ok(bumped);
+        }
+
+        error::NextSequenceSnafu {
+            err_msg: format!("{}.bump_to()", &self.name),
+        }
+        .fail()
+    }
+
+    fn decode(&self, value: Vec<u8>) -> Result<u64> {
+        ensure!(
+            value.len() == std::mem::size_of::<u64>(),
+            error::UnexceptedSequenceValueSnafu {
+                err_msg: format!("key={}, unexpected value={:?}", self.name, value)
+            }
+        );
+        Ok(u64::from_le_bytes(value.try_into().unwrap()))
+    }
+
     pub async fn next_range(&self) -> Result<Range<u64>> {
         let key = self.name.as_bytes();
         let mut start = self.next;
@@ -119,14 +187,7 @@ impl Inner {
 
             if !res.success {
                 if let Some(kv) = res.prev_kv {
-                    let value = kv.value;
-                    ensure!(
-                        value.len() == std::mem::size_of::<u64>(),
-                        error::UnexceptedSequenceValueSnafu {
-                            err_msg: format!("key={}, unexpected value={:?}", self.name, value)
-                        }
-                    );
-                    start = u64::from_le_bytes(value.try_into().unwrap());
+                    start = self.decode(kv.value)?;
                 } else {
                     start = self.initial;
                 }
@@ -148,6 +209,7 @@ impl Inner {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use std::sync::Arc;
 
     use api::v1::meta::{
@@ -169,6 +231,60 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_sequence_concurrent_next() {
+        let kv_store = Arc::new(MemStore::new());
+        let initial = 1024;
+        // Two sequences sharing the same generator, like two metasrv nodes do.
+        let seqs = [
+            Arc::new(Sequence::new("test_seq", initial, 10, kv_store.clone())),
+            Arc::new(Sequence::new("test_seq", initial, 10, kv_store)),
+        ];
+
+        let handles = (0..8)
+            .map(|i| {
+                let seq = seqs[i % 2].clone();
+                tokio::spawn(async move {
+                    let mut ids = Vec::with_capacity(100);
+                    for _ in 0..100 {
+                        ids.push(seq.next().await.unwrap());
+                    }
+                    ids
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut ids = HashSet::new();
+        for handle in handles {
+            for id in handle.await.unwrap() {
+                assert!(id >= initial);
+                assert!(ids.insert(id), "duplicated id {id}");
+            }
+        }
+        assert_eq!(800, ids.len());
+    }
+
+    #[tokio::test]
+    async fn test_sequence_bump_to() {
+        let kv_store = Arc::new(MemStore::new());
+        let seq = Sequence::new("test_seq", 1024, 10, kv_store);
+
+        assert_eq!(1024, seq.persisted().await.unwrap());
+        assert!(!seq.bump_to(1000).await.unwrap());
+
+        assert_eq!(1024, seq.next().await.unwrap());
+        assert_eq!(1034, seq.persisted().await.unwrap());
+
+        // The local cache [1025, 1034) is dropped as well.
+        assert!(seq.bump_to(2048).await.unwrap());
+        assert_eq!(2048, seq.persisted().await.unwrap());
+        assert_eq!(2048, seq.next().await.unwrap());
+        assert_eq!(2049, seq.next().await.unwrap());
+
+        assert!(!seq.bump_to(2049).await.unwrap());
+        assert_eq!(2050, seq.next().await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_sequence_fouce_quit() {
         struct Noop;
@@ -16,6 +16,8 @@ mod health;
 mod heartbeat;
 mod leader;
 mod meta;
+mod repartition;
+mod table_id;
 
 use std::collections::HashMap;
 use std::convert::Infallible;
@@ -66,6 +68,30 @@ pub fn make_admin_service(meta_srv: MetaSrv) -> Admin {
         },
     );
 
+    let router = router.route(
+        "/table_id_audit",
+        table_id::TableIdAuditHandler {
+            kv_store: meta_srv.kv_store(),
+            table_id_sequence: meta_srv.table_id_sequence(),
+        },
+    );
+
+    let router = router.route(
+        "/repartition",
+        repartition::RepartitionHandler {
+            kv_store: meta_srv.kv_store(),
+            procedure_manager: meta_srv.procedure_manager(),
+            region_operator: meta_srv.region_operator(),
+        },
+    );
+
+    let router = router.route(
+        "/procedure",
+        repartition::ProcedureStateHandler {
+            procedure_manager: meta_srv.procedure_manager(),
+        },
+    );
+
     let router = router.route(
         "/leader",
         leader::LeaderHandler {
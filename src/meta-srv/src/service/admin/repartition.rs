@@ -0,0 +1,159 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use api::v1::meta::{Partition, TableName};
+use common_procedure::{ProcedureId, ProcedureManagerRef, ProcedureState, ProcedureWithId};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use tonic::codegen::http;
+
+use crate::error::{self, Result};
+use crate::procedure::repartition::{RegionOperatorRef, RepartitionTableProcedure};
+use crate::service::admin::HttpHandler;
+use crate::service::store::kv::KvStoreRef;
+
+/// Submits a procedure to repartition a table, responds the id of the procedure.
+///
+/// `partitions` is a json array of the new partitions in order, like
+/// `[{"columns":["id"],"bounds":["{\"Value\":{\"Int64\":100}}"]},{"columns":["id"],"bounds":["\"MaxValue\""]}]`,
+/// where bounds are the serialized exclusive upper bounds of the partition columns.
+pub struct RepartitionHandler {
+    pub kv_store: KvStoreRef,
+    pub procedure_manager: ProcedureManagerRef,
+    pub region_operator: Option<RegionOperatorRef>,
+}
+
+/// Responds the state of a procedure.
+pub struct ProcedureStateHandler {
+    pub procedure_manager: ProcedureManagerRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct PartitionParam {
+    columns: Vec<String>,
+    bounds: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProcedureStateResponse {
+    procedure_id: String,
+    state: &'static str,
+    error: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl HttpHandler for RepartitionHandler {
+    async fn handle(
+        &self,
+        _: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<http::Response<String>> {
+        let region_operator = self
+            .region_operator
+            .clone()
+            .context(error::RegionOperatorNotConfigSnafu)?;
+        let table_name = TableName {
+            catalog_name: required_param(params, "catalog_name")?.clone(),
+            schema_name: required_param(params, "schema_name")?.clone(),
+            table_name: required_param(params, "table_name")?.clone(),
+        };
+        let partitions = required_param(params, "partitions")?;
+        let partitions: Vec<PartitionParam> =
+            serde_json::from_str(partitions).context(error::DeserializeFromJsonSnafu {
+                input: partitions.clone(),
+            })?;
+        let partitions = partitions
+            .into_iter()
+            .map(|p| Partition {
+                column_list: p.columns.into_iter().map(String::into_bytes).collect(),
+                value_list: p.bounds.into_iter().map(String::into_bytes).collect(),
+            })
+            .collect();
+
+        let procedure = RepartitionTableProcedure::new(
+            table_name,
+            partitions,
+            self.kv_store.clone(),
+            region_operator,
+        );
+        let procedure = ProcedureWithId::with_random_id(Box::new(procedure));
+        let procedure_id = procedure.id.to_string();
+        let _ = self
+            .procedure_manager
+            .submit(procedure)
+            .await
+            .context(error::SubmitProcedureSnafu)?;
+
+        http::Response::builder()
+            .status(http::StatusCode::OK)
+            .body(format!("{{\"procedure_id\":\"{procedure_id}\"}}"))
+            .context(error::InvalidHttpBodySnafu)
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpHandler for ProcedureStateHandler {
+    async fn handle(
+        &self,
+        _: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<http::Response<String>> {
+        let id = required_param(params, "id")?;
+        let procedure_id =
+            ProcedureId::parse_str(id)
+                .ok()
+                .with_context(|| error::InvalidArgumentsSnafu {
+                    err_msg: format!("Invalid procedure id: {id}"),
+                })?;
+        let state = self
+            .procedure_manager
+            .procedure_state(procedure_id)
+            .await
+            .context(error::QueryProcedureSnafu)?;
+        let Some(state) = state else {
+            return http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(format!("Procedure {id} not found"))
+                .context(error::InvalidHttpBodySnafu);
+        };
+
+        let state_name = match &state {
+            ProcedureState::Running => "Running",
+            ProcedureState::Done => "Done",
+            ProcedureState::Retrying { .. } => "Retrying",
+            ProcedureState::Failed { .. } => "Failed",
+        };
+        let response = ProcedureStateResponse {
+            procedure_id: id.clone(),
+            state: state_name,
+            error: state.error().map(|e| e.to_string()),
+        };
+        let body = serde_json::to_string(&response).context(error::SerializeToJsonSnafu {
+            input: format!("{response:?}"),
+        })?;
+
+        http::Response::builder()
+            .status(http::StatusCode::OK)
+            .body(body)
+            .context(error::InvalidHttpBodySnafu)
+    }
+}
+
+fn required_param<'a>(params: &'a HashMap<String, String>, param: &str) -> Result<&'a String> {
+    params
+        .get(param)
+        .context(error::MissingRequiredParameterSnafu { param })
+}
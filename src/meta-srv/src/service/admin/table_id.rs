@@ -0,0 +1,44 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use snafu::ResultExt;
+use tonic::codegen::http;
+
+use crate::error::{self, Result};
+use crate::sequence::SequenceRef;
+use crate::service::admin::HttpHandler;
+use crate::service::store::kv::KvStoreRef;
+use crate::table_id_audit::audit_table_ids;
+
+pub struct TableIdAuditHandler {
+    pub kv_store: KvStoreRef,
+    pub table_id_sequence: SequenceRef,
+}
+
+#[async_trait::async_trait]
+impl HttpHandler for TableIdAuditHandler {
+    async fn handle(&self, _: &str, _: &HashMap<String, String>) -> Result<http::Response<String>> {
+        let report = audit_table_ids(&self.kv_store, &self.table_id_sequence).await?;
+        let body = serde_json::to_string(&report).context(error::SerializeToJsonSnafu {
+            input: format!("{report:?}"),
+        })?;
+
+        http::Response::builder()
+            .status(http::StatusCode::OK)
+            .body(body)
+            .context(error::InvalidHttpBodySnafu)
+    }
+}
@@ -15,11 +15,12 @@
 use std::collections::HashMap;
 
 use api::v1::meta::{
-    router_server, BatchPutRequest, CreateRequest, DeleteRequest, Error, KeyValue,
+    router_server, BatchGetRequest, BatchPutRequest, CreateRequest, DeleteRequest, Error, KeyValue,
     MoveValueRequest, Peer, PeerDict, Region, RegionRoute, ResponseHeader, RouteRequest,
     RouteResponse, Table, TableName, TableRoute, TableRouteValue,
 };
 use catalog::helper::{TableGlobalKey, TableGlobalValue};
+use common_catalog::naming::validate_table_name;
 use common_telemetry::warn;
 use snafu::{OptionExt, ResultExt};
 use table::metadata::RawTableInfo;
@@ -27,7 +28,7 @@ use tonic::{Request, Response};
 
 use crate::error;
 use crate::error::Result;
-use crate::keys::TableRouteKey;
+use crate::keys::{to_removed_key, TableRouteKey};
 use crate::metasrv::{Context, MetaSrv, SelectorRef};
 use crate::sequence::SequenceRef;
 use crate::service::store::ext::KvStoreExt;
@@ -96,6 +97,8 @@ async fn handle_create(
         table_info,
     } = req;
     let table_name = table_name.context(error::EmptyTableNameSnafu)?;
+    // Validated again in case the request isn't from a frontend validating it.
+    validate_table_name(&table_name.table_name).context(error::InvalidTableNameSnafu)?;
 
     let mut table_info: RawTableInfo =
         serde_json::from_slice(&table_info).with_context(|_| error::DeserializeFromJsonSnafu {
@@ -197,7 +200,7 @@ async fn handle_create(
     })
 }
 
-fn create_table_global_value(
+pub(crate) fn create_table_global_value(
     table_route_value: &TableRouteValue,
     table_info: RawTableInfo,
 ) -> Result<TableGlobalValue> {
@@ -326,41 +329,52 @@ async fn fetch_tables(
     kv_store: &KvStoreRef,
     keys: impl Iterator<Item = TableGlobalKey>,
 ) -> Result<Vec<(TableGlobalValue, TableRouteValue)>> {
-    let mut tables = vec![];
-    // Maybe we can optimize the for loop in the future, but in general,
-    // there won't be many keys, in fact, there is usually just one.
+    let keys = keys.collect::<Vec<_>>();
+    // Fetches the values of all tables in one batch, then all routes in another, so routing a
+    // page of tables (e.g. while a frontend warms up its caches) costs two store requests.
+    let mut tgvs = batch_get(kv_store, keys.iter().map(|tgk| tgk.to_string())).await?;
+
+    let mut tables = Vec::with_capacity(keys.len());
     for tgk in keys {
-        let tgv = get_table_global_value(kv_store, &tgk).await?;
-        if tgv.is_none() {
+        let Some(tgv) = tgvs.remove(tgk.to_string().as_bytes()) else {
             warn!("Table global value is absent: {}", tgk);
             continue;
-        }
-        let tgv = tgv.unwrap();
-
-        let trk = TableRouteKey::with_table_global_key(tgv.table_id() as u64, &tgk);
-        let trv = get_table_route_value(kv_store, &trk).await?;
-
-        tables.push((tgv, trv));
+        };
+        let tgv = TableGlobalValue::from_bytes(tgv).context(error::InvalidCatalogValueSnafu)?;
+        let trk = TableRouteKey::with_table_global_key(tgv.table_id() as u64, &tgk).key();
+        tables.push((tgv, trk));
     }
 
-    Ok(tables)
+    let mut trvs = batch_get(kv_store, tables.iter().map(|(_, trk)| trk.clone())).await?;
+    tables
+        .into_iter()
+        .map(|(tgv, trk)| {
+            let trv: TableRouteValue = trvs
+                .remove(trk.as_bytes())
+                .context(error::TableRouteNotFoundSnafu { key: &trk })?
+                .as_slice()
+                .try_into()
+                .context(error::DecodeTableRouteSnafu)?;
+            Ok((tgv, trv))
+        })
+        .collect()
 }
 
-async fn get_table_route_value(
+async fn batch_get(
     kv_store: &KvStoreRef,
-    key: &TableRouteKey<'_>,
-) -> Result<TableRouteValue> {
-    let trkv = kv_store
-        .get(key.key().into_bytes())
-        .await?
-        .context(error::TableRouteNotFoundSnafu { key: key.key() })?;
-    let trv: TableRouteValue = trkv
-        .value
-        .as_slice()
-        .try_into()
-        .context(error::DecodeTableRouteSnafu)?;
-
-    Ok(trv)
+    keys: impl Iterator<Item = String>,
+) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+    let keys = keys.map(String::into_bytes).collect::<Vec<_>>();
+    if keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let resp = kv_store
+        .batch_get(BatchGetRequest {
+            keys,
+            ..Default::default()
+        })
+        .await?;
+    Ok(resp.kvs.into_iter().map(|kv| (kv.key, kv.value)).collect())
 }
 
 async fn remove_table_route_value(
@@ -368,7 +382,7 @@ async fn remove_table_route_value(
     key: &TableRouteKey<'_>,
 ) -> Result<(Vec<u8>, TableRouteValue)> {
     let from_key = key.key().into_bytes();
-    let to_key = key.removed_key().into_bytes();
+    let to_key = to_removed_key(&key.key()).into_bytes();
     let v = move_value(kv_store, from_key, to_key)
         .await?
         .context(error::TableRouteNotFoundSnafu { key: key.key() })?;
@@ -394,7 +408,7 @@ async fn remove_table_global_value(
     Ok((kv.0, value))
 }
 
-async fn get_table_global_value(
+pub(crate) async fn get_table_global_value(
     kv_store: &KvStoreRef,
     key: &TableGlobalKey,
 ) -> Result<Option<TableGlobalValue>> {
@@ -23,10 +23,23 @@ use api::v1::meta::{
     DeleteRangeRequest, DeleteRangeResponse, MoveValueRequest, MoveValueResponse, PutRequest,
     PutResponse, RangeRequest, RangeResponse,
 };
+use common_telemetry::warn;
 use tonic::{Request, Response};
 
 use crate::metasrv::MetaSrv;
 use crate::service::GrpcResult;
+use crate::table_invalidation::{is_cached_catalog_key, publish_key_invalidation};
+
+impl MetaSrv {
+    /// Publishes the invalidation of the keys cached by the frontends among the `keys` changed
+    /// through the store service. The change is done anyway, so a failure is only logged, and
+    /// the values cached by the frontends are stale until they expire.
+    async fn invalidate_cached_keys<'a>(&self, keys: impl IntoIterator<Item = &'a [u8]>) {
+        if let Err(e) = publish_key_invalidation(&self.kv_store(), keys).await {
+            warn!("Failed to publish the invalidation of the changed keys, error: {e}");
+        }
+    }
+}
 
 #[async_trait::async_trait]
 impl store_server::Store for MetaSrv {
@@ -39,7 +52,9 @@ impl store_server::Store for MetaSrv {
 
     async fn put(&self, req: Request<PutRequest>) -> GrpcResult<PutResponse> {
         let req = req.into_inner();
+        let key = req.key.clone();
         let res = self.kv_store().put(req).await?;
+        self.invalidate_cached_keys([key.as_slice()]).await;
 
         Ok(Response::new(res))
     }
@@ -53,7 +68,10 @@ impl store_server::Store for MetaSrv {
 
     async fn batch_put(&self, req: Request<BatchPutRequest>) -> GrpcResult<BatchPutResponse> {
         let req = req.into_inner();
+        let keys = req.kvs.iter().map(|kv| kv.key.clone()).collect::<Vec<_>>();
         let res = self.kv_store().batch_put(req).await?;
+        self.invalidate_cached_keys(keys.iter().map(Vec::as_slice))
+            .await;
 
         Ok(Response::new(res))
     }
@@ -63,7 +81,10 @@ impl store_server::Store for MetaSrv {
         req: Request<BatchDeleteRequest>,
     ) -> GrpcResult<BatchDeleteResponse> {
         let req = req.into_inner();
+        let keys = req.keys.clone();
         let res = self.kv_store().batch_delete(req).await?;
+        self.invalidate_cached_keys(keys.iter().map(Vec::as_slice))
+            .await;
         Ok(Response::new(res))
     }
 
@@ -72,7 +93,11 @@ impl store_server::Store for MetaSrv {
         req: Request<CompareAndPutRequest>,
     ) -> GrpcResult<CompareAndPutResponse> {
         let req = req.into_inner();
+        let key = req.key.clone();
         let res = self.kv_store().compare_and_put(req).await?;
+        if res.success {
+            self.invalidate_cached_keys([key.as_slice()]).await;
+        }
 
         Ok(Response::new(res))
     }
@@ -81,15 +106,33 @@ impl store_server::Store for MetaSrv {
         &self,
         req: Request<DeleteRangeRequest>,
     ) -> GrpcResult<DeleteRangeResponse> {
-        let req = req.into_inner();
-        let res = self.kv_store().delete_range(req).await?;
+        let mut req = req.into_inner();
+        if req.range_end.is_empty() {
+            let key = req.key.clone();
+            let res = self.kv_store().delete_range(req).await?;
+            self.invalidate_cached_keys([key.as_slice()]).await;
+            return Ok(Response::new(res));
+        }
+
+        // The keys deleted in the range are only known from the deleted key-values.
+        let prev_kv = req.prev_kv;
+        req.prev_kv = true;
+        let mut res = self.kv_store().delete_range(req).await?;
+        self.invalidate_cached_keys(res.prev_kvs.iter().map(|kv| kv.key.as_slice()))
+            .await;
+        if !prev_kv {
+            res.prev_kvs.clear();
+        }
 
         Ok(Response::new(res))
     }
 
     async fn move_value(&self, req: Request<MoveValueRequest>) -> GrpcResult<MoveValueResponse> {
         let req = req.into_inner();
+        let keys = [req.from_key.clone(), req.to_key.clone()];
         let res = self.kv_store().move_value(req).await?;
+        self.invalidate_cached_keys(keys.iter().map(Vec::as_slice))
+            .await;
 
         Ok(Response::new(res))
     }
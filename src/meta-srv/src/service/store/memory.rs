@@ -86,12 +86,10 @@ impl KvStore for MemStore {
                 .collect::<Vec<_>>()
         };
 
-        let more = if limit > 0 {
+        let more = limit > 0 && kvs.len() > limit as usize;
+        if more {
             kvs.truncate(limit as usize);
-            true
-        } else {
-            false
-        };
+        }
 
         let cluster_id = header.map_or(0, |h| h.cluster_id);
         let header = Some(ResponseHeader::success(cluster_id));
@@ -429,8 +427,8 @@ mod tests {
 
         let resp = kv_store
             .range(RangeRequest {
-                key,
-                range_end,
+                key: key.clone(),
+                range_end: range_end.clone(),
                 limit: 1,
                 keys_only: false,
                 ..Default::default()
@@ -441,6 +439,21 @@ mod tests {
         assert_eq!(1, resp.kvs.len());
         assert_eq!(b"key1".as_slice(), resp.kvs[0].key);
         assert_eq!(b"val1".as_slice(), resp.kvs[0].value);
+        assert!(resp.more);
+
+        let resp = kv_store
+            .range(RangeRequest {
+                key,
+                range_end,
+                limit: 2,
+                keys_only: false,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(2, resp.kvs.len());
+        assert!(!resp.more);
     }
 
     #[tokio::test]
@@ -0,0 +1,202 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use api::v1::meta::RangeRequest;
+use catalog::helper::{TableGlobalKey, TableGlobalValue, TABLE_GLOBAL_KEY_PREFIX};
+use common_telemetry::{info, warn};
+use serde::Serialize;
+use snafu::ResultExt;
+use table::metadata::TableId;
+
+use crate::error::{self, Result};
+use crate::sequence::SequenceRef;
+use crate::service::store::kv::KvStoreRef;
+use crate::util;
+
+/// Result of [audit_table_ids].
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct TableIdAuditReport {
+    /// Number of tables scanned.
+    pub tables: usize,
+    /// The largest table id in use, if there are any tables.
+    pub max_table_id: Option<TableId>,
+    /// The value the table id sequence was bumped to, if it was behind `max_table_id`.
+    pub sequence_bumped_to: Option<u64>,
+    /// Table ids shared by more than one table, which need to be resolved manually.
+    pub collisions: Vec<TableIdCollision>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct TableIdCollision {
+    pub table_id: TableId,
+    /// Full names (`catalog.schema.table`) of the tables sharing `table_id`.
+    pub tables: Vec<String>,
+}
+
+/// Scans the global values of all tables, bumps the table id sequence past the largest
+/// table id in use, and reports the table ids shared by more than one table.
+///
+/// Ids can get out of sync with the sequence when metasrv is restored from a backup that
+/// was taken before some tables were created.
+pub async fn audit_table_ids(
+    kv_store: &KvStoreRef,
+    table_id_sequence: &SequenceRef,
+) -> Result<TableIdAuditReport> {
+    let prefix = format!("{TABLE_GLOBAL_KEY_PREFIX}-").into_bytes();
+    let req = RangeRequest {
+        range_end: util::get_prefix_end_key(&prefix),
+        key: prefix,
+        ..Default::default()
+    };
+    let kvs = kv_store.range(req).await?.kvs;
+
+    let mut tables_by_id: BTreeMap<TableId, Vec<String>> = BTreeMap::new();
+    for kv in kvs {
+        let key = String::from_utf8(kv.key).context(error::InvalidUtf8ValueSnafu)?;
+        let key = TableGlobalKey::parse(key).context(error::InvalidCatalogValueSnafu)?;
+        let value =
+            TableGlobalValue::from_bytes(kv.value).context(error::InvalidCatalogValueSnafu)?;
+
+        let table_name = format!(
+            "{}.{}.{}",
+            key.catalog_name, key.schema_name, key.table_name
+        );
+        tables_by_id
+            .entry(value.table_id())
+            .or_default()
+            .push(table_name);
+    }
+
+    let mut report = TableIdAuditReport {
+        tables: tables_by_id.values().map(|tables| tables.len()).sum(),
+        max_table_id: tables_by_id.keys().next_back().copied(),
+        ..Default::default()
+    };
+
+    if let Some(max_table_id) = report.max_table_id {
+        let next = max_table_id as u64 + 1;
+        if table_id_sequence.bump_to(next).await? {
+            warn!("Table id sequence was behind the max table id {max_table_id}, bumped to {next}");
+            report.sequence_bumped_to = Some(next);
+        }
+    }
+
+    report.collisions = tables_by_id
+        .into_iter()
+        .filter(|(_, tables)| tables.len() > 1)
+        .map(|(table_id, tables)| TableIdCollision { table_id, tables })
+        .collect();
+    for collision in &report.collisions {
+        warn!(
+            "Table id {} is shared by tables: {:?}, please resolve it manually",
+            collision.table_id, collision.tables
+        );
+    }
+
+    info!(
+        "Audited {} table ids, max table id: {:?}, collisions: {}",
+        report.tables,
+        report.max_table_id,
+        report.collisions.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use api::v1::meta::PutRequest;
+    use table::metadata::RawTableInfo;
+    use table::test_util::MemTable;
+    use table::Table;
+
+    use super::*;
+    use crate::sequence::Sequence;
+    use crate::service::store::memory::MemStore;
+
+    async fn put_table(kv_store: &KvStoreRef, table_name: &str, table_id: TableId) {
+        let mut table_info = (*MemTable::default_numbers_table().table_info()).clone();
+        table_info.ident.table_id = table_id;
+        table_info.name = table_name.to_string();
+
+        let key = TableGlobalKey {
+            catalog_name: table_info.catalog_name.clone(),
+            schema_name: table_info.schema_name.clone(),
+            table_name: table_name.to_string(),
+        };
+        let value = TableGlobalValue {
+            node_id: 1,
+            regions_id_map: Default::default(),
+            table_info: RawTableInfo::from(table_info),
+        };
+        kv_store
+            .put(PutRequest {
+                key: key.to_string().into_bytes(),
+                value: value.as_bytes().unwrap(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_audit_bumps_sequence() {
+        let kv_store = Arc::new(MemStore::new()) as KvStoreRef;
+        let sequence = Arc::new(Sequence::new("table_id", 1024, 10, kv_store.clone()));
+        put_table(&kv_store, "t1", 1024).await;
+        put_table(&kv_store, "t2", 2000).await;
+
+        let report = audit_table_ids(&kv_store, &sequence).await.unwrap();
+        assert_eq!(
+            TableIdAuditReport {
+                tables: 2,
+                max_table_id: Some(2000),
+                sequence_bumped_to: Some(2001),
+                collisions: vec![],
+            },
+            report
+        );
+        assert_eq!(2001, sequence.next().await.unwrap());
+
+        // Nothing to bump for the second time.
+        let report = audit_table_ids(&kv_store, &sequence).await.unwrap();
+        assert_eq!(None, report.sequence_bumped_to);
+    }
+
+    #[tokio::test]
+    async fn test_audit_reports_collisions() {
+        let kv_store = Arc::new(MemStore::new()) as KvStoreRef;
+        let sequence = Arc::new(Sequence::new("table_id", 1024, 10, kv_store.clone()));
+        put_table(&kv_store, "t1", 1024).await;
+        put_table(&kv_store, "t2", 1025).await;
+        put_table(&kv_store, "t3", 1025).await;
+
+        let report = audit_table_ids(&kv_store, &sequence).await.unwrap();
+        assert_eq!(3, report.tables);
+        assert_eq!(
+            vec![TableIdCollision {
+                table_id: 1025,
+                tables: vec![
+                    "greptime.public.t2".to_string(),
+                    "greptime.public.t3".to_string()
+                ],
+            }],
+            report.collisions
+        );
+        assert_eq!(Some(1026), report.sequence_bumped_to);
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use api::v1::meta::{DeleteRangeRequest, PutRequest};
+use catalog::helper::{
+    build_table_invalidation_prefix, build_table_invalidation_start, RemovedDatanode,
+    TableInvalidationKey, TableInvalidationValue, CATALOG_KEY_PREFIX, SCHEMA_KEY_PREFIX,
+    TABLE_GLOBAL_KEY_PREFIX,
+};
+use common_telemetry::info;
+use common_time::util::current_time_millis;
+use snafu::ResultExt;
+
+use crate::error::{InvalidCatalogValueSnafu, Result};
+use crate::lease::take_expired_datanodes;
+use crate::service::store::kv::KvStoreRef;
+
+/// Time the invalidations of tables published by the frontends are kept, long enough for every
+/// frontend to poll them, see [catalog::helper::TableInvalidationValue].
+pub const TABLE_INVALIDATION_TTL_MILLIS: i64 = 10 * 60 * 1000;
+
+/// Deletes the invalidations of tables published before `TABLE_INVALIDATION_TTL_MILLIS` ago.
+/// The keys of the invalidations are ordered by the time they're published, so they are
+/// deleted by a single range.
+pub async fn delete_expired_table_invalidations(
+    kv_store: &KvStoreRef,
+    now_millis: i64,
+) -> Result<()> {
+    let _ = kv_store
+        .delete_range(DeleteRangeRequest {
+            key: build_table_invalidation_prefix().into_bytes(),
+            range_end: build_table_invalidation_start(now_millis - TABLE_INVALIDATION_TTL_MILLIS)
+                .into_bytes(),
+            ..Default::default()
+        })
+        .await?;
+    Ok(())
+}
+
+/// Returns whether `key` is the key of a catalog, schema or table, whose values the frontends
+/// cache.
+pub fn is_cached_catalog_key(key: &[u8]) -> bool {
+    [
+        CATALOG_KEY_PREFIX,
+        SCHEMA_KEY_PREFIX,
+        TABLE_GLOBAL_KEY_PREFIX,
+    ]
+    .iter()
+    .any(|prefix| {
+        key.strip_prefix(prefix.as_bytes())
+            .map_or(false, |rest| rest.starts_with(b"-"))
+    })
+}
+
+/// Publishes the invalidation of the keys of catalogs, schemas and tables among `keys`, which
+/// are changed, for the frontends to drop the values they cache. Does nothing if there is no
+/// such key.
+pub async fn publish_key_invalidation<'a>(
+    kv_store: &KvStoreRef,
+    keys: impl IntoIterator<Item = &'a [u8]>,
+) -> Result<()> {
+    let keys = keys
+        .into_iter()
+        .filter(|key| is_cached_catalog_key(key))
+        .map(|key| String::from_utf8_lossy(key).to_string())
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let value = TableInvalidationValue {
+        keys,
+        ..Default::default()
+    };
+    put_invalidation(kv_store, &value).await
+}
+
+/// Publishes the datanodes whose leases have expired for `lease_secs` as removed from the
+/// cluster, for the frontends to close the clients of them, see [take_expired_datanodes].
+pub async fn publish_expired_datanodes(
+    kv_store: &KvStoreRef,
+    lease_secs: i64,
+    now_millis: i64,
+) -> Result<()> {
+    let removed_datanodes = take_expired_datanodes(kv_store, lease_secs, now_millis)
+        .await?
+        .into_iter()
+        .map(|(key, value)| {
+            info!(
+                "Datanode {} at {} is removed, its lease has expired",
+                key.node_id, value.node_addr
+            );
+            RemovedDatanode {
+                id: key.node_id,
+                addr: value.node_addr,
+            }
+        })
+        .collect::<Vec<_>>();
+    if removed_datanodes.is_empty() {
+        return Ok(());
+    }
+
+    let value = TableInvalidationValue {
+        removed_datanodes,
+        ..Default::default()
+    };
+    put_invalidation(kv_store, &value).await
+}
+
+async fn put_invalidation(kv_store: &KvStoreRef, value: &TableInvalidationValue) -> Result<()> {
+    let key = TableInvalidationKey {
+        timestamp_millis: current_time_millis(),
+        id: uuid::Uuid::new_v4().to_string(),
+    };
+    let _ = kv_store
+        .put(PutRequest {
+            key: key.to_string().into_bytes(),
+            value: value.as_bytes().context(InvalidCatalogValueSnafu)?,
+            ..Default::default()
+        })
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use api::v1::meta::RangeRequest;
+
+    use super::*;
+    use crate::keys::{LeaseKey, LeaseValue};
+    use crate::service::store::memory::MemStore;
+
+    #[tokio::test]
+    async fn test_delete_expired_table_invalidations() {
+        let kv_store: KvStoreRef = Arc::new(MemStore::default());
+        let now = TABLE_INVALIDATION_TTL_MILLIS * 2;
+        for timestamp_millis in [0, now - TABLE_INVALIDATION_TTL_MILLIS, now] {
+            let key = TableInvalidationKey {
+                timestamp_millis,
+                id: "id".to_string(),
+            };
+            let _ = kv_store
+                .put(PutRequest {
+                    key: key.to_string().into_bytes(),
+                    value: TableInvalidationValue::default().as_bytes().unwrap(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        delete_expired_table_invalidations(&kv_store, now)
+            .await
+            .unwrap();
+        let prefix = build_table_invalidation_prefix().into_bytes();
+        let resp = kv_store
+            .range(RangeRequest {
+                range_end: crate::util::get_prefix_end_key(&prefix),
+                key: prefix,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let timestamps = resp
+            .kvs
+            .iter()
+            .map(|kv| {
+                TableInvalidationKey::parse(String::from_utf8_lossy(&kv.key))
+                    .unwrap()
+                    .timestamp_millis
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![now - TABLE_INVALIDATION_TTL_MILLIS, now], timestamps);
+    }
+
+    #[tokio::test]
+    async fn test_publish_key_invalidation() {
+        let kv_store: KvStoreRef = Arc::new(MemStore::default());
+        publish_key_invalidation(&kv_store, [b"__tr-1".as_slice(), b"__cx-a"])
+            .await
+            .unwrap();
+        publish_key_invalidation(&kv_store, [b"__tg-a-b-c".as_slice(), b"__s-a-b", b"__tr-1"])
+            .await
+            .unwrap();
+
+        let prefix = build_table_invalidation_prefix().into_bytes();
+        let resp = kv_store
+            .range(RangeRequest {
+                range_end: crate::util::get_prefix_end_key(&prefix),
+                key: prefix,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(1, resp.kvs.len());
+        let value = TableInvalidationValue::from_bytes(&resp.kvs[0].value).unwrap();
+        assert_eq!(vec!["__tg-a-b-c", "__s-a-b"], value.keys);
+    }
+
+    #[tokio::test]
+    async fn test_publish_expired_datanodes() {
+        let kv_store: KvStoreRef = Arc::new(MemStore::default());
+        let lease = LeaseValue {
+            timestamp_millis: 0,
+            node_addr: "127.0.0.1:3001".to_string(),
+        };
+        let _ = kv_store
+            .put(PutRequest {
+                key: LeaseKey {
+                    cluster_id: 0,
+                    node_id: 1,
+                }
+                .try_into()
+                .unwrap(),
+                value: lease.try_into().unwrap(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let prefix = build_table_invalidation_prefix().into_bytes();
+        let range = RangeRequest {
+            range_end: crate::util::get_prefix_end_key(&prefix),
+            key: prefix,
+            ..Default::default()
+        };
+        // Nothing is published until the lease expires, then the datanode is published once.
+        for now in [1_000, 20_000, 40_000] {
+            publish_expired_datanodes(&kv_store, 15, now).await.unwrap();
+        }
+        let resp = kv_store.range(range).await.unwrap();
+        assert_eq!(1, resp.kvs.len());
+        let value = TableInvalidationValue::from_bytes(&resp.kvs[0].value).unwrap();
+        assert_eq!(
+            vec![RemovedDatanode {
+                id: 1,
+                addr: "127.0.0.1:3001".to_string(),
+            }],
+            value.removed_datanodes
+        );
+    }
+}
@@ -15,4 +15,7 @@
 //! Table Engine config
 
 #[derive(Debug, Clone, Default)]
-pub struct EngineConfig {}
+pub struct EngineConfig {
+    /// Whether the scans of the tables encode the string tags with dictionaries.
+    pub dictionary_tags: bool,
+}
@@ -26,7 +26,9 @@ use common_error::ext::BoxedError;
 use common_procedure::{BoxedProcedure, ProcedureManager};
 use common_telemetry::tracing::log::info;
 use common_telemetry::{debug, logging};
+use common_time::util::{ClockRef, SystemClock};
 use dashmap::DashMap;
+use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::Schema;
 use key_lock::KeyLock;
 use object_store::ObjectStore;
@@ -34,7 +36,8 @@ use snafu::{ensure, OptionExt, ResultExt};
 use store_api::storage::{
     ColumnDescriptorBuilder, ColumnFamilyDescriptor, ColumnFamilyDescriptorBuilder, ColumnId,
     CreateOptions, EngineContext as StorageEngineContext, OpenOptions, Region,
-    RegionDescriptorBuilder, RowKeyDescriptor, RowKeyDescriptorBuilder, StorageEngine,
+    RegionDescriptorBuilder, RegionNumber, RowKeyDescriptor, RowKeyDescriptorBuilder,
+    StorageEngine,
 };
 use table::engine::{
     region_id, region_name, table_dir, EngineContext, TableEngine, TableEngineProcedure,
@@ -52,7 +55,8 @@ use crate::engine::procedure::{AlterMitoTable, CreateMitoTable, DropMitoTable};
 use crate::error::{
     self, BuildColumnDescriptorSnafu, BuildColumnFamilyDescriptorSnafu, BuildRegionDescriptorSnafu,
     BuildRowKeyDescriptorSnafu, InvalidPrimaryKeySnafu, InvalidRawSchemaSnafu,
-    MissingTimestampIndexSnafu, RegionNotFoundSnafu, Result, TableExistsSnafu,
+    InvalidTtlColumnSnafu, MissingTimestampIndexSnafu, RegionNotFoundSnafu, Result,
+    TableExistsSnafu,
 };
 use crate::manifest::TableManifest;
 use crate::metrics;
@@ -71,8 +75,23 @@ pub struct MitoEngine<S: StorageEngine> {
 
 impl<S: StorageEngine> MitoEngine<S> {
     pub fn new(config: EngineConfig, storage_engine: S, object_store: ObjectStore) -> Self {
+        Self::with_clock(config, storage_engine, object_store, Arc::new(SystemClock))
+    }
+
+    /// Creates an engine whose tables record their write time by `clock`.
+    pub fn with_clock(
+        config: EngineConfig,
+        storage_engine: S,
+        object_store: ObjectStore,
+        clock: ClockRef,
+    ) -> Self {
         Self {
-            inner: Arc::new(MitoEngineInner::new(config, storage_engine, object_store)),
+            inner: Arc::new(MitoEngineInner::new(
+                config,
+                storage_engine,
+                object_store,
+                clock,
+            )),
         }
     }
 
@@ -157,6 +176,28 @@ impl<S: StorageEngine> TableEngine for MitoEngine<S> {
     async fn close(&self) -> TableResult<()> {
         self.inner.close().await
     }
+
+    async fn create_region(
+        &self,
+        _ctx: &EngineContext,
+        table_ref: &TableReference,
+        region_number: RegionNumber,
+    ) -> TableResult<()> {
+        self.inner
+            .create_region(table_ref, region_number)
+            .await
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)
+    }
+
+    async fn drop_region(
+        &self,
+        _ctx: &EngineContext,
+        table_ref: &TableReference,
+        region_number: RegionNumber,
+    ) -> TableResult<bool> {
+        self.inner.drop_region(table_ref, region_number).await
+    }
 }
 
 impl<S: StorageEngine> TableEngineProcedure for MitoEngine<S> {
@@ -214,6 +255,8 @@ pub(crate) struct MitoEngineInner<S: StorageEngine> {
     /// Table mutex is used to protect the operations such as creating/opening/closing
     /// a table, to avoid things like opening the same table simultaneously.
     table_mutex: Arc<KeyLock<String>>,
+    config: EngineConfig,
+    clock: ClockRef,
 }
 
 fn build_row_key_desc(
@@ -235,6 +278,8 @@ fn build_row_key_desc(
     )
     .default_constraint(ts_column_schema.default_constraint().cloned())
     .is_nullable(ts_column_schema.is_nullable())
+    .encoding(ts_column_schema.encoding().map(String::from))
+    .compression(ts_column_schema.compression().map(String::from))
     .is_time_index(true)
     .build()
     .context(BuildColumnDescriptorSnafu {
@@ -262,6 +307,8 @@ fn build_row_key_desc(
         )
         .default_constraint(column_schema.default_constraint().cloned())
         .is_nullable(column_schema.is_nullable())
+        .encoding(column_schema.encoding().map(String::from))
+        .compression(column_schema.compression().map(String::from))
         .build()
         .context(BuildColumnDescriptorSnafu {
             column_name: &column_schema.name,
@@ -305,6 +352,8 @@ fn build_column_family(
         )
         .default_constraint(column_schema.default_constraint().cloned())
         .is_nullable(column_schema.is_nullable())
+        .encoding(column_schema.encoding().map(String::from))
+        .compression(column_schema.compression().map(String::from))
         .build()
         .context(BuildColumnDescriptorSnafu {
             column_name: &column_schema.name,
@@ -341,6 +390,30 @@ fn validate_create_table_request(request: &CreateTableRequest) -> Result<()> {
         }
     );
 
+    if let Some(ttl_column) = &request.table_options.ttl_column {
+        let column_schema = request
+            .schema
+            .column_schemas
+            .iter()
+            .find(|column_schema| column_schema.name == *ttl_column)
+            .context(InvalidTtlColumnSnafu {
+                table_name: &request.table_name,
+                column_name: ttl_column,
+                reason: "column not found",
+            })?;
+        ensure!(
+            matches!(column_schema.data_type, ConcreteDataType::Timestamp(_)),
+            InvalidTtlColumnSnafu {
+                table_name: &request.table_name,
+                column_name: ttl_column,
+                reason: format!(
+                    "expect a timestamp column, found {:?}",
+                    column_schema.data_type
+                ),
+            }
+        );
+    }
+
     Ok(())
 }
 
@@ -425,6 +498,8 @@ impl<S: StorageEngine> MitoEngineInner<S> {
                     .map(|size| size.0 as usize),
                 ttl: request.table_options.ttl,
                 compaction_time_window: request.table_options.compaction_time_window,
+                wal_disabled: request.table_options.wal_disabled,
+                ttl_column: request.table_options.ttl_column.clone(),
             };
 
             let region = {
@@ -471,7 +546,9 @@ impl<S: StorageEngine> MitoEngineInner<S> {
                 regions,
                 self.object_store.clone(),
             )
-            .await?,
+            .await?
+            .with_dictionary_tags(self.config.dictionary_tags)
+            .with_clock(self.clock.clone()),
         );
 
         logging::info!(
@@ -534,6 +611,8 @@ impl<S: StorageEngine> MitoEngineInner<S> {
                     .map(|s| s.0 as usize),
                 ttl: table_info.meta.options.ttl,
                 compaction_time_window: table_info.meta.options.compaction_time_window,
+                wal_disabled: table_info.meta.options.wal_disabled,
+                ttl_column: table_info.meta.options.ttl_column.clone(),
             };
 
             debug!(
@@ -562,7 +641,11 @@ impl<S: StorageEngine> MitoEngineInner<S> {
                 regions.insert(*region_number, region);
             }
 
-            let table = Arc::new(MitoTable::new(table_info, regions, manifest));
+            let table = Arc::new(
+                MitoTable::new(table_info, regions, manifest)
+                    .with_dictionary_tags(self.config.dictionary_tags)
+                    .with_clock(self.clock.clone()),
+            );
 
             // already locked
             self.tables.insert(table_ref.to_string(), table.clone());
@@ -655,6 +738,124 @@ impl<S: StorageEngine> MitoEngineInner<S> {
         Ok(table)
     }
 
+    /// Creates region `region_number` of the table like the regions created with the table, by
+    /// the current schema and options of the table, and adds it to the table unpublished.
+    async fn create_region(
+        &self,
+        table_ref: &TableReference<'_>,
+        region_number: RegionNumber,
+    ) -> Result<()> {
+        let _lock = self.table_mutex.lock(table_ref.to_string()).await;
+        let table = self
+            .get_mito_table(table_ref)
+            .context(error::TableNotFoundSnafu {
+                table_name: table_ref.to_string(),
+            })?;
+        if table.regions().contains_key(&region_number) {
+            return Ok(());
+        }
+
+        let table_info = table.table_info();
+        let table_name = &table_info.name;
+        let table_id = table_info.ident.table_id;
+        let table_schema = &table_info.meta.schema;
+        let primary_key_indices = &table_info.meta.primary_key_indices;
+        let table_options = &table_info.meta.options;
+        let (next_column_id, default_cf) = build_column_family(
+            INIT_COLUMN_ID,
+            table_name,
+            table_schema,
+            primary_key_indices,
+        )?;
+        let (_, row_key) = build_row_key_desc(
+            next_column_id,
+            table_name,
+            table_schema,
+            primary_key_indices,
+        )?;
+
+        let region_name = region_name(table_id, region_number);
+        let region_descriptor = RegionDescriptorBuilder::default()
+            .id(region_id(table_id, region_number))
+            .name(&region_name)
+            .row_key(row_key)
+            .compaction_time_window(table_options.compaction_time_window)
+            .default_cf(default_cf)
+            .build()
+            .context(BuildRegionDescriptorSnafu {
+                table_name,
+                region_name,
+            })?;
+        let opts = CreateOptions {
+            parent_dir: table_dir(table_ref.catalog, table_ref.schema, table_id),
+            write_buffer_size: table_options.write_buffer_size.map(|size| size.0 as usize),
+            ttl: table_options.ttl,
+            compaction_time_window: table_options.compaction_time_window,
+            wal_disabled: table_options.wal_disabled,
+            ttl_column: table_options.ttl_column.clone(),
+        };
+        let region = self
+            .storage_engine
+            .create_region(&StorageEngineContext::default(), region_descriptor, &opts)
+            .await
+            .map_err(BoxedError::new)
+            .context(error::CreateRegionSnafu)?;
+        info!(
+            "Mito engine created region: {}, id: {} of table {}",
+            region.name(),
+            region.id(),
+            table_ref
+        );
+        table.add_region(region_number, region).await;
+
+        Ok(())
+    }
+
+    /// Drops region `region_number` of the table, including the region created but not added
+    /// to the table before the datanode restarts.
+    async fn drop_region(
+        &self,
+        table_ref: &TableReference<'_>,
+        region_number: RegionNumber,
+    ) -> TableResult<bool> {
+        let _lock = self.table_mutex.lock(table_ref.to_string()).await;
+        let Some(table) = self.get_mito_table(table_ref) else { return Ok(false) };
+        let region = match table.remove_region(region_number).await? {
+            Some(region) => Some(region),
+            None => {
+                let table_info = table.table_info();
+                let table_id = table_info.ident.table_id;
+                let opts = OpenOptions {
+                    parent_dir: table_dir(table_ref.catalog, table_ref.schema, table_id),
+                    ..Default::default()
+                };
+                self.storage_engine
+                    .open_region(
+                        &StorageEngineContext::default(),
+                        &region_name(table_id, region_number),
+                        &opts,
+                    )
+                    .await
+                    .map_err(BoxedError::new)
+                    .context(table_error::TableOperationSnafu)?
+            }
+        };
+        let Some(region) = region else { return Ok(false) };
+
+        info!(
+            "Mito engine drops region: {}, id: {} of table {}",
+            region.name(),
+            region.id(),
+            table_ref
+        );
+        self.storage_engine
+            .drop_region(&StorageEngineContext::default(), region)
+            .await
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)?;
+        Ok(true)
+    }
+
     /// Drop table. Returns whether a table is dropped (true) or not exist (false).
     async fn drop_table(&self, req: DropTableRequest) -> Result<bool> {
         let table_reference = TableReference {
@@ -693,12 +894,19 @@ async fn close_table(lock: Arc<KeyLock<String>>, table: TableRef) -> TableResult
 }
 
 impl<S: StorageEngine> MitoEngineInner<S> {
-    fn new(_config: EngineConfig, storage_engine: S, object_store: ObjectStore) -> Self {
+    fn new(
+        config: EngineConfig,
+        storage_engine: S,
+        object_store: ObjectStore,
+        clock: ClockRef,
+    ) -> Self {
         Self {
             tables: DashMap::new(),
             storage_engine,
             object_store,
             table_mutex: Arc::new(KeyLock::new()),
+            config,
+            clock,
         }
     }
 }
@@ -293,7 +293,9 @@ impl<S: StorageEngine> AlterMitoTable<S> {
             AlterKind::RenameTable { new_table_name } => {
                 new_info.name = new_table_name.clone();
             }
-            AlterKind::AddColumns { .. } | AlterKind::DropColumns { .. } => {
+            AlterKind::AddColumns { .. }
+            | AlterKind::DropColumns { .. }
+            | AlterKind::SetColumnOptions { .. } => {
                 let table_meta = &current_info.meta;
                 let new_meta = table_meta
                     .builder_with_alter_kind(table_name, &self.data.request.alter_kind)
@@ -164,17 +164,23 @@ impl<S: StorageEngine> CreateMitoTable<S> {
         let write_buffer_size = table_options.write_buffer_size.map(|size| size.0 as usize);
         let ttl = table_options.ttl;
         let compaction_time_window = table_options.compaction_time_window;
+        let wal_disabled = table_options.wal_disabled;
+        let ttl_column = table_options.ttl_column.clone();
         let open_opts = OpenOptions {
             parent_dir: table_dir.clone(),
             write_buffer_size,
             ttl,
             compaction_time_window,
+            wal_disabled,
+            ttl_column: ttl_column.clone(),
         };
         let create_opts = CreateOptions {
             parent_dir: table_dir,
             write_buffer_size,
             ttl,
             compaction_time_window,
+            wal_disabled,
+            ttl_column,
         };
 
         let primary_key_indices = &self.data.request.primary_key_indices;
@@ -256,7 +262,11 @@ impl<S: StorageEngine> CreateMitoTable<S> {
             .recover_table_manifest_and_info(&self.data.request.table_name, &table_dir)
             .await?
         {
-            let table = Arc::new(MitoTable::new(table_info, self.regions.clone(), manifest));
+            let table = Arc::new(
+                MitoTable::new(table_info, self.regions.clone(), manifest)
+                    .with_dictionary_tags(self.engine_inner.config.dictionary_tags)
+                    .with_clock(self.engine_inner.clock.clone()),
+            );
 
             let _lock = self.engine_inner.table_mutex.lock(table_ref.to_string());
             self.engine_inner
@@ -316,7 +326,9 @@ impl<S: StorageEngine> CreateMitoTable<S> {
             self.regions.clone(),
             self.engine_inner.object_store.clone(),
         )
-        .await?;
+        .await?
+        .with_dictionary_tags(self.engine_inner.config.dictionary_tags)
+        .with_clock(self.engine_inner.clock.clone());
 
         Ok(table)
     }
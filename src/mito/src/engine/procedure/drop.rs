@@ -17,10 +17,12 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use common_procedure::error::{Error, FromJsonSnafu, ToJsonSnafu};
 use common_procedure::{Context, LockKey, Procedure, ProcedureManager, Result, Status};
+use common_telemetry::warn;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
+use storage::region_tag_dictionary_dir;
 use store_api::storage::StorageEngine;
-use table::engine::TableReference;
+use table::engine::{region_name, table_dir, TableReference};
 use table::requests::DropTableRequest;
 use table::Table;
 
@@ -140,6 +142,7 @@ impl<S: StorageEngine> DropMitoTable<S> {
         // Close the table to close all regions. Closing a region is idempotent.
         if let Some(table) = &self.table {
             table.close().await.map_err(Error::from_error_ext)?;
+            self.remove_tag_dictionaries(table).await;
         }
 
         // TODO(yingwen): Currently, DROP TABLE doesn't remove data. We can
@@ -147,6 +150,25 @@ impl<S: StorageEngine> DropMitoTable<S> {
         // background.
         Ok(Status::Done)
     }
+
+    /// Removes the tag dictionaries of the regions of the closed table, which are only
+    /// written by the regions. A failure is only logged, as the table is already dropped.
+    async fn remove_tag_dictionaries(&self, table: &MitoTable<S::Region>) {
+        let table_ref = self.data.table_ref();
+        let table_info = table.table_info();
+        let table_id = table_info.ident.table_id;
+        let parent_dir = table_dir(table_ref.catalog, table_ref.schema, table_id);
+        for region_number in &table_info.meta.region_numbers {
+            let dir =
+                region_tag_dictionary_dir(&parent_dir, &region_name(table_id, *region_number));
+            if let Err(e) = self.engine_inner.object_store.remove_all(&dir).await {
+                warn!(
+                    "Failed to remove tag dictionary {} of dropped table {}, err: {}",
+                    dir, table_ref, e
+                );
+            }
+        }
+    }
 }
 
 /// Represents each step while dropping table in the mito engine.
@@ -173,6 +195,8 @@ impl DropTableData {
 
 #[cfg(test)]
 mod tests {
+    use object_store::services::Fs;
+    use object_store::ObjectStore;
     use table::engine::{EngineContext, TableEngine, TableEngineProcedure};
 
     use super::*;
@@ -183,10 +207,8 @@ mod tests {
     async fn test_procedure_drop_table() {
         common_telemetry::init_default_ut_logging();
 
-        let TestEnv {
-            table_engine,
-            dir: _dir,
-        } = procedure_test_util::setup_test_engine("add_column").await;
+        let TestEnv { table_engine, dir } =
+            procedure_test_util::setup_test_engine("add_column").await;
         let schema = Arc::new(test_util::schema_for_test());
         let request = test_util::new_create_request(schema.clone());
 
@@ -197,6 +219,18 @@ mod tests {
             .unwrap();
         procedure_test_util::execute_procedure_until_done(&mut procedure).await;
 
+        // A tag dictionary file of the region.
+        let mut builder = Fs::default();
+        builder.root(&dir.path().to_string_lossy());
+        let object_store = ObjectStore::new(builder).unwrap().finish();
+        let parent_dir = table_dir(&request.catalog_name, &request.schema_name, request.id);
+        let dictionary_file = format!(
+            "{}{:020}.json",
+            region_tag_dictionary_dir(&parent_dir, &region_name(request.id, 0)),
+            1
+        );
+        object_store.write(&dictionary_file, vec![]).await.unwrap();
+
         // Drop the table.
         let request = test_util::new_drop_request();
         let mut procedure = table_engine
@@ -214,5 +248,7 @@ mod tests {
             .get_table(&engine_ctx, &table_ref)
             .unwrap()
             .is_none());
+        // So is its tag dictionary.
+        assert!(!object_store.is_exist(&dictionary_file).await.unwrap());
     }
 }
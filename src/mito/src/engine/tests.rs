@@ -14,30 +14,42 @@
 
 //! Tests for mito table engine.
 
+use std::collections::HashSet;
+use std::time::Duration;
+
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
 use common_query::physical_plan::SessionContext;
 use common_recordbatch::util;
 use common_test_util::temp_dir::TempDir;
+use common_time::util::Clock;
 use datatypes::prelude::ConcreteDataType;
-use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema, RawSchema};
+use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema, RawSchema, SchemaBuilder};
 use datatypes::value::Value;
 use datatypes::vectors::{
     Float64Vector, Int32Vector, StringVector, TimestampMillisecondVector, VectorRef,
 };
 use log_store::NoopLogStore;
+use parquet::basic::{Compression, Encoding};
+use parquet::file::reader::{FileReader, SerializedFileReader};
 use storage::compaction::noop::NoopCompactionScheduler;
 use storage::config::EngineConfig as StorageEngineConfig;
 use storage::region::RegionImpl;
 use storage::EngineImpl;
 use store_api::manifest::Manifest;
 use store_api::storage::ReadContext;
+use table::engine::split_region_id;
 use table::requests::{
-    AddColumnRequest, AlterKind, DeleteRequest, FlushTableRequest, TableOptions,
+    AddColumnRequest, AlterKind, DeleteRequest, FlushTableRequest, InsertRequest, TableOptions,
+    WriteMode,
 };
+use table::table::write_freshness_secs;
 
 use super::*;
 use crate::table::test_util::{
-    self, new_insert_request, schema_for_test, setup_table, TestEngineComponents, TABLE_NAME,
+    self, new_insert_request, schema_for_test, setup_table, MockClock, MockEngine,
+    TestEngineComponents, TABLE_NAME,
 };
 
 pub fn has_parquet_file(sst_dir: &str) -> bool {
@@ -223,6 +235,20 @@ fn test_validate_create_table_request() {
 
     request.primary_key_indices = vec![0];
     assert!(validate_create_table_request(&request).is_ok());
+
+    request.table_options.ttl_column = Some("unknown".to_string());
+    let err = validate_create_table_request(&request).unwrap_err();
+    assert!(err.to_string().contains("column not found"), "{err}");
+
+    request.table_options.ttl_column = Some("name".to_string());
+    let err = validate_create_table_request(&request).unwrap_err();
+    assert!(
+        err.to_string().contains("expect a timestamp column"),
+        "{err}"
+    );
+
+    request.table_options.ttl_column = Some("ts".to_string());
+    assert!(validate_create_table_request(&request).is_ok());
 }
 
 #[tokio::test]
@@ -309,6 +335,280 @@ async fn test_create_table_insert_scan() {
     assert_eq!(tss, *record.column(0));
 }
 
+#[tokio::test]
+async fn test_insert_ignore_unsupported() {
+    let TestEngineComponents {
+        table_ref: table,
+        dir: _dir,
+        ..
+    } = test_util::setup_test_engine_and_table().await;
+
+    let mut columns_values: HashMap<String, VectorRef> = HashMap::with_capacity(4);
+    columns_values.insert(
+        "host".to_string(),
+        Arc::new(StringVector::from(vec!["host1"])),
+    );
+    columns_values.insert(
+        "cpu".to_string(),
+        Arc::new(Float64Vector::from_vec(vec![1.0])),
+    );
+    columns_values.insert(
+        "memory".to_string(),
+        Arc::new(Float64Vector::from_vec(vec![1024f64])),
+    );
+    columns_values.insert(
+        "ts".to_string(),
+        Arc::new(TimestampMillisecondVector::from_vec(vec![1])),
+    );
+
+    let mut insert_req = new_insert_request("demo".to_string(), columns_values);
+    insert_req.write_mode = WriteMode::InsertIgnore;
+    let err = table.insert(insert_req).await.unwrap_err();
+    assert_eq!(StatusCode::Unsupported, err.status_code());
+    assert!(err.to_string().contains("insert_ignore"), "{err}");
+}
+
+#[tokio::test]
+async fn test_table_write_stats() {
+    common_telemetry::init_default_metrics_recorder();
+    let clock = Arc::new(MockClock::default());
+    clock.set(10000);
+    let TestEngineComponents {
+        table_engine,
+        schema_ref,
+        dir: _dir,
+        ..
+    } = test_util::setup_test_engine_and_table_with_clock(clock.clone()).await;
+
+    let table_name = "test_table_write_stats";
+    let table = table_engine
+        .create_table(
+            &EngineContext::default(),
+            CreateTableRequest {
+                id: 2,
+                table_name: table_name.to_string(),
+                ..test_util::new_create_request(schema_ref)
+            },
+        )
+        .await
+        .unwrap();
+    let gauge_line = |name: &str| {
+        let name = format!("greptime_{}", name.replace('.', "_"));
+        common_telemetry::metric::render()
+            .unwrap()
+            .lines()
+            .find(|line| line.starts_with(&name) && line.contains(table_name))
+            .map(|line| line.to_string())
+    };
+
+    // Not written since opened.
+    let stats = table.region_stats().unwrap();
+    assert_eq!(1, stats.len());
+    assert_eq!(None, stats[0].max_timestamp_millis);
+    assert_eq!(None, stats[0].last_write_millis);
+    assert_eq!(0, stats[0].approximate_rows);
+    assert_eq!(None, write_freshness_secs(&stats, clock.now_millis()));
+    assert!(gauge_line(crate::metrics::MITO_TABLE_MAX_TIMESTAMP)
+        .unwrap()
+        .ends_with(" NaN"));
+    assert!(gauge_line(crate::metrics::MITO_TABLE_LAST_WRITE_TIMESTAMP)
+        .unwrap()
+        .ends_with(" NaN"));
+
+    let insert = |ts: Vec<i64>| {
+        let mut columns_values: HashMap<String, VectorRef> = HashMap::with_capacity(4);
+        let hosts = (0..ts.len())
+            .map(|i| format!("host{i}"))
+            .collect::<Vec<_>>();
+        columns_values.insert("host".to_string(), Arc::new(StringVector::from(hosts)));
+        columns_values.insert(
+            "cpu".to_string(),
+            Arc::new(Float64Vector::from_vec(vec![55.5; ts.len()])),
+        );
+        columns_values.insert(
+            "memory".to_string(),
+            Arc::new(Float64Vector::from_vec(vec![1024f64; ts.len()])),
+        );
+        columns_values.insert(
+            "ts".to_string(),
+            Arc::new(TimestampMillisecondVector::from_vec(ts)),
+        );
+        new_insert_request(table_name.to_string(), columns_values)
+    };
+
+    clock.set(20000);
+    assert_eq!(2, table.insert(insert(vec![2000, 1000])).await.unwrap());
+    let stats = table.region_stats().unwrap();
+    assert_eq!(Some(2000), stats[0].max_timestamp_millis);
+    assert_eq!(2, stats[0].approximate_rows);
+    assert!(stats[0].memtable_bytes > 0);
+    assert_eq!(Some(20000), stats[0].last_write_millis);
+    assert!(gauge_line(crate::metrics::MITO_TABLE_MAX_TIMESTAMP)
+        .unwrap()
+        .ends_with(" 2000"));
+    assert!(gauge_line(crate::metrics::MITO_TABLE_LAST_WRITE_TIMESTAMP)
+        .unwrap()
+        .ends_with(" 20000"));
+
+    // The freshness grows with the clock until the next write.
+    assert_eq!(Some(0.0), write_freshness_secs(&stats, clock.now_millis()));
+    clock.set(25500);
+    let stats = table.region_stats().unwrap();
+    assert_eq!(Some(5.5), write_freshness_secs(&stats, clock.now_millis()));
+
+    // An older time index value doesn't move the max timestamp back.
+    assert_eq!(1, table.insert(insert(vec![500])).await.unwrap());
+    let stats = table.region_stats().unwrap();
+    assert_eq!(Some(2000), stats[0].max_timestamp_millis);
+    assert_eq!(Some(25500), stats[0].last_write_millis);
+    assert_eq!(Some(0.0), write_freshness_secs(&stats, clock.now_millis()));
+    assert!(gauge_line(crate::metrics::MITO_TABLE_MAX_TIMESTAMP)
+        .unwrap()
+        .ends_with(" 2000"));
+    assert!(gauge_line(crate::metrics::MITO_TABLE_LAST_WRITE_TIMESTAMP)
+        .unwrap()
+        .ends_with(" 25500"));
+
+    // The gauges of the table are removed once it is closed.
+    table.close().await.unwrap();
+    assert!(gauge_line(crate::metrics::MITO_TABLE_MAX_TIMESTAMP).is_none());
+    assert!(gauge_line(crate::metrics::MITO_TABLE_LAST_WRITE_TIMESTAMP).is_none());
+}
+
+#[tokio::test]
+async fn test_wait_for_commit_token() {
+    common_telemetry::init_default_ut_logging();
+
+    // Regions apply writes after a delay, like a replica catching up.
+    let mock_engine = MockEngine::with_apply_delay(Duration::from_millis(500));
+    let (_engine, _table_engine, table, _object_store, _dir) =
+        test_util::setup_table_with_mock_engine(mock_engine).await;
+    assert!(table.commit_token().unwrap().is_empty());
+
+    let mut columns_values: HashMap<String, VectorRef> = HashMap::with_capacity(4);
+    columns_values.insert(
+        "host".to_string(),
+        Arc::new(StringVector::from(vec!["host1"])),
+    );
+    columns_values.insert(
+        "cpu".to_string(),
+        Arc::new(Float64Vector::from_vec(vec![55.5])),
+    );
+    columns_values.insert(
+        "memory".to_string(),
+        Arc::new(Float64Vector::from_vec(vec![1024f64])),
+    );
+    columns_values.insert(
+        "ts".to_string(),
+        Arc::new(TimestampMillisecondVector::from_vec(vec![1000])),
+    );
+    let insert_req = new_insert_request(TABLE_NAME.to_string(), columns_values);
+    assert_eq!(1, table.insert(insert_req).await.unwrap());
+
+    let token = table.commit_token().unwrap();
+    let entries = token.iter().collect::<Vec<_>>();
+    assert_eq!(1, entries.len());
+    let (region_id, sequence) = entries[0];
+    let (table_id, region_number) = split_region_id(region_id);
+    assert_eq!(table.table_info().ident.table_id, table_id);
+
+    // The row is invisible without waiting for the token.
+    assert_eq!(0, scan_num_rows(&table).await);
+
+    let err = table
+        .wait_for_sequence(region_number, sequence, Duration::from_millis(10))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, table::error::Error::WaitSequenceTimeout { .. }),
+        "{err:?}"
+    );
+    assert!(err.status_code().is_retryable());
+
+    table
+        .wait_for_sequence(region_number, sequence, Duration::from_secs(10))
+        .await
+        .unwrap();
+    assert_eq!(1, scan_num_rows(&table).await);
+
+    // Regions not in this table are skipped.
+    table
+        .wait_for_sequence(region_number + 1, sequence + 1, Duration::from_millis(10))
+        .await
+        .unwrap();
+}
+
+async fn scan_num_rows(table: &TableRef) -> usize {
+    let session_ctx = SessionContext::new();
+    let stream = table.scan(None, &[], None).await.unwrap();
+    let stream = stream.execute(0, session_ctx.task_ctx()).unwrap();
+    let batches = util::collect(stream).await.unwrap();
+    batches.iter().map(|batch| batch.num_rows()).sum()
+}
+
+#[tokio::test]
+async fn test_scan_dictionary_tags() {
+    let (_dir, object_store) = test_util::new_test_object_store("test_scan_dictionary_tags").await;
+    let compaction_scheduler = Arc::new(NoopCompactionScheduler::default());
+    let table_engine = MitoEngine::new(
+        EngineConfig {
+            dictionary_tags: true,
+        },
+        EngineImpl::new(
+            StorageEngineConfig::default(),
+            Arc::new(NoopLogStore::default()),
+            object_store.clone(),
+            compaction_scheduler,
+        ),
+        object_store,
+    );
+    let table = table_engine
+        .create_table(
+            &EngineContext::default(),
+            test_util::new_create_request(Arc::new(schema_for_test())),
+        )
+        .await
+        .unwrap();
+    // Only the string tag is encoded.
+    let dictionary_type = ConcreteDataType::dictionary_datatype(
+        ConcreteDataType::uint32_datatype(),
+        ConcreteDataType::string_datatype(),
+    );
+    let scan_schema = table.scan_schema();
+    assert_eq!(dictionary_type, scan_schema.column_schemas()[0].data_type);
+    assert_eq!(
+        table.schema().column_schemas()[1..],
+        scan_schema.column_schemas()[1..]
+    );
+
+    let mut columns_values: HashMap<String, VectorRef> = HashMap::with_capacity(4);
+    let hosts: VectorRef = Arc::new(StringVector::from(vec!["host1", "host2", "host1"]));
+    let cpus: VectorRef = Arc::new(Float64Vector::from_vec(vec![55.5, 66.6, 77.7]));
+    let memories: VectorRef = Arc::new(Float64Vector::from_vec(vec![1024f64, 4096f64, 512f64]));
+    let tss: VectorRef = Arc::new(TimestampMillisecondVector::from_vec(vec![1, 2, 3]));
+    columns_values.insert("host".to_string(), hosts);
+    columns_values.insert("cpu".to_string(), cpus);
+    columns_values.insert("memory".to_string(), memories);
+    columns_values.insert("ts".to_string(), tss);
+    let insert_req = new_insert_request("demo".to_string(), columns_values);
+    assert_eq!(3, table.insert(insert_req).await.unwrap());
+
+    let session_ctx = SessionContext::new();
+    let stream = table.scan(None, &[], None).await.unwrap();
+    assert_eq!(scan_schema, stream.schema());
+    let stream = stream.execute(0, session_ctx.task_ctx()).unwrap();
+    let batches = util::collect(stream).await.unwrap();
+    assert_eq!(1, batches.len());
+    let batch = &batches[0];
+    assert_eq!(
+        &dictionary_type.as_arrow_type(),
+        batch.df_record_batch().column(0).data_type()
+    );
+    // Rows are sorted by the row key.
+    let expect: VectorRef = Arc::new(StringVector::from(vec!["host1", "host1", "host2"]));
+    assert_eq!(expect, *batch.column(0));
+}
+
 #[tokio::test]
 async fn test_create_table_scan_batches() {
     common_telemetry::init_default_ut_logging();
@@ -530,6 +830,61 @@ async fn test_alter_table_add_column() {
     assert_eq!(new_meta.region_numbers, old_meta.region_numbers);
 }
 
+#[tokio::test]
+async fn test_create_table_wal_disabled() {
+    let (_dir, object_store) =
+        test_util::new_test_object_store("test_create_table_wal_disabled").await;
+    let new_engine = |storage_config| {
+        MitoEngine::new(
+            EngineConfig::default(),
+            EngineImpl::new(
+                storage_config,
+                Arc::new(NoopLogStore::default()),
+                object_store.clone(),
+                Arc::new(NoopCompactionScheduler::default()),
+            ),
+            object_store.clone(),
+        )
+    };
+    let request = CreateTableRequest {
+        table_options: TableOptions {
+            wal_disabled: true,
+            ..Default::default()
+        },
+        ..test_util::new_create_request(Arc::new(schema_for_test()))
+    };
+
+    // The server forbids disabling the WAL.
+    let table_engine = new_engine(StorageEngineConfig {
+        allow_wal_disabled: false,
+        ..Default::default()
+    });
+    let err = table_engine
+        .create_table(&EngineContext::default(), request.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+
+    let table_engine = new_engine(StorageEngineConfig::default());
+    let table = table_engine
+        .create_table(&EngineContext::default(), request)
+        .await
+        .unwrap();
+    let stats = table.region_stats().unwrap();
+    assert!(stats.iter().all(|stat| stat.wal_disabled));
+
+    // Altering the table keeps the option.
+    let new_tag = ColumnSchema::new("my_tag", ConcreteDataType::string_datatype(), true);
+    let new_field = ColumnSchema::new("my_field", ConcreteDataType::string_datatype(), true);
+    let req = new_add_columns_req(&new_tag, &new_field);
+    let table = table_engine
+        .alter_table(&EngineContext::default(), req)
+        .await
+        .unwrap();
+    assert!(table.table_info().meta.options.wal_disabled);
+    assert!(table.region_stats().unwrap()[0].wal_disabled);
+}
+
 #[tokio::test]
 async fn test_alter_table_remove_column() {
     let (_engine, table_engine, _table, _object_store, _dir) =
@@ -776,6 +1131,124 @@ async fn test_table_delete_rows() {
     );
 }
 
+#[tokio::test]
+async fn test_table_exact_row_count() {
+    let TestEngineComponents {
+        table_ref: table,
+        dir: _dir,
+        ..
+    } = test_util::setup_test_engine_and_table().await;
+    assert_eq!(Some(0), table.exact_row_count());
+
+    let mut columns_values: HashMap<String, VectorRef> = HashMap::with_capacity(4);
+    let hosts: VectorRef = Arc::new(StringVector::from(vec!["host1", "host2", "host3", "host4"]));
+    let cpus: VectorRef = Arc::new(Float64Vector::from_vec(vec![1.0, 2.0, 3.0, 4.0]));
+    let memories: VectorRef = Arc::new(Float64Vector::from_vec(vec![1.0, 2.0, 3.0, 4.0]));
+    let tss: VectorRef = Arc::new(TimestampMillisecondVector::from_vec(vec![1, 2, 2, 1]));
+    columns_values.insert("host".to_string(), hosts);
+    columns_values.insert("cpu".to_string(), cpus);
+    columns_values.insert("memory".to_string(), memories);
+    columns_values.insert("ts".to_string(), tss);
+    let insert_req = new_insert_request("demo".to_string(), columns_values);
+    assert_eq!(4, table.insert(insert_req).await.unwrap());
+
+    table.flush(None, Some(true)).await.unwrap();
+    assert_eq!(Some(4), table.exact_row_count());
+
+    // Deleted rows are still counted in the SST and memtable, so the count isn't exact.
+    let del_hosts: VectorRef = Arc::new(StringVector::from(vec!["host1"]));
+    let del_tss: VectorRef = Arc::new(TimestampMillisecondVector::from_vec(vec![1]));
+    let mut key_column_values = HashMap::with_capacity(2);
+    key_column_values.insert("host".to_string(), del_hosts);
+    key_column_values.insert("ts".to_string(), del_tss);
+    let del_req = DeleteRequest { key_column_values };
+    table.delete(del_req).await.unwrap();
+    assert_eq!(None, table.exact_row_count());
+
+    let session_ctx = SessionContext::new();
+    let stream = table.scan(None, &[], None).await.unwrap();
+    let stream = stream.execute(0, session_ctx.task_ctx()).unwrap();
+    let batches = util::collect_batches(stream).await.unwrap();
+    assert_eq!(
+        3,
+        batches.iter().map(|batch| batch.num_rows()).sum::<usize>()
+    );
+}
+
+async fn count_rows(table: &TableRef) -> usize {
+    let session_ctx = SessionContext::new();
+    let stream = table.scan(None, &[], None).await.unwrap();
+    let stream = stream.execute(0, session_ctx.task_ctx()).unwrap();
+    let batches = util::collect_batches(stream).await.unwrap();
+    batches.iter().map(|batch| batch.num_rows()).sum()
+}
+
+fn new_row_insert_request(region_number: RegionNumber, ts: i64) -> InsertRequest {
+    let mut columns_values: HashMap<String, VectorRef> = HashMap::with_capacity(4);
+    let hosts: VectorRef = Arc::new(StringVector::from(vec!["host5"]));
+    let values: VectorRef = Arc::new(Float64Vector::from_vec(vec![5.0]));
+    let tss: VectorRef = Arc::new(TimestampMillisecondVector::from_vec(vec![ts]));
+    columns_values.insert("host".to_string(), hosts);
+    columns_values.insert("cpu".to_string(), values.clone());
+    columns_values.insert("memory".to_string(), values);
+    columns_values.insert("ts".to_string(), tss);
+    InsertRequest {
+        region_number,
+        ..new_insert_request("demo".to_string(), columns_values)
+    }
+}
+
+#[tokio::test]
+async fn test_create_publish_drop_region() {
+    let TestEngineComponents {
+        table_engine,
+        table_ref: table,
+        dir: _dir,
+        ..
+    } = test_util::setup_test_engine_and_table().await;
+    setup_table(table.clone()).await;
+    let ctx = EngineContext::default();
+    let table_ref = TableReference::full(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "demo");
+
+    table_engine
+        .create_region(&ctx, &table_ref, 1)
+        .await
+        .unwrap();
+    // Creating the region again does nothing.
+    table_engine
+        .create_region(&ctx, &table_ref, 1)
+        .await
+        .unwrap();
+    assert_eq!(1, table.insert(new_row_insert_request(1, 5)).await.unwrap());
+
+    // The scans don't read the region before it's published.
+    assert_eq!(4, count_rows(&table).await);
+    assert_eq!(Some(4), table.exact_row_count());
+    let (stream, sequence) = table.scan_region(1).await.unwrap();
+    let batches = util::collect(stream).await.unwrap();
+    assert_eq!(1, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+    assert_eq!(sequence, table.committed_sequence(1).unwrap());
+
+    // Writes to a region are rejected while they are paused.
+    table.set_region_writable(0, false).unwrap();
+    let err = table
+        .insert(new_row_insert_request(0, 6))
+        .await
+        .unwrap_err();
+    assert_eq!(StatusCode::StorageUnavailable, err.status_code());
+    table.set_region_writable(0, true).unwrap();
+    assert_eq!(1, table.insert(new_row_insert_request(0, 6)).await.unwrap());
+
+    table.publish_region(1).await.unwrap();
+    assert_eq!(6, count_rows(&table).await);
+    assert_eq!(vec![0, 1], table.table_info().meta.region_numbers);
+
+    assert!(table_engine.drop_region(&ctx, &table_ref, 0).await.unwrap());
+    assert!(!table_engine.drop_region(&ctx, &table_ref, 0).await.unwrap());
+    assert_eq!(1, count_rows(&table).await);
+    assert_eq!(vec![1], table.table_info().meta.region_numbers);
+}
+
 #[tokio::test]
 async fn test_flush_table_all_regions() {
     let TestEngineComponents {
@@ -810,6 +1283,270 @@ async fn test_flush_table_all_regions() {
     assert!(has_parquet_file(&region_dir));
 }
 
+#[tokio::test]
+async fn test_flush_table_with_storage_options() {
+    let column_schemas = vec![
+        ColumnSchema::new("host", ConcreteDataType::string_datatype(), false)
+            .with_encoding("dictionary")
+            .unwrap(),
+        ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true)
+            .with_compression("zstd")
+            .unwrap(),
+        ColumnSchema::new("memory", ConcreteDataType::float64_datatype(), false)
+            .with_encoding("plain")
+            .unwrap()
+            .with_compression("snappy")
+            .unwrap(),
+        ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_datatype(common_time::timestamp::TimeUnit::Millisecond),
+            true,
+        )
+        .with_time_index(true),
+    ];
+    let schema = Arc::new(
+        SchemaBuilder::try_from(column_schemas)
+            .unwrap()
+            .build()
+            .unwrap(),
+    );
+
+    let (dir, object_store) =
+        test_util::new_test_object_store("test_flush_table_with_storage_options").await;
+    // The columns without compression options take the one of the engine.
+    let storage_config = StorageEngineConfig {
+        sst_compression: Compression::LZ4_RAW,
+        ..Default::default()
+    };
+    let table_engine = MitoEngine::new(
+        EngineConfig::default(),
+        EngineImpl::new(
+            storage_config,
+            Arc::new(NoopLogStore::default()),
+            object_store.clone(),
+            Arc::new(NoopCompactionScheduler::default()),
+        ),
+        object_store,
+    );
+    let table = table_engine
+        .create_table(
+            &EngineContext::default(),
+            test_util::new_create_request(schema),
+        )
+        .await
+        .unwrap();
+
+    let hosts: VectorRef = Arc::new(StringVector::from(vec!["host1", "host1", "host2"]));
+    let cpus: VectorRef = Arc::new(Float64Vector::from(vec![Some(55.5), None, Some(66.6)]));
+    let memories: VectorRef = Arc::new(Float64Vector::from_vec(vec![1024.0, 2048.0, 4096.0]));
+    let tss: VectorRef = Arc::new(TimestampMillisecondVector::from_vec(vec![1, 2, 1]));
+    let columns_values = HashMap::from([
+        ("host".to_string(), hosts.clone()),
+        ("cpu".to_string(), cpus.clone()),
+        ("memory".to_string(), memories.clone()),
+        ("ts".to_string(), tss.clone()),
+    ]);
+    let insert_req = new_insert_request(TABLE_NAME.to_string(), columns_values);
+    assert_eq!(3, table.insert(insert_req).await.unwrap());
+
+    table.flush(None, Some(true)).await.unwrap();
+
+    // Reads back the flushed rows.
+    let session_ctx = SessionContext::new();
+    let stream = table.scan(None, &[], None).await.unwrap();
+    let stream = stream.execute(0, session_ctx.task_ctx()).unwrap();
+    let batches = util::collect_batches(stream).await.unwrap();
+    assert_eq!(
+        batches.pretty_print().unwrap(),
+        "\
++-------+------+--------+-------------------------+
+| host  | cpu  | memory | ts                      |
++-------+------+--------+-------------------------+
+| host1 | 55.5 | 1024.0 | 1970-01-01T00:00:00.001 |
+| host1 |      | 2048.0 | 1970-01-01T00:00:00.002 |
+| host2 | 66.6 | 4096.0 | 1970-01-01T00:00:00.001 |
++-------+------+--------+-------------------------+"
+    );
+
+    // Checks the column chunks of the SST.
+    let table_info = table.table_info();
+    let region_dir = format!(
+        "{}/{}/{}",
+        dir.path().to_str().unwrap(),
+        table_dir(&table_info.catalog_name, &table_info.schema_name, 1),
+        region_name(1, 0)
+    );
+    let sst_path = std::fs::read_dir(&region_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| !path.is_dir())
+        .unwrap();
+    let reader = SerializedFileReader::new(std::fs::File::open(sst_path).unwrap()).unwrap();
+    let row_group = reader.metadata().row_group(0);
+    let column_chunk = |name: &str| {
+        row_group
+            .columns()
+            .iter()
+            .find(|column| column.column_path().string() == name)
+            .unwrap()
+    };
+    let is_dictionary = |encoding: &Encoding| {
+        matches!(
+            encoding,
+            Encoding::RLE_DICTIONARY | Encoding::PLAIN_DICTIONARY
+        )
+    };
+
+    assert!(column_chunk("host").encodings().iter().any(is_dictionary));
+    assert_eq!(Compression::LZ4_RAW, column_chunk("host").compression());
+    assert_eq!(Compression::LZ4_RAW, column_chunk("ts").compression());
+    assert!(matches!(
+        column_chunk("cpu").compression(),
+        Compression::ZSTD(_)
+    ));
+    let memory = column_chunk("memory");
+    assert!(!memory.encodings().iter().any(is_dictionary));
+    assert!(memory.encodings().contains(&Encoding::PLAIN));
+    assert_eq!(Compression::SNAPPY, memory.compression());
+}
+
+/// Returns the paths of the SSTs in the `region_dir`.
+fn list_ssts(region_dir: &str) -> HashSet<std::path::PathBuf> {
+    std::fs::read_dir(region_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| !path.is_dir())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_alter_column_storage_options() {
+    let column_schemas = vec![
+        ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+        ColumnSchema::new("memory", ConcreteDataType::float64_datatype(), false)
+            .with_encoding("plain")
+            .unwrap()
+            .with_compression("snappy")
+            .unwrap(),
+        ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_datatype(common_time::timestamp::TimeUnit::Millisecond),
+            true,
+        )
+        .with_time_index(true),
+    ];
+    let schema = Arc::new(
+        SchemaBuilder::try_from(column_schemas)
+            .unwrap()
+            .build()
+            .unwrap(),
+    );
+
+    let (dir, object_store) =
+        test_util::new_test_object_store("test_alter_column_storage_options").await;
+    let table_engine = MitoEngine::new(
+        EngineConfig::default(),
+        EngineImpl::new(
+            StorageEngineConfig::default(),
+            Arc::new(NoopLogStore::default()),
+            object_store.clone(),
+            Arc::new(NoopCompactionScheduler::default()),
+        ),
+        object_store,
+    );
+    let table = table_engine
+        .create_table(
+            &EngineContext::default(),
+            test_util::new_create_request(schema),
+        )
+        .await
+        .unwrap();
+    let table_info = table.table_info();
+    let region_dir = format!(
+        "{}/{}/{}",
+        dir.path().to_str().unwrap(),
+        table_dir(&table_info.catalog_name, &table_info.schema_name, 1),
+        region_name(1, 0)
+    );
+
+    let insert_and_flush = |ts: i64| {
+        let table = table.clone();
+        async move {
+            let hosts: VectorRef = Arc::new(StringVector::from(vec!["host1", "host2"]));
+            let memories: VectorRef = Arc::new(Float64Vector::from_vec(vec![1024.0, 2048.0]));
+            let tss: VectorRef = Arc::new(TimestampMillisecondVector::from_vec(vec![ts, ts]));
+            let columns_values = HashMap::from([
+                ("host".to_string(), hosts),
+                ("memory".to_string(), memories),
+                ("ts".to_string(), tss),
+            ]);
+            let insert_req = new_insert_request(TABLE_NAME.to_string(), columns_values);
+            assert_eq!(2, table.insert(insert_req).await.unwrap());
+            table.flush(None, Some(true)).await.unwrap();
+        }
+    };
+    insert_and_flush(1).await;
+    let old_ssts = list_ssts(&region_dir);
+    assert_eq!(1, old_ssts.len());
+
+    let req = AlterTableRequest {
+        catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+        schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+        table_name: TABLE_NAME.to_string(),
+        alter_kind: AlterKind::SetColumnOptions {
+            name: "memory".to_string(),
+            encoding: None,
+            compression: Some("ZSTD".to_string()),
+        },
+    };
+    let table = table_engine
+        .alter_table(&EngineContext::default(), req)
+        .await
+        .unwrap();
+    let memory = table
+        .schema()
+        .column_schema_by_name("memory")
+        .unwrap()
+        .clone();
+    assert_eq!(Some("plain"), memory.encoding());
+    assert_eq!(Some("zstd"), memory.compression());
+
+    insert_and_flush(2).await;
+    let ssts = list_ssts(&region_dir);
+    assert_eq!(2, ssts.len());
+
+    let memory_chunk = |path: &std::path::PathBuf| {
+        let reader = SerializedFileReader::new(std::fs::File::open(path).unwrap()).unwrap();
+        reader
+            .metadata()
+            .row_group(0)
+            .columns()
+            .iter()
+            .find(|column| column.column_path().string() == "memory")
+            .unwrap()
+            .clone()
+    };
+    // The SST written before the alteration keeps its options.
+    for path in &ssts {
+        let chunk = memory_chunk(path);
+        assert!(chunk.encodings().contains(&Encoding::PLAIN));
+        if old_ssts.contains(path) {
+            assert_eq!(Compression::SNAPPY, chunk.compression());
+        } else {
+            assert!(matches!(chunk.compression(), Compression::ZSTD(_)));
+        }
+    }
+
+    let session_ctx = SessionContext::new();
+    let stream = table.scan(None, &[], None).await.unwrap();
+    let stream = stream.execute(0, session_ctx.task_ctx()).unwrap();
+    let batches = util::collect_batches(stream).await.unwrap();
+    assert_eq!(
+        4,
+        batches.iter().map(|batch| batch.num_rows()).sum::<usize>()
+    );
+}
+
 #[tokio::test]
 async fn test_flush_table_with_region_id() {
     let TestEngineComponents {
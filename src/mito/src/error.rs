@@ -60,6 +60,19 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Invalid ttl column {} of table {}, reason: {}",
+        column_name,
+        table_name,
+        reason
+    ))]
+    InvalidTtlColumn {
+        table_name: String,
+        column_name: String,
+        reason: String,
+        location: Location,
+    },
+
     #[snafu(display(
         "Failed to build row key descriptor for table: {}, source: {}",
         table_name,
@@ -180,6 +193,16 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Writes to region {} of table {} are paused", region, table))]
+    RegionReadonly {
+        table: String,
+        region: RegionNumber,
+        location: Location,
+    },
+
+    #[snafu(display("Table {} is in the recycle bin, restore it to write", table))]
+    TableInRecycleBin { table: String, location: Location },
+
     #[snafu(display("Invalid region name: {}", region_name))]
     InvalidRegionName {
         region_name: String,
@@ -217,6 +240,7 @@ impl ErrorExt for Error {
             | ProjectedColumnNotFound { .. }
             | InvalidPrimaryKey { .. }
             | MissingTimestampIndex { .. }
+            | InvalidTtlColumn { .. }
             | TableNotFound { .. }
             | InvalidRawSchema { .. }
             | VersionChanged { .. } => StatusCode::InvalidArguments,
@@ -225,6 +249,8 @@ impl ErrorExt for Error {
 
             ScanTableManifest { .. } | UpdateTableManifest { .. } => StatusCode::StorageUnavailable,
             RegionNotFound { .. } => StatusCode::Internal,
+            RegionReadonly { .. } => StatusCode::StorageUnavailable,
+            TableInRecycleBin { .. } => StatusCode::TableNotFound,
             InvalidRegionName { .. } => StatusCode::Internal,
         }
     }
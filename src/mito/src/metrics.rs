@@ -25,3 +25,7 @@ pub const MITO_CREATE_TABLE_UPDATE_MANIFEST_ELAPSED: &str =
 pub const MITO_OPEN_TABLE_ELAPSED: &str = "datanode.mito.open_table";
 /// Elapsed time of altering tables
 pub const MITO_ALTER_TABLE_ELAPSED: &str = "datanode.mito.alter_table";
+/// Max time index value written to a table since opened, in milliseconds.
+pub const MITO_TABLE_MAX_TIMESTAMP: &str = "datanode.mito.table.max_timestamp";
+/// Wall-clock time of the last write to a table since opened, in milliseconds.
+pub const MITO_TABLE_LAST_WRITE_TIMESTAMP: &str = "datanode.mito.table.last_write_timestamp";
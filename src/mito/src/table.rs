@@ -16,37 +16,55 @@
 pub mod test_util;
 
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
-use common_error::ext::BoxedError;
+use common_base::commit_token::CommitToken;
+use common_catalog::naming::is_recycled_table_name;
+use common_error::ext::{BoxedError, ErrorExt};
 use common_query::logical_plan::Expr;
 use common_query::physical_plan::PhysicalPlanRef;
 use common_recordbatch::error::{ExternalSnafu, Result as RecordBatchResult};
-use common_recordbatch::{RecordBatch, RecordBatchStream};
-use common_telemetry::logging;
-use datatypes::schema::Schema;
+use common_recordbatch::{RecordBatch, RecordBatchStream, SendableRecordBatchStream};
+use common_telemetry::{logging, metric};
+use common_time::timestamp::TimeUnit;
+use common_time::util::{ClockRef, SystemClock};
+use common_time::Timestamp;
+use dashmap::DashMap;
+use datatypes::prelude::{ConcreteDataType, ScalarVector, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema, SchemaBuilder};
+use datatypes::types::TimestampType;
+use datatypes::vectors::{
+    TimestampMicrosecondVector, TimestampMillisecondVector, TimestampNanosecondVector,
+    TimestampSecondVector,
+};
 use futures::task::{Context, Poll};
 use futures::Stream;
 use object_store::ObjectStore;
 use snafu::{ensure, OptionExt, ResultExt};
 use store_api::manifest::{self, Manifest, ManifestVersion, MetaActionIterator};
 use store_api::storage::{
-    AddColumn, AlterOperation, AlterRequest, ChunkReader, FlushContext, ReadContext, Region,
-    RegionMeta, RegionNumber, ScanRequest, SchemaRef, Snapshot, WriteContext, WriteRequest,
+    AddColumn, AlterOperation, AlterRequest, ChunkReader,
+    DeleteRangeRequest as RegionDeleteRangeRequest, FlushContext, OrphanFile, OrphanGcRequest,
+    ReadContext, Region, RegionMeta, RegionNumber, ScanRequest, SchemaRef, SequenceNumber,
+    Snapshot, WriteContext, WriteRequest,
 };
 use table::error as table_error;
 use table::error::{
     InvalidTableSnafu, RegionSchemaMismatchSnafu, Result as TableResult, TableOperationSnafu,
+    UnsupportedSnafu, WaitSequenceTimeoutSnafu,
 };
 use table::metadata::{
     FilterPushDownType, RawTableInfo, TableInfo, TableInfoRef, TableMeta, TableType,
 };
 use table::requests::{
-    AddColumnRequest, AlterKind, AlterTableRequest, DeleteRequest, InsertRequest,
+    AddColumnRequest, AlterKind, AlterTableRequest, DeleteRangeRequest, DeleteRequest,
+    InsertRequest, WriteMode,
 };
 use table::table::scan::SimpleTableScan;
 use table::table::{AlterContext, RegionStat, Table};
@@ -54,24 +72,96 @@ use tokio::sync::Mutex;
 
 use crate::error;
 use crate::error::{
-    ProjectedColumnNotFoundSnafu, RegionNotFoundSnafu, Result, ScanTableManifestSnafu,
-    UpdateTableManifestSnafu,
+    ProjectedColumnNotFoundSnafu, RegionNotFoundSnafu, RegionReadonlySnafu, Result,
+    ScanTableManifestSnafu, TableInRecycleBinSnafu, UpdateTableManifestSnafu,
 };
 use crate::manifest::action::*;
 use crate::manifest::TableManifest;
 
+/// Interval to check whether a region has applied the writes to wait for.
+const WAIT_SEQUENCE_INTERVAL: Duration = Duration::from_millis(10);
+/// Max number of times to rebuild and retry a write rejected because the schema of the
+/// region is altered concurrently.
+const MAX_WRITE_RETRIES: usize = 3;
+
+/// Returns true if the region rejects the write because it's built against another schema.
+fn is_schema_version_mismatch(err: &dyn ErrorExt) -> bool {
+    matches!(
+        err.as_any().downcast_ref::<storage::error::Error>(),
+        Some(storage::error::Error::SchemaVersionMismatch { .. })
+    )
+}
+
 #[inline]
 fn table_manifest_dir(table_dir: &str) -> String {
     format!("{table_dir}/manifest/")
 }
 
+/// Write statistics of a region since it was opened.
+#[derive(Debug)]
+struct RegionWriteStat {
+    // `i64::MIN` if the region has not been written yet.
+    max_timestamp_millis: AtomicI64,
+    // `i64::MIN` if the region has not been written yet.
+    last_write_millis: AtomicI64,
+    // 0 if the region has not been written yet.
+    last_sequence: AtomicU64,
+}
+
+impl Default for RegionWriteStat {
+    fn default() -> Self {
+        Self {
+            max_timestamp_millis: AtomicI64::new(i64::MIN),
+            last_write_millis: AtomicI64::new(i64::MIN),
+            last_sequence: AtomicU64::new(0),
+        }
+    }
+}
+
+impl RegionWriteStat {
+    fn record(&self, max_timestamp_millis: Option<i64>, now_millis: i64) {
+        if let Some(ts) = max_timestamp_millis {
+            let _ = self.max_timestamp_millis.fetch_max(ts, Ordering::Relaxed);
+        }
+        let _ = self
+            .last_write_millis
+            .fetch_max(now_millis, Ordering::Relaxed);
+    }
+
+    fn max_timestamp_millis(&self) -> Option<i64> {
+        Some(self.max_timestamp_millis.load(Ordering::Relaxed)).filter(|x| *x != i64::MIN)
+    }
+
+    fn last_write_millis(&self) -> Option<i64> {
+        Some(self.last_write_millis.load(Ordering::Relaxed)).filter(|x| *x != i64::MIN)
+    }
+
+    fn record_sequence(&self, sequence: SequenceNumber) {
+        let _ = self.last_sequence.fetch_max(sequence, Ordering::Relaxed);
+    }
+
+    fn last_sequence(&self) -> Option<SequenceNumber> {
+        Some(self.last_sequence.load(Ordering::Relaxed)).filter(|x| *x != 0)
+    }
+}
+
 /// [Table] implementation.
 pub struct MitoTable<R: Region> {
     manifest: TableManifest,
     // guarded by `self.alter_lock`
     table_info: ArcSwap<TableInfo>,
-    regions: HashMap<RegionNumber, R>,
+    // Regions are only added or removed with `self.alter_lock` held.
+    regions: ArcSwap<HashMap<RegionNumber, R>>,
+    write_stats: DashMap<RegionNumber, RegionWriteStat>,
+    /// Regions added but not published yet, which the scans of the table don't read.
+    staged_regions: RwLock<HashSet<RegionNumber>>,
+    /// Regions whose writes are paused.
+    readonly_regions: RwLock<HashSet<RegionNumber>>,
     alter_lock: Mutex<()>,
+    /// Whether the scans encode the string tags with dictionaries.
+    dictionary_tags: bool,
+    /// Clock of the write time recorded in `write_stats`.
+    clock: ClockRef,
 }
 
 #[async_trait]
@@ -84,13 +174,30 @@ impl<R: Region> Table for MitoTable<R> {
         self.table_info().meta.schema.clone()
     }
 
+    fn scan_schema(&self) -> SchemaRef {
+        let schema = self.schema();
+        if self.dictionary_tags {
+            self.dictionary_tags_schema(&schema)
+        } else {
+            schema
+        }
+    }
+
     async fn insert(&self, request: InsertRequest) -> TableResult<usize> {
+        // Regions always overwrite the rows with the same key, skipping them requires reading
+        // the keys before writing.
+        ensure!(
+            request.write_mode == WriteMode::Upsert,
+            UnsupportedSnafu {
+                operation: format!("insert with write mode {}", request.write_mode),
+            }
+        );
         if request.columns_values.is_empty() {
             return Ok(0);
         }
 
-        let region = self
-            .regions
+        let regions = self.regions();
+        let region = regions
             .get(&request.region_number)
             .with_context(|| RegionNotFoundSnafu {
                 table: common_catalog::format_full_table_name(
@@ -102,11 +209,12 @@ impl<R: Region> Table for MitoTable<R> {
             })
             .map_err(BoxedError::new)
             .context(table_error::TableOperationSnafu)?;
-        let mut write_request = region.write_request();
+        self.ensure_writable(request.region_number)?;
 
         let columns_values = request.columns_values;
         // columns_values is not empty, it's safe to unwrap
         let rows_num = columns_values.values().next().unwrap().len();
+        let max_timestamp_millis = self.max_timestamp_millis(&columns_values);
 
         logging::trace!(
             "Insert into table {} region {} with data: {:?}",
@@ -115,16 +223,38 @@ impl<R: Region> Table for MitoTable<R> {
             columns_values
         );
 
-        write_request
-            .put(columns_values)
-            .map_err(BoxedError::new)
-            .context(table_error::TableOperationSnafu)?;
+        let mut retries = 0;
+        let resp = loop {
+            // Build the request against the latest schema of the region.
+            let mut write_request = region.write_request();
+            write_request
+                .put(columns_values.clone())
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?;
 
-        let _resp = region
-            .write(&WriteContext::default(), write_request)
-            .await
-            .map_err(BoxedError::new)
-            .context(table_error::TableOperationSnafu)?;
+            match region.write(&WriteContext::default(), write_request).await {
+                Err(e) if retries < MAX_WRITE_RETRIES && is_schema_version_mismatch(&e) => {
+                    retries += 1;
+                    logging::warn!(
+                        "Schema of region {} is altered during writing, retry {}, err: {}",
+                        region.id(),
+                        retries,
+                        e
+                    );
+                }
+                result => {
+                    break result
+                        .map_err(BoxedError::new)
+                        .context(table_error::TableOperationSnafu)?
+                }
+            }
+        };
+
+        if let Some(write_stat) = self.write_stats.get(&request.region_number) {
+            write_stat.record_sequence(resp.sequence);
+            write_stat.record(max_timestamp_millis, self.clock.now_millis());
+        }
+        self.update_write_gauges();
 
         Ok(rows_num)
     }
@@ -143,74 +273,26 @@ impl<R: Region> Table for MitoTable<R> {
         filters: &[Expr],
         _limit: Option<usize>,
     ) -> TableResult<PhysicalPlanRef> {
-        let read_ctx = ReadContext::default();
-        let mut readers = Vec::with_capacity(self.regions.len());
-        let mut first_schema: Option<Arc<Schema>> = None;
-
-        let table_info = self.table_info.load();
-        // TODO(hl): Currently the API between frontend and datanode is under refactoring in
-        // https://github.com/GreptimeTeam/greptimedb/issues/597 . Once it's finished, query plan
-        // can carry filtered region info to avoid scanning all regions on datanode.
-        for region in self.regions.values() {
-            let snapshot = region
-                .snapshot(&read_ctx)
-                .map_err(BoxedError::new)
-                .context(table_error::TableOperationSnafu)?;
-            let projection = self
-                .transform_projection(region, projection.cloned())
-                .map_err(BoxedError::new)
-                .context(table_error::TableOperationSnafu)?;
-            let filters = filters.into();
-            let scan_request = ScanRequest {
-                projection,
-                filters,
-                ..Default::default()
-            };
-            let reader = snapshot
-                .scan(&read_ctx, scan_request)
-                .await
-                .map_err(BoxedError::new)
-                .context(table_error::TableOperationSnafu)?
-                .reader;
-
-            let schema = reader.user_schema().clone();
-            if let Some(first_schema) = &first_schema {
-                // TODO(hl): we assume all regions' schemas are the same, but undergoing table altering
-                // may make these schemas inconsistent.
-                ensure!(
-                    first_schema.version() == schema.version(),
-                    RegionSchemaMismatchSnafu {
-                        table: common_catalog::format_full_table_name(
-                            &table_info.catalog_name,
-                            &table_info.schema_name,
-                            &table_info.name
-                        )
-                    }
-                );
-            } else {
-                first_schema = Some(schema);
-            }
-            readers.push(reader);
-        }
-
-        // TODO(hl): we assume table contains at least one region, but with region migration this
-        // assumption may become invalid.
-        let stream_schema = first_schema.context(InvalidTableSnafu {
-            table_id: table_info.ident.table_id,
-        })?;
+        self.scan_with_ctx(&ReadContext::default(), projection, filters)
+            .await
+    }
 
-        let schema = stream_schema.clone();
-        let stream = Box::pin(async_stream::try_stream! {
-            for mut reader in readers {
-                while let Some(chunk) = reader.next_chunk().await.map_err(BoxedError::new).context(ExternalSnafu)? {
-                    let chunk = reader.project_chunk(chunk);
-                    yield RecordBatch::new(stream_schema.clone(), chunk.columns)?
-                }
-            }
-        });
+    fn supports_time_travel(&self) -> bool {
+        true
+    }
 
-        let stream = Box::pin(ChunkStream { schema, stream });
-        Ok(Arc::new(SimpleTableScan::new(stream)))
+    async fn scan_as_of(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        _limit: Option<usize>,
+        as_of: i64,
+    ) -> TableResult<PhysicalPlanRef> {
+        let read_ctx = ReadContext {
+            as_of: Some(as_of),
+            ..Default::default()
+        };
+        self.scan_with_ctx(&read_ctx, projection, filters).await
     }
 
     fn supports_filters_pushdown(&self, filters: &[&Expr]) -> TableResult<Vec<FilterPushDownType>> {
@@ -230,7 +312,9 @@ impl<R: Region> Table for MitoTable<R> {
             AlterKind::RenameTable { new_table_name } => {
                 new_info.name = new_table_name.clone();
             }
-            AlterKind::AddColumns { .. } | AlterKind::DropColumns { .. } => {
+            AlterKind::AddColumns { .. }
+            | AlterKind::DropColumns { .. }
+            | AlterKind::SetColumnOptions { .. } => {
                 let table_meta = &table_info.meta;
                 let new_meta = table_meta
                     .builder_with_alter_kind(table_name, &req.alter_kind)?
@@ -302,7 +386,11 @@ impl<R: Region> Table for MitoTable<R> {
         let mut rows_deleted = 0;
         // TODO(hl): Should be tracked by procedure.
         // TODO(hl): Parse delete request into region->keys instead of delete in each region
-        for region in self.regions.values() {
+        let regions = self.regions();
+        for region_number in regions.keys() {
+            self.ensure_writable(*region_number)?;
+        }
+        for (region_number, region) in regions.iter() {
             let mut write_request = region.write_request();
             let key_column_values = request.key_column_values.clone();
             // Safety: key_column_values isn't empty.
@@ -318,24 +406,61 @@ impl<R: Region> Table for MitoTable<R> {
                 .delete(key_column_values)
                 .map_err(BoxedError::new)
                 .context(table_error::TableOperationSnafu)?;
-            region
+            let resp = region
                 .write(&WriteContext::default(), write_request)
                 .await
                 .map_err(BoxedError::new)
                 .context(table_error::TableOperationSnafu)?;
+            if let Some(write_stat) = self.write_stats.get(region_number) {
+                write_stat.record_sequence(resp.sequence);
+            }
             rows_deleted += rows_num;
         }
         Ok(rows_deleted)
     }
 
+    fn supports_delete_range(&self) -> bool {
+        true
+    }
+
+    async fn delete_range(&self, request: DeleteRangeRequest) -> TableResult<()> {
+        let regions = self.regions();
+        for region_number in regions.keys() {
+            self.ensure_writable(*region_number)?;
+        }
+        logging::debug!(
+            "Delete range from table {}, request: {:?}",
+            self.table_info().name,
+            request
+        );
+
+        let keys: Vec<_> = request.key_column_values.into_iter().collect();
+        for (region_number, region) in regions.iter() {
+            let region_request = RegionDeleteRangeRequest {
+                keys: keys.clone(),
+                time_range: request.time_range,
+            };
+            let resp = region
+                .delete_range(region_request)
+                .await
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?;
+            if let Some(write_stat) = self.write_stats.get(region_number) {
+                write_stat.record_sequence(resp.sequence);
+            }
+        }
+        Ok(())
+    }
+
     async fn flush(
         &self,
         region_number: Option<RegionNumber>,
         wait: Option<bool>,
     ) -> TableResult<()> {
         let flush_ctx = wait.map(|wait| FlushContext { wait }).unwrap_or_default();
+        let regions = self.regions();
         if let Some(region_number) = region_number {
-            if let Some(region) = self.regions.get(&region_number) {
+            if let Some(region) = regions.get(&region_number) {
                 region
                     .flush(&flush_ctx)
                     .await
@@ -343,36 +468,197 @@ impl<R: Region> Table for MitoTable<R> {
                     .context(table_error::TableOperationSnafu)?;
             }
         } else {
-            futures::future::try_join_all(
-                self.regions.values().map(|region| region.flush(&flush_ctx)),
-            )
-            .await
-            .map_err(BoxedError::new)
-            .context(table_error::TableOperationSnafu)?;
+            futures::future::try_join_all(regions.values().map(|region| region.flush(&flush_ctx)))
+                .await
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?;
         }
 
         Ok(())
     }
 
     async fn close(&self) -> TableResult<()> {
-        futures::future::try_join_all(self.regions.values().map(|region| region.close()))
+        futures::future::try_join_all(self.regions().values().map(|region| region.close()))
             .await
             .map_err(BoxedError::new)
             .context(table_error::TableOperationSnafu)?;
+        // The gauges of a closed table would otherwise be exported until the process exits.
+        let labels = self.gauge_labels();
+        metric::remove_removable_gauge(crate::metrics::MITO_TABLE_MAX_TIMESTAMP, &labels);
+        metric::remove_removable_gauge(crate::metrics::MITO_TABLE_LAST_WRITE_TIMESTAMP, &labels);
 
         Ok(())
     }
 
     fn region_stats(&self) -> TableResult<Vec<RegionStat>> {
+        let wal_disabled = self.table_info().meta.options.wal_disabled;
         Ok(self
-            .regions
-            .values()
-            .map(|region| RegionStat {
-                region_id: region.id(),
-                disk_usage_bytes: region.disk_usage_bytes(),
+            .regions()
+            .iter()
+            .map(|(region_number, region)| {
+                let write_stat = self.write_stats.get(region_number);
+                let write_stat = write_stat.as_deref();
+                RegionStat {
+                    region_id: region.id(),
+                    disk_usage_bytes: region.disk_usage_bytes(),
+                    storage_bytes: region.storage_bytes(),
+                    max_timestamp_millis: write_stat.and_then(|x| x.max_timestamp_millis()),
+                    last_write_millis: write_stat.and_then(|x| x.last_write_millis()),
+                    flush_threshold_bytes: region.flush_threshold_bytes(),
+                    wal_disabled,
+                    approximate_rows: region.approximate_rows(),
+                    memtable_bytes: region.memtable_bytes(),
+                }
             })
             .collect())
     }
+
+    fn manifest_bytes(&self) -> u64 {
+        self.manifest.stored_bytes()
+    }
+
+    fn exact_row_count(&self) -> Option<u64> {
+        let staged_regions = self.staged_regions.read().unwrap();
+        self.regions()
+            .iter()
+            .filter(|(region_number, _)| !staged_regions.contains(region_number))
+            .map(|(_, region)| region.exact_rows())
+            .sum()
+    }
+
+    fn commit_token(&self) -> TableResult<CommitToken> {
+        let mut token = CommitToken::default();
+        for (region_number, region) in self.regions().iter() {
+            let last_sequence = self
+                .write_stats
+                .get(region_number)
+                .and_then(|x| x.last_sequence());
+            if let Some(sequence) = last_sequence {
+                token.record(region.id(), sequence);
+            }
+        }
+        Ok(token)
+    }
+
+    async fn wait_for_sequence(
+        &self,
+        region_number: RegionNumber,
+        sequence: SequenceNumber,
+        timeout: Duration,
+    ) -> TableResult<()> {
+        let region = match self.regions().get(&region_number) {
+            Some(region) => region.clone(),
+            // The region may be served by another datanode.
+            None => return Ok(()),
+        };
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let committed_sequence = region.committed_sequence();
+            if committed_sequence >= sequence {
+                return Ok(());
+            }
+            ensure!(
+                Instant::now() < deadline,
+                WaitSequenceTimeoutSnafu {
+                    region_id: region.id(),
+                    sequence,
+                    committed_sequence,
+                    timeout,
+                }
+            );
+            tokio::time::sleep(WAIT_SEQUENCE_INTERVAL).await;
+        }
+    }
+
+    async fn gc_orphans(&self, request: &OrphanGcRequest) -> TableResult<Vec<OrphanFile>> {
+        let mut orphans = Vec::new();
+        // Collects the regions one by one so the deletions are limited across the regions.
+        for region in self.regions().values() {
+            let resp = region
+                .gc_orphans(request)
+                .await
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?;
+            orphans.extend(resp.orphans);
+        }
+        Ok(orphans)
+    }
+
+    async fn purge_expired(&self, _expire_before: Timestamp) -> TableResult<()> {
+        // The compaction of the regions drops the files expired by the ttl of the table.
+        UnsupportedSnafu {
+            operation: "PURGE_EXPIRED",
+        }
+        .fail()?
+    }
+
+    async fn scan_region(
+        &self,
+        region_number: RegionNumber,
+    ) -> TableResult<(SendableRecordBatchStream, SequenceNumber)> {
+        let region = self.region(region_number)?;
+        // Reads the sequence before taking the snapshot, which has all the rows up to the
+        // sequence and maybe some later ones.
+        let sequence = region.committed_sequence();
+        let read_ctx = ReadContext::default();
+        let snapshot = region
+            .snapshot(&read_ctx)
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)?;
+        let projection = self
+            .transform_projection(&region, None)
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)?;
+        let scan_request = ScanRequest {
+            projection,
+            ..Default::default()
+        };
+        let mut reader = snapshot
+            .scan(&read_ctx, scan_request)
+            .await
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)?
+            .reader;
+
+        let stream_schema = reader.user_schema().clone();
+        let schema = stream_schema.clone();
+        let stream = Box::pin(async_stream::try_stream! {
+            while let Some(chunk) = reader.next_chunk().await.map_err(BoxedError::new).context(ExternalSnafu)? {
+                let chunk = reader.project_chunk(chunk);
+                yield RecordBatch::new(stream_schema.clone(), chunk.columns)?
+            }
+        });
+        Ok((Box::pin(ChunkStream { schema, stream }), sequence))
+    }
+
+    fn committed_sequence(&self, region_number: RegionNumber) -> TableResult<SequenceNumber> {
+        Ok(self.region(region_number)?.committed_sequence())
+    }
+
+    fn set_region_writable(&self, region_number: RegionNumber, writable: bool) -> TableResult<()> {
+        let _ = self.region(region_number)?;
+        let mut readonly_regions = self.readonly_regions.write().unwrap();
+        if writable {
+            let _ = readonly_regions.remove(&region_number);
+        } else {
+            let _ = readonly_regions.insert(region_number);
+        }
+        Ok(())
+    }
+
+    async fn publish_region(&self, region_number: RegionNumber) -> TableResult<()> {
+        let _lock = self.alter_lock.lock().await;
+        let _ = self.region(region_number)?;
+        self.update_region_numbers(|region_numbers| {
+            if !region_numbers.contains(&region_number) {
+                region_numbers.push(region_number);
+            }
+        })
+        .await?;
+        let _ = self.staged_regions.write().unwrap().remove(&region_number);
+        Ok(())
+    }
 }
 
 struct ChunkStream {
@@ -405,12 +691,241 @@ impl<R: Region> MitoTable<R> {
         regions: HashMap<RegionNumber, R>,
         manifest: TableManifest,
     ) -> Self {
-        Self {
+        let write_stats = regions
+            .keys()
+            .map(|region_number| (*region_number, RegionWriteStat::default()))
+            .collect();
+        let table = Self {
             table_info: ArcSwap::new(Arc::new(table_info)),
-            regions,
+            regions: ArcSwap::new(Arc::new(regions)),
+            write_stats,
+            staged_regions: RwLock::new(HashSet::new()),
+            readonly_regions: RwLock::new(HashSet::new()),
             manifest,
             alter_lock: Mutex::new(()),
+            dictionary_tags: false,
+            clock: Arc::new(SystemClock),
+        };
+        table.update_write_gauges();
+        table
+    }
+
+    /// Sets whether the scans encode the string tags with dictionaries.
+    pub(crate) fn with_dictionary_tags(mut self, dictionary_tags: bool) -> Self {
+        self.dictionary_tags = dictionary_tags;
+        self
+    }
+
+    /// Sets the clock of the write time of the table.
+    pub(crate) fn with_clock(mut self, clock: ClockRef) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the `schema` with the string tags encoded with dictionaries, the same as the
+    /// chunks read with [ScanRequest::dictionary_tags].
+    fn dictionary_tags_schema(&self, schema: &SchemaRef) -> SchemaRef {
+        let table_info = self.table_info();
+        let table_schema = &table_info.meta.schema;
+        let is_string_tag = |column_schema: &ColumnSchema| {
+            column_schema.data_type == ConcreteDataType::string_datatype()
+                && table_info
+                    .meta
+                    .primary_key_indices
+                    .iter()
+                    .any(|i| table_schema.column_schemas()[*i].name == column_schema.name)
+        };
+        if !schema.column_schemas().iter().any(is_string_tag) {
+            return schema.clone();
+        }
+
+        let column_schemas = schema
+            .column_schemas()
+            .iter()
+            .map(|column_schema| {
+                if is_string_tag(column_schema) {
+                    let mut column_schema = column_schema.clone();
+                    column_schema.data_type = ConcreteDataType::dictionary_datatype(
+                        ConcreteDataType::uint32_datatype(),
+                        ConcreteDataType::string_datatype(),
+                    );
+                    column_schema
+                } else {
+                    column_schema.clone()
+                }
+            })
+            .collect();
+        // The schema is built from a valid one, with only the types of some columns changed.
+        let builder = SchemaBuilder::try_from_columns(column_schemas)
+            .unwrap()
+            .version(schema.version());
+        let builder = schema
+            .metadata()
+            .iter()
+            .fold(builder, |builder, (key, value)| {
+                builder.add_metadata(key, value)
+            });
+        Arc::new(builder.build().unwrap())
+    }
+
+    /// Returns the max time index value in `columns_values`, in milliseconds.
+    fn max_timestamp_millis(&self, columns_values: &HashMap<String, VectorRef>) -> Option<i64> {
+        let table_info = self.table_info();
+        let ts_column = table_info.meta.schema.timestamp_column()?;
+        let vector = columns_values.get(&ts_column.name)?;
+
+        macro_rules! max_timestamp {
+            ($VectorType: ty) => {
+                vector
+                    .as_any()
+                    .downcast_ref::<$VectorType>()?
+                    .iter_data()
+                    .flatten()
+                    .map(|ts| ts.0)
+                    .max()
+            };
+        }
+
+        let max = match vector.data_type() {
+            ConcreteDataType::Timestamp(TimestampType::Second(_)) => {
+                max_timestamp!(TimestampSecondVector)
+            }
+            ConcreteDataType::Timestamp(TimestampType::Millisecond(_)) => {
+                max_timestamp!(TimestampMillisecondVector)
+            }
+            ConcreteDataType::Timestamp(TimestampType::Microsecond(_)) => {
+                max_timestamp!(TimestampMicrosecondVector)
+            }
+            ConcreteDataType::Timestamp(TimestampType::Nanosecond(_)) => {
+                max_timestamp!(TimestampNanosecondVector)
+            }
+            _ => None,
+        }?;
+        max.convert_to(TimeUnit::Millisecond).map(|ts| ts.value())
+    }
+
+    /// Exports the max time index value and the last write time of the table, which are the
+    /// max values among its regions. Both are `NaN` if the table has not been written since
+    /// opened.
+    fn update_write_gauges(&self) {
+        let labels = self.gauge_labels();
+        let max_timestamp_millis = self
+            .write_stats
+            .iter()
+            .filter_map(|x| x.max_timestamp_millis())
+            .max();
+        let last_write_millis = self
+            .write_stats
+            .iter()
+            .filter_map(|x| x.last_write_millis())
+            .max();
+
+        metric::set_removable_gauge(
+            crate::metrics::MITO_TABLE_MAX_TIMESTAMP,
+            &labels,
+            max_timestamp_millis.map_or(f64::NAN, |x| x as f64),
+        );
+        metric::set_removable_gauge(
+            crate::metrics::MITO_TABLE_LAST_WRITE_TIMESTAMP,
+            &labels,
+            last_write_millis.map_or(f64::NAN, |x| x as f64),
+        );
+    }
+
+    fn gauge_labels(&self) -> [(&'static str, String); 3] {
+        let table_info = self.table_info();
+        [
+            ("catalog", table_info.catalog_name.clone()),
+            ("schema", table_info.schema_name.clone()),
+            ("table", table_info.name.clone()),
+        ]
+    }
+
+    /// Scans the snapshots of all regions read by `read_ctx`.
+    async fn scan_with_ctx(
+        &self,
+        read_ctx: &ReadContext,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+    ) -> TableResult<PhysicalPlanRef> {
+        let regions = self.regions();
+        let mut readers = Vec::with_capacity(regions.len());
+        let mut first_schema: Option<Arc<Schema>> = None;
+
+        let table_info = self.table_info.load();
+        let staged_regions = self.staged_regions.read().unwrap().clone();
+        // TODO(hl): Currently the API between frontend and datanode is under refactoring in
+        // https://github.com/GreptimeTeam/greptimedb/issues/597 . Once it's finished, query plan
+        // can carry filtered region info to avoid scanning all regions on datanode.
+        for (_, region) in regions
+            .iter()
+            .filter(|(region_number, _)| !staged_regions.contains(region_number))
+        {
+            let snapshot = region
+                .snapshot(read_ctx)
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?;
+            let projection = self
+                .transform_projection(region, projection.cloned())
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?;
+            let filters = filters.into();
+            let scan_request = ScanRequest {
+                projection,
+                filters,
+                dictionary_tags: self.dictionary_tags,
+                ..Default::default()
+            };
+            let reader = snapshot
+                .scan(read_ctx, scan_request)
+                .await
+                .map_err(BoxedError::new)
+                .context(table_error::TableOperationSnafu)?
+                .reader;
+
+            let schema = reader.user_schema().clone();
+            if let Some(first_schema) = &first_schema {
+                // TODO(hl): we assume all regions' schemas are the same, but undergoing table altering
+                // may make these schemas inconsistent.
+                ensure!(
+                    first_schema.version() == schema.version(),
+                    RegionSchemaMismatchSnafu {
+                        table: common_catalog::format_full_table_name(
+                            &table_info.catalog_name,
+                            &table_info.schema_name,
+                            &table_info.name
+                        )
+                    }
+                );
+            } else {
+                first_schema = Some(schema);
+            }
+            readers.push(reader);
         }
+
+        // TODO(hl): we assume table contains at least one region, but with region migration this
+        // assumption may become invalid.
+        let stream_schema = first_schema.context(InvalidTableSnafu {
+            table_id: table_info.ident.table_id,
+        })?;
+        let stream_schema = if self.dictionary_tags {
+            self.dictionary_tags_schema(&stream_schema)
+        } else {
+            stream_schema
+        };
+
+        let schema = stream_schema.clone();
+        let stream = Box::pin(async_stream::try_stream! {
+            for mut reader in readers {
+                while let Some(chunk) = reader.next_chunk().await.map_err(BoxedError::new).context(ExternalSnafu)? {
+                    let chunk = reader.project_chunk(chunk);
+                    yield RecordBatch::new(stream_schema.clone(), chunk.columns)?
+                }
+            }
+        });
+
+        let stream = Box::pin(ChunkStream { schema, stream });
+        Ok(Arc::new(SimpleTableScan::new(stream)))
     }
 
     /// Transform projection which is based on table schema
@@ -533,6 +1048,10 @@ impl<R: Region> MitoTable<R> {
             // update manifest state after recovering
             let protocol = iter.last_protocol();
             manifest.update_state(last_manifest_version + 1, protocol.clone());
+            manifest
+                .load_stored_bytes()
+                .await
+                .context(ScanTableManifestSnafu { table_name })?;
         }
 
         logging::debug!(
@@ -545,8 +1064,134 @@ impl<R: Region> MitoTable<R> {
     }
 
     #[inline]
-    pub fn regions(&self) -> &HashMap<RegionNumber, R> {
-        &self.regions
+    pub fn regions(&self) -> Arc<HashMap<RegionNumber, R>> {
+        self.regions.load_full()
+    }
+
+    /// Adds `region` as region `region_number` of the table, which isn't read by the scans
+    /// of the table until it's published.
+    pub(crate) async fn add_region(&self, region_number: RegionNumber, region: R) {
+        let _lock = self.alter_lock.lock().await;
+        if self.regions().contains_key(&region_number) {
+            return;
+        }
+        let _ = self.staged_regions.write().unwrap().insert(region_number);
+        let _ = self
+            .write_stats
+            .insert(region_number, RegionWriteStat::default());
+        let mut regions = HashMap::clone(&self.regions());
+        let _ = regions.insert(region_number, region);
+        self.regions.store(Arc::new(regions));
+    }
+
+    /// Removes region `region_number` from the table and its manifest, returns the removed
+    /// region.
+    pub(crate) async fn remove_region(
+        &self,
+        region_number: RegionNumber,
+    ) -> TableResult<Option<R>> {
+        let _lock = self.alter_lock.lock().await;
+        if !self.regions().contains_key(&region_number) {
+            return Ok(None);
+        }
+        self.update_region_numbers(|region_numbers| region_numbers.retain(|n| *n != region_number))
+            .await?;
+
+        let mut regions = HashMap::clone(&self.regions());
+        let region = regions.remove(&region_number);
+        self.regions.store(Arc::new(regions));
+        let _ = self.write_stats.remove(&region_number);
+        let _ = self.staged_regions.write().unwrap().remove(&region_number);
+        let _ = self
+            .readonly_regions
+            .write()
+            .unwrap()
+            .remove(&region_number);
+        Ok(region)
+    }
+
+    /// Persists the region numbers of the table changed by `f`, must be called with
+    /// `self.alter_lock` held.
+    async fn update_region_numbers(
+        &self,
+        f: impl FnOnce(&mut Vec<RegionNumber>),
+    ) -> TableResult<()> {
+        let mut new_info = TableInfo::clone(&*self.table_info());
+        f(&mut new_info.meta.region_numbers);
+        if new_info.meta.region_numbers == self.table_info().meta.region_numbers {
+            return Ok(());
+        }
+        self.manifest
+            .update(TableMetaActionList::with_action(TableMetaAction::Change(
+                Box::new(TableChange {
+                    table_info: RawTableInfo::from(new_info.clone()),
+                }),
+            )))
+            .await
+            .context(UpdateTableManifestSnafu {
+                table_name: &new_info.name,
+            })
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)?;
+        self.set_table_info(new_info);
+        Ok(())
+    }
+
+    /// Returns region `region_number` of the table.
+    fn region(&self, region_number: RegionNumber) -> TableResult<R> {
+        self.regions()
+            .get(&region_number)
+            .cloned()
+            .with_context(|| {
+                let table_info = self.table_info();
+                RegionNotFoundSnafu {
+                    table: common_catalog::format_full_table_name(
+                        &table_info.catalog_name,
+                        &table_info.schema_name,
+                        &table_info.name,
+                    ),
+                    region: region_number,
+                }
+            })
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu)
+    }
+
+    /// Returns the error if the table is in the recycle bin, or the writes to region
+    /// `region_number` are paused.
+    fn ensure_writable(&self, region_number: RegionNumber) -> TableResult<()> {
+        let table_info = self.table_info();
+        if is_recycled_table_name(&table_info.name) {
+            return TableInRecycleBinSnafu {
+                table: common_catalog::format_full_table_name(
+                    &table_info.catalog_name,
+                    &table_info.schema_name,
+                    &table_info.name,
+                ),
+            }
+            .fail()
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu);
+        }
+        let is_readonly = self
+            .readonly_regions
+            .read()
+            .unwrap()
+            .contains(&region_number);
+        if is_readonly {
+            return RegionReadonlySnafu {
+                table: common_catalog::format_full_table_name(
+                    &table_info.catalog_name,
+                    &table_info.schema_name,
+                    &table_info.name,
+                ),
+                region: region_number,
+            }
+            .fail()
+            .map_err(BoxedError::new)
+            .context(table_error::TableOperationSnafu);
+        }
+        Ok(())
     }
 
     pub fn set_table_info(&self, table_info: TableInfo) {
@@ -577,6 +1222,28 @@ pub(crate) fn create_alter_operation(
         AlterKind::DropColumns { names } => Ok(Some(AlterOperation::DropColumns {
             names: names.clone(),
         })),
+        AlterKind::SetColumnOptions {
+            name,
+            encoding,
+            compression,
+        } => {
+            // Takes the options validated and normalized by the new meta.
+            let column_schema = table_meta.schema.column_schema_by_name(name).context(
+                table_error::ColumnNotExistsSnafu {
+                    column_name: name,
+                    table_name,
+                },
+            )?;
+            Ok(Some(AlterOperation::SetColumnOptions {
+                name: name.clone(),
+                encoding: encoding
+                    .as_ref()
+                    .and(column_schema.encoding().map(String::from)),
+                compression: compression
+                    .as_ref()
+                    .and(column_schema.compression().map(String::from)),
+            }))
+        }
         // No need to build alter operation when reaming tables.
         AlterKind::RenameTable { .. } => Ok(None),
     }
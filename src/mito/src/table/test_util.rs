@@ -14,10 +14,12 @@
 
 mod mock_engine;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
 
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_test_util::temp_dir::{create_temp_dir, TempDir};
+use common_time::util::{Clock, ClockRef, SystemClock};
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::{ColumnSchema, RawSchema, Schema, SchemaBuilder, SchemaRef};
 use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector, VectorRef};
@@ -51,6 +53,7 @@ pub fn new_insert_request(
         table_name,
         columns_values,
         region_number: 0,
+        write_mode: Default::default(),
     }
 }
 
@@ -147,7 +150,29 @@ pub struct TestEngineComponents {
     pub dir: TempDir,
 }
 
+/// Clock whose time only changes by [MockClock::set].
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now_millis: AtomicI64,
+}
+
+impl MockClock {
+    pub fn set(&self, now_millis: i64) {
+        self.now_millis.store(now_millis, Ordering::Relaxed);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_millis(&self) -> i64 {
+        self.now_millis.load(Ordering::Relaxed)
+    }
+}
+
 pub async fn setup_test_engine_and_table() -> TestEngineComponents {
+    setup_test_engine_and_table_with_clock(Arc::new(SystemClock)).await
+}
+
+pub async fn setup_test_engine_and_table_with_clock(clock: ClockRef) -> TestEngineComponents {
     let (dir, object_store) = new_test_object_store("setup_test_engine_and_table").await;
     let compaction_scheduler = Arc::new(NoopCompactionScheduler::default());
     let storage_engine = EngineImpl::new(
@@ -156,10 +181,11 @@ pub async fn setup_test_engine_and_table() -> TestEngineComponents {
         object_store.clone(),
         compaction_scheduler,
     );
-    let table_engine = MitoEngine::new(
+    let table_engine = MitoEngine::with_clock(
         EngineConfig::default(),
         storage_engine.clone(),
         object_store.clone(),
+        clock,
     );
 
     let schema = Arc::new(schema_for_test());
@@ -183,7 +209,12 @@ pub async fn setup_test_engine_and_table() -> TestEngineComponents {
 
 pub async fn setup_mock_engine_and_table(
 ) -> (MockEngine, MockMitoEngine, TableRef, ObjectStore, TempDir) {
-    let mock_engine = MockEngine::default();
+    setup_table_with_mock_engine(MockEngine::default()).await
+}
+
+pub async fn setup_table_with_mock_engine(
+    mock_engine: MockEngine,
+) -> (MockEngine, MockMitoEngine, TableRef, ObjectStore, TempDir) {
     let (dir, object_store) = new_test_object_store("setup_mock_engine_and_table").await;
     let table_engine = MitoEngine::new(
         EngineConfig::default(),
@@ -14,21 +14,25 @@
 
 //! A mock storage engine for table test purpose.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use common_error::mock::MockError;
+use common_error::status_code::StatusCode;
 use common_telemetry::logging;
 use datatypes::prelude::{DataType, Value, VectorRef};
 use datatypes::schema::{ColumnSchema, Schema};
 use storage::metadata::{RegionMetaImpl, RegionMetadata};
 use storage::write_batch::WriteBatch;
 use store_api::storage::{
-    AlterRequest, Chunk, ChunkReader, CreateOptions, EngineContext, FlushContext, GetRequest,
-    GetResponse, OpenOptions, ReadContext, Region, RegionDescriptor, RegionId, ScanRequest,
-    ScanResponse, SchemaRef, Snapshot, StorageEngine, WriteContext, WriteResponse,
+    AlterRequest, Chunk, ChunkReader, CreateOptions, DeleteRangeRequest, EngineContext,
+    FlushContext, GetRequest, GetResponse, OpenOptions, OrphanGcRequest, OrphanGcResponse,
+    ReadContext, Region, RegionDescriptor, RegionId, ScanRequest, ScanResponse, SchemaRef,
+    SequenceNumber, Snapshot, StorageEngine, WriteContext, WriteResponse,
 };
 
 pub type Result<T> = std::result::Result<T, MockError>;
@@ -140,6 +144,10 @@ pub struct MockRegionInner {
     name: String,
     pub metadata: ArcSwap<RegionMetadata>,
     memtable: Arc<RwLock<MockMemtable>>,
+    /// Delay to apply writes, which are invisible to reads until applied.
+    apply_delay: Option<Duration>,
+    last_sequence: AtomicU64,
+    committed_sequence: AtomicU64,
 }
 
 /// A columnar memtable, maps column name to data of that column in each row.
@@ -165,8 +173,24 @@ impl Region for MockRegion {
     }
 
     async fn write(&self, _ctx: &WriteContext, request: WriteBatch) -> Result<WriteResponse> {
-        self.inner.write(request);
-        Ok(WriteResponse {})
+        let sequence = self.inner.last_sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        match self.inner.apply_delay {
+            Some(delay) => {
+                let inner = self.inner.clone();
+                let _handle = tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    inner.write(request, sequence);
+                });
+            }
+            None => self.inner.write(request, sequence),
+        }
+        Ok(WriteResponse { sequence })
+    }
+
+    async fn delete_range(&self, request: DeleteRangeRequest) -> Result<WriteResponse> {
+        let sequence = self.inner.last_sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        self.inner.delete_range(request, sequence);
+        Ok(WriteResponse { sequence })
     }
 
     fn snapshot(&self, _ctx: &ReadContext) -> Result<MockSnapshot> {
@@ -201,13 +225,41 @@ impl Region for MockRegion {
         0
     }
 
+    fn storage_bytes(&self) -> u64 {
+        0
+    }
+
+    fn approximate_rows(&self) -> u64 {
+        0
+    }
+
+    fn exact_rows(&self) -> Option<u64> {
+        None
+    }
+
+    fn memtable_bytes(&self) -> u64 {
+        0
+    }
+
+    fn committed_sequence(&self) -> SequenceNumber {
+        self.inner.committed_sequence.load(Ordering::Relaxed)
+    }
+
+    fn flush_threshold_bytes(&self) -> Option<u64> {
+        None
+    }
+
     async fn flush(&self, _ctx: &FlushContext) -> Result<()> {
         unimplemented!()
     }
+
+    async fn gc_orphans(&self, _request: &OrphanGcRequest) -> Result<OrphanGcResponse> {
+        Ok(OrphanGcResponse::default())
+    }
 }
 
 impl MockRegionInner {
-    fn new(metadata: RegionMetadata) -> Self {
+    fn new(metadata: RegionMetadata, apply_delay: Option<Duration>) -> Self {
         let mut memtable = HashMap::new();
         for column in metadata.user_schema().column_schemas() {
             memtable.insert(column.name.clone(), vec![]);
@@ -216,6 +268,9 @@ impl MockRegionInner {
             name: metadata.name().to_string(),
             metadata: ArcSwap::new(Arc::new(metadata)),
             memtable: Arc::new(RwLock::new(memtable)),
+            apply_delay,
+            last_sequence: AtomicU64::new(0),
+            committed_sequence: AtomicU64::new(0),
         }
     }
 
@@ -234,7 +289,7 @@ impl MockRegionInner {
         self.metadata.swap(Arc::new(metadata));
     }
 
-    fn write(&self, request: WriteBatch) {
+    fn write(&self, request: WriteBatch, sequence: SequenceNumber) {
         let metadata = self.metadata.load();
 
         let mut memtable = self.memtable.write().unwrap();
@@ -247,6 +302,39 @@ impl MockRegionInner {
                 }
             }
         }
+        let _ = self
+            .committed_sequence
+            .fetch_max(sequence, Ordering::Relaxed);
+    }
+
+    fn delete_range(&self, request: DeleteRangeRequest, sequence: SequenceNumber) {
+        let metadata = self.metadata.load();
+        // Mock engine just panic if the request is invalid.
+        let ts_name = &metadata.user_schema().timestamp_column().unwrap().name;
+
+        let mut memtable = self.memtable.write().unwrap();
+        let deleted: Vec<_> = memtable[ts_name]
+            .iter()
+            .enumerate()
+            .map(|(i, ts)| {
+                let in_range = match ts {
+                    Value::Timestamp(ts) => request.time_range.contains(ts),
+                    _ => false,
+                };
+                in_range
+                    && request
+                        .keys
+                        .iter()
+                        .all(|(name, value)| memtable[name][i] == *value)
+            })
+            .collect();
+        for column in memtable.values_mut() {
+            let mut deleted = deleted.iter();
+            column.retain(|_| !deleted.next().unwrap());
+        }
+        let _ = self
+            .committed_sequence
+            .fetch_max(sequence, Ordering::Relaxed);
     }
 }
 
@@ -256,11 +344,40 @@ type RegionMap = HashMap<String, MockRegion>;
 struct RegionManager {
     opened_regions: RegionMap,
     closed_regions: RegionMap,
+    /// Regions failed to open, like regions with corrupted WALs.
+    corrupted_regions: HashSet<String>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct MockEngine {
     regions: Arc<Mutex<RegionManager>>,
+    apply_delay: Option<Duration>,
+    replay_delay: Option<Duration>,
+}
+
+impl MockEngine {
+    /// Creates a mock engine whose regions apply writes after `delay`.
+    pub fn with_apply_delay(delay: Duration) -> Self {
+        Self {
+            apply_delay: Some(delay),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a mock engine sharing the regions, which takes `delay` to open a region as
+    /// replaying its WAL.
+    pub fn with_replay_delay(&self, delay: Duration) -> Self {
+        Self {
+            replay_delay: Some(delay),
+            ..self.clone()
+        }
+    }
+
+    /// Fails to open the region `name` afterwards.
+    pub fn corrupt_region(&self, name: &str) {
+        let mut regions = self.regions.lock().unwrap();
+        let _ = regions.corrupted_regions.insert(name.to_string());
+    }
 }
 
 #[async_trait]
@@ -274,9 +391,16 @@ impl StorageEngine for MockEngine {
         name: &str,
         _opts: &OpenOptions,
     ) -> Result<Option<MockRegion>> {
-        logging::info!("Mock engine create region, name: {}", name);
+        logging::info!("Mock engine open region, name: {}", name);
+
+        if let Some(delay) = self.replay_delay {
+            tokio::time::sleep(delay).await;
+        }
 
         let mut regions = self.regions.lock().unwrap();
+        if regions.corrupted_regions.contains(name) {
+            return Err(MockError::new(StatusCode::StorageUnavailable));
+        }
         if let Some(region) = regions.opened_regions.get(name) {
             return Ok(Some(region.clone()));
         }
@@ -311,7 +435,7 @@ impl StorageEngine for MockEngine {
         let name = descriptor.name.clone();
         let metadata = descriptor.try_into().unwrap();
         let region = MockRegion {
-            inner: Arc::new(MockRegionInner::new(metadata)),
+            inner: Arc::new(MockRegionInner::new(metadata, self.apply_delay)),
         };
         regions.opened_regions.insert(name, region.clone());
 
@@ -12,6 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use opendal::ops::{OpDelete, OpList, OpRead, OpScan, OpStat, OpWrite};
+use opendal::raw::{
+    Accessor, Layer, LayeredAccessor, RpDelete, RpList, RpRead, RpScan, RpStat, RpWrite,
+};
+
 use crate::{ObjectStore, Result};
 
 pub struct TempFolder {
@@ -32,3 +40,108 @@ impl TempFolder {
         self.store.remove_all(&self.path).await
     }
 }
+
+/// A layer that records the operations issued to the object store, e.g. to check that a
+/// code path only lists files without reading them.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingLayer {
+    operations: Arc<Mutex<Vec<(&'static str, String)>>>,
+}
+
+impl RecordingLayer {
+    /// Returns the recorded operations and their paths, in the order they are issued.
+    pub fn operations(&self) -> Vec<(&'static str, String)> {
+        self.operations.lock().unwrap().clone()
+    }
+}
+
+impl<A: Accessor> Layer<A> for RecordingLayer {
+    type LayeredAccessor = RecordingAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        RecordingAccessor {
+            inner,
+            operations: self.operations.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RecordingAccessor<A> {
+    inner: A,
+    operations: Arc<Mutex<Vec<(&'static str, String)>>>,
+}
+
+impl<A> RecordingAccessor<A> {
+    fn record(&self, operation: &'static str, path: &str) {
+        self.operations
+            .lock()
+            .unwrap()
+            .push((operation, path.to_string()));
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for RecordingAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.record("read", path);
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.record("write", path);
+        self.inner.write(path, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.record("stat", path);
+        self.inner.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.record("delete", path);
+        self.inner.delete(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.record("list", path);
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.record("scan", path);
+        self.inner.scan(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.record("read", path);
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.record("write", path);
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.record("list", path);
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.record("scan", path);
+        self.inner.blocking_scan(path, args)
+    }
+}
@@ -16,11 +16,13 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use common_query::prelude::Expr;
-use datafusion_expr::{BinaryExpr, Expr as DfExpr, Operator};
+use datafusion_common::ScalarValue;
+use datafusion_expr::{Between, BinaryExpr, Expr as DfExpr, Operator};
 use datatypes::prelude::Value;
 use meta_client::rpc::{Peer, TableName, TableRoute};
 use snafu::{ensure, OptionExt, ResultExt};
 use store_api::storage::{RegionId, RegionNumber};
+use table::predicate::{in_list_literals, MAX_PRUNING_DISJUNCTS};
 use table::requests::InsertRequest;
 
 use crate::columns::RangeColumnsPartitionRule;
@@ -243,14 +245,42 @@ fn find_regions0(partition_rule: PartitionRuleRef, filter: &Expr) -> Result<Hash
                 _ => None,
             };
             if let Some((column, op, scalar)) = column_op_value {
-                let value = Value::try_from(scalar.clone()).with_context(|_| {
-                    error::ConvertScalarValueSnafu {
-                        value: scalar.clone(),
+                return find_regions_by_value(&partition_rule, column, op, scalar);
+            }
+        }
+        DfExpr::InList { .. } => {
+            if let Some((column, values)) = in_list_literals(expr) {
+                // Too many lookups may cost more than scanning all regions.
+                if values.len() <= MAX_PRUNING_DISJUNCTS {
+                    let mut regions = HashSet::new();
+                    for scalar in values {
+                        regions.extend(find_regions_by_value(
+                            &partition_rule,
+                            &column.name,
+                            Operator::Eq,
+                            scalar,
+                        )?);
                     }
-                })?;
-                return Ok(partition_rule
-                    .find_regions_by_exprs(&[PartitionExpr::new(column, op, value)])?
-                    .into_iter()
+                    return Ok(regions);
+                }
+            }
+        }
+        DfExpr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) => {
+            if let (DfExpr::Column(c), DfExpr::Literal(low), DfExpr::Literal(high)) =
+                (expr.as_ref(), low.as_ref(), high.as_ref())
+            {
+                let low_regions =
+                    find_regions_by_value(&partition_rule, &c.name, Operator::GtEq, low)?;
+                let high_regions =
+                    find_regions_by_value(&partition_rule, &c.name, Operator::LtEq, high)?;
+                return Ok(low_regions
+                    .intersection(&high_regions)
+                    .cloned()
                     .collect::<HashSet<RegionNumber>>());
             }
         }
@@ -282,6 +312,22 @@ fn find_regions0(partition_rule: PartitionRuleRef, filter: &Expr) -> Result<Hash
         .collect::<HashSet<RegionNumber>>())
 }
 
+fn find_regions_by_value(
+    partition_rule: &PartitionRuleRef,
+    column: &str,
+    op: Operator,
+    scalar: &ScalarValue,
+) -> Result<HashSet<RegionNumber>> {
+    let value =
+        Value::try_from(scalar.clone()).with_context(|_| error::ConvertScalarValueSnafu {
+            value: scalar.clone(),
+        })?;
+    Ok(partition_rule
+        .find_regions_by_exprs(&[PartitionExpr::new(column, op, value)])?
+        .into_iter()
+        .collect::<HashSet<RegionNumber>>())
+}
+
 #[inline]
 fn is_compare_op(op: &Operator) -> bool {
     matches!(
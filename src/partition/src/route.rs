@@ -15,8 +15,10 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use catalog::helper::TableRouteKey;
 use meta_client::client::MetaClient;
-use meta_client::rpc::{RouteRequest, TableName, TableRoute};
+use meta_client::rpc::router::RenameRequest;
+use meta_client::rpc::{DeleteRangeRequest, RouteRequest, TableName, TableRoute};
 use moka::future::{Cache, CacheBuilder};
 use snafu::{ensure, ResultExt};
 
@@ -77,4 +79,267 @@ impl TableRoutes {
     pub async fn invalidate_table_route(&self, table_name: &TableName) {
         self.cache.invalidate(table_name).await
     }
+
+    /// Puts the route of table `table_id` under its new name in Meta before the table is
+    /// renamed, see [MetaClient::rename_route].
+    ///
+    /// The route of the old name is kept until the rename commits, then removed by
+    /// [TableRoutes::remove_renamed_table_route], so the table is routed by whichever name it's
+    /// found by during the rename. The cached route of the old name is kept too, as it's still
+    /// valid until then. Both are idempotent, so a failed rename is safe to retry.
+    pub async fn rename_table_route(
+        &self,
+        table_id: u64,
+        table_name: &TableName,
+        new_table_name: &str,
+    ) -> Result<()> {
+        let new_table_name = renamed(table_name, new_table_name);
+        let req = RenameRequest::new(
+            table_name.clone(),
+            new_table_name.clone(),
+            route_key(table_id, table_name),
+            route_key(table_id, &new_table_name),
+        );
+        self.meta_client
+            .rename_route(req)
+            .await
+            .context(error::RequestMetaSnafu)?;
+        // Drops the route of a former table of the new name, if any.
+        self.invalidate_table_route(&new_table_name).await;
+        Ok(())
+    }
+
+    /// Invalidates the cached route of the old name of table `table_id` after the rename
+    /// commits, and removes the route of the old name in Meta, so the table is only routed by
+    /// its new name.
+    pub async fn remove_renamed_table_route(
+        &self,
+        table_id: u64,
+        table_name: &TableName,
+    ) -> Result<()> {
+        self.delete_route(route_key(table_id, table_name)).await?;
+        self.invalidate_table_route(table_name).await;
+        Ok(())
+    }
+
+    /// Removes the route put under the new name of table `table_id` by
+    /// [TableRoutes::rename_table_route], if the rename fails to commit.
+    pub async fn rollback_renamed_table_route(
+        &self,
+        table_id: u64,
+        table_name: &TableName,
+        new_table_name: &str,
+    ) -> Result<()> {
+        let new_table_name = renamed(table_name, new_table_name);
+        self.delete_route(route_key(table_id, &new_table_name))
+            .await?;
+        self.invalidate_table_route(&new_table_name).await;
+        Ok(())
+    }
+
+    async fn delete_route(&self, key: String) -> Result<()> {
+        let _ = self
+            .meta_client
+            .delete_range(DeleteRangeRequest::new().with_key(key))
+            .await
+            .context(error::RequestMetaSnafu)?;
+        Ok(())
+    }
+}
+
+fn renamed(table_name: &TableName, new_table_name: &str) -> TableName {
+    TableName::new(
+        &table_name.catalog_name,
+        &table_name.schema_name,
+        new_table_name,
+    )
+}
+
+fn route_key(table_id: u64, table_name: &TableName) -> String {
+    TableRouteKey::with_table_name(table_id, &table_name.clone().into()).key()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use api::v1::meta::{
+        Peer, Region, RegionRoute, Table, TableName as PbTableName, TableRoute as PbTableRoute,
+        TableRouteValue,
+    };
+    use catalog::helper::{TableGlobalKey, TableGlobalValue};
+    use meta_client::client::MetaClientBuilder;
+    use meta_client::rpc::{MoveValueRequest, PutRequest, RangeRequest};
+    use meta_srv::mocks::MockInfo;
+    use table::metadata::RawTableInfo;
+    use table::test_util::MemTable;
+
+    use super::*;
+
+    const TABLE_ID: u64 = 1024;
+
+    async fn new_table_routes() -> TableRoutes {
+        let MockInfo {
+            server_addr,
+            channel_manager,
+        } = meta_srv::mocks::mock_with_memstore().await;
+        let mut meta_client = MetaClientBuilder::new(1000, 0)
+            .enable_router()
+            .enable_store()
+            .channel_manager(channel_manager)
+            .build();
+        meta_client.start(&[&server_addr]).await.unwrap();
+        TableRoutes::new(Arc::new(meta_client))
+    }
+
+    fn table_global_key(table_name: &TableName) -> String {
+        TableGlobalKey {
+            catalog_name: table_name.catalog_name.clone(),
+            schema_name: table_name.schema_name.clone(),
+            table_name: table_name.table_name.clone(),
+        }
+        .to_string()
+    }
+
+    /// Puts the table global value and the route of table [TABLE_ID].
+    async fn put_table(table_routes: &TableRoutes, table_name: &TableName) {
+        let mut table_info = (*MemTable::default_numbers_table().table_info()).clone();
+        table_info.ident.table_id = TABLE_ID as u32;
+        table_info.name = table_name.table_name.clone();
+        let value = TableGlobalValue {
+            node_id: 1,
+            regions_id_map: HashMap::from([(1, vec![0])]),
+            table_info: RawTableInfo::from(table_info),
+        };
+        let req = PutRequest::new()
+            .with_key(table_global_key(table_name))
+            .with_value(value.as_bytes().unwrap());
+        let _ = table_routes.meta_client.put(req).await.unwrap();
+
+        let route = TableRouteValue {
+            peers: vec![Peer {
+                id: 1,
+                addr: "127.0.0.1:3001".to_string(),
+            }],
+            table_route: Some(PbTableRoute {
+                table: Some(Table {
+                    id: TABLE_ID,
+                    table_name: Some(PbTableName::from(table_name.clone())),
+                    ..Default::default()
+                }),
+                region_routes: vec![RegionRoute {
+                    region: Some(Region {
+                        id: 0,
+                        ..Default::default()
+                    }),
+                    leader_peer_index: 0,
+                    follower_peer_indexes: vec![],
+                }],
+            }),
+        };
+        let req = PutRequest::new()
+            .with_key(route_key(TABLE_ID, table_name))
+            .with_value(route);
+        let _ = table_routes.meta_client.put(req).await.unwrap();
+    }
+
+    async fn route_exists(table_routes: &TableRoutes, table_id: u64, name: &TableName) -> bool {
+        let req = RangeRequest::new().with_key(route_key(table_id, name));
+        let mut resp = table_routes.meta_client.range(req).await.unwrap();
+        !resp.take_kvs().is_empty()
+    }
+
+    #[tokio::test]
+    async fn test_rename_table_route() {
+        let table_routes = new_table_routes().await;
+        let old_name = TableName::new("greptime", "public", "old");
+        let new_name = TableName::new("greptime", "public", "renamed");
+        put_table(&table_routes, &old_name).await;
+        let cached = table_routes.get_route(&old_name).await.unwrap();
+
+        // Until the rename commits, the table is routed by both names, and by the cached route
+        // of its old name.
+        table_routes
+            .rename_table_route(TABLE_ID, &old_name, "renamed")
+            .await
+            .unwrap();
+        assert!(route_exists(&table_routes, TABLE_ID, &old_name).await);
+        assert!(route_exists(&table_routes, TABLE_ID, &new_name).await);
+        let route = table_routes.get_route(&old_name).await.unwrap();
+        assert!(Arc::ptr_eq(&cached, &route));
+
+        // Commits the rename.
+        let req = MoveValueRequest::new(table_global_key(&old_name), table_global_key(&new_name));
+        let _ = table_routes.meta_client.move_value(req).await.unwrap();
+
+        // Then the old name isn't routed anymore, and the new name is routed to the same regions.
+        table_routes
+            .remove_renamed_table_route(TABLE_ID, &old_name)
+            .await
+            .unwrap();
+        assert!(!route_exists(&table_routes, TABLE_ID, &old_name).await);
+        assert!(table_routes.get_route(&old_name).await.is_err());
+        // Retrying the rename after it commits is a no-op.
+        table_routes
+            .rename_table_route(TABLE_ID, &old_name, "renamed")
+            .await
+            .unwrap();
+        let route = table_routes.get_route(&new_name).await.unwrap();
+        assert_eq!(TABLE_ID, route.table.id);
+        assert_eq!(new_name, route.table.table_name);
+        assert_eq!(
+            vec![0],
+            route
+                .region_routes
+                .iter()
+                .map(|route| route.region.id)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollback_renamed_table_route() {
+        let table_routes = new_table_routes().await;
+        let old_name = TableName::new("greptime", "public", "old");
+        let new_name = TableName::new("greptime", "public", "renamed");
+        put_table(&table_routes, &old_name).await;
+
+        table_routes
+            .rename_table_route(TABLE_ID, &old_name, "renamed")
+            .await
+            .unwrap();
+        // Retrying the rename is fine.
+        table_routes
+            .rename_table_route(TABLE_ID, &old_name, "renamed")
+            .await
+            .unwrap();
+
+        table_routes
+            .rollback_renamed_table_route(TABLE_ID, &old_name, "renamed")
+            .await
+            .unwrap();
+        assert!(!route_exists(&table_routes, TABLE_ID, &new_name).await);
+        let route = table_routes.get_route(&old_name).await.unwrap();
+        assert_eq!(TABLE_ID, route.table.id);
+    }
+
+    #[tokio::test]
+    async fn test_rename_missing_table_route() {
+        let table_routes = new_table_routes().await;
+        let old_name = TableName::new("greptime", "public", "old");
+        let err = table_routes
+            .rename_table_route(TABLE_ID, &old_name, "renamed")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                error::Error::RequestMeta {
+                    source: meta_client::error::Error::TableRouteNotFound { .. },
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
 }
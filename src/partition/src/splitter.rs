@@ -232,6 +232,7 @@ fn split_insert_request(
                     table_name: table_name.to_string(),
                     columns_values,
                     region_number,
+                    write_mode: insert.write_mode,
                 },
             )
         })
@@ -465,6 +466,7 @@ mod tests {
             table_name: "demo".to_string(),
             columns_values,
             region_number: 0,
+            write_mode: Default::default(),
         }
     }
 
@@ -493,6 +495,7 @@ mod tests {
             table_name: "demo".to_string(),
             columns_values,
             region_number: 0,
+            write_mode: Default::default(),
         }
     }
 
@@ -31,7 +31,10 @@
 use std::fmt::Display;
 use std::sync::Arc;
 
-use datafusion::arrow::array::{Float64Array, TimestampMillisecondArray};
+use common_function::scalars::aggregate::counter::{counter_increase, gauge_delta, CounterNative};
+use datafusion::arrow::array::{
+    ArrayRef, Float64Array, Int64Array, TimestampMillisecondArray, UInt64Array,
+};
 use datafusion::arrow::datatypes::TimeUnit;
 use datafusion::common::DataFusionError;
 use datafusion::logical_expr::{ScalarUDF, Signature, TypeSignature, Volatility};
@@ -61,17 +64,32 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
         Self { range_length }
     }
 
-    fn input_type() -> Vec<DataType> {
+    fn input_type(value_type: DataType) -> Vec<DataType> {
         vec![
             // timestamp range vector
             RangeArray::convert_data_type(DataType::Timestamp(TimeUnit::Millisecond, None)),
             // value range vector
-            RangeArray::convert_data_type(DataType::Float64),
+            RangeArray::convert_data_type(value_type),
             // timestamp vector
             DataType::Timestamp(TimeUnit::Millisecond, None),
         ]
     }
 
+    /// Values can be either float or integer. Integer counters are computed without
+    /// converting samples to float first, to not lose precision or wrap around on
+    /// values near the max of `UInt64`.
+    fn signature() -> Signature {
+        Signature::new(
+            TypeSignature::OneOf(
+                [DataType::Float64, DataType::Int64, DataType::UInt64]
+                    .into_iter()
+                    .map(|value_type| TypeSignature::Exact(Self::input_type(value_type)))
+                    .collect(),
+            ),
+            Volatility::Immutable,
+        )
+    }
+
     fn return_type() -> DataType {
         DataType::Float64
     }
@@ -101,34 +119,16 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
                 .values();
             let end_ts = ts.value(index);
             let values = value_range.get(index).unwrap();
-            let values = values
-                .as_any()
-                .downcast_ref::<Float64Array>()
-                .unwrap()
-                .values();
-
-            if values.len() < 2 {
+            let Some((first_value, result_value)) = Self::result_value(&values)? else {
                 result_array.push(None);
                 continue;
-            }
-
-            // refer to functions.go L83-L110
-            let mut result_value = values.last().unwrap() - values.first().unwrap();
-            if IS_COUNTER {
-                for window in values.windows(2) {
-                    let prev = window[0];
-                    let curr = window[1];
-                    if curr < prev {
-                        result_value += prev
-                    }
-                }
-            }
+            };
 
             let mut factor = Self::extrapolate_factor(
                 timestamps,
                 end_ts,
                 self.range_length,
-                *values.first().unwrap(),
+                first_value,
                 result_value,
             );
 
@@ -144,6 +144,48 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
         Ok(result)
     }
 
+    /// Returns the first value and the increase (for counters) or the delta of `values`,
+    /// or `None` if there are less than two values.
+    ///
+    /// Refer to functions.go L83-L110
+    fn result_value(values: &ArrayRef) -> Result<Option<(f64, f64)>, DataFusionError> {
+        match values.data_type() {
+            DataType::Float64 => Ok(Self::calc_result_value(
+                values
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .unwrap()
+                    .values(),
+            )),
+            DataType::Int64 => Ok(Self::calc_result_value(
+                values
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .values(),
+            )),
+            DataType::UInt64 => Ok(Self::calc_result_value(
+                values
+                    .as_any()
+                    .downcast_ref::<UInt64Array>()
+                    .unwrap()
+                    .values(),
+            )),
+            other => Err(DataFusionError::Execution(format!(
+                "unsupported value type {other:?} of range vector"
+            ))),
+        }
+    }
+
+    fn calc_result_value<T: CounterNative>(values: &[T]) -> Option<(f64, f64)> {
+        let result_value = if IS_COUNTER {
+            counter_increase(values)?
+        } else {
+            gauge_delta(values)?
+        };
+        Some((values[0].to_f64(), result_value))
+    }
+
     fn extrapolate_factor(
         timestamps: &[Millisecond],
         range_end: Millisecond,
@@ -205,10 +247,7 @@ impl ExtrapolatedRate<false, false> {
     pub fn scalar_udf(range_length: i64) -> ScalarUDF {
         ScalarUDF {
             name: Self::name().to_string(),
-            signature: Signature::new(
-                TypeSignature::Exact(Self::input_type()),
-                Volatility::Immutable,
-            ),
+            signature: Self::signature(),
             return_type: Arc::new(|_| Ok(Arc::new(Self::return_type()))),
             fun: Arc::new(move |input| Self::new(range_length).calc(input)),
         }
@@ -224,10 +263,7 @@ impl ExtrapolatedRate<true, true> {
     pub fn scalar_udf(range_length: i64) -> ScalarUDF {
         ScalarUDF {
             name: Self::name().to_string(),
-            signature: Signature::new(
-                TypeSignature::Exact(Self::input_type()),
-                Volatility::Immutable,
-            ),
+            signature: Self::signature(),
             return_type: Arc::new(|_| Ok(Arc::new(Self::return_type()))),
             fun: Arc::new(move |input| Self::new(range_length).calc(input)),
         }
@@ -243,10 +279,7 @@ impl ExtrapolatedRate<true, false> {
     pub fn scalar_udf(range_length: i64) -> ScalarUDF {
         ScalarUDF {
             name: Self::name().to_string(),
-            signature: Signature::new(
-                TypeSignature::Exact(Self::input_type()),
-                Volatility::Immutable,
-            ),
+            signature: Self::signature(),
             return_type: Arc::new(|_| Ok(Arc::new(Self::return_type()))),
             fun: Arc::new(move |input| Self::new(range_length).calc(input)),
         }
@@ -562,4 +595,25 @@ mod test {
             vec![1.5, 1.5, 1.5, 1.5, 1.5, 1.5, 1.5, 1.5],
         );
     }
+
+    #[test]
+    fn increase_uint64_counter_near_max() {
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [1, 2, 3, 4, 5, 6].into_iter().map(Some),
+        ));
+        // two resets, the increase is 2 + 1 + 2 + 0 + 2
+        let values_array = Arc::new(UInt64Array::from_iter_values([
+            u64::MAX - 2,
+            u64::MAX,
+            1,
+            3,
+            0,
+            2,
+        ]));
+        let ranges = [(0, 6)];
+        let ts_range = RangeArray::from_ranges(ts_array, ranges).unwrap();
+        let value_range = RangeArray::from_ranges(values_array, ranges).unwrap();
+        let timestamps = Arc::new(TimestampMillisecondArray::from_iter([Some(6)])) as _;
+        extrapolated_rate_runner::<true, false>(ts_range, value_range, timestamps, vec![7.0]);
+    }
 }
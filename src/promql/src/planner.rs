@@ -1264,13 +1264,13 @@ mod test {
         let table = Arc::new(EmptyTable::from_table_info(&table_info));
         let catalog_list = Arc::new(MemoryCatalogManager::default());
         catalog_list
-            .register_table(RegisterTableRequest {
-                catalog: DEFAULT_CATALOG_NAME.to_string(),
-                schema: DEFAULT_SCHEMA_NAME.to_string(),
-                table_name,
-                table_id: 1024,
+            .register_table(RegisterTableRequest::new(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                &table_name,
+                1024,
                 table,
-            })
+            ))
             .await
             .unwrap();
         DfTableSourceProvider::new(catalog_list, false, &QueryContext::new())
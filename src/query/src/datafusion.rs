@@ -14,13 +14,17 @@
 
 //! Planner, QueryEngine implementations based on DataFusion.
 
+mod delete_range;
 mod error;
 mod planner;
+mod table_option;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use ::metrics::increment_counter;
 use async_trait::async_trait;
+use common_base::commit_token::CommitToken;
 use common_error::prelude::BoxedError;
 use common_function::scalars::aggregate::AggregateFunctionMetaRef;
 use common_function::scalars::udf::create_udf;
@@ -29,25 +33,33 @@ use common_query::physical_plan::{DfPhysicalPlanAdapter, PhysicalPlan, PhysicalP
 use common_query::prelude::ScalarUdf;
 use common_query::Output;
 use common_recordbatch::adapter::RecordBatchStreamAdapter;
-use common_recordbatch::{EmptyRecordBatchStream, SendableRecordBatchStream};
-use common_telemetry::timer;
+use common_recordbatch::{EmptyRecordBatchStream, RecordBatch, SendableRecordBatchStream};
+use common_telemetry::{timer, warn};
+use datafusion::datasource::DefaultTableSource;
 use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::ExecutionPlan;
-use datafusion_common::ResolvedTableReference;
-use datafusion_expr::{DmlStatement, LogicalPlan as DfLogicalPlan, WriteOp};
+use datafusion_common::tree_node::{Transformed, TreeNode};
+use datafusion_common::{Column, ResolvedTableReference};
+use datafusion_expr::utils::expr_to_columns;
+use datafusion_expr::{
+    DmlStatement, Expr, LogicalPlan as DfLogicalPlan, LogicalPlanBuilder, TableScan, WriteOp,
+};
 use datatypes::prelude::VectorRef;
 use datatypes::schema::Schema;
 use futures_util::StreamExt;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
+use table::engine::split_region_id;
 use table::requests::{DeleteRequest, InsertRequest};
+use table::table::adapter::DfTableProviderAdapter;
+use table::table::as_of::AsOfTable;
 use table::TableRef;
 
 pub use crate::datafusion::planner::DfContextProviderAdapter;
 use crate::error::{
     CatalogNotFoundSnafu, CatalogSnafu, CreateRecordBatchSnafu, DataFusionSnafu,
-    MissingTimestampColumnSnafu, QueryExecutionSnafu, Result, SchemaNotFoundSnafu,
-    TableNotFoundSnafu, UnsupportedExprSnafu,
+    DeleteRowLimitExceededSnafu, ExpiredTimeRangeSnafu, MissingTimestampColumnSnafu,
+    QueryExecutionSnafu, Result, SchemaNotFoundSnafu, TableNotFoundSnafu, UnsupportedExprSnafu,
 };
 use crate::executor::QueryExecutor;
 use crate::logical_optimizer::LogicalOptimizer;
@@ -56,6 +68,8 @@ use crate::physical_planner::PhysicalPlanner;
 use crate::plan::LogicalPlan;
 use crate::planner::{DfLogicalPlanner, LogicalPlanner};
 use crate::query_engine::{QueryEngineContext, QueryEngineState};
+use crate::rebatch::{RebatchExec, RebatchOptions};
+use crate::retention::find_expired_ranges;
 use crate::{metrics, QueryEngine};
 
 pub struct DatafusionQueryEngine {
@@ -73,10 +87,35 @@ impl DatafusionQueryEngine {
         // `create_physical_plan` will optimize logical plan internally
         let physical_plan = self.create_physical_plan(&mut ctx, &plan).await?;
         let physical_plan = self.optimize_physical_plan(&mut ctx, physical_plan)?;
+        let physical_plan = match self.state.result_rebatch() {
+            Some(options) => rebatch_physical_plan(physical_plan, options)?,
+            None => physical_plan,
+        };
 
         Ok(Output::Stream(self.execute_stream(&ctx, &physical_plan)?))
     }
 
+    /// Warns about the queries of the time ranges before the retention boundaries of the
+    /// tables, or rejects them if [QueryOptions::reject_expired_ranges] is set.
+    ///
+    /// [QueryOptions::reject_expired_ranges]: crate::query_engine::options::QueryOptions::reject_expired_ranges
+    fn check_retention(&self, plan: &LogicalPlan, query_ctx: &QueryContextRef) -> Result<()> {
+        // The plan is not analyzed yet, so the filters of the ttl columns don't clamp the ranges.
+        let LogicalPlan::DfPlan(df_plan) = plan;
+        let now_millis = self.state.clock().now_millis();
+        for expired in find_expired_ranges(df_plan, now_millis) {
+            ensure!(
+                !self.state.reject_expired_ranges(),
+                ExpiredTimeRangeSnafu {
+                    table_name: expired.table_name,
+                    boundary: expired.boundary.to_iso8601_string(),
+                }
+            );
+            query_ctx.add_warning(expired.warning());
+        }
+        Ok(())
+    }
+
     async fn exec_dml_statement(
         &self,
         dml: DmlStatement,
@@ -92,17 +131,30 @@ impl DatafusionQueryEngine {
         let default_catalog = query_ctx.current_catalog();
         let default_schema = query_ctx.current_schema();
         let table_name = dml.table_name.resolve(&default_catalog, &default_schema);
-        let table = self.find_table(&table_name).await?;
-
-        let output = self
-            .exec_query_plan(LogicalPlan::DfPlan((*dml.input).clone()))
-            .await?;
-        let mut stream = match output {
-            Output::RecordBatches(batches) => batches.as_stream(),
-            Output::Stream(stream) => stream,
-            _ => unreachable!(),
+        // A delete writes to the table it scans, the one it's planned against, so the whole
+        // statement sees the same table even if it's dropped or recreated in the meantime.
+        let planned_table = match dml.op {
+            WriteOp::Delete => find_scanned_table(&dml.input, &table_name),
+            _ => None,
+        };
+        let table = match planned_table {
+            Some(table) => table,
+            None => self.find_table(&table_name).await?,
         };
+        // Writes through the view of the session record the writes in its commit token, and
+        // read the writes in it.
+        let token = query_ctx.commit_token();
+        let table = table.with_commit_token(token.clone()).unwrap_or(table);
+        let LogicalPlan::DfPlan(input) =
+            scan_tables_with_commit_token(LogicalPlan::DfPlan((*dml.input).clone()), &token)?;
 
+        if dml.op == WriteOp::Delete {
+            let output = self.exec_delete(&table_name, &table, &input).await?;
+            record_commit_token(&table, &query_ctx)?;
+            return Ok(output);
+        }
+
+        let mut stream = self.exec_dml_input(input).await?;
         let mut affected_rows = 0;
         while let Some(batch) = stream.next().await {
             let batch = batch.context(CreateRecordBatchSnafu)?;
@@ -111,42 +163,169 @@ impl DatafusionQueryEngine {
                 .map_err(BoxedError::new)
                 .context(QueryExecutionSnafu)?;
 
-            let rows = match dml.op {
-                WriteOp::Insert => Self::insert(&table_name, &table, column_vectors).await?,
-                WriteOp::Delete => Self::delete(&table_name, &table, column_vectors).await?,
-                _ => unreachable!("guarded by the 'ensure!' at the beginning"),
-            };
-            affected_rows += rows;
+            affected_rows += Self::insert(&table_name, &table, column_vectors).await?;
         }
+        record_commit_token(&table, &query_ctx)?;
         Ok(Output::AffectedRows(affected_rows))
     }
 
-    async fn delete<'a>(
+    /// Waits until the tables scanned by `plan` have applied the writes in the commit token
+    /// of the query, so the query reads these writes.
+    async fn wait_for_commit_token(
+        &self,
+        plan: &LogicalPlan,
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let token = query_ctx.commit_token();
+        if token.is_empty() {
+            return Ok(());
+        }
+
+        let mut tables = Vec::new();
+        match plan {
+            LogicalPlan::DfPlan(plan) => collect_scanned_tables(plan, &mut tables),
+        }
+        let timeout = self.state.commit_token_wait_timeout();
+        for table in tables {
+            let table_id = table.table_info().ident.table_id;
+            for (region_id, sequence) in token.iter() {
+                let (region_table_id, region_number) = split_region_id(region_id);
+                if region_table_id != table_id {
+                    continue;
+                }
+                table
+                    .wait_for_sequence(region_number, sequence, timeout)
+                    .await
+                    .map_err(BoxedError::new)
+                    .context(QueryExecutionSnafu)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn exec_dml_input(&self, input: DfLogicalPlan) -> Result<SendableRecordBatchStream> {
+        let output = self.exec_query_plan(LogicalPlan::DfPlan(input)).await?;
+        Ok(match output {
+            Output::RecordBatches(batches) => batches.as_stream(),
+            Output::Stream(stream) => stream,
+            _ => unreachable!(),
+        })
+    }
+
+    /// Executes a `DELETE` by scanning only the primary key and time index columns of the
+    /// rows matched by the `WHERE` clause, then deleting them by key.
+    ///
+    /// When the predicate only references key columns, the filters are pushed down to the
+    /// table scan (and pruned by partition in distributed mode) and the matched keys are
+    /// deleted batch by batch. Otherwise the matched keys are buffered first, and the
+    /// delete is rejected if they exceed the configured row limit, so nothing is deleted
+    /// partially. The affected rows are the number of keys deleted, so they are exact in both
+    /// cases.
+    ///
+    /// If the table supports range deletes and the predicate is a conjunction of equalities on
+    /// the primary key columns and a time range (see [delete_range]), the rows are deleted by
+    /// a range tombstone without reading them, so the cost doesn't grow with the number of rows
+    /// deleted. The number of rows deleted is unknown then, and the affected rows are 0.
+    /// Distributed tables always delete by key, with the partitions pruned by the predicate.
+    async fn exec_delete<'a>(
+        &self,
         table_name: &ResolvedTableReference<'a>,
         table: &TableRef,
-        column_vectors: HashMap<String, VectorRef>,
-    ) -> Result<usize> {
+        input: &DfLogicalPlan,
+    ) -> Result<Output> {
         let table_schema = table.schema();
         let ts_column = table_schema
             .timestamp_column()
-            .map(|x| &x.name)
+            .map(|x| x.name.clone())
             .with_context(|| MissingTimestampColumnSnafu {
                 table_name: table_name.to_string(),
             })?;
-
         let table_info = table.table_info();
-        let rowkey_columns = table_info
+        let mut key_columns = table_info
             .meta
             .row_key_column_names()
-            .collect::<Vec<&String>>();
-        let column_vectors = column_vectors
-            .into_iter()
-            .filter(|x| &x.0 == ts_column || rowkey_columns.contains(&&x.0))
-            .collect::<HashMap<_, _>>();
-
-        let request = DeleteRequest {
-            key_column_values: column_vectors,
-        };
+            .cloned()
+            .collect::<Vec<_>>();
+        key_columns.push(ts_column);
+
+        let mut predicate_columns = HashSet::new();
+        collect_predicate_columns(input, &mut predicate_columns)?;
+        let key_only = predicate_columns
+            .iter()
+            .all(|column| key_columns.contains(&column.name));
+
+        if key_only && table.supports_delete_range() {
+            let row_key_columns = &key_columns[..key_columns.len() - 1];
+            if let Some(request) =
+                delete_range::delete_range_request(input, &table_schema, row_key_columns)
+            {
+                table
+                    .delete_range(request)
+                    .await
+                    .map_err(BoxedError::new)
+                    .context(QueryExecutionSnafu)?;
+                return Ok(Output::AffectedRows(0));
+            }
+        }
+
+        let input = LogicalPlanBuilder::from(input.clone())
+            .project(
+                key_columns
+                    .iter()
+                    .map(|name| Expr::Column(Column::from_name(name))),
+            )
+            .and_then(|builder| builder.build())
+            .context(DataFusionSnafu)?;
+        let mut stream = self.exec_dml_input(input).await?;
+
+        let mut affected_rows = 0;
+        if key_only {
+            while let Some(batch) = stream.next().await {
+                let batch = batch.context(CreateRecordBatchSnafu)?;
+                affected_rows += Self::delete(table_name, table, batch).await?;
+            }
+            return Ok(Output::AffectedRows(affected_rows));
+        }
+
+        let limit = self.state.delete_scan_row_limit();
+        warn!(
+            "Delete on table {} has predicates on non-key columns, fallback to scan at most {} rows",
+            table_name, limit
+        );
+        let mut batches = Vec::new();
+        let mut scanned_rows = 0;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.context(CreateRecordBatchSnafu)?;
+            scanned_rows += batch.num_rows();
+            ensure!(
+                scanned_rows <= limit,
+                DeleteRowLimitExceededSnafu {
+                    table_name: table_name.to_string(),
+                    limit,
+                }
+            );
+            batches.push(batch);
+        }
+        for batch in batches {
+            affected_rows += Self::delete(table_name, table, batch).await?;
+        }
+        Ok(Output::AffectedRows(affected_rows))
+    }
+
+    async fn delete<'a>(
+        table_name: &ResolvedTableReference<'a>,
+        table: &TableRef,
+        batch: RecordBatch,
+    ) -> Result<usize> {
+        if batch.num_rows() == 0 {
+            return Ok(0);
+        }
+
+        let key_column_values = batch
+            .column_vectors(&table_name.to_string(), table.schema())
+            .map_err(BoxedError::new)
+            .context(QueryExecutionSnafu)?;
+        let request = DeleteRequest { key_column_values };
 
         table
             .delete(request)
@@ -166,6 +345,7 @@ impl DatafusionQueryEngine {
             table_name: table_name.table.to_string(),
             columns_values: column_vectors,
             region_number: 0,
+            write_mode: Default::default(),
         };
 
         table
@@ -205,6 +385,129 @@ impl DatafusionQueryEngine {
     }
 }
 
+/// Merges the commit token of the written table into the query context, so later queries
+/// carrying the token read the writes.
+fn record_commit_token(table: &TableRef, query_ctx: &QueryContextRef) -> Result<()> {
+    let token = table
+        .commit_token()
+        .map_err(BoxedError::new)
+        .context(QueryExecutionSnafu)?;
+    query_ctx.merge_commit_token(&token);
+    Ok(())
+}
+
+/// Collects the tables scanned by `plan`.
+fn collect_scanned_tables(plan: &DfLogicalPlan, tables: &mut Vec<TableRef>) {
+    if let DfLogicalPlan::TableScan(scan) = plan {
+        if let Some(table) = scanned_table(scan) {
+            tables.push(table);
+        }
+    }
+    for input in plan.inputs() {
+        collect_scanned_tables(input, tables);
+    }
+}
+
+/// Rewrites the tables scanned by `plan` to read their snapshots as of the wall-clock time
+/// `as_of` in millis. The tables without versions, like the system and information_schema
+/// tables, are read as is, so are the tables already read as of a time.
+pub(crate) fn scan_tables_as_of(plan: LogicalPlan, as_of: i64) -> Result<LogicalPlan> {
+    rewrite_scanned_tables(plan, |table| {
+        table
+            .supports_time_travel()
+            .then(|| Arc::new(AsOfTable::new(table, as_of)) as _)
+    })
+}
+
+/// Rewrites the tables scanned by `plan` to their views reading the writes in the commit
+/// token of the session, see [Table::with_commit_token](table::Table::with_commit_token).
+fn scan_tables_with_commit_token(plan: LogicalPlan, token: &CommitToken) -> Result<LogicalPlan> {
+    if token.is_empty() {
+        return Ok(plan);
+    }
+    rewrite_scanned_tables(plan, |table| table.with_commit_token(token.clone()))
+}
+
+/// Returns the table of the `scan`, `None` if it doesn't scan a table.
+fn scanned_table(scan: &TableScan) -> Option<TableRef> {
+    scan.source
+        .as_any()
+        .downcast_ref::<DefaultTableSource>()
+        .and_then(|source| {
+            source
+                .table_provider
+                .as_any()
+                .downcast_ref::<DfTableProviderAdapter>()
+        })
+        .map(|adapter| adapter.table())
+}
+
+/// Returns the table named `table_name` scanned by `plan`, if any.
+fn find_scanned_table(
+    plan: &DfLogicalPlan,
+    table_name: &ResolvedTableReference<'_>,
+) -> Option<TableRef> {
+    if let DfLogicalPlan::TableScan(scan) = plan {
+        let table = scanned_table(scan).filter(|table| {
+            let table_info = table.table_info();
+            table_info.catalog_name == table_name.catalog
+                && table_info.schema_name == table_name.schema
+                && table_info.name == table_name.table
+        });
+        if table.is_some() {
+            return table;
+        }
+    }
+    plan.inputs()
+        .into_iter()
+        .find_map(|input| find_scanned_table(input, table_name))
+}
+
+/// Replaces the tables scanned by `plan` with the ones returned by `rewrite`, the tables it
+/// returns `None` for are kept.
+fn rewrite_scanned_tables(
+    plan: LogicalPlan,
+    rewrite: impl Fn(TableRef) -> Option<TableRef>,
+) -> Result<LogicalPlan> {
+    let LogicalPlan::DfPlan(plan) = plan;
+    let plan = plan
+        .transform_up(&|plan| {
+            let DfLogicalPlan::TableScan(mut scan) = plan else {
+                return Ok(Transformed::No(plan));
+            };
+            let table = scanned_table(&scan).and_then(&rewrite);
+            match table {
+                Some(table) => {
+                    let provider = Arc::new(DfTableProviderAdapter::new(table));
+                    scan.source = Arc::new(DefaultTableSource::new(provider));
+                    Ok(Transformed::Yes(DfLogicalPlan::TableScan(scan)))
+                }
+                None => Ok(Transformed::No(DfLogicalPlan::TableScan(scan))),
+            }
+        })
+        .context(DataFusionSnafu)?;
+    Ok(LogicalPlan::DfPlan(plan))
+}
+
+/// Collects the columns referenced by the filters in `plan`.
+fn collect_predicate_columns(plan: &DfLogicalPlan, columns: &mut HashSet<Column>) -> Result<()> {
+    match plan {
+        DfLogicalPlan::Filter(filter) => {
+            expr_to_columns(&filter.predicate, columns).context(DataFusionSnafu)?
+        }
+        DfLogicalPlan::TableScan(scan) => {
+            for filter in &scan.filters {
+                expr_to_columns(filter, columns).context(DataFusionSnafu)?;
+            }
+        }
+        _ => {}
+    }
+    for input in plan.inputs() {
+        collect_predicate_columns(input, columns)?;
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl QueryEngine for DatafusionQueryEngine {
     fn planner(&self) -> Arc<dyn LogicalPlanner> {
@@ -223,11 +526,25 @@ impl QueryEngine for DatafusionQueryEngine {
     }
 
     async fn execute(&self, plan: LogicalPlan, query_ctx: QueryContextRef) -> Result<Output> {
+        increment_counter!(
+            metrics::METRIC_EXEC_QUERY_COUNT,
+            metrics::LABEL_ORIGIN => query_ctx.origin().label()
+        );
         match plan {
             LogicalPlan::DfPlan(DfLogicalPlan::Dml(dml)) => {
                 self.exec_dml_statement(dml, query_ctx).await
             }
-            _ => self.exec_query_plan(plan).await,
+            _ => {
+                query_ctx.set_result_tables(result_tables(&plan));
+                self.wait_for_commit_token(&plan, &query_ctx).await?;
+                self.check_retention(&plan, &query_ctx)?;
+                let plan = scan_tables_with_commit_token(plan, &query_ctx.commit_token())?;
+                let plan = match query_ctx.as_of() {
+                    Some(as_of) => scan_tables_as_of(plan, as_of)?,
+                    None => plan,
+                };
+                self.exec_query_plan(plan).await
+            }
         }
     }
 
@@ -242,8 +559,8 @@ impl QueryEngine for DatafusionQueryEngine {
     /// `SELECT "my_UDAF"(x)` will look for an aggregate named `"my_UDAF"`
     ///
     /// So it's better to make UDAF name lowercase when creating one.
-    fn register_aggregate_function(&self, func: AggregateFunctionMetaRef) {
-        self.state.register_aggregate_function(func);
+    fn register_aggregate_function(&self, func: AggregateFunctionMetaRef) -> Result<()> {
+        self.state.register_aggregate_function(func)
     }
 
     fn register_function(&self, func: FunctionRef) {
@@ -335,6 +652,24 @@ impl PhysicalOptimizer for DatafusionQueryEngine {
     }
 }
 
+/// Puts a [RebatchExec] on the top of `plan` to coalesce the batches of its results.
+fn rebatch_physical_plan(
+    plan: Arc<dyn PhysicalPlan>,
+    options: RebatchOptions,
+) -> Result<Arc<dyn PhysicalPlan>> {
+    let df_plan = plan
+        .as_any()
+        .downcast_ref::<PhysicalPlanAdapter>()
+        .context(error::PhysicalPlanDowncastSnafu)
+        .map_err(BoxedError::new)
+        .context(QueryExecutionSnafu)?
+        .df_plan();
+    Ok(Arc::new(PhysicalPlanAdapter::new(
+        plan.schema(),
+        Arc::new(RebatchExec::new(df_plan, options)),
+    )))
+}
+
 impl QueryExecutor for DatafusionQueryEngine {
     fn execute_stream(
         &self,
@@ -372,25 +707,53 @@ impl QueryExecutor for DatafusionQueryEngine {
     }
 }
 
+/// Returns the columns of the result of `plan` with the tables they are read from, which are
+/// the qualifiers of the fields of the plan. The computed columns have no qualifier.
+fn result_tables(plan: &LogicalPlan) -> Vec<(String, Option<String>)> {
+    let LogicalPlan::DfPlan(plan) = plan;
+    plan.schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            let table = field
+                .qualifier()
+                .map(|qualifier| qualifier.table().to_string());
+            (field.name().clone(), table)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
     use catalog::local::{MemoryCatalogProvider, MemorySchemaProvider};
     use catalog::{CatalogProvider, SchemaProvider};
+    use common_base::Plugins;
     use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use common_error::prelude::{ErrorExt, StatusCode};
     use common_query::Output;
-    use common_recordbatch::util;
+    use common_recordbatch::{util, RecordBatch};
     use datatypes::prelude::ConcreteDataType;
-    use datatypes::schema::ColumnSchema;
-    use datatypes::vectors::{UInt64Vector, VectorRef};
-    use session::context::QueryContext;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{
+        Float64Vector, StringVector, TimestampMillisecondVector, UInt64Vector, VectorRef,
+    };
+    use session::context::{QueryContext, QueryContextRef, QueryOrigin};
     use table::table::numbers::NumbersTable;
+    use table::test_util::MemTable;
 
+    use crate::error::Error;
     use crate::parser::QueryLanguageParser;
+    use crate::query_engine::options::QueryOptions;
     use crate::query_engine::{QueryEngineFactory, QueryEngineRef};
+    use crate::rebatch::RebatchOptions;
 
     async fn create_test_engine() -> QueryEngineRef {
+        create_test_engine_with_plugins(Plugins::new()).await
+    }
+
+    async fn create_test_engine_with_plugins(plugins: Plugins) -> QueryEngineRef {
         let catalog_list = catalog::local::new_memory_catalog_list().unwrap();
 
         let default_schema = Arc::new(MemorySchemaProvider::new());
@@ -407,7 +770,7 @@ mod tests {
             .register_catalog_sync(DEFAULT_CATALOG_NAME.to_string(), default_catalog)
             .unwrap();
 
-        QueryEngineFactory::new(catalog_list).query_engine()
+        QueryEngineFactory::new_with_plugins(catalog_list, Arc::new(plugins)).query_engine()
     }
 
     #[tokio::test]
@@ -470,6 +833,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_result_tables() {
+        let engine = create_test_engine().await;
+        let sql = "select number, number + 1 as next from numbers as n";
+        let stmt = QueryLanguageParser::parse_sql(sql).unwrap();
+        let query_ctx = QueryContext::arc();
+        let plan = engine
+            .planner()
+            .plan(stmt, query_ctx.clone())
+            .await
+            .unwrap();
+        let _ = engine.execute(plan, query_ctx.clone()).await.unwrap();
+
+        let expected = vec![
+            ("number".to_string(), Some("n".to_string())),
+            ("next".to_string(), None),
+        ];
+        assert_eq!(expected, query_ctx.result_tables());
+    }
+
     #[tokio::test]
     async fn test_describe() {
         let engine = create_test_engine().await;
@@ -494,4 +877,150 @@ mod tests {
             )
         );
     }
+
+    #[tokio::test]
+    async fn test_delete_exceeds_row_limit() {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["host1", "host2", "host3"])),
+            Arc::new(TimestampMillisecondVector::from_vec(vec![1000, 2000, 3000])),
+            Arc::new(Float64Vector::from_vec(vec![1.0, 2.0, 3.0])),
+        ];
+        let recordbatch = RecordBatch::new(schema, columns).unwrap();
+
+        let catalog_list = catalog::local::new_memory_catalog_list().unwrap();
+        let default_schema = Arc::new(MemorySchemaProvider::new());
+        default_schema
+            .register_table(
+                "metrics".to_string(),
+                Arc::new(MemTable::new("metrics", recordbatch)),
+            )
+            .await
+            .unwrap();
+        let default_catalog = Arc::new(MemoryCatalogProvider::new());
+        default_catalog
+            .register_schema(DEFAULT_SCHEMA_NAME.to_string(), default_schema)
+            .await
+            .unwrap();
+        catalog_list
+            .register_catalog_sync(DEFAULT_CATALOG_NAME.to_string(), default_catalog)
+            .unwrap();
+
+        let mut plugins = Plugins::new();
+        plugins.insert(QueryOptions {
+            delete_scan_row_limit: Some(2),
+            ..Default::default()
+        });
+        let engine =
+            QueryEngineFactory::new_with_plugins(catalog_list, Arc::new(plugins)).query_engine();
+
+        let stmt = QueryLanguageParser::parse_sql("delete from metrics where cpu > 0").unwrap();
+        let plan = engine
+            .planner()
+            .plan(stmt, QueryContext::arc())
+            .await
+            .unwrap();
+        let err = engine.execute(plan, QueryContext::arc()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::DeleteRowLimitExceeded { limit: 2, .. }
+        ));
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        assert!(err
+            .to_string()
+            .contains("restrict the WHERE clause to primary key and time index columns"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_count_by_origin() {
+        common_telemetry::init_default_metrics_recorder();
+        let engine = create_test_engine().await;
+        let query_count = |origin: &str| {
+            let prefix = format!("greptime_query_execute_query_count{{origin=\"{origin}\"}} ");
+            common_telemetry::metric::try_handle()
+                .unwrap()
+                .render()
+                .lines()
+                .find_map(|line| {
+                    line.strip_prefix(&prefix)
+                        .map(|v| v.parse::<f64>().unwrap())
+                })
+                .unwrap_or(0.0)
+        };
+        let execute = |query_ctx: QueryContextRef| {
+            let engine = engine.clone();
+            async move {
+                let stmt = QueryLanguageParser::parse_sql("select * from numbers limit 1").unwrap();
+                let plan = engine
+                    .planner()
+                    .plan(stmt, query_ctx.clone())
+                    .await
+                    .unwrap();
+                let _ = engine.execute(plan, query_ctx).await.unwrap();
+            }
+        };
+
+        let system_count = query_count("system");
+        execute(Arc::new(
+            QueryContext::new().with_origin(QueryOrigin::System),
+        ))
+        .await;
+        assert_eq!(system_count + 1.0, query_count("system"));
+
+        // Other tests may run user queries concurrently.
+        let user_count = query_count("user");
+        execute(QueryContext::arc()).await;
+        assert!(query_count("user") >= user_count + 1.0);
+        assert_eq!(system_count + 1.0, query_count("system"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_query_rebatch() {
+        let execute = |engine: QueryEngineRef, sql: &'static str| async move {
+            let stmt = QueryLanguageParser::parse_sql(sql).unwrap();
+            let plan = engine
+                .planner()
+                .plan(stmt, QueryContext::arc())
+                .await
+                .unwrap();
+            match engine.execute(plan, QueryContext::arc()).await.unwrap() {
+                Output::Stream(stream) => util::collect(stream).await.unwrap(),
+                _ => unreachable!(),
+            }
+        };
+
+        let mut plugins = Plugins::new();
+        plugins.insert(QueryOptions {
+            result_rebatch: Some(RebatchOptions {
+                target_rows: 30,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let engine = create_test_engine_with_plugins(plugins).await;
+        let plain_engine = create_test_engine().await;
+
+        let sql = "select * from numbers";
+        let batches = execute(engine.clone(), sql).await;
+        let rows = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(vec![30, 30, 30, 10], rows);
+        let expected = execute(plain_engine, sql).await;
+        let collect_rows =
+            |batches: Vec<RecordBatch>| batches.iter().flat_map(|b| b.rows()).collect::<Vec<_>>();
+        assert_eq!(collect_rows(expected), collect_rows(batches));
+
+        // A small limit shrinks the target so the only batch is sent as soon as it's read.
+        let batches = execute(engine, "select * from numbers limit 5").await;
+        let rows = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(vec![5], rows);
+    }
 }
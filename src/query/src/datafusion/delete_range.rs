@@ -0,0 +1,310 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Translation of the `WHERE` clause of a `DELETE` into a range delete of the table.
+//!
+//! Only predicates a range tombstone represents exactly are translated: a conjunction of
+//! `key = literal` on the row key columns and comparisons of the time index with literals.
+//! Any other predicate is deleted by key instead, as a range delete would delete more or
+//! fewer rows than it matches.
+
+use std::collections::HashMap;
+
+use common_time::range::TimestampRange;
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
+use datafusion_common::ScalarValue;
+use datafusion_expr::utils::split_conjunction;
+use datafusion_expr::{Between, BinaryExpr, Expr, LogicalPlan, Operator};
+use datatypes::arrow::compute;
+use datatypes::arrow::datatypes::{DataType, TimeUnit as ArrowTimeUnit};
+use datatypes::prelude::{ConcreteDataType, Value};
+use datatypes::schema::Schema;
+use table::requests::DeleteRangeRequest;
+
+/// Returns the range delete request deleting exactly the rows matched by the `DELETE` input
+/// `plan` of a table with `schema` and row `key_columns`, or `None` if the predicates can't be
+/// represented by a range delete.
+pub(crate) fn delete_range_request(
+    plan: &LogicalPlan,
+    schema: &Schema,
+    key_columns: &[String],
+) -> Option<DeleteRangeRequest> {
+    let ts_column = schema.timestamp_column()?;
+    let ConcreteDataType::Timestamp(ts_type) = &ts_column.data_type else {
+        return None;
+    };
+    let mut builder = RangeBuilder {
+        schema,
+        key_columns,
+        ts_column: &ts_column.name,
+        unit: ts_type.unit(),
+        key_column_values: HashMap::new(),
+        start: None,
+        end: None,
+    };
+
+    let mut predicates = Vec::new();
+    collect_predicates(plan, &mut predicates)?;
+    for predicate in predicates {
+        builder.update(predicate)?;
+    }
+    builder.build()
+}
+
+/// Collects the conjuncts of the filters of `plan`, returns `None` if `plan` does anything
+/// other than filtering a table scan.
+fn collect_predicates<'a>(plan: &'a LogicalPlan, predicates: &mut Vec<&'a Expr>) -> Option<()> {
+    match plan {
+        LogicalPlan::Filter(filter) => {
+            predicates.extend(split_conjunction(&filter.predicate));
+            collect_predicates(&filter.input, predicates)
+        }
+        LogicalPlan::TableScan(scan) if scan.fetch.is_none() => {
+            for filter in &scan.filters {
+                predicates.extend(split_conjunction(filter));
+            }
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+struct RangeBuilder<'a> {
+    schema: &'a Schema,
+    key_columns: &'a [String],
+    ts_column: &'a str,
+    /// Time unit of the time index.
+    unit: TimeUnit,
+    key_column_values: HashMap<String, Value>,
+    /// Inclusive start of the time range, in `unit`.
+    start: Option<i64>,
+    /// Exclusive end of the time range, in `unit`.
+    end: Option<i64>,
+}
+
+impl<'a> RangeBuilder<'a> {
+    fn update(&mut self, predicate: &Expr) -> Option<()> {
+        match predicate {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let (name, op, value) = match (left.as_ref(), right.as_ref()) {
+                    (Expr::Column(c), value) => (&c.name, *op, value),
+                    (value, Expr::Column(c)) => (&c.name, op.swap()?, value),
+                    _ => return None,
+                };
+                if name == self.ts_column {
+                    let value = self.literal_time(value)?;
+                    match op {
+                        Operator::Gt => self.update_start(value.checked_add(1)?),
+                        Operator::GtEq => self.update_start(value),
+                        Operator::Lt => self.update_end(value),
+                        Operator::LtEq => self.update_end(value.checked_add(1)?),
+                        Operator::Eq => {
+                            self.update_start(value);
+                            self.update_end(value.checked_add(1)?);
+                        }
+                        _ => return None,
+                    }
+                    Some(())
+                } else if op == Operator::Eq {
+                    self.update_key(name, value)
+                } else {
+                    None
+                }
+            }
+            Expr::Between(Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            }) => {
+                if !matches!(expr.as_ref(), Expr::Column(c) if c.name == self.ts_column) {
+                    return None;
+                }
+                let low = self.literal_time(low)?;
+                let high = self.literal_time(high)?;
+                self.update_start(low);
+                self.update_end(high.checked_add(1)?);
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn update_key(&mut self, name: &str, value: &Expr) -> Option<()> {
+        if !self.key_columns.iter().any(|key| key == name) {
+            return None;
+        }
+        let Expr::Literal(value) = value else {
+            return None;
+        };
+        let value = Value::try_from(value.clone()).ok()?;
+        let column = self.schema.column_schema_by_name(name)?;
+        // Values of other types are compared after coercion, which a tombstone doesn't do.
+        if value.is_null() || value.data_type() != column.data_type {
+            return None;
+        }
+        match self.key_column_values.get(name) {
+            // Conflicting values match nothing, leave it to the delete by key.
+            Some(prev) if *prev != value => None,
+            _ => {
+                let _ = self.key_column_values.insert(name.to_string(), value);
+                Some(())
+            }
+        }
+    }
+
+    fn update_start(&mut self, start: i64) {
+        self.start = Some(self.start.map_or(start, |prev| prev.max(start)));
+    }
+
+    fn update_end(&mut self, end: i64) {
+        self.end = Some(self.end.map_or(end, |prev| prev.min(end)));
+    }
+
+    /// Returns the value of the time literal `expr` in the unit of the time index, or `None`
+    /// if it can't be converted exactly.
+    fn literal_time(&self, expr: &Expr) -> Option<i64> {
+        let value = match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Cast(cast) => {
+                let Expr::Literal(value) = cast.expr.as_ref() else {
+                    return None;
+                };
+                let array = compute::cast(&value.to_array(), &cast.data_type).ok()?;
+                ScalarValue::try_from_array(&array, 0).ok()?
+            }
+            _ => return None,
+        };
+        let value = match value {
+            ScalarValue::Int64(Some(v)) => return Some(v),
+            // Strings are compared with the time index after the same cast.
+            ScalarValue::Utf8(Some(_)) => {
+                let data_type = DataType::Timestamp(ArrowTimeUnit::Nanosecond, None);
+                let array = compute::cast(&value.to_array(), &data_type).ok()?;
+                ScalarValue::try_from_array(&array, 0).ok()?
+            }
+            value => value,
+        };
+        let ts = match value {
+            ScalarValue::TimestampSecond(Some(v), _) => Timestamp::new(v, TimeUnit::Second),
+            ScalarValue::TimestampMillisecond(Some(v), _) => {
+                Timestamp::new(v, TimeUnit::Millisecond)
+            }
+            ScalarValue::TimestampMicrosecond(Some(v), _) => {
+                Timestamp::new(v, TimeUnit::Microsecond)
+            }
+            ScalarValue::TimestampNanosecond(Some(v), _) => Timestamp::new(v, TimeUnit::Nanosecond),
+            _ => return None,
+        };
+        let converted = ts.convert_to(self.unit)?;
+        // Timestamps of different units are compared by their values in time.
+        (converted == ts).then_some(converted.value())
+    }
+
+    fn build(self) -> Option<DeleteRangeRequest> {
+        let time_range = match (self.start, self.end) {
+            (Some(start), Some(end)) => TimestampRange::with_unit(start, end, self.unit)?,
+            (Some(start), None) => TimestampRange::from_start(Timestamp::new(start, self.unit)),
+            (None, Some(end)) => TimestampRange::until_end(Timestamp::new(end, self.unit), false),
+            (None, None) => TimestampRange::min_to_max(),
+        };
+        Some(DeleteRangeRequest {
+            key_column_values: self.key_column_values,
+            time_range,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::datasource::empty::EmptyTable;
+    use datafusion::datasource::provider_as_source;
+    use datafusion_expr::{col, lit, LogicalPlanBuilder};
+    use datatypes::schema::ColumnSchema;
+
+    use super::*;
+
+    fn new_schema() -> Schema {
+        Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ])
+    }
+
+    fn request_of(schema: &Schema, predicate: Expr) -> Option<DeleteRangeRequest> {
+        let table = EmptyTable::new(schema.arrow_schema().clone());
+        let plan = LogicalPlanBuilder::scan("t", provider_as_source(Arc::new(table)), None)
+            .unwrap()
+            .filter(predicate)
+            .unwrap()
+            .build()
+            .unwrap();
+        delete_range_request(&plan, schema, &["host".to_string()])
+    }
+
+    #[test]
+    fn test_delete_range_request() {
+        let schema = new_schema();
+        let request = request_of(
+            &schema,
+            col("host")
+                .eq(lit("host1"))
+                .and(col("ts").gt_eq(lit(1000_i64)))
+                .and(lit("1970-01-01T00:00:02").gt(col("ts"))),
+        )
+        .unwrap();
+        assert_eq!(
+            HashMap::from([("host".to_string(), Value::from("host1"))]),
+            request.key_column_values
+        );
+        assert_eq!(
+            TimestampRange::with_unit(1000, 2000, TimeUnit::Millisecond).unwrap(),
+            request.time_range
+        );
+
+        let request = request_of(&schema, col("ts").lt_eq(lit(1000_i64))).unwrap();
+        assert!(request.key_column_values.is_empty());
+        assert_eq!(
+            TimestampRange::until_end(Timestamp::new_millisecond(1001), false),
+            request.time_range
+        );
+
+        // Predicates a range tombstone can't represent exactly.
+        let unsupported = [
+            col("cpu").gt(lit(1.0_f64)),
+            col("host").not_eq(lit("host1")),
+            col("host").eq(lit("host1")).or(col("ts").lt(lit(1000_i64))),
+            col("host")
+                .eq(lit("host1"))
+                .and(col("host").eq(lit("host2"))),
+            col("ts").gt(lit(ScalarValue::TimestampMicrosecond(Some(1001), None))),
+            col("ts").gt(lit(2000_i64)).and(col("ts").lt(lit(1000_i64))),
+        ];
+        for predicate in unsupported {
+            assert!(
+                request_of(&schema, predicate.clone()).is_none(),
+                "{predicate}"
+            );
+        }
+    }
+}
@@ -18,6 +18,7 @@ use std::sync::Arc;
 
 use arrow_schema::DataType;
 use catalog::table_source::DfTableSourceProvider;
+use common_function::scalars::udf::create_udf;
 use common_query::logical_plan::create_aggregate_function;
 use datafusion::catalog::TableReference;
 use datafusion::error::Result as DfResult;
@@ -33,6 +34,10 @@ use datafusion_sql::parser::Statement as DfStatement;
 use session::context::QueryContextRef;
 use snafu::ResultExt;
 
+use crate::datafusion::table_option::{
+    resolve_table_options, table_option_tables, TableOptionFunction, TableOptionValues,
+    TABLE_OPTION,
+};
 use crate::error::{CatalogSnafu, DataFusionSnafu, Result};
 use crate::query_engine::QueryEngineState;
 
@@ -41,6 +46,7 @@ pub struct DfContextProviderAdapter {
     session_state: SessionState,
     tables: HashMap<String, Arc<dyn TableSource>>,
     table_provider: DfTableSourceProvider,
+    table_options: Arc<TableOptionValues>,
 }
 
 impl DfContextProviderAdapter {
@@ -61,12 +67,19 @@ impl DfContextProviderAdapter {
         );
 
         let tables = resolve_tables(table_names, &mut table_provider).await?;
+        let table_options = resolve_table_options(
+            table_option_tables(df_stmt),
+            &table_provider,
+            engine_state.catalog_manager(),
+        )
+        .await?;
 
         Ok(Self {
             engine_state,
             session_state,
             tables,
             table_provider,
+            table_options: Arc::new(table_options),
         })
     }
 }
@@ -104,6 +117,10 @@ impl ContextProvider for DfContextProviderAdapter {
     }
 
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
+        if name.eq_ignore_ascii_case(TABLE_OPTION) {
+            let func = TableOptionFunction::new(self.table_options.clone());
+            return Some(Arc::new(create_udf(Arc::new(func)).into_df_udf()));
+        }
         self.session_state.scalar_functions().get(name).cloned()
     }
 
@@ -0,0 +1,172 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `table_option(table, option)` function, returning the value of an option of a table,
+//! the same as the `option_value` of `information_schema.table_options`, or null if the table
+//! has no such option.
+//!
+//! Functions can't access the catalog on evaluation, so the tables must be string literals,
+//! like `'schema.table'` or `'table'` in the current schema, whose options are looked up on
+//! planning.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use catalog::table_source::DfTableSourceProvider;
+use catalog::CatalogManagerRef;
+use common_function::scalars::{Function, FunctionContext};
+use common_query::error::{InvalidFuncArgsSnafu, Result as QueryResult};
+use common_query::prelude::{Signature, Volatility};
+use datafusion::catalog::TableReference;
+use datafusion_sql::parser::Statement as DfStatement;
+use datatypes::prelude::{ConcreteDataType, Value, VectorRef};
+use datatypes::vectors::StringVector;
+use snafu::{OptionExt, ResultExt};
+use sql::ast::{visit_expressions, Expr, FunctionArg, FunctionArgExpr, Value as SqlValue};
+
+use crate::error::{CatalogSnafu, Result, TableNotFoundSnafu};
+
+pub(crate) const TABLE_OPTION: &str = "table_option";
+
+/// Options of tables by the names in the `table_option` calls.
+pub(crate) type TableOptionValues = HashMap<String, HashMap<String, String>>;
+
+/// Returns the names of the tables in the `table_option` calls of the statement.
+pub(crate) fn table_option_tables(df_stmt: &DfStatement) -> Vec<String> {
+    let DfStatement::Statement(stmt) = df_stmt else {
+        return vec![];
+    };
+    let mut tables = Vec::new();
+    let _ = visit_expressions(stmt.as_ref(), |expr| {
+        if let Expr::Function(func) = expr {
+            if func.name.to_string().eq_ignore_ascii_case(TABLE_OPTION) {
+                if let Some(FunctionArg::Unnamed(FunctionArgExpr::Expr(Expr::Value(
+                    SqlValue::SingleQuotedString(table),
+                )))) = func.args.first()
+                {
+                    tables.push(table.clone());
+                }
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    tables
+}
+
+/// Looks up the options of the `tables`.
+pub(crate) async fn resolve_table_options(
+    tables: Vec<String>,
+    table_provider: &DfTableSourceProvider,
+    catalog_manager: &CatalogManagerRef,
+) -> Result<TableOptionValues> {
+    let mut options = HashMap::with_capacity(tables.len());
+    for name in tables {
+        if options.contains_key(&name) {
+            continue;
+        }
+
+        let table_ref = match name.split_once('.') {
+            Some((schema, table)) => TableReference::Partial {
+                schema: Cow::Borrowed(schema),
+                table: Cow::Borrowed(table),
+            },
+            None => TableReference::Bare {
+                table: Cow::Borrowed(&name),
+            },
+        };
+        let table_ref = table_provider
+            .resolve_table_ref(table_ref)
+            .context(CatalogSnafu)?;
+        let table = catalog_manager
+            .table(&table_ref.catalog, &table_ref.schema, &table_ref.table)
+            .await
+            .context(CatalogSnafu)?
+            .with_context(|| TableNotFoundSnafu {
+                table: table_ref.to_string(),
+            })?;
+
+        let values = table
+            .table_info()
+            .meta
+            .option_values()
+            .into_iter()
+            .map(|v| (v.key, v.value))
+            .collect();
+        let _ = options.insert(name, values);
+    }
+    Ok(options)
+}
+
+#[derive(Clone)]
+pub(crate) struct TableOptionFunction {
+    options: Arc<TableOptionValues>,
+}
+
+impl TableOptionFunction {
+    pub(crate) fn new(options: Arc<TableOptionValues>) -> Self {
+        Self { options }
+    }
+}
+
+impl Function for TableOptionFunction {
+    fn name(&self) -> &str {
+        TABLE_OPTION
+    }
+
+    fn return_type(&self, _input_types: &[ConcreteDataType]) -> QueryResult<ConcreteDataType> {
+        Ok(ConcreteDataType::string_datatype())
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::exact(
+            vec![
+                ConcreteDataType::string_datatype(),
+                ConcreteDataType::string_datatype(),
+            ],
+            Volatility::Stable,
+        )
+    }
+
+    fn eval(&self, _func_ctx: FunctionContext, columns: &[VectorRef]) -> QueryResult<VectorRef> {
+        let (tables, keys) = (&columns[0], &columns[1]);
+        let values = (0..tables.len())
+            .map(|i| {
+                let (Value::String(table), Value::String(key)) = (tables.get(i), keys.get(i))
+                else {
+                    return Ok(None);
+                };
+                let options = self
+                    .options
+                    .get(table.as_utf8())
+                    .context(InvalidFuncArgsSnafu {
+                        err_msg: format!(
+                            "table {} of {TABLE_OPTION} is not a string literal",
+                            table.as_utf8()
+                        ),
+                    })?;
+                Ok(options.get(key.as_utf8()).cloned())
+            })
+            .collect::<QueryResult<Vec<_>>>()?;
+        Ok(Arc::new(StringVector::from(values)))
+    }
+}
+
+impl fmt::Display for TableOptionFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TABLE_OPTION")
+    }
+}
@@ -0,0 +1,527 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decorrelation of subqueries referencing columns of the outer query, like
+//!
+//! ```sql
+//! SELECT host, (SELECT max(ts) FROM t2 WHERE t2.host = t1.host) FROM t1
+//! ```
+//!
+//! which BI tools generate a lot. [DecorrelateSubqueryRule] rewrites them into joins:
+//! - a correlated scalar subquery becomes a left join with the subquery grouped by the
+//!   correlated columns;
+//! - `[NOT] EXISTS` becomes a semi (anti) join;
+//! - `[NOT] IN` becomes a semi (anti) join. As `x NOT IN (...)` is never true if `x` or any
+//!   value of the subquery is null, the anti join also matches the null values.
+//!
+//! Only subqueries correlated by equalities in their `WHERE` clause are rewritten, others are
+//! left to DataFusion.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use datafusion::config::ConfigOptions;
+use datafusion::error::Result as DfResult;
+use datafusion_common::tree_node::{Transformed, TreeNode, TreeNodeRewriter, VisitRecursion};
+use datafusion_common::{Column, DataFusionError, ScalarValue};
+use datafusion_expr::expr::AggregateFunction;
+use datafusion_expr::utils::{conjunction, split_conjunction};
+use datafusion_expr::{
+    aggregate_function, lit, when, Aggregate, BinaryExpr, Expr, Filter, JoinType, LogicalPlan,
+    LogicalPlanBuilder, Operator, Projection,
+};
+use datafusion_optimizer::analyzer::AnalyzerRule;
+
+/// Name of the column of the value of a decorrelated subquery.
+const VALUE_COLUMN: &str = "__value";
+
+/// Rewrites correlated subqueries into joins, see the [module](self) docs.
+pub struct DecorrelateSubqueryRule;
+
+impl AnalyzerRule for DecorrelateSubqueryRule {
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> DfResult<LogicalPlan> {
+        let next_id = AtomicUsize::new(0);
+        plan.transform_up(&|plan| {
+            let rewritten = match &plan {
+                LogicalPlan::Projection(projection) => {
+                    Self::rewrite_projection(projection, &next_id)?
+                }
+                LogicalPlan::Filter(filter) => Self::rewrite_filter(filter, &next_id)?,
+                _ => None,
+            };
+            match rewritten {
+                Some(plan) => Ok(Transformed::Yes(plan)),
+                None => Ok(Transformed::No(plan)),
+            }
+        })
+    }
+
+    fn name(&self) -> &str {
+        "DecorrelateSubqueryRule"
+    }
+}
+
+impl DecorrelateSubqueryRule {
+    fn rewrite_projection(
+        projection: &Projection,
+        next_id: &AtomicUsize,
+    ) -> DfResult<Option<LogicalPlan>> {
+        let mut replacer = ScalarSubqueryReplacer::new(next_id);
+        let exprs = projection
+            .expr
+            .iter()
+            .map(|expr| {
+                let rewritten = expr.clone().rewrite(&mut replacer)?;
+                // Keeps the name of the output column.
+                if rewritten != *expr && !matches!(expr, Expr::Alias(..)) {
+                    Ok(rewritten.alias(expr.display_name()?))
+                } else {
+                    Ok(rewritten)
+                }
+            })
+            .collect::<DfResult<Vec<_>>>()?;
+        if replacer.joins.is_empty() {
+            return Ok(None);
+        }
+
+        let input = replacer.join_all(projection.input.as_ref().clone())?;
+        LogicalPlanBuilder::from(input)
+            .project(exprs)?
+            .build()
+            .map(Some)
+    }
+
+    fn rewrite_filter(filter: &Filter, next_id: &AtomicUsize) -> DfResult<Option<LogicalPlan>> {
+        let mut input = filter.input.as_ref().clone();
+        let mut decorrelated = false;
+        let mut replacer = ScalarSubqueryReplacer::new(next_id);
+        let mut predicates = vec![];
+        for expr in split_conjunction(&filter.predicate) {
+            match decorrelate_predicate(expr, next_id)? {
+                Some(join) => {
+                    input = join.join(input)?;
+                    decorrelated = true;
+                }
+                None => predicates.push(expr.clone().rewrite(&mut replacer)?),
+            }
+        }
+        if !decorrelated && replacer.joins.is_empty() {
+            return Ok(None);
+        }
+
+        let has_scalar_joins = !replacer.joins.is_empty();
+        let mut builder = LogicalPlanBuilder::from(replacer.join_all(input)?);
+        if let Some(predicate) = conjunction(predicates) {
+            builder = builder.filter(predicate)?;
+        }
+        if has_scalar_joins {
+            // Removes the columns of the subqueries joined.
+            let columns = filter
+                .input
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| Expr::Column(field.qualified_column()))
+                .collect::<Vec<_>>();
+            builder = builder.project(columns)?;
+        }
+        builder.build().map(Some)
+    }
+}
+
+/// A subquery to join with the outer query.
+struct SubqueryJoin {
+    /// The subquery, aliased so its columns don't clash with the outer query.
+    subquery: LogicalPlan,
+    join_type: JoinType,
+    /// Columns of the outer query equal to the columns of the subquery.
+    keys: (Vec<Column>, Vec<Column>),
+    filter: Option<Expr>,
+}
+
+impl SubqueryJoin {
+    fn join(self, input: LogicalPlan) -> DfResult<LogicalPlan> {
+        LogicalPlanBuilder::from(input)
+            .join(self.subquery, self.join_type, self.keys, self.filter)?
+            .build()
+    }
+}
+
+/// Replaces the correlated scalar subqueries by the value column of the subqueries left
+/// joined.
+struct ScalarSubqueryReplacer<'a> {
+    next_id: &'a AtomicUsize,
+    joins: Vec<SubqueryJoin>,
+}
+
+impl<'a> ScalarSubqueryReplacer<'a> {
+    fn new(next_id: &'a AtomicUsize) -> Self {
+        Self {
+            next_id,
+            joins: vec![],
+        }
+    }
+
+    fn join_all(&mut self, input: LogicalPlan) -> DfResult<LogicalPlan> {
+        self.joins
+            .drain(..)
+            .try_fold(input, |input, join| join.join(input))
+    }
+}
+
+impl TreeNodeRewriter for ScalarSubqueryReplacer<'_> {
+    type N = Expr;
+
+    fn mutate(&mut self, expr: Expr) -> DfResult<Expr> {
+        let Expr::ScalarSubquery(subquery) = &expr else { return Ok(expr) };
+        if subquery.outer_ref_columns.is_empty() {
+            return Ok(expr);
+        }
+        ensure_no_correlated_window(&subquery.subquery)?;
+
+        // Only aggregations over a correlated filter are decorrelated, which are guaranteed to
+        // return a single row:
+        // Projection: value
+        //   Aggregate: aggr_expr
+        //     Filter: correlated predicate
+        let LogicalPlan::Projection(projection) = subquery.subquery.as_ref() else {
+            return Ok(expr);
+        };
+        let LogicalPlan::Aggregate(aggregate) = projection.input.as_ref() else { return Ok(expr) };
+        let LogicalPlan::Filter(filter) = aggregate.input.as_ref() else { return Ok(expr) };
+        if projection.expr.len() != 1
+            || !aggregate.group_expr.is_empty()
+            || projection.expr.iter().any(contains_outer_reference)
+            || aggregate.aggr_expr.iter().any(contains_outer_reference)
+        {
+            return Ok(expr);
+        }
+        let Some(correlation) = Correlation::try_new(filter) else { return Ok(expr) };
+
+        let alias = next_alias(self.next_id);
+        let inner_keys = correlation
+            .inner_keys
+            .iter()
+            .cloned()
+            .map(Expr::Column)
+            .collect::<Vec<_>>();
+        let value = &projection.expr[0];
+        let subquery = correlation
+            .filtered_input()?
+            .aggregate(inner_keys, aggregate.aggr_expr.clone())?
+            .project(correlation.projection(Some(value.clone())))?
+            .alias(&alias)?
+            .build()?;
+        self.joins.push(SubqueryJoin {
+            subquery,
+            join_type: JoinType::Left,
+            keys: correlation.join_keys(&alias),
+            filter: None,
+        });
+
+        let value_column = Expr::Column(Column::new(Some(alias.clone()), VALUE_COLUMN));
+        // The outer rows without any matched row are the ones joined with null keys, as the
+        // keys are matched by equalities. The value of no rows is not null if it counts them.
+        match value_of_no_rows(value, aggregate)? {
+            Some(value_of_no_rows) => {
+                let unmatched = Expr::Column(Column::new(Some(alias), key_column(0))).is_null();
+                when(unmatched, value_of_no_rows).otherwise(value_column)
+            }
+            None => Ok(value_column),
+        }
+    }
+}
+
+/// Decorrelates a `[NOT] EXISTS` or `[NOT] IN` predicate into a semi (anti) join.
+fn decorrelate_predicate(expr: &Expr, next_id: &AtomicUsize) -> DfResult<Option<SubqueryJoin>> {
+    match expr {
+        Expr::Exists { subquery, negated } => {
+            if subquery.outer_ref_columns.is_empty() {
+                return Ok(None);
+            }
+            ensure_no_correlated_window(&subquery.subquery)?;
+
+            // The output of the subquery doesn't matter.
+            let plan = match subquery.subquery.as_ref() {
+                LogicalPlan::Projection(projection) => projection.input.as_ref(),
+                plan => plan,
+            };
+            let LogicalPlan::Filter(filter) = plan else { return Ok(None) };
+            let Some(correlation) = Correlation::try_new(filter) else { return Ok(None) };
+
+            let alias = next_alias(next_id);
+            let subquery = correlation
+                .filtered_input()?
+                .project(correlation.projection(None))?
+                .alias(&alias)?
+                .build()?;
+            Ok(Some(SubqueryJoin {
+                subquery,
+                join_type: semi_join_type(*negated),
+                keys: correlation.join_keys(&alias),
+                filter: None,
+            }))
+        }
+        Expr::InSubquery {
+            expr,
+            subquery,
+            negated,
+        } => {
+            if contains_outer_reference(expr) {
+                return Ok(None);
+            }
+            ensure_no_correlated_window(&subquery.subquery)?;
+
+            let LogicalPlan::Projection(projection) = subquery.subquery.as_ref() else {
+                return Ok(None);
+            };
+            if projection.expr.len() != 1 || projection.expr.iter().any(contains_outer_reference) {
+                return Ok(None);
+            }
+            let correlation = match projection.input.as_ref() {
+                LogicalPlan::Filter(filter) => Correlation::try_new(filter),
+                input => Correlation::uncorrelated(input),
+            };
+            let Some(correlation) = correlation else { return Ok(None) };
+
+            let alias = next_alias(next_id);
+            let subquery = correlation
+                .filtered_input()?
+                .project(correlation.projection(Some(projection.expr[0].clone())))?
+                .alias(&alias)?
+                .build()?;
+            let value = Expr::Column(Column::new(Some(alias.clone()), VALUE_COLUMN));
+            let filter = if *negated {
+                // `x NOT IN (...)` is not true if any value is equal to `x` or null, or if `x`
+                // is null and the subquery is not empty.
+                expr.as_ref()
+                    .clone()
+                    .eq(value.clone())
+                    .or(value.is_null())
+                    .or(expr.as_ref().clone().is_null())
+            } else {
+                expr.as_ref().clone().eq(value)
+            };
+            Ok(Some(SubqueryJoin {
+                subquery,
+                join_type: semi_join_type(*negated),
+                keys: correlation.join_keys(&alias),
+                filter: Some(filter),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Equalities between columns of the outer query and columns of a subquery, split from the
+/// filter of the subquery.
+struct Correlation {
+    outer_keys: Vec<Column>,
+    inner_keys: Vec<Column>,
+    /// The filter of the subquery without the correlated predicates.
+    predicate: Option<Expr>,
+    input: LogicalPlan,
+}
+
+impl Correlation {
+    /// Returns `None` if the filter isn't correlated only by equalities of columns, or the
+    /// outer query is referenced elsewhere in the subquery.
+    fn try_new(filter: &Filter) -> Option<Self> {
+        let mut outer_keys = vec![];
+        let mut inner_keys = vec![];
+        let mut predicates = vec![];
+        for expr in split_conjunction(&filter.predicate) {
+            if !contains_outer_reference(expr) {
+                predicates.push(expr.clone());
+                continue;
+            }
+            let Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            }) = expr
+            else {
+                return None;
+            };
+            match (left.as_ref(), right.as_ref()) {
+                (Expr::OuterReferenceColumn(_, outer), Expr::Column(inner))
+                | (Expr::Column(inner), Expr::OuterReferenceColumn(_, outer)) => {
+                    outer_keys.push(outer.clone());
+                    inner_keys.push(inner.clone());
+                }
+                _ => return None,
+            }
+        }
+        if outer_keys.is_empty() || plan_contains_outer_reference(&filter.input) {
+            return None;
+        }
+
+        Some(Self {
+            outer_keys,
+            inner_keys,
+            predicate: conjunction(predicates),
+            input: filter.input.as_ref().clone(),
+        })
+    }
+
+    fn uncorrelated(input: &LogicalPlan) -> Option<Self> {
+        if plan_contains_outer_reference(input) {
+            return None;
+        }
+        Some(Self {
+            outer_keys: vec![],
+            inner_keys: vec![],
+            predicate: None,
+            input: input.clone(),
+        })
+    }
+
+    fn filtered_input(&self) -> DfResult<LogicalPlanBuilder> {
+        let builder = LogicalPlanBuilder::from(self.input.clone());
+        match &self.predicate {
+            Some(predicate) => builder.filter(predicate.clone()),
+            None => Ok(builder),
+        }
+    }
+
+    /// Projects the keys, and the value of the subquery if any.
+    fn projection(&self, value: Option<Expr>) -> Vec<Expr> {
+        self.inner_keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| Expr::Column(key.clone()).alias(key_column(i)))
+            .chain(value.map(|value| value.alias(VALUE_COLUMN)))
+            .collect()
+    }
+
+    fn join_keys(&self, alias: &str) -> (Vec<Column>, Vec<Column>) {
+        let inner_keys = (0..self.inner_keys.len())
+            .map(|i| Column::new(Some(alias), key_column(i)))
+            .collect();
+        (self.outer_keys.clone(), inner_keys)
+    }
+}
+
+fn next_alias(next_id: &AtomicUsize) -> String {
+    format!(
+        "__decorrelated_sq_{}",
+        next_id.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+fn key_column(i: usize) -> String {
+    format!("__key_{i}")
+}
+
+fn semi_join_type(negated: bool) -> JoinType {
+    if negated {
+        JoinType::LeftAnti
+    } else {
+        JoinType::LeftSemi
+    }
+}
+
+/// Returns the value of the subquery `value` over the output of `aggregate` when no row
+/// matches, or `None` if it doesn't count the rows and so is null like the aggregates of no
+/// rows. The counts of no rows are 0, e.g. `count(x) + 1` is 1.
+fn value_of_no_rows(value: &Expr, aggregate: &Aggregate) -> DfResult<Option<Expr>> {
+    let value = match value {
+        Expr::Alias(expr, _) => expr.as_ref(),
+        value => value,
+    };
+    let aggr_expr_of = |column: &Column| {
+        aggregate.aggr_expr.iter().find(|expr| {
+            expr.display_name()
+                .map(|name| name == column.name)
+                .unwrap_or(false)
+        })
+    };
+    let mut counts = false;
+    let _ = value.apply(&mut |expr| {
+        if let Expr::Column(column) = expr {
+            counts |= aggr_expr_of(column).map(is_count).unwrap_or(false);
+        }
+        Ok(VisitRecursion::Continue)
+    })?;
+    if !counts {
+        return Ok(None);
+    }
+
+    value
+        .clone()
+        .transform(&|expr| {
+            let Expr::Column(column) = &expr else { return Ok(Transformed::No(expr)) };
+            let Some(aggr_expr) = aggr_expr_of(column) else { return Ok(Transformed::No(expr)) };
+            if is_count(aggr_expr) {
+                return Ok(Transformed::Yes(lit(0i64)));
+            }
+            let field = aggregate.schema.field_with_unqualified_name(&column.name)?;
+            Ok(Transformed::Yes(Expr::Literal(ScalarValue::try_from(
+                field.data_type(),
+            )?)))
+        })
+        .map(Some)
+}
+
+fn is_count(aggr_expr: &Expr) -> bool {
+    match aggr_expr {
+        Expr::Alias(expr, _) => is_count(expr),
+        expr => matches!(
+            expr,
+            Expr::AggregateFunction(AggregateFunction {
+                fun: aggregate_function::AggregateFunction::Count,
+                ..
+            })
+        ),
+    }
+}
+
+/// Correlated columns in window functions can't be turned into join keys.
+fn ensure_no_correlated_window(plan: &LogicalPlan) -> DfResult<()> {
+    let _ = plan.apply(&mut |plan| {
+        if let LogicalPlan::Window(window) = plan {
+            if window.window_expr.iter().any(contains_outer_reference) {
+                return Err(DataFusionError::Plan(
+                    "Correlated columns are not supported in window functions of subqueries"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(VisitRecursion::Continue)
+    })?;
+    Ok(())
+}
+
+fn contains_outer_reference(expr: &Expr) -> bool {
+    let mut found = false;
+    let _ = expr.apply(&mut |expr| {
+        if matches!(expr, Expr::OuterReferenceColumn(..)) {
+            found = true;
+            return Ok(VisitRecursion::Stop);
+        }
+        Ok(VisitRecursion::Continue)
+    });
+    found
+}
+
+fn plan_contains_outer_reference(plan: &LogicalPlan) -> bool {
+    let mut found = false;
+    let _ = plan.apply(&mut |plan| {
+        if plan.expressions().iter().any(contains_outer_reference) {
+            found = true;
+            return Ok(VisitRecursion::Stop);
+        }
+        Ok(VisitRecursion::Continue)
+    });
+    found
+}
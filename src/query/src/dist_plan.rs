@@ -0,0 +1,868 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Two-phase aggregation and top-k for tables whose data are spread over several places (like
+//! the distributed tables in frontend).
+//!
+//! [AggregatePushdownRule] splits a decomposable aggregation over such a table into a partial
+//! aggregation, which is evaluated next to the data by [TableAggregateScan], and a final
+//! aggregation that merges the partial states.
+//!
+//! Likewise, a top-k over such a table keeps the top-k rows of each place by [TableTopKScan],
+//! which are merged into the top-k rows of the whole table, see [pushdown_topk].
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use common_function::scalars::aggregate::{AggregateFunctionMeta, AggregateFunctionMetaRef};
+use common_query::logical_plan::dist_aggr::{
+    create_merge_aggregate_function, create_partial_aggregate_function,
+};
+use common_query::physical_plan::DfPhysicalPlanAdapter;
+use datafusion::datasource::DefaultTableSource;
+use datafusion::error::Result as DfResult;
+use datafusion::execution::context::SessionState;
+use datafusion::physical_plan::planner::ExtensionPlanner;
+use datafusion::physical_plan::{ExecutionPlan, PhysicalPlanner};
+use datafusion_common::{Column, DFSchema, DFSchemaRef, DataFusionError};
+use datafusion_expr::expr::AggregateFunction;
+use datafusion_expr::expr_rewriter::unnormalize_col;
+use datafusion_expr::{
+    aggregate_function, cast, coalesce, count, lit, max, min, sum, Aggregate, Expr, ExprSchemable,
+    Extension, LogicalPlan, Projection, TableScan, UserDefinedLogicalNode,
+    UserDefinedLogicalNodeCore,
+};
+use datafusion_optimizer::optimizer::ApplyOrder;
+use datafusion_optimizer::{OptimizerConfig, OptimizerRule};
+use datatypes::arrow::datatypes::DataType;
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::Schema;
+use table::table::adapter::DfTableProviderAdapter;
+use table::table::{PartialAggregate, PartialTopK};
+use table::TableRef;
+
+/// Rewrites `Aggregate <- Filter* <- TableScan` into
+/// `Projection <- Aggregate(final) <- TableAggregateScan(partial)`, if the scanned table
+/// supports aggregate pushdown and all the aggregate functions are decomposable.
+///
+/// Decomposable aggregate functions are `COUNT`, `SUM`, `MIN`, `MAX` and `AVG` (as a `SUM`
+/// and a `COUNT`) without `DISTINCT` or `FILTER`, and the registered UDAFs supporting
+/// distributed aggregation without `FILTER` if the table supports UDAF pushdown. Otherwise the
+/// plan is left untouched, and the raw rows are aggregated after being pulled from the table.
+pub struct AggregatePushdownRule {
+    /// Aggregate functions registered in the query engine, by their names.
+    aggregate_functions: Arc<RwLock<HashMap<String, AggregateFunctionMetaRef>>>,
+}
+
+impl AggregatePushdownRule {
+    pub fn new(
+        aggregate_functions: Arc<RwLock<HashMap<String, AggregateFunctionMetaRef>>>,
+    ) -> Self {
+        Self {
+            aggregate_functions,
+        }
+    }
+}
+
+impl OptimizerRule for AggregatePushdownRule {
+    fn try_optimize(
+        &self,
+        plan: &LogicalPlan,
+        _config: &dyn OptimizerConfig,
+    ) -> DfResult<Option<LogicalPlan>> {
+        let LogicalPlan::Aggregate(aggregate) = plan else { return Ok(None) };
+        if aggregate.aggr_expr.is_empty() && aggregate.group_expr.is_empty() {
+            return Ok(None);
+        }
+        if aggregate
+            .group_expr
+            .iter()
+            .any(|expr| matches!(expr, Expr::GroupingSet(_)))
+        {
+            return Ok(None);
+        }
+
+        let mut filters = vec![];
+        let Some((table_scan, table)) =
+            find_pushdown_scan(&aggregate.input, &mut filters, |table| {
+                table.supports_aggregate_pushdown()
+            })
+        else {
+            return Ok(None);
+        };
+
+        let aggregate_functions = self.aggregate_functions.read().unwrap();
+        let udafs = table
+            .supports_udaf_pushdown()
+            .then_some(&*aggregate_functions);
+        let Some(decomposed) =
+            decompose_aggr_exprs(&aggregate.aggr_expr, aggregate.input.schema(), udafs)
+        else {
+            return Ok(None);
+        };
+
+        let partial = Aggregate::try_new(
+            aggregate.input.clone(),
+            aggregate.group_expr.clone(),
+            decomposed.partial_exprs.clone(),
+        )?;
+        let scan = TableAggregateScan {
+            table_name: table_scan.table_name.to_string(),
+            table,
+            projection: table_scan.projection.clone(),
+            filters: filters
+                .into_iter()
+                .chain(table_scan.filters.iter().cloned())
+                .map(unnormalize_col)
+                .collect(),
+            group_expr: aggregate
+                .group_expr
+                .iter()
+                .cloned()
+                .map(unnormalize_col)
+                .collect(),
+            aggr_expr: decomposed
+                .partial_exprs
+                .into_iter()
+                .map(unnormalize_col)
+                .collect(),
+            schema: partial.schema,
+        };
+
+        let group_columns = scan.schema.fields()[..aggregate.group_expr.len()]
+            .iter()
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect::<Vec<_>>();
+        let final_aggregate = Aggregate::try_new(
+            Arc::new(LogicalPlan::Extension(Extension {
+                node: Arc::new(scan),
+            })),
+            group_columns.clone(),
+            decomposed.final_exprs,
+        )?;
+
+        let output_fields = &aggregate.schema.fields()[aggregate.group_expr.len()..];
+        let projection_exprs = group_columns
+            .into_iter()
+            .chain(
+                decomposed
+                    .output_exprs
+                    .into_iter()
+                    .zip(output_fields.iter())
+                    .map(|(expr, field)| cast(expr, field.data_type().clone()).alias(field.name())),
+            )
+            .collect::<Vec<_>>();
+
+        Ok(Some(LogicalPlan::Projection(Projection::try_new(
+            projection_exprs,
+            Arc::new(LogicalPlan::Aggregate(final_aggregate)),
+        )?)))
+    }
+
+    fn name(&self) -> &str {
+        "AggregatePushdownRule"
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::TopDown)
+    }
+}
+
+/// Walks down the input of an aggregation or a top-k through filters, returns the table scan
+/// and the scanned table if the table `supports` the pushdown.
+fn find_pushdown_scan<'a>(
+    plan: &'a LogicalPlan,
+    filters: &mut Vec<Expr>,
+    supports: impl Fn(&TableRef) -> bool,
+) -> Option<(&'a TableScan, TableRef)> {
+    match plan {
+        LogicalPlan::Filter(filter) => {
+            filters.push(filter.predicate.clone());
+            find_pushdown_scan(&filter.input, filters, supports)
+        }
+        LogicalPlan::TableScan(table_scan) => {
+            if table_scan.fetch.is_some() {
+                return None;
+            }
+            let table = table_scan
+                .source
+                .as_any()
+                .downcast_ref::<DefaultTableSource>()?
+                .table_provider
+                .as_any()
+                .downcast_ref::<DfTableProviderAdapter>()?
+                .table();
+            supports(&table).then_some((table_scan, table))
+        }
+        _ => None,
+    }
+}
+
+/// Rewrites the input of a top-k, `Projection? <- Filter* <- TableScan`, into
+/// `Projection? <- TableTopKScan`, if the scanned table supports top-k pushdown. The
+/// projection must only select columns, so the sort expressions can be evaluated against the
+/// scanned rows.
+///
+/// The top-k over the rewritten input merges the top-k rows of each place into the top-k rows
+/// of the whole table.
+pub fn pushdown_topk(
+    input: &LogicalPlan,
+    sort_expr: &[Expr],
+    fetch: usize,
+) -> DfResult<Option<LogicalPlan>> {
+    if let LogicalPlan::Projection(projection) = input {
+        if !projection
+            .expr
+            .iter()
+            .all(|expr| matches!(expr, Expr::Column(_)))
+        {
+            return Ok(None);
+        }
+        let Some(scan) = pushdown_topk(&projection.input, sort_expr, fetch)? else {
+            return Ok(None);
+        };
+        return Ok(Some(LogicalPlan::Projection(Projection::try_new(
+            projection.expr.clone(),
+            Arc::new(scan),
+        )?)));
+    }
+
+    let mut filters = vec![];
+    let Some((table_scan, table)) =
+        find_pushdown_scan(input, &mut filters, |table| table.supports_topk_pushdown())
+    else {
+        return Ok(None);
+    };
+    let scan = TableTopKScan {
+        table_name: table_scan.table_name.to_string(),
+        table,
+        projection: table_scan.projection.clone(),
+        filters: filters
+            .into_iter()
+            .chain(table_scan.filters.iter().cloned())
+            .map(unnormalize_col)
+            .collect(),
+        sort_expr: sort_expr.iter().cloned().map(unnormalize_col).collect(),
+        fetch,
+        schema: table_scan.projected_schema.clone(),
+    };
+    Ok(Some(LogicalPlan::Extension(Extension {
+        node: Arc::new(scan),
+    })))
+}
+
+struct DecomposedAggregate {
+    /// Aggregate expressions evaluated next to the data.
+    partial_exprs: Vec<Expr>,
+    /// Aggregate expressions merging the partial states.
+    final_exprs: Vec<Expr>,
+    /// Expressions computing the original aggregate values from the final aggregation.
+    output_exprs: Vec<Expr>,
+}
+
+fn decompose_aggr_exprs(
+    aggr_exprs: &[Expr],
+    input_schema: &DFSchema,
+    udafs: Option<&HashMap<String, AggregateFunctionMetaRef>>,
+) -> Option<DecomposedAggregate> {
+    let mut decomposed = DecomposedAggregate {
+        partial_exprs: vec![],
+        final_exprs: vec![],
+        output_exprs: vec![],
+    };
+
+    for expr in aggr_exprs {
+        let output = match expr {
+            Expr::AggregateFunction(AggregateFunction {
+                fun,
+                args,
+                distinct: false,
+                filter: None,
+                ..
+            }) => decomposed.add_builtin(fun, args)?,
+            Expr::AggregateUDF {
+                fun,
+                args,
+                filter: None,
+            } => {
+                let func = udafs?.get(&fun.name)?;
+                decomposed.add_udaf(func, args, input_schema)?
+            }
+            _ => return None,
+        };
+        decomposed.output_exprs.push(output);
+    }
+    Some(decomposed)
+}
+
+impl DecomposedAggregate {
+    /// Decomposes a built-in aggregate function, returns the expression computing its value
+    /// from the final aggregation.
+    fn add_builtin(
+        &mut self,
+        fun: &aggregate_function::AggregateFunction,
+        args: &[Expr],
+    ) -> Option<Expr> {
+        if args.len() != 1 {
+            return None;
+        }
+        let arg = args[0].clone();
+
+        let output = match fun {
+            aggregate_function::AggregateFunction::Count => {
+                let state = self.add_partial(count(arg));
+                // No partial state at all if every region is pruned, `COUNT` is 0 then.
+                coalesce(vec![self.add_final(sum(state)), lit(0_i64)])
+            }
+            aggregate_function::AggregateFunction::Sum => {
+                let state = self.add_partial(sum(arg));
+                self.add_final(sum(state))
+            }
+            aggregate_function::AggregateFunction::Min => {
+                let state = self.add_partial(min(arg));
+                self.add_final(min(state))
+            }
+            aggregate_function::AggregateFunction::Max => {
+                let state = self.add_partial(max(arg));
+                self.add_final(max(state))
+            }
+            aggregate_function::AggregateFunction::Avg => {
+                let sum_state = self.add_partial(sum(arg.clone()));
+                let count_state = self.add_partial(count(arg));
+                let sum = self.add_final(sum(sum_state));
+                let count = self.add_final(sum(count_state));
+                cast(sum, DataType::Float64) / cast(count, DataType::Float64)
+            }
+            _ => return None,
+        };
+        Some(output)
+    }
+
+    /// Decomposes a UDAF supporting distributed aggregation into its partial stage, which
+    /// outputs the serialized states of the accumulators, and its merge stage. Returns the
+    /// column of the output of the merge stage.
+    fn add_udaf(
+        &mut self,
+        func: &AggregateFunctionMeta,
+        args: &[Expr],
+        input_schema: &DFSchema,
+    ) -> Option<Expr> {
+        let creator = func.create();
+        if !creator.supports_distributed() {
+            return None;
+        }
+        // The merge stage only takes the states, so its accumulators are created with the
+        // input types of the original aggregation.
+        let input_types = args
+            .iter()
+            .map(|arg| {
+                let data_type = arg.get_type(input_schema).ok()?;
+                ConcreteDataType::try_from(&data_type).ok()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        creator.set_input_types(input_types).ok()?;
+
+        let name = func.name();
+        let partial = create_partial_aggregate_function(&name, func.args_count(), func.create());
+        let state = self.add_partial(Expr::AggregateUDF {
+            fun: Arc::new(partial.into()),
+            args: args.to_vec(),
+            filter: None,
+        });
+        let merge = create_merge_aggregate_function(&name, creator);
+        Some(self.add_final(Expr::AggregateUDF {
+            fun: Arc::new(merge.into()),
+            args: vec![state],
+            filter: None,
+        }))
+    }
+
+    /// Adds a partial aggregate expression, returns the column of its output state.
+    fn add_partial(&mut self, expr: Expr) -> Expr {
+        let name = format!("__partial_state_{}", self.partial_exprs.len());
+        self.partial_exprs.push(expr.alias(&name));
+        Expr::Column(Column::from_name(name))
+    }
+
+    /// Adds a final aggregate expression, returns the column of its output.
+    fn add_final(&mut self, expr: Expr) -> Expr {
+        let name = format!("__final_state_{}", self.final_exprs.len());
+        self.final_exprs.push(expr.alias(&name));
+        Expr::Column(Column::from_name(name))
+    }
+}
+
+/// A leaf node that scans a table and evaluates the partial stage of an aggregation, by
+/// [`Table::scan_partial_aggregate`](table::Table::scan_partial_aggregate).
+#[derive(Clone)]
+pub struct TableAggregateScan {
+    table_name: String,
+    table: TableRef,
+    projection: Option<Vec<usize>>,
+    /// Filters, group by and aggregate expressions are column-unqualified, so that they can be
+    /// planned against the table scan on the other side regardless of its table reference.
+    filters: Vec<Expr>,
+    group_expr: Vec<Expr>,
+    aggr_expr: Vec<Expr>,
+    schema: DFSchemaRef,
+}
+
+impl TableAggregateScan {
+    pub async fn to_execution_plan(&self) -> DfResult<Arc<dyn ExecutionPlan>> {
+        let schema = Schema::try_from(self.schema.clone())
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+        let aggregate = PartialAggregate {
+            group_expr: self.group_expr.iter().cloned().map(Into::into).collect(),
+            aggr_expr: self.aggr_expr.iter().cloned().map(Into::into).collect(),
+            schema: Arc::new(schema),
+        };
+        let filters = self
+            .filters
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+
+        let plan = self
+            .table
+            .scan_partial_aggregate(self.projection.as_ref(), &filters, &aggregate)
+            .await?;
+        Ok(Arc::new(DfPhysicalPlanAdapter(plan)))
+    }
+}
+
+impl fmt::Debug for TableAggregateScan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TableAggregateScan")
+            .field("table_name", &self.table_name)
+            .field("projection", &self.projection)
+            .field("filters", &self.filters)
+            .field("group_expr", &self.group_expr)
+            .field("aggr_expr", &self.aggr_expr)
+            .finish()
+    }
+}
+
+impl PartialEq for TableAggregateScan {
+    fn eq(&self, other: &Self) -> bool {
+        self.table_name == other.table_name
+            && self.projection == other.projection
+            && self.filters == other.filters
+            && self.group_expr == other.group_expr
+            && self.aggr_expr == other.aggr_expr
+            && self.schema == other.schema
+    }
+}
+
+impl Eq for TableAggregateScan {}
+
+impl Hash for TableAggregateScan {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.table_name.hash(state);
+        self.projection.hash(state);
+        self.filters.hash(state);
+        self.group_expr.hash(state);
+        self.aggr_expr.hash(state);
+        self.schema.hash(state);
+    }
+}
+
+impl UserDefinedLogicalNodeCore for TableAggregateScan {
+    fn name(&self) -> &str {
+        "TableAggregateScan"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TableAggregateScan: table={}, groupBy={:?}, aggr={:?}, filters={:?}",
+            self.table_name, self.group_expr, self.aggr_expr, self.filters
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], _inputs: &[LogicalPlan]) -> Self {
+        self.clone()
+    }
+}
+
+/// A leaf node that scans a table and keeps the top-k rows of each place the data are stored,
+/// by [`Table::scan_topk`](table::Table::scan_topk).
+#[derive(Clone)]
+pub struct TableTopKScan {
+    table_name: String,
+    table: TableRef,
+    projection: Option<Vec<usize>>,
+    /// Filters and sort expressions are column-unqualified, like the ones of
+    /// [TableAggregateScan].
+    filters: Vec<Expr>,
+    sort_expr: Vec<Expr>,
+    fetch: usize,
+    schema: DFSchemaRef,
+}
+
+impl TableTopKScan {
+    pub async fn to_execution_plan(&self) -> DfResult<Arc<dyn ExecutionPlan>> {
+        let topk = PartialTopK {
+            sort_expr: self.sort_expr.iter().cloned().map(Into::into).collect(),
+            fetch: self.fetch,
+        };
+        let filters = self
+            .filters
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+
+        let plan = self
+            .table
+            .scan_topk(self.projection.as_ref(), &filters, &topk)
+            .await?;
+        Ok(Arc::new(DfPhysicalPlanAdapter(plan)))
+    }
+}
+
+impl fmt::Debug for TableTopKScan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TableTopKScan")
+            .field("table_name", &self.table_name)
+            .field("projection", &self.projection)
+            .field("filters", &self.filters)
+            .field("sort_expr", &self.sort_expr)
+            .field("fetch", &self.fetch)
+            .finish()
+    }
+}
+
+impl PartialEq for TableTopKScan {
+    fn eq(&self, other: &Self) -> bool {
+        self.table_name == other.table_name
+            && self.projection == other.projection
+            && self.filters == other.filters
+            && self.sort_expr == other.sort_expr
+            && self.fetch == other.fetch
+            && self.schema == other.schema
+    }
+}
+
+impl Eq for TableTopKScan {}
+
+impl Hash for TableTopKScan {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.table_name.hash(state);
+        self.projection.hash(state);
+        self.filters.hash(state);
+        self.sort_expr.hash(state);
+        self.fetch.hash(state);
+        self.schema.hash(state);
+    }
+}
+
+impl UserDefinedLogicalNodeCore for TableTopKScan {
+    fn name(&self) -> &str {
+        "TableTopKScan"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TableTopKScan: table={}, sort={:?}, fetch={}, filters={:?}",
+            self.table_name, self.sort_expr, self.fetch, self.filters
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], _inputs: &[LogicalPlan]) -> Self {
+        self.clone()
+    }
+}
+
+/// Plans [TableAggregateScan] and [TableTopKScan] into the physical plans given by the scanned
+/// tables.
+pub struct DistExtensionPlanner;
+
+#[async_trait]
+impl ExtensionPlanner for DistExtensionPlanner {
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        _session_state: &SessionState,
+    ) -> DfResult<Option<Arc<dyn ExecutionPlan>>> {
+        if let Some(node) = node.as_any().downcast_ref::<TableAggregateScan>() {
+            return node.to_execution_plan().await.map(Some);
+        }
+        if let Some(node) = node.as_any().downcast_ref::<TableTopKScan>() {
+            return node.to_execution_plan().await.map(Some);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_function::scalars::FUNCTION_REGISTRY;
+    use common_query::logical_plan::{create_aggregate_function, Expr as TableExpr};
+    use common_query::physical_plan::PhysicalPlanRef;
+    use datafusion_expr::{avg, col, count_distinct, LogicalPlanBuilder};
+    use datafusion_optimizer::OptimizerContext;
+    use datatypes::schema::SchemaRef;
+    use table::metadata::TableInfoRef;
+    use table::test_util::MemTable;
+
+    use super::*;
+
+    /// A [MemTable] that claims to support aggregate pushdown.
+    struct PushdownTable {
+        inner: TableRef,
+    }
+
+    #[async_trait]
+    impl table::Table for PushdownTable {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.inner.schema()
+        }
+
+        fn table_info(&self) -> TableInfoRef {
+            self.inner.table_info()
+        }
+
+        async fn scan(
+            &self,
+            projection: Option<&Vec<usize>>,
+            filters: &[TableExpr],
+            limit: Option<usize>,
+        ) -> table::Result<PhysicalPlanRef> {
+            self.inner.scan(projection, filters, limit).await
+        }
+
+        fn supports_aggregate_pushdown(&self) -> bool {
+            true
+        }
+
+        fn supports_udaf_pushdown(&self) -> bool {
+            true
+        }
+
+        async fn scan_partial_aggregate(
+            &self,
+            _projection: Option<&Vec<usize>>,
+            _filters: &[TableExpr],
+            _aggregate: &PartialAggregate,
+        ) -> table::Result<PhysicalPlanRef> {
+            unimplemented!()
+        }
+    }
+
+    fn table_scan() -> LogicalPlanBuilder {
+        let table = Arc::new(PushdownTable {
+            inner: Arc::new(MemTable::default_numbers_table()),
+        });
+        let source = Arc::new(DefaultTableSource::new(Arc::new(
+            DfTableProviderAdapter::new(table),
+        )));
+        LogicalPlanBuilder::scan("numbers", source, None).unwrap()
+    }
+
+    fn optimize(plan: &LogicalPlan) -> Option<LogicalPlan> {
+        let aggregate_functions = ["approx_percentile", "percentile"]
+            .into_iter()
+            .map(|name| {
+                let func = FUNCTION_REGISTRY.get_aggr_function(name).unwrap();
+                (name.to_string(), func)
+            })
+            .collect();
+        AggregatePushdownRule::new(Arc::new(RwLock::new(aggregate_functions)))
+            .try_optimize(plan, &OptimizerContext::new())
+            .unwrap()
+    }
+
+    fn udaf(name: &str, args: Vec<Expr>) -> Expr {
+        let func = FUNCTION_REGISTRY.get_aggr_function(name).unwrap();
+        let udaf = create_aggregate_function(func.name(), func.args_count(), func.create());
+        Expr::AggregateUDF {
+            fun: Arc::new(udaf.into()),
+            args,
+            filter: None,
+        }
+    }
+
+    fn udaf_name(expr: &Expr) -> &str {
+        let Expr::Alias(expr, _) = expr else { unreachable!() };
+        let Expr::AggregateUDF { fun, .. } = expr.as_ref() else { unreachable!() };
+        &fun.name
+    }
+
+    #[test]
+    fn test_pushdown_decomposable_aggregate() {
+        let plan = table_scan()
+            .filter(col("uint32s").gt(lit(10_u32)))
+            .unwrap()
+            .aggregate(
+                vec![col("uint32s")],
+                vec![count(col("uint32s")), avg(col("uint32s"))],
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let optimized = optimize(&plan).unwrap();
+        assert_eq!(
+            plan.schema().field_names(),
+            optimized.schema().field_names()
+        );
+
+        let LogicalPlan::Projection(projection) = &optimized else { unreachable!() };
+        let LogicalPlan::Aggregate(final_aggregate) = projection.input.as_ref() else {
+            unreachable!()
+        };
+        assert_eq!(final_aggregate.aggr_expr.len(), 3);
+        let LogicalPlan::Extension(extension) = final_aggregate.input.as_ref() else {
+            unreachable!()
+        };
+        let scan = extension
+            .node
+            .as_any()
+            .downcast_ref::<TableAggregateScan>()
+            .unwrap();
+        assert_eq!(scan.group_expr, vec![col("uint32s")]);
+        assert_eq!(scan.filters, vec![col("uint32s").gt(lit(10_u32))]);
+        assert_eq!(
+            scan.aggr_expr,
+            vec![
+                count(col("uint32s")).alias("__partial_state_0"),
+                sum(col("uint32s")).alias("__partial_state_1"),
+                count(col("uint32s")).alias("__partial_state_2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pushdown_distributed_udaf() {
+        let args = vec![col("uint32s"), lit(50.0)];
+        let plan = table_scan()
+            .aggregate(
+                vec![col("uint32s")],
+                vec![count(col("uint32s")), udaf("approx_percentile", args)],
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let optimized = optimize(&plan).unwrap();
+        assert_eq!(
+            plan.schema().field_names(),
+            optimized.schema().field_names()
+        );
+
+        let LogicalPlan::Projection(projection) = &optimized else { unreachable!() };
+        let LogicalPlan::Aggregate(final_aggregate) = projection.input.as_ref() else {
+            unreachable!()
+        };
+        assert_eq!(
+            "__approx_percentile_merge",
+            udaf_name(&final_aggregate.aggr_expr[1])
+        );
+        let LogicalPlan::Extension(extension) = final_aggregate.input.as_ref() else {
+            unreachable!()
+        };
+        let scan = extension
+            .node
+            .as_any()
+            .downcast_ref::<TableAggregateScan>()
+            .unwrap();
+        assert_eq!("__approx_percentile_partial", udaf_name(&scan.aggr_expr[1]));
+        assert_eq!(
+            &DataType::Binary,
+            scan.schema
+                .field_with_unqualified_name("__partial_state_1")
+                .unwrap()
+                .data_type()
+        );
+    }
+
+    #[test]
+    fn test_not_pushdown_udaf() {
+        // `percentile` doesn't support distributed aggregation.
+        let args = vec![col("uint32s"), lit(50.0)];
+        let plan = table_scan()
+            .aggregate(
+                vec![col("uint32s")],
+                vec![count(col("uint32s")), udaf("percentile", args)],
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(optimize(&plan).is_none());
+    }
+
+    #[test]
+    fn test_not_pushdown_distinct_aggregate() {
+        let plan = table_scan()
+            .aggregate(
+                vec![col("uint32s")],
+                vec![count(col("uint32s")), count_distinct(col("uint32s"))],
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(optimize(&plan).is_none());
+    }
+
+    #[test]
+    fn test_not_pushdown_unsupported_table() {
+        let source = Arc::new(DefaultTableSource::new(Arc::new(
+            DfTableProviderAdapter::new(Arc::new(MemTable::default_numbers_table())),
+        )));
+        let plan = LogicalPlanBuilder::scan("numbers", source, None)
+            .unwrap()
+            .aggregate(vec![col("uint32s")], vec![count(col("uint32s"))])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(optimize(&plan).is_none());
+    }
+}
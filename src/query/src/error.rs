@@ -88,6 +88,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to parse duration `{}`: {}", raw, msg))]
+    ParseDuration {
+        raw: String,
+        msg: String,
+        location: Location,
+    },
+
     #[snafu(display("DataFusion error: {}", source))]
     DataFusion {
         source: DataFusionError,
@@ -113,6 +120,24 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Delete on table '{table_name}' matches more than {limit} rows, restrict the WHERE clause to primary key and time index columns, or delete a smaller time range"
+    ))]
+    DeleteRowLimitExceeded {
+        table_name: String,
+        limit: usize,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Query on table '{table_name}' reads the time range before its retention boundary {boundary}, whose rows are expired by the ttl of the table"
+    ))]
+    ExpiredTimeRange {
+        table_name: String,
+        boundary: String,
+        location: Location,
+    },
+
     #[snafu(display("Failed to convert value to sql value: {}", value))]
     ConvertSqlValue {
         value: Value,
@@ -174,6 +199,16 @@ pub enum Error {
         #[snafu(backtrace)]
         source: datatypes::error::Error,
     },
+
+    #[snafu(display("Aggregate function {} already exists", name))]
+    AggregateFunctionExists { name: String, location: Location },
+
+    #[snafu(display("Invalid aggregate function name {}: {}", name, reason))]
+    InvalidAggregateFunctionName {
+        name: String,
+        reason: String,
+        location: Location,
+    },
 }
 
 impl ErrorExt for Error {
@@ -188,10 +223,15 @@ impl ErrorExt for Error {
             | TableNotFound { .. }
             | ParseTimestamp { .. }
             | ParseFloat { .. }
+            | ParseDuration { .. }
             | MissingRequiredField { .. }
             | BuildRegex { .. }
             | UnsupportedFileFormat { .. }
-            | ConvertSchema { .. } => StatusCode::InvalidArguments,
+            | DeleteRowLimitExceeded { .. }
+            | ExpiredTimeRange { .. }
+            | ConvertSchema { .. }
+            | AggregateFunctionExists { .. }
+            | InvalidAggregateFunctionName { .. } => StatusCode::InvalidArguments,
 
             BuildBackend { .. } | ListObjects { .. } => StatusCode::StorageUnavailable,
 
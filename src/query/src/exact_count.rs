@@ -0,0 +1,426 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Answers `SELECT COUNT(*) FROM t` from the row count of the table kept in its metadata,
+//! instead of scanning all the rows.
+//!
+//! [ExactCountRule] replaces such an aggregation with an [ExactCount] node if the table knows
+//! its exact row count ([Table::exact_row_count](table::Table::exact_row_count)). The row count
+//! is asked again on execution, and the original aggregation is executed if the table can't
+//! tell it anymore (e.g. some rows were deleted after planning).
+
+use std::any::Any;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::datasource::DefaultTableSource;
+use datafusion::error::Result as DfResult;
+use datafusion::execution::context::{SessionState, TaskContext};
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::planner::ExtensionPlanner;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalPlanner, SendableRecordBatchStream,
+    Statistics,
+};
+use datafusion_common::{DFSchemaRef, ScalarValue};
+use datafusion_expr::expr::AggregateFunction;
+use datafusion_expr::{
+    aggregate_function, Expr, Extension, LogicalPlan, UserDefinedLogicalNode,
+    UserDefinedLogicalNodeCore,
+};
+use datafusion_optimizer::optimizer::ApplyOrder;
+use datafusion_optimizer::{OptimizerConfig, OptimizerRule};
+use datatypes::arrow::compute;
+use datatypes::arrow::datatypes::SchemaRef;
+use datatypes::arrow::record_batch::RecordBatch;
+use table::table::adapter::DfTableProviderAdapter;
+use table::TableRef;
+
+/// Rewrites `Aggregate <- TableScan` into [ExactCount], if the aggregation has no group by and
+/// only counts the rows (`COUNT(*)` or `COUNT(1)`), the scan has neither filters nor limit, and
+/// the scanned table knows its exact row count.
+pub struct ExactCountRule;
+
+impl OptimizerRule for ExactCountRule {
+    fn try_optimize(
+        &self,
+        plan: &LogicalPlan,
+        _config: &dyn OptimizerConfig,
+    ) -> DfResult<Option<LogicalPlan>> {
+        let LogicalPlan::Aggregate(aggregate) = plan else { return Ok(None) };
+        if !aggregate.group_expr.is_empty()
+            || aggregate.aggr_expr.is_empty()
+            || !aggregate.aggr_expr.iter().all(is_count_rows)
+        {
+            return Ok(None);
+        }
+
+        let LogicalPlan::TableScan(table_scan) = aggregate.input.as_ref() else {
+            return Ok(None);
+        };
+        if !table_scan.filters.is_empty() || table_scan.fetch.is_some() {
+            return Ok(None);
+        }
+        let Some(table) = table_scan
+            .source
+            .as_any()
+            .downcast_ref::<DefaultTableSource>()
+            .and_then(|source| {
+                source
+                    .table_provider
+                    .as_any()
+                    .downcast_ref::<DfTableProviderAdapter>()
+            })
+            .map(|adapter| adapter.table())
+        else {
+            return Ok(None);
+        };
+        if table.exact_row_count().is_none() {
+            return Ok(None);
+        }
+
+        let node = ExactCount {
+            table_name: table_scan.table_name.to_string(),
+            table,
+            aggr_expr: aggregate.aggr_expr.clone(),
+            aggregate: plan.clone(),
+            schema: aggregate.schema.clone(),
+        };
+        Ok(Some(LogicalPlan::Extension(Extension {
+            node: Arc::new(node),
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "ExactCountRule"
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::TopDown)
+    }
+}
+
+/// Whether the aggregate expression counts all the rows, which is how `COUNT(*)` is planned.
+fn is_count_rows(expr: &Expr) -> bool {
+    match expr {
+        Expr::Alias(expr, _) => is_count_rows(expr),
+        Expr::AggregateFunction(AggregateFunction {
+            fun: aggregate_function::AggregateFunction::Count,
+            args,
+            distinct: false,
+            filter: None,
+            ..
+        }) => matches!(args.as_slice(), [Expr::Literal(value)] if !value.is_null()),
+        _ => false,
+    }
+}
+
+/// Counts the rows of a table by its exact row count.
+#[derive(Clone)]
+pub struct ExactCount {
+    table_name: String,
+    table: TableRef,
+    aggr_expr: Vec<Expr>,
+    /// The original aggregation, executed if the row count isn't exact on execution. It's not
+    /// an input of this node, so the optimizer won't rewrite it into another [ExactCount].
+    aggregate: LogicalPlan,
+    schema: DFSchemaRef,
+}
+
+impl fmt::Debug for ExactCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExactCount")
+            .field("table_name", &self.table_name)
+            .field("aggr_expr", &self.aggr_expr)
+            .finish()
+    }
+}
+
+impl PartialEq for ExactCount {
+    fn eq(&self, other: &Self) -> bool {
+        self.table_name == other.table_name
+            && self.aggr_expr == other.aggr_expr
+            && self.schema == other.schema
+    }
+}
+
+impl Eq for ExactCount {}
+
+impl Hash for ExactCount {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.table_name.hash(state);
+        self.aggr_expr.hash(state);
+        self.schema.hash(state);
+    }
+}
+
+impl UserDefinedLogicalNodeCore for ExactCount {
+    fn name(&self) -> &str {
+        "ExactCount"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ExactCount: table={}, aggr={:?}",
+            self.table_name, self.aggr_expr
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], _inputs: &[LogicalPlan]) -> Self {
+        self.clone()
+    }
+}
+
+/// Outputs the exact row count of the table for every count, or the output of the original
+/// aggregation if the table doesn't know its exact row count anymore.
+pub struct ExactCountExec {
+    table_name: String,
+    table: TableRef,
+    schema: SchemaRef,
+    input: Arc<dyn ExecutionPlan>,
+}
+
+impl fmt::Debug for ExactCountExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExactCountExec")
+            .field("table_name", &self.table_name)
+            .field("input", &self.input)
+            .finish()
+    }
+}
+
+impl ExecutionPlan for ExactCountExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[datafusion::physical_expr::PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        Ok(Arc::new(Self {
+            table_name: self.table_name.clone(),
+            table: self.table.clone(),
+            schema: self.schema.clone(),
+            input: children[0].clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        _partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DfResult<SendableRecordBatchStream> {
+        let Some(rows) = self.table.exact_row_count() else {
+            let input: Arc<dyn ExecutionPlan> =
+                if self.input.output_partitioning().partition_count() > 1 {
+                    Arc::new(CoalescePartitionsExec::new(self.input.clone()))
+                } else {
+                    self.input.clone()
+                };
+            return input.execute(0, context);
+        };
+
+        let count = ScalarValue::Int64(Some(rows as i64)).to_array();
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| compute::cast(&count, field.data_type()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            futures::stream::once(async move { Ok(batch) }),
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "ExactCountExec: table={}", self.table_name)
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+/// Plans [ExactCount] into [ExactCountExec], with the original aggregation as its input.
+pub struct ExactCountExtensionPlanner;
+
+#[async_trait]
+impl ExtensionPlanner for ExactCountExtensionPlanner {
+    async fn plan_extension(
+        &self,
+        planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        _physical_inputs: &[Arc<dyn ExecutionPlan>],
+        session_state: &SessionState,
+    ) -> DfResult<Option<Arc<dyn ExecutionPlan>>> {
+        let Some(node) = node.as_any().downcast_ref::<ExactCount>() else {
+            return Ok(None);
+        };
+        let input = planner
+            .create_physical_plan(&node.aggregate, session_state)
+            .await?;
+        Ok(Some(Arc::new(ExactCountExec {
+            table_name: node.table_name.clone(),
+            table: node.table.clone(),
+            schema: Arc::new(node.schema.as_ref().into()),
+            input,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_query::logical_plan::Expr as TableExpr;
+    use common_query::physical_plan::PhysicalPlanRef;
+    use datafusion_expr::{col, count, lit, LogicalPlanBuilder};
+    use datafusion_optimizer::OptimizerContext;
+    use datatypes::schema::SchemaRef;
+    use table::metadata::TableInfoRef;
+    use table::test_util::MemTable;
+
+    use super::*;
+
+    /// A [MemTable] reporting the given exact row count.
+    struct CountedTable {
+        inner: TableRef,
+        exact_row_count: Option<u64>,
+    }
+
+    #[async_trait]
+    impl table::Table for CountedTable {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn schema(&self) -> SchemaRef {
+            self.inner.schema()
+        }
+
+        fn table_info(&self) -> TableInfoRef {
+            self.inner.table_info()
+        }
+
+        async fn scan(
+            &self,
+            projection: Option<&Vec<usize>>,
+            filters: &[TableExpr],
+            limit: Option<usize>,
+        ) -> table::Result<PhysicalPlanRef> {
+            self.inner.scan(projection, filters, limit).await
+        }
+
+        fn exact_row_count(&self) -> Option<u64> {
+            self.exact_row_count
+        }
+    }
+
+    fn table_scan(exact_row_count: Option<u64>) -> LogicalPlanBuilder {
+        let table = Arc::new(CountedTable {
+            inner: Arc::new(MemTable::default_numbers_table()),
+            exact_row_count,
+        });
+        let source = Arc::new(DefaultTableSource::new(Arc::new(
+            DfTableProviderAdapter::new(table),
+        )));
+        LogicalPlanBuilder::scan("numbers", source, None).unwrap()
+    }
+
+    fn optimize(plan: &LogicalPlan) -> Option<LogicalPlan> {
+        ExactCountRule
+            .try_optimize(plan, &OptimizerContext::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_exact_count() {
+        let plan = table_scan(Some(100))
+            .aggregate(Vec::<Expr>::new(), vec![count(lit(1_u8))])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let optimized = optimize(&plan).unwrap();
+        assert_eq!(plan.schema(), optimized.schema());
+        let LogicalPlan::Extension(extension) = &optimized else { unreachable!() };
+        let node = extension
+            .node
+            .as_any()
+            .downcast_ref::<ExactCount>()
+            .unwrap();
+        assert_eq!(node.aggregate, plan);
+        assert_eq!(
+            "ExactCount: table=numbers, aggr=[COUNT(UInt8(1))]",
+            format!("{}", optimized.display())
+        );
+    }
+
+    #[test]
+    fn test_not_exact_count() {
+        let plans = [
+            // Counts of a column skip nulls.
+            table_scan(Some(100)).aggregate(Vec::<Expr>::new(), vec![count(col("uint32s"))]),
+            table_scan(Some(100)).aggregate(vec![col("uint32s")], vec![count(lit(1_u8))]),
+            table_scan(Some(100))
+                .filter(col("uint32s").gt(lit(10_u32)))
+                .unwrap()
+                .aggregate(Vec::<Expr>::new(), vec![count(lit(1_u8))]),
+            table_scan(None).aggregate(Vec::<Expr>::new(), vec![count(lit(1_u8))]),
+        ];
+        for plan in plans {
+            let plan = plan.unwrap().build().unwrap();
+            assert!(optimize(&plan).is_none(), "{plan:?}");
+        }
+    }
+}
@@ -0,0 +1,905 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Gap filling for time series grouped into regular time buckets, like
+//!
+//! ```sql
+//! SELECT time_bucket_gapfill(INTERVAL '1 minute', ts), host, locf(avg(cpu)), interpolate(max(cpu))
+//! FROM monitor
+//! WHERE ts >= '2023-01-01 00:00:00' AND ts < '2023-01-01 01:00:00'
+//! GROUP BY 1, 2
+//! ```
+//!
+//! `time_bucket_gapfill` groups the rows into buckets like `date_bin`. [GapFillRule] then
+//! puts a [GapFill] node on top of the aggregation, which generates the buckets missing in
+//! every group within the time bounds given by the `WHERE` clause. Values of the generated
+//! buckets are null, unless the aggregation is wrapped by `locf()` (carries the last
+//! observation forward) or `interpolate()` (linear interpolation between the neighbours).
+
+use std::any::Any;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datafusion::config::ConfigOptions;
+use datafusion::error::Result as DfResult;
+use datafusion::execution::context::{SessionState, TaskContext};
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::planner::ExtensionPlanner;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalPlanner, SendableRecordBatchStream,
+    Statistics,
+};
+use datafusion_common::tree_node::{Transformed, TreeNode, VisitRecursion};
+use datafusion_common::{Column, DFSchemaRef, DataFusionError, ScalarValue};
+use datafusion_expr::utils::split_conjunction;
+use datafusion_expr::{
+    Aggregate, Between, BinaryExpr, ColumnarValue, Expr, Extension, LogicalPlan, Operator,
+    Projection, ScalarUDF, Signature, UserDefinedLogicalNode, UserDefinedLogicalNodeCore,
+    Volatility,
+};
+use datafusion_optimizer::analyzer::AnalyzerRule;
+use datatypes::arrow::array::{Array, ArrayRef, Int64Array};
+use datatypes::arrow::compute;
+use datatypes::arrow::datatypes::{
+    DataType, IntervalDayTimeType, IntervalMonthDayNanoType, SchemaRef, TimeUnit,
+};
+use datatypes::arrow::record_batch::RecordBatch;
+use futures::TryStreamExt;
+
+pub const TIME_BUCKET_GAPFILL: &str = "time_bucket_gapfill";
+pub const LOCF: &str = "locf";
+pub const INTERPOLATE: &str = "interpolate";
+
+/// Max number of buckets a group may be filled to.
+const MAX_BUCKETS_PER_GROUP: i64 = 100_000;
+
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+const NANOS_PER_MILLI: i64 = 1_000_000;
+
+/// `time_bucket_gapfill(interval, ts)` truncates `ts` to the start of the `interval` long
+/// bucket it falls in, counting from the unix epoch.
+pub fn time_bucket_gapfill_udf() -> ScalarUDF {
+    ScalarUDF {
+        name: TIME_BUCKET_GAPFILL.to_string(),
+        signature: Signature::any(2, Volatility::Immutable),
+        return_type: Arc::new(|args| Ok(Arc::new(args[1].clone()))),
+        fun: Arc::new(time_bucket),
+    }
+}
+
+/// `locf(value)` marks an aggregation whose missing buckets are filled with the last
+/// observation. It returns `value` as is.
+pub fn locf_udf() -> ScalarUDF {
+    fill_marker_udf(LOCF)
+}
+
+/// `interpolate(value)` marks an aggregation whose missing buckets are filled by linear
+/// interpolation. It returns `value` as is.
+pub fn interpolate_udf() -> ScalarUDF {
+    fill_marker_udf(INTERPOLATE)
+}
+
+fn fill_marker_udf(name: &str) -> ScalarUDF {
+    ScalarUDF {
+        name: name.to_string(),
+        signature: Signature::any(1, Volatility::Immutable),
+        return_type: Arc::new(|args| Ok(Arc::new(args[0].clone()))),
+        fun: Arc::new(|args| Ok(args[0].clone())),
+    }
+}
+
+fn time_bucket(args: &[ColumnarValue]) -> DfResult<ColumnarValue> {
+    let ColumnarValue::Scalar(interval) = &args[0] else {
+        return Err(DataFusionError::Execution(format!(
+            "The first argument of {TIME_BUCKET_GAPFILL} must be a constant interval"
+        )));
+    };
+    let stride_nanos = interval_nanos(interval)?;
+
+    let (ts, is_scalar) = match &args[1] {
+        ColumnarValue::Array(array) => (array.clone(), false),
+        ColumnarValue::Scalar(scalar) => (scalar.to_array(), true),
+    };
+    let DataType::Timestamp(unit, _) = ts.data_type() else {
+        return Err(DataFusionError::Execution(format!(
+            "The second argument of {TIME_BUCKET_GAPFILL} must be a timestamp, found {}",
+            ts.data_type()
+        )));
+    };
+    let stride = nanos_to_unit(stride_nanos, unit)?;
+
+    let values = compute::cast(&ts, &DataType::Int64)?;
+    let values = values.as_any().downcast_ref::<Int64Array>().unwrap();
+    let buckets = values
+        .iter()
+        .map(|v| v.map(|v| v.div_euclid(stride) * stride))
+        .collect::<Int64Array>();
+    let buckets = compute::cast(&buckets, ts.data_type())?;
+
+    if is_scalar {
+        ScalarValue::try_from_array(&buckets, 0).map(ColumnarValue::Scalar)
+    } else {
+        Ok(ColumnarValue::Array(buckets))
+    }
+}
+
+fn interval_nanos(interval: &ScalarValue) -> DfResult<i64> {
+    let nanos = match interval {
+        ScalarValue::IntervalDayTime(Some(v)) => {
+            let (days, millis) = IntervalDayTimeType::to_parts(*v);
+            days as i64 * NANOS_PER_DAY + millis as i64 * NANOS_PER_MILLI
+        }
+        ScalarValue::IntervalMonthDayNano(Some(v)) => {
+            let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(*v);
+            if months != 0 {
+                return Err(DataFusionError::Execution(format!(
+                    "{TIME_BUCKET_GAPFILL} doesn't support intervals with months"
+                )));
+            }
+            days as i64 * NANOS_PER_DAY + nanos
+        }
+        _ => {
+            return Err(DataFusionError::Execution(format!(
+                "{TIME_BUCKET_GAPFILL} expects a fixed length interval, found {interval:?}"
+            )))
+        }
+    };
+    if nanos <= 0 {
+        return Err(DataFusionError::Execution(format!(
+            "{TIME_BUCKET_GAPFILL} expects a positive interval, found {interval:?}"
+        )));
+    }
+    Ok(nanos)
+}
+
+fn nanos_per_unit(unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => 1_000_000_000,
+        TimeUnit::Millisecond => 1_000_000,
+        TimeUnit::Microsecond => 1_000,
+        TimeUnit::Nanosecond => 1,
+    }
+}
+
+fn nanos_to_unit(stride_nanos: i64, unit: &TimeUnit) -> DfResult<i64> {
+    let stride = stride_nanos / nanos_per_unit(unit);
+    if stride == 0 {
+        return Err(DataFusionError::Execution(format!(
+            "The interval of {TIME_BUCKET_GAPFILL} is too small for timestamps in {unit:?}"
+        )));
+    }
+    Ok(stride)
+}
+
+/// How to fill the value of a generated bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FillStrategy {
+    /// Null.
+    Null,
+    /// The last non-null value before the bucket.
+    Locf,
+    /// Linear interpolation between the non-null values around the bucket.
+    Interpolate,
+}
+
+/// Puts a [GapFill] node between `Projection <- Aggregate`, if `time_bucket_gapfill` is one
+/// of the group by expressions, and strips `locf()` and `interpolate()` from the projection.
+///
+/// The time bounds of buckets are inferred from the filters on the bucketed time column
+/// below the aggregation. It's an error if either the lower or the upper bound is missing.
+pub struct GapFillRule;
+
+impl AnalyzerRule for GapFillRule {
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> DfResult<LogicalPlan> {
+        plan.transform_up(&|plan| {
+            let LogicalPlan::Projection(projection) = &plan else {
+                return Ok(Transformed::No(plan));
+            };
+            let LogicalPlan::Aggregate(aggregate) = projection.input.as_ref() else {
+                return Ok(Transformed::No(plan));
+            };
+            match Self::rewrite(projection, aggregate)? {
+                Some(plan) => Ok(Transformed::Yes(plan)),
+                None => Ok(Transformed::No(plan)),
+            }
+        })
+    }
+
+    fn name(&self) -> &str {
+        "GapFillRule"
+    }
+}
+
+impl GapFillRule {
+    fn rewrite(projection: &Projection, aggregate: &Aggregate) -> DfResult<Option<LogicalPlan>> {
+        let Some((time_index, stride_nanos, ts_column)) = aggregate
+            .group_expr
+            .iter()
+            .enumerate()
+            .find_map(|(i, expr)| time_bucket_args(expr).map(|(interval, ts)| (i, interval, ts)))
+        else {
+            return Ok(None);
+        };
+        let Expr::Literal(interval) = stride_nanos else {
+            return Err(DataFusionError::Plan(format!(
+                "The first argument of {TIME_BUCKET_GAPFILL} must be a constant interval"
+            )));
+        };
+        let stride_nanos = interval_nanos(interval)?;
+        let Expr::Column(ts_column) = ts_column else {
+            return Err(DataFusionError::Plan(format!(
+                "The second argument of {TIME_BUCKET_GAPFILL} must be a column"
+            )));
+        };
+
+        let mut bounds = TimeBounds::default();
+        bounds.collect(aggregate.input.as_ref(), ts_column);
+        let (Some(start_nanos), Some(end_nanos)) = (bounds.start, bounds.end) else {
+            return Err(DataFusionError::Plan(format!(
+                "{TIME_BUCKET_GAPFILL} requires both lower and upper bounds of column '{}' in the WHERE clause, like \"{} >= '2023-01-01 00:00:00' AND {} < '2023-01-02 00:00:00'\"",
+                ts_column.name, ts_column.name, ts_column.name
+            )));
+        };
+
+        let group_indices = (0..aggregate.group_expr.len())
+            .filter(|i| *i != time_index)
+            .collect::<Vec<_>>();
+
+        let mut fill_columns = Vec::new();
+        for expr in &projection.expr {
+            collect_fills(expr, &mut fill_columns)?;
+        }
+        let fills = fill_columns
+            .into_iter()
+            .map(|(column, strategy)| Ok((aggregate.schema.index_of_column(&column)?, strategy)))
+            .collect::<DfResult<Vec<_>>>()?;
+
+        let gap_fill = GapFill {
+            time_index,
+            group_indices,
+            fills,
+            stride_nanos,
+            start_nanos,
+            end_nanos,
+            input: LogicalPlan::Aggregate(aggregate.clone()),
+        };
+        let exprs = projection
+            .expr
+            .iter()
+            .map(strip_fill_markers)
+            .collect::<DfResult<Vec<_>>>()?;
+        let plan = Projection::try_new(
+            exprs,
+            Arc::new(LogicalPlan::Extension(Extension {
+                node: Arc::new(gap_fill),
+            })),
+        )?;
+        Ok(Some(LogicalPlan::Projection(plan)))
+    }
+}
+
+fn time_bucket_args(expr: &Expr) -> Option<(&Expr, &Expr)> {
+    match expr {
+        Expr::ScalarUDF { fun, args } if fun.name == TIME_BUCKET_GAPFILL => {
+            Some((&args[0], &args[1]))
+        }
+        Expr::Alias(expr, _) => time_bucket_args(expr),
+        _ => None,
+    }
+}
+
+fn fill_marker(expr: &Expr) -> Option<(FillStrategy, &Expr)> {
+    match expr {
+        Expr::ScalarUDF { fun, args } if fun.name == LOCF => Some((FillStrategy::Locf, &args[0])),
+        Expr::ScalarUDF { fun, args } if fun.name == INTERPOLATE => {
+            Some((FillStrategy::Interpolate, &args[0]))
+        }
+        _ => None,
+    }
+}
+
+/// Collects the columns wrapped by `locf()` or `interpolate()` in `expr`.
+fn collect_fills(expr: &Expr, fills: &mut Vec<(Column, FillStrategy)>) -> DfResult<()> {
+    let mut result = Ok(());
+    expr.apply(&mut |expr| {
+        let Some((strategy, arg)) = fill_marker(expr) else {
+            return Ok(VisitRecursion::Continue);
+        };
+        match arg {
+            Expr::Column(column) => fills.push((column.clone(), strategy)),
+            _ => {
+                result = Err(DataFusionError::Plan(format!(
+                    "{LOCF} and {INTERPOLATE} only accept an aggregate function, found {arg}"
+                )));
+                return Ok(VisitRecursion::Stop);
+            }
+        }
+        Ok(VisitRecursion::Skip)
+    })?;
+    result
+}
+
+/// Replaces `locf(x)` and `interpolate(x)` with `x`, keeping the output name of `expr`.
+fn strip_fill_markers(expr: &Expr) -> DfResult<Expr> {
+    let name = expr.display_name()?;
+    let stripped = expr
+        .clone()
+        .transform_up(&|expr| match fill_marker(&expr) {
+            Some((_, arg)) => Ok(Transformed::Yes(arg.clone())),
+            None => Ok(Transformed::No(expr)),
+        })?;
+    if stripped.display_name()? == name {
+        Ok(stripped)
+    } else {
+        Ok(stripped.alias(name))
+    }
+}
+
+/// Time bounds `[start, end)` in nanoseconds.
+#[derive(Debug, Default)]
+struct TimeBounds {
+    start: Option<i64>,
+    end: Option<i64>,
+}
+
+impl TimeBounds {
+    fn collect(&mut self, plan: &LogicalPlan, ts_column: &Column) {
+        match plan {
+            LogicalPlan::Filter(filter) => {
+                for predicate in split_conjunction(&filter.predicate) {
+                    self.update(predicate, ts_column);
+                }
+                self.collect(filter.input.as_ref(), ts_column);
+            }
+            LogicalPlan::TableScan(scan) => {
+                for filter in &scan.filters {
+                    for predicate in split_conjunction(filter) {
+                        self.update(predicate, ts_column);
+                    }
+                }
+            }
+            LogicalPlan::Projection(_) | LogicalPlan::SubqueryAlias(_) => {
+                for input in plan.inputs() {
+                    self.collect(input, ts_column);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, predicate: &Expr, ts_column: &Column) {
+        match predicate {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let (op, value) = match (left.as_ref(), right.as_ref()) {
+                    (Expr::Column(c), value) if c.name == ts_column.name => (*op, value),
+                    (value, Expr::Column(c)) if c.name == ts_column.name => {
+                        let Some(op) = op.swap() else { return };
+                        (op, value)
+                    }
+                    _ => return,
+                };
+                let Some(value) = literal_nanos(value) else {
+                    return;
+                };
+                match op {
+                    Operator::Gt => self.update_start(value.saturating_add(1)),
+                    Operator::GtEq => self.update_start(value),
+                    Operator::Lt => self.update_end(value),
+                    Operator::LtEq => self.update_end(value.saturating_add(1)),
+                    Operator::Eq => {
+                        self.update_start(value);
+                        self.update_end(value.saturating_add(1));
+                    }
+                    _ => {}
+                }
+            }
+            Expr::Between(Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            }) => {
+                if !matches!(expr.as_ref(), Expr::Column(c) if c.name == ts_column.name) {
+                    return;
+                }
+                if let Some(low) = literal_nanos(low) {
+                    self.update_start(low);
+                }
+                if let Some(high) = literal_nanos(high) {
+                    self.update_end(high.saturating_add(1));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn update_start(&mut self, start: i64) {
+        self.start = Some(self.start.map_or(start, |x| x.max(start)));
+    }
+
+    fn update_end(&mut self, end: i64) {
+        self.end = Some(self.end.map_or(end, |x| x.min(end)));
+    }
+}
+
+/// Returns the nanoseconds of a timestamp literal.
+fn literal_nanos(expr: &Expr) -> Option<i64> {
+    let value = match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Cast(cast) => {
+            let Expr::Literal(value) = cast.expr.as_ref() else {
+                return None;
+            };
+            let array = compute::cast(&value.to_array(), &cast.data_type).ok()?;
+            ScalarValue::try_from_array(&array, 0).ok()?
+        }
+        _ => return None,
+    };
+    match value {
+        ScalarValue::TimestampSecond(Some(v), _) => v.checked_mul(1_000_000_000),
+        ScalarValue::TimestampMillisecond(Some(v), _) => v.checked_mul(1_000_000),
+        ScalarValue::TimestampMicrosecond(Some(v), _) => v.checked_mul(1_000),
+        ScalarValue::TimestampNanosecond(Some(v), _) => Some(v),
+        _ => None,
+    }
+}
+
+/// Generates the missing buckets of every group within `[start, end)`.
+///
+/// The schema is the same as the input's: the bucket column, the other group by columns and
+/// the aggregated values.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct GapFill {
+    /// Index of the bucket column.
+    time_index: usize,
+    /// Indices of the other group by columns.
+    group_indices: Vec<usize>,
+    /// Indices of the columns not filled with null.
+    fills: Vec<(usize, FillStrategy)>,
+    stride_nanos: i64,
+    start_nanos: i64,
+    end_nanos: i64,
+    input: LogicalPlan,
+}
+
+impl UserDefinedLogicalNodeCore for GapFill {
+    fn name(&self) -> &str {
+        "GapFill"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = self
+            .input
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        write!(f, "GapFill: ")?;
+        fmt_gap_fill(
+            f,
+            &names,
+            self.time_index,
+            &self.group_indices,
+            &self.fills,
+            (self.start_nanos, self.end_nanos, self.stride_nanos),
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        assert!(!inputs.is_empty());
+
+        Self {
+            time_index: self.time_index,
+            group_indices: self.group_indices.clone(),
+            fills: self.fills.clone(),
+            stride_nanos: self.stride_nanos,
+            start_nanos: self.start_nanos,
+            end_nanos: self.end_nanos,
+            input: inputs[0].clone(),
+        }
+    }
+}
+
+impl GapFill {
+    pub fn to_execution_plan(&self, exec_input: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        Arc::new(GapFillExec {
+            time_index: self.time_index,
+            group_indices: self.group_indices.clone(),
+            fills: self.fills.clone(),
+            stride_nanos: self.stride_nanos,
+            start_nanos: self.start_nanos,
+            end_nanos: self.end_nanos,
+            input: exec_input,
+            metric: ExecutionPlanMetricsSet::new(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct GapFillExec {
+    /// Index of the bucket column.
+    time_index: usize,
+    /// Indices of the other group by columns.
+    group_indices: Vec<usize>,
+    /// Indices of the columns not filled with null.
+    fills: Vec<(usize, FillStrategy)>,
+    stride_nanos: i64,
+    start_nanos: i64,
+    end_nanos: i64,
+
+    input: Arc<dyn ExecutionPlan>,
+    metric: ExecutionPlanMetricsSet,
+}
+
+impl ExecutionPlan for GapFillExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[datafusion::physical_expr::PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        Ok(Arc::new(Self {
+            time_index: self.time_index,
+            group_indices: self.group_indices.clone(),
+            fills: self.fills.clone(),
+            stride_nanos: self.stride_nanos,
+            start_nanos: self.start_nanos,
+            end_nanos: self.end_nanos,
+            input: children[0].clone(),
+            metric: self.metric.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DfResult<SendableRecordBatchStream> {
+        let baseline_metric = BaselineMetrics::new(&self.metric, partition);
+
+        // Rows of a group may come from any partition of the input.
+        let input: Arc<dyn ExecutionPlan> =
+            if self.input.output_partitioning().partition_count() > 1 {
+                Arc::new(CoalescePartitionsExec::new(self.input.clone()))
+            } else {
+                self.input.clone()
+            };
+        let stream = input.execute(0, context)?;
+
+        let schema = self.schema();
+        let filler = GapFiller {
+            schema: schema.clone(),
+            time_index: self.time_index,
+            group_indices: self.group_indices.clone(),
+            fills: self.fills.iter().cloned().collect(),
+            stride_nanos: self.stride_nanos,
+            start_nanos: self.start_nanos,
+            end_nanos: self.end_nanos,
+        };
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(async move {
+                let batches = stream.try_collect::<Vec<_>>().await?;
+                let _timer = baseline_metric.elapsed_compute().timer();
+                let batch = filler.fill(&batches)?;
+                baseline_metric.record_output(batch.num_rows());
+                Ok(batch)
+            }),
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let names = self
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|field| field.name().clone())
+                    .collect::<Vec<_>>();
+                write!(f, "GapFillExec: ")?;
+                fmt_gap_fill(
+                    f,
+                    &names,
+                    self.time_index,
+                    &self.group_indices,
+                    &self.fills,
+                    (self.start_nanos, self.end_nanos, self.stride_nanos),
+                )
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+fn fmt_gap_fill(
+    f: &mut fmt::Formatter,
+    names: &[String],
+    time_index: usize,
+    group_indices: &[usize],
+    fills: &[(usize, FillStrategy)],
+    (start_nanos, end_nanos, stride_nanos): (i64, i64, i64),
+) -> fmt::Result {
+    let group_columns = group_indices
+        .iter()
+        .map(|i| names[*i].as_str())
+        .collect::<Vec<_>>();
+    let fills = fills
+        .iter()
+        .map(|(i, strategy)| format!("{}={strategy:?}", names[*i]))
+        .collect::<Vec<_>>();
+    write!(
+        f,
+        "time={}, groupBy={group_columns:?}, fill={fills:?}, range=[{start_nanos}..{end_nanos}), stride={stride_nanos}",
+        names[time_index]
+    )
+}
+
+/// Rows of a group, keyed by the bucket.
+#[derive(Default)]
+struct Group {
+    rows: HashMap<i64, usize>,
+    // rows whose bucket is null
+    null_bucket_rows: Vec<usize>,
+}
+
+struct GapFiller {
+    schema: SchemaRef,
+    time_index: usize,
+    group_indices: Vec<usize>,
+    fills: HashMap<usize, FillStrategy>,
+    stride_nanos: i64,
+    start_nanos: i64,
+    end_nanos: i64,
+}
+
+impl GapFiller {
+    fn fill(&self, batches: &[RecordBatch]) -> DfResult<RecordBatch> {
+        let batch = compute::concat_batches(&self.schema, batches)?;
+        if batch.num_rows() == 0 {
+            return Ok(batch);
+        }
+
+        let time_index = self.time_index;
+        let time_field = self.schema.field(time_index);
+        let DataType::Timestamp(unit, _) = time_field.data_type() else {
+            return Err(DataFusionError::Execution(format!(
+                "Column {} to fill gaps is not a timestamp",
+                time_field.name()
+            )));
+        };
+        let nanos_per_unit = nanos_per_unit(unit);
+        let stride = nanos_to_unit(self.stride_nanos, unit)?;
+        let first_bucket = self
+            .start_nanos
+            .div_euclid(nanos_per_unit)
+            .div_euclid(stride)
+            * stride;
+        // exclusive
+        let end = (self.end_nanos - 1).div_euclid(nanos_per_unit) + 1;
+        if (end - first_bucket) / stride > MAX_BUCKETS_PER_GROUP {
+            return Err(DataFusionError::Execution(format!(
+                "Too many buckets to fill, the time range should be no more than {MAX_BUCKETS_PER_GROUP} times of the interval"
+            )));
+        }
+
+        let times = compute::cast(batch.column(time_index), &DataType::Int64)?;
+        let times = times.as_any().downcast_ref::<Int64Array>().unwrap();
+        let group_indices = &self.group_indices;
+
+        // Groups in the order of appearance.
+        let mut keys = Vec::new();
+        let mut groups: HashMap<Vec<ScalarValue>, Group> = HashMap::new();
+        for row in 0..batch.num_rows() {
+            let key = group_indices
+                .iter()
+                .map(|i| ScalarValue::try_from_array(batch.column(*i), row))
+                .collect::<DfResult<Vec<_>>>()?;
+            let group = groups.entry(key.clone()).or_insert_with(|| {
+                keys.push(key);
+                Group::default()
+            });
+            if times.is_null(row) {
+                group.null_bucket_rows.push(row);
+            } else {
+                group.rows.insert(times.value(row), row);
+            }
+        }
+
+        let mut out_times: Vec<Option<i64>> = Vec::new();
+        let mut out_columns: Vec<Vec<ScalarValue>> = vec![Vec::new(); batch.num_columns()];
+        for key in keys {
+            let group = &groups[&key];
+            let buckets = (0..)
+                .map(|i| first_bucket + i * stride)
+                .take_while(|bucket| *bucket < end)
+                .chain(group.rows.keys().copied())
+                .collect::<BTreeSet<_>>();
+
+            for (i, column) in batch.columns().iter().enumerate() {
+                let field = self.schema.field(i);
+                if i == time_index {
+                    continue;
+                }
+                if let Some(pos) = group_indices.iter().position(|x| *x == i) {
+                    let values = &mut out_columns[i];
+                    values.extend(std::iter::repeat(key[pos].clone()).take(buckets.len()));
+                    for _ in &group.null_bucket_rows {
+                        values.push(key[pos].clone());
+                    }
+                    continue;
+                }
+
+                let strategy = self.fills.get(&i).copied().unwrap_or(FillStrategy::Null);
+                let observed = observations(&group.rows, column)?;
+                let null = ScalarValue::try_from(field.data_type())?;
+                for bucket in &buckets {
+                    let value = match group.rows.get(bucket) {
+                        Some(row) => ScalarValue::try_from_array(column, *row)?,
+                        None => fill_value(&observed, *bucket, strategy, field.data_type())?
+                            .unwrap_or_else(|| null.clone()),
+                    };
+                    out_columns[i].push(value);
+                }
+                for row in &group.null_bucket_rows {
+                    out_columns[i].push(ScalarValue::try_from_array(column, *row)?);
+                }
+            }
+            out_times.extend(buckets.iter().map(|x| Some(*x)));
+            out_times.extend(group.null_bucket_rows.iter().map(|_| None));
+        }
+
+        let columns = out_columns
+            .into_iter()
+            .enumerate()
+            .map(|(i, values)| {
+                let data_type = self.schema.field(i).data_type();
+                if i == time_index {
+                    let times = Int64Array::from(out_times.clone());
+                    Ok(compute::cast(&times, data_type)?)
+                } else {
+                    let array = ScalarValue::iter_to_array(values)?;
+                    if array.data_type() == data_type {
+                        Ok(array)
+                    } else {
+                        Ok(compute::cast(&array, data_type)?)
+                    }
+                }
+            })
+            .collect::<DfResult<Vec<ArrayRef>>>()?;
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+/// Non-null values of `column` in a group, ordered by bucket.
+fn observations(
+    rows: &HashMap<i64, usize>,
+    column: &ArrayRef,
+) -> DfResult<Vec<(i64, ScalarValue)>> {
+    let mut observed = rows
+        .iter()
+        .filter(|(_, row)| !column.is_null(**row))
+        .map(|(bucket, row)| Ok((*bucket, ScalarValue::try_from_array(column, *row)?)))
+        .collect::<DfResult<Vec<_>>>()?;
+    observed.sort_unstable_by_key(|(bucket, _)| *bucket);
+    Ok(observed)
+}
+
+fn fill_value(
+    observed: &[(i64, ScalarValue)],
+    bucket: i64,
+    strategy: FillStrategy,
+    data_type: &DataType,
+) -> DfResult<Option<ScalarValue>> {
+    let next = observed.partition_point(|(x, _)| *x < bucket);
+    let prev = next.checked_sub(1).map(|i| &observed[i]);
+    let next = observed.get(next);
+
+    match strategy {
+        FillStrategy::Null => Ok(None),
+        FillStrategy::Locf => Ok(prev.map(|(_, value)| value.clone())),
+        FillStrategy::Interpolate => {
+            let (Some((t0, v0)), Some((t1, v1))) = (prev, next) else {
+                return Ok(None);
+            };
+            let (Some(v0), Some(v1)) = (scalar_to_f64(v0), scalar_to_f64(v1)) else {
+                return Err(DataFusionError::Execution(format!(
+                    "{INTERPOLATE} only supports numeric values, found {data_type}"
+                )));
+            };
+            let value = v0 + (v1 - v0) * (bucket - t0) as f64 / (t1 - t0) as f64;
+            let array = compute::cast(&ScalarValue::Float64(Some(value)).to_array(), data_type)?;
+            ScalarValue::try_from_array(&array, 0).map(Some)
+        }
+    }
+}
+
+fn scalar_to_f64(value: &ScalarValue) -> Option<f64> {
+    match value {
+        ScalarValue::Float64(v) => *v,
+        ScalarValue::Float32(v) => v.map(|v| v as f64),
+        ScalarValue::Int8(v) => v.map(|v| v as f64),
+        ScalarValue::Int16(v) => v.map(|v| v as f64),
+        ScalarValue::Int32(v) => v.map(|v| v as f64),
+        ScalarValue::Int64(v) => v.map(|v| v as f64),
+        ScalarValue::UInt8(v) => v.map(|v| v as f64),
+        ScalarValue::UInt16(v) => v.map(|v| v as f64),
+        ScalarValue::UInt32(v) => v.map(|v| v as f64),
+        ScalarValue::UInt64(v) => v.map(|v| v as f64),
+        _ => None,
+    }
+}
+
+/// Plans [GapFill] into [GapFillExec].
+pub struct GapFillExtensionPlanner;
+
+#[async_trait]
+impl ExtensionPlanner for GapFillExtensionPlanner {
+    async fn plan_extension(
+        &self,
+        _planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        _logical_inputs: &[&LogicalPlan],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
+        _session_state: &SessionState,
+    ) -> DfResult<Option<Arc<dyn ExecutionPlan>>> {
+        let Some(node) = node.as_any().downcast_ref::<GapFill>() else {
+            return Ok(None);
+        };
+        Ok(Some(node.to_execution_plan(physical_inputs[0].clone())))
+    }
+}
@@ -13,8 +13,12 @@
 // limitations under the License.
 
 pub mod datafusion;
+pub mod decorrelate;
+pub mod dist_plan;
 pub mod error;
+pub mod exact_count;
 pub mod executor;
+pub mod gap_fill;
 pub mod logical_optimizer;
 mod metrics;
 mod optimizer;
@@ -24,9 +28,13 @@ pub mod physical_planner;
 pub mod plan;
 pub mod planner;
 pub mod query_engine;
+pub mod rebatch;
+pub mod retention;
+pub mod row_ttl;
 pub mod sql;
 #[cfg(test)]
 mod tests;
+pub mod topk;
 
 pub use crate::datafusion::DfContextProviderAdapter;
 pub use crate::query_engine::{
@@ -18,3 +18,10 @@ pub static METRIC_OPTIMIZE_LOGICAL_ELAPSED: &str = "query.optimize_logicalplan_e
 pub static METRIC_OPTIMIZE_PHYSICAL_ELAPSED: &str = "query.optimize_physicalplan_elapsed";
 pub static METRIC_CREATE_PHYSICAL_ELAPSED: &str = "query.create_physicalplan_elapsed";
 pub static METRIC_EXEC_PLAN_ELAPSED: &str = "query.execute_plan_elapsed";
+pub static METRIC_EXEC_QUERY_COUNT: &str = "query.execute_query_count";
+pub static METRIC_REBATCH_OUTPUT_ROWS: &str = "query.rebatch_output_rows";
+pub static METRIC_REBATCH_OUTPUT_BYTES: &str = "query.rebatch_output_bytes";
+pub static METRIC_TOPK_RETAINED_ROWS: &str = "query.topk_retained_rows";
+
+/// Label of the origin of a query, see [session::context::QueryOrigin].
+pub static LABEL_ORIGIN: &str = "origin";
@@ -20,13 +20,14 @@ use common_error::prelude::BoxedError;
 use common_error::status_code::StatusCode;
 use common_telemetry::timer;
 use promql_parser::parser::EvalStmt;
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 use sql::dialect::GenericDialect;
 use sql::parser::ParserContext;
 use sql::statements::statement::Statement;
 
 use crate::error::{
-    MultipleStatementsSnafu, ParseFloatSnafu, ParseTimestampSnafu, QueryParseSnafu, Result,
+    MultipleStatementsSnafu, ParseDurationSnafu, ParseFloatSnafu, ParseTimestampSnafu,
+    QueryParseSnafu, Result,
 };
 use crate::metrics::{METRIC_PARSE_PROMQL_ELAPSED, METRIC_PARSE_SQL_ELAPSED};
 
@@ -46,6 +47,10 @@ pub struct PromQuery {
     pub step: String,
 }
 
+/// Default max number of points per timeseries of a PromQL range query, the same as
+/// Prometheus.
+pub const DEFAULT_MAX_POINTS: usize = 11_000;
+
 pub struct QueryLanguageParser {}
 
 impl QueryLanguageParser {
@@ -66,8 +71,9 @@ impl QueryLanguageParser {
         }
     }
 
-    // TODO(ruihang): implement this method when parser is ready.
-    pub fn parse_promql(query: &PromQuery) -> Result<QueryStatement> {
+    /// Parses a PromQL query, rejecting the range queries with more than `max_points` points
+    /// per timeseries.
+    pub fn parse_promql(query: &PromQuery, max_points: usize) -> Result<QueryStatement> {
         let _timer = timer!(METRIC_PARSE_PROMQL_ELAPSED);
 
         let expr = promql_parser::parser::parse(&query.query)
@@ -88,16 +94,32 @@ impl QueryLanguageParser {
                 query: &query.query,
             })?;
 
-        let step = query
-            .step
-            .parse::<u64>()
-            .map(Duration::from_secs)
-            .or_else(|_| promql_parser::util::parse_duration(&query.step))
-            .map_err(|msg| BoxedError::new(PlainError::new(msg, StatusCode::InvalidArguments)))
+        let step = Self::parse_promql_duration(&query.step)
+            .map_err(BoxedError::new)
             .context(QueryParseSnafu {
                 query: &query.query,
             })?;
 
+        if step.is_zero() {
+            return Self::invalid_promql(
+                query,
+                "zero or negative query resolution step widths are not accepted. \
+                 Try a positive integer",
+            );
+        }
+        let Ok(range) = end.duration_since(start) else {
+            return Self::invalid_promql(query, "end timestamp must not be before start time");
+        };
+        if range.as_millis() / step.as_millis() > max_points as u128 {
+            return Self::invalid_promql(
+                query,
+                &format!(
+                    "exceeded maximum resolution of {max_points} points per timeseries. \
+                     Try decreasing the query resolution (?step=XX)"
+                ),
+            );
+        }
+
         let eval_stmt = EvalStmt {
             expr,
             start,
@@ -110,7 +132,19 @@ impl QueryLanguageParser {
         Ok(QueryStatement::Promql(eval_stmt))
     }
 
-    fn parse_promql_timestamp(timestamp: &str) -> Result<SystemTime> {
+    fn invalid_promql<T>(query: &PromQuery, msg: &str) -> Result<T> {
+        Err(BoxedError::new(PlainError::new(
+            msg.to_string(),
+            StatusCode::InvalidArguments,
+        )))
+        .context(QueryParseSnafu {
+            query: &query.query,
+        })
+    }
+
+    /// Parses a timestamp of PromQL, in RFC3339 or unix seconds like `1435781451.781`, with
+    /// millisecond precision.
+    pub fn parse_promql_timestamp(timestamp: &str) -> Result<SystemTime> {
         // try rfc3339 format
         let rfc3339_result = DateTime::parse_from_rfc3339(timestamp)
             .context(ParseTimestampSnafu { raw: timestamp })
@@ -125,15 +159,98 @@ impl QueryLanguageParser {
         timestamp
             .parse::<f64>()
             .context(ParseFloatSnafu { raw: timestamp })
-            .map(|float| {
-                let duration = Duration::from_secs_f64(float);
-                SystemTime::UNIX_EPOCH
+            .and_then(|float| {
+                let duration = secs_to_duration(float).context(ParseDurationSnafu {
+                    raw: timestamp,
+                    msg: "timestamp before 1970",
+                })?;
+                Ok(SystemTime::UNIX_EPOCH
                     .checked_add(duration)
-                    .unwrap_or(max_system_timestamp())
+                    .unwrap_or(max_system_timestamp()))
             })
             // also report rfc3339 error if float parsing fails
             .map_err(|_| rfc3339_result.unwrap_err())
     }
+
+    /// Parses a duration of PromQL, in seconds like `15` or `0.5`, or in the Prometheus
+    /// duration syntax like `500ms` or `1m30s`, with millisecond precision.
+    pub fn parse_promql_duration(duration: &str) -> Result<Duration> {
+        if let Ok(float) = duration.parse::<f64>() {
+            return secs_to_duration(float).context(ParseDurationSnafu {
+                raw: duration,
+                msg: "zero or negative query resolution step widths are not accepted",
+            });
+        }
+        parse_prometheus_duration(duration)
+            .map_err(|msg| ParseDurationSnafu { raw: duration, msg }.build())
+    }
+}
+
+/// Units of the Prometheus duration syntax, which must be in this order in a duration.
+const DURATION_UNITS: [(&str, u64); 7] = [
+    ("y", 365 * 24 * 60 * 60 * 1000),
+    ("w", 7 * 24 * 60 * 60 * 1000),
+    ("d", 24 * 60 * 60 * 1000),
+    ("h", 60 * 60 * 1000),
+    ("m", 60 * 1000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
+/// Parses the Prometheus duration syntax, like `1h30m` or `500ms`.
+fn parse_prometheus_duration(duration: &str) -> std::result::Result<Duration, String> {
+    if duration.is_empty() {
+        return Err("empty duration".to_string());
+    }
+
+    let mut millis: u64 = 0;
+    // Index of the next unit allowed in `DURATION_UNITS`.
+    let mut next_unit = 0;
+    let mut rest = duration;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("expect a number at `{rest}`"));
+        }
+        let value = rest[..digits]
+            .parse::<u64>()
+            .map_err(|e| format!("invalid number `{}`: {e}", &rest[..digits]))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        let Some(index) = DURATION_UNITS.iter().position(|(name, _)| *name == unit) else {
+            return Err(format!("unknown unit `{unit}`"));
+        };
+        if index < next_unit {
+            return Err(format!("unit `{unit}` is out of order"));
+        }
+        next_unit = index + 1;
+        rest = &rest[unit_len..];
+
+        millis = value
+            .checked_mul(DURATION_UNITS[index].1)
+            .and_then(|v| millis.checked_add(v))
+            .ok_or_else(|| "duration out of range".to_string())?;
+    }
+    Ok(Duration::from_millis(millis))
+}
+
+/// Converts the seconds to a duration, rounded to milliseconds as the floats of seconds like
+/// `1435781451.781` aren't exact. Returns `None` if the seconds are negative or not a number.
+fn secs_to_duration(secs: f64) -> Option<Duration> {
+    if secs.is_nan() || secs < 0.0 {
+        return None;
+    }
+    if secs >= i64::MAX as f64 {
+        return Some(Duration::from_secs(i64::MAX as u64));
+    }
+    let millis = (secs.fract() * 1000.0).round() as u64;
+    Some(Duration::from_secs(secs.trunc() as u64) + Duration::from_millis(millis))
 }
 
 fn max_system_timestamp() -> SystemTime {
@@ -221,6 +338,86 @@ mod test {
         }
     }
 
+    #[test]
+    fn parse_promql_timestamp_millis() {
+        let result = QueryLanguageParser::parse_promql_timestamp("1435781451.781").unwrap();
+        assert_eq!(
+            1435781451781,
+            result
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+        );
+
+        assert!(QueryLanguageParser::parse_promql_timestamp("-1").is_err());
+        assert!(QueryLanguageParser::parse_promql_timestamp("NaN").is_err());
+    }
+
+    #[test]
+    fn parse_promql_duration() {
+        let cases = vec![
+            ("15", Duration::from_secs(15)),
+            ("0.5", Duration::from_millis(500)),
+            ("0.001", Duration::from_millis(1)),
+            ("500ms", Duration::from_millis(500)),
+            ("1m30s", Duration::from_secs(90)),
+            ("1h", Duration::from_secs(3600)),
+            ("1d2h", Duration::from_secs(26 * 3600)),
+            ("1s500ms", Duration::from_millis(1500)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                expected,
+                QueryLanguageParser::parse_promql_duration(input).unwrap(),
+                "{input}"
+            );
+        }
+
+        for input in ["", "-1", "1x", "30s1m", "ms", "1m1m", "1.5m"] {
+            assert!(
+                QueryLanguageParser::parse_promql_duration(input).is_err(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_promql_invalid_range() {
+        let mut promql = PromQuery {
+            query: "http_request".to_string(),
+            start: "1676308440".to_string(),
+            end: "1676308500".to_string(),
+            step: "0s".to_string(),
+        };
+        let err = QueryLanguageParser::parse_promql(&promql, DEFAULT_MAX_POINTS).unwrap_err();
+        assert!(err.to_string().contains("zero or negative"), "{err}");
+
+        promql.step = "15s".to_string();
+        promql.end = "1676308000".to_string();
+        let err = QueryLanguageParser::parse_promql(&promql, DEFAULT_MAX_POINTS).unwrap_err();
+        assert!(err.to_string().contains("before start time"), "{err}");
+    }
+
+    #[test]
+    fn parse_promql_max_points() {
+        // 100 steps of 500ms, 101 points.
+        let mut promql = PromQuery {
+            query: "http_request".to_string(),
+            start: "0".to_string(),
+            end: "50".to_string(),
+            step: "500ms".to_string(),
+        };
+        assert!(QueryLanguageParser::parse_promql(&promql, 100).is_ok());
+
+        promql.end = "50.5".to_string();
+        let err = QueryLanguageParser::parse_promql(&promql, 100).unwrap_err();
+        assert!(
+            err.to_string().contains("exceeded maximum resolution"),
+            "{err}"
+        );
+        assert!(QueryLanguageParser::parse_promql(&promql, DEFAULT_MAX_POINTS).is_ok());
+    }
+
     #[test]
     fn parse_promql_simple() {
         let promql = PromQuery {
@@ -249,7 +446,7 @@ mod test {
             })",
         );
 
-        let result = QueryLanguageParser::parse_promql(&promql).unwrap();
+        let result = QueryLanguageParser::parse_promql(&promql, DEFAULT_MAX_POINTS).unwrap();
         assert_eq!(format!("{result:?}"), expected);
     }
 }
@@ -25,6 +25,7 @@ use session::context::QueryContextRef;
 use snafu::ResultExt;
 use sql::statements::statement::Statement;
 
+use crate::datafusion::scan_tables_as_of;
 use crate::error::{PlanSqlSnafu, QueryPlanSnafu, Result, SqlSnafu};
 use crate::parser::QueryStatement;
 use crate::plan::LogicalPlan;
@@ -69,6 +70,11 @@ impl DfLogicalPlanner {
 
         let sql_to_rel = SqlToRel::new_with_options(&context_provider, parser_options);
 
+        // The time the query reads the snapshots as of, set by `FOR SYSTEM_TIME AS OF`.
+        let as_of = match &stmt {
+            Statement::Query(query) => query.as_of,
+            _ => None,
+        };
         let result = sql_to_rel.statement_to_plan(df_stmt).with_context(|_| {
             let sql = if let Statement::Query(query) = stmt {
                 query.inner.to_string()
@@ -77,7 +83,11 @@ impl DfLogicalPlanner {
             };
             PlanSqlSnafu { sql }
         })?;
-        Ok(LogicalPlan::DfPlan(result))
+        let plan = LogicalPlan::DfPlan(result);
+        match as_of {
+            Some(as_of) => scan_tables_as_of(plan, as_of),
+            None => Ok(plan),
+        }
     }
 
     async fn plan_pql(&self, stmt: EvalStmt, query_ctx: QueryContextRef) -> Result<LogicalPlan> {
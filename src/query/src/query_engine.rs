@@ -55,7 +55,9 @@ pub trait QueryEngine: Send + Sync {
 
     fn register_udf(&self, udf: ScalarUdf);
 
-    fn register_aggregate_function(&self, func: AggregateFunctionMetaRef);
+    /// Registers an aggregate function at runtime, fails if there is already an aggregate
+    /// function of the same name.
+    fn register_aggregate_function(&self, func: AggregateFunctionMetaRef) -> Result<()>;
 
     fn register_function(&self, func: FunctionRef);
 }
@@ -87,7 +89,10 @@ fn register_functions(query_engine: &Arc<DatafusionQueryEngine>) {
     }
 
     for accumulator in FUNCTION_REGISTRY.aggregate_functions() {
-        query_engine.register_aggregate_function(accumulator);
+        // Safety: the names of the aggregate functions in the registry are unique.
+        query_engine
+            .register_aggregate_function(accumulator)
+            .unwrap();
     }
 }
 
@@ -12,14 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use session::context::QueryContextRef;
 use snafu::ensure;
 
 use crate::error::{QueryAccessDeniedSnafu, Result};
+use crate::rebatch::RebatchOptions;
+
+/// Default upper bound of rows a `DELETE` may scan when its predicate can't be
+/// answered by primary key and time index columns alone.
+pub const DEFAULT_DELETE_SCAN_ROW_LIMIT: usize = 100_000;
+
+/// Default time a query waits for the scanned regions to apply the writes in its
+/// commit token.
+pub const DEFAULT_COMMIT_TOKEN_WAIT_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Default, Clone)]
 pub struct QueryOptions {
     pub disallow_cross_schema_query: bool,
+    /// Overrides [DEFAULT_DELETE_SCAN_ROW_LIMIT].
+    pub delete_scan_row_limit: Option<usize>,
+    /// Overrides [DEFAULT_COMMIT_TOKEN_WAIT_TIMEOUT].
+    pub commit_token_wait_timeout: Option<Duration>,
+    /// Re-batches the results of queries by a [RebatchExec](crate::rebatch::RebatchExec) if
+    /// present, like the datanodes sending the results to the frontends.
+    pub result_rebatch: Option<RebatchOptions>,
+    /// Rejects the queries of the time ranges before the retention boundaries of the tables,
+    /// which are only warned about by default.
+    pub reject_expired_ranges: bool,
 }
 
 // TODO(shuiyisong): remove one method after #559 is done
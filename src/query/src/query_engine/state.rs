@@ -15,6 +15,7 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use catalog::CatalogManagerRef;
@@ -22,6 +23,7 @@ use common_base::Plugins;
 use common_function::scalars::aggregate::AggregateFunctionMetaRef;
 use common_query::physical_plan::SessionContext;
 use common_query::prelude::ScalarUdf;
+use common_time::util::{ClockRef, SystemClock};
 use datafusion::catalog::catalog::MemoryCatalogList;
 use datafusion::error::Result as DfResult;
 use datafusion::execution::context::{QueryPlanner, SessionConfig, SessionState};
@@ -31,9 +33,22 @@ use datafusion::physical_plan::{ExecutionPlan, PhysicalPlanner};
 use datafusion_expr::LogicalPlan as DfLogicalPlan;
 use datafusion_optimizer::analyzer::Analyzer;
 use promql::extension_plan::PromExtensionPlanner;
-
+use snafu::ensure;
+
+use crate::decorrelate::DecorrelateSubqueryRule;
+use crate::dist_plan::{AggregatePushdownRule, DistExtensionPlanner};
+use crate::error::{AggregateFunctionExistsSnafu, InvalidAggregateFunctionNameSnafu, Result};
+use crate::exact_count::{ExactCountExtensionPlanner, ExactCountRule};
+use crate::gap_fill::{
+    interpolate_udf, locf_udf, time_bucket_gapfill_udf, GapFillExtensionPlanner, GapFillRule,
+};
 use crate::optimizer::TypeConversionRule;
-use crate::query_engine::options::QueryOptions;
+use crate::query_engine::options::{
+    QueryOptions, DEFAULT_COMMIT_TOKEN_WAIT_TIMEOUT, DEFAULT_DELETE_SCAN_ROW_LIMIT,
+};
+use crate::rebatch::RebatchOptions;
+use crate::row_ttl::RowTtlRule;
+use crate::topk::{TopKExtensionPlanner, TopKRule};
 
 /// Query engine global state
 // TODO(yingwen): This QueryEngineState still relies on datafusion, maybe we can define a trait for it,
@@ -61,6 +76,15 @@ impl QueryEngineState {
         // Apply the type conversion rule first.
         let mut analyzer = Analyzer::new();
         analyzer.rules.insert(0, Arc::new(TypeConversionRule));
+        // Filters out expired rows before the types of the filters are coerced.
+        let clock = clock_of(&plugins);
+        analyzer.rules.insert(1, Arc::new(RowTtlRule::new(clock)));
+        // Decorrelates the subqueries after their types are coerced.
+        analyzer.rules.push(Arc::new(DecorrelateSubqueryRule));
+        // Gap filling needs the time bounds coerced to timestamps.
+        analyzer.rules.push(Arc::new(GapFillRule));
+
+        let aggregate_functions = Arc::new(RwLock::new(HashMap::new()));
 
         let session_state = SessionState::with_config_rt_and_catalog_list(
             session_config,
@@ -68,14 +92,23 @@ impl QueryEngineState {
             Arc::new(MemoryCatalogList::default()), // pass a dummy catalog list
         )
         .with_analyzer_rules(analyzer.rules)
+        // Applied last, after filters and projections are pushed down to table scans.
+        .add_optimizer_rule(Arc::new(ExactCountRule))
+        .add_optimizer_rule(Arc::new(AggregatePushdownRule::new(
+            aggregate_functions.clone(),
+        )))
+        .add_optimizer_rule(Arc::new(TopKRule))
         .with_query_planner(Arc::new(DfQueryPlanner::new()));
 
         let df_context = SessionContext::with_state(session_state);
+        df_context.register_udf(time_bucket_gapfill_udf());
+        df_context.register_udf(locf_udf());
+        df_context.register_udf(interpolate_udf());
 
         Self {
             df_context,
             catalog_manager: catalog_list,
-            aggregate_functions: Arc::new(RwLock::new(HashMap::new())),
+            aggregate_functions,
             plugins,
         }
     }
@@ -94,13 +127,28 @@ impl QueryEngineState {
             .cloned()
     }
 
-    pub fn register_aggregate_function(&self, func: AggregateFunctionMetaRef) {
-        // TODO(LFC): Return some error if there exists an aggregate function with the same name.
-        // Simply overwrite the old value for now.
-        self.aggregate_functions
-            .write()
-            .unwrap()
-            .insert(func.name(), func);
+    /// Registers an aggregate function, fails if there is already an aggregate function of the
+    /// same name.
+    ///
+    /// Names starting with `__` are reserved for the aggregate functions generated by the query
+    /// engine, like the partial and merge stages of distributed aggregations.
+    pub fn register_aggregate_function(&self, func: AggregateFunctionMetaRef) -> Result<()> {
+        let name = func.name();
+        ensure!(
+            !name.starts_with("__"),
+            InvalidAggregateFunctionNameSnafu {
+                name,
+                reason: "names starting with `__` are reserved",
+            }
+        );
+
+        let mut aggregate_functions = self.aggregate_functions.write().unwrap();
+        ensure!(
+            !aggregate_functions.contains_key(&name),
+            AggregateFunctionExistsSnafu { name }
+        );
+        aggregate_functions.insert(name, func);
+        Ok(())
     }
 
     #[inline]
@@ -115,11 +163,50 @@ impl QueryEngineState {
             .unwrap_or(false)
     }
 
+    pub(crate) fn delete_scan_row_limit(&self) -> usize {
+        self.plugins
+            .get::<QueryOptions>()
+            .and_then(|x| x.delete_scan_row_limit)
+            .unwrap_or(DEFAULT_DELETE_SCAN_ROW_LIMIT)
+    }
+
+    pub(crate) fn commit_token_wait_timeout(&self) -> Duration {
+        self.plugins
+            .get::<QueryOptions>()
+            .and_then(|x| x.commit_token_wait_timeout)
+            .unwrap_or(DEFAULT_COMMIT_TOKEN_WAIT_TIMEOUT)
+    }
+
+    pub(crate) fn result_rebatch(&self) -> Option<RebatchOptions> {
+        self.plugins
+            .get::<QueryOptions>()
+            .and_then(|x| x.result_rebatch)
+    }
+
+    pub(crate) fn reject_expired_ranges(&self) -> bool {
+        self.plugins
+            .get::<QueryOptions>()
+            .map(|x| x.reject_expired_ranges)
+            .unwrap_or(false)
+    }
+
+    /// Returns the clock of the current time of the queries.
+    pub(crate) fn clock(&self) -> ClockRef {
+        clock_of(&self.plugins)
+    }
+
     pub(crate) fn session_state(&self) -> SessionState {
         self.df_context.state()
     }
 }
 
+fn clock_of(plugins: &Plugins) -> ClockRef {
+    plugins
+        .get::<ClockRef>()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(SystemClock))
+}
+
 struct DfQueryPlanner {
     physical_planner: DefaultPhysicalPlanner,
 }
@@ -140,9 +227,13 @@ impl QueryPlanner for DfQueryPlanner {
 impl DfQueryPlanner {
     fn new() -> Self {
         Self {
-            physical_planner: DefaultPhysicalPlanner::with_extension_planners(vec![Arc::new(
-                PromExtensionPlanner {},
-            )]),
+            physical_planner: DefaultPhysicalPlanner::with_extension_planners(vec![
+                Arc::new(PromExtensionPlanner {}),
+                Arc::new(DistExtensionPlanner),
+                Arc::new(GapFillExtensionPlanner),
+                Arc::new(ExactCountExtensionPlanner),
+                Arc::new(TopKExtensionPlanner),
+            ]),
         }
     }
 }
@@ -0,0 +1,483 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Re-batching of the results of queries.
+//!
+//! The batches scanned from the storage are sized by its internal chunks, which may be a few
+//! dozens of rows. [RebatchExec] coalesces them to batches of a target number of rows or bytes,
+//! so the results sent over the network are not split into lots of tiny messages. The target
+//! is reduced to the limit of the query, like `LIMIT 10`, to not wait for or build a batch
+//! larger than the whole result.
+
+use std::any::Any;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use datafusion::error::Result as DfResult;
+use datafusion::execution::context::TaskContext;
+use datafusion::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::projection::ProjectionExec;
+use datafusion::physical_plan::sorts::sort::SortExec;
+use datafusion::physical_plan::{
+    DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream, SendableRecordBatchStream,
+    Statistics,
+};
+use datatypes::arrow::compute::concat_batches;
+use datatypes::arrow::datatypes::SchemaRef;
+use datatypes::arrow::record_batch::RecordBatch;
+use futures::{ready, Stream, StreamExt};
+
+use crate::metrics;
+
+/// Default target number of rows of the re-batched batches.
+pub const DEFAULT_TARGET_ROWS: usize = 8192;
+/// Default target size in bytes of the re-batched batches.
+pub const DEFAULT_TARGET_BYTES: usize = 1024 * 1024;
+
+/// Targets of the batches re-batched by [RebatchExec], a batch is output once it reaches
+/// either of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebatchOptions {
+    pub target_rows: usize,
+    pub target_bytes: usize,
+}
+
+impl Default for RebatchOptions {
+    fn default() -> Self {
+        Self {
+            target_rows: DEFAULT_TARGET_ROWS,
+            target_bytes: DEFAULT_TARGET_BYTES,
+        }
+    }
+}
+
+/// Coalesces the batches of all the partitions of its input into batches of about the target
+/// rows or bytes, in one partition. Input batches larger than the targets are split.
+#[derive(Debug)]
+pub struct RebatchExec {
+    input: Arc<dyn ExecutionPlan>,
+    /// Target rows, reduced to the limit of the input.
+    target_rows: usize,
+    target_bytes: usize,
+    metric: ExecutionPlanMetricsSet,
+}
+
+impl RebatchExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, options: RebatchOptions) -> Self {
+        let target_rows = match limit_of(input.as_ref()) {
+            Some(limit) => options.target_rows.min(limit),
+            None => options.target_rows,
+        };
+        Self {
+            input,
+            target_rows: target_rows.max(1),
+            target_bytes: options.target_bytes.max(1),
+            metric: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    /// Returns the target rows of the output batches, which may be less than the option if the
+    /// input is limited.
+    pub fn target_rows(&self) -> usize {
+        self.target_rows
+    }
+
+    pub fn target_bytes(&self) -> usize {
+        self.target_bytes
+    }
+}
+
+/// Returns the max number of rows `plan` outputs if it's limited, looking through the nodes
+/// keeping the number of rows.
+fn limit_of(plan: &dyn ExecutionPlan) -> Option<usize> {
+    let any = plan.as_any();
+    if let Some(limit) = any.downcast_ref::<GlobalLimitExec>() {
+        return limit.fetch();
+    }
+    if let Some(limit) = any.downcast_ref::<LocalLimitExec>() {
+        return Some(limit.fetch());
+    }
+    if let Some(sort) = any.downcast_ref::<SortExec>() {
+        return sort.fetch();
+    }
+    if any.is::<ProjectionExec>()
+        || any.is::<CoalescePartitionsExec>()
+        || any.is::<CoalesceBatchesExec>()
+    {
+        return plan
+            .children()
+            .first()
+            .and_then(|child| limit_of(child.as_ref()));
+    }
+    None
+}
+
+impl ExecutionPlan for RebatchExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[datafusion::physical_expr::PhysicalSortExpr]> {
+        // Batches of one partition are re-batched in order.
+        if self.input.output_partitioning().partition_count() <= 1 {
+            self.input.output_ordering()
+        } else {
+            None
+        }
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        Ok(Arc::new(Self {
+            input: children[0].clone(),
+            target_rows: self.target_rows,
+            target_bytes: self.target_bytes,
+            metric: self.metric.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DfResult<SendableRecordBatchStream> {
+        let input: Arc<dyn ExecutionPlan> =
+            if self.input.output_partitioning().partition_count() > 1 {
+                Arc::new(CoalescePartitionsExec::new(self.input.clone()))
+            } else {
+                self.input.clone()
+            };
+        let input = input.execute(0, context)?;
+        Ok(Box::pin(RebatchStream::new(
+            input,
+            self.target_rows,
+            self.target_bytes,
+            BaselineMetrics::new(&self.metric, partition),
+        )))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => write!(
+                f,
+                "RebatchExec: target_rows={}, target_bytes={}",
+                self.target_rows, self.target_bytes
+            ),
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.input.statistics()
+    }
+}
+
+pub(crate) struct RebatchStream {
+    input: SendableRecordBatchStream,
+    schema: SchemaRef,
+    target_rows: usize,
+    target_bytes: usize,
+    buffer: Vec<RecordBatch>,
+    buffered_rows: usize,
+    /// Estimated bytes of the buffered rows.
+    buffered_bytes: usize,
+    input_done: bool,
+    baseline_metric: BaselineMetrics,
+}
+
+impl RebatchStream {
+    pub(crate) fn new(
+        input: SendableRecordBatchStream,
+        target_rows: usize,
+        target_bytes: usize,
+        baseline_metric: BaselineMetrics,
+    ) -> Self {
+        Self {
+            schema: input.schema(),
+            input,
+            target_rows,
+            target_bytes,
+            buffer: vec![],
+            buffered_rows: 0,
+            buffered_bytes: 0,
+            input_done: false,
+            baseline_metric,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.buffered_rows >= self.target_rows || self.buffered_bytes >= self.target_bytes
+    }
+
+    fn push(&mut self, batch: RecordBatch) {
+        if batch.num_rows() == 0 {
+            return;
+        }
+        self.buffered_rows += batch.num_rows();
+        self.buffered_bytes += batch
+            .columns()
+            .iter()
+            .map(|column| column.get_array_memory_size())
+            .sum::<usize>();
+        self.buffer.push(batch);
+    }
+
+    /// Outputs up to the target rows and bytes of the buffered rows, the rest stays buffered.
+    fn flush(&mut self) -> DfResult<RecordBatch> {
+        let _timer = self.baseline_metric.elapsed_compute().timer();
+        let batch = if self.buffer.len() == 1 {
+            self.buffer.remove(0)
+        } else {
+            let batch = concat_batches(&self.schema, &self.buffer)?;
+            self.buffer.clear();
+            batch
+        };
+
+        // Bytes of the rows are estimated in proportion to the rows, as the slices of arrays
+        // report the size of the whole arrays.
+        let total_rows = batch.num_rows();
+        let rows_of_target_bytes = (self.target_bytes as u128 * total_rows as u128
+            / self.buffered_bytes.max(1) as u128)
+            .max(1) as usize;
+        let rows = total_rows.min(self.target_rows).min(rows_of_target_bytes);
+        let bytes = (self.buffered_bytes as u128 * rows as u128 / total_rows as u128) as usize;
+        if rows < total_rows {
+            self.buffer.push(batch.slice(rows, total_rows - rows));
+        }
+        self.buffered_rows -= rows;
+        self.buffered_bytes -= bytes;
+
+        ::metrics::histogram!(metrics::METRIC_REBATCH_OUTPUT_ROWS, rows as f64);
+        ::metrics::histogram!(metrics::METRIC_REBATCH_OUTPUT_BYTES, bytes as f64);
+        Ok(batch.slice(0, rows))
+    }
+
+    fn poll_next_inner(&mut self, cx: &mut Context<'_>) -> Poll<Option<DfResult<RecordBatch>>> {
+        loop {
+            if self.is_full() {
+                return Poll::Ready(Some(self.flush()));
+            }
+            if self.input_done {
+                return if self.buffered_rows == 0 {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Ready(Some(self.flush()))
+                };
+            }
+            match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => self.push(batch),
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => self.input_done = true,
+            }
+        }
+    }
+}
+
+impl Stream for RebatchStream {
+    type Item = DfResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.poll_next_inner(cx);
+        self.baseline_metric.record_poll(poll)
+    }
+}
+
+impl RecordBatchStream for RebatchStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use datafusion::prelude::SessionContext;
+    use datatypes::arrow::array::{Int64Array, StringArray};
+    use datatypes::arrow::datatypes::{DataType, Field, Schema};
+    use futures::TryStreamExt;
+
+    use super::*;
+
+    fn test_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("n", DataType::Int64, false),
+            Field::new("s", DataType::Utf8, false),
+        ]))
+    }
+
+    /// Returns `num_batches` batches of `rows` rows of the same size, numbered continuously.
+    fn test_batches(num_batches: usize, rows: usize) -> Vec<RecordBatch> {
+        (0..num_batches)
+            .map(|i| {
+                let start = (i * rows) as i64;
+                let numbers = (start..start + rows as i64).collect::<Vec<_>>();
+                let strings = numbers
+                    .iter()
+                    .map(|n| format!("row-{n:06}"))
+                    .collect::<Vec<_>>();
+                RecordBatch::try_new(
+                    test_schema(),
+                    vec![
+                        Arc::new(Int64Array::from(numbers)),
+                        Arc::new(StringArray::from(strings)),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    async fn execute(plan: Arc<dyn ExecutionPlan>) -> Vec<RecordBatch> {
+        let stream = plan.execute(0, SessionContext::new().task_ctx()).unwrap();
+        stream.try_collect().await.unwrap()
+    }
+
+    fn row_counts(batches: &[RecordBatch]) -> Vec<usize> {
+        batches.iter().map(|batch| batch.num_rows()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_rebatch_to_target_rows() {
+        // Like a scan producing batches of 100 rows.
+        let input = test_batches(100, 100);
+        let scan = Arc::new(MemoryExec::try_new(&[input.clone()], test_schema(), None).unwrap());
+        let options = RebatchOptions {
+            target_rows: 1000,
+            ..Default::default()
+        };
+        let output = execute(Arc::new(RebatchExec::new(scan, options))).await;
+
+        assert_eq!(vec![1000; 10], row_counts(&output));
+        // Results are identical to the input.
+        assert_eq!(
+            concat_batches(&test_schema(), &input).unwrap(),
+            concat_batches(&test_schema(), &output).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebatch_to_target_bytes() {
+        let input = test_batches(20, 100);
+        let bytes_per_batch = input[0]
+            .columns()
+            .iter()
+            .map(|column| column.get_array_memory_size())
+            .sum::<usize>();
+        let scan = Arc::new(MemoryExec::try_new(&[input.clone()], test_schema(), None).unwrap());
+        let options = RebatchOptions {
+            target_bytes: bytes_per_batch * 5,
+            ..Default::default()
+        };
+        let output = execute(Arc::new(RebatchExec::new(scan, options))).await;
+
+        assert_eq!(vec![500; 4], row_counts(&output));
+        assert_eq!(
+            concat_batches(&test_schema(), &input).unwrap(),
+            concat_batches(&test_schema(), &output).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebatch_split_and_merge_partitions() {
+        // A large batch is split, batches of the partitions are merged.
+        let mut input = test_batches(1, 2500);
+        input.extend(test_batches(3, 10));
+        let partitions = vec![input[..1].to_vec(), input[1..].to_vec()];
+        let scan = Arc::new(MemoryExec::try_new(&partitions, test_schema(), None).unwrap());
+        let options = RebatchOptions {
+            target_rows: 1000,
+            ..Default::default()
+        };
+        let output = execute(Arc::new(RebatchExec::new(scan, options))).await;
+
+        assert_eq!(2530, row_counts(&output).iter().sum::<usize>());
+        assert!(row_counts(&output).iter().all(|rows| *rows <= 1000));
+        let mut numbers = output
+            .iter()
+            .flat_map(|batch| {
+                let numbers = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap();
+                numbers.values().to_vec()
+            })
+            .collect::<Vec<_>>();
+        numbers.sort_unstable();
+        let mut expected = (0..2500).collect::<Vec<_>>();
+        expected.extend(0..30);
+        expected.sort_unstable();
+        assert_eq!(expected, numbers);
+    }
+
+    #[tokio::test]
+    async fn test_rebatch_limited_input() {
+        let scan =
+            Arc::new(MemoryExec::try_new(&[test_batches(100, 100)], test_schema(), None).unwrap());
+        let limit = Arc::new(GlobalLimitExec::new(scan, 0, Some(5)));
+        let rebatch = RebatchExec::new(limit, RebatchOptions::default());
+        assert_eq!(5, rebatch.target_rows());
+
+        let output = execute(Arc::new(rebatch)).await;
+        assert_eq!(vec![5], row_counts(&output));
+    }
+
+    #[tokio::test]
+    async fn test_rebatch_not_waiting_for_full_batch() {
+        // The input has 5 rows and never ends, the batch is output once it reaches the
+        // target reduced to the limit.
+        let input = futures::stream::iter(test_batches(1, 5).into_iter().map(Ok))
+            .chain(futures::stream::pending());
+        let input = Box::pin(RecordBatchStreamAdapter::new(test_schema(), input));
+        let mut stream = RebatchStream::new(
+            input,
+            5,
+            DEFAULT_TARGET_BYTES,
+            BaselineMetrics::new(&ExecutionPlanMetricsSet::new(), 0),
+        );
+
+        let batch = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(5, batch.num_rows());
+    }
+}
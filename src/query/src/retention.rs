@@ -0,0 +1,182 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Finds the time ranges queried before the retention boundaries of the tables, `now - ttl`,
+//! whose rows are expired and silently missing from the results.
+
+use std::collections::HashSet;
+
+use common_catalog::format_full_table_name;
+use common_query::logical_plan::Expr;
+use common_time::Timestamp;
+use datafusion_common::DFSchemaRef;
+use datafusion_expr::expr_rewriter::unnormalize_col;
+use datafusion_expr::utils::{expr_to_columns, split_conjunction};
+use datafusion_expr::{Expr as DfExpr, LogicalPlan, TableScan};
+use table::predicate::TimeRangePredicateBuilder;
+
+use crate::row_ttl::scanned_table;
+
+/// A table whose queried time range precedes its retention boundary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiredRange {
+    pub table_name: String,
+    /// Time before which the rows of the table are expired by its ttl.
+    pub boundary: Timestamp,
+    /// Whether the whole queried time range precedes the boundary, so no rows of the table are
+    /// returned.
+    pub fully_expired: bool,
+}
+
+impl ExpiredRange {
+    /// Returns the warning of the query reading the range.
+    pub fn warning(&self) -> String {
+        let boundary = self.boundary.to_iso8601_string();
+        if self.fully_expired {
+            format!(
+                "Queried time range of table {} is before its retention boundary {}, the rows before it are expired by the ttl of the table and no rows are returned",
+                self.table_name, boundary
+            )
+        } else {
+            format!(
+                "Queried time range of table {} starts before its retention boundary {}, the rows before it are expired by the ttl of the table and only the later rows are returned",
+                self.table_name, boundary
+            )
+        }
+    }
+}
+
+/// Returns the tables scanned by `plan` whose queried time ranges precede their retention
+/// boundaries at `now_millis`.
+///
+/// The plan should not be analyzed, otherwise the filters hiding the expired rows of the tables
+/// with a ttl column clamp the queried ranges. The filters are attributed to the scans below
+/// them by the columns they reference, so every table in a join is checked by its own filters.
+/// The ranges without a lower bound are not checked unless they end before the boundary, as
+/// such queries don't ask for the rows of a specific time.
+pub fn find_expired_ranges(plan: &LogicalPlan, now_millis: i64) -> Vec<ExpiredRange> {
+    let mut expired = vec![];
+    collect_expired_ranges(plan, vec![], now_millis, &mut expired);
+    expired
+}
+
+/// Collects the expired ranges of the scans in `plan`, where `filters` are the conjuncts of the
+/// filters above `plan` that may apply to its scans.
+fn collect_expired_ranges(
+    plan: &LogicalPlan,
+    mut filters: Vec<DfExpr>,
+    now_millis: i64,
+    expired: &mut Vec<ExpiredRange>,
+) {
+    match plan {
+        LogicalPlan::TableScan(scan) => {
+            let filters = filters
+                .into_iter()
+                .filter(|filter| references_only(filter, &scan.projected_schema))
+                .chain(scan.filters.iter().cloned())
+                .map(Expr::from)
+                .collect::<Vec<_>>();
+            if let Some(range) = expired_range(scan, &filters, now_millis) {
+                expired.push(range);
+            }
+        }
+        LogicalPlan::Filter(filter) => {
+            filters.extend(split_conjunction(&filter.predicate).into_iter().cloned());
+            collect_expired_ranges(&filter.input, filters, now_millis, expired);
+        }
+        LogicalPlan::Join(join) => {
+            if let Some(filter) = &join.filter {
+                filters.extend(split_conjunction(filter).into_iter().cloned());
+            }
+            collect_expired_ranges(&join.left, filters.clone(), now_millis, expired);
+            collect_expired_ranges(&join.right, filters, now_millis, expired);
+        }
+        LogicalPlan::CrossJoin(join) => {
+            collect_expired_ranges(&join.left, filters.clone(), now_millis, expired);
+            collect_expired_ranges(&join.right, filters, now_millis, expired);
+        }
+        // The columns of the subquery are qualified by its alias above it.
+        LogicalPlan::SubqueryAlias(alias) => {
+            let filters = filters.into_iter().map(unnormalize_col).collect();
+            collect_expired_ranges(&alias.input, filters, now_millis, expired);
+        }
+        // Only the filters on the columns passed through as they are still apply below.
+        LogicalPlan::Projection(projection) => {
+            let filters = filters
+                .into_iter()
+                .filter(|filter| passes_through(filter, &projection.expr))
+                .collect();
+            collect_expired_ranges(&projection.input, filters, now_millis, expired);
+        }
+        LogicalPlan::Sort(_) | LogicalPlan::Limit(_) | LogicalPlan::Distinct(_) => {
+            for input in plan.inputs() {
+                collect_expired_ranges(input, filters.clone(), now_millis, expired);
+            }
+        }
+        _ => {
+            for input in plan.inputs() {
+                collect_expired_ranges(input, vec![], now_millis, expired);
+            }
+        }
+    }
+}
+
+/// Returns whether all the columns referenced by `filter` are in `schema`.
+fn references_only(filter: &DfExpr, schema: &DFSchemaRef) -> bool {
+    let mut columns = HashSet::new();
+    expr_to_columns(filter, &mut columns).is_ok()
+        && columns
+            .iter()
+            .all(|column| schema.field_from_column(column).is_ok())
+}
+
+/// Returns whether all the columns referenced by `filter` are output by a projection of `exprs`
+/// as the same columns of its input.
+fn passes_through(filter: &DfExpr, exprs: &[DfExpr]) -> bool {
+    let mut columns = HashSet::new();
+    expr_to_columns(filter, &mut columns).is_ok()
+        && columns.iter().all(|column| {
+            exprs
+                .iter()
+                .any(|expr| matches!(expr, DfExpr::Column(c) if c.name == column.name))
+        })
+}
+
+fn expired_range(scan: &TableScan, filters: &[Expr], now_millis: i64) -> Option<ExpiredRange> {
+    let table = scanned_table(scan)?;
+    let table_info = table.table_info();
+    let ttl = table_info.meta.options.ttl?;
+    let ts_column = table_info.meta.schema.timestamp_column()?;
+
+    let range = TimeRangePredicateBuilder::new(&ts_column.name, filters).build();
+    if range.is_empty() {
+        return None;
+    }
+    let boundary = Timestamp::new_millisecond(now_millis.saturating_sub(ttl.as_millis() as i64));
+    let fully_expired = matches!(range.end(), Some(end) if *end <= boundary);
+    let partially_expired = matches!(range.start(), Some(start) if *start < boundary);
+    if !fully_expired && !partially_expired {
+        return None;
+    }
+
+    Some(ExpiredRange {
+        table_name: format_full_table_name(
+            &table_info.catalog_name,
+            &table_info.schema_name,
+            &table_info.name,
+        ),
+        boundary,
+        fully_expired,
+    })
+}
@@ -0,0 +1,181 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hides the expired rows of the tables with a ttl column from queries.
+
+use common_time::util::ClockRef;
+use common_time::Timestamp;
+use datafusion::config::ConfigOptions;
+use datafusion::datasource::DefaultTableSource;
+use datafusion::error::Result as DfResult;
+use datafusion_common::tree_node::{Transformed, TreeNode};
+use datafusion_common::{DFSchemaRef, DataFusionError};
+use datafusion_expr::utils::conjunction;
+use datafusion_expr::{lit, Expr, LogicalPlan, LogicalPlanBuilder, TableScan};
+use datafusion_optimizer::analyzer::AnalyzerRule;
+use datatypes::prelude::{ConcreteDataType, Value};
+use datatypes::schema::{ColumnSchema, Schema};
+use table::table::adapter::DfTableProviderAdapter;
+use table::TableRef;
+
+/// RowTtlRule filters out the expired rows of the tables with a ttl column, by a filter above
+/// the scans of these tables. A row expires once its time in the ttl column passes, or once
+/// it's older than the ttl of the table, whichever comes first. Rows without expiry time only
+/// expire by the ttl of the table.
+///
+/// Compaction only drops the rows expired by the ttl column once it rewrites their SSTs, so
+/// queries always need this filter.
+pub struct RowTtlRule {
+    clock: ClockRef,
+}
+
+impl AnalyzerRule for RowTtlRule {
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> DfResult<LogicalPlan> {
+        // All the scans of the query see the same rows.
+        let now = Timestamp::new_millisecond(self.clock.now_millis());
+        plan.transform_up(&|plan| {
+            let LogicalPlan::TableScan(scan) = &plan else { return Ok(Transformed::No(plan)) };
+            match Self::filter_expired_rows(scan, now)? {
+                Some(plan) => Ok(Transformed::Yes(plan)),
+                None => Ok(Transformed::No(plan)),
+            }
+        })
+    }
+
+    fn name(&self) -> &str {
+        "RowTtlRule"
+    }
+}
+
+impl RowTtlRule {
+    pub fn new(clock: ClockRef) -> Self {
+        Self { clock }
+    }
+
+    /// Returns the plan filtering out the rows of `scan` expired at `now`, or `None` if the
+    /// scanned table has no ttl column.
+    fn filter_expired_rows(scan: &TableScan, now: Timestamp) -> DfResult<Option<LogicalPlan>> {
+        let Some(table) = scanned_table(scan) else { return Ok(None) };
+        let table_info = table.table_info();
+        let options = &table_info.meta.options;
+        let Some(ttl_column) = &options.ttl_column else { return Ok(None) };
+
+        let schema = &table_info.meta.schema;
+        let mut bounds = Vec::with_capacity(2);
+        // Rows expire after the time in the ttl column.
+        bounds.push((ttl_column_index(schema, ttl_column)?, now));
+        // Rows expire after the ttl of the table since their timestamps.
+        if let (Some(ttl), Some(ts_index)) = (options.ttl, schema.timestamp_index()) {
+            let expired_before = now.value() - ttl.as_millis() as i64;
+            bounds.push((ts_index, Timestamp::new_millisecond(expired_before)));
+        }
+
+        // Scans the columns to filter by if they are not projected, and projects them out after
+        // filtering.
+        let mut restored = None;
+        let builder = match &scan.projection {
+            Some(projection) if bounds.iter().any(|(i, _)| !projection.contains(i)) => {
+                restored = Some(columns_of(&scan.projected_schema));
+                let mut projection = projection.clone();
+                for (i, _) in &bounds {
+                    if !projection.contains(i) {
+                        projection.push(*i);
+                    }
+                }
+                LogicalPlanBuilder::scan_with_filters(
+                    scan.table_name.clone(),
+                    scan.source.clone(),
+                    Some(projection),
+                    scan.filters.clone(),
+                )?
+            }
+            _ => LogicalPlanBuilder::from(LogicalPlan::TableScan(scan.clone())),
+        };
+
+        let scan_schema = builder.schema().clone();
+        let predicates = bounds
+            .into_iter()
+            .map(|(index, bound)| {
+                live_rows_predicate(&scan_schema, &schema.column_schemas()[index], bound)
+            })
+            .collect::<DfResult<Vec<_>>>()?;
+        // Safety: there is at least the predicate of the ttl column.
+        let mut builder = builder.filter(conjunction(predicates).unwrap())?;
+        if let Some(columns) = restored {
+            builder = builder.project(columns)?;
+        }
+        builder.build().map(Some)
+    }
+}
+
+/// Returns the table scanned by `scan`, if it's a table of ours.
+pub(crate) fn scanned_table(scan: &TableScan) -> Option<TableRef> {
+    let table = scan
+        .source
+        .as_any()
+        .downcast_ref::<DefaultTableSource>()?
+        .table_provider
+        .as_any()
+        .downcast_ref::<DfTableProviderAdapter>()?
+        .table();
+    Some(table)
+}
+
+fn ttl_column_index(schema: &Schema, ttl_column: &str) -> DfResult<usize> {
+    schema.column_index_by_name(ttl_column).ok_or_else(|| {
+        DataFusionError::Plan(format!("ttl column {ttl_column} not found in table schema"))
+    })
+}
+
+fn columns_of(schema: &DFSchemaRef) -> Vec<Expr> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| Expr::Column(field.qualified_column()))
+        .collect()
+}
+
+/// Returns the predicate selecting the rows whose value of the timestamp `column` is after
+/// `bound`, or null.
+fn live_rows_predicate(
+    scan_schema: &DFSchemaRef,
+    column: &ColumnSchema,
+    bound: Timestamp,
+) -> DfResult<Expr> {
+    let ConcreteDataType::Timestamp(timestamp_type) = &column.data_type else {
+        return Err(DataFusionError::Plan(format!(
+            "ttl column {} is not a timestamp column",
+            column.name
+        )));
+    };
+    let bound = bound
+        .convert_to(timestamp_type.unit())
+        .and_then(|bound| {
+            Value::Timestamp(bound)
+                .try_to_scalar_value(&column.data_type)
+                .ok()
+        })
+        .ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "expiry time of column {} overflows: {bound:?}",
+                column.name
+            ))
+        })?;
+    let column = Expr::Column(
+        scan_schema
+            .field_with_unqualified_name(&column.name)?
+            .qualified_column(),
+    );
+    Ok(column.clone().is_null().or(column.gt(lit(bound))))
+}
@@ -15,19 +15,26 @@
 mod show;
 
 use std::collections::HashMap;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
-use catalog::CatalogManagerRef;
-use common_catalog::consts::DEFAULT_CATALOG_NAME;
+use async_stream::try_stream;
+use catalog::recycle_bin::is_recycled_table_name;
+use catalog::{table_names_stream, CatalogManagerRef, SchemaProviderRef, NAMES_PAGE_SIZE};
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, IMMUTABLE_FILE_ENGINE};
 use common_datasource::file_format::{infer_schemas, FileFormat, Format};
 use common_datasource::lister::{Lister, Source};
 use common_datasource::object_store::build_backend;
 use common_datasource::util::find_dir_and_filename;
+use common_error::prelude::BoxedError;
 use common_query::Output;
-use common_recordbatch::RecordBatches;
+use common_recordbatch::error::{Error as RecordBatchError, Result as RecordBatchResult};
+use common_recordbatch::{RecordBatch, RecordBatchStream, RecordBatches};
 use datatypes::prelude::*;
-use datatypes::schema::{ColumnSchema, RawSchema, Schema};
-use datatypes::vectors::{Helper, StringVector};
+use datatypes::schema::{ColumnSchema, RawSchema, Schema, SchemaRef};
+use datatypes::vectors::{Helper, StringVector, StringVectorBuilder};
+use futures::{Stream, TryStreamExt};
 use object_store::ObjectStore;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -44,6 +51,8 @@ use crate::error::{self, Result};
 
 const SCHEMAS_COLUMN: &str = "Schemas";
 const TABLES_COLUMN: &str = "Tables";
+const TABLE_TYPE_COLUMN: &str = "Table_type";
+const TABLE_COMMENT_COLUMN: &str = "Comment";
 const COLUMN_NAME_COLUMN: &str = "Field";
 const COLUMN_TYPE_COLUMN: &str = "Type";
 const COLUMN_NULLABLE_COLUMN: &str = "Null";
@@ -146,43 +155,166 @@ pub async fn show_tables(
         }
     );
 
-    let schema = if let Some(database) = stmt.database {
-        database
+    let schema_name = if let Some(database) = &stmt.database {
+        database.clone()
     } else {
         query_ctx.current_schema()
     };
     // TODO(sunng87): move this function into query_ctx
     let schema = catalog_manager
-        .schema(&query_ctx.current_catalog(), &schema)
+        .schema(&query_ctx.current_catalog(), &schema_name)
         .await
         .context(error::CatalogSnafu)?
-        .context(error::SchemaNotFoundSnafu { schema })?;
-    let mut tables = schema.table_names().await.context(error::CatalogSnafu)?;
-    // TODO(dennis): Specify the order of the results in schema provider API
-    tables.sort();
+        .context(error::SchemaNotFoundSnafu {
+            schema: schema_name,
+        })?;
+    let output_schema = show_tables_output_schema(&stmt);
+    let stream = show_tables_stream(output_schema.clone(), schema, stmt).map_err(|e| {
+        RecordBatchError::External {
+            source: BoxedError::new(e),
+        }
+    });
+    Ok(Output::Stream(Box::pin(ShowTablesStream {
+        schema: output_schema,
+        stream: Box::pin(stream),
+    })))
+}
+
+/// Streams a record batch of the tables shown for every [NAMES_PAGE_SIZE] names listed, so the
+/// names of large schemas are neither listed nor shown at once. The names are filtered page by
+/// page, so only the shown names are kept.
+fn show_tables_stream(
+    output_schema: SchemaRef,
+    schema: SchemaProviderRef,
+    stmt: ShowTables,
+) -> impl Stream<Item = Result<RecordBatch>> + Send {
+    try_stream!({
+        let mut table_names = Box::pin(table_names_stream(schema.clone()));
+        let mut tables = Vec::new();
+        while let Some(table) = table_names.try_next().await.context(error::CatalogSnafu)? {
+            if !is_recycled_table_name(&table) {
+                tables.push(table);
+            }
+            if tables.len() == NAMES_PAGE_SIZE {
+                let tables = std::mem::take(&mut tables);
+                yield show_tables_batch(&output_schema, &schema, tables, &stmt).await?;
+            }
+        }
+        yield show_tables_batch(&output_schema, &schema, tables, &stmt).await?;
+    })
+}
+
+struct ShowTablesStream {
+    schema: SchemaRef,
+    stream: Pin<Box<dyn Stream<Item = RecordBatchResult<RecordBatch>> + Send>>,
+}
+
+impl RecordBatchStream for ShowTablesStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Stream for ShowTablesStream {
+    type Item = RecordBatchResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.stream).poll_next(ctx)
+    }
+}
+
+/// Returns the output schema of SHOW TABLES: the table names, followed by the table types for
+/// `SHOW FULL TABLES` and the table comments for `WITH COMMENT`.
+fn show_tables_output_schema(stmt: &ShowTables) -> SchemaRef {
+    let mut columns = vec![ColumnSchema::new(
+        TABLES_COLUMN,
+        ConcreteDataType::string_datatype(),
+        false,
+    )];
+    if stmt.full {
+        columns.push(ColumnSchema::new(
+            TABLE_TYPE_COLUMN,
+            ConcreteDataType::string_datatype(),
+            false,
+        ));
+    }
+    if stmt.with_comment {
+        columns.push(ColumnSchema::new(
+            TABLE_COMMENT_COLUMN,
+            ConcreteDataType::string_datatype(),
+            true,
+        ));
+    }
+    Arc::new(Schema::new(columns))
+}
 
-    let tables = if let ShowKind::Like(ident) = stmt.kind {
+/// Builds the record batch of the `tables` matching the `kind` of SHOW TABLES.
+async fn show_tables_batch(
+    output_schema: &SchemaRef,
+    schema: &SchemaProviderRef,
+    tables: Vec<String>,
+    stmt: &ShowTables,
+) -> Result<RecordBatch> {
+    let tables = if let ShowKind::Like(ident) = &stmt.kind {
         Helper::like_utf8(tables, &ident.value).context(error::VectorComputationSnafu)?
     } else {
         Arc::new(StringVector::from(tables))
     };
+    if !stmt.full && !stmt.with_comment {
+        return RecordBatch::new(output_schema.clone(), vec![tables])
+            .context(error::CreateRecordBatchSnafu);
+    }
 
-    let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
-        TABLES_COLUMN,
-        ConcreteDataType::string_datatype(),
-        false,
-    )]));
-    let records = RecordBatches::try_from_columns(schema, vec![tables])
-        .context(error::CreateRecordBatchSnafu)?;
-    Ok(Output::RecordBatches(records))
+    let tables = tables
+        .as_any()
+        .downcast_ref::<StringVector>()
+        .expect("table names should be strings")
+        .iter_data()
+        .flatten()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    // Looks up the tables of the page at once, as each lookup may be a request to the metasrv.
+    let found = schema.tables(&tables).await.context(error::CatalogSnafu)?;
+    let mut names = StringVectorBuilder::with_capacity(tables.len());
+    let mut types = StringVectorBuilder::with_capacity(tables.len());
+    let mut comments = StringVectorBuilder::with_capacity(tables.len());
+    for (name, table) in tables.iter().zip(found) {
+        // The table may be dropped after its name is listed.
+        let Some(table) = table else { continue };
+        names.push(Some(name.as_str()));
+        types.push(Some(table.table_type().as_sql_str()));
+        comments.push(table.table_info().desc.as_deref());
+    }
+
+    let mut columns: Vec<VectorRef> = vec![Arc::new(names.finish())];
+    if stmt.full {
+        columns.push(Arc::new(types.finish()));
+    }
+    if stmt.with_comment {
+        columns.push(Arc::new(comments.finish()));
+    }
+    RecordBatch::new(output_schema.clone(), columns).context(error::CreateRecordBatchSnafu)
 }
 
-pub fn show_create_table(table: TableRef, partitions: Option<Partitions>) -> Result<Output> {
+/// Shows the statement creating the table, in standard MySQL syntax if `for_mysql` is true.
+pub fn show_create_table(
+    table: TableRef,
+    partitions: Option<Partitions>,
+    for_mysql: bool,
+) -> Result<Output> {
     let table_info = table.table_info();
     let table_name = &table_info.name;
-    let mut stmt = show::create_table_stmt(&table_info)?;
-    stmt.partitions = partitions;
-    let sql = format!("{}", stmt);
+    let sql = if table_info.meta.engine == IMMUTABLE_FILE_ENGINE {
+        // Only the external table statement is able to recreate a file engine table.
+        show::create_external_table_stmt(&table_info)?.to_string()
+    } else {
+        let stmt = show::create_table_stmt(&table_info, partitions)?;
+        if for_mysql {
+            stmt.for_mysql().to_string()
+        } else {
+            stmt.to_string()
+        }
+    };
     let columns = vec![
         Arc::new(StringVector::from(vec![table_name.clone()])) as _,
         Arc::new(StringVector::from(vec![sql])) as _,
@@ -336,6 +468,7 @@ fn parse_immutable_file_table_format(
             Format::Csv(format) => Box::new(format),
             Format::Json(format) => Box::new(format),
             Format::Parquet(format) => Box::new(format),
+            Format::Orc(format) => Box::new(format),
         },
     )
 }
@@ -357,23 +490,115 @@ async fn infer_immutable_file_table_schema(
 mod test {
     use std::sync::Arc;
 
+    use catalog::local::new_memory_catalog_list;
+    use catalog::CatalogManager;
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
     use common_query::Output;
     use common_recordbatch::{RecordBatch, RecordBatches};
     use common_time::timestamp::TimeUnit;
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema, Schema, SchemaRef};
     use datatypes::vectors::{StringVector, TimestampMillisecondVector, UInt32Vector, VectorRef};
+    use session::context::QueryContext;
     use snafu::ResultExt;
+    use sql::dialect::GenericDialect;
+    use sql::parser::ParserContext;
+    use sql::statements::statement::Statement;
     use table::test_util::MemTable;
     use table::TableRef;
 
     use crate::error;
     use crate::error::Result;
     use crate::sql::{
-        describe_table, DESCRIBE_TABLE_OUTPUT_SCHEMA, NULLABLE_NO, NULLABLE_YES,
+        describe_table, show_tables, DESCRIBE_TABLE_OUTPUT_SCHEMA, NULLABLE_NO, NULLABLE_YES,
         SEMANTIC_TYPE_FIELD, SEMANTIC_TYPE_TIME_INDEX,
     };
 
+    async fn show_tables_output(sql: &str) -> String {
+        let catalog_manager = new_memory_catalog_list().unwrap();
+        let schema = catalog_manager
+            .schema(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let tables = [
+            MemTable::default_numbers_table().with_desc("numbers from 0 to 99"),
+            MemTable::new(
+                "metrics",
+                RecordBatch::new(
+                    Arc::new(Schema::new(vec![ColumnSchema::new(
+                        "n",
+                        ConcreteDataType::uint32_datatype(),
+                        true,
+                    )])),
+                    vec![Arc::new(UInt32Vector::from_slice([0])) as _],
+                )
+                .unwrap(),
+            ),
+        ];
+        for table in tables {
+            let _ = schema
+                .register_table(table.table_name().to_string(), Arc::new(table))
+                .await
+                .unwrap();
+        }
+
+        let mut stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        let Statement::ShowTables(stmt) = stmts.remove(0) else { unreachable!() };
+        let Output::Stream(stream) = show_tables(stmt, catalog_manager, QueryContext::arc())
+            .await
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        let records = RecordBatches::try_collect(stream).await.unwrap();
+        records.pretty_print().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_show_full_tables() {
+        let expected = "\
++---------+------------+
+| Tables  | Table_type |
++---------+------------+
+| metrics | BASE TABLE |
+| numbers | BASE TABLE |
++---------+------------+";
+        assert_eq!(expected, show_tables_output("SHOW FULL TABLES").await);
+
+        let expected = "\
++---------+------------+----------------------+
+| Tables  | Table_type | Comment              |
++---------+------------+----------------------+
+| metrics | BASE TABLE |                      |
+| numbers | BASE TABLE | numbers from 0 to 99 |
++---------+------------+----------------------+";
+        assert_eq!(
+            expected,
+            show_tables_output("SHOW FULL TABLES WITH COMMENT").await
+        );
+
+        let expected = "\
++---------+----------------------+
+| Tables  | Comment              |
++---------+----------------------+
+| numbers | numbers from 0 to 99 |
++---------+----------------------+";
+        assert_eq!(
+            expected,
+            show_tables_output("SHOW TABLES LIKE 'num%' WITH COMMENT").await
+        );
+
+        let expected = "\
++---------+
+| Tables  |
++---------+
+| metrics |
+| numbers |
++---------+";
+        assert_eq!(expected, show_tables_output("SHOW TABLES").await);
+    }
+
     #[test]
     fn test_describe_table_multiple_columns() -> Result<()> {
         let table_name = "test_table";
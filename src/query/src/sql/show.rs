@@ -13,8 +13,9 @@
 // limitations under the License.
 use std::fmt::Display;
 
+use common_datasource::file_format::normalize_format_options;
+use common_telemetry::warn;
 use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema, SchemaRef, COMMENT_KEY};
-use humantime::format_duration;
 use snafu::ResultExt;
 use sql::ast::{
     ColumnDef, ColumnOption, ColumnOptionDef, Expr, ObjectName, SqlOption, TableConstraint,
@@ -22,9 +23,13 @@ use sql::ast::{
 };
 use sql::dialect::GenericDialect;
 use sql::parser::ParserContext;
-use sql::statements::create::{CreateTable, TIME_INDEX};
+use sql::statements::create::{
+    storage_column_option, CreateExternalTable, CreateTable, Partitions, COMPRESSION, ENCODING,
+    TIME_INDEX,
+};
 use sql::statements::{self};
 use table::metadata::{TableInfoRef, TableMeta};
+use table::requests::{COMPACTION_TIME_WINDOW_KEY, IMMUTABLE_TABLE_META_KEY, REGIONS_KEY};
 
 use crate::error::{ConvertSqlTypeSnafu, ConvertSqlValueSnafu, Result, SqlSnafu};
 
@@ -52,30 +57,20 @@ fn create_sql_options(table_meta: &TableMeta) -> Vec<SqlOption> {
 
     if !table_meta.region_numbers.is_empty() {
         options.push(sql_option(
-            "regions",
+            REGIONS_KEY,
             number_value(table_meta.region_numbers.len()),
         ));
     }
 
-    if let Some(write_buffer_size) = table_opts.write_buffer_size {
-        options.push(sql_option(
-            "write_buffer_size",
-            string_value(write_buffer_size.to_string()),
-        ));
-    }
-    if let Some(ttl) = table_opts.ttl {
-        options.push(sql_option(
-            "ttl",
-            string_value(format_duration(ttl).to_string()),
-        ));
-    }
-
-    if let Some(w) = table_opts.compaction_time_window {
-        options.push(sql_option("compaction_time_window", number_value(w)));
-    }
-
-    for (k, v) in &table_opts.extra_options {
-        options.push(sql_option(k, string_value(v)));
+    // Values of the options are in the canonical format, shared with the `table_options` of
+    // `information_schema`.
+    for (key, value) in table_opts.option_values() {
+        let value = if key == COMPACTION_TIME_WINDOW_KEY {
+            SqlValue::Number(value, false)
+        } else {
+            string_value(value)
+        };
+        options.push(sql_option(&key, value));
     }
 
     options
@@ -114,6 +109,17 @@ fn create_column_def(column_schema: &ColumnSchema) -> Result<ColumnDef> {
         options.push(column_option_def(ColumnOption::Comment(c.to_string())));
     }
 
+    if let Some(encoding) = column_schema.encoding() {
+        options.push(column_option_def(storage_column_option(ENCODING, encoding)));
+    }
+
+    if let Some(compression) = column_schema.compression() {
+        options.push(column_option_def(storage_column_option(
+            COMPRESSION,
+            compression,
+        )));
+    }
+
     Ok(ColumnDef {
         name: name[..].into(),
         data_type: statements::concrete_data_type_to_sql_data_type(&column_schema.data_type)
@@ -150,8 +156,12 @@ fn create_table_constraints(schema: &SchemaRef, table_meta: &TableMeta) -> Vec<T
     constraints
 }
 
-/// Create a CreateTable statement from table info.
-pub fn create_table_stmt(table_info: &TableInfoRef) -> Result<CreateTable> {
+/// Create a CreateTable statement from table info, with the `partitions` of the table if it's
+/// partitioned.
+pub fn create_table_stmt(
+    table_info: &TableInfoRef,
+    partitions: Option<Partitions>,
+) -> Result<CreateTable> {
     let table_meta = &table_info.meta;
     let table_name = &table_info.name;
     let schema = &table_info.meta.schema;
@@ -172,7 +182,38 @@ pub fn create_table_stmt(table_info: &TableInfoRef) -> Result<CreateTable> {
         engine: table_meta.engine.clone(),
         constraints,
         options: create_sql_options(table_meta),
-        partitions: None,
+        partitions,
+    })
+}
+
+/// Create a CreateExternalTable statement from the table info of a file engine table.
+pub fn create_external_table_stmt(table_info: &TableInfoRef) -> Result<CreateExternalTable> {
+    let table_meta = &table_info.meta;
+    let columns = table_meta
+        .schema
+        .column_schemas()
+        .iter()
+        .map(create_column_def)
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut options = table_meta.options.extra_options.clone();
+    // The files are listed again when the table is created.
+    let _ = options.remove(IMMUTABLE_TABLE_META_KEY);
+    // Tables created by old versions may store the format options in deprecated keys.
+    if let Err(e) = normalize_format_options(&mut options) {
+        warn!(
+            "Failed to normalize the format options of table {}, error: {e:?}",
+            table_info.name
+        );
+    }
+
+    Ok(CreateExternalTable {
+        name: ObjectName(vec![table_info.name[..].into()]),
+        columns,
+        constraints: vec![],
+        options,
+        if_not_exists: true,
+        engine: table_meta.engine.clone(),
     })
 }
 
@@ -183,7 +224,10 @@ mod tests {
     use common_time::timestamp::TimeUnit;
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::{Schema, SchemaRef};
+    use sql::statements::create::PartitionEntry;
+    use sql::statements::statement::Statement;
     use table::metadata::*;
+    use table::requests::TableOptions;
 
     use super::*;
 
@@ -191,7 +235,11 @@ mod tests {
     fn test_show_create_table_sql() {
         let schema = vec![
             ColumnSchema::new("id", ConcreteDataType::uint32_datatype(), true),
-            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true)
+                .with_encoding("dictionary")
+                .unwrap()
+                .with_compression("zstd")
+                .unwrap(),
             ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
             ColumnSchema::new("disk", ConcreteDataType::float32_datatype(), true),
             ColumnSchema::new(
@@ -238,14 +286,14 @@ mod tests {
                 .unwrap(),
         );
 
-        let stmt = create_table_stmt(&info).unwrap();
+        let stmt = create_table_stmt(&info, None).unwrap();
 
         let sql = format!("\n{}", stmt);
         assert_eq!(
             r#"
 CREATE TABLE IF NOT EXISTS system_metrics (
   id INT UNSIGNED NULL,
-  host STRING NULL,
+  host STRING NULL ENCODING 'dictionary' COMPRESSION 'zstd',
   cpu DOUBLE NULL,
   disk FLOAT NULL,
   ts TIMESTAMP(3) NOT NULL DEFAULT current_timestamp(),
@@ -258,5 +306,231 @@ WITH(
 )"#,
             sql
         );
+
+        let stmts = ParserContext::create_with_dialect(&sql, &GenericDialect {}).unwrap();
+        let Statement::CreateTable(reparsed) = &stmts[0] else { unreachable!() };
+        assert_eq!(sql, format!("\n{}", reparsed));
+
+        let sql = format!("\n{}", stmt.for_mysql());
+        assert_eq!(
+            r#"
+CREATE TABLE IF NOT EXISTS `system_metrics` (
+  `id` INT UNSIGNED,
+  `host` VARCHAR(255),
+  `cpu` DOUBLE NULL,
+  `disk` FLOAT NULL,
+  `ts` TIMESTAMP(3) NOT NULL DEFAULT current_timestamp(),
+  KEY `__time_index` (`ts`),
+  PRIMARY KEY (`id`, `host`)
+)
+/* ENGINE=mito
+WITH(
+  regions = 3
+) */"#,
+            sql
+        );
+    }
+
+    #[test]
+    fn test_show_create_table_with_partitions() {
+        let schema = vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_datatype(TimeUnit::Millisecond),
+                false,
+            )
+            .with_time_index(true),
+        ];
+        let meta = TableMetaBuilder::default()
+            .schema(SchemaRef::new(Schema::new(schema)))
+            .primary_key_indices(vec![0])
+            .engine("mito".to_string())
+            .next_column_id(0)
+            .engine_options(Default::default())
+            .options(Default::default())
+            .created_on(Default::default())
+            .build()
+            .unwrap();
+        let info = Arc::new(
+            TableInfoBuilder::default()
+                .table_id(1024)
+                .table_version(0 as TableVersion)
+                .name("system_metrics")
+                .schema_name("public".to_string())
+                .catalog_name("greptime".to_string())
+                .table_type(TableType::Base)
+                .meta(meta)
+                .build()
+                .unwrap(),
+        );
+        let partitions = Partitions {
+            column_list: vec!["host".into(), "ts".into()],
+            entries: vec![
+                PartitionEntry {
+                    name: "r0".into(),
+                    value_list: vec![string_value("a"), number_value(1000)],
+                },
+                PartitionEntry {
+                    name: "r1".into(),
+                    value_list: vec![
+                        string_value("z"),
+                        SqlValue::Number("MAXVALUE".to_string(), false),
+                    ],
+                },
+                PartitionEntry {
+                    name: "r2".into(),
+                    value_list: vec![
+                        SqlValue::Number("MAXVALUE".to_string(), false),
+                        SqlValue::Number("MAXVALUE".to_string(), false),
+                    ],
+                },
+            ],
+        };
+
+        let stmt = create_table_stmt(&info, Some(partitions)).unwrap();
+
+        let sql = format!("\n{}", stmt);
+        assert_eq!(
+            r#"
+CREATE TABLE IF NOT EXISTS system_metrics (
+  host STRING NULL,
+  cpu DOUBLE NULL,
+  ts TIMESTAMP(3) NOT NULL,
+  TIME INDEX (ts),
+  PRIMARY KEY (host)
+)
+PARTITION BY RANGE COLUMNS (host, ts) (
+  PARTITION r0 VALUES LESS THAN ('a', 1000),
+  PARTITION r1 VALUES LESS THAN ('z', MAXVALUE),
+  PARTITION r2 VALUES LESS THAN (MAXVALUE, MAXVALUE)
+)
+ENGINE=mito
+"#,
+            sql
+        );
+
+        let stmts = ParserContext::create_with_dialect(&sql, &GenericDialect {}).unwrap();
+        let Statement::CreateTable(reparsed) = &stmts[0] else { unreachable!() };
+        assert_eq!(stmt.partitions, reparsed.partitions);
+
+        let sql = format!("\n{}", stmt.for_mysql());
+        assert_eq!(
+            r#"
+CREATE TABLE IF NOT EXISTS `system_metrics` (
+  `host` VARCHAR(255),
+  `cpu` DOUBLE NULL,
+  `ts` TIMESTAMP(3) NOT NULL,
+  KEY `__time_index` (`ts`),
+  PRIMARY KEY (`host`)
+)
+/* PARTITION BY RANGE COLUMNS (host, ts) (
+  PARTITION r0 VALUES LESS THAN ('a', 1000),
+  PARTITION r1 VALUES LESS THAN ('z', MAXVALUE),
+  PARTITION r2 VALUES LESS THAN (MAXVALUE, MAXVALUE)
+)
+ENGINE=mito */"#,
+            sql
+        );
+    }
+
+    #[test]
+    fn test_show_create_table_wal_disabled() {
+        let schema = vec![ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_datatype(TimeUnit::Millisecond),
+            false,
+        )
+        .with_time_index(true)];
+        let options = TableOptions {
+            wal_disabled: true,
+            ..Default::default()
+        };
+        let meta = TableMetaBuilder::default()
+            .schema(SchemaRef::new(Schema::new(schema)))
+            .primary_key_indices(vec![])
+            .engine("mito".to_string())
+            .next_column_id(0)
+            .engine_options(Default::default())
+            .options(options)
+            .created_on(Default::default())
+            .region_numbers(vec![0])
+            .build()
+            .unwrap();
+        let info = Arc::new(
+            TableInfoBuilder::default()
+                .table_id(1024)
+                .table_version(0 as TableVersion)
+                .name("benchmark")
+                .schema_name("public".to_string())
+                .catalog_name("greptime".to_string())
+                .table_type(TableType::Base)
+                .meta(meta)
+                .build()
+                .unwrap(),
+        );
+
+        let stmt = create_table_stmt(&info, None).unwrap();
+        let sql = stmt.to_string();
+        assert!(
+            sql.ends_with("WITH(\n  regions = 1,\n  wal = 'disabled'\n)"),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn test_show_create_external_table() {
+        let schema = vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+        ];
+        let mut options = TableOptions::default();
+        options.extra_options = [
+            ("LOCATION", "/var/data/city.csv"),
+            ("FORMAT", "CSV"),
+            ("DELIMTERL", "9"),
+            (IMMUTABLE_TABLE_META_KEY, r#"{"files":["city.csv"]}"#),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let meta = TableMetaBuilder::new_external_table()
+            .schema(SchemaRef::new(Schema::new(schema)))
+            .engine("file")
+            .options(options)
+            .build()
+            .unwrap();
+        let info = Arc::new(
+            TableInfoBuilder::new("city", meta)
+                .table_id(1024)
+                .table_type(TableType::Base)
+                .build()
+                .unwrap(),
+        );
+
+        let stmt = create_external_table_stmt(&info).unwrap();
+
+        let sql = format!("\n{}", stmt);
+        assert_eq!(
+            r#"
+CREATE EXTERNAL TABLE IF NOT EXISTS city (
+  host STRING NULL,
+  cpu DOUBLE NULL
+)
+ENGINE=file
+WITH(
+  DELIMITER = '9',
+  FORMAT = 'csv',
+  FORMAT_HAS_HEADER = 'true',
+  LOCATION = '/var/data/city.csv',
+  SCHEMA_INFER_MAX_RECORD = '1000'
+)"#,
+            sql
+        );
+
+        let stmts = ParserContext::create_with_dialect(&sql, &GenericDialect {}).unwrap();
+        let Statement::CreateExternalTable(reparsed) = &stmts[0] else { unreachable!() };
+        assert_eq!(&stmt, reparsed);
     }
 }
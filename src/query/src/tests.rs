@@ -21,14 +21,24 @@ use crate::QueryEngineRef;
 
 mod argmax_test;
 mod argmin_test;
+mod decorrelate_test;
+mod dist_aggr_test;
+mod exact_count_test;
+mod gap_fill_test;
+mod information_schema_test;
 mod mean_test;
+mod multi_node_table;
 mod my_sum_udaf_example;
 mod percentile_test;
 mod polyval_test;
 mod query_engine_test;
+mod retention_test;
+mod row_ttl_test;
 mod scipy_stats_norm_cdf_test;
 mod scipy_stats_norm_pdf;
+mod table_options_test;
 mod time_range_filter_test;
+mod topk_test;
 
 mod function;
 mod pow;
@@ -0,0 +1,223 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use catalog::local::{new_memory_catalog_list, MemoryCatalogProvider, MemorySchemaProvider};
+use common_recordbatch::RecordBatch;
+use datatypes::data_type::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{Int64Vector, StringVector};
+use table::test_util::MemTable;
+
+use crate::tests::exec_selection;
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+fn new_table(name: &str, hosts: Vec<&str>, values: Vec<Option<i64>>) -> Arc<MemTable> {
+    let schema = Schema::try_new(vec![
+        ColumnSchema::new(
+            "host".to_string(),
+            ConcreteDataType::string_datatype(),
+            false,
+        ),
+        ColumnSchema::new("v".to_string(), ConcreteDataType::int64_datatype(), true),
+    ])
+    .unwrap();
+    Arc::new(MemTable::new(
+        name,
+        RecordBatch::new(
+            Arc::new(schema),
+            vec![
+                Arc::new(StringVector::from(hosts)) as Arc<_>,
+                Arc::new(Int64Vector::from(values)) as Arc<_>,
+            ],
+        )
+        .unwrap(),
+    ))
+}
+
+fn create_test_engine() -> QueryEngineRef {
+    let t1 = new_table(
+        "t1",
+        vec!["a", "b", "c", "d"],
+        vec![Some(1), Some(2), None, Some(4)],
+    );
+    // Host "b" only has a null value in t2.
+    let t2 = new_table(
+        "t2",
+        vec!["a", "a", "b", "e"],
+        vec![Some(1), Some(3), None, Some(5)],
+    );
+
+    let catalog_list = new_memory_catalog_list().unwrap();
+
+    let default_schema = Arc::new(MemorySchemaProvider::new());
+    MemorySchemaProvider::register_table_sync(&default_schema, "t1".to_string(), t1).unwrap();
+    MemorySchemaProvider::register_table_sync(&default_schema, "t2".to_string(), t2).unwrap();
+
+    let default_catalog = Arc::new(MemoryCatalogProvider::new());
+    default_catalog
+        .register_schema_sync("public".to_string(), default_schema)
+        .unwrap();
+    catalog_list
+        .register_catalog_sync("greptime".to_string(), default_catalog)
+        .unwrap();
+
+    QueryEngineFactory::new(catalog_list).query_engine()
+}
+
+/// Returns the rows of the query, so queries with different column names can be compared.
+async fn query_rows(engine: &QueryEngineRef, sql: &str) -> Vec<String> {
+    exec_selection(engine.clone(), sql)
+        .await
+        .iter()
+        .flat_map(|batch| {
+            batch.rows().map(|row| {
+                row.iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+        })
+        .collect()
+}
+
+async fn check_same_rows(engine: &QueryEngineRef, sql: &str, join_sql: &str) -> Vec<String> {
+    let rows = query_rows(engine, sql).await;
+    assert_eq!(query_rows(engine, join_sql).await, rows, "{sql}");
+    rows
+}
+
+#[tokio::test]
+async fn test_correlated_scalar_subquery() {
+    let engine = create_test_engine();
+
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host, (SELECT max(v) FROM t2 WHERE t2.host = t1.host) AS m FROM t1 ORDER BY host",
+        "SELECT t1.host, m.m FROM t1 \
+         LEFT JOIN (SELECT host, max(v) AS m FROM t2 GROUP BY host) m ON t1.host = m.host \
+         ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(4, rows.len());
+
+    // The count of no rows is 0.
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host, (SELECT count(v) FROM t2 WHERE t2.host = t1.host AND t2.v > 0) AS c \
+         FROM t1 ORDER BY host",
+        "SELECT t1.host, coalesce(c.c, 0) AS c FROM t1 \
+         LEFT JOIN (SELECT host, count(v) AS c FROM t2 WHERE v > 0 GROUP BY host) c \
+         ON t1.host = c.host ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(4, rows.len());
+
+    // So are the counts in an expression, while the other aggregates of no rows are null.
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host, (SELECT count(v) + 1 FROM t2 WHERE t2.host = t1.host AND t2.v > 0) AS c, \
+         (SELECT count(v) + max(v) FROM t2 WHERE t2.host = t1.host AND t2.v > 0) AS m \
+         FROM t1 ORDER BY host",
+        "SELECT t1.host, coalesce(c.c, 0) + 1 AS c, c.c + c.m AS m FROM t1 \
+         LEFT JOIN (SELECT host, count(v) AS c, max(v) AS m FROM t2 WHERE v > 0 GROUP BY host) c \
+         ON t1.host = c.host ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(4, rows.len());
+
+    // In a filter.
+    check_same_rows(
+        &engine,
+        "SELECT host FROM t1 WHERE v <= (SELECT max(v) FROM t2 WHERE t2.host = t1.host) \
+         ORDER BY host",
+        "SELECT t1.host FROM t1 \
+         JOIN (SELECT host, max(v) AS m FROM t2 GROUP BY host) m ON t1.host = m.host \
+         WHERE t1.v <= m.m ORDER BY t1.host",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_correlated_exists() {
+    let engine = create_test_engine();
+
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host FROM t1 WHERE EXISTS (SELECT 1 FROM t2 WHERE t2.host = t1.host) \
+         ORDER BY host",
+        "SELECT DISTINCT t1.host FROM t1 JOIN t2 ON t1.host = t2.host ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(2, rows.len());
+
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host FROM t1 WHERE NOT EXISTS (SELECT 1 FROM t2 WHERE t2.host = t1.host) \
+         ORDER BY host",
+        "SELECT t1.host FROM t1 LEFT JOIN t2 ON t1.host = t2.host WHERE t2.host IS NULL \
+         ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(2, rows.len());
+}
+
+#[tokio::test]
+async fn test_not_in_with_nulls() {
+    let engine = create_test_engine();
+
+    // t2 has a null value, so `v NOT IN (...)` is never true.
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host FROM t1 WHERE v NOT IN (SELECT v FROM t2) ORDER BY host",
+        "SELECT t1.host FROM t1 LEFT JOIN t2 ON t1.v = t2.v \
+         CROSS JOIN (SELECT count(*) - count(v) AS nulls FROM t2) n \
+         WHERE t2.v IS NULL AND t1.v IS NOT NULL AND n.nulls = 0 ORDER BY t1.host",
+    )
+    .await;
+    assert!(rows.is_empty());
+
+    // The null value of t1 is not in the result.
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host FROM t1 WHERE v NOT IN (SELECT v FROM t2 WHERE v IS NOT NULL) \
+         ORDER BY host",
+        "SELECT t1.host FROM t1 \
+         LEFT JOIN (SELECT v FROM t2 WHERE v IS NOT NULL) s ON t1.v = s.v \
+         WHERE s.v IS NULL AND t1.v IS NOT NULL ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(vec!["b", "d"], rows);
+
+    // Correlated: "a" has 1 in its values, "b" has a null value, and "c" and "d" have no
+    // values at all.
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host FROM t1 WHERE v NOT IN (SELECT v FROM t2 WHERE t2.host = t1.host) \
+         ORDER BY host",
+        "SELECT t1.host FROM t1 LEFT JOIN t2 ON t1.host = t2.host WHERE t2.host IS NULL \
+         ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(2, rows.len());
+
+    let rows = check_same_rows(
+        &engine,
+        "SELECT host FROM t1 WHERE v IN (SELECT v FROM t2) ORDER BY host",
+        "SELECT DISTINCT t1.host FROM t1 JOIN t2 ON t1.v = t2.v ORDER BY t1.host",
+    )
+    .await;
+    assert_eq!(1, rows.len());
+}
@@ -0,0 +1,81 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_recordbatch::{RecordBatch, RecordBatches};
+use datatypes::prelude::*;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{Float64Vector, StringVector};
+use table::test_util::MemTable;
+
+use crate::tests::exec_selection;
+use crate::tests::multi_node_table::{create_engine, MultiNodeTable};
+use crate::QueryEngineRef;
+
+fn metrics(rows: impl Iterator<Item = usize> + Clone) -> MemTable {
+    let schema = Arc::new(Schema::new(vec![
+        ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+        ColumnSchema::new("value", ConcreteDataType::float64_datatype(), true),
+    ]));
+    let hosts = rows
+        .clone()
+        .map(|i| format!("host{}", i % 3))
+        .collect::<Vec<_>>();
+    let values = rows
+        .map(|i| (i * 37 % 101) as f64 / 4.0)
+        .collect::<Vec<_>>();
+    let columns: Vec<VectorRef> = vec![
+        Arc::new(StringVector::from(hosts)),
+        Arc::new(Float64Vector::from_vec(values)),
+    ];
+    MemTable::new("metrics", RecordBatch::new(schema, columns).unwrap())
+}
+
+async fn query(engine: QueryEngineRef, sql: &str) -> String {
+    let batches = exec_selection(engine, sql).await;
+    let batches = RecordBatches::try_new(batches[0].schema.clone(), batches).unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_distributed_udaf() {
+    let num_rows = 100;
+    let table = Arc::new(MultiNodeTable::new(
+        Arc::new(metrics(0..num_rows)),
+        (0..num_rows)
+            .step_by(30)
+            .map(|start| Arc::new(metrics(start..num_rows.min(start + 30))) as _)
+            .collect(),
+    ));
+    let dist_engine = create_engine(table.clone());
+    let engine = create_engine(Arc::new(metrics(0..num_rows)));
+
+    let sqls = [
+        "select approx_percentile(value, 50.0) as p from metrics",
+        "select host, approx_percentile(value, 90.0) as p, count(value) as c from metrics \
+        where value > 5 group by host order by host",
+    ];
+    for sql in sqls {
+        let dist_output = query(dist_engine.clone(), sql).await;
+        assert!(table.take_pushed_down(), "{sql}");
+
+        let output = query(engine.clone(), sql).await;
+        assert_eq!(output, dist_output, "{sql}");
+
+        // Few values are not compressed by the digest, so the percentiles are exact.
+        let exact_sql = sql.replace("approx_percentile", "percentile");
+        assert_eq!(output, query(engine.clone(), &exact_sql).await, "{sql}");
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use catalog::local::{MemoryCatalogManager, MemoryCatalogProvider, MemorySchemaProvider};
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_query::logical_plan::Expr as TableExpr;
+use common_query::physical_plan::{Partitioning, PhysicalPlan, PhysicalPlanRef};
+use common_recordbatch::{RecordBatches, SendableRecordBatchStream};
+use datafusion::execution::context::TaskContext;
+use datatypes::schema::SchemaRef;
+use table::metadata::TableInfoRef;
+use table::test_util::MemTable;
+use table::{Table, TableRef};
+
+use crate::tests::exec_selection;
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+/// A [MemTable] that records whether its rows are scanned, and knows its exact row count
+/// unless some rows are "deleted".
+struct CountedTable {
+    table: MemTable,
+    deleted: AtomicBool,
+    scanned: Arc<AtomicBool>,
+}
+
+impl CountedTable {
+    fn new() -> Self {
+        Self {
+            table: MemTable::default_numbers_table(),
+            deleted: AtomicBool::new(false),
+            scanned: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn take_scanned(&self) -> bool {
+        self.scanned.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Sets `scanned` once the scan is executed, as the scan is also planned for the fallback of
+/// the exact count.
+#[derive(Debug)]
+struct RecordedScan {
+    plan: PhysicalPlanRef,
+    scanned: Arc<AtomicBool>,
+}
+
+impl PhysicalPlan for RecordedScan {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.plan.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.plan.output_partitioning()
+    }
+
+    fn children(&self) -> Vec<PhysicalPlanRef> {
+        vec![]
+    }
+
+    fn with_new_children(
+        &self,
+        _children: Vec<PhysicalPlanRef>,
+    ) -> common_query::error::Result<PhysicalPlanRef> {
+        unimplemented!()
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> common_query::error::Result<SendableRecordBatchStream> {
+        self.scanned.store(true, Ordering::Relaxed);
+        self.plan.execute(partition, context)
+    }
+}
+
+#[async_trait]
+impl Table for CountedTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema()
+    }
+
+    fn table_info(&self) -> TableInfoRef {
+        self.table.table_info()
+    }
+
+    async fn scan(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[TableExpr],
+        limit: Option<usize>,
+    ) -> table::Result<PhysicalPlanRef> {
+        let plan = self.table.scan(projection, filters, limit).await?;
+        Ok(Arc::new(RecordedScan {
+            plan,
+            scanned: self.scanned.clone(),
+        }))
+    }
+
+    fn exact_row_count(&self) -> Option<u64> {
+        if self.deleted.load(Ordering::Relaxed) {
+            None
+        } else {
+            Some(100)
+        }
+    }
+}
+
+fn create_engine(table: TableRef) -> QueryEngineRef {
+    let schema_provider = Arc::new(MemorySchemaProvider::new());
+    let catalog_provider = Arc::new(MemoryCatalogProvider::new());
+    let catalog_list = Arc::new(MemoryCatalogManager::default());
+    schema_provider
+        .register_table_sync("numbers".to_string(), table)
+        .unwrap();
+    catalog_provider
+        .register_schema_sync(DEFAULT_SCHEMA_NAME.to_string(), schema_provider)
+        .unwrap();
+    catalog_list
+        .register_catalog_sync(DEFAULT_CATALOG_NAME.to_string(), catalog_provider)
+        .unwrap();
+
+    QueryEngineFactory::new(catalog_list).query_engine()
+}
+
+async fn query(engine: QueryEngineRef, sql: &str) -> String {
+    let batches = exec_selection(engine, sql).await;
+    let batches = RecordBatches::try_new(batches[0].schema.clone(), batches).unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_exact_count() {
+    let table = Arc::new(CountedTable::new());
+    let engine = create_engine(table.clone());
+    let scan_engine = create_engine(Arc::new(MemTable::default_numbers_table()));
+
+    let sqls = [
+        "select count(*) from numbers",
+        "select count(1) as c, count(*) from numbers",
+    ];
+    for sql in sqls {
+        let output = query(engine.clone(), sql).await;
+        assert!(!table.take_scanned(), "{sql}");
+        assert_eq!(query(scan_engine.clone(), sql).await, output, "{sql}");
+    }
+
+    let explained = query(engine, "explain select count(*) from numbers").await;
+    assert!(
+        explained.contains("ExactCount: table=numbers"),
+        "{explained}"
+    );
+    assert!(
+        explained.contains("ExactCountExec: table=numbers"),
+        "{explained}"
+    );
+}
+
+#[tokio::test]
+async fn test_exact_count_fallback() {
+    let table = Arc::new(CountedTable::new());
+    let engine = create_engine(table.clone());
+    let scan_engine = create_engine(Arc::new(MemTable::default_numbers_table()));
+
+    // Counts with filters or groups always scan the rows.
+    let sqls = [
+        "select count(*) from numbers where uint32s > 10",
+        "select uint32s % 3 as n, count(*) from numbers group by n order by n",
+        "select count(uint32s) from numbers",
+    ];
+    for sql in sqls {
+        let output = query(engine.clone(), sql).await;
+        assert!(table.take_scanned(), "{sql}");
+        assert_eq!(query(scan_engine.clone(), sql).await, output, "{sql}");
+    }
+
+    // The table can't tell its exact row count after some rows are deleted.
+    table.deleted.store(true, Ordering::Relaxed);
+    let sql = "select count(*) from numbers";
+    let output = query(engine.clone(), sql).await;
+    assert!(table.take_scanned());
+    assert_eq!(query(scan_engine, sql).await, output);
+    let explained = query(engine, "explain select count(*) from numbers").await;
+    assert!(!explained.contains("ExactCount"), "{explained}");
+}
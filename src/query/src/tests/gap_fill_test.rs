@@ -0,0 +1,182 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use catalog::local::{new_memory_catalog_list, MemoryCatalogProvider, MemorySchemaProvider};
+use common_recordbatch::{RecordBatch, RecordBatches};
+use datatypes::data_type::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+use session::context::QueryContext;
+use table::test_util::MemTable;
+
+use crate::parser::QueryLanguageParser;
+use crate::tests::exec_selection;
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+fn create_test_engine() -> QueryEngineRef {
+    let schema = Schema::try_new(vec![
+        ColumnSchema::new(
+            "host".to_string(),
+            ConcreteDataType::string_datatype(),
+            false,
+        ),
+        ColumnSchema::new(
+            "ts".to_string(),
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            false,
+        ),
+        ColumnSchema::new(
+            "cpu".to_string(),
+            ConcreteDataType::float64_datatype(),
+            true,
+        ),
+    ])
+    .unwrap();
+
+    // host3 only has data out of the queried time range.
+    let table = Arc::new(MemTable::new(
+        "m",
+        RecordBatch::new(
+            Arc::new(schema),
+            vec![
+                Arc::new(StringVector::from(vec![
+                    "host1", "host1", "host2", "host2", "host3",
+                ])) as Arc<_>,
+                Arc::new(TimestampMillisecondVector::from_vec(vec![
+                    0, 30000, 10000, 50000, 100000,
+                ])) as Arc<_>,
+                Arc::new(Float64Vector::from_vec(vec![1.0, 4.0, 2.0, 6.0, 9.0])) as Arc<_>,
+            ],
+        )
+        .unwrap(),
+    ));
+
+    let catalog_list = new_memory_catalog_list().unwrap();
+
+    let default_schema = Arc::new(MemorySchemaProvider::new());
+    MemorySchemaProvider::register_table_sync(&default_schema, "m".to_string(), table).unwrap();
+
+    let default_catalog = Arc::new(MemoryCatalogProvider::new());
+    default_catalog
+        .register_schema_sync("public".to_string(), default_schema)
+        .unwrap();
+    catalog_list
+        .register_catalog_sync("greptime".to_string(), default_catalog)
+        .unwrap();
+
+    QueryEngineFactory::new(catalog_list).query_engine()
+}
+
+async fn check_query(engine: QueryEngineRef, sql: &str, expected: &str) {
+    let batches = exec_selection(engine, sql).await;
+    let batches = RecordBatches::try_new(batches.first().unwrap().schema.clone(), batches).unwrap();
+    assert_eq!(expected, batches.pretty_print().unwrap(), "{sql}");
+}
+
+#[tokio::test]
+async fn test_gap_fill_locf() {
+    let engine = create_test_engine();
+    let sql =
+        "SELECT time_bucket_gapfill(INTERVAL '10 seconds', ts) AS t, host, locf(avg(cpu)) AS cpu \
+               FROM m WHERE ts >= 0 AND ts < 60000 GROUP BY 1, 2 ORDER BY host, t";
+    let expected = "\
++---------------------+-------+-----+
+| t                   | host  | cpu |
++---------------------+-------+-----+
+| 1970-01-01T00:00:00 | host1 | 1.0 |
+| 1970-01-01T00:00:10 | host1 | 1.0 |
+| 1970-01-01T00:00:20 | host1 | 1.0 |
+| 1970-01-01T00:00:30 | host1 | 4.0 |
+| 1970-01-01T00:00:40 | host1 | 4.0 |
+| 1970-01-01T00:00:50 | host1 | 4.0 |
+| 1970-01-01T00:00:00 | host2 |     |
+| 1970-01-01T00:00:10 | host2 | 2.0 |
+| 1970-01-01T00:00:20 | host2 | 2.0 |
+| 1970-01-01T00:00:30 | host2 | 2.0 |
+| 1970-01-01T00:00:40 | host2 | 2.0 |
+| 1970-01-01T00:00:50 | host2 | 6.0 |
++---------------------+-------+-----+";
+    check_query(engine, sql, expected).await;
+}
+
+#[tokio::test]
+async fn test_gap_fill_interpolate() {
+    let engine = create_test_engine();
+    let sql = "SELECT time_bucket_gapfill(INTERVAL '10 seconds', ts) AS t, host, interpolate(max(cpu)) AS cpu \
+               FROM m WHERE ts >= 0 AND ts < 60000 GROUP BY 1, 2 ORDER BY host, t";
+    let expected = "\
++---------------------+-------+-----+
+| t                   | host  | cpu |
++---------------------+-------+-----+
+| 1970-01-01T00:00:00 | host1 | 1.0 |
+| 1970-01-01T00:00:10 | host1 | 2.0 |
+| 1970-01-01T00:00:20 | host1 | 3.0 |
+| 1970-01-01T00:00:30 | host1 | 4.0 |
+| 1970-01-01T00:00:40 | host1 |     |
+| 1970-01-01T00:00:50 | host1 |     |
+| 1970-01-01T00:00:00 | host2 |     |
+| 1970-01-01T00:00:10 | host2 | 2.0 |
+| 1970-01-01T00:00:20 | host2 | 3.0 |
+| 1970-01-01T00:00:30 | host2 | 4.0 |
+| 1970-01-01T00:00:40 | host2 | 5.0 |
+| 1970-01-01T00:00:50 | host2 | 6.0 |
++---------------------+-------+-----+";
+    check_query(engine, sql, expected).await;
+}
+
+#[tokio::test]
+async fn test_gap_fill_without_fill_function() {
+    let engine = create_test_engine();
+    let sql = "SELECT time_bucket_gapfill(INTERVAL '20 seconds', ts) AS t, host, count(cpu) AS c \
+               FROM m WHERE ts >= 0 AND ts < 60000 AND host = 'host1' GROUP BY 1, 2 ORDER BY t";
+    let expected = "\
++---------------------+-------+---+
+| t                   | host  | c |
++---------------------+-------+---+
+| 1970-01-01T00:00:00 | host1 | 1 |
+| 1970-01-01T00:00:20 | host1 | 1 |
+| 1970-01-01T00:00:40 | host1 |   |
++---------------------+-------+---+";
+    check_query(engine, sql, expected).await;
+}
+
+#[tokio::test]
+async fn test_gap_fill_requires_time_bounds() {
+    let engine = create_test_engine();
+    for sql in [
+        "SELECT time_bucket_gapfill(INTERVAL '10 seconds', ts), host, locf(avg(cpu)) FROM m GROUP BY 1, 2",
+        "SELECT time_bucket_gapfill(INTERVAL '10 seconds', ts), host, locf(avg(cpu)) FROM m WHERE ts >= 0 GROUP BY 1, 2",
+        "SELECT time_bucket_gapfill(INTERVAL '10 seconds', ts), host, locf(avg(cpu)) FROM m WHERE ts < 60000 GROUP BY 1, 2",
+    ] {
+        let stmt = QueryLanguageParser::parse_sql(sql).unwrap();
+        let plan = engine
+            .planner()
+            .plan(stmt, QueryContext::arc())
+            .await
+            .unwrap();
+        let err = engine
+            .execute(plan, QueryContext::arc())
+            .await
+            .err()
+            .unwrap();
+        // The planning error is wrapped in the query execution error.
+        let err = format!("{err:?}");
+        assert!(
+            err.contains("time_bucket_gapfill requires both lower and upper bounds"),
+            "{sql}: {err}"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Paging through the `information_schema` virtual tables by `LIMIT` and `OFFSET`.
+
+use std::sync::Arc;
+
+use catalog::local::{MemoryCatalogManager, MemoryCatalogProvider, MemorySchemaProvider};
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, INFORMATION_SCHEMA_NAME};
+use common_recordbatch::RecordBatch;
+use datatypes::prelude::*;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::Int64Vector;
+use table::test_util::MemTable;
+
+use crate::tests::exec_selection;
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+const NUM_SCHEMAS: usize = 4;
+const TABLES_PER_SCHEMA: usize = 250;
+const INFORMATION_SCHEMA_TABLES: [&str; 6] = [
+    "column_limits",
+    "column_statistics",
+    "columns",
+    "recycled_tables",
+    "table_options",
+    "tables",
+];
+
+/// Creates an engine over a catalog of 1000 tables, in schemas `schema_0` to `schema_3`.
+fn create_query_engine() -> QueryEngineRef {
+    let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+        "ts",
+        ConcreteDataType::int64_datatype(),
+        false,
+    )]));
+    let columns: Vec<VectorRef> = vec![Arc::new(Int64Vector::from_slice([0]))];
+    let recordbatch = RecordBatch::new(schema, columns).unwrap();
+
+    let catalog_provider = Arc::new(MemoryCatalogProvider::new());
+    for schema_idx in 0..NUM_SCHEMAS {
+        let schema_name = format!("schema_{schema_idx}");
+        let schema_provider = Arc::new(MemorySchemaProvider::new());
+        for table_idx in 0..TABLES_PER_SCHEMA {
+            let table = MemTable::new_with_catalog(
+                format!("table_{table_idx:03}"),
+                recordbatch.clone(),
+                (schema_idx * TABLES_PER_SCHEMA + table_idx) as u32,
+                DEFAULT_CATALOG_NAME.to_string(),
+                schema_name.clone(),
+                vec![0],
+            );
+            schema_provider
+                .register_table_sync(table.table_name().to_string(), Arc::new(table))
+                .unwrap();
+        }
+        catalog_provider
+            .register_schema_sync(schema_name, schema_provider)
+            .unwrap();
+    }
+
+    let catalog_manager = Arc::new(MemoryCatalogManager::default());
+    catalog_manager
+        .register_catalog_sync(DEFAULT_CATALOG_NAME.to_string(), catalog_provider)
+        .unwrap();
+    QueryEngineFactory::new(catalog_manager).query_engine()
+}
+
+/// Returns the `schema.table` names of a page of `information_schema.tables`.
+async fn tables_page(
+    engine: &QueryEngineRef,
+    order_by: &str,
+    limit: usize,
+    offset: usize,
+) -> Vec<String> {
+    let sql = format!(
+        "SELECT table_schema, table_name FROM information_schema.tables {order_by} LIMIT {limit} OFFSET {offset}"
+    );
+    exec_selection(engine.clone(), &sql)
+        .await
+        .iter()
+        .flat_map(|batch| batch.rows())
+        .map(|row| match (&row[0], &row[1]) {
+            (Value::String(schema), Value::String(table)) => {
+                format!("{}.{}", schema.as_utf8(), table.as_utf8())
+            }
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_page_information_schema_tables() {
+    let engine = create_query_engine();
+
+    let expected = INFORMATION_SCHEMA_TABLES
+        .iter()
+        .map(|table| format!("{INFORMATION_SCHEMA_NAME}.{table}"))
+        .chain((0..NUM_SCHEMAS).flat_map(|schema_idx| {
+            (0..TABLES_PER_SCHEMA)
+                .map(move |table_idx| format!("schema_{schema_idx}.table_{table_idx:03}"))
+        }))
+        .collect::<Vec<_>>();
+    assert_eq!(1006, expected.len());
+
+    // The rows are in the order of the names even without `ORDER BY`.
+    for order_by in ["", "ORDER BY table_schema, table_name"] {
+        let first = tables_page(&engine, order_by, 600, 0).await;
+        let second = tables_page(&engine, order_by, 600, 600).await;
+        assert_eq!(600, first.len(), "{order_by}");
+        assert_eq!(406, second.len(), "{order_by}");
+
+        let pages = first.into_iter().chain(second).collect::<Vec<_>>();
+        assert_eq!(expected, pages, "{order_by}");
+    }
+
+    // Pages ending in the middle of a schema.
+    let page = tables_page(&engine, "", 10, 250).await;
+    assert_eq!(expected[250..260], page);
+}
@@ -0,0 +1,189 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A table `metrics` spread over several nodes, for the tests of the plans pushed down to the
+//! datanodes.
+
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use catalog::local::{MemoryCatalogManager, MemoryCatalogProvider, MemorySchemaProvider};
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_query::logical_plan::Expr as TableExpr;
+use common_query::physical_plan::{PhysicalPlanAdapter, PhysicalPlanRef};
+use datafusion::datasource::DefaultTableSource;
+use datafusion::physical_plan::union::UnionExec;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::SessionContext;
+use datafusion_expr::LogicalPlanBuilder;
+use datatypes::schema::{Schema, SchemaRef};
+use table::metadata::TableInfoRef;
+use table::table::adapter::DfTableProviderAdapter;
+use table::table::{PartialAggregate, PartialTopK};
+use table::{Table, TableRef};
+
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+/// A table whose rows are spread over several nodes, each of which evaluates the partial
+/// aggregation or keeps the top-k rows over its own rows.
+pub(super) struct MultiNodeTable {
+    /// All the rows, which are scanned if nothing is pushed down.
+    table: TableRef,
+    nodes: Vec<TableRef>,
+    pushed_down: AtomicBool,
+}
+
+impl MultiNodeTable {
+    pub(super) fn new(table: TableRef, nodes: Vec<TableRef>) -> Self {
+        Self {
+            table,
+            nodes,
+            pushed_down: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns whether anything is pushed down to the nodes since the last call.
+    pub(super) fn take_pushed_down(&self) -> bool {
+        self.pushed_down.swap(false, Ordering::Relaxed)
+    }
+
+    /// Plans the scan of every node, filtered by `filters` and followed by `build`.
+    async fn node_plans(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[TableExpr],
+        build: impl Fn(LogicalPlanBuilder) -> LogicalPlanBuilder,
+    ) -> Vec<Arc<dyn ExecutionPlan>> {
+        self.pushed_down.store(true, Ordering::Relaxed);
+
+        let mut plans = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let source =
+                DefaultTableSource::new(Arc::new(DfTableProviderAdapter::new(node.clone())));
+            let mut builder =
+                LogicalPlanBuilder::scan("metrics", Arc::new(source), projection.cloned()).unwrap();
+            for filter in filters {
+                builder = builder.filter(filter.df_expr().clone()).unwrap();
+            }
+            let plan = build(builder).build().unwrap();
+            let plan = SessionContext::new()
+                .state()
+                .create_physical_plan(&plan)
+                .await
+                .unwrap();
+            plans.push(plan);
+        }
+        plans
+    }
+}
+
+#[async_trait]
+impl Table for MultiNodeTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema()
+    }
+
+    fn table_info(&self) -> TableInfoRef {
+        self.table.table_info()
+    }
+
+    async fn scan(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[TableExpr],
+        limit: Option<usize>,
+    ) -> table::Result<PhysicalPlanRef> {
+        self.table.scan(projection, filters, limit).await
+    }
+
+    fn supports_aggregate_pushdown(&self) -> bool {
+        true
+    }
+
+    fn supports_udaf_pushdown(&self) -> bool {
+        true
+    }
+
+    async fn scan_partial_aggregate(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[TableExpr],
+        aggregate: &PartialAggregate,
+    ) -> table::Result<PhysicalPlanRef> {
+        let plans = self
+            .node_plans(projection, filters, |builder| {
+                builder
+                    .aggregate(
+                        aggregate.group_expr.iter().map(|x| x.df_expr().clone()),
+                        aggregate.aggr_expr.iter().map(|x| x.df_expr().clone()),
+                    )
+                    .unwrap()
+            })
+            .await;
+        Ok(Arc::new(PhysicalPlanAdapter::new(
+            aggregate.schema.clone(),
+            Arc::new(UnionExec::new(plans)),
+        )))
+    }
+
+    fn supports_topk_pushdown(&self) -> bool {
+        true
+    }
+
+    async fn scan_topk(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[TableExpr],
+        topk: &PartialTopK,
+    ) -> table::Result<PhysicalPlanRef> {
+        let plans = self
+            .node_plans(projection, filters, |builder| {
+                builder
+                    .sort(topk.sort_expr.iter().map(|x| x.df_expr().clone()))
+                    .unwrap()
+                    .limit(0, Some(topk.fetch))
+                    .unwrap()
+            })
+            .await;
+        let schema = Schema::try_from(plans[0].schema()).unwrap();
+        Ok(Arc::new(PhysicalPlanAdapter::new(
+            Arc::new(schema),
+            Arc::new(UnionExec::new(plans)),
+        )))
+    }
+}
+
+/// Creates an engine querying `table` as table `metrics`.
+pub(super) fn create_engine(table: TableRef) -> QueryEngineRef {
+    let schema_provider = Arc::new(MemorySchemaProvider::new());
+    let catalog_provider = Arc::new(MemoryCatalogProvider::new());
+    let catalog_list = Arc::new(MemoryCatalogManager::default());
+    schema_provider
+        .register_table_sync("metrics".to_string(), table)
+        .unwrap();
+    catalog_provider
+        .register_schema_sync(DEFAULT_SCHEMA_NAME.to_string(), schema_provider)
+        .unwrap();
+    catalog_list
+        .register_catalog_sync(DEFAULT_CATALOG_NAME.to_string(), catalog_provider)
+        .unwrap();
+
+    QueryEngineFactory::new(catalog_list).query_engine()
+}
@@ -210,11 +210,14 @@ where
     let factory = new_query_engine_factory(testing_table);
     let engine = factory.query_engine();
 
-    engine.register_aggregate_function(Arc::new(AggregateFunctionMeta::new(
+    let my_sum = Arc::new(AggregateFunctionMeta::new(
         "my_sum",
         1,
         Arc::new(|| Arc::new(MySumAccumulatorCreator::default())),
-    )));
+    ));
+    engine.register_aggregate_function(my_sum.clone()).unwrap();
+    // The name is taken.
+    assert!(engine.register_aggregate_function(my_sum).is_err());
 
     let sql = format!("select MY_SUM({column_name}) as my_sum from {table_name}");
     let batches = exec_selection(engine, &sql).await;
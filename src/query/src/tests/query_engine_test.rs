@@ -130,6 +130,7 @@ async fn test_query_validate() -> Result<()> {
     let mut plugins = Plugins::new();
     plugins.insert(QueryOptions {
         disallow_cross_schema_query: true,
+        ..Default::default()
     });
     let plugins = Arc::new(plugins);
 
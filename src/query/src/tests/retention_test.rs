@@ -0,0 +1,256 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use catalog::local::{new_memory_catalog_list, MemoryCatalogProvider, MemorySchemaProvider};
+use common_base::Plugins;
+use common_error::prelude::{ErrorExt, StatusCode};
+use common_query::Output;
+use common_recordbatch::{util, RecordBatch};
+use common_time::util::{Clock, ClockRef};
+use datatypes::data_type::ConcreteDataType;
+use datatypes::prelude::ScalarVector;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{TimestampMillisecondVector, VectorRef};
+use session::context::QueryContext;
+use table::requests::TableOptions;
+use table::test_util::MemTable;
+
+use crate::error::{Error, Result};
+use crate::parser::QueryLanguageParser;
+use crate::query_engine::options::QueryOptions;
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+/// Current time of the queries, so the rows before 15000 are expired in table `m`.
+const NOW_MILLIS: i64 = 25000;
+
+#[derive(Debug)]
+struct FixedClock;
+
+impl Clock for FixedClock {
+    fn now_millis(&self) -> i64 {
+        NOW_MILLIS
+    }
+}
+
+fn new_table(name: &str, ttl: Option<Duration>, ttl_column: Option<&str>) -> MemTable {
+    let mut column_schemas = vec![ColumnSchema::new(
+        "ts".to_string(),
+        ConcreteDataType::timestamp_millisecond_datatype(),
+        false,
+    )
+    .with_time_index(true)];
+    let mut columns: Vec<VectorRef> = vec![Arc::new(TimestampMillisecondVector::from_vec(vec![
+        15000, 20000,
+    ]))];
+    // The rows never expire by the ttl column.
+    if let Some(ttl_column) = ttl_column {
+        column_schemas.push(ColumnSchema::new(
+            ttl_column.to_string(),
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            true,
+        ));
+        columns.push(Arc::new(TimestampMillisecondVector::from(vec![
+            None::<i64>,
+            None,
+        ])));
+    }
+    let schema = Schema::try_new(column_schemas).unwrap();
+    let recordbatch = RecordBatch::new(Arc::new(schema), columns).unwrap();
+    MemTable::new(name, recordbatch).with_options(TableOptions {
+        ttl,
+        ttl_column: ttl_column.map(|column| column.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Creates an engine with table `m` of ttl 10s, table `n` without ttl, and table `r` of ttl 10s
+/// and ttl column `expire_at`.
+fn create_test_engine(reject_expired_ranges: bool) -> QueryEngineRef {
+    let default_schema = Arc::new(MemorySchemaProvider::new());
+    let tables = [
+        ("m", new_table("m", Some(Duration::from_secs(10)), None)),
+        ("n", new_table("n", None, None)),
+        (
+            "r",
+            new_table("r", Some(Duration::from_secs(10)), Some("expire_at")),
+        ),
+    ];
+    for (name, table) in tables {
+        MemorySchemaProvider::register_table_sync(
+            &default_schema,
+            name.to_string(),
+            Arc::new(table),
+        )
+        .unwrap();
+    }
+
+    let catalog_list = new_memory_catalog_list().unwrap();
+    let default_catalog = Arc::new(MemoryCatalogProvider::new());
+    default_catalog
+        .register_schema_sync("public".to_string(), default_schema)
+        .unwrap();
+    catalog_list
+        .register_catalog_sync("greptime".to_string(), default_catalog)
+        .unwrap();
+
+    let mut plugins = Plugins::new();
+    plugins.insert::<ClockRef>(Arc::new(FixedClock));
+    plugins.insert(QueryOptions {
+        reject_expired_ranges,
+        ..Default::default()
+    });
+    QueryEngineFactory::new_with_plugins(catalog_list, Arc::new(plugins)).query_engine()
+}
+
+/// Returns the timestamps in the first column of the results of `sql`, and the warnings of
+/// the query.
+async fn query(engine: &QueryEngineRef, sql: &str) -> Result<(Vec<i64>, Vec<String>)> {
+    let query_ctx = QueryContext::arc();
+    let stmt = QueryLanguageParser::parse_sql(sql).unwrap();
+    let plan = engine.planner().plan(stmt, query_ctx.clone()).await?;
+    let Output::Stream(stream) = engine.execute(plan, query_ctx.clone()).await? else {
+        unreachable!()
+    };
+
+    let mut timestamps = vec![];
+    for batch in util::collect(stream).await.unwrap() {
+        let column = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondVector>()
+            .unwrap();
+        timestamps.extend(column.iter_data().map(|ts| i64::from(ts.unwrap())));
+    }
+    Ok((timestamps, query_ctx.warnings()))
+}
+
+#[tokio::test]
+async fn test_warn_expired_range() {
+    let engine = create_test_engine(false);
+
+    let (timestamps, warnings) = query(&engine, "SELECT ts FROM m WHERE ts >= 1000 AND ts < 5000")
+        .await
+        .unwrap();
+    assert!(timestamps.is_empty());
+    assert_eq!(1, warnings.len(), "{warnings:?}");
+    assert!(
+        warnings[0].contains("table greptime.public.m is before its retention boundary"),
+        "{warnings:?}"
+    );
+
+    let (timestamps, warnings) = query(&engine, "SELECT ts FROM m WHERE ts >= 12000 ORDER BY ts")
+        .await
+        .unwrap();
+    assert_eq!(vec![15000, 20000], timestamps);
+    assert_eq!(1, warnings.len(), "{warnings:?}");
+    assert!(
+        warnings[0].contains("table greptime.public.m starts before its retention boundary"),
+        "{warnings:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_no_warning_in_retention() {
+    let engine = create_test_engine(false);
+
+    let sqls = [
+        // Queries without a lower bound don't ask for the rows of a specific time.
+        "SELECT ts FROM m ORDER BY ts",
+        "SELECT ts FROM m WHERE ts < 30000 ORDER BY ts",
+        "SELECT ts FROM m WHERE ts >= 15000 ORDER BY ts",
+        // The table without ttl never expires its rows.
+        "SELECT ts FROM n WHERE ts >= 1000 ORDER BY ts",
+    ];
+    for sql in sqls {
+        let (timestamps, warnings) = query(&engine, sql).await.unwrap();
+        assert_eq!(vec![15000, 20000], timestamps, "{sql}");
+        assert!(warnings.is_empty(), "{sql}: {warnings:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_check_tables_of_join() {
+    let engine = create_test_engine(false);
+
+    // Each table is checked by its own filters.
+    let sql = "SELECT m.ts FROM m, n WHERE m.ts >= 15000 AND n.ts >= 1000 ORDER BY m.ts";
+    let (_, warnings) = query(&engine, sql).await.unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    let sql = "SELECT m.ts FROM m, n WHERE m.ts >= 12000 AND n.ts >= 15000 ORDER BY m.ts";
+    let (timestamps, warnings) = query(&engine, sql).await.unwrap();
+    assert_eq!(vec![15000, 15000, 20000, 20000], timestamps);
+    assert_eq!(1, warnings.len(), "{warnings:?}");
+    assert!(warnings[0].contains("greptime.public.m"), "{warnings:?}");
+}
+
+#[tokio::test]
+async fn test_warn_expired_range_of_subquery() {
+    let engine = create_test_engine(false);
+
+    let sql = "SELECT ts FROM (SELECT * FROM m) AS s WHERE s.ts >= 1000 AND s.ts < 5000";
+    let (timestamps, warnings) = query(&engine, sql).await.unwrap();
+    assert!(timestamps.is_empty());
+    assert_eq!(1, warnings.len(), "{warnings:?}");
+    assert!(
+        warnings[0].contains("table greptime.public.m is before its retention boundary"),
+        "{warnings:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_warn_expired_range_with_ttl_column() {
+    let engine = create_test_engine(false);
+
+    // The filter hiding the expired rows doesn't clamp the queried range.
+    let (timestamps, warnings) = query(&engine, "SELECT ts FROM r WHERE ts >= 1000 AND ts < 5000")
+        .await
+        .unwrap();
+    assert!(timestamps.is_empty());
+    assert_eq!(1, warnings.len(), "{warnings:?}");
+    assert!(
+        warnings[0].contains("table greptime.public.r is before its retention boundary"),
+        "{warnings:?}"
+    );
+
+    let (timestamps, warnings) = query(&engine, "SELECT ts FROM r WHERE ts >= 12000 ORDER BY ts")
+        .await
+        .unwrap();
+    assert_eq!(vec![15000, 20000], timestamps);
+    assert_eq!(1, warnings.len(), "{warnings:?}");
+    assert!(
+        warnings[0].contains("table greptime.public.r starts before its retention boundary"),
+        "{warnings:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_reject_expired_range() {
+    let engine = create_test_engine(true);
+
+    let err = query(&engine, "SELECT ts FROM m WHERE ts >= 12000")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::ExpiredTimeRange { .. }), "{err:?}");
+    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+
+    let (timestamps, warnings) = query(&engine, "SELECT ts FROM n WHERE ts >= 1000 ORDER BY ts")
+        .await
+        .unwrap();
+    assert_eq!(vec![15000, 20000], timestamps);
+    assert!(warnings.is_empty());
+}
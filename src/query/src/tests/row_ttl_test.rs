@@ -0,0 +1,175 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use catalog::local::{new_memory_catalog_list, MemoryCatalogProvider, MemorySchemaProvider};
+use common_base::Plugins;
+use common_recordbatch::{RecordBatch, RecordBatches};
+use common_time::util::{Clock, ClockRef};
+use datatypes::data_type::ConcreteDataType;
+use datatypes::prelude::ScalarVector;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{StringVector, TimestampMillisecondVector};
+use table::requests::TableOptions;
+use table::test_util::MemTable;
+
+use crate::tests::exec_selection;
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+#[derive(Debug, Default)]
+struct MockClock {
+    now_millis: AtomicI64,
+}
+
+impl MockClock {
+    fn set(&self, now_millis: i64) {
+        self.now_millis.store(now_millis, Ordering::Relaxed);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_millis(&self) -> i64 {
+        self.now_millis.load(Ordering::Relaxed)
+    }
+}
+
+/// Creates an engine with table `m`, whose rows expire by column `expires_at` and the
+/// `ttl` of the table.
+fn create_test_engine(clock: Arc<MockClock>, ttl: Option<Duration>) -> QueryEngineRef {
+    let schema = Schema::try_new(vec![
+        ColumnSchema::new(
+            "host".to_string(),
+            ConcreteDataType::string_datatype(),
+            false,
+        ),
+        ColumnSchema::new(
+            "ts".to_string(),
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            false,
+        )
+        .with_time_index(true),
+        ColumnSchema::new(
+            "expires_at".to_string(),
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            true,
+        ),
+    ])
+    .unwrap();
+
+    // host3 never expires by its expiry time.
+    let table = MemTable::new(
+        "m",
+        RecordBatch::new(
+            Arc::new(schema),
+            vec![
+                Arc::new(StringVector::from(vec!["host1", "host2", "host3"])) as Arc<_>,
+                Arc::new(TimestampMillisecondVector::from_vec(vec![0, 5000, 0])) as Arc<_>,
+                Arc::new(TimestampMillisecondVector::from(vec![
+                    Some(1000),
+                    Some(20000),
+                    None,
+                ])) as Arc<_>,
+            ],
+        )
+        .unwrap(),
+    )
+    .with_options(TableOptions {
+        ttl,
+        ttl_column: Some("expires_at".to_string()),
+        ..Default::default()
+    });
+
+    let catalog_list = new_memory_catalog_list().unwrap();
+
+    let default_schema = Arc::new(MemorySchemaProvider::new());
+    MemorySchemaProvider::register_table_sync(&default_schema, "m".to_string(), Arc::new(table))
+        .unwrap();
+
+    let default_catalog = Arc::new(MemoryCatalogProvider::new());
+    default_catalog
+        .register_schema_sync("public".to_string(), default_schema)
+        .unwrap();
+    catalog_list
+        .register_catalog_sync("greptime".to_string(), default_catalog)
+        .unwrap();
+
+    let mut plugins = Plugins::new();
+    plugins.insert::<ClockRef>(clock);
+    QueryEngineFactory::new_with_plugins(catalog_list, Arc::new(plugins)).query_engine()
+}
+
+async fn query_hosts(engine: &QueryEngineRef) -> Vec<String> {
+    let batches = exec_selection(engine.clone(), "SELECT host FROM m ORDER BY host").await;
+    let mut hosts = vec![];
+    for batch in batches {
+        let column = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringVector>()
+            .unwrap();
+        hosts.extend(column.iter_data().map(|host| host.unwrap().to_string()));
+    }
+    hosts
+}
+
+#[tokio::test]
+async fn test_row_ttl_hides_expired_rows() {
+    let clock = Arc::new(MockClock::default());
+    let engine = create_test_engine(clock.clone(), None);
+
+    clock.set(999);
+    assert_eq!(vec!["host1", "host2", "host3"], query_hosts(&engine).await);
+
+    // Rows are expired once their expiry time passes.
+    clock.set(1000);
+    assert_eq!(vec!["host2", "host3"], query_hosts(&engine).await);
+
+    // Rows without expiry time are untouched.
+    clock.set(100000);
+    assert_eq!(vec!["host3"], query_hosts(&engine).await);
+}
+
+#[tokio::test]
+async fn test_row_ttl_with_table_ttl() {
+    let clock = Arc::new(MockClock::default());
+    let engine = create_test_engine(clock.clone(), Some(Duration::from_secs(10)));
+
+    // host1 expires by its expiry time, host3 expires by the ttl of the table.
+    clock.set(10000);
+    assert_eq!(vec!["host2"], query_hosts(&engine).await);
+
+    // The ttl of the table expires host2 before its expiry time.
+    clock.set(15000);
+    assert!(query_hosts(&engine).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_row_ttl_explain() {
+    let clock = Arc::new(MockClock::default());
+    let engine = create_test_engine(clock.clone(), None);
+    clock.set(1000);
+
+    let batches = exec_selection(engine, "EXPLAIN SELECT host FROM m").await;
+    let batches = RecordBatches::try_new(batches[0].schema.clone(), batches).unwrap();
+    let explained = batches.pretty_print().unwrap();
+    assert!(
+        explained.contains(
+            "Filter: m.expires_at IS NULL OR m.expires_at > TimestampMillisecond(1000, None)"
+        ),
+        "{explained}"
+    );
+}
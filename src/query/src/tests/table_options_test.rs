@@ -0,0 +1,144 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use catalog::local::{new_memory_catalog_list, MemoryCatalogProvider, MemorySchemaProvider};
+use common_base::readable_size::ReadableSize;
+use common_recordbatch::{RecordBatch, RecordBatches};
+use datatypes::data_type::ConcreteDataType;
+use datatypes::prelude::Value;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::TimestampMillisecondVector;
+use session::context::QueryContext;
+use table::requests::TableOptions;
+use table::test_util::MemTable;
+
+use crate::parser::QueryLanguageParser;
+use crate::tests::exec_selection;
+use crate::{QueryEngineFactory, QueryEngineRef};
+
+/// Creates an engine with table `m`, which sets some options, inherits some from its schema and
+/// takes the defaults of the others.
+fn create_test_engine() -> QueryEngineRef {
+    let schema = Schema::try_new(vec![ColumnSchema::new(
+        "ts".to_string(),
+        ConcreteDataType::timestamp_millisecond_datatype(),
+        false,
+    )
+    .with_time_index(true)])
+    .unwrap();
+    // The schema sets a default ttl and write buffer size, the table only takes the ttl as it
+    // sets its own write buffer size.
+    let schema_options = TableOptions {
+        write_buffer_size: Some(ReadableSize::mb(16)),
+        ttl: Some(Duration::from_secs(7 * 24 * 3600)),
+        ..Default::default()
+    };
+    let mut table_options = TableOptions {
+        write_buffer_size: Some(ReadableSize::mb(8)),
+        extra_options: HashMap::from([("foo".to_string(), "bar".to_string())]),
+        ..Default::default()
+    };
+    table_options.inherit(&schema_options).unwrap();
+    let table = MemTable::new(
+        "m",
+        RecordBatch::new(
+            Arc::new(schema),
+            vec![Arc::new(TimestampMillisecondVector::from_vec(vec![0])) as Arc<_>],
+        )
+        .unwrap(),
+    )
+    .with_options(table_options);
+
+    let catalog_list = new_memory_catalog_list().unwrap();
+    let default_schema = Arc::new(MemorySchemaProvider::new().with_table_options(schema_options));
+    MemorySchemaProvider::register_table_sync(&default_schema, "m".to_string(), Arc::new(table))
+        .unwrap();
+    let default_catalog = Arc::new(MemoryCatalogProvider::new());
+    default_catalog
+        .register_schema_sync("public".to_string(), default_schema)
+        .unwrap();
+    catalog_list
+        .register_catalog_sync("greptime".to_string(), default_catalog)
+        .unwrap();
+
+    QueryEngineFactory::new(catalog_list).query_engine()
+}
+
+async fn query_string(engine: &QueryEngineRef, sql: &str) -> Option<String> {
+    let batches = exec_selection(engine.clone(), sql).await;
+    match batches[0].column(0).get(0) {
+        Value::String(s) => Some(s.as_utf8().to_string()),
+        Value::Null => None,
+        v => unreachable!("{v:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_information_schema_table_options() {
+    let engine = create_test_engine();
+
+    let sql = "SELECT table_schema, table_name, option_name, option_value, is_inherited \
+               FROM information_schema.table_options";
+    let batches = exec_selection(engine.clone(), sql).await;
+    let batches = RecordBatches::try_new(batches[0].schema.clone(), batches).unwrap();
+    let expected = "\
++--------------+------------+-------------------+--------------+--------------+
+| table_schema | table_name | option_name       | option_value | is_inherited |
++--------------+------------+-------------------+--------------+--------------+
+| public       | m          | regions           | 1            | false        |
+| public       | m          | write_buffer_size | 8.0MiB       | false        |
+| public       | m          | ttl               | 7days        | true         |
+| public       | m          | foo               | bar          | false        |
+| public       | m          | wal               | enabled      | true         |
++--------------+------------+-------------------+--------------+--------------+";
+    assert_eq!(expected, batches.pretty_print().unwrap());
+}
+
+#[tokio::test]
+async fn test_table_option_function() {
+    let engine = create_test_engine();
+
+    // The function returns the same values as `information_schema.table_options`.
+    let sql = "SELECT option_name, option_value FROM information_schema.table_options";
+    for batch in exec_selection(engine.clone(), sql).await {
+        for row in batch.rows() {
+            let (Value::String(key), Value::String(value)) = (&row[0], &row[1]) else {
+                unreachable!()
+            };
+            for table in ["public.m", "m"] {
+                let sql = format!("SELECT table_option('{table}', '{}')", key.as_utf8());
+                assert_eq!(
+                    Some(value.as_utf8()),
+                    query_string(&engine, &sql).await.as_deref(),
+                    "{sql}"
+                );
+            }
+        }
+    }
+
+    let sql = "SELECT table_option('public.m', 'compaction_time_window')";
+    assert_eq!(None, query_string(&engine, sql).await);
+
+    let stmt = QueryLanguageParser::parse_sql("SELECT table_option('public.n', 'ttl')").unwrap();
+    let err = engine
+        .planner()
+        .plan(stmt, QueryContext::arc())
+        .await
+        .unwrap_err();
+    assert_eq!("Table not found: greptime.public.n", err.to_string());
+}
@@ -26,7 +26,7 @@ use datatypes::data_type::ConcreteDataType;
 use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
 use datatypes::vectors::{Int64Vector, TimestampMillisecondVector};
 use table::metadata::{FilterPushDownType, TableInfoRef};
-use table::predicate::TimeRangePredicateBuilder;
+use table::predicate::{TimeRangePredicateBuilder, MAX_PRUNING_DISJUNCTS};
 use table::test_util::MemTable;
 use table::Table;
 use tokio::sync::RwLock;
@@ -136,6 +136,18 @@ impl TimeRangeTester {
         let range = TimeRangePredicateBuilder::new("ts", &filters).build();
         assert_eq!(expect, range);
     }
+
+    async fn check_ranges(&self, sql: &str, expect: Vec<TimestampRange>) {
+        let _ = exec_selection(self.engine.clone(), sql).await;
+        let filters = self.table.get_filters().await;
+
+        let ranges = TimeRangePredicateBuilder::new("ts", &filters).build_ranges();
+        assert_eq!(expect, ranges);
+    }
+}
+
+fn millis_range(start: i64, end: i64) -> TimestampRange {
+    TimestampRange::with_unit(start, end, TimeUnit::Millisecond).unwrap()
 }
 
 #[tokio::test]
@@ -233,3 +245,52 @@ async fn test_range_filter() {
         )
         .await;
 }
+
+#[tokio::test]
+async fn test_disjoint_time_ranges() {
+    let tester = create_test_engine();
+
+    tester
+        .check_ranges(
+            "select * from m where (ts >= 1000 and ts < 2000) or (ts>=3000 and ts<4000);",
+            vec![millis_range(1000, 2000), millis_range(3000, 4000)],
+        )
+        .await;
+
+    tester
+        .check_ranges(
+            "select * from m where ts in (40, 10, 20, 11)",
+            vec![
+                millis_range(10, 12),
+                millis_range(20, 21),
+                millis_range(40, 41),
+            ],
+        )
+        .await;
+
+    tester
+        .check_ranges(
+            "select * from m where (ts < 100 or ts >= 200) and ts >= 50 and v > 10",
+            vec![
+                millis_range(50, 100),
+                TimestampRange::from_start(Timestamp::new(200, TimeUnit::Millisecond)),
+            ],
+        )
+        .await;
+
+    tester
+        .check_ranges("select * from m where ts > 10 and ts < 9", vec![])
+        .await;
+
+    // Ranges beyond the limit are merged into the range covering them.
+    let values = (0..=MAX_PRUNING_DISJUNCTS as i64)
+        .map(|i| (i * 10).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    tester
+        .check_ranges(
+            &format!("select * from m where ts in ({values})"),
+            vec![millis_range(0, MAX_PRUNING_DISJUNCTS as i64 * 10 + 1)],
+        )
+        .await;
+}
@@ -0,0 +1,189 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use common_recordbatch::{RecordBatch, RecordBatches};
+use datatypes::prelude::*;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+use rand::Rng;
+use table::test_util::MemTable;
+use table::TableRef;
+
+use crate::tests::exec_selection;
+use crate::tests::multi_node_table::{create_engine, MultiNodeTable};
+use crate::QueryEngineRef;
+
+/// Rows of table `metrics`.
+struct Rows {
+    timestamps: Vec<i64>,
+    hosts: Vec<String>,
+    values: Vec<Option<f64>>,
+}
+
+impl Rows {
+    /// Returns `num_rows` random rows, with lots of ties and nulls in the `value` column.
+    fn random(num_rows: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let values = (0..num_rows)
+            .map(|_| {
+                let value = rng.gen_range(0..40);
+                (value != 0).then_some(value as f64 / 4.0)
+            })
+            .collect();
+        Self {
+            timestamps: (0..num_rows as i64).collect(),
+            hosts: (0..num_rows).map(|i| format!("host{}", i % 7)).collect(),
+            values,
+        }
+    }
+
+    /// Returns the table of the rows in `range`.
+    fn table(&self, range: Range<usize>) -> TableRef {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("value", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(TimestampMillisecondVector::from_vec(
+                self.timestamps[range.clone()].to_vec(),
+            )),
+            Arc::new(StringVector::from(self.hosts[range.clone()].to_vec())),
+            Arc::new(Float64Vector::from(self.values[range].to_vec())),
+        ];
+        Arc::new(MemTable::new(
+            "metrics",
+            RecordBatch::new(schema, columns).unwrap(),
+        ))
+    }
+}
+
+fn batch_rows(batch: &RecordBatch) -> Vec<Vec<Value>> {
+    (0..batch.num_rows())
+        .map(|i| {
+            (0..batch.num_columns())
+                .map(|j| batch.column(j).get(i))
+                .collect()
+        })
+        .collect()
+}
+
+async fn query_rows(engine: QueryEngineRef, sql: &str) -> Vec<Vec<Value>> {
+    let batches = exec_selection(engine, sql).await;
+    batches.iter().flat_map(batch_rows).collect()
+}
+
+async fn query(engine: QueryEngineRef, sql: &str) -> String {
+    let batches = exec_selection(engine, sql).await;
+    let batches = RecordBatches::try_new(batches[0].schema.clone(), batches).unwrap();
+    batches.pretty_print().unwrap()
+}
+
+/// Queries selecting the sort keys only, so the rows of equal keys are indistinguishable.
+const SORTED_QUERIES: [&str; 6] = [
+    "select value from metrics order by value",
+    "select value from metrics order by value desc",
+    "select value from metrics order by value nulls first",
+    "select value from metrics order by value desc nulls last",
+    "select host, value from metrics where ts > 500 order by host desc, value",
+    "select value * 2 as v from metrics order by v",
+];
+
+#[tokio::test]
+async fn test_topk_equals_sort() {
+    let rows = Rows::random(3000);
+    let engine = create_engine(rows.table(0..3000));
+
+    for sql in SORTED_QUERIES {
+        let sorted = query_rows(engine.clone(), sql).await;
+        for (limit, offset) in [(1, 0), (10, 0), (100, 0), (10, 5), (1000, 0)] {
+            let topk_sql = format!("{sql} limit {limit} offset {offset}");
+            let output = query_rows(engine.clone(), &topk_sql).await;
+            let expected = sorted
+                .iter()
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(expected, output, "{topk_sql}");
+        }
+    }
+
+    let explained = query(
+        engine.clone(),
+        "explain select value from metrics order by value limit 10",
+    )
+    .await;
+    assert!(explained.contains("TopK: fetch=10"), "{explained}");
+    assert!(explained.contains("TopKExec: fetch=10"), "{explained}");
+
+    // Larger limits and the sorts by the time index are sorted as usual.
+    for sql in [
+        "explain select value from metrics order by value limit 5000",
+        "explain select ts, value from metrics order by ts desc limit 10",
+    ] {
+        let explained = query(engine.clone(), sql).await;
+        assert!(!explained.contains("TopK"), "{sql}: {explained}");
+    }
+}
+
+#[tokio::test]
+async fn test_distributed_topk() {
+    let num_rows = 1000;
+    let rows = Rows::random(num_rows);
+    let dist_table = Arc::new(MultiNodeTable::new(
+        rows.table(0..num_rows),
+        (0..num_rows)
+            .step_by(300)
+            .map(|start| rows.table(start..num_rows.min(start + 300)))
+            .collect(),
+    ));
+    let dist_engine = create_engine(dist_table.clone());
+    let engine = create_engine(rows.table(0..num_rows));
+
+    for sql in SORTED_QUERIES {
+        for (limit, offset) in [(1, 0), (10, 0), (10, 5), (500, 0)] {
+            let topk_sql = format!("{sql} limit {limit} offset {offset}");
+            let expected = query_rows(engine.clone(), &topk_sql).await;
+
+            let output = query_rows(dist_engine.clone(), &topk_sql).await;
+            assert_eq!(expected, output, "{topk_sql}");
+            // The top-k over a computed column can't be pushed down.
+            assert_eq!(
+                !sql.contains("value * 2"),
+                dist_table.take_pushed_down(),
+                "{topk_sql}"
+            );
+        }
+    }
+
+    let explained = query(
+        dist_engine,
+        "explain select host, value from metrics where ts > 500 order by value desc limit 10",
+    )
+    .await;
+    assert!(
+        explained.contains("TableTopKScan: table=metrics"),
+        "{explained}"
+    );
+    assert!(explained.contains("TopKExec: fetch=10"), "{explained}");
+}
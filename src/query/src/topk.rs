@@ -0,0 +1,736 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Top-k of `ORDER BY ... LIMIT k` with a small `k`, by a bounded heap instead of sorting all
+//! the rows.
+//!
+//! [TopKRule] replaces such a sort with a [TopK] node, unless the rows are ordered by the time
+//! index, which the storage is organized by. [TopKExec] keeps the top `k` rows seen so far in
+//! a heap, so it only retains about `k` rows plus a batch of the input, no matter how many rows
+//! are scanned. If the scanned table supports it, the top-k of each place the data are stored
+//! is pushed down to the table, see [pushdown_topk](crate::dist_plan::pushdown_topk).
+
+use std::any::Any;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use datafusion::error::Result as DfResult;
+use datafusion::execution::context::{SessionState, TaskContext};
+use datafusion::physical_expr::PhysicalSortExpr;
+use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::planner::ExtensionPlanner;
+use datafusion::physical_plan::{
+    DisplayFormatType, ExecutionPlan, Partitioning, PhysicalPlanner, RecordBatchStream,
+    SendableRecordBatchStream, Statistics,
+};
+use datafusion_common::{DFSchemaRef, DataFusionError};
+use datafusion_expr::expr::Sort;
+use datafusion_expr::{
+    Expr, Extension, LogicalPlan, UserDefinedLogicalNode, UserDefinedLogicalNodeCore,
+};
+use datafusion_optimizer::optimizer::ApplyOrder;
+use datafusion_optimizer::{OptimizerConfig, OptimizerRule};
+use datatypes::arrow::array::{Array, ArrayRef};
+use datatypes::arrow::compute::{interleave, SortOptions};
+use datatypes::arrow::datatypes::SchemaRef;
+use datatypes::arrow::record_batch::RecordBatch;
+use datatypes::arrow::row::{OwnedRow, RowConverter, SortField};
+use futures::{ready, Stream, StreamExt};
+
+use crate::dist_plan::pushdown_topk;
+use crate::metrics;
+use crate::row_ttl::scanned_table;
+
+/// Max `k` of the sorts replaced by [TopK], larger limits are sorted as usual.
+pub const MAX_TOPK_FETCH: usize = 1024;
+/// Number of rows of the input batches a [TopKExec] may retain besides the top-k rows, before
+/// it compacts the top-k rows into a new batch to release the input batches.
+const MIN_COMPACT_ROWS: usize = 8192;
+
+/// Rewrites `Sort(fetch = k)` into [TopK], if `k` is at most [MAX_TOPK_FETCH] and the first sort
+/// expression is not the time index of the scanned table.
+///
+/// The input of the [TopK] keeps the top-k rows of each place the data are stored if the
+/// scanned table supports top-k pushdown.
+pub struct TopKRule;
+
+impl OptimizerRule for TopKRule {
+    fn try_optimize(
+        &self,
+        plan: &LogicalPlan,
+        _config: &dyn OptimizerConfig,
+    ) -> DfResult<Option<LogicalPlan>> {
+        let LogicalPlan::Sort(sort) = plan else { return Ok(None) };
+        let Some(fetch) = sort.fetch else { return Ok(None) };
+        if fetch > MAX_TOPK_FETCH || sort.expr.is_empty() || sorts_by_time_index(sort) {
+            return Ok(None);
+        }
+
+        let input = match pushdown_topk(&sort.input, &sort.expr, fetch)? {
+            Some(input) => input,
+            None => sort.input.as_ref().clone(),
+        };
+        let node = TopK {
+            expr: sort.expr.clone(),
+            fetch,
+            input,
+        };
+        Ok(Some(LogicalPlan::Extension(Extension {
+            node: Arc::new(node),
+        })))
+    }
+
+    fn name(&self) -> &str {
+        "TopKRule"
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::TopDown)
+    }
+}
+
+/// Whether the first sort expression is the time index of the table scanned under the sort,
+/// looking through projections and filters.
+fn sorts_by_time_index(sort: &datafusion_expr::Sort) -> bool {
+    let Some(Expr::Sort(Sort { expr, .. })) = sort.expr.first() else { return false };
+    let Expr::Column(column) = expr.as_ref() else { return false };
+
+    let mut plan = sort.input.as_ref();
+    loop {
+        match plan {
+            LogicalPlan::Projection(projection) => plan = &projection.input,
+            LogicalPlan::Filter(filter) => plan = &filter.input,
+            LogicalPlan::TableScan(scan) => {
+                return scanned_table(scan)
+                    .and_then(|table| table.schema().timestamp_column().cloned())
+                    .map(|ts_column| ts_column.name == column.name)
+                    .unwrap_or(false);
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Keeps the top `fetch` rows of its input by the sort expressions, in order.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct TopK {
+    expr: Vec<Expr>,
+    fetch: usize,
+    input: LogicalPlan,
+}
+
+impl UserDefinedLogicalNodeCore for TopK {
+    fn name(&self) -> &str {
+        "TopK"
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        self.expr.clone()
+    }
+
+    fn fmt_for_explain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TopK: fetch={}, expr={:?}", self.fetch, self.expr)
+    }
+
+    fn from_template(&self, exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        assert!(!inputs.is_empty());
+
+        Self {
+            expr: exprs.to_vec(),
+            fetch: self.fetch,
+            input: inputs[0].clone(),
+        }
+    }
+}
+
+/// Plans [TopK] into [TopKExec].
+pub struct TopKExtensionPlanner;
+
+#[async_trait]
+impl ExtensionPlanner for TopKExtensionPlanner {
+    async fn plan_extension(
+        &self,
+        planner: &dyn PhysicalPlanner,
+        node: &dyn UserDefinedLogicalNode,
+        logical_inputs: &[&LogicalPlan],
+        physical_inputs: &[Arc<dyn ExecutionPlan>],
+        session_state: &SessionState,
+    ) -> DfResult<Option<Arc<dyn ExecutionPlan>>> {
+        let Some(node) = node.as_any().downcast_ref::<TopK>() else {
+            return Ok(None);
+        };
+        let input = physical_inputs[0].clone();
+        let input_schema = input.schema();
+        let expr = node
+            .expr
+            .iter()
+            .map(|expr| {
+                let Expr::Sort(Sort { expr, asc, nulls_first }) = expr else {
+                    return Err(DataFusionError::Plan(format!(
+                        "Expect a sort expression of TopK, found {expr:?}"
+                    )));
+                };
+                Ok(PhysicalSortExpr {
+                    expr: planner.create_physical_expr(
+                        expr,
+                        logical_inputs[0].schema(),
+                        &input_schema,
+                        session_state,
+                    )?,
+                    options: SortOptions {
+                        descending: !asc,
+                        nulls_first: *nulls_first,
+                    },
+                })
+            })
+            .collect::<DfResult<Vec<_>>>()?;
+        Ok(Some(Arc::new(TopKExec::new(input, expr, node.fetch))))
+    }
+}
+
+/// Outputs the top `fetch` rows of all the partitions of its input by the sort expressions, in
+/// order and in one partition. Rows of equal sort keys are output in the order they arrive.
+#[derive(Debug)]
+pub struct TopKExec {
+    input: Arc<dyn ExecutionPlan>,
+    expr: Vec<PhysicalSortExpr>,
+    fetch: usize,
+    metric: ExecutionPlanMetricsSet,
+}
+
+impl TopKExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>, expr: Vec<PhysicalSortExpr>, fetch: usize) -> Self {
+        Self {
+            input,
+            expr,
+            fetch,
+            metric: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    pub fn fetch(&self) -> usize {
+        self.fetch
+    }
+}
+
+impl ExecutionPlan for TopKExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        Some(&self.expr)
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        Ok(Arc::new(Self {
+            input: children[0].clone(),
+            expr: self.expr.clone(),
+            fetch: self.fetch,
+            metric: self.metric.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DfResult<SendableRecordBatchStream> {
+        let input: Arc<dyn ExecutionPlan> =
+            if self.input.output_partitioning().partition_count() > 1 {
+                Arc::new(CoalescePartitionsExec::new(self.input.clone()))
+            } else {
+                self.input.clone()
+            };
+        let input = input.execute(0, context)?;
+        Ok(Box::pin(TopKStream::try_new(
+            input,
+            self.expr.clone(),
+            self.fetch,
+            BaselineMetrics::new(&self.metric, partition),
+        )?))
+    }
+
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                let expr = self
+                    .expr
+                    .iter()
+                    .map(|expr| expr.to_string())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "TopKExec: fetch={}, expr=[{}]",
+                    self.fetch,
+                    expr.join(",")
+                )
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+struct TopKStream {
+    input: SendableRecordBatchStream,
+    schema: SchemaRef,
+    expr: Vec<PhysicalSortExpr>,
+    heap: TopKHeap,
+    done: bool,
+    baseline_metric: BaselineMetrics,
+}
+
+impl TopKStream {
+    fn try_new(
+        input: SendableRecordBatchStream,
+        expr: Vec<PhysicalSortExpr>,
+        fetch: usize,
+        baseline_metric: BaselineMetrics,
+    ) -> DfResult<Self> {
+        let schema = input.schema();
+        let fields = expr
+            .iter()
+            .map(|expr| {
+                Ok(SortField::new_with_options(
+                    expr.expr.data_type(&schema)?,
+                    expr.options,
+                ))
+            })
+            .collect::<DfResult<Vec<_>>>()?;
+        Ok(Self {
+            heap: TopKHeap::try_new(schema.clone(), fields, fetch)?,
+            input,
+            schema,
+            expr,
+            done: false,
+            baseline_metric,
+        })
+    }
+
+    fn insert(&mut self, batch: RecordBatch) -> DfResult<()> {
+        let _timer = self.baseline_metric.elapsed_compute().timer();
+        let sort_columns = self
+            .expr
+            .iter()
+            .map(|expr| Ok(expr.expr.evaluate(&batch)?.into_array(batch.num_rows())))
+            .collect::<DfResult<Vec<_>>>()?;
+        self.heap.insert(batch, &sort_columns)
+    }
+
+    fn poll_next_inner(&mut self, cx: &mut Context<'_>) -> Poll<Option<DfResult<RecordBatch>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        loop {
+            match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => {
+                    if let Err(e) = self.insert(batch) {
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    self.done = true;
+                    let _timer = self.baseline_metric.elapsed_compute().timer();
+                    return Poll::Ready(Some(self.heap.emit()));
+                }
+            }
+        }
+    }
+}
+
+impl Stream for TopKStream {
+    type Item = DfResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.poll_next_inner(cx);
+        self.baseline_metric.record_poll(poll)
+    }
+}
+
+impl RecordBatchStream for TopKStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// A row in the heap, ordered by its sort key, then by the order it arrives.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct HeapItem {
+    /// Sort key of the row in the row format, which compares as the sort expressions order.
+    row: OwnedRow,
+    /// Id of the batch holding the row, ids are increasing in the order batches arrive.
+    batch_id: usize,
+    /// Index of the row in the batch.
+    index: usize,
+}
+
+/// The top `fetch` rows seen so far. The heap is a max-heap, so the last of the top rows is at
+/// the top of the heap and is evicted once a smaller row arrives.
+///
+/// The rows are kept in the batches they arrive in, and a batch is released once none of its
+/// rows are in the heap. As the top rows may be scattered over many batches, they are compacted
+/// into a new batch once the retained batches hold [MIN_COMPACT_ROWS] rows more than `fetch`.
+struct TopKHeap {
+    schema: SchemaRef,
+    converter: RowConverter,
+    fetch: usize,
+    heap: BinaryHeap<HeapItem>,
+    /// Retained batches and the number of their rows in the heap, by their ids.
+    batches: HashMap<usize, (RecordBatch, usize)>,
+    next_batch_id: usize,
+    /// Number of rows of the retained batches.
+    retained_rows: usize,
+    /// Max number of rows ever retained.
+    max_retained_rows: usize,
+}
+
+impl TopKHeap {
+    fn try_new(schema: SchemaRef, fields: Vec<SortField>, fetch: usize) -> DfResult<Self> {
+        Ok(Self {
+            schema,
+            converter: RowConverter::new(fields)?,
+            fetch,
+            heap: BinaryHeap::with_capacity(fetch),
+            batches: HashMap::new(),
+            next_batch_id: 0,
+            retained_rows: 0,
+            max_retained_rows: 0,
+        })
+    }
+
+    /// Inserts the rows of `batch` that are among the top rows, `sort_columns` are the values
+    /// of the sort expressions of the rows.
+    fn insert(&mut self, batch: RecordBatch, sort_columns: &[ArrayRef]) -> DfResult<()> {
+        if self.fetch == 0 || batch.num_rows() == 0 {
+            return Ok(());
+        }
+
+        let rows = self.converter.convert_columns(sort_columns)?;
+        let batch_id = self.next_batch_id;
+        self.next_batch_id += 1;
+        let mut uses = 0;
+        for (index, row) in rows.iter().enumerate() {
+            if self.heap.len() >= self.fetch {
+                // Rows equal to the last of the top rows arrive later, so they are not taken.
+                let last = self.heap.peek().unwrap();
+                if row >= last.row.row() {
+                    continue;
+                }
+                let evicted = self.heap.pop().unwrap();
+                if evicted.batch_id == batch_id {
+                    uses -= 1;
+                } else {
+                    self.release(evicted.batch_id);
+                }
+            }
+            self.heap.push(HeapItem {
+                row: row.owned(),
+                batch_id,
+                index,
+            });
+            uses += 1;
+        }
+
+        if uses > 0 {
+            self.retained_rows += batch.num_rows();
+            self.max_retained_rows = self.max_retained_rows.max(self.retained_rows);
+            let _ = self.batches.insert(batch_id, (batch, uses));
+        }
+        if self.retained_rows > self.fetch + MIN_COMPACT_ROWS {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Releases a row of the batch, and the batch itself if it has no rows in the heap.
+    fn release(&mut self, batch_id: usize) {
+        let (batch, uses) = self.batches.get_mut(&batch_id).unwrap();
+        *uses -= 1;
+        if *uses == 0 {
+            self.retained_rows -= batch.num_rows();
+            let _ = self.batches.remove(&batch_id);
+        }
+    }
+
+    /// Moves the rows in the heap into a new batch, and releases all the other batches.
+    fn compact(&mut self) -> DfResult<()> {
+        let mut items = std::mem::take(&mut self.heap).into_vec();
+        // Keeps the order of arrival of the rows in the new batch.
+        items.sort_unstable_by_key(|item| (item.batch_id, item.index));
+        let batch = self.take_rows(&items)?;
+
+        let batch_id = self.next_batch_id;
+        self.next_batch_id += 1;
+        for (index, item) in items.iter_mut().enumerate() {
+            item.batch_id = batch_id;
+            item.index = index;
+        }
+        self.batches.clear();
+        self.retained_rows = batch.num_rows();
+        let _ = self.batches.insert(batch_id, (batch, items.len()));
+        self.heap = BinaryHeap::from(items);
+        Ok(())
+    }
+
+    /// Returns the rows of the items from the retained batches, in the order of the items.
+    fn take_rows(&self, items: &[HeapItem]) -> DfResult<RecordBatch> {
+        if items.is_empty() {
+            return Ok(RecordBatch::new_empty(self.schema.clone()));
+        }
+
+        let mut batches = vec![];
+        let mut positions = HashMap::new();
+        let indices = items
+            .iter()
+            .map(|item| {
+                let position = *positions.entry(item.batch_id).or_insert_with(|| {
+                    batches.push(&self.batches[&item.batch_id].0);
+                    batches.len() - 1
+                });
+                (position, item.index)
+            })
+            .collect::<Vec<_>>();
+        let columns = (0..self.schema.fields().len())
+            .map(|i| {
+                let arrays = batches
+                    .iter()
+                    .map(|batch| batch.column(i).as_ref())
+                    .collect::<Vec<&dyn Array>>();
+                interleave(&arrays, &indices)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+
+    /// Returns the top rows in order.
+    fn emit(&mut self) -> DfResult<RecordBatch> {
+        let items = std::mem::take(&mut self.heap).into_sorted_vec();
+        let batch = self.take_rows(&items)?;
+        self.batches.clear();
+        self.retained_rows = 0;
+
+        ::metrics::histogram!(
+            metrics::METRIC_TOPK_RETAINED_ROWS,
+            self.max_retained_rows as f64
+        );
+        Ok(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use datafusion::physical_plan::expressions::col;
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::prelude::SessionContext;
+    use datatypes::arrow::array::{Int64Array, StringArray};
+    use datatypes::arrow::compute::concat_batches;
+    use datatypes::arrow::datatypes::{DataType, Field, Schema};
+    use futures::TryStreamExt;
+    use rand::Rng;
+
+    use super::*;
+
+    fn test_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("n", DataType::Int64, true),
+            Field::new("s", DataType::Utf8, false),
+        ]))
+    }
+
+    /// Returns `num_batches` batches of `rows` random rows, with lots of ties and nulls in
+    /// column `n`, and the sequence numbers of the rows in column `s`.
+    fn random_batches(num_batches: usize, rows: usize) -> Vec<RecordBatch> {
+        let mut rng = rand::thread_rng();
+        (0..num_batches)
+            .map(|i| {
+                let numbers = (0..rows)
+                    .map(|_| {
+                        let n = rng.gen_range(0..20);
+                        (n != 0).then_some(n)
+                    })
+                    .collect::<Vec<_>>();
+                let strings = (0..rows)
+                    .map(|j| format!("row-{:06}", i * rows + j))
+                    .collect::<Vec<_>>();
+                RecordBatch::try_new(
+                    test_schema(),
+                    vec![
+                        Arc::new(Int64Array::from(numbers)),
+                        Arc::new(StringArray::from(strings)),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    /// Sorts the rows by column `n` stably and takes the first `fetch` rows.
+    fn naive_topk(batches: &[RecordBatch], options: SortOptions, fetch: usize) -> RecordBatch {
+        let batch = concat_batches(&test_schema(), batches).unwrap();
+        let numbers = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        let mut indices = (0..batch.num_rows()).collect::<Vec<_>>();
+        let key = |i: usize| numbers.is_valid(i).then(|| numbers.value(i));
+        indices.sort_by(|a, b| match (key(*a), key(*b)) {
+            (Some(a), Some(b)) if options.descending => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) if options.nulls_first => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) if options.nulls_first => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+        });
+        indices.truncate(fetch);
+        let indices = indices.into_iter().map(|i| (0, i)).collect::<Vec<_>>();
+        let columns = batch
+            .columns()
+            .iter()
+            .map(|column| interleave(&[column.as_ref()], &indices).unwrap())
+            .collect();
+        RecordBatch::try_new(test_schema(), columns).unwrap()
+    }
+
+    async fn execute_topk(
+        partitions: &[Vec<RecordBatch>],
+        options: SortOptions,
+        fetch: usize,
+    ) -> RecordBatch {
+        let scan = Arc::new(MemoryExec::try_new(partitions, test_schema(), None).unwrap());
+        let expr = vec![PhysicalSortExpr {
+            expr: col("n", &test_schema()).unwrap(),
+            options,
+        }];
+        let topk = Arc::new(TopKExec::new(scan, expr, fetch));
+        let stream = topk.execute(0, SessionContext::new().task_ctx()).unwrap();
+        let batches = stream.try_collect::<Vec<_>>().await.unwrap();
+        concat_batches(&test_schema(), &batches).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_topk_equals_naive_sort() {
+        for fetch in [0, 1, 7, 100, 1000, 5000] {
+            for (descending, nulls_first) in
+                [(false, false), (false, true), (true, false), (true, true)]
+            {
+                let options = SortOptions {
+                    descending,
+                    nulls_first,
+                };
+                let input = random_batches(30, 100);
+                let expected = naive_topk(&input, options, fetch);
+
+                let output = execute_topk(&[input], options, fetch).await;
+                assert_eq!(expected, output, "fetch: {fetch}, options: {options:?}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_topk_of_partitions() {
+        let options = SortOptions {
+            descending: true,
+            nulls_first: true,
+        };
+        let input = random_batches(30, 100);
+        let partitions = input.chunks(10).map(|c| c.to_vec()).collect::<Vec<_>>();
+        let output = execute_topk(&partitions, options, 50).await;
+
+        // Ties may arrive in any order from the partitions, so only the sort keys are compared.
+        let expected = naive_topk(&input, options, 50);
+        assert_eq!(expected.column(0), output.column(0));
+    }
+
+    #[test]
+    fn test_topk_retains_bounded_rows() {
+        const FETCH: usize = 100;
+        const BATCH_ROWS: usize = 8192;
+        const TOTAL_ROWS: usize = 10_000_000;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, false)]));
+        let fields = vec![SortField::new_with_options(
+            DataType::Int64,
+            SortOptions::default(),
+        )];
+        let mut heap = TopKHeap::try_new(schema.clone(), fields, FETCH).unwrap();
+
+        // Descending numbers, so every batch has new top rows and the heap keeps evicting.
+        let mut start = TOTAL_ROWS as i64;
+        while start > 0 {
+            let end = (start - BATCH_ROWS as i64).max(0);
+            let column: ArrayRef = Arc::new(Int64Array::from_iter_values((end..start).rev()));
+            let batch = RecordBatch::try_new(schema.clone(), vec![column.clone()]).unwrap();
+            heap.insert(batch, &[column]).unwrap();
+            assert!(heap.batches.len() <= FETCH + 1);
+            start = end;
+        }
+
+        assert!(
+            heap.max_retained_rows <= FETCH + MIN_COMPACT_ROWS + BATCH_ROWS,
+            "retained {} rows",
+            heap.max_retained_rows
+        );
+        let output = heap.emit().unwrap();
+        let numbers = output
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(
+            (1..=FETCH as i64).collect::<Vec<_>>(),
+            numbers.values().to_vec()
+        );
+    }
+}
@@ -82,6 +82,9 @@ pub enum Error {
 
     #[snafu(display("Failed to cast type, msg: {}", msg))]
     CastType { msg: String, location: Location },
+
+    #[snafu(display("Recompiling scripts panicked, msg: {}", msg))]
+    RecompilePanicked { msg: String, location: Location },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -90,7 +93,9 @@ impl ErrorExt for Error {
     fn status_code(&self) -> StatusCode {
         use Error::*;
         match self {
-            FindColumnInScriptsTable { .. } | CastType { .. } => StatusCode::Unexpected,
+            FindColumnInScriptsTable { .. } | CastType { .. } | RecompilePanicked { .. } => {
+                StatusCode::Unexpected
+            }
             ScriptsTableNotFound { .. } => StatusCode::TableNotFound,
             RegisterScriptsTable { source } | FindScriptsTable { source } => source.status_code(),
             InsertScript { source, .. } => source.status_code(),
@@ -13,41 +13,81 @@
 // limitations under the License.
 
 //! Scripts manager
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 
-use catalog::CatalogManagerRef;
+use catalog::error::CompileScriptInternalSnafu;
+use catalog::{CatalogManagerRef, OpenSystemTableHook};
+use common_error::prelude::BoxedError;
 use common_query::Output;
 use common_telemetry::logging;
+use futures::StreamExt;
 use query::QueryEngineRef;
 use snafu::{OptionExt, ResultExt};
+use table::TableRef;
 
 use crate::engine::{CompileContext, EvalContext, Script, ScriptEngine};
-use crate::error::{CompilePythonSnafu, ExecutePythonSnafu, Result, ScriptNotFoundSnafu};
+use crate::error::{
+    CompilePythonSnafu, ExecutePythonSnafu, RecompilePanickedSnafu, Result, ScriptNotFoundSnafu,
+};
+use crate::python::utils::block_on_async;
 use crate::python::{PyEngine, PyScript};
 use crate::table::ScriptsTable;
 
-pub struct ScriptManager {
-    compiled: RwLock<HashMap<String, Arc<PyScript>>>,
-    py_engine: PyEngine,
-    table: ScriptsTable,
+/// Options of recompiling the scripts in the scripts table when it's opened at startup.
+#[derive(Debug, Clone)]
+pub struct RecompileOptions {
+    /// Recompiles the scripts before the start completes and fails the start on any compile
+    /// error. Otherwise they are recompiled in background, and the scripts failed to compile
+    /// are only recorded in their states.
+    pub strict: bool,
+    /// Max number of scripts recompiled concurrently in background.
+    pub parallelism: usize,
 }
 
-impl ScriptManager {
-    pub async fn new(
-        catalog_manager: CatalogManagerRef,
-        query_engine: QueryEngineRef,
-    ) -> Result<Self> {
-        Ok(Self {
-            compiled: RwLock::new(HashMap::default()),
-            py_engine: PyEngine::new(query_engine.clone()),
-            table: ScriptsTable::new(catalog_manager, query_engine).await?,
-        })
+impl Default for RecompileOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            parallelism: 4,
+        }
     }
+}
 
+/// Compile state of a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptState {
+    /// Not compiled yet, it's compiled on its first call.
+    Pending,
+    Compiled,
+    /// Failed to compile, with the error.
+    Failed(String),
+}
+
+/// Compiles the scripts and keeps the compiled ones, shared by the manager and the
+/// recompilation of the scripts table.
+struct Compiler {
+    py_engine: PyEngine,
+    compiled: RwLock<HashMap<String, Arc<PyScript>>>,
+    states: RwLock<BTreeMap<String, ScriptState>>,
+}
+
+impl Compiler {
     /// compile script, and register them to the query engine and UDF registry
     async fn compile(&self, name: &str, script: &str) -> Result<Arc<PyScript>> {
-        let script = Arc::new(Self::compile_without_cache(&self.py_engine, name, script).await?);
+        let script = match self
+            .py_engine
+            .compile(script, CompileContext::default())
+            .await
+            .context(CompilePythonSnafu { name })
+        {
+            Ok(script) => Arc::new(script),
+            Err(e) => {
+                self.set_state(name, ScriptState::Failed(e.to_string()));
+                return Err(e);
+            }
+        };
 
         {
             let mut compiled = self.compiled.write().unwrap();
@@ -59,19 +99,128 @@ impl ScriptManager {
 
         logging::info!("Script register as UDF: {}", name);
 
+        self.set_state(name, ScriptState::Compiled);
         Ok(script)
     }
 
-    /// compile script to PyScript, but not register them to the query engine and UDF registry nor caching in `compiled`
-    async fn compile_without_cache(
-        py_engine: &PyEngine,
-        name: &str,
-        script: &str,
-    ) -> Result<PyScript> {
-        py_engine
-            .compile(script, CompileContext::default())
-            .await
-            .context(CompilePythonSnafu { name })
+    fn set_state(&self, name: &str, state: ScriptState) {
+        let _ = self.states.write().unwrap().insert(name.to_string(), state);
+    }
+
+    /// Recompiles the scripts in the scripts `table`, stops at the first script failed to
+    /// compile.
+    async fn recompile_strictly(&self, table: TableRef) -> catalog::error::Result<()> {
+        for (name, script) in ScriptsTable::read_scripts(table).await? {
+            let _ = self
+                .compile(&name, &script)
+                .await
+                .map_err(BoxedError::new)
+                .context(CompileScriptInternalSnafu)?;
+        }
+        Ok(())
+    }
+
+    /// Recompiles the scripts in the scripts `table`, at most `parallelism` scripts at a time.
+    /// A script failed to compile doesn't affect the others.
+    async fn recompile(self: Arc<Self>, table: TableRef, parallelism: usize) {
+        let scripts = match ScriptsTable::read_scripts(table).await {
+            Ok(scripts) => scripts,
+            Err(e) => {
+                logging::warn!("Failed to read scripts in `scripts` table: {}", e);
+                return;
+            }
+        };
+        {
+            let mut states = self.states.write().unwrap();
+            for (name, _) in &scripts {
+                let _ = states.entry(name.clone()).or_insert(ScriptState::Pending);
+            }
+        }
+
+        let total = scripts.len();
+        let compiled = futures::stream::iter(scripts)
+            .map(|(name, script)| {
+                let compiler = self.clone();
+                common_runtime::spawn_bg(async move {
+                    match compiler.compile(&name, &script).await {
+                        Ok(_) => true,
+                        Err(e) => {
+                            logging::warn!(
+                                r#"Failed to compile script "{}" in `scripts` table: {}"#,
+                                name,
+                                e
+                            );
+                            false
+                        }
+                    }
+                })
+            })
+            .buffer_unordered(parallelism.max(1))
+            .filter(|compiled| futures::future::ready(matches!(compiled, Ok(true))))
+            .count()
+            .await;
+        logging::info!(
+            "Recompiled {} of {} scripts in `scripts` table",
+            compiled,
+            total
+        );
+    }
+}
+
+/// Returns the message of a panic `payload`, which is usually a `&str` or a `String`.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Returns the hook recompiling the scripts when the scripts table is opened.
+fn recompile_hook(compiler: Arc<Compiler>, options: RecompileOptions) -> OpenSystemTableHook {
+    Arc::new(move |table: TableRef| {
+        let compiler = compiler.clone();
+        if options.strict {
+            return block_on_async(async move { compiler.recompile_strictly(table).await })
+                .map_err(|payload| {
+                    let msg = panic_message(payload.as_ref());
+                    BoxedError::new(RecompilePanickedSnafu { msg }.build())
+                })
+                .context(CompileScriptInternalSnafu)?;
+        }
+
+        let _handle = common_runtime::spawn_bg(compiler.recompile(table, options.parallelism));
+        Ok(())
+    })
+}
+
+pub struct ScriptManager {
+    compiler: Arc<Compiler>,
+    table: ScriptsTable,
+}
+
+impl ScriptManager {
+    pub async fn new(
+        catalog_manager: CatalogManagerRef,
+        query_engine: QueryEngineRef,
+        recompile_options: RecompileOptions,
+    ) -> Result<Self> {
+        let compiler = Arc::new(Compiler {
+            py_engine: PyEngine::new(query_engine.clone()),
+            compiled: RwLock::new(HashMap::default()),
+            states: RwLock::new(BTreeMap::default()),
+        });
+        let open_hook = recompile_hook(compiler.clone(), recompile_options);
+        Ok(Self {
+            compiler,
+            table: ScriptsTable::new(catalog_manager, query_engine, open_hook).await?,
+        })
+    }
+
+    async fn compile(&self, name: &str, script: &str) -> Result<Arc<PyScript>> {
+        self.compiler.compile(name, script).await
     }
 
     pub async fn insert_and_compile(
@@ -92,7 +241,7 @@ impl ScriptManager {
         params: HashMap<String, String>,
     ) -> Result<Output> {
         let script = {
-            let s = self.compiled.read().unwrap().get(name).cloned();
+            let s = self.compiler.compiled.read().unwrap().get(name).cloned();
 
             if s.is_some() {
                 s
@@ -109,6 +258,17 @@ impl ScriptManager {
             .context(ExecutePythonSnafu { name })
     }
 
+    /// Returns the compile states of the scripts by their names.
+    pub fn script_states(&self) -> Vec<(String, ScriptState)> {
+        self.compiler
+            .states
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, state)| (name.clone(), state.clone()))
+            .collect()
+    }
+
     async fn try_find_script_and_compile(
         &self,
         schema: &str,
@@ -122,30 +282,42 @@ impl ScriptManager {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use catalog::CatalogManager;
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use common_test_util::temp_dir::{create_temp_dir, TempDir};
+    use log_store::raft_engine::log_store::RaftEngineLogStore;
+    use log_store::LogConfig;
     use mito::config::EngineConfig as TableEngineConfig;
+    use mito::engine::MitoEngine;
     use mito::table::test_util::new_test_object_store;
     use query::QueryEngineFactory;
+    use storage::compaction::noop::NoopCompactionScheduler;
+    use storage::config::EngineConfig as StorageEngineConfig;
+    use storage::EngineImpl;
     use table::engine::manager::MemoryTableEngineManager;
 
     use super::*;
+    use crate::error::Error;
+    use crate::table::SCRIPTS_TABLE_NAME;
+
     type DefaultEngine = MitoEngine<EngineImpl<RaftEngineLogStore>>;
 
-    use common_test_util::temp_dir::create_temp_dir;
-    use log_store::raft_engine::log_store::RaftEngineLogStore;
-    use log_store::LogConfig;
-    use mito::engine::MitoEngine;
-    use storage::compaction::noop::NoopCompactionScheduler;
-    use storage::config::EngineConfig as StorageEngineConfig;
-    use storage::EngineImpl;
+    const SCRIPT: &str = r#"
+@copr(sql='select number from numbers limit 10', args=['number'], returns=['n'])
+def test(n):
+    return n + 1;
+"#;
 
-    #[tokio::test]
-    async fn test_insert_find_compile_script() {
-        let wal_dir = create_temp_dir("test_insert_find_compile_script_wal");
+    async fn create_script_manager(
+        name: &str,
+    ) -> (ScriptManager, CatalogManagerRef, (TempDir, TempDir)) {
+        let wal_dir = create_temp_dir(&format!("{name}_wal"));
         let wal_dir_str = wal_dir.path().to_string_lossy();
 
         common_telemetry::init_default_ut_logging();
-        let (_dir, object_store) = new_test_object_store("test_insert_find_compile_script").await;
+        let (dir, object_store) = new_test_object_store(name).await;
         let log_config = LogConfig {
             log_file_dir: wal_dir_str.to_string(),
             ..Default::default()
@@ -172,28 +344,29 @@ mod tests {
 
         let factory = QueryEngineFactory::new(catalog_manager.clone());
         let query_engine = factory.query_engine();
-        let mgr = ScriptManager::new(catalog_manager.clone(), query_engine)
-            .await
-            .unwrap();
+        let mgr = ScriptManager::new(
+            catalog_manager.clone(),
+            query_engine,
+            RecompileOptions::default(),
+        )
+        .await
+        .unwrap();
         catalog_manager.start().await.unwrap();
 
+        (mgr, catalog_manager, (dir, wal_dir))
+    }
+
+    #[tokio::test]
+    async fn test_insert_find_compile_script() {
+        let (mgr, _catalog_manager, _dirs) =
+            create_script_manager("test_insert_find_compile_script").await;
+
         let schema = "schema";
         let name = "test";
-        mgr.table
-            .insert(
-                schema,
-                name,
-                r#"
-@copr(sql='select number from numbers limit 10', args=['number'], returns=['n'])
-def test(n):
-    return n + 1;
-"#,
-            )
-            .await
-            .unwrap();
+        mgr.table.insert(schema, name, SCRIPT).await.unwrap();
 
         {
-            let cached = mgr.compiled.read().unwrap();
+            let cached = mgr.compiler.compiled.read().unwrap();
             assert!(cached.get(name).is_none());
         }
 
@@ -202,8 +375,86 @@ def test(n):
         assert!(script.is_some());
 
         {
-            let cached = mgr.compiled.read().unwrap();
+            let cached = mgr.compiler.compiled.read().unwrap();
             assert!(cached.get(name).is_some());
         }
+        assert_eq!(
+            vec![(name.to_string(), ScriptState::Compiled)],
+            mgr.script_states()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_recompile_broken_script() {
+        let (mgr, catalog_manager, _dirs) =
+            create_script_manager("test_recompile_broken_script").await;
+
+        let schema = "schema";
+        mgr.table.insert(schema, "test", SCRIPT).await.unwrap();
+        let broken = SCRIPT
+            .replace("def test(n):", "def broken(n)")
+            .replace("returns=['n']", "returns=['b']");
+        mgr.table.insert(schema, "broken", &broken).await.unwrap();
+        let table = catalog_manager
+            .table(
+                DEFAULT_CATALOG_NAME,
+                DEFAULT_SCHEMA_NAME,
+                SCRIPTS_TABLE_NAME,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Reopening the table doesn't wait for the scripts to compile.
+        let hook = recompile_hook(mgr.compiler.clone(), RecompileOptions::default());
+        hook(table.clone()).unwrap();
+        for _ in 0..100 {
+            if mgr
+                .script_states()
+                .iter()
+                .all(|(_, state)| *state != ScriptState::Pending)
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let states = mgr.script_states();
+        assert_eq!(2, states.len());
+        assert_eq!("broken", states[0].0);
+        let ScriptState::Failed(err) = &states[0].1 else { panic!("unexpected state: {:?}", states[0].1) };
+        assert!(err.contains("broken"), "{err}");
+        assert_eq!(("test".to_string(), ScriptState::Compiled), states[1]);
+
+        // The good script runs, the broken one reports its compile error.
+        let _ = mgr.execute(schema, "test", HashMap::new()).await.unwrap();
+        let err = mgr
+            .execute(schema, "broken", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::CompilePython { .. }), "{err:?}");
+
+        // The strict recompilation fails on the broken script.
+        let hook = recompile_hook(
+            mgr.compiler.clone(),
+            RecompileOptions {
+                strict: true,
+                ..Default::default()
+            },
+        );
+        let err = hook(table).unwrap_err();
+        assert!(
+            matches!(err, catalog::error::Error::CompileScriptInternal { .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let payload = std::panic::catch_unwind(|| panic!("static message")).unwrap_err();
+        assert_eq!("static message", panic_message(payload.as_ref()));
+        let payload = std::panic::catch_unwind(|| panic!("formatted {}", 1)).unwrap_err();
+        assert_eq!("formatted 1", panic_message(payload.as_ref()));
+        let payload = std::panic::catch_unwind(|| std::panic::panic_any(1)).unwrap_err();
+        assert_eq!("unknown panic", panic_message(payload.as_ref()));
     }
 }
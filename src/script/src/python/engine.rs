@@ -35,14 +35,13 @@ use datatypes::vectors::VectorRef;
 use futures::Stream;
 use query::parser::{QueryLanguageParser, QueryStatement};
 use query::QueryEngineRef;
-use session::context::QueryContext;
 use snafu::{ensure, ResultExt};
 use sql::statements::statement::Statement;
 
 use crate::engine::{CompileContext, EvalContext, Script, ScriptEngine};
 use crate::python::error::{self, PyRuntimeSnafu, Result, TokioJoinSnafu};
 use crate::python::ffi_types::copr::{exec_parsed, parse, AnnotationInfo, CoprocessorRef};
-use crate::python::utils::spawn_blocking_script;
+use crate::python::utils::{script_query_ctx, spawn_blocking_script};
 const PY_ENGINE: &str = "python";
 
 #[derive(Debug)]
@@ -286,12 +285,13 @@ impl Script for PyScript {
                 matches!(stmt, QueryStatement::Sql(Statement::Query { .. })),
                 error::UnsupportedSqlSnafu { sql }
             );
+            let query_ctx = script_query_ctx();
             let plan = self
                 .query_engine
                 .planner()
-                .plan(stmt, QueryContext::arc())
+                .plan(stmt, query_ctx.clone())
                 .await?;
-            let res = self.query_engine.execute(plan, QueryContext::arc()).await?;
+            let res = self.query_engine.execute(plan, query_ctx).await?;
             let copr = self.copr.clone();
             match res {
                 Output::Stream(stream) => Ok(Output::Stream(Box::pin(CoprStream::try_new(
@@ -34,7 +34,6 @@ use rustpython_compiler_core::CodeObject;
 use rustpython_vm as vm;
 #[cfg(test)]
 use serde::Deserialize;
-use session::context::QueryContext;
 use snafu::{OptionExt, ResultExt};
 use vm::convert::ToPyObject;
 use vm::{pyclass as rspyclass, PyObjectRef, PyPayload, PyResult, VirtualMachine};
@@ -45,6 +44,7 @@ use crate::python::ffi_types::PyVector;
 #[cfg(feature = "pyo3_backend")]
 use crate::python::pyo3::pyo3_exec_parsed;
 use crate::python::rspython::rspy_exec_parsed;
+use crate::python::utils::script_query_ctx;
 
 #[cfg_attr(test, derive(Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -379,14 +379,15 @@ impl PyQueryEngine {
                 let rt = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
                 let handle = rt.handle().clone();
                 let res = handle.block_on(async {
+                    let query_ctx = script_query_ctx();
                     let plan = engine
                         .planner()
-                        .plan(stmt, Default::default())
+                        .plan(stmt, query_ctx.clone())
                         .await
                         .map_err(|e| e.to_string())?;
                     let res = engine
                         .clone()
-                        .execute(plan, QueryContext::arc())
+                        .execute(plan, query_ctx)
                         .await
                         .map_err(|e| e.to_string());
                     match res {
@@ -12,10 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use common_runtime::JoinHandle;
 use futures::Future;
 use rustpython_vm::builtins::PyBaseExceptionRef;
 use rustpython_vm::VirtualMachine;
+use session::context::{QueryContext, QueryContextRef, QueryOrigin};
 
 use crate::python::error;
 
@@ -30,6 +33,11 @@ pub fn format_py_error(excep: PyBaseExceptionRef, vm: &VirtualMachine) -> error:
     error::PyRuntimeSnafu { msg }.build()
 }
 
+/// Context of the queries issued by the scripts, e.g. the SQL of a coprocessor.
+pub fn script_query_ctx() -> QueryContextRef {
+    Arc::new(QueryContext::new().with_origin(QueryOrigin::Background("scripts".to_string())))
+}
+
 /// just like [`tokio::task::spawn_blocking`] but using a dedicated runtime(runtime `bg`) using by `scripts` crate
 pub fn spawn_blocking_script<F, R>(f: F) -> JoinHandle<R>
 where
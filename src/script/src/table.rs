@@ -33,7 +33,7 @@ use datatypes::schema::{ColumnSchema, RawSchema};
 use datatypes::vectors::{StringVector, TimestampMillisecondVector, Vector, VectorRef};
 use query::parser::QueryLanguageParser;
 use query::QueryEngineRef;
-use session::context::QueryContext;
+use session::context::{QueryContext, QueryOrigin};
 use snafu::{ensure, OptionExt, ResultExt};
 use table::requests::{CreateTableRequest, InsertRequest, TableOptions};
 use table::TableRef;
@@ -43,8 +43,6 @@ use crate::error::{
     FindScriptsTableSnafu, InsertScriptSnafu, RegisterScriptsTableSnafu, Result,
     ScriptNotFoundSnafu, ScriptsTableNotFoundSnafu,
 };
-use crate::python::utils::block_on_async;
-use crate::python::PyScript;
 
 pub const SCRIPTS_TABLE_NAME: &str = "scripts";
 
@@ -70,14 +68,8 @@ impl ScriptsTable {
             })?;
         Ok(column)
     }
-    /// this is used as a callback function when scripts table is created. `table` should be `scripts` table.
-    /// the function will try it best to register all scripts, and ignore the error in parsing and register scripts
-    ///  if any, just emit a warning
-    /// TODO(discord9): rethink error handling here
-    pub async fn recompile_register_udf(
-        table: TableRef,
-        query_engine: QueryEngineRef,
-    ) -> catalog::error::Result<()> {
+    /// Reads the names and the scripts in the scripts `table`.
+    pub async fn read_scripts(table: TableRef) -> catalog::error::Result<Vec<(String, String)>> {
         let scan_stream = table
             .scan(None, &[], None)
             .await
@@ -112,30 +104,14 @@ impl ScriptsTable {
                     });
             script_list.extend(part_of_scripts_list);
         }
-
-        for (name, script) in script_list {
-            match PyScript::from_script(&script, query_engine.clone()) {
-                Ok(script) => {
-                    script.register_udf().await;
-                    logging::debug!(
-                        "Script in `scripts` system table re-register as UDF: {}",
-                        name
-                    );
-                }
-                Err(err) => {
-                    logging::warn!(
-                        r#"Failed to compile script "{}"" in `scripts` table: {}"#,
-                        name,
-                        err
-                    );
-                }
-            }
-        }
-        Ok(())
+        Ok(script_list)
     }
+
+    /// Registers the scripts table, `open_hook` is called with the table when it's opened.
     pub async fn new(
         catalog_manager: CatalogManagerRef,
         query_engine: QueryEngineRef,
+        open_hook: OpenSystemTableHook,
     ) -> Result<Self> {
         let schema = build_scripts_schema();
         // TODO(dennis): we put scripts table into default catalog and schema.
@@ -154,19 +130,10 @@ impl ScriptsTable {
             table_options: TableOptions::default(),
             engine: MITO_ENGINE.to_string(),
         };
-        let callback_query_engine = query_engine.clone();
-        let script_recompile_callback: OpenSystemTableHook = Arc::new(move |table: TableRef| {
-            let callback_query_engine = callback_query_engine.clone();
-            block_on_async(async move {
-                Self::recompile_register_udf(table, callback_query_engine.clone()).await
-            })
-            .unwrap()
-        });
-
         catalog_manager
             .register_system_table(RegisterSystemTableRequest {
                 create_table_request: request,
-                open_hook: Some(script_recompile_callback),
+                open_hook: Some(open_hook),
             })
             .await
             .context(RegisterScriptsTableSnafu)?;
@@ -233,6 +200,7 @@ impl ScriptsTable {
                 table_name: SCRIPTS_TABLE_NAME.to_string(),
                 columns_values,
                 region_number: 0,
+                write_mode: Default::default(),
             })
             .await
             .context(InsertScriptSnafu { name })?;
@@ -253,17 +221,18 @@ impl ScriptsTable {
             name
         );
         let stmt = QueryLanguageParser::parse_sql(&sql).unwrap();
+        let query_ctx = Arc::new(QueryContext::new().with_origin(QueryOrigin::System));
 
         let plan = self
             .query_engine
             .planner()
-            .plan(stmt, QueryContext::arc())
+            .plan(stmt, query_ctx.clone())
             .await
             .unwrap();
 
         let stream = match self
             .query_engine
-            .execute(plan, QueryContext::arc())
+            .execute(plan, query_ctx)
             .await
             .context(FindScriptSnafu { name })?
         {
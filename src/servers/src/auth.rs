@@ -22,6 +22,7 @@ use snafu::{Location, OptionExt, Snafu};
 
 use crate::auth::user_provider::StaticUserProvider;
 
+pub mod authorizer;
 pub mod user_provider;
 
 #[async_trait::async_trait]
@@ -134,6 +135,21 @@ pub enum Error {
         schema: String,
         username: String,
     },
+
+    #[snafu(display(
+        "Access denied for user '{}' to {}, reason: {}",
+        username,
+        action,
+        reason
+    ))]
+    StatementDenied {
+        username: String,
+        action: String,
+        reason: String,
+    },
+
+    #[snafu(display("Statement authorizer is unavailable: {}", msg))]
+    AuthorizerUnavailable { msg: String },
 }
 
 impl ErrorExt for Error {
@@ -144,11 +160,12 @@ impl ErrorExt for Error {
             Error::InternalState { .. } => StatusCode::Unexpected,
             Error::Io { .. } => StatusCode::Internal,
             Error::AuthBackend { .. } => StatusCode::Internal,
+            Error::AuthorizerUnavailable { .. } => StatusCode::Internal,
 
             Error::UserNotFound { .. } => StatusCode::UserNotFound,
             Error::UnsupportedPasswordType { .. } => StatusCode::UnsupportedPasswordType,
             Error::UserPasswordMismatch { .. } => StatusCode::UserPasswordMismatch,
-            Error::AccessDenied { .. } => StatusCode::AccessDenied,
+            Error::AccessDenied { .. } | Error::StatementDenied { .. } => StatusCode::AccessDenied,
         }
     }
 
@@ -0,0 +1,417 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Authorization of statements by an external policy engine, like [OPA](https://www.openpolicyagent.org).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common_telemetry::warn;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, Uri};
+use moka::future::{Cache, CacheBuilder};
+use serde::{Deserialize, Serialize};
+use session::context::{QueryContextRef, UserInfo};
+use snafu::OptionExt;
+
+use crate::auth::{AuthorizerUnavailableSnafu, InvalidConfigSnafu, Result, StatementDeniedSnafu};
+
+/// The decision of a [StatementAuthorizer] on a statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Deny { reason: String },
+}
+
+#[async_trait]
+pub trait StatementAuthorizer: Send + Sync {
+    /// [`authorize`] decides whether the user is allowed to run the statement of kind `action`
+    /// (like `SELECT` or `DROP TABLE`) on the `objects`, the full names of the tables the
+    /// statement and its subqueries touch.
+    async fn authorize(
+        &self,
+        user_info: &UserInfo,
+        action: &str,
+        objects: &[String],
+        query_ctx: &QueryContextRef,
+    ) -> Result<Decision>;
+
+    /// [`check`] is [`authorize`] which turns a denial into a `StatementDenied` error.
+    async fn check(
+        &self,
+        user_info: &UserInfo,
+        action: &str,
+        objects: &[String],
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        match self
+            .authorize(user_info, action, objects, query_ctx)
+            .await?
+        {
+            Decision::Allow => Ok(()),
+            Decision::Deny { reason } => StatementDeniedSnafu {
+                username: user_info.username(),
+                action,
+                reason,
+            }
+            .fail(),
+        }
+    }
+}
+
+pub type StatementAuthorizerRef = Arc<dyn StatementAuthorizer>;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct StatementAuthorizerOptions {
+    /// Url of the decision endpoint, like `http://127.0.0.1:8181/v1/data/greptime/allow`.
+    pub url: String,
+    #[serde(with = "humantime_serde")]
+    pub timeout: Duration,
+    /// How long a decision is reused for the same user, action and objects.
+    #[serde(with = "humantime_serde")]
+    pub cache_ttl: Duration,
+    pub cache_capacity: u64,
+    /// Whether to allow the statements when the authorizer is unavailable, instead of
+    /// denying them.
+    pub fail_open: bool,
+}
+
+impl Default for StatementAuthorizerOptions {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            timeout: Duration::from_secs(1),
+            cache_ttl: Duration::from_secs(60),
+            cache_capacity: 10000,
+            fail_open: false,
+        }
+    }
+}
+
+/// Builds the webhook authorizer of the options, wrapped by the decision cache.
+pub fn statement_authorizer_from_options(
+    opts: &StatementAuthorizerOptions,
+) -> Result<StatementAuthorizerRef> {
+    let webhook = WebhookStatementAuthorizer::try_new(&opts.url, opts.timeout)?;
+    Ok(Arc::new(CachedStatementAuthorizer::new(
+        Arc::new(webhook),
+        opts.cache_ttl,
+        opts.cache_capacity,
+        opts.fail_open,
+    )))
+}
+
+type DecisionKey = (String, String, Vec<String>);
+
+/// Caches the decisions of the inner authorizer by user, action and objects, and decides on
+/// its own when the inner authorizer fails.
+pub struct CachedStatementAuthorizer {
+    inner: StatementAuthorizerRef,
+    decisions: Cache<DecisionKey, Decision>,
+    fail_open: bool,
+}
+
+impl CachedStatementAuthorizer {
+    pub fn new(
+        inner: StatementAuthorizerRef,
+        ttl: Duration,
+        capacity: u64,
+        fail_open: bool,
+    ) -> Self {
+        Self {
+            inner,
+            decisions: CacheBuilder::new(capacity).time_to_live(ttl).build(),
+            fail_open,
+        }
+    }
+}
+
+#[async_trait]
+impl StatementAuthorizer for CachedStatementAuthorizer {
+    async fn authorize(
+        &self,
+        user_info: &UserInfo,
+        action: &str,
+        objects: &[String],
+        query_ctx: &QueryContextRef,
+    ) -> Result<Decision> {
+        let key = (
+            user_info.username().to_string(),
+            action.to_string(),
+            objects.to_vec(),
+        );
+        if let Some(decision) = self.decisions.get(&key) {
+            return Ok(decision);
+        }
+
+        // Failures are not cached, so the authorizer is asked again once it's back.
+        match self
+            .inner
+            .authorize(user_info, action, objects, query_ctx)
+            .await
+        {
+            Ok(decision) => {
+                self.decisions.insert(key, decision.clone()).await;
+                Ok(decision)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to authorize {} of user {}, err: {e}",
+                    action,
+                    user_info.username()
+                );
+                if self.fail_open {
+                    Ok(Decision::Allow)
+                } else {
+                    Ok(Decision::Deny {
+                        reason: format!("authorizer is unavailable: {e}"),
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookRequest<'a> {
+    input: WebhookInput<'a>,
+}
+
+#[derive(Serialize)]
+struct WebhookInput<'a> {
+    user: &'a str,
+    action: &'a str,
+    objects: &'a [String],
+    catalog: String,
+    schema: String,
+}
+
+/// The response of OPA, whose `result` is absent if the policy is undefined for the input.
+#[derive(Deserialize)]
+struct WebhookResponse {
+    #[serde(default)]
+    result: Option<WebhookResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WebhookResult {
+    Allow(bool),
+    Decision {
+        allow: bool,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+}
+
+/// Asks an HTTP endpoint speaking the OPA data API for the decisions: posts
+/// `{"input": {"user", "action", "objects", "catalog", "schema"}}` and expects
+/// `{"result": true}` or `{"result": {"allow": false, "reason": "..."}}` back.
+pub struct WebhookStatementAuthorizer {
+    client: Client<HttpConnector>,
+    url: Uri,
+    timeout: Duration,
+}
+
+impl WebhookStatementAuthorizer {
+    pub fn try_new(url: &str, timeout: Duration) -> Result<Self> {
+        let url = url
+            .parse::<Uri>()
+            .ok()
+            .filter(|uri| uri.host().is_some())
+            .context(InvalidConfigSnafu {
+                value: url,
+                msg: "statement authorizer url must be an absolute http url",
+            })?;
+        Ok(Self {
+            client: Client::new(),
+            url,
+            timeout,
+        })
+    }
+
+    async fn request(&self, body: Vec<u8>) -> std::result::Result<Vec<u8>, String> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(self.url.clone())
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .map_err(|e| e.to_string())?;
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("unexpected status {}", response.status()));
+        }
+        hyper::body::to_bytes(response.into_body())
+            .await
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl StatementAuthorizer for WebhookStatementAuthorizer {
+    async fn authorize(
+        &self,
+        user_info: &UserInfo,
+        action: &str,
+        objects: &[String],
+        query_ctx: &QueryContextRef,
+    ) -> Result<Decision> {
+        let request = WebhookRequest {
+            input: WebhookInput {
+                user: user_info.username(),
+                action,
+                objects,
+                catalog: query_ctx.current_catalog(),
+                schema: query_ctx.current_schema(),
+            },
+        };
+        let body = serde_json::to_vec(&request).map_err(|e| e.to_string());
+
+        let response = match body {
+            Ok(body) => tokio::time::timeout(self.timeout, self.request(body))
+                .await
+                .unwrap_or_else(|_| Err(format!("timeout after {:?}", self.timeout))),
+            Err(e) => Err(e),
+        };
+        let response: WebhookResponse = response
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+            .map_err(|msg| AuthorizerUnavailableSnafu { msg }.build())?;
+
+        Ok(match response.result {
+            Some(WebhookResult::Allow(true))
+            | Some(WebhookResult::Decision { allow: true, .. }) => Decision::Allow,
+            Some(WebhookResult::Allow(false)) => Decision::Deny {
+                reason: "denied by policy".to_string(),
+            },
+            Some(WebhookResult::Decision {
+                allow: false,
+                reason,
+            }) => Decision::Deny {
+                reason: reason.unwrap_or_else(|| "denied by policy".to_string()),
+            },
+            None => Decision::Deny {
+                reason: "no policy decision".to_string(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use common_error::prelude::{ErrorExt, StatusCode};
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::auth::Error;
+
+    struct CountingAuthorizer {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl StatementAuthorizer for CountingAuthorizer {
+        async fn authorize(
+            &self,
+            _user_info: &UserInfo,
+            action: &str,
+            _objects: &[String],
+            _query_ctx: &QueryContextRef,
+        ) -> Result<Decision> {
+            let _ = self.calls.fetch_add(1, Ordering::Relaxed);
+            if action == "DROP TABLE" {
+                Ok(Decision::Deny {
+                    reason: "tables are never dropped".to_string(),
+                })
+            } else {
+                Ok(Decision::Allow)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_decisions() {
+        let inner = Arc::new(CountingAuthorizer {
+            calls: AtomicUsize::new(0),
+        });
+        let authorizer =
+            CachedStatementAuthorizer::new(inner.clone(), Duration::from_secs(60), 100, false);
+        let user = UserInfo::new("alice");
+        let ctx = QueryContext::arc();
+        let objects = vec!["greptime.public.t".to_string()];
+
+        for _ in 0..3 {
+            authorizer
+                .check(&user, "SELECT", &objects, &ctx)
+                .await
+                .unwrap();
+        }
+        assert_eq!(1, inner.calls.load(Ordering::Relaxed));
+
+        let err = authorizer
+            .check(&user, "DROP TABLE", &objects, &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::AccessDenied, err.status_code());
+        assert!(err.to_string().contains("tables are never dropped"));
+        assert_eq!(2, inner.calls.load(Ordering::Relaxed));
+
+        // Another user is authorized on its own.
+        authorizer
+            .check(&UserInfo::new("bob"), "SELECT", &objects, &ctx)
+            .await
+            .unwrap();
+        assert_eq!(3, inner.calls.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_authorizer() {
+        let user = UserInfo::new("alice");
+        let ctx = QueryContext::arc();
+        let objects = vec!["greptime.public.t".to_string()];
+        // Nothing listens on the discard port.
+        let opts = StatementAuthorizerOptions {
+            url: "http://127.0.0.1:9/allow".to_string(),
+            ..Default::default()
+        };
+
+        let authorizer = statement_authorizer_from_options(&opts).unwrap();
+        let err = authorizer
+            .check(&user, "SELECT", &objects, &ctx)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::StatementDenied { .. }));
+        assert!(err.to_string().contains("authorizer is unavailable"));
+
+        let authorizer = statement_authorizer_from_options(&StatementAuthorizerOptions {
+            fail_open: true,
+            ..opts
+        })
+        .unwrap();
+        authorizer
+            .check(&user, "SELECT", &objects, &ctx)
+            .await
+            .unwrap();
+
+        assert!(statement_authorizer_from_options(&StatementAuthorizerOptions::default()).is_err());
+    }
+}
@@ -26,9 +26,9 @@ use sha1::Sha1;
 use snafu::{ensure, OptionExt, ResultExt};
 
 use crate::auth::{
-    Error, HashedPassword, Identity, IllegalParamSnafu, InvalidConfigSnafu, IoSnafu, Password,
-    Result, Salt, UnsupportedPasswordTypeSnafu, UserNotFoundSnafu, UserPasswordMismatchSnafu,
-    UserProvider,
+    AccessDeniedSnafu, Error, HashedPassword, Identity, IllegalParamSnafu, InvalidConfigSnafu,
+    IoSnafu, Password, Result, Salt, UnsupportedPasswordTypeSnafu, UserNotFoundSnafu,
+    UserPasswordMismatchSnafu, UserProvider,
 };
 
 pub const STATIC_USER_PROVIDER: &str = "static_user_provider";
@@ -61,14 +61,14 @@ impl TryFrom<&str> for StaticUserProvider {
                             None
                         }
                     })
-                    .collect::<HashMap<String, Vec<u8>>>();
+                    .collect::<Vec<(String, Vec<u8>)>>();
 
                 ensure!(!credential.is_empty(), InvalidConfigSnafu {
                     value: content.to_string(),
                     msg: "StaticUserProviderOption file must contains at least one valid credential",
                 });
 
-                Ok(StaticUserProvider { users: credential, })
+                StaticUserProvider::from_credentials(credential)
             }
             "cmd" => content
                 .split(',')
@@ -79,8 +79,8 @@ impl TryFrom<&str> for StaticUserProvider {
                     })?;
                     Ok((k.to_string(), v.as_bytes().to_vec()))
                 })
-                .collect::<Result<HashMap<String, Vec<u8>>>>()
-                .map(|users| StaticUserProvider { users }),
+                .collect::<Result<Vec<(String, Vec<u8>)>>>()
+                .and_then(StaticUserProvider::from_credentials),
             _ => InvalidConfigSnafu {
                 value: mode.to_string(),
                 msg: "StaticUserProviderOption must be in format `file:<path>` or `cmd:<values>`",
@@ -90,8 +90,71 @@ impl TryFrom<&str> for StaticUserProvider {
     }
 }
 
+/// Default schema of a user.
+struct DefaultSchema {
+    catalog: String,
+    schema: String,
+    /// Whether the user is only allowed to use this schema.
+    pinned: bool,
+}
+
 pub struct StaticUserProvider {
     users: HashMap<String, Vec<u8>>,
+    default_schemas: HashMap<String, DefaultSchema>,
+}
+
+impl StaticUserProvider {
+    /// Creates the provider from `(user, password)` pairs. A user is either a username or
+    /// `<username>:<database>[:strict]`, which makes the sessions of the user start in the
+    /// database, and pins the user to it if `strict` is present.
+    fn from_credentials(credentials: Vec<(String, Vec<u8>)>) -> Result<Self> {
+        let mut users = HashMap::with_capacity(credentials.len());
+        let mut default_schemas = HashMap::new();
+        for (user, password) in credentials {
+            let mut parts = user.split(':');
+            // `split` always yields at least one item.
+            let username = parts.next().unwrap().to_string();
+            match (parts.next(), parts.next(), parts.next()) {
+                (None, _, _) => {}
+                (Some(database), pinned @ (None | Some("strict")), None)
+                    if !database.is_empty() =>
+                {
+                    let (catalog, schema) =
+                        crate::parse_catalog_and_schema_from_client_database_name(database);
+                    let default_schema = DefaultSchema {
+                        catalog: catalog.to_string(),
+                        schema: schema.to_string(),
+                        pinned: pinned.is_some(),
+                    };
+                    let _ = default_schemas.insert(username.clone(), default_schema);
+                }
+                _ => {
+                    return InvalidConfigSnafu {
+                        value: user,
+                        msg: "StaticUserProviderOption user must be in format `<username>[:<database>[:strict]]`",
+                    }
+                    .fail();
+                }
+            }
+            let _ = users.insert(username, password);
+        }
+        Ok(StaticUserProvider {
+            users,
+            default_schemas,
+        })
+    }
+
+    fn user_info(&self, username: &str) -> UserInfo {
+        let user_info = UserInfo::new(username);
+        match self.default_schemas.get(username) {
+            Some(default_schema) => user_info.with_default_schema(
+                Some(default_schema.catalog.clone()),
+                &default_schema.schema,
+                default_schema.pinned,
+            ),
+            None => user_info,
+        }
+    }
 }
 
 #[async_trait]
@@ -126,7 +189,7 @@ impl UserProvider for StaticUserProvider {
                             }
                         );
                         return if save_pwd == pwd.as_bytes() {
-                            Ok(UserInfo::new(username))
+                            Ok(self.user_info(username))
                         } else {
                             UserPasswordMismatchSnafu {
                                 username: username.to_string(),
@@ -142,7 +205,7 @@ impl UserProvider for StaticUserProvider {
                             }
                         );
                         auth_mysql(auth_data, salt, username, save_pwd)
-                            .map(|_| UserInfo::new(username))
+                            .map(|_| self.user_info(username))
                     }
                     Password::PgMD5(_, _) => UnsupportedPasswordTypeSnafu {
                         password_type: "pg_md5",
@@ -153,8 +216,16 @@ impl UserProvider for StaticUserProvider {
         }
     }
 
-    async fn authorize(&self, _catalog: &str, _schema: &str, _user_info: &UserInfo) -> Result<()> {
-        // default allow all
+    async fn authorize(&self, catalog: &str, schema: &str, user_info: &UserInfo) -> Result<()> {
+        // Users not pinned to their default schemas are allowed to access all schemas.
+        ensure!(
+            user_info.can_use_schema(catalog, schema),
+            AccessDeniedSnafu {
+                catalog,
+                schema,
+                username: user_info.username(),
+            }
+        );
         Ok(())
     }
 }
@@ -255,6 +326,59 @@ pub mod test {
         assert!(re.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_default_schema() {
+        let provider = StaticUserProvider::try_from(
+            "cmd:root=123456,tenant:tenant_a=654321,pinned:catalog-tenant_b:strict=654321",
+        )
+        .unwrap();
+
+        let user_info = provider
+            .authenticate(
+                Identity::UserId("root", None),
+                Password::PlainText("123456"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(None, user_info.default_schema());
+
+        let user_info = provider
+            .authenticate(
+                Identity::UserId("tenant", None),
+                Password::PlainText("654321"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(Some("greptime"), user_info.default_catalog());
+        assert_eq!(Some("tenant_a"), user_info.default_schema());
+        assert!(!user_info.is_schema_pinned());
+        assert!(provider
+            .authorize("greptime", "tenant_b", &user_info)
+            .await
+            .is_ok());
+
+        let user_info = provider
+            .authenticate(
+                Identity::UserId("pinned", None),
+                Password::PlainText("654321"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(Some("catalog"), user_info.default_catalog());
+        assert_eq!(Some("tenant_b"), user_info.default_schema());
+        assert!(user_info.is_schema_pinned());
+        assert!(provider
+            .authorize("catalog", "tenant_b", &user_info)
+            .await
+            .is_ok());
+        assert!(provider
+            .authorize("catalog", "tenant_a", &user_info)
+            .await
+            .is_err());
+
+        assert!(StaticUserProvider::try_from("cmd:tenant:tenant_a:loose=654321").is_err());
+    }
+
     #[tokio::test]
     async fn test_inline_provider() {
         let provider = StaticUserProvider::try_from("cmd:root=123456,admin=654321").unwrap();
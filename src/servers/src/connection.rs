@@ -0,0 +1,301 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Management of the connections of the MySQL and PostgreSQL servers: the TCP keepalive of
+//! the sockets, closing the idle sessions and recycling the old connections.
+//!
+//! A connection is closed by the server only when it's not running any statement, after its
+//! session has been idle for the idle timeout of the session, or the connection is older than
+//! the max connection age. The client is told why in the error message of its protocol before
+//! the connection is closed.
+
+use std::io::Write;
+use std::net::Shutdown;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common_telemetry::debug;
+use serde::{Deserialize, Serialize};
+use session::context::QueryContext;
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// Options of the connections of the MySQL and PostgreSQL servers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionOptions {
+    /// Idle time of a connection before the TCP keepalive probes are sent, which keeps the
+    /// connection alive through load balancers and detects dead clients. Disabled if absent.
+    #[serde(with = "humantime_serde")]
+    pub tcp_keepalive: Option<Duration>,
+    /// Interval between the TCP keepalive probes, the default of the system if absent.
+    #[serde(with = "humantime_serde")]
+    pub tcp_keepalive_interval: Option<Duration>,
+    /// Sessions without any statement for this long are closed, like the `wait_timeout` of
+    /// MySQL, which sessions can override. Never closed if absent.
+    #[serde(with = "humantime_serde")]
+    pub idle_timeout: Option<Duration>,
+    /// Connections open for this long are closed once they finish their running statements,
+    /// so the clients reconnect, e.g. to other servers behind a load balancer. Never closed
+    /// if absent.
+    #[serde(with = "humantime_serde")]
+    pub max_connection_age: Option<Duration>,
+}
+
+impl ConnectionOptions {
+    /// Sets the TCP keepalive of the accepted `stream`, if enabled.
+    pub(crate) fn set_keepalive(&self, stream: &TcpStream) -> std::io::Result<()> {
+        let Some(time) = self.tcp_keepalive else { return Ok(()) };
+        let mut keepalive = TcpKeepalive::new().with_time(time);
+        if let Some(interval) = self.tcp_keepalive_interval {
+            keepalive = keepalive.with_interval(interval);
+        }
+        SockRef::from(stream).set_tcp_keepalive(&keepalive)
+    }
+}
+
+/// Why a connection is closed by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CloseReason {
+    /// The session has no statement for its idle timeout.
+    IdleTimeout,
+    /// The connection is older than the max connection age.
+    MaxAge,
+}
+
+/// Statements running in a connection, telling when the connection should be closed.
+#[derive(Debug)]
+pub(crate) struct ConnectionActivity {
+    connected_at: Instant,
+    state: Mutex<ActivityState>,
+    /// Notified when a statement finishes.
+    finished: Notify,
+}
+
+#[derive(Debug)]
+struct ActivityState {
+    running: usize,
+    /// When the last statement finished, or the connection is accepted.
+    last_active: Instant,
+}
+
+impl ConnectionActivity {
+    pub(crate) fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            connected_at: now,
+            state: Mutex::new(ActivityState {
+                running: 0,
+                last_active: now,
+            }),
+            finished: Notify::new(),
+        }
+    }
+
+    /// Marks a statement running until the returned guard is dropped.
+    pub(crate) fn start_statement(self: &Arc<Self>) -> StatementGuard {
+        self.state.lock().unwrap().running += 1;
+        StatementGuard {
+            activity: self.clone(),
+        }
+    }
+
+    /// Waits until the connection should be closed, when no statement is running and either
+    /// the session of `query_ctx` has been idle for its idle timeout, or the connection is
+    /// older than `max_age`. Never returns if the connection is never closed.
+    pub(crate) async fn wait_close(
+        &self,
+        query_ctx: &QueryContext,
+        max_age: Option<Duration>,
+    ) -> CloseReason {
+        loop {
+            // Created before checking the state, so no finished statement is missed.
+            let finished = self.finished.notified();
+            let deadline = {
+                let state = self.state.lock().unwrap();
+                if state.running > 0 {
+                    None
+                } else {
+                    let now = Instant::now();
+                    let max_age_deadline = max_age.map(|age| self.connected_at + age);
+                    if max_age_deadline.map_or(false, |deadline| deadline <= now) {
+                        return CloseReason::MaxAge;
+                    }
+                    let idle_deadline = query_ctx
+                        .idle_timeout()
+                        .map(|timeout| state.last_active + timeout);
+                    if idle_deadline.map_or(false, |deadline| deadline <= now) {
+                        return CloseReason::IdleTimeout;
+                    }
+                    max_age_deadline.into_iter().chain(idle_deadline).min()
+                }
+            };
+
+            match deadline {
+                Some(deadline) => {
+                    tokio::select! {
+                        _ = finished => {}
+                        _ = tokio::time::sleep_until(deadline) => {}
+                    }
+                }
+                None => finished.await,
+            }
+        }
+    }
+}
+
+/// Guard of a running statement of a connection.
+pub(crate) struct StatementGuard {
+    activity: Arc<ConnectionActivity>,
+}
+
+impl Drop for StatementGuard {
+    fn drop(&mut self) {
+        {
+            let mut state = self.activity.state.lock().unwrap();
+            state.running -= 1;
+            state.last_active = Instant::now();
+        }
+        self.activity.finished.notify_waiters();
+    }
+}
+
+/// Handle of the socket of a connection, to write the last message to the client and close the
+/// connection after the protocol handler owning the stream is dropped.
+pub(crate) struct ClosingHandle {
+    socket: std::net::TcpStream,
+}
+
+impl ClosingHandle {
+    /// Creates the handle of the socket of `stream`, returning the stream to pass to the
+    /// protocol handler.
+    pub(crate) fn new(stream: TcpStream) -> std::io::Result<(TcpStream, ClosingHandle)> {
+        let stream = stream.into_std()?;
+        let socket = stream.try_clone()?;
+        Ok((TcpStream::from_std(stream)?, ClosingHandle { socket }))
+    }
+
+    /// Writes the `message`, if any, to the client and closes the connection.
+    pub(crate) fn close(mut self, message: Option<&[u8]>) {
+        if let Some(message) = message {
+            // The socket is non-blocking, the message is dropped if the client doesn't read.
+            if let Err(e) = self.socket.write_all(message) {
+                debug!("Failed to write the closing message to the client: {e}");
+            }
+        }
+        let _ = self.socket.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_keepalive() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+
+        ConnectionOptions::default().set_keepalive(&stream).unwrap();
+        assert!(!SockRef::from(&stream).keepalive().unwrap());
+
+        let options = ConnectionOptions {
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            tcp_keepalive_interval: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        options.set_keepalive(&stream).unwrap();
+        let socket = SockRef::from(&stream);
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(Duration::from_secs(60), socket.keepalive_time().unwrap());
+        assert_eq!(
+            Duration::from_secs(10),
+            socket.keepalive_interval().unwrap()
+        );
+        drop(client);
+    }
+
+    #[tokio::test]
+    async fn test_close_idle_connection() {
+        let activity = Arc::new(ConnectionActivity::new());
+        let query_ctx = QueryContext::new();
+        let start = Instant::now();
+
+        // Never closed without timeouts.
+        let wait = activity.wait_close(&query_ctx, None);
+        assert!(tokio::time::timeout(Duration::from_millis(100), wait)
+            .await
+            .is_err());
+
+        query_ctx.set_idle_timeout(Some(Duration::from_millis(200)));
+        let reason = activity.wait_close(&query_ctx, None).await;
+        assert_eq!(CloseReason::IdleTimeout, reason);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+
+        // Idle from the last statement.
+        let statement = activity.start_statement();
+        drop(statement);
+        let start = Instant::now();
+        let reason = activity.wait_close(&query_ctx, None).await;
+        assert_eq!(CloseReason::IdleTimeout, reason);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_running_statement_not_closed() {
+        let activity = Arc::new(ConnectionActivity::new());
+        let query_ctx = QueryContext::new();
+        query_ctx.set_idle_timeout(Some(Duration::from_millis(100)));
+
+        let statement = activity.start_statement();
+        let wait = activity.wait_close(&query_ctx, Some(Duration::from_millis(100)));
+        tokio::pin!(wait);
+        assert!(tokio::time::timeout(Duration::from_millis(300), &mut wait)
+            .await
+            .is_err());
+
+        // The connection is older than the max age once the statement finishes.
+        drop(statement);
+        let reason = tokio::time::timeout(Duration::from_millis(100), wait)
+            .await
+            .unwrap();
+        assert_eq!(CloseReason::MaxAge, reason);
+    }
+
+    #[tokio::test]
+    async fn test_closing_handle() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+
+        let (stream, handle) = ClosingHandle::new(stream).unwrap();
+        drop(stream);
+        handle.close(Some(b"bye"));
+
+        let mut buf = Vec::new();
+        let _ = tokio::io::AsyncReadExt::read_to_end(&mut client, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(b"bye", buf.as_slice());
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::string::FromUtf8Error;
 
@@ -22,12 +23,16 @@ use axum::{http, Json};
 use base64::DecodeError;
 use catalog;
 use common_error::prelude::*;
+use common_grpc_expr::validation::{
+    find_row_errors, RowErrors, ERROR_INFO_DOMAIN, INVALID_ROWS_REASON, ROW_ERRORS_KEY,
+};
 use query::parser::PromQuery;
 use serde_json::json;
 use snafu::Location;
 use tonic::codegen::http::{HeaderMap, HeaderValue};
 use tonic::metadata::MetadataMap;
 use tonic::Code;
+use tonic_types::{ErrorDetails, StatusExt};
 
 use crate::auth;
 
@@ -74,6 +79,24 @@ pub enum Error {
         source: BoxedError,
     },
 
+    #[snafu(display("Failed to collect storage usage, source: {}", source))]
+    CollectStorageUsage {
+        #[snafu(backtrace)]
+        source: BoxedError,
+    },
+
+    #[snafu(display("Failed to collect orphans, source: {}", source))]
+    GcOrphans {
+        #[snafu(backtrace)]
+        source: BoxedError,
+    },
+
+    #[snafu(display("Failed to handle region request, source: {}", source))]
+    HandleRegionRequest {
+        #[snafu(backtrace)]
+        source: BoxedError,
+    },
+
     #[snafu(display("{source}"))]
     ExecuteGrpcQuery {
         #[snafu(backtrace)]
@@ -128,8 +151,14 @@ pub enum Error {
         source: influxdb_line_protocol::Error,
     },
 
-    #[snafu(display("Failed to write InfluxDB line protocol, source: {}", source))]
-    InfluxdbLinesWrite {
+    #[snafu(display("{}", source))]
+    InvalidRows {
+        source: RowErrors,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to write JSON documents, source: {}", source))]
+    JsonLinesWrite {
         #[snafu(backtrace)]
         source: common_grpc::error::Error,
     },
@@ -190,6 +219,18 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid Flight action, source: {}", source))]
+    InvalidFlightAction {
+        source: api::DecodeError,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to convert row inserts, source: {}", source))]
+    ConvertRowInserts {
+        #[snafu(backtrace)]
+        source: common_grpc_expr::error::Error,
+    },
+
     #[snafu(display("Failed to start frontend service, source: {}", source))]
     StartFrontend {
         #[snafu(backtrace)]
@@ -263,6 +304,9 @@ pub enum Error {
     #[snafu(display("Invalid flush argument: {}", err_msg))]
     InvalidFlushArgument { err_msg: String },
 
+    #[snafu(display("Job not found: {id}"))]
+    JobNotFound { id: u64, location: Location },
+
     #[snafu(display("Failed to build gRPC reflection service, source: {}", source))]
     GrpcReflectionService {
         source: tonic_reflection::server::Error,
@@ -306,6 +350,9 @@ impl ErrorExt for Error {
             InsertScript { source, .. }
             | ExecuteScript { source, .. }
             | ExecuteQuery { source, .. }
+            | CollectStorageUsage { source, .. }
+            | GcOrphans { source, .. }
+            | HandleRegionRequest { source, .. }
             | ExecuteGrpcQuery { source, .. }
             | ExecuteStatement { source, .. }
             | CheckDatabaseValidity { source, .. }
@@ -322,12 +369,13 @@ impl ErrorExt for Error {
             | DecompressPromRemoteRequest { .. }
             | InvalidPromRemoteRequest { .. }
             | InvalidFlightTicket { .. }
+            | InvalidFlightAction { .. }
             | InvalidPrepareStatement { .. }
-            | TimePrecision { .. } => StatusCode::InvalidArguments,
+            | TimePrecision { .. }
+            | InvalidRows { .. } => StatusCode::InvalidArguments,
+            ConvertRowInserts { source } => source.status_code(),
 
-            InfluxdbLinesWrite { source, .. } | ConvertFlightMessage { source } => {
-                source.status_code()
-            }
+            JsonLinesWrite { source, .. } | ConvertFlightMessage { source } => source.status_code(),
 
             Hyper { .. } => StatusCode::Unknown,
             TlsRequired { .. } => StatusCode::Unknown,
@@ -345,7 +393,7 @@ impl ErrorExt for Error {
             DatabaseNotFound { .. } => StatusCode::DatabaseNotFound,
             #[cfg(feature = "mem-prof")]
             DumpProfileData { source, .. } => source.status_code(),
-            InvalidFlushArgument { .. } => StatusCode::InvalidArguments,
+            InvalidFlushArgument { .. } | JobNotFound { .. } => StatusCode::InvalidArguments,
 
             ParsePromQL { source, .. } => source.status_code(),
         }
@@ -371,7 +419,25 @@ impl From<Error> for tonic::Status {
         }
 
         let metadata = MetadataMap::from_headers(headers);
-        tonic::Status::with_metadata(Code::Internal, err.to_string(), metadata)
+        // The row errors are put in the standard error details, for the clients to tell which
+        // rows are rejected.
+        let row_errors = find_row_errors(&err).and_then(|e| serde_json::to_string(e).ok());
+        match row_errors {
+            Some(row_errors) => {
+                let details = ErrorDetails::with_error_info(
+                    INVALID_ROWS_REASON,
+                    ERROR_INFO_DOMAIN,
+                    HashMap::from([(ROW_ERRORS_KEY.to_string(), row_errors)]),
+                );
+                tonic::Status::with_error_details_and_metadata(
+                    Code::Internal,
+                    err.to_string(),
+                    details,
+                    metadata,
+                )
+            }
+            None => tonic::Status::with_metadata(Code::Internal, err.to_string(), metadata),
+        }
     }
 }
 
@@ -387,11 +453,26 @@ impl From<auth::Error> for Error {
     }
 }
 
+/// Returns the details of `err` to be put in responses as is, which are the row errors of the
+/// insertion rejected by `err`, if any.
+pub fn error_detail(err: &Error) -> Option<serde_json::Value> {
+    let row_errors = find_row_errors(err)?;
+    serde_json::to_value(row_errors).ok()
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
+        if let Some(detail) = error_detail(&self) {
+            let body = Json(json!({
+                "error": self.to_string(),
+                "detail": detail,
+            }));
+            return (HttpStatusCode::BAD_REQUEST, body).into_response();
+        }
+
         let (status, error_message) = match self {
             Error::InfluxdbLineProtocol { .. }
-            | Error::InfluxdbLinesWrite { .. }
+            | Error::JsonLinesWrite { .. }
             | Error::InvalidOpentsdbLine { .. }
             | Error::InvalidOpentsdbJsonRequest { .. }
             | Error::DecodePromRemoteRequest { .. }
@@ -399,6 +480,7 @@ impl IntoResponse for Error {
             | Error::InvalidPromRemoteRequest { .. }
             | Error::InvalidQuery { .. }
             | Error::TimePrecision { .. } => (HttpStatusCode::BAD_REQUEST, self.to_string()),
+            Error::JobNotFound { .. } => (HttpStatusCode::NOT_FOUND, self.to_string()),
             _ => (HttpStatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
         let body = Json(json!({
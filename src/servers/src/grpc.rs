@@ -46,6 +46,7 @@ use crate::grpc::flight::FlightHandler;
 use crate::grpc::handler::GreptimeRequestHandler;
 use crate::prom::PromHandlerRef;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
+use crate::query_handler::{DdlBatchHandlerRef, RegionHandlerRef};
 use crate::server::Server;
 
 type TonicResult<T> = std::result::Result<T, Status>;
@@ -55,6 +56,10 @@ pub struct GrpcServer {
     request_handler: Arc<GreptimeRequestHandler>,
     /// Handler for Prometheus-compatible PromQL queries. Only present for frontend server.
     promql_handler: Option<PromHandlerRef>,
+    /// Handler to create tables in batch. Only present for frontend server.
+    ddl_batch_handler: Option<DdlBatchHandlerRef>,
+    /// Handler of the requests to regions. Only present for datanode server.
+    region_handler: Option<RegionHandlerRef>,
 }
 
 impl GrpcServer {
@@ -73,11 +78,27 @@ impl GrpcServer {
             shutdown_tx: Mutex::new(None),
             request_handler,
             promql_handler,
+            ddl_batch_handler: None,
+            region_handler: None,
         }
     }
 
+    pub fn with_ddl_batch_handler(mut self, handler: DdlBatchHandlerRef) -> Self {
+        self.ddl_batch_handler = Some(handler);
+        self
+    }
+
+    pub fn with_region_handler(mut self, handler: RegionHandlerRef) -> Self {
+        self.region_handler = Some(handler);
+        self
+    }
+
     pub fn create_flight_service(&self) -> FlightServiceServer<impl FlightService> {
-        FlightServiceServer::new(FlightHandler::new(self.request_handler.clone()))
+        FlightServiceServer::new(
+            FlightHandler::new(self.request_handler.clone())
+                .with_ddl_batch_handler(self.ddl_batch_handler.clone())
+                .with_region_handler(self.region_handler.clone()),
+        )
     }
 
     pub fn create_database_service(&self) -> GreptimeDatabaseServer<impl GreptimeDatabase> {
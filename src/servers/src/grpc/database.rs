@@ -22,7 +22,11 @@ use common_query::Output;
 use futures::StreamExt;
 use tonic::{Request, Response, Status, Streaming};
 
-use crate::grpc::handler::GreptimeRequestHandler;
+use crate::grpc::handler::{
+    as_of_from_metadata, commit_token_from_metadata, dry_run_from_metadata,
+    set_commit_token_metadata, set_dry_run_report_metadata, validation_mode_from_metadata,
+    write_mode_from_metadata, GreptimeRequestHandler,
+};
 use crate::grpc::TonicResult;
 
 pub(crate) struct DatabaseService {
@@ -41,18 +45,47 @@ impl GreptimeDatabase for DatabaseService {
         &self,
         request: Request<GreptimeRequest>,
     ) -> TonicResult<Response<GreptimeResponse>> {
+        let commit_token = commit_token_from_metadata(request.metadata())?;
+        let dry_run = dry_run_from_metadata(request.metadata())?;
+        let write_mode = write_mode_from_metadata(request.metadata())?;
+        let validation_mode = validation_mode_from_metadata(request.metadata())?;
+        let as_of = as_of_from_metadata(request.metadata())?;
         let request = request.into_inner();
-        let output = self.handler.handle_request(request).await?;
-        let response = match output {
-            Output::AffectedRows(rows) => GreptimeResponse {
-                header: None,
-                response: Some(RawResponse::AffectedRows(AffectedRows { value: rows as _ })),
-            },
+        let (output, commit_token) = self
+            .handler
+            .handle_request(
+                request,
+                commit_token,
+                dry_run,
+                write_mode,
+                validation_mode,
+                as_of,
+            )
+            .await?;
+        let mut report = None;
+        let affected_rows = match output {
+            Output::AffectedRows(rows) => rows,
+            // The dry runs write nothing, their report is returned in the metadata.
+            Output::RecordBatches(batches) if dry_run => {
+                report = Some(batches.take());
+                0
+            }
             Output::Stream(_) | Output::RecordBatches(_) => {
                 return Err(Status::unimplemented("GreptimeDatabase::Handle for query"));
             }
         };
-        Ok(Response::new(response))
+        let response = GreptimeResponse {
+            header: None,
+            response: Some(RawResponse::AffectedRows(AffectedRows {
+                value: affected_rows as _,
+            })),
+        };
+        let mut response = Response::new(response);
+        set_commit_token_metadata(response.metadata_mut(), &commit_token);
+        if let Some(report) = report {
+            set_dry_run_report_metadata(response.metadata_mut(), report)?;
+        }
+        Ok(response)
     }
 
     async fn handle_requests(
@@ -60,13 +93,31 @@ impl GreptimeDatabase for DatabaseService {
         request: Request<Streaming<GreptimeRequest>>,
     ) -> Result<Response<GreptimeResponse>, Status> {
         let mut affected_rows = 0;
+        let mut commit_token = commit_token_from_metadata(request.metadata())?;
+        let dry_run = dry_run_from_metadata(request.metadata())?;
+        let write_mode = write_mode_from_metadata(request.metadata())?;
+        let validation_mode = validation_mode_from_metadata(request.metadata())?;
+        let as_of = as_of_from_metadata(request.metadata())?;
+        let mut report = Vec::new();
 
         let mut stream = request.into_inner();
         while let Some(request) = stream.next().await {
             let request = request?;
-            let output = self.handler.handle_request(request).await?;
+            let (output, token) = self
+                .handler
+                .handle_request(
+                    request,
+                    commit_token,
+                    dry_run,
+                    write_mode,
+                    validation_mode,
+                    as_of,
+                )
+                .await?;
+            commit_token = token;
             match output {
                 Output::AffectedRows(rows) => affected_rows += rows,
+                Output::RecordBatches(batches) if dry_run => report.extend(batches.take()),
                 Output::Stream(_) | Output::RecordBatches(_) => {
                     return Err(Status::unimplemented(
                         "GreptimeDatabase::HandleRequests for query",
@@ -81,6 +132,11 @@ impl GreptimeDatabase for DatabaseService {
                 value: affected_rows as u32,
             })),
         };
-        Ok(Response::new(response))
+        let mut response = Response::new(response);
+        set_commit_token_metadata(response.metadata_mut(), &commit_token);
+        if dry_run {
+            set_dry_run_report_metadata(response.metadata_mut(), report)?;
+        }
+        Ok(response)
     }
 }
@@ -17,6 +17,7 @@ mod stream;
 use std::pin::Pin;
 use std::sync::Arc;
 
+use api::v1::row::RowInsertRequests;
 use api::v1::GreptimeRequest;
 use arrow_flight::flight_service_server::FlightService;
 use arrow_flight::{
@@ -24,7 +25,9 @@ use arrow_flight::{
     HandshakeRequest, HandshakeResponse, PutResult, SchemaResult, Ticket,
 };
 use async_trait::async_trait;
-use common_grpc::flight::{FlightEncoder, FlightMessage};
+use common_grpc::flight::{
+    FlightEncoder, FlightMessage, CREATE_TABLES_ACTION, REGION_ACTION, ROW_INSERTS_ACTION,
+};
 use common_query::Output;
 use futures::Stream;
 use prost::Message;
@@ -33,18 +36,86 @@ use tonic::{Request, Response, Status, Streaming};
 
 use crate::error;
 use crate::grpc::flight::stream::FlightRecordBatchStream;
-use crate::grpc::handler::GreptimeRequestHandler;
+use crate::grpc::handler::{
+    as_of_from_metadata, commit_token_from_metadata, dry_run_from_metadata,
+    set_commit_token_metadata, validation_mode_from_metadata, write_mode_from_metadata,
+    GreptimeRequestHandler,
+};
 use crate::grpc::TonicResult;
+use crate::query_handler::{DdlBatchHandlerRef, RegionHandlerRef};
 
 type TonicStream<T> = Pin<Box<dyn Stream<Item = TonicResult<T>> + Send + Sync + 'static>>;
 
 pub struct FlightHandler {
     handler: Arc<GreptimeRequestHandler>,
+    /// Handler of the [CREATE_TABLES_ACTION], which is not supported if absent.
+    ddl_batch_handler: Option<DdlBatchHandlerRef>,
+    /// Handler of the [REGION_ACTION], which is not supported if absent.
+    region_handler: Option<RegionHandlerRef>,
 }
 
 impl FlightHandler {
     pub fn new(handler: Arc<GreptimeRequestHandler>) -> Self {
-        Self { handler }
+        Self {
+            handler,
+            ddl_batch_handler: None,
+            region_handler: None,
+        }
+    }
+
+    pub fn with_ddl_batch_handler(mut self, handler: Option<DdlBatchHandlerRef>) -> Self {
+        self.ddl_batch_handler = handler;
+        self
+    }
+
+    pub fn with_region_handler(mut self, handler: Option<RegionHandlerRef>) -> Self {
+        self.region_handler = handler;
+        self
+    }
+}
+
+impl FlightHandler {
+    /// Handles the [ROW_INSERTS_ACTION], whose only result is the number of rows inserted.
+    async fn do_row_inserts(
+        &self,
+        request: Request<Action>,
+    ) -> TonicResult<Response<TonicStream<arrow_flight::Result>>> {
+        let commit_token = commit_token_from_metadata(request.metadata())?;
+        let write_mode = write_mode_from_metadata(request.metadata())?;
+        let validation_mode = validation_mode_from_metadata(request.metadata())?;
+        let action = request.into_inner();
+        let request = RowInsertRequests::decode(action.body.as_ref())
+            .context(error::InvalidFlightActionSnafu)?;
+
+        let (affected_rows, commit_token) = self
+            .handler
+            .handle_row_inserts(request, commit_token, write_mode, validation_mode)
+            .await?;
+
+        let result = serde_json::to_vec(&affected_rows)
+            .map(|body| arrow_flight::Result { body: body.into() })
+            .map_err(|e| Status::internal(e.to_string()));
+        let mut response = Response::new(
+            Box::pin(futures::stream::iter([result])) as TonicStream<arrow_flight::Result>
+        );
+        set_commit_token_metadata(response.metadata_mut(), &commit_token);
+        Ok(response)
+    }
+
+    /// Handles the [REGION_ACTION], whose only result is the watermark of the region.
+    async fn do_region_request(
+        &self,
+        region_handler: RegionHandlerRef,
+        action: Action,
+    ) -> TonicResult<Response<TonicStream<arrow_flight::Result>>> {
+        let request = serde_json::from_slice(&action.body)
+            .map_err(|e| Status::invalid_argument(format!("Invalid region request: {e}")))?;
+        let watermark = region_handler.handle_region_request(request).await?;
+
+        let result = serde_json::to_vec(&watermark)
+            .map(|body| arrow_flight::Result { body: body.into() })
+            .map_err(|e| Status::internal(e.to_string()));
+        Ok(Response::new(Box::pin(futures::stream::iter([result]))))
     }
 }
 
@@ -85,14 +156,31 @@ impl FlightService for FlightHandler {
     type DoGetStream = TonicStream<FlightData>;
 
     async fn do_get(&self, request: Request<Ticket>) -> TonicResult<Response<Self::DoGetStream>> {
+        let commit_token = commit_token_from_metadata(request.metadata())?;
+        let dry_run = dry_run_from_metadata(request.metadata())?;
+        let write_mode = write_mode_from_metadata(request.metadata())?;
+        let validation_mode = validation_mode_from_metadata(request.metadata())?;
+        let as_of = as_of_from_metadata(request.metadata())?;
         let ticket = request.into_inner().ticket;
         let request =
             GreptimeRequest::decode(ticket.as_ref()).context(error::InvalidFlightTicketSnafu)?;
 
-        let output = self.handler.handle_request(request).await?;
+        let (output, commit_token) = self
+            .handler
+            .handle_request(
+                request,
+                commit_token,
+                dry_run,
+                write_mode,
+                validation_mode,
+                as_of,
+            )
+            .await?;
 
         let stream = to_flight_data_stream(output);
-        Ok(Response::new(stream))
+        let mut response = Response::new(stream);
+        set_commit_token_metadata(response.metadata_mut(), &commit_token);
+        Ok(response)
     }
 
     type DoPutStream = TonicStream<PutResult>;
@@ -115,8 +203,44 @@ impl FlightService for FlightHandler {
 
     type DoActionStream = TonicStream<arrow_flight::Result>;
 
-    async fn do_action(&self, _: Request<Action>) -> TonicResult<Response<Self::DoActionStream>> {
-        Err(Status::unimplemented("Not yet implemented"))
+    async fn do_action(
+        &self,
+        request: Request<Action>,
+    ) -> TonicResult<Response<Self::DoActionStream>> {
+        if request.get_ref().r#type == ROW_INSERTS_ACTION {
+            return self.do_row_inserts(request).await;
+        }
+
+        let action = request.into_inner();
+        if let Some(handler) = &self.region_handler {
+            if action.r#type == REGION_ACTION {
+                return self.do_region_request(handler.clone(), action).await;
+            }
+        }
+        let ddl_batch_handler = match &self.ddl_batch_handler {
+            Some(handler) if action.r#type == CREATE_TABLES_ACTION => handler.clone(),
+            _ => {
+                return Err(Status::unimplemented(format!(
+                    "Unsupported action: {}",
+                    action.r#type
+                )))
+            }
+        };
+        let request = GreptimeRequest::decode(action.body.as_ref())
+            .context(error::InvalidFlightActionSnafu)?;
+
+        let results = self
+            .handler
+            .handle_create_tables(request, ddl_batch_handler)
+            .await?
+            .iter()
+            .map(|creation| {
+                serde_json::to_vec(creation)
+                    .map(|body| arrow_flight::Result { body: body.into() })
+                    .map_err(|e| Status::internal(e.to_string()))
+            })
+            .collect::<Vec<_>>();
+        Ok(Response::new(Box::pin(futures::stream::iter(results))))
     }
 
     type ListActionsStream = TonicStream<ActionType>;
@@ -125,7 +249,25 @@ impl FlightService for FlightHandler {
         &self,
         _: Request<Empty>,
     ) -> TonicResult<Response<Self::ListActionsStream>> {
-        Err(Status::unimplemented("Not yet implemented"))
+        let mut actions = vec![Ok(ActionType {
+            r#type: ROW_INSERTS_ACTION.to_string(),
+            description: "Inserts rows the same way as the inserts of a GreptimeRequest"
+                .to_string(),
+        })];
+        if self.ddl_batch_handler.is_some() {
+            actions.push(Ok(ActionType {
+                r#type: CREATE_TABLES_ACTION.to_string(),
+                description: "Creates the tables of CREATE TABLE statements all or nothing"
+                    .to_string(),
+            }));
+        }
+        if self.region_handler.is_some() {
+            actions.push(Ok(ActionType {
+                r#type: REGION_ACTION.to_string(),
+                description: "Operates a region of a table for changing its partitions".to_string(),
+            }));
+        }
+        Ok(Response::new(Box::pin(futures::stream::iter(actions))))
     }
 }
 
@@ -12,21 +12,45 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::ParseIntError;
+use std::str::ParseBoolError;
 use std::sync::Arc;
 
 use api::v1::auth_header::AuthScheme;
-use api::v1::{Basic, GreptimeRequest, RequestHeader};
+use api::v1::greptime_request::Request as GreptimeRequestKind;
+use api::v1::query_request::Query;
+use api::v1::row::RowInsertRequests;
+use api::v1::{Basic, GreptimeRequest, QueryRequest, RequestHeader};
+use common_base::commit_token::{CommitToken, COMMIT_TOKEN_HEADER};
+use common_base::validation_mode::{ValidationMode, VALIDATION_MODE_HEADER};
+use common_base::write_mode::{WriteMode, WRITE_MODE_HEADER};
+use common_base::AS_OF_HEADER;
+use common_grpc_expr::rows_to_insert_request;
 use common_query::Output;
+use common_recordbatch::RecordBatch;
 use common_runtime::Runtime;
 use session::context::{QueryContext, QueryContextRef};
-use snafu::OptionExt;
+use snafu::{OptionExt, ResultExt};
+use tokio::task::JoinError;
+use tonic::metadata::{MetadataMap, MetadataValue};
 use tonic::Status;
 
 use crate::auth::{Identity, Password, UserProviderRef};
 use crate::error::Error::{Auth, UnsupportedAuthScheme};
-use crate::error::{InvalidQuerySnafu, NotFoundAuthHeaderSnafu};
+use crate::error::{ConvertRowInsertsSnafu, InvalidQuerySnafu, NotFoundAuthHeaderSnafu};
 use crate::grpc::TonicResult;
+use crate::http::HttpRecordsOutput;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
+use crate::query_handler::{DdlBatchHandlerRef, TableCreation};
+
+/// Metadata key of the flag to dry run the inserts of a request, which validates the rows and
+/// reports the tables and columns the inserts would create, without writing anything. `DoGet`
+/// of the flight service returns the report as records, and the `GreptimeDatabase` service
+/// returns it in the metadata of [DRY_RUN_REPORT_HEADER].
+pub const DRY_RUN_HEADER: &str = "x-greptime-dry-run";
+/// Binary metadata key of the report of the dry runs of the `GreptimeDatabase` service, which
+/// is the JSON of the records of the report as the HTTP API returns them.
+pub const DRY_RUN_REPORT_HEADER: &str = "x-greptime-dry-run-report-bin";
 
 pub struct GreptimeRequestHandler {
     handler: ServerGrpcQueryHandlerRef,
@@ -47,13 +71,31 @@ impl GreptimeRequestHandler {
         }
     }
 
-    pub(crate) async fn handle_request(&self, request: GreptimeRequest) -> TonicResult<Output> {
+    /// Handles the request reading the writes in `commit_token`, returns the output and the
+    /// commit token updated by the writes of the request. The inserts of the request are dry
+    /// runs if `dry_run` is set, write the existing rows as `write_mode` says, and report
+    /// their invalid rows as `validation_mode` says. The queries of the request read the
+    /// snapshots of tables as of `as_of` if set.
+    pub(crate) async fn handle_request(
+        &self,
+        request: GreptimeRequest,
+        commit_token: CommitToken,
+        dry_run: bool,
+        write_mode: WriteMode,
+        validation_mode: ValidationMode,
+        as_of: Option<i64>,
+    ) -> TonicResult<(Output, CommitToken)> {
         let query = request.request.context(InvalidQuerySnafu {
             reason: "Expecting non-empty GreptimeRequest.",
         })?;
 
         let header = request.header.as_ref();
-        let query_ctx = create_query_context(header);
+        let query_ctx = QueryContext::arc();
+        query_ctx.merge_commit_token(&commit_token);
+        query_ctx.set_dry_run(dry_run);
+        query_ctx.set_write_mode(write_mode);
+        query_ctx.set_validation_mode(validation_mode);
+        query_ctx.set_as_of(as_of);
 
         self.auth(header, &query_ctx).await?;
 
@@ -66,28 +108,92 @@ impl GreptimeRequestHandler {
         //   - Obtaining a `JoinHandle` to get the panic message (if there's any).
         //     From its docs, `JoinHandle` is cancel safe. The task keeps running even it's handle been dropped.
         // 2. avoid the handler blocks the gRPC runtime incidentally.
+        let ctx = query_ctx.clone();
         let handle = self
             .runtime
-            .spawn(async move { handler.do_query(query, query_ctx).await });
-
-        let output = handle.await.map_err(|e| {
-            if e.is_cancelled() {
-                Status::cancelled(e.to_string())
-            } else if e.is_panic() {
-                Status::internal(format!("{:?}", e.into_panic()))
-            } else {
-                Status::unknown(e.to_string())
+            .spawn(async move { handler.do_query(query, ctx).await });
+
+        let output = handle.await.map_err(join_error_to_status)??;
+        Ok((output, query_ctx.commit_token()))
+    }
+
+    /// Handles the row inserts of the request the same way as the inserts of a
+    /// [GreptimeRequest], returns the number of rows inserted and the commit token updated by
+    /// the inserts. The inserts are never dry runs, as their report can't be returned.
+    pub(crate) async fn handle_row_inserts(
+        &self,
+        request: RowInsertRequests,
+        commit_token: CommitToken,
+        write_mode: WriteMode,
+        validation_mode: ValidationMode,
+    ) -> TonicResult<(usize, CommitToken)> {
+        let inserts = request
+            .inserts
+            .into_iter()
+            .map(|insert| rows_to_insert_request(insert).map(GreptimeRequestKind::Insert))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context(ConvertRowInsertsSnafu)?;
+
+        let query_ctx = QueryContext::arc();
+        query_ctx.merge_commit_token(&commit_token);
+        query_ctx.set_write_mode(write_mode);
+        query_ctx.set_validation_mode(validation_mode);
+
+        self.auth(request.header.as_ref(), &query_ctx).await?;
+
+        let handler = self.handler.clone();
+        // Executes in another runtime for the same reasons as `handle_request`.
+        let ctx = query_ctx.clone();
+        let handle = self.runtime.spawn(async move {
+            let mut affected_rows = 0;
+            for insert in inserts {
+                if let Output::AffectedRows(rows) = handler.do_query(insert, ctx.clone()).await? {
+                    affected_rows += rows;
+                }
             }
-        })??;
-        Ok(output)
+            Ok::<_, crate::error::Error>(affected_rows)
+        });
+
+        let affected_rows = handle.await.map_err(join_error_to_status)??;
+        Ok((affected_rows, query_ctx.commit_token()))
     }
 
+    /// Creates the tables of the `CREATE TABLE` statements in the SQL query of the request all
+    /// or nothing, in the database of the request.
+    pub(crate) async fn handle_create_tables(
+        &self,
+        request: GreptimeRequest,
+        ddl_batch_handler: DdlBatchHandlerRef,
+    ) -> TonicResult<Vec<TableCreation>> {
+        let Some(GreptimeRequestKind::Query(QueryRequest { query: Some(Query::Sql(sql)) })) =
+            request.request else {
+            return Err(Status::invalid_argument(
+                "Expecting a SQL query of CREATE TABLE statements.",
+            ));
+        };
+
+        let query_ctx = QueryContext::arc();
+        self.auth(request.header.as_ref(), &query_ctx).await?;
+
+        // Executes in another runtime for the same reasons as `handle_request`, moreover the
+        // batch must not be cancelled halfway, or the tables created are not rolled back.
+        let handle = self
+            .runtime
+            .spawn(async move { ddl_batch_handler.create_tables(&sql, query_ctx).await });
+        Ok(handle.await.map_err(join_error_to_status)??)
+    }
+
+    /// Authenticates the user of the request and sets the database of the request to the
+    /// `query_ctx`, which is the default schema of the user if the request doesn't specify one.
     async fn auth(
         &self,
         header: Option<&RequestHeader>,
         query_ctx: &QueryContextRef,
     ) -> TonicResult<()> {
-        let Some(user_provider) = self.user_provider.as_ref() else { return Ok(()) };
+        let Some(user_provider) = self.user_provider.as_ref() else {
+            set_database(query_ctx, header);
+            return Ok(());
+        };
 
         let auth_scheme = header
             .and_then(|header| {
@@ -99,15 +205,28 @@ impl GreptimeRequestHandler {
             .context(NotFoundAuthHeaderSnafu)?;
 
         match auth_scheme {
-            AuthScheme::Basic(Basic { username, password }) => user_provider
-                .auth(
+            AuthScheme::Basic(Basic { username, password }) => match user_provider
+                .authenticate(
                     Identity::UserId(&username, None),
                     Password::PlainText(&password),
-                    &query_ctx.current_catalog(),
-                    &query_ctx.current_schema(),
                 )
                 .await
-                .map_err(|e| Auth { source: e }),
+            {
+                Ok(user_info) => {
+                    query_ctx.use_default_schema_of(&user_info);
+                    set_database(query_ctx, header);
+                    user_provider
+                        .authorize(
+                            &query_ctx.current_catalog(),
+                            &query_ctx.current_schema(),
+                            &user_info,
+                        )
+                        .await
+                        .map(|_| query_ctx.set_current_user(user_info))
+                }
+                Err(e) => Err(e),
+            }
+            .map_err(|e| Auth { source: e }),
             AuthScheme::Token(_) => Err(UnsupportedAuthScheme {
                 name: "Token AuthScheme".to_string(),
             }),
@@ -117,8 +236,111 @@ impl GreptimeRequestHandler {
     }
 }
 
+fn join_error_to_status(e: JoinError) -> Status {
+    if e.is_cancelled() {
+        Status::cancelled(e.to_string())
+    } else if e.is_panic() {
+        Status::internal(format!("{:?}", e.into_panic()))
+    } else {
+        Status::unknown(e.to_string())
+    }
+}
+
+/// Parses the commit token in the metadata of a request, which is empty if absent.
+pub(crate) fn commit_token_from_metadata(metadata: &MetadataMap) -> TonicResult<CommitToken> {
+    match metadata.get(COMMIT_TOKEN_HEADER) {
+        Some(value) => value
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(str::parse)
+            .map_err(|e| Status::invalid_argument(format!("Invalid commit token: {e}"))),
+        None => Ok(CommitToken::default()),
+    }
+}
+
+/// Parses the flag to dry run the inserts in the metadata of a request, which is false if
+/// absent.
+pub(crate) fn dry_run_from_metadata(metadata: &MetadataMap) -> TonicResult<bool> {
+    match metadata.get(DRY_RUN_HEADER) {
+        Some(value) => value
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(|value| value.parse().map_err(|e: ParseBoolError| e.to_string()))
+            .map_err(|e| Status::invalid_argument(format!("Invalid dry run flag: {e}"))),
+        None => Ok(false),
+    }
+}
+
+/// Parses the write mode of the inserts in the metadata of a request, which is
+/// [WriteMode::Upsert] if absent.
+pub(crate) fn write_mode_from_metadata(metadata: &MetadataMap) -> TonicResult<WriteMode> {
+    match metadata.get(WRITE_MODE_HEADER) {
+        Some(value) => value
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(str::parse)
+            .map_err(|e| Status::invalid_argument(format!("Invalid write mode: {e}"))),
+        None => Ok(WriteMode::default()),
+    }
+}
+
+/// Parses the validation mode of the inserts in the metadata of a request, which is
+/// [ValidationMode::Strict] if absent.
+pub(crate) fn validation_mode_from_metadata(metadata: &MetadataMap) -> TonicResult<ValidationMode> {
+    match metadata.get(VALIDATION_MODE_HEADER) {
+        Some(value) => value
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(str::parse)
+            .map_err(|e| Status::invalid_argument(format!("Invalid validation mode: {e}"))),
+        None => Ok(ValidationMode::default()),
+    }
+}
+
+/// Parses the wall-clock time in millis the queries of a request read the snapshots of tables
+/// as of in the metadata of the request, which reads the latest snapshots if absent.
+pub(crate) fn as_of_from_metadata(metadata: &MetadataMap) -> TonicResult<Option<i64>> {
+    match metadata.get(AS_OF_HEADER) {
+        Some(value) => value
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(|value| value.parse().map_err(|e: ParseIntError| e.to_string()))
+            .map(Some)
+            .map_err(|e| Status::invalid_argument(format!("Invalid as of time: {e}"))),
+        None => Ok(None),
+    }
+}
+
+/// Attaches the commit token to the metadata of a response, unless the token is empty.
+pub(crate) fn set_commit_token_metadata(metadata: &mut MetadataMap, commit_token: &CommitToken) {
+    if commit_token.is_empty() {
+        return;
+    }
+    // The formatted token only contains digits and separators, so it's always valid.
+    if let Ok(value) = commit_token.to_string().parse() {
+        let _ = metadata.insert(COMMIT_TOKEN_HEADER, value);
+    }
+}
+
+/// Attaches the records of the report of dry runs to the metadata of a response.
+pub(crate) fn set_dry_run_report_metadata(
+    metadata: &mut MetadataMap,
+    report: Vec<RecordBatch>,
+) -> TonicResult<()> {
+    let report = HttpRecordsOutput::try_from(report).map_err(Status::internal)?;
+    let report = serde_json::to_vec(&report).map_err(|e| Status::internal(e.to_string()))?;
+    let _ = metadata.insert_bin(DRY_RUN_REPORT_HEADER, MetadataValue::from_bytes(&report));
+    Ok(())
+}
+
 pub(crate) fn create_query_context(header: Option<&RequestHeader>) -> QueryContextRef {
     let ctx = QueryContext::arc();
+    set_database(&ctx, header);
+    ctx
+}
+
+/// Sets the database specified in the header, if any, to the `ctx`.
+fn set_database(ctx: &QueryContext, header: Option<&RequestHeader>) {
     if let Some(header) = header {
         // We provide dbname field in newer versions of protos/sdks
         // parse dbname from header in priority
@@ -136,5 +358,65 @@ pub(crate) fn create_query_context(header: Option<&RequestHeader>) -> QueryConte
             }
         }
     };
-    ctx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_mode_from_metadata() {
+        let mut metadata = MetadataMap::new();
+        assert_eq!(
+            WriteMode::Upsert,
+            write_mode_from_metadata(&metadata).unwrap()
+        );
+
+        let _ = metadata.insert(WRITE_MODE_HEADER, "insert_ignore".parse().unwrap());
+        assert_eq!(
+            WriteMode::InsertIgnore,
+            write_mode_from_metadata(&metadata).unwrap()
+        );
+        let _ = metadata.insert(WRITE_MODE_HEADER, "upsert".parse().unwrap());
+        assert_eq!(
+            WriteMode::Upsert,
+            write_mode_from_metadata(&metadata).unwrap()
+        );
+
+        let _ = metadata.insert(WRITE_MODE_HEADER, "replace".parse().unwrap());
+        let status = write_mode_from_metadata(&metadata).unwrap_err();
+        assert_eq!(tonic::Code::InvalidArgument, status.code());
+    }
+
+    #[test]
+    fn test_validation_mode_from_metadata() {
+        let mut metadata = MetadataMap::new();
+        assert_eq!(
+            ValidationMode::Strict,
+            validation_mode_from_metadata(&metadata).unwrap()
+        );
+
+        let _ = metadata.insert(VALIDATION_MODE_HEADER, "lenient".parse().unwrap());
+        assert_eq!(
+            ValidationMode::Lenient,
+            validation_mode_from_metadata(&metadata).unwrap()
+        );
+
+        let _ = metadata.insert(VALIDATION_MODE_HEADER, "loose".parse().unwrap());
+        let status = validation_mode_from_metadata(&metadata).unwrap_err();
+        assert_eq!(tonic::Code::InvalidArgument, status.code());
+    }
+
+    #[test]
+    fn test_as_of_from_metadata() {
+        let mut metadata = MetadataMap::new();
+        assert_eq!(None, as_of_from_metadata(&metadata).unwrap());
+
+        let _ = metadata.insert(AS_OF_HEADER, "1681293600123".parse().unwrap());
+        assert_eq!(Some(1681293600123), as_of_from_metadata(&metadata).unwrap());
+
+        let _ = metadata.insert(AS_OF_HEADER, "yesterday".parse().unwrap());
+        let status = as_of_from_metadata(&metadata).unwrap_err();
+        assert_eq!(tonic::Code::InvalidArgument, status.code());
+    }
 }
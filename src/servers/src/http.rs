@@ -13,10 +13,16 @@
 // limitations under the License.
 
 pub mod authorize;
+pub mod batch;
+pub mod commit_token;
+pub mod csv;
 pub mod handler;
 pub mod influxdb;
+pub mod jobs;
+pub mod json_ingest;
 pub mod opentsdb;
 pub mod prometheus;
+pub mod queries;
 pub mod script;
 
 mod admin;
@@ -34,64 +40,133 @@ use aide::openapi::{Info, OpenApi, Server as OpenAPIServer};
 use async_trait::async_trait;
 use axum::body::BoxBody;
 use axum::error_handling::HandleErrorLayer;
+use axum::extract::connect_info::Connected;
 use axum::response::{Html, Json};
-use axum::{routing, BoxError, Extension, Router};
+use axum::{middleware, routing, BoxError, Extension, Router};
+use common_base::validation_mode::ValidationMode;
 use common_error::prelude::ErrorExt;
 use common_error::status_code::StatusCode;
 use common_query::Output;
 use common_recordbatch::{util, RecordBatch};
-use common_telemetry::logging::info;
+use common_telemetry::logging::{debug, info, warn};
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
 use datatypes::data_type::DataType;
+use datatypes::float_format::FloatPrecision;
 use futures::FutureExt;
+use rustls::{Certificate, ServerConfig};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use session::context::QueryContext;
+use session::context::{QueryContext, UserInfo};
+use session::registry::SessionRegistryRef;
 use snafu::{ensure, ResultExt};
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::oneshot::{self, Sender};
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::wrappers::ReceiverStream;
 use tower::timeout::TimeoutLayer;
 use tower::ServiceBuilder;
 use tower_http::auth::AsyncRequireAuthorizationLayer;
 use tower_http::trace::TraceLayer;
 
 use self::authorize::HttpAuth;
+use self::batch::BatchQueryOptions;
 use self::influxdb::{influxdb_health, influxdb_ping, influxdb_write};
+use self::prometheus::{PromState, RemoteWriteOptions, RemoteWriteQueue};
 use crate::auth::UserProviderRef;
-use crate::error::{AlreadyStartedSnafu, Result, StartHttpSnafu};
-use crate::http::admin::flush;
+use crate::error::{
+    error_detail, AlreadyStartedSnafu, InternalIoSnafu, InvalidQuerySnafu, Result, StartHttpSnafu,
+    TcpBindSnafu,
+};
+use crate::http::admin::{create_tables, flush, gc_orphans, sessions, storage_usage};
 use crate::metrics_handler::MetricsHandler;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::query_handler::{
-    InfluxdbLineProtocolHandlerRef, OpentsdbProtocolHandlerRef, PrometheusProtocolHandlerRef,
-    ScriptHandlerRef,
+    DdlBatchHandlerRef, InfluxdbLineProtocolHandlerRef, JobHandlerRef, JsonIngestHandlerRef,
+    OpentsdbProtocolHandlerRef, OrphanGcHandlerRef, PrometheusProtocolHandlerRef,
+    ReadinessHandlerRef, RunningQueryHandlerRef, ScriptHandlerRef, StorageUsageHandlerRef,
 };
 use crate::server::Server;
+use crate::tls::TlsOption;
 
-/// create query context from database name information, catalog and schema are
-/// resolved from the name
+/// Creates the context of a query of the user on `db`, or on the default schema of the user if
+/// `db` is absent. The user must be authorized on the database the query runs in.
 pub(crate) async fn query_context_from_db(
-    query_handler: ServerSqlQueryHandlerRef,
+    state: &ApiState,
     db: Option<String>,
+    user_info: &UserInfo,
 ) -> std::result::Result<Arc<QueryContext>, JsonResponse> {
+    let query_ctx = QueryContext::arc();
+    query_ctx.use_default_schema_of(user_info);
+    query_ctx.set_current_user(user_info.clone());
     if let Some(db) = &db {
         let (catalog, schema) = super::parse_catalog_and_schema_from_client_database_name(db);
 
-        match query_handler.is_valid_schema(catalog, schema).await {
-            Ok(true) => Ok(Arc::new(QueryContext::with(catalog, schema))),
-            Ok(false) => Err(JsonResponse::with_error(
-                format!("Database not found: {db}"),
-                StatusCode::DatabaseNotFound,
-            )),
-            Err(e) => Err(JsonResponse::with_error(
-                format!("Error checking database: {db}, {e}"),
-                StatusCode::Internal,
-            )),
+        match state.sql_handler.is_valid_schema(catalog, schema).await {
+            Ok(true) => {
+                query_ctx.set_current_catalog(catalog);
+                query_ctx.set_current_schema(schema);
+            }
+            Ok(false) => {
+                return Err(JsonResponse::with_error(
+                    format!("Database not found: {db}"),
+                    StatusCode::DatabaseNotFound,
+                ))
+            }
+            Err(e) => {
+                return Err(JsonResponse::with_error(
+                    format!("Error checking database: {db}, {e}"),
+                    StatusCode::Internal,
+                ))
+            }
+        }
+    }
+
+    let catalog = query_ctx.current_catalog();
+    let schema = query_ctx.current_schema();
+    // Users pinned by their client certificates may not have a user provider to authorize them.
+    if !user_info.can_use_schema(&catalog, &schema) {
+        return Err(JsonResponse::with_error(
+            format!("Access denied to database: {catalog}-{schema}"),
+            StatusCode::AccessDenied,
+        ));
+    }
+    if let Some(user_provider) = &state.user_provider {
+        if let Err(e) = user_provider.authorize(&catalog, &schema, user_info).await {
+            return Err(JsonResponse::with_error(
+                format!("Access denied to database: {catalog}-{schema}, {e}"),
+                StatusCode::AccessDenied,
+            ));
         }
-    } else {
-        Ok(QueryContext::arc())
     }
+    Ok(query_ctx)
+}
+
+/// Parses the `validation_mode` parameter of the inserts of a request, which is
+/// [ValidationMode::Strict] if absent.
+pub(crate) fn parse_validation_mode(value: Option<&str>) -> Result<ValidationMode> {
+    let Some(value) = value else { return Ok(ValidationMode::default()) };
+    value
+        .parse()
+        .map_err(|reason| InvalidQuerySnafu { reason }.build())
+}
+
+/// Parses the `as_of` parameter of the queries of a request into epoch millis, which is a
+/// timestamp string or epoch millis.
+pub(crate) fn parse_as_of(value: Option<&str>) -> Result<Option<i64>> {
+    let Some(value) = value else { return Ok(None) };
+    Timestamp::parse_in_unit(value, TimeUnit::Millisecond)
+        .map(|as_of| Some(as_of.value()))
+        .map_err(|_| {
+            InvalidQuerySnafu {
+                reason: format!("Invalid as_of: {value}"),
+            }
+            .build()
+        })
 }
 
 pub const HTTP_API_VERSION: &str = "v1";
@@ -106,6 +181,14 @@ pub struct HttpServer {
     grpc_handler: Option<ServerGrpcQueryHandlerRef>,
     options: HttpOptions,
     influxdb_handler: Option<InfluxdbLineProtocolHandlerRef>,
+    json_ingest_handler: Option<JsonIngestHandlerRef>,
+    ddl_batch_handler: Option<DdlBatchHandlerRef>,
+    storage_usage_handler: Option<StorageUsageHandlerRef>,
+    orphan_gc_handler: Option<OrphanGcHandlerRef>,
+    job_handler: Option<JobHandlerRef>,
+    running_query_handler: Option<RunningQueryHandlerRef>,
+    readiness_handler: Option<ReadinessHandlerRef>,
+    sessions: Option<SessionRegistryRef>,
     opentsdb_handler: Option<OpentsdbProtocolHandlerRef>,
     prom_handler: Option<PrometheusProtocolHandlerRef>,
     script_handler: Option<ScriptHandlerRef>,
@@ -124,6 +207,15 @@ pub struct HttpOptions {
 
     #[serde(skip)]
     pub disable_dashboard: bool,
+
+    /// Options of `/sql/batch`.
+    pub batch_query: BatchQueryOptions,
+
+    /// Options of `/prometheus/write`.
+    pub prom_remote_write: RemoteWriteOptions,
+
+    /// TLS options, serving HTTPS unless the mode is `disable`.
+    pub tls: TlsOption,
 }
 
 impl Default for HttpOptions {
@@ -132,6 +224,9 @@ impl Default for HttpOptions {
             addr: "127.0.0.1:4000".to_string(),
             timeout: Duration::from_secs(30),
             disable_dashboard: false,
+            batch_query: BatchQueryOptions::default(),
+            prom_remote_write: RemoteWriteOptions::default(),
+            tls: TlsOption::default(),
         }
     }
 }
@@ -191,10 +286,20 @@ impl HttpRecordsOutput {
 impl TryFrom<Vec<RecordBatch>> for HttpRecordsOutput {
     type Error = String;
 
-    // TODO(sunng87): use schema from recordstreams when #366 fixed
     fn try_from(
         recordbatches: Vec<RecordBatch>,
     ) -> std::result::Result<HttpRecordsOutput, Self::Error> {
+        HttpRecordsOutput::try_new(recordbatches, FloatPrecision::default())
+    }
+}
+
+impl HttpRecordsOutput {
+    /// Creates the output of `recordbatches`, with floats in `precision`.
+    // TODO(sunng87): use schema from recordstreams when #366 fixed
+    pub fn try_new(
+        recordbatches: Vec<RecordBatch>,
+        precision: FloatPrecision,
+    ) -> std::result::Result<HttpRecordsOutput, String> {
         if recordbatches.is_empty() {
             Ok(HttpRecordsOutput {
                 schema: None,
@@ -222,7 +327,7 @@ impl TryFrom<Vec<RecordBatch>> for HttpRecordsOutput {
                 for row in recordbatch.rows() {
                     let value_row = row
                         .into_iter()
-                        .map(|f| Value::try_from(f).map_err(|err| err.to_string()))
+                        .map(|f| f.try_into_json(precision).map_err(|err| err.to_string()))
                         .collect::<std::result::Result<Vec<Value>, _>>()?;
 
                     rows.push(value_row);
@@ -249,28 +354,44 @@ pub struct JsonResponse {
     code: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    /// Details of the error, e.g. the row errors of a rejected insertion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     output: Option<Vec<JsonOutput>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     execution_time_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    /// Results of the statements of a request with many statements, in their order. The
+    /// `code`, `error` and `detail` are of the first failed statement then, and the `output`
+    /// is absent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    results: Option<Vec<StatementResult>>,
 }
 
 impl JsonResponse {
     fn with_error(error: String, error_code: StatusCode) -> Self {
         JsonResponse {
             error: Some(error),
+            detail: None,
             code: error_code as u32,
             output: None,
             execution_time_ms: None,
+            warnings: None,
+            results: None,
         }
     }
 
     fn with_output(output: Option<Vec<JsonOutput>>) -> Self {
         JsonResponse {
             error: None,
+            detail: None,
             code: StatusCode::Success as u32,
             output,
             execution_time_ms: None,
+            warnings: None,
+            results: None,
         }
     }
 
@@ -279,8 +400,40 @@ impl JsonResponse {
         self
     }
 
+    fn with_detail(mut self, detail: Option<serde_json::Value>) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    /// Creates a response of the `results` of the statements, failed if any of them failed.
+    fn with_results(results: Vec<StatementResult>) -> Self {
+        let mut resp = Self::with_output(None);
+        if let Some(failed) = results.iter().find(|result| !result.response.success()) {
+            resp.code = failed.response.code;
+            resp.error = failed.response.error.clone();
+            resp.detail = failed.response.detail.clone();
+        }
+        resp.results = Some(results);
+        resp
+    }
+
+    fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        if !warnings.is_empty() {
+            self.warnings = Some(warnings);
+        }
+        self
+    }
+
     /// Create a json response from query result
     async fn from_output(outputs: Vec<Result<Output>>) -> Self {
+        Self::from_output_with_precision(outputs, FloatPrecision::default()).await
+    }
+
+    /// Create a json response from query result, with floats in `precision`
+    async fn from_output_with_precision(
+        outputs: Vec<Result<Output>>,
+        precision: FloatPrecision,
+    ) -> Self {
         // TODO(sunng87): this api response structure cannot represent error
         // well. It hides successful execution results from error response
         let mut results = Vec::with_capacity(outputs.len());
@@ -292,7 +445,7 @@ impl JsonResponse {
                 Ok(Output::Stream(stream)) => {
                     // TODO(sunng87): streaming response
                     match util::collect(stream).await {
-                        Ok(rows) => match HttpRecordsOutput::try_from(rows) {
+                        Ok(rows) => match HttpRecordsOutput::try_new(rows, precision) {
                             Ok(rows) => {
                                 results.push(JsonOutput::Records(rows));
                             }
@@ -309,19 +462,22 @@ impl JsonResponse {
                         }
                     }
                 }
-                Ok(Output::RecordBatches(rbs)) => match HttpRecordsOutput::try_from(rbs.take()) {
-                    Ok(rows) => {
-                        results.push(JsonOutput::Records(rows));
-                    }
-                    Err(err) => {
-                        return Self::with_error(err, StatusCode::Internal);
+                Ok(Output::RecordBatches(rbs)) => {
+                    match HttpRecordsOutput::try_new(rbs.take(), precision) {
+                        Ok(rows) => {
+                            results.push(JsonOutput::Records(rows));
+                        }
+                        Err(err) => {
+                            return Self::with_error(err, StatusCode::Internal);
+                        }
                     }
-                },
+                }
                 Err(e) => {
                     return Self::with_error(
                         format!("Query engine output error: {e}"),
                         e.status_code(),
-                    );
+                    )
+                    .with_detail(error_detail(&e));
                 }
             }
         }
@@ -340,6 +496,10 @@ impl JsonResponse {
         self.error.as_ref()
     }
 
+    pub fn detail(&self) -> Option<&serde_json::Value> {
+        self.detail.as_ref()
+    }
+
     pub fn output(&self) -> Option<&[JsonOutput]> {
         self.output.as_deref()
     }
@@ -347,6 +507,37 @@ impl JsonResponse {
     pub fn execution_time_ms(&self) -> Option<u128> {
         self.execution_time_ms
     }
+
+    pub fn warnings(&self) -> Option<&[String]> {
+        self.warnings.as_deref()
+    }
+
+    pub fn results(&self) -> Option<&[StatementResult]> {
+        self.results.as_deref()
+    }
+}
+
+/// Result of a statement of a request with many statements, which is the same as the response
+/// of `/sql` for the statement alone.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct StatementResult {
+    sql: String,
+    #[serde(flatten)]
+    response: JsonResponse,
+}
+
+impl StatementResult {
+    fn new(sql: String, response: JsonResponse) -> Self {
+        Self { sql, response }
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    pub fn response(&self) -> &JsonResponse {
+        &self.response
+    }
 }
 
 async fn serve_api(Extension(api): Extension<OpenApi>) -> impl IntoApiResponse {
@@ -361,6 +552,9 @@ async fn serve_docs() -> Html<String> {
 pub struct ApiState {
     pub sql_handler: ServerSqlQueryHandlerRef,
     pub script_handler: Option<ScriptHandlerRef>,
+    pub batch_query_options: BatchQueryOptions,
+    /// Authorizes the users on the databases of their queries, if any.
+    pub user_provider: Option<UserProviderRef>,
 }
 
 #[derive(Default)]
@@ -377,6 +571,14 @@ impl HttpServerBuilder {
                 options,
                 opentsdb_handler: None,
                 influxdb_handler: None,
+                json_ingest_handler: None,
+                ddl_batch_handler: None,
+                storage_usage_handler: None,
+                orphan_gc_handler: None,
+                job_handler: None,
+                running_query_handler: None,
+                readiness_handler: None,
+                sessions: None,
                 prom_handler: None,
                 user_provider: None,
                 script_handler: None,
@@ -411,6 +613,47 @@ impl HttpServerBuilder {
         self
     }
 
+    pub fn with_json_ingest_handler(&mut self, handler: JsonIngestHandlerRef) -> &mut Self {
+        self.inner.json_ingest_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_ddl_batch_handler(&mut self, handler: DdlBatchHandlerRef) -> &mut Self {
+        self.inner.ddl_batch_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_storage_usage_handler(&mut self, handler: StorageUsageHandlerRef) -> &mut Self {
+        self.inner.storage_usage_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_orphan_gc_handler(&mut self, handler: OrphanGcHandlerRef) -> &mut Self {
+        self.inner.orphan_gc_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_job_handler(&mut self, handler: JobHandlerRef) -> &mut Self {
+        self.inner.job_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_running_query_handler(&mut self, handler: RunningQueryHandlerRef) -> &mut Self {
+        self.inner.running_query_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_readiness_handler(&mut self, handler: ReadinessHandlerRef) -> &mut Self {
+        self.inner.readiness_handler.get_or_insert(handler);
+        self
+    }
+
+    /// Lists the sessions of the registry by `/admin/sessions`.
+    pub fn with_session_registry(&mut self, sessions: SessionRegistryRef) -> &mut Self {
+        self.inner.sessions.get_or_insert(sessions);
+        self
+    }
+
     pub fn with_prom_handler(&mut self, handler: PrometheusProtocolHandlerRef) -> &mut Self {
         self.inner.prom_handler.get_or_insert(handler);
         self
@@ -453,16 +696,51 @@ impl HttpServer {
                 .route_sql(ApiState {
                     sql_handler,
                     script_handler: self.script_handler.clone(),
+                    batch_query_options: self.options.batch_query.clone(),
+                    user_provider: self.user_provider.clone(),
                 })
                 .finish_api(&mut api)
-                .layer(Extension(api));
+                .layer(Extension(api))
+                .layer(middleware::from_fn(commit_token::track_commit_token));
             router = router.nest(&format!("/{HTTP_API_VERSION}"), sql_router);
         }
 
-        if let Some(grpc_handler) = self.grpc_handler.clone() {
+        let admin_routers = [
+            self.grpc_handler
+                .clone()
+                .map(|handler| self.route_admin(handler)),
+            self.ddl_batch_handler
+                .clone()
+                .map(|handler| self.route_ddl_batch(handler)),
+            self.storage_usage_handler
+                .clone()
+                .map(|handler| self.route_storage_usage(handler)),
+            self.orphan_gc_handler
+                .clone()
+                .map(|handler| self.route_orphan_gc(handler)),
+            self.sessions
+                .clone()
+                .map(|registry| self.route_sessions(registry)),
+        ];
+        if let Some(admin_router) = admin_routers
+            .into_iter()
+            .flatten()
+            .reduce(|router, other| router.merge(other))
+        {
+            router = router.nest(&format!("/{HTTP_API_VERSION}/admin"), admin_router);
+        }
+
+        if let Some(job_handler) = self.job_handler.clone() {
+            router = router.nest(
+                &format!("/{HTTP_API_VERSION}/jobs"),
+                self.route_jobs(job_handler),
+            );
+        }
+
+        if let Some(running_query_handler) = self.running_query_handler.clone() {
             router = router.nest(
-                &format!("/{HTTP_API_VERSION}/admin"),
-                self.route_admin(grpc_handler.clone()),
+                &format!("/{HTTP_API_VERSION}/queries"),
+                self.route_queries(running_query_handler),
             );
         }
 
@@ -480,6 +758,13 @@ impl HttpServer {
             );
         }
 
+        if let Some(json_ingest_handler) = self.json_ingest_handler.clone() {
+            router = router.nest(
+                &format!("/{HTTP_API_VERSION}/ingest"),
+                self.route_ingest(json_ingest_handler),
+            );
+        }
+
         if let Some(prom_handler) = self.prom_handler.clone() {
             router = router.nest(
                 &format!("/{HTTP_API_VERSION}/prometheus"),
@@ -504,6 +789,11 @@ impl HttpServer {
             "/health",
             routing::get(handler::health).post(handler::health),
         );
+        // Nodes without components to wait for are ready once they're alive.
+        router = match self.readiness_handler.clone() {
+            Some(readiness_handler) => router.nest("", self.route_ready(readiness_handler)),
+            None => router.route("/ready", routing::get(handler::health)),
+        };
 
         #[cfg(feature = "dashboard")]
         {
@@ -522,7 +812,8 @@ impl HttpServer {
                     .layer(TimeoutLayer::new(self.options.timeout))
                     // custom layer
                     .layer(AsyncRequireAuthorizationLayer::new(
-                        HttpAuth::<BoxBody>::new(self.user_provider.clone()),
+                        HttpAuth::<BoxBody>::new(self.user_provider.clone())
+                            .with_client_cert_mapping(self.options.tls.client_cert_mapping()),
                     )),
             )
     }
@@ -533,6 +824,12 @@ impl HttpServer {
             .with_state(metrics_handler)
     }
 
+    fn route_ready<S>(&self, readiness_handler: ReadinessHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/ready", routing::get(handler::ready))
+            .with_state(readiness_handler)
+    }
+
     fn route_sql<S>(&self, api_state: ApiState) -> ApiRouter<S> {
         ApiRouter::new()
             .api_route(
@@ -540,6 +837,14 @@ impl HttpServer {
                 apirouting::get_with(handler::sql, handler::sql_docs)
                     .post_with(handler::sql, handler::sql_docs),
             )
+            .route(
+                "/sql/csv",
+                routing::get(handler::sql_csv).post(handler::sql_csv),
+            )
+            .api_route(
+                "/sql/batch",
+                apirouting::post_with(batch::sql_batch, batch::sql_batch_docs),
+            )
             .api_route(
                 "/promql",
                 apirouting::get_with(handler::promql, handler::sql_docs)
@@ -547,6 +852,7 @@ impl HttpServer {
             )
             .api_route("/scripts", apirouting::post(script::scripts))
             .api_route("/run-script", apirouting::post(script::run_script))
+            .api_route("/scripts/status", apirouting::get(script::script_states))
             .route("/private/api.json", apirouting::get(serve_api))
             .route("/private/docs", apirouting::get(serve_docs))
             .with_state(api_state)
@@ -556,7 +862,12 @@ impl HttpServer {
         Router::new()
             .route("/write", routing::post(prometheus::remote_write))
             .route("/read", routing::post(prometheus::remote_read))
-            .with_state(prom_handler)
+            .with_state(PromState {
+                handler: prom_handler,
+                write_queue: Arc::new(RemoteWriteQueue::new(
+                    self.options.prom_remote_write.clone(),
+                )),
+            })
     }
 
     fn route_influxdb<S>(&self, influxdb_handler: InfluxdbLineProtocolHandlerRef) -> Router<S> {
@@ -567,6 +878,12 @@ impl HttpServer {
             .with_state(influxdb_handler)
     }
 
+    fn route_ingest<S>(&self, json_ingest_handler: JsonIngestHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/json", routing::post(json_ingest::ingest_json))
+            .with_state(json_ingest_handler)
+    }
+
     fn route_opentsdb<S>(&self, opentsdb_handler: OpentsdbProtocolHandlerRef) -> Router<S> {
         Router::new()
             .route("/api/put", routing::post(opentsdb::put))
@@ -578,10 +895,129 @@ impl HttpServer {
             .route("/flush", routing::post(flush))
             .with_state(grpc_handler)
     }
+
+    fn route_ddl_batch<S>(&self, ddl_batch_handler: DdlBatchHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/create_tables", routing::post(create_tables))
+            .with_state(ddl_batch_handler)
+    }
+
+    fn route_storage_usage<S>(&self, storage_usage_handler: StorageUsageHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/storage_usage", routing::get(storage_usage))
+            .with_state(storage_usage_handler)
+    }
+
+    fn route_orphan_gc<S>(&self, orphan_gc_handler: OrphanGcHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/gc_orphans", routing::post(gc_orphans))
+            .with_state(orphan_gc_handler)
+    }
+
+    fn route_sessions<S>(&self, registry: SessionRegistryRef) -> Router<S> {
+        Router::new()
+            .route("/sessions", routing::get(sessions))
+            .with_state(registry)
+    }
+
+    fn route_jobs<S>(&self, job_handler: JobHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/:id", routing::get(jobs::job).delete(jobs::cancel_job))
+            .with_state(job_handler)
+    }
+
+    fn route_queries<S>(&self, handler: RunningQueryHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/", routing::get(queries::running_queries))
+            .with_state(handler)
+    }
 }
 
 pub const HTTP_SERVER: &str = "HTTP_SERVER";
 
+/// Timeout of the TLS handshake of an HTTPS connection.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Max number of the HTTPS connections finishing the TLS handshake but not served yet.
+const TLS_ACCEPTED_QUEUE_SIZE: usize = 128;
+
+/// Certificate the client of an HTTPS connection presented in the TLS handshake, once verified.
+/// It's in the extensions of the requests as [axum::extract::ConnectInfo].
+#[derive(Debug, Clone, Default)]
+pub struct ClientCertificate(pub Option<Certificate>);
+
+impl Connected<&TlsStream<TcpStream>> for ClientCertificate {
+    fn connect_info(stream: &TlsStream<TcpStream>) -> Self {
+        let (_, conn) = stream.get_ref();
+        Self(
+            conn.peer_certificates()
+                .and_then(|certs| certs.first().cloned()),
+        )
+    }
+}
+
+impl HttpServer {
+    /// Serves HTTPS on `listening` until shutdown.
+    async fn start_tls(&self, listening: SocketAddr, tls: ServerConfig) -> Result<SocketAddr> {
+        let (tx, rx) = oneshot::channel();
+        let (app, listener) = {
+            let mut shutdown_tx = self.shutdown_tx.lock().await;
+            ensure!(
+                shutdown_tx.is_none(),
+                AlreadyStartedSnafu { server: "HTTP" }
+            );
+
+            let listener = TcpListener::bind(listening)
+                .await
+                .context(TcpBindSnafu { addr: listening })?;
+
+            *shutdown_tx = Some(tx);
+
+            (self.make_app(), listener)
+        };
+        let listening = listener
+            .local_addr()
+            .context(TcpBindSnafu { addr: listening })?;
+        info!("HTTP server is bound to {} with TLS", listening);
+
+        // Handshakes in their own tasks, so slow clients don't hold up accepting the others.
+        let acceptor = TlsAcceptor::from(Arc::new(tls));
+        let (accepted_tx, accepted_rx) = mpsc::channel(TLS_ACCEPTED_QUEUE_SIZE);
+        let accepting = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        warn!("Failed to accept HTTPS connection, err: {}", e);
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let accepted_tx = accepted_tx.clone();
+                let _handle = tokio::spawn(async move {
+                    let handshake = acceptor.accept(stream);
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake).await {
+                        Ok(Ok(stream)) => {
+                            let _ = accepted_tx.send(Ok::<_, std::io::Error>(stream)).await;
+                        }
+                        Ok(Err(e)) => debug!("Failed TLS handshake of HTTPS connection: {}", e),
+                        Err(_) => debug!("TLS handshake of HTTPS connection timed out"),
+                    }
+                });
+            }
+        });
+
+        let incoming = hyper::server::accept::from_stream(ReceiverStream::new(accepted_rx));
+        let result = axum::Server::builder(incoming)
+            .serve(app.into_make_service_with_connect_info::<ClientCertificate>())
+            .with_graceful_shutdown(rx.map(drop))
+            .await;
+        accepting.abort();
+        result.context(StartHttpSnafu)?;
+
+        Ok(listening)
+    }
+}
+
 #[async_trait]
 impl Server for HttpServer {
     async fn shutdown(&self) -> Result<()> {
@@ -597,6 +1033,10 @@ impl Server for HttpServer {
     }
 
     async fn start(&self, listening: SocketAddr) -> Result<SocketAddr> {
+        if let Some(tls) = self.options.tls.setup().context(InternalIoSnafu)? {
+            return self.start_tls(listening, tls).await;
+        }
+
         let (tx, rx) = oneshot::channel();
         let server = {
             let mut shutdown_tx = self.shutdown_tx.lock().await;
@@ -13,18 +13,27 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use api::v1::ddl_request::Expr;
 use api::v1::greptime_request::Request;
 use api::v1::{DdlRequest, FlushTableExpr};
-use axum::extract::{Query, RawBody, State};
+use axum::extract::{Json, Query, RawBody, State};
 use axum::http::StatusCode;
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use serde::{Deserialize, Serialize};
 use session::context::QueryContext;
+use session::registry::SessionRegistryRef;
 use snafu::OptionExt;
 
-use crate::error;
 use crate::error::Result;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
+use crate::query_handler::{
+    DdlBatchHandlerRef, OrphanGcHandlerRef, OrphanGcReport, StorageUsage, StorageUsageHandlerRef,
+    TableCreation, TableCreationStatus,
+};
+use crate::{error, parse_catalog_and_schema_from_client_database_name};
 
 #[axum_macros::debug_handler]
 pub async fn flush(
@@ -65,3 +74,127 @@ pub async fn flush(
     grpc_handler.do_query(request, QueryContext::arc()).await?;
     Ok((StatusCode::NO_CONTENT, ()))
 }
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreateTablesParams {
+    /// Default database of the tables.
+    pub db: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTablesResponse {
+    /// Whether all the tables are created.
+    pub success: bool,
+    pub tables: Vec<TableCreation>,
+}
+
+/// Handler to create the tables of the `CREATE TABLE` statements in the body all or nothing.
+///
+/// Responds `400 Bad Request` with the status of each table if any of them fails, in which
+/// case none of the tables is left created.
+#[axum_macros::debug_handler]
+pub async fn create_tables(
+    State(handler): State<DdlBatchHandlerRef>,
+    Query(params): Query<CreateTablesParams>,
+    sql: String,
+) -> Result<(StatusCode, Json<CreateTablesResponse>)> {
+    let db = params.db.as_deref().unwrap_or(DEFAULT_SCHEMA_NAME);
+    let (catalog, schema) = parse_catalog_and_schema_from_client_database_name(db);
+    let ctx = Arc::new(QueryContext::with(catalog, schema));
+
+    let tables = handler.create_tables(&sql, ctx).await?;
+    let success = tables
+        .iter()
+        .all(|table| table.status != TableCreationStatus::Failed);
+    let status = if success {
+        StatusCode::OK
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    Ok((status, Json(CreateTablesResponse { success, tables })))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StorageUsageParams {
+    /// Whether to list the objects of each table, which is costly on large tables.
+    #[serde(default)]
+    pub full: bool,
+}
+
+/// Handler to get the bytes stored in the object store per table and per schema.
+#[axum_macros::debug_handler]
+pub async fn storage_usage(
+    State(handler): State<StorageUsageHandlerRef>,
+    Query(params): Query<StorageUsageParams>,
+) -> Result<Json<StorageUsage>> {
+    let usage = handler.storage_usage(params.full).await?;
+    Ok(Json(usage))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrphanGcParams {
+    #[serde(default = "default_catalog")]
+    pub catalog: String,
+    pub db: String,
+    pub table: String,
+    /// Whether to delete the orphans, the orphans are only reported otherwise.
+    #[serde(default)]
+    pub delete: bool,
+    /// Min age of the orphans to delete, like `1h`, defaults to the configured safety age.
+    #[serde(default, with = "humantime_serde")]
+    pub safety_age: Option<Duration>,
+}
+
+fn default_catalog() -> String {
+    DEFAULT_CATALOG_NAME.to_string()
+}
+
+/// Handler to find the orphaned objects in the storage of a table, and delete them on demand.
+#[axum_macros::debug_handler]
+pub async fn gc_orphans(
+    State(handler): State<OrphanGcHandlerRef>,
+    Query(params): Query<OrphanGcParams>,
+) -> Result<Json<OrphanGcReport>> {
+    let report = handler
+        .gc_orphans(
+            &params.catalog,
+            &params.db,
+            &params.table,
+            params.delete,
+            params.safety_age,
+        )
+        .await?;
+    Ok(Json(report))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionsResponse {
+    pub sessions: Vec<SessionStatus>,
+}
+
+/// A session of a MySQL or PostgreSQL connection to the server.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionStatus {
+    pub id: u64,
+    pub client_host: String,
+    pub channel: String,
+    /// How long the connection has been open, in seconds.
+    pub age_secs: u64,
+}
+
+/// Handler to list the sessions of the connections to the server, in the order they are
+/// connected.
+#[axum_macros::debug_handler]
+pub async fn sessions(State(registry): State<SessionRegistryRef>) -> Json<SessionsResponse> {
+    let sessions = registry
+        .sessions()
+        .into_iter()
+        .map(|session| SessionStatus {
+            id: session.id,
+            client_host: session.client_host.to_string(),
+            channel: session.channel.to_string(),
+            age_secs: session.age.as_secs(),
+        })
+        .collect();
+    Json(SessionsResponse { sessions })
+}
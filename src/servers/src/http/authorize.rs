@@ -14,8 +14,10 @@
 
 use std::marker::PhantomData;
 
+use axum::extract::ConnectInfo;
 use axum::http::{self, Request, StatusCode};
 use axum::response::Response;
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_telemetry::error;
 use futures::future::BoxFuture;
 use http_body::Body;
@@ -23,18 +25,20 @@ use session::context::UserInfo;
 use snafu::{ensure, OptionExt, ResultExt};
 use tower_http::auth::AsyncAuthorizeRequest;
 
-use super::PUBLIC_APIS;
+use super::{ClientCertificate, PUBLIC_APIS};
 use crate::auth::Error::IllegalParam;
-use crate::auth::{Identity, IllegalParamSnafu, UserProviderRef};
+use crate::auth::{Identity, UserProviderRef};
 use crate::error::Error::Auth;
 use crate::error::{
     self, InvalidAuthorizationHeaderSnafu, InvisibleASCIISnafu, NotFoundInfluxAuthSnafu, Result,
     UnsupportedAuthSchemeSnafu,
 };
 use crate::http::HTTP_API_PREFIX;
+use crate::tls::ClientCertMapping;
 
 pub struct HttpAuth<RespBody> {
     user_provider: Option<UserProviderRef>,
+    client_cert_mapping: ClientCertMapping,
     _ty: PhantomData<RespBody>,
 }
 
@@ -42,15 +46,24 @@ impl<RespBody> HttpAuth<RespBody> {
     pub fn new(user_provider: Option<UserProviderRef>) -> Self {
         Self {
             user_provider,
+            client_cert_mapping: ClientCertMapping::default(),
             _ty: PhantomData,
         }
     }
+
+    /// Sets how the default schemas of the users are read from the client certificates of the
+    /// HTTPS connections.
+    pub fn with_client_cert_mapping(mut self, client_cert_mapping: ClientCertMapping) -> Self {
+        self.client_cert_mapping = client_cert_mapping;
+        self
+    }
 }
 
 impl<RespBody> Clone for HttpAuth<RespBody> {
     fn clone(&self) -> Self {
         Self {
             user_provider: self.user_provider.clone(),
+            client_cert_mapping: self.client_cert_mapping,
             _ty: PhantomData,
         }
     }
@@ -67,13 +80,20 @@ where
 
     fn authorize(&mut self, mut request: Request<B>) -> Self::Future {
         let user_provider = self.user_provider.clone();
+        let client_cert_mapping = self.client_cert_mapping;
         Box::pin(async move {
             let need_auth = need_auth(&request);
+            let client_cert = request
+                .extensions()
+                .get::<ConnectInfo<ClientCertificate>>()
+                .and_then(|ConnectInfo(cert)| cert.0.clone());
 
             let user_provider = if let Some(user_provider) = user_provider.filter(|_| need_auth) {
                 user_provider
             } else {
-                request.extensions_mut().insert(UserInfo::default());
+                let user_info =
+                    client_cert_mapping.apply(UserInfo::default(), client_cert.as_ref());
+                request.extensions_mut().insert(user_info);
                 return Ok(request);
             };
 
@@ -85,48 +105,43 @@ where
                 }
             };
 
-            let (catalog, schema) = match extract_catalog_and_schema(&request) {
-                Ok((catalog, schema)) => (catalog, schema),
-                Err(e) => {
-                    error!("extract catalog and schema failed: {}", e);
-                    return Err(unauthorized_resp());
-                }
-            };
-
-            match user_provider
-                .auth(
+            let user_info = match user_provider
+                .authenticate(
                     Identity::UserId(username.as_str(), None),
                     crate::auth::Password::PlainText(password.as_str()),
-                    catalog,
-                    schema,
                 )
                 .await
             {
-                Ok(userinfo) => {
-                    request.extensions_mut().insert(userinfo);
-                    Ok(request)
-                }
+                Ok(user_info) => client_cert_mapping.apply(user_info, client_cert.as_ref()),
                 Err(e) => {
                     error!("authenticate failed: {}", e);
-                    Err(unauthorized_resp())
+                    return Err(unauthorized_resp());
                 }
+            };
+
+            // Requests without a database use the default schema of the user.
+            let query = request.uri().query().unwrap_or_default();
+            let (catalog, schema) = match extract_db_from_query(query) {
+                Some(db) => crate::parse_catalog_and_schema_from_client_database_name(db),
+                None => default_catalog_and_schema(&user_info),
+            };
+            if let Err(e) = user_provider.authorize(catalog, schema, &user_info).await {
+                error!("authorize failed: {}", e);
+                return Err(unauthorized_resp());
             }
+
+            request.extensions_mut().insert(user_info);
+            Ok(request)
         })
     }
 }
 
-fn extract_catalog_and_schema<B: Send + Sync + 'static>(
-    request: &Request<B>,
-) -> crate::auth::Result<(&str, &str)> {
-    // try get database name
-    let query = request.uri().query().unwrap_or_default();
-    let input_database = extract_db_from_query(query).context(IllegalParamSnafu {
-        msg: "db not provided or corrupted",
-    })?;
-
-    Ok(crate::parse_catalog_and_schema_from_client_database_name(
-        input_database,
-    ))
+/// Returns the catalog and schema a request of the user without a database uses.
+fn default_catalog_and_schema(user_info: &UserInfo) -> (&str, &str) {
+    (
+        user_info.default_catalog().unwrap_or(DEFAULT_CATALOG_NAME),
+        user_info.default_schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+    )
 }
 
 fn get_influxdb_credentials<B: Send + Sync + 'static>(
@@ -0,0 +1,379 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handler running a batch of independent SQL queries in one request.
+
+use std::time::{Duration, Instant};
+
+use aide::transform::TransformOperation;
+use axum::extract::{Json, Query, State};
+use axum::Extension;
+use common_base::commit_token::CommitToken;
+use common_error::status_code::StatusCode;
+use common_telemetry::timer;
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use session::context::UserInfo;
+
+use crate::http::commit_token::HttpCommitToken;
+use crate::http::{query_context_from_db, ApiState, JsonResponse};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchQueryOptions {
+    /// Max number of queries of a batch running concurrently.
+    pub max_concurrency: usize,
+    /// Max number of queries in a batch.
+    pub max_queries: usize,
+}
+
+impl Default for BatchQueryOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 8,
+            max_queries: 100,
+        }
+    }
+}
+
+/// A query in the batch.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BatchQuery {
+    pub sql: String,
+    /// Database of the query, overrides the `db` of the batch.
+    pub db: Option<String>,
+    /// Timeout of the query in milliseconds.
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BatchQueryParams {
+    /// Default database of the queries.
+    pub db: Option<String>,
+    /// Deadline of the whole batch in milliseconds. Queries still running or not started
+    /// when it's exceeded are cancelled.
+    pub timeout_ms: Option<u64>,
+}
+
+/// Result of a query in the batch, which is the same as the response of `/sql` for the query.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BatchQueryResult {
+    #[serde(flatten)]
+    response: JsonResponse,
+    /// Whether the query is cancelled by the deadline of the batch.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    cancelled: bool,
+}
+
+impl BatchQueryResult {
+    pub fn response(&self) -> &JsonResponse {
+        &self.response
+    }
+
+    pub fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BatchQueryResponse {
+    code: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Results of the queries, in the order of the queries in the request.
+    results: Vec<BatchQueryResult>,
+    execution_time_ms: u128,
+}
+
+impl BatchQueryResponse {
+    pub fn success(&self) -> bool {
+        self.code == (StatusCode::Success as u32)
+    }
+
+    pub fn error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    pub fn results(&self) -> &[BatchQueryResult] {
+        &self.results
+    }
+}
+
+/// Handler to execute a JSON array of independent sql queries. One query's failure doesn't
+/// affect the others.
+#[axum_macros::debug_handler]
+pub async fn sql_batch(
+    State(state): State<ApiState>,
+    Query(params): Query<BatchQueryParams>,
+    Extension(user_info): Extension<UserInfo>,
+    Extension(commit_token): Extension<HttpCommitToken>,
+    Json(queries): Json<Vec<BatchQuery>>,
+) -> Json<BatchQueryResponse> {
+    let _timer = timer!(crate::metrics::METRIC_HTTP_SQL_BATCH_ELAPSED);
+
+    let start = Instant::now();
+    let options = &state.batch_query_options;
+    if queries.len() > options.max_queries {
+        return Json(BatchQueryResponse {
+            code: StatusCode::InvalidArguments as u32,
+            error: Some(format!(
+                "Too many queries in a batch, {} > {}.",
+                queries.len(),
+                options.max_queries
+            )),
+            results: vec![],
+            execution_time_ms: start.elapsed().as_millis(),
+        });
+    }
+    metrics::histogram!(
+        crate::metrics::METRIC_HTTP_SQL_BATCH_SIZE,
+        queries.len() as f64
+    );
+
+    let deadline = params
+        .timeout_ms
+        .map(|timeout_ms| tokio::time::Instant::now() + Duration::from_millis(timeout_ms));
+    let token = commit_token.get();
+    let outputs = futures::stream::iter(queries)
+        .map(|query| {
+            let db = query.db.clone().or_else(|| params.db.clone());
+            let execute = execute_query(&state, query, db, &user_info, &token);
+            async move {
+                let Some(deadline) = deadline else { return execute.await };
+                tokio::time::timeout_at(deadline, execute)
+                    .await
+                    .unwrap_or_else(|_| {
+                        let result = BatchQueryResult {
+                            response: JsonResponse::with_error(
+                                "Query is cancelled by the deadline of the batch.".to_string(),
+                                StatusCode::Cancelled,
+                            ),
+                            cancelled: true,
+                        };
+                        (result, CommitToken::default())
+                    })
+            }
+        })
+        .buffered(options.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut token = token;
+    let mut results = Vec::with_capacity(outputs.len());
+    for (result, query_token) in outputs {
+        token.merge(&query_token);
+        results.push(result);
+    }
+    commit_token.set(token);
+
+    Json(BatchQueryResponse {
+        code: StatusCode::Success as u32,
+        error: None,
+        results,
+        execution_time_ms: start.elapsed().as_millis(),
+    })
+}
+
+pub(crate) fn sql_batch_docs(op: TransformOperation) -> TransformOperation {
+    op.response::<200, Json<BatchQueryResponse>>()
+}
+
+/// Executes a query of the batch, returning its result and the commit token of its writes.
+async fn execute_query(
+    state: &ApiState,
+    query: BatchQuery,
+    db: Option<String>,
+    user_info: &UserInfo,
+    commit_token: &CommitToken,
+) -> (BatchQueryResult, CommitToken) {
+    let start = Instant::now();
+    let execute = async {
+        let query_ctx = match query_context_from_db(state, db, user_info).await {
+            Ok(query_ctx) => query_ctx,
+            Err(resp) => return (resp, CommitToken::default()),
+        };
+        query_ctx.merge_commit_token(commit_token);
+        let outputs = state
+            .sql_handler
+            .do_query(&query.sql, query_ctx.clone())
+            .await;
+        let resp = JsonResponse::from_output(outputs).await;
+        (resp, query_ctx.commit_token())
+    };
+
+    let (response, token) = match query.timeout_ms {
+        Some(timeout_ms) => tokio::time::timeout(Duration::from_millis(timeout_ms), execute)
+            .await
+            .unwrap_or_else(|_| {
+                let resp = JsonResponse::with_error(
+                    format!("Query timeout after {timeout_ms}ms."),
+                    StatusCode::Cancelled,
+                );
+                (resp, CommitToken::default())
+            }),
+        None => execute.await,
+    };
+    let result = BatchQueryResult {
+        response: response.with_execution_time(start.elapsed().as_millis()),
+        cancelled: false,
+    };
+    (result, token)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use common_query::Output;
+    use datatypes::schema::Schema;
+    use query::parser::PromQuery;
+    use session::context::QueryContextRef;
+    use sql::statements::statement::Statement;
+
+    use super::*;
+    use crate::error::{self, Result};
+    use crate::http::JsonOutput;
+    use crate::query_handler::sql::SqlQueryHandler;
+
+    /// Returns the number in `select <n>` as affected rows, sleeps `n` millis for `sleep <n>`
+    /// and fails other queries.
+    struct MockSqlQueryHandler;
+
+    #[async_trait]
+    impl SqlQueryHandler for MockSqlQueryHandler {
+        type Error = error::Error;
+
+        async fn do_query(&self, query: &str, _: QueryContextRef) -> Vec<Result<Output>> {
+            let output = match query.split_once(' ') {
+                Some(("select", n)) => Ok(Output::AffectedRows(n.parse().unwrap())),
+                Some(("sleep", n)) => {
+                    tokio::time::sleep(Duration::from_millis(n.parse().unwrap())).await;
+                    Ok(Output::AffectedRows(0))
+                }
+                _ => error::InvalidQuerySnafu {
+                    reason: format!("syntax error: {query}"),
+                }
+                .fail(),
+            };
+            vec![output]
+        }
+
+        async fn do_promql_query(&self, _: &PromQuery, _: QueryContextRef) -> Vec<Result<Output>> {
+            unimplemented!()
+        }
+
+        async fn do_describe(&self, _: Statement, _: QueryContextRef) -> Result<Option<Schema>> {
+            unimplemented!()
+        }
+
+        async fn is_valid_schema(&self, _: &str, _: &str) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    async fn run_batch(queries: Vec<BatchQuery>, timeout_ms: Option<u64>) -> BatchQueryResponse {
+        let state = ApiState {
+            sql_handler: Arc::new(MockSqlQueryHandler),
+            script_handler: None,
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
+        };
+        let params = BatchQueryParams {
+            db: None,
+            timeout_ms,
+        };
+        let Json(resp) = sql_batch(
+            State(state),
+            Query(params),
+            Extension(UserInfo::default()),
+            Extension(HttpCommitToken::default()),
+            Json(queries),
+        )
+        .await;
+        resp
+    }
+
+    fn new_query(sql: &str, timeout_ms: Option<u64>) -> BatchQuery {
+        BatchQuery {
+            sql: sql.to_string(),
+            db: None,
+            timeout_ms,
+        }
+    }
+
+    fn affected_rows(result: &BatchQueryResult) -> usize {
+        match result.response().output().unwrap() {
+            [JsonOutput::AffectedRows(rows)] => *rows,
+            output => panic!("unexpected output {output:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_failed_query() {
+        let queries = vec![
+            new_query("select 1", None),
+            new_query("selec 2", None),
+            new_query("select 3", None),
+        ];
+        let resp = run_batch(queries, None).await;
+        assert!(resp.success());
+
+        let results = resp.results();
+        assert_eq!(3, results.len());
+        assert_eq!(1, affected_rows(&results[0]));
+        assert!(!results[1].response().success());
+        assert!(results[1]
+            .response()
+            .error()
+            .unwrap()
+            .contains("syntax error: selec 2"));
+        assert!(!results[1].cancelled());
+        assert_eq!(3, affected_rows(&results[2]));
+    }
+
+    #[tokio::test]
+    async fn test_batch_deadline() {
+        let queries = vec![
+            new_query("select 1", None),
+            new_query("sleep 10000", None),
+            new_query("sleep 10", None),
+            new_query("sleep 10000", Some(10)),
+        ];
+        let resp = run_batch(queries, Some(500)).await;
+        assert!(resp.success());
+
+        let results = resp.results();
+        assert_eq!(1, affected_rows(&results[0]));
+        assert!(results[1].cancelled());
+        assert_eq!(StatusCode::Cancelled as u32, results[1].response().code());
+        assert_eq!(0, affected_rows(&results[2]));
+        // Timed out by its own timeout, before the deadline of the batch.
+        assert!(!results[3].cancelled());
+        assert!(results[3]
+            .response()
+            .error()
+            .unwrap()
+            .contains("Query timeout after 10ms"));
+    }
+
+    #[tokio::test]
+    async fn test_too_many_queries() {
+        let queries = (0..101).map(|_| new_query("select 1", None)).collect();
+        let resp = run_batch(queries, None).await;
+        assert!(!resp.success());
+        assert!(resp.results().is_empty());
+    }
+}
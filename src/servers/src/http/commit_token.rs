@@ -0,0 +1,116 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Mutex};
+
+use axum::http::{HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Json, Response};
+use common_base::commit_token::{CommitToken, COMMIT_TOKEN_HEADER};
+use common_error::status_code::StatusCode;
+
+use crate::http::JsonResponse;
+
+/// Commit token of a HTTP request, read from the request header by [track_commit_token] and
+/// updated by the handler with the token of its writes.
+#[derive(Clone, Debug, Default)]
+pub struct HttpCommitToken(Arc<Mutex<CommitToken>>);
+
+impl HttpCommitToken {
+    pub fn get(&self) -> CommitToken {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, token: CommitToken) {
+        *self.0.lock().unwrap() = token;
+    }
+}
+
+/// Middleware passing the commit token in the request header to the handler as a
+/// [HttpCommitToken] extension, and returning the updated token in the response header.
+pub async fn track_commit_token<B>(mut request: Request<B>, next: Next<B>) -> Response {
+    let token = match request.headers().get(COMMIT_TOKEN_HEADER) {
+        Some(value) => match value
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(str::parse)
+        {
+            Ok(token) => token,
+            Err(e) => {
+                return Json(JsonResponse::with_error(
+                    format!("Invalid commit token: {e}"),
+                    StatusCode::InvalidArguments,
+                ))
+                .into_response();
+            }
+        },
+        None => CommitToken::default(),
+    };
+
+    let commit_token = HttpCommitToken(Arc::new(Mutex::new(token)));
+    let _ = request.extensions_mut().insert(commit_token.clone());
+
+    let mut response = next.run(request).await;
+    let token = commit_token.get();
+    if !token.is_empty() {
+        // The formatted token only contains digits and separators, so it's always valid.
+        if let Ok(value) = HeaderValue::from_str(&token.to_string()) {
+            let _ = response.headers_mut().insert(COMMIT_TOKEN_HEADER, value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::routing::get;
+    use axum::{middleware, Extension, Router};
+    use axum_test_helper::TestClient;
+
+    use super::*;
+
+    async fn write(Extension(commit_token): Extension<HttpCommitToken>) -> &'static str {
+        let mut token = commit_token.get();
+        token.record(1, 2);
+        commit_token.set(token);
+        "ok"
+    }
+
+    #[tokio::test]
+    async fn test_track_commit_token() {
+        let app = Router::new()
+            .route("/write", get(write))
+            .layer(middleware::from_fn(track_commit_token));
+        let client = TestClient::new(app);
+
+        let res = client.get("/write").send().await;
+        assert_eq!("1:2", res.headers().get(COMMIT_TOKEN_HEADER).unwrap());
+
+        let res = client
+            .get("/write")
+            .header(COMMIT_TOKEN_HEADER, "1:1,3:4")
+            .send()
+            .await;
+        assert_eq!("1:2,3:4", res.headers().get(COMMIT_TOKEN_HEADER).unwrap());
+
+        let res = client
+            .get("/write")
+            .header(COMMIT_TOKEN_HEADER, "1")
+            .send()
+            .await;
+        assert!(res.headers().get(COMMIT_TOKEN_HEADER).is_none());
+        let body = serde_json::from_str::<JsonResponse>(&res.text().await).unwrap();
+        assert!(!body.success());
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exports the records of a query as csv.
+
+use common_error::prelude::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_query::Output;
+use common_recordbatch::{util, RecordBatch};
+use datatypes::float_format::{format_f32, format_f64, FloatPrecision};
+use datatypes::value::Value;
+
+use crate::error::Result;
+use crate::http::JsonResponse;
+
+pub const CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Writes the records of the only output to csv, with a header of the column names.
+pub(crate) async fn from_output(
+    mut outputs: Vec<Result<Output>>,
+    precision: FloatPrecision,
+) -> std::result::Result<String, JsonResponse> {
+    if outputs.len() != 1 {
+        return Err(JsonResponse::with_error(
+            format!(
+                "csv exports the output of one statement, got {}.",
+                outputs.len()
+            ),
+            StatusCode::InvalidArguments,
+        ));
+    }
+    // Safety: there is exactly one output.
+    let recordbatches = match outputs.pop().unwrap() {
+        Ok(Output::Stream(stream)) => util::collect(stream).await.map_err(|e| {
+            JsonResponse::with_error(format!("Recordbatch error: {e}"), e.status_code())
+        })?,
+        Ok(Output::RecordBatches(recordbatches)) => recordbatches.take(),
+        Ok(Output::AffectedRows(_)) => {
+            return Err(JsonResponse::with_error(
+                "csv exports the records of a query, the statement returns affected rows."
+                    .to_string(),
+                StatusCode::InvalidArguments,
+            ))
+        }
+        Err(e) => {
+            return Err(JsonResponse::with_error(
+                format!("Query engine output error: {e}"),
+                e.status_code(),
+            ))
+        }
+    };
+
+    Ok(records_to_csv(&recordbatches, precision))
+}
+
+/// Writes `recordbatches` to csv, floats are written in `precision`.
+pub fn records_to_csv(recordbatches: &[RecordBatch], precision: FloatPrecision) -> String {
+    let mut csv = String::new();
+    let Some(first) = recordbatches.first() else { return csv };
+
+    let header = first
+        .schema
+        .column_schemas()
+        .iter()
+        .map(|column| escape(column.name.clone()));
+    write_line(&mut csv, header);
+    for recordbatch in recordbatches {
+        for row in recordbatch.rows() {
+            let fields = row
+                .into_iter()
+                .map(|value| escape(value_to_field(value, precision)));
+            write_line(&mut csv, fields);
+        }
+    }
+    csv
+}
+
+fn value_to_field(value: Value, precision: FloatPrecision) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Float32(v) => format_f32(v.0, precision),
+        Value::Float64(v) => format_f64(v.0, precision),
+        Value::String(v) => v.as_utf8().to_string(),
+        value => value.to_string(),
+    }
+}
+
+fn write_line(csv: &mut String, fields: impl Iterator<Item = String>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+        csv.push_str(&field);
+    }
+    csv.push_str("\r\n");
+}
+
+/// Quotes the field containing delimiters, quotes or line breaks, as RFC 4180 specifies.
+fn escape(field: String) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{Float64Vector, StringVector, VectorRef};
+
+    use super::*;
+
+    #[test]
+    fn test_records_to_csv() {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec![
+                Some("a,b"),
+                Some("say \"hi\""),
+                None,
+            ])),
+            Arc::new(Float64Vector::from(vec![Some(0.1 + 0.2), None, Some(1e20)])),
+        ];
+        let recordbatch = RecordBatch::new(schema, columns).unwrap();
+
+        assert_eq!(
+            "host,cpu\r\n\"a,b\",0.30000000000000004\r\n\"say \"\"hi\"\"\",\r\n,1e20\r\n",
+            records_to_csv(&[recordbatch.clone()], FloatPrecision::Roundtrip)
+        );
+        assert_eq!(
+            "host,cpu\r\n\"a,b\",0.3\r\n\"say \"\"hi\"\"\",\r\n,1e20\r\n",
+            records_to_csv(&[recordbatch], FloatPrecision::significant(3).unwrap())
+        );
+        assert_eq!("", records_to_csv(&[], FloatPrecision::Roundtrip));
+    }
+}
@@ -17,21 +17,44 @@ use std::time::Instant;
 
 use aide::transform::TransformOperation;
 use axum::extract::{Json, Query, State};
+use axum::http::{header, StatusCode as HttpStatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::{Extension, Form};
 use common_error::status_code::StatusCode;
+use common_query::Output;
 use common_telemetry::timer;
+use datatypes::float_format::{FloatPrecision, MAX_SIGNIFICANT_DIGITS};
 use query::parser::PromQuery;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use session::context::UserInfo;
+use session::context::{QueryContextRef, UserInfo};
+use sql::util::split_statements;
 
-use crate::http::{ApiState, JsonResponse};
+use crate::error::Result as ServerResult;
+use crate::http::commit_token::HttpCommitToken;
+use crate::http::{csv, ApiState, JsonResponse, StatementResult};
 use crate::metrics_handler::MetricsHandler;
+use crate::query_handler::{ComponentStatus, ReadinessHandlerRef};
 
 #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct SqlQuery {
     pub db: Option<String>,
     pub sql: Option<String>,
+    /// Significant digits of the floats in results, in `1..=15`. Floats are written in the
+    /// shortest representation that parses back to the same value if absent.
+    pub precision: Option<u8>,
+    /// Max number of rows returned by the `SELECT`s without a `LIMIT`, unlimited if absent.
+    /// The response has a warning if the rows are truncated.
+    pub sql_select_limit: Option<usize>,
+    /// How many invalid rows of the inserts are reported before they are rejected, `strict`
+    /// if absent to report the first one, or `lenient` to report more.
+    pub validation_mode: Option<String>,
+    /// Reads the snapshots of tables as of this time, a timestamp string or epoch millis, within
+    /// the time-travel retention of the server. Writes and DDL are not affected.
+    pub as_of: Option<String>,
+    /// Whether to go on executing the rest statements of a request with many statements after
+    /// one fails, `false` if absent to stop at the first failed one.
+    pub continue_on_error: Option<bool>,
 }
 
 /// Handler to execute sql
@@ -39,33 +62,162 @@ pub struct SqlQuery {
 pub async fn sql(
     State(state): State<ApiState>,
     Query(query_params): Query<SqlQuery>,
-    // TODO(fys): pass _user_info into query context
-    _user_info: Extension<UserInfo>,
+    Extension(user_info): Extension<UserInfo>,
+    Extension(commit_token): Extension<HttpCommitToken>,
     Form(form_params): Form<SqlQuery>,
 ) -> Json<JsonResponse> {
     let _timer = timer!(crate::metrics::METRIC_HTTP_SQL_ELAPSED);
 
-    let sql_handler = &state.sql_handler;
+    let start = Instant::now();
+    let resp = match sql_request(&state, query_params, form_params, &user_info, &commit_token).await
+    {
+        Ok(request) => {
+            let statements = split_statements(&request.sql);
+            let resp = if statements.len() > 1 {
+                execute_statements(&state, &statements, &request).await
+            } else {
+                let outputs = state
+                    .sql_handler
+                    .do_query(&request.sql, request.query_ctx.clone())
+                    .await;
+                JsonResponse::from_output_with_precision(outputs, request.precision).await
+            };
+            commit_token.set(request.query_ctx.commit_token());
+            resp.with_warnings(request.query_ctx.warnings())
+        }
+        Err(resp) => resp,
+    };
+
+    Json(resp.with_execution_time(start.elapsed().as_millis()))
+}
+
+/// Handler to execute sql and export the records of the query as csv
+#[axum_macros::debug_handler]
+pub async fn sql_csv(
+    State(state): State<ApiState>,
+    Query(query_params): Query<SqlQuery>,
+    Extension(user_info): Extension<UserInfo>,
+    Extension(commit_token): Extension<HttpCommitToken>,
+    Form(form_params): Form<SqlQuery>,
+) -> Response {
+    let _timer = timer!(crate::metrics::METRIC_HTTP_SQL_ELAPSED);
 
     let start = Instant::now();
+    let resp = match execute_sql(&state, query_params, form_params, &user_info, &commit_token).await
+    {
+        Ok((outputs, precision, _)) => csv::from_output(outputs, precision).await,
+        Err(resp) => Err(resp),
+    };
+
+    match resp {
+        Ok(csv) => ([(header::CONTENT_TYPE, csv::CONTENT_TYPE)], csv).into_response(),
+        Err(resp) => Json(resp.with_execution_time(start.elapsed().as_millis())).into_response(),
+    }
+}
+
+/// Executes the statements of a request one by one, stopping at the first failed one unless
+/// `continue_on_error` is set.
+async fn execute_statements(
+    state: &ApiState,
+    statements: &[&str],
+    request: &SqlRequest,
+) -> JsonResponse {
+    let mut results = Vec::with_capacity(statements.len());
+    for statement in statements {
+        let start = Instant::now();
+        let outputs = state
+            .sql_handler
+            .do_query(statement, request.query_ctx.clone())
+            .await;
+        let response = JsonResponse::from_output_with_precision(outputs, request.precision)
+            .await
+            .with_execution_time(start.elapsed().as_millis());
+        let failed = !response.success();
+        results.push(StatementResult::new(statement.to_string(), response));
+        if failed && !request.continue_on_error {
+            break;
+        }
+    }
+    JsonResponse::with_results(results)
+}
+
+/// Executes the sql of the query or the form, returns the outputs, the float precision
+/// to write them in and the context of the query.
+async fn execute_sql(
+    state: &ApiState,
+    query_params: SqlQuery,
+    form_params: SqlQuery,
+    user_info: &UserInfo,
+    commit_token: &HttpCommitToken,
+) -> Result<(Vec<ServerResult<Output>>, FloatPrecision, QueryContextRef), JsonResponse> {
+    let request = sql_request(state, query_params, form_params, user_info, commit_token).await?;
+    let outputs = state
+        .sql_handler
+        .do_query(&request.sql, request.query_ctx.clone())
+        .await;
+    commit_token.set(request.query_ctx.commit_token());
+    Ok((outputs, request.precision, request.query_ctx))
+}
+
+/// A sql request of the query or the form, ready to execute.
+struct SqlRequest {
+    sql: String,
+    /// Float precision to write the outputs in.
+    precision: FloatPrecision,
+    query_ctx: QueryContextRef,
+    continue_on_error: bool,
+}
+
+/// Parses the sql request of the query or the form and creates its context.
+async fn sql_request(
+    state: &ApiState,
+    query_params: SqlQuery,
+    form_params: SqlQuery,
+    user_info: &UserInfo,
+    commit_token: &HttpCommitToken,
+) -> Result<SqlRequest, JsonResponse> {
     let sql = query_params.sql.or(form_params.sql);
     let db = query_params.db.or(form_params.db);
+    let sql_select_limit = query_params
+        .sql_select_limit
+        .or(form_params.sql_select_limit);
+    let precision = match query_params.precision.or(form_params.precision) {
+        Some(digits) => FloatPrecision::significant(digits).ok_or_else(|| {
+            JsonResponse::with_error(
+                format!("precision must be between 1 and {MAX_SIGNIFICANT_DIGITS}, got {digits}."),
+                StatusCode::InvalidArguments,
+            )
+        })?,
+        None => FloatPrecision::Roundtrip,
+    };
+    let validation_mode = query_params.validation_mode.or(form_params.validation_mode);
+    let validation_mode = crate::http::parse_validation_mode(validation_mode.as_deref())
+        .map_err(|e| JsonResponse::with_error(e.to_string(), StatusCode::InvalidArguments))?;
+    let as_of = query_params.as_of.or(form_params.as_of);
+    let as_of = crate::http::parse_as_of(as_of.as_deref())
+        .map_err(|e| JsonResponse::with_error(e.to_string(), StatusCode::InvalidArguments))?;
+    let continue_on_error = query_params
+        .continue_on_error
+        .or(form_params.continue_on_error)
+        .unwrap_or(false);
 
-    let resp = if let Some(sql) = &sql {
-        match crate::http::query_context_from_db(sql_handler.clone(), db).await {
-            Ok(query_ctx) => {
-                JsonResponse::from_output(sql_handler.do_query(sql, query_ctx).await).await
-            }
-            Err(resp) => resp,
-        }
-    } else {
-        JsonResponse::with_error(
+    let Some(sql) = sql else {
+        return Err(JsonResponse::with_error(
             "sql parameter is required.".to_string(),
             StatusCode::InvalidArguments,
-        )
+        ));
     };
-
-    Json(resp.with_execution_time(start.elapsed().as_millis()))
+    let query_ctx = crate::http::query_context_from_db(state, db, user_info).await?;
+    query_ctx.merge_commit_token(&commit_token.get());
+    query_ctx.set_select_limit(sql_select_limit);
+    query_ctx.set_validation_mode(validation_mode);
+    query_ctx.set_as_of(as_of);
+    Ok(SqlRequest {
+        sql,
+        precision,
+        query_ctx,
+        continue_on_error,
+    })
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -93,8 +245,7 @@ impl From<PromqlQuery> for PromQuery {
 pub async fn promql(
     State(state): State<ApiState>,
     Query(params): Query<PromqlQuery>,
-    // TODO(fys): pass _user_info into query context
-    _user_info: Extension<UserInfo>,
+    Extension(user_info): Extension<UserInfo>,
 ) -> Json<JsonResponse> {
     let _timer = timer!(crate::metrics::METRIC_HTTP_PROMQL_ELAPSED);
 
@@ -102,7 +253,7 @@ pub async fn promql(
     let exec_start = Instant::now();
     let db = params.db.clone();
     let prom_query = params.into();
-    let resp = match super::query_context_from_db(sql_handler.clone(), db).await {
+    let resp = match super::query_context_from_db(&state, db, &user_info).await {
         Ok(query_ctx) => {
             JsonResponse::from_output(sql_handler.do_promql_query(&prom_query, query_ctx).await)
                 .await
@@ -129,13 +280,42 @@ pub async fn metrics(
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct HealthQuery {}
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
-pub struct HealthResponse {}
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct HealthResponse {
+    /// Status of the components, only reported by the readiness check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<ComponentStatus>,
+    /// The component the node is waiting for to be ready.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unready_component: Option<String>,
+}
 
 /// Handler to export healthy check
 ///
 /// Currently simply return status "200 OK" (default) with an empty json payload "{}"
 #[axum_macros::debug_handler]
 pub async fn health(Query(_params): Query<HealthQuery>) -> Json<HealthResponse> {
-    Json(HealthResponse {})
+    Json(HealthResponse::default())
+}
+
+/// Handler of readiness check, which responds "503 Service Unavailable" naming the component
+/// not ready yet, like the catalog manager still opening the tables.
+#[axum_macros::debug_handler]
+pub async fn ready(
+    State(handler): State<ReadinessHandlerRef>,
+) -> (HttpStatusCode, Json<HealthResponse>) {
+    let components = handler.component_status().await;
+    let unready_component = components.unready_component().map(str::to_string);
+    let status = if unready_component.is_some() {
+        HttpStatusCode::SERVICE_UNAVAILABLE
+    } else {
+        HttpStatusCode::OK
+    };
+    (
+        status,
+        Json(HealthResponse {
+            components: Some(components),
+            unready_component,
+        }),
+    )
 }
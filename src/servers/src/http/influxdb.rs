@@ -17,12 +17,15 @@ use std::sync::Arc;
 
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
 use common_grpc::writer::Precision;
 use session::context::QueryContext;
+use snafu::OptionExt;
 
-use crate::error::{Result, TimePrecisionSnafu};
+use crate::error::{InvalidQuerySnafu, Result, TimePrecisionSnafu};
+use crate::http::parse_validation_mode;
 use crate::influxdb::InfluxdbRequest;
 use crate::parse_catalog_and_schema_from_client_database_name;
 use crate::query_handler::InfluxdbLineProtocolHandlerRef;
@@ -44,21 +47,40 @@ pub async fn influxdb_write(
     State(handler): State<InfluxdbLineProtocolHandlerRef>,
     Query(mut params): Query<HashMap<String, String>>,
     lines: String,
-) -> Result<impl IntoResponse> {
+) -> Result<Response> {
     let db = params
         .remove("db")
         .unwrap_or_else(|| DEFAULT_SCHEMA_NAME.to_string());
     let (catalog, schema) = parse_catalog_and_schema_from_client_database_name(&db);
     let ctx = Arc::new(QueryContext::with(catalog, schema));
+    ctx.set_validation_mode(parse_validation_mode(
+        params.get("validation_mode").map(String::as_str),
+    )?);
 
     let precision = params
         .get("precision")
         .map(|val| parse_time_precision(val))
         .transpose()?;
+    let dry_run = params
+        .get("dry_run")
+        .map(|val| parse_dry_run(val))
+        .transpose()?
+        .unwrap_or(false);
     let request = InfluxdbRequest { precision, lines };
 
+    if dry_run {
+        let dry_runs = handler.dry_run(&request, ctx).await?;
+        return Ok(Json(dry_runs).into_response());
+    }
+
     handler.exec(&request, ctx).await?;
-    Ok((StatusCode::NO_CONTENT, ()))
+    Ok((StatusCode::NO_CONTENT, ()).into_response())
+}
+
+fn parse_dry_run(value: &str) -> Result<bool> {
+    value.parse().ok().context(InvalidQuerySnafu {
+        reason: format!("invalid dry_run: {value}, expecting true or false"),
+    })
 }
 
 fn parse_time_precision(value: &str) -> Result<Precision> {
@@ -80,7 +102,7 @@ fn parse_time_precision(value: &str) -> Result<Precision> {
 mod tests {
     use common_grpc::writer::Precision;
 
-    use crate::http::influxdb::parse_time_precision;
+    use crate::http::influxdb::{parse_dry_run, parse_time_precision};
 
     #[test]
     fn test_parse_time_precision() {
@@ -92,4 +114,11 @@ mod tests {
         assert_eq!(Precision::Hour, parse_time_precision("h").unwrap());
         assert!(parse_time_precision("unknown").is_err());
     }
+
+    #[test]
+    fn test_parse_dry_run() {
+        assert!(parse_dry_run("true").unwrap());
+        assert!(!parse_dry_run("false").unwrap());
+        assert!(parse_dry_run("1").is_err());
+    }
 }
@@ -0,0 +1,42 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::extract::{Json, Path, State};
+use snafu::OptionExt;
+
+use crate::error::{JobNotFoundSnafu, Result};
+use crate::query_handler::{JobHandlerRef, JobStatus};
+
+/// Handler to get the status of an asynchronous job.
+#[axum_macros::debug_handler]
+pub async fn job(
+    State(handler): State<JobHandlerRef>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobStatus>> {
+    let status = handler.job(id).await?.context(JobNotFoundSnafu { id })?;
+    Ok(Json(status))
+}
+
+/// Handler to cancel an asynchronous job, responds the status of the job once it stops.
+#[axum_macros::debug_handler]
+pub async fn cancel_job(
+    State(handler): State<JobHandlerRef>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobStatus>> {
+    let status = handler
+        .cancel_job(id)
+        .await?
+        .context(JobNotFoundSnafu { id })?;
+    Ok(Json(status))
+}
@@ -0,0 +1,125 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use axum::extract::{Json, Query, State};
+use common_catalog::consts::DEFAULT_SCHEMA_NAME;
+use common_grpc_expr::dry_run::InsertDryRun;
+use common_time::util::current_time_millis;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use session::context::QueryContext;
+
+use crate::error::Result;
+use crate::json_ingest::{documents_to_insert_request, DocumentError, JsonIngestOptions};
+use crate::parse_catalog_and_schema_from_client_database_name;
+use crate::query_handler::JsonIngestHandlerRef;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct JsonIngestParams {
+    pub db: Option<String>,
+    pub table: String,
+    /// Field of the time of the documents, defaults to `ts`.
+    pub time_field: Option<String>,
+    /// Separator joining the keys of nested fields, defaults to `_`.
+    pub separator: Option<String>,
+    /// Max depth of the objects to flatten, defaults to 1.
+    pub max_depth: Option<usize>,
+    /// Comma separated fields stored as tags.
+    pub tags: Option<String>,
+    /// Whether to only validate the documents and report what the insertion would do.
+    pub dry_run: Option<bool>,
+}
+
+impl JsonIngestParams {
+    fn options(&self) -> JsonIngestOptions {
+        let default = JsonIngestOptions::default();
+        JsonIngestOptions {
+            separator: self.separator.clone().unwrap_or(default.separator),
+            max_depth: self.max_depth.unwrap_or(default.max_depth),
+            time_field: self.time_field.clone().unwrap_or(default.time_field),
+            tags: self
+                .tags
+                .as_deref()
+                .map(|tags| {
+                    tags.split(',')
+                        .map(|tag| tag.trim().to_string())
+                        .filter(|tag| !tag.is_empty())
+                        .collect()
+                })
+                .unwrap_or_else(HashSet::new),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonIngestResponse {
+    /// Number of documents ingested.
+    pub ingested: usize,
+    /// Errors of the documents not ingested.
+    pub errors: Vec<DocumentError>,
+    /// Result of the dry run of the insertion, if it's a dry run, in which case no document is
+    /// ingested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<InsertDryRun>,
+}
+
+/// Handler to ingest a JSON array of documents into a table, flattening the fields of the
+/// documents into columns.
+#[axum_macros::debug_handler]
+pub async fn ingest_json(
+    State(handler): State<JsonIngestHandlerRef>,
+    Query(params): Query<JsonIngestParams>,
+    Json(documents): Json<Vec<Value>>,
+) -> Result<Json<JsonIngestResponse>> {
+    let db = params.db.as_deref().unwrap_or(DEFAULT_SCHEMA_NAME);
+    let (catalog, schema) = parse_catalog_and_schema_from_client_database_name(db);
+    let ctx = Arc::new(QueryContext::with(catalog, schema));
+
+    let table = handler.table(&params.table, ctx.clone()).await?;
+    let table_info = table.as_ref().map(|table| table.table_info());
+    let (request, errors) = documents_to_insert_request(
+        &params.table,
+        &documents,
+        &params.options(),
+        table_info.as_ref().map(|table_info| &table_info.meta),
+        current_time_millis(),
+    )?;
+    let Some(request) = request else {
+        return Ok(Json(JsonIngestResponse {
+            ingested: 0,
+            errors,
+            dry_run: None,
+        }));
+    };
+
+    if params.dry_run.unwrap_or(false) {
+        let dry_run = handler.dry_run(request, ctx).await?;
+        return Ok(Json(JsonIngestResponse {
+            ingested: 0,
+            errors,
+            dry_run: Some(dry_run),
+        }));
+    }
+
+    let ingested = request.row_count as usize;
+    handler.ingest(request, ctx).await?;
+    Ok(Json(JsonIngestResponse {
+        ingested,
+        errors,
+        dry_run: None,
+    }))
+}
@@ -19,12 +19,14 @@ use axum::extract::{Query, RawBody, State};
 use axum::http::StatusCode as HttpStatusCode;
 use axum::Json;
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
+use common_grpc_expr::validation::find_row_errors;
 use hyper::Body;
 use serde::{Deserialize, Serialize};
 use session::context::QueryContext;
 use snafu::ResultExt;
 
 use crate::error::{self, Error, Result};
+use crate::http::parse_validation_mode;
 use crate::opentsdb::codec::DataPoint;
 use crate::parse_catalog_and_schema_from_client_database_name;
 use crate::query_handler::OpentsdbProtocolHandlerRef;
@@ -92,13 +94,20 @@ pub async fn put(
 
     let (catalog, schema) = parse_catalog_and_schema_from_client_database_name(db);
     let ctx = Arc::new(QueryContext::with(catalog, schema));
+    ctx.set_validation_mode(parse_validation_mode(
+        params.get("validation_mode").map(String::as_str),
+    )?);
 
     let data_points = parse_data_points(body).await?;
 
     let response = if !summary && !details {
         for data_point in data_points.into_iter() {
             if let Err(e) = opentsdb_handler.exec(&data_point.into(), ctx.clone()).await {
-                // Not debugging purpose, failed fast.
+                // Not debugging purpose, failed fast. The invalid rows are reported as they
+                // are, so the client knows which values are rejected.
+                if find_row_errors(&e).is_some() {
+                    return Err(e);
+                }
                 return error::InternalSnafu {
                     err_msg: e.to_string(),
                 }
@@ -12,23 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use api::prometheus::remote::{ReadRequest, WriteRequest};
 use axum::extract::{Query, RawBody, State};
 use axum::http::{header, StatusCode};
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
+use common_error::prelude::ErrorExt;
+use common_error::status_code::StatusCode as ErrorCode;
 use hyper::Body;
+use metrics::{decrement_gauge, increment_counter, increment_gauge};
 use prost::Message;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use session::context::QueryContext;
 use snafu::prelude::*;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
-use crate::error::{self, Result};
+use crate::error::{self, Error, Result};
+use crate::http::parse_validation_mode;
+use crate::metrics::{
+    LABEL_DB, LABEL_REASON, METRIC_PROM_WRITE_QUEUE_DEPTH, METRIC_PROM_WRITE_REJECTED,
+};
 use crate::parse_catalog_and_schema_from_client_database_name;
-use crate::prometheus::snappy_decompress;
+use crate::prometheus::{drop_nan_samples, snappy_decompress};
 use crate::query_handler::{PrometheusProtocolHandlerRef, PrometheusResponse};
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -44,24 +55,302 @@ impl Default for DatabaseQuery {
     }
 }
 
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RemoteWriteQuery {
+    pub db: Option<String>,
+    /// Drops samples with NaN values, e.g. the stale markers, instead of storing them.
+    #[serde(default)]
+    pub drop_nan: bool,
+    /// How many invalid rows of the writes are reported before they are rejected, `strict` if
+    /// absent.
+    pub validation_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteWriteOptions {
+    /// Max number of remote writes of a database waiting for the running ones, before
+    /// rejecting more writes. 0 disables the queue.
+    pub queue_size: usize,
+    /// Max number of remote writes of a database running concurrently, only applies if the
+    /// queue is enabled.
+    pub max_concurrency: usize,
+    /// Min delay for Prometheus to retry the writes rejected by backpressure, which grows
+    /// with the time to drain the writes queued.
+    #[serde(with = "humantime_serde")]
+    pub retry_after: Duration,
+}
+
+impl Default for RemoteWriteOptions {
+    fn default() -> Self {
+        Self {
+            queue_size: 0,
+            max_concurrency: 4,
+            retry_after: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Bounded queues of the remote writes of every database, absorbing short spikes of writes.
+#[derive(Debug)]
+pub struct RemoteWriteQueue {
+    options: RemoteWriteOptions,
+    /// Queues of the databases with writes queued or running, a queue is removed once it's
+    /// idle.
+    tenants: Mutex<HashMap<String, Arc<TenantQueue>>>,
+}
+
+#[derive(Debug)]
+struct TenantQueue {
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+    /// Moving average of the time the writes run, in milliseconds.
+    write_millis: AtomicU64,
+}
+
+impl TenantQueue {
+    fn record_write(&self, elapsed: Duration) {
+        let elapsed = elapsed.as_millis() as u64;
+        // The closure always returns `Some`.
+        let _ = self
+            .write_millis
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |avg| {
+                Some(if avg == 0 {
+                    elapsed
+                } else {
+                    (avg * 7 + elapsed) / 8
+                })
+            });
+    }
+}
+
+/// Turn of a remote write to run, released on drop.
+#[derive(Debug)]
+pub struct WritePermit<'a> {
+    queue: &'a RemoteWriteQueue,
+    db: &'a str,
+    tenant: Option<Arc<TenantQueue>>,
+    permit: Option<OwnedSemaphorePermit>,
+    acquired_at: Instant,
+}
+
+impl<'a> WritePermit<'a> {
+    fn new(
+        queue: &'a RemoteWriteQueue,
+        db: &'a str,
+        tenant: Option<Arc<TenantQueue>>,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Self {
+        Self {
+            queue,
+            db,
+            tenant,
+            permit,
+            acquired_at: Instant::now(),
+        }
+    }
+}
+
+impl Drop for WritePermit<'_> {
+    fn drop(&mut self) {
+        let Some(tenant) = self.tenant.take() else { return };
+        if let Some(permit) = self.permit.take() {
+            drop(permit);
+            tenant.record_write(self.acquired_at.elapsed());
+        }
+        self.queue.release(self.db, tenant);
+    }
+}
+
+impl RemoteWriteQueue {
+    pub fn new(options: RemoteWriteOptions) -> Self {
+        Self {
+            options,
+            tenants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Waits for the turn of a remote write to the database `db`. Returns the number of the
+    /// writes queued if the queue is full.
+    pub async fn acquire<'a>(&'a self, db: &'a str) -> std::result::Result<WritePermit<'a>, usize> {
+        if self.options.queue_size == 0 {
+            return Ok(WritePermit::new(self, db, None, None));
+        }
+
+        let tenant = self.tenant(db);
+        let permits = tenant.permits.clone();
+        if let Ok(permit) = permits.clone().try_acquire_owned() {
+            return Ok(WritePermit::new(self, db, Some(tenant), Some(permit)));
+        }
+
+        // Releases the queue of the database if the write is rejected or cancelled while
+        // waiting, after `dequeue` is dropped.
+        let mut write_permit = WritePermit::new(self, db, Some(tenant.clone()), None);
+        let dequeue = Dequeue::enqueue(tenant, db, self.options.queue_size)?;
+        // The semaphore is never closed.
+        let permit = permits.acquire_owned().await.unwrap();
+        drop(dequeue);
+        write_permit.permit = Some(permit);
+        write_permit.acquired_at = Instant::now();
+        Ok(write_permit)
+    }
+
+    /// Seconds for Prometheus to wait before retrying the writes to `db`, the estimated time
+    /// to drain the writes queued by the average time of the writes, at least the configured
+    /// `retry_after`.
+    pub fn retry_after_secs(&self, db: &str) -> u64 {
+        let (queued, write_millis) = self
+            .tenants
+            .lock()
+            .unwrap()
+            .get(db)
+            .map(|tenant| {
+                (
+                    tenant.queued.load(Ordering::Relaxed),
+                    tenant.write_millis.load(Ordering::Relaxed),
+                )
+            })
+            .unwrap_or_default();
+        // The queued writes run `max_concurrency` at a time, after the running ones.
+        let rounds = 1 + queued / self.options.max_concurrency.max(1);
+        let drain = Duration::from_millis(write_millis * rounds as u64);
+        let retry_after = drain.max(self.options.retry_after);
+        (retry_after.as_secs_f64().ceil() as u64).max(1)
+    }
+
+    /// Number of the databases with remote writes queued or running.
+    pub fn tenants(&self) -> usize {
+        self.tenants.lock().unwrap().len()
+    }
+
+    fn tenant(&self, db: &str) -> Arc<TenantQueue> {
+        self.tenants
+            .lock()
+            .unwrap()
+            .entry(db.to_string())
+            .or_insert_with(|| {
+                Arc::new(TenantQueue {
+                    permits: Arc::new(Semaphore::new(self.options.max_concurrency.max(1))),
+                    queued: AtomicUsize::new(0),
+                    write_millis: AtomicU64::new(0),
+                })
+            })
+            .clone()
+    }
+
+    /// Removes the queue of `db` if no other write holds it. The queues are only handed out
+    /// under the lock, so no write gets the queue once it's removed.
+    fn release(&self, db: &str, tenant: Arc<TenantQueue>) {
+        let mut tenants = self.tenants.lock().unwrap();
+        // Held by the map and `tenant` only.
+        if Arc::strong_count(&tenant) == 2 {
+            let _ = tenants.remove(db);
+        }
+    }
+}
+
+/// Removes a write from the queue on drop, even if the write is cancelled while waiting.
+struct Dequeue<'a> {
+    tenant: Arc<TenantQueue>,
+    db: &'a str,
+}
+
+impl<'a> Dequeue<'a> {
+    /// Queues a write unless the queue is full, in which case returns the number of the
+    /// writes queued.
+    fn enqueue(
+        tenant: Arc<TenantQueue>,
+        db: &'a str,
+        queue_size: usize,
+    ) -> std::result::Result<Self, usize> {
+        let queued = tenant.queued.fetch_add(1, Ordering::Relaxed);
+        if queued >= queue_size {
+            let _ = tenant.queued.fetch_sub(1, Ordering::Relaxed);
+            return Err(queued);
+        }
+        increment_gauge!(METRIC_PROM_WRITE_QUEUE_DEPTH, 1.0, LABEL_DB => db.to_string());
+        Ok(Self { tenant, db })
+    }
+}
+
+impl Drop for Dequeue<'_> {
+    fn drop(&mut self) {
+        let _ = self.tenant.queued.fetch_sub(1, Ordering::Relaxed);
+        decrement_gauge!(METRIC_PROM_WRITE_QUEUE_DEPTH, 1.0, LABEL_DB => self.db.to_string());
+    }
+}
+
+#[derive(Clone)]
+pub struct PromState {
+    pub handler: PrometheusProtocolHandlerRef,
+    pub write_queue: Arc<RemoteWriteQueue>,
+}
+
 #[axum_macros::debug_handler]
 pub async fn remote_write(
-    State(handler): State<PrometheusProtocolHandlerRef>,
-    Query(params): Query<DatabaseQuery>,
+    State(state): State<PromState>,
+    Query(params): Query<RemoteWriteQuery>,
     RawBody(body): RawBody,
-) -> Result<(StatusCode, ())> {
-    let request = decode_remote_write_request(body).await?;
+) -> Result<Response> {
+    let mut request = decode_remote_write_request(body).await?;
+    if params.drop_nan {
+        drop_nan_samples(&mut request);
+    }
 
-    let ctx = if let Some(db) = params.db {
-        let (catalog, schema) = parse_catalog_and_schema_from_client_database_name(&db);
+    let ctx = if let Some(db) = &params.db {
+        let (catalog, schema) = parse_catalog_and_schema_from_client_database_name(db);
         Arc::new(QueryContext::with(catalog, schema))
     } else {
         QueryContext::arc()
     };
+    ctx.set_validation_mode(parse_validation_mode(params.validation_mode.as_deref())?);
+    let db = params.db.as_deref().unwrap_or(DEFAULT_SCHEMA_NAME);
+
+    let Ok(_permit) = state.write_queue.acquire(db).await else {
+        increment_counter!(
+            METRIC_PROM_WRITE_REJECTED,
+            LABEL_DB => db.to_string(),
+            LABEL_REASON => "queue_full"
+        );
+        return Ok(too_many_requests(
+            state.write_queue.retry_after_secs(db),
+            format!("Too many remote writes queued for database {db}"),
+        ));
+    };
 
     // TODO(shuiyisong): add more error log
-    handler.write(request, ctx).await?;
-    Ok((StatusCode::NO_CONTENT, ()))
+    match state.handler.write(request, ctx).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Err(e) if is_backpressure(&e) => {
+            increment_counter!(
+                METRIC_PROM_WRITE_REJECTED,
+                LABEL_DB => db.to_string(),
+                LABEL_REASON => "backpressure"
+            );
+            Ok(too_many_requests(
+                state.write_queue.retry_after_secs(db),
+                e.to_string(),
+            ))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether the write is rejected as the storage is throttling the writes, which Prometheus
+/// should retry later rather than drop.
+fn is_backpressure(e: &Error) -> bool {
+    e.status_code() == ErrorCode::RuntimeResourcesExhausted
+}
+
+/// Prometheus backs off and retries the writes on 429, waiting for `Retry-After` if it's
+/// configured to. The body is logged by Prometheus as plain text.
+fn too_many_requests(retry_after_secs: u64, message: String) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after_secs.to_string())],
+        message,
+    )
+        .into_response()
 }
 
 impl IntoResponse for PrometheusResponse {
@@ -79,7 +368,7 @@ impl IntoResponse for PrometheusResponse {
 
 #[axum_macros::debug_handler]
 pub async fn remote_read(
-    State(handler): State<PrometheusProtocolHandlerRef>,
+    State(state): State<PromState>,
     Query(params): Query<DatabaseQuery>,
     RawBody(body): RawBody,
 ) -> Result<PrometheusResponse> {
@@ -93,7 +382,7 @@ pub async fn remote_read(
     };
 
     // TODO(shuiyisong): add more error log
-    handler.read(request, ctx).await
+    state.handler.read(request, ctx).await
 }
 
 async fn decode_remote_write_request(body: Body) -> Result<WriteRequest> {
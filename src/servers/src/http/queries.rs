@@ -0,0 +1,33 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::extract::{Json, State};
+use serde::{Deserialize, Serialize};
+
+use crate::query_handler::{RunningQuery, RunningQueryHandlerRef};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunningQueriesResponse {
+    pub queries: Vec<RunningQuery>,
+}
+
+/// Handler to list the long-running statements with their progress, like `SHOW PROCESSLIST`.
+#[axum_macros::debug_handler]
+pub async fn running_queries(
+    State(handler): State<RunningQueryHandlerRef>,
+) -> Json<RunningQueriesResponse> {
+    Json(RunningQueriesResponse {
+        queries: handler.running_queries(),
+    })
+}
@@ -121,3 +121,14 @@ pub async fn run_script(
         json_err!("Script execution not supported, missing script handler");
     }
 }
+
+/// Handler to show the compile states of the scripts
+#[axum_macros::debug_handler]
+pub async fn script_states(State(state): State<ApiState>) -> Json<JsonResponse> {
+    if let Some(script_handler) = &state.script_handler {
+        let output = script_handler.script_states().await;
+        Json(JsonResponse::from_output(vec![output]).await)
+    } else {
+        json_err!("Script execution not supported, missing script handler");
+    }
+}
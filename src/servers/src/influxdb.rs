@@ -13,13 +13,16 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::fmt;
 
-use api::v1::InsertRequest as GrpcInsertRequest;
+use api::v1::{ColumnDataType, InsertRequest as GrpcInsertRequest};
+use common_base::validation_mode::ValidationMode;
 use common_grpc::writer::{LinesWriter, Precision};
-use influxdb_line_protocol::{parse_lines, FieldValue};
-use snafu::ResultExt;
+use common_grpc_expr::validation::{RowError, RowErrorCollector, RowErrors};
+use influxdb_line_protocol::{parse_lines, FieldValue, ParsedLine};
+use snafu::{IntoError, ResultExt};
 
-use crate::error::{Error, InfluxdbLineProtocolSnafu, InfluxdbLinesWriteSnafu};
+use crate::error::{Error, InfluxdbLineProtocolSnafu, InvalidRowsSnafu};
 
 pub const INFLUXDB_TIMESTAMP_COLUMN_NAME: &str = "ts";
 pub const DEFAULT_TIME_PRECISION: Precision = Precision::Nanosecond;
@@ -36,72 +39,84 @@ impl TryFrom<&InfluxdbRequest> for Vec<GrpcInsertRequest> {
     type Error = Error;
 
     fn try_from(value: &InfluxdbRequest) -> Result<Self, Self::Error> {
+        value.to_insert_requests(ValidationMode::default())
+    }
+}
+
+impl InfluxdbRequest {
+    /// Converts the lines to the insert requests of their tables.
+    ///
+    /// A line is invalid if its values conflict with the types of the columns written by the
+    /// previous lines of its table, or with each other. The invalid lines are skipped and
+    /// reported as the row errors, up to the cap of `validation_mode`, so the request is
+    /// rejected with all of them rather than the first one.
+    pub fn to_insert_requests(
+        &self,
+        validation_mode: ValidationMode,
+    ) -> Result<Vec<GrpcInsertRequest>, Error> {
         let mut writers: HashMap<TableName, LinesWriter> = HashMap::new();
-        let lines = parse_lines(&value.lines)
+        let lines = parse_lines(&self.lines)
             .collect::<influxdb_line_protocol::Result<Vec<_>>>()
             .context(InfluxdbLineProtocolSnafu)?;
         let line_len = lines.len();
+        let precision = self.precision.unwrap_or(DEFAULT_TIME_PRECISION);
+        let mut collector = RowErrorCollector::new(validation_mode);
 
-        for line in lines {
-            let table_name = line.series.measurement;
+        for (row, line) in lines.into_iter().enumerate() {
+            let table_name = line.series.measurement.as_str();
             let writer = writers
                 .entry(table_name.to_string())
                 .or_insert_with(|| LinesWriter::with_lines(line_len));
 
-            let tags = line.series.tag_set;
-            if let Some(tags) = tags {
+            let errors = check_line(writer, &line, row);
+            if !errors.is_empty() {
+                for error in errors {
+                    collector.push(error);
+                }
+                if collector.is_full() {
+                    break;
+                }
+                continue;
+            }
+
+            if let Some(tags) = &line.series.tag_set {
                 for (k, v) in tags {
                     writer
                         .write_tag(k.as_str(), v.as_str())
-                        .context(InfluxdbLinesWriteSnafu)?;
+                        .map_err(|e| line_error(row, k.as_str(), v.as_str(), e))?;
                 }
             }
 
-            let fields = line.field_set;
-            for (k, v) in fields {
+            for (k, v) in &line.field_set {
                 let column_name = k.as_str();
-                match v {
-                    FieldValue::I64(value) => {
-                        writer
-                            .write_i64(column_name, value)
-                            .context(InfluxdbLinesWriteSnafu)?;
-                    }
-                    FieldValue::U64(value) => {
-                        writer
-                            .write_u64(column_name, value)
-                            .context(InfluxdbLinesWriteSnafu)?;
-                    }
-                    FieldValue::F64(value) => {
-                        writer
-                            .write_f64(column_name, value)
-                            .context(InfluxdbLinesWriteSnafu)?;
-                    }
-                    FieldValue::String(value) => {
-                        writer
-                            .write_string(column_name, value.as_str())
-                            .context(InfluxdbLinesWriteSnafu)?;
-                    }
-                    FieldValue::Boolean(value) => {
-                        writer
-                            .write_bool(column_name, value)
-                            .context(InfluxdbLinesWriteSnafu)?;
-                    }
-                }
+                let result = match v {
+                    FieldValue::I64(value) => writer.write_i64(column_name, *value),
+                    FieldValue::U64(value) => writer.write_u64(column_name, *value),
+                    FieldValue::F64(value) => writer.write_f64(column_name, *value),
+                    FieldValue::String(value) => writer.write_string(column_name, value.as_str()),
+                    FieldValue::Boolean(value) => writer.write_bool(column_name, *value),
+                };
+                result.map_err(|e| line_error(row, column_name, &v.to_string(), e))?;
             }
 
             if let Some(timestamp) = line.timestamp {
-                let precision = if let Some(val) = &value.precision {
-                    *val
-                } else {
-                    DEFAULT_TIME_PRECISION
-                };
                 writer
                     .write_ts(INFLUXDB_TIMESTAMP_COLUMN_NAME, (timestamp, precision))
-                    .context(InfluxdbLinesWriteSnafu)?;
+                    .map_err(|e| {
+                        line_error(
+                            row,
+                            INFLUXDB_TIMESTAMP_COLUMN_NAME,
+                            &timestamp.to_string(),
+                            e,
+                        )
+                    })?;
             }
 
             writer.commit();
         }
+        collector
+            .finish()
+            .map_err(|row_errors| InvalidRowsSnafu.into_error(row_errors))?;
 
         Ok(writers
             .into_iter()
@@ -118,11 +133,86 @@ impl TryFrom<&InfluxdbRequest> for Vec<GrpcInsertRequest> {
     }
 }
 
+/// Returns the errors of the values of the `row`th `line` conflicting with the types of the
+/// columns in `writer`, or with the values before them in the line.
+fn check_line<'a>(writer: &'a LinesWriter, line: &'a ParsedLine, row: usize) -> Vec<RowError> {
+    let mut checker = LineChecker {
+        writer,
+        row,
+        datatypes: HashMap::new(),
+        errors: Vec::new(),
+    };
+    if let Some(tags) = &line.series.tag_set {
+        for (k, v) in tags {
+            checker.check(k.as_str(), ColumnDataType::String, v);
+        }
+    }
+    for (k, v) in &line.field_set {
+        let datatype = match v {
+            FieldValue::I64(_) => ColumnDataType::Int64,
+            FieldValue::U64(_) => ColumnDataType::Uint64,
+            FieldValue::F64(_) => ColumnDataType::Float64,
+            FieldValue::String(_) => ColumnDataType::String,
+            FieldValue::Boolean(_) => ColumnDataType::Boolean,
+        };
+        checker.check(k.as_str(), datatype, v);
+    }
+    if let Some(timestamp) = line.timestamp {
+        checker.check(
+            INFLUXDB_TIMESTAMP_COLUMN_NAME,
+            ColumnDataType::TimestampMillisecond,
+            &timestamp,
+        );
+    }
+    checker.errors
+}
+
+struct LineChecker<'a> {
+    writer: &'a LinesWriter,
+    row: usize,
+    /// Types of the columns of the values checked in the line.
+    datatypes: HashMap<&'a str, ColumnDataType>,
+    errors: Vec<RowError>,
+}
+
+impl<'a> LineChecker<'a> {
+    fn check(&mut self, column: &'a str, datatype: ColumnDataType, value: &dyn fmt::Display) {
+        let expected = self
+            .writer
+            .column_datatype(column)
+            .or_else(|| self.datatypes.get(column).copied());
+        match expected {
+            Some(expected) if expected != datatype => {
+                self.errors.push(RowError::new(
+                    self.row,
+                    column,
+                    Some(value.to_string().as_str()),
+                    format!("column expects type {expected:?}, got {datatype:?}"),
+                ));
+            }
+            Some(_) => {}
+            None => {
+                let _ = self.datatypes.insert(column, datatype);
+            }
+        }
+    }
+}
+
+/// Returns the error of the `value` of `column` in the line `row`, which the writer of the table
+/// rejects after the line is checked by [check_line].
+fn line_error(row: usize, column: &str, value: &str, source: common_grpc::Error) -> Error {
+    InvalidRowsSnafu.into_error(RowErrors {
+        errors: vec![RowError::new(row, column, Some(value), source.to_string())],
+        incomplete: false,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use api::v1::column::{SemanticType, Values};
     use api::v1::{Column, ColumnDataType};
     use common_base::BitVec;
+    use common_grpc_expr::validation::find_row_errors;
 
     use super::*;
     use crate::influxdb::InfluxdbRequest;
@@ -152,6 +242,72 @@ monitor2,host=host4 cpu=66.3,memory=1029 1663840496400340003";
         }
     }
 
+    #[test]
+    fn test_convert_conflicting_influxdb_lines() {
+        let lines = r"
+monitor,host=host1 cpu=66.6 1663840496100023100
+monitor,host=host2 cpu=true 1663840496400340001";
+
+        let influxdb_req = &InfluxdbRequest {
+            precision: None,
+            lines: lines.to_string(),
+        };
+
+        let result: Result<Vec<GrpcInsertRequest>, _> = influxdb_req.try_into();
+        let err = result.unwrap_err();
+        let row_errors = find_row_errors(&err).unwrap();
+        assert_eq!(1, row_errors.errors.len());
+        let row_error = &row_errors.errors[0];
+        assert_eq!(1, row_error.row);
+        assert_eq!("cpu", row_error.column);
+        assert_eq!(Some("true"), row_error.value.as_deref());
+    }
+
+    #[test]
+    fn test_report_all_conflicting_influxdb_lines() {
+        let lines = r"
+monitor,host=host1 cpu=66.6 1663840496100023100
+monitor,host=host2 cpu=true 1663840496400340001
+monitor,host=host3 cpu=66.7 1663840496400340002
+monitor,host=host4 cpu=1i,host=4i 1663840496400340003
+other,host=host5 cpu=true 1663840496400340004";
+        let influxdb_req = InfluxdbRequest {
+            precision: None,
+            lines: lines.to_string(),
+        };
+
+        let row_errors = |validation_mode| {
+            let err = influxdb_req
+                .to_insert_requests(validation_mode)
+                .unwrap_err();
+            find_row_errors(&err)
+                .unwrap()
+                .errors
+                .iter()
+                .map(|e| (e.row, e.column.clone(), e.reason.clone()))
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![
+            (
+                1,
+                "cpu".to_string(),
+                "column expects type Float64, got Boolean".to_string(),
+            ),
+            (
+                3,
+                "cpu".to_string(),
+                "column expects type Float64, got Int64".to_string(),
+            ),
+            (
+                3,
+                "host".to_string(),
+                "column expects type String, got Int64".to_string(),
+            ),
+        ];
+        assert_eq!(expected, row_errors(ValidationMode::Lenient));
+        assert_eq!(expected[..1], row_errors(ValidationMode::Strict));
+    }
+
     fn assert_monitor_1(columns: &[Column]) {
         assert_eq!(4, columns.len());
         verify_column(
@@ -0,0 +1,490 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ingestion of JSON documents, whose fields are flattened into columns.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use api::v1::InsertRequest as GrpcInsertRequest;
+use common_grpc::writer::{LinesWriter, Precision};
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
+use datatypes::prelude::ConcreteDataType;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use snafu::ResultExt;
+use table::metadata::TableMeta;
+
+use crate::error::{JsonLinesWriteSnafu, Result};
+
+/// Column storing the arrays and the objects nested deeper than the max depth, as JSON.
+pub const JSON_OVERFLOW_COLUMN_NAME: &str = "_overflow";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonIngestOptions {
+    /// Separator joining the keys of nested fields into column names.
+    pub separator: String,
+    /// Max depth of the objects to flatten, 1 only flattens the top level fields.
+    pub max_depth: usize,
+    /// Field of the time of the document. Its value is either milliseconds since the epoch
+    /// or a RFC3339 string, documents without it use the ingestion time.
+    pub time_field: String,
+    /// Fields stored as tags instead of fields.
+    pub tags: HashSet<String>,
+}
+
+impl Default for JsonIngestOptions {
+    fn default() -> Self {
+        Self {
+            separator: "_".to_string(),
+            max_depth: 1,
+            time_field: "ts".to_string(),
+            tags: HashSet::new(),
+        }
+    }
+}
+
+/// Error of a document, which is not ingested.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentError {
+    /// Index of the document in the request.
+    pub index: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Tag,
+    String,
+    Float,
+    Boolean,
+    /// Column of the table whose type no JSON value is ingested as.
+    Other,
+}
+
+impl ColumnKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ColumnKind::Tag => "tag",
+            ColumnKind::String => "string",
+            ColumnKind::Float => "float",
+            ColumnKind::Boolean => "boolean",
+            ColumnKind::Other => "not ingestible from JSON",
+        }
+    }
+}
+
+/// Returns the kinds of the columns of the table, except the time index.
+fn table_column_kinds(table_meta: &TableMeta) -> HashMap<String, ColumnKind> {
+    let schema = &table_meta.schema;
+    schema
+        .column_schemas()
+        .iter()
+        .enumerate()
+        .filter(|(_, column_schema)| !column_schema.is_time_index())
+        .map(|(i, column_schema)| {
+            let kind = match &column_schema.data_type {
+                ConcreteDataType::String(_) if table_meta.primary_key_indices.contains(&i) => {
+                    ColumnKind::Tag
+                }
+                ConcreteDataType::String(_) => ColumnKind::String,
+                ConcreteDataType::Float64(_) => ColumnKind::Float,
+                ConcreteDataType::Boolean(_) => ColumnKind::Boolean,
+                _ => ColumnKind::Other,
+            };
+            (column_schema.name.clone(), kind)
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum FieldValue {
+    Tag(String),
+    String(String),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl FieldValue {
+    fn kind(&self) -> ColumnKind {
+        match self {
+            FieldValue::Tag(_) => ColumnKind::Tag,
+            FieldValue::String(_) => ColumnKind::String,
+            FieldValue::Float(_) => ColumnKind::Float,
+            FieldValue::Boolean(_) => ColumnKind::Boolean,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FlattenedDocument {
+    timestamp_millis: i64,
+    fields: Vec<(String, FieldValue)>,
+}
+
+/// Flattens `documents` into an insert request of `table_name`, whose meta is `table_meta` if
+/// it exists.
+///
+/// Invalid documents, e.g. with a bad time value or a field whose type conflicts with the
+/// column of the table or of a previous document, are reported instead of failing the whole
+/// batch. Returns `None` if no document is valid.
+pub fn documents_to_insert_request(
+    table_name: &str,
+    documents: &[Value],
+    options: &JsonIngestOptions,
+    table_meta: Option<&TableMeta>,
+    now_millis: i64,
+) -> Result<(Option<GrpcInsertRequest>, Vec<DocumentError>)> {
+    let mut column_kinds = table_meta.map(table_column_kinds).unwrap_or_default();
+    let mut flattened = Vec::with_capacity(documents.len());
+    let mut errors = Vec::new();
+    for (index, document) in documents.iter().enumerate() {
+        let result = flatten_document(document, options, now_millis)
+            .and_then(|doc| check_column_kinds(&doc, &mut column_kinds).map(|_| doc));
+        match result {
+            Ok(doc) => flattened.push(doc),
+            Err(error) => errors.push(DocumentError { index, error }),
+        }
+    }
+    if flattened.is_empty() {
+        return Ok((None, errors));
+    }
+
+    let mut writer = LinesWriter::with_lines(flattened.len());
+    for doc in flattened {
+        for (column, value) in doc.fields {
+            match value {
+                FieldValue::Tag(v) => writer.write_tag(&column, &v),
+                FieldValue::String(v) => writer.write_string(&column, &v),
+                FieldValue::Float(v) => writer.write_f64(&column, v),
+                FieldValue::Boolean(v) => writer.write_bool(&column, v),
+            }
+            .context(JsonLinesWriteSnafu)?;
+        }
+        writer
+            .write_ts(
+                &options.time_field,
+                (doc.timestamp_millis, Precision::Millisecond),
+            )
+            .context(JsonLinesWriteSnafu)?;
+        writer.commit();
+    }
+
+    let (columns, row_count) = writer.finish();
+    let request = GrpcInsertRequest {
+        table_name: table_name.to_string(),
+        region_number: 0,
+        columns,
+        row_count,
+    };
+    Ok((Some(request), errors))
+}
+
+fn flatten_document(
+    document: &Value,
+    options: &JsonIngestOptions,
+    now_millis: i64,
+) -> std::result::Result<FlattenedDocument, String> {
+    let Value::Object(object) = document else {
+        return Err(format!("document is not a JSON object: {document}"));
+    };
+
+    let mut fields = Vec::with_capacity(object.len());
+    let mut overflow = Map::new();
+    let mut timestamp_millis = now_millis;
+    for (key, value) in object {
+        if *key == options.time_field {
+            timestamp_millis = parse_time(value)?;
+            continue;
+        }
+        flatten_value(key.clone(), value, 1, options, &mut fields, &mut overflow)?;
+    }
+    if !overflow.is_empty() {
+        fields.push((
+            JSON_OVERFLOW_COLUMN_NAME.to_string(),
+            FieldValue::String(Value::Object(overflow).to_string()),
+        ));
+    }
+
+    let mut columns = HashSet::with_capacity(fields.len());
+    for (column, _) in &fields {
+        if column == &options.time_field || !columns.insert(column.as_str()) {
+            return Err(format!("duplicate column {column} after flattening"));
+        }
+    }
+
+    Ok(FlattenedDocument {
+        timestamp_millis,
+        fields,
+    })
+}
+
+/// Flattens the `value` of the field `column` at `depth` into `fields`, or into `overflow`
+/// if it's an array or an object deeper than the max depth.
+fn flatten_value(
+    column: String,
+    value: &Value,
+    depth: usize,
+    options: &JsonIngestOptions,
+    fields: &mut Vec<(String, FieldValue)>,
+    overflow: &mut Map<String, Value>,
+) -> std::result::Result<(), String> {
+    let value = match value {
+        Value::Null => return Ok(()),
+        Value::Object(object) if depth < options.max_depth => {
+            for (key, value) in object {
+                let column = format!("{column}{}{key}", options.separator);
+                flatten_value(column, value, depth + 1, options, fields, overflow)?;
+            }
+            return Ok(());
+        }
+        Value::Object(_) | Value::Array(_) => {
+            let _ = overflow.insert(column, value.clone());
+            return Ok(());
+        }
+        Value::String(s) if options.tags.contains(&column) => FieldValue::Tag(s.clone()),
+        Value::Number(_) | Value::Bool(_) if options.tags.contains(&column) => {
+            FieldValue::Tag(value.to_string())
+        }
+        Value::String(s) => FieldValue::String(s.clone()),
+        Value::Number(n) => FieldValue::Float(
+            n.as_f64()
+                .ok_or_else(|| format!("invalid number {n} of field {column}"))?,
+        ),
+        Value::Bool(b) => FieldValue::Boolean(*b),
+    };
+    fields.push((column, value));
+    Ok(())
+}
+
+fn parse_time(value: &Value) -> std::result::Result<i64, String> {
+    let timestamp = match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => Timestamp::from_str(s)
+            .ok()
+            .and_then(|ts| ts.convert_to(TimeUnit::Millisecond))
+            .map(|ts| ts.value()),
+        _ => None,
+    };
+    timestamp.ok_or_else(|| format!("invalid time value {value}"))
+}
+
+fn check_column_kinds(
+    doc: &FlattenedDocument,
+    column_kinds: &mut HashMap<String, ColumnKind>,
+) -> std::result::Result<(), String> {
+    for (column, value) in &doc.fields {
+        if let Some(kind) = column_kinds.get(column) {
+            if *kind != value.kind() {
+                return Err(format!(
+                    "type of column {column} is {}, but {} is given",
+                    kind.name(),
+                    value.kind().name()
+                ));
+            }
+        }
+    }
+    for (column, value) in &doc.fields {
+        let _ = column_kinds.insert(column.clone(), value.kind());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use api::v1::column::{SemanticType, Values};
+    use api::v1::ColumnDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use serde_json::json;
+    use table::metadata::TableMetaBuilder;
+
+    use super::*;
+
+    fn column<'a>(request: &'a GrpcInsertRequest, name: &str) -> &'a api::v1::Column {
+        request
+            .columns
+            .iter()
+            .find(|c| c.column_name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_evolving_keys() {
+        let documents = vec![
+            json!({"ts": 1000, "host": "a", "cpu": 0.5}),
+            json!({"ts": "1970-01-01T00:00:02Z", "host": "b", "cpu": 1, "up": true}),
+        ];
+        let options = JsonIngestOptions {
+            tags: HashSet::from(["host".to_string()]),
+            ..Default::default()
+        };
+        let (request, errors) =
+            documents_to_insert_request("logs", &documents, &options, None, 0).unwrap();
+        assert!(errors.is_empty());
+        let request = request.unwrap();
+        assert_eq!(2, request.row_count);
+
+        let host = column(&request, "host");
+        assert_eq!(SemanticType::Tag as i32, host.semantic_type);
+        let cpu = column(&request, "cpu");
+        assert_eq!(ColumnDataType::Float64 as i32, cpu.datatype);
+        assert_eq!(vec![0.5, 1.0], cpu.values.as_ref().unwrap().f64_values);
+        // Null in the first row.
+        let up = column(&request, "up");
+        assert_eq!(vec![1], up.null_mask);
+        let ts = column(&request, "ts");
+        assert_eq!(SemanticType::Timestamp as i32, ts.semantic_type);
+        assert_eq!(
+            &Values {
+                ts_millisecond_values: vec![1000, 2000],
+                ..Default::default()
+            },
+            ts.values.as_ref().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_document_errors() {
+        let documents = vec![
+            json!({"ts": "yesterday", "cpu": 0.5}),
+            json!({"ts": 1000, "cpu": 0.5}),
+            json!({"ts": 2000, "cpu": "high"}),
+            json!([1, 2]),
+        ];
+        let (request, errors) =
+            documents_to_insert_request("logs", &documents, &JsonIngestOptions::default(), None, 0)
+                .unwrap();
+        assert_eq!(1, request.unwrap().row_count);
+        assert_eq!(
+            vec![0, 2, 3],
+            errors.iter().map(|e| e.index).collect::<Vec<_>>()
+        );
+        assert!(errors[0].error.contains("invalid time value"));
+        assert!(errors[1]
+            .error
+            .contains("type of column cpu is float, but string is given"));
+
+        let (request, errors) = documents_to_insert_request(
+            "logs",
+            &documents[..1],
+            &JsonIngestOptions::default(),
+            None,
+            0,
+        )
+        .unwrap();
+        assert!(request.is_none());
+        assert_eq!(1, errors.len());
+    }
+
+    #[test]
+    fn test_conflicts_with_table() {
+        let schema = Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new("level", ConcreteDataType::int64_datatype(), true),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ]);
+        let table_meta = TableMetaBuilder::default()
+            .schema(Arc::new(schema))
+            .primary_key_indices(vec![0])
+            .next_column_id(4)
+            .engine("mito")
+            .build()
+            .unwrap();
+        let documents = vec![
+            json!({"ts": 1000, "host": "a", "cpu": 0.5}),
+            json!({"ts": 2000, "host": "a", "cpu": "high"}),
+            json!({"ts": 3000, "host": "a", "level": 1}),
+            // The host is a tag of the table.
+            json!({"ts": 4000, "host": "a", "cpu": 0.5}),
+        ];
+        let options = JsonIngestOptions {
+            tags: HashSet::from(["host".to_string()]),
+            ..Default::default()
+        };
+        let (request, errors) =
+            documents_to_insert_request("logs", &documents, &options, Some(&table_meta), 0)
+                .unwrap();
+        assert_eq!(2, request.unwrap().row_count);
+        assert_eq!(
+            vec![1, 2],
+            errors.iter().map(|e| e.index).collect::<Vec<_>>()
+        );
+        assert!(errors[0]
+            .error
+            .contains("type of column cpu is float, but string is given"));
+        assert!(errors[1]
+            .error
+            .contains("type of column level is not ingestible from JSON, but float is given"));
+
+        // A field conflicts with the tag of the table.
+        let (request, errors) = documents_to_insert_request(
+            "logs",
+            &documents[..1],
+            &JsonIngestOptions::default(),
+            Some(&table_meta),
+            0,
+        )
+        .unwrap();
+        assert!(request.is_none());
+        assert!(errors[0]
+            .error
+            .contains("type of column host is tag, but string is given"));
+    }
+
+    #[test]
+    fn test_flatten_and_overflow() {
+        let documents = vec![json!({
+            "http": {"method": "GET", "headers": {"host": "localhost"}},
+            "tags": ["a", "b"],
+        })];
+        let options = JsonIngestOptions {
+            separator: ".".to_string(),
+            max_depth: 2,
+            ..Default::default()
+        };
+        let (request, errors) =
+            documents_to_insert_request("logs", &documents, &options, None, 3000).unwrap();
+        assert!(errors.is_empty());
+        let request = request.unwrap();
+
+        let method = column(&request, "http.method");
+        assert_eq!(
+            vec!["GET".to_string()],
+            method.values.as_ref().unwrap().string_values
+        );
+        let overflow = column(&request, JSON_OVERFLOW_COLUMN_NAME);
+        let overflow: Value =
+            serde_json::from_str(&overflow.values.as_ref().unwrap().string_values[0]).unwrap();
+        assert_eq!(
+            json!({"http.headers": {"host": "localhost"}, "tags": ["a", "b"]}),
+            overflow
+        );
+        // Ingestion time is used without the time field.
+        let ts = column(&request, "ts");
+        assert_eq!(
+            vec![3000],
+            ts.values.as_ref().unwrap().ts_millisecond_values
+        );
+    }
+}
@@ -19,11 +19,13 @@ use common_catalog::consts::DEFAULT_CATALOG_NAME;
 use serde::{Deserialize, Serialize};
 
 pub mod auth;
+pub mod connection;
 pub mod error;
 pub mod grpc;
 pub mod http;
 pub mod influxdb;
 pub mod interceptor;
+pub mod json_ingest;
 pub mod line_writer;
 mod metrics;
 pub mod metrics_handler;
@@ -142,6 +142,7 @@ impl LineWriter {
             table_name: self.table_name,
             columns_values,
             region_number: 0, // TODO(hl): Check if assign 0 region is ok?
+            write_mode: Default::default(),
         }
     }
 }
@@ -14,3 +14,11 @@
 
 pub(crate) const METRIC_HTTP_SQL_ELAPSED: &str = "servers.http_sql_elapsed";
 pub(crate) const METRIC_HTTP_PROMQL_ELAPSED: &str = "servers.http_promql_elapsed";
+pub(crate) const METRIC_HTTP_SQL_BATCH_ELAPSED: &str = "servers.http_sql_batch_elapsed";
+pub(crate) const METRIC_HTTP_SQL_BATCH_SIZE: &str = "servers.http_sql_batch_size";
+pub(crate) const METRIC_PROM_WRITE_QUEUE_DEPTH: &str =
+    "servers.prometheus_remote_write_queue_depth";
+pub(crate) const METRIC_PROM_WRITE_REJECTED: &str = "servers.prometheus_remote_write_rejected";
+
+pub(crate) const LABEL_DB: &str = "db";
+pub(crate) const LABEL_REASON: &str = "reason";
@@ -21,10 +21,6 @@ pub struct MetricsHandler;
 
 impl MetricsHandler {
     pub fn render(&self) -> String {
-        if let Some(handle) = metric::try_handle() {
-            handle.render()
-        } else {
-            "Prometheus handle not initialized.".to_owned()
-        }
+        metric::render().unwrap_or_else(|| "Prometheus handle not initialized.".to_owned())
     }
 }
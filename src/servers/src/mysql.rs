@@ -14,5 +14,7 @@
 
 mod federated;
 pub mod handler;
+mod helper;
 pub mod server;
+mod warnings;
 pub mod writer;
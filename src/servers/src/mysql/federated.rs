@@ -17,17 +17,22 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_query::Output;
 use common_recordbatch::RecordBatches;
+use common_time::timestamp::TimeUnit;
+use common_time::{TimeZone, Timestamp};
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::{ColumnSchema, Schema};
-use datatypes::vectors::StringVector;
+use datatypes::vectors::{StringVector, UInt32Vector};
 use once_cell::sync::Lazy;
 use regex::bytes::RegexSet;
 use regex::Regex;
 use session::context::QueryContextRef;
 
+use crate::error::{InvalidQuerySnafu, Result};
+
 // TODO(LFC): Include GreptimeDB's version and git commit tag etc.
 const MYSQL_VERSION: &str = "8.0.26";
 
@@ -54,6 +59,34 @@ static SELECT_TIME_DIFF_FUNC_PATTERN: Lazy<Regex> =
 static SHOW_SQL_MODE_PATTERN: Lazy<Regex> =
     Lazy::new(|| Regex::new("(?i)^(SHOW VARIABLES LIKE 'sql_mode'(.*))").unwrap());
 
+// `SET SQL_SELECT_LIMIT = n | DEFAULT`, also in the forms of `SET SESSION` and `SET @@`.
+static SET_SELECT_LIMIT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(/\* ApplicationName=.*\*/\s*)?SET\s+(SESSION\s+|@@SESSION\.|@@)?SQL_SELECT_LIMIT\s*=\s*(\w+)\s*;?\s*$").unwrap()
+});
+// `SET TIME_TRAVEL_AS_OF = '<timestamp>' | <epoch millis> | DEFAULT`, also in the forms of
+// `SET SESSION` and `SET @@`.
+static SET_AS_OF_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^SET\s+(SESSION\s+|@@SESSION\.|@@)?TIME_TRAVEL_AS_OF\s*=\s*(?:'([^']*)'|(\w+))\s*;?\s*$").unwrap()
+});
+// `SET TIME_ZONE = '<time zone>' | DEFAULT`, also in the forms of `SET SESSION` and `SET @@`.
+static SET_TIME_ZONE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^SET\s+(SESSION\s+|@@SESSION\.|@@)?TIME_ZONE\s*=\s*(?:'([^']*)'|(\w+))\s*;?\s*$",
+    )
+    .unwrap()
+});
+// `SET WAIT_TIMEOUT = n | DEFAULT`, also in the forms of `SET SESSION` and `SET @@`.
+static SET_WAIT_TIMEOUT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^SET\s+(SESSION\s+|@@SESSION\.|@@)?WAIT_TIMEOUT\s*=\s*(\w+)\s*;?\s*$").unwrap()
+});
+static SHOW_WARNINGS_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(/\* ApplicationName=.*\*/\s*)?SHOW WARNINGS").unwrap());
+
+/// Value of `sql_select_limit` meaning no limit in MySQL.
+const UNLIMITED_SELECT_LIMIT: u64 = u64::MAX;
+/// Code of the warnings, which is `ER_UNKNOWN_ERROR` of MySQL.
+const WARNING_CODE: u32 = 1105;
+
 static OTHER_NOT_SUPPORTED_STMT: Lazy<RegexSet> = Lazy::new(|| {
     RegexSet::new([
         // Txn.
@@ -69,7 +102,6 @@ static OTHER_NOT_SUPPORTED_STMT: Lazy<RegexSet> = Lazy::new(|| {
         "(?i)^(SET AUTOCOMMIT(.*))",
         "(?i)^(SET SQL_LOG_BIN(.*))",
         "(?i)^(SET sql_mode(.*))",
-        "(?i)^(SET SQL_SELECT_LIMIT(.*))",
         "(?i)^(SET @@(.*))",
 
         "(?i)^(SHOW COLLATION)",
@@ -92,8 +124,6 @@ static OTHER_NOT_SUPPORTED_STMT: Lazy<RegexSet> = Lazy::new(|| {
         "(?i)^(/\\*!40101 SET(.*) \\*/)$",
 
         // DBeaver.
-        "(?i)^(SHOW WARNINGS)",
-        "(?i)^(/\\* ApplicationName=(.*)SHOW WARNINGS)",
         "(?i)^(/\\* ApplicationName=(.*)SHOW PLUGINS)",
         "(?i)^(/\\* ApplicationName=(.*)SHOW COLLATION)",
         "(?i)^(/\\* ApplicationName=(.*)SHOW CHARSET)",
@@ -101,7 +131,6 @@ static OTHER_NOT_SUPPORTED_STMT: Lazy<RegexSet> = Lazy::new(|| {
         "(?i)^(/\\* ApplicationName=(.*)SELECT @@(.*))",
         "(?i)^(/\\* ApplicationName=(.*)SHOW @@(.*))",
         "(?i)^(/\\* ApplicationName=(.*)SET net_write_timeout(.*))",
-        "(?i)^(/\\* ApplicationName=(.*)SET SQL_SELECT_LIMIT(.*))",
         "(?i)^(/\\* ApplicationName=(.*)SHOW VARIABLES(.*))",
 
         // pt-toolkit
@@ -251,8 +280,32 @@ fn check_show_variables(query: &str) -> Option<Output> {
     recordbatches.map(Output::RecordBatches)
 }
 
+// Recordbatches for show warnings statement.
+// Format is:
+// | Level   | Code | Message |
+// | Warning | 1105 | xx      |
+fn show_warnings(warnings: Vec<String>) -> RecordBatches {
+    let schema = Arc::new(Schema::new(vec![
+        ColumnSchema::new("Level", ConcreteDataType::string_datatype(), true),
+        ColumnSchema::new("Code", ConcreteDataType::uint32_datatype(), true),
+        ColumnSchema::new("Message", ConcreteDataType::string_datatype(), true),
+    ]));
+    let columns = vec![
+        Arc::new(StringVector::from(vec!["Warning"; warnings.len()])) as _,
+        Arc::new(UInt32Vector::from_vec(vec![WARNING_CODE; warnings.len()])) as _,
+        Arc::new(StringVector::from(warnings)) as _,
+    ];
+    RecordBatches::try_from_columns(schema, columns)
+        // unwrap is safe because the schema and data are definitely able to form a recordbatch
+        .unwrap()
+}
+
 // Check for SET or others query, this is the final check of the federated query.
 fn check_others(query: &str, query_ctx: QueryContextRef) -> Option<Output> {
+    if SHOW_WARNINGS_PATTERN.is_match(query) {
+        return Some(Output::RecordBatches(show_warnings(query_ctx.warnings())));
+    }
+
     if OTHER_NOT_SUPPORTED_STMT.is_match(query.as_bytes()) {
         return Some(Output::RecordBatches(RecordBatches::empty()));
     }
@@ -298,6 +351,113 @@ pub(crate) fn check(query: &str, query_ctx: QueryContextRef) -> Option<Output> {
     check_others(query, query_ctx)
 }
 
+/// Sets the select limit of the session if the query is `SET SQL_SELECT_LIMIT = n | DEFAULT`,
+/// where `DEFAULT` restores `default_limit` configured by the server.
+pub(crate) fn check_set_select_limit(
+    query: &str,
+    query_ctx: QueryContextRef,
+    default_limit: Option<usize>,
+) -> Option<Result<Output>> {
+    let value = SET_SELECT_LIMIT_PATTERN.captures(query)?.get(3)?.as_str();
+    let limit = if value.eq_ignore_ascii_case("DEFAULT") {
+        default_limit
+    } else {
+        match value.parse::<u64>() {
+            Ok(UNLIMITED_SELECT_LIMIT) => None,
+            Ok(limit) => Some(usize::try_from(limit).unwrap_or(usize::MAX)),
+            Err(_) => {
+                return Some(
+                    InvalidQuerySnafu {
+                        reason: format!("Invalid value for sql_select_limit: {value}"),
+                    }
+                    .fail(),
+                )
+            }
+        }
+    };
+    query_ctx.set_select_limit(limit);
+    Some(Ok(Output::AffectedRows(0)))
+}
+
+/// Sets the time the queries of the session read the snapshots of tables as of if the query is
+/// `SET TIME_TRAVEL_AS_OF = '<timestamp>' | DEFAULT`, where `DEFAULT` reads the latest
+/// snapshots.
+pub(crate) fn check_set_as_of(query: &str, query_ctx: QueryContextRef) -> Option<Result<Output>> {
+    let captures = SET_AS_OF_PATTERN.captures(query)?;
+    let value = captures.get(2).or_else(|| captures.get(3))?.as_str();
+    let as_of = if captures.get(3).is_some() && value.eq_ignore_ascii_case("DEFAULT") {
+        None
+    } else {
+        match Timestamp::parse_in_unit(value, TimeUnit::Millisecond) {
+            Ok(as_of) => Some(as_of.value()),
+            Err(_) => {
+                return Some(
+                    InvalidQuerySnafu {
+                        reason: format!("Invalid value for time_travel_as_of: {value}"),
+                    }
+                    .fail(),
+                )
+            }
+        }
+    };
+    query_ctx.set_as_of(as_of);
+    Some(Ok(Output::AffectedRows(0)))
+}
+
+/// Sets the time zone the results of the session are shown in if the query is
+/// `SET TIME_ZONE = '<time zone>' | DEFAULT`, where `DEFAULT` is the system time zone.
+pub(crate) fn check_set_time_zone(
+    query: &str,
+    query_ctx: QueryContextRef,
+) -> Option<Result<Output>> {
+    let captures = SET_TIME_ZONE_PATTERN.captures(query)?;
+    let value = captures.get(2).or_else(|| captures.get(3))?.as_str();
+    let time_zone = if captures.get(3).is_some() && value.eq_ignore_ascii_case("DEFAULT") {
+        TimeZone::default()
+    } else {
+        match value.parse::<TimeZone>() {
+            Ok(time_zone) => time_zone,
+            Err(_) => {
+                return Some(
+                    InvalidQuerySnafu {
+                        reason: format!("Unknown or incorrect time zone: {value}"),
+                    }
+                    .fail(),
+                )
+            }
+        }
+    };
+    query_ctx.set_time_zone(time_zone);
+    Some(Ok(Output::AffectedRows(0)))
+}
+
+/// Sets the idle timeout of the session if the query is `SET WAIT_TIMEOUT = n | DEFAULT`, where
+/// `n` is in seconds and `DEFAULT` restores `default_timeout` configured by the server.
+pub(crate) fn check_set_wait_timeout(
+    query: &str,
+    query_ctx: QueryContextRef,
+    default_timeout: Option<Duration>,
+) -> Option<Result<Output>> {
+    let value = SET_WAIT_TIMEOUT_PATTERN.captures(query)?.get(2)?.as_str();
+    let timeout = if value.eq_ignore_ascii_case("DEFAULT") {
+        default_timeout
+    } else {
+        match value.parse::<u64>() {
+            Ok(secs) if secs > 0 => Some(Duration::from_secs(secs)),
+            _ => {
+                return Some(
+                    InvalidQuerySnafu {
+                        reason: format!("Invalid value for wait_timeout: {value}"),
+                    }
+                    .fail(),
+                )
+            }
+        }
+    };
+    query_ctx.set_idle_timeout(timeout);
+    Some(Ok(Output::AffectedRows(0)))
+}
+
 #[cfg(test)]
 mod test {
     use session::context::QueryContext;
@@ -395,4 +555,149 @@ mod test {
 +----------------------------------+";
         test(query, expected);
     }
+
+    #[test]
+    fn test_set_select_limit() {
+        let query_ctx = Arc::new(QueryContext::new());
+        let default_limit = Some(1000);
+
+        assert!(check_set_select_limit("select 1", query_ctx.clone(), default_limit).is_none());
+
+        let set = |query: &str| {
+            let output = check_set_select_limit(query, query_ctx.clone(), default_limit)
+                .unwrap()
+                .unwrap();
+            assert!(matches!(output, Output::AffectedRows(0)));
+            query_ctx.select_limit()
+        };
+        assert_eq!(Some(10), set("SET SQL_SELECT_LIMIT=10"));
+        assert_eq!(Some(1000), set("set sql_select_limit = DEFAULT"));
+        assert_eq!(Some(20), set("SET SESSION sql_select_limit = 20;"));
+        assert_eq!(None, set("SET @@sql_select_limit=18446744073709551615"));
+        assert_eq!(
+            Some(200),
+            set("/* ApplicationName=DBeaver 21.0.1 - Main */ SET SQL_SELECT_LIMIT=200")
+        );
+
+        assert!(check_set_select_limit(
+            "SET SQL_SELECT_LIMIT=abc",
+            query_ctx.clone(),
+            default_limit
+        )
+        .unwrap()
+        .is_err());
+        assert_eq!(Some(200), query_ctx.select_limit());
+    }
+
+    #[test]
+    fn test_set_as_of() {
+        let query_ctx = Arc::new(QueryContext::new());
+
+        assert!(check_set_as_of("select 1", query_ctx.clone()).is_none());
+
+        let set = |query: &str| {
+            let output = check_set_as_of(query, query_ctx.clone()).unwrap().unwrap();
+            assert!(matches!(output, Output::AffectedRows(0)));
+            query_ctx.as_of()
+        };
+        assert_eq!(
+            Some(1681293600000),
+            set("SET TIME_TRAVEL_AS_OF = '2023-04-12T10:00:00Z'")
+        );
+        assert_eq!(None, set("set time_travel_as_of = default;"));
+        assert_eq!(
+            Some(1681293600123),
+            set("SET @@time_travel_as_of=1681293600123")
+        );
+
+        assert!(
+            check_set_as_of("SET TIME_TRAVEL_AS_OF = 'yesterday'", query_ctx.clone())
+                .unwrap()
+                .is_err()
+        );
+        assert_eq!(Some(1681293600123), query_ctx.as_of());
+    }
+
+    #[test]
+    fn test_set_time_zone() {
+        let query_ctx = Arc::new(QueryContext::new());
+        assert!(check_set_time_zone("select 1", query_ctx.clone()).is_none());
+
+        let set = |query: &str| {
+            let output = check_set_time_zone(query, query_ctx.clone())
+                .unwrap()
+                .unwrap();
+            assert!(matches!(output, Output::AffectedRows(0)));
+            query_ctx.time_zone().to_string()
+        };
+        assert_eq!("+08:00", set("SET time_zone = '+08:00'"));
+        assert_eq!("SYSTEM", set("set time_zone = default;"));
+        assert_eq!("-05:30", set("SET @@session.time_zone='-05:30'"));
+        assert_eq!("+00:00", set("SET SESSION TIME_ZONE = 'UTC'"));
+        assert_eq!("SYSTEM", set("SET @@time_zone = 'SYSTEM'"));
+
+        assert!(
+            check_set_time_zone("SET time_zone = 'Mars/Olympus'", query_ctx.clone())
+                .unwrap()
+                .is_err()
+        );
+        assert_eq!(TimeZone::System, query_ctx.time_zone());
+    }
+
+    #[test]
+    fn test_set_wait_timeout() {
+        let query_ctx = Arc::new(QueryContext::new());
+        let default = Some(Duration::from_secs(600));
+        assert!(check_set_wait_timeout("select 1", query_ctx.clone(), default).is_none());
+
+        let set = |query: &str| {
+            let output = check_set_wait_timeout(query, query_ctx.clone(), default)
+                .unwrap()
+                .unwrap();
+            assert!(matches!(output, Output::AffectedRows(0)));
+            query_ctx.idle_timeout()
+        };
+        assert_eq!(Some(Duration::from_secs(30)), set("SET wait_timeout = 30"));
+        assert_eq!(default, set("set wait_timeout = default;"));
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            set("SET @@session.wait_timeout=5")
+        );
+        assert_eq!(
+            Some(Duration::from_secs(60)),
+            set("SET SESSION WAIT_TIMEOUT = 60")
+        );
+
+        for query in ["SET wait_timeout = 0", "SET wait_timeout = forever"] {
+            assert!(check_set_wait_timeout(query, query_ctx.clone(), default)
+                .unwrap()
+                .is_err());
+        }
+        assert_eq!(Some(Duration::from_secs(60)), query_ctx.idle_timeout());
+    }
+
+    #[test]
+    fn test_show_warnings() {
+        let query_ctx = Arc::new(QueryContext::new());
+        let show_warnings = |query_ctx| match check("SHOW WARNINGS", query_ctx).unwrap() {
+            Output::RecordBatches(r) => r.pretty_print().unwrap(),
+            _ => unreachable!(),
+        };
+
+        let expected = "\
++-------+------+---------+
+| Level | Code | Message |
++-------+------+---------+
++-------+------+---------+";
+        assert_eq!(expected, show_warnings(query_ctx.clone()));
+
+        query_ctx.add_warning("Result is truncated".to_string());
+        let expected = "\
++---------+------+---------------------+
+| Level   | Code | Message             |
++---------+------+---------------------+
+| Warning | 1105 | Result is truncated |
++---------+------+---------------------+";
+        assert_eq!(expected, show_warnings(query_ctx));
+    }
 }
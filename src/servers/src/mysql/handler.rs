@@ -19,13 +19,15 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use chrono::{NaiveDate, NaiveDateTime};
 use common_query::Output;
 use common_telemetry::tracing::log;
-use common_telemetry::{error, trace};
+use common_telemetry::{debug, error, trace};
+use datatypes::float_format::FloatPrecision;
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::Schema;
 use opensrv_mysql::{
-    AsyncMysqlShim, Column, ColumnFlags, ColumnType, ErrorKind, InitWriter, ParamParser,
-    ParamValue, QueryResultWriter, StatementMetaWriter, ValueInner,
+    AsyncMysqlShim, ErrorKind, InitWriter, ParamParser, ParamValue, QueryResultWriter,
+    StatementMetaWriter,
 };
 use parking_lot::RwLock;
 use rand::RngCore;
@@ -38,9 +40,24 @@ use sql::statements::statement::Statement;
 use tokio::io::AsyncWrite;
 
 use crate::auth::{Identity, Password, UserProviderRef};
+use crate::connection::ConnectionActivity;
 use crate::error::{self, InvalidPrepareStatementSnafu, Result};
+use crate::mysql::helper::{self, BoundColumn};
+use crate::mysql::warnings::ResultSetWarningsRef;
 use crate::mysql::writer;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::tls::{self, ClientCertMapping};
+
+/// A statement prepared by a connection.
+#[derive(Clone)]
+struct PreparedStatement {
+    /// The query with the `?` placeholders, as the client sent it.
+    query: String,
+    /// Byte offsets of the placeholders in the query.
+    placeholders: Vec<usize>,
+    /// Types of the params, inferred from the columns the placeholders are bound to.
+    param_types: Vec<Option<ConcreteDataType>>,
+}
 
 // An intermediate shim for executing MySQL queries.
 pub struct MysqlInstanceShim {
@@ -49,8 +66,19 @@ pub struct MysqlInstanceShim {
     session: Arc<Session>,
     user_provider: Option<UserProviderRef>,
     // TODO(SSebo): use something like moka to achieve TTL or LRU
-    prepared_stmts: Arc<RwLock<HashMap<u32, String>>>,
+    /// Statements prepared by the connection, by their ids, until the client closes them.
+    prepared_stmts: Arc<RwLock<HashMap<u32, PreparedStatement>>>,
     prepared_stmts_counter: AtomicU32,
+    /// Select limit of the session restored by `SET SQL_SELECT_LIMIT = DEFAULT`.
+    default_select_limit: Option<usize>,
+    /// Idle timeout of the session restored by `SET WAIT_TIMEOUT = DEFAULT`.
+    default_idle_timeout: Option<Duration>,
+    /// Precision of the floats written in the text protocol.
+    float_precision: FloatPrecision,
+    activity: Arc<ConnectionActivity>,
+    /// Warning count of the result set being terminated.
+    result_set_warnings: ResultSetWarningsRef,
+    client_cert_mapping: ClientCertMapping,
 }
 
 impl MysqlInstanceShim {
@@ -58,6 +86,10 @@ impl MysqlInstanceShim {
         query_handler: ServerSqlQueryHandlerRef,
         user_provider: Option<UserProviderRef>,
         client_addr: SocketAddr,
+        default_select_limit: Option<usize>,
+        default_idle_timeout: Option<Duration>,
+        float_precision: FloatPrecision,
+        client_cert_mapping: ClientCertMapping,
     ) -> MysqlInstanceShim {
         // init a random salt
         let mut bs = vec![0u8; 20];
@@ -72,16 +104,39 @@ impl MysqlInstanceShim {
             }
         }
 
+        let session = Session::new(client_addr, Channel::Mysql);
+        session.context().set_select_limit(default_select_limit);
+        session.context().set_idle_timeout(default_idle_timeout);
+
         MysqlInstanceShim {
             query_handler,
             salt: scramble,
-            session: Arc::new(Session::new(client_addr, Channel::Mysql)),
+            session: Arc::new(session),
             user_provider,
             prepared_stmts: Default::default(),
             prepared_stmts_counter: AtomicU32::new(1),
+            default_select_limit,
+            default_idle_timeout,
+            float_precision,
+            activity: Arc::new(ConnectionActivity::new()),
+            result_set_warnings: ResultSetWarningsRef::default(),
+            client_cert_mapping,
         }
     }
 
+    pub(crate) fn session(&self) -> Arc<Session> {
+        self.session.clone()
+    }
+
+    /// Returns the activity of the connection, tracking the commands it's running.
+    pub(crate) fn activity(&self) -> Arc<ConnectionActivity> {
+        self.activity.clone()
+    }
+
+    pub(crate) fn result_set_warnings(&self) -> ResultSetWarningsRef {
+        self.result_set_warnings.clone()
+    }
+
     async fn do_query(&self, query: &str) -> Vec<Result<Output>> {
         trace!("Start executing query: '{}'", query);
         let start = Instant::now();
@@ -89,14 +144,33 @@ impl MysqlInstanceShim {
         // TODO(LFC): Find a better way to deal with these special federated queries:
         // `check` uses regex to filter out unsupported statements emitted by MySQL's federated
         // components, this is quick and dirty, there must be a better way to do it.
-        let output =
-            if let Some(output) = crate::mysql::federated::check(query, self.session.context()) {
-                vec![Ok(output)]
-            } else {
-                self.query_handler
-                    .do_query(query, self.session.context())
-                    .await
-            };
+        let output = if let Some(output) = crate::mysql::federated::check_set_select_limit(
+            query,
+            self.session.context(),
+            self.default_select_limit,
+        ) {
+            vec![output]
+        } else if let Some(output) =
+            crate::mysql::federated::check_set_as_of(query, self.session.context())
+        {
+            vec![output]
+        } else if let Some(output) =
+            crate::mysql::federated::check_set_time_zone(query, self.session.context())
+        {
+            vec![output]
+        } else if let Some(output) = crate::mysql::federated::check_set_wait_timeout(
+            query,
+            self.session.context(),
+            self.default_idle_timeout,
+        ) {
+            vec![output]
+        } else if let Some(output) = crate::mysql::federated::check(query, self.session.context()) {
+            vec![Ok(output)]
+        } else {
+            self.query_handler
+                .do_query(query, self.session.context())
+                .await
+        };
 
         trace!(
             "Finished executing query: '{}', total time costs in microseconds: {}",
@@ -106,17 +180,70 @@ impl MysqlInstanceShim {
         output
     }
 
-    fn set_query(&self, query: String) -> u32 {
+    fn set_query(&self, stmt: PreparedStatement) -> u32 {
         let stmt_id = self.prepared_stmts_counter.fetch_add(1, Ordering::SeqCst);
         let mut guard = self.prepared_stmts.write();
-        guard.insert(stmt_id, query);
+        guard.insert(stmt_id, stmt);
         stmt_id
     }
 
-    fn query(&self, stmt_id: u32) -> Option<String> {
+    fn query(&self, stmt_id: u32) -> Option<PreparedStatement> {
         let guard = self.prepared_stmts.read();
         guard.get(&stmt_id).cloned()
     }
+
+    /// Infers the types of the `param_num` params of `stmt` from the columns the placeholders
+    /// are bound to, the types are `None` if unknown.
+    async fn infer_param_types(
+        &self,
+        stmt: &Statement,
+        param_num: usize,
+    ) -> Vec<Option<ConcreteDataType>> {
+        let mut param_types = vec![None; param_num];
+        let Some((table, columns)) = helper::placeholder_columns(stmt) else { return param_types };
+        if columns.is_empty() {
+            return param_types;
+        }
+        let Some(schema) = self.describe_table(&table.to_string()).await else {
+            return param_types;
+        };
+
+        for (index, column) in columns {
+            let column_schema = match column {
+                BoundColumn::Name(name) => schema.column_schema_by_name(&name).or_else(|| {
+                    schema
+                        .column_schemas()
+                        .iter()
+                        .find(|column_schema| column_schema.name.eq_ignore_ascii_case(&name))
+                }),
+                BoundColumn::Index(i) => schema.column_schemas().get(i),
+            };
+            if let (Some(param_type), Some(column_schema)) =
+                (param_types.get_mut(index), column_schema)
+            {
+                *param_type = Some(column_schema.data_type.clone());
+            }
+        }
+        param_types
+    }
+
+    async fn describe_table(&self, table: &str) -> Option<Schema> {
+        let query = format!("SELECT * FROM {table}");
+        let stmt = ParserContext::create_with_dialect(&query, &GenericDialect {})
+            .ok()?
+            .pop()?;
+        match self
+            .query_handler
+            .do_describe(stmt, self.session.context())
+            .await
+        {
+            Ok(schema) => schema,
+            Err(e) => {
+                debug!("Failed to describe table {table} of prepared statement, error: {e}");
+                None
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -159,7 +286,9 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
                 }
             };
         }
-        let user_info = user_info.unwrap_or_default();
+        let user_info = self
+            .client_cert_mapping
+            .apply(user_info.unwrap_or_default(), tls::client_cert().as_ref());
 
         self.session.set_user_info(user_info);
 
@@ -171,15 +300,27 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         query: &'a str,
         w: StatementMetaWriter<'a, W>,
     ) -> Result<()> {
-        let (query, param_num) = replace_placeholder(query);
-        if let Err(e) = validate_query(&query).await {
-            w.error(ErrorKind::ER_UNKNOWN_ERROR, e.to_string().as_bytes())
-                .await?;
-            return Ok(());
+        let _command = self.activity.start_statement();
+        let (numbered_query, placeholders) = helper::transform_placeholders(query);
+        let statement = match validate_query(&numbered_query).await {
+            Ok(statement) => statement,
+            Err(e) => {
+                w.error(ErrorKind::ER_UNKNOWN_ERROR, e.to_string().as_bytes())
+                    .await?;
+                return Ok(());
+            }
         };
 
-        let stmt_id = self.set_query(query);
-        let params = dummy_params(param_num);
+        let param_types = self.infer_param_types(&statement, placeholders.len()).await;
+        let params = param_types
+            .iter()
+            .map(|param_type| helper::param_column(param_type.as_ref()))
+            .collect::<Vec<_>>();
+        let stmt_id = self.set_query(PreparedStatement {
+            query: query.to_string(),
+            placeholders,
+            param_types,
+        });
 
         w.reply(stmt_id, &params, &[]).await?;
         return Ok(());
@@ -191,8 +332,9 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         p: ParamParser<'a>,
         w: QueryResultWriter<'a, W>,
     ) -> Result<()> {
+        let _command = self.activity.start_statement();
         let params: Vec<ParamValue> = p.into_iter().collect();
-        let query = match self.query(stmt_id) {
+        let stmt = match self.query(stmt_id) {
             None => {
                 w.error(
                     ErrorKind::ER_UNKNOWN_STMT_HANDLER,
@@ -201,14 +343,40 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
                 .await?;
                 return Ok(());
             }
-            Some(query) => query,
+            Some(stmt) => stmt,
         };
+        if params.len() != stmt.placeholders.len() {
+            let message = format!(
+                "prepare statement expects {} params, got {}",
+                stmt.placeholders.len(),
+                params.len()
+            );
+            w.error(ErrorKind::ER_WRONG_ARGUMENTS, message.as_bytes())
+                .await?;
+            return Ok(());
+        }
 
-        let query = replace_params(params, query);
+        let literals = params
+            .into_iter()
+            .zip(&stmt.param_types)
+            .map(|(param, param_type)| {
+                helper::sql_literal(&helper::convert_param_value(param, param_type.as_ref()))
+            })
+            .collect::<Vec<_>>();
+        let query = helper::bind_params(&stmt.query, &stmt.placeholders, &literals);
         log::debug!("execute replaced query: {}", query);
 
         let outputs = self.do_query(&query).await;
-        writer::write_output(w, &query, outputs).await?;
+        writer::write_output(
+            w,
+            &query,
+            outputs,
+            true,
+            self.float_precision,
+            self.session.context(),
+            &self.result_set_warnings,
+        )
+        .await?;
 
         Ok(())
     }
@@ -226,12 +394,23 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         query: &'a str,
         writer: QueryResultWriter<'a, W>,
     ) -> Result<()> {
+        let _command = self.activity.start_statement();
         let outputs = self.do_query(query).await;
-        writer::write_output(writer, query, outputs).await?;
+        writer::write_output(
+            writer,
+            query,
+            outputs,
+            false,
+            self.float_precision,
+            self.session.context(),
+            &self.result_set_warnings,
+        )
+        .await?;
         Ok(())
     }
 
     async fn on_init<'a>(&'a mut self, database: &'a str, w: InitWriter<'a, W>) -> Result<()> {
+        let _command = self.activity.start_statement();
         let (catalog, schema) = crate::parse_catalog_and_schema_from_client_database_name(database);
         ensure!(
             self.query_handler.is_valid_schema(catalog, schema).await?,
@@ -240,6 +419,18 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
 
         let user_info = &self.session.user_info();
 
+        // There may be no user provider checking the users pinned by their client certificates.
+        if !user_info.can_use_schema(catalog, schema) {
+            let msg = format!(
+                "Access denied for user '{}' to database '{catalog}-{schema}'",
+                user_info.username()
+            );
+            return w
+                .error(ErrorKind::ER_DBACCESS_DENIED_ERROR, msg.as_bytes())
+                .await
+                .map_err(|e| e.into());
+        }
+
         if let Some(schema_validator) = &self.user_provider {
             if let Err(e) = schema_validator.authorize(catalog, schema, user_info).await {
                 return w
@@ -260,33 +451,6 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
     }
 }
 
-fn replace_params(params: Vec<ParamValue>, query: String) -> String {
-    let mut query = query;
-    let mut index = 1;
-    for param in params {
-        let s = match param.value.into_inner() {
-            ValueInner::Int(u) => u.to_string(),
-            ValueInner::UInt(u) => u.to_string(),
-            ValueInner::Double(u) => u.to_string(),
-            ValueInner::NULL => "NULL".to_string(),
-            ValueInner::Bytes(b) => format!("'{}'", &String::from_utf8_lossy(b)),
-            ValueInner::Date(_) => NaiveDate::from(param.value).to_string(),
-            ValueInner::Datetime(_) => NaiveDateTime::from(param.value).to_string(),
-            ValueInner::Time(_) => format_duration(Duration::from(param.value)),
-        };
-        query = query.replace(&format!("${}", index), &s);
-        index += 1;
-    }
-    query
-}
-
-fn format_duration(duration: Duration) -> String {
-    let seconds = duration.as_secs() % 60;
-    let minutes = (duration.as_secs() / 60) % 60;
-    let hours = (duration.as_secs() / 60) / 60;
-    format!("{}:{}:{}", hours, minutes, seconds)
-}
-
 async fn validate_query(query: &str) -> Result<Statement> {
     let statement = ParserContext::create_with_dialect(query, &GenericDialect {});
     let mut statement = statement.map_err(|e| {
@@ -306,38 +470,14 @@ async fn validate_query(query: &str) -> Result<Statement> {
     let statement = statement.remove(0);
 
     ensure!(
-        matches!(statement, Statement::Query(_)),
+        matches!(
+            statement,
+            Statement::Query(_) | Statement::Insert(_) | Statement::Delete(_)
+        ),
         InvalidPrepareStatementSnafu {
-            err_msg: "prepare statement only support SELECT for now".to_string(),
+            err_msg: "prepare statement only support SELECT, INSERT and DELETE".to_string(),
         }
     );
 
     Ok(statement)
 }
-
-// dummy columns to satisfy opensrv_mysql, just the number of params is useful
-// TODO(SSebo): use parameter type inference to return actual types
-fn dummy_params(index: u32) -> Vec<Column> {
-    let mut params = vec![];
-
-    for _ in 1..index {
-        params.push(opensrv_mysql::Column {
-            table: "".to_string(),
-            column: "".to_string(),
-            coltype: ColumnType::MYSQL_TYPE_LONG,
-            colflags: ColumnFlags::NOT_NULL_FLAG,
-        });
-    }
-    params
-}
-
-fn replace_placeholder(query: &str) -> (String, u32) {
-    let mut query = query.to_string();
-    let mut index = 1;
-    while let Some(position) = query.find('?') {
-        let place_holder = format!("${}", index);
-        query.replace_range(position..position + 1, &place_holder);
-        index += 1;
-    }
-    (query, index)
-}
@@ -0,0 +1,391 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers of the prepared statements of the MySQL binary protocol.
+//!
+//! A statement is prepared by numbering its `?` placeholders to `$1`, `$2`... to parse it, and
+//! executed by converting the params to [Value]s and binding them to the placeholders as SQL
+//! literals, so the bound statement goes through the same path as the text protocol queries.
+
+use std::collections::HashMap;
+use std::ops::{ControlFlow, Deref};
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use common_time::{Date, Timestamp};
+use datatypes::prelude::ConcreteDataType;
+use datatypes::value::Value;
+use opensrv_mysql::{Column, ColumnFlags, ColumnType, ParamValue, ValueInner};
+use sql::ast::{visit_expressions, BinaryOperator, Expr, ObjectName, Value as SqlValue};
+use sql::statements::statement::Statement;
+use sql::util::skip_quoted_or_comment;
+
+use crate::mysql::writer;
+
+/// Column of a table a placeholder is bound to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BoundColumn {
+    /// Column of the name.
+    Name(String),
+    /// Column at the index of the table schema, like the values of an `INSERT` without columns.
+    Index(usize),
+}
+
+/// Replaces the `?` placeholders of `query` with the numbered placeholders `$1`, `$2`... the
+/// parser understands, returns the replaced query and the byte offsets of the `?` in `query`.
+///
+/// The `?` in the quoted strings, the quoted identifiers and the comments aren't placeholders.
+pub(crate) fn transform_placeholders(query: &str) -> (String, Vec<usize>) {
+    let mut positions = vec![];
+    let mut i = 0;
+    while i < query.len() {
+        if let Some(end) = skip_quoted_or_comment(query, i) {
+            i = end;
+            continue;
+        }
+        if query.as_bytes()[i] == b'?' {
+            positions.push(i);
+        }
+        i += 1;
+    }
+
+    let numbered = (1..=positions.len())
+        .map(|n| format!("${n}"))
+        .collect::<Vec<_>>();
+    (bind_params(query, &positions, &numbered), positions)
+}
+
+/// Replaces the placeholders at `positions` of `query` with the `literals`.
+pub(crate) fn bind_params(query: &str, positions: &[usize], literals: &[String]) -> String {
+    let mut bound = String::with_capacity(query.len());
+    let mut start = 0;
+    for (position, literal) in positions.iter().zip(literals) {
+        bound.push_str(&query[start..*position]);
+        bound.push_str(literal);
+        start = position + 1;
+    }
+    bound.push_str(&query[start..]);
+    bound
+}
+
+/// Returns the table of `stmt` and the columns its placeholders are bound to, keyed by the
+/// indexes of the placeholders from 0.
+///
+/// The columns are taken from the comparisons like `col = ?`, `col BETWEEN ? AND ?` and
+/// `col IN (?, ?)` of a `SELECT` of one table, and from the values of an `INSERT`. Other
+/// statements aren't supported.
+pub(crate) fn placeholder_columns(
+    stmt: &Statement,
+) -> Option<(ObjectName, HashMap<usize, BoundColumn>)> {
+    let mut columns = HashMap::new();
+    match stmt {
+        Statement::Query(query) => {
+            let table = query.source_table()?.clone();
+            let _ = visit_expressions(&query.inner, |expr| {
+                bind_comparison(expr, &mut columns);
+                ControlFlow::<()>::Continue(())
+            });
+            Some((table, columns))
+        }
+        Statement::Insert(insert) => {
+            let insert_columns = insert.columns();
+            for row in insert.values_body().ok()?? {
+                for (i, value) in row.iter().enumerate() {
+                    let Some(index) = placeholder_index(value) else { continue };
+                    let column = if insert_columns.is_empty() {
+                        BoundColumn::Index(i)
+                    } else if let Some(name) = insert_columns.get(i) {
+                        BoundColumn::Name(name.to_string())
+                    } else {
+                        continue;
+                    };
+                    let _ = columns.insert(index, column);
+                }
+            }
+            Some((insert.table_name().clone(), columns))
+        }
+        _ => None,
+    }
+}
+
+fn bind_comparison(expr: &Expr, columns: &mut HashMap<usize, BoundColumn>) {
+    let (column, operands) = match expr {
+        Expr::BinaryOp { left, op, right } if is_comparison(op) => {
+            match (column_name(left), column_name(right)) {
+                (Some(column), _) => (column, vec![right.as_ref()]),
+                (_, Some(column)) => (column, vec![left.as_ref()]),
+                _ => return,
+            }
+        }
+        Expr::Between {
+            expr, low, high, ..
+        } => match column_name(expr) {
+            Some(column) => (column, vec![low.as_ref(), high.as_ref()]),
+            None => return,
+        },
+        Expr::InList { expr, list, .. } => match column_name(expr) {
+            Some(column) => (column, list.iter().collect()),
+            None => return,
+        },
+        _ => return,
+    };
+    for operand in operands {
+        if let Expr::Value(value) = operand {
+            if let Some(index) = placeholder_index(value) {
+                let _ = columns.insert(index, BoundColumn::Name(column.clone()));
+            }
+        }
+    }
+}
+
+fn is_comparison(op: &BinaryOperator) -> bool {
+    matches!(
+        op,
+        BinaryOperator::Eq
+            | BinaryOperator::NotEq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq
+    )
+}
+
+fn column_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Identifier(ident) => Some(ident.value.clone()),
+        Expr::CompoundIdentifier(idents) => idents.last().map(|ident| ident.value.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the index from 0 of the numbered placeholder `$n`.
+fn placeholder_index(value: &SqlValue) -> Option<usize> {
+    match value {
+        SqlValue::Placeholder(placeholder) => placeholder
+            .strip_prefix('$')?
+            .parse::<usize>()
+            .ok()?
+            .checked_sub(1),
+        _ => None,
+    }
+}
+
+/// Returns the definition of the param of `data_type` to reply to the prepare command, params of
+/// unknown types are strings like MySQL.
+pub(crate) fn param_column(data_type: Option<&ConcreteDataType>) -> Column {
+    let coltype = data_type
+        .and_then(|data_type| writer::mysql_column_type(data_type).ok())
+        .unwrap_or(ColumnType::MYSQL_TYPE_VAR_STRING);
+    let colflags = match data_type {
+        Some(
+            ConcreteDataType::UInt8(_)
+            | ConcreteDataType::UInt16(_)
+            | ConcreteDataType::UInt32(_)
+            | ConcreteDataType::UInt64(_),
+        ) => ColumnFlags::UNSIGNED_FLAG,
+        _ => ColumnFlags::empty(),
+    };
+    Column {
+        table: String::new(),
+        column: "?".to_string(),
+        coltype,
+        colflags,
+    }
+}
+
+/// Converts the param sent by the client to a [Value], `data_type` is the type of the column the
+/// param is bound to if known.
+pub(crate) fn convert_param_value(
+    param: ParamValue,
+    data_type: Option<&ConcreteDataType>,
+) -> Value {
+    let is_boolean = matches!(data_type, Some(ConcreteDataType::Boolean(_)));
+    match param.value.into_inner() {
+        ValueInner::NULL => Value::Null,
+        ValueInner::Int(i) if is_boolean => Value::Boolean(i != 0),
+        ValueInner::Int(i) => Value::Int64(i),
+        ValueInner::UInt(u) if is_boolean => Value::Boolean(u != 0),
+        ValueInner::UInt(u) => Value::UInt64(u),
+        ValueInner::Double(f) => Value::Float64(f.into()),
+        ValueInner::Bytes(b) => match std::str::from_utf8(b) {
+            Ok(s) if !matches!(data_type, Some(ConcreteDataType::Binary(_))) => {
+                Value::String(s.into())
+            }
+            _ => Value::Binary(b.into()),
+        },
+        ValueInner::Date(_) => NaiveDate::from(param.value)
+            .to_string()
+            .parse::<Date>()
+            .map_or(Value::Null, Value::Date),
+        ValueInner::Datetime(_) => Value::Timestamp(Timestamp::new_microsecond(
+            NaiveDateTime::from(param.value).timestamp_micros(),
+        )),
+        // There is no type of the time of day, the times are compared as strings.
+        ValueInner::Time(_) => Value::String(format_duration(Duration::from(param.value)).into()),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs() % 60;
+    let minutes = (duration.as_secs() / 60) % 60;
+    let hours = (duration.as_secs() / 60) / 60;
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+/// Returns the SQL literal of the `value` to bind to a placeholder. The times are written in
+/// UTC like the literals of the text protocol queries.
+pub(crate) fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Float32(f) if !f.is_finite() => format!("CAST('{f}' AS FLOAT)"),
+        Value::Float64(f) if !f.is_finite() => format!("CAST('{f}' AS DOUBLE)"),
+        Value::Boolean(_)
+        | Value::UInt8(_)
+        | Value::UInt16(_)
+        | Value::UInt32(_)
+        | Value::UInt64(_)
+        | Value::Int8(_)
+        | Value::Int16(_)
+        | Value::Int32(_)
+        | Value::Int64(_)
+        | Value::Float32(_)
+        | Value::Float64(_) => value.to_string(),
+        Value::String(s) => quote_string(s.as_utf8()),
+        Value::Binary(b) => format!("X'{}'", hex::encode(b.deref())),
+        Value::Timestamp(ts) => match ts.to_chrono_datetime() {
+            Some(datetime) => quote_string(&datetime.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            None => ts.value().to_string(),
+        },
+        Value::Date(_) | Value::DateTime(_) | Value::List(_) => quote_string(&value.to_string()),
+    }
+}
+
+fn quote_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use sql::dialect::GenericDialect;
+    use sql::parser::ParserContext;
+
+    use super::*;
+
+    #[test]
+    fn test_transform_placeholders() {
+        let (query, positions) =
+            transform_placeholders("SELECT * FROM t WHERE a = ? AND b IN (?,?)");
+        assert_eq!("SELECT * FROM t WHERE a = $1 AND b IN ($2,$3)", query);
+        assert_eq!(vec![26, 38, 40], positions);
+
+        let (query, positions) = transform_placeholders(
+            "SELECT '?', 'it''s ?', 'a\\', \"?\", `?` -- ?\nFROM t /* ? */ WHERE a = ?",
+        );
+        assert_eq!(
+            "SELECT '?', 'it''s ?', 'a\\', \"?\", `?` -- ?\nFROM t /* ? */ WHERE a = $1",
+            query
+        );
+        assert_eq!(1, positions.len());
+
+        assert_eq!(
+            ("SELECT 1".to_string(), vec![]),
+            transform_placeholders("SELECT 1")
+        );
+    }
+
+    #[test]
+    fn test_bind_params() {
+        let query = "SELECT * FROM t WHERE a = ? AND b = ?";
+        let (_, positions) = transform_placeholders(query);
+        assert_eq!(
+            "SELECT * FROM t WHERE a = 1 AND b = 'x'",
+            bind_params(query, &positions, &["1".to_string(), "'x'".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_sql_literal() {
+        assert_eq!("NULL", sql_literal(&Value::Null));
+        assert_eq!("true", sql_literal(&Value::Boolean(true)));
+        assert_eq!("-42", sql_literal(&Value::Int64(-42)));
+        assert_eq!("1.5", sql_literal(&Value::Float64(1.5.into())));
+        assert_eq!(
+            "CAST('NaN' AS DOUBLE)",
+            sql_literal(&Value::Float64(f64::NAN.into()))
+        );
+        assert_eq!("'it''s \\'", sql_literal(&Value::String("it's \\".into())));
+        assert_eq!("X'00ff'", sql_literal(&Value::Binary(vec![0, 255].into())));
+        assert_eq!(
+            "'2023-01-02 03:04:05.000006'",
+            sql_literal(&Value::Timestamp(Timestamp::new_microsecond(
+                1_672_628_645_000_006
+            )))
+        );
+        assert_eq!(
+            "'2023-01-02'",
+            sql_literal(&Value::Date("2023-01-02".parse().unwrap()))
+        );
+    }
+
+    fn columns_of(query: &str) -> Option<(String, Vec<(usize, BoundColumn)>)> {
+        let (query, _) = transform_placeholders(query);
+        let mut stmts = ParserContext::create_with_dialect(&query, &GenericDialect {}).unwrap();
+        placeholder_columns(&stmts.remove(0)).map(|(table, columns)| {
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort_by_key(|(index, _)| *index);
+            (table.to_string(), columns)
+        })
+    }
+
+    #[test]
+    fn test_placeholder_columns() {
+        let name = |name: &str| BoundColumn::Name(name.to_string());
+        assert_eq!(
+            Some((
+                "db.t".to_string(),
+                vec![
+                    (0, name("a")),
+                    (1, name("b")),
+                    (2, name("c")),
+                    (3, name("c")),
+                    (4, name("d")),
+                    (5, name("d")),
+                ]
+            )),
+            columns_of(
+                "SELECT a FROM db.t WHERE a = ? AND ? < t.b AND c BETWEEN ? AND ? \
+                 AND d IN (?, ?) AND e + ? > 1"
+            )
+        );
+        assert_eq!(
+            Some((
+                "t".to_string(),
+                vec![(0, name("b")), (1, name("a")), (2, name("b"))]
+            )),
+            columns_of("INSERT INTO t (a, b) VALUES (1, ?), (?, ?)")
+        );
+        assert_eq!(
+            Some((
+                "t".to_string(),
+                vec![(0, BoundColumn::Index(0)), (1, BoundColumn::Index(2))]
+            )),
+            columns_of("INSERT INTO t VALUES (?, 1, ?)")
+        );
+        assert_eq!(
+            None,
+            columns_of("SELECT * FROM t1 JOIN t2 ON t1.a = t2.a WHERE t1.a = ?")
+        );
+        assert_eq!(None, columns_of("DELETE FROM t WHERE a = ?"));
+    }
+}
@@ -14,44 +14,60 @@
 
 use std::future::Future;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use common_runtime::Runtime;
 use common_telemetry::logging::{info, warn};
+use datatypes::float_format::FloatPrecision;
 use futures::StreamExt;
 use opensrv_mysql::{
     plain_run_with_options, secure_run_with_options, AsyncMysqlIntermediary, IntermediaryOptions,
 };
+use session::context::Channel;
+use session::registry::SessionRegistryRef;
 use tokio;
 use tokio::io::BufWriter;
 use tokio::net::TcpStream;
 use tokio_rustls::rustls::ServerConfig;
 
 use crate::auth::UserProviderRef;
+use crate::connection::{CloseReason, ClosingHandle, ConnectionOptions};
 use crate::error::{Error, Result};
 use crate::mysql::handler::MysqlInstanceShim;
+use crate::mysql::warnings::WarningsWriter;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::server::{AbortableStream, BaseTcpServer, Server};
+use crate::tls::{self, ClientCertMapping};
 
 // Default size of ResultSet write buffer: 100KB
 const DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE: usize = 100 * 1024;
 
+/// Code of the error closing the idle connections, `ER_CLIENT_INTERACTION_TIMEOUT` of MySQL.
+pub const ER_CLIENT_INTERACTION_TIMEOUT: u16 = 4031;
+/// Code of the error closing the connections older than the max age, `ER_SERVER_SHUTDOWN` of
+/// MySQL, on which the clients reconnect.
+pub const ER_SERVER_SHUTDOWN: u16 = 1053;
+
 /// [`MysqlSpawnRef`] stores arc refs
 /// that should be passed to new [`MysqlInstanceShim`]s.
 pub struct MysqlSpawnRef {
     query_handler: ServerSqlQueryHandlerRef,
     user_provider: Option<UserProviderRef>,
+    sessions: SessionRegistryRef,
 }
 
 impl MysqlSpawnRef {
     pub fn new(
         query_handler: ServerSqlQueryHandlerRef,
         user_provider: Option<UserProviderRef>,
+        sessions: SessionRegistryRef,
     ) -> MysqlSpawnRef {
         MysqlSpawnRef {
             query_handler,
             user_provider,
+            sessions,
         }
     }
 
@@ -69,20 +85,32 @@ pub struct MysqlSpawnConfig {
     // tls config
     force_tls: bool,
     tls: Option<Arc<ServerConfig>>,
+    client_cert_mapping: ClientCertMapping,
     // other shim config
     reject_no_database: bool,
+    sql_select_limit: Option<usize>,
+    float_precision: FloatPrecision,
+    connection: ConnectionOptions,
 }
 
 impl MysqlSpawnConfig {
     pub fn new(
         force_tls: bool,
         tls: Option<Arc<ServerConfig>>,
+        client_cert_mapping: ClientCertMapping,
         reject_no_database: bool,
+        sql_select_limit: Option<usize>,
+        float_precision: FloatPrecision,
+        connection: ConnectionOptions,
     ) -> MysqlSpawnConfig {
         MysqlSpawnConfig {
             force_tls,
             tls,
+            client_cert_mapping,
             reject_no_database,
+            sql_select_limit,
+            float_precision,
+            connection,
         }
     }
 
@@ -154,6 +182,9 @@ impl MysqlServer {
         spawn_config: Arc<MysqlSpawnConfig>,
     ) -> Result<()> {
         info!("MySQL connection coming from: {}", stream.peer_addr()?);
+        if let Err(e) = spawn_config.connection.set_keepalive(&stream) {
+            warn!("Failed to set TCP keepalive of MySQL connection: {}", e);
+        }
         io_runtime.spawn(async move {
             // TODO(LFC): Use `output_stream` to write large MySQL ResultSet to client.
             if let Err(e)  = Self::do_handle(stream, spawn_ref, spawn_config).await {
@@ -171,35 +202,99 @@ impl MysqlServer {
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
     ) -> Result<()> {
-        let mut shim = MysqlInstanceShim::create(
+        let client_addr = stream.peer_addr()?;
+        let _registration = spawn_ref.sessions.register(client_addr, Channel::Mysql);
+        let (stream, closing) = ClosingHandle::new(stream)?;
+        let shim = MysqlInstanceShim::create(
             spawn_ref.query_handler(),
             spawn_ref.user_provider(),
-            stream.peer_addr()?,
+            client_addr,
+            spawn_config.sql_select_limit,
+            spawn_config.connection.idle_timeout,
+            spawn_config.float_precision,
+            spawn_config.client_cert_mapping,
         );
+        let query_ctx = shim.session().context();
+        let activity = shim.activity();
+        let client_tls = AtomicBool::new(false);
+
+        let max_age = spawn_config.connection.max_connection_age;
+        let run = tls::scope_client_cert(Self::run(stream, shim, &spawn_config, &client_tls));
+        tokio::select! {
+            result = run => result,
+            reason = activity.wait_close(&query_ctx, max_age) => {
+                info!("Closing MySQL connection from {client_addr}, reason: {reason:?}");
+                // The error packet can't be written in plain text to a TLS connection.
+                let packet = (!client_tls.load(Ordering::Relaxed)).then(|| closing_packet(reason));
+                closing.close(packet.as_deref());
+                Ok(())
+            }
+        }
+    }
+
+    async fn run(
+        stream: TcpStream,
+        mut shim: MysqlInstanceShim,
+        spawn_config: &MysqlSpawnConfig,
+        client_tls: &AtomicBool,
+    ) -> Result<()> {
         let (mut r, w) = stream.into_split();
         let mut w = BufWriter::with_capacity(DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE, w);
+        let result_set_warnings = shim.result_set_warnings();
 
-        let ops = spawn_config.as_ref().into();
+        let ops = spawn_config.into();
 
-        let (client_tls, init_params) =
+        let (is_tls, init_params) =
             AsyncMysqlIntermediary::init_before_ssl(&mut shim, &mut r, &mut w, &spawn_config.tls())
                 .await?;
+        client_tls.store(is_tls, Ordering::Relaxed);
 
-        if spawn_config.force_tls && !client_tls {
+        if spawn_config.force_tls && !is_tls {
             return Err(Error::TlsRequired {
                 server: "mysql".to_owned(),
             });
         }
 
         match spawn_config.tls() {
-            Some(tls_conf) if client_tls => {
+            Some(tls_conf) if is_tls => {
                 secure_run_with_options(shim, w, ops, tls_conf, init_params).await
             }
-            _ => plain_run_with_options(shim, w, ops, init_params).await,
+            _ => {
+                let w = WarningsWriter::new(w, result_set_warnings);
+                plain_run_with_options(shim, w, ops, init_params).await
+            }
         }
     }
 }
 
+/// Error packet telling the client why the connection is closed, which is sent unsolicited with
+/// the sequence id 0 of a new command, like MySQL does.
+fn closing_packet(reason: CloseReason) -> Vec<u8> {
+    let (code, message) = match reason {
+        CloseReason::IdleTimeout => (
+            ER_CLIENT_INTERACTION_TIMEOUT,
+            "The client was disconnected by the server because of inactivity. See wait_timeout \
+             for configuring this behavior.",
+        ),
+        CloseReason::MaxAge => (
+            ER_SERVER_SHUTDOWN,
+            "The client was disconnected by the server because the connection is older than \
+             the max connection age. Please reconnect.",
+        ),
+    };
+    let mut payload = Vec::with_capacity(9 + message.len());
+    payload.push(0xff);
+    payload.extend_from_slice(&code.to_le_bytes());
+    payload.extend_from_slice(b"#HY000");
+    payload.extend_from_slice(message.as_bytes());
+
+    let mut packet = Vec::with_capacity(4 + payload.len());
+    packet.extend_from_slice(&(payload.len() as u32).to_le_bytes()[..3]);
+    packet.push(0);
+    packet.extend(payload);
+    packet
+}
+
 pub const MYSQL_SERVER: &str = "MYSQL_SERVER";
 
 #[async_trait]
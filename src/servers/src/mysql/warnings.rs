@@ -0,0 +1,314 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Warning count of the packet terminating a result set.
+//!
+//! opensrv-mysql always writes no warnings in the packet terminating a result set, which is an
+//! EOF packet, or an OK packet if the client negotiates `CLIENT_DEPRECATE_EOF`. And some
+//! warnings of a query, like the truncation by the select limit, are only known once all the
+//! rows are written. So the count is set to the [ResultSetWarnings] of the connection before
+//! the result set is terminated, and the [WarningsWriter] between opensrv-mysql and the socket
+//! patches it into the terminator packet passing through.
+//!
+//! The packets of TLS connections are encrypted by opensrv-mysql before they are written, so
+//! the counts of their result sets are not patched, and the clients only see the warnings by
+//! `SHOW WARNINGS`.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use tokio::io::AsyncWrite;
+
+/// Length of the header of a packet: 3 bytes of the payload length and the sequence id.
+const HEADER_LEN: usize = 4;
+/// Payload length of an EOF packet: the header, warnings and status flags.
+const EOF_PAYLOAD_LEN: usize = 5;
+/// Payloads of this length continue in the next packet.
+const MAX_PAYLOAD_LEN: usize = 0xFF_FFFF;
+
+pub type ResultSetWarningsRef = Arc<ResultSetWarnings>;
+
+/// Warning count of the result set being terminated on a connection.
+#[derive(Debug, Default)]
+pub struct ResultSetWarnings {
+    /// Whether a [WarningsWriter] patches the counts.
+    enabled: AtomicBool,
+    pending: Mutex<Option<u16>>,
+}
+
+impl ResultSetWarnings {
+    /// Sets the warning count of the result set being written. Must be called after all the
+    /// rows are written, right before the result set is terminated.
+    pub fn set(&self, warnings: usize) {
+        if warnings > 0 && self.enabled.load(Ordering::Relaxed) {
+            *self.pending.lock().unwrap() = Some(u16::try_from(warnings).unwrap_or(u16::MAX));
+        }
+    }
+
+    fn pending(&self) -> bool {
+        self.pending.lock().unwrap().is_some()
+    }
+}
+
+/// Writer patching the warning count of [ResultSetWarnings] into the next packet terminating a
+/// result set.
+pub struct WarningsWriter<W> {
+    inner: W,
+    warnings: ResultSetWarningsRef,
+    /// Bytes of the header of the current packet received so far.
+    header: Vec<u8>,
+    /// Bytes of the payload of the current packet to pass through.
+    payload_left: usize,
+    /// The current packet, buffered as it may terminate a result set.
+    packet: Option<Vec<u8>>,
+    /// Bytes to write to `inner` before anything else, and how many of them are written.
+    out: Vec<u8>,
+    out_pos: usize,
+}
+
+impl<W> WarningsWriter<W> {
+    pub fn new(inner: W, warnings: ResultSetWarningsRef) -> Self {
+        warnings.enabled.store(true, Ordering::Relaxed);
+        Self {
+            inner,
+            warnings,
+            header: Vec::with_capacity(HEADER_LEN),
+            payload_left: 0,
+            packet: None,
+            out: Vec::new(),
+            out_pos: 0,
+        }
+    }
+
+    /// Consumes the bytes of the header of the next packet from `buf`.
+    fn consume_header(&mut self, buf: &[u8]) -> usize {
+        let n = buf.len().min(HEADER_LEN - self.header.len());
+        self.header.extend_from_slice(&buf[..n]);
+        if self.header.len() < HEADER_LEN {
+            return n;
+        }
+
+        let header = std::mem::replace(&mut self.header, Vec::with_capacity(HEADER_LEN));
+        let len = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+        if self.warnings.pending() {
+            let mut packet = Vec::with_capacity(HEADER_LEN + len);
+            packet.extend_from_slice(&header);
+            self.packet = Some(packet);
+            self.payload_left = len;
+            self.complete_packet();
+        } else {
+            self.out.extend_from_slice(&header);
+            self.payload_left = len;
+        }
+        n
+    }
+
+    /// Consumes the payload bytes of the buffered packet from `buf`.
+    fn consume_packet(&mut self, buf: &[u8]) -> usize {
+        let n = buf.len().min(self.payload_left);
+        if let Some(packet) = &mut self.packet {
+            packet.extend_from_slice(&buf[..n]);
+        }
+        self.payload_left -= n;
+        self.complete_packet();
+        n
+    }
+
+    /// Patches the buffered packet if it's complete and terminates a result set, then writes
+    /// it out.
+    fn complete_packet(&mut self) {
+        if self.payload_left > 0 {
+            return;
+        }
+        let Some(mut packet) = self.packet.take() else { return };
+        let mut pending = self.warnings.pending.lock().unwrap();
+        if let Some(warnings) = *pending {
+            if patch_terminator(&mut packet[HEADER_LEN..], warnings) {
+                *pending = None;
+            }
+        }
+        self.out.extend_from_slice(&packet);
+    }
+}
+
+impl<W: AsyncWrite + Unpin> WarningsWriter<W> {
+    fn poll_write_out(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.out_pos < self.out.len() {
+            let n = futures::ready!(
+                Pin::new(&mut self.inner).poll_write(cx, &self.out[self.out_pos..])
+            )?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.out_pos += n;
+        }
+        self.out.clear();
+        self.out_pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for WarningsWriter<W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        futures::ready!(this.poll_write_out(cx))?;
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if this.packet.is_some() {
+            return Poll::Ready(Ok(this.consume_packet(buf)));
+        }
+        if this.payload_left == 0 {
+            return Poll::Ready(Ok(this.consume_header(buf)));
+        }
+        let n = buf.len().min(this.payload_left);
+        let written = futures::ready!(Pin::new(&mut this.inner).poll_write(cx, &buf[..n]))?;
+        this.payload_left -= written;
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures::ready!(self.poll_write_out(cx))?;
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures::ready!(self.poll_write_out(cx))?;
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Sets the warnings of the `payload` if it's an EOF or OK packet terminating a result set,
+/// returns whether it is.
+fn patch_terminator(payload: &mut [u8], warnings: u16) -> bool {
+    if payload.first() != Some(&0xFE) || payload.len() >= MAX_PAYLOAD_LEN {
+        return false;
+    }
+    let warnings = warnings.to_le_bytes();
+    if payload.len() == EOF_PAYLOAD_LEN {
+        // EOF packet: header, warnings, status flags.
+        payload[1..3].copy_from_slice(&warnings);
+        return true;
+    }
+
+    // OK packet: header, affected rows, last insert id, status flags, warnings, info.
+    let mut pos = 1;
+    for _ in 0..2 {
+        pos += match payload.get(pos) {
+            Some(0xFC) => 3,
+            Some(0xFD) => 4,
+            Some(0xFE) => 9,
+            Some(_) => 1,
+            None => return false,
+        };
+    }
+    pos += 2;
+    match payload.get_mut(pos..pos + 2) {
+        Some(bytes) => {
+            bytes.copy_from_slice(&warnings);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+
+    fn packet(seq: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = (payload.len() as u32).to_le_bytes()[..3].to_vec();
+        packet.push(seq);
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    async fn write_packets(warnings: &ResultSetWarningsRef, packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut writer = WarningsWriter::new(Vec::new(), warnings.clone());
+        for packet in packets {
+            // Writes the header and the payload separately, like opensrv-mysql.
+            writer.write_all(&packet[..HEADER_LEN]).await.unwrap();
+            writer.write_all(&packet[HEADER_LEN..]).await.unwrap();
+        }
+        writer.flush().await.unwrap();
+        writer.inner
+    }
+
+    #[tokio::test]
+    async fn test_patch_eof_terminator() {
+        let warnings = ResultSetWarningsRef::default();
+        let row = packet(3, &[0x01, b'1']);
+        let eof = packet(4, &[0xFE, 0, 0, 0x02, 0]);
+        assert_eq!(
+            [row.clone(), eof.clone()].concat(),
+            write_packets(&warnings, &[row.clone(), eof.clone()]).await
+        );
+
+        let mut writer = WarningsWriter::new(Vec::new(), warnings.clone());
+        writer.write_all(&row).await.unwrap();
+        warnings.set(2);
+        writer.write_all(&eof).await.unwrap();
+        writer.flush().await.unwrap();
+        let patched = packet(4, &[0xFE, 2, 0, 0x02, 0]);
+        assert_eq!([row, patched].concat(), writer.inner);
+        assert!(!warnings.pending());
+    }
+
+    #[tokio::test]
+    async fn test_patch_ok_terminator() {
+        let warnings = ResultSetWarningsRef::default();
+        let ok = packet(5, &[0xFE, 0, 0, 0x02, 0, 0, 0]);
+        let mut writer = WarningsWriter::new(Vec::new(), warnings.clone());
+        warnings.set(70000);
+        // Other packets are passed through while waiting for the terminator.
+        let row = packet(4, &[0x01, b'1']);
+        writer.write_all(&row).await.unwrap();
+        writer.write_all(&ok).await.unwrap();
+        writer.flush().await.unwrap();
+
+        let patched = packet(5, &[0xFE, 0, 0, 0x02, 0, 0xFF, 0xFF]);
+        assert_eq!([row, patched].concat(), writer.inner);
+        assert!(!warnings.pending());
+    }
+
+    #[test]
+    fn test_patch_terminator() {
+        // Affected rows and last insert id of multiple bytes.
+        let mut payload = vec![0xFE, 0xFC, 1, 1, 0xFD, 1, 1, 1, 0x02, 0, 0, 0];
+        assert!(patch_terminator(&mut payload, 3));
+        assert_eq!([3, 0], payload[10..]);
+
+        let mut row = vec![0x01, 0xFE];
+        assert!(!patch_terminator(&mut row, 3));
+        let mut truncated = vec![0xFE, 0, 0, 0x02];
+        assert!(!patch_terminator(&mut truncated, 3));
+    }
+
+    #[test]
+    fn test_disabled_without_writer() {
+        let warnings = ResultSetWarnings::default();
+        warnings.set(1);
+        assert!(!warnings.pending());
+    }
+}
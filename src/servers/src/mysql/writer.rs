@@ -15,29 +15,53 @@
 use std::ops::Deref;
 
 use common_query::Output;
-use common_recordbatch::{util, RecordBatch};
+use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
 use common_telemetry::error;
+use common_time::TimeZone;
+use datatypes::float_format::{format_f32, format_f64, FloatPrecision};
 use datatypes::prelude::{ConcreteDataType, Value};
 use datatypes::schema::{ColumnSchema, SchemaRef};
+use futures::StreamExt;
 use opensrv_mysql::{
     Column, ColumnFlags, ColumnType, ErrorKind, OkResponse, QueryResultWriter, RowWriter,
 };
+use session::context::QueryContextRef;
 use snafu::prelude::*;
 use tokio::io::AsyncWrite;
 
 use crate::error::{self, Error, Result};
+use crate::mysql::warnings::ResultSetWarnings;
 
 /// Try to write multiple output to the writer if possible.
+///
+/// `binary_protocol` is whether the results are written in the binary protocol of prepared
+/// statements, instead of the text protocol, and `float_precision` is the precision of the
+/// floats written in the text protocol. The times are written in the time zone of
+/// `query_ctx`, and the columns carry the tables of the result of the last query in it. The
+/// warnings in it are of the last statement, so they are counted in the response of the last
+/// output, and set to `result_set_warnings` if the output is a result set.
 pub async fn write_output<'a, W: AsyncWrite + Send + Sync + Unpin>(
     w: QueryResultWriter<'a, W>,
     query: &str,
     outputs: Vec<Result<Output>>,
+    binary_protocol: bool,
+    float_precision: FloatPrecision,
+    query_ctx: QueryContextRef,
+    result_set_warnings: &ResultSetWarnings,
 ) -> Result<()> {
-    let mut writer = Some(MysqlResultWriter::new(w));
-    for output in outputs {
-        let result_writer = writer.take().context(error::InternalSnafu {
+    let mut writer = Some(
+        MysqlResultWriter::new(w, binary_protocol, query_ctx.time_zone())
+            .with_float_precision(float_precision)
+            .with_result_tables(query_ctx.result_tables()),
+    );
+    let num_outputs = outputs.len();
+    for (i, output) in outputs.into_iter().enumerate() {
+        let mut result_writer = writer.take().context(error::InternalSnafu {
             err_msg: "Sending multiple result set is unsupported",
         })?;
+        if i + 1 == num_outputs {
+            result_writer = result_writer.with_warnings_of(&query_ctx, result_set_warnings);
+        }
         writer = result_writer.try_write_one(query, output).await?;
     }
 
@@ -47,18 +71,67 @@ pub async fn write_output<'a, W: AsyncWrite + Send + Sync + Unpin>(
     Ok(())
 }
 
-struct QueryResult {
-    recordbatches: Vec<RecordBatch>,
-    schema: SchemaRef,
-}
-
-pub struct MysqlResultWriter<'a, W: AsyncWrite + Unpin> {
+pub struct MysqlResultWriter<'a, 'b, W: AsyncWrite + Unpin> {
     writer: QueryResultWriter<'a, W>,
+    binary_protocol: bool,
+    /// Precision of the floats written in the text protocol.
+    float_precision: FloatPrecision,
+    /// Time zone to write the times in.
+    time_zone: TimeZone,
+    /// Columns of the result set and the tables they are read from.
+    result_tables: Vec<(String, Option<String>)>,
+    /// Context holding the warnings of the output, and where to set their count if the output
+    /// is a result set, as they are only known once the rows are written.
+    warnings: Option<(&'b QueryContextRef, &'b ResultSetWarnings)>,
 }
 
-impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
-    pub fn new(writer: QueryResultWriter<'a, W>) -> MysqlResultWriter<'a, W> {
-        MysqlResultWriter::<'a, W> { writer }
+impl<'a, 'b, W: AsyncWrite + Unpin> MysqlResultWriter<'a, 'b, W> {
+    pub fn new(
+        writer: QueryResultWriter<'a, W>,
+        binary_protocol: bool,
+        time_zone: TimeZone,
+    ) -> MysqlResultWriter<'a, 'b, W> {
+        MysqlResultWriter::<'a, 'b, W> {
+            writer,
+            binary_protocol,
+            float_precision: FloatPrecision::default(),
+            time_zone,
+            result_tables: Vec::new(),
+            warnings: None,
+        }
+    }
+
+    /// Writes the floats in `float_precision` in the text protocol. The binary protocol always
+    /// writes them as they are.
+    pub fn with_float_precision(mut self, float_precision: FloatPrecision) -> Self {
+        self.float_precision = float_precision;
+        self
+    }
+
+    /// Fills the tables of the columns of the result set by `result_tables`, see
+    /// [create_mysql_column_def].
+    pub fn with_result_tables(mut self, result_tables: Vec<(String, Option<String>)>) -> Self {
+        self.result_tables = result_tables;
+        self
+    }
+
+    /// Reports the count of the warnings in `query_ctx` in the packet ending the output, so the
+    /// clients know to `SHOW WARNINGS`. The count of a result set is set to
+    /// `result_set_warnings` before it's terminated. The count is capped at `u16::MAX` as the
+    /// packets hold.
+    pub fn with_warnings_of(
+        mut self,
+        query_ctx: &'b QueryContextRef,
+        result_set_warnings: &'b ResultSetWarnings,
+    ) -> Self {
+        self.warnings = Some((query_ctx, result_set_warnings));
+        self
+    }
+
+    fn num_warnings(&self) -> usize {
+        self.warnings
+            .map(|(query_ctx, _)| query_ctx.warnings().len())
+            .unwrap_or(0)
     }
 
     /// Try to write one result set. If there are more than one result set, return `Some`.
@@ -66,32 +139,25 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
         self,
         query: &str,
         output: Result<Output>,
-    ) -> Result<Option<MysqlResultWriter<'a, W>>> {
+    ) -> Result<Option<MysqlResultWriter<'a, 'b, W>>> {
         // We don't support sending multiple query result because the RowWriter's lifetime is bound to
         // a local variable.
         match output {
             Ok(output) => match output {
-                Output::Stream(stream) => {
-                    let schema = stream.schema().clone();
-                    let recordbatches = util::collect(stream)
-                        .await
-                        .context(error::CollectRecordbatchSnafu)?;
-                    let query_result = QueryResult {
-                        recordbatches,
-                        schema,
-                    };
-                    Self::write_query_result(query, query_result, self.writer).await?;
-                }
+                Output::Stream(stream) => self.write_query_result(query, stream).await?,
                 Output::RecordBatches(recordbatches) => {
-                    let query_result = QueryResult {
-                        schema: recordbatches.schema(),
-                        recordbatches: recordbatches.take(),
-                    };
-                    Self::write_query_result(query, query_result, self.writer).await?;
+                    let stream = recordbatches.as_stream();
+                    self.write_query_result(query, stream).await?;
                 }
                 Output::AffectedRows(rows) => {
-                    let next_writer = Self::write_affected_rows(self.writer, rows).await?;
-                    return Ok(Some(MysqlResultWriter::new(next_writer)));
+                    let warnings = u16::try_from(self.num_warnings()).unwrap_or(u16::MAX);
+                    let next_writer =
+                        Self::write_affected_rows(self.writer, rows, warnings).await?;
+                    return Ok(Some(
+                        MysqlResultWriter::new(next_writer, self.binary_protocol, self.time_zone)
+                            .with_float_precision(self.float_precision)
+                            .with_result_tables(self.result_tables),
+                    ));
                 }
             },
             Err(error) => Self::write_query_error(query, error, self.writer).await?,
@@ -108,39 +174,77 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
     async fn write_affected_rows(
         w: QueryResultWriter<'a, W>,
         rows: usize,
+        warnings: u16,
     ) -> Result<QueryResultWriter<'a, W>> {
         let next_writer = w
             .complete_one(OkResponse {
                 affected_rows: rows as u64,
+                warnings,
                 ..Default::default()
             })
             .await?;
         Ok(next_writer)
     }
 
+    /// Writes the record batches of `stream` one by one as they are polled, so only one batch
+    /// is buffered at a time.
+    ///
+    /// If polling the stream fails after the rows of some batches are written, the result set
+    /// is terminated by an error packet instead of an end-of-rows packet. The rows already
+    /// written are not taken back, so the client may have received a partial result before
+    /// the error.
     async fn write_query_result(
+        self,
         query: &str,
-        query_result: QueryResult,
-        writer: QueryResultWriter<'a, W>,
+        mut stream: SendableRecordBatchStream,
     ) -> Result<()> {
-        match create_mysql_column_def(&query_result.schema) {
+        match create_mysql_column_def(&stream.schema(), &self.result_tables) {
             Ok(column_def) => {
                 // The RowWriter's lifetime is bound to `column_def` thus we can't use finish_one()
                 // to return a new QueryResultWriter.
-                let mut row_writer = writer.start(&column_def).await?;
-                for recordbatch in &query_result.recordbatches {
-                    Self::write_recordbatch(&mut row_writer, recordbatch).await?;
+                let mut row_writer = self.writer.start(&column_def).await?;
+                while let Some(recordbatch) = stream.next().await {
+                    match recordbatch {
+                        Ok(recordbatch) => {
+                            Self::write_recordbatch(
+                                &mut row_writer,
+                                &recordbatch,
+                                self.binary_protocol,
+                                self.float_precision,
+                                &self.time_zone,
+                            )
+                            .await?;
+                        }
+                        Err(e) => {
+                            let error = Error::CollectRecordbatch { source: e };
+                            error!(error; "Failed to read the result of query '{}'", query);
+
+                            let kind = ErrorKind::ER_INTERNAL_ERROR;
+                            row_writer
+                                .finish_error(kind, error.to_string().as_bytes())
+                                .await?;
+                            return Ok(());
+                        }
+                    }
+                }
+                // The warnings are complete once the rows are written, e.g. the truncation by
+                // the select limit is only known after polling the last row.
+                if let Some((query_ctx, result_set_warnings)) = self.warnings {
+                    result_set_warnings.set(query_ctx.warnings().len());
                 }
                 row_writer.finish().await?;
                 Ok(())
             }
-            Err(error) => Self::write_query_error(query, error, writer).await,
+            Err(error) => Self::write_query_error(query, error, self.writer).await,
         }
     }
 
     async fn write_recordbatch(
         row_writer: &mut RowWriter<'_, W>,
         recordbatch: &RecordBatch,
+        binary_protocol: bool,
+        float_precision: FloatPrecision,
+        time_zone: &TimeZone,
     ) -> Result<()> {
         for row in recordbatch.rows() {
             for value in row.into_iter() {
@@ -155,13 +259,25 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
                     Value::Int16(v) => row_writer.write_col(v)?,
                     Value::Int32(v) => row_writer.write_col(v)?,
                     Value::Int64(v) => row_writer.write_col(v)?,
-                    Value::Float32(v) => row_writer.write_col(v.0)?,
-                    Value::Float64(v) => row_writer.write_col(v.0)?,
+                    // MySQL has no NaN, so it's written as NULL.
+                    Value::Float32(v) if v.is_nan() => row_writer.write_col(None::<f32>)?,
+                    Value::Float64(v) if v.is_nan() => row_writer.write_col(None::<f64>)?,
+                    Value::Float32(v) if binary_protocol => row_writer.write_col(v.0)?,
+                    Value::Float64(v) if binary_protocol => row_writer.write_col(v.0)?,
+                    // Floats in text are written the same as other output formats.
+                    Value::Float32(v) => row_writer.write_col(format_f32(v.0, float_precision))?,
+                    Value::Float64(v) => row_writer.write_col(format_f64(v.0, float_precision))?,
                     Value::String(v) => row_writer.write_col(v.as_utf8())?,
                     Value::Binary(v) => row_writer.write_col(v.deref())?,
-                    Value::Date(v) => row_writer.write_col(v.val())?,
-                    Value::DateTime(v) => row_writer.write_col(v.val())?,
-                    Value::Timestamp(v) => row_writer.write_col(v.to_iso8601_string())?,
+                    // Times are written in the text format of MySQL, like
+                    // `YYYY-MM-DD HH:MM:SS[.fff]`, in the time zone of the session.
+                    Value::Date(v) => row_writer.write_col(v.to_string())?,
+                    Value::DateTime(v) => {
+                        row_writer.write_col(v.to_string_in_time_zone(time_zone))?
+                    }
+                    Value::Timestamp(v) => {
+                        row_writer.write_col(v.to_string_in_time_zone(time_zone))?
+                    }
                     Value::List(_) => {
                         return Err(Error::Internal {
                             err_msg: format!(
@@ -190,8 +306,9 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
     }
 }
 
-fn create_mysql_column(column_schema: &ColumnSchema) -> Result<Column> {
-    let column_type = match column_schema.data_type {
+/// Returns the MySQL type of the columns of `data_type`.
+pub(crate) fn mysql_column_type(data_type: &ConcreteDataType) -> Result<ColumnType> {
+    match data_type {
         ConcreteDataType::Null(_) => Ok(ColumnType::MYSQL_TYPE_NULL),
         ConcreteDataType::Boolean(_) | ConcreteDataType::Int8(_) | ConcreteDataType::UInt8(_) => {
             Ok(ColumnType::MYSQL_TYPE_TINY)
@@ -205,44 +322,70 @@ fn create_mysql_column(column_schema: &ColumnSchema) -> Result<Column> {
         }
         ConcreteDataType::Float32(_) => Ok(ColumnType::MYSQL_TYPE_FLOAT),
         ConcreteDataType::Float64(_) => Ok(ColumnType::MYSQL_TYPE_DOUBLE),
-        ConcreteDataType::Binary(_) | ConcreteDataType::String(_) => {
-            Ok(ColumnType::MYSQL_TYPE_VARCHAR)
-        }
+        ConcreteDataType::Binary(_) => Ok(ColumnType::MYSQL_TYPE_BLOB),
+        ConcreteDataType::String(_) => Ok(ColumnType::MYSQL_TYPE_VARCHAR),
         ConcreteDataType::Timestamp(_) => Ok(ColumnType::MYSQL_TYPE_TIMESTAMP),
         ConcreteDataType::Date(_) => Ok(ColumnType::MYSQL_TYPE_DATE),
         ConcreteDataType::DateTime(_) => Ok(ColumnType::MYSQL_TYPE_DATETIME),
         _ => error::InternalSnafu {
-            err_msg: format!(
-                "not implemented for column datatype {:?}",
-                column_schema.data_type
-            ),
+            err_msg: format!("not implemented for column datatype {data_type:?}"),
         }
         .fail(),
-    };
+    }
+}
+
+fn create_mysql_column(table_name: &str, column_schema: &ColumnSchema) -> Result<Column> {
+    let column_type = mysql_column_type(&column_schema.data_type);
     let mut colflags = ColumnFlags::empty();
     match column_schema.data_type {
         ConcreteDataType::UInt16(_)
         | ConcreteDataType::UInt8(_)
         | ConcreteDataType::UInt32(_)
         | ConcreteDataType::UInt64(_) => colflags |= ColumnFlags::UNSIGNED_FLAG,
+        // Like MySQL, blobs are flagged both as blobs and as binary.
+        ConcreteDataType::Binary(_) => {
+            colflags |= ColumnFlags::BLOB_FLAG | ColumnFlags::BINARY_FLAG
+        }
+        ConcreteDataType::Timestamp(_) => colflags |= ColumnFlags::TIMESTAMP_FLAG,
         _ => {}
     };
+    if !column_schema.is_nullable() {
+        colflags |= ColumnFlags::NOT_NULL_FLAG;
+    }
     column_type.map(|column_type| Column {
         column: column_schema.name.clone(),
         coltype: column_type,
-
-        // TODO(LFC): Currently "table" and "colflags" are not relevant in MySQL server
-        //   implementation, will revisit them again in the future.
-        table: "".to_string(),
+        table: table_name.to_string(),
         colflags,
     })
 }
 
 /// Creates MySQL columns definition from our column schema.
-pub fn create_mysql_column_def(schema: &SchemaRef) -> Result<Vec<Column>> {
-    schema
-        .column_schemas()
+///
+/// The table of each column is taken from `result_tables`, the columns of the result and the
+/// tables they are read from. It's empty for the computed columns, or if `result_tables` is
+/// not of this schema, e.g. the result of a statement other than a query.
+/// The length of the columns is left to `opensrv_mysql`, which doesn't take it from the column
+/// definition.
+pub fn create_mysql_column_def(
+    schema: &SchemaRef,
+    result_tables: &[(String, Option<String>)],
+) -> Result<Vec<Column>> {
+    let column_schemas = schema.column_schemas();
+    let is_of_schema = result_tables.len() == column_schemas.len()
+        && result_tables
+            .iter()
+            .zip(column_schemas)
+            .all(|((name, _), column_schema)| *name == column_schema.name);
+    column_schemas
         .iter()
-        .map(create_mysql_column)
+        .enumerate()
+        .map(|(i, column_schema)| {
+            let table_name = match result_tables.get(i) {
+                Some((_, Some(table_name))) if is_of_schema => table_name.as_str(),
+                _ => "",
+            };
+            create_mysql_column(table_name, column_schema)
+        })
         .collect()
 }
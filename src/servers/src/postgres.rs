@@ -25,19 +25,22 @@ pub(crate) const METADATA_SCHEMA: &str = "schema";
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use derive_builder::Builder;
 use pgwire::api::auth::ServerParameterProvider;
 use pgwire::api::store::MemPortalStore;
 use pgwire::api::{ClientInfo, MakeHandler};
-pub use server::PostgresServer;
+pub use server::{PostgresServer, ADMIN_SHUTDOWN_CODE, IDLE_SESSION_TIMEOUT_CODE};
 use session::context::{QueryContext, QueryContextRef};
 use sql::statements::statement::Statement;
 
 use self::auth_handler::PgLoginVerifier;
 use self::handler::POCQueryParser;
 use crate::auth::UserProviderRef;
+use crate::connection::ConnectionActivity;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::tls::ClientCertMapping;
 
 pub(crate) struct GreptimeDBStartupParameters {
     version: &'static str,
@@ -71,11 +74,15 @@ pub struct PostgresServerHandler {
     query_handler: ServerSqlQueryHandlerRef,
     login_verifier: PgLoginVerifier,
     force_tls: bool,
+    client_cert_mapping: ClientCertMapping,
     param_provider: Arc<GreptimeDBStartupParameters>,
 
     query_ctx: QueryContextRef,
     portal_store: Arc<MemPortalStore<(Statement, String)>>,
     query_parser: Arc<POCQueryParser>,
+    /// Idle timeout of the session restored by `SET IDLE_SESSION_TIMEOUT TO DEFAULT`.
+    default_idle_timeout: Option<Duration>,
+    activity: Arc<ConnectionActivity>,
 }
 
 #[derive(Builder)]
@@ -87,21 +94,30 @@ pub(crate) struct MakePostgresServerHandler {
     #[builder(default = "Arc::new(POCQueryParser::default())")]
     query_parser: Arc<POCQueryParser>,
     force_tls: bool,
+    #[builder(default)]
+    client_cert_mapping: ClientCertMapping,
+    #[builder(default)]
+    idle_timeout: Option<Duration>,
 }
 
 impl MakeHandler for MakePostgresServerHandler {
     type Handler = Arc<PostgresServerHandler>;
 
     fn make(&self) -> Self::Handler {
+        let query_ctx = QueryContext::arc();
+        query_ctx.set_idle_timeout(self.idle_timeout);
         Arc::new(PostgresServerHandler {
             query_handler: self.query_handler.clone(),
             login_verifier: PgLoginVerifier::new(self.user_provider.clone()),
             force_tls: self.force_tls,
+            client_cert_mapping: self.client_cert_mapping,
             param_provider: self.param_provider.clone(),
 
-            query_ctx: QueryContext::arc(),
+            query_ctx,
             portal_store: Arc::new(MemPortalStore::new()),
             query_parser: self.query_parser.clone(),
+            default_idle_timeout: self.idle_timeout,
+            activity: Arc::new(ConnectionActivity::new()),
         })
     }
 }
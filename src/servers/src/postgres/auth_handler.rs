@@ -15,6 +15,7 @@
 use std::fmt::Debug;
 
 use async_trait::async_trait;
+use common_catalog::consts::DEFAULT_CATALOG_NAME;
 use futures::{Sink, SinkExt};
 use pgwire::api::auth::StartupHandler;
 use pgwire::api::{auth, ClientInfo, PgWireConnectionState};
@@ -22,12 +23,13 @@ use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
 use pgwire::messages::response::ErrorResponse;
 use pgwire::messages::startup::Authentication;
 use pgwire::messages::{PgWireBackendMessage, PgWireFrontendMessage};
-use session::context::QueryContextRef;
+use session::context::UserInfo;
 
 use super::PostgresServerHandler;
 use crate::auth::{Identity, Password, UserProviderRef};
 use crate::error::Result;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::tls;
 
 pub(crate) struct PgLoginVerifier {
     user_provider: Option<UserProviderRef>,
@@ -68,47 +70,89 @@ impl LoginInfo {
 }
 
 impl PgLoginVerifier {
-    async fn auth(&self, login: &LoginInfo, password: &str) -> Result<bool> {
+    async fn auth(&self, login: &LoginInfo, password: &str) -> Result<Option<UserInfo>> {
         let user_provider = match &self.user_provider {
             Some(provider) => provider,
-            None => return Ok(false),
+            None => return Ok(None),
         };
 
         let user_name = match &login.user {
             Some(name) => name,
-            None => return Ok(false),
-        };
-        let catalog = match &login.catalog {
-            Some(name) => name,
-            None => return Ok(false),
-        };
-        let schema = match &login.schema {
-            Some(name) => name,
-            None => return Ok(false),
+            None => return Ok(None),
         };
 
-        let _user_info = user_provider
-            .auth(
+        let user_info = user_provider
+            .authenticate(
                 Identity::UserId(user_name, None),
                 Password::PlainText(password),
-                catalog,
-                schema,
             )
             .await?;
 
-        Ok(true)
+        Ok(Some(user_info))
+    }
+
+    async fn authorize(&self, catalog: &str, schema: &str, user_info: &UserInfo) -> Result<()> {
+        match &self.user_provider {
+            Some(provider) => provider.authorize(catalog, schema, user_info).await,
+            None => Ok(()),
+        }
     }
 }
 
-fn set_query_context_from_client_info<C>(client: &C, query_context: QueryContextRef)
+/// Returns the catalog and schema the client specifies by the database of the startup message.
+fn client_database<C>(client: &C) -> Option<(String, String)>
 where
     C: ClientInfo,
 {
-    if let Some(current_catalog) = client.metadata().get(super::METADATA_CATALOG) {
-        query_context.set_current_catalog(current_catalog);
-    }
-    if let Some(current_schema) = client.metadata().get(super::METADATA_SCHEMA) {
-        query_context.set_current_schema(current_schema);
+    let metadata = client.metadata();
+    let catalog = metadata.get(super::METADATA_CATALOG)?;
+    let schema = metadata.get(super::METADATA_SCHEMA)?;
+    Some((catalog.clone(), schema.clone()))
+}
+
+impl PostgresServerHandler {
+    /// Starts the session of the user in the database of the client, or the default schema of
+    /// the user if the client doesn't specify one. Returns the code and message of the error
+    /// if the user can't use the database.
+    async fn start_session(
+        &self,
+        database: Option<(String, String)>,
+        user_info: UserInfo,
+    ) -> std::result::Result<(), (&'static str, String)> {
+        let (catalog, schema) = match (database, user_info.default_schema()) {
+            (Some(database), _) => database,
+            (None, Some(schema)) => (
+                user_info
+                    .default_catalog()
+                    .unwrap_or(DEFAULT_CATALOG_NAME)
+                    .to_owned(),
+                schema.to_owned(),
+            ),
+            (None, None) => return Err(("3D000", "Database not specified".to_owned())),
+        };
+        // Checked without the user provider as well, for the users pinned by client certificates.
+        if !user_info.can_use_schema(&catalog, &schema) {
+            return Err((
+                "42501",
+                format!(
+                    "Access denied for user '{}' to database '{catalog}-{schema}'",
+                    user_info.username()
+                ),
+            ));
+        }
+        if let Err(e) = self
+            .login_verifier
+            .authorize(&catalog, &schema, &user_info)
+            .await
+        {
+            return Err(("42501", e.to_string()));
+        }
+
+        self.query_ctx.use_default_schema_of(&user_info);
+        self.query_ctx.set_current_catalog(&catalog);
+        self.query_ctx.set_current_schema(&schema);
+        self.query_ctx.set_current_user(user_info);
+        Ok(())
     }
 }
 
@@ -148,6 +192,8 @@ impl StartupHandler for PostgresServerHandler {
                         send_error(client, "FATAL", "3D000", msg).await?;
                         return Ok(());
                     }
+                    // Resolved to the default schema of the user once authenticated.
+                    DbResolution::Unspecified => {}
                 }
 
                 if self.login_verifier.user_provider.is_some() {
@@ -158,7 +204,14 @@ impl StartupHandler for PostgresServerHandler {
                         ))
                         .await?;
                 } else {
-                    set_query_context_from_client_info(client, self.query_ctx.clone());
+                    let user_info = self
+                        .client_cert_mapping
+                        .apply(UserInfo::default(), tls::client_cert().as_ref());
+                    if let Err((code, msg)) =
+                        self.start_session(client_database(client), user_info).await
+                    {
+                        return send_error(client, "FATAL", code, msg).await;
+                    }
                     auth::finish_authentication(client, self.param_provider.as_ref()).await;
                 }
             }
@@ -172,7 +225,7 @@ impl StartupHandler for PostgresServerHandler {
 
                 // do authenticate
                 let auth_result = self.login_verifier.auth(&login_info, pwd.password()).await;
-                if !matches!(auth_result, Ok(true)) {
+                let Ok(Some(user_info)) = auth_result else {
                     return send_error(
                         client,
                         "FATAL",
@@ -180,8 +233,15 @@ impl StartupHandler for PostgresServerHandler {
                         "password authentication failed".to_owned(),
                     )
                     .await;
+                };
+                let user_info = self
+                    .client_cert_mapping
+                    .apply(user_info, tls::client_cert().as_ref());
+                if let Err((code, msg)) =
+                    self.start_session(client_database(client), user_info).await
+                {
+                    return send_error(client, "FATAL", code, msg).await;
                 }
-                set_query_context_from_client_info(client, self.query_ctx.clone());
                 auth::finish_authentication(client, self.param_provider.as_ref()).await;
             }
             _ => {}
@@ -207,6 +267,7 @@ where
 enum DbResolution {
     Resolved(String, String),
     NotFound(String),
+    Unspecified,
 }
 
 /// A function extracted to resolve lifetime and readability issues:
@@ -233,6 +294,6 @@ where
             Ok(DbResolution::NotFound(format!("Database not found: {db}")))
         }
     } else {
-        Ok(DbResolution::NotFound("Database not specified".to_owned()))
+        Ok(DbResolution::Unspecified)
     }
 }
@@ -14,6 +14,7 @@
 
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use common_query::Output;
@@ -22,6 +23,7 @@ use common_recordbatch::RecordBatch;
 use datatypes::prelude::{ConcreteDataType, Value};
 use datatypes::schema::{Schema, SchemaRef};
 use futures::{future, stream, Stream, StreamExt};
+use once_cell::sync::Lazy;
 use pgwire::api::portal::{Format, Portal};
 use pgwire::api::query::{ExtendedQueryHandler, SimpleQueryHandler, StatementOrPortal};
 use pgwire::api::results::{
@@ -31,6 +33,8 @@ use pgwire::api::stmt::QueryParser;
 use pgwire::api::store::MemPortalStore;
 use pgwire::api::{ClientInfo, Type};
 use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
+use regex::Regex;
+use session::context::QueryContext;
 use sql::dialect::GenericDialect;
 use sql::parser::ParserContext;
 use sql::statements::statement::Statement;
@@ -38,12 +42,27 @@ use sql::statements::statement::Statement;
 use super::PostgresServerHandler;
 use crate::error::{self, Error, Result};
 
+// `SET [SESSION] IDLE_SESSION_TIMEOUT { = | TO } <value> | DEFAULT`.
+static SET_IDLE_SESSION_TIMEOUT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^SET\s+(?:SESSION\s+)?IDLE_SESSION_TIMEOUT(?:\s*=|\s+TO)\s*(?:'([^']*)'|(\w+))\s*;?\s*$",
+    )
+    .unwrap()
+});
+
 #[async_trait]
 impl SimpleQueryHandler for PostgresServerHandler {
     async fn do_query<'a, C>(&self, _client: &C, query: &'a str) -> PgWireResult<Vec<Response<'a>>>
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
+        let _statement = self.activity.start_statement();
+        if let Some(resp) =
+            check_set_idle_session_timeout(query, &self.query_ctx, self.default_idle_timeout)
+        {
+            return resp.map(|resp| vec![resp]);
+        }
+
         let outputs = self
             .query_handler
             .do_query(query, self.query_ctx.clone())
@@ -60,6 +79,55 @@ impl SimpleQueryHandler for PostgresServerHandler {
     }
 }
 
+/// Sets the idle timeout of the session if the query is `SET IDLE_SESSION_TIMEOUT`, like
+/// PostgreSQL, in millis or with a unit like `'5min'`. `0` disables the timeout and `DEFAULT`
+/// restores `default_timeout` configured by the server.
+fn check_set_idle_session_timeout(
+    query: &str,
+    query_ctx: &QueryContext,
+    default_timeout: Option<Duration>,
+) -> Option<PgWireResult<Response<'static>>> {
+    let captures = SET_IDLE_SESSION_TIMEOUT_PATTERN.captures(query)?;
+    let value = captures.get(1).or_else(|| captures.get(2))?.as_str();
+    let timeout = if captures.get(2).is_some() && value.eq_ignore_ascii_case("DEFAULT") {
+        default_timeout
+    } else {
+        match parse_timeout(value) {
+            Some(timeout) if timeout.is_zero() => None,
+            Some(timeout) => Some(timeout),
+            None => {
+                return Some(Err(PgWireError::UserError(Box::new(ErrorInfo::new(
+                    "ERROR".to_owned(),
+                    "22023".to_owned(),
+                    format!("invalid value for parameter \"idle_session_timeout\": \"{value}\""),
+                )))))
+            }
+        }
+    };
+    query_ctx.set_idle_timeout(timeout);
+    Some(Ok(Response::Execution(Tag::new_for_execution("SET", None))))
+}
+
+/// Parses a timeout in millis, or with a unit of `ms`, `s`, `min`, `h` or `d`.
+fn parse_timeout(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (amount, unit) = value.split_at(
+        value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len()),
+    );
+    let millis = match unit.trim() {
+        "" | "ms" => 1,
+        "s" => 1000,
+        "min" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        "d" => 24 * 60 * 60 * 1000,
+        _ => return None,
+    };
+    let amount = amount.parse::<u64>().ok()?;
+    Some(Duration::from_millis(amount.checked_mul(millis)?))
+}
+
 fn output_to_query_response<'a>(
     output: Result<Output>,
     field_format: &Format,
@@ -336,6 +404,7 @@ impl ExtendedQueryHandler for PostgresServerHandler {
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
+        let _statement = self.activity.start_statement();
         let (_, sql) = portal.statement().statement();
 
         // manually replace variables in prepared statement
@@ -615,4 +684,35 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_set_idle_session_timeout() {
+        let query_ctx = QueryContext::new();
+        let default = Some(Duration::from_secs(600));
+        assert!(check_set_idle_session_timeout("select 1", &query_ctx, default).is_none());
+
+        let set = |query: &str| {
+            let resp = check_set_idle_session_timeout(query, &query_ctx, default)
+                .unwrap()
+                .unwrap();
+            assert!(matches!(resp, Response::Execution(_)));
+            query_ctx.idle_timeout()
+        };
+        let minutes = |n| Some(Duration::from_secs(n * 60));
+        assert_eq!(minutes(5), set("SET idle_session_timeout = '5min'"));
+        assert_eq!(default, set("set idle_session_timeout to default;"));
+        assert_eq!(minutes(1), set("SET SESSION IDLE_SESSION_TIMEOUT TO 60000"));
+        assert_eq!(None, set("SET idle_session_timeout = 0"));
+        assert_eq!(minutes(120), set("SET idle_session_timeout='2h'"));
+
+        for query in [
+            "SET idle_session_timeout = '5 weeks'",
+            "SET idle_session_timeout = forever",
+        ] {
+            assert!(check_set_idle_session_timeout(query, &query_ctx, default)
+                .unwrap()
+                .is_err());
+        }
+        assert_eq!(minutes(120), query_ctx.idle_timeout());
+    }
 }
@@ -19,24 +19,36 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use common_runtime::Runtime;
 use common_telemetry::logging::error;
-use common_telemetry::{debug, warn};
+use common_telemetry::{debug, info, warn};
 use futures::StreamExt;
 use pgwire::api::MakeHandler;
 use pgwire::tokio::process_socket;
+use session::context::Channel;
+use session::registry::SessionRegistryRef;
 use tokio;
+use tokio::net::TcpStream;
 use tokio_rustls::TlsAcceptor;
 
-use super::{MakePostgresServerHandler, MakePostgresServerHandlerBuilder};
+use super::{MakePostgresServerHandler, MakePostgresServerHandlerBuilder, PostgresServerHandler};
 use crate::auth::UserProviderRef;
+use crate::connection::{CloseReason, ClosingHandle, ConnectionOptions};
 use crate::error::Result;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::server::{AbortableStream, BaseTcpServer, Server};
-use crate::tls::TlsOption;
+use crate::tls::{self, TlsOption};
+
+/// Code of the error closing the idle connections, `idle_session_timeout` of PostgreSQL.
+pub const IDLE_SESSION_TIMEOUT_CODE: &str = "57P05";
+/// Code of the error closing the connections older than the max age, `admin_shutdown` of
+/// PostgreSQL.
+pub const ADMIN_SHUTDOWN_CODE: &str = "57P01";
 
 pub struct PostgresServer {
     base_server: BaseTcpServer,
     make_handler: Arc<MakePostgresServerHandler>,
     tls: TlsOption,
+    connection: ConnectionOptions,
+    sessions: SessionRegistryRef,
 }
 
 impl PostgresServer {
@@ -46,12 +58,16 @@ impl PostgresServer {
         tls: TlsOption,
         io_runtime: Arc<Runtime>,
         user_provider: Option<UserProviderRef>,
+        connection: ConnectionOptions,
+        sessions: SessionRegistryRef,
     ) -> PostgresServer {
         let make_handler = Arc::new(
             MakePostgresServerHandlerBuilder::default()
                 .query_handler(query_handler.clone())
                 .user_provider(user_provider.clone())
                 .force_tls(tls.should_force_tls())
+                .client_cert_mapping(tls.client_cert_mapping())
+                .idle_timeout(connection.idle_timeout)
                 .build()
                 .unwrap(),
         );
@@ -59,6 +75,8 @@ impl PostgresServer {
             base_server: BaseTcpServer::create_server("Postgres", io_runtime),
             make_handler,
             tls,
+            connection,
+            sessions,
         }
     }
 
@@ -69,32 +87,117 @@ impl PostgresServer {
         tls_acceptor: Option<Arc<TlsAcceptor>>,
     ) -> impl Future<Output = ()> {
         let handler = self.make_handler.clone();
+        let connection = self.connection.clone();
+        let sessions = self.sessions.clone();
         accepting_stream.for_each(move |tcp_stream| {
             let io_runtime = io_runtime.clone();
             let tls_acceptor = tls_acceptor.clone();
             let handler = handler.make();
+            let connection = connection.clone();
+            let sessions = sessions.clone();
 
             async move {
                 match tcp_stream {
                     Err(error) => error!("Broken pipe: {}", error), // IoError doesn't impl ErrorExt.
                     Ok(io_stream) => {
-                        match io_stream.peer_addr() {
-                            Ok(addr) => debug!("PostgreSQL client coming from {}", addr),
-                            Err(e) => warn!("Failed to get PostgreSQL client addr, err: {}", e),
+                        if let Err(e) = connection.set_keepalive(&io_stream) {
+                            warn!(
+                                "Failed to set TCP keepalive of PostgreSQL connection: {}",
+                                e
+                            );
                         }
-
-                        io_runtime.spawn(process_socket(
+                        io_runtime.spawn(Self::handle(
                             io_stream,
-                            tls_acceptor.clone(),
-                            handler.clone(),
-                            handler.clone(),
+                            tls_acceptor,
                             handler,
+                            connection,
+                            sessions,
                         ));
                     }
                 };
             }
         })
     }
+
+    async fn handle(
+        stream: TcpStream,
+        tls_acceptor: Option<Arc<TlsAcceptor>>,
+        handler: Arc<PostgresServerHandler>,
+        connection: ConnectionOptions,
+        sessions: SessionRegistryRef,
+    ) {
+        let client_addr = match stream.peer_addr() {
+            Ok(addr) => addr,
+            Err(e) => {
+                warn!("Failed to get PostgreSQL client addr, err: {}", e);
+                return;
+            }
+        };
+        debug!("PostgreSQL client coming from {}", client_addr);
+        let _registration = sessions.register(client_addr, Channel::Postgres);
+        let (stream, closing) = match ClosingHandle::new(stream) {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Failed to handle PostgreSQL connection from {client_addr}, err: {e}");
+                return;
+            }
+        };
+
+        let query_ctx = handler.query_ctx.clone();
+        let activity = handler.activity.clone();
+        // The error message can't be written in plain text if the client may use TLS.
+        let plain = tls_acceptor.is_none();
+        let process = tls::scope_client_cert(process_socket(
+            stream,
+            tls_acceptor,
+            handler.clone(),
+            handler.clone(),
+            handler,
+        ));
+        tokio::select! {
+            result = process => {
+                if let Err(e) = result {
+                    debug!("PostgreSQL connection from {client_addr} is closed, err: {e}");
+                }
+            }
+            reason = activity.wait_close(&query_ctx, connection.max_connection_age) => {
+                info!("Closing PostgreSQL connection from {client_addr}, reason: {reason:?}");
+                closing.close(plain.then(|| closing_message(reason)).as_deref());
+            }
+        }
+    }
+}
+
+/// Fatal `ErrorResponse` message telling the client why the connection is closed.
+fn closing_message(reason: CloseReason) -> Vec<u8> {
+    let (code, message) = match reason {
+        CloseReason::IdleTimeout => (
+            IDLE_SESSION_TIMEOUT_CODE,
+            "terminating connection due to idle-session timeout",
+        ),
+        CloseReason::MaxAge => (
+            ADMIN_SHUTDOWN_CODE,
+            "terminating connection due to max connection age",
+        ),
+    };
+    let mut body = Vec::new();
+    for (field, value) in [
+        (b'S', "FATAL"),
+        (b'V', "FATAL"),
+        (b'C', code),
+        (b'M', message),
+    ] {
+        body.push(field);
+        body.extend_from_slice(value.as_bytes());
+        body.push(0);
+    }
+    body.push(0);
+
+    let mut msg = Vec::with_capacity(5 + body.len());
+    msg.push(b'E');
+    msg.extend_from_slice(&(body.len() as i32 + 4).to_be_bytes());
+    msg.extend(body);
+    msg
 }
 
 pub const POSTGRES_SERVER: &str = "POSTGRES_SERVER";
@@ -240,6 +240,12 @@ impl PromJsonResponse {
                         result_type: "matrix".to_string(),
                         ..Default::default()
                     })
+                } else if matches!(
+                    err.status_code(),
+                    StatusCode::InvalidSyntax | StatusCode::InvalidArguments
+                ) {
+                    // Invalid queries, like too many points, are bad data to Prometheus.
+                    Self::error("bad_data", err.to_string())
                 } else {
                     Self::error(err.status_code().to_string(), err.to_string())
                 }
@@ -283,6 +283,21 @@ fn recordbatch_to_timeseries(table: &str, recordbatch: RecordBatch) -> Result<Ve
     Ok(timeseries_map.into_values().collect())
 }
 
+/// Removes the samples with NaN values, like the stale markers of Prometheus, and the
+/// timeseries left without samples.
+///
+/// NaN values are stored as is otherwise, and they propagate through aggregations: `sum` and
+/// `avg` over them return NaN, `max` returns NaN as it orders NaN above all values, while `count`
+/// counts them like other non-null values.
+pub fn drop_nan_samples(request: &mut WriteRequest) {
+    for timeseries in &mut request.timeseries {
+        timeseries.samples.retain(|sample| !sample.value.is_nan());
+    }
+    request
+        .timeseries
+        .retain(|timeseries| !timeseries.samples.is_empty());
+}
+
 pub fn to_grpc_insert_requests(mut request: WriteRequest) -> Result<Vec<GrpcInsertRequest>> {
     let timeseries = std::mem::take(&mut request.timeseries);
     timeseries.into_iter().map(to_grpc_insert_request).collect()
@@ -602,6 +617,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_drop_nan_samples() {
+        let mut timeseries = mock_timeseries();
+        timeseries[0].samples[0].value = f64::NAN;
+        timeseries[1].samples[0].value = f64::NAN;
+        timeseries[1].samples[1].value = f64::NAN;
+        timeseries[2].samples[2].value = f64::INFINITY;
+        let mut write_request = WriteRequest {
+            timeseries,
+            ..Default::default()
+        };
+
+        drop_nan_samples(&mut write_request);
+        let timeseries = &write_request.timeseries;
+        assert_eq!(2, timeseries.len());
+        assert_eq!(
+            vec![(2000, 2.0)],
+            timeseries[0]
+                .samples
+                .iter()
+                .map(|x| (x.timestamp, x.value))
+                .collect::<Vec<_>>()
+        );
+        // Infinite values are kept.
+        assert_eq!(
+            vec![5.0, 6.0, f64::INFINITY],
+            timeseries[1]
+                .samples
+                .iter()
+                .map(|x| x.value)
+                .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_recordbatches_to_timeseries() {
         let schema = Arc::new(Schema::new(vec![
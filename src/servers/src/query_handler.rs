@@ -27,11 +27,18 @@ pub mod sql;
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use api::prometheus::remote::{ReadRequest, WriteRequest};
+use api::v1::InsertRequest as GrpcInsertRequest;
 use async_trait::async_trait;
+use common_grpc_expr::dry_run::InsertDryRun;
 use common_query::Output;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use session::context::QueryContextRef;
+use table::requests::RegionRequest;
+use table::TableRef;
 
 use crate::error::Result;
 use crate::influxdb::InfluxdbRequest;
@@ -39,9 +46,17 @@ use crate::opentsdb::codec::DataPoint;
 use crate::prometheus::Metrics;
 
 pub type OpentsdbProtocolHandlerRef = Arc<dyn OpentsdbProtocolHandler + Send + Sync>;
+pub type DdlBatchHandlerRef = Arc<dyn DdlBatchHandler + Send + Sync>;
 pub type InfluxdbLineProtocolHandlerRef = Arc<dyn InfluxdbLineProtocolHandler + Send + Sync>;
+pub type JsonIngestHandlerRef = Arc<dyn JsonIngestHandler + Send + Sync>;
 pub type PrometheusProtocolHandlerRef = Arc<dyn PrometheusProtocolHandler + Send + Sync>;
 pub type ScriptHandlerRef = Arc<dyn ScriptHandler + Send + Sync>;
+pub type StorageUsageHandlerRef = Arc<dyn StorageUsageHandler + Send + Sync>;
+pub type OrphanGcHandlerRef = Arc<dyn OrphanGcHandler + Send + Sync>;
+pub type RegionHandlerRef = Arc<dyn RegionHandler + Send + Sync>;
+pub type JobHandlerRef = Arc<dyn JobHandler + Send + Sync>;
+pub type RunningQueryHandlerRef = Arc<dyn RunningQueryHandler + Send + Sync>;
+pub type ReadinessHandlerRef = Arc<dyn ReadinessHandler + Send + Sync>;
 
 #[async_trait]
 pub trait ScriptHandler {
@@ -52,6 +67,9 @@ pub trait ScriptHandler {
         name: &str,
         params: HashMap<String, String>,
     ) -> Result<Output>;
+
+    /// Returns the compile states of the scripts.
+    async fn script_states(&self) -> Result<Output>;
 }
 
 #[async_trait]
@@ -59,6 +77,277 @@ pub trait InfluxdbLineProtocolHandler {
     /// A successful request will not return a response.
     /// Only on error will the socket return a line of data.
     async fn exec(&self, request: &InfluxdbRequest, ctx: QueryContextRef) -> Result<()>;
+
+    /// Dry runs the inserts of the lines, which reports the tables to create, the columns to
+    /// add and the errors of the rows, without writing anything.
+    async fn dry_run(
+        &self,
+        request: &InfluxdbRequest,
+        ctx: QueryContextRef,
+    ) -> Result<Vec<InsertDryRun>>;
+}
+
+#[async_trait]
+pub trait JsonIngestHandler {
+    /// Returns the table to ingest into, `None` if it doesn't exist yet.
+    async fn table(&self, table_name: &str, ctx: QueryContextRef) -> Result<Option<TableRef>>;
+
+    /// Inserts the rows flattened from JSON documents, creating the table or adding the
+    /// missing columns if necessary.
+    async fn ingest(&self, request: GrpcInsertRequest, ctx: QueryContextRef) -> Result<()>;
+
+    /// Dry runs the insertion of the rows, without creating or altering the table or writing
+    /// anything.
+    async fn dry_run(
+        &self,
+        request: GrpcInsertRequest,
+        ctx: QueryContextRef,
+    ) -> Result<InsertDryRun>;
+}
+
+/// Status of a table in a batch of table creations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableCreationStatus {
+    Created,
+    /// The table already exists and is created with `IF NOT EXISTS`, so it's left untouched.
+    Existed,
+    /// The table is invalid or fails to be created, which fails the whole batch.
+    Failed,
+    /// The table was created, but dropped as another table of the batch failed.
+    RolledBack,
+    /// The table is not created as another table of the batch failed.
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableCreation {
+    /// Full name of the table, or the position of the statement if it's not a `CREATE TABLE`.
+    pub table: String,
+    pub status: TableCreationStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TableCreation {
+    pub fn new(table: String, status: TableCreationStatus) -> Self {
+        Self {
+            table,
+            status,
+            error: None,
+        }
+    }
+
+    pub fn failed(table: String, error: String) -> Self {
+        Self {
+            table,
+            status: TableCreationStatus::Failed,
+            error: Some(error),
+        }
+    }
+}
+
+#[async_trait]
+pub trait DdlBatchHandler {
+    /// Creates the tables of the `CREATE TABLE` statements in `sql` all or nothing. All the
+    /// tables are validated before creating any of them, and the created tables are dropped
+    /// if a table fails to be created.
+    async fn create_tables(&self, sql: &str, ctx: QueryContextRef) -> Result<Vec<TableCreation>>;
+}
+
+/// Bytes stored in the object store by the tables, as of the last sample.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub sampled_at_millis: i64,
+    /// Time of the previous sample the deltas are computed against, if any.
+    pub previous_sampled_at_millis: Option<i64>,
+    pub tables: Vec<TableStorageUsage>,
+    pub schemas: Vec<SchemaStorageUsage>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableStorageUsage {
+    pub catalog: String,
+    pub schema: String,
+    pub table: String,
+    /// Bytes of the SSTs and the manifests, including the SSTs removed by compaction but not
+    /// purged yet.
+    pub storage_bytes: u64,
+    /// Change of `storage_bytes` since the previous sample, or `None` if the table is not
+    /// in the previous sample.
+    pub delta_bytes: Option<i64>,
+    /// Bytes found by listing the objects of the table, only on demand as listing is costly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listed: Option<ListedStorageUsage>,
+}
+
+/// Bytes of the objects under the directory of a table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListedStorageUsage {
+    pub sst_bytes: u64,
+    pub manifest_bytes: u64,
+    pub other_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaStorageUsage {
+    pub catalog: String,
+    pub schema: String,
+    pub table_num: usize,
+    pub storage_bytes: u64,
+    /// Change of `storage_bytes` since the previous sample, or `None` if there is no previous
+    /// sample.
+    pub delta_bytes: Option<i64>,
+}
+
+#[async_trait]
+pub trait StorageUsageHandler {
+    /// Returns the bytes stored by the tables as of the last sample. Also lists the objects
+    /// of each table if `full` is true.
+    async fn storage_usage(&self, full: bool) -> Result<StorageUsage>;
+}
+
+/// Orphans found in the storage of a table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanGcReport {
+    pub catalog: String,
+    pub schema: String,
+    pub table: String,
+    pub orphans: Vec<Orphan>,
+}
+
+/// An object in the storage of a table that no region of the table references.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Orphan {
+    pub path: String,
+    /// Kind of the object, `sst` or `manifest`.
+    pub kind: String,
+    pub size: u64,
+    /// Seconds since the object was last modified, or `None` if unknown.
+    pub age_secs: Option<u64>,
+    pub deleted: bool,
+}
+
+#[async_trait]
+pub trait OrphanGcHandler {
+    /// Finds the orphans of the table. Also deletes the orphans older than `safety_age`, or
+    /// than the configured safety age if it's `None`, if `delete` is true.
+    async fn gc_orphans(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table: &str,
+        delete: bool,
+        safety_age: Option<Duration>,
+    ) -> Result<OrphanGcReport>;
+}
+
+#[async_trait]
+pub trait RegionHandler {
+    /// Handles the `request` to a region of a table. Returns the watermark of the region if
+    /// the request returns one.
+    async fn handle_region_request(&self, request: RegionRequest) -> Result<Option<u64>>;
+}
+
+/// State of an asynchronous job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Status of an asynchronous job, like `COPY TO` with `async = 'true'`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: u64,
+    /// Type of the job, e.g. `copy_to`.
+    #[serde(rename = "type")]
+    pub job_type: String,
+    pub description: String,
+    pub state: JobState,
+    pub rows: u64,
+    pub bytes: u64,
+    pub files: u64,
+    /// URLs of the files produced, only reported once the job is completed.
+    pub file_urls: Vec<String>,
+    /// Error of the job if it's failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[async_trait]
+pub trait JobHandler {
+    /// Returns the status of the job, or `None` if there is no such job.
+    async fn job(&self, id: u64) -> Result<Option<JobStatus>>;
+
+    /// Cancels the job if it's running and waits until it stops. The objects the job has
+    /// partially written are removed. Returns the status of the job afterwards, or `None` if
+    /// there is no such job.
+    async fn cancel_job(&self, id: u64) -> Result<Option<JobStatus>>;
+}
+
+/// A long-running statement, like `COPY`, with its progress.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunningQuery {
+    pub id: u64,
+    pub description: String,
+    /// Who issues the statement, e.g. `user` or `background(copy_to)`.
+    pub origin: String,
+    /// Start time in milliseconds since the epoch.
+    pub start_time: i64,
+    pub elapsed_secs: u64,
+    /// Number of units done, e.g. the files imported.
+    pub done: u64,
+    /// Total number of units, `None` if unknown.
+    pub total: Option<u64>,
+    /// The item being processed, e.g. the file being read.
+    pub current_item: Option<String>,
+    /// Estimated remaining time in seconds, `None` if unknown.
+    pub eta_secs: Option<u64>,
+}
+
+pub trait RunningQueryHandler {
+    /// Returns the long-running statements running on the node, in the order they started.
+    fn running_queries(&self) -> Vec<RunningQuery>;
+}
+
+/// Status of the components a node depends on to serve requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ComponentStatus {
+    /// Whether the catalog manager has completed its start.
+    pub catalog_manager_started: bool,
+    /// Whether the meta server is reachable, `None` if the node doesn't use one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta_client_connected: Option<bool>,
+    /// Number of the alive datanodes in the cluster, if the node talks to datanodes. `None` if
+    /// the node doesn't, or the number is unknown as the meta server is unreachable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datanodes: Option<u64>,
+}
+
+impl ComponentStatus {
+    /// Returns the name of the first component not ready to serve, or `None` if the node is
+    /// ready.
+    pub fn unready_component(&self) -> Option<&'static str> {
+        if !self.catalog_manager_started {
+            Some("catalog_manager")
+        } else if self.meta_client_connected == Some(false) {
+            Some("meta_client")
+        } else if self.datanodes == Some(0) {
+            Some("datanodes")
+        } else {
+            None
+        }
+    }
+}
+
+#[async_trait]
+pub trait ReadinessHandler {
+    /// Returns the status of the components, checking the remote ones like the meta server.
+    async fn component_status(&self) -> ComponentStatus;
 }
 
 #[async_trait]
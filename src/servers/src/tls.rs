@@ -12,12 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::RefCell;
 use std::fs::File;
+use std::future::Future;
 use std::io::{BufReader, Error, ErrorKind};
-
-use rustls::{Certificate, PrivateKey, ServerConfig};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rustls::client::HandshakeSignatureValid;
+use rustls::server::{
+    AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, ClientCertVerified,
+    ClientCertVerifier,
+};
+use rustls::{
+    Certificate, DigitallySignedStruct, DistinguishedName, PrivateKey, RootCertStore, ServerConfig,
+    SignatureScheme,
+};
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
 use serde::{Deserialize, Serialize};
+use session::context::UserInfo;
 use strum::EnumString;
 
 /// TlsMode is used for Mysql and Postgres server start up.
@@ -43,6 +56,19 @@ pub enum TlsMode {
     VerifyFull,
 }
 
+/// Where the default schema of a client is read from its certificate.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientCertSchema {
+    /// The client certificate doesn't set the default schema.
+    #[default]
+    Disable,
+    /// The first DNS name of the subject alternative names.
+    San,
+    /// The first organizational unit of the subject.
+    Ou,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub struct TlsOption {
@@ -51,6 +77,17 @@ pub struct TlsOption {
     pub cert_path: String,
     #[serde(default)]
     pub key_path: String,
+    /// CA certificates verifying the client certificates, which enables mutual TLS. The clients
+    /// must present a certificate unless the mode is `prefer`.
+    #[serde(default)]
+    pub ca_cert_path: String,
+    /// Where the default schema of the clients is read from their certificates, in the form
+    /// of `[<catalog>-]<schema>`.
+    #[serde(default)]
+    pub client_cert_schema: ClientCertSchema,
+    /// Whether the clients are pinned to the schema of their certificates.
+    #[serde(default)]
+    pub client_cert_schema_strict: bool,
 }
 
 impl TlsOption {
@@ -76,9 +113,7 @@ impl TlsOption {
         if let TlsMode::Disable = self.mode {
             return Ok(None);
         }
-        let cert = certs(&mut BufReader::new(File::open(&self.cert_path)?))
-            .map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid cert"))
-            .map(|mut certs| certs.drain(..).map(Certificate).collect())?;
+        let cert = load_certs(&self.cert_path)?;
 
         let key = {
             let mut pkcs8 = pkcs8_private_keys(&mut BufReader::new(File::open(&self.key_path)?))
@@ -96,19 +131,245 @@ impl TlsOption {
             }
         };
 
-        // TODO(SSebo): with_client_cert_verifier if TlsMode is Required.
-        let config = ServerConfig::builder()
-            .with_safe_defaults()
-            .with_no_client_auth()
+        let builder = ServerConfig::builder().with_safe_defaults();
+        let builder = if self.ca_cert_path.is_empty() {
+            builder.with_no_client_auth()
+        } else {
+            builder.with_client_cert_verifier(self.client_cert_verifier()?)
+        };
+        let config = builder
             .with_single_cert(cert, key)
             .map_err(|err| std::io::Error::new(ErrorKind::InvalidInput, err))?;
 
         Ok(Some(config))
     }
 
+    fn client_cert_verifier(&self) -> Result<Arc<dyn ClientCertVerifier>, Error> {
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(&self.ca_cert_path)? {
+            roots
+                .add(&cert)
+                .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        }
+        let verifier = if self.should_force_tls() {
+            AllowAnyAuthenticatedClient::new(roots).boxed()
+        } else {
+            AllowAnyAnonymousOrAuthenticatedClient::new(roots).boxed()
+        };
+        Ok(Arc::new(RecordingClientCertVerifier(verifier)))
+    }
+
     pub fn should_force_tls(&self) -> bool {
         !matches!(self.mode, TlsMode::Disable | TlsMode::Prefer)
     }
+
+    pub fn client_cert_mapping(&self) -> ClientCertMapping {
+        ClientCertMapping {
+            schema: self.client_cert_schema,
+            strict: self.client_cert_schema_strict,
+        }
+    }
+}
+
+fn load_certs(path: &str) -> Result<Vec<Certificate>, Error> {
+    certs(&mut BufReader::new(File::open(path)?))
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid cert"))
+        .map(|mut certs| certs.drain(..).map(Certificate).collect())
+}
+
+/// Maps the client certificates to the default schemas of the clients.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClientCertMapping {
+    schema: ClientCertSchema,
+    strict: bool,
+}
+
+impl ClientCertMapping {
+    /// Sets the default schema of the user from the certificate of the client, if the user
+    /// doesn't have one from the user provider.
+    pub fn apply(&self, user_info: UserInfo, cert: Option<&Certificate>) -> UserInfo {
+        if user_info.default_schema().is_some() {
+            return user_info;
+        }
+        let database = match (self.schema, cert) {
+            (ClientCertSchema::San, Some(cert)) => first_dns_name(&cert.0),
+            (ClientCertSchema::Ou, Some(cert)) => first_organizational_unit(&cert.0),
+            _ => None,
+        };
+        let Some(database) = database else { return user_info };
+        let (catalog, schema) =
+            crate::parse_catalog_and_schema_from_client_database_name(&database);
+        user_info.with_default_schema(Some(catalog.to_string()), schema, self.strict)
+    }
+}
+
+tokio::task_local! {
+    /// Certificate the client of the connection presented in the TLS handshake, once verified.
+    static CLIENT_CERT: RefCell<Option<Certificate>>;
+}
+
+/// Runs `fut` serving a connection, recording the certificate of the client verified in the
+/// TLS handshake of the connection for [client_cert].
+///
+/// It's for the listeners whose protocol libraries accept the TLS connections themselves, so
+/// the certificate can only be recorded by the verifier.
+pub async fn scope_client_cert<F: Future>(fut: F) -> F::Output {
+    CLIENT_CERT.scope(RefCell::new(None), fut).await
+}
+
+/// Returns the verified certificate of the client of the connection served in
+/// [scope_client_cert].
+pub fn client_cert() -> Option<Certificate> {
+    CLIENT_CERT
+        .try_with(|cert| cert.borrow().clone())
+        .ok()
+        .flatten()
+}
+
+/// Verifies the client certificates by the inner verifier, recording the verified ones in the
+/// scope of the connections, see [scope_client_cert].
+struct RecordingClientCertVerifier(Arc<dyn ClientCertVerifier>);
+
+impl ClientCertVerifier for RecordingClientCertVerifier {
+    fn offer_client_auth(&self) -> bool {
+        self.0.offer_client_auth()
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        self.0.client_auth_mandatory()
+    }
+
+    fn client_auth_root_subjects(&self) -> &[DistinguishedName] {
+        self.0.client_auth_root_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        now: SystemTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let verified = self.0.verify_client_cert(end_entity, intermediates, now)?;
+        // Not in a connection scope if the listener reads the certificate from the connection.
+        let _ = CLIENT_CERT.try_with(|cert| *cert.borrow_mut() = Some(end_entity.clone()));
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.0.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_verify_schemes()
+    }
+}
+
+const TAG_OID: u8 = 0x06;
+const TAG_OCTET_STRING: u8 = 0x04;
+/// Tag of the version of `TBSCertificate`, `[0] EXPLICIT`.
+const TAG_VERSION: u8 = 0xa0;
+/// Tag of the extensions of `TBSCertificate`, `[3] EXPLICIT`.
+const TAG_EXTENSIONS: u8 = 0xa3;
+/// Tag of the `dNSName` choice of `GeneralName`, `[2] IMPLICIT IA5String`.
+const TAG_DNS_NAME: u8 = 0x82;
+/// DER of OID 2.5.4.11, `id-at-organizationalUnitName`.
+const OID_ORGANIZATIONAL_UNIT: &[u8] = &[0x55, 0x04, 0x0b];
+/// DER of OID 2.5.29.17, `id-ce-subjectAltName`.
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+
+/// Reads a DER element, returning its tag, its contents and the rest of the input.
+fn read_der(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&len, rest) = rest.split_first()?;
+    let (len, rest) = if len < 0x80 {
+        (len as usize, rest)
+    } else {
+        let num_bytes = (len & 0x7f) as usize;
+        if num_bytes == 0 || num_bytes > std::mem::size_of::<usize>() || rest.len() < num_bytes {
+            return None;
+        }
+        let (len, rest) = rest.split_at(num_bytes);
+        let len = len.iter().fold(0, |len, b| (len << 8) | *b as usize);
+        (len, rest)
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let (contents, rest) = rest.split_at(len);
+    Some((tag, contents, rest))
+}
+
+/// Iterates the DER elements in `input`, as their tags and contents.
+fn der_elements(mut input: &[u8]) -> impl Iterator<Item = (u8, &[u8])> + '_ {
+    std::iter::from_fn(move || {
+        let (tag, contents, rest) = read_der(input)?;
+        input = rest;
+        Some((tag, contents))
+    })
+}
+
+/// Returns the contents of the subject and the extensions of the DER encoded X.509 certificate.
+fn subject_and_extensions(cert: &[u8]) -> Option<(&[u8], Option<&[u8]>)> {
+    let (_, cert, _) = read_der(cert)?;
+    let (_, tbs_certificate, _) = read_der(cert)?;
+    let mut fields = der_elements(tbs_certificate).peekable();
+    if fields.peek()?.0 == TAG_VERSION {
+        let _ = fields.next();
+    }
+    // Skips the serial number, the signature, the issuer and the validity.
+    let (_, subject) = fields.nth(4)?;
+    let extensions = fields
+        .find(|(tag, _)| *tag == TAG_EXTENSIONS)
+        .map(|(_, extensions)| extensions);
+    Some((subject, extensions))
+}
+
+fn first_organizational_unit(cert: &[u8]) -> Option<String> {
+    let (subject, _) = subject_and_extensions(cert)?;
+    der_elements(subject)
+        .flat_map(|(_, rdn)| der_elements(rdn))
+        .find_map(|(_, attribute)| {
+            let (tag, oid, value) = read_der(attribute)?;
+            if tag != TAG_OID || oid != OID_ORGANIZATIONAL_UNIT {
+                return None;
+            }
+            let (_, value, _) = read_der(value)?;
+            String::from_utf8(value.to_vec()).ok()
+        })
+}
+
+fn first_dns_name(cert: &[u8]) -> Option<String> {
+    let (_, extensions) = subject_and_extensions(cert)?;
+    let (_, extensions, _) = read_der(extensions?)?;
+    let alt_names = der_elements(extensions).find_map(|(_, extension)| {
+        let mut fields = der_elements(extension);
+        let (tag, oid) = fields.next()?;
+        if tag != TAG_OID || oid != OID_SUBJECT_ALT_NAME {
+            return None;
+        }
+        // Skips the critical flag, if any.
+        fields
+            .find(|(tag, _)| *tag == TAG_OCTET_STRING)
+            .map(|(_, value)| value)
+    })?;
+    let (_, alt_names, _) = read_der(alt_names)?;
+    der_elements(alt_names)
+        .find(|(tag, _)| *tag == TAG_DNS_NAME)
+        .and_then(|(_, name)| String::from_utf8(name.to_vec()).ok())
 }
 
 #[cfg(test)]
@@ -131,6 +392,7 @@ mod tests {
                 mode: Disable,
                 cert_path: "/path/to/cert_path".to_string(),
                 key_path: "/path/to/key_path".to_string(),
+                ..Default::default()
             },
             TlsOption::new(
                 Some(Disable),
@@ -237,4 +499,79 @@ mod tests {
         assert!(!t.key_path.is_empty());
         assert!(!t.cert_path.is_empty());
     }
+
+    #[test]
+    fn test_tls_option_mutual_tls() {
+        let s = r#"
+        {
+            "mode": "require",
+            "cert_path": "tests/ssl/server.crt",
+            "key_path": "tests/ssl/server-rsa.key",
+            "ca_cert_path": "tests/ssl/client-ca.crt",
+            "client_cert_schema": "san",
+            "client_cert_schema_strict": true
+        }
+        "#;
+
+        let t: TlsOption = serde_json::from_str(s).unwrap();
+        assert_eq!(ClientCertSchema::San, t.client_cert_schema);
+        assert!(t.client_cert_schema_strict);
+        assert!(t.setup().unwrap().is_some());
+
+        let t = TlsOption {
+            ca_cert_path: "tests/ssl/not-exist.crt".to_string(),
+            ..t
+        };
+        assert!(t.setup().is_err());
+    }
+
+    #[test]
+    fn test_client_cert_mapping() {
+        let cert = load_certs("tests/ssl/client.crt").unwrap().remove(0);
+        let mapping = |schema, strict| {
+            TlsOption {
+                client_cert_schema: schema,
+                client_cert_schema_strict: strict,
+                ..Default::default()
+            }
+            .client_cert_mapping()
+        };
+
+        let user_info =
+            mapping(ClientCertSchema::San, true).apply(UserInfo::default(), Some(&cert));
+        assert_eq!(Some("greptime"), user_info.default_catalog());
+        assert_eq!(Some("tenant_a"), user_info.default_schema());
+        assert!(user_info.is_schema_pinned());
+
+        let user_info =
+            mapping(ClientCertSchema::Ou, false).apply(UserInfo::default(), Some(&cert));
+        assert_eq!(Some("public"), user_info.default_schema());
+        assert!(!user_info.is_schema_pinned());
+
+        // The default schema from the user provider takes precedence.
+        let user_info = UserInfo::default().with_default_schema(None, "tenant_b", false);
+        let user_info = mapping(ClientCertSchema::San, true).apply(user_info, Some(&cert));
+        assert_eq!(Some("tenant_b"), user_info.default_schema());
+        assert!(!user_info.is_schema_pinned());
+
+        let user_info =
+            mapping(ClientCertSchema::Disable, true).apply(UserInfo::default(), Some(&cert));
+        assert_eq!(None, user_info.default_schema());
+        let user_info = mapping(ClientCertSchema::San, true).apply(UserInfo::default(), None);
+        assert_eq!(None, user_info.default_schema());
+    }
+
+    #[tokio::test]
+    async fn test_scope_client_cert() {
+        assert!(client_cert().is_none());
+        let cert = load_certs("tests/ssl/client.crt").unwrap().remove(0);
+        let recorded = scope_client_cert(async {
+            assert!(client_cert().is_none());
+            CLIENT_CERT.with(|recorded| *recorded.borrow_mut() = Some(cert.clone()));
+            client_cert()
+        })
+        .await;
+        assert_eq!(Some(cert), recorded);
+        assert!(client_cert().is_none());
+    }
 }
@@ -12,13 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fs::File;
+use std::io::BufReader;
 use std::sync::Arc;
 
 use axum::body::BoxBody;
+use axum::extract::ConnectInfo;
 use axum::http;
 use hyper::Request;
+use rustls::Certificate;
 use servers::auth::UserProvider;
 use servers::http::authorize::HttpAuth;
+use servers::http::ClientCertificate;
+use servers::tls::{ClientCertSchema, TlsOption};
 use session::context::UserInfo;
 use tower_http::auth::AsyncAuthorizeRequest;
 
@@ -104,6 +110,35 @@ async fn test_whitelist_no_auth() {
     assert!(req.is_ok());
 }
 
+#[tokio::test]
+async fn test_client_cert_schema() {
+    let tls = TlsOption {
+        client_cert_schema: ClientCertSchema::San,
+        client_cert_schema_strict: true,
+        ..Default::default()
+    };
+    let mut http_auth: HttpAuth<BoxBody> =
+        HttpAuth::new(None).with_client_cert_mapping(tls.client_cert_mapping());
+
+    let mut certs = rustls_pemfile::certs(&mut BufReader::new(
+        File::open("tests/ssl/client.crt").unwrap(),
+    ))
+    .unwrap();
+    let cert = ClientCertificate(Some(Certificate(certs.remove(0))));
+    let mut req = mock_http_request(None, None).unwrap();
+    req.extensions_mut().insert(ConnectInfo(cert));
+    let req = http_auth.authorize(req).await.unwrap();
+    let user_info: &UserInfo = req.extensions().get().unwrap();
+    assert_eq!(Some("tenant_a"), user_info.default_schema());
+    assert!(user_info.is_schema_pinned());
+
+    // Requests of the plain connections don't have client certificates.
+    let req = mock_http_request(None, None).unwrap();
+    let req = http_auth.authorize(req).await.unwrap();
+    let user_info: &UserInfo = req.extensions().get().unwrap();
+    assert_eq!(None, user_info.default_schema());
+}
+
 // copy from http::authorize
 fn mock_http_request(
     auth_header: Option<&str>,
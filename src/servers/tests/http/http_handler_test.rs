@@ -13,14 +13,27 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
 
 use axum::body::Body;
 use axum::extract::{Json, Query, RawBody, State};
 use axum::Form;
+use common_error::status_code::StatusCode;
+use common_recordbatch::RecordBatch;
 use common_telemetry::metric;
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{Float64Vector, VectorRef};
 use metrics::counter;
+use rustls::Certificate;
+use servers::auth::{user_provider_from_option, Identity, Password as AuthPassword};
+use servers::http::batch::{sql_batch, BatchQuery, BatchQueryOptions, BatchQueryParams};
+use servers::http::commit_token::HttpCommitToken;
 use servers::http::{handler as http_handler, script as script_handler, ApiState, JsonOutput};
 use servers::metrics_handler::MetricsHandler;
+use servers::tls::{ClientCertSchema, TlsOption};
 use session::context::UserInfo;
 use table::test_util::MemTable;
 
@@ -36,9 +49,12 @@ async fn test_sql_not_provided() {
         State(ApiState {
             sql_handler,
             script_handler: None,
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
         }),
         Query(http_handler::SqlQuery::default()),
         axum::Extension(UserInfo::default()),
+        axum::Extension(HttpCommitToken::default()),
         Form(http_handler::SqlQuery::default()),
     )
     .await;
@@ -61,9 +77,12 @@ async fn test_sql_output_rows() {
         State(ApiState {
             sql_handler,
             script_handler: None,
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
         }),
         query,
         axum::Extension(UserInfo::default()),
+        axum::Extension(HttpCommitToken::default()),
         Form(http_handler::SqlQuery::default()),
     )
     .await;
@@ -107,9 +126,12 @@ async fn test_sql_form() {
         State(ApiState {
             sql_handler,
             script_handler: None,
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
         }),
         Query(http_handler::SqlQuery::default()),
         axum::Extension(UserInfo::default()),
+        axum::Extension(HttpCommitToken::default()),
         form,
     )
     .await;
@@ -142,6 +164,289 @@ async fn test_sql_form() {
     }
 }
 
+#[tokio::test]
+async fn test_sql_authorize_db() {
+    let sql_handler = create_testing_sql_query_handler(MemTable::default_numbers_table());
+    let user_provider = user_provider_from_option(
+        &"static_user_provider:cmd:pinned:tenant_a:strict=123456".to_string(),
+    )
+    .unwrap();
+    let user_info = user_provider
+        .authenticate(
+            Identity::UserId("pinned", None),
+            AuthPassword::PlainText("123456"),
+        )
+        .await
+        .unwrap();
+    let state = || ApiState {
+        sql_handler: sql_handler.clone(),
+        script_handler: None,
+        batch_query_options: BatchQueryOptions::default(),
+        user_provider: Some(user_provider.clone()),
+    };
+
+    // The db of the form is authorized as well as the one of the query string.
+    let Form(mut form) = create_form();
+    form.db = Some("public".to_string());
+    let Json(json) = http_handler::sql(
+        State(state()),
+        Query(http_handler::SqlQuery::default()),
+        axum::Extension(user_info.clone()),
+        axum::Extension(HttpCommitToken::default()),
+        Form(form),
+    )
+    .await;
+    assert!(!json.success());
+    assert_eq!(StatusCode::AccessDenied as u32, json.code());
+
+    // So is the db of each query in a batch.
+    let queries = vec![BatchQuery {
+        sql: "select sum(uint32s) from numbers".to_string(),
+        db: Some("public".to_string()),
+        timeout_ms: None,
+    }];
+    let Json(json) = sql_batch(
+        State(state()),
+        Query(BatchQueryParams::default()),
+        axum::Extension(user_info),
+        axum::Extension(HttpCommitToken::default()),
+        Json(queries),
+    )
+    .await;
+    assert!(json.success());
+    let response = json.results()[0].response();
+    assert_eq!(StatusCode::AccessDenied as u32, response.code());
+}
+
+#[tokio::test]
+async fn test_sql_client_cert_schema() {
+    let sql_handler = create_testing_sql_query_handler(MemTable::default_numbers_table());
+    let state = || ApiState {
+        sql_handler: sql_handler.clone(),
+        script_handler: None,
+        batch_query_options: BatchQueryOptions::default(),
+        user_provider: None,
+    };
+    let mut certs = rustls_pemfile::certs(&mut BufReader::new(
+        File::open("tests/ssl/client.crt").unwrap(),
+    ))
+    .unwrap();
+    let cert = Certificate(certs.remove(0));
+    let user_info = |client_cert_schema| {
+        TlsOption {
+            client_cert_schema,
+            client_cert_schema_strict: true,
+            ..Default::default()
+        }
+        .client_cert_mapping()
+        .apply(UserInfo::default(), Some(&cert))
+    };
+
+    // The certificate maps to "public" by its OU, where the unqualified tables resolve.
+    let Json(json) = http_handler::sql(
+        State(state()),
+        Query(http_handler::SqlQuery::default()),
+        axum::Extension(user_info(ClientCertSchema::Ou)),
+        axum::Extension(HttpCommitToken::default()),
+        create_form(),
+    )
+    .await;
+    assert!(json.success(), "{json:?}");
+
+    // The certificate pins the user to "tenant_a" by its SAN, even without a user provider.
+    let Form(mut form) = create_form();
+    form.db = Some("public".to_string());
+    let Json(json) = http_handler::sql(
+        State(state()),
+        Query(http_handler::SqlQuery::default()),
+        axum::Extension(user_info(ClientCertSchema::San)),
+        axum::Extension(HttpCommitToken::default()),
+        Form(form),
+    )
+    .await;
+    assert!(!json.success());
+    assert_eq!(StatusCode::AccessDenied as u32, json.code());
+}
+
+fn create_floats_table() -> MemTable {
+    let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+        "v",
+        ConcreteDataType::float64_datatype(),
+        false,
+    )]));
+    let columns: Vec<VectorRef> = vec![Arc::new(Float64Vector::from_slice([
+        0.1 + 0.2,
+        1.0 / 3.0,
+        1e20,
+        123.456,
+        -2.5e-7,
+    ]))];
+    MemTable::new("floats", RecordBatch::new(schema, columns).unwrap())
+}
+
+#[tokio::test]
+async fn test_sql_float_precision() {
+    common_telemetry::init_default_ut_logging();
+
+    let sql_handler = create_testing_sql_query_handler(create_floats_table());
+    let state = || ApiState {
+        sql_handler: sql_handler.clone(),
+        script_handler: None,
+        batch_query_options: BatchQueryOptions::default(),
+        user_provider: None,
+    };
+    let query = |precision| {
+        Query(http_handler::SqlQuery {
+            sql: Some("select v from floats order by v".to_string()),
+            db: None,
+            precision,
+            sql_select_limit: None,
+            validation_mode: None,
+            as_of: None,
+            continue_on_error: None,
+        })
+    };
+
+    let cases = [
+        (
+            None,
+            vec![
+                "-2.5e-7",
+                "0.30000000000000004",
+                "0.3333333333333333",
+                "123.456",
+                "1e20",
+            ],
+        ),
+        (Some(4), vec!["-2.5e-7", "0.3", "0.3333", "123.5", "1e20"]),
+    ];
+    for (precision, expect) in cases {
+        let Json(json) = http_handler::sql(
+            State(state()),
+            query(precision),
+            axum::Extension(UserInfo::default()),
+            axum::Extension(HttpCommitToken::default()),
+            Form(http_handler::SqlQuery::default()),
+        )
+        .await;
+        assert!(json.success(), "{json:?}");
+        let JsonOutput::Records(records) = &json.output().unwrap()[0] else { unreachable!() };
+        let json_values = records
+            .rows()
+            .iter()
+            .map(|row| serde_json::to_string(&row[0]).unwrap())
+            .collect::<Vec<_>>();
+
+        let response = http_handler::sql_csv(
+            State(state()),
+            query(precision),
+            axum::Extension(UserInfo::default()),
+            axum::Extension(HttpCommitToken::default()),
+            Form(http_handler::SqlQuery::default()),
+        )
+        .await;
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(Some("v"), lines.next());
+        let csv_values = lines.collect::<Vec<_>>();
+
+        // Json and csv write the same strings.
+        assert_eq!(expect, json_values);
+        assert_eq!(expect, csv_values);
+    }
+
+    let Json(json) = http_handler::sql(
+        State(state()),
+        query(Some(16)),
+        axum::Extension(UserInfo::default()),
+        axum::Extension(HttpCommitToken::default()),
+        Form(http_handler::SqlQuery::default()),
+    )
+    .await;
+    assert!(!json.success());
+    assert_eq!(
+        Some(&"precision must be between 1 and 15, got 16.".to_string()),
+        json.error()
+    );
+}
+
+#[tokio::test]
+async fn test_sql_multi_statements() {
+    common_telemetry::init_default_ut_logging();
+
+    let sql_handler = create_testing_sql_query_handler(MemTable::default_numbers_table());
+    let execute = |sql: &str, continue_on_error| {
+        http_handler::sql(
+            State(ApiState {
+                sql_handler: sql_handler.clone(),
+                script_handler: None,
+                batch_query_options: BatchQueryOptions::default(),
+                user_provider: None,
+            }),
+            Query(http_handler::SqlQuery {
+                sql: Some(sql.to_string()),
+                continue_on_error,
+                ..Default::default()
+            }),
+            axum::Extension(UserInfo::default()),
+            axum::Extension(HttpCommitToken::default()),
+            Form(http_handler::SqlQuery::default()),
+        )
+    };
+    let num_rows = |output: Option<&[JsonOutput]>| {
+        let JsonOutput::Records(records) = &output.unwrap()[0] else { unreachable!() };
+        records.num_rows()
+    };
+
+    // All the statements succeed.
+    let Json(json) = execute(
+        "select sum(uint32s) from numbers; select uint32s from numbers limit 3;",
+        None,
+    )
+    .await;
+    assert!(json.success(), "{json:?}");
+    assert!(json.output().is_none());
+    let results = json.results().unwrap();
+    assert_eq!(2, results.len());
+    assert_eq!("select sum(uint32s) from numbers", results[0].sql());
+    assert_eq!("select uint32s from numbers limit 3", results[1].sql());
+    for (result, rows) in results.iter().zip([1, 3]) {
+        assert!(result.response().success());
+        assert!(result.response().execution_time_ms().is_some());
+        assert_eq!(rows, num_rows(result.response().output()));
+    }
+
+    // Stops at the first failed statement by default.
+    let sql = "select uint32s from numbers limit 1; select * from missing; select 1";
+    let Json(json) = execute(sql, None).await;
+    assert!(!json.success());
+    let results = json.results().unwrap();
+    assert_eq!(2, results.len());
+    assert!(results[0].response().success());
+    let failed = results[1].response();
+    assert!(!failed.success());
+    assert!(failed.error().unwrap().contains("missing"), "{failed:?}");
+    assert_eq!(failed.code(), json.code());
+    assert_eq!(failed.error(), json.error());
+
+    // Executes the rest statements on `continue_on_error`.
+    let Json(json) = execute(sql, Some(true)).await;
+    assert!(!json.success());
+    let results = json.results().unwrap();
+    assert_eq!(3, results.len());
+    assert!(!results[1].response().success());
+    assert_eq!(results[1].response().error(), json.error());
+    assert!(results[2].response().success());
+    assert_eq!(1, num_rows(results[2].response().output()));
+
+    // A single statement has no results.
+    let Json(json) = execute("select 1; -- the only statement", Some(true)).await;
+    assert!(json.success(), "{json:?}");
+    assert!(json.results().is_none());
+    assert_eq!(1, num_rows(json.output()));
+}
+
 #[tokio::test]
 async fn test_metrics() {
     metric::init_default_metrics_recorder();
@@ -163,6 +468,8 @@ async fn insert_script(
         State(ApiState {
             sql_handler: sql_handler.clone(),
             script_handler: Some(script_handler.clone()),
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
         }),
         invalid_query,
         body,
@@ -178,6 +485,8 @@ async fn insert_script(
         State(ApiState {
             sql_handler: sql_handler.clone(),
             script_handler: Some(script_handler.clone()),
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
         }),
         exec,
         body,
@@ -208,6 +517,8 @@ def test(n) -> vector[i64]:
         State(ApiState {
             sql_handler,
             script_handler: Some(script_handler),
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
         }),
         exec,
     )
@@ -275,6 +586,8 @@ def test(n, **params)  -> vector[i64]:
         State(ApiState {
             sql_handler,
             script_handler: Some(script_handler),
+            batch_query_options: BatchQueryOptions::default(),
+            user_provider: None,
         }),
         exec,
     )
@@ -341,6 +654,11 @@ fn create_query() -> Query<http_handler::SqlQuery> {
     Query(http_handler::SqlQuery {
         sql: Some("select sum(uint32s) from numbers limit 20".to_string()),
         db: None,
+        precision: None,
+        sql_select_limit: None,
+        validation_mode: None,
+        as_of: None,
+        continue_on_error: None,
     })
 }
 
@@ -348,13 +666,18 @@ fn create_form() -> Form<http_handler::SqlQuery> {
     Form(http_handler::SqlQuery {
         sql: Some("select sum(uint32s) from numbers limit 20".to_string()),
         db: None,
+        precision: None,
+        sql_select_limit: None,
+        validation_mode: None,
+        as_of: None,
+        continue_on_error: None,
     })
 }
 
 /// Currently the payload of response should be simply an empty json "{}";
 #[tokio::test]
 async fn test_health() {
-    let expected_json = http_handler::HealthResponse {};
+    let expected_json = http_handler::HealthResponse::default();
     let expected_json_str = "{}".to_string();
 
     let query = http_handler::HealthQuery {};
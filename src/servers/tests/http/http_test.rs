@@ -12,9 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
 use axum::Router;
 use axum_test_helper::TestClient;
+use serde_json::Value;
+use servers::http::handler::HealthResponse;
+use servers::http::queries::RunningQueriesResponse;
 use servers::http::{HttpOptions, HttpServerBuilder};
+use servers::query_handler::{
+    ComponentStatus, ReadinessHandler, RunningQuery, RunningQueryHandler,
+};
+use session::context::Channel;
+use session::registry::SessionRegistryRef;
 use table::test_util::MemTable;
 
 use crate::{create_testing_grpc_query_handler, create_testing_sql_query_handler};
@@ -40,3 +52,126 @@ async fn test_api_and_doc() {
     let result = client.get("/v1/private/docs").send().await;
     assert_eq!(result.status(), 200);
 }
+
+struct MockReadiness {
+    catalog_started: AtomicBool,
+    meta_connected: AtomicBool,
+}
+
+#[async_trait]
+impl ReadinessHandler for MockReadiness {
+    async fn component_status(&self) -> ComponentStatus {
+        ComponentStatus {
+            catalog_manager_started: self.catalog_started.load(Ordering::Relaxed),
+            meta_client_connected: Some(self.meta_connected.load(Ordering::Relaxed)),
+            datanodes: Some(3),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_ready() {
+    let readiness = Arc::new(MockReadiness {
+        catalog_started: AtomicBool::new(false),
+        meta_connected: AtomicBool::new(false),
+    });
+    let server = HttpServerBuilder::new(HttpOptions::default())
+        .with_readiness_handler(readiness.clone())
+        .build();
+    let client = TestClient::new(server.make_app());
+
+    // Alive but not ready.
+    let result = client.get("/health").send().await;
+    assert_eq!(result.status(), 200);
+    let result = client.get("/ready").send().await;
+    assert_eq!(result.status(), 503);
+    let body: HealthResponse = serde_json::from_str(&result.text().await).unwrap();
+    assert_eq!(Some("catalog_manager"), body.unready_component.as_deref());
+
+    readiness.catalog_started.store(true, Ordering::Relaxed);
+    let result = client.get("/ready").send().await;
+    assert_eq!(result.status(), 503);
+    let body: HealthResponse = serde_json::from_str(&result.text().await).unwrap();
+    assert_eq!(Some("meta_client"), body.unready_component.as_deref());
+
+    readiness.meta_connected.store(true, Ordering::Relaxed);
+    let result = client.get("/ready").send().await;
+    assert_eq!(result.status(), 200);
+    let body: HealthResponse = serde_json::from_str(&result.text().await).unwrap();
+    assert_eq!(
+        HealthResponse {
+            components: Some(ComponentStatus {
+                catalog_manager_started: true,
+                meta_client_connected: Some(true),
+                datanodes: Some(3),
+            }),
+            unready_component: None,
+        },
+        body
+    );
+
+    // Nodes without a readiness handler are ready once alive.
+    let client = TestClient::new(make_test_app());
+    let result = client.get("/ready").send().await;
+    assert_eq!(result.status(), 200);
+}
+
+struct MockRunningQueryHandler;
+
+impl RunningQueryHandler for MockRunningQueryHandler {
+    fn running_queries(&self) -> Vec<RunningQuery> {
+        vec![RunningQuery {
+            id: 7,
+            description: "COPY demo FROM '/tmp/'".to_string(),
+            origin: "background(copy_to)".to_string(),
+            start_time: 1000,
+            elapsed_secs: 20,
+            done: 2,
+            total: Some(3),
+            current_item: Some("c.parquet".to_string()),
+            eta_secs: Some(10),
+        }]
+    }
+}
+
+#[tokio::test]
+async fn test_list_running_queries() {
+    let server = HttpServerBuilder::new(HttpOptions::default())
+        .with_running_query_handler(Arc::new(MockRunningQueryHandler))
+        .build();
+    let client = TestClient::new(server.make_app());
+
+    let result = client.get("/v1/queries").send().await;
+    assert_eq!(result.status(), 200);
+    let body: RunningQueriesResponse = serde_json::from_str(&result.text().await).unwrap();
+    assert_eq!(MockRunningQueryHandler.running_queries(), body.queries);
+}
+
+#[tokio::test]
+async fn test_list_sessions() {
+    let registry = SessionRegistryRef::default();
+    let server = HttpServerBuilder::new(HttpOptions::default())
+        .with_session_registry(registry.clone())
+        .build();
+    let client = TestClient::new(server.make_app());
+
+    let mysql = registry.register("127.0.0.1:9000".parse().unwrap(), Channel::Mysql);
+    let postgres = registry.register("127.0.0.1:9001".parse().unwrap(), Channel::Postgres);
+    let result = client.get("/v1/admin/sessions").send().await;
+    assert_eq!(result.status(), 200);
+    let body: Value = serde_json::from_str(&result.text().await).unwrap();
+    let sessions = body["sessions"].as_array().unwrap();
+    assert_eq!(2, sessions.len());
+    assert_eq!(mysql.id(), sessions[0]["id"].as_u64().unwrap());
+    assert_eq!("127.0.0.1:9000", sessions[0]["client_host"]);
+    assert_eq!("mysql", sessions[0]["channel"]);
+    assert_eq!(postgres.id(), sessions[1]["id"].as_u64().unwrap());
+    assert_eq!("postgres", sessions[1]["channel"]);
+
+    // Closed connections are deregistered.
+    drop(mysql);
+    drop(postgres);
+    let result = client.get("/v1/admin/sessions").send().await;
+    let body: Value = serde_json::from_str(&result.text().await).unwrap();
+    assert!(body["sessions"].as_array().unwrap().is_empty());
+}
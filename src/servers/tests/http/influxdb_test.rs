@@ -19,6 +19,7 @@ use api::v1::InsertRequest;
 use async_trait::async_trait;
 use axum::{http, Router};
 use axum_test_helper::TestClient;
+use common_grpc_expr::dry_run::InsertDryRun;
 use common_query::Output;
 use datatypes::schema::Schema;
 use query::parser::PromQuery;
@@ -61,6 +62,22 @@ impl InfluxdbLineProtocolHandler for DummyInstance {
 
         Ok(())
     }
+
+    async fn dry_run(
+        &self,
+        request: &InfluxdbRequest,
+        _ctx: QueryContextRef,
+    ) -> Result<Vec<InsertDryRun>> {
+        let requests: Vec<InsertRequest> = request.try_into()?;
+        Ok(requests
+            .into_iter()
+            .map(|request| InsertDryRun {
+                table_name: request.table_name,
+                rows_accepted: request.row_count as usize,
+                ..Default::default()
+            })
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -145,6 +162,32 @@ async fn test_influxdb_write() {
     assert_eq!(result.status(), 204);
     assert!(result.text().await.is_empty());
 
+    // dry run, which writes nothing
+    let result = client
+        .post("/v1/influxdb/write?db=public&dry_run=true")
+        .body("monitor,host=host1 cpu=1.2 1664370459457010101")
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 200);
+    let dry_runs: Vec<InsertDryRun> = serde_json::from_str(&result.text().await).unwrap();
+    assert_eq!(
+        vec![InsertDryRun {
+            table_name: "monitor".to_string(),
+            rows_accepted: 1,
+            ..Default::default()
+        }],
+        dry_runs
+    );
+
+    let result = client
+        .post("/v1/influxdb/write?db=public&dry_run=yes")
+        .body("monitor,host=host1 cpu=1.2 1664370459457010101")
+        .header(http::header::AUTHORIZATION, "token greptime:greptime")
+        .send()
+        .await;
+    assert_eq!(result.status(), 400);
+
     // wrong pwd
     let result = client
         .post("/v1/influxdb/write?db=public")
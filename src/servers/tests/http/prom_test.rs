@@ -0,0 +1,72 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum_test_helper::TestClient;
+use common_query::Output;
+use query::parser::{PromQuery, QueryLanguageParser};
+use servers::error::{ParsePromQLSnafu, Result};
+use servers::prom::{PromHandler, PromJsonResponse, PromServer};
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+
+/// Parses the queries with at most 100 points per timeseries.
+struct DummyInstance;
+
+#[async_trait]
+impl PromHandler for DummyInstance {
+    async fn do_query(&self, query: &PromQuery, _query_ctx: QueryContextRef) -> Result<Output> {
+        let _ =
+            QueryLanguageParser::parse_promql(query, 100).with_context(|_| ParsePromQLSnafu {
+                query: query.clone(),
+            })?;
+        Ok(Output::AffectedRows(0))
+    }
+}
+
+async fn range_query(client: &TestClient, start: &str, end: &str, step: &str) -> PromJsonResponse {
+    let result = client
+        .get(&format!(
+            "/api/v1/query_range?query=up&start={start}&end={end}&step={step}"
+        ))
+        .send()
+        .await;
+    assert_eq!(result.status(), 200);
+    serde_json::from_str(&result.text().await).unwrap()
+}
+
+#[tokio::test]
+async fn test_range_query_max_points() {
+    let server = PromServer::create_server(Arc::new(DummyInstance));
+    let client = TestClient::new(server.make_app());
+
+    // 100 steps of 500ms, 101 points.
+    let response = range_query(&client, "0", "50", "500ms").await;
+    assert_ne!(Some("bad_data"), response.error_type.as_deref());
+
+    let response = range_query(&client, "0", "50.5", "500ms").await;
+    assert_eq!("error", response.status);
+    assert_eq!(Some("bad_data"), response.error_type.as_deref());
+    let reason = response.error.unwrap();
+    assert!(reason.contains("exceeded maximum resolution"), "{reason}");
+
+    let response = range_query(&client, "0", "50", "0").await;
+    assert_eq!(Some("bad_data"), response.error_type.as_deref());
+    let response = range_query(&client, "50", "0", "1s").await;
+    assert_eq!(Some("bad_data"), response.error_type.as_deref());
+    let response = range_query(&client, "0", "50", "1x").await;
+    assert_eq!(Some("bad_data"), response.error_type.as_deref());
+}
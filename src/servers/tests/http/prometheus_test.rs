@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use api::prometheus::remote::{
     LabelMatcher, Query, QueryResult, ReadRequest, ReadResponse, WriteRequest,
@@ -21,11 +22,15 @@ use api::v1::greptime_request::Request;
 use async_trait::async_trait;
 use axum::Router;
 use axum_test_helper::TestClient;
+use common_error::mock::MockError;
+use common_error::prelude::BoxedError;
+use common_error::status_code::StatusCode;
 use common_query::Output;
 use datatypes::schema::Schema;
 use prost::Message;
 use query::parser::PromQuery;
-use servers::error::{Error, Result};
+use servers::error::{Error, ExecuteGrpcQuerySnafu, Result};
+use servers::http::prometheus::{RemoteWriteOptions, RemoteWriteQueue};
 use servers::http::{HttpOptions, HttpServerBuilder};
 use servers::prometheus;
 use servers::prometheus::{snappy_compress, Metrics};
@@ -33,6 +38,7 @@ use servers::query_handler::grpc::GrpcQueryHandler;
 use servers::query_handler::sql::SqlQueryHandler;
 use servers::query_handler::{PrometheusProtocolHandler, PrometheusResponse};
 use session::context::QueryContextRef;
+use snafu::ResultExt;
 use tokio::sync::mpsc;
 
 struct DummyInstance {
@@ -55,6 +61,20 @@ impl GrpcQueryHandler for DummyInstance {
 #[async_trait]
 impl PrometheusProtocolHandler for DummyInstance {
     async fn write(&self, request: WriteRequest, ctx: QueryContextRef) -> Result<()> {
+        // Mocks the storage throttling the writes stalled by flushes, failing, or being slow.
+        let code = match ctx.current_schema().as_str() {
+            "throttled" => Some(StatusCode::RuntimeResourcesExhausted),
+            "failed" => Some(StatusCode::Internal),
+            "slow" => {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                None
+            }
+            _ => None,
+        };
+        if let Some(code) = code {
+            return Err(BoxedError::new(MockError::new(code))).context(ExecuteGrpcQuerySnafu);
+        }
+
         let _ = self
             .tx
             .send((ctx.current_schema(), request.encode_to_vec()))
@@ -116,8 +136,12 @@ impl SqlQueryHandler for DummyInstance {
 }
 
 fn make_test_app(tx: mpsc::Sender<(String, Vec<u8>)>) -> Router {
+    make_test_app_with_options(tx, HttpOptions::default())
+}
+
+fn make_test_app_with_options(tx: mpsc::Sender<(String, Vec<u8>)>, options: HttpOptions) -> Router {
     let instance = Arc::new(DummyInstance { tx });
-    let server = HttpServerBuilder::new(HttpOptions::default())
+    let server = HttpServerBuilder::new(options)
         .with_grpc_handler(instance.clone())
         .with_sql_handler(instance.clone())
         .with_prom_handler(instance)
@@ -230,3 +254,146 @@ async fn test_prometheus_remote_write_read() {
         ReadRequest::decode(&(requests[3].1)[..]).unwrap()
     );
 }
+
+#[tokio::test]
+async fn test_prometheus_remote_write_drop_nan() {
+    let (tx, mut rx) = mpsc::channel(100);
+
+    let app = make_test_app(tx);
+    let client = TestClient::new(app);
+
+    let mut timeseries = prometheus::mock_timeseries();
+    timeseries[0].samples[0].value = f64::NAN;
+    let write_request = WriteRequest {
+        timeseries,
+        ..Default::default()
+    };
+    let body = snappy_compress(&write_request.encode_to_vec()[..]).unwrap();
+
+    let result = client
+        .post("/v1/prometheus/write")
+        .body(body.clone())
+        .send()
+        .await;
+    assert_eq!(result.status(), 204);
+    let result = client
+        .post("/v1/prometheus/write?drop_nan=true")
+        .body(body)
+        .send()
+        .await;
+    assert_eq!(result.status(), 204);
+
+    let (_, request) = rx.try_recv().unwrap();
+    let request = WriteRequest::decode(&request[..]).unwrap();
+    assert!(request.timeseries[0].samples[0].value.is_nan());
+
+    let (_, request) = rx.try_recv().unwrap();
+    let request = WriteRequest::decode(&request[..]).unwrap();
+    assert_eq!(1, request.timeseries[0].samples.len());
+    assert_eq!(2.0, request.timeseries[0].samples[0].value);
+}
+
+fn mock_write_body() -> Vec<u8> {
+    let write_request = WriteRequest {
+        timeseries: prometheus::mock_timeseries(),
+        ..Default::default()
+    };
+    snappy_compress(&write_request.encode_to_vec()[..]).unwrap()
+}
+
+#[tokio::test]
+async fn test_prometheus_remote_write_backpressure() {
+    let (tx, _rx) = mpsc::channel(100);
+    let app = make_test_app(tx);
+    let client = TestClient::new(app);
+
+    let result = client
+        .post("/v1/prometheus/write?db=throttled")
+        .body(mock_write_body())
+        .send()
+        .await;
+    assert_eq!(result.status(), 429);
+    assert_eq!(
+        "1",
+        result
+            .headers()
+            .get("retry-after")
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+    assert!(!result.text().await.is_empty());
+
+    // Other errors keep their status codes.
+    let result = client
+        .post("/v1/prometheus/write?db=failed")
+        .body(mock_write_body())
+        .send()
+        .await;
+    assert_eq!(result.status(), 500);
+    assert!(result.headers().get("retry-after").is_none());
+}
+
+#[tokio::test]
+async fn test_prometheus_remote_write_queue() {
+    let (tx, _rx) = mpsc::channel(100);
+    let options = HttpOptions {
+        prom_remote_write: RemoteWriteOptions {
+            queue_size: 2,
+            max_concurrency: 1,
+            retry_after: Duration::from_secs(1),
+        },
+        ..Default::default()
+    };
+    let app = make_test_app_with_options(tx, options);
+    let client = TestClient::new(app);
+
+    let write = || async {
+        client
+            .post("/v1/prometheus/write?db=slow")
+            .body(mock_write_body())
+            .send()
+            .await
+            .status()
+    };
+
+    // One write runs and the others wait in the queue.
+    let statuses = futures::future::join_all((0..3).map(|_| write())).await;
+    assert!(statuses.iter().all(|status| *status == 204), "{statuses:?}");
+
+    // The write beyond the queue is rejected.
+    let mut statuses = futures::future::join_all((0..4).map(|_| write())).await;
+    statuses.sort();
+    assert_eq!(vec![204, 204, 204, 429], statuses);
+}
+
+#[tokio::test]
+async fn test_remote_write_queue_state() {
+    let queue = RemoteWriteQueue::new(RemoteWriteOptions {
+        queue_size: 1,
+        max_concurrency: 2,
+        retry_after: Duration::from_secs(1),
+    });
+    let first = queue.acquire("db").await.unwrap();
+    let second = queue.acquire("db").await.unwrap();
+    assert_eq!(1, queue.tenants());
+    // No write has finished yet.
+    assert_eq!(1, queue.retry_after_secs("db"));
+
+    // The write cancelled while waiting leaves the queue.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(10), queue.acquire("db"))
+            .await
+            .is_err()
+    );
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    drop(first);
+    // Derived from the time the finished write took.
+    assert_eq!(2, queue.retry_after_secs("db"));
+
+    // The queue of the database is removed once it's idle.
+    drop(second);
+    assert_eq!(0, queue.tenants());
+    assert_eq!(1, queue.retry_after_secs("db"));
+}
@@ -20,18 +20,19 @@ use api::v1::query_request::Query;
 use async_trait::async_trait;
 use catalog::local::{MemoryCatalogManager, MemoryCatalogProvider, MemorySchemaProvider};
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_error::prelude::BoxedError;
 use common_query::Output;
 use datatypes::schema::Schema;
 use query::parser::{PromQuery, QueryLanguageParser, QueryStatement};
 use query::{QueryEngineFactory, QueryEngineRef};
 use script::engine::{CompileContext, EvalContext, Script, ScriptEngine};
 use script::python::{PyEngine, PyScript};
-use servers::error::{Error, NotSupportedSnafu, Result};
+use servers::error::{Error, ExecuteQuerySnafu, NotSupportedSnafu, Result};
 use servers::query_handler::grpc::{GrpcQueryHandler, ServerGrpcQueryHandlerRef};
 use servers::query_handler::sql::{ServerSqlQueryHandlerRef, SqlQueryHandler};
 use servers::query_handler::{ScriptHandler, ScriptHandlerRef};
 use session::context::QueryContextRef;
-use snafu::ensure;
+use snafu::{ensure, ResultExt};
 use sql::statements::statement::Statement;
 use table::test_util::MemTable;
 
@@ -67,15 +68,20 @@ impl SqlQueryHandler for DummyInstance {
     type Error = Error;
 
     async fn do_query(&self, query: &str, query_ctx: QueryContextRef) -> Vec<Result<Output>> {
-        let stmt = QueryLanguageParser::parse_sql(query).unwrap();
-        let plan = self
-            .query_engine
-            .planner()
-            .plan(stmt, query_ctx.clone())
+        let execute = async {
+            let stmt = QueryLanguageParser::parse_sql(query)?;
+            let plan = self
+                .query_engine
+                .planner()
+                .plan(stmt, query_ctx.clone())
+                .await?;
+            self.query_engine.execute(plan, query_ctx).await
+        };
+        let output = execute
             .await
-            .unwrap();
-        let output = self.query_engine.execute(plan, query_ctx).await.unwrap();
-        vec![Ok(output)]
+            .map_err(BoxedError::new)
+            .context(ExecuteQuerySnafu { query });
+        vec![output]
     }
 
     async fn do_promql_query(
@@ -142,6 +148,10 @@ impl ScriptHandler for DummyInstance {
             .await
             .unwrap())
     }
+
+    async fn script_states(&self) -> Result<Output> {
+        unimplemented!()
+    }
 }
 
 #[async_trait]
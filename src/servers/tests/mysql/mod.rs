@@ -120,7 +120,7 @@ pub fn all_datatype_testing_data() -> TestingData {
         ColumnType::MYSQL_TYPE_LONGLONG,
         ColumnType::MYSQL_TYPE_FLOAT,
         ColumnType::MYSQL_TYPE_DOUBLE,
-        ColumnType::MYSQL_TYPE_VARCHAR,
+        ColumnType::MYSQL_TYPE_BLOB,
         ColumnType::MYSQL_TYPE_VARCHAR,
     ];
     let columns: Vec<VectorRef> = vec![
@@ -205,8 +205,7 @@ pub fn all_datatype_testing_data() -> TestingData {
         ])),
     ];
 
-    // Because we can only use MySQL text protocol (binary protocol requires prepared statement,
-    // which we are not implemented yet), every MysqlValue is of type "Bytes"
+    // The rows are queried in the MySQL text protocol, so every MysqlValue is of type "Bytes"
     let mysql_text_output_rows = vec![
         vec![
             Value::Null,
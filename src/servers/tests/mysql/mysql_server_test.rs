@@ -13,24 +13,47 @@
 // limitations under the License.
 
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
 use std::time::Duration;
 
+use async_trait::async_trait;
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
-use common_recordbatch::RecordBatch;
+use common_query::Output;
+use common_recordbatch::error::CreateRecordBatchesSnafu;
+use common_recordbatch::{RecordBatch, RecordBatchStream};
 use common_runtime::Builder as RuntimeBuilder;
-use datatypes::prelude::VectorRef;
-use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::float_format::FloatPrecision;
+use datatypes::prelude::{ConcreteDataType, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
 use datatypes::value::Value;
+use datatypes::vectors::{
+    DateTimeVector, DateVector, Float64Vector, TimestampMicrosecondVector,
+    TimestampMillisecondVector, TimestampNanosecondVector, TimestampSecondVector, UInt32Vector,
+    Vector,
+};
+use futures::Stream;
 use mysql_async::prelude::*;
 use mysql_async::{Conn, Row, SslOpts};
+use query::parser::PromQuery;
 use rand::rngs::StdRng;
 use rand::Rng;
-use servers::error::Result;
-use servers::mysql::server::{MysqlServer, MysqlSpawnConfig, MysqlSpawnRef};
+use servers::connection::ConnectionOptions;
+use servers::error::{Error, Result};
+use servers::mysql::server::{
+    MysqlServer, MysqlSpawnConfig, MysqlSpawnRef, ER_CLIENT_INTERACTION_TIMEOUT,
+};
+use servers::query_handler::sql::{ServerSqlQueryHandlerRef, SqlQueryHandler};
 use servers::server::Server;
 use servers::tls::TlsOption;
+use session::context::QueryContextRef;
+use session::registry::SessionRegistryRef;
+use sql::statements::statement::Statement;
 use table::test_util::MemTable;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
 
 use crate::auth::{DatabaseAuthInfo, MockUserProvider};
 use crate::create_testing_sql_query_handler;
@@ -41,10 +64,19 @@ struct MysqlOpts<'a> {
     tls: TlsOption,
     auth_info: Option<DatabaseAuthInfo<'a>>,
     reject_no_database: bool,
+    float_precision: FloatPrecision,
+    connection: ConnectionOptions,
+    sessions: SessionRegistryRef,
 }
 
 fn create_mysql_server(table: MemTable, opts: MysqlOpts<'_>) -> Result<Box<dyn Server>> {
-    let query_handler = create_testing_sql_query_handler(table);
+    create_mysql_server_with_handler(create_testing_sql_query_handler(table), opts)
+}
+
+fn create_mysql_server_with_handler(
+    query_handler: ServerSqlQueryHandlerRef,
+    opts: MysqlOpts<'_>,
+) -> Result<Box<dyn Server>> {
     let io_runtime = Arc::new(
         RuntimeBuilder::default()
             .worker_threads(4)
@@ -60,11 +92,19 @@ fn create_mysql_server(table: MemTable, opts: MysqlOpts<'_>) -> Result<Box<dyn S
 
     Ok(MysqlServer::create_server(
         io_runtime,
-        Arc::new(MysqlSpawnRef::new(query_handler, Some(Arc::new(provider)))),
+        Arc::new(MysqlSpawnRef::new(
+            query_handler,
+            Some(Arc::new(provider)),
+            opts.sessions,
+        )),
         Arc::new(MysqlSpawnConfig::new(
             opts.tls.should_force_tls(),
             opts.tls.setup()?.map(Arc::new),
+            opts.tls.client_cert_mapping(),
             opts.reject_no_database,
+            None,
+            opts.float_precision,
+            opts.connection,
         )),
     ))
 }
@@ -111,6 +151,89 @@ async fn test_reject_no_database() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_close_idle_connection() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+    let sessions = SessionRegistryRef::default();
+    let mysql_server = create_mysql_server(
+        MemTable::default_numbers_table(),
+        MysqlOpts {
+            connection: ConnectionOptions {
+                idle_timeout: Some(Duration::from_millis(500)),
+                ..Default::default()
+            },
+            sessions: sessions.clone(),
+            ..Default::default()
+        },
+    )?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_addr = mysql_server.start(listening).await.unwrap();
+
+    // A client saying nothing after the handshake of the server.
+    let mut stream = TcpStream::connect(server_addr).await.unwrap();
+    let (_, handshake) = read_packet(&mut stream).await;
+    assert_eq!(10, handshake[0]);
+    assert_eq!(1, sessions.len());
+
+    let (seq, error) = read_packet(&mut stream).await;
+    assert_eq!(0, seq);
+    assert_eq!(0xff, error[0]);
+    assert_eq!(
+        ER_CLIENT_INTERACTION_TIMEOUT,
+        u16::from_le_bytes([error[1], error[2]])
+    );
+    assert_eq!(b"#HY000", &error[3..9]);
+    assert_eq!(0, stream.read(&mut [0; 1]).await.unwrap());
+    wait_sessions_closed(&sessions).await;
+
+    // Active sessions are kept, and sessions can override the timeout.
+    let opts = mysql_async::OptsBuilder::default()
+        .ip_or_hostname("127.0.0.1")
+        .tcp_port(server_addr.port())
+        .prefer_socket(false)
+        .user(Some("greptime".to_string()))
+        .pass(Some("greptime".to_string()));
+    let mut conn = Conn::new(opts).await.unwrap();
+    for _ in 0..5 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let _: Vec<u32> = conn
+            .query("SELECT uint32s FROM numbers LIMIT 1")
+            .await
+            .unwrap();
+    }
+    conn.query_drop("SET wait_timeout = 3600").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(800)).await;
+    let _: Vec<u32> = conn
+        .query("SELECT uint32s FROM numbers LIMIT 1")
+        .await
+        .unwrap();
+    assert_eq!(1, sessions.len());
+
+    conn.disconnect().await.unwrap();
+    wait_sessions_closed(&sessions).await;
+    mysql_server.shutdown().await
+}
+
+/// Reads a packet, returning its sequence id and payload.
+async fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut header = [0; 4];
+    let _ = stream.read_exact(&mut header).await.unwrap();
+    let len = u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize;
+    let mut payload = vec![0; len];
+    let _ = stream.read_exact(&mut payload).await.unwrap();
+    (header[3], payload)
+}
+
+async fn wait_sessions_closed(sessions: &SessionRegistryRef) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !sessions.is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
 #[tokio::test]
 async fn test_schema_validation() -> Result<()> {
     async fn generate_server(auth_info: DatabaseAuthInfo<'_>) -> Result<(Box<dyn Server>, u16)> {
@@ -218,6 +341,139 @@ async fn test_query_all_datatypes() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_query_times_in_session_time_zone() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+
+    let column_schemas = vec![
+        ColumnSchema::new("s", ConcreteDataType::timestamp_second_datatype(), true),
+        ColumnSchema::new(
+            "ms",
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            true,
+        ),
+        ColumnSchema::new(
+            "us",
+            ConcreteDataType::timestamp_microsecond_datatype(),
+            true,
+        ),
+        ColumnSchema::new(
+            "ns",
+            ConcreteDataType::timestamp_nanosecond_datatype(),
+            true,
+        ),
+        ColumnSchema::new("dt", ConcreteDataType::datetime_datatype(), true),
+        ColumnSchema::new("d", ConcreteDataType::date_datatype(), true),
+    ];
+    let columns: Vec<VectorRef> = vec![
+        Arc::new(TimestampSecondVector::from_vec(vec![1668070237])),
+        Arc::new(TimestampMillisecondVector::from_vec(vec![1668070237042])),
+        Arc::new(TimestampMicrosecondVector::from_vec(vec![1668070237000042])),
+        Arc::new(TimestampNanosecondVector::from_vec(vec![
+            1668070237000000042,
+        ])),
+        Arc::new(DateTimeVector::from_vec(vec![1668070237])),
+        Arc::new(DateVector::from_vec(vec![19306])),
+    ];
+    let schema = Arc::new(Schema::new(column_schemas));
+    let recordbatch = RecordBatch::new(schema, columns).unwrap();
+    let table = MemTable::new("times", recordbatch);
+
+    let mysql_server = create_mysql_server(table, Default::default())?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_addr = mysql_server.start(listening).await.unwrap();
+    let mut connection = create_connection_default_db_name(server_addr.port(), false)
+        .await
+        .unwrap();
+
+    for (time_zone, expected) in [
+        (
+            "+08:00",
+            [
+                "2022-11-10 16:50:37",
+                "2022-11-10 16:50:37.042",
+                "2022-11-10 16:50:37.000042",
+                "2022-11-10 16:50:37.000000042",
+                "2022-11-10 16:50:37",
+                "2022-11-10",
+            ],
+        ),
+        (
+            "-05:30",
+            [
+                "2022-11-10 03:20:37",
+                "2022-11-10 03:20:37.042",
+                "2022-11-10 03:20:37.000042",
+                "2022-11-10 03:20:37.000000042",
+                "2022-11-10 03:20:37",
+                "2022-11-10",
+            ],
+        ),
+    ] {
+        connection
+            .query_drop(format!("SET time_zone = '{time_zone}'"))
+            .await
+            .unwrap();
+        let rows: Vec<Row> = connection.query("SELECT * FROM times").await.unwrap();
+        assert_eq!(1, rows.len());
+        let values: Vec<String> = (0..expected.len())
+            .map(|i| rows[0].get::<String, _>(i).unwrap())
+            .collect();
+        assert_eq!(expected.to_vec(), values, "time zone {time_zone}");
+    }
+
+    let err = connection
+        .query_drop("SET time_zone = 'Mars/Olympus'")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Mars/Olympus"), "{err}");
+
+    mysql_server.shutdown().await.unwrap();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_floats_in_configured_precision() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+
+    for (float_precision, expected) in [
+        (
+            FloatPrecision::Roundtrip,
+            ["0.30000000000000004", "2.71828"],
+        ),
+        (FloatPrecision::significant(3).unwrap(), ["0.3", "2.72"]),
+    ] {
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "f",
+            ConcreteDataType::float64_datatype(),
+            true,
+        )]));
+        let columns: Vec<VectorRef> =
+            vec![Arc::new(Float64Vector::from_vec(vec![0.1 + 0.2, 2.71828]))];
+        let recordbatch = RecordBatch::new(schema, columns).unwrap();
+        let table = MemTable::new("floats", recordbatch);
+
+        let mysql_server = create_mysql_server(
+            table,
+            MysqlOpts {
+                float_precision,
+                ..Default::default()
+            },
+        )?;
+        let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+        let server_addr = mysql_server.start(listening).await.unwrap();
+        let mut connection = create_connection_default_db_name(server_addr.port(), false)
+            .await
+            .unwrap();
+
+        // The floats are written in text by the text protocol.
+        let values: Vec<String> = connection.query("SELECT f FROM floats").await.unwrap();
+        assert_eq!(expected.to_vec(), values, "{float_precision:?}");
+        mysql_server.shutdown().await.unwrap();
+    }
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_server_prefer_secure_client_plain() -> Result<()> {
     do_test_query_all_datatypes_with_secure_server(servers::tls::TlsMode::Prefer, false, false)
@@ -252,6 +508,7 @@ async fn test_server_required_secure_client_plain() -> Result<()> {
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-rsa.key".to_owned(),
+        ..Default::default()
     };
 
     let client_tls = false;
@@ -289,6 +546,7 @@ async fn test_server_required_secure_client_plain_with_pkcs8_priv_key() -> Resul
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-pkcs8.key".to_owned(),
+        ..Default::default()
     };
 
     let client_tls = false;
@@ -393,6 +651,7 @@ async fn do_test_query_all_datatypes(server_tls: TlsOption, client_tls: bool) ->
     for (i, column) in columns.iter().enumerate() {
         assert_eq!(mysql_columns_def[i], column.column_type());
         assert_eq!(column_schemas[i].name, column.name_str());
+        assert_eq!("all_datatypes", column.table_str());
     }
 
     let rows = result.collect::<MysqlTextRow>().await.unwrap();
@@ -453,7 +712,189 @@ async fn test_query_concurrently() -> Result<()> {
     Ok(())
 }
 
-#[ignore = "https://github.com/GreptimeTeam/greptimedb/issues/1385"]
+const NUM_STREAMED_BATCHES: u32 = 4;
+const STREAMED_BATCH_SIZE: u32 = 100;
+/// Index of the batch failing if the query contains "broken".
+const BROKEN_BATCH: u32 = 2;
+
+/// Query handler returning a stream of [NUM_STREAMED_BATCHES] batches of numbers, whose
+/// batch [BROKEN_BATCH] fails if the query contains "broken".
+#[derive(Default)]
+struct StreamingQueryHandler {
+    /// Max number of the yielded batches still alive when the streams are polled again.
+    max_alive_batches: Arc<AtomicUsize>,
+}
+
+/// Stream creating the batches as it's polled, counting the batches it yielded that are
+/// still alive when it's polled again.
+struct CountingRecordBatchStream {
+    schema: SchemaRef,
+    broken: bool,
+    next_batch: u32,
+    yielded: Vec<Weak<dyn Vector>>,
+    max_alive_batches: Arc<AtomicUsize>,
+    /// Context to warn in once all the batches are yielded, like a truncated result.
+    warn_on_end: Option<QueryContextRef>,
+}
+
+impl RecordBatchStream for CountingRecordBatchStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Stream for CountingRecordBatchStream {
+    type Item = common_recordbatch::error::Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let alive = self
+            .yielded
+            .iter()
+            .filter(|column| column.strong_count() > 0)
+            .count();
+        self.max_alive_batches.fetch_max(alive, Ordering::Relaxed);
+
+        let i = self.next_batch;
+        if i == NUM_STREAMED_BATCHES {
+            if let Some(query_ctx) = self.warn_on_end.take() {
+                query_ctx.add_warning("Result is truncated".to_string());
+            }
+            return Poll::Ready(None);
+        }
+        self.next_batch += 1;
+        if self.broken && i == BROKEN_BATCH {
+            let error = CreateRecordBatchesSnafu {
+                reason: "broken batch",
+            }
+            .fail();
+            return Poll::Ready(Some(error));
+        }
+
+        let numbers: VectorRef = Arc::new(UInt32Vector::from_values(
+            i * STREAMED_BATCH_SIZE..(i + 1) * STREAMED_BATCH_SIZE,
+        ));
+        self.yielded.push(Arc::downgrade(&numbers));
+        Poll::Ready(Some(RecordBatch::new(self.schema.clone(), vec![numbers])))
+    }
+}
+
+#[async_trait]
+impl SqlQueryHandler for StreamingQueryHandler {
+    type Error = Error;
+
+    async fn do_query(&self, query: &str, query_ctx: QueryContextRef) -> Vec<Result<Output>> {
+        query_ctx.clear_warnings();
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "n",
+            ConcreteDataType::uint32_datatype(),
+            false,
+        )]));
+        let stream = CountingRecordBatchStream {
+            schema,
+            broken: query.contains("broken"),
+            next_batch: 0,
+            yielded: Vec::new(),
+            max_alive_batches: self.max_alive_batches.clone(),
+            warn_on_end: query.contains("truncated").then_some(query_ctx),
+        };
+        vec![Ok(Output::Stream(Box::pin(stream)))]
+    }
+
+    async fn do_promql_query(&self, _: &PromQuery, _: QueryContextRef) -> Vec<Result<Output>> {
+        unimplemented!()
+    }
+
+    async fn do_describe(&self, _: Statement, _: QueryContextRef) -> Result<Option<Schema>> {
+        Ok(None)
+    }
+
+    async fn is_valid_schema(&self, _: &str, _: &str) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+#[tokio::test]
+async fn test_query_streamed_batches() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+
+    let handler = Arc::new(StreamingQueryHandler::default());
+    let mysql_server = create_mysql_server_with_handler(handler.clone(), Default::default())?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_addr = mysql_server.start(listening).await.unwrap();
+
+    let mut connection = create_connection_default_db_name(server_addr.port(), false)
+        .await
+        .unwrap();
+
+    let numbers: Vec<u32> = connection.query("SELECT n FROM numbers").await.unwrap();
+    let expected: Vec<_> = (0..NUM_STREAMED_BATCHES * STREAMED_BATCH_SIZE).collect();
+    assert_eq!(expected, numbers);
+    // Each batch is dropped once its rows are written, before the next one is polled.
+    assert_eq!(0, handler.max_alive_batches.load(Ordering::Relaxed));
+
+    // The rows of the batches before the broken one are sent, then the error ends the result
+    // set instead of leaving the client waiting for more rows.
+    let mut numbers = Vec::new();
+    let err = tokio::time::timeout(
+        Duration::from_secs(10),
+        connection
+            .query_iter("SELECT n FROM broken")
+            .await
+            .unwrap()
+            .for_each(|row| numbers.push(mysql_async::from_row::<u32>(row))),
+    )
+    .await
+    .expect("the broken result set should end")
+    .unwrap_err();
+    assert!(err.to_string().contains("broken batch"), "{err}");
+    let expected: Vec<_> = (0..BROKEN_BATCH * STREAMED_BATCH_SIZE).collect();
+    assert_eq!(expected, numbers);
+
+    // The connection is still usable after the error.
+    let numbers: Vec<u32> = connection.query("SELECT n FROM numbers").await.unwrap();
+    assert_eq!(
+        (NUM_STREAMED_BATCHES * STREAMED_BATCH_SIZE) as usize,
+        numbers.len()
+    );
+
+    mysql_server.shutdown().await.unwrap();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_warnings_of_result_set() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+
+    let handler = Arc::new(StreamingQueryHandler::default());
+    let mysql_server = create_mysql_server_with_handler(handler, Default::default())?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_addr = mysql_server.start(listening).await.unwrap();
+
+    let mut connection = create_connection_default_db_name(server_addr.port(), false)
+        .await
+        .unwrap();
+
+    let numbers: Vec<u32> = connection.query("SELECT n FROM numbers").await.unwrap();
+    assert!(!numbers.is_empty());
+    assert_eq!(0, connection.get_warnings());
+
+    // The warning is added after the rows are written, and counted in the packet terminating
+    // the result set.
+    let numbers: Vec<u32> = connection.query("SELECT n FROM truncated").await.unwrap();
+    assert_eq!(
+        (NUM_STREAMED_BATCHES * STREAMED_BATCH_SIZE) as usize,
+        numbers.len()
+    );
+    assert_eq!(1, connection.get_warnings());
+
+    // The count is of the last statement.
+    let _: Vec<u32> = connection.query("SELECT n FROM numbers").await.unwrap();
+    assert_eq!(0, connection.get_warnings());
+
+    mysql_server.shutdown().await.unwrap();
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn test_query_prepared() -> Result<()> {
     common_telemetry::init_default_ut_logging();
@@ -486,6 +927,99 @@ async fn test_query_prepared() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_prepare_execute_close() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+    let TestingData {
+        column_schemas,
+        columns,
+        ..
+    } = all_datatype_testing_data();
+    let schema = Arc::new(Schema::new(column_schemas));
+    let recordbatch = RecordBatch::new(schema, columns).unwrap();
+    let table = MemTable::new("all_datatypes", recordbatch);
+
+    let mysql_server = create_mysql_server(table, Default::default())?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_addr = mysql_server.start(listening).await.unwrap();
+    let mut connection = create_connection_default_db_name(server_addr.port(), false)
+        .await
+        .unwrap();
+
+    // The `?` in the literals are not placeholders, the types of the params are inferred from
+    // the columns they are compared with.
+    let statement = connection
+        .prep(
+            "SELECT uint32s, strings FROM all_datatypes \
+             WHERE uint32s >= ? AND strings = ? AND '?' <> '??'",
+        )
+        .await
+        .unwrap();
+    assert_eq!(2, statement.num_params());
+    let param_types = statement
+        .params()
+        .iter()
+        .map(|column| column.column_type())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            mysql_async::consts::ColumnType::MYSQL_TYPE_LONG,
+            mysql_async::consts::ColumnType::MYSQL_TYPE_VARCHAR
+        ],
+        param_types
+    );
+
+    let rows: Vec<(u32, String)> = connection.exec(&statement, (0u32, "hola")).await.unwrap();
+    assert_eq!(vec![(0, "hola".to_string())], rows);
+    // The params are bound as values, not as SQL.
+    let rows: Vec<(u32, String)> = connection
+        .exec(&statement, (0u32, "hola' OR '1' = '1"))
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+
+    // The types of the params of inserts are the types of the columns inserted into.
+    let insert = connection
+        .prep("INSERT INTO all_datatypes (strings, int8s, float64s) VALUES (?, ?, 1.0)")
+        .await
+        .unwrap();
+    let param_types = insert
+        .params()
+        .iter()
+        .map(|column| column.column_type())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            mysql_async::consts::ColumnType::MYSQL_TYPE_VARCHAR,
+            mysql_async::consts::ColumnType::MYSQL_TYPE_TINY
+        ],
+        param_types
+    );
+
+    // The closed statements are deallocated and can't be executed any more.
+    connection.close(statement.clone()).await.unwrap();
+    let result: mysql_async::Result<Vec<Row>> = connection.exec(&statement, (0u32, "hola")).await;
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("prepare statement not exist"));
+
+    // Statements other than the DML ones are not supported.
+    assert!(connection.prep("SHOW TABLES").await.is_err());
+    // The connection is still usable after the errors.
+    let rows: Vec<u32> = connection
+        .exec(
+            "SELECT uint32s FROM all_datatypes WHERE uint32s = ?",
+            (u32::MAX,),
+        )
+        .await
+        .unwrap();
+    assert_eq!(vec![u32::MAX], rows);
+
+    mysql_server.shutdown().await.unwrap();
+    Ok(())
+}
+
 async fn test_prepare_all_type(
     column_schemas: Vec<ColumnSchema>,
     columns: Vec<VectorRef>,
@@ -592,6 +1126,7 @@ async fn do_test_query_all_datatypes_with_secure_server(
                 "tests/ssl/server-rsa.key".to_owned()
             }
         },
+        ..Default::default()
     };
 
     do_test_query_all_datatypes(server_tls, client_tls).await
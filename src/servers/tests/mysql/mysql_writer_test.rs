@@ -16,6 +16,7 @@ use std::sync::Arc;
 
 use datatypes::prelude::*;
 use datatypes::schema::{ColumnSchema, Schema};
+use opensrv_mysql::{ColumnFlags, ColumnType};
 use servers::mysql::writer::create_mysql_column_def;
 
 use crate::mysql::{all_datatype_testing_data, TestingData};
@@ -28,21 +29,72 @@ fn test_create_mysql_column_def() {
         ..
     } = all_datatype_testing_data();
     let schema = Arc::new(Schema::new(column_schemas.clone()));
-    let columns_def = create_mysql_column_def(&schema).unwrap();
+    let result_tables = column_schemas
+        .iter()
+        .map(|c| (c.name.clone(), Some("my_table".to_string())))
+        .collect::<Vec<_>>();
+    let columns_def = create_mysql_column_def(&schema, &result_tables).unwrap();
     assert_eq!(column_schemas.len(), columns_def.len());
 
     for (i, column_def) in columns_def.iter().enumerate() {
         let column_schema = &column_schemas[i];
         assert_eq!(column_schema.name, column_def.column);
+        assert_eq!("my_table", column_def.table);
         let expected_coltype = mysql_columns_def[i];
         assert_eq!(column_def.coltype, expected_coltype);
     }
 
+    let expected_colflags = [
+        ("uint8s", ColumnFlags::UNSIGNED_FLAG),
+        ("uint64s", ColumnFlags::UNSIGNED_FLAG),
+        (
+            "binaries",
+            ColumnFlags::BLOB_FLAG | ColumnFlags::BINARY_FLAG,
+        ),
+        ("strings", ColumnFlags::empty()),
+        ("int64s", ColumnFlags::empty()),
+    ];
+    for (name, expected) in expected_colflags {
+        let column_def = columns_def.iter().find(|c| c.column == name).unwrap();
+        assert_eq!(expected, column_def.colflags, "{name}");
+    }
+
+    let column_schemas = vec![
+        ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            false,
+        ),
+        ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+        ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+    ];
+    let schema = Arc::new(Schema::new(column_schemas));
+    // The computed columns have no table.
+    let result_tables = vec![
+        ("ts".to_string(), Some("my_table".to_string())),
+        ("host".to_string(), Some("my_table".to_string())),
+        ("cpu".to_string(), None),
+    ];
+    let columns_def = create_mysql_column_def(&schema, &result_tables).unwrap();
+    assert_eq!("my_table", columns_def[0].table);
+    assert_eq!("my_table", columns_def[1].table);
+    assert!(columns_def[2].table.is_empty());
+    // The tables of another result are not used.
+    let columns_def = create_mysql_column_def(&schema, &result_tables[..2]).unwrap();
+    assert_eq!(ColumnType::MYSQL_TYPE_TIMESTAMP, columns_def[0].coltype);
+    assert_eq!(
+        ColumnFlags::TIMESTAMP_FLAG | ColumnFlags::NOT_NULL_FLAG,
+        columns_def[0].colflags
+    );
+    assert_eq!(ColumnFlags::NOT_NULL_FLAG, columns_def[1].colflags);
+    assert_eq!(ColumnFlags::empty(), columns_def[2].colflags);
+    assert!(columns_def.iter().all(|c| c.table.is_empty()));
+
     let column_schemas = vec![ColumnSchema::new(
         "lists",
         ConcreteDataType::list_datatype(ConcreteDataType::string_datatype()),
         true,
     )];
     let schema = Arc::new(Schema::new(column_schemas));
-    assert!(create_mysql_column_def(&schema).is_err());
+    assert!(create_mysql_column_def(&schema, &[]).is_err());
 }
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fs::File;
+use std::io::BufReader;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
@@ -22,13 +24,16 @@ use pgwire::api::Type;
 use rand::rngs::StdRng;
 use rand::Rng;
 use rustls::client::{ServerCertVerified, ServerCertVerifier};
-use rustls::{Certificate, Error, ServerName};
+use rustls::{Certificate, Error, PrivateKey, ServerName};
 use servers::auth::UserProviderRef;
+use servers::connection::ConnectionOptions;
 use servers::error::Result;
-use servers::postgres::PostgresServer;
+use servers::postgres::{PostgresServer, IDLE_SESSION_TIMEOUT_CODE};
 use servers::server::Server;
-use servers::tls::TlsOption;
+use servers::tls::{ClientCertSchema, TlsOption};
+use session::registry::SessionRegistryRef;
 use table::test_util::MemTable;
+use tokio::io::AsyncReadExt;
 use tokio_postgres::{Client, Error as PgError, NoTls, SimpleQueryMessage};
 
 use crate::auth::{DatabaseAuthInfo, MockUserProvider};
@@ -39,6 +44,24 @@ fn create_postgres_server(
     check_pwd: bool,
     tls: TlsOption,
     auth_info: Option<DatabaseAuthInfo>,
+) -> Result<Box<dyn Server>> {
+    create_postgres_server_with_connection(
+        table,
+        check_pwd,
+        tls,
+        auth_info,
+        ConnectionOptions::default(),
+        SessionRegistryRef::default(),
+    )
+}
+
+fn create_postgres_server_with_connection(
+    table: MemTable,
+    check_pwd: bool,
+    tls: TlsOption,
+    auth_info: Option<DatabaseAuthInfo>,
+    connection: ConnectionOptions,
+    sessions: SessionRegistryRef,
 ) -> Result<Box<dyn Server>> {
     let instance = Arc::new(create_testing_instance(table));
     let io_runtime = Arc::new(
@@ -63,6 +86,8 @@ fn create_postgres_server(
         tls,
         io_runtime,
         user_provider,
+        connection,
+        sessions,
     )))
 }
 
@@ -83,6 +108,61 @@ pub async fn test_start_postgres_server() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_close_idle_connection() -> Result<()> {
+    common_telemetry::init_default_ut_logging();
+    let sessions = SessionRegistryRef::default();
+    let pg_server = create_postgres_server_with_connection(
+        MemTable::default_numbers_table(),
+        false,
+        Default::default(),
+        None,
+        ConnectionOptions {
+            idle_timeout: Some(Duration::from_millis(500)),
+            ..Default::default()
+        },
+        sessions.clone(),
+    )?;
+    let listening = "127.0.0.1:0".parse::<SocketAddr>().unwrap();
+    let server_addr = pg_server.start(listening).await.unwrap();
+
+    // A client saying nothing after connecting.
+    let mut stream = tokio::net::TcpStream::connect(server_addr).await.unwrap();
+    let mut message = Vec::new();
+    let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut message))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(b'E', message[0]);
+    let code = format!("C{IDLE_SESSION_TIMEOUT_CODE}\0");
+    assert!(message
+        .windows(code.len())
+        .any(|field| field == code.as_bytes()));
+
+    // Active sessions are kept.
+    let client = create_plain_connection(server_addr.port(), false)
+        .await
+        .unwrap();
+    for _ in 0..5 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let _ = client
+            .simple_query("SELECT uint32s FROM numbers LIMIT 1")
+            .await
+            .unwrap();
+    }
+    assert_eq!(1, sessions.len());
+
+    drop(client);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !sessions.is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    pg_server.shutdown().await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_shutdown_pg_server_range() -> Result<()> {
     assert!(test_shutdown_pg_server(false).await.is_ok());
@@ -269,6 +349,7 @@ async fn test_server_secure_require_client_plain() -> Result<()> {
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-rsa.key".to_owned(),
+        ..Default::default()
     };
     let server_port = start_test_server(server_tls).await?;
     let r = create_plain_connection(server_port, false).await;
@@ -284,6 +365,7 @@ async fn test_server_secure_require_client_plain_with_pkcs8_priv_key() -> Result
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-pkcs8.key".to_owned(),
+        ..Default::default()
     };
     let server_port = start_test_server(server_tls).await?;
     let r = create_plain_connection(server_port, false).await;
@@ -327,6 +409,44 @@ async fn test_using_db() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_client_cert_schema() -> Result<()> {
+    let server_tls = |client_cert_schema, client_cert_schema_strict| TlsOption {
+        mode: servers::tls::TlsMode::Require,
+        cert_path: "tests/ssl/server.crt".to_owned(),
+        key_path: "tests/ssl/server-rsa.key".to_owned(),
+        ca_cert_path: "tests/ssl/client-ca.crt".to_owned(),
+        client_cert_schema,
+        client_cert_schema_strict,
+    };
+
+    // The client certificate maps to "public" by its OU.
+    let server_port = start_test_server(server_tls(ClientCertSchema::Ou, false)).await?;
+    let client = create_connection_with_client_cert(server_port, None, true)
+        .await
+        .unwrap();
+    let result = client.simple_query("SELECT uint32s FROM numbers").await;
+    assert!(result.is_ok());
+    // The clients must present a certificate.
+    let client = create_connection_with_client_cert(server_port, None, false).await;
+    assert!(client.is_err());
+
+    // The client certificate pins the client to "tenant_a" by its SAN.
+    let server_port = start_test_server(server_tls(ClientCertSchema::San, true)).await?;
+    let client = create_connection_with_client_cert(server_port, None, true)
+        .await
+        .unwrap();
+    let err = client
+        .simple_query("SELECT uint32s FROM numbers")
+        .await
+        .unwrap_err();
+    assert!(err.as_db_error().unwrap().message().contains("tenant_a"));
+    let client =
+        create_connection_with_client_cert(server_port, Some(DEFAULT_SCHEMA_NAME), true).await;
+    assert!(client.is_err());
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_extended_query() -> Result<()> {
     let server_port = start_test_server(TlsOption::default()).await?;
@@ -403,6 +523,48 @@ async fn create_secure_connection(
     Ok(client)
 }
 
+async fn create_connection_with_client_cert(
+    port: u16,
+    db: Option<&str>,
+    with_cert: bool,
+) -> std::result::Result<Client, PgError> {
+    let mut url =
+        format!("sslmode=require host=127.0.0.1 port={port} user=greptime connect_timeout=2");
+    if let Some(db) = db {
+        url.push_str(&format!(" dbname={db}"));
+    }
+
+    let config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(rustls::RootCertStore::empty());
+    let mut config = if with_cert {
+        let certs = rustls_pemfile::certs(&mut BufReader::new(
+            File::open("tests/ssl/client.crt").unwrap(),
+        ))
+        .unwrap();
+        let mut keys = rustls_pemfile::pkcs8_private_keys(&mut BufReader::new(
+            File::open("tests/ssl/client.key").unwrap(),
+        ))
+        .unwrap();
+        config
+            .with_client_auth_cert(
+                certs.into_iter().map(Certificate).collect(),
+                PrivateKey(keys.remove(0)),
+            )
+            .unwrap()
+    } else {
+        config.with_no_client_auth()
+    };
+    config
+        .dangerous()
+        .set_certificate_verifier(Arc::new(AcceptAllVerifier {}));
+
+    let tls = tokio_postgres_rustls::MakeRustlsConnect::new(config);
+    let (client, conn) = tokio_postgres::connect(&url, tls).await?;
+    tokio::spawn(conn);
+    Ok(client)
+}
+
 async fn create_plain_connection(
     port: u16,
     with_pwd: bool,
@@ -488,6 +650,7 @@ async fn do_simple_query_with_secure_server(
                 "tests/ssl/server-rsa.key".to_owned()
             }
         },
+        ..Default::default()
     };
 
     do_simple_query(server_tls, client_tls).await
@@ -24,3 +24,28 @@ openssl x509 -req \
 
 # Create private key of pkcs8 format from rsa key.
 openssl pkcs8 -topk8 -inform PEM -in ./server-rsa.key -outform pem -nocrypt -out server-pkcs8.key
+
+# Create the self-signed CA certificate verifying the client certificates.
+openssl req -x509 \
+            -sha256 -days 36500 \
+            -nodes \
+            -newkey rsa:2048 \
+            -subj "/CN=greptime-client-ca" \
+            -addext "basicConstraints=critical,CA:TRUE" \
+            -addext "keyUsage=critical,keyCertSign,cRLSign" \
+            -keyout client-ca.key -out client-ca.crt
+
+# Create the client private key of pkcs8 format.
+openssl genrsa -out client-rsa.key 2048
+openssl pkcs8 -topk8 -inform PEM -in ./client-rsa.key -outform pem -nocrypt -out client.key
+rm client-rsa.key
+
+# Create the client certificate, mapped to schema "tenant_a" by its SAN and "public" by its OU.
+openssl req -new -key client.key -out client.csr -subj "/O=Greptime/OU=public/CN=tenant"
+openssl x509 -req \
+    -in client.csr \
+    -CA client-ca.crt -CAkey client-ca.key \
+    -CAcreateserial -out client.crt \
+    -days 36500 \
+    -sha256 -extfile client-cert.conf
+rm client.csr
@@ -14,11 +14,17 @@
 
 use std::fmt::{Display, Formatter};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use arc_swap::ArcSwap;
+use common_base::commit_token::CommitToken;
+use common_base::validation_mode::ValidationMode;
+use common_base::write_mode::WriteMode;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_telemetry::debug;
+use common_time::TimeZone;
 
 pub type QueryContextRef = Arc<QueryContext>;
 pub type ConnInfoRef = Arc<ConnInfo>;
@@ -27,6 +33,38 @@ pub type ConnInfoRef = Arc<ConnInfo>;
 pub struct QueryContext {
     current_catalog: ArcSwap<String>,
     current_schema: ArcSwap<String>,
+    /// Commit token of the writes the query should read, also updated by the writes of the
+    /// query itself.
+    commit_token: Mutex<CommitToken>,
+    /// Whether the session is pinned to the current schema, which rejects switching to other
+    /// schemas.
+    schema_pinned: AtomicBool,
+    /// Whether inserts only validate their rows and report the DDL they would issue, without
+    /// writing anything.
+    dry_run: AtomicBool,
+    /// How inserts write the rows whose primary key and timestamp already exist.
+    write_mode: Mutex<WriteMode>,
+    /// How many of the invalid rows of an insert are reported before it's rejected.
+    validation_mode: Mutex<ValidationMode>,
+    /// Max number of rows returned by the `SELECT`s without a `LIMIT`, unlimited if `None`.
+    select_limit: Mutex<Option<usize>>,
+    /// Wall-clock time in millis the queries read the snapshots of tables as of, or the
+    /// latest snapshots if `None`.
+    as_of: Mutex<Option<i64>>,
+    /// Time zone to show the times of the results in.
+    time_zone: Mutex<TimeZone>,
+    /// How long the session is kept without any statement before its connection is closed,
+    /// like the `wait_timeout` of MySQL. Never closed if `None`.
+    idle_timeout: Mutex<Option<Duration>>,
+    /// Warnings of the last statement, e.g. its result is truncated by the select limit.
+    warnings: Mutex<Vec<String>>,
+    /// Columns of the result of the last query, with the tables (or their aliases) the columns
+    /// are read from, `None` for the computed columns.
+    result_tables: Mutex<Vec<(String, Option<String>)>>,
+    /// Who issues the query.
+    origin: QueryOrigin,
+    /// The authenticated user of the query.
+    current_user: ArcSwap<UserInfo>,
 }
 
 impl Default for QueryContext {
@@ -55,6 +93,19 @@ impl QueryContext {
         Self {
             current_catalog: ArcSwap::new(Arc::new(DEFAULT_CATALOG_NAME.to_string())),
             current_schema: ArcSwap::new(Arc::new(DEFAULT_SCHEMA_NAME.to_string())),
+            commit_token: Mutex::new(CommitToken::default()),
+            schema_pinned: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            write_mode: Mutex::new(WriteMode::default()),
+            validation_mode: Mutex::new(ValidationMode::default()),
+            select_limit: Mutex::new(None),
+            as_of: Mutex::new(None),
+            time_zone: Mutex::new(TimeZone::default()),
+            idle_timeout: Mutex::new(None),
+            warnings: Mutex::new(Vec::new()),
+            result_tables: Mutex::new(Vec::new()),
+            origin: QueryOrigin::User,
+            current_user: ArcSwap::new(Arc::new(UserInfo::default())),
         }
     }
 
@@ -62,6 +113,51 @@ impl QueryContext {
         Self {
             current_catalog: ArcSwap::new(Arc::new(catalog.to_string())),
             current_schema: ArcSwap::new(Arc::new(schema.to_string())),
+            commit_token: Mutex::new(CommitToken::default()),
+            schema_pinned: AtomicBool::new(false),
+            dry_run: AtomicBool::new(false),
+            write_mode: Mutex::new(WriteMode::default()),
+            validation_mode: Mutex::new(ValidationMode::default()),
+            select_limit: Mutex::new(None),
+            as_of: Mutex::new(None),
+            time_zone: Mutex::new(TimeZone::default()),
+            idle_timeout: Mutex::new(None),
+            warnings: Mutex::new(Vec::new()),
+            result_tables: Mutex::new(Vec::new()),
+            origin: QueryOrigin::User,
+            current_user: ArcSwap::new(Arc::new(UserInfo::default())),
+        }
+    }
+
+    /// Marks the queries of the context as issued by `origin`.
+    pub fn with_origin(mut self, origin: QueryOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn origin(&self) -> &QueryOrigin {
+        &self.origin
+    }
+
+    /// Returns a new context of the same session state, e.g. schema, user and snapshot to read,
+    /// whose queries are issued by `origin`. Warnings and results of the context are not kept.
+    pub fn fork(&self, origin: QueryOrigin) -> Self {
+        Self {
+            current_catalog: ArcSwap::new(self.current_catalog.load_full()),
+            current_schema: ArcSwap::new(self.current_schema.load_full()),
+            commit_token: Mutex::new(self.commit_token()),
+            schema_pinned: AtomicBool::new(self.is_schema_pinned()),
+            dry_run: AtomicBool::new(self.is_dry_run()),
+            write_mode: Mutex::new(self.write_mode()),
+            validation_mode: Mutex::new(self.validation_mode()),
+            select_limit: Mutex::new(self.select_limit()),
+            as_of: Mutex::new(self.as_of()),
+            time_zone: Mutex::new(self.time_zone()),
+            idle_timeout: Mutex::new(self.idle_timeout()),
+            warnings: Mutex::new(Vec::new()),
+            result_tables: Mutex::new(Vec::new()),
+            origin,
+            current_user: ArcSwap::new(self.current_user()),
         }
     }
 
@@ -73,6 +169,14 @@ impl QueryContext {
         self.current_catalog.load().as_ref().clone()
     }
 
+    pub fn current_user(&self) -> Arc<UserInfo> {
+        self.current_user.load().clone()
+    }
+
+    pub fn set_current_user(&self, user_info: UserInfo) {
+        self.current_user.store(Arc::new(user_info));
+    }
+
     pub fn set_current_schema(&self, schema: &str) {
         let last = self.current_schema.swap(Arc::new(schema.to_string()));
         if schema != last.as_str() {
@@ -92,6 +196,160 @@ impl QueryContext {
             )
         }
     }
+
+    /// Switches to the default catalog and schema of the user, if any, and pins the session to
+    /// them if the user is not allowed to use other schemas.
+    ///
+    /// Callers should set the database specified by the client, if any, after this.
+    pub fn use_default_schema_of(&self, user_info: &UserInfo) {
+        let Some(schema) = user_info.default_schema() else { return };
+        if let Some(catalog) = user_info.default_catalog() {
+            self.set_current_catalog(catalog);
+        }
+        self.set_current_schema(schema);
+        if user_info.is_schema_pinned() {
+            self.schema_pinned.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns true if the session is not allowed to switch to other schemas.
+    pub fn is_schema_pinned(&self) -> bool {
+        self.schema_pinned.load(Ordering::Relaxed)
+    }
+
+    pub fn set_dry_run(&self, dry_run: bool) {
+        self.dry_run.store(dry_run, Ordering::Relaxed);
+    }
+
+    /// Returns true if the inserts of the context are dry runs.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::Relaxed)
+    }
+
+    pub fn set_write_mode(&self, write_mode: WriteMode) {
+        *self.write_mode.lock().unwrap() = write_mode;
+    }
+
+    /// Returns the write mode of the inserts of the context.
+    pub fn write_mode(&self) -> WriteMode {
+        *self.write_mode.lock().unwrap()
+    }
+
+    pub fn set_validation_mode(&self, validation_mode: ValidationMode) {
+        *self.validation_mode.lock().unwrap() = validation_mode;
+    }
+
+    /// Returns the validation mode of the inserts of the context.
+    pub fn validation_mode(&self) -> ValidationMode {
+        *self.validation_mode.lock().unwrap()
+    }
+
+    pub fn set_select_limit(&self, select_limit: Option<usize>) {
+        *self.select_limit.lock().unwrap() = select_limit;
+    }
+
+    /// Returns the max number of rows returned by the `SELECT`s without a `LIMIT`, if any.
+    pub fn select_limit(&self) -> Option<usize> {
+        *self.select_limit.lock().unwrap()
+    }
+
+    pub fn set_as_of(&self, as_of: Option<i64>) {
+        *self.as_of.lock().unwrap() = as_of;
+    }
+
+    /// Returns the wall-clock time in millis the queries read the snapshots of tables as of,
+    /// if any. Writes and DDL are not affected.
+    pub fn as_of(&self) -> Option<i64> {
+        *self.as_of.lock().unwrap()
+    }
+
+    pub fn set_time_zone(&self, time_zone: TimeZone) {
+        *self.time_zone.lock().unwrap() = time_zone;
+    }
+
+    /// Returns the time zone to show the times of the results in, the system time zone of the
+    /// server by default.
+    pub fn time_zone(&self) -> TimeZone {
+        *self.time_zone.lock().unwrap()
+    }
+
+    pub fn set_idle_timeout(&self, idle_timeout: Option<Duration>) {
+        *self.idle_timeout.lock().unwrap() = idle_timeout;
+    }
+
+    /// Returns how long the session is kept without any statement, if it's closed when idle.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        *self.idle_timeout.lock().unwrap()
+    }
+
+    pub fn add_warning(&self, warning: String) {
+        self.warnings.lock().unwrap().push(warning);
+    }
+
+    /// Returns the warnings of the last statement.
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    /// Clears the warnings of the last statement, before a new statement is executed.
+    pub fn clear_warnings(&self) {
+        self.warnings.lock().unwrap().clear();
+    }
+
+    /// Sets the columns of the result of the last query and the tables they are read from.
+    pub fn set_result_tables(&self, result_tables: Vec<(String, Option<String>)>) {
+        *self.result_tables.lock().unwrap() = result_tables;
+    }
+
+    /// Returns the columns of the result of the last query and the tables they are read from,
+    /// empty if the last statement isn't a query.
+    pub fn result_tables(&self) -> Vec<(String, Option<String>)> {
+        self.result_tables.lock().unwrap().clone()
+    }
+
+    pub fn commit_token(&self) -> CommitToken {
+        self.commit_token.lock().unwrap().clone()
+    }
+
+    pub fn merge_commit_token(&self, token: &CommitToken) {
+        self.commit_token.lock().unwrap().merge(token);
+    }
+}
+
+/// Who issues a query, so metrics of internal queries can be told apart from the ones of users.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum QueryOrigin {
+    /// Query of a client.
+    #[default]
+    User,
+    /// Query issued by the database itself, e.g. reading system tables.
+    System,
+    /// Query of the background task with the given name.
+    Background(String),
+}
+
+impl QueryOrigin {
+    pub fn is_user(&self) -> bool {
+        matches!(self, QueryOrigin::User)
+    }
+
+    /// Value of the `origin` label of the query metrics.
+    pub fn label(&self) -> &'static str {
+        match self {
+            QueryOrigin::User => "user",
+            QueryOrigin::System => "system",
+            QueryOrigin::Background(_) => "background",
+        }
+    }
+}
+
+impl Display for QueryOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryOrigin::Background(task) => write!(f, "background({task})"),
+            _ => f.write_str(self.label()),
+        }
+    }
 }
 
 pub const DEFAULT_USERNAME: &str = "greptime";
@@ -99,13 +357,18 @@ pub const DEFAULT_USERNAME: &str = "greptime";
 #[derive(Clone, Debug)]
 pub struct UserInfo {
     username: String,
+    /// Catalog of the default schema, `None` for the default catalog of the session.
+    default_catalog: Option<String>,
+    /// Schema the sessions of the user start in when the client doesn't specify a database,
+    /// from the config of the user provider or the TLS client certificate.
+    default_schema: Option<String>,
+    /// Whether the user is only allowed to use the default schema.
+    schema_pinned: bool,
 }
 
 impl Default for UserInfo {
     fn default() -> Self {
-        Self {
-            username: DEFAULT_USERNAME.to_string(),
-        }
+        Self::new(DEFAULT_USERNAME)
     }
 }
 
@@ -117,8 +380,44 @@ impl UserInfo {
     pub fn new(username: impl Into<String>) -> Self {
         Self {
             username: username.into(),
+            default_catalog: None,
+            default_schema: None,
+            schema_pinned: false,
         }
     }
+
+    /// Sets the default schema of the user, pinning the user to it if `pinned` is true.
+    pub fn with_default_schema(
+        mut self,
+        catalog: Option<String>,
+        schema: impl Into<String>,
+        pinned: bool,
+    ) -> Self {
+        self.default_catalog = catalog;
+        self.default_schema = Some(schema.into());
+        self.schema_pinned = pinned;
+        self
+    }
+
+    pub fn default_catalog(&self) -> Option<&str> {
+        self.default_catalog.as_deref()
+    }
+
+    pub fn default_schema(&self) -> Option<&str> {
+        self.default_schema.as_deref()
+    }
+
+    pub fn is_schema_pinned(&self) -> bool {
+        self.schema_pinned
+    }
+
+    /// Returns true if the user may use the schema, which is always the case unless the user is
+    /// pinned to the default schema.
+    pub fn can_use_schema(&self, catalog: &str, schema: &str) -> bool {
+        !self.schema_pinned
+            || (self.default_catalog().unwrap_or(DEFAULT_CATALOG_NAME) == catalog
+                && self.default_schema() == Some(schema))
+    }
 }
 
 pub struct ConnInfo {
@@ -135,7 +434,7 @@ impl ConnInfo {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Channel {
     Grpc,
     Http,
@@ -146,9 +445,24 @@ pub enum Channel {
     Prometheus,
 }
 
+impl Display for Channel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Channel::Grpc => "grpc",
+            Channel::Http => "http",
+            Channel::Mysql => "mysql",
+            Channel::Postgres => "postgres",
+            Channel::Opentsdb => "opentsdb",
+            Channel::Influxdb => "influxdb",
+            Channel::Prometheus => "prometheus",
+        };
+        f.write_str(name)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::context::{Channel, UserInfo};
+    use crate::context::{Channel, QueryContext, QueryOrigin, UserInfo};
     use crate::Session;
 
     #[test]
@@ -158,6 +472,7 @@ mod test {
         assert_eq!(session.user_info().username(), "greptime");
         session.set_user_info(UserInfo::new("root"));
         assert_eq!(session.user_info().username(), "root");
+        assert_eq!(session.context().current_user().username(), "root");
 
         // test channel
         assert_eq!(session.conn_info().channel, Channel::Mysql);
@@ -167,4 +482,52 @@ mod test {
         );
         assert_eq!(session.conn_info().client_host.port(), 9000);
     }
+
+    #[test]
+    fn test_use_default_schema_of_user() {
+        let session = Session::new("127.0.0.1:9000".parse().unwrap(), Channel::Mysql);
+        session.set_user_info(UserInfo::new("root"));
+        assert_eq!("greptime", session.context().current_catalog());
+        assert_eq!("public", session.context().current_schema());
+
+        session.set_user_info(UserInfo::new("tenant").with_default_schema(
+            Some("tenant_catalog".to_string()),
+            "tenant_a",
+            true,
+        ));
+        let ctx = session.context();
+        assert_eq!("tenant_catalog", ctx.current_catalog());
+        assert_eq!("tenant_a", ctx.current_schema());
+        assert!(ctx.is_schema_pinned());
+
+        let user_info = session.user_info();
+        assert!(user_info.can_use_schema("tenant_catalog", "tenant_a"));
+        assert!(!user_info.can_use_schema("tenant_catalog", "tenant_b"));
+        assert!(!user_info.can_use_schema("greptime", "tenant_a"));
+        let user_info = UserInfo::new("tenant").with_default_schema(None, "tenant_a", true);
+        assert!(user_info.can_use_schema("greptime", "tenant_a"));
+        let user_info = UserInfo::new("tenant").with_default_schema(None, "tenant_a", false);
+        assert!(user_info.can_use_schema("greptime", "tenant_b"));
+    }
+
+    #[test]
+    fn test_query_origin() {
+        let ctx = QueryContext::new();
+        assert!(ctx.origin().is_user());
+        assert_eq!("user", ctx.origin().to_string());
+
+        let ctx = QueryContext::new().with_origin(QueryOrigin::Background("scripts".to_string()));
+        assert!(!ctx.origin().is_user());
+        assert_eq!("background", ctx.origin().label());
+        assert_eq!("background(scripts)", ctx.origin().to_string());
+
+        let ctx = QueryContext::with("c", "s");
+        ctx.set_as_of(Some(42));
+        let forked = ctx.fork(QueryOrigin::System);
+        assert_eq!("system", forked.origin().label());
+        assert_eq!("c", forked.current_catalog());
+        assert_eq!("s", forked.current_schema());
+        assert_eq!(Some(42), forked.as_of());
+        assert!(ctx.origin().is_user());
+    }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod context;
+pub mod registry;
 
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -45,7 +46,11 @@ impl Session {
     pub fn user_info(&self) -> Arc<UserInfo> {
         self.user_info.load().clone()
     }
+    /// Sets the authenticated user of the session, which also switches the session to the
+    /// default schema of the user, if any.
     pub fn set_user_info(&self, user_info: UserInfo) {
+        self.query_ctx.use_default_schema_of(&user_info);
+        self.query_ctx.set_current_user(user_info.clone());
         self.user_info.store(Arc::new(user_info));
     }
 }
@@ -0,0 +1,141 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::context::Channel;
+
+pub type SessionRegistryRef = Arc<SessionRegistry>;
+
+/// Registry of the sessions of the connections to the server, from a connection being accepted
+/// until it's closed.
+#[derive(Debug, Default)]
+pub struct SessionRegistry {
+    next_id: AtomicU64,
+    sessions: Mutex<BTreeMap<u64, SessionEntry>>,
+}
+
+#[derive(Debug)]
+struct SessionEntry {
+    client_host: SocketAddr,
+    channel: Channel,
+    connected_at: Instant,
+}
+
+/// A session in the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub id: u64,
+    pub client_host: SocketAddr,
+    pub channel: Channel,
+    /// How long the connection of the session has been open.
+    pub age: Duration,
+}
+
+impl SessionRegistry {
+    /// Registers the session of a connection, which is deregistered when the returned
+    /// registration is dropped.
+    pub fn register(self: &Arc<Self>, client_host: SocketAddr, channel: Channel) -> Registration {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let entry = SessionEntry {
+            client_host,
+            channel,
+            connected_at: Instant::now(),
+        };
+        let _ = self.sessions.lock().unwrap().insert(id, entry);
+        Registration {
+            registry: self.clone(),
+            id,
+        }
+    }
+
+    /// Returns the registered sessions, in the order they are registered.
+    pub fn sessions(&self) -> Vec<SessionInfo> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, entry)| SessionInfo {
+                id: *id,
+                client_host: entry.client_host,
+                channel: entry.channel,
+                age: entry.connected_at.elapsed(),
+            })
+            .collect()
+    }
+
+    /// Returns the number of the registered sessions.
+    pub fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Registration of a session, deregistering the session on drop.
+#[derive(Debug)]
+pub struct Registration {
+    registry: SessionRegistryRef,
+    id: u64,
+}
+
+impl Registration {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let _ = self.registry.sessions.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_session() {
+        let registry = Arc::new(SessionRegistry::default());
+        let addr = "127.0.0.1:9000".parse().unwrap();
+        let mysql = registry.register(addr, Channel::Mysql);
+        let postgres = registry.register(addr, Channel::Postgres);
+
+        let sessions = registry.sessions();
+        assert_eq!(2, sessions.len());
+        assert_eq!(
+            (mysql.id(), Channel::Mysql),
+            (sessions[0].id, sessions[0].channel)
+        );
+        assert_eq!(
+            (postgres.id(), Channel::Postgres),
+            (sessions[1].id, sessions[1].channel)
+        );
+
+        drop(mysql);
+        let sessions = registry.sessions();
+        assert_eq!(1, sessions.len());
+        assert_eq!(Channel::Postgres, sessions[0].channel);
+
+        drop(postgres);
+        assert!(registry.is_empty());
+    }
+}
@@ -15,8 +15,6 @@
 use std::any::Any;
 
 use common_error::prelude::*;
-use common_time::timestamp::TimeUnit;
-use common_time::Timestamp;
 use datatypes::prelude::{ConcreteDataType, Value};
 use snafu::Location;
 use sqlparser::parser::ParserError;
@@ -128,13 +126,14 @@ pub enum Error {
     InvalidSqlValue { value: String },
 
     #[snafu(display(
-        "Converting timestamp {:?} to unit {:?} overflow",
-        timestamp,
-        target_unit
+        "Failed to parse timestamp of column {}, source: {}",
+        column_name,
+        source
     ))]
-    TimestampOverflow {
-        timestamp: Timestamp,
-        target_unit: TimeUnit,
+    ParseTimestamp {
+        column_name: String,
+        #[snafu(backtrace)]
+        source: common_time::error::Error,
     },
 
     #[snafu(display("Unable to convert statement {} to DataFusion statement", statement))]
@@ -174,12 +173,12 @@ impl ErrorExt for Error {
             | InvalidDatabaseName { .. }
             | ColumnTypeMismatch { .. }
             | InvalidTableName { .. }
-            | InvalidSqlValue { .. }
-            | TimestampOverflow { .. } => StatusCode::InvalidArguments,
+            | InvalidSqlValue { .. } => StatusCode::InvalidArguments,
 
             UnsupportedAlterTableStatement { .. } => StatusCode::InvalidSyntax,
             SerializeColumnDefaultConstraint { source, .. } => source.status_code(),
             ConvertToGrpcDataType { source, .. } => source.status_code(),
+            ParseTimestamp { source, .. } => source.status_code(),
             ConvertToDfStatement { .. } => StatusCode::Internal,
             ConvertSqlValue { .. } | ConvertValue { .. } => StatusCode::Unsupported,
         }
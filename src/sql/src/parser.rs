@@ -16,15 +16,19 @@ use snafu::{ensure, ResultExt};
 use sqlparser::dialect::Dialect;
 use sqlparser::keywords::Keyword;
 use sqlparser::parser::{Parser, ParserError};
-use sqlparser::tokenizer::{Token, TokenWithLocation};
+use sqlparser::tokenizer::{Token, TokenWithLocation, Tokenizer};
 
 use crate::ast::{Expr, ObjectName};
-use crate::error::{self, InvalidDatabaseNameSnafu, InvalidTableNameSnafu, Result, SyntaxSnafu};
-use crate::parsers::tql_parser;
+use crate::error::{
+    self, InvalidDatabaseNameSnafu, InvalidTableNameSnafu, Result, SyntaxSnafu, TokenizerSnafu,
+};
+use crate::parsers::{query_parser, restore_parser, tql_parser};
 use crate::statements::describe::DescribeTable;
 use crate::statements::drop::DropTable;
 use crate::statements::explain::Explain;
-use crate::statements::show::{ShowCreateTable, ShowDatabases, ShowKind, ShowTables};
+use crate::statements::show::{
+    ShowCreateTable, ShowDatabases, ShowKind, ShowProcesslist, ShowTables,
+};
 use crate::statements::statement::Statement;
 
 /// GrepTime SQL parser context, a simple wrapper for Datafusion SQL parser.
@@ -38,9 +42,20 @@ impl<'a> ParserContext<'a> {
     pub fn create_with_dialect(sql: &'a str, dialect: &dyn Dialect) -> Result<Vec<Statement>> {
         let mut stmts: Vec<Statement> = Vec::new();
 
-        let parser = Parser::new(dialect)
-            .try_with_sql(sql)
-            .context(SyntaxSnafu { sql })?;
+        // Only tokenizes the statements here if they may have the clause `FOR SYSTEM_TIME AS
+        // OF`, as the tokens built here have no locations for the errors.
+        let (parser, as_ofs) = if query_parser::may_have_system_time_as_of(sql) {
+            let mut tokens = Tokenizer::new(dialect, sql)
+                .tokenize()
+                .context(TokenizerSnafu { sql })?;
+            let as_ofs = query_parser::take_system_time_as_of(&mut tokens)?;
+            (Parser::new(dialect).with_tokens(tokens), as_ofs)
+        } else {
+            let parser = Parser::new(dialect)
+                .try_with_sql(sql)
+                .context(SyntaxSnafu { sql })?;
+            (parser, Vec::new())
+        };
         let mut parser_ctx = ParserContext { sql, parser };
 
         let mut expecting_statement_delimiter = false;
@@ -57,7 +72,13 @@ impl<'a> ParserContext<'a> {
                 return parser_ctx.unsupported(parser_ctx.peek_token_as_string());
             }
 
-            let statement = parser_ctx.parse_statement()?;
+            let mut statement = parser_ctx.parse_statement()?;
+            if let Some(as_of) = as_ofs.get(stmts.len()).copied().flatten() {
+                let Statement::Query(query) = &mut statement else {
+                    return parser_ctx.unsupported("FOR SYSTEM_TIME AS OF".to_string());
+                };
+                query.as_of = Some(as_of);
+            }
             stmts.push(statement);
             expecting_statement_delimiter = true;
         }
@@ -133,6 +154,13 @@ impl<'a> ParserContext<'a> {
                         self.parse_tql()
                     }
 
+                    Keyword::NoKeyword
+                        if w.value.to_uppercase() == restore_parser::RESTORE
+                            && w.quote_style.is_none() =>
+                    {
+                        self.parse_restore()
+                    }
+
                     // todo(hl) support more statements.
                     _ => self.unsupported(self.peek_token_as_string()),
                 }
@@ -158,19 +186,30 @@ impl<'a> ParserContext<'a> {
             self.parse_show_databases()
         } else if self.matches_keyword(Keyword::TABLES) {
             self.parser.next_token();
-            self.parse_show_tables()
+            self.parse_show_tables(false)
         } else if self.consume_token("CREATE") {
             if self.consume_token("TABLE") {
                 self.parse_show_create_table()
             } else {
                 self.unsupported(self.peek_token_as_string())
             }
+        } else if self.consume_token("PROCESSLIST") {
+            Ok(Statement::ShowProcesslist(ShowProcesslist { full: false }))
+        } else if self.consume_token("FULL") {
+            if self.consume_token("PROCESSLIST") {
+                Ok(Statement::ShowProcesslist(ShowProcesslist { full: true }))
+            } else if self.matches_keyword(Keyword::TABLES) {
+                self.parser.next_token();
+                self.parse_show_tables(true)
+            } else {
+                self.unsupported(self.peek_token_as_string())
+            }
         } else {
             self.unsupported(self.peek_token_as_string())
         }
     }
 
-    /// Parse SHOW CREATE TABLE statement
+    /// Parse SHOW CREATE TABLE statement, optionally followed by `FOR MYSQL`.
     fn parse_show_create_table(&mut self) -> Result<Statement> {
         let table_name =
             self.parser
@@ -186,15 +225,29 @@ impl<'a> ParserContext<'a> {
                 name: table_name.to_string(),
             }
         );
-        Ok(Statement::ShowCreateTable(ShowCreateTable { table_name }))
+        let for_mysql = if self.consume_token("FOR") {
+            if !self.consume_token("MYSQL") {
+                return self.unsupported(self.peek_token_as_string());
+            }
+            true
+        } else {
+            false
+        };
+        Ok(Statement::ShowCreateTable(ShowCreateTable {
+            table_name,
+            for_mysql,
+        }))
     }
 
-    fn parse_show_tables(&mut self) -> Result<Statement> {
+    /// Parses SHOW [FULL] TABLES statement, `full` is whether FULL is present.
+    fn parse_show_tables(&mut self, full: bool) -> Result<Statement> {
         let database = match self.parser.peek_token().token {
             Token::EOF | Token::SemiColon => {
                 return Ok(Statement::ShowTables(ShowTables {
                     kind: ShowKind::All,
                     database: None,
+                    full,
+                    with_comment: false,
                 }));
             }
 
@@ -249,12 +302,28 @@ impl<'a> ParserContext<'a> {
                         }
                     })?)
                 }
+                Keyword::WITH => ShowKind::All,
                 _ => return self.unsupported(self.peek_token_as_string()),
             },
             _ => return self.unsupported(self.peek_token_as_string()),
         };
 
-        Ok(Statement::ShowTables(ShowTables { kind, database }))
+        // SHOW TABLES ... [WITH COMMENT]
+        let with_comment = if self.consume_token("WITH") {
+            if !self.consume_token("COMMENT") {
+                return self.unsupported(self.peek_token_as_string());
+            }
+            true
+        } else {
+            false
+        };
+
+        Ok(Statement::ShowTables(ShowTables {
+            kind,
+            database,
+            full,
+            with_comment,
+        }))
     }
 
     /// Parses DESCRIBE statements
@@ -286,6 +355,14 @@ impl<'a> ParserContext<'a> {
     }
 
     fn parse_explain(&mut self) -> Result<Statement> {
+        // COPY is not a sqlparser statement, so EXPLAIN COPY is parsed here.
+        if let Token::Word(w) = self.parser.peek_token().token {
+            if w.keyword == Keyword::COPY {
+                self.parser.next_token();
+                return Ok(Statement::ExplainCopy(self.parse_copy_table()?));
+            }
+        }
+
         let explain_statement =
             self.parser
                 .parse_explain(false)
@@ -396,6 +473,7 @@ mod tests {
     use sqlparser::dialect::GenericDialect;
 
     use super::*;
+    use crate::statements::copy::CopyTable;
     use crate::statements::create::CreateTable;
     use crate::statements::sql_data_type_to_concrete_data_type;
 
@@ -463,6 +541,8 @@ mod tests {
             Statement::ShowTables(ShowTables {
                 kind: ShowKind::All,
                 database: None,
+                full: false,
+                with_comment: false,
             })
         );
     }
@@ -482,6 +562,8 @@ mod tests {
                     quote_style: None,
                 }),
                 database: None,
+                full: false,
+                with_comment: false,
             })
         );
 
@@ -498,6 +580,8 @@ mod tests {
                     quote_style: None,
                 }),
                 database: Some(_),
+                full: false,
+                with_comment: false,
             })
         );
     }
@@ -514,6 +598,8 @@ mod tests {
             Statement::ShowTables(ShowTables {
                 kind: ShowKind::Where(sqlparser::ast::Expr::Like { .. }),
                 database: None,
+                full: false,
+                with_comment: false,
             })
         );
 
@@ -527,8 +613,54 @@ mod tests {
             Statement::ShowTables(ShowTables {
                 kind: ShowKind::Where(sqlparser::ast::Expr::Like { .. }),
                 database: Some(_),
+                full: false,
+                with_comment: false,
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_show_full_tables() {
+        let sql = "SHOW FULL TABLES";
+        let stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        assert_matches!(
+            &stmts[0],
+            Statement::ShowTables(ShowTables {
+                kind: ShowKind::All,
+                database: None,
+                full: true,
+                with_comment: false,
+            })
+        );
+
+        let sql = "SHOW FULL TABLES IN test_db LIKE test_table WITH COMMENT";
+        let stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        assert_matches!(
+            &stmts[0],
+            Statement::ShowTables(ShowTables {
+                kind: ShowKind::Like(_),
+                database: Some(_),
+                full: true,
+                with_comment: true,
             })
         );
+
+        let sql = "SHOW FULL TABLES FROM test_db WITH COMMENT";
+        let stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_matches!(
+            &stmts[0],
+            Statement::ShowTables(ShowTables {
+                kind: ShowKind::All,
+                database: Some(_),
+                full: true,
+                with_comment: true,
+            })
+        );
+
+        let sql = "SHOW FULL TABLES WITH OPTIONS";
+        assert!(ParserContext::create_with_dialect(sql, &GenericDialect {}).is_err());
     }
 
     #[test]
@@ -586,6 +718,22 @@ mod tests {
         assert_eq!(stmts[0], Statement::Explain(explain))
     }
 
+    #[test]
+    pub fn test_explain_copy() {
+        let sql = "EXPLAIN COPY foo FROM 's3://bucket/dir/' WITH (PATTERN = '.*parquet')";
+        let stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        let Statement::ExplainCopy(CopyTable::From(copy)) = &stmts[0] else {
+            unreachable!("{:?}", stmts[0]);
+        };
+        assert_eq!("foo", copy.table_name.to_string());
+        assert_eq!("s3://bucket/dir/", copy.location);
+
+        let sql = "EXPLAIN INSERT INTO foo VALUES (1)";
+        let stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_matches!(&stmts[0], Statement::Explain(_));
+    }
+
     #[test]
     pub fn test_drop_table() {
         let sql = "DROP TABLE foo";
@@ -18,4 +18,5 @@ pub(crate) mod create_parser;
 pub(crate) mod delete_parser;
 pub(crate) mod insert_parser;
 pub(crate) mod query_parser;
+pub(crate) mod restore_parser;
 pub(crate) mod tql_parser;
@@ -12,29 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
+use sqlparser::ast::Ident;
 use sqlparser::keywords::Keyword;
-use sqlparser::parser::ParserError;
+use sqlparser::parser::{Parser, ParserError};
 
 use crate::error::{self, Result};
 use crate::parser::ParserContext;
+use crate::parsers::create_parser::validate_repartitions;
 use crate::statements::alter::{AlterTable, AlterTableOperation};
+use crate::statements::create::ENCODING;
 use crate::statements::statement::Statement;
 
+const REPARTITION: &str = "REPARTITION";
+
 impl<'a> ParserContext<'a> {
     pub(crate) fn parse_alter(&mut self) -> Result<Statement> {
-        let alter_table = self
-            .parse_alter_table()
-            .context(error::SyntaxSnafu { sql: self.sql })?;
+        let alter_table = self.parse_alter_table()?;
         Ok(Statement::Alter(alter_table))
     }
 
-    fn parse_alter_table(&mut self) -> std::result::Result<AlterTable, ParserError> {
-        let parser = &mut self.parser;
-        parser.expect_keywords(&[Keyword::ALTER, Keyword::TABLE])?;
+    fn parse_alter_table(&mut self) -> Result<AlterTable> {
+        let table_name = self
+            .parser
+            .expect_keywords(&[Keyword::ALTER, Keyword::TABLE])
+            .and_then(|_| self.parser.parse_object_name())
+            .context(error::SyntaxSnafu { sql: self.sql })?;
 
-        let table_name = parser.parse_object_name()?;
+        let alter_operation = if self.consume_token(REPARTITION) {
+            let partitions = self.parse_partitions()?.context(error::InvalidSqlSnafu {
+                msg: "expect PARTITION BY after ALTER TABLE REPARTITION",
+            })?;
+            validate_repartitions(&partitions)?;
+            AlterTableOperation::Repartition { partitions }
+        } else {
+            self.parse_alter_operation()
+                .context(error::SyntaxSnafu { sql: self.sql })?
+        };
+        Ok(AlterTable::new(table_name, alter_operation))
+    }
 
+    fn parse_alter_operation(&mut self) -> std::result::Result<AlterTableOperation, ParserError> {
+        let parser = &mut self.parser;
         let alter_operation = if parser.parse_keyword(Keyword::ADD) {
             if let Some(constraint) = parser.parse_optional_table_constraint()? {
                 AlterTableOperation::AddConstraint(constraint)
@@ -64,13 +83,51 @@ impl<'a> ParserContext<'a> {
                 }
             };
             AlterTableOperation::RenameTable { new_table_name }
+        } else if parser.parse_keyword(Keyword::ALTER) {
+            let _ = parser.parse_keyword(Keyword::COLUMN);
+            let name = parser.parse_identifier()?;
+            parser.expect_keyword(Keyword::SET)?;
+            Self::parse_set_column_options(parser, name)?
         } else {
             return Err(ParserError::ParserError(format!(
-                "expect keyword ADD or DROP or RENAME after ALTER TABLE, found {}",
+                "expect keyword ADD or DROP or RENAME or ALTER or REPARTITION after ALTER TABLE, \
+                 found {}",
                 parser.peek_token()
             )));
         };
-        Ok(AlterTable::new(table_name, alter_operation))
+        Ok(alter_operation)
+    }
+
+    /// Parses the storage options set to the column `name`, at least one option is required.
+    fn parse_set_column_options(
+        parser: &mut Parser<'a>,
+        name: Ident,
+    ) -> std::result::Result<AlterTableOperation, ParserError> {
+        let mut encoding = None;
+        let mut compression = None;
+        while let Some((option, value)) = Self::parse_optional_storage_option(parser)? {
+            let prev = if option == ENCODING {
+                encoding.replace(value)
+            } else {
+                compression.replace(value)
+            };
+            if prev.is_some() {
+                return Err(ParserError::ParserError(format!(
+                    "duplicate option {option} of column {name}"
+                )));
+            }
+        }
+        if encoding.is_none() && compression.is_none() {
+            return Err(ParserError::ParserError(format!(
+                "expect ENCODING or COMPRESSION after SET, found {}",
+                parser.peek_token()
+            )));
+        }
+        Ok(AlterTableOperation::SetColumnOptions {
+            name,
+            encoding,
+            compression,
+        })
     }
 }
 
@@ -148,9 +205,9 @@ mod tests {
     fn test_parse_alter_rename_table() {
         let sql = "ALTER TABLE test_table table_t";
         let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
-        assert!(result
-            .to_string()
-            .contains("expect keyword ADD or DROP or RENAME after ALTER TABLE"));
+        assert!(result.to_string().contains(
+            "expect keyword ADD or DROP or RENAME or ALTER or REPARTITION after ALTER TABLE"
+        ));
 
         let sql = "ALTER TABLE test_table RENAME table_t";
         let mut result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
@@ -174,4 +231,93 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_parse_alter_set_column_options() {
+        let sql = "ALTER TABLE my_metric_1 ALTER COLUMN host SET ENCODING('dictionary') \
+                   COMPRESSION 'zstd'";
+        let mut result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(1, result.len());
+        let Statement::Alter(alter_table) = result.remove(0) else { unreachable!() };
+        assert_eq!("my_metric_1", alter_table.table_name().0[0].value);
+        assert_eq!(
+            &AlterTableOperation::SetColumnOptions {
+                name: "host".into(),
+                encoding: Some("dictionary".to_string()),
+                compression: Some("zstd".to_string()),
+            },
+            alter_table.alter_operation()
+        );
+
+        let sql = "ALTER TABLE my_metric_1 ALTER cpu SET compression('snappy')";
+        let mut result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        let Statement::Alter(alter_table) = result.remove(0) else { unreachable!() };
+        assert_eq!(
+            &AlterTableOperation::SetColumnOptions {
+                name: "cpu".into(),
+                encoding: None,
+                compression: Some("snappy".to_string()),
+            },
+            alter_table.alter_operation()
+        );
+
+        let sql = "ALTER TABLE my_metric_1 ALTER cpu SET";
+        let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
+        assert!(result
+            .to_string()
+            .contains("expect ENCODING or COMPRESSION after SET"));
+
+        let sql = "ALTER TABLE my_metric_1 ALTER cpu SET ENCODING 'plain' ENCODING 'dictionary'";
+        let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
+        assert!(result
+            .to_string()
+            .contains("duplicate option ENCODING of column cpu"));
+    }
+
+    #[test]
+    fn test_parse_alter_repartition() {
+        let sql = "ALTER TABLE test_table REPARTITION";
+        let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
+        assert!(result
+            .to_string()
+            .contains("expect PARTITION BY after ALTER TABLE REPARTITION"));
+
+        let sql = r"
+ALTER TABLE test_table REPARTITION PARTITION BY RANGE COLUMNS (a) (
+  PARTITION r0 VALUES LESS THAN (10),
+  PARTITION r1 VALUES LESS THAN (20),
+)";
+        let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
+        assert!(result
+            .to_string()
+            .contains("Please provide an extra partition that is bounded by 'MAXVALUE'."));
+
+        let sql = r"
+ALTER TABLE test_table REPARTITION PARTITION BY RANGE COLUMNS (a) (
+  PARTITION r0 VALUES LESS THAN (10),
+  PARTITION r1 VALUES LESS THAN (MAXVALUE),
+)";
+        let mut result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(1, result.len());
+
+        let statement = result.remove(0);
+        match statement {
+            Statement::Alter(alter_table) => {
+                assert_eq!("test_table", alter_table.table_name().0[0].value);
+                match alter_table.alter_operation() {
+                    AlterTableOperation::Repartition { partitions } => {
+                        assert_eq!("a", partitions.column_list[0].value);
+                        let names = partitions
+                            .entries
+                            .iter()
+                            .map(|e| e.name.value.as_str())
+                            .collect::<Vec<_>>();
+                        assert_eq!(vec!["r0", "r1"], names);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
 }
@@ -12,25 +12,78 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use snafu::ResultExt;
 use sqlparser::ast::ObjectName;
 use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::Token;
 
 use crate::error::{self, Result};
 use crate::parser::ParserContext;
-use crate::statements::copy::{CopyTable, CopyTableArgument};
+use crate::statements::copy::{CopyQueryTo, CopyTable, CopyTableArgument};
+use crate::statements::query::Query;
 use crate::statements::statement::Statement;
 use crate::util::parse_option_string;
 
 // COPY tbl TO 'output.parquet';
+// COPY (SELECT ...) TO 'output.parquet';
 impl<'a> ParserContext<'a> {
     pub(crate) fn parse_copy(&mut self) -> Result<Statement> {
         self.parser.next_token();
+        if self.parser.consume_token(&Token::LParen) {
+            return Ok(Statement::CopyQueryTo(self.parse_copy_query_to()?));
+        }
         let copy_table = self.parse_copy_table()?;
         Ok(Statement::Copy(copy_table))
     }
 
-    fn parse_copy_table(&mut self) -> Result<CopyTable> {
+    fn parse_copy_query_to(&mut self) -> Result<CopyQueryTo> {
+        let query = self
+            .parser
+            .parse_query()
+            .context(error::SyntaxSnafu { sql: self.sql })?;
+        self.parser
+            .expect_token(&Token::RParen)
+            .context(error::SyntaxSnafu { sql: self.sql })?;
+        self.parser
+            .expect_keyword(Keyword::TO)
+            .context(error::SyntaxSnafu { sql: self.sql })?;
+
+        let location =
+            self.parser
+                .parse_literal_string()
+                .with_context(|_| error::UnexpectedSnafu {
+                    sql: self.sql,
+                    expected: "a uri",
+                    actual: self.peek_token_as_string(),
+                })?;
+        let with = self.parse_copy_options(Keyword::WITH)?;
+        let connection = self.parse_copy_options(Keyword::CONNECTION)?;
+
+        Ok(CopyQueryTo {
+            query: Box::new(Query::try_from(query)?),
+            with,
+            connection,
+            location,
+        })
+    }
+
+    fn parse_copy_options(&mut self, keyword: Keyword) -> Result<HashMap<String, String>> {
+        let options = self
+            .parser
+            .parse_options(keyword)
+            .context(error::SyntaxSnafu { sql: self.sql })?;
+
+        Ok(options
+            .into_iter()
+            .filter_map(|option| {
+                parse_option_string(option.value).map(|v| (option.name.to_string(), v))
+            })
+            .collect())
+    }
+
+    pub(crate) fn parse_copy_table(&mut self) -> Result<CopyTable> {
         let table_name =
             self.parser
                 .parse_object_name()
@@ -303,4 +356,27 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_parse_copy_query_to() {
+        let sql = "COPY (SELECT host, avg(cpu) FROM demo GROUP BY host) TO 'query.parquet' WITH (FORMAT = 'parquet') CONNECTION (FOO='Bar')";
+        let mut result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(1, result.len());
+
+        match result.remove(0) {
+            Statement::CopyQueryTo(copy) => {
+                assert_eq!(
+                    "SELECT host, avg(cpu) FROM demo GROUP BY host",
+                    copy.query.inner.to_string()
+                );
+                assert_eq!("query.parquet", copy.location);
+                assert_eq!(Some(&"parquet".to_string()), copy.with.get("FORMAT"));
+                assert_eq!(Some(&"Bar".to_string()), copy.connection.get("FOO"));
+            }
+            _ => unreachable!(),
+        }
+
+        let sql = "COPY (SELECT 1 TO 'query.parquet'";
+        assert!(ParserContext::create_with_dialect(sql, &GenericDialect {}).is_err());
+    }
 }
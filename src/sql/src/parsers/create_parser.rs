@@ -31,7 +31,8 @@ use crate::error::{
 };
 use crate::parser::ParserContext;
 use crate::statements::create::{
-    CreateDatabase, CreateExternalTable, CreateTable, PartitionEntry, Partitions, TIME_INDEX,
+    storage_column_option, CreateDatabase, CreateExternalTable, CreateTable, PartitionEntry,
+    Partitions, COMPRESSION, ENCODING, TIME_INDEX,
 };
 use crate::statements::statement::Statement;
 use crate::statements::{sql_data_type_to_concrete_data_type, sql_value_to_value};
@@ -118,9 +119,15 @@ impl<'a> ParserContext<'a> {
                 actual: self.peek_token_as_string(),
             })?;
 
+        let options = self
+            .parser
+            .parse_options(Keyword::WITH)
+            .context(error::SyntaxSnafu { sql: self.sql })?;
+
         Ok(Statement::CreateDatabase(CreateDatabase {
             name: database_name,
             if_not_exists,
+            options,
         }))
     }
 
@@ -166,7 +173,7 @@ impl<'a> ParserContext<'a> {
 
     // "PARTITION BY ..." syntax:
     // https://dev.mysql.com/doc/refman/8.0/en/partitioning-columns-range.html
-    fn parse_partitions(&mut self) -> Result<Option<Partitions>> {
+    pub(crate) fn parse_partitions(&mut self) -> Result<Option<Partitions>> {
         if !self.parser.parse_keyword(Keyword::PARTITION) {
             return Ok(None);
         }
@@ -470,11 +477,53 @@ impl<'a> ParserContext<'a> {
                     keyword: Keyword::INDEX,
                 }),
             ])))
+        } else if let Some((name, value)) = Self::parse_optional_storage_option(parser)? {
+            Ok(Some(storage_column_option(name, &value)))
         } else {
             Ok(None)
         }
     }
 
+    /// Parses a storage column option, [ENCODING] or [COMPRESSION], returns its name and value
+    /// if the next token is one.
+    pub(crate) fn parse_optional_storage_option(
+        parser: &mut Parser<'a>,
+    ) -> std::result::Result<Option<(&'static str, String)>, ParserError> {
+        let Some(name) = Self::parse_optional_storage_option_name(parser) else {
+            return Ok(None);
+        };
+        // Both `ENCODING('dictionary')` and `ENCODING 'dictionary'` are accepted.
+        let parenthesized = parser.consume_token(&Token::LParen);
+        let value = match parser.next_token() {
+            TokenWithLocation {
+                token: Token::SingleQuotedString(value),
+                ..
+            } => value,
+            unexpected => return parser.expected("string", unexpected),
+        };
+        if parenthesized {
+            parser.expect_token(&Token::RParen)?;
+        }
+        Ok(Some((name, value)))
+    }
+
+    /// Consumes the name of a storage column option, [ENCODING] or [COMPRESSION], if the next
+    /// token is one.
+    fn parse_optional_storage_option_name(parser: &mut Parser<'a>) -> Option<&'static str> {
+        let word = match parser.peek_token() {
+            TokenWithLocation {
+                token: Token::Word(word),
+                ..
+            } if word.quote_style.is_none() => word,
+            _ => return None,
+        };
+        let name = [ENCODING, COMPRESSION]
+            .into_iter()
+            .find(|name| word.value.eq_ignore_ascii_case(name))?;
+        let _ = parser.next_token();
+        Some(name)
+    }
+
     fn parse_optional_table_constraint(&mut self) -> Result<Option<TableConstraint>> {
         let name = if self.parser.parse_keyword(Keyword::CONSTRAINT) {
             Some(
@@ -643,6 +692,28 @@ fn validate_partitions(columns: &[ColumnDef], partitions: &Partitions) -> Result
     Ok(())
 }
 
+/// Validates the partitions of `ALTER TABLE ... REPARTITION`, whose columns are checked against
+/// the table, which is unknown to the parser, when the statement is executed.
+pub(crate) fn validate_repartitions(partitions: &Partitions) -> Result<()> {
+    ensure_partition_names_no_duplicate(partitions)?;
+
+    for entry in partitions.entries.iter() {
+        ensure!(
+            entry.value_list.len() == partitions.column_list.len(),
+            error::InvalidSqlSnafu {
+                msg: "Partition value list does not match column list.",
+            }
+        );
+    }
+
+    let value_lists = partitions
+        .entries
+        .iter()
+        .map(|x| &x.value_list)
+        .collect::<Vec<_>>();
+    ensure_value_lists_bounded_by_maxvalue(value_lists)
+}
+
 /// Ensure that partition ranges fully cover all values.
 // Simply check the last partition is bounded by "MAXVALUE"s.
 // MySQL does not have this restriction. However, I think we'd better have it because:
@@ -787,6 +858,8 @@ mod tests {
     use sqlparser::dialect::GenericDialect;
 
     use super::*;
+    use crate::statements::create::as_storage_column_option;
+    use crate::util::to_lowercase_options_map;
 
     #[test]
     fn test_parse_create_external_table() {
@@ -914,6 +987,18 @@ mod tests {
             Statement::CreateDatabase(c) => {
                 assert_eq!(c.name.to_string(), "prometheus");
                 assert!(c.if_not_exists);
+                assert!(c.options.is_empty());
+            }
+            _ => unreachable!(),
+        }
+
+        let sql = "create database prometheus with (ttl = '7d', write_buffer_size = '1MB')";
+        let stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        match &stmts[0] {
+            Statement::CreateDatabase(c) => {
+                let options = to_lowercase_options_map(&c.options);
+                assert_eq!("7d", options["ttl"]);
+                assert_eq!("1MB", options["write_buffer_size"]);
             }
             _ => unreachable!(),
         }
@@ -1427,6 +1512,49 @@ ENGINE=mito";
         }
     }
 
+    #[test]
+    fn test_parse_create_table_with_storage_options() {
+        let sql = r"create table demo(
+                             host string encoding('dictionary') compression('zstd'),
+                             ts timestamp time index,
+                             cpu float64 Encoding 'byte_stream_split',
+                             PRIMARY KEY(host)) engine=mito";
+        let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        let Statement::CreateTable(c) = &result[0] else { unreachable!() };
+        let options = |column: &ColumnDef| {
+            column
+                .options
+                .iter()
+                .filter_map(|o| as_storage_column_option(&o.option))
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![
+                (ENCODING.to_string(), "dictionary".to_string()),
+                (COMPRESSION.to_string(), "zstd".to_string())
+            ],
+            options(&c.columns[0])
+        );
+        assert_eq!(
+            vec![(ENCODING.to_string(), "byte_stream_split".to_string())],
+            options(&c.columns[2])
+        );
+        assert_eq!(
+            "host STRING ENCODING 'dictionary' COMPRESSION 'zstd'",
+            c.columns[0].to_string()
+        );
+
+        // The displayed options can be parsed again.
+        let sql = c.to_string();
+        let result = ParserContext::create_with_dialect(&sql, &GenericDialect {}).unwrap();
+        let Statement::CreateTable(reparsed) = &result[0] else { unreachable!() };
+        assert_eq!(c.columns, reparsed.columns);
+
+        let sql = "create table demo(host string encoding(dictionary), ts timestamp time index)";
+        assert!(ParserContext::create_with_dialect(sql, &GenericDialect {}).is_err());
+    }
+
     #[test]
     fn test_invalid_index_keys() {
         let sql = r"create table demo(
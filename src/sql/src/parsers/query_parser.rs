@@ -12,13 +12,94 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
 use snafu::prelude::*;
+use sqlparser::tokenizer::Token;
 
 use crate::error::{self, Result};
 use crate::parser::ParserContext;
 use crate::statements::query::Query;
 use crate::statements::statement::Statement;
 
+/// Keywords of the clause `FOR SYSTEM_TIME AS OF <timestamp>` at the end of a query.
+const SYSTEM_TIME_AS_OF: [&str; 4] = ["FOR", "SYSTEM_TIME", "AS", "OF"];
+
+/// Returns whether `sql` may have the clause `FOR SYSTEM_TIME AS OF`, which is cheaper than
+/// tokenizing it.
+pub(crate) fn may_have_system_time_as_of(sql: &str) -> bool {
+    sql.to_ascii_uppercase().contains(SYSTEM_TIME_AS_OF[1])
+}
+
+/// Removes the clauses `FOR SYSTEM_TIME AS OF <timestamp>` at the end of the statements in
+/// `tokens`, which sqlparser doesn't know. Returns the time in millis of the clause of each
+/// non-empty statement, where `<timestamp>` is a timestamp string or epoch millis.
+pub(crate) fn take_system_time_as_of(tokens: &mut Vec<Token>) -> Result<Vec<Option<i64>>> {
+    let mut as_ofs = Vec::new();
+    let mut kept = Vec::with_capacity(tokens.len());
+    let mut statements = tokens.split(|token| *token == Token::SemiColon).peekable();
+    while let Some(statement) = statements.next() {
+        // Indices of the tokens that are not whitespaces.
+        let indices = statement
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| !matches!(token, Token::Whitespace(_)))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let clause_start = indices.len().checked_sub(SYSTEM_TIME_AS_OF.len() + 1);
+        let as_of = match clause_start {
+            Some(start) if is_system_time_as_of(statement, &indices[start..]) => {
+                let value = match &statement[indices[indices.len() - 1]] {
+                    Token::SingleQuotedString(value) | Token::Number(value, _) => value,
+                    _ => unreachable!("checked by is_system_time_as_of"),
+                };
+                let as_of =
+                    Timestamp::parse_in_unit(value, TimeUnit::Millisecond).map_err(|e| {
+                        error::ParseSqlValueSnafu {
+                            msg: format!("invalid timestamp of FOR SYSTEM_TIME AS OF: {e}"),
+                        }
+                        .build()
+                    })?;
+                kept.extend_from_slice(&statement[..indices[start]]);
+                Some(as_of.value())
+            }
+            _ => {
+                kept.extend_from_slice(statement);
+                None
+            }
+        };
+        if !indices.is_empty() {
+            as_ofs.push(as_of);
+        }
+        if statements.peek().is_some() {
+            kept.push(Token::SemiColon);
+        }
+    }
+    *tokens = kept;
+    Ok(as_ofs)
+}
+
+/// Returns whether the tokens of `statement` at `indices` are the clause
+/// `FOR SYSTEM_TIME AS OF <timestamp>`.
+fn is_system_time_as_of(statement: &[Token], indices: &[usize]) -> bool {
+    let (value, keywords) = indices.split_last().unwrap();
+    let keywords_match =
+        keywords
+            .iter()
+            .zip(SYSTEM_TIME_AS_OF)
+            .all(|(i, keyword)| match &statement[*i] {
+                Token::Word(word) => {
+                    word.quote_style.is_none() && word.value.eq_ignore_ascii_case(keyword)
+                }
+                _ => false,
+            });
+    keywords_match
+        && matches!(
+            statement[*value],
+            Token::SingleQuotedString(_) | Token::Number(_, _)
+        )
+}
+
 impl<'a> ParserContext<'a> {
     /// Parses select and it's variants.
     pub(crate) fn parse_query(&mut self) -> Result<Statement> {
@@ -36,6 +117,7 @@ mod tests {
     use sqlparser::dialect::GenericDialect;
 
     use crate::parser::ParserContext;
+    use crate::statements::statement::Statement;
 
     #[test]
     pub fn test_parse_query() {
@@ -47,6 +129,33 @@ mod tests {
         let _ = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
     }
 
+    #[test]
+    pub fn test_parse_query_as_of() {
+        let sql = "SELECT * FROM t FOR SYSTEM_TIME AS OF '2023-04-12T10:00:00Z'; \
+            ;select * from (select 1) for system_time as of 1681293600123;\
+            SELECT * FROM t";
+        let stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        let as_ofs = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Statement::Query(query) => query.as_of,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some(1681293600000), Some(1681293600123), None], as_ofs);
+        let Statement::Query(query) = &stmts[0] else { unreachable!() };
+        assert_eq!("SELECT * FROM t", query.inner.to_string());
+
+        let sql = "SELECT * FROM t FOR SYSTEM_TIME AS OF 'yesterday'";
+        let err = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
+        assert!(err.to_string().contains("FOR SYSTEM_TIME AS OF"), "{err}");
+
+        // Only queries read snapshots.
+        let sql = "DELETE FROM t FOR SYSTEM_TIME AS OF 1681293600123";
+        let err = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
+        assert!(err.to_string().contains("FOR SYSTEM_TIME AS OF"), "{err}");
+    }
+
     #[test]
     pub fn test_parse_invalid_query() {
         let sql = "SELECT * FROM table_1 WHERE";
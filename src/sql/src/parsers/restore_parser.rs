@@ -0,0 +1,112 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snafu::{ensure, ResultExt};
+use sqlparser::keywords::Keyword;
+
+use crate::error::{self, InvalidTableNameSnafu, Result};
+use crate::parser::ParserContext;
+use crate::statements::restore::RestoreTable;
+use crate::statements::statement::Statement;
+
+pub const RESTORE: &str = "RESTORE";
+
+/// RESTORE statement parser implementation
+impl<'a> ParserContext<'a> {
+    /// Parses `RESTORE TABLE <table_name> [AS <new_table_name>]`.
+    pub(crate) fn parse_restore(&mut self) -> Result<Statement> {
+        self.parser.next_token();
+        if !self.matches_keyword(Keyword::TABLE) {
+            return self.unsupported(self.peek_token_as_string());
+        }
+        self.parser.next_token();
+
+        let table_ident =
+            self.parser
+                .parse_object_name()
+                .with_context(|_| error::UnexpectedSnafu {
+                    sql: self.sql,
+                    expected: "a table name",
+                    actual: self.peek_token_as_string(),
+                })?;
+        ensure!(
+            !table_ident.0.is_empty(),
+            InvalidTableNameSnafu {
+                name: table_ident.to_string()
+            }
+        );
+
+        let new_table_name = if self.parser.parse_keyword(Keyword::AS) {
+            let new_table_name =
+                self.parser
+                    .parse_identifier()
+                    .with_context(|_| error::UnexpectedSnafu {
+                        sql: self.sql,
+                        expected: "a new table name",
+                        actual: self.peek_token_as_string(),
+                    })?;
+            Some(new_table_name.value)
+        } else {
+            None
+        };
+
+        Ok(Statement::RestoreTable(RestoreTable::new(
+            table_ident,
+            new_table_name,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlparser::ast::{Ident, ObjectName};
+    use sqlparser::dialect::GenericDialect;
+
+    use super::*;
+
+    #[test]
+    pub fn test_parse_restore_table() {
+        let sql = "RESTORE TABLE my_schema.foo";
+        let mut stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(
+            stmts.pop().unwrap(),
+            Statement::RestoreTable(RestoreTable::new(
+                ObjectName(vec![Ident::new("my_schema"), Ident::new("foo")]),
+                None
+            ))
+        );
+
+        let sql = "restore table foo as bar";
+        let mut stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(
+            stmts.pop().unwrap(),
+            Statement::RestoreTable(RestoreTable::new(
+                ObjectName(vec![Ident::new("foo")]),
+                Some("bar".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    pub fn test_parse_invalid_restore_table() {
+        for sql in [
+            "RESTORE foo",
+            "RESTORE TABLE foo AS",
+            "RESTORE TABLE foo AS my_schema.bar",
+        ] {
+            let result = ParserContext::create_with_dialect(sql, &GenericDialect {});
+            assert!(result.is_err(), "{sql}: {result:?}");
+        }
+    }
+}
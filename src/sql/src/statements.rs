@@ -21,6 +21,7 @@ pub mod drop;
 pub mod explain;
 pub mod insert;
 pub mod query;
+pub mod restore;
 pub mod show;
 pub mod statement;
 pub mod tql;
@@ -42,9 +43,10 @@ use crate::ast::{
 };
 use crate::error::{
     self, ColumnTypeMismatchSnafu, ConvertSqlValueSnafu, ConvertToGrpcDataTypeSnafu,
-    ConvertValueSnafu, InvalidSqlValueSnafu, ParseSqlValueSnafu, Result,
-    SerializeColumnDefaultConstraintSnafu, TimestampOverflowSnafu, UnsupportedDefaultValueSnafu,
+    ConvertValueSnafu, InvalidSqlValueSnafu, ParseSqlValueSnafu, ParseTimestampSnafu, Result,
+    SerializeColumnDefaultConstraintSnafu, UnsupportedDefaultValueSnafu,
 };
+use crate::statements::create::{as_storage_column_option, ENCODING};
 
 fn parse_string_to_value(
     column_name: &str,
@@ -52,7 +54,7 @@ fn parse_string_to_value(
     data_type: &ConcreteDataType,
 ) -> Result<Value> {
     ensure!(
-        data_type.is_stringifiable(),
+        data_type.is_stringifiable() || data_type.is_float(),
         ColumnTypeMismatchSnafu {
             column_name,
             expect: data_type.clone(),
@@ -62,6 +64,26 @@ fn parse_string_to_value(
 
     match data_type {
         ConcreteDataType::String(_) => Ok(Value::String(s.into())),
+        // Float columns only accept strings of non-finite values, like 'NaN', 'Infinity' and
+        // '-inf', which have no number literals.
+        ConcreteDataType::Float32(_) => match s.parse::<f32>() {
+            Ok(v) if !v.is_finite() => Ok(Value::from(v)),
+            _ => ColumnTypeMismatchSnafu {
+                column_name,
+                expect: data_type.clone(),
+                actual: ConcreteDataType::string_datatype(),
+            }
+            .fail(),
+        },
+        ConcreteDataType::Float64(_) => match s.parse::<f64>() {
+            Ok(v) if !v.is_finite() => Ok(Value::from(v)),
+            _ => ColumnTypeMismatchSnafu {
+                column_name,
+                expect: data_type.clone(),
+                actual: ConcreteDataType::string_datatype(),
+            }
+            .fail(),
+        },
         ConcreteDataType::Date(_) => {
             if let Ok(date) = common_time::date::Date::from_str(&s) {
                 Ok(Value::Date(date))
@@ -83,19 +105,9 @@ fn parse_string_to_value(
             }
         }
         ConcreteDataType::Timestamp(t) => {
-            if let Ok(ts) = Timestamp::from_str(&s) {
-                Ok(Value::Timestamp(ts.convert_to(t.unit()).context(
-                    TimestampOverflowSnafu {
-                        timestamp: ts,
-                        target_unit: t.unit(),
-                    },
-                )?))
-            } else {
-                ParseSqlValueSnafu {
-                    msg: format!("Failed to parse {s} to Timestamp value"),
-                }
-                .fail()
-            }
+            let ts = Timestamp::parse_in_unit(&s, t.unit())
+                .context(ParseTimestampSnafu { column_name })?;
+            Ok(Value::Timestamp(ts))
         }
         _ => {
             unreachable!()
@@ -300,6 +312,22 @@ pub fn column_def_to_schema(column_def: &ColumnDef, is_time_index: bool) -> Resu
             .insert(COMMENT_KEY.to_string(), c.to_string());
     }
 
+    for option in &column_def.options {
+        let Some((name, value)) = as_storage_column_option(&option.option) else { continue };
+        column_schema = if name == ENCODING {
+            column_schema.with_encoding(value)
+        } else {
+            column_schema.with_compression(value)
+        }
+        .map_err(|e| {
+            error::InvalidColumnOptionSnafu {
+                name: &column_def.name.value,
+                msg: e.to_string(),
+            }
+            .build()
+        })?;
+    }
+
     Ok(column_schema)
 }
 
@@ -528,6 +556,18 @@ mod tests {
         let v = sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val).unwrap();
         assert_eq!(Value::Binary(Bytes::from(b"Hello world!".as_slice())), v);
 
+        let sql_val = SqlValue::SingleQuotedString("NaN".to_string());
+        let v = sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val).unwrap();
+        assert!(matches!(v, Value::Float64(v) if v.is_nan()));
+
+        let sql_val = SqlValue::SingleQuotedString("-Infinity".to_string());
+        let v = sql_value_to_value("a", &ConcreteDataType::float32_datatype(), &sql_val).unwrap();
+        assert_eq!(Value::Float32(OrderedFloat(f32::NEG_INFINITY)), v);
+
+        let sql_val = SqlValue::SingleQuotedString("1.5".to_string());
+        let v = sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val);
+        assert!(v.is_err());
+
         let sql_val = SqlValue::HexStringLiteral("9AF".to_string());
         let v = sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val);
         assert!(v.is_err());
@@ -659,6 +699,82 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn test_parse_timestamp_forms() {
+        let units = [
+            TimeUnit::Second,
+            TimeUnit::Millisecond,
+            TimeUnit::Microsecond,
+            TimeUnit::Nanosecond,
+        ];
+        let cases = [
+            (
+                "2023-04-01T10:00:00Z",
+                [
+                    1680343200,
+                    1680343200000,
+                    1680343200000000,
+                    1680343200000000000,
+                ],
+            ),
+            (
+                "2023-04-01T18:00:00.5+08:00",
+                [
+                    1680343200,
+                    1680343200500,
+                    1680343200500000,
+                    1680343200500000000,
+                ],
+            ),
+            (
+                "2023-04-01 10:00:00.123456789Z",
+                [
+                    1680343200,
+                    1680343200123,
+                    1680343200123456,
+                    1680343200123456789,
+                ],
+            ),
+            // Integers are epochs in the unit of the column.
+            (
+                "1680000000",
+                [1680000000, 1680000000, 1680000000, 1680000000],
+            ),
+        ];
+        for (s, expects) in cases {
+            for (unit, expect) in units.iter().zip(expects) {
+                let value = parse_string_to_value(
+                    "ts",
+                    s.to_string(),
+                    &ConcreteDataType::timestamp_datatype(*unit),
+                )
+                .unwrap();
+                assert_eq!(Value::Timestamp(Timestamp::new(expect, *unit)), value);
+            }
+        }
+
+        // Out of the range of nanosecond timestamps, but fine in coarser units.
+        let data_type = ConcreteDataType::timestamp_datatype(TimeUnit::Second);
+        assert_eq!(
+            Value::Timestamp(Timestamp::new_second(10413792000)),
+            parse_string_to_value("ts", "2300-01-01T00:00:00Z".to_string(), &data_type).unwrap()
+        );
+        let data_type = ConcreteDataType::timestamp_datatype(TimeUnit::Nanosecond);
+        let err = parse_string_to_value("ts", "2300-01-01T00:00:00Z".to_string(), &data_type)
+            .unwrap_err();
+        assert!(err.to_string().contains("out of the range"), "{err}");
+
+        for s in [
+            "2023/04/01 10:00:00",
+            "1680000000.5",
+            "2023-04-01T10:00:00+08",
+        ] {
+            let err = parse_string_to_value("ts", s.to_string(), &data_type).unwrap_err();
+            let msg = err.to_string();
+            assert!(msg.contains(s) && msg.contains("RFC3339"), "{msg}");
+        }
+    }
+
     #[test]
     pub fn test_parse_column_default_constraint() {
         let bool_value = sqlparser::ast::Value::Boolean(true);
@@ -773,6 +889,34 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_column_def_to_schema_with_storage_options() {
+        let mut column_def = ColumnDef {
+            name: "host".into(),
+            data_type: SqlDataType::String,
+            collation: None,
+            options: vec![
+                ColumnOptionDef {
+                    name: None,
+                    option: create::storage_column_option(create::ENCODING, "Dictionary"),
+                },
+                ColumnOptionDef {
+                    name: None,
+                    option: create::storage_column_option(create::COMPRESSION, "zstd"),
+                },
+            ],
+        };
+
+        let column_schema = column_def_to_schema(&column_def, false).unwrap();
+        assert_eq!(Some("dictionary"), column_schema.encoding());
+        assert_eq!(Some("zstd"), column_schema.compression());
+
+        column_def.options[0].option =
+            create::storage_column_option(create::ENCODING, "byte_stream_split");
+        let err = column_def_to_schema(&column_def, false).unwrap_err();
+        assert_matches!(err, error::Error::InvalidColumnOption { .. });
+    }
+
     #[test]
     pub fn test_parse_placeholder_value() {
         assert!(sql_value_to_value(
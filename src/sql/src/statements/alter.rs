@@ -14,6 +14,8 @@
 
 use sqlparser::ast::{ColumnDef, Ident, ObjectName, TableConstraint};
 
+use crate::statements::create::Partitions;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AlterTable {
     table_name: ObjectName,
@@ -47,4 +49,12 @@ pub enum AlterTableOperation {
     DropColumn { name: Ident },
     /// `RENAME <new_table_name>`
     RenameTable { new_table_name: String },
+    /// `REPARTITION PARTITION BY RANGE COLUMNS (<columns>) (<partition_entries>)`
+    Repartition { partitions: Partitions },
+    /// `ALTER [ COLUMN ] <name> SET [ ENCODING <encoding> ] [ COMPRESSION <compression> ]`
+    SetColumnOptions {
+        name: Ident,
+        encoding: Option<String>,
+        compression: Option<String>,
+    },
 }
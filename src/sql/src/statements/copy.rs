@@ -16,6 +16,8 @@ use std::collections::HashMap;
 
 use sqlparser::ast::ObjectName;
 
+use crate::statements::query::Query;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CopyTable {
     To(CopyTableArgument),
@@ -31,6 +33,15 @@ pub struct CopyTableArgument {
     pub location: String,
 }
 
+/// `COPY (<query>) TO 'location'`, exporting the result of the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyQueryTo {
+    pub query: Box<Query>,
+    pub with: HashMap<String, String>,
+    pub connection: HashMap<String, String>,
+    pub location: String,
+}
+
 #[cfg(test)]
 impl CopyTableArgument {
     const FORMAT: &str = "FORMAT";
@@ -16,8 +16,13 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 use itertools::Itertools;
+use sqlparser::ast::CharacterLength;
+use sqlparser::tokenizer::{Token, Word};
 
-use crate::ast::{ColumnDef, Ident, ObjectName, SqlOption, TableConstraint, Value as SqlValue};
+use crate::ast::{
+    ColumnDef, ColumnOption, DataType as SqlDataType, Ident, ObjectName, SqlOption,
+    TableConstraint, Value as SqlValue,
+};
 
 const LINE_SEP: &str = ",\n";
 const COMMA_SEP: &str = ", ";
@@ -47,6 +52,39 @@ macro_rules! format_list_comma {
 /// Time index name, used in table constraints.
 pub const TIME_INDEX: &str = "__time_index";
 
+/// Length of the `VARCHAR` (or `VARBINARY`) of key columns in MySQL syntax, which is bounded by
+/// the max length of MySQL index keys (3072 bytes).
+pub const MYSQL_KEY_VARCHAR_LENGTH: u64 = 255;
+/// Max fractional seconds precision of MySQL `TIMESTAMP`.
+pub const MYSQL_MAX_TIMESTAMP_PRECISION: u64 = 6;
+
+/// Column option setting the encoding of the column in SST files, e.g. `ENCODING('dictionary')`.
+pub const ENCODING: &str = "ENCODING";
+/// Column option setting the compression of the column in SST files, e.g. `COMPRESSION('zstd')`.
+pub const COMPRESSION: &str = "COMPRESSION";
+
+/// Creates the column option setting the storage option `name` ([ENCODING] or [COMPRESSION])
+/// to `value`, which is displayed as `name 'value'`.
+pub fn storage_column_option(name: &str, value: &str) -> ColumnOption {
+    ColumnOption::DialectSpecific(vec![
+        Token::make_word(name, None),
+        Token::SingleQuotedString(value.to_string()),
+    ])
+}
+
+/// Returns the name and value of the column option if it's a storage option.
+pub fn as_storage_column_option(option: &ColumnOption) -> Option<(&str, &str)> {
+    let ColumnOption::DialectSpecific(tokens) = option else { return None };
+    match &tokens[..] {
+        [Token::Word(Word { value: name, .. }), Token::SingleQuotedString(value)]
+            if name == ENCODING || name == COMPRESSION =>
+        {
+            Some((name, value))
+        }
+        _ => None,
+    }
+}
+
 #[inline]
 pub fn is_time_index(constraint: &TableConstraint) -> bool {
     matches!(constraint, TableConstraint::Unique {
@@ -71,19 +109,22 @@ pub struct CreateTable {
     pub partitions: Option<Partitions>,
 }
 
+/// Formats the indented constraint, the time index is formatted as `TIME INDEX (ts)`.
+fn format_constraint(constraint: &TableConstraint) -> String {
+    if is_time_index(constraint) {
+        let TableConstraint::Unique { columns, ..} = constraint else { unreachable!() };
+
+        format_indent!("{}TIME INDEX ({})", format_list_comma!(columns))
+    } else {
+        format_indent!(constraint)
+    }
+}
+
 impl CreateTable {
     fn format_constraints(&self) -> String {
         self.constraints
             .iter()
-            .map(|c| {
-                if is_time_index(c) {
-                    let TableConstraint::Unique { columns, ..} = c else { unreachable!() };
-
-                    format_indent!("{}TIME INDEX ({})", format_list_comma!(columns))
-                } else {
-                    format_indent!(c)
-                }
-            })
+            .map(format_constraint)
             .join(LINE_SEP)
     }
 
@@ -105,6 +146,15 @@ impl CreateTable {
         }
     }
 
+    /// Formats the partitions, the engine and the options after the column list.
+    fn format_table_options(&self) -> String {
+        let partitions = self.format_partitions();
+        let engine = &self.engine;
+        let options = self.format_options();
+
+        format!("{partitions}ENGINE={engine}\n{options}")
+    }
+
     #[inline]
     fn format_options(&self) -> String {
         if self.options.is_empty() {
@@ -162,9 +212,113 @@ impl Display for CreateTable {
         let name = &self.name;
         let columns = format_list_indent!(self.columns);
         let constraints = self.format_constraints();
-        let partitions = self.format_partitions();
-        let engine = &self.engine;
-        let options = self.format_options();
+        let table_options = self.format_table_options();
+
+        write!(
+            f,
+            r#"CREATE TABLE {if_not_exists} {name} (
+{columns},
+{constraints}
+)
+{table_options}"#
+        )
+    }
+}
+
+impl CreateTable {
+    /// Returns the statement displayed in standard MySQL syntax, see [MySqlCreateTable].
+    pub fn for_mysql(&self) -> MySqlCreateTable<'_> {
+        MySqlCreateTable(self)
+    }
+}
+
+/// Displays a [CreateTable] in standard MySQL syntax, for tools parsing the output of
+/// `SHOW CREATE TABLE` as MySQL:
+/// - identifiers are quoted by backticks;
+/// - the time index is displayed as a `KEY` named [TIME_INDEX];
+/// - column types are mapped to MySQL types by [mysql_data_type];
+/// - storage column options are omitted, the partitions, the engine and the table options are
+///   displayed in a trailing comment.
+///
+/// Unlike [CreateTable], the displayed statement doesn't round-trip through our parser.
+pub struct MySqlCreateTable<'a>(&'a CreateTable);
+
+impl MySqlCreateTable<'_> {
+    fn is_key_column(&self, column: &Ident) -> bool {
+        self.0.constraints.iter().any(|c| {
+            matches!(c, TableConstraint::Unique {
+                columns,
+                is_primary: true,
+                ..
+            } if columns.contains(column))
+        })
+    }
+
+    fn column_def(&self, column: &ColumnDef) -> ColumnDef {
+        let is_key = self.is_key_column(&column.name);
+        let options = column
+            .options
+            .iter()
+            .filter(|o| as_storage_column_option(&o.option).is_none())
+            // MySQL rejects key columns declared as NULL.
+            .filter(|o| !(is_key && matches!(o.option, ColumnOption::Null)))
+            .cloned()
+            .collect();
+
+        ColumnDef {
+            name: mysql_ident(&column.name),
+            data_type: mysql_data_type(&column.data_type, is_key),
+            collation: column.collation.clone(),
+            options,
+        }
+    }
+
+    fn constraint(&self, constraint: &TableConstraint) -> TableConstraint {
+        match constraint {
+            TableConstraint::Unique { name, columns, .. } if is_time_index(constraint) => {
+                TableConstraint::Index {
+                    display_as_key: true,
+                    name: name.as_ref().map(mysql_ident),
+                    index_type: None,
+                    columns: columns.iter().map(mysql_ident).collect(),
+                }
+            }
+            TableConstraint::Unique {
+                name,
+                columns,
+                is_primary,
+            } => TableConstraint::Unique {
+                name: name.as_ref().map(mysql_ident),
+                columns: columns.iter().map(mysql_ident).collect(),
+                is_primary: *is_primary,
+            },
+            c => c.clone(),
+        }
+    }
+}
+
+impl Display for MySqlCreateTable<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let create_table = self.0;
+        let if_not_exists = create_table.format_if_not_exits();
+        let name = ObjectName(create_table.name.0.iter().map(mysql_ident).collect());
+        let columns = create_table
+            .columns
+            .iter()
+            .map(|c| self.column_def(c))
+            .collect::<Vec<_>>();
+        let columns = format_list_indent!(columns);
+        let constraints = create_table
+            .constraints
+            .iter()
+            .map(|c| self.constraint(c))
+            .collect::<Vec<_>>();
+        let constraints = format_list_indent!(constraints);
+        // The comment ends at the first `*/`, which may be in the values of the options.
+        let table_options = create_table
+            .format_table_options()
+            .trim_end()
+            .replace("*/", "*\\/");
 
         write!(
             f,
@@ -172,17 +326,52 @@ impl Display for CreateTable {
 {columns},
 {constraints}
 )
-{partitions}ENGINE={engine}
-{options}"#
+/* {table_options} */"#
         )
     }
 }
 
+#[inline]
+fn mysql_ident(ident: &Ident) -> Ident {
+    Ident::with_quote('`', &ident.value)
+}
+
+/// Maps the column type to the MySQL type, `is_key` is whether the column is in the primary key:
+/// - `STRING` is mapped to `TEXT`, which is stored off the row so the columns don't add up to
+///   the max row size of MySQL, or `VARCHAR(`[MYSQL_KEY_VARCHAR_LENGTH]`)` for key columns;
+/// - `VARBINARY` is mapped to `BLOB`, or `VARBINARY(`[MYSQL_KEY_VARCHAR_LENGTH]`)` for key columns;
+/// - `TIMESTAMP` with a precision above [MYSQL_MAX_TIMESTAMP_PRECISION] (i.e. `TIMESTAMP(9)`)
+///   falls back to `TIMESTAMP(6)`, the nanoseconds are truncated when the values are imported
+///   to MySQL.
+pub fn mysql_data_type(data_type: &SqlDataType, is_key: bool) -> SqlDataType {
+    match data_type {
+        SqlDataType::String | SqlDataType::Varchar(None) if is_key => {
+            SqlDataType::Varchar(Some(CharacterLength {
+                length: MYSQL_KEY_VARCHAR_LENGTH,
+                unit: None,
+            }))
+        }
+        SqlDataType::String | SqlDataType::Varchar(None) => SqlDataType::Text,
+        SqlDataType::Varbinary(None) if is_key => {
+            SqlDataType::Varbinary(Some(MYSQL_KEY_VARCHAR_LENGTH))
+        }
+        SqlDataType::Varbinary(None) => SqlDataType::Blob(None),
+        SqlDataType::Timestamp(Some(precision), timezone)
+            if *precision > MYSQL_MAX_TIMESTAMP_PRECISION =>
+        {
+            SqlDataType::Timestamp(Some(MYSQL_MAX_TIMESTAMP_PRECISION), timezone.clone())
+        }
+        data_type => data_type.clone(),
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct CreateDatabase {
     pub name: ObjectName,
     /// Create if not exists
     pub if_not_exists: bool,
+    /// Default options of the tables in the database.
+    pub options: Vec<SqlOption>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -199,9 +388,57 @@ pub struct CreateExternalTable {
     pub engine: String,
 }
 
+impl Display for CreateExternalTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let if_not_exists = if self.if_not_exists {
+            "IF NOT EXISTS"
+        } else {
+            ""
+        };
+        let name = &self.name;
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| format_indent!(c))
+            .chain(self.constraints.iter().map(format_constraint))
+            .join(LINE_SEP);
+        let engine = &self.engine;
+        // Sorts the options to keep the output stable.
+        let options = self
+            .options
+            .iter()
+            .sorted()
+            .map(|(name, value)| SqlOption {
+                name: name[..].into(),
+                value: SqlValue::SingleQuotedString(value.clone()),
+            })
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            r#"CREATE EXTERNAL TABLE {if_not_exists} {name} (
+{columns}
+)
+ENGINE={engine}"#
+        )?;
+        if !options.is_empty() {
+            let options = format_list_indent!(options);
+            write!(
+                f,
+                r#"
+WITH(
+{options}
+)"#
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use sqlparser::dialect::GenericDialect;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect};
+    use sqlparser::parser::Parser;
 
     use crate::parser::ParserContext;
     use crate::statements::statement::Statement;
@@ -260,4 +497,94 @@ WITH(
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_display_create_external_table() {
+        let sql = r"create external table if not exists city (
+                             host string,
+                             ts timestamp,
+                             TIME INDEX (ts)
+                       )
+                       with(location='/var/data/city.csv', format='csv', delimiter='9');
+         ";
+        let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        let Statement::CreateExternalTable(c) = &result[0] else { unreachable!() };
+
+        let new_sql = format!("\n{}", c);
+        assert_eq!(
+            r#"
+CREATE EXTERNAL TABLE IF NOT EXISTS city (
+  host STRING,
+  ts TIMESTAMP,
+  TIME INDEX (ts)
+)
+ENGINE=file
+WITH(
+  DELIMITER = '9',
+  FORMAT = 'csv',
+  LOCATION = '/var/data/city.csv'
+)"#,
+            &new_sql
+        );
+
+        let new_result = ParserContext::create_with_dialect(&new_sql, &GenericDialect {}).unwrap();
+        assert_eq!(result, new_result);
+    }
+
+    #[test]
+    fn test_display_create_table_for_mysql() {
+        let sql = r"create table if not exists demo(
+                             host string null encoding 'dictionary',
+                             idc string null,
+                             ts timestamp(9) default current_timestamp(),
+                             cpu double default 0 comment 'cpu usage',
+                             bytes varbinary,
+                             TIME INDEX (ts),
+                             PRIMARY KEY(host)
+                       )
+                       PARTITION BY RANGE COLUMNS (host) (
+                         PARTITION r0 VALUES LESS THAN ('a*/'),
+                         PARTITION r1 VALUES LESS THAN (MAXVALUE),
+                       )
+                       engine=mito
+                       with(regions=1, ttl='7d');
+         ";
+        let result = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        let Statement::CreateTable(c) = &result[0] else { unreachable!() };
+
+        let mysql = c.for_mysql().to_string();
+        assert_eq!(
+            r#"CREATE TABLE IF NOT EXISTS `demo` (
+  `host` VARCHAR(255),
+  `idc` TEXT NULL,
+  `ts` TIMESTAMP(6) DEFAULT current_timestamp(),
+  `cpu` DOUBLE DEFAULT 0 COMMENT 'cpu usage',
+  `bytes` BLOB,
+  KEY `__time_index` (`ts`),
+  PRIMARY KEY (`host`)
+)
+/* PARTITION BY RANGE COLUMNS (host) (
+  PARTITION r0 VALUES LESS THAN ('a*\/'),
+  PARTITION r1 VALUES LESS THAN (MAXVALUE)
+)
+ENGINE=mito
+WITH(
+  regions = 1,
+  ttl = '7d'
+) */"#,
+            mysql
+        );
+
+        let stmts = Parser::parse_sql(&MySqlDialect {}, &mysql).unwrap();
+        assert_eq!(1, stmts.len());
+        assert!(matches!(
+            stmts[0],
+            sqlparser::ast::Statement::CreateTable { .. }
+        ));
+
+        // The default display is not affected.
+        let new_result =
+            ParserContext::create_with_dialect(&c.to_string(), &GenericDialect {}).unwrap();
+        assert_eq!(result, new_result);
+    }
 }
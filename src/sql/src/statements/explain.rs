@@ -15,6 +15,9 @@
 use sqlparser::ast::Statement as SpStatement;
 
 use crate::error::Error;
+use crate::statements::delete::Delete;
+use crate::statements::insert::Insert;
+use crate::statements::statement::Statement;
 
 /// Explain statement.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -22,6 +25,30 @@ pub struct Explain {
     pub inner: SpStatement,
 }
 
+impl Explain {
+    /// Returns the explained `INSERT` or `DELETE`, or `None` for other statements and for
+    /// `EXPLAIN ANALYZE`, which executes the statement.
+    pub fn write_statement(&self) -> Option<Statement> {
+        let statement = match &self.inner {
+            SpStatement::Explain {
+                analyze: false,
+                statement,
+                ..
+            } => statement,
+            _ => return None,
+        };
+        match statement.as_ref() {
+            SpStatement::Insert { .. } => Some(Statement::Insert(Box::new(Insert {
+                inner: *statement.clone(),
+            }))),
+            SpStatement::Delete { .. } => Some(Statement::Delete(Box::new(Delete {
+                inner: *statement.clone(),
+            }))),
+            _ => None,
+        }
+    }
+}
+
 impl TryFrom<SpStatement> for Explain {
     type Error = Error;
 
@@ -35,3 +62,36 @@ impl ToString for Explain {
         self.inner.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use sqlparser::dialect::GenericDialect;
+
+    use super::*;
+    use crate::parser::ParserContext;
+
+    fn parse_explain(sql: &str) -> Explain {
+        let mut stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        match stmts.pop().unwrap() {
+            Statement::Explain(explain) => explain,
+            stmt => unreachable!("{stmt:?}"),
+        }
+    }
+
+    #[test]
+    fn test_write_statement() {
+        let explain = parse_explain("EXPLAIN INSERT INTO foo VALUES (1)");
+        assert_matches!(explain.write_statement(), Some(Statement::Insert(_)));
+
+        let explain = parse_explain("EXPLAIN DELETE FROM foo WHERE a = 1");
+        assert_matches!(explain.write_statement(), Some(Statement::Delete(_)));
+
+        let explain = parse_explain("EXPLAIN ANALYZE DELETE FROM foo WHERE a = 1");
+        assert!(explain.write_statement().is_none());
+
+        let explain = parse_explain("EXPLAIN SELECT * FROM foo");
+        assert!(explain.write_statement().is_none());
+    }
+}
@@ -92,6 +92,12 @@ fn sql_exprs_to_values(exprs: &Vec<Vec<Expr>>) -> Result<Vec<Vec<Value>>> {
                         Value::SingleQuotedString(ident.value.clone())
                     }
                 }
+                // Literals are converted to the column type anyway, so casts of them like
+                // `'NaN'::DOUBLE` are treated as the literals.
+                Expr::Cast {
+                    expr: box Expr::Value(v),
+                    ..
+                } => v.clone(),
                 Expr::UnaryOp { op, expr }
                     if matches!(op, UnaryOperator::Minus | UnaryOperator::Plus) =>
                 {
@@ -222,6 +228,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_insert_value_with_cast() {
+        let sql = "INSERT INTO my_table VALUES('NaN'::DOUBLE, CAST('-inf' AS FLOAT))";
+        let stmt = ParserContext::create_with_dialect(sql, &GenericDialect {})
+            .unwrap()
+            .remove(0);
+        match stmt {
+            Statement::Insert(insert) => {
+                let values = insert.values_body().unwrap().unwrap();
+                assert_eq!(
+                    values,
+                    vec![vec![
+                        Value::SingleQuotedString("NaN".to_owned()),
+                        Value::SingleQuotedString("-inf".to_owned())
+                    ]]
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn test_insert_select() {
         let sql = "INSERT INTO my_table select * from other_table";
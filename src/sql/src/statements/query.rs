@@ -13,15 +13,24 @@
 // limitations under the License.
 
 use datatypes::prelude::ConcreteDataType;
-use sqlparser::ast::Query as SpQuery;
+use sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, Ident, ObjectName, Query as SpQuery, SelectItem, SetExpr,
+    TableFactor, Value,
+};
 
 use crate::error::Error;
 
+/// Name of the admin function computing the column statistics of a table.
+pub const ANALYZE_TABLE: &str = "analyze_table";
+
 /// Query statement instance.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Query {
     pub inner: SpQuery,
     pub param_types: Vec<ConcreteDataType>,
+    /// Wall-clock time in millis the query reads the snapshots of tables as of, set by the
+    /// clause `FOR SYSTEM_TIME AS OF <timestamp>`.
+    pub as_of: Option<i64>,
 }
 
 /// Automatically converts from sqlparser Query instance to SqlQuery.
@@ -32,6 +41,7 @@ impl TryFrom<SpQuery> for Query {
         Ok(Query {
             inner: q,
             param_types: vec![],
+            as_of: None,
         })
     }
 }
@@ -52,4 +62,106 @@ impl Query {
     pub fn param_types_mut(&mut self) -> &mut Vec<ConcreteDataType> {
         &mut self.param_types
     }
+
+    /// Returns the table name if the query is the admin function call
+    /// `SELECT analyze_table('<table>')`.
+    pub fn analyze_table_name(&self) -> Option<ObjectName> {
+        let select = match self.inner.body.as_ref() {
+            SetExpr::Select(select) if select.from.is_empty() && select.projection.len() == 1 => {
+                select
+            }
+            _ => return None,
+        };
+        let function = match &select.projection[0] {
+            SelectItem::UnnamedExpr(Expr::Function(function))
+                if function
+                    .name
+                    .to_string()
+                    .eq_ignore_ascii_case(ANALYZE_TABLE) =>
+            {
+                function
+            }
+            _ => return None,
+        };
+        match function.args.as_slice() {
+            [FunctionArg::Unnamed(FunctionArgExpr::Expr(Expr::Value(
+                Value::SingleQuotedString(table_name),
+            )))] => Some(ObjectName(table_name.split('.').map(Ident::new).collect())),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the only table the query selects from, or `None` if the query isn't
+    /// a plain `SELECT` of one table without joins.
+    pub fn source_table(&self) -> Option<&ObjectName> {
+        let SetExpr::Select(select) = self.inner.body.as_ref() else { return None };
+        let [from] = select.from.as_slice() else { return None };
+        if !from.joins.is_empty() {
+            return None;
+        }
+        match &from.relation {
+            TableFactor::Table { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlparser::dialect::GenericDialect;
+
+    use super::*;
+    use crate::parser::ParserContext;
+    use crate::statements::statement::Statement;
+
+    fn analyze_table_name(sql: &str) -> Option<String> {
+        let mut stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        match stmts.remove(0) {
+            Statement::Query(query) => query.analyze_table_name().map(|name| name.to_string()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_analyze_table_name() {
+        assert_eq!(
+            Some("my_schema.t"),
+            analyze_table_name("SELECT ANALYZE_TABLE('my_schema.t')").as_deref()
+        );
+        assert_eq!(None, analyze_table_name("SELECT analyze_table(t) FROM t"));
+        assert_eq!(None, analyze_table_name("SELECT analyze_table('t', 1)"));
+        assert_eq!(None, analyze_table_name("SELECT analyze_table('t'), 1"));
+        assert_eq!(None, analyze_table_name("SELECT now()"));
+    }
+
+    fn source_table(sql: &str) -> Option<String> {
+        let mut stmts = ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        match stmts.remove(0) {
+            Statement::Query(query) => query.source_table().map(|name| name.to_string()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_source_table() {
+        assert_eq!(
+            Some("t"),
+            source_table("SELECT a, b FROM t WHERE a > 1").as_deref()
+        );
+        assert_eq!(
+            Some("my_schema.\"t\""),
+            source_table("SELECT * FROM my_schema.\"t\" AS x").as_deref()
+        );
+        assert_eq!(
+            None,
+            source_table("SELECT * FROM t1 JOIN t2 ON t1.a = t2.a")
+        );
+        assert_eq!(None, source_table("SELECT * FROM t1, t2"));
+        assert_eq!(None, source_table("SELECT * FROM (SELECT * FROM t)"));
+        assert_eq!(None, source_table("SELECT 1"));
+        assert_eq!(
+            None,
+            source_table("SELECT * FROM t1 UNION SELECT * FROM t2")
+        );
+    }
 }
@@ -0,0 +1,42 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sqlparser::ast::ObjectName;
+
+/// RESTORE TABLE statement, restores a dropped table from the recycle bin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreTable {
+    table_name: ObjectName,
+    new_table_name: Option<String>,
+}
+
+impl RestoreTable {
+    /// Creates a statement for `RESTORE TABLE <table_name> [AS <new_table_name>]`
+    pub fn new(table_name: ObjectName, new_table_name: Option<String>) -> Self {
+        Self {
+            table_name,
+            new_table_name,
+        }
+    }
+
+    /// Name of the table when it was dropped.
+    pub fn table_name(&self) -> &ObjectName {
+        &self.table_name
+    }
+
+    /// Name to restore the table as, the table keeps its name if it's `None`.
+    pub fn new_table_name(&self) -> Option<&str> {
+        self.new_table_name.as_deref()
+    }
+}
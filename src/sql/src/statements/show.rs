@@ -47,17 +47,32 @@ impl ShowDatabases {
     }
 }
 
-/// SQL structure for `SHOW TABLES`.
+/// SQL structure for `SHOW [FULL] TABLES [IN | FROM database] [LIKE | WHERE ...] [WITH COMMENT]`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ShowTables {
     pub kind: ShowKind,
     pub database: Option<String>,
+    /// Whether to show the type of each table (`SHOW FULL TABLES`), which MySQL clients use to
+    /// tell base tables from views.
+    pub full: bool,
+    /// Whether to show the comment of each table.
+    pub with_comment: bool,
 }
 
 /// SQL structure for `SHOW CREATE TABLE`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ShowCreateTable {
     pub table_name: ObjectName,
+    /// Whether to show the statement in standard MySQL syntax (`SHOW CREATE TABLE t FOR MYSQL`),
+    /// for tools parsing it as MySQL.
+    pub for_mysql: bool,
+}
+
+/// SQL structure for `SHOW [FULL] PROCESSLIST`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShowProcesslist {
+    /// Whether to show the whole statement of each process, rather than its first 100 chars.
+    pub full: bool,
 }
 
 #[cfg(test)]
@@ -126,15 +141,55 @@ mod tests {
             Statement::ShowCreateTable(show) => {
                 let table_name = show.table_name.to_string();
                 assert_eq!(table_name, "test");
+                assert!(!show.for_mysql);
             }
             _ => {
                 unreachable!();
             }
         }
     }
+
+    #[test]
+    pub fn test_show_create_table_for_mysql() {
+        let sql = "SHOW CREATE TABLE test FOR MYSQL";
+        let stmts: Vec<Statement> =
+            ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        match &stmts[0] {
+            Statement::ShowCreateTable(show) => {
+                assert_eq!("test", show.table_name.to_string());
+                assert!(show.for_mysql);
+            }
+            _ => {
+                unreachable!();
+            }
+        }
+
+        let sql = "SHOW CREATE TABLE test FOR POSTGRES";
+        ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
+    }
     #[test]
     pub fn test_show_create_missing_table_name() {
         let sql = "SHOW CREATE TABLE";
         ParserContext::create_with_dialect(sql, &GenericDialect {}).unwrap_err();
     }
+
+    #[test]
+    pub fn test_show_processlist() {
+        let stmts =
+            ParserContext::create_with_dialect("SHOW PROCESSLIST", &GenericDialect {}).unwrap();
+        assert_eq!(
+            vec![Statement::ShowProcesslist(ShowProcesslist { full: false })],
+            stmts
+        );
+
+        let stmts = ParserContext::create_with_dialect("SHOW FULL PROCESSLIST", &GenericDialect {})
+            .unwrap();
+        assert_eq!(
+            vec![Statement::ShowProcesslist(ShowProcesslist { full: true })],
+            stmts
+        );
+
+        ParserContext::create_with_dialect("SHOW FULL TABLES", &GenericDialect {}).unwrap_err();
+    }
 }
@@ -12,12 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ops::ControlFlow;
+
 use datafusion_sql::parser::Statement as DfStatement;
-use sqlparser::ast::Statement as SpStatement;
+use sqlparser::ast::{visit_relations, ObjectName, Statement as SpStatement, Visit};
 
 use crate::error::{ConvertToDfStatementSnafu, Error};
 use crate::statements::alter::AlterTable;
-use crate::statements::copy::CopyTable;
+use crate::statements::copy::{CopyQueryTo, CopyTable};
 use crate::statements::create::{CreateDatabase, CreateExternalTable, CreateTable};
 use crate::statements::delete::Delete;
 use crate::statements::describe::DescribeTable;
@@ -25,7 +27,8 @@ use crate::statements::drop::DropTable;
 use crate::statements::explain::Explain;
 use crate::statements::insert::Insert;
 use crate::statements::query::Query;
-use crate::statements::show::{ShowCreateTable, ShowDatabases, ShowTables};
+use crate::statements::restore::RestoreTable;
+use crate::statements::show::{ShowCreateTable, ShowDatabases, ShowProcesslist, ShowTables};
 use crate::statements::tql::Tql;
 
 /// Tokens parsed by `DFParser` are converted into these values.
@@ -44,6 +47,8 @@ pub enum Statement {
     CreateExternalTable(CreateExternalTable),
     // DROP TABLE
     DropTable(DropTable),
+    // RESTORE TABLE
+    RestoreTable(RestoreTable),
     // CREATE DATABASE
     CreateDatabase(CreateDatabase),
     /// ALTER TABLE
@@ -54,16 +59,91 @@ pub enum Statement {
     ShowTables(ShowTables),
     // SHOW CREATE TABLE
     ShowCreateTable(ShowCreateTable),
+    // SHOW [FULL] PROCESSLIST
+    ShowProcesslist(ShowProcesslist),
     // DESCRIBE TABLE
     DescribeTable(DescribeTable),
     // EXPLAIN QUERY
     Explain(Explain),
+    // EXPLAIN COPY
+    ExplainCopy(CopyTable),
     Use(String),
     // COPY
     Copy(CopyTable),
+    // COPY (query) TO
+    CopyQueryTo(CopyQueryTo),
     Tql(Tql),
 }
 
+impl Statement {
+    /// Returns the kind of the statement, e.g. `SELECT` or `DROP TABLE`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Statement::Query(_) => "SELECT",
+            Statement::Insert(_) => "INSERT",
+            Statement::Delete(_) => "DELETE",
+            Statement::CreateTable(_) => "CREATE TABLE",
+            Statement::CreateExternalTable(_) => "CREATE EXTERNAL TABLE",
+            Statement::DropTable(_) => "DROP TABLE",
+            Statement::RestoreTable(_) => "RESTORE TABLE",
+            Statement::CreateDatabase(_) => "CREATE DATABASE",
+            Statement::Alter(_) => "ALTER TABLE",
+            Statement::ShowDatabases(_) => "SHOW DATABASES",
+            Statement::ShowTables(_) => "SHOW TABLES",
+            Statement::ShowCreateTable(_) => "SHOW CREATE TABLE",
+            Statement::ShowProcesslist(_) => "SHOW PROCESSLIST",
+            Statement::DescribeTable(_) => "DESCRIBE TABLE",
+            Statement::Explain(_) | Statement::ExplainCopy(_) => "EXPLAIN",
+            Statement::Use(_) => "USE",
+            Statement::Copy(CopyTable::To(_)) | Statement::CopyQueryTo(_) => "COPY TO",
+            Statement::Copy(CopyTable::From(_)) => "COPY FROM",
+            Statement::Tql(_) => "TQL",
+        }
+    }
+
+    /// Returns the names of the tables the statement reads or writes, including the tables of
+    /// its subqueries, in the order they appear and without duplicates.
+    ///
+    /// `TQL` names no table, the tables of its PromQL are only known by planning it.
+    pub fn referenced_tables(&self) -> Vec<ObjectName> {
+        let mut tables = Vec::new();
+        match self {
+            Statement::Query(query) => collect_relations(&query.inner, &mut tables),
+            Statement::CopyQueryTo(copy) => collect_relations(&copy.query.inner, &mut tables),
+            Statement::Insert(insert) => collect_relations(&insert.inner, &mut tables),
+            Statement::Delete(delete) => collect_relations(&delete.inner, &mut tables),
+            Statement::Explain(explain) => collect_relations(&explain.inner, &mut tables),
+            Statement::CreateTable(create) => tables.push(create.name.clone()),
+            Statement::CreateExternalTable(create) => tables.push(create.name.clone()),
+            Statement::DropTable(drop) => tables.push(drop.table_name().clone()),
+            Statement::RestoreTable(restore) => tables.push(restore.table_name().clone()),
+            Statement::Alter(alter) => tables.push(alter.table_name().clone()),
+            Statement::ShowCreateTable(show) => tables.push(show.table_name.clone()),
+            Statement::DescribeTable(describe) => tables.push(describe.name().clone()),
+            Statement::Copy(CopyTable::To(arg) | CopyTable::From(arg))
+            | Statement::ExplainCopy(CopyTable::To(arg) | CopyTable::From(arg)) => {
+                tables.push(arg.table_name.clone())
+            }
+            Statement::CreateDatabase(_)
+            | Statement::ShowDatabases(_)
+            | Statement::ShowTables(_)
+            | Statement::ShowProcesslist(_)
+            | Statement::Use(_)
+            | Statement::Tql(_) => {}
+        }
+        tables
+    }
+}
+
+fn collect_relations<V: Visit>(node: &V, tables: &mut Vec<ObjectName>) {
+    let _ = visit_relations(node, |relation| {
+        if !tables.contains(relation) {
+            tables.push(relation.clone());
+        }
+        ControlFlow::<()>::Continue(())
+    });
+}
+
 /// Comment hints from SQL.
 /// It'll be enabled when using `--comment` in mysql client.
 /// Eg: `SELECT * FROM system.number LIMIT 1; -- { ErrorCode 25 }`
@@ -93,3 +173,43 @@ impl TryFrom<&Statement> for DfStatement {
         Ok(DfStatement::Statement(Box::new(s)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlparser::dialect::GenericDialect;
+
+    use crate::parser::ParserContext;
+
+    fn kind_and_tables(sql: &str) -> (&'static str, Vec<String>) {
+        let stmt = ParserContext::create_with_dialect(sql, &GenericDialect {})
+            .unwrap()
+            .remove(0);
+        let tables = stmt
+            .referenced_tables()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        (stmt.kind(), tables)
+    }
+
+    #[test]
+    fn test_referenced_tables() {
+        assert_eq!(
+            ("SELECT", vec!["a".to_string(), "s.b".to_string()]),
+            kind_and_tables("SELECT * FROM a WHERE x IN (SELECT x FROM s.b) UNION SELECT * FROM a")
+        );
+        assert_eq!(
+            ("INSERT", vec!["t".to_string(), "u".to_string()]),
+            kind_and_tables("INSERT INTO t SELECT * FROM u")
+        );
+        assert_eq!(
+            ("DROP TABLE", vec!["c.s.t".to_string()]),
+            kind_and_tables("DROP TABLE c.s.t")
+        );
+        assert_eq!(
+            ("COPY TO", vec!["t".to_string()]),
+            kind_and_tables("COPY (SELECT * FROM t) TO 'a.parquet'")
+        );
+        assert_eq!(("SHOW TABLES", vec![]), kind_and_tables("SHOW TABLES"));
+    }
+}
@@ -36,3 +36,119 @@ pub fn to_lowercase_options_map(opts: &[SqlOption]) -> HashMap<String, String> {
     }
     map
 }
+
+/// Returns the offset after the quoted string, the quoted identifier or the comment starting
+/// at byte `start` of `sql`, or `None` if none starts there.
+///
+/// Quotes are escaped by doubling them, backslashes are not escapes to the parser. The
+/// unterminated ones end at the end of `sql`.
+pub fn skip_quoted_or_comment(sql: &str, start: usize) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    match bytes[start] {
+        quote @ (b'\'' | b'"' | b'`') => {
+            let mut i = start + 1;
+            while i < bytes.len() {
+                if bytes[i] != quote {
+                    i += 1;
+                } else if bytes.get(i + 1) == Some(&quote) {
+                    i += 2;
+                } else {
+                    return Some(i + 1);
+                }
+            }
+            Some(bytes.len())
+        }
+        b'-' if bytes.get(start + 1) == Some(&b'-') => Some(
+            sql[start..]
+                .find('\n')
+                .map_or(bytes.len(), |end| start + end + 1),
+        ),
+        b'/' if bytes.get(start + 1) == Some(&b'*') => Some(
+            sql[start + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |end| start + 2 + end + 2),
+        ),
+        _ => None,
+    }
+}
+
+/// Splits `sql` into its statements by the `;` out of the quoted strings, the quoted
+/// identifiers and the comments. The statements are trimmed, and the empty ones are skipped.
+pub fn split_statements(sql: &str) -> Vec<&str> {
+    let mut statements = vec![];
+    let mut start = 0;
+    let mut i = 0;
+    while i < sql.len() {
+        if let Some(end) = skip_quoted_or_comment(sql, i) {
+            i = end;
+            continue;
+        }
+        if sql.as_bytes()[i] == b';' {
+            statements.push(&sql[start..i]);
+            start = i + 1;
+        }
+        i += 1;
+    }
+    statements.push(&sql[start..]);
+
+    statements
+        .into_iter()
+        .map(str::trim)
+        .filter(|stmt| !is_blank(stmt))
+        .collect()
+}
+
+/// Returns whether `sql` has nothing but whitespaces and comments.
+fn is_blank(sql: &str) -> bool {
+    let mut i = 0;
+    while i < sql.len() {
+        if sql.as_bytes()[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        match skip_quoted_or_comment(sql, i) {
+            Some(end) if matches!(sql.as_bytes()[i], b'-' | b'/') => i = end,
+            _ => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_quoted_or_comment() {
+        let sql = "'it''s' \"a\" `b` -- c\n/* d */ e";
+        assert_eq!(Some(7), skip_quoted_or_comment(sql, 0));
+        assert_eq!(Some(11), skip_quoted_or_comment(sql, 8));
+        assert_eq!(Some(15), skip_quoted_or_comment(sql, 12));
+        assert_eq!(Some(21), skip_quoted_or_comment(sql, 16));
+        assert_eq!(Some(28), skip_quoted_or_comment(sql, 21));
+        assert_eq!(None, skip_quoted_or_comment(sql, 29));
+        assert_eq!(Some(4), skip_quoted_or_comment("'a\\'", 0));
+        assert_eq!(Some(3), skip_quoted_or_comment("'ab", 0));
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(vec!["SELECT 1"], split_statements("SELECT 1"));
+        assert_eq!(
+            vec!["CREATE TABLE t (ts TIMESTAMP TIME INDEX)", "SELECT 1"],
+            split_statements("CREATE TABLE t (ts TIMESTAMP TIME INDEX);\n SELECT 1;\n")
+        );
+        assert_eq!(
+            vec![
+                "INSERT INTO t VALUES ('a;b', 1)",
+                "SELECT `c;d` FROM t -- e;f\nWHERE g = 'h''; i'",
+                "/* j; */ SELECT 2",
+            ],
+            split_statements(
+                "INSERT INTO t VALUES ('a;b', 1); SELECT `c;d` FROM t -- e;f\n\
+                 WHERE g = 'h''; i'; /* j; */ SELECT 2"
+            )
+        );
+        assert!(split_statements(" ; -- a;\n ;").is_empty());
+    }
+}
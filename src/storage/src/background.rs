@@ -16,6 +16,7 @@
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use common_runtime::{self, JoinHandle};
@@ -66,6 +67,13 @@ impl JobHandle {
         self.handle.await.context(error::JoinTaskSnafu)?
     }
 
+    /// Waits until this background job is finished or the `timeout` elapses. Returns `None`
+    /// if the job is still running, which can be joined again later.
+    pub async fn join_timeout(&mut self, timeout: Duration) -> Option<Result<()>> {
+        let result = tokio::time::timeout(timeout, &mut self.handle).await.ok()?;
+        Some(result.context(error::JoinTaskSnafu).and_then(|r| r))
+    }
+
     /// Cancels this background job gracefully and waits until it exits.
     #[allow(unused)]
     pub async fn cancel(self) -> Result<()> {
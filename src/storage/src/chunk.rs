@@ -18,15 +18,20 @@ use async_trait::async_trait;
 use common_query::logical_plan::Expr;
 use common_telemetry::debug;
 use common_time::range::TimestampRange;
+use datatypes::vectors::{StringDictionaryBuilder, StringVector};
 use snafu::ResultExt;
 use store_api::storage::{Chunk, ChunkReader, SchemaRef, SequenceNumber};
 use table::predicate::{Predicate, TimeRangePredicateBuilder};
 
 use crate::error::{self, Error, Result};
 use crate::memtable::{IterContext, MemtableRef};
-use crate::read::{Batch, BoxedBatchReader, DedupReader, MergeReaderBuilder};
+use crate::read::{
+    Batch, BoxedBatchReader, DedupReader, ExpiryReader, MergeReaderBuilder, RowExpiry,
+    SequenceReader, TombstoneReader,
+};
 use crate::schema::{ProjectedSchema, ProjectedSchemaRef, RegionSchemaRef};
 use crate::sst::{AccessLayerRef, FileHandle, LevelMetas, ReadOptions};
+use crate::tombstone::RangeTombstone;
 
 /// Chunk reader implementation.
 // Now we use async-trait to implement the chunk reader, which is easier to implement than
@@ -35,6 +40,9 @@ use crate::sst::{AccessLayerRef, FileHandle, LevelMetas, ReadOptions};
 pub struct ChunkReaderImpl {
     schema: ProjectedSchemaRef,
     batch_reader: BoxedBatchReader,
+    /// Dictionaries of row key columns to encode string tags, `None` if the tags are not
+    /// encoded.
+    tag_dictionaries: Option<Vec<StringDictionaryBuilder>>,
 }
 
 #[async_trait]
@@ -50,7 +58,19 @@ impl ChunkReader for ChunkReaderImpl {
             Some(b) => b,
             None => return Ok(None),
         };
-        Ok(Some(Chunk::new(batch.columns)))
+        let mut columns = batch.columns;
+        if let Some(dictionaries) = &mut self.tag_dictionaries {
+            // Row key columns are at the front of the batch.
+            for (column, dictionary) in columns.iter_mut().zip(dictionaries.iter_mut()) {
+                if column.as_any().is::<StringVector>() {
+                    let encoded = dictionary
+                        .encode(&**column)
+                        .context(error::EncodeTagDictionarySnafu)?;
+                    *column = Arc::new(encoded);
+                }
+            }
+        }
+        Ok(Some(Chunk::new(columns)))
     }
 
     fn project_chunk(&self, chunk: Chunk) -> Chunk {
@@ -66,9 +86,21 @@ impl ChunkReaderImpl {
         ChunkReaderImpl {
             schema,
             batch_reader,
+            tag_dictionaries: None,
         }
     }
 
+    /// Encodes string tag columns of chunks with dictionaries shared by all chunks.
+    pub fn with_dictionary_tags(mut self) -> ChunkReaderImpl {
+        let num_row_keys = self.schema.schema_to_read().row_key_end();
+        self.tag_dictionaries = Some(
+            (0..num_row_keys)
+                .map(|_| StringDictionaryBuilder::default())
+                .collect(),
+        );
+        self
+    }
+
     #[inline]
     pub fn projected_schema(&self) -> &ProjectedSchemaRef {
         &self.schema
@@ -84,6 +116,11 @@ pub struct ChunkReaderBuilder {
     iter_ctx: IterContext,
     memtables: Vec<MemtableRef>,
     files_to_read: Vec<FileHandle>,
+    dictionary_tags: bool,
+    row_expiry: Option<RowExpiry>,
+    range_tombstones: Vec<RangeTombstone>,
+    /// Max sequence number (inclusive) of the rows in the SSTs to read.
+    flushed_sequence: SequenceNumber,
 }
 
 impl ChunkReaderBuilder {
@@ -96,6 +133,10 @@ impl ChunkReaderBuilder {
             iter_ctx: IterContext::default(),
             memtables: Vec::new(),
             files_to_read: Vec::new(),
+            dictionary_tags: false,
+            row_expiry: None,
+            range_tombstones: Vec::new(),
+            flushed_sequence: SequenceNumber::MAX,
         }
     }
 
@@ -115,6 +156,25 @@ impl ChunkReaderBuilder {
         self
     }
 
+    pub fn dictionary_tags(mut self, dictionary_tags: bool) -> Self {
+        self.dictionary_tags = dictionary_tags;
+        self
+    }
+
+    /// Drops the rows expired by `row_expiry`, which only takes effect if the column holding
+    /// the expiry time is read.
+    pub fn row_expiry(mut self, row_expiry: Option<RowExpiry>) -> Self {
+        self.row_expiry = row_expiry;
+        self
+    }
+
+    /// Drops the rows deleted by `range_tombstones`, ignoring the tombstones above the visible
+    /// sequence.
+    pub fn range_tombstones(mut self, range_tombstones: &[RangeTombstone]) -> Self {
+        self.range_tombstones = range_tombstones.to_vec();
+        self
+    }
+
     pub fn batch_size(mut self, batch_size: usize) -> Self {
         self.iter_ctx.batch_size = batch_size;
         self
@@ -125,6 +185,13 @@ impl ChunkReaderBuilder {
         self
     }
 
+    /// Sets the max sequence of the rows in the SSTs to read, so the rows of SSTs are only
+    /// filtered by the visible sequence if it's below the flushed sequence.
+    pub fn flushed_sequence(mut self, sequence: SequenceNumber) -> Self {
+        self.flushed_sequence = sequence;
+        self
+    }
+
     pub fn pick_memtables(mut self, memtables: MemtableRef) -> Self {
         self.memtables.push(memtables);
         self
@@ -152,11 +219,12 @@ impl ChunkReaderBuilder {
     }
 
     pub async fn build(mut self) -> Result<ChunkReaderImpl> {
-        let time_range_predicate = self.build_time_range_predicate();
-        debug!(
-            "Time range predicate for chunk reader: {:?}",
-            time_range_predicate
-        );
+        let time_ranges = self.build_time_range_predicates();
+        // Rows in the files are filtered by the range covering the time ranges.
+        let time_range_predicate = time_ranges
+            .iter()
+            .fold(TimestampRange::empty(), |res, range| res.or(range));
+        debug!("Time range predicates for chunk reader: {:?}", time_ranges);
 
         let schema = Arc::new(
             ProjectedSchema::new(self.schema, self.projection)
@@ -180,39 +248,250 @@ impl ChunkReaderBuilder {
             time_range: time_range_predicate,
         };
         for file in &self.files_to_read {
-            if !Self::file_in_range(file, time_range_predicate) {
-                debug!(
-                    "Skip file {:?}, predicate: {:?}",
-                    file, time_range_predicate
-                );
+            if !Self::file_in_ranges(file, &time_ranges) {
+                debug!("Skip file {:?}, predicates: {:?}", file, time_ranges);
                 continue;
             }
-            let reader = self.sst_layer.read_sst(file.clone(), &read_opts).await?;
+            let mut reader = self.sst_layer.read_sst(file.clone(), &read_opts).await?;
+            if self.iter_ctx.visible_sequence < self.flushed_sequence {
+                reader = Box::new(SequenceReader::new(
+                    schema.clone(),
+                    reader,
+                    self.iter_ctx.visible_sequence,
+                ));
+            }
 
             reader_builder = reader_builder.push_batch_reader(reader);
         }
 
         let reader = reader_builder.build();
-        let reader = DedupReader::new(schema.clone(), reader);
+        let mut reader: BoxedBatchReader = Box::new(DedupReader::new(schema.clone(), reader));
+        // Drops the rows deleted by range tombstones after dedup, like the expired rows below.
+        let visible_sequence = self.iter_ctx.visible_sequence;
+        self.range_tombstones
+            .retain(|tombstone| tombstone.sequence <= visible_sequence);
+        let ts_index = schema.schema_to_read().schema().timestamp_index();
+        if let (Some(ts_index), false) = (ts_index, self.range_tombstones.is_empty()) {
+            reader = Box::new(TombstoneReader::new(
+                schema.clone(),
+                reader,
+                self.range_tombstones,
+                ts_index,
+            ));
+        }
+        // Drops expired rows after dedup, so they can't unveil the older rows of the same key.
+        let ttl_index = self.row_expiry.as_ref().and_then(|row_expiry| {
+            schema
+                .schema_to_read()
+                .schema()
+                .column_index_by_name(&row_expiry.ttl_column)
+        });
+        let reader: BoxedBatchReader = match (self.row_expiry, ttl_index) {
+            (Some(row_expiry), Some(ttl_index)) => Box::new(ExpiryReader::new(
+                schema.clone(),
+                reader,
+                ttl_index,
+                row_expiry.now,
+            )),
+            _ => reader,
+        };
 
-        Ok(ChunkReaderImpl::new(schema, Box::new(reader)))
+        let reader = ChunkReaderImpl::new(schema, reader);
+        if self.dictionary_tags {
+            Ok(reader.with_dictionary_tags())
+        } else {
+            Ok(reader)
+        }
     }
 
-    /// Build time range predicate from schema and filters.
-    pub fn build_time_range_predicate(&self) -> TimestampRange {
-        let Some(ts_col) = self.schema.user_schema().timestamp_column() else { return TimestampRange::min_to_max() };
-        TimeRangePredicateBuilder::new(&ts_col.name, &self.filters).build()
+    /// Build disjoint time range predicates from schema and filters.
+    pub fn build_time_range_predicates(&self) -> Vec<TimestampRange> {
+        let Some(ts_col) = self.schema.user_schema().timestamp_column() else {
+            return vec![TimestampRange::min_to_max()];
+        };
+        TimeRangePredicateBuilder::new(&ts_col.name, &self.filters).build_ranges()
     }
 
-    /// Check if SST file's time range matches predicate.
+    /// Check if SST file's time range matches any of the predicates.
     #[inline]
-    fn file_in_range(file: &FileHandle, predicate: TimestampRange) -> bool {
-        if predicate == TimestampRange::min_to_max() {
+    fn file_in_ranges(file: &FileHandle, predicates: &[TimestampRange]) -> bool {
+        if predicates == [TimestampRange::min_to_max()] {
             return true;
         }
         // end_timestamp of sst file is inclusive.
         let Some((start, end)) = *file.time_range() else { return true; };
         let file_ts_range = TimestampRange::new_inclusive(Some(start), Some(end));
-        file_ts_range.intersects(&predicate)
+        predicates
+            .iter()
+            .any(|predicate| file_ts_range.intersects(predicate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_recordbatch::RecordBatch;
+    use common_time::timestamp::TimeUnit;
+    use common_time::Timestamp;
+    use datatypes::arrow::datatypes::DataType as ArrowDataType;
+    use datatypes::prelude::{Vector, VectorRef};
+    use datatypes::type_id::LogicalTypeId;
+    use datatypes::vectors::{
+        DictionaryVector, Int64Vector, TimestampMillisecondVector, UInt64Vector, UInt8Vector,
+    };
+
+    use super::*;
+    use crate::file_purger::noop::new_noop_file_purger;
+    use crate::metadata::RegionMetadata;
+    use crate::read::BatchReader;
+    use crate::sst::{FileId, FileMeta};
+    use crate::test_util::access_layer_util::MockAccessLayer;
+    use crate::test_util::descriptor_util::RegionDescBuilder;
+
+    struct VecReader(Vec<Batch>);
+
+    #[async_trait]
+    impl BatchReader for VecReader {
+        async fn next_batch(&mut self) -> Result<Option<Batch>> {
+            Ok(self.0.pop())
+        }
+    }
+
+    /// Creates a projected schema (host, timestamp, v0).
+    fn new_projected_schema() -> ProjectedSchemaRef {
+        let desc = RegionDescBuilder::new("chunk-test")
+            .enable_version_column(false)
+            .push_key_column(("host", LogicalTypeId::String, false))
+            .push_field_column(("v0", LogicalTypeId::Int64, true))
+            .build();
+        let metadata: RegionMetadata = desc.try_into().unwrap();
+        Arc::new(ProjectedSchema::new(metadata.schema().clone(), None).unwrap())
+    }
+
+    fn new_batch(num_rows: usize) -> Batch {
+        let hosts: Vec<_> = (0..num_rows).map(|i| format!("host-{}", i % 4)).collect();
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(hosts)),
+            Arc::new(TimestampMillisecondVector::from_values(0..num_rows as i64)),
+            Arc::new(Int64Vector::from_values(0..num_rows as i64)),
+            Arc::new(UInt64Vector::from_vec(vec![0; num_rows])),
+            Arc::new(UInt8Vector::from_vec(vec![0; num_rows])),
+        ];
+        Batch::new(columns)
+    }
+
+    async fn read_chunks(dictionary_tags: bool) -> Vec<Chunk> {
+        let schema = new_projected_schema();
+        let batches = vec![new_batch(1024), new_batch(1024)];
+        let mut reader = ChunkReaderImpl::new(schema, Box::new(VecReader(batches)));
+        if dictionary_tags {
+            reader = reader.with_dictionary_tags();
+        }
+
+        let mut chunks = Vec::new();
+        while let Some(chunk) = reader.next_chunk().await.unwrap() {
+            chunks.push(reader.project_chunk(chunk));
+        }
+        chunks
+    }
+
+    #[tokio::test]
+    async fn test_read_dictionary_tags() {
+        let expect = read_chunks(false).await;
+        let chunks = read_chunks(true).await;
+        assert_eq!(expect.len(), chunks.len());
+
+        let first = chunks[0].columns[0]
+            .as_any()
+            .downcast_ref::<DictionaryVector>()
+            .unwrap();
+        assert_eq!(
+            &ArrowDataType::Dictionary(
+                Box::new(ArrowDataType::UInt32),
+                Box::new(ArrowDataType::Utf8)
+            ),
+            first.to_dictionary_array().data_type()
+        );
+        assert_eq!(4, first.values().len());
+
+        let schema = new_projected_schema().projected_user_schema().clone();
+        for (expect, chunk) in expect.iter().zip(&chunks) {
+            let dict = chunk.columns[0]
+                .as_any()
+                .downcast_ref::<DictionaryVector>()
+                .unwrap();
+            // Chunks share the same dictionary.
+            assert!(Arc::ptr_eq(first.values(), dict.values()));
+            assert_eq!(expect.columns, chunk.columns);
+            assert!(dict.memory_size() < expect.columns[0].memory_size());
+
+            // Outputs of protocol writers are built from the record batch.
+            let expect = RecordBatch::new(schema.clone(), expect.columns.clone()).unwrap();
+            let batch = RecordBatch::new(schema.clone(), chunk.columns.clone()).unwrap();
+            assert_eq!(expect.df_record_batch(), batch.df_record_batch());
+            assert_eq!(
+                expect.rows().collect::<Vec<_>>(),
+                batch.rows().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                serde_json::to_string(&expect).unwrap(),
+                serde_json::to_string(&batch).unwrap()
+            );
+        }
+    }
+
+    fn new_file_handle(start_ts_millis: i64, end_ts_millis: i64) -> FileHandle {
+        FileHandle::new(
+            FileMeta {
+                region_id: 0,
+                file_id: FileId::random(),
+                time_range: Some((
+                    Timestamp::new_millisecond(start_ts_millis),
+                    Timestamp::new_millisecond(end_ts_millis),
+                )),
+                level: 0,
+                file_size: 0,
+                num_rows: 0,
+                distinct_puts: false,
+                tag_dictionary_version: None,
+                tag_dictionary_generation: 0,
+                tombstone_sequence: None,
+            },
+            Arc::new(MockAccessLayer),
+            new_noop_file_purger(),
+        )
+    }
+
+    #[test]
+    fn test_file_in_ranges() {
+        let ranges = [
+            TimestampRange::with_unit(1000, 2000, TimeUnit::Millisecond).unwrap(),
+            TimestampRange::with_unit(3000, 4000, TimeUnit::Millisecond).unwrap(),
+        ];
+        // Files between the ranges are pruned although they are in the range covering them.
+        assert!(!ChunkReaderBuilder::file_in_ranges(
+            &new_file_handle(2000, 2999),
+            &ranges
+        ));
+        assert!(ChunkReaderBuilder::file_in_ranges(
+            &new_file_handle(1999, 2500),
+            &ranges
+        ));
+        assert!(ChunkReaderBuilder::file_in_ranges(
+            &new_file_handle(2500, 3000),
+            &ranges
+        ));
+        assert!(!ChunkReaderBuilder::file_in_ranges(
+            &new_file_handle(4000, 5000),
+            &ranges
+        ));
+
+        assert!(!ChunkReaderBuilder::file_in_ranges(
+            &new_file_handle(0, 100),
+            &[]
+        ));
+        assert!(ChunkReaderBuilder::file_in_ranges(
+            &new_file_handle(0, 100),
+            &[TimestampRange::min_to_max()]
+        ));
     }
 }
@@ -142,8 +142,9 @@ impl<S: LogStore> Picker for SimplePicker<S> {
                 wal: req.wal.clone(),
                 manifest: req.manifest.clone(),
                 expired_ssts,
-                sst_write_buffer_size: req.sst_write_buffer_size,
+                sst_write_options: req.sst_write_options.clone(),
                 compaction_time_window,
+                ttl_column: req.ttl_column.clone(),
             }));
         }
 
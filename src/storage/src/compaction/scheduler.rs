@@ -15,7 +15,6 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use common_base::readable_size::ReadableSize;
 use common_telemetry::{debug, error, info};
 use store_api::logstore::LogStore;
 use store_api::storage::RegionId;
@@ -30,7 +29,7 @@ use crate::region::{RegionWriterRef, SharedDataRef};
 use crate::scheduler::rate_limit::BoxedRateLimitToken;
 use crate::scheduler::{Handler, Request};
 use crate::schema::RegionSchemaRef;
-use crate::sst::AccessLayerRef;
+use crate::sst::{AccessLayerRef, WriteOptions};
 use crate::version::LevelMetasRef;
 use crate::wal::Wal;
 
@@ -60,10 +59,12 @@ pub struct CompactionRequestImpl<S: LogStore> {
     pub wal: Wal<S>,
     pub ttl: Option<Duration>,
     pub compaction_time_window: Option<i64>,
+    /// Column holding the expiry time of rows.
+    pub ttl_column: Option<String>,
     /// Compaction result sender.
     pub sender: Option<Sender<Result<()>>>,
 
-    pub sst_write_buffer_size: ReadableSize,
+    pub sst_write_options: WriteOptions,
 }
 
 impl<S: LogStore> CompactionRequestImpl<S> {
@@ -246,6 +246,11 @@ mod tests {
                 )),
                 level: 0,
                 file_size: 0,
+                num_rows: 0,
+                distinct_puts: false,
+                tag_dictionary_version: None,
+                tag_dictionary_generation: 0,
+                tombstone_sequence: None,
             },
             layer,
             file_purger,
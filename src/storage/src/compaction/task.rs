@@ -15,8 +15,8 @@
 use std::collections::HashSet;
 use std::fmt::{Debug, Formatter};
 
-use common_base::readable_size::ReadableSize;
 use common_telemetry::{debug, error};
+use common_time::Timestamp;
 use store_api::logstore::LogStore;
 use store_api::storage::RegionId;
 
@@ -24,11 +24,13 @@ use crate::compaction::writer::build_sst_reader;
 use crate::error::Result;
 use crate::manifest::action::RegionEdit;
 use crate::manifest::region::RegionManifest;
+use crate::read::RowExpiry;
 use crate::region::{RegionWriterRef, SharedDataRef};
 use crate::schema::RegionSchemaRef;
 use crate::sst::{
     AccessLayerRef, FileHandle, FileId, FileMeta, Level, Source, SstInfo, WriteOptions,
 };
+use crate::tombstone::{self, RangeTombstone};
 use crate::wal::Wal;
 
 const MAX_PARALLEL_COMPACTION: usize = 8;
@@ -47,8 +49,10 @@ pub struct CompactionTaskImpl<S: LogStore> {
     pub wal: Wal<S>,
     pub manifest: RegionManifest,
     pub expired_ssts: Vec<FileHandle>,
-    pub sst_write_buffer_size: ReadableSize,
+    pub sst_write_options: WriteOptions,
     pub compaction_time_window: Option<i64>,
+    /// Column holding the expiry time of rows, whose expired rows are dropped.
+    pub ttl_column: Option<String>,
 }
 
 impl<S: LogStore> Debug for CompactionTaskImpl<S> {
@@ -71,16 +75,39 @@ impl<S: LogStore> CompactionTaskImpl<S> {
         let mut futs = Vec::with_capacity(self.outputs.len());
         let mut compacted_inputs = HashSet::new();
         let region_id = self.shared_data.id();
+        let now = Timestamp::current_millis();
+        // Outputs drop the rows deleted by the tombstones at the start of the compaction.
+        let range_tombstones = self
+            .shared_data
+            .version_control
+            .current()
+            .range_tombstones()
+            .clone();
+        // Outputs rewrite the values of a full dictionary into a new generation, so the stale
+        // values are dropped once no SST references the old generation.
+        self.sst_layer.rotate_tag_dictionary().await?;
         for output in self.outputs.drain(..) {
             let schema = self.schema.clone();
             let sst_layer = self.sst_layer.clone();
-            let sst_write_buffer_size = self.sst_write_buffer_size;
+            let sst_write_options = self.sst_write_options.clone();
+            let row_expiry = self
+                .ttl_column
+                .clone()
+                .map(|ttl_column| RowExpiry { ttl_column, now });
+            let range_tombstones = range_tombstones.clone();
             compacted_inputs.extend(output.inputs.iter().map(FileHandle::meta));
 
             // TODO(hl): Maybe spawn to runtime to exploit in-job parallelism.
             futs.push(async move {
                 output
-                    .build(region_id, schema, sst_layer, sst_write_buffer_size)
+                    .build(
+                        region_id,
+                        schema,
+                        sst_layer,
+                        &sst_write_options,
+                        row_expiry,
+                        &range_tombstones,
+                    )
                     .await
             });
         }
@@ -114,8 +141,11 @@ impl<S: LogStore> CompactionTaskImpl<S> {
         let edit = RegionEdit {
             region_version,
             flushed_sequence: None,
+            flushed_at: None,
             files_to_add: Vec::from_iter(output.into_iter()),
             files_to_remove: Vec::from_iter(input.into_iter()),
+            tombstones_to_add: Vec::new(),
+            tombstones_to_remove: Vec::new(),
         };
         debug!(
             "Compacted region: {}, region edit: {:?}",
@@ -152,7 +182,18 @@ impl<S: LogStore> CompactionTask for CompactionTaskImpl<S> {
             .map_err(|e| {
                 error!(e; "Failed to update region manifest: {}", self.shared_data.name());
                 e
-            })
+            })?;
+
+        // The files of the generations are purged on the next open of the region, as the
+        // SSTs being flushed may still reference them.
+        let live = self
+            .shared_data
+            .version_control
+            .current()
+            .ssts()
+            .tag_dictionary_generations();
+        self.sst_layer.release_tag_dictionaries(&live).await;
+        Ok(())
     }
 }
 
@@ -176,7 +217,9 @@ impl CompactionOutput {
         region_id: RegionId,
         schema: RegionSchemaRef,
         sst_layer: AccessLayerRef,
-        sst_write_buffer_size: ReadableSize,
+        sst_write_options: &WriteOptions,
+        row_expiry: Option<RowExpiry>,
+        range_tombstones: &[RangeTombstone],
     ) -> Result<Option<FileMeta>> {
         let reader = build_sst_reader(
             schema,
@@ -184,28 +227,35 @@ impl CompactionOutput {
             &self.inputs,
             self.bucket_bound,
             self.bucket_bound + self.bucket,
+            row_expiry,
+            range_tombstones,
         )
         .await?;
+        let tombstone_sequence = tombstone::max_sequence(range_tombstones);
 
         let output_file_id = FileId::random();
-        let opts = WriteOptions {
-            sst_write_buffer_size,
-        };
-
         Ok(sst_layer
-            .write_sst(output_file_id, Source::Reader(reader), &opts)
+            .write_sst(output_file_id, Source::Reader(reader), sst_write_options)
             .await?
             .map(
                 |SstInfo {
                      time_range,
                      file_size,
-                     ..
+                     num_rows,
+                     distinct_puts,
+                     tag_dictionary_version,
+                     tag_dictionary_generation,
                  }| FileMeta {
                     region_id,
                     file_id: output_file_id,
                     time_range,
                     level: self.output_level,
                     file_size,
+                    num_rows,
+                    distinct_puts,
+                    tag_dictionary_version,
+                    tag_dictionary_generation,
+                    tombstone_sequence,
                 },
             ))
     }
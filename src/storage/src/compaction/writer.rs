@@ -18,16 +18,21 @@ use datafusion_expr::{BinaryExpr, Operator};
 
 use crate::chunk::{ChunkReaderBuilder, ChunkReaderImpl};
 use crate::error;
+use crate::read::RowExpiry;
 use crate::schema::RegionSchemaRef;
 use crate::sst::{AccessLayerRef, FileHandle};
+use crate::tombstone::RangeTombstone;
 
-/// Builds an SST reader that only reads rows within given time range.
+/// Builds an SST reader that only reads rows within given time range, dropping the rows
+/// expired by `row_expiry` and the rows deleted by `range_tombstones`.
 pub(crate) async fn build_sst_reader(
     schema: RegionSchemaRef,
     sst_layer: AccessLayerRef,
     files: &[FileHandle],
     lower_sec_inclusive: i64,
     upper_sec_exclusive: i64,
+    row_expiry: Option<RowExpiry>,
+    range_tombstones: &[RangeTombstone],
 ) -> error::Result<ChunkReaderImpl> {
     // TODO(hl): Schemas in different SSTs may differ, thus we should infer
     // timestamp column name from Parquet metadata.
@@ -45,6 +50,8 @@ pub(crate) async fn build_sst_reader(
             upper_sec_exclusive,
             &ts_col_name,
         )])
+        .row_expiry(row_expiry)
+        .range_tombstones(range_tombstones)
         .build()
         .await
 }
@@ -85,7 +92,6 @@ mod tests {
     use std::sync::atomic::{AtomicU64, Ordering};
     use std::sync::Arc;
 
-    use common_base::readable_size::ReadableSize;
     use common_test_util::temp_dir::create_temp_dir;
     use common_time::Timestamp;
     use datatypes::prelude::{LogicalTypeId, ScalarVector, ScalarVectorBuilder};
@@ -225,7 +231,10 @@ mod tests {
         let SstInfo {
             time_range,
             file_size,
-            ..
+            num_rows,
+            distinct_puts,
+            tag_dictionary_version,
+            tag_dictionary_generation,
         } = writer
             .write_sst(&sst::WriteOptions::default())
             .await
@@ -238,6 +247,11 @@ mod tests {
                 time_range,
                 level: 0,
                 file_size,
+                num_rows,
+                distinct_puts,
+                tag_dictionary_version,
+                tag_dictionary_generation,
+                tombstone_sequence: None,
             },
             Arc::new(crate::test_util::access_layer_util::MockAccessLayer {}),
             new_noop_file_purger(),
@@ -260,6 +274,8 @@ mod tests {
             files,
             lower_sec_inclusive,
             upper_sec_exclusive,
+            None,
+            &[],
         )
         .await
         .unwrap();
@@ -339,7 +355,7 @@ mod tests {
         sst_layer: AccessLayerRef,
     ) -> Vec<i64> {
         let mut timestamps = vec![];
-        let mut reader = build_sst_reader(schema, sst_layer, files, i64::MIN, i64::MAX)
+        let mut reader = build_sst_reader(schema, sst_layer, files, i64::MIN, i64::MAX, None, &[])
             .await
             .unwrap();
         while let Some(chunk) = reader.next_chunk().await.unwrap() {
@@ -403,19 +419,41 @@ mod tests {
         let sst_layer = Arc::new(FsAccessLayer::new("./", object_store.clone()));
         let input_files = vec![file2, file1];
 
-        let reader1 = build_sst_reader(schema.clone(), sst_layer.clone(), &input_files, 0, 3)
-            .await
-            .unwrap();
-        let reader2 = build_sst_reader(schema.clone(), sst_layer.clone(), &input_files, 3, 6)
-            .await
-            .unwrap();
-        let reader3 = build_sst_reader(schema.clone(), sst_layer.clone(), &input_files, 6, 10)
-            .await
-            .unwrap();
+        let reader1 = build_sst_reader(
+            schema.clone(),
+            sst_layer.clone(),
+            &input_files,
+            0,
+            3,
+            None,
+            &[],
+        )
+        .await
+        .unwrap();
+        let reader2 = build_sst_reader(
+            schema.clone(),
+            sst_layer.clone(),
+            &input_files,
+            3,
+            6,
+            None,
+            &[],
+        )
+        .await
+        .unwrap();
+        let reader3 = build_sst_reader(
+            schema.clone(),
+            sst_layer.clone(),
+            &input_files,
+            6,
+            10,
+            None,
+            &[],
+        )
+        .await
+        .unwrap();
 
-        let opts = WriteOptions {
-            sst_write_buffer_size: ReadableSize::mb(8),
-        };
+        let opts = WriteOptions::default();
         let s1 = ParquetWriter::new(
             &output_file_ids[0].as_parquet(),
             Source::Reader(reader1),
@@ -478,6 +516,11 @@ mod tests {
                         level: 1,
                         time_range: None,
                         file_size: 0,
+                        num_rows: 0,
+                        distinct_puts: false,
+                        tag_dictionary_version: None,
+                        tag_dictionary_generation: 0,
+                        tombstone_sequence: None,
                     },
                     Arc::new(crate::test_util::access_layer_util::MockAccessLayer {}),
                     new_noop_file_purger(),
@@ -491,4 +534,97 @@ mod tests {
 
         assert_eq!(timestamps_in_outputs, timestamps_in_inputs);
     }
+
+    /// Writes rows with their expiry time into a SST file and rewrites it, checks the
+    /// rewritten file only contains unexpired rows.
+    #[tokio::test]
+    async fn test_sst_rewrite_drop_expired_rows() {
+        let dir = create_temp_dir("write_parquet");
+        let path = dir.path().to_str().unwrap();
+        let mut builder = Fs::default();
+        builder.root(path);
+        let object_store = ObjectStore::new(builder).unwrap().finish();
+
+        let desc = RegionDescBuilder::new("test")
+            .enable_version_column(false)
+            .push_field_column(("expires_at", LogicalTypeId::TimestampMillisecond, true))
+            .build();
+        let metadata: RegionMetadata = desc.try_into().unwrap();
+        let schema = metadata.schema().clone();
+
+        let memtable = DefaultMemtableBuilder::default().build(schema.clone());
+        let ts = [1000, 2000, 3000, 4000, 5000];
+        let expires_at = [Some(2000), None, Some(3000), Some(4000), None];
+        let kvs = KeyValues {
+            sequence: 0,
+            op_type: OpType::Put,
+            start_index_in_batch: 0,
+            keys: vec![Arc::new(TimestampMillisecondVector::from_values(ts)) as _],
+            values: vec![Arc::new(TimestampMillisecondVector::from(expires_at.to_vec())) as _],
+        };
+        memtable.write(&kvs).unwrap();
+
+        let input_file_id = FileId::random();
+        let iter = memtable.iter(&IterContext::default()).unwrap();
+        let input = ParquetWriter::new(
+            &input_file_id.as_parquet(),
+            Source::Iter(iter),
+            object_store.clone(),
+        )
+        .write_sst(&sst::WriteOptions::default())
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(5, input.num_rows);
+
+        let file_handle = |file_id| {
+            FileHandle::new(
+                FileMeta {
+                    region_id: 0,
+                    file_id,
+                    level: 1,
+                    time_range: None,
+                    file_size: 0,
+                    num_rows: 0,
+                    distinct_puts: false,
+                    tag_dictionary_version: None,
+                    tag_dictionary_generation: 0,
+                    tombstone_sequence: None,
+                },
+                Arc::new(crate::test_util::access_layer_util::MockAccessLayer {}),
+                new_noop_file_purger(),
+            )
+        };
+        let sst_layer = Arc::new(FsAccessLayer::new("./", object_store.clone()));
+        let row_expiry = RowExpiry {
+            ttl_column: "expires_at".to_string(),
+            now: Timestamp::new_millisecond(3000),
+        };
+        let reader = build_sst_reader(
+            schema.clone(),
+            sst_layer.clone(),
+            &[file_handle(input_file_id)],
+            i64::MIN,
+            i64::MAX,
+            Some(row_expiry),
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let output_file_id = FileId::random();
+        let output = ParquetWriter::new(
+            &output_file_id.as_parquet(),
+            Source::Reader(reader),
+            object_store.clone(),
+        )
+        .write_sst(&sst::WriteOptions::default())
+        .await
+        .unwrap()
+        .unwrap();
+        // Rows expired at or before 3000 are dropped, rows without expiry time are kept.
+        assert_eq!(3, output.num_rows);
+        let timestamps = read_file(&[file_handle(output_file_id)], schema, sst_layer).await;
+        assert_eq!(vec![2000, 4000, 5000], timestamps);
+    }
 }
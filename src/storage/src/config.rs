@@ -17,6 +17,9 @@
 use std::time::Duration;
 
 use common_base::readable_size::ReadableSize;
+use parquet::basic::{Compression, Encoding, ZstdLevel};
+
+use crate::sst::WriteOptions;
 
 #[derive(Debug, Clone)]
 pub struct EngineConfig {
@@ -26,6 +29,24 @@ pub struct EngineConfig {
     pub max_files_in_l0: usize,
     pub max_purge_tasks: usize,
     pub sst_write_buffer_size: ReadableSize,
+    /// Compression of the SST columns without their own compression option.
+    pub sst_compression: Compression,
+    /// Encoding of the SST columns without their own encoding option.
+    pub sst_encoding: Encoding,
+    /// Sizes the flush threshold of regions without `write_buffer_size` by their ingest rate
+    /// if set, or uses the default write buffer size otherwise.
+    pub adaptive_flush: Option<AdaptiveFlushConfig>,
+    /// Whether regions can be created with the WAL disabled.
+    pub allow_wal_disabled: bool,
+    /// Writes the string tags of SSTs by a dictionary shared by all SSTs of the region if set.
+    /// SSTs written by the dictionary are still readable after it's unset.
+    pub tag_dictionary: Option<TagDictionaryConfig>,
+    /// How long the snapshots of regions are readable by time-travel reads, which are
+    /// disabled if it's zero.
+    pub time_travel_retention: Duration,
+    /// Max time a write waits for the previous flush of its region to finish before it's
+    /// rejected as throttled, the write waits until the flush finishes if it's `None`.
+    pub max_write_stall: Option<Duration>,
 }
 
 impl Default for EngineConfig {
@@ -37,6 +58,69 @@ impl Default for EngineConfig {
             max_files_in_l0: 8,
             max_purge_tasks: 32,
             sst_write_buffer_size: ReadableSize::mb(8),
+            sst_compression: Compression::ZSTD(ZstdLevel::default()),
+            sst_encoding: Encoding::PLAIN,
+            adaptive_flush: None,
+            allow_wal_disabled: true,
+            tag_dictionary: None,
+            time_travel_retention: Duration::from_secs(10 * 60),
+            max_write_stall: None,
+        }
+    }
+}
+
+impl EngineConfig {
+    /// Returns the options of writing SSTs by flushes and compactions.
+    pub fn sst_write_options(&self) -> WriteOptions {
+        WriteOptions {
+            sst_write_buffer_size: self.sst_write_buffer_size,
+            compression: self.sst_compression,
+            encoding: self.sst_encoding,
+        }
+    }
+}
+
+/// Config of the adaptive flush strategy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptiveFlushConfig {
+    /// Expected interval between two flushes of a region, which the flush threshold is sized
+    /// for by the ingest rate of the region.
+    pub target_flush_interval: Duration,
+    /// Lower bound of the flush threshold.
+    pub min_write_buffer_size: ReadableSize,
+    /// Upper bound of the flush threshold.
+    pub max_write_buffer_size: ReadableSize,
+    /// Max age of unflushed data. Regions holding older data are flushed even if they are
+    /// idle, so their WAL can be purged.
+    pub max_memtable_age: Duration,
+    /// Interval to check the age of unflushed data.
+    pub age_check_interval: Duration,
+}
+
+impl Default for AdaptiveFlushConfig {
+    fn default() -> Self {
+        Self {
+            target_flush_interval: Duration::from_secs(10 * 60),
+            min_write_buffer_size: ReadableSize::mb(4),
+            max_write_buffer_size: ReadableSize::mb(256),
+            max_memtable_age: Duration::from_secs(60 * 60),
+            age_check_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Config of the tag dictionary shared by the SSTs of a region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagDictionaryConfig {
+    /// Max number of values of each column in the dictionary. Values beyond it are stored
+    /// inline in the SSTs.
+    pub max_values_per_column: usize,
+}
+
+impl Default for TagDictionaryConfig {
+    fn default() -> Self {
+        Self {
+            max_values_per_column: 65536,
         }
     }
 }
@@ -19,7 +19,7 @@ use std::time::Duration;
 use async_trait::async_trait;
 use common_telemetry::logging::debug;
 use object_store::{util, ObjectStore};
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
 use store_api::logstore::LogStore;
 use store_api::manifest::Manifest;
 use store_api::storage::{
@@ -31,12 +31,16 @@ use crate::compaction::CompactionSchedulerRef;
 use crate::config::EngineConfig;
 use crate::error::{self, Error, Result};
 use crate::file_purger::{FilePurgeHandler, FilePurgerRef};
-use crate::flush::{FlushSchedulerImpl, FlushSchedulerRef, FlushStrategyRef, SizeBasedStrategy};
+use crate::flush::{
+    AdaptiveStrategy, FlushSchedulerImpl, FlushSchedulerRef, FlushStrategyRef, SizeBasedStrategy,
+    SystemClock,
+};
 use crate::manifest::region::RegionManifest;
 use crate::memtable::{DefaultMemtableBuilder, MemtableBuilderRef};
 use crate::metadata::RegionMetadata;
 use crate::region::{RegionImpl, StoreConfig};
 use crate::scheduler::{LocalScheduler, SchedulerConfig};
+use crate::sst::dictionary::TAG_DICTIONARY_DIR;
 use crate::sst::FsAccessLayer;
 
 /// [StorageEngine] implementation.
@@ -120,6 +124,15 @@ pub fn region_manifest_dir(parent_dir: &str, region_name: &str) -> String {
     format!("{parent_dir}{region_name}/manifest/")
 }
 
+/// Generate the dir of the tag dictionary of a region, which is under its sst dir.
+#[inline]
+pub fn region_tag_dictionary_dir(parent_dir: &str, region_name: &str) -> String {
+    format!(
+        "{}{TAG_DICTIONARY_DIR}",
+        region_sst_dir(&util::normalize_dir(parent_dir), region_name)
+    )
+}
+
 /// A slot for region in the engine.
 ///
 /// Also used as a placeholder in the region map when the region isn't ready, e.g. during
@@ -299,6 +312,8 @@ impl<S: LogStore> EngineInner<S> {
                 &self.config,
                 opts.ttl,
                 opts.compaction_time_window,
+                opts.wal_disabled,
+                opts.ttl_column.clone(),
             )
             .await?;
 
@@ -328,6 +343,13 @@ impl<S: LogStore> EngineInner<S> {
         let region_name = descriptor.name.clone();
         let mut guard = SlotGuard::new(&region_name, &self.regions);
 
+        ensure!(
+            !opts.wal_disabled || self.config.allow_wal_disabled,
+            error::WalDisabledNotAllowedSnafu {
+                region: &region_name,
+            }
+        );
+
         let metadata: RegionMetadata =
             descriptor
                 .try_into()
@@ -342,6 +364,8 @@ impl<S: LogStore> EngineInner<S> {
                 &self.config,
                 opts.ttl,
                 opts.compaction_time_window,
+                opts.wal_disabled,
+                opts.ttl_column.clone(),
             )
             .await?;
 
@@ -363,6 +387,7 @@ impl<S: LogStore> EngineInner<S> {
         slot.get_ready_region()
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn region_store_config(
         &self,
         parent_dir: &str,
@@ -371,11 +396,16 @@ impl<S: LogStore> EngineInner<S> {
         config: &EngineConfig,
         ttl: Option<Duration>,
         compaction_time_window: Option<i64>,
+        wal_disabled: bool,
+        ttl_column: Option<String>,
     ) -> Result<StoreConfig<S>> {
         let parent_dir = util::normalize_dir(parent_dir);
 
         let sst_dir = &region_sst_dir(&parent_dir, region_name);
-        let sst_layer = Arc::new(FsAccessLayer::new(sst_dir, self.object_store.clone()));
+        let sst_layer = Arc::new(
+            FsAccessLayer::new(sst_dir, self.object_store.clone())
+                .with_tag_dictionary(config.tag_dictionary.as_ref()),
+        );
         let manifest_dir = region_manifest_dir(&parent_dir, region_name);
         let manifest = RegionManifest::with_checkpointer(
             &manifest_dir,
@@ -385,9 +415,16 @@ impl<S: LogStore> EngineInner<S> {
         );
         manifest.start().await?;
 
-        let flush_strategy = write_buffer_size
-            .map(|size| Arc::new(SizeBasedStrategy::new(size)) as Arc<_>)
-            .unwrap_or_else(|| self.flush_strategy.clone());
+        let flush_strategy = match (write_buffer_size, &config.adaptive_flush) {
+            (Some(size), _) => Arc::new(SizeBasedStrategy::new(size)) as Arc<_>,
+            // The adaptive strategy keeps the ingest rate of the region, so each region has its
+            // own one.
+            (None, Some(adaptive_flush)) => Arc::new(AdaptiveStrategy::new(
+                adaptive_flush.clone(),
+                Arc::new(SystemClock),
+            )) as Arc<_>,
+            (None, None) => self.flush_strategy.clone(),
+        };
 
         Ok(StoreConfig {
             log_store: self.log_store.clone(),
@@ -401,6 +438,8 @@ impl<S: LogStore> EngineInner<S> {
             file_purger: self.file_purger.clone(),
             ttl,
             compaction_time_window,
+            wal_disabled,
+            ttl_column,
         })
     }
 }
@@ -15,9 +15,11 @@
 use std::any::Any;
 use std::io::Error as IoError;
 use std::str::Utf8Error;
+use std::time::Duration;
 
 use common_error::prelude::*;
 use common_runtime::error::Error as RuntimeError;
+use common_time::Timestamp;
 use datatypes::arrow::error::ArrowError;
 use datatypes::prelude::ConcreteDataType;
 use serde_json::error::Error as JsonError;
@@ -137,6 +139,17 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Write stalled longer than {:?} by the flush of region {}",
+        timeout,
+        region
+    ))]
+    WriteStall {
+        region: String,
+        timeout: Duration,
+        location: Location,
+    },
+
     #[snafu(display("Task already cancelled"))]
     Cancelled { location: Location },
 
@@ -297,6 +310,12 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Columns of the write batch mismatch the schema of the region with the same version {}, the region may be altered concurrently",
+        version
+    ))]
+    SchemaVersionMismatch { version: u32, location: Location },
+
     #[snafu(display("Column {} not in schema with version {}", column, version))]
     NotInSchemaToCompat {
         column: String,
@@ -332,6 +351,12 @@ pub enum Error {
         source: datatypes::error::Error,
     },
 
+    #[snafu(display("Failed to encode tag dictionary, source: {}", source))]
+    EncodeTagDictionary {
+        #[snafu(backtrace)]
+        source: datatypes::error::Error,
+    },
+
     #[snafu(display("Unknown column {}", name))]
     UnknownColumn { name: String, location: Location },
 
@@ -422,6 +447,28 @@ pub enum Error {
         source: RuntimeError,
     },
 
+    #[snafu(display(
+        "Failed to start flush by age task, region: {}, source: {}",
+        region,
+        source
+    ))]
+    StartFlushByAgeTask {
+        region: String,
+        #[snafu(backtrace)]
+        source: RuntimeError,
+    },
+
+    #[snafu(display(
+        "Failed to stop flush by age task, region: {}, source: {}",
+        region,
+        source
+    ))]
+    StopFlushByAgeTask {
+        region: String,
+        #[snafu(backtrace)]
+        source: RuntimeError,
+    },
+
     #[snafu(display("Failed to stop scheduler, source: {}", source))]
     StopScheduler {
         source: JoinError,
@@ -448,6 +495,28 @@ pub enum Error {
         region_id: RegionId,
         source: tokio::sync::oneshot::error::RecvError,
     },
+
+    #[snafu(display("Disabling WAL is not allowed by the server, region: {}", region))]
+    WalDisabledNotAllowed { region: String, location: Location },
+
+    #[snafu(display("Invalid region tag dictionary, {}", msg))]
+    InvalidTagDictionary { msg: String, location: Location },
+
+    #[snafu(display("Invalid delete range request, {}", msg))]
+    InvalidDeleteRange { msg: String, location: Location },
+
+    #[snafu(display(
+        "Snapshot of region {} as of {} is gone, {}",
+        region,
+        Timestamp::new_millisecond(*as_of).to_iso8601_string(),
+        reason
+    ))]
+    SnapshotGone {
+        region: String,
+        as_of: i64,
+        reason: String,
+        location: Location,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -468,7 +537,10 @@ impl ErrorExt for Error {
             | TypeMismatch { .. }
             | HasNull { .. }
             | UnequalLengths { .. }
-            | MoreColumnThanExpected { .. } => StatusCode::InvalidArguments,
+            | MoreColumnThanExpected { .. }
+            | WalDisabledNotAllowed { .. }
+            | SnapshotGone { .. }
+            | InvalidDeleteRange { .. } => StatusCode::InvalidArguments,
 
             Utf8 { .. }
             | EncodeJson { .. }
@@ -493,7 +565,8 @@ impl ErrorExt for Error {
             | DecodeArrow { .. }
             | EncodeArrow { .. }
             | ManifestCheckpoint { .. }
-            | ParseSchema { .. } => StatusCode::Unexpected,
+            | ParseSchema { .. }
+            | InvalidTagDictionary { .. } => StatusCode::Unexpected,
 
             WriteParquet { .. }
             | ReadObject { .. }
@@ -507,7 +580,8 @@ impl ErrorExt for Error {
             | ManifestProtocolForbidWrite { .. }
             | ReadParquet { .. }
             | InvalidRegionState { .. }
-            | ReadWal { .. } => StatusCode::StorageUnavailable,
+            | ReadWal { .. }
+            | SchemaVersionMismatch { .. } => StatusCode::StorageUnavailable,
 
             UnknownColumn { .. } => StatusCode::TableColumnNotFound,
 
@@ -517,8 +591,10 @@ impl ErrorExt for Error {
             PushBatch { source, .. } => source.status_code(),
             CreateDefault { source, .. } => source.status_code(),
             ConvertChunk { source, .. } => source.status_code(),
+            EncodeTagDictionary { source, .. } => source.status_code(),
             MarkWalObsolete { source, .. } => source.status_code(),
             DecodeParquetTimeRange { .. } => StatusCode::Unexpected,
+            WriteStall { .. } => StatusCode::RuntimeResourcesExhausted,
             RateLimited { .. } | StopScheduler { .. } | CompactTaskCancel { .. } => {
                 StatusCode::Internal
             }
@@ -526,6 +602,8 @@ impl ErrorExt for Error {
 
             StartManifestGcTask { .. }
             | StopManifestGcTask { .. }
+            | StartFlushByAgeTask { .. }
+            | StopFlushByAgeTask { .. }
             | IllegalSchedulerState { .. } => StatusCode::Unexpected,
 
             TtlCalculation { source, .. } => source.status_code(),
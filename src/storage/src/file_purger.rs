@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use common_telemetry::{debug, error};
@@ -27,6 +28,9 @@ pub struct FilePurgeRequest {
     pub region_id: RegionId,
     pub file_id: FileId,
     pub sst_layer: AccessLayerRef,
+    pub file_size: u64,
+    /// Bytes pending purge of the region, released once the file is deleted.
+    pub pending_purge_bytes: Arc<AtomicU64>,
 }
 
 impl Request for FilePurgeRequest {
@@ -56,6 +60,8 @@ impl Handler for FilePurgeHandler {
                 req.file_id.as_parquet(), req.region_id);
             e
         })?;
+        req.pending_purge_bytes
+            .fetch_sub(req.file_size, Ordering::Relaxed);
         debug!(
             "Successfully deleted SST file: {}, region: {}",
             req.file_id.as_parquet(),
@@ -160,6 +166,11 @@ mod tests {
                     time_range: None,
                     level: 0,
                     file_size: sst_info.file_size,
+                    num_rows: sst_info.num_rows,
+                    distinct_puts: sst_info.distinct_puts,
+                    tag_dictionary_version: sst_info.tag_dictionary_version,
+                    tag_dictionary_generation: sst_info.tag_dictionary_generation,
+                    tombstone_sequence: None,
                 },
                 layer.clone(),
                 file_purger,
@@ -182,12 +193,15 @@ mod tests {
             SchedulerConfig::default(),
             NoopFilePurgeHandler,
         ));
-        let (_file, path, layer) =
+        let (file, path, layer) =
             create_sst_file(object_store.clone(), sst_file_id, noop_file_purger).await;
+        let pending_purge_bytes = Arc::new(AtomicU64::new(file.file_size()));
         let request = FilePurgeRequest {
             region_id: 0,
             file_id: sst_file_id,
             sst_layer: layer,
+            file_size: file.file_size(),
+            pending_purge_bytes: pending_purge_bytes.clone(),
         };
 
         let handler = FilePurgeHandler;
@@ -203,6 +217,7 @@ mod tests {
             .await
             .unwrap();
         assert!(!exists);
+        assert_eq!(0, pending_purge_bytes.load(Ordering::Relaxed));
     }
 
     #[tokio::test]
@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod adaptive;
+
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use common_telemetry::logging;
@@ -25,6 +28,7 @@ use store_api::storage::SequenceNumber;
 use crate::background::{Context, Job, JobHandle, JobPoolRef};
 use crate::config::EngineConfig;
 use crate::error::{CancelledSnafu, Result};
+pub use crate::flush::adaptive::{AdaptiveStrategy, Clock, ClockRef, SystemClock};
 use crate::manifest::action::*;
 use crate::manifest::region::RegionManifest;
 use crate::memtable::{IterContext, MemtableId, MemtableRef};
@@ -42,6 +46,24 @@ pub trait FlushStrategy: Send + Sync + std::fmt::Debug {
         bytes_mutable: usize,
         bytes_total: usize,
     ) -> bool;
+
+    /// Returns whether to flush the region by the age of its unflushed data. Unlike
+    /// [FlushStrategy::should_flush], which is only checked on writes, this is checked every
+    /// [FlushStrategy::age_check_interval] so idle regions are flushed too.
+    fn should_flush_by_age(&self, _shared: &SharedDataRef, _bytes_mutable: usize) -> bool {
+        false
+    }
+
+    /// Returns the interval to check [FlushStrategy::should_flush_by_age], or `None` to never
+    /// check it.
+    fn age_check_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns the current flush threshold of the region in bytes, if any.
+    fn flush_threshold_bytes(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub type FlushStrategyRef = Arc<dyn FlushStrategy>;
@@ -125,6 +147,10 @@ impl FlushStrategy for SizeBasedStrategy {
 
         should_flush
     }
+
+    fn flush_threshold_bytes(&self) -> Option<usize> {
+        Some(self.max_write_buffer_size)
+    }
 }
 
 #[async_trait]
@@ -163,6 +189,8 @@ pub struct FlushJob<S: LogStore> {
     pub memtables: Vec<MemtableRef>,
     /// Last sequence of data to be flushed.
     pub flush_sequence: SequenceNumber,
+    /// Time in millis when the memtables are frozen.
+    pub flushed_at: i64,
     /// Shared data of region to be flushed.
     pub shared: SharedDataRef,
     /// Sst access layer of the region.
@@ -204,9 +232,7 @@ impl<S: LogStore> FlushJob<S> {
             // TODO(hl): Check if random file name already exists in meta.
             let iter = m.iter(&iter_ctx)?;
             let sst_layer = self.sst_layer.clone();
-            let write_options = WriteOptions {
-                sst_write_buffer_size: self.engine_config.sst_write_buffer_size,
-            };
+            let write_options = self.engine_config.sst_write_options();
             futures.push(async move {
                 Ok(sst_layer
                     .write_sst(file_id, Source::Iter(iter), &write_options)
@@ -215,13 +241,21 @@ impl<S: LogStore> FlushJob<S> {
                         |SstInfo {
                              time_range,
                              file_size,
-                             ..
+                             num_rows,
+                             distinct_puts,
+                             tag_dictionary_version,
+                             tag_dictionary_generation,
                          }| FileMeta {
                             region_id,
                             file_id,
                             time_range,
                             level: 0,
                             file_size,
+                            num_rows,
+                            distinct_puts,
+                            tag_dictionary_version,
+                            tag_dictionary_generation,
+                            tombstone_sequence: None,
                         },
                     ))
             });
@@ -241,8 +275,11 @@ impl<S: LogStore> FlushJob<S> {
         let edit = RegionEdit {
             region_version: self.shared.version_control.metadata().version(),
             flushed_sequence: Some(self.flush_sequence),
+            flushed_at: Some(self.flushed_at),
             files_to_add: file_metas.to_vec(),
             files_to_remove: Vec::default(),
+            tombstones_to_add: Vec::new(),
+            tombstones_to_remove: Vec::new(),
         };
 
         self.writer
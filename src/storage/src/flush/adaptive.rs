@@ -0,0 +1,328 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use common_telemetry::logging;
+pub use common_time::util::{Clock, ClockRef, SystemClock};
+
+use crate::config::AdaptiveFlushConfig;
+use crate::flush::{get_mutable_limitation, FlushStrategy, DEFAULT_WRITE_BUFFER_SIZE};
+use crate::region::SharedDataRef;
+
+/// Min duration of the windows the ingest rate is sampled over.
+const RATE_WINDOW_MILLIS: i64 = 10_000;
+/// Weight of the latest sample in the moving average of the ingest rate.
+const RATE_SMOOTHING: f64 = 0.3;
+
+/// Flush strategy of a region that sizes its flush threshold by a moving average of the ingest
+/// rate, so the region flushes about every [AdaptiveFlushConfig::target_flush_interval]. It also
+/// flushes the region once its unflushed data is older than
+/// [AdaptiveFlushConfig::max_memtable_age].
+///
+/// Unlike [SizeBasedStrategy](crate::flush::SizeBasedStrategy), it keeps the state of one region
+/// and can't be shared between regions.
+#[derive(Debug)]
+pub struct AdaptiveStrategy {
+    config: AdaptiveFlushConfig,
+    clock: ClockRef,
+    /// Current flush threshold, also known as the write buffer size.
+    threshold: AtomicUsize,
+    state: Mutex<IngestState>,
+}
+
+#[derive(Debug)]
+struct IngestState {
+    /// Bytes of the mutable memtables seen by the last check.
+    last_bytes_mutable: usize,
+    /// Start of the current sampling window.
+    window_start_millis: i64,
+    /// Bytes written in the current sampling window.
+    window_bytes: usize,
+    /// Moving average of the ingest rate, `None` until the first window ends.
+    bytes_per_sec: Option<f64>,
+    /// When the unflushed data was first seen, `None` if there is no unflushed data.
+    unflushed_since_millis: Option<i64>,
+}
+
+impl IngestState {
+    /// Forgets the mutable memtables, which are going to be frozen to flush.
+    fn reset_mutable(&mut self) {
+        self.last_bytes_mutable = 0;
+        self.unflushed_since_millis = None;
+    }
+}
+
+impl AdaptiveStrategy {
+    pub fn new(config: AdaptiveFlushConfig, clock: ClockRef) -> Self {
+        let now_millis = clock.now_millis();
+        let threshold = clamp_threshold(&config, DEFAULT_WRITE_BUFFER_SIZE as f64);
+        Self {
+            config,
+            clock,
+            threshold: AtomicUsize::new(threshold),
+            state: Mutex::new(IngestState {
+                last_bytes_mutable: 0,
+                window_start_millis: now_millis,
+                window_bytes: 0,
+                bytes_per_sec: None,
+                unflushed_since_millis: None,
+            }),
+        }
+    }
+
+    /// Accounts the bytes written since the last check and returns the flush threshold,
+    /// resized by the ingest rate if a sampling window ends.
+    fn observe(&self, state: &mut IngestState, bytes_mutable: usize, now_millis: i64) -> usize {
+        // The mutable memtables shrink only if they are frozen, and the new ones start empty.
+        let written = bytes_mutable
+            .checked_sub(state.last_bytes_mutable)
+            .unwrap_or(bytes_mutable);
+        state.last_bytes_mutable = bytes_mutable;
+        state.window_bytes += written;
+
+        if bytes_mutable == 0 {
+            state.unflushed_since_millis = None;
+        } else if state.unflushed_since_millis.is_none() {
+            state.unflushed_since_millis = Some(now_millis);
+        }
+
+        let elapsed_millis = now_millis - state.window_start_millis;
+        if elapsed_millis >= RATE_WINDOW_MILLIS {
+            let sample = state.window_bytes as f64 * 1000.0 / elapsed_millis as f64;
+            let bytes_per_sec = match state.bytes_per_sec {
+                Some(average) => RATE_SMOOTHING * sample + (1.0 - RATE_SMOOTHING) * average,
+                None => sample,
+            };
+            state.bytes_per_sec = Some(bytes_per_sec);
+            state.window_start_millis = now_millis;
+            state.window_bytes = 0;
+
+            let threshold = clamp_threshold(
+                &self.config,
+                bytes_per_sec * self.config.target_flush_interval.as_secs_f64(),
+            );
+            self.threshold.store(threshold, Ordering::Relaxed);
+        }
+
+        self.threshold.load(Ordering::Relaxed)
+    }
+}
+
+fn clamp_threshold(config: &AdaptiveFlushConfig, threshold: f64) -> usize {
+    let min = config.min_write_buffer_size.as_bytes() as usize;
+    let max = config.max_write_buffer_size.as_bytes() as usize;
+    (threshold as usize).clamp(min, max.max(min))
+}
+
+impl FlushStrategy for AdaptiveStrategy {
+    fn should_flush(
+        &self,
+        shared: &SharedDataRef,
+        bytes_mutable: usize,
+        bytes_total: usize,
+    ) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let threshold = self.observe(&mut state, bytes_mutable, self.clock.now_millis());
+
+        // Same as the `SizeBasedStrategy` with a write buffer size of `threshold`.
+        let should_flush = bytes_mutable > get_mutable_limitation(threshold)
+            || (bytes_total >= threshold && bytes_mutable >= threshold / 2);
+        if should_flush {
+            logging::info!(
+                "Region should flush, region: {}, bytes_mutable: {}, bytes_total: {}, \
+                 adaptive_write_buffer_size: {}, bytes_per_sec: {:?} .",
+                shared.name(),
+                bytes_mutable,
+                bytes_total,
+                threshold,
+                state.bytes_per_sec
+            );
+            state.reset_mutable();
+        }
+
+        should_flush
+    }
+
+    fn should_flush_by_age(&self, shared: &SharedDataRef, bytes_mutable: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let now_millis = self.clock.now_millis();
+        let _ = self.observe(&mut state, bytes_mutable, now_millis);
+
+        let max_age_millis = self.config.max_memtable_age.as_millis() as i64;
+        let should_flush = state
+            .unflushed_since_millis
+            .map(|since| now_millis - since >= max_age_millis)
+            .unwrap_or(false);
+        if should_flush {
+            logging::info!(
+                "Region should flush by age, region: {}, bytes_mutable: {}, \
+                 max_memtable_age: {:?} .",
+                shared.name(),
+                bytes_mutable,
+                self.config.max_memtable_age
+            );
+            state.reset_mutable();
+        }
+
+        should_flush
+    }
+
+    fn age_check_interval(&self) -> Option<Duration> {
+        Some(self.config.age_check_interval)
+    }
+
+    fn flush_threshold_bytes(&self) -> Option<usize> {
+        Some(self.threshold.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicI64;
+    use std::sync::Arc;
+
+    use common_base::readable_size::ReadableSize;
+
+    use super::*;
+    use crate::region::SharedData;
+
+    #[derive(Debug, Default)]
+    struct MockClock {
+        now_millis: AtomicI64,
+    }
+
+    impl MockClock {
+        fn advance(&self, duration: Duration) {
+            let _ = self
+                .now_millis
+                .fetch_add(duration.as_millis() as i64, Ordering::Relaxed);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now_millis(&self) -> i64 {
+            self.now_millis.load(Ordering::Relaxed)
+        }
+    }
+
+    fn new_config() -> AdaptiveFlushConfig {
+        AdaptiveFlushConfig {
+            target_flush_interval: Duration::from_secs(600),
+            min_write_buffer_size: ReadableSize::mb(1),
+            max_write_buffer_size: ReadableSize::mb(512),
+            max_memtable_age: Duration::from_secs(3600),
+            age_check_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Writes `bytes_per_sec` every second for `secs` seconds, and returns the number of
+    /// flushes.
+    fn ingest(
+        strategy: &AdaptiveStrategy,
+        clock: &MockClock,
+        shared: &SharedDataRef,
+        bytes_per_sec: usize,
+        secs: usize,
+    ) -> usize {
+        let mut bytes_mutable = 0;
+        let mut flushes = 0;
+        for _ in 0..secs {
+            clock.advance(Duration::from_secs(1));
+            if strategy.should_flush(shared, bytes_mutable, bytes_mutable) {
+                flushes += 1;
+                bytes_mutable = 0;
+            }
+            bytes_mutable += bytes_per_sec;
+        }
+        flushes
+    }
+
+    #[test]
+    fn test_threshold_converges_to_ingest_rate() {
+        let shared = Arc::new(SharedData::new_for_test("adaptive-flush"));
+
+        let high_clock = Arc::new(MockClock::default());
+        let high = AdaptiveStrategy::new(new_config(), high_clock.clone());
+        // 256KB/s, expected to flush every 10 minutes with a 150MB threshold.
+        let _ = ingest(&high, &high_clock, &shared, 256 * 1024, 3600);
+
+        let low_clock = Arc::new(MockClock::default());
+        let low = AdaptiveStrategy::new(new_config(), low_clock.clone());
+        // 1KB/s, bounded by the 1MB min threshold.
+        let _ = ingest(&low, &low_clock, &shared, 1024, 3600);
+
+        let high_threshold = high.flush_threshold_bytes().unwrap();
+        let low_threshold = low.flush_threshold_bytes().unwrap();
+        assert!(
+            high_threshold > low_threshold,
+            "{high_threshold} <= {low_threshold}"
+        );
+        let expect = 256 * 1024 * 600;
+        assert!(
+            high_threshold > expect * 9 / 10 && high_threshold < expect * 11 / 10,
+            "{high_threshold}"
+        );
+        assert_eq!(ReadableSize::mb(1).as_bytes() as usize, low_threshold);
+
+        // Once converged, flushes about every target flush interval.
+        let flushes = ingest(&high, &high_clock, &shared, 256 * 1024, 3600);
+        assert!((5..=8).contains(&flushes), "{flushes}");
+    }
+
+    #[test]
+    fn test_threshold_bounded() {
+        let shared = Arc::new(SharedData::new_for_test("adaptive-flush"));
+        let clock = Arc::new(MockClock::default());
+        let strategy = AdaptiveStrategy::new(new_config(), clock.clone());
+        assert_eq!(
+            DEFAULT_WRITE_BUFFER_SIZE,
+            strategy.flush_threshold_bytes().unwrap()
+        );
+
+        let _ = ingest(&strategy, &clock, &shared, 64 * 1024 * 1024, 600);
+        assert_eq!(
+            ReadableSize::mb(512).as_bytes() as usize,
+            strategy.flush_threshold_bytes().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_flush_idle_region_by_age() {
+        let shared = Arc::new(SharedData::new_for_test("adaptive-flush"));
+        let clock = Arc::new(MockClock::default());
+        let strategy = AdaptiveStrategy::new(new_config(), clock.clone());
+
+        // Nothing to flush.
+        clock.advance(Duration::from_secs(7200));
+        assert!(!strategy.should_flush_by_age(&shared, 0));
+
+        // A small write, far below the threshold.
+        assert!(!strategy.should_flush(&shared, 0, 0));
+        clock.advance(Duration::from_secs(1));
+        assert!(!strategy.should_flush_by_age(&shared, 1024));
+
+        // The region is idle since then.
+        clock.advance(Duration::from_secs(1800));
+        assert!(!strategy.should_flush_by_age(&shared, 1024));
+        clock.advance(Duration::from_secs(1800));
+        assert!(strategy.should_flush_by_age(&shared, 1024));
+
+        // The frozen memtable is being flushed, and nothing is written since.
+        clock.advance(Duration::from_secs(7200));
+        assert!(!strategy.should_flush_by_age(&shared, 0));
+    }
+}
@@ -25,6 +25,7 @@ mod flush;
 pub mod manifest;
 pub mod memtable;
 pub mod metadata;
+mod orphan;
 pub mod proto;
 pub mod read;
 pub mod region;
@@ -35,13 +36,15 @@ pub mod sst;
 mod sync;
 #[cfg(test)]
 mod test_util;
+mod timeline;
+mod tombstone;
 mod version;
 mod wal;
 pub mod write_batch;
 
-pub use engine::EngineImpl;
+pub use engine::{region_tag_dictionary_dir, EngineImpl};
 mod file_purger;
 mod metrics;
 
-pub use sst::parquet::ParquetWriter;
+pub use sst::parquet::{ParquetWriter, WriteProgress};
 pub use sst::Source;
@@ -29,6 +29,7 @@ use crate::error::{
 use crate::manifest::helper;
 use crate::metadata::{ColumnFamilyMetadata, ColumnMetadata, VersionNumber};
 use crate::sst::{FileId, FileMeta};
+use crate::tombstone::RangeTombstone;
 
 /// Minimal data that could be used to persist and recover [RegionMetadata](crate::metadata::RegionMetadata).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -77,8 +78,17 @@ pub struct RegionRemove {
 pub struct RegionEdit {
     pub region_version: VersionNumber,
     pub flushed_sequence: Option<SequenceNumber>,
+    /// Time in millis when the flushed memtables are frozen, the region holds exactly the
+    /// rows up to `flushed_sequence` at that time.
+    #[serde(default)]
+    pub flushed_at: Option<i64>,
     pub files_to_add: Vec<FileMeta>,
     pub files_to_remove: Vec<FileMeta>,
+    #[serde(default)]
+    pub tombstones_to_add: Vec<RangeTombstone>,
+    /// Sequences of the range tombstones to remove.
+    #[serde(default)]
+    pub tombstones_to_remove: Vec<SequenceNumber>,
 }
 
 /// The region version checkpoint
@@ -87,6 +97,8 @@ pub struct RegionVersion {
     pub manifest_version: ManifestVersion,
     pub flushed_sequence: Option<SequenceNumber>,
     pub files: HashMap<FileId, FileMeta>,
+    #[serde(default)]
+    pub range_tombstones: Vec<RangeTombstone>,
 }
 
 /// The region manifest data checkpoint
@@ -125,13 +137,19 @@ impl RegionManifestDataBuilder {
     pub fn apply_edit(&mut self, manifest_version: ManifestVersion, edit: RegionEdit) {
         if let Some(version) = &mut self.version {
             version.manifest_version = manifest_version;
-            version.flushed_sequence = edit.flushed_sequence;
+            if edit.flushed_sequence.is_some() {
+                version.flushed_sequence = edit.flushed_sequence;
+            }
             for file in edit.files_to_add {
                 version.files.insert(file.file_id, file);
             }
             for file in edit.files_to_remove {
                 version.files.remove(&file.file_id);
             }
+            version
+                .range_tombstones
+                .retain(|tombstone| !edit.tombstones_to_remove.contains(&tombstone.sequence));
+            version.range_tombstones.extend(edit.tombstones_to_add);
         } else {
             self.version = Some(RegionVersion {
                 manifest_version,
@@ -141,6 +159,7 @@ impl RegionManifestDataBuilder {
                     .into_iter()
                     .map(|f| (f.file_id, f))
                     .collect(),
+                range_tombstones: edit.tombstones_to_add,
             });
         }
     }
@@ -359,6 +378,11 @@ mod tests {
             time_range: None,
             level: 0,
             file_size: 1024,
+            num_rows: 0,
+            distinct_puts: false,
+            tag_dictionary_version: None,
+            tag_dictionary_generation: 0,
+            tombstone_sequence: None,
         }
     }
 
@@ -382,8 +406,11 @@ mod tests {
             RegionEdit {
                 region_version: 0,
                 flushed_sequence: Some(99),
+                flushed_at: None,
                 files_to_add: files.clone(),
                 files_to_remove: vec![],
+                tombstones_to_add: vec![],
+                tombstones_to_remove: vec![],
             },
         );
         builder.apply_edit(
@@ -391,8 +418,45 @@ mod tests {
             RegionEdit {
                 region_version: 0,
                 flushed_sequence: Some(100),
+                flushed_at: None,
                 files_to_add: vec![],
                 files_to_remove: vec![files[0].clone()],
+                tombstones_to_add: vec![],
+                tombstones_to_remove: vec![],
+            },
+        );
+        let tombstones: Vec<_> = [101, 102]
+            .into_iter()
+            .map(|sequence| RangeTombstone {
+                sequence,
+                keys: vec![],
+                start: None,
+                end: None,
+            })
+            .collect();
+        // Edits of range tombstones don't flush.
+        builder.apply_edit(
+            86,
+            RegionEdit {
+                region_version: 0,
+                flushed_sequence: None,
+                flushed_at: None,
+                files_to_add: vec![],
+                files_to_remove: vec![],
+                tombstones_to_add: tombstones.clone(),
+                tombstones_to_remove: vec![],
+            },
+        );
+        builder.apply_edit(
+            87,
+            RegionEdit {
+                region_version: 0,
+                flushed_sequence: None,
+                flushed_at: None,
+                files_to_add: vec![],
+                files_to_remove: vec![],
+                tombstones_to_add: vec![],
+                tombstones_to_remove: vec![101],
             },
         );
 
@@ -402,9 +466,10 @@ mod tests {
         assert_eq!(
             manifest.version,
             Some(RegionVersion {
-                manifest_version: 85,
+                manifest_version: 87,
                 flushed_sequence: Some(100),
                 files: files[1..].iter().map(|f| (f.file_id, f.clone())).collect(),
+                range_tombstones: tombstones[1..].to_vec(),
             })
         );
     }
@@ -425,6 +490,7 @@ mod tests {
                         .into_iter()
                         .map(|f| (f.file_id, f))
                         .collect(),
+                    range_tombstones: vec![],
                 }),
             }),
         };
@@ -130,6 +130,19 @@ impl<S: 'static + Checkpoint<Error = Error>, M: 'static + MetaAction<Error = Err
     pub(crate) fn manifest_store(&self) -> &Arc<ManifestObjectStore> {
         self.inner.manifest_store()
     }
+
+    /// Returns bytes of the files of the manifest in the object store.
+    #[inline]
+    pub fn stored_bytes(&self) -> u64 {
+        self.manifest_store().stored_bytes()
+    }
+
+    /// Lists the files of the manifest to load the bytes they store, which are tracked
+    /// afterwards. The manifest of an existing region or table must be loaded once it's
+    /// opened.
+    pub async fn load_stored_bytes(&self) -> Result<()> {
+        self.manifest_store().load_stored_bytes().await
+    }
 }
 
 #[async_trait]
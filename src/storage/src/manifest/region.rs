@@ -359,6 +359,7 @@ mod tests {
                 manifest_version: 1,
                 flushed_sequence: Some(3),
                 files,
+                ..
             }),
         }) if files.len() == 2 &&
                          files.contains_key(&file_ids[0]) &&
@@ -413,6 +414,7 @@ mod tests {
                 manifest_version: 1,
                 flushed_sequence: Some(3),
                 files,
+                ..
             }),
         }) if files.len() == 2 &&
                          files.contains_key(&file_ids[0]) &&
@@ -435,6 +437,7 @@ mod tests {
                 manifest_version: 4,
                 flushed_sequence: Some(201),
                 files,
+                ..
             }),
         }) if files.len() == 1 &&
                          files.contains_key(&new_file) &&
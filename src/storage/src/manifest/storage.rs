@@ -14,12 +14,14 @@
 
 use std::collections::HashMap;
 use std::iter::Iterator;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use common_telemetry::logging;
 use futures::TryStreamExt;
 use lazy_static::lazy_static;
-use object_store::{raw_normalize_path, util, Entry, ErrorKind, ObjectStore};
+use object_store::{raw_normalize_path, util, Entry, EntryMode, ErrorKind, Metakey, ObjectStore};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use snafu::{ensure, ResultExt};
@@ -29,6 +31,7 @@ use crate::error::{
     DecodeJsonSnafu, DeleteObjectSnafu, EncodeJsonSnafu, Error, InvalidScanIndexSnafu,
     ListObjectsSnafu, ReadObjectSnafu, Result, Utf8Snafu, WriteObjectSnafu,
 };
+use crate::sst::ListedObject;
 
 lazy_static! {
     static ref DELTA_RE: Regex = Regex::new("^\\d+\\.json$").unwrap();
@@ -95,6 +98,10 @@ impl LogIterator for ObjectStoreLogIterator {
 pub struct ManifestObjectStore {
     object_store: ObjectStore,
     path: String,
+    /// Bytes of the files under the manifest dir, loaded by
+    /// [ManifestObjectStore::load_stored_bytes] and then tracked by the writes and deletes of
+    /// the store.
+    stored_bytes: Arc<AtomicU64>,
 }
 
 impl ManifestObjectStore {
@@ -102,6 +109,7 @@ impl ManifestObjectStore {
         Self {
             object_store,
             path: util::normalize_dir(path),
+            stored_bytes: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -123,6 +131,139 @@ impl ManifestObjectStore {
     pub(crate) fn path(&self) -> &str {
         &self.path
     }
+
+    /// Returns bytes of the files under the manifest dir.
+    #[inline]
+    pub(crate) fn stored_bytes(&self) -> u64 {
+        self.stored_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Lists the files under the manifest dir to load the bytes they store, which are then
+    /// tracked without listing them again.
+    pub(crate) async fn load_stored_bytes(&self) -> Result<()> {
+        let mut lister = self
+            .object_store
+            .list(&self.path)
+            .await
+            .context(ListObjectsSnafu { path: &self.path })?;
+        let mut bytes = 0;
+        while let Some(entry) = lister
+            .try_next()
+            .await
+            .context(ListObjectsSnafu { path: &self.path })?
+        {
+            let metadata = self
+                .object_store
+                .metadata(&entry, Metakey::Mode | Metakey::ContentLength)
+                .await
+                .context(ListObjectsSnafu { path: entry.path() })?;
+            if matches!(metadata.mode(), EntryMode::FILE) {
+                bytes += metadata.content_length();
+            }
+        }
+        self.stored_bytes.store(bytes, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn add_stored_bytes(&self, bytes: u64) {
+        self.stored_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn sub_stored_bytes(&self, bytes: u64) {
+        // The bytes are not loaded if the files are deleted before the store is loaded.
+        let _ = self
+            .stored_bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |stored| {
+                Some(stored.saturating_sub(bytes))
+            });
+    }
+
+    /// Returns the size of the file at `path`, 0 if it doesn't exist.
+    async fn file_size(&self, path: &str) -> Result<u64> {
+        match self.object_store.stat(path).await {
+            Ok(metadata) => Ok(metadata.content_length()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e).context(ReadObjectSnafu { path }),
+        }
+    }
+
+    /// Lists the delta and checkpoint files before the last checkpoint, which are left in
+    /// the object store if the manifest gc task fails to delete them.
+    pub(crate) async fn list_stale_files(&self) -> Result<Vec<ListedObject>> {
+        let Some(checkpoint) = self.load_last_checkpoint_metadata().await? else {
+            return Ok(Vec::new());
+        };
+
+        let mut lister = self
+            .object_store
+            .list(&self.path)
+            .await
+            .context(ListObjectsSnafu { path: &self.path })?;
+        let mut files = Vec::new();
+        while let Some(entry) = lister
+            .try_next()
+            .await
+            .context(ListObjectsSnafu { path: &self.path })?
+        {
+            let file_name = entry.name();
+            if !(is_delta_file(file_name) || is_checkpoint_file(file_name))
+                || file_version(file_name) >= checkpoint.version
+            {
+                continue;
+            }
+
+            let metadata = self
+                .object_store
+                .metadata(&entry, Metakey::ContentLength | Metakey::LastModified)
+                .await
+                .context(ListObjectsSnafu { path: entry.path() })?;
+            files.push(ListedObject {
+                path: entry.path().to_string(),
+                size: metadata.content_length(),
+                last_modified_millis: metadata
+                    .last_modified()
+                    .map(|time| (time.unix_timestamp_nanos() / 1_000_000) as i64),
+            });
+        }
+        Ok(files)
+    }
+
+    /// Deletes the file at `path` under the manifest dir.
+    pub(crate) async fn delete_file(&self, path: &str) -> Result<()> {
+        let size = self.file_size(path).await?;
+        self.object_store
+            .delete(path)
+            .await
+            .context(DeleteObjectSnafu { path })?;
+        self.sub_stored_bytes(size);
+        Ok(())
+    }
+
+    async fn load_last_checkpoint_metadata(&self) -> Result<Option<CheckpointMetadata>> {
+        let last_checkpoint_path = self.last_checkpoint_path();
+
+        let last_checkpoint_data = match self.object_store.read(&last_checkpoint_path).await {
+            Ok(last_checkpoint_data) => last_checkpoint_data,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e).context(ReadObjectSnafu {
+                    path: last_checkpoint_path,
+                });
+            }
+        };
+
+        let checkpoint_metadata = CheckpointMetadata::decode(&last_checkpoint_data)?;
+
+        logging::debug!(
+            "Load checkpoint in path: {}, metadata: {:?}",
+            last_checkpoint_path,
+            checkpoint_metadata
+        );
+
+        Ok(Some(checkpoint_metadata))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -196,13 +337,13 @@ impl ManifestLogStorage for ManifestObjectStore {
             .await
             .context(ListObjectsSnafu { path: &self.path })?;
 
-        let paths: Vec<_> = streamer
+        let entries: Vec<_> = streamer
             .try_filter_map(|e| async move {
                 let file_name = e.name();
                 if is_delta_file(file_name) || is_checkpoint_file(file_name) {
                     let version = file_version(file_name);
                     if version < end {
-                        Ok(Some(e.path().to_string()))
+                        Ok(Some(e))
                     } else {
                         Ok(None)
                     }
@@ -213,7 +354,19 @@ impl ManifestLogStorage for ManifestObjectStore {
             .try_collect::<Vec<_>>()
             .await
             .context(ListObjectsSnafu { path: &self.path })?;
-        let ret = paths.len();
+        let ret = entries.len();
+
+        let mut paths = Vec::with_capacity(entries.len());
+        let mut bytes = 0;
+        for entry in entries {
+            bytes += self
+                .object_store
+                .metadata(&entry, Metakey::ContentLength)
+                .await
+                .context(ListObjectsSnafu { path: entry.path() })?
+                .content_length();
+            paths.push(entry.path().to_string());
+        }
 
         logging::debug!(
             "Deleting {} logs from manifest storage path {}.",
@@ -227,6 +380,7 @@ impl ManifestLogStorage for ManifestObjectStore {
             .with_context(|_| DeleteObjectSnafu {
                 path: self.path.clone(),
             })?;
+        self.sub_stored_bytes(bytes);
 
         Ok(ret)
     }
@@ -236,13 +390,19 @@ impl ManifestLogStorage for ManifestObjectStore {
         self.object_store
             .write(&path, bytes.to_vec())
             .await
-            .context(WriteObjectSnafu { path })
+            .context(WriteObjectSnafu { path })?;
+        self.add_stored_bytes(bytes.len() as u64);
+        Ok(())
     }
 
     async fn delete(&self, start: ManifestVersion, end: ManifestVersion) -> Result<()> {
         let raw_paths = (start..end)
             .map(|v| self.delta_file_path(v))
             .collect::<Vec<_>>();
+        let mut bytes = 0;
+        for path in &raw_paths {
+            bytes += self.file_size(path).await?;
+        }
 
         let paths = raw_paths
             .iter()
@@ -255,6 +415,7 @@ impl ManifestLogStorage for ManifestObjectStore {
             .with_context(|_| DeleteObjectSnafu {
                 path: raw_paths.join(","),
             })?;
+        self.sub_stored_bytes(bytes);
 
         Ok(())
     }
@@ -265,8 +426,10 @@ impl ManifestLogStorage for ManifestObjectStore {
             .write(&path, bytes.to_vec())
             .await
             .context(WriteObjectSnafu { path })?;
+        self.add_stored_bytes(bytes.len() as u64);
 
         let last_checkpoint_path = self.last_checkpoint_path();
+        let last_checkpoint_size = self.file_size(&last_checkpoint_path).await?;
 
         let checkpoint_metadata = CheckpointMetadata {
             size: bytes.len(),
@@ -289,6 +452,9 @@ impl ManifestLogStorage for ManifestObjectStore {
             .context(WriteObjectSnafu {
                 path: last_checkpoint_path,
             })?;
+        // The last checkpoint file is overwritten.
+        self.sub_stored_bytes(last_checkpoint_size);
+        self.add_stored_bytes(bs.as_ref().len() as u64);
 
         Ok(())
     }
@@ -307,37 +473,20 @@ impl ManifestLogStorage for ManifestObjectStore {
 
     async fn delete_checkpoint(&self, version: ManifestVersion) -> Result<()> {
         let path = self.checkpoint_file_path(version);
+        let size = self.file_size(&path).await?;
         self.object_store
             .delete(&path)
             .await
             .context(DeleteObjectSnafu { path })?;
+        self.sub_stored_bytes(size);
         Ok(())
     }
 
     async fn load_last_checkpoint(&self) -> Result<Option<(ManifestVersion, Vec<u8>)>> {
-        let last_checkpoint_path = self.last_checkpoint_path();
-
-        let last_checkpoint_data = match self.object_store.read(&last_checkpoint_path).await {
-            Ok(last_checkpoint_data) => last_checkpoint_data,
-            Err(e) if e.kind() == ErrorKind::NotFound => {
-                return Ok(None);
-            }
-            Err(e) => {
-                return Err(e).context(ReadObjectSnafu {
-                    path: last_checkpoint_path,
-                });
-            }
-        };
-
-        let checkpoint_metadata = CheckpointMetadata::decode(&last_checkpoint_data)?;
-
-        logging::debug!(
-            "Load checkpoint in path: {}, metadata: {:?}",
-            last_checkpoint_path,
-            checkpoint_metadata
-        );
-
-        self.load_checkpoint(checkpoint_metadata.version).await
+        match self.load_last_checkpoint_metadata().await? {
+            Some(checkpoint_metadata) => self.load_checkpoint(checkpoint_metadata.version).await,
+            None => Ok(None),
+        }
     }
 }
 
@@ -422,4 +571,66 @@ mod tests {
         let mut it = log_store.scan(0, 11).await.unwrap();
         assert!(it.next_log().await.unwrap().is_none());
     }
+    /// Returns bytes of the files under the dir of `log_store`, loaded by a new store.
+    async fn load_stored_bytes(log_store: &ManifestObjectStore) -> u64 {
+        let loaded = ManifestObjectStore::new(log_store.path(), log_store.object_store.clone());
+        loaded.load_stored_bytes().await.unwrap();
+        loaded.stored_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_manifest_stored_bytes() {
+        let tmp_dir = create_temp_dir("test_manifest_stored_bytes");
+        let mut builder = Fs::default();
+        builder.root(&tmp_dir.path().to_string_lossy());
+        let object_store = ObjectStore::new(builder).unwrap().finish();
+
+        let log_store = ManifestObjectStore::new("/manifest", object_store);
+        log_store.load_stored_bytes().await.unwrap();
+        assert_eq!(0, log_store.stored_bytes());
+
+        for v in 0..5 {
+            log_store
+                .save(v, format!("hello, {v}").as_bytes())
+                .await
+                .unwrap();
+        }
+        assert_eq!(5 * "hello, 0".len() as u64, log_store.stored_bytes());
+        assert_eq!(
+            load_stored_bytes(&log_store).await,
+            log_store.stored_bytes()
+        );
+
+        log_store.delete(0, 2).await.unwrap();
+        assert_eq!(3 * "hello, 0".len() as u64, log_store.stored_bytes());
+
+        for v in [3, 4] {
+            log_store
+                .save_checkpoint(v, "checkpoint".as_bytes())
+                .await
+                .unwrap();
+            assert_eq!(
+                load_stored_bytes(&log_store).await,
+                log_store.stored_bytes()
+            );
+        }
+
+        log_store.delete_checkpoint(3).await.unwrap();
+        assert_eq!(
+            load_stored_bytes(&log_store).await,
+            log_store.stored_bytes()
+        );
+
+        log_store.delete_until(4).await.unwrap();
+        assert_eq!(
+            load_stored_bytes(&log_store).await,
+            log_store.stored_bytes()
+        );
+
+        log_store.delete_until(11).await.unwrap();
+        assert_eq!(
+            load_stored_bytes(&log_store).await,
+            log_store.stored_bytes()
+        );
+    }
 }
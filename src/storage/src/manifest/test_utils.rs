@@ -51,6 +51,7 @@ pub fn build_region_edit(
     RegionEdit {
         region_version: 0,
         flushed_sequence: Some(sequence),
+        flushed_at: None,
         files_to_add: files_to_add
             .iter()
             .map(|f| FileMeta {
@@ -59,6 +60,11 @@ pub fn build_region_edit(
                 time_range: None,
                 level: 0,
                 file_size: DEFAULT_TEST_FILE_SIZE,
+                num_rows: 0,
+                distinct_puts: false,
+                tag_dictionary_version: None,
+                tag_dictionary_generation: 0,
+                tombstone_sequence: None,
             })
             .collect(),
         files_to_remove: files_to_remove
@@ -69,7 +75,14 @@ pub fn build_region_edit(
                 time_range: None,
                 level: 0,
                 file_size: DEFAULT_TEST_FILE_SIZE,
+                num_rows: 0,
+                distinct_puts: false,
+                tag_dictionary_version: None,
+                tag_dictionary_generation: 0,
+                tombstone_sequence: None,
             })
             .collect(),
+        tombstones_to_add: vec![],
+        tombstones_to_remove: vec![],
     }
 }
@@ -21,6 +21,7 @@ mod version;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
+use common_time::Timestamp;
 use datatypes::vectors::VectorRef;
 use store_api::storage::{consts, OpType, SequenceNumber};
 
@@ -58,6 +59,13 @@ pub trait Memtable: Send + Sync + std::fmt::Debug {
 
     /// Return the number of rows contained in this memtable.
     fn num_rows(&self) -> usize;
+
+    /// Returns the time range of the rows in this memtable, `None` if it's empty.
+    fn time_range(&self) -> Option<(Timestamp, Timestamp)>;
+
+    /// Returns whether the rows in this memtable are all puts of distinct row keys, so all
+    /// of them are visible and [`num_rows`](Memtable::num_rows) counts the rows read from it.
+    fn distinct_puts(&self) -> bool;
 }
 
 pub type MemtableRef = Arc<dyn Memtable>;
@@ -17,8 +17,9 @@ use std::collections::{btree_map, BTreeMap};
 use std::fmt;
 use std::ops::Bound;
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
+use common_time::Timestamp;
 use datatypes::data_type::DataType;
 use datatypes::prelude::*;
 use datatypes::value::Value;
@@ -43,6 +44,19 @@ pub struct BTreeMemtable {
     schema: RegionSchemaRef,
     map: Arc<RwLockMap>,
     estimated_bytes: AtomicUsize,
+    /// Number of writes to the memtable, updated under the write lock of the map.
+    writes: AtomicUsize,
+    /// Stats of the rows and the number of writes they are computed after. The stats are
+    /// computed from the map on demand, so writes don't pay for them.
+    stats: Mutex<Option<(usize, RowStats)>>,
+}
+
+/// Stats of the rows written to the memtable.
+#[derive(Debug, Clone, Copy)]
+struct RowStats {
+    time_range: Option<(Timestamp, Timestamp)>,
+    /// Whether the rows are all puts of distinct row keys.
+    distinct_puts: bool,
 }
 
 impl BTreeMemtable {
@@ -52,7 +66,45 @@ impl BTreeMemtable {
             schema,
             map: Arc::new(RwLock::new(BTreeMap::new())),
             estimated_bytes: AtomicUsize::new(0),
+            writes: AtomicUsize::new(0),
+            stats: Mutex::new(None),
+        }
+    }
+
+    /// Returns the stats of the rows, computing them if the memtable is written after they
+    /// are computed.
+    fn row_stats(&self) -> RowStats {
+        let map = self.map.read().unwrap();
+        let writes = self.writes.load(AtomicOrdering::Acquire);
+        let mut cached = self.stats.lock().unwrap();
+        if let Some((computed_after, stats)) = *cached {
+            if computed_after == writes {
+                return stats;
+            }
+        }
+
+        let timestamp_index = self.schema.timestamp_key_index();
+        let mut stats = RowStats {
+            time_range: None,
+            distinct_puts: true,
+        };
+        let mut prev: Option<&InnerKey> = None;
+        for key in map.keys() {
+            // Rows of the same row key are adjacent in the map.
+            if stats.distinct_puts {
+                stats.distinct_puts = key.op_type == OpType::Put
+                    && !prev.map_or(false, |prev| prev.is_row_key_equal(key));
+            }
+            if let Value::Timestamp(ts) = key.row_key[timestamp_index] {
+                stats.time_range = Some(match stats.time_range {
+                    Some((start, end)) => (start.min(ts), end.max(ts)),
+                    None => (ts, ts),
+                });
+            }
+            prev = Some(key);
         }
+        *cached = Some((writes, stats));
+        stats
     }
 }
 
@@ -88,6 +140,7 @@ impl Memtable for BTreeMemtable {
         for (inner_key, row_value) in iter_row {
             map.insert(inner_key, row_value);
         }
+        self.writes.fetch_add(1, AtomicOrdering::Release);
 
         Ok(())
     }
@@ -107,6 +160,14 @@ impl Memtable for BTreeMemtable {
     fn num_rows(&self) -> usize {
         self.map.read().unwrap().len()
     }
+
+    fn time_range(&self) -> Option<(Timestamp, Timestamp)> {
+        self.row_stats().time_range
+    }
+
+    fn distinct_puts(&self) -> bool {
+        self.row_stats().distinct_puts
+    }
 }
 
 struct BTreeIterator {
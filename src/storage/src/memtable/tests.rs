@@ -440,6 +440,48 @@ fn test_duplicate_key_in_batch() {
     });
 }
 
+#[test]
+fn test_row_stats() {
+    let tester = MemtableTester::default();
+    tester.run_testcase(|ctx| {
+        assert_eq!(None, ctx.memtable.time_range());
+        assert!(ctx.memtable.distinct_puts());
+
+        write_kvs(
+            &*ctx.memtable,
+            10, // sequence
+            OpType::Put,
+            &[(1000, 1), (1000, 2), (2001, 2)],          // keys
+            &[(None, None), (None, None), (None, None)], // values
+        );
+        assert_eq!(
+            Some((
+                Timestamp::new_millisecond(1000),
+                Timestamp::new_millisecond(2001)
+            )),
+            ctx.memtable.time_range()
+        );
+        assert!(ctx.memtable.distinct_puts());
+
+        // The stats are updated by the writes after they are computed.
+        write_kvs(
+            &*ctx.memtable,
+            11, // sequence
+            OpType::Put,
+            &[(500, 1), (1000, 2)],        // keys
+            &[(None, None), (None, None)], // values
+        );
+        assert_eq!(
+            Some((
+                Timestamp::new_millisecond(500),
+                Timestamp::new_millisecond(2001)
+            )),
+            ctx.memtable.time_range()
+        );
+        assert!(!ctx.memtable.distinct_puts());
+    });
+}
+
 #[test]
 fn test_sequence_visibility() {
     let tester = MemtableTester::default();
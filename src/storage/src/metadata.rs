@@ -19,7 +19,7 @@ use std::sync::Arc;
 
 use common_error::prelude::*;
 use datatypes::data_type::ConcreteDataType;
-use datatypes::schema::{ColumnSchema, Metadata, COMMENT_KEY};
+use datatypes::schema::{ColumnSchema, Metadata, COMMENT_KEY, COMPRESSION_KEY, ENCODING_KEY};
 use serde::{Deserialize, Serialize};
 use snafu::{ensure, Location, OptionExt};
 use store_api::storage::consts::{self, ReservedColumnId};
@@ -83,6 +83,9 @@ pub enum Error {
     #[snafu(display("Failed to drop column {} as it is an internal column", name))]
     DropInternalColumn { name: String },
 
+    #[snafu(display("Failed to set options of column as there is no column named {}", name))]
+    SetOptionsOfAbsentColumn { name: String },
+
     // End of variants for validating `AlterRequest`.
     #[snafu(display("Failed to convert to column schema, source: {}", source))]
     ToColumnSchema {
@@ -250,6 +253,12 @@ impl RegionMetadata {
                     self.validate_drop_column(name)?;
                 }
             }
+            AlterOperation::SetColumnOptions { name, .. } => {
+                ensure!(
+                    self.schema.store_schema().is_user_column(name),
+                    SetOptionsOfAbsentColumnSnafu { name }
+                );
+            }
         }
 
         Ok(())
@@ -426,6 +435,8 @@ impl ColumnMetadata {
         .is_time_index(column_schema.is_time_index())
         .default_constraint(column_schema.default_constraint().cloned())
         .comment(comment)
+        .encoding(metadata.get(ENCODING_KEY).cloned())
+        .compression(metadata.get(COMPRESSION_KEY).cloned())
         .build()
         .context(BuildColumnDescriptorSnafu)?;
 
@@ -441,6 +452,12 @@ impl ColumnMetadata {
         if !self.desc.comment.is_empty() {
             metadata.insert(COMMENT_KEY.to_string(), self.desc.comment.clone());
         }
+        if let Some(encoding) = &self.desc.encoding {
+            metadata.insert(ENCODING_KEY.to_string(), encoding.clone());
+        }
+        if let Some(compression) = &self.desc.compression {
+            metadata.insert(COMPRESSION_KEY.to_string(), compression.clone());
+        }
 
         metadata
     }
@@ -1389,6 +1406,44 @@ mod tests {
             names: vec![String::from("v0")],
         };
         metadata.validate_alter(&req).unwrap();
+
+        // Set options of an absent column.
+        req.operation = AlterOperation::SetColumnOptions {
+            name: String::from("v9"),
+            encoding: Some(String::from("plain")),
+            compression: None,
+        };
+        assert!(matches!(
+            metadata.validate_alter(&req).err().unwrap(),
+            Error::SetOptionsOfAbsentColumn { .. }
+        ));
+    }
+
+    #[test]
+    fn test_alter_metadata_set_column_options() {
+        let metadata: RegionMetadata = RegionDescBuilder::new("region-0")
+            .push_key_column(("k1", LogicalTypeId::String, false))
+            .push_field_column(("v1", LogicalTypeId::Float32, true))
+            .build()
+            .try_into()
+            .unwrap();
+
+        let req = AlterRequest {
+            operation: AlterOperation::SetColumnOptions {
+                name: String::from("k1"),
+                encoding: Some(String::from("dictionary")),
+                compression: Some(String::from("zstd")),
+            },
+            version: 0,
+        };
+        metadata.validate_alter(&req).unwrap();
+        let metadata = metadata.alter(&req).unwrap();
+        assert_eq!(1, metadata.version());
+        let column_schema = metadata.user_schema().column_schema_by_name("k1").unwrap();
+        assert_eq!(Some("dictionary"), column_schema.encoding());
+        assert_eq!(Some("zstd"), column_schema.compression());
+        let column_schema = metadata.user_schema().column_schema_by_name("v1").unwrap();
+        assert_eq!(None, column_schema.encoding());
     }
 
     #[test]
@@ -1397,6 +1452,8 @@ mod tests {
             .is_nullable(false)
             .default_constraint(Some(ColumnDefaultConstraint::Value(Value::Int32(321))))
             .comment("hello")
+            .encoding(Some("plain".to_string()))
+            .compression(Some("zstd".to_string()))
             .build()
             .unwrap();
 
@@ -16,3 +16,5 @@
 
 /// Elapsed time of updating manifest when creating regions.
 pub const CREATE_REGION_UPDATE_MANIFEST: &str = "storage.create_region.update_manifest";
+/// Number of WAL entries applied to the memtables when replaying the WALs of regions.
+pub const WAL_REPLAY_ENTRIES: &str = "storage.wal.replay.entries";
@@ -0,0 +1,120 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Garbage collection of the orphans of a region.
+//!
+//! Orphans are the objects under the storage of a region the region no longer references,
+//! like the SSTs left by crashed flushes and failed compactions, or the manifest files the
+//! manifest gc task fails to delete.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use common_telemetry::logging;
+use common_time::util::current_time_millis;
+use store_api::storage::{OrphanFile, OrphanGcRequest, OrphanGcResponse, OrphanKind};
+
+use crate::error::Result;
+use crate::manifest::region::RegionManifest;
+use crate::sst::{AccessLayerRef, FileId, ListedObject};
+use crate::version::VersionControl;
+
+/// Finds the orphans of a region, and deletes the ones older than the safety age if the
+/// request asks to.
+///
+/// An SST is referenced if it is in the current version, or if it is leased by a handle of
+/// any version, e.g. a SST removed by compaction but still read by an ongoing scan.
+pub(crate) async fn gc_orphans(
+    region_name: &str,
+    sst_layer: &AccessLayerRef,
+    manifest: &RegionManifest,
+    version_control: &VersionControl,
+    request: &OrphanGcRequest,
+) -> Result<OrphanGcResponse> {
+    // Lists the objects before collecting the referenced files, so an object written and
+    // referenced after the listing is never taken as an orphan.
+    let ssts = sst_layer.list_ssts().await?;
+    let manifest_files = manifest.manifest_store().list_stale_files().await?;
+
+    let referenced = referenced_files(version_control);
+    let now_millis = current_time_millis();
+    // Orphans with the ids of the orphaned SSTs, to delete them by the access layer.
+    let mut orphans = Vec::new();
+    for (file_id, object) in ssts {
+        if !referenced.contains(&file_id) {
+            let orphan = new_orphan(object, OrphanKind::Sst, now_millis);
+            orphans.push((orphan, Some(file_id)));
+        }
+    }
+    for object in manifest_files {
+        orphans.push((new_orphan(object, OrphanKind::Manifest, now_millis), None));
+    }
+
+    if request.delete {
+        let mut deleted_num = 0;
+        for (orphan, file_id) in &mut orphans {
+            if !orphan.age.map_or(false, |age| age >= request.safety_age) {
+                continue;
+            }
+
+            if let Some(pacer) = &request.deletion_pacer {
+                let wait = pacer.reserve();
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+            match file_id {
+                Some(file_id) => sst_layer.delete_sst(*file_id).await?,
+                None => manifest.manifest_store().delete_file(&orphan.path).await?,
+            }
+            orphan.deleted = true;
+            deleted_num += 1;
+        }
+
+        logging::info!(
+            "Deleted {} of {} orphans of region {}",
+            deleted_num,
+            orphans.len(),
+            region_name
+        );
+    }
+
+    let orphans = orphans.into_iter().map(|(orphan, _)| orphan).collect();
+    Ok(OrphanGcResponse { orphans })
+}
+
+/// Returns the SSTs in the current version and the SSTs leased by handles of any version.
+fn referenced_files(version_control: &VersionControl) -> HashSet<FileId> {
+    let version = version_control.current();
+    let ssts = version.ssts();
+    let mut files = ssts.leased_files();
+    for level in ssts.levels() {
+        files.extend(level.files().map(|file| file.file_id()));
+    }
+    files
+}
+
+fn new_orphan(object: ListedObject, kind: OrphanKind, now_millis: i64) -> OrphanFile {
+    // Objects modified after now due to clock skew are taken as new.
+    let age = object
+        .last_modified_millis
+        .map(|millis| Duration::from_millis(now_millis.saturating_sub(millis).max(0) as u64));
+    OrphanFile {
+        path: object.path,
+        kind,
+        size: object.size,
+        age,
+        deleted: false,
+    }
+}
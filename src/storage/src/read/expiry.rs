@@ -0,0 +1,119 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use common_time::Timestamp;
+use datatypes::prelude::{ConcreteDataType, ScalarVector};
+use datatypes::types::TimestampType;
+use datatypes::vectors::{
+    BooleanVector, TimestampMicrosecondVector, TimestampMillisecondVector,
+    TimestampNanosecondVector, TimestampSecondVector,
+};
+
+use crate::error::Result;
+use crate::read::{Batch, BatchOp, BatchReader};
+use crate::schema::ProjectedSchemaRef;
+
+/// Expiry of rows by the timestamp column holding their expiry time.
+#[derive(Debug, Clone)]
+pub struct RowExpiry {
+    /// Name of the column holding the expiry time of rows.
+    pub ttl_column: String,
+    /// Rows whose expiry time is not after `now` are expired, rows without expiry time never
+    /// expire.
+    pub now: Timestamp,
+}
+
+/// A reader that drops expired rows from inner reader.
+pub struct ExpiryReader<R> {
+    /// Projected schema to read.
+    schema: ProjectedSchemaRef,
+    /// The inner reader.
+    reader: R,
+    /// Index of the column holding the expiry time in batches.
+    ttl_index: usize,
+    now: Timestamp,
+}
+
+impl<R> ExpiryReader<R> {
+    pub fn new(
+        schema: ProjectedSchemaRef,
+        reader: R,
+        ttl_index: usize,
+        now: Timestamp,
+    ) -> ExpiryReader<R> {
+        ExpiryReader {
+            schema,
+            reader,
+            ttl_index,
+            now,
+        }
+    }
+
+    /// Returns a new batch without the expired rows of `batch`.
+    ///
+    /// This method may returns empty `Batch`.
+    fn drop_expired(&self, batch: Batch) -> Result<Batch> {
+        let expiry = batch.column(self.ttl_index);
+        if expiry.null_count() == expiry.len() {
+            return Ok(batch);
+        }
+
+        macro_rules! unexpired {
+            ($VectorType: ty) => {
+                expiry.as_any().downcast_ref::<$VectorType>().map(|expiry| {
+                    BooleanVector::from_iterator(
+                        expiry
+                            .iter_data()
+                            .map(|expire_at| expire_at.map_or(true, |ts| ts.0 > self.now)),
+                    )
+                })
+            };
+        }
+
+        let filter = match expiry.data_type() {
+            ConcreteDataType::Timestamp(TimestampType::Second(_)) => {
+                unexpired!(TimestampSecondVector)
+            }
+            ConcreteDataType::Timestamp(TimestampType::Millisecond(_)) => {
+                unexpired!(TimestampMillisecondVector)
+            }
+            ConcreteDataType::Timestamp(TimestampType::Microsecond(_)) => {
+                unexpired!(TimestampMicrosecondVector)
+            }
+            ConcreteDataType::Timestamp(TimestampType::Nanosecond(_)) => {
+                unexpired!(TimestampNanosecondVector)
+            }
+            _ => None,
+        };
+        // The ttl column is validated to be a timestamp column.
+        let Some(filter) = filter else { return Ok(batch) };
+        self.schema.filter(&batch, &filter)
+    }
+}
+
+#[async_trait]
+impl<R: BatchReader> BatchReader for ExpiryReader<R> {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(batch) = self.reader.next_batch().await? {
+            let filtered = self.drop_expired(batch)?;
+            // Skip empty batch.
+            if !filtered.is_empty() {
+                return Ok(Some(filtered));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use datatypes::prelude::ScalarVector;
+use datatypes::vectors::{BooleanVector, UInt64Vector};
+use store_api::storage::SequenceNumber;
+
+use crate::error::Result;
+use crate::read::{Batch, BatchOp, BatchReader};
+use crate::schema::ProjectedSchemaRef;
+
+/// A reader that drops the rows of inner reader whose sequence is above the visible sequence.
+///
+/// Memtables hide these rows themselves, so the reader is only needed by the SSTs that may
+/// contain rows written after the sequence to read.
+pub struct SequenceReader<R> {
+    /// Projected schema to read.
+    schema: ProjectedSchemaRef,
+    /// The inner reader.
+    reader: R,
+    /// Max sequence number (inclusive) visible to the reader.
+    visible_sequence: SequenceNumber,
+}
+
+impl<R> SequenceReader<R> {
+    pub fn new(
+        schema: ProjectedSchemaRef,
+        reader: R,
+        visible_sequence: SequenceNumber,
+    ) -> SequenceReader<R> {
+        SequenceReader {
+            schema,
+            reader,
+            visible_sequence,
+        }
+    }
+
+    /// Returns a new batch without the invisible rows of `batch`.
+    ///
+    /// This method may returns empty `Batch`.
+    fn drop_invisible(&self, batch: Batch) -> Result<Batch> {
+        let sequences = batch.column(self.schema.schema_to_read().sequence_index());
+        // Safety: The sequence column of batches is always a UInt64 column, which the read
+        // procedure guarantees.
+        let sequences = sequences.as_any().downcast_ref::<UInt64Vector>().unwrap();
+        if sequences
+            .iter_data()
+            .all(|sequence| sequence.map_or(true, |s| s <= self.visible_sequence))
+        {
+            return Ok(batch);
+        }
+
+        let filter = BooleanVector::from_iterator(
+            sequences
+                .iter_data()
+                .map(|sequence| sequence.map_or(true, |s| s <= self.visible_sequence)),
+        );
+        self.schema.filter(&batch, &filter)
+    }
+}
+
+#[async_trait]
+impl<R: BatchReader> BatchReader for SequenceReader<R> {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(batch) = self.reader.next_batch().await? {
+            let filtered = self.drop_invisible(batch)?;
+            // Skip empty batch.
+            if !filtered.is_empty() {
+                return Ok(Some(filtered));
+            }
+        }
+
+        Ok(None)
+    }
+}
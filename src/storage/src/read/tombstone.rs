@@ -0,0 +1,162 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use datatypes::prelude::ScalarVector;
+use datatypes::value::ValueRef;
+use datatypes::vectors::{BooleanVector, UInt64Vector};
+
+use crate::error::Result;
+use crate::read::{Batch, BatchOp, BatchReader};
+use crate::schema::ProjectedSchemaRef;
+use crate::tombstone::RangeTombstone;
+
+/// A reader that drops the rows of inner reader deleted by range tombstones.
+///
+/// The reader must be applied after dedup, so the rows deleted can't unveil the older rows of
+/// the same key.
+pub struct TombstoneReader<R> {
+    /// Projected schema to read.
+    schema: ProjectedSchemaRef,
+    /// The inner reader.
+    reader: R,
+    /// Tombstones to apply, with the indices of their key columns in batches.
+    tombstones: Vec<(RangeTombstone, Vec<usize>)>,
+    /// Index of the timestamp column in batches.
+    ts_index: usize,
+}
+
+impl<R> TombstoneReader<R> {
+    pub fn new(
+        schema: ProjectedSchemaRef,
+        reader: R,
+        tombstones: Vec<RangeTombstone>,
+        ts_index: usize,
+    ) -> TombstoneReader<R> {
+        let columns = schema.schema_to_read().columns();
+        let tombstones = tombstones
+            .into_iter()
+            .filter_map(|tombstone| {
+                // Row key columns are always read, so the tombstones of keys not in the schema
+                // delete nothing.
+                let indices = tombstone
+                    .keys
+                    .iter()
+                    .map(|(id, _)| columns.iter().position(|column| column.id() == *id))
+                    .collect::<Option<Vec<_>>>()?;
+                Some((tombstone, indices))
+            })
+            .collect();
+
+        TombstoneReader {
+            schema,
+            reader,
+            tombstones,
+            ts_index,
+        }
+    }
+
+    /// Returns a new batch without the rows of `batch` deleted by the tombstones.
+    ///
+    /// This method may returns empty `Batch`.
+    fn drop_deleted(&self, batch: Batch) -> Result<Batch> {
+        let sequences = batch.column(self.schema.schema_to_read().sequence_index());
+        // Safety: The sequence column of batches is always a UInt64 column, which the read
+        // procedure guarantees.
+        let sequences = sequences.as_any().downcast_ref::<UInt64Vector>().unwrap();
+        let timestamps = batch.column(self.ts_index);
+
+        let is_deleted = |i: usize| {
+            let ValueRef::Timestamp(ts) = timestamps.get_ref(i) else { return false };
+            let sequence = sequences.get_data(i).unwrap_or_default();
+            self.tombstones.iter().any(|(tombstone, indices)| {
+                sequence <= tombstone.sequence
+                    && tombstone.contains_time(ts)
+                    && tombstone
+                        .keys
+                        .iter()
+                        .zip(indices)
+                        .all(|((_, value), index)| {
+                            batch.column(*index).get_ref(i) == value.as_value_ref()
+                        })
+            })
+        };
+        if !(0..batch.num_rows()).any(is_deleted) {
+            return Ok(batch);
+        }
+
+        let filter = BooleanVector::from_iterator((0..batch.num_rows()).map(|i| !is_deleted(i)));
+        self.schema.filter(&batch, &filter)
+    }
+}
+
+#[async_trait]
+impl<R: BatchReader> BatchReader for TombstoneReader<R> {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(batch) = self.reader.next_batch().await? {
+            let filtered = self.drop_deleted(batch)?;
+            // Skip empty batch.
+            if !filtered.is_empty() {
+                return Ok(Some(filtered));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_time::Timestamp;
+    use store_api::storage::OpType;
+
+    use super::*;
+    use crate::test_util::read_util;
+
+    fn new_tombstone(sequence: u64, start: i64, end: i64) -> RangeTombstone {
+        RangeTombstone {
+            sequence,
+            keys: vec![],
+            start: Some(Timestamp::new_millisecond(start)),
+            end: Some(Timestamp::new_millisecond(end)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tombstone_reader() {
+        let schema = read_util::new_projected_schema();
+        let reader = read_util::build_full_vec_reader(&[
+            // key, value, sequence, op_type
+            &[
+                (100, 1, 10, OpType::Put),
+                (101, 2, 10, OpType::Put),
+                (102, 3, 30, OpType::Put),
+            ],
+            &[(103, 4, 10, OpType::Put), (104, 5, 10, OpType::Put)],
+            &[(105, 6, 10, OpType::Put)],
+        ]);
+        let tombstones = vec![new_tombstone(20, 101, 104), new_tombstone(5, 105, 106)];
+        let mut reader = TombstoneReader::new(schema, reader, tombstones, 0);
+
+        let result = read_util::collect_kv_batch(&mut reader).await;
+        // Rows written after the tombstones are not deleted.
+        let expect = [
+            (100, Some(1)),
+            (102, Some(3)),
+            (104, Some(5)),
+            (105, Some(6)),
+        ];
+        assert_eq!(&expect, &result[..]);
+    }
+}
@@ -18,17 +18,19 @@ mod writer;
 
 use std::collections::BTreeMap;
 use std::fmt;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 
 use async_trait::async_trait;
+use common_runtime::{RepeatedTask, TaskFunction};
 use common_telemetry::logging;
+use common_time::util::current_time_millis;
 use snafu::ResultExt;
 use store_api::logstore::LogStore;
 use store_api::manifest::{self, Manifest, ManifestVersion, MetaActionIterator};
 use store_api::storage::{
-    AlterRequest, FlushContext, OpenOptions, ReadContext, Region, RegionId, SequenceNumber,
-    WriteContext, WriteResponse,
+    AlterRequest, DeleteRangeRequest, FlushContext, OpenOptions, OrphanGcRequest, OrphanGcResponse,
+    ReadContext, Region, RegionId, SequenceNumber, WriteContext, WriteResponse,
 };
 
 use crate::compaction::CompactionSchedulerRef;
@@ -42,10 +44,12 @@ use crate::manifest::action::{
 use crate::manifest::region::RegionManifest;
 use crate::memtable::MemtableBuilderRef;
 use crate::metadata::{RegionMetaImpl, RegionMetadata, RegionMetadataRef};
+use crate::orphan;
 pub use crate::region::writer::{AlterContext, RegionWriter, RegionWriterRef, WriterContext};
 use crate::schema::compat::CompatWrite;
 use crate::snapshot::SnapshotImpl;
 use crate::sst::AccessLayerRef;
+use crate::timeline::SnapshotTimeline;
 use crate::version::{
     Version, VersionControl, VersionControlRef, VersionEdit, INIT_COMMITTED_SEQUENCE,
 };
@@ -106,8 +110,12 @@ impl<S: LogStore> Region for RegionImpl<S> {
         self.inner.write(ctx, request).await
     }
 
-    fn snapshot(&self, _ctx: &ReadContext) -> Result<SnapshotImpl> {
-        Ok(self.inner.create_snapshot())
+    async fn delete_range(&self, request: DeleteRangeRequest) -> Result<WriteResponse> {
+        self.inner.delete_range(request).await
+    }
+
+    fn snapshot(&self, ctx: &ReadContext) -> Result<SnapshotImpl> {
+        self.inner.create_snapshot(ctx.as_of)
     }
 
     fn write_request(&self) -> Self::WriteRequest {
@@ -128,17 +136,56 @@ impl<S: LogStore> Region for RegionImpl<S> {
 
     fn disk_usage_bytes(&self) -> u64 {
         let version = self.inner.version_control().current();
-        version
-            .ssts()
-            .levels()
-            .iter()
-            .map(|level_ssts| level_ssts.files().map(|sst| sst.file_size()).sum::<u64>())
-            .sum()
+        version.ssts().file_bytes()
+    }
+
+    fn storage_bytes(&self) -> u64 {
+        let version = self.inner.version_control().current();
+        let ssts = version.ssts();
+        ssts.file_bytes() + ssts.pending_purge_bytes() + self.inner.manifest.stored_bytes()
+    }
+
+    fn approximate_rows(&self) -> u64 {
+        let version = self.inner.version_control().current();
+        (version.ssts().num_rows() + version.memtables().total_num_rows()) as u64
+    }
+
+    fn exact_rows(&self) -> Option<u64> {
+        let version = self.inner.version_control().current();
+        version.exact_rows().map(|rows| rows as u64)
+    }
+
+    fn memtable_bytes(&self) -> u64 {
+        let version = self.inner.version_control().current();
+        version.memtables().total_bytes_allocated() as u64
+    }
+
+    fn committed_sequence(&self) -> SequenceNumber {
+        self.inner.version_control().committed_sequence()
+    }
+
+    fn flush_threshold_bytes(&self) -> Option<u64> {
+        self.inner
+            .flush_strategy
+            .flush_threshold_bytes()
+            .map(|bytes| bytes as u64)
     }
 
     async fn flush(&self, ctx: &FlushContext) -> Result<()> {
         self.inner.flush(ctx).await
     }
+
+    async fn gc_orphans(&self, request: &OrphanGcRequest) -> Result<OrphanGcResponse> {
+        let inner = &self.inner;
+        orphan::gc_orphans(
+            &inner.shared.name,
+            &inner.sst_layer,
+            &inner.manifest,
+            inner.version_control(),
+            request,
+        )
+        .await
+    }
 }
 
 /// Storage related config for region.
@@ -157,6 +204,10 @@ pub struct StoreConfig<S: LogStore> {
     pub file_purger: FilePurgerRef,
     pub ttl: Option<Duration>,
     pub compaction_time_window: Option<i64>,
+    /// Whether writes skip the WAL, so unflushed data is lost on crash.
+    pub wal_disabled: bool,
+    /// Column holding the expiry time of rows, whose expired rows are dropped by compaction.
+    pub ttl_column: Option<String>,
 }
 
 pub type RecoverdMetadata = (SequenceNumber, (ManifestVersion, RawRegionMetadata));
@@ -215,6 +266,7 @@ impl<S: LogStore> RegionImpl<S> {
             store_config.file_purger.clone(),
         );
         let region = RegionImpl::new(version, store_config);
+        region.inner.start_flush_by_age().await?;
 
         Ok(region)
     }
@@ -225,21 +277,26 @@ impl<S: LogStore> RegionImpl<S> {
         let id = metadata.id();
         let name = metadata.name().to_string();
         let version_control = VersionControl::with_version(version);
-        let wal = Wal::new(id, store_config.log_store);
+        let wal = Wal::new(id, store_config.log_store).with_disabled(store_config.wal_disabled);
+        let timeline = SnapshotTimeline::new(store_config.engine_config.time_travel_retention);
+        timeline.record_open(current_time_millis(), version_control.committed_sequence());
 
-        let inner = Arc::new(RegionInner {
+        let inner = Arc::new_cyclic(|weak| RegionInner {
             shared: Arc::new(SharedData {
                 id,
                 name,
                 version_control: Arc::new(version_control),
+                timeline,
             }),
             writer: Arc::new(RegionWriter::new(
                 store_config.memtable_builder,
                 store_config.engine_config.clone(),
                 store_config.ttl,
                 store_config.compaction_time_window,
+                store_config.ttl_column,
             )),
             wal,
+            flush_by_age_task: new_flush_by_age_task(&store_config.flush_strategy, weak),
             flush_strategy: store_config.flush_strategy,
             flush_scheduler: store_config.flush_scheduler,
             compaction_scheduler: store_config.compaction_scheduler,
@@ -258,23 +315,40 @@ impl<S: LogStore> RegionImpl<S> {
         store_config: StoreConfig<S>,
         opts: &OpenOptions,
     ) -> Result<Option<RegionImpl<S>>> {
+        let timeline = SnapshotTimeline::new(store_config.engine_config.time_travel_retention);
         // Load version meta data from manifest.
         let (version, mut recovered_metadata) = match Self::recover_from_manifest(
             &store_config.manifest,
             &store_config.memtable_builder,
             &store_config.sst_layer,
             &store_config.file_purger,
+            &timeline,
         )
         .await?
         {
             (None, _) => return Ok(None),
             (Some(v), m) => (v, m),
         };
+        store_config.manifest.load_stored_bytes().await?;
 
         logging::debug!(
             "Region recovered version from manifest, version: {:?}",
             version
         );
+        // No SST is being written yet, so the generations of the tag dictionary unreferenced
+        // by the version are never used again.
+        let live_generations = version.ssts().tag_dictionary_generations();
+        if let Err(e) = store_config
+            .sst_layer
+            .purge_tag_dictionaries(&live_generations)
+            .await
+        {
+            logging::warn!(
+                "Failed to purge the tag dictionaries of region: {}, err: {:?}",
+                name,
+                e
+            );
+        }
 
         let metadata = version.metadata().clone();
         let flushed_sequence = version.flushed_sequence();
@@ -306,12 +380,14 @@ impl<S: LogStore> RegionImpl<S> {
             );
         }
 
-        let wal = Wal::new(metadata.id(), store_config.log_store);
+        let wal = Wal::new(metadata.id(), store_config.log_store)
+            .with_disabled(store_config.wal_disabled);
         wal.obsolete(flushed_sequence).await?;
         let shared = Arc::new(SharedData {
             id: metadata.id(),
             name,
             version_control,
+            timeline,
         });
         let compaction_time_window = store_config
             .compaction_time_window
@@ -321,6 +397,7 @@ impl<S: LogStore> RegionImpl<S> {
             store_config.engine_config.clone(),
             store_config.ttl,
             compaction_time_window,
+            store_config.ttl_column,
         ));
         let writer_ctx = WriterContext {
             shared: &shared,
@@ -336,6 +413,10 @@ impl<S: LogStore> RegionImpl<S> {
         writer
             .replay(recovered_metadata_after_flushed, writer_ctx)
             .await?;
+        shared.timeline.record_open(
+            current_time_millis(),
+            shared.version_control.committed_sequence(),
+        );
 
         // Try to do a manifest checkpoint on opening
         if store_config.engine_config.manifest_checkpoint_on_startup {
@@ -343,16 +424,18 @@ impl<S: LogStore> RegionImpl<S> {
             manifest.may_do_checkpoint(manifest.last_version()).await?;
         }
 
-        let inner = Arc::new(RegionInner {
+        let inner = Arc::new_cyclic(|weak| RegionInner {
             shared,
             writer,
             wal,
+            flush_by_age_task: new_flush_by_age_task(&store_config.flush_strategy, weak),
             flush_strategy: store_config.flush_strategy,
             flush_scheduler: store_config.flush_scheduler,
             compaction_scheduler: store_config.compaction_scheduler,
             sst_layer: store_config.sst_layer,
             manifest: store_config.manifest,
         });
+        inner.start_flush_by_age().await?;
 
         Ok(Some(RegionImpl { inner }))
     }
@@ -394,6 +477,7 @@ impl<S: LogStore> RegionImpl<S> {
                 v.flushed_sequence,
                 v.manifest_version,
                 v.files.into_values(),
+                v.range_tombstones,
             );
         }
 
@@ -405,6 +489,7 @@ impl<S: LogStore> RegionImpl<S> {
         memtable_builder: &MemtableBuilderRef,
         sst_layer: &AccessLayerRef,
         file_purger: &FilePurgerRef,
+        timeline: &SnapshotTimeline,
     ) -> Result<(Option<Version>, RecoveredMetadataMap)> {
         let checkpoint = manifest.last_checkpoint().await?;
 
@@ -451,7 +536,8 @@ impl<S: LogStore> RegionImpl<S> {
                             file_purger.clone(),
                         ));
                         for (manifest_version, action) in actions.drain(..) {
-                            version = Self::replay_edit(manifest_version, action, version);
+                            version =
+                                Self::replay_edit(manifest_version, action, version, timeline);
                         }
                     }
                     (RegionMetaAction::Change(c), Some(v)) => {
@@ -464,7 +550,7 @@ impl<S: LogStore> RegionImpl<S> {
                         version = None;
                     }
                     (action, Some(v)) => {
-                        version = Self::replay_edit(manifest_version, action, Some(v));
+                        version = Self::replay_edit(manifest_version, action, Some(v), timeline);
                     }
                 }
             }
@@ -486,14 +572,20 @@ impl<S: LogStore> RegionImpl<S> {
         manifest_version: ManifestVersion,
         action: RegionMetaAction,
         version: Option<Version>,
+        timeline: &SnapshotTimeline,
     ) -> Option<Version> {
         if let RegionMetaAction::Edit(e) = action {
+            if let Some(v) = &version {
+                timeline.record_edit(&e, v.flushed_sequence());
+            }
             let edit = VersionEdit {
                 files_to_add: e.files_to_add,
                 files_to_remove: e.files_to_remove,
                 flushed_sequence: e.flushed_sequence,
                 manifest_version,
                 max_memtable_id: None,
+                tombstones_to_add: e.tombstones_to_add,
+                tombstones_to_remove: e.tombstones_to_remove,
             };
             version.map(|mut v| {
                 v.apply_edit(edit);
@@ -513,11 +605,6 @@ impl<S: LogStore> RegionImpl<S> {
 // Private methods for tests.
 #[cfg(test)]
 impl<S: LogStore> RegionImpl<S> {
-    #[inline]
-    fn committed_sequence(&self) -> store_api::storage::SequenceNumber {
-        self.inner.version_control().committed_sequence()
-    }
-
     fn current_manifest_version(&self) -> ManifestVersion {
         self.inner.version_control().current_manifest_version()
     }
@@ -554,6 +641,8 @@ pub struct SharedData {
     name: String,
     // TODO(yingwen): Maybe no need to use Arc for version control.
     pub version_control: VersionControlRef,
+    /// Sequences committed at the recent times, for time-travel reads.
+    pub(crate) timeline: SnapshotTimeline,
 }
 
 impl SharedData {
@@ -568,12 +657,35 @@ impl SharedData {
     }
 }
 
+#[cfg(test)]
+impl SharedData {
+    /// Creates the shared data of an empty region named `name`.
+    pub(crate) fn new_for_test(name: &str) -> SharedData {
+        use crate::memtable::{DefaultMemtableBuilder, MemtableBuilder};
+        use crate::test_util::descriptor_util::RegionDescBuilder;
+
+        let desc = RegionDescBuilder::new(name).build();
+        let metadata: RegionMetadataRef = Arc::new(desc.try_into().unwrap());
+        let memtable = DefaultMemtableBuilder::default().build(metadata.schema().clone());
+        let version = Version::new(metadata, memtable);
+        SharedData {
+            id: version.metadata().id(),
+            name: name.to_string(),
+            version_control: Arc::new(VersionControl::with_version(version)),
+            timeline: SnapshotTimeline::new(Duration::ZERO),
+        }
+    }
+}
+
 pub type SharedDataRef = Arc<SharedData>;
 
 struct RegionInner<S: LogStore> {
     shared: SharedDataRef,
     writer: RegionWriterRef,
     wal: Wal<S>,
+    /// Checks whether to flush the region by the age of its unflushed data, if the flush
+    /// strategy needs it.
+    flush_by_age_task: Option<Arc<RepeatedTask<Error>>>,
     flush_strategy: FlushStrategyRef,
     flush_scheduler: FlushSchedulerRef,
     compaction_scheduler: CompactionSchedulerRef<S>,
@@ -593,11 +705,51 @@ impl<S: LogStore> RegionInner<S> {
         RegionMetaImpl::new(metadata)
     }
 
-    fn create_snapshot(&self) -> SnapshotImpl {
-        let version = self.version_control().current();
-        let sequence = self.version_control().committed_sequence();
+    /// Creates a snapshot of the current version and committed sequence, or the sequence
+    /// committed at `as_of` if set.
+    ///
+    /// The version and the sequence must be loaded consistently: if a flush freezes the
+    /// mutable memtable between the two loads, the rows committed to the new memtable before
+    /// the sequence is loaded are not in the version. So the version is loaded again after
+    /// the sequence and the loads are retried until the version stays the same, which never
+    /// blocks writes or flushes.
+    ///
+    /// The sequence as of a time is resolved after the version is loaded. Edits are recorded
+    /// to the timeline before they are applied, so the timeline knows all the rows dropped
+    /// from the version.
+    fn create_snapshot(&self, as_of: Option<i64>) -> Result<SnapshotImpl> {
+        let version_control = self.version_control();
+        let mut version = version_control.current();
+        let mut sequence;
+        loop {
+            sequence = version_control.committed_sequence();
+            let current = version_control.current();
+            if Arc::ptr_eq(&version, &current) {
+                break;
+            }
+            version = current;
+        }
+
+        if let Some(as_of) = as_of {
+            match self
+                .shared
+                .timeline
+                .sequence_as_of(as_of, current_time_millis())
+            {
+                Ok(Some(sequence_as_of)) => sequence = sequence.min(sequence_as_of),
+                Ok(None) => (),
+                Err(reason) => {
+                    return error::SnapshotGoneSnafu {
+                        region: &self.shared.name,
+                        as_of,
+                        reason,
+                    }
+                    .fail()
+                }
+            }
+        }
 
-        SnapshotImpl::new(version, sequence, self.sst_layer.clone())
+        Ok(SnapshotImpl::new(version, sequence, self.sst_layer.clone()))
     }
 
     fn compat_write_batch(&self, request: &mut WriteBatch) -> Result<()> {
@@ -643,11 +795,63 @@ impl<S: LogStore> RegionInner<S> {
         self.writer.alter(alter_ctx, request).await
     }
 
+    async fn delete_range(&self, request: DeleteRangeRequest) -> Result<WriteResponse> {
+        logging::info!(
+            "Delete range of region {}, name: {}, request: {:?}",
+            self.shared.id,
+            self.shared.name,
+            request
+        );
+
+        let alter_ctx = AlterContext {
+            shared: &self.shared,
+            wal: &self.wal,
+            manifest: &self.manifest,
+        };
+
+        self.writer.delete_range(alter_ctx, request).await
+    }
+
     async fn close(&self) -> Result<()> {
+        if let Some(task) = self
+            .flush_by_age_task
+            .as_ref()
+            .filter(|task| task.started())
+        {
+            task.stop().await.context(error::StopFlushByAgeTaskSnafu {
+                region: &self.shared.name,
+            })?;
+        }
         self.writer.close().await?;
         self.manifest.stop().await
     }
 
+    async fn start_flush_by_age(&self) -> Result<()> {
+        if let Some(task) = &self.flush_by_age_task {
+            task.start(common_runtime::bg_runtime()).await.context(
+                error::StartFlushByAgeTaskSnafu {
+                    region: &self.shared.name,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    async fn flush_by_age(&self) -> Result<()> {
+        let writer_ctx = WriterContext {
+            shared: &self.shared,
+            flush_strategy: &self.flush_strategy,
+            flush_scheduler: &self.flush_scheduler,
+            compaction_scheduler: &self.compaction_scheduler,
+            sst_layer: &self.sst_layer,
+            wal: &self.wal,
+            writer: &self.writer,
+            manifest: &self.manifest,
+        };
+        self.writer.flush_by_age(writer_ctx).await
+    }
+
     async fn flush(&self, ctx: &FlushContext) -> Result<()> {
         let writer_ctx = WriterContext {
             shared: &self.shared,
@@ -677,3 +881,46 @@ impl<S: LogStore> RegionInner<S> {
         self.writer.compact(writer_ctx, ctx).await
     }
 }
+
+impl<S: LogStore> Drop for RegionInner<S> {
+    fn drop(&mut self) {
+        // A region dropped without being closed would otherwise leave the task waking up
+        // until the runtime shuts down.
+        if let Some(task) = &self.flush_by_age_task {
+            task.cancel();
+        }
+    }
+}
+
+fn new_flush_by_age_task<S: LogStore>(
+    flush_strategy: &FlushStrategyRef,
+    inner: &Weak<RegionInner<S>>,
+) -> Option<Arc<RepeatedTask<Error>>> {
+    flush_strategy.age_check_interval().map(|interval| {
+        let task_fn = Arc::new(FlushByAgeTask {
+            inner: inner.clone(),
+        });
+        Arc::new(RepeatedTask::new(interval, task_fn as _))
+    })
+}
+
+/// Flushes the region by the age of its unflushed data, as the flush strategy is only checked
+/// on writes otherwise.
+struct FlushByAgeTask<S: LogStore> {
+    // Weak so the task doesn't keep the region alive.
+    inner: Weak<RegionInner<S>>,
+}
+
+#[async_trait]
+impl<S: LogStore> TaskFunction<Error> for FlushByAgeTask<S> {
+    fn name(&self) -> &str {
+        "region-flush-by-age"
+    }
+
+    async fn call(&self) -> Result<()> {
+        match self.inner.upgrade() {
+            Some(inner) => inner.flush_by_age().await,
+            None => Ok(()),
+        }
+    }
+}
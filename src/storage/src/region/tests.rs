@@ -20,11 +20,14 @@ mod close;
 mod compact;
 mod flush;
 mod projection;
+mod wal;
 
 use std::collections::{HashMap, HashSet};
 
 use common_telemetry::logging;
 use common_test_util::temp_dir::create_temp_dir;
+use common_time::range::TimestampRange;
+use common_time::timestamp::TimeUnit;
 use datatypes::prelude::{ScalarVector, WrapperType};
 use datatypes::timestamp::TimestampMillisecond;
 use datatypes::type_id::LogicalTypeId;
@@ -35,7 +38,8 @@ use object_store::services::Fs;
 use object_store::ObjectStore;
 use store_api::manifest::MAX_VERSION;
 use store_api::storage::{
-    consts, Chunk, ChunkReader, RegionMeta, ScanRequest, SequenceNumber, Snapshot, WriteRequest,
+    consts, Chunk, ChunkReader, DeleteRangeRequest, RegionMeta, ScanRequest, SequenceNumber,
+    Snapshot, WriteRequest,
 };
 
 use super::*;
@@ -161,6 +165,16 @@ impl<S: LogStore> TesterBase<S> {
         self.region.write(&self.write_ctx, batch).await.unwrap()
     }
 
+    /// Delete the rows in time range `[start, end)` by a range tombstone.
+    pub async fn delete_range(&self, start: i64, end: i64) -> WriteResponse {
+        let request = DeleteRangeRequest {
+            keys: vec![],
+            time_range: TimestampRange::with_unit(start, end, TimeUnit::Millisecond).unwrap(),
+        };
+
+        self.region.delete_range(request).await.unwrap()
+    }
+
     /// Returns a reader to scan all data.
     pub async fn full_scan_reader(&self) -> ChunkReaderImpl {
         let snapshot = self.region.snapshot(&self.read_ctx).unwrap();
@@ -437,6 +437,42 @@ async fn test_put_old_schema_after_alter() {
     assert_eq!(expect, scanned);
 }
 
+#[tokio::test]
+async fn test_put_old_schema_concurrently_with_alter() {
+    let dir = create_temp_dir("put-old-concurrently");
+    let store_dir = dir.path().to_str().unwrap();
+    let mut tester = AlterTester::new(store_dir).await;
+
+    let mut req = add_column_req(&[
+        (new_column_desc(4, "k0"), true),  // key column k0
+        (new_column_desc(5, "v1"), false), // value column v1
+    ]);
+    req.version = tester.version();
+
+    // Writes built against the initial schema race with the alter.
+    let puts = (0..20).map(|i| tester.base().try_put(&[(1000 + i, Some(i))]));
+    let (alter_result, put_results) = futures::future::join(
+        tester.base().region.alter(req),
+        futures::future::join_all(puts),
+    )
+    .await;
+    alter_result.unwrap();
+    for result in put_results {
+        result.unwrap();
+    }
+
+    // All rows have the layout of the new schema.
+    let expect = (0..20)
+        .map(|i| DataRow::new(None, 1000 + i, Some(i), None))
+        .collect::<Vec<_>>();
+    let scanned = tester.full_scan().await;
+    assert_eq!(expect, scanned);
+
+    // Still readable after replaying the WAL.
+    tester.reopen().await;
+    assert_eq!(expect, tester.full_scan().await);
+}
+
 #[tokio::test]
 async fn test_replay_metadata_after_open() {
     let dir = create_temp_dir("replay-metadata-after-open");
@@ -14,14 +14,21 @@
 
 //! Region read/write tests.
 
+use std::time::Duration;
+
+use common_error::prelude::{ErrorExt, StatusCode};
 use common_telemetry::info;
 use common_test_util::temp_dir::create_temp_dir;
+use common_time::util::current_time_millis;
 use log_store::raft_engine::log_store::RaftEngineLogStore;
-use store_api::storage::{OpenOptions, SequenceNumber, WriteResponse};
+use store_api::storage::{
+    FlushContext, OpenOptions, ReadContext, Region, SequenceNumber, WriteResponse,
+};
 
 use crate::error::Result;
 use crate::region::tests::{self, FileTesterBase};
 use crate::region::RegionImpl;
+use crate::snapshot::SnapshotImpl;
 use crate::test_util::config_util;
 
 const REGION_NAME: &str = "region-basic-0";
@@ -116,6 +123,22 @@ impl Tester {
     async fn delete(&self, keys: &[i64]) -> WriteResponse {
         self.base().delete(keys).await
     }
+
+    async fn delete_range(&self, start: i64, end: i64) -> WriteResponse {
+        self.base().delete_range(start, end).await
+    }
+
+    fn set_as_of(&mut self, as_of: Option<i64>) {
+        self.base.as_mut().unwrap().read_ctx.as_of = as_of;
+    }
+
+    fn snapshot_as_of(&self, as_of: i64) -> Result<SnapshotImpl> {
+        let ctx = ReadContext {
+            as_of: Some(as_of),
+            ..Default::default()
+        };
+        self.base().region.snapshot(&ctx)
+    }
 }
 
 #[tokio::test]
@@ -244,6 +267,42 @@ async fn test_put_delete_scan() {
     assert_eq!(expect, output);
 }
 
+#[tokio::test]
+async fn test_put_delete_range_scan() {
+    let dir = create_temp_dir("put-delete-range-scan");
+    let store_dir = dir.path().to_str().unwrap();
+    let mut tester = Tester::new(REGION_NAME, store_dir).await;
+
+    let data: Vec<_> = (1000..1005).map(|ts| (ts, Some(ts))).collect();
+    tester.put(&data).await;
+
+    let resp = tester.delete_range(1001, 1003).await;
+    assert_eq!(tester.committed_sequence(), resp.sequence);
+    let expect = vec![(1000, Some(1000)), (1003, Some(1003)), (1004, Some(1004))];
+    assert_eq!(expect, tester.full_scan().await);
+
+    // Rows written after the tombstone are visible.
+    tester.put(&[(1002, Some(2002))]).await;
+    let expect = vec![
+        (1000, Some(1000)),
+        (1002, Some(2002)),
+        (1003, Some(1003)),
+        (1004, Some(1004)),
+    ];
+    assert_eq!(expect, tester.full_scan().await);
+
+    // The tombstone still hides the rows flushed to SSTs, and is recovered on reopen.
+    tester
+        .base()
+        .region
+        .flush(&FlushContext { wait: true })
+        .await
+        .unwrap();
+    assert_eq!(expect, tester.full_scan().await);
+    tester.reopen().await;
+    assert_eq!(expect, tester.full_scan().await);
+}
+
 #[tokio::test]
 async fn test_put_delete_absent_key() {
     let dir = create_temp_dir("put-delete-scan");
@@ -274,3 +333,53 @@ async fn test_put_delete_absent_key() {
     let output = tester.full_scan().await;
     assert_eq!(expect, output);
 }
+
+#[tokio::test]
+async fn test_time_travel_scan() {
+    let dir = create_temp_dir("time-travel-scan");
+    let store_dir = dir.path().to_str().unwrap();
+    let mut tester = Tester::new(REGION_NAME, store_dir).await;
+
+    let pause = Duration::from_millis(200);
+    tester.put(&[(1000, Some(100)), (1001, Some(101))]).await;
+    tokio::time::sleep(pause).await;
+    let before_overwrite = current_time_millis();
+    tokio::time::sleep(pause).await;
+    tester.put(&[(1000, Some(200))]).await;
+    tokio::time::sleep(pause).await;
+    let after_overwrite = current_time_millis();
+
+    tester.set_as_of(Some(before_overwrite));
+    assert_eq!(
+        vec![(1000, Some(100)), (1001, Some(101))],
+        tester.full_scan().await
+    );
+    tester.set_as_of(Some(after_overwrite));
+    assert_eq!(
+        vec![(1000, Some(200)), (1001, Some(101))],
+        tester.full_scan().await
+    );
+
+    let two_hours_ago = current_time_millis() - 2 * 60 * 60 * 1000;
+    let err = tester.snapshot_as_of(two_hours_ago).unwrap_err();
+    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+    assert!(err
+        .to_string()
+        .contains("is gone, it's out of the time travel retention 600s"));
+
+    // The old value is dropped by the flush, but the new one is still readable.
+    tester
+        .base()
+        .region
+        .flush(&FlushContext { wait: true })
+        .await
+        .unwrap();
+    let err = tester.snapshot_as_of(before_overwrite).unwrap_err();
+    assert!(err
+        .to_string()
+        .ends_with("is gone, the rows overwritten after it are dropped by flush or compaction"));
+    assert_eq!(
+        vec![(1000, Some(200)), (1001, Some(101))],
+        tester.full_scan().await
+    );
+}
@@ -17,13 +17,18 @@
 use std::env;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use common_telemetry::logging;
 use common_test_util::temp_dir::create_temp_dir;
+use futures::StreamExt;
 use log_store::raft_engine::log_store::RaftEngineLogStore;
 use object_store::services::{Fs, S3};
-use object_store::ObjectStore;
-use store_api::storage::{FlushContext, Region, WriteResponse};
+use object_store::{EntryMode, Metakey, ObjectStore};
+use store_api::storage::{
+    DeletionPacer, FlushContext, OrphanGcRequest, OrphanGcResponse, OrphanKind, Region,
+    ScanRequest, Snapshot, WriteResponse,
+};
 use tokio::sync::Notify;
 
 use crate::compaction::{CompactionHandler, SimplePicker};
@@ -34,6 +39,7 @@ use crate::region::tests::{self, FileTesterBase};
 use crate::region::{CompactContext, FlushStrategyRef, RegionImpl};
 use crate::scheduler::rate_limit::BoxedRateLimitToken;
 use crate::scheduler::{Handler, LocalScheduler, SchedulerConfig};
+use crate::sst::FileId;
 use crate::test_util::config_util;
 use crate::test_util::flush_switch::FlushSwitch;
 
@@ -205,6 +211,52 @@ impl CompactionTester {
             .unwrap();
     }
 
+    /// Returns bytes of the SSTs and the manifest files in the object store.
+    async fn listed_bytes(&self) -> (u64, u64) {
+        let mut lister = self.object_store.scan("/").await.unwrap();
+        let (mut sst_bytes, mut manifest_bytes) = (0, 0);
+        while let Some(entry) = lister.next().await {
+            let entry = entry.unwrap();
+            let metadata = self
+                .object_store
+                .metadata(&entry, Metakey::Mode | Metakey::ContentLength)
+                .await
+                .unwrap();
+            if !matches!(metadata.mode(), EntryMode::FILE) {
+                continue;
+            }
+            if entry.path().ends_with(".parquet") {
+                sst_bytes += metadata.content_length();
+            } else if entry.path().contains("/manifest/") {
+                manifest_bytes += metadata.content_length();
+            }
+        }
+        (sst_bytes, manifest_bytes)
+    }
+
+    async fn gc_orphans(&self, delete: bool, safety_age: Duration) -> OrphanGcResponse {
+        let request = OrphanGcRequest {
+            delete,
+            safety_age,
+            deletion_pacer: Some(Arc::new(DeletionPacer::new(100))),
+        };
+        self.base().region.gc_orphans(&request).await.unwrap()
+    }
+
+    /// Writes a SST not referenced by the region, returns its path.
+    async fn write_orphan_sst(&self) -> String {
+        let path = format!("{REGION_NAME}/{}", FileId::random().as_parquet());
+        self.object_store
+            .write(&path, b"orphan".to_vec())
+            .await
+            .unwrap();
+        path
+    }
+
+    async fn exists(&self, path: &str) -> bool {
+        self.object_store.is_exist(path).await.unwrap()
+    }
+
     /// Close region and clean up files.
     async fn clean_up(mut self) {
         self.base = None;
@@ -280,3 +332,213 @@ async fn test_compact_during_read_on_s3() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_snapshot_scan_across_flush_and_compaction() {
+    common_telemetry::init_default_ut_logging();
+
+    let dir = create_temp_dir("compact_snapshot_scan");
+    let store_dir = dir.path().to_str().unwrap();
+
+    let mut tester = CompactionTester::new(
+        store_dir,
+        EngineConfig {
+            max_files_in_l0: 100,
+            ..Default::default()
+        },
+        Arc::new(FlushSwitch::default()),
+        None,
+    )
+    .await;
+
+    let expect: Vec<_> = (0..200).map(|v| (v, Some(v))).collect();
+    tester.put(&expect[0..100]).await;
+    tester.flush(None).await;
+    // Rows of the snapshot still in the mutable memtable.
+    tester.put(&expect[100..150]).await;
+
+    tester.base_mut().read_ctx.batch_size = 1;
+    let read_ctx = tester.base().read_ctx.clone();
+    let snapshot = tester.base().region.snapshot(&read_ctx).unwrap();
+    let sequence = tester.base().committed_sequence();
+    let reader = snapshot
+        .scan(&read_ctx, ScanRequest::default())
+        .await
+        .unwrap()
+        .reader;
+
+    // Overwrites rows of the snapshot and writes new rows to the memtable the scan reads, then
+    // flushes the memtable and compacts all SSTs while the scan is ongoing.
+    let updated: Vec<_> = (0..50).map(|v| (v, Some(v + 1000))).collect();
+    tester.put(&updated).await;
+    tester.put(&expect[150..200]).await;
+    tester.flush(None).await;
+
+    // The rows above the sequence are also hidden in the flushed SST.
+    let resp = tester
+        .base()
+        .region
+        .snapshot(&read_ctx)
+        .unwrap()
+        .scan(
+            &read_ctx,
+            ScanRequest {
+                sequence: Some(sequence),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let output = tester.base().collect_reader(resp.reader).await;
+    assert_eq!(expect[0..150], output);
+
+    tester.compact().await;
+
+    // Exactly the rows of the snapshot, without duplicates or gaps.
+    let output = tester.base().collect_reader(reader).await;
+    assert_eq!(expect[0..150], output);
+    // The snapshot still reads its version after the compaction.
+    let resp = snapshot
+        .scan(&read_ctx, ScanRequest::default())
+        .await
+        .unwrap();
+    let output = tester.base().collect_reader(resp.reader).await;
+    assert_eq!(expect[0..150], output);
+
+    // The compacted SSTs are kept until the snapshot is dropped.
+    assert_eq!(0, tester.purge_handler.num_deleted());
+    drop(snapshot);
+    while tester.purge_handler.num_deleted() < 2 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let mut latest = updated;
+    latest.extend_from_slice(&expect[50..200]);
+    assert_eq!(latest, tester.base().full_scan().await);
+
+    tester.clean_up().await;
+}
+
+#[tokio::test]
+async fn test_storage_bytes_after_compaction() {
+    common_telemetry::init_default_ut_logging();
+
+    let dir = create_temp_dir("compact_storage_bytes");
+    let store_dir = dir.path().to_str().unwrap();
+
+    let mut tester = CompactionTester::new(
+        store_dir,
+        EngineConfig {
+            max_files_in_l0: 100,
+            ..Default::default()
+        },
+        Arc::new(FlushSwitch::default()),
+        None,
+    )
+    .await;
+
+    let expect: Vec<_> = (0..200).map(|v| (v, Some(v))).collect();
+    tester.put(&expect[0..100]).await;
+    tester.flush(None).await;
+    tester.put(&expect[100..200]).await;
+    tester.flush(None).await;
+
+    let region = &tester.base().region;
+    let (sst_bytes, manifest_bytes) = tester.listed_bytes().await;
+    assert!(sst_bytes > 0);
+    assert!(manifest_bytes > 0);
+    assert_eq!(sst_bytes + manifest_bytes, region.storage_bytes());
+    assert_eq!(sst_bytes, region.disk_usage_bytes());
+
+    // Holds the SSTs by a reader so they are not purged after compaction.
+    tester.base_mut().read_ctx.batch_size = 1;
+    let reader = tester.base().full_scan_reader().await;
+    tester.compact().await;
+
+    let region = &tester.base().region;
+    let (sst_bytes, manifest_bytes) = tester.listed_bytes().await;
+    assert_eq!(sst_bytes + manifest_bytes, region.storage_bytes());
+    // The removed SSTs are still stored until purged.
+    assert!(region.disk_usage_bytes() < sst_bytes);
+
+    drop(reader);
+    while tester.purge_handler.num_deleted() < 2 {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let region = &tester.base().region;
+    let (sst_bytes, manifest_bytes) = tester.listed_bytes().await;
+    assert_eq!(sst_bytes + manifest_bytes, region.storage_bytes());
+    assert_eq!(sst_bytes, region.disk_usage_bytes());
+
+    tester.clean_up().await;
+}
+
+#[tokio::test]
+async fn test_gc_orphans() {
+    common_telemetry::init_default_ut_logging();
+
+    let dir = create_temp_dir("compact_gc_orphans");
+    let store_dir = dir.path().to_str().unwrap();
+
+    let mut tester = CompactionTester::new(
+        store_dir,
+        EngineConfig {
+            max_files_in_l0: 100,
+            ..Default::default()
+        },
+        Arc::new(FlushSwitch::default()),
+        None,
+    )
+    .await;
+
+    let expect: Vec<_> = (0..200).map(|v| (v, Some(v))).collect();
+    tester.put(&expect[0..100]).await;
+    tester.flush(None).await;
+    tester.put(&expect[100..200]).await;
+    tester.flush(None).await;
+
+    let orphan = tester.write_orphan_sst().await;
+    // Audits the orphans.
+    let resp = tester.gc_orphans(false, Duration::ZERO).await;
+    assert_eq!(1, resp.orphans.len());
+    let found = &resp.orphans[0];
+    assert_eq!(orphan, found.path);
+    assert_eq!(OrphanKind::Sst, found.kind);
+    assert_eq!(6, found.size);
+    assert!(found.age.is_some());
+    assert!(!found.deleted);
+    assert!(tester.exists(&orphan).await);
+
+    // Simulates an in-flight scan holding the SSTs removed by compaction.
+    tester.base_mut().read_ctx.batch_size = 1;
+    let reader = tester.base().full_scan_reader().await;
+    tester.compact().await;
+    assert_eq!(0, tester.purge_handler.num_deleted());
+    let listed_bytes = tester.listed_bytes().await.0;
+
+    // Young orphans are only reported.
+    let resp = tester.gc_orphans(true, Duration::from_secs(3600)).await;
+    assert_eq!(1, resp.orphans.len());
+    assert!(!resp.orphans[0].deleted);
+    assert!(tester.exists(&orphan).await);
+
+    // Deletes the orphan but spares the SSTs leased by the reader.
+    let resp = tester.gc_orphans(true, Duration::ZERO).await;
+    assert_eq!(1, resp.orphans.len());
+    assert_eq!(orphan, resp.orphans[0].path);
+    assert!(resp.orphans[0].deleted);
+    assert!(!tester.exists(&orphan).await);
+    assert_eq!(listed_bytes - 6, tester.listed_bytes().await.0);
+
+    let output = tester.base().collect_reader(reader).await;
+    assert_eq!(expect.len(), output.len());
+
+    // The removed SSTs are purged instead once released.
+    while tester.purge_handler.num_deleted() < 2 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let resp = tester.gc_orphans(false, Duration::ZERO).await;
+    assert!(resp.orphans.is_empty());
+
+    tester.clean_up().await;
+}
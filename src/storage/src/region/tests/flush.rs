@@ -251,3 +251,53 @@ async fn test_merge_read_after_flush() {
     let output = tester.full_scan().await;
     assert_eq!(expect, output);
 }
+
+#[tokio::test]
+async fn test_exact_rows_after_flush() {
+    let dir = create_temp_dir("exact-rows-flush");
+    let store_dir = dir.path().to_str().unwrap();
+
+    let flush_switch = Arc::new(FlushSwitch::default());
+    let tester = FlushTester::new(store_dir, flush_switch.clone()).await;
+    assert_eq!(Some(0), tester.base().region.exact_rows());
+
+    tester.put(&[(1000, Some(100)), (2000, Some(200))]).await;
+    assert_eq!(Some(2), tester.base().region.exact_rows());
+
+    tester.flush(None).await;
+    assert_eq!(Some(2), tester.base().region.exact_rows());
+
+    // Rows in the memtable are newer than the rows in the SST.
+    tester.put(&[(3000, Some(300))]).await;
+    assert_eq!(Some(3), tester.base().region.exact_rows());
+    assert_eq!(3, tester.full_scan().await.len());
+
+    // Reopen
+    let mut tester = tester;
+    tester.reopen().await;
+    assert_eq!(Some(3), tester.base().region.exact_rows());
+
+    // The row in the SST is overwritten by the memtable.
+    tester.put(&[(2000, Some(201))]).await;
+    assert_eq!(None, tester.base().region.exact_rows());
+    assert_eq!(3, tester.full_scan().await.len());
+}
+
+#[tokio::test]
+async fn test_exact_rows_after_delete() {
+    let dir = create_temp_dir("exact-rows-delete");
+    let store_dir = dir.path().to_str().unwrap();
+
+    let flush_switch = Arc::new(FlushSwitch::default());
+    let tester = FlushTester::new(store_dir, flush_switch.clone()).await;
+
+    tester.put(&[(1000, Some(100)), (2000, Some(200))]).await;
+    tester.flush(None).await;
+    assert_eq!(Some(2), tester.base().region.exact_rows());
+
+    // The SST has a tombstone after flush.
+    tester.base().delete(&[1000]).await;
+    tester.flush(None).await;
+    assert_eq!(None, tester.base().region.exact_rows());
+    assert_eq!(vec![(2000, Some(200))], tester.full_scan().await);
+}
@@ -0,0 +1,135 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for regions with the WAL disabled.
+
+use common_test_util::temp_dir::create_temp_dir;
+use futures::TryStreamExt;
+use store_api::storage::{FlushContext, OpenOptions, Region};
+
+use crate::region::tests::{self, FileTesterBase};
+use crate::region::RegionImpl;
+use crate::test_util::config_util;
+
+const REGION_NAME: &str = "region-wal-0";
+
+/// Tester for regions with or without the WAL.
+struct WalTester {
+    base: Option<FileTesterBase>,
+    store_dir: String,
+    wal_disabled: bool,
+}
+
+impl WalTester {
+    async fn new(store_dir: &str, wal_disabled: bool) -> WalTester {
+        let metadata = tests::new_metadata(REGION_NAME, false);
+        let mut store_config = config_util::new_store_config(REGION_NAME, store_dir).await;
+        store_config.wal_disabled = wal_disabled;
+        let region = RegionImpl::create(metadata, store_config).await.unwrap();
+
+        WalTester {
+            base: Some(FileTesterBase::with_region(region)),
+            store_dir: store_dir.to_string(),
+            wal_disabled,
+        }
+    }
+
+    /// Closes the region without flushing it, like a crash, and reopens it.
+    async fn reopen(&mut self) {
+        if let Some(base) = self.base.as_ref() {
+            base.close().await;
+        }
+        self.base = None;
+
+        let mut store_config = config_util::new_store_config(REGION_NAME, &self.store_dir).await;
+        store_config.wal_disabled = self.wal_disabled;
+        let opts = OpenOptions {
+            wal_disabled: self.wal_disabled,
+            ..Default::default()
+        };
+        let region = RegionImpl::open(REGION_NAME.to_string(), store_config, &opts)
+            .await
+            .unwrap()
+            .unwrap();
+        self.base = Some(FileTesterBase::with_region(region));
+    }
+
+    #[inline]
+    fn base(&self) -> &FileTesterBase {
+        self.base.as_ref().unwrap()
+    }
+
+    /// Returns the number of entries of the region in the WAL.
+    async fn num_wal_entries(&self) -> usize {
+        let wal = &self.base().region.inner.wal;
+        let stream = wal.read_from_wal(0).await.unwrap();
+        stream.try_collect::<Vec<_>>().await.unwrap().len()
+    }
+}
+
+#[tokio::test]
+async fn test_write_wal_disabled_region() {
+    common_telemetry::init_default_ut_logging();
+
+    for wal_disabled in [false, true] {
+        let dir = create_temp_dir("write-wal-disabled");
+        let store_dir = dir.path().to_str().unwrap();
+        let tester = WalTester::new(store_dir, wal_disabled).await;
+
+        tester.base().put(&[(1000, Some(100))]).await;
+        tester.base().put(&[(2000, Some(200))]).await;
+
+        let expect = if wal_disabled { 0 } else { 2 };
+        assert_eq!(expect, tester.num_wal_entries().await);
+        assert_eq!(
+            vec![(1000, Some(100)), (2000, Some(200))],
+            tester.base().full_scan().await
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_reopen_wal_disabled_region() {
+    common_telemetry::init_default_ut_logging();
+
+    for wal_disabled in [false, true] {
+        let dir = create_temp_dir("reopen-wal-disabled");
+        let store_dir = dir.path().to_str().unwrap();
+        let mut tester = WalTester::new(store_dir, wal_disabled).await;
+
+        tester.base().put(&[(1000, Some(100))]).await;
+        tester
+            .base()
+            .region
+            .flush(&FlushContext::default())
+            .await
+            .unwrap();
+        tester.base().put(&[(2000, Some(200))]).await;
+
+        tester.reopen().await;
+
+        // Only the flushed data survives if the WAL is disabled.
+        let expect = if wal_disabled {
+            vec![(1000, Some(100))]
+        } else {
+            vec![(1000, Some(100)), (2000, Some(200))]
+        };
+        assert_eq!(expect, tester.base().full_scan().await);
+
+        // The region is still writable after reopening.
+        tester.base().put(&[(3000, Some(300))]).await;
+        let output = tester.base().full_scan().await;
+        assert_eq!(Some(&(3000, Some(300))), output.last());
+    }
+}
@@ -15,15 +15,18 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use common_base::readable_size::ReadableSize;
 use common_error::prelude::BoxedError;
 use common_telemetry::tracing::log::{debug, info};
 use common_telemetry::{error, logging};
+use common_time::util::current_time_millis;
 use futures::TryStreamExt;
+use metrics::increment_counter;
 use snafu::{ensure, ResultExt};
 use store_api::logstore::LogStore;
 use store_api::manifest::{Manifest, ManifestVersion, MetaAction};
-use store_api::storage::{AlterRequest, FlushContext, SequenceNumber, WriteContext, WriteResponse};
+use store_api::storage::{
+    AlterRequest, DeleteRangeRequest, FlushContext, SequenceNumber, WriteContext, WriteResponse,
+};
 use tokio::sync::{oneshot, Mutex};
 
 use crate::background::JobHandle;
@@ -41,7 +44,8 @@ use crate::region::{
     CompactContext, RecoverdMetadata, RecoveredMetadataMap, RegionManifest, SharedDataRef,
 };
 use crate::schema::compat::CompatWrite;
-use crate::sst::AccessLayerRef;
+use crate::sst::{AccessLayerRef, WriteOptions};
+use crate::tombstone::{self, RangeTombstone};
 use crate::version::{VersionControl, VersionControlRef, VersionEdit, VersionRef};
 use crate::wal::Wal;
 use crate::write_batch::WriteBatch;
@@ -69,6 +73,7 @@ impl RegionWriter {
         config: Arc<EngineConfig>,
         ttl: Option<Duration>,
         compaction_time_window: Option<i64>,
+        ttl_column: Option<String>,
     ) -> RegionWriter {
         RegionWriter {
             inner: Mutex::new(WriterInner::new(
@@ -76,6 +81,7 @@ impl RegionWriter {
                 config,
                 ttl,
                 compaction_time_window,
+                ttl_column,
             )),
             version_mutex: Mutex::new(()),
         }
@@ -135,6 +141,11 @@ impl RegionWriter {
         let files_to_add = edit.files_to_add.clone();
         let files_to_remove = edit.files_to_remove.clone();
         let flushed_sequence = edit.flushed_sequence;
+        // Records the edit before it's applied, so no reader resolves the times whose rows are
+        // dropped by the edit against the new version.
+        shared
+            .timeline
+            .record_edit(&edit, version_control.current().flushed_sequence());
 
         // Persist the meta action.
         let mut action_list = RegionMetaActionList::with_action(RegionMetaAction::Edit(edit));
@@ -152,11 +163,15 @@ impl RegionWriter {
             flushed_sequence,
             manifest_version,
             max_memtable_id,
+            tombstones_to_add: Vec::new(),
+            tombstones_to_remove: Vec::new(),
         };
 
         // We could tolerate failure during persisting manifest version to the WAL, since it won't
         // affect how we applying the edit to the version.
         version_control.apply_edit(version_edit);
+        let manifest_version =
+            Self::remove_obsolete_tombstones(version_control, manifest, manifest_version).await;
         // TODO(yingwen): We should set the flush handle to `None`, but we can't acquire
         // write lock here.
 
@@ -231,6 +246,112 @@ impl RegionWriter {
             .await
     }
 
+    /// Deletes the rows matching `request` by a range tombstone, which takes the sequence
+    /// persisting the manifest version of the tombstone, so it deletes all the rows committed
+    /// before it.
+    pub async fn delete_range<S: LogStore>(
+        &self,
+        alter_ctx: AlterContext<'_, S>,
+        request: DeleteRangeRequest,
+    ) -> Result<WriteResponse> {
+        // Holds the write lock, so no write takes the sequence of the tombstone.
+        let inner = self.inner.lock().await;
+
+        ensure!(!inner.is_closed(), error::ClosedRegionSnafu);
+
+        let version_control = alter_ctx.version_control();
+        let _lock = self.version_mutex.lock().await;
+
+        let metadata = version_control.metadata();
+        let sequence = version_control.committed_sequence() + 1;
+        let tombstone = RangeTombstone::new(&metadata, sequence, request)?;
+        let edit = RegionEdit {
+            region_version: metadata.version(),
+            flushed_sequence: None,
+            flushed_at: None,
+            files_to_add: Vec::new(),
+            files_to_remove: Vec::new(),
+            tombstones_to_add: vec![tombstone.clone()],
+            tombstones_to_remove: Vec::new(),
+        };
+        let mut action_list = RegionMetaActionList::with_action(RegionMetaAction::Edit(edit));
+        action_list.set_prev_version(version_control.current_manifest_version());
+        let manifest_version = alter_ctx.manifest.update(action_list).await?;
+
+        // The tombstone is above the committed sequence, so readers ignore it until the
+        // sequence is committed.
+        version_control.apply_edit(VersionEdit {
+            files_to_add: Vec::new(),
+            files_to_remove: Vec::new(),
+            flushed_sequence: None,
+            manifest_version,
+            max_memtable_id: None,
+            tombstones_to_add: vec![tombstone],
+            tombstones_to_remove: Vec::new(),
+        });
+        self.persist_manifest_version(alter_ctx.wal, version_control, manifest_version)
+            .await?;
+        alter_ctx
+            .shared
+            .timeline
+            .record_write(current_time_millis(), sequence);
+
+        Ok(WriteResponse { sequence })
+    }
+
+    /// Removes the range tombstones no SST or memtable may hold a row hidden by, returns the
+    /// manifest version after the removal. The tombstones are kept if the removal fails, as
+    /// they delete nothing but cost the readers.
+    ///
+    /// This method should be protected by the `version_mutex`.
+    async fn remove_obsolete_tombstones(
+        version_control: &VersionControlRef,
+        manifest: &RegionManifest,
+        manifest_version: ManifestVersion,
+    ) -> ManifestVersion {
+        let version = version_control.current();
+        let obsolete = version.obsolete_tombstones();
+        if obsolete.is_empty() {
+            return manifest_version;
+        }
+
+        let edit = RegionEdit {
+            region_version: version.metadata().version(),
+            flushed_sequence: None,
+            flushed_at: None,
+            files_to_add: Vec::new(),
+            files_to_remove: Vec::new(),
+            tombstones_to_add: Vec::new(),
+            tombstones_to_remove: obsolete.clone(),
+        };
+        let mut action_list = RegionMetaActionList::with_action(RegionMetaAction::Edit(edit));
+        action_list.set_prev_version(manifest_version);
+        match manifest.update(action_list).await {
+            Ok(manifest_version) => {
+                info!(
+                    "Remove range tombstones {:?} of region {}",
+                    obsolete,
+                    version.metadata().name()
+                );
+                version_control.apply_edit(VersionEdit {
+                    files_to_add: Vec::new(),
+                    files_to_remove: Vec::new(),
+                    flushed_sequence: None,
+                    manifest_version,
+                    max_memtable_id: None,
+                    tombstones_to_add: Vec::new(),
+                    tombstones_to_remove: obsolete,
+                });
+                manifest_version
+            }
+            Err(e) => {
+                let region = version.metadata().name();
+                error!(e; "Failed to remove range tombstones of region {}", region);
+                manifest_version
+            }
+        }
+    }
+
     /// Allocate a sequence and persist the manifest version using that sequence to the wal.
     ///
     /// This method should be protected by the `version_mutex`.
@@ -295,6 +416,29 @@ impl RegionWriter {
         Ok(())
     }
 
+    /// Flushes the region if its flush strategy decides so by the age of its unflushed data.
+    pub async fn flush_by_age<S: LogStore>(&self, writer_ctx: WriterContext<'_, S>) -> Result<()> {
+        let mut inner = self.inner.lock().await;
+
+        if inner.is_closed() {
+            return Ok(());
+        }
+
+        let mutable_bytes_allocated = writer_ctx
+            .version_control()
+            .current()
+            .memtables()
+            .mutable_bytes_allocated();
+        if writer_ctx
+            .flush_strategy
+            .should_flush_by_age(writer_ctx.shared, mutable_bytes_allocated)
+        {
+            inner.trigger_flush(&writer_ctx).await?;
+        }
+
+        Ok(())
+    }
+
     /// Compact manually.
     pub async fn compact<S: LogStore>(
         &self,
@@ -304,9 +448,9 @@ impl RegionWriter {
         let mut inner = self.inner.lock().await;
 
         ensure!(!inner.is_closed(), error::ClosedRegionSnafu);
-        let sst_write_buffer_size = inner.engine_config.sst_write_buffer_size;
+        let sst_write_options = inner.engine_config.sst_write_options();
         inner
-            .manual_compact(writer_ctx, ctx, sst_write_buffer_size)
+            .manual_compact(writer_ctx, ctx, sst_write_options)
             .await
     }
 
@@ -368,6 +512,7 @@ struct WriterInner {
     engine_config: Arc<EngineConfig>,
     ttl: Option<Duration>,
     compaction_time_window: Option<i64>,
+    ttl_column: Option<String>,
 }
 
 impl WriterInner {
@@ -376,6 +521,7 @@ impl WriterInner {
         engine_config: Arc<EngineConfig>,
         ttl: Option<Duration>,
         compaction_time_window: Option<i64>,
+        ttl_column: Option<String>,
     ) -> WriterInner {
         WriterInner {
             memtable_builder,
@@ -384,6 +530,7 @@ impl WriterInner {
             closed: false,
             ttl,
             compaction_time_window,
+            ttl_column,
         }
     }
 
@@ -428,8 +575,14 @@ impl WriterInner {
         // Update committed_sequence to make current batch visible. The `&mut self` of WriterInner
         // guarantees the writer is exclusive.
         version_control.set_committed_sequence(next_sequence);
+        writer_ctx
+            .shared
+            .timeline
+            .record_write(current_time_millis(), next_sequence);
 
-        Ok(WriteResponse {})
+        Ok(WriteResponse {
+            sequence: next_sequence,
+        })
     }
 
     async fn replay<S: LogStore>(
@@ -475,6 +628,7 @@ impl WriterInner {
 
                 if let Some(payload) = payload {
                     num_requests += 1;
+                    increment_counter!(crate::metrics::WAL_REPLAY_ENTRIES);
                     // Note that memtables of `Version` may be updated during replay.
                     let version = version_control.current();
 
@@ -523,11 +677,21 @@ impl WriterInner {
                     next_apply_metadata,
                     version_control,
                 )?;
+                // Sequences before the metadata change have been used, even if the requests
+                // using them are not in the WAL, e.g. the WAL is disabled.
+                last_sequence = sequence_before_alter;
 
                 num_recovered_metadata += 1;
                 next_apply_metadata = recovered_metadata.pop_first();
             }
 
+            // Sequences of the range tombstones have been used, even if the WAL entries
+            // persisting their manifest versions are lost.
+            let version = version_control.current();
+            if let Some(sequence) = tombstone::max_sequence(version.range_tombstones()) {
+                last_sequence = last_sequence.max(sequence);
+            }
+
             version_control.set_committed_sequence(last_sequence);
         }
 
@@ -620,11 +784,8 @@ impl WriterInner {
 
     async fn trigger_flush<S: LogStore>(&mut self, ctx: &WriterContext<'_, S>) -> Result<()> {
         let version_control = &ctx.shared.version_control;
-        let new_mutable = self.alloc_memtable(version_control);
-        // Freeze all mutable memtables so we can flush them later.
-        version_control.freeze_mutable(new_mutable);
 
-        if let Some(flush_handle) = self.flush_handle.take() {
+        if let Some(mut flush_handle) = self.flush_handle.take() {
             // Previous flush job is incomplete, wait util it is finished (write stall).
             // However the last flush job may fail, in which case, we just return error
             // and abort current write request. The flush handle is left empty, so the next
@@ -633,12 +794,31 @@ impl WriterInner {
 
             // TODO(yingwen): We should release the write lock during waiting flush done, which
             // needs something like async condvar.
-            flush_handle.join().await.map_err(|e| {
+            let result = match self.engine_config.max_write_stall {
+                Some(timeout) => match flush_handle.join_timeout(timeout).await {
+                    Some(result) => result,
+                    None => {
+                        // The flush is still running, the next write waits for it again.
+                        self.flush_handle = Some(flush_handle);
+                        return error::WriteStallSnafu {
+                            region: &ctx.shared.name,
+                            timeout,
+                        }
+                        .fail();
+                    }
+                },
+                None => flush_handle.join().await,
+            };
+            result.map_err(|e| {
                 logging::error!(e; "Previous flush job failed, region: {}", ctx.shared.name);
                 e
             })?;
         }
 
+        let new_mutable = self.alloc_memtable(version_control);
+        // Freeze all mutable memtables so we can flush them later.
+        version_control.freeze_mutable(new_mutable);
+
         let current_version = version_control.current();
         let (max_memtable_id, mem_to_flush) = current_version.memtables().memtables_to_flush();
 
@@ -653,6 +833,7 @@ impl WriterInner {
             &self.engine_config,
             self.ttl,
             self.compaction_time_window,
+            self.ttl_column.clone(),
         );
 
         let flush_req = FlushJob {
@@ -660,6 +841,7 @@ impl WriterInner {
             memtables: mem_to_flush,
             // In write thread, safe to use current committed sequence.
             flush_sequence: version_control.committed_sequence(),
+            flushed_at: current_time_millis(),
             shared: ctx.shared.clone(),
             sst_layer: ctx.sst_layer.clone(),
             writer: ctx.writer.clone(),
@@ -682,7 +864,7 @@ impl WriterInner {
         &mut self,
         writer_ctx: WriterContext<'_, S>,
         compact_ctx: CompactContext,
-        sst_write_buffer_size: ReadableSize,
+        sst_write_options: WriteOptions,
     ) -> Result<()> {
         let region_id = writer_ctx.shared.id();
         let mut compaction_request = CompactionRequestImpl {
@@ -694,8 +876,9 @@ impl WriterInner {
             wal: writer_ctx.wal.clone(),
             ttl: self.ttl,
             compaction_time_window: self.compaction_time_window,
+            ttl_column: self.ttl_column.clone(),
             sender: None,
-            sst_write_buffer_size,
+            sst_write_options,
         };
 
         let compaction_scheduler = writer_ctx.compaction_scheduler.clone();
@@ -742,6 +925,7 @@ impl WriterInner {
         config: &Arc<EngineConfig>,
         ttl: Option<Duration>,
         compaction_time_window: Option<i64>,
+        ttl_column: Option<String>,
     ) -> Option<FlushCallback> {
         let region_id = version.metadata().id();
         let compaction_request = CompactionRequestImpl {
@@ -753,8 +937,9 @@ impl WriterInner {
             wal: ctx.wal.clone(),
             ttl,
             compaction_time_window,
+            ttl_column,
             sender: None,
-            sst_write_buffer_size: config.sst_write_buffer_size,
+            sst_write_options: config.sst_write_options(),
         };
         let compaction_scheduler = ctx.compaction_scheduler.clone();
         let shared_data = ctx.shared.clone();
@@ -115,6 +115,12 @@ impl RegionSchema {
         self.columns.row_key_end()
     }
 
+    /// Returns the index of the timestamp column in the row key columns.
+    #[inline]
+    pub(crate) fn timestamp_key_index(&self) -> usize {
+        self.columns.timestamp_key_index()
+    }
+
     #[inline]
     pub(crate) fn sequence_index(&self) -> usize {
         self.store_schema.sequence_index()
@@ -26,6 +26,11 @@ use crate::sst::AccessLayerRef;
 use crate::version::VersionRef;
 
 /// [Snapshot] implementation.
+///
+/// A snapshot pins the version it reads, so the memtables and SSTs of the version are still
+/// readable after a flush or compaction replaces them, and the removed SSTs are only purged
+/// after all snapshots and readers of them are dropped. Rows above the visible sequence are
+/// hidden from both memtables and SSTs.
 pub struct SnapshotImpl {
     version: VersionRef,
     /// Max sequence number (inclusive) visible to user.
@@ -58,8 +63,11 @@ impl Snapshot for SnapshotImpl {
                 .reserve_num_memtables(memtable_version.num_memtables())
                 .projection(request.projection)
                 .filters(request.filters)
+                .dictionary_tags(request.dictionary_tags)
                 .batch_size(ctx.batch_size)
                 .visible_sequence(visible_sequence)
+                .flushed_sequence(self.version.flushed_sequence())
+                .range_tombstones(self.version.range_tombstones())
                 .pick_memtables(mutables.clone());
 
         for memtable in immutables {
@@ -12,38 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub(crate) mod dictionary;
 pub(crate) mod parquet;
 mod stream_writer;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::str::FromStr;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use common_base::readable_size::ReadableSize;
 use common_recordbatch::SendableRecordBatchStream;
-use common_telemetry::{debug, error};
+use common_telemetry::{debug, error, info};
 use common_time::range::TimestampRange;
 use common_time::Timestamp;
 use datatypes::schema::SchemaRef;
 use futures_util::StreamExt;
-use object_store::{util, ObjectStore};
+use object_store::{util, EntryMode, ErrorKind, Metakey, ObjectStore};
+use parquet::basic::{Compression, Encoding, ZstdLevel};
 use serde::{Deserialize, Deserializer, Serialize};
 use snafu::{ResultExt, Snafu};
-use store_api::storage::{ChunkReader, RegionId};
+use store_api::storage::{ChunkReader, RegionId, SequenceNumber};
 use table::predicate::Predicate;
 use uuid::Uuid;
 
 use crate::chunk::ChunkReaderImpl;
+use crate::config::TagDictionaryConfig;
 use crate::error;
-use crate::error::{DeleteSstSnafu, Result};
+use crate::error::{DeleteSstSnafu, ListObjectsSnafu, Result};
 use crate::file_purger::{FilePurgeRequest, FilePurgerRef};
 use crate::memtable::BoxedBatchIterator;
 use crate::read::{Batch, BoxedBatchReader};
 use crate::scheduler::Scheduler;
-use crate::schema::ProjectedSchemaRef;
+use crate::schema::{ProjectedSchemaRef, StoreSchemaRef};
+use crate::sst::dictionary::{RegionTagDictionary, RegionTagDictionaryRef};
 use crate::sst::parquet::{ParquetReader, ParquetWriter};
 
 /// Maximum level of SSTs.
@@ -51,6 +55,7 @@ pub const MAX_LEVEL: u8 = 2;
 
 pub type Level = u8;
 
+pub use crate::sst::parquet::{parse_compression, parse_encoding};
 pub use crate::sst::stream_writer::BufferedWriter;
 
 // We only has fixed number of level, so we use array to hold elements. This implementation
@@ -65,6 +70,11 @@ pub struct LevelMetas {
     levels: LevelMetaVec,
     sst_layer: AccessLayerRef,
     file_purger: FilePurgerRef,
+    /// Bytes of the files removed from the levels but not purged yet, shared by all versions
+    /// of the metas.
+    pending_purge_bytes: Arc<AtomicU64>,
+    /// Leases of the files of all versions of the metas.
+    file_leases: FileLeasesRef,
 }
 
 impl std::fmt::Debug for LevelMetas {
@@ -82,6 +92,8 @@ impl LevelMetas {
             levels: new_level_meta_vec(),
             sst_layer,
             file_purger,
+            pending_purge_bytes: Arc::new(AtomicU64::new(0)),
+            file_leases: Arc::new(FileLeases::default()),
         }
     }
 
@@ -108,7 +120,13 @@ impl LevelMetas {
         let mut merged = self.clone();
         for file in files_to_add {
             let level = file.level;
-            let handle = FileHandle::new(file, self.sst_layer.clone(), self.file_purger.clone());
+            let handle = FileHandle::new_tracked(
+                file,
+                self.sst_layer.clone(),
+                self.file_purger.clone(),
+                self.pending_purge_bytes.clone(),
+                self.file_leases.clone(),
+            );
             merged.levels[level as usize].add_file(handle);
         }
 
@@ -124,6 +142,77 @@ impl LevelMetas {
     pub fn levels(&self) -> &[LevelMeta] {
         &self.levels
     }
+
+    /// Returns bytes of the files in all levels.
+    pub fn file_bytes(&self) -> u64 {
+        self.levels
+            .iter()
+            .map(|level| level.files().map(|file| file.file_size()).sum::<u64>())
+            .sum()
+    }
+
+    /// Returns the generations of the tag dictionary the files in all levels are encoded by.
+    pub fn tag_dictionary_generations(&self) -> HashSet<u64> {
+        self.levels
+            .iter()
+            .flat_map(|level| level.files())
+            .filter_map(|file| file.tag_dictionary_generation())
+            .collect()
+    }
+
+    /// Returns rows of the files in all levels, rows deleted or overwritten in other files are
+    /// still counted.
+    pub fn num_rows(&self) -> usize {
+        self.levels
+            .iter()
+            .map(|level| level.files().map(|file| file.num_rows()).sum::<usize>())
+            .sum()
+    }
+
+    /// Returns bytes of the removed files still in the object store, waiting to be purged.
+    #[inline]
+    pub fn pending_purge_bytes(&self) -> u64 {
+        self.pending_purge_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the files with live handles in any version of the metas, including the files
+    /// removed from the levels but still read by snapshots of older versions.
+    pub fn leased_files(&self) -> HashSet<FileId> {
+        self.file_leases.leased_files()
+    }
+}
+
+/// Number of live [FileHandle]s of each file.
+///
+/// A handle holds a lease of its file until the handle is dropped, so the files still
+/// read by ongoing scans are known after they are removed from the current version.
+#[derive(Debug, Default)]
+pub struct FileLeases {
+    files: Mutex<HashMap<FileId, usize>>,
+}
+
+pub type FileLeasesRef = Arc<FileLeases>;
+
+impl FileLeases {
+    fn acquire(&self, file_id: FileId) {
+        let mut files = self.files.lock().unwrap();
+        *files.entry(file_id).or_default() += 1;
+    }
+
+    fn release(&self, file_id: FileId) {
+        let mut files = self.files.lock().unwrap();
+        if let Some(count) = files.get_mut(&file_id) {
+            *count -= 1;
+            if *count == 0 {
+                files.remove(&file_id);
+            }
+        }
+    }
+
+    /// Returns the files with at least one lease.
+    pub fn leased_files(&self) -> HashSet<FileId> {
+        self.files.lock().unwrap().keys().copied().collect()
+    }
 }
 
 /// Metadata of files in same SST level.
@@ -213,7 +302,33 @@ impl FileHandle {
         file_purger: FilePurgerRef,
     ) -> FileHandle {
         FileHandle {
-            inner: Arc::new(FileHandleInner::new(meta, sst_layer, file_purger)),
+            inner: Arc::new(FileHandleInner::new(
+                meta,
+                sst_layer,
+                file_purger,
+                Arc::new(AtomicU64::new(0)),
+                Arc::new(FileLeases::default()),
+            )),
+        }
+    }
+
+    /// Creates a handle which adds its size to `pending_purge_bytes` once deleted, until
+    /// the file is purged, and holds a lease of the file in `file_leases` until dropped.
+    fn new_tracked(
+        meta: FileMeta,
+        sst_layer: AccessLayerRef,
+        file_purger: FilePurgerRef,
+        pending_purge_bytes: Arc<AtomicU64>,
+        file_leases: FileLeasesRef,
+    ) -> FileHandle {
+        FileHandle {
+            inner: Arc::new(FileHandleInner::new(
+                meta,
+                sst_layer,
+                file_purger,
+                pending_purge_bytes,
+                file_leases,
+            )),
         }
     }
 
@@ -264,7 +379,11 @@ impl FileHandle {
 
     #[inline]
     pub fn mark_deleted(&self) {
-        self.inner.deleted.store(true, Ordering::Relaxed);
+        if !self.inner.deleted.swap(true, Ordering::Relaxed) {
+            self.inner
+                .pending_purge_bytes
+                .fetch_add(self.inner.meta.file_size, Ordering::Relaxed);
+        }
     }
 
     #[inline]
@@ -276,6 +395,31 @@ impl FileHandle {
     pub fn file_size(&self) -> u64 {
         self.inner.meta.file_size
     }
+
+    #[inline]
+    pub fn num_rows(&self) -> usize {
+        self.inner.meta.num_rows
+    }
+
+    #[inline]
+    pub fn distinct_puts(&self) -> bool {
+        self.inner.meta.distinct_puts
+    }
+
+    #[inline]
+    pub fn tombstone_sequence(&self) -> Option<SequenceNumber> {
+        self.inner.meta.tombstone_sequence
+    }
+
+    /// Returns the generation of the tag dictionary the file is encoded by, `None` if the
+    /// file isn't encoded by the dictionary.
+    #[inline]
+    pub fn tag_dictionary_generation(&self) -> Option<u64> {
+        self.inner
+            .meta
+            .tag_dictionary_version
+            .map(|_| self.inner.meta.tag_dictionary_generation)
+    }
 }
 
 /// Actually data of [FileHandle].
@@ -287,6 +431,8 @@ struct FileHandleInner {
     deleted: AtomicBool,
     sst_layer: AccessLayerRef,
     file_purger: FilePurgerRef,
+    pending_purge_bytes: Arc<AtomicU64>,
+    file_leases: FileLeasesRef,
 }
 
 impl fmt::Debug for FileHandleInner {
@@ -301,12 +447,18 @@ impl fmt::Debug for FileHandleInner {
 
 impl Drop for FileHandleInner {
     fn drop(&mut self) {
+        self.file_leases.release(self.meta.file_id);
+
         if self.deleted.load(Ordering::Relaxed) {
             let request = FilePurgeRequest {
                 sst_layer: self.sst_layer.clone(),
                 file_id: self.meta.file_id,
                 region_id: self.meta.region_id,
+                file_size: self.meta.file_size,
+                pending_purge_bytes: self.pending_purge_bytes.clone(),
             };
+            // The file stays in the object store if the purge task fails, so its size is
+            // still counted as pending.
             match self.file_purger.schedule(request) {
                 Ok(res) => {
                     debug!(
@@ -330,13 +482,18 @@ impl FileHandleInner {
         meta: FileMeta,
         sst_layer: AccessLayerRef,
         file_purger: FilePurgerRef,
+        pending_purge_bytes: Arc<AtomicU64>,
+        file_leases: FileLeasesRef,
     ) -> FileHandleInner {
+        file_leases.acquire(meta.file_id);
         FileHandleInner {
             meta,
             compacting: AtomicBool::new(false),
             deleted: AtomicBool::new(false),
             sst_layer,
             file_purger,
+            pending_purge_bytes,
+            file_leases,
         }
     }
 }
@@ -397,6 +554,28 @@ pub struct FileMeta {
     pub level: Level,
     /// Size of the file.
     pub file_size: u64,
+    /// Number of rows in the file, 0 for files written before it was recorded.
+    #[serde(default)]
+    pub num_rows: usize,
+    /// Whether the rows in the file are all puts of distinct row keys, so all of them are
+    /// visible if no other file or memtable has the same keys. False for files written before
+    /// it was recorded.
+    #[serde(default)]
+    pub distinct_puts: bool,
+    /// Version of the region tag dictionary the string tags of the file are encoded by, `None`
+    /// if the tags are stored as plain strings, which is the case for all files written before
+    /// the dictionary was introduced.
+    #[serde(default)]
+    pub tag_dictionary_version: Option<u64>,
+    /// Generation of the region tag dictionary the file is encoded by, which is the legacy
+    /// generation 0 for the files written before generations were introduced.
+    #[serde(default)]
+    pub tag_dictionary_generation: u64,
+    /// Sequence of the last range tombstone applied when the file is written, so the file
+    /// holds no row hidden by the tombstones up to it. `None` if no tombstone is applied, like
+    /// the files written by flushes.
+    #[serde(default)]
+    pub tombstone_sequence: Option<SequenceNumber>,
 }
 
 fn deserialize_from_string<'de, D>(deserializer: D) -> std::result::Result<FileId, D::Error>
@@ -408,16 +587,22 @@ where
     FileId::from_str(stripped).map_err(<D::Error as serde::de::Error>::custom)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WriteOptions {
     // TODO(yingwen): [flush] row group size.
     pub sst_write_buffer_size: ReadableSize,
+    /// Compression of the columns without their own compression option.
+    pub compression: Compression,
+    /// Encoding of the columns without their own encoding option.
+    pub encoding: Encoding,
 }
 
 impl Default for WriteOptions {
     fn default() -> Self {
         Self {
             sst_write_buffer_size: ReadableSize::mb(8),
+            compression: Compression::ZSTD(ZstdLevel::default()),
+            encoding: Encoding::PLAIN,
         }
     }
 }
@@ -438,6 +623,22 @@ pub struct SstInfo {
     pub time_range: Option<(Timestamp, Timestamp)>,
     pub file_size: u64,
     pub num_rows: usize,
+    /// Whether the rows written are all puts of distinct row keys, always false for the rows
+    /// of a [Source::Stream].
+    pub distinct_puts: bool,
+    /// Version of the region tag dictionary the file is encoded by, if any.
+    pub tag_dictionary_version: Option<u64>,
+    /// Generation of the region tag dictionary the file is encoded by.
+    pub tag_dictionary_generation: u64,
+}
+
+/// An object listed from the object store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedObject {
+    pub path: String,
+    pub size: u64,
+    /// Last modified time of the object in millis, `None` if the object store doesn't tell.
+    pub last_modified_millis: Option<i64>,
 }
 
 /// SST access layer.
@@ -464,6 +665,25 @@ pub trait AccessLayer: Send + Sync + std::fmt::Debug {
 
     /// Deletes a SST file with given name.
     async fn delete_sst(&self, file_id: FileId) -> Result<()>;
+
+    /// Lists the SST files in the object store, including the ones not referenced by any
+    /// version.
+    async fn list_ssts(&self) -> Result<Vec<(FileId, ListedObject)>>;
+
+    /// Starts a new generation of the tag dictionary if a column of the current one is full,
+    /// so the SSTs written afterwards only reference the values still written.
+    async fn rotate_tag_dictionary(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Releases the generations of the tag dictionary not in `live` from memory.
+    async fn release_tag_dictionaries(&self, _live: &HashSet<u64>) {}
+
+    /// Deletes the generations of the tag dictionary not in `live`, which must only be called
+    /// when no SST is written or read, like on opening the region.
+    async fn purge_tag_dictionaries(&self, _live: &HashSet<u64>) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub type AccessLayerRef = Arc<dyn AccessLayer>;
@@ -505,12 +725,22 @@ impl Source {
             Source::Stream(stream) => stream.schema(),
         }
     }
+
+    /// Returns the store schema of the rows, `None` for the rows of a table scan.
+    fn store_schema(&self) -> Option<StoreSchemaRef> {
+        match self {
+            Source::Iter(iter) => Some(iter.schema().schema_to_read().clone()),
+            Source::Reader(reader) => Some(reader.projected_schema().schema_to_read().clone()),
+            Source::Stream(_) => None,
+        }
+    }
 }
 
 /// Sst access layer.
 pub struct FsAccessLayer {
     sst_dir: String,
     object_store: ObjectStore,
+    tag_dictionary: RegionTagDictionaryRef,
 }
 
 impl fmt::Debug for FsAccessLayer {
@@ -523,11 +753,31 @@ impl fmt::Debug for FsAccessLayer {
 
 impl FsAccessLayer {
     pub fn new(sst_dir: &str, object_store: ObjectStore) -> FsAccessLayer {
+        let sst_dir = util::normalize_dir(sst_dir);
+        // SSTs written by the tag dictionary are always readable.
+        let tag_dictionary = Arc::new(RegionTagDictionary::new(
+            &sst_dir,
+            object_store.clone(),
+            None,
+        ));
         FsAccessLayer {
-            sst_dir: util::normalize_dir(sst_dir),
+            sst_dir,
             object_store,
+            tag_dictionary,
         }
     }
+
+    /// Writes the string tags of SSTs by the region tag dictionary if `config` is set.
+    pub fn with_tag_dictionary(mut self, config: Option<&TagDictionaryConfig>) -> FsAccessLayer {
+        if let Some(config) = config {
+            self.tag_dictionary = Arc::new(RegionTagDictionary::new(
+                &self.sst_dir,
+                self.object_store.clone(),
+                Some(config.max_values_per_column),
+            ));
+        }
+        self
+    }
 }
 
 #[async_trait]
@@ -546,7 +796,8 @@ impl AccessLayer for FsAccessLayer {
         // Now we only supports parquet format. We may allow caller to specific SST format in
         // WriteOptions in the future.
         let file_path = self.sst_file_path(&file_id.as_parquet());
-        let writer = ParquetWriter::new(&file_path, source, self.object_store.clone());
+        let writer = ParquetWriter::new(&file_path, source, self.object_store.clone())
+            .with_tag_dictionary(self.tag_dictionary.clone());
         writer.write_sst(opts).await
     }
 
@@ -562,7 +813,8 @@ impl AccessLayer for FsAccessLayer {
             opts.projected_schema.clone(),
             opts.predicate.clone(),
             opts.time_range,
-        );
+        )
+        .with_tag_dictionary(self.tag_dictionary.clone());
 
         let stream = reader.chunk_stream().await?;
         Ok(Box::new(stream))
@@ -576,6 +828,62 @@ impl AccessLayer for FsAccessLayer {
             .await
             .context(DeleteSstSnafu)
     }
+
+    /// Lists the parquet files under the sst dir whose names are file ids.
+    async fn list_ssts(&self) -> Result<Vec<(FileId, ListedObject)>> {
+        let sst_dir = &self.sst_dir;
+        let mut ssts = Vec::new();
+        let mut lister = match self.object_store.list(sst_dir).await {
+            Ok(lister) => lister,
+            // Nothing is written by the region yet.
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(ssts),
+            Err(e) => return Err(e).context(ListObjectsSnafu { path: sst_dir }),
+        };
+        while let Some(entry) = lister.next().await {
+            let entry = entry.context(ListObjectsSnafu { path: sst_dir })?;
+            let Some(id) = entry.name().strip_suffix(".parquet") else { continue };
+            let Ok(file_id) = FileId::parse_str(id) else { continue };
+            let metadata = self
+                .object_store
+                .metadata(
+                    &entry,
+                    Metakey::Mode | Metakey::ContentLength | Metakey::LastModified,
+                )
+                .await
+                .context(ListObjectsSnafu { path: entry.path() })?;
+            if !matches!(metadata.mode(), EntryMode::FILE) {
+                continue;
+            }
+
+            let object = ListedObject {
+                path: entry.path().to_string(),
+                size: metadata.content_length(),
+                last_modified_millis: metadata
+                    .last_modified()
+                    .map(|time| (time.unix_timestamp_nanos() / 1_000_000) as i64),
+            };
+            ssts.push((file_id, object));
+        }
+        Ok(ssts)
+    }
+
+    async fn rotate_tag_dictionary(&self) -> Result<()> {
+        if self.tag_dictionary.rotate_if_full().await? {
+            info!(
+                "Start a new generation of the tag dictionary of {}",
+                self.sst_dir
+            );
+        }
+        Ok(())
+    }
+
+    async fn release_tag_dictionaries(&self, live: &HashSet<u64>) {
+        self.tag_dictionary.release_generations(live).await
+    }
+
+    async fn purge_tag_dictionaries(&self, live: &HashSet<u64>) -> Result<()> {
+        self.tag_dictionary.purge_generations(live).await
+    }
 }
 
 #[cfg(test)]
@@ -616,6 +924,22 @@ mod tests {
         assert_eq!(file_meta, deserialized_file_meta.unwrap());
     }
 
+    #[test]
+    fn test_deserialize_file_meta_with_tag_dictionary() {
+        let mut file_meta = create_file_meta(FileId::random(), 0);
+        file_meta.tag_dictionary_version = Some(3);
+        file_meta.tag_dictionary_generation = 2;
+        let serialized_file_meta = serde_json::to_string(&file_meta).unwrap();
+        let deserialized_file_meta: FileMeta = serde_json::from_str(&serialized_file_meta).unwrap();
+        assert_eq!(file_meta, deserialized_file_meta);
+
+        // Files written before the dictionary store plain tags.
+        let json_file_meta = "{\"region_id\":0,\"file_id\":\"bc5896ec-e4d8-4017-a80d-f2de73188d55\",\"time_range\":null,\"level\":0,\"file_size\":1024,\"num_rows\":10}";
+        let deserialized_file_meta: FileMeta = serde_json::from_str(json_file_meta).unwrap();
+        assert_eq!(None, deserialized_file_meta.tag_dictionary_version);
+        assert_eq!(0, deserialized_file_meta.tag_dictionary_generation);
+    }
+
     #[test]
     fn test_deserialize_from_string() {
         let json_file_meta = "{\"region_id\":0,\"file_id\":\"bc5896ec-e4d8-4017-a80d-f2de73188d55\",\"time_range\":null,\"level\":0}";
@@ -664,6 +988,11 @@ mod tests {
             time_range: None,
             level,
             file_size: 0,
+            num_rows: 0,
+            distinct_puts: false,
+            tag_dictionary_version: None,
+            tag_dictionary_generation: 0,
+            tombstone_sequence: None,
         }
     }
 
@@ -750,4 +1079,38 @@ mod tests {
             removed2.level(1).files().map(|f| f.file_id()).collect()
         );
     }
+
+    #[test]
+    fn test_leased_files() {
+        let layer = Arc::new(crate::test_util::access_layer_util::MockAccessLayer {});
+        let purger = Arc::new(LocalScheduler::new(
+            SchedulerConfig::default(),
+            NoopFilePurgeHandler,
+        ));
+        let file_ids = [FileId::random(), FileId::random()];
+
+        let metas = LevelMetas::new(layer, purger);
+        let merged = metas.merge(
+            file_ids.iter().map(|id| create_file_meta(*id, 0)),
+            vec![].into_iter(),
+        );
+        assert_eq!(HashSet::from(file_ids), merged.leased_files());
+
+        // A reader holds the handle of the first file.
+        let held = merged
+            .level(0)
+            .files()
+            .find(|file| file.file_id() == file_ids[0])
+            .cloned()
+            .unwrap();
+        let removed = merged.merge(
+            vec![].into_iter(),
+            file_ids.iter().map(|id| create_file_meta(*id, 0)),
+        );
+        drop(merged);
+        assert_eq!(HashSet::from([file_ids[0]]), removed.leased_files());
+
+        drop(held);
+        assert!(removed.leased_files().is_empty());
+    }
 }
@@ -0,0 +1,1163 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tag dictionary shared by the SSTs of a region.
+//!
+//! The values of the string row key columns are assigned `u32` codes by a dictionary persisted
+//! under the sst dir of the region, and the SSTs store the codes instead of the strings. Values
+//! absent in the dictionary, e.g. the ones beyond the bound of the dictionary, are stored inline
+//! in an extra string column of the SST, so each value lives in exactly one of the two columns.
+//!
+//! The dictionary has generations, and codes are never reassigned within a generation, so the
+//! latest version of a generation resolves the codes of all SSTs encoded by it. A generation is
+//! persisted as pages under a dir of its own, each page holds the values added since the
+//! previous one, so persisting never rewrites the values persisted before, and reading an SST
+//! only loads the pages of the generation the SST is encoded by.
+//!
+//! Once a column of the current generation is full, the next compaction starts a new empty
+//! generation, which encodes the outputs of compactions and flushes from then on, so it only
+//! gets the values still written. The generations no SST references any more are released from
+//! memory after compactions, and their files are deleted when the region is opened, as no SST
+//! is written or read then. The dictionary written before generations, a single file of all
+//! values directly under the dir, is read as the generation 0.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow::compute;
+use arrow::datatypes::{DataType, Field, Schema as ArrowSchema, SchemaRef as ArrowSchemaRef};
+use arrow_array::{Array, ArrayRef, BooleanArray, RecordBatch, StringArray, UInt32Array};
+use datafusion_common::ScalarValue;
+use datafusion_expr::{BinaryExpr, Expr as DfExpr, Operator};
+use datatypes::arrow::error::ArrowError;
+use datatypes::schema::SchemaRef;
+use futures_util::TryStreamExt;
+use object_store::{ErrorKind, ObjectStore};
+use parquet::arrow::arrow_reader::ArrowPredicate;
+use parquet::arrow::ProjectionMask;
+use parquet::schema::types::SchemaDescriptor;
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt};
+use tokio::sync::Mutex;
+
+use crate::error::{
+    DecodeJsonSnafu, DeleteObjectSnafu, EncodeJsonSnafu, InvalidTagDictionarySnafu,
+    ListObjectsSnafu, NewRecordBatchSnafu, ReadObjectSnafu, Result, WriteObjectSnafu,
+};
+use crate::read::Batch;
+use crate::schema::StoreSchema;
+
+/// Dir of the dictionary files under the sst dir.
+pub(crate) const TAG_DICTIONARY_DIR: &str = "tag_dictionary/";
+/// Generation of the dictionary written before generations, whose files are directly under
+/// the dir.
+pub(crate) const LEGACY_GENERATION: u64 = 0;
+/// Key of the [TagLayout] in the metadata of the arrow schema of SSTs.
+const TAG_LAYOUT_KEY: &str = "greptime:storage:tag_dictionary";
+/// Version of the layout of the SSTs encoded by the dictionary.
+const TAG_LAYOUT_VERSION: u32 = 1;
+/// Prefix of the columns holding the values absent in the dictionary.
+const INLINE_COLUMN_PREFIX: &str = "__inline_";
+
+/// Values of a column in the dictionary, the code of a value is its index.
+#[derive(Debug, Default, Clone)]
+struct ColumnDictionary {
+    values: Vec<String>,
+    codes: HashMap<String, u32>,
+}
+
+impl ColumnDictionary {
+    fn new(values: Vec<String>) -> ColumnDictionary {
+        let codes = values
+            .iter()
+            .enumerate()
+            .map(|(code, value)| (value.clone(), code as u32))
+            .collect();
+        ColumnDictionary { values, codes }
+    }
+
+    fn push(&mut self, value: &str) {
+        let _ = self
+            .codes
+            .insert(value.to_string(), self.values.len() as u32);
+        self.values.push(value.to_string());
+    }
+}
+
+/// Snapshot of a generation of the tag dictionary of a region.
+#[derive(Debug, Default, Clone)]
+pub struct TagDictionary {
+    generation: u64,
+    version: u64,
+    columns: HashMap<String, Arc<ColumnDictionary>>,
+}
+
+/// Persisted format of the dictionary of [LEGACY_GENERATION].
+#[derive(Serialize, Deserialize)]
+struct TagDictionaryFile {
+    version: u64,
+    columns: HashMap<String, Vec<String>>,
+}
+
+/// Persisted values added to a generation after the previous page, up to `version`.
+#[derive(Debug, Serialize, Deserialize)]
+struct TagDictionaryPage {
+    version: u64,
+    /// Code of the first value added and the values added of each column.
+    columns: HashMap<String, (u32, Vec<String>)>,
+}
+
+impl TagDictionary {
+    fn new(generation: u64) -> TagDictionary {
+        TagDictionary {
+            generation,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the generation of the dictionary.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the version of the dictionary, which is bumped each time values are added.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the code of `value` in `column`.
+    pub fn code(&self, column: &str, value: &str) -> Option<u32> {
+        self.columns.get(column)?.codes.get(value).copied()
+    }
+
+    /// Returns the value of `code` in `column`.
+    pub fn value(&self, column: &str, code: u32) -> Option<&str> {
+        self.columns
+            .get(column)?
+            .values
+            .get(code as usize)
+            .map(|v| v.as_str())
+    }
+
+    /// Returns the number of values of `column`.
+    pub fn num_values(&self, column: &str) -> usize {
+        self.columns
+            .get(column)
+            .map(|c| c.values.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns true if a column has `max_values` values.
+    fn is_full(&self, max_values: usize) -> bool {
+        self.columns
+            .values()
+            .any(|column| column.values.len() >= max_values)
+    }
+
+    fn num_values_by_column(&self) -> HashMap<String, usize> {
+        self.columns
+            .iter()
+            .map(|(name, column)| (name.clone(), column.values.len()))
+            .collect()
+    }
+
+    /// Returns the page of the values added after the columns have `persisted` values.
+    fn page_since(&self, persisted: &HashMap<String, usize>) -> TagDictionaryPage {
+        let columns = self
+            .columns
+            .iter()
+            .filter_map(|(name, column)| {
+                let start = persisted.get(name).copied().unwrap_or(0);
+                let added = column.values.get(start..).filter(|v| !v.is_empty())?;
+                Some((name.clone(), (start as u32, added.to_vec())))
+            })
+            .collect();
+        TagDictionaryPage {
+            version: self.version,
+            columns,
+        }
+    }
+
+    /// Appends the values of `page`, which must be the page after the values of the dictionary.
+    fn apply_page(&mut self, page: TagDictionaryPage) -> Result<()> {
+        for (name, (start, values)) in page.columns {
+            let column = Arc::make_mut(self.columns.entry(name.clone()).or_default());
+            ensure!(
+                column.values.len() == start as usize,
+                InvalidTagDictionarySnafu {
+                    msg: format!(
+                        "page of version {} starts at code {} of column {} with {} values",
+                        page.version,
+                        start,
+                        name,
+                        column.values.len()
+                    ),
+                }
+            );
+            for value in &values {
+                column.push(value);
+            }
+        }
+        self.version = page.version;
+        Ok(())
+    }
+
+    fn decode_legacy(bytes: &[u8]) -> Result<TagDictionary> {
+        let file: TagDictionaryFile = serde_json::from_slice(bytes).context(DecodeJsonSnafu)?;
+        Ok(TagDictionary {
+            generation: LEGACY_GENERATION,
+            version: file.version,
+            columns: file
+                .columns
+                .into_iter()
+                .map(|(name, values)| (name, Arc::new(ColumnDictionary::new(values))))
+                .collect(),
+        })
+    }
+}
+
+/// The generation of the dictionary new values are added to.
+struct WriterState {
+    generation: u64,
+    /// Version of the generation persisted last.
+    persisted_version: u64,
+    /// Number of values of each column persisted.
+    persisted_values: HashMap<String, usize>,
+}
+
+#[derive(Default)]
+struct DictionaryState {
+    /// Loaded generations of the dictionary.
+    generations: HashMap<u64, Arc<TagDictionary>>,
+    /// `None` until the latest generation is loaded for the writer.
+    writer: Option<WriterState>,
+}
+
+/// Tag dictionary of a region, loaded lazily from the object store.
+pub struct RegionTagDictionary {
+    dir: String,
+    object_store: ObjectStore,
+    /// Max number of values of each column, the dictionary is read only if it's `None`.
+    max_values_per_column: Option<usize>,
+    /// State of the dictionary, never held across the I/O of the object store.
+    state: Mutex<DictionaryState>,
+    /// Serializes writing the pages, so each page follows the previous one.
+    persist_lock: Mutex<()>,
+}
+
+pub type RegionTagDictionaryRef = Arc<RegionTagDictionary>;
+
+impl std::fmt::Debug for RegionTagDictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegionTagDictionary")
+            .field("dir", &self.dir)
+            .field("max_values_per_column", &self.max_values_per_column)
+            .finish()
+    }
+}
+
+impl RegionTagDictionary {
+    /// Creates the dictionary of the region whose SSTs are under `sst_dir`, new values are
+    /// only added if `max_values_per_column` is set.
+    pub fn new(
+        sst_dir: &str,
+        object_store: ObjectStore,
+        max_values_per_column: Option<usize>,
+    ) -> RegionTagDictionary {
+        RegionTagDictionary {
+            dir: format!("{sst_dir}{TAG_DICTIONARY_DIR}"),
+            object_store,
+            max_values_per_column,
+            state: Mutex::new(DictionaryState::default()),
+            persist_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns whether SSTs are written by the dictionary.
+    pub fn is_writable(&self) -> bool {
+        self.max_values_per_column.is_some()
+    }
+
+    /// Returns a snapshot of `generation` whose version is at least `min_version`.
+    pub async fn snapshot(&self, generation: u64, min_version: u64) -> Result<Arc<TagDictionary>> {
+        if let Some(dictionary) = self.state.lock().await.generations.get(&generation) {
+            if dictionary.version >= min_version {
+                return Ok(dictionary.clone());
+            }
+        }
+
+        // Reloads the generation as it's updated by another writer of the region, e.g. the one
+        // before the region is reopened.
+        let current = self.reload(generation).await?;
+        ensure!(
+            current.version >= min_version,
+            InvalidTagDictionarySnafu {
+                msg: format!(
+                    "version {} of generation {} is required but the latest version is {}, dir: {}",
+                    min_version, generation, current.version, self.dir
+                ),
+            }
+        );
+        Ok(current)
+    }
+
+    /// Loads the latest version of `generation` without holding the lock, so the readers and
+    /// writers of the region aren't blocked by the object store. Returns the current snapshot,
+    /// which is the loaded one unless a newer one is loaded or added meanwhile.
+    async fn reload(&self, generation: u64) -> Result<Arc<TagDictionary>> {
+        let loaded = Arc::new(self.load(generation).await?);
+        let mut state = self.state.lock().await;
+        match state.generations.entry(generation) {
+            Entry::Occupied(entry) if entry.get().version >= loaded.version => {
+                Ok(entry.get().clone())
+            }
+            Entry::Occupied(mut entry) => {
+                let _ = entry.insert(loaded.clone());
+                Ok(loaded)
+            }
+            Entry::Vacant(entry) => Ok(entry.insert(loaded).clone()),
+        }
+    }
+
+    /// Returns the generation new values are added to, which is the latest one persisted,
+    /// loaded on the first call.
+    pub(crate) async fn writer_generation(&self) -> Result<u64> {
+        if let Some(writer) = &self.state.lock().await.writer {
+            return Ok(writer.generation);
+        }
+
+        // Values are only added to the generations after the legacy one.
+        let generation = self
+            .list_generations()
+            .await?
+            .into_iter()
+            .max()
+            .unwrap_or(LEGACY_GENERATION)
+            .max(LEGACY_GENERATION + 1);
+        let dictionary = self.reload(generation).await?;
+        let mut state = self.state.lock().await;
+        let writer = state.writer.get_or_insert_with(|| WriterState {
+            generation,
+            persisted_version: dictionary.version,
+            persisted_values: dictionary.num_values_by_column(),
+        });
+        Ok(writer.generation)
+    }
+
+    /// Adds the `values` absent in `generation` to `column` in order, until the column has
+    /// `max_values_per_column` values. Returns the snapshot of `generation` after adding them.
+    ///
+    /// Nothing is added if `generation` is no longer the one of the writer.
+    async fn add_values(
+        &self,
+        generation: u64,
+        column: &str,
+        values: &[&str],
+    ) -> Result<Arc<TagDictionary>> {
+        let max_values = self
+            .max_values_per_column
+            .context(InvalidTagDictionarySnafu {
+                msg: format!("dictionary is read only, dir: {}", self.dir),
+            })?;
+        if self.writer_generation().await? != generation {
+            return self.snapshot(generation, 0).await;
+        }
+
+        let mut state = self.state.lock().await;
+        // Safety: the writer is loaded above and never unset.
+        if state.writer.as_ref().unwrap().generation != generation {
+            drop(state);
+            return self.snapshot(generation, 0).await;
+        }
+        // The generation of the writer is never released.
+        let current =
+            state
+                .generations
+                .get_mut(&generation)
+                .context(InvalidTagDictionarySnafu {
+                    msg: format!("generation {generation} is not loaded"),
+                })?;
+        let absent: Vec<_> = values
+            .iter()
+            .filter(|v| current.code(column, v).is_none())
+            .take(max_values.saturating_sub(current.num_values(column)))
+            .collect();
+        if absent.is_empty() {
+            return Ok(current.clone());
+        }
+
+        let dictionary = Arc::make_mut(current);
+        let column_dict = Arc::make_mut(dictionary.columns.entry(column.to_string()).or_default());
+        for value in absent {
+            column_dict.push(value);
+        }
+        dictionary.version += 1;
+        Ok(current.clone())
+    }
+
+    /// Persists the values of `generation` not persisted yet as a page, returns the version
+    /// persisted.
+    ///
+    /// Generations other than the one of the writer are always persisted, as the writer only
+    /// moves to the next generation once the current one is persisted.
+    async fn persist(&self, generation: u64) -> Result<u64> {
+        let _guard = self.persist_lock.lock().await;
+        let pending = {
+            let state = self.state.lock().await;
+            let writer = state
+                .writer
+                .as_ref()
+                .filter(|writer| writer.generation == generation);
+            match (writer, state.generations.get(&generation)) {
+                (Some(writer), Some(current)) if current.version > writer.persisted_version => {
+                    Some((
+                        current.version,
+                        current.page_since(&writer.persisted_values),
+                    ))
+                }
+                (_, Some(current)) => return Ok(current.version),
+                (_, None) => None,
+            }
+        };
+        // The generation is released from memory or never loaded.
+        let Some((version, page)) = pending else {
+            return Ok(self.snapshot(generation, 0).await?.version);
+        };
+
+        let path = self.page_path(generation, version);
+        let bytes = serde_json::to_vec(&page).context(EncodeJsonSnafu)?;
+        self.object_store
+            .write(&path, bytes)
+            .await
+            .context(WriteObjectSnafu { path: &path })?;
+
+        let mut state = self.state.lock().await;
+        // Safety: the generation of the writer doesn't change while it has values not
+        // persisted.
+        let writer = state.writer.as_mut().unwrap();
+        writer.persisted_version = version;
+        for (name, (start, values)) in page.columns {
+            let _ = writer
+                .persisted_values
+                .insert(name, start as usize + values.len());
+        }
+        Ok(version)
+    }
+
+    /// Moves the writer to a new empty generation if a column of its generation is full, so
+    /// the SSTs written afterwards don't reference the values no longer written. Returns true
+    /// if the writer moves.
+    ///
+    /// The writer only moves if all values of its generation are persisted, as the SSTs being
+    /// encoded by the generation can't add or persist values to it afterwards.
+    pub async fn rotate_if_full(&self) -> Result<bool> {
+        let Some(max_values) = self.max_values_per_column else { return Ok(false) };
+        let generation = self.writer_generation().await?;
+        let _ = self.persist(generation).await?;
+
+        let mut state = self.state.lock().await;
+        let DictionaryState {
+            generations,
+            writer,
+        } = &mut *state;
+        // Safety: the writer is loaded above and never unset.
+        let writer = writer.as_mut().unwrap();
+        let Some(current) = generations.get(&writer.generation) else { return Ok(false) };
+        if current.version > writer.persisted_version || !current.is_full(max_values) {
+            return Ok(false);
+        }
+
+        let generation = writer.generation + 1;
+        let _ = generations.insert(generation, Arc::new(TagDictionary::new(generation)));
+        *writer = WriterState {
+            generation,
+            persisted_version: 0,
+            persisted_values: HashMap::new(),
+        };
+        Ok(true)
+    }
+
+    /// Releases the loaded generations not in `live` from memory, except the one of the
+    /// writer. They are loaded again if an SST encoded by them is read.
+    pub async fn release_generations(&self, live: &HashSet<u64>) {
+        let mut state = self.state.lock().await;
+        let writer_generation = state.writer.as_ref().map(|writer| writer.generation);
+        state.generations.retain(|generation, _| {
+            live.contains(generation) || Some(*generation) == writer_generation
+        });
+    }
+
+    /// Deletes the files of the generations not in `live`, except the latest one, which the
+    /// writer adds values to.
+    ///
+    /// No SST encoded by the generations deleted may be written or read, which is the case
+    /// when the region is being opened.
+    pub async fn purge_generations(&self, live: &HashSet<u64>) -> Result<()> {
+        let generations = self.list_generations().await?;
+        let latest = generations.iter().max().copied();
+        for generation in generations {
+            if live.contains(&generation) || Some(generation) == latest {
+                continue;
+            }
+            let dir = self.generation_dir(generation);
+            self.object_store
+                .remove_all(&dir)
+                .await
+                .context(DeleteObjectSnafu { path: &dir })?;
+        }
+
+        if !live.contains(&LEGACY_GENERATION) {
+            for version in self.list_versions(&self.dir).await? {
+                let path = self.legacy_file_path(version);
+                self.object_store
+                    .delete(&path)
+                    .await
+                    .context(DeleteObjectSnafu { path: &path })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the latest version of `generation`, which is empty if it has no file.
+    async fn load(&self, generation: u64) -> Result<TagDictionary> {
+        if generation == LEGACY_GENERATION {
+            return self.load_legacy().await;
+        }
+
+        let mut versions = self.list_versions(&self.generation_dir(generation)).await?;
+        versions.sort_unstable();
+        let mut dictionary = TagDictionary::new(generation);
+        for version in versions {
+            let path = self.page_path(generation, version);
+            let bytes = self
+                .object_store
+                .read(&path)
+                .await
+                .context(ReadObjectSnafu { path: &path })?;
+            let page: TagDictionaryPage =
+                serde_json::from_slice(&bytes).context(DecodeJsonSnafu)?;
+            dictionary.apply_page(page)?;
+        }
+        Ok(dictionary)
+    }
+
+    /// Loads the dictionary of [LEGACY_GENERATION] from its latest file, whose older files
+    /// are deleted once it's written.
+    async fn load_legacy(&self) -> Result<TagDictionary> {
+        let Some(version) = self.list_versions(&self.dir).await?.into_iter().max() else {
+            return Ok(TagDictionary::new(LEGACY_GENERATION));
+        };
+        let path = self.legacy_file_path(version);
+        let bytes = self
+            .object_store
+            .read(&path)
+            .await
+            .context(ReadObjectSnafu { path: &path })?;
+        TagDictionary::decode_legacy(&bytes)
+    }
+
+    /// Lists the generations that have a dir, except the legacy one.
+    async fn list_generations(&self) -> Result<Vec<u64>> {
+        Ok(self
+            .list_names(&self.dir)
+            .await?
+            .iter()
+            .filter_map(|name| name.strip_suffix('/')?.parse::<u64>().ok())
+            .collect())
+    }
+
+    /// Lists the versions of the files under `dir`.
+    async fn list_versions(&self, dir: &str) -> Result<Vec<u64>> {
+        Ok(self
+            .list_names(dir)
+            .await?
+            .iter()
+            .filter_map(|name| name.strip_suffix(".json")?.parse::<u64>().ok())
+            .collect())
+    }
+
+    /// Lists the names of the entries under `dir`, empty if the dir doesn't exist.
+    async fn list_names(&self, dir: &str) -> Result<Vec<String>> {
+        let mut lister = match self.object_store.list(dir).await {
+            Ok(lister) => lister,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).context(ListObjectsSnafu { path: dir }),
+        };
+        let mut names = Vec::new();
+        while let Some(entry) = lister
+            .try_next()
+            .await
+            .context(ListObjectsSnafu { path: dir })?
+        {
+            names.push(entry.name().to_string());
+        }
+        Ok(names)
+    }
+
+    fn generation_dir(&self, generation: u64) -> String {
+        format!("{}{:020}/", self.dir, generation)
+    }
+
+    fn page_path(&self, generation: u64, version: u64) -> String {
+        format!("{}{:020}.json", self.generation_dir(generation), version)
+    }
+
+    fn legacy_file_path(&self, version: u64) -> String {
+        format!("{}{:020}.json", self.dir, version)
+    }
+}
+
+/// Layout of the string row key columns of an SST encoded by the dictionary.
+#[derive(Debug, Serialize, Deserialize)]
+struct TagLayoutMeta {
+    version: u32,
+    columns: Vec<String>,
+}
+
+/// A string row key column encoded by the dictionary.
+#[derive(Debug, Clone)]
+pub(crate) struct EncodedColumn {
+    pub(crate) name: String,
+    /// Index of the code column in the SST.
+    pub(crate) index: usize,
+    /// Index of the inline column in the SST.
+    pub(crate) inline_index: usize,
+}
+
+/// Columns of an SST encoded by the dictionary.
+#[derive(Debug, Clone)]
+pub(crate) struct TagLayout {
+    columns: Vec<EncodedColumn>,
+}
+
+impl TagLayout {
+    pub(crate) fn column(&self, name: &str) -> Option<&EncodedColumn> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Returns the indices of the inline columns in the SST to read along with the columns
+    /// at `indices`.
+    pub(crate) fn inline_indices(&self, indices: &[usize]) -> Vec<usize> {
+        self.columns
+            .iter()
+            .filter(|c| indices.contains(&c.index))
+            .map(|c| c.inline_index)
+            .collect()
+    }
+}
+
+fn inline_column_name(name: &str) -> String {
+    format!("{INLINE_COLUMN_PREFIX}{name}")
+}
+
+/// Returns `field` with its type replaced by `data_type`, the metadata of the column is kept.
+fn with_data_type(field: &Field, data_type: DataType) -> Field {
+    Field::new(field.name(), data_type, field.is_nullable()).with_metadata(field.metadata().clone())
+}
+
+/// Returns the arrow schema of the data in the SST whose arrow schema is `sst_schema`, and the
+/// layout of the tags if the SST is encoded by the dictionary.
+pub(crate) fn decode_sst_schema(
+    sst_schema: &ArrowSchemaRef,
+) -> Result<(ArrowSchemaRef, Option<TagLayout>)> {
+    let Some(meta) = sst_schema.metadata().get(TAG_LAYOUT_KEY) else { return Ok((sst_schema.clone(), None)) };
+    let meta: TagLayoutMeta = serde_json::from_str(meta).context(DecodeJsonSnafu)?;
+    ensure!(
+        meta.version == TAG_LAYOUT_VERSION,
+        InvalidTagDictionarySnafu {
+            msg: format!("unsupported SST layout version {}", meta.version),
+        }
+    );
+
+    let mut fields = sst_schema.fields().clone();
+    let mut columns = Vec::with_capacity(meta.columns.len());
+    for name in meta.columns {
+        let inline_name = inline_column_name(&name);
+        let (Some(index), Some(inline_index)) = (
+            fields.iter().position(|f| *f.name() == name),
+            fields.iter().position(|f| *f.name() == inline_name),
+        ) else {
+            return InvalidTagDictionarySnafu {
+                msg: format!("encoded column {name} not found in SST"),
+            }
+            .fail();
+        };
+        fields[index] = with_data_type(&fields[index], DataType::Utf8);
+        columns.push(EncodedColumn {
+            name,
+            index,
+            inline_index,
+        });
+    }
+    // The inline columns are after all columns of the data.
+    let num_columns = fields.len() - columns.len();
+    fields.truncate(num_columns);
+
+    let mut metadata = sst_schema.metadata().clone();
+    let _ = metadata.remove(TAG_LAYOUT_KEY);
+    Ok((
+        Arc::new(ArrowSchema::new_with_metadata(fields, metadata)),
+        Some(TagLayout { columns }),
+    ))
+}
+
+/// Encodes the string row key columns of the batches written to an SST by the dictionary.
+pub(crate) struct TagEncoder {
+    dictionary: RegionTagDictionaryRef,
+    /// Indices and names of the encoded columns.
+    columns: Vec<(usize, String)>,
+    sst_schema: ArrowSchemaRef,
+    /// Generation of the dictionary the batches are encoded by, fixed by the first batch.
+    generation: Option<u64>,
+}
+
+impl TagEncoder {
+    /// Returns an encoder if the dictionary is writable and the data of `schema` has string
+    /// row key columns.
+    pub(crate) fn try_new(
+        dictionary: RegionTagDictionaryRef,
+        schema: &SchemaRef,
+    ) -> Option<TagEncoder> {
+        if !dictionary.is_writable() {
+            return None;
+        }
+        // Only data with the storage schema, which tells the row key columns, is encoded.
+        let store_schema = StoreSchema::try_from(schema.arrow_schema().clone()).ok()?;
+        let arrow_schema = store_schema.arrow_schema();
+        let columns: Vec<_> = store_schema
+            .row_key_indices()
+            .filter(|idx| *arrow_schema.field(*idx).data_type() == DataType::Utf8)
+            .map(|idx| (idx, arrow_schema.field(idx).name().clone()))
+            .collect();
+        if columns.is_empty() {
+            return None;
+        }
+
+        let mut fields = arrow_schema.fields().clone();
+        for (idx, name) in &columns {
+            fields[*idx] = with_data_type(&fields[*idx], DataType::UInt32);
+            fields.push(Field::new(inline_column_name(name), DataType::Utf8, true));
+        }
+        let meta = TagLayoutMeta {
+            version: TAG_LAYOUT_VERSION,
+            columns: columns.iter().map(|(_, name)| name.clone()).collect(),
+        };
+        let mut metadata = arrow_schema.metadata().clone();
+        // Safety: the layout meta is always serializable.
+        let _ = metadata.insert(
+            TAG_LAYOUT_KEY.to_string(),
+            serde_json::to_string(&meta).unwrap(),
+        );
+
+        Some(TagEncoder {
+            dictionary,
+            columns,
+            sst_schema: Arc::new(ArrowSchema::new_with_metadata(fields, metadata)),
+            generation: None,
+        })
+    }
+
+    /// Returns the arrow schema of the encoded batches.
+    pub(crate) fn sst_schema(&self) -> &ArrowSchemaRef {
+        &self.sst_schema
+    }
+
+    /// Returns the names of the inline columns.
+    pub(crate) fn inline_column_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.columns
+            .iter()
+            .map(|(_, name)| inline_column_name(name))
+    }
+
+    /// Encodes the `batch`, the absent values are added to the dictionary before encoding,
+    /// more frequent ones first.
+    pub(crate) async fn encode(&mut self, batch: &Batch) -> Result<RecordBatch> {
+        let generation = match self.generation {
+            Some(generation) => generation,
+            None => *self
+                .generation
+                .insert(self.dictionary.writer_generation().await?),
+        };
+        let mut arrays: Vec<ArrayRef> =
+            batch.columns().iter().map(|v| v.to_arrow_array()).collect();
+        let mut inline_arrays = Vec::with_capacity(self.columns.len());
+        for (idx, name) in &self.columns {
+            let values = string_array(&arrays[*idx], name)?;
+            let dictionary = self
+                .dictionary
+                .add_values(generation, name, &values_by_frequency(values))
+                .await?;
+
+            let codes: UInt32Array = values
+                .iter()
+                .map(|v| v.and_then(|v| dictionary.code(name, v)))
+                .collect();
+            let inline: StringArray = values
+                .iter()
+                .map(|v| v.filter(|v| dictionary.code(name, v).is_none()))
+                .collect();
+            arrays[*idx] = Arc::new(codes);
+            inline_arrays.push(Arc::new(inline) as ArrayRef);
+        }
+        arrays.extend(inline_arrays);
+
+        RecordBatch::try_new(self.sst_schema.clone(), arrays).context(NewRecordBatchSnafu)
+    }
+
+    /// Persists the values added by the encoder, returns the generation and the version of
+    /// the dictionary that resolve all codes written by the encoder.
+    pub(crate) async fn finish(&self) -> Result<(u64, u64)> {
+        let generation = match self.generation {
+            Some(generation) => generation,
+            None => self.dictionary.writer_generation().await?,
+        };
+        let version = self.dictionary.persist(generation).await?;
+        Ok((generation, version))
+    }
+}
+
+fn string_array<'a>(array: &'a ArrayRef, name: &str) -> Result<&'a StringArray> {
+    array
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .context(InvalidTagDictionarySnafu {
+            msg: format!("column {name} is not a string column"),
+        })
+}
+
+/// Returns the distinct non-null values in `array`, ordered by frequency descending.
+fn values_by_frequency(array: &StringArray) -> Vec<&str> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for value in array.iter().flatten() {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut values: Vec<_> = counts.into_iter().collect();
+    values.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    values.into_iter().map(|(value, _)| value).collect()
+}
+
+/// Decodes the batches read from an SST encoded by the dictionary into string columns.
+pub(crate) struct TagDecoder {
+    dictionary: Arc<TagDictionary>,
+    /// Names and positions of the code columns and the inline columns in the batches read.
+    columns: Vec<(String, usize, usize)>,
+    /// Number of the data columns in the batches read, the inline columns are after them.
+    num_columns: usize,
+    schema: ArrowSchemaRef,
+}
+
+impl TagDecoder {
+    /// Returns a decoder of the batches that contain the data columns at `indices`, ascending,
+    /// and the inline columns of them, or `None` if no encoded column is read.
+    pub(crate) fn try_new(
+        dictionary: Arc<TagDictionary>,
+        layout: &TagLayout,
+        data_schema: &ArrowSchemaRef,
+        indices: &[usize],
+    ) -> Result<Option<TagDecoder>> {
+        let columns: Vec<_> = layout
+            .columns
+            .iter()
+            .filter_map(|c| {
+                let pos = indices.iter().position(|idx| *idx == c.index)?;
+                Some((c.name.clone(), pos))
+            })
+            .enumerate()
+            .map(|(i, (name, pos))| (name, pos, indices.len() + i))
+            .collect();
+        if columns.is_empty() {
+            return Ok(None);
+        }
+
+        let schema = data_schema.project(indices).context(NewRecordBatchSnafu)?;
+        Ok(Some(TagDecoder {
+            dictionary,
+            columns,
+            num_columns: indices.len(),
+            schema: Arc::new(schema),
+        }))
+    }
+
+    pub(crate) fn decode(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let mut arrays = batch.columns()[..self.num_columns].to_vec();
+        for (name, pos, inline_pos) in &self.columns {
+            let codes = batch
+                .column(*pos)
+                .as_any()
+                .downcast_ref::<UInt32Array>()
+                .context(InvalidTagDictionarySnafu {
+                    msg: format!("column {name} is not a code column"),
+                })?;
+            let inline = string_array(batch.column(*inline_pos), name)?;
+            let values = codes
+                .iter()
+                .zip(inline.iter())
+                .map(|(code, inline)| match code {
+                    Some(code) => self
+                        .dictionary
+                        .value(name, code)
+                        .map(Some)
+                        .with_context(|| InvalidTagDictionarySnafu {
+                            msg: format!(
+                                "unknown code {} of column {} in version {}",
+                                code, name, self.dictionary.version
+                            ),
+                        }),
+                    None => Ok(inline),
+                })
+                .collect::<Result<StringArray>>()?;
+            arrays[*pos] = Arc::new(values);
+        }
+
+        RecordBatch::try_new(self.schema.clone(), arrays).context(NewRecordBatchSnafu)
+    }
+}
+
+/// Returns the names of the columns referenced by `expr`.
+pub(crate) fn referenced_columns(expr: &DfExpr) -> HashSet<String> {
+    let mut columns = HashSet::new();
+    if datafusion_expr::utils::expr_to_columns(expr, &mut columns).is_err() {
+        return HashSet::new();
+    }
+    columns.into_iter().map(|c| c.name).collect()
+}
+
+/// Returns the column and the value of `expr` if it's an equality between a column and a
+/// string literal.
+pub(crate) fn string_eq_literal(expr: &DfExpr) -> Option<(&str, &str)> {
+    let DfExpr::BinaryExpr(BinaryExpr { left, op: Operator::Eq, right }) = expr else { return None };
+    match (left.as_ref(), right.as_ref()) {
+        (DfExpr::Column(column), DfExpr::Literal(ScalarValue::Utf8(Some(value))))
+        | (DfExpr::Literal(ScalarValue::Utf8(Some(value))), DfExpr::Column(column)) => {
+            Some((column.name.as_str(), value.as_str()))
+        }
+        _ => None,
+    }
+}
+
+/// Selects the rows whose encoded column equals a string by comparing the codes if the string
+/// is in the dictionary, and comparing the inline values otherwise.
+pub(crate) struct TagEqRowFilter {
+    code: Option<u32>,
+    value: String,
+    projection: ProjectionMask,
+}
+
+impl TagEqRowFilter {
+    pub(crate) fn new(
+        dictionary: &TagDictionary,
+        column: &EncodedColumn,
+        value: &str,
+        schema_desc: &SchemaDescriptor,
+    ) -> TagEqRowFilter {
+        let code = dictionary.code(&column.name, value);
+        // The inline column is after the code column, so it's always the last column read.
+        let roots = match code {
+            Some(_) => vec![column.index, column.inline_index],
+            None => vec![column.inline_index],
+        };
+        TagEqRowFilter {
+            code,
+            value: value.to_string(),
+            projection: ProjectionMask::roots(schema_desc, roots),
+        }
+    }
+}
+
+impl ArrowPredicate for TagEqRowFilter {
+    fn projection(&self) -> &ProjectionMask {
+        &self.projection
+    }
+
+    fn evaluate(&mut self, batch: RecordBatch) -> std::result::Result<BooleanArray, ArrowError> {
+        let inline = batch
+            .column(batch.num_columns() - 1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .ok_or_else(|| ArrowError::CastError("inline column is not a string".to_string()))?;
+        let inline_eq =
+            compute::prep_null_mask_filter(&compute::eq_utf8_scalar(inline, &self.value)?);
+        let Some(code) = self.code else { return Ok(inline_eq) };
+
+        let codes = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .ok_or_else(|| ArrowError::CastError("code column is not u32".to_string()))?;
+        let code_eq = compute::prep_null_mask_filter(&compute::eq_scalar(codes, code)?);
+        compute::or(&code_eq, &inline_eq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_test_util::temp_dir::create_temp_dir;
+    use object_store::services::Fs;
+
+    use super::*;
+
+    fn new_object_store(root: &str) -> ObjectStore {
+        let mut builder = Fs::default();
+        builder.root(root);
+        ObjectStore::new(builder).unwrap().finish()
+    }
+
+    fn new_dictionary(root: &str, max_values: Option<usize>) -> RegionTagDictionary {
+        RegionTagDictionary::new("sst/", new_object_store(root), max_values)
+    }
+
+    #[tokio::test]
+    async fn test_dictionary_add_and_load() {
+        let dir = create_temp_dir("tag_dictionary");
+        let root = dir.path().to_str().unwrap();
+        let dictionary = new_dictionary(root, Some(2));
+        let generation = dictionary.writer_generation().await.unwrap();
+        assert_eq!(1, generation);
+
+        let snapshot = dictionary
+            .add_values(generation, "k0", &["a", "b", "c"])
+            .await
+            .unwrap();
+        assert_eq!(1, snapshot.version());
+        assert_eq!(Some(0), snapshot.code("k0", "a"));
+        assert_eq!(Some(1), snapshot.code("k0", "b"));
+        // Spills over the bound.
+        assert_eq!(None, snapshot.code("k0", "c"));
+        assert_eq!(Some("b"), snapshot.value("k0", 1));
+
+        // Adding present values doesn't bump the version.
+        let snapshot = dictionary
+            .add_values(generation, "k0", &["b", "a"])
+            .await
+            .unwrap();
+        assert_eq!(1, snapshot.version());
+        assert_eq!(1, dictionary.persist(generation).await.unwrap());
+
+        let snapshot = dictionary
+            .add_values(generation, "k1", &["x"])
+            .await
+            .unwrap();
+        assert_eq!(2, snapshot.version());
+        assert_eq!(2, dictionary.persist(generation).await.unwrap());
+
+        // Each page only holds the values added after the previous one.
+        let object_store = new_object_store(root);
+        let page = object_store
+            .read(&dictionary.page_path(generation, 2))
+            .await
+            .unwrap();
+        let page: TagDictionaryPage = serde_json::from_slice(&page).unwrap();
+        assert_eq!(
+            HashMap::from([("k1".to_string(), (0, vec!["x".to_string()]))]),
+            page.columns
+        );
+
+        let reopened = new_dictionary(root, None);
+        assert!(!reopened.is_writable());
+        let snapshot = reopened.snapshot(generation, 2).await.unwrap();
+        assert_eq!(2, snapshot.version());
+        assert_eq!(Some(1), snapshot.code("k0", "b"));
+        assert_eq!(Some(0), snapshot.code("k1", "x"));
+        assert!(reopened.snapshot(generation, 3).await.is_err());
+        assert!(reopened.add_values(generation, "k0", &["c"]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empty_dictionary() {
+        let dir = create_temp_dir("tag_dictionary");
+        let dictionary = new_dictionary(dir.path().to_str().unwrap(), Some(2));
+        let snapshot = dictionary.snapshot(1, 0).await.unwrap();
+        assert_eq!(0, snapshot.version());
+        assert_eq!(None, snapshot.code("k0", "a"));
+        assert_eq!(1, dictionary.writer_generation().await.unwrap());
+        assert_eq!(0, dictionary.persist(1).await.unwrap());
+        assert!(!dictionary.rotate_if_full().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_and_purge_generations() {
+        let dir = create_temp_dir("tag_dictionary");
+        let root = dir.path().to_str().unwrap();
+        let dictionary = new_dictionary(root, Some(2));
+        let _ = dictionary.add_values(1, "k0", &["a"]).await.unwrap();
+        // The generation isn't full.
+        assert!(!dictionary.rotate_if_full().await.unwrap());
+
+        let _ = dictionary.add_values(1, "k0", &["b"]).await.unwrap();
+        // The values of the generation are persisted before rotating.
+        assert!(dictionary.rotate_if_full().await.unwrap());
+        assert_eq!(2, dictionary.writer_generation().await.unwrap());
+        assert_eq!(2, dictionary.persist(1).await.unwrap());
+
+        // Values are no longer added to the previous generation.
+        let snapshot = dictionary.add_values(1, "k0", &["c"]).await.unwrap();
+        assert_eq!(None, snapshot.code("k0", "c"));
+        let snapshot = dictionary.add_values(2, "k0", &["c"]).await.unwrap();
+        assert_eq!(Some(0), snapshot.code("k0", "c"));
+        assert_eq!(1, dictionary.persist(2).await.unwrap());
+
+        // Released generations are loaded again from the pages.
+        dictionary.release_generations(&HashSet::from([2])).await;
+        let snapshot = dictionary.snapshot(1, 2).await.unwrap();
+        assert_eq!(Some(1), snapshot.code("k0", "b"));
+
+        // The writer continues with the latest generation after reopening.
+        let reopened = new_dictionary(root, Some(2));
+        assert_eq!(2, reopened.writer_generation().await.unwrap());
+        reopened.purge_generations(&HashSet::new()).await.unwrap();
+        assert!(reopened.snapshot(1, 2).await.is_err());
+        let snapshot = reopened.snapshot(2, 1).await.unwrap();
+        assert_eq!(Some(0), snapshot.code("k0", "c"));
+    }
+
+    #[tokio::test]
+    async fn test_read_legacy_dictionary() {
+        let dir = create_temp_dir("tag_dictionary");
+        let root = dir.path().to_str().unwrap();
+        let dictionary = new_dictionary(root, Some(2));
+        let file = TagDictionaryFile {
+            version: 3,
+            columns: HashMap::from([("k0".to_string(), vec!["a".to_string()])]),
+        };
+        new_object_store(root)
+            .write(
+                &dictionary.legacy_file_path(3),
+                serde_json::to_vec(&file).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let snapshot = dictionary.snapshot(LEGACY_GENERATION, 3).await.unwrap();
+        assert_eq!(Some(0), snapshot.code("k0", "a"));
+        // New values are added to the generations after the legacy one.
+        assert_eq!(1, dictionary.writer_generation().await.unwrap());
+
+        dictionary
+            .purge_generations(&HashSet::from([LEGACY_GENERATION]))
+            .await
+            .unwrap();
+        let reopened = new_dictionary(root, None);
+        assert!(reopened.snapshot(LEGACY_GENERATION, 3).await.is_ok());
+        reopened.purge_generations(&HashSet::new()).await.unwrap();
+        let reopened = new_dictionary(root, None);
+        assert!(reopened.snapshot(LEGACY_GENERATION, 3).await.is_err());
+    }
+
+    #[test]
+    fn test_values_by_frequency() {
+        let array = StringArray::from(vec![Some("b"), Some("a"), None, Some("b"), Some("c")]);
+        assert_eq!(vec!["b", "a", "c"], values_by_frequency(&array));
+    }
+}
@@ -27,6 +27,7 @@ use arrow_array::{
 use async_compat::CompatExt;
 use async_stream::try_stream;
 use async_trait::async_trait;
+use common_base::BitVec;
 use common_telemetry::{error, warn};
 use common_time::range::TimestampRange;
 use common_time::timestamp::TimeUnit;
@@ -34,34 +35,45 @@ use common_time::Timestamp;
 use datatypes::arrow::array::BooleanArray;
 use datatypes::arrow::error::ArrowError;
 use datatypes::arrow::record_batch::RecordBatch;
-use datatypes::prelude::ConcreteDataType;
+use datatypes::prelude::{ConcreteDataType, ScalarVector};
+use datatypes::vectors::UInt8Vector;
 use futures_util::{Stream, StreamExt, TryStreamExt};
 use object_store::ObjectStore;
 use parquet::arrow::arrow_reader::{ArrowPredicate, RowFilter};
 use parquet::arrow::{ParquetRecordBatchStreamBuilder, ProjectionMask};
-use parquet::basic::{Compression, Encoding, ZstdLevel};
+use parquet::basic::{Compression, Encoding, GzipLevel, ZstdLevel};
 use parquet::file::metadata::KeyValue;
-use parquet::file::properties::WriterProperties;
+use parquet::file::properties::{WriterProperties, WriterPropertiesBuilder};
 use parquet::format::FileMetaData;
-use parquet::schema::types::SchemaDescriptor;
+use parquet::schema::types::{ColumnPath, SchemaDescriptor};
 use snafu::{OptionExt, ResultExt};
+use store_api::storage::OpType;
 use table::predicate::Predicate;
 use tokio::io::BufReader;
 
 use crate::error::{self, DecodeParquetTimeRangeSnafu, ReadObjectSnafu, ReadParquetSnafu, Result};
 use crate::read::{Batch, BatchReader};
 use crate::schema::compat::ReadAdapter;
-use crate::schema::{ProjectedSchemaRef, StoreSchema};
+use crate::schema::{ProjectedSchemaRef, StoreSchema, StoreSchemaRef};
 use crate::sst;
+use crate::sst::dictionary::{
+    self, RegionTagDictionaryRef, TagDecoder, TagDictionary, TagEncoder, TagEqRowFilter, TagLayout,
+};
 use crate::sst::stream_writer::BufferedWriter;
 use crate::sst::{FileHandle, Source, SstInfo};
 
+/// Callback of the progress of writing a file, called with the number of bytes flushed to the
+/// object store since the last call.
+pub type WriteProgress = Box<dyn Fn(u64) + Send + Sync>;
+
 /// Parquet sst writer.
 pub struct ParquetWriter<'a> {
     file_path: &'a str,
     source: Source,
     object_store: ObjectStore,
     max_row_group_size: usize,
+    tag_dictionary: Option<RegionTagDictionaryRef>,
+    write_progress: Option<WriteProgress>,
 }
 
 impl<'a> ParquetWriter<'a> {
@@ -71,9 +83,24 @@ impl<'a> ParquetWriter<'a> {
             source,
             object_store,
             max_row_group_size: 4096, // TODO(hl): make this configurable
+            tag_dictionary: None,
+            write_progress: None,
         }
     }
 
+    /// Reports the bytes written to `write_progress` as they are flushed, so the progress of
+    /// writing a large file is visible before it's closed.
+    pub fn with_write_progress(mut self, write_progress: WriteProgress) -> Self {
+        self.write_progress = Some(write_progress);
+        self
+    }
+
+    /// Encodes the string tags by `tag_dictionary` if it's writable.
+    pub fn with_tag_dictionary(mut self, tag_dictionary: RegionTagDictionaryRef) -> Self {
+        self.tag_dictionary = Some(tag_dictionary);
+        self
+    }
+
     pub async fn write_sst(self, opts: &sst::WriteOptions) -> Result<Option<SstInfo>> {
         self.write_rows(None, opts).await
     }
@@ -87,30 +114,55 @@ impl<'a> ParquetWriter<'a> {
         opts: &sst::WriteOptions,
     ) -> Result<Option<SstInfo>> {
         let schema = self.source.schema();
+        let mut encoder = self
+            .tag_dictionary
+            .take()
+            .and_then(|tag_dictionary| TagEncoder::try_new(tag_dictionary, &schema));
         let writer_props = WriterProperties::builder()
-            .set_compression(Compression::ZSTD(ZstdLevel::default()))
-            .set_encoding(Encoding::PLAIN)
+            .set_compression(opts.compression)
+            .set_encoding(opts.encoding)
             .set_max_row_group_size(self.max_row_group_size)
             .set_key_value_metadata(extra_meta.map(|map| {
                 map.iter()
                     .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
                     .collect::<Vec<_>>()
-            }))
-            .build();
+            }));
+        let mut writer_props = set_column_storage_options(writer_props, &schema);
+        let arrow_schema = match &encoder {
+            Some(encoder) => {
+                // Values absent in the region dictionary are still dictionary encoded in the SST.
+                for name in encoder.inline_column_names() {
+                    writer_props =
+                        writer_props.set_column_dictionary_enabled(ColumnPath::from(name), true);
+                }
+                encoder.sst_schema().clone()
+            }
+            None => schema.arrow_schema().clone(),
+        };
 
         let mut buffered_writer = BufferedWriter::try_new(
             self.file_path.to_string(),
             self.object_store.clone(),
-            &schema,
-            Some(writer_props),
+            arrow_schema,
+            Some(writer_props.build()),
             opts.sst_write_buffer_size.as_bytes() as usize,
         )
         .await?;
         let mut rows_written = 0;
+        let mut bytes_reported = 0;
+        let mut distinct_puts = DistinctPutsChecker::new(self.source.store_schema());
 
         while let Some(batch) = self.source.next_batch().await? {
-            buffered_writer.write(&batch).await?;
+            distinct_puts.check(&batch);
+            match &mut encoder {
+                Some(encoder) => {
+                    let arrow_batch = encoder.encode(&batch).await?;
+                    buffered_writer.write_record_batch(&arrow_batch).await?;
+                }
+                None => buffered_writer.write(&batch).await?,
+            }
             rows_written += batch.num_rows();
+            self.report_written(&mut bytes_reported, buffered_writer.bytes_written());
         }
 
         if rows_written == 0 {
@@ -125,15 +177,157 @@ impl<'a> ParquetWriter<'a> {
         }
 
         let (file_meta, file_size) = buffered_writer.close().await?;
+        self.report_written(&mut bytes_reported, file_size);
         let time_range = decode_timestamp_range(&file_meta, &schema).ok().flatten();
+        // The values added to the dictionary must be persisted before the SST is visible.
+        let (tag_dictionary_generation, tag_dictionary_version) = match &encoder {
+            Some(encoder) => {
+                let (generation, version) = encoder.finish().await?;
+                (generation, Some(version))
+            }
+            None => (0, None),
+        };
 
         // object_store.write will make sure all bytes are written or an error is raised.
         Ok(Some(SstInfo {
             time_range,
             file_size,
             num_rows: rows_written,
+            distinct_puts: distinct_puts.finish(),
+            tag_dictionary_version,
+            tag_dictionary_generation,
         }))
     }
+
+    /// Reports the bytes `written` beyond the `reported` ones.
+    fn report_written(&self, reported: &mut u64, written: u64) {
+        if let Some(write_progress) = &self.write_progress {
+            if written > *reported {
+                write_progress(written - *reported);
+                *reported = written;
+            }
+        }
+    }
+}
+
+/// Checks whether the rows written to an SST are all puts of distinct row keys, where the rows
+/// of the same row key are adjacent, as the rows are sorted by row key.
+struct DistinctPutsChecker {
+    /// Schema of the rows, `None` if they aren't in the store schema.
+    schema: Option<StoreSchemaRef>,
+    prev: Option<Batch>,
+    distinct: bool,
+    /// Reused bitmap buffer.
+    selected: BitVec,
+}
+
+impl DistinctPutsChecker {
+    fn new(schema: Option<StoreSchemaRef>) -> Self {
+        let distinct = schema.is_some();
+        Self {
+            schema,
+            prev: None,
+            distinct,
+            selected: BitVec::default(),
+        }
+    }
+
+    fn check(&mut self, batch: &Batch) {
+        let Some(schema) = &self.schema else { return };
+        if !self.distinct || batch.is_empty() {
+            return;
+        }
+
+        let op_types = batch.column(schema.op_type_index());
+        let op_types = op_types.as_any().downcast_ref::<UInt8Vector>();
+        if op_types.map_or(true, |op_types| {
+            op_types
+                .iter_data()
+                .any(|op_type| op_type != Some(OpType::Put.as_u8()))
+        }) {
+            self.distinct = false;
+            return;
+        }
+
+        // A row is unique if any row key column differs from the previous row.
+        self.selected.clear();
+        self.selected.resize(batch.num_rows(), false);
+        for idx in schema.row_key_indices() {
+            let prev = self.prev.as_ref().map(|prev| prev.column(idx).as_ref());
+            batch.column(idx).find_unique(&mut self.selected, prev);
+        }
+        self.distinct = self.selected.all();
+        self.prev = Some(batch.clone());
+    }
+
+    fn finish(self) -> bool {
+        self.distinct
+    }
+}
+
+/// Overrides the table level writer properties by the storage options of the columns.
+fn set_column_storage_options(
+    mut builder: WriterPropertiesBuilder,
+    schema: &datatypes::schema::SchemaRef,
+) -> WriterPropertiesBuilder {
+    for column_schema in schema.column_schemas() {
+        let path = ColumnPath::from(column_schema.name.as_str());
+        if let Some(encoding) = column_schema.encoding() {
+            builder = match encoding {
+                "dictionary" => builder.set_column_dictionary_enabled(path.clone(), true),
+                _ => match parse_encoding(encoding) {
+                    // The dictionary encoding takes precedence over other encodings.
+                    Some(encoding) => builder
+                        .set_column_dictionary_enabled(path.clone(), false)
+                        .set_column_encoding(path.clone(), encoding),
+                    None => {
+                        warn!(
+                            "Ignore unknown encoding {} of column {}",
+                            encoding, column_schema.name
+                        );
+                        builder
+                    }
+                },
+            };
+        }
+        if let Some(compression) = column_schema.compression() {
+            builder = match parse_compression(compression) {
+                Some(compression) => builder.set_column_compression(path, compression),
+                None => {
+                    warn!(
+                        "Ignore unknown compression {} of column {}",
+                        compression, column_schema.name
+                    );
+                    builder
+                }
+            };
+        }
+    }
+    builder
+}
+
+/// Returns the parquet encoding named `encoding`, `None` if it's unknown.
+pub fn parse_encoding(encoding: &str) -> Option<Encoding> {
+    match encoding {
+        "plain" => Some(Encoding::PLAIN),
+        "delta_binary_packed" => Some(Encoding::DELTA_BINARY_PACKED),
+        "delta_length_byte_array" => Some(Encoding::DELTA_LENGTH_BYTE_ARRAY),
+        "delta_byte_array" => Some(Encoding::DELTA_BYTE_ARRAY),
+        "byte_stream_split" => Some(Encoding::BYTE_STREAM_SPLIT),
+        _ => None,
+    }
+}
+
+/// Returns the parquet compression named `compression`, `None` if it's unknown.
+pub fn parse_compression(compression: &str) -> Option<Compression> {
+    match compression {
+        "uncompressed" => Some(Compression::UNCOMPRESSED),
+        "snappy" => Some(Compression::SNAPPY),
+        "gzip" => Some(Compression::GZIP(GzipLevel::default())),
+        "lz4" => Some(Compression::LZ4_RAW),
+        "zstd" => Some(Compression::ZSTD(ZstdLevel::default())),
+        _ => None,
+    }
 }
 
 fn decode_timestamp_range(
@@ -221,6 +415,7 @@ pub struct ParquetReader {
     projected_schema: ProjectedSchemaRef,
     predicate: Predicate,
     time_range: TimestampRange,
+    tag_dictionary: Option<RegionTagDictionaryRef>,
 }
 
 impl ParquetReader {
@@ -237,9 +432,16 @@ impl ParquetReader {
             projected_schema,
             predicate,
             time_range,
+            tag_dictionary: None,
         }
     }
 
+    /// Sets the dictionary to decode the string tags of the SST if they are encoded by it.
+    pub fn with_tag_dictionary(mut self, tag_dictionary: RegionTagDictionaryRef) -> Self {
+        self.tag_dictionary = Some(tag_dictionary);
+        self
+    }
+
     pub async fn chunk_stream(&self) -> Result<ChunkStream> {
         let file_path = self.file_handle.file_path();
         let operator = self.object_store.clone();
@@ -253,17 +455,17 @@ impl ParquetReader {
         let builder = ParquetRecordBatchStreamBuilder::new(buf_reader)
             .await
             .context(ReadParquetSnafu { file: &file_path })?;
-        let arrow_schema = builder.schema().clone();
+        let (arrow_schema, tag_layout) = dictionary::decode_sst_schema(builder.schema())?;
 
         let store_schema = Arc::new(
-            StoreSchema::try_from(arrow_schema)
+            StoreSchema::try_from(arrow_schema.clone())
                 .context(error::ConvertStoreSchemaSnafu { file: &file_path })?,
         );
 
         let adapter = ReadAdapter::new(store_schema.clone(), self.projected_schema.clone())?;
 
         let pruned_row_groups = self
-            .predicate
+            .pruning_predicate(tag_layout.as_ref())
             .prune_row_groups(
                 store_schema.schema().clone(),
                 builder.metadata().row_groups(),
@@ -275,14 +477,36 @@ impl ParquetReader {
 
         let parquet_schema_desc = builder.metadata().file_metadata().schema_descr_ptr();
 
-        let projection = ProjectionMask::roots(&parquet_schema_desc, adapter.fields_to_read());
+        let fields_to_read = adapter.fields_to_read();
+        let mut decoder = None;
+        let mut predicates = Vec::new();
+        // if time range row filter is present, we can push down the filter to reduce rows to scan.
+        if let Some(predicate) = self.build_time_range_row_filter(&parquet_schema_desc) {
+            predicates.push(predicate);
+        }
+        let mut projection = fields_to_read.clone();
+        if let Some(tag_layout) = &tag_layout {
+            let tag_dictionary = self.tag_dictionary_snapshot(&file_path).await?;
+            decoder = TagDecoder::try_new(
+                tag_dictionary.clone(),
+                tag_layout,
+                &arrow_schema,
+                &fields_to_read,
+            )?;
+            projection.extend(tag_layout.inline_indices(&fields_to_read));
+            predicates.extend(self.build_tag_row_filters(
+                &tag_dictionary,
+                tag_layout,
+                &parquet_schema_desc,
+            ));
+        }
+
+        let projection = ProjectionMask::roots(&parquet_schema_desc, projection);
         let mut builder = builder
             .with_projection(projection)
             .with_row_groups(pruned_row_groups);
-
-        // if time range row filter is present, we can push down the filter to reduce rows to scan.
-        if let Some(row_filter) = self.build_time_range_row_filter(&parquet_schema_desc) {
-            builder = builder.with_row_filter(row_filter);
+        if !predicates.is_empty() {
+            builder = builder.with_row_filter(RowFilter::new(predicates));
         }
 
         let mut stream = builder
@@ -291,15 +515,86 @@ impl ParquetReader {
 
         let chunk_stream = try_stream!({
             while let Some(res) = stream.next().await {
-                yield res.context(ReadParquetSnafu { file: &file_path })?
+                let batch = res.context(ReadParquetSnafu { file: &file_path })?;
+                let batch = match &decoder {
+                    Some(decoder) => decoder.decode(batch)?,
+                    None => batch,
+                };
+                yield batch
             }
         });
 
         ChunkStream::new(self.file_handle.clone(), adapter, Box::pin(chunk_stream))
     }
 
+    /// Returns the dictionary to decode the SST, whose version is at least the one the SST is
+    /// encoded by.
+    async fn tag_dictionary_snapshot(&self, file_path: &str) -> Result<Arc<TagDictionary>> {
+        let tag_dictionary =
+            self.tag_dictionary
+                .as_ref()
+                .context(error::InvalidTagDictionarySnafu {
+                    msg: format!("SST {file_path} is encoded by an absent tag dictionary"),
+                })?;
+        let min_version = self
+            .file_handle
+            .meta()
+            .tag_dictionary_version
+            .unwrap_or_default();
+        tag_dictionary
+            .snapshot(
+                self.file_handle.meta().tag_dictionary_generation,
+                min_version,
+            )
+            .await
+    }
+
+    /// Returns the predicate to prune row groups by. The statistics of the columns encoded by
+    /// the tag dictionary are about the codes, so the exprs over them are ignored.
+    fn pruning_predicate(&self, tag_layout: Option<&TagLayout>) -> Predicate {
+        let Some(tag_layout) = tag_layout else { return self.predicate.clone() };
+        let exprs = self
+            .predicate
+            .exprs()
+            .iter()
+            .filter(|expr| {
+                dictionary::referenced_columns(expr.df_expr())
+                    .iter()
+                    .all(|name| tag_layout.column(name).is_none())
+            })
+            .cloned()
+            .collect();
+        Predicate::new(exprs)
+    }
+
+    /// Builds the row filters of the equalities between the encoded columns and strings.
+    fn build_tag_row_filters(
+        &self,
+        tag_dictionary: &TagDictionary,
+        tag_layout: &TagLayout,
+        schema_desc: &SchemaDescriptor,
+    ) -> Vec<Box<dyn ArrowPredicate>> {
+        self.predicate
+            .exprs()
+            .iter()
+            .filter_map(|expr| {
+                let (name, value) = dictionary::string_eq_literal(expr.df_expr())?;
+                let column = tag_layout.column(name)?;
+                Some(Box::new(TagEqRowFilter::new(
+                    tag_dictionary,
+                    column,
+                    value,
+                    schema_desc,
+                )) as Box<dyn ArrowPredicate>)
+            })
+            .collect()
+    }
+
     /// Builds time range row filter.
-    fn build_time_range_row_filter(&self, schema_desc: &SchemaDescriptor) -> Option<RowFilter> {
+    fn build_time_range_row_filter(
+        &self,
+        schema_desc: &SchemaDescriptor,
+    ) -> Option<Box<dyn ArrowPredicate>> {
         let ts_col_idx = self
             .projected_schema
             .schema_to_read()
@@ -321,11 +616,10 @@ impl ParquetReader {
 
         // checks if converting time range unit into ts col unit will result into rounding error.
         if time_unit_lossy(&self.time_range, ts_col_unit) {
-            let filter = RowFilter::new(vec![Box::new(PlainTimestampRowFilter::new(
+            return Some(Box::new(PlainTimestampRowFilter::new(
                 self.time_range,
                 projection,
-            ))]);
-            return Some(filter);
+            )));
         }
 
         // If any of the conversion overflows, we cannot use arrow's computation method, instead
@@ -333,7 +627,7 @@ impl ParquetReader {
         // but simpler.
         // TODO(hl): If the range is gt_eq/lt, we also use PlainTimestampRowFilter, but these cases
         // can also use arrow's gt_eq_scalar/lt_scalar methods.
-        let row_filter = if let (Some(lower), Some(upper)) = (
+        if let (Some(lower), Some(upper)) = (
             self.time_range
                 .start()
                 .and_then(|s| s.convert_to(ts_col_unit))
@@ -343,12 +637,15 @@ impl ParquetReader {
                 .and_then(|s| s.convert_to(ts_col_unit))
                 .map(|t| t.value()),
         ) {
-            Box::new(FastTimestampRowFilter::new(projection, lower, upper)) as _
+            Some(Box::new(FastTimestampRowFilter::new(
+                projection, lower, upper,
+            )))
         } else {
-            Box::new(PlainTimestampRowFilter::new(self.time_range, projection)) as _
-        };
-        let filter = RowFilter::new(vec![row_filter]);
-        Some(filter)
+            Some(Box::new(PlainTimestampRowFilter::new(
+                self.time_range,
+                projection,
+            )))
+        }
     }
 }
 
@@ -533,21 +830,30 @@ impl BatchReader for ChunkStream {
 mod tests {
     use std::sync::Arc;
 
+    use common_base::readable_size::ReadableSize;
+    use common_query::logical_plan::Expr;
     use common_test_util::temp_dir::create_temp_dir;
-    use datatypes::arrow::array::{Array, ArrayRef, UInt64Array, UInt8Array};
-    use datatypes::prelude::{ScalarVector, Vector};
+    use datafusion_expr::{col, lit};
+    use datatypes::arrow::array::{Array, ArrayRef, StringArray, UInt64Array, UInt8Array};
+    use datatypes::prelude::{LogicalTypeId, ScalarVector, Vector, VectorRef};
     use datatypes::types::{TimestampMillisecondType, TimestampType};
-    use datatypes::vectors::TimestampMillisecondVector;
+    use datatypes::vectors::{
+        Int64Vector, StringVector, TimestampMillisecondVector, UInt64Vector, UInt8Vector,
+    };
     use object_store::services::Fs;
     use store_api::storage::OpType;
 
     use super::*;
+    use crate::chunk::ChunkReaderImpl;
     use crate::file_purger::noop::new_noop_file_purger;
     use crate::memtable::{
         tests as memtable_tests, DefaultMemtableBuilder, IterContext, MemtableBuilder,
     };
-    use crate::schema::ProjectedSchema;
+    use crate::metadata::RegionMetadata;
+    use crate::schema::{ProjectedSchema, RegionSchemaRef};
+    use crate::sst::dictionary::RegionTagDictionary;
     use crate::sst::{FileId, FileMeta};
+    use crate::test_util::descriptor_util::RegionDescBuilder;
 
     fn create_object_store(root: &str) -> ObjectStore {
         let mut builder = Fs::default();
@@ -719,6 +1025,40 @@ mod tests {
         assert_eq!(rows_total, rows_fetched);
     }
 
+    #[tokio::test]
+    async fn test_parquet_writer_progress() {
+        let schema = memtable_tests::schema_for_test();
+        let memtable = DefaultMemtableBuilder::default().build(schema);
+        let rows_total = 4096 * 4;
+        let keys_vec = (0..rows_total)
+            .map(|i| (i as i64, i as u64))
+            .collect::<Vec<_>>();
+        let values_vec = (0..rows_total)
+            .map(|i| (Some(i as u64), Some(i as u64)))
+            .collect::<Vec<_>>();
+        memtable_tests::write_kvs(&*memtable, 10, OpType::Put, &keys_vec, &values_vec);
+
+        let dir = create_temp_dir("write_parquet");
+        let object_store = create_object_store(dir.path().to_str().unwrap());
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let iter = memtable.iter(&IterContext::default()).unwrap();
+        let writer = ParquetWriter::new("progress.parquet", Source::Iter(iter), object_store)
+            .with_write_progress({
+                let reported = reported.clone();
+                Box::new(move |bytes| reported.lock().unwrap().push(bytes))
+            });
+        let opts = sst::WriteOptions {
+            sst_write_buffer_size: ReadableSize(1024),
+            ..Default::default()
+        };
+        let SstInfo { file_size, .. } = writer.write_sst(&opts).await.unwrap().unwrap();
+
+        // Each row group is reported once flushed, before the file is closed.
+        let reported = reported.lock().unwrap();
+        assert!(reported.len() > 1, "{reported:?}");
+        assert_eq!(file_size, reported.iter().sum::<u64>());
+    }
+
     fn new_file_handle(file_id: FileId) -> FileHandle {
         let file_purger = new_noop_file_purger();
         let layer = Arc::new(crate::test_util::access_layer_util::MockAccessLayer {});
@@ -732,6 +1072,11 @@ mod tests {
                 )),
                 level: 0,
                 file_size: 0,
+                num_rows: 0,
+                distinct_puts: false,
+                tag_dictionary_version: None,
+                tag_dictionary_generation: 0,
+                tombstone_sequence: None,
             },
             layer,
             file_purger,
@@ -993,6 +1338,303 @@ mod tests {
         assert!(sst_info_opt.is_none());
     }
 
+    /// Creates a region schema (host, timestamp, v0).
+    fn new_tag_schema() -> RegionSchemaRef {
+        let desc = RegionDescBuilder::new("tag-test")
+            .enable_version_column(false)
+            .push_key_column(("host", LogicalTypeId::String, true))
+            .push_field_column(("v0", LogicalTypeId::Int64, true))
+            .build();
+        let metadata: RegionMetadata = desc.try_into().unwrap();
+        metadata.schema().clone()
+    }
+
+    struct VecReader(Vec<Batch>);
+
+    #[async_trait]
+    impl BatchReader for VecReader {
+        async fn next_batch(&mut self) -> Result<Option<Batch>> {
+            Ok(self.0.pop())
+        }
+    }
+
+    fn new_tag_batch(hosts: &[Option<&str>]) -> Batch {
+        let num_rows = hosts.len();
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(hosts.to_vec())),
+            Arc::new(TimestampMillisecondVector::from_values(0..num_rows as i64)),
+            Arc::new(Int64Vector::from_values(0..num_rows as i64)),
+            Arc::new(UInt64Vector::from_vec(vec![0; num_rows])),
+            Arc::new(UInt8Vector::from_vec(vec![0; num_rows])),
+        ];
+        Batch::new(columns)
+    }
+
+    async fn write_tag_sst(
+        object_store: &ObjectStore,
+        file_handle: &FileHandle,
+        hosts: &[Option<&str>],
+        tag_dictionary: Option<RegionTagDictionaryRef>,
+    ) -> SstInfo {
+        let schema = Arc::new(ProjectedSchema::new(new_tag_schema(), None).unwrap());
+        let reader = ChunkReaderImpl::new(schema, Box::new(VecReader(vec![new_tag_batch(hosts)])));
+        let file_name = file_handle.file_name();
+        let mut writer =
+            ParquetWriter::new(&file_name, Source::Reader(reader), object_store.clone());
+        if let Some(tag_dictionary) = tag_dictionary {
+            writer = writer.with_tag_dictionary(tag_dictionary);
+        }
+        writer
+            .write_sst(&sst::WriteOptions::default())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    fn new_tag_file_handle(tag_dictionary_version: Option<u64>) -> FileHandle {
+        FileHandle::new(
+            FileMeta {
+                file_id: FileId::random(),
+                tag_dictionary_version,
+                // The SSTs in tests are all encoded by the first generation.
+                tag_dictionary_generation: tag_dictionary_version.map_or(0, |_| 1),
+                tombstone_sequence: None,
+                ..Default::default()
+            },
+            Arc::new(crate::test_util::access_layer_util::MockAccessLayer {}),
+            new_noop_file_purger(),
+        )
+    }
+
+    /// Reads the (host, v0) of the rows in the SST.
+    async fn read_tag_sst(
+        object_store: &ObjectStore,
+        file_handle: FileHandle,
+        predicate: Predicate,
+        tag_dictionary: RegionTagDictionaryRef,
+    ) -> Vec<(Option<String>, i64)> {
+        // Projects (v0, host).
+        let schema = Arc::new(ProjectedSchema::new(new_tag_schema(), Some(vec![2, 0])).unwrap());
+        let reader = ParquetReader::new(
+            file_handle,
+            object_store.clone(),
+            schema,
+            predicate,
+            TimestampRange::min_to_max(),
+        )
+        .with_tag_dictionary(tag_dictionary);
+
+        let mut stream = reader.chunk_stream().await.unwrap();
+        let mut rows = Vec::new();
+        while let Some(batch) = stream.next_batch().await.unwrap() {
+            // The batch to read is (host, timestamp, v0, sequence, op_type).
+            let hosts = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<StringVector>()
+                .unwrap();
+            let values = batch
+                .column(2)
+                .as_any()
+                .downcast_ref::<Int64Vector>()
+                .unwrap();
+            for i in 0..batch.num_rows() {
+                rows.push((
+                    hosts.get_data(i).map(|h| h.to_string()),
+                    values.get_data(i).unwrap(),
+                ));
+            }
+        }
+        rows
+    }
+
+    fn host_eq(host: &str) -> Predicate {
+        Predicate::new(vec![Expr::from(col("host").eq(lit(host)))])
+    }
+
+    const TAG_HOSTS: [Option<&str>; 8] = [
+        Some("a"),
+        Some("b"),
+        Some("a"),
+        Some("c"),
+        None,
+        Some("a"),
+        Some("b"),
+        Some("d"),
+    ];
+
+    #[tokio::test]
+    async fn test_read_sst_encoded_by_tag_dictionary() {
+        common_telemetry::init_default_ut_logging();
+        let dir = create_temp_dir("read-tag-dictionary");
+        let object_store = create_object_store(dir.path().to_str().unwrap());
+        let read_dictionary = Arc::new(RegionTagDictionary::new("", object_store.clone(), None));
+        let write_dictionary =
+            Arc::new(RegionTagDictionary::new("", object_store.clone(), Some(2)));
+
+        // SST of the plain layout.
+        let plain_handle = new_tag_file_handle(None);
+        let plain_info = write_tag_sst(&object_store, &plain_handle, &TAG_HOSTS, None).await;
+        assert_eq!(None, plain_info.tag_dictionary_version);
+        assert_eq!(0, plain_info.tag_dictionary_generation);
+        // The dictionary is not used if it's read only.
+        let info = write_tag_sst(
+            &object_store,
+            &new_tag_file_handle(None),
+            &TAG_HOSTS,
+            Some(read_dictionary.clone()),
+        )
+        .await;
+        assert_eq!(None, info.tag_dictionary_version);
+
+        // SST encoded by the dictionary.
+        let info = write_tag_sst(
+            &object_store,
+            &new_tag_file_handle(None),
+            &TAG_HOSTS,
+            Some(write_dictionary.clone()),
+        )
+        .await;
+        assert_eq!(Some(1), info.tag_dictionary_version);
+        assert_eq!(1, info.tag_dictionary_generation);
+        let encoded_handle = new_tag_file_handle(info.tag_dictionary_version);
+        let encoded_info = write_tag_sst(
+            &object_store,
+            &encoded_handle,
+            &TAG_HOSTS,
+            Some(write_dictionary),
+        )
+        .await;
+        // Nothing is added by the same values.
+        assert_eq!(Some(1), encoded_info.tag_dictionary_version);
+        assert_eq!(plain_info.num_rows, encoded_info.num_rows);
+        assert_eq!(plain_info.time_range, encoded_info.time_range);
+
+        let expect: Vec<_> = TAG_HOSTS
+            .iter()
+            .enumerate()
+            .map(|(i, host)| (host.map(|h| h.to_string()), i as i64))
+            .collect();
+        let plain_rows = read_tag_sst(
+            &object_store,
+            plain_handle,
+            Predicate::empty(),
+            read_dictionary.clone(),
+        )
+        .await;
+        assert_eq!(expect, plain_rows);
+        let encoded_rows = read_tag_sst(
+            &object_store,
+            encoded_handle.clone(),
+            Predicate::empty(),
+            read_dictionary.clone(),
+        )
+        .await;
+        assert_eq!(expect, encoded_rows);
+
+        // The SST can't be decoded without the dictionary.
+        let reader = ParquetReader::new(
+            encoded_handle,
+            object_store,
+            Arc::new(ProjectedSchema::new(new_tag_schema(), None).unwrap()),
+            Predicate::empty(),
+            TimestampRange::min_to_max(),
+        );
+        assert!(reader.chunk_stream().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tag_dictionary_spillover() {
+        common_telemetry::init_default_ut_logging();
+        let dir = create_temp_dir("tag-dictionary-spillover");
+        let object_store = create_object_store(dir.path().to_str().unwrap());
+        let tag_dictionary = Arc::new(RegionTagDictionary::new("", object_store.clone(), Some(2)));
+
+        let file_handle = new_tag_file_handle(Some(1));
+        let _ = write_tag_sst(
+            &object_store,
+            &file_handle,
+            &TAG_HOSTS,
+            Some(tag_dictionary.clone()),
+        )
+        .await;
+
+        // The most frequent values are in the dictionary.
+        let snapshot = tag_dictionary.snapshot(1, 1).await.unwrap();
+        assert_eq!(Some(0), snapshot.code("host", "a"));
+        assert_eq!(Some(1), snapshot.code("host", "b"));
+        assert_eq!(None, snapshot.code("host", "c"));
+        assert_eq!(2, snapshot.num_values("host"));
+
+        // The others are stored inline.
+        let reader = BufReader::new(
+            object_store
+                .reader(&file_handle.file_name())
+                .await
+                .unwrap()
+                .compat(),
+        );
+        let builder = ParquetRecordBatchStreamBuilder::new(reader).await.unwrap();
+        let chunk = builder.build().unwrap().next().await.unwrap().unwrap();
+        assert_eq!(&DataType::UInt32, chunk.column(0).data_type());
+        let inline_index = chunk.schema().index_of("__inline_host").unwrap();
+        let inline = chunk
+            .column(inline_index)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let inline: Vec<_> = inline.iter().collect();
+        assert_eq!(
+            vec![None, None, None, Some("c"), None, None, None, Some("d")],
+            inline
+        );
+        // Codes of the null and the inline values are null.
+        assert_eq!(3, chunk.column(0).null_count());
+    }
+
+    #[tokio::test]
+    async fn test_tag_eq_filter_by_codes() {
+        common_telemetry::init_default_ut_logging();
+        let dir = create_temp_dir("tag-eq-filter");
+        let object_store = create_object_store(dir.path().to_str().unwrap());
+        let tag_dictionary = Arc::new(RegionTagDictionary::new("", object_store.clone(), Some(2)));
+
+        let plain_handle = new_tag_file_handle(None);
+        let _ = write_tag_sst(&object_store, &plain_handle, &TAG_HOSTS, None).await;
+        let encoded_handle = new_tag_file_handle(Some(1));
+        let _ = write_tag_sst(
+            &object_store,
+            &encoded_handle,
+            &TAG_HOSTS,
+            Some(tag_dictionary.clone()),
+        )
+        .await;
+        let all_rows = read_tag_sst(
+            &object_store,
+            plain_handle,
+            Predicate::empty(),
+            tag_dictionary.clone(),
+        )
+        .await;
+
+        // Values in the dictionary, stored inline and absent in the SST.
+        for host in ["a", "b", "c", "d", "x"] {
+            let expect: Vec<_> = all_rows
+                .iter()
+                .filter(|(h, _)| h.as_deref() == Some(host))
+                .cloned()
+                .collect();
+            let rows = read_tag_sst(
+                &object_store,
+                encoded_handle.clone(),
+                host_eq(host),
+                tag_dictionary.clone(),
+            )
+            .await;
+            assert_eq!(expect, rows, "host = {host}");
+        }
+    }
+
     #[test]
     fn test_time_unit_lossy() {
         // converting a range with unit second to millisecond will not cause rounding error
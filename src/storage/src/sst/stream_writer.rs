@@ -18,7 +18,6 @@ use std::sync::{Arc, Mutex};
 
 use arrow_array::RecordBatch;
 use bytes::{BufMut, BytesMut};
-use datatypes::schema::SchemaRef;
 use object_store::{ObjectStore, Writer};
 use parquet::arrow::ArrowWriter;
 use parquet::file::properties::WriterProperties;
@@ -75,11 +74,10 @@ impl BufferedWriter {
     pub async fn try_new(
         path: String,
         store: ObjectStore,
-        schema: &SchemaRef,
+        arrow_schema: arrow::datatypes::SchemaRef,
         props: Option<WriterProperties>,
         buffer_threshold: usize,
     ) -> error::Result<Self> {
-        let arrow_schema = schema.arrow_schema();
         let buffer = Buffer::with_capacity(buffer_threshold);
         let writer = store
             .writer(&path)
@@ -97,7 +95,7 @@ impl BufferedWriter {
             bytes_written: Default::default(),
             flushed: false,
             threshold: buffer_threshold,
-            arrow_schema: arrow_schema.clone(),
+            arrow_schema,
         })
     }
 
@@ -112,8 +110,13 @@ impl BufferedWriter {
                 .collect::<Vec<_>>(),
         )
         .context(NewRecordBatchSnafu)?;
+        self.write_record_batch(&arrow_batch).await
+    }
+
+    /// Write an arrow record batch with the schema of the writer to stream writer.
+    pub async fn write_record_batch(&mut self, arrow_batch: &RecordBatch) -> error::Result<()> {
         self.arrow_writer
-            .write(&arrow_batch)
+            .write(arrow_batch)
             .context(WriteParquetSnafu)?;
         let written = Self::try_flush(
             &self.path,
@@ -128,6 +131,11 @@ impl BufferedWriter {
         Ok(())
     }
 
+    /// Returns the number of bytes flushed to the underlying storage.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
     /// Abort writer.
     pub async fn abort(self) -> bool {
         // TODO(hl): Currently we can do nothing if file's parts have been uploaded to remote storage
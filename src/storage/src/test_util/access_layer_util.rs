@@ -13,7 +13,9 @@
 // limitations under the License.
 
 use crate::read::BoxedBatchReader;
-use crate::sst::{AccessLayer, FileHandle, FileId, ReadOptions, Source, SstInfo, WriteOptions};
+use crate::sst::{
+    AccessLayer, FileHandle, FileId, ListedObject, ReadOptions, Source, SstInfo, WriteOptions,
+};
 
 #[derive(Debug)]
 pub struct MockAccessLayer;
@@ -44,4 +46,8 @@ impl AccessLayer for MockAccessLayer {
     async fn delete_sst(&self, _file_id: FileId) -> crate::error::Result<()> {
         Ok(())
     }
+
+    async fn list_ssts(&self) -> crate::error::Result<Vec<(FileId, ListedObject)>> {
+        Ok(Vec::new())
+    }
 }
@@ -84,5 +84,7 @@ pub async fn new_store_config_with_object_store(
         file_purger,
         ttl: None,
         compaction_time_window: None,
+        wal_disabled: false,
+        ttl_column: None,
     }
 }
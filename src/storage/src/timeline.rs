@@ -0,0 +1,347 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Timeline of a region mapping the wall-clock time to the sequence committed at that time,
+//! which serves the reads of the snapshots as of a time in the past.
+//!
+//! A snapshot as of a time is only readable if the versions of the rows overwritten after
+//! that time are still kept by the region:
+//! - Memtables keep all versions, so any time after the last flush is readable.
+//! - A flush only keeps the latest versions of the flushed memtables, so the times between
+//!   the previous flush and this flush are no longer readable, except the time the flushed
+//!   memtables are frozen.
+//! - A compaction merges the versions of the SSTs, so the times before the last flush are no
+//!   longer readable.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use store_api::storage::SequenceNumber;
+
+use crate::manifest::action::RegionEdit;
+
+/// Writes in the same resolution window share a point in the timeline, so a snapshot as of a
+/// time may miss the writes made in the window before that time.
+pub const TIMELINE_RESOLUTION_MILLIS: i64 = 100;
+
+/// Max number of points in the timeline of a region. The oldest points are dropped if
+/// there are more.
+const MAX_TIMELINE_POINTS: usize = 16384;
+
+/// The state of the region at `time` is the rows whose sequence is not greater than
+/// `sequence`, which is not readable if its overwritten rows are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Point {
+    time: i64,
+    sequence: SequenceNumber,
+    readable: bool,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Points ordered by time.
+    points: VecDeque<Point>,
+    /// Snapshots before this time are gone as the points are dropped.
+    horizon: i64,
+}
+
+/// Maps the wall-clock time in millis to the sequence of a region within the retention.
+#[derive(Debug)]
+pub struct SnapshotTimeline {
+    retention: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl SnapshotTimeline {
+    /// Creates a timeline keeping the snapshots within `retention`, time travel is
+    /// disabled if it's zero.
+    pub fn new(retention: Duration) -> SnapshotTimeline {
+        SnapshotTimeline {
+            retention,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.retention.is_zero()
+    }
+
+    fn retention_millis(&self) -> i64 {
+        i64::try_from(self.retention.as_millis()).unwrap_or(i64::MAX)
+    }
+
+    /// Records that `sequence` is committed at `now`.
+    pub fn record_write(&self, now: i64, sequence: SequenceNumber) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        match inner.points.back_mut() {
+            Some(last)
+                if last.readable
+                    && last.time.div_euclid(TIMELINE_RESOLUTION_MILLIS)
+                        == now.div_euclid(TIMELINE_RESOLUTION_MILLIS) =>
+            {
+                last.time = last.time.max(now);
+                last.sequence = last.sequence.max(sequence);
+            }
+            _ => inner.push(Point {
+                time: now,
+                sequence,
+                readable: true,
+            }),
+        }
+        self.trim(&mut inner, now);
+    }
+
+    /// Records the region edit, which is applied to the version whose flushed sequence is
+    /// `prev_flushed`.
+    ///
+    /// This must be called before the edit is applied, so no reader resolves a time whose
+    /// overwritten rows are dropped by the edit against the new version.
+    pub fn record_edit(&self, edit: &RegionEdit, prev_flushed: SequenceNumber) {
+        match edit.flushed_sequence {
+            Some(flushed) => self.record_flush(edit.flushed_at, prev_flushed, flushed),
+            None if !edit.files_to_remove.is_empty() => self.record_compaction(prev_flushed),
+            None => (),
+        }
+    }
+
+    /// Records that the memtables holding the sequences in (`prev_flushed`, `flushed`] are
+    /// flushed, and they are frozen at `frozen_at` if known.
+    fn record_flush(
+        &self,
+        frozen_at: Option<i64>,
+        prev_flushed: SequenceNumber,
+        flushed: SequenceNumber,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        for point in inner.points.iter_mut() {
+            if point.sequence > prev_flushed && point.sequence < flushed {
+                point.readable = false;
+            }
+        }
+        // The state at the time the memtables are frozen is exactly the flushed sequence.
+        if let Some(frozen_at) = frozen_at {
+            let index = inner
+                .points
+                .partition_point(|point| point.time <= frozen_at);
+            let mut point = Point {
+                time: frozen_at,
+                sequence: flushed,
+                readable: true,
+            };
+            inner.points.insert(index, point);
+            // The writes before the freeze are not recorded if the flush is recovered from
+            // the manifest, so the time between the previous point and the freeze is unknown.
+            if let Some(prev) = index.checked_sub(1).map(|i| inner.points[i]) {
+                if prev.readable && prev.sequence < flushed && prev.time + 1 < frozen_at {
+                    point.time = prev.time + 1;
+                    point.sequence = prev.sequence;
+                    point.readable = false;
+                    inner.points.insert(index, point);
+                }
+            }
+            self.trim(&mut inner, frozen_at);
+        }
+    }
+
+    /// Records that the SSTs holding the sequences up to `flushed` are compacted.
+    fn record_compaction(&self, flushed: SequenceNumber) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        for point in inner.points.iter_mut() {
+            if point.sequence < flushed {
+                point.readable = false;
+            }
+        }
+    }
+
+    /// Records that the region is opened at `now` with the `committed` sequence. The
+    /// writes replayed from the WAL have no time, so the time between the last point and
+    /// `now` is not readable if there are such writes.
+    pub fn record_open(&self, now: i64, committed: SequenceNumber) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(last) = inner.points.back().copied() {
+            if last.sequence < committed && last.time < now {
+                inner.push(Point {
+                    time: last.time + 1,
+                    sequence: last.sequence,
+                    readable: false,
+                });
+            }
+        }
+        inner.push(Point {
+            time: now,
+            sequence: committed,
+            readable: true,
+        });
+        self.trim(&mut inner, now);
+    }
+
+    /// Returns the sequence committed at `as_of`, `None` if it's not before `now`, or the
+    /// reason why the snapshot is gone.
+    pub fn sequence_as_of(&self, as_of: i64, now: i64) -> Result<Option<SequenceNumber>, String> {
+        if !self.is_enabled() {
+            return Err("time travel is disabled".to_string());
+        }
+        if as_of >= now {
+            return Ok(None);
+        }
+        if now.saturating_sub(as_of) > self.retention_millis() {
+            return Err(format!(
+                "it's out of the time travel retention {:?}",
+                self.retention
+            ));
+        }
+
+        let inner = self.inner.lock().unwrap();
+        if as_of < inner.horizon {
+            return Err("too many snapshots are retained after it".to_string());
+        }
+        let index = inner.points.partition_point(|point| point.time <= as_of);
+        if index == 0 {
+            return Err("no snapshot is recorded before it".to_string());
+        }
+        let point = inner.points[index - 1];
+        if !point.readable {
+            return Err(
+                "the rows overwritten after it are dropped by flush or compaction".to_string(),
+            );
+        }
+        Ok(Some(point.sequence))
+    }
+
+    /// Drops the points out of the retention, but keeps the last one before the retention
+    /// as the state at the start of the retention.
+    fn trim(&self, inner: &mut Inner, now: i64) {
+        let start = now.saturating_sub(self.retention_millis());
+        while inner.points.len() > 1 && inner.points[1].time <= start {
+            let _ = inner.points.pop_front();
+        }
+        while inner.points.len() > MAX_TIMELINE_POINTS {
+            let _ = inner.points.pop_front();
+            inner.horizon = inner.points[0].time;
+        }
+    }
+}
+
+impl Inner {
+    fn push(&mut self, point: Point) {
+        self.points.push_back(point);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETENTION: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_sequence_as_of_writes() {
+        let timeline = SnapshotTimeline::new(RETENTION);
+        timeline.record_open(1000, 0);
+        timeline.record_write(2000, 1);
+        // Coalesced into the point of the same resolution window.
+        timeline.record_write(2050, 2);
+        timeline.record_write(3000, 3);
+
+        let now = 4000;
+        assert_eq!(Ok(Some(0)), timeline.sequence_as_of(1999, now));
+        assert_eq!(Ok(Some(2)), timeline.sequence_as_of(2050, now));
+        assert_eq!(Ok(Some(2)), timeline.sequence_as_of(2999, now));
+        assert_eq!(Ok(Some(3)), timeline.sequence_as_of(3999, now));
+        assert_eq!(Ok(None), timeline.sequence_as_of(4000, now));
+        assert_eq!(
+            Err("no snapshot is recorded before it".to_string()),
+            timeline.sequence_as_of(999, now)
+        );
+        assert_eq!(
+            Err("it's out of the time travel retention 60s".to_string()),
+            timeline.sequence_as_of(now - 60_001, now)
+        );
+
+        let disabled = SnapshotTimeline::new(Duration::ZERO);
+        disabled.record_write(2000, 1);
+        assert_eq!(
+            Err("time travel is disabled".to_string()),
+            disabled.sequence_as_of(2000, now)
+        );
+    }
+
+    #[test]
+    fn test_sequence_as_of_flush_and_compaction() {
+        let timeline = SnapshotTimeline::new(RETENTION);
+        timeline.record_open(1000, 0);
+        timeline.record_write(2000, 1);
+        timeline.record_write(3000, 2);
+        // Frozen at 3500 with sequence 2, and a write after the freeze.
+        timeline.record_write(4000, 3);
+        timeline.record_flush(Some(3500), 0, 2);
+
+        let now = 5000;
+        let gone =
+            Err("the rows overwritten after it are dropped by flush or compaction".to_string());
+        assert_eq!(Ok(Some(0)), timeline.sequence_as_of(1500, now));
+        assert_eq!(gone, timeline.sequence_as_of(2500, now));
+        assert_eq!(Ok(Some(2)), timeline.sequence_as_of(3000, now));
+        assert_eq!(Ok(Some(2)), timeline.sequence_as_of(3500, now));
+        assert_eq!(Ok(Some(3)), timeline.sequence_as_of(4500, now));
+
+        timeline.record_compaction(2);
+        assert_eq!(gone, timeline.sequence_as_of(1500, now));
+        assert_eq!(Ok(Some(2)), timeline.sequence_as_of(3500, now));
+    }
+
+    #[test]
+    fn test_sequence_as_of_reopen() {
+        let timeline = SnapshotTimeline::new(RETENTION);
+        // Recovered flushes from the manifest, then the WAL is replayed up to sequence 5.
+        timeline.record_flush(Some(1000), 0, 1);
+        timeline.record_flush(Some(2000), 1, 3);
+        timeline.record_open(4000, 5);
+
+        let now = 5000;
+        let unknown =
+            Err("the rows overwritten after it are dropped by flush or compaction".to_string());
+        assert_eq!(Ok(Some(1)), timeline.sequence_as_of(1000, now));
+        assert_eq!(unknown, timeline.sequence_as_of(1500, now));
+        assert_eq!(Ok(Some(3)), timeline.sequence_as_of(2000, now));
+        assert_eq!(unknown, timeline.sequence_as_of(3000, now));
+        assert_eq!(Ok(Some(5)), timeline.sequence_as_of(4000, now));
+    }
+
+    #[test]
+    fn test_trim_timeline() {
+        let timeline = SnapshotTimeline::new(Duration::from_secs(1));
+        timeline.record_open(0, 0);
+        for i in 1..=20 {
+            timeline.record_write(i * 200, i as u64);
+        }
+        let inner = timeline.inner.lock().unwrap();
+        // The point at 3000 is the state at the start of the retention.
+        assert_eq!(3000, inner.points[0].time);
+        assert_eq!(6, inner.points.len());
+    }
+}
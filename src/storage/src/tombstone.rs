@@ -0,0 +1,197 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Range tombstones of a region.
+//!
+//! A range tombstone deletes the rows of some row keys in a time range without reading them.
+//! It's recorded in the manifest with the sequence allocated to it, and hides the rows whose
+//! sequences are not after it from the readers. Compactions drop the hidden rows from their
+//! outputs, and the tombstone is removed once no SST or memtable may hold a row it hides.
+
+use common_time::Timestamp;
+use datatypes::value::Value;
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt};
+use store_api::storage::{ColumnId, DeleteRangeRequest, SequenceNumber};
+
+use crate::error::{self, Result};
+use crate::metadata::RegionMetadata;
+use crate::sst::FileHandle;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeTombstone {
+    /// Sequence of the tombstone, rows with greater sequences are not deleted.
+    pub sequence: SequenceNumber,
+    /// Ids and values of the row key columns of the rows to delete, all keys are deleted if
+    /// empty.
+    pub keys: Vec<(ColumnId, Value)>,
+    /// Inclusive start of the time range.
+    pub start: Option<Timestamp>,
+    /// Exclusive end of the time range.
+    pub end: Option<Timestamp>,
+}
+
+impl RangeTombstone {
+    /// Creates a tombstone of `request` with `sequence`, validating the request against the
+    /// region `metadata`.
+    pub fn new(
+        metadata: &RegionMetadata,
+        sequence: SequenceNumber,
+        request: DeleteRangeRequest,
+    ) -> Result<RangeTombstone> {
+        let schema = metadata.schema();
+        let ts_index = schema.timestamp_key_index();
+        let mut keys = Vec::with_capacity(request.keys.len());
+        for (name, value) in request.keys {
+            let (index, column) = schema
+                .row_key_columns()
+                .enumerate()
+                .find(|(_, column)| column.name() == name)
+                .context(error::UnknownColumnSnafu { name: &name })?;
+            ensure!(
+                index != ts_index,
+                error::InvalidDeleteRangeSnafu {
+                    msg: format!("timestamp column {name} should be deleted by the time range"),
+                }
+            );
+            ensure!(!value.is_null(), error::HasNullSnafu { name: &name });
+            let expect = &column.desc.data_type;
+            let given = value.data_type();
+            ensure!(
+                *expect == given,
+                error::TypeMismatchSnafu {
+                    name: &name,
+                    expect: expect.clone(),
+                    given,
+                }
+            );
+            keys.push((column.id(), value));
+        }
+
+        Ok(RangeTombstone {
+            sequence,
+            keys,
+            start: *request.time_range.start(),
+            end: *request.time_range.end(),
+        })
+    }
+
+    /// Returns true if `ts` is in the time range of the tombstone.
+    pub fn contains_time(&self, ts: Timestamp) -> bool {
+        self.start.map_or(true, |start| ts >= start) && self.end.map_or(true, |end| ts < end)
+    }
+
+    /// Returns true if the time range of the tombstone overlaps the inclusive `time_range` of
+    /// a file, which is unknown if `None`.
+    pub fn overlaps(&self, time_range: &Option<(Timestamp, Timestamp)>) -> bool {
+        let Some((file_start, file_end)) = time_range else { return true };
+        self.start.map_or(true, |start| *file_end >= start)
+            && self.end.map_or(true, |end| *file_start < end)
+    }
+
+    /// Returns true if the file may hold rows hidden by the tombstone, which is the case if
+    /// the tombstone is not applied when the file is written.
+    pub fn may_hide(&self, file: &FileHandle) -> bool {
+        file.tombstone_sequence().unwrap_or(0) < self.sequence && self.overlaps(file.time_range())
+    }
+}
+
+/// Returns the max sequence of `tombstones`, `None` if there is no tombstone.
+pub fn max_sequence(tombstones: &[RangeTombstone]) -> Option<SequenceNumber> {
+    tombstones.iter().map(|tombstone| tombstone.sequence).max()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_time::range::TimestampRange;
+    use common_time::timestamp::TimeUnit;
+    use datatypes::prelude::LogicalTypeId;
+
+    use super::*;
+    use crate::file_purger::noop::new_noop_file_purger;
+    use crate::sst::FileMeta;
+    use crate::test_util::access_layer_util::MockAccessLayer;
+    use crate::test_util::descriptor_util::RegionDescBuilder;
+
+    fn new_metadata() -> RegionMetadata {
+        let desc = RegionDescBuilder::new("tombstone-test")
+            .enable_version_column(false)
+            .push_key_column(("host", LogicalTypeId::String, false))
+            .push_field_column(("v0", LogicalTypeId::Int64, true))
+            .build();
+        desc.try_into().unwrap()
+    }
+
+    fn new_request(keys: Vec<(&str, Value)>, start: i64, end: i64) -> DeleteRangeRequest {
+        DeleteRangeRequest {
+            keys: keys
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            time_range: TimestampRange::with_unit(start, end, TimeUnit::Millisecond).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_new_tombstone() {
+        let metadata = new_metadata();
+        let request = new_request(vec![("host", Value::from("a"))], 10, 20);
+        let tombstone = RangeTombstone::new(&metadata, 5, request).unwrap();
+        assert_eq!(5, tombstone.sequence);
+        assert_eq!(1, tombstone.keys.len());
+        assert!(tombstone.contains_time(Timestamp::new_millisecond(10)));
+        assert!(tombstone.contains_time(Timestamp::new_millisecond(19)));
+        assert!(!tombstone.contains_time(Timestamp::new_millisecond(20)));
+        // Timestamps of other units are compared by their values in time.
+        assert!(tombstone.contains_time(Timestamp::new_second(0)));
+
+        let invalid = [
+            new_request(vec![("v0", Value::from(1i64))], 0, 1),
+            new_request(vec![("timestamp", Value::from(1i64))], 0, 1),
+            new_request(vec![("host", Value::Null)], 0, 1),
+            new_request(vec![("host", Value::from(1i64))], 0, 1),
+        ];
+        for request in invalid {
+            assert!(RangeTombstone::new(&metadata, 5, request).is_err());
+        }
+    }
+
+    #[test]
+    fn test_may_hide_file() {
+        let metadata = new_metadata();
+        let tombstone = RangeTombstone::new(&metadata, 5, new_request(vec![], 10, 20)).unwrap();
+        let new_file = |time_range: Option<(i64, i64)>, tombstone_sequence| {
+            let meta = FileMeta {
+                time_range: time_range.map(|(start, end)| {
+                    (
+                        Timestamp::new_millisecond(start),
+                        Timestamp::new_millisecond(end),
+                    )
+                }),
+                tombstone_sequence,
+                ..Default::default()
+            };
+            FileHandle::new(meta, Arc::new(MockAccessLayer), new_noop_file_purger())
+        };
+
+        assert!(tombstone.may_hide(&new_file(Some((0, 10)), None)));
+        assert!(tombstone.may_hide(&new_file(Some((0, 10)), Some(4))));
+        assert!(!tombstone.may_hide(&new_file(Some((0, 10)), Some(5))));
+        assert!(!tombstone.may_hide(&new_file(Some((0, 9)), None)));
+        assert!(!tombstone.may_hide(&new_file(Some((20, 30)), None)));
+        assert!(tombstone.may_hide(&new_file(None, None)));
+    }
+}
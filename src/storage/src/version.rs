@@ -34,6 +34,7 @@ use crate::metadata::RegionMetadataRef;
 use crate::schema::RegionSchemaRef;
 use crate::sst::{AccessLayerRef, FileMeta, LevelMetas};
 use crate::sync::CowCell;
+use crate::tombstone::RangeTombstone;
 pub const INIT_COMMITTED_SEQUENCE: u64 = 0;
 
 /// Controls version of in memory state for a region.
@@ -76,7 +77,7 @@ impl VersionControl {
 
     #[inline]
     pub fn committed_sequence(&self) -> SequenceNumber {
-        self.committed_sequence.load(Ordering::Relaxed)
+        self.committed_sequence.load(Ordering::Acquire)
     }
 
     /// Set committed sequence to `value`.
@@ -85,8 +86,9 @@ impl VersionControl {
     /// last sequence.
     #[inline]
     pub fn set_committed_sequence(&self, value: SequenceNumber) {
-        // Relaxed ordering is enough for this update as this method requires external synchoronization.
-        self.committed_sequence.store(value, Ordering::Relaxed);
+        // Only one thread updates the sequence, but snapshots loading the sequence must also
+        // observe the versions committed before it.
+        self.committed_sequence.store(value, Ordering::Release);
     }
 
     /// Freeze all mutable memtables.
@@ -134,6 +136,9 @@ pub struct VersionEdit {
     pub flushed_sequence: Option<SequenceNumber>,
     pub manifest_version: ManifestVersion,
     pub max_memtable_id: Option<MemtableId>,
+    pub tombstones_to_add: Vec<RangeTombstone>,
+    /// Sequences of the range tombstones to remove.
+    pub tombstones_to_remove: Vec<SequenceNumber>,
 }
 
 pub type VersionControlRef = Arc<VersionControl>;
@@ -157,6 +162,8 @@ pub struct Version {
     ssts: LevelMetasRef,
     /// Inclusive max sequence of flushed data.
     flushed_sequence: SequenceNumber,
+    /// Range tombstones of the region, in the order of their sequences.
+    range_tombstones: Arc<Vec<RangeTombstone>>,
     /// Current version of manifest.
     manifest_version: ManifestVersion,
     // TODO(yingwen): Maybe also store last sequence to this version when switching
@@ -188,6 +195,7 @@ impl Version {
             memtables: Arc::new(MemtableVersion::new(mutable_memtable)),
             ssts: Arc::new(LevelMetas::new(sst_layer, file_purger)),
             flushed_sequence: 0,
+            range_tombstones: Arc::new(Vec::new()),
             manifest_version,
         }
     }
@@ -227,13 +235,79 @@ impl Version {
         self.flushed_sequence
     }
 
+    #[inline]
+    pub fn range_tombstones(&self) -> &Arc<Vec<RangeTombstone>> {
+        &self.range_tombstones
+    }
+
+    /// Returns the sequences of the range tombstones that no SST or memtable of the version
+    /// may hold a row hidden by, which are safe to remove.
+    pub fn obsolete_tombstones(&self) -> Vec<SequenceNumber> {
+        self.range_tombstones
+            .iter()
+            .filter(|tombstone| {
+                // Rows in memtables are only dropped once they are flushed and compacted.
+                tombstone.sequence <= self.flushed_sequence
+                    && !self
+                        .ssts
+                        .levels()
+                        .iter()
+                        .flat_map(|level| level.files())
+                        .any(|file| tombstone.may_hide(file))
+            })
+            .map(|tombstone| tombstone.sequence)
+            .collect()
+    }
+
+    /// Returns the exact number of rows visible in the version if it can tell without reading
+    /// them. It can when the rows in each SST and memtable are all puts of distinct row keys,
+    /// and the time ranges of the SSTs and memtables don't overlap, so no row key is in two of
+    /// them, and no range tombstone hides any of them.
+    pub fn exact_rows(&self) -> Option<usize> {
+        if !self.range_tombstones.is_empty() {
+            return None;
+        }
+        let mut rows = 0;
+        let mut time_ranges = Vec::new();
+        for file in self.ssts.levels().iter().flat_map(|level| level.files()) {
+            if !file.distinct_puts() {
+                return None;
+            }
+            rows += file.num_rows();
+            time_ranges.push((*file.time_range())?);
+        }
+        let memtables = self
+            .memtables
+            .immutable_memtables()
+            .iter()
+            .chain(std::iter::once(self.memtables.mutable_memtable()));
+        for memtable in memtables {
+            if memtable.num_rows() == 0 {
+                continue;
+            }
+            if !memtable.distinct_puts() {
+                return None;
+            }
+            rows += memtable.num_rows();
+            time_ranges.push(memtable.time_range()?);
+        }
+
+        time_ranges.sort_unstable();
+        time_ranges
+            .windows(2)
+            .all(|ranges| ranges[0].1 < ranges[1].0)
+            .then_some(rows)
+    }
+
     pub fn apply_checkpoint(
         &mut self,
         flushed_sequence: Option<SequenceNumber>,
         manifest_version: ManifestVersion,
         files: impl Iterator<Item = FileMeta>,
+        range_tombstones: Vec<RangeTombstone>,
     ) {
         self.flushed_sequence = flushed_sequence.unwrap_or(self.flushed_sequence);
+        self.range_tombstones = Arc::new(range_tombstones);
         self.manifest_version = manifest_version;
         let ssts = self.ssts.merge(files, std::iter::empty());
         info!(
@@ -263,6 +337,14 @@ impl Version {
             self.memtables = Arc::new(removed);
         }
 
+        if !edit.tombstones_to_add.is_empty() || !edit.tombstones_to_remove.is_empty() {
+            let mut range_tombstones = self.range_tombstones.as_ref().clone();
+            range_tombstones
+                .retain(|tombstone| !edit.tombstones_to_remove.contains(&tombstone.sequence));
+            range_tombstones.extend(edit.tombstones_to_add);
+            self.range_tombstones = Arc::new(range_tombstones);
+        }
+
         let handles_to_add = edit.files_to_add.into_iter();
         let merged_ssts = self
             .ssts
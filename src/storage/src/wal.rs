@@ -37,6 +37,8 @@ pub struct Wal<S: LogStore> {
     region_id: RegionId,
     namespace: S::Namespace,
     store: Arc<S>,
+    /// Skips writing entries if true, the entries to read are empty then.
+    disabled: bool,
 }
 
 pub type PayloadStream<'a> =
@@ -49,6 +51,7 @@ impl<S: LogStore> Clone for Wal<S> {
             region_id: self.region_id,
             namespace: self.namespace.clone(),
             store: self.store.clone(),
+            disabled: self.disabled,
         }
     }
 }
@@ -60,9 +63,16 @@ impl<S: LogStore> Wal<S> {
             region_id,
             namespace,
             store,
+            disabled: false,
         }
     }
 
+    /// Sets whether to skip writing entries.
+    pub fn with_disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
     pub async fn obsolete(&self, seq: SequenceNumber) -> Result<()> {
         self.store
             .obsolete(self.namespace.clone(), seq)
@@ -106,6 +116,10 @@ impl<S: LogStore> Wal<S> {
         mut header: WalHeader,
         payload: Option<&Payload>,
     ) -> Result<Id> {
+        if self.disabled {
+            return Ok(seq);
+        }
+
         if let Some(p) = payload {
             header.mutation_types = wal::gen_mutation_types(p);
         }
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use common_recordbatch::RecordBatch;
 use datatypes::schema::{ColumnSchema, SchemaRef};
 use snafu::{ensure, ResultExt};
@@ -27,7 +29,15 @@ impl CompatWrite for WriteBatch {
         // Fast path, nothing to do if schema version of the write batch is equal to version
         // of destination.
         if data_version == schema_version {
-            debug_assert_eq!(dest_schema.column_schemas(), self.schema().column_schemas());
+            // Writing columns of another layout would corrupt the region, so we reject the
+            // batch and let the caller rebuild it against the latest schema.
+            ensure!(
+                Arc::ptr_eq(dest_schema, self.schema())
+                    || dest_schema.column_schemas() == self.schema().column_schemas(),
+                error::SchemaVersionMismatchSnafu {
+                    version: schema_version
+                }
+            );
 
             return Ok(());
         }
@@ -100,8 +110,8 @@ fn column_not_in_schema(schema: &SchemaRef, column_schemas: &[ColumnSchema]) ->
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
-    use std::sync::Arc;
 
+    use common_error::prelude::ErrorExt;
     use datatypes::data_type::ConcreteDataType;
     use datatypes::schema::{ColumnDefaultConstraint, SchemaBuilder};
     use datatypes::vectors::{Int32Vector, TimestampMillisecondVector, VectorRef};
@@ -216,6 +226,21 @@ mod tests {
         batch.compat_write(&schema).unwrap();
     }
 
+    #[test]
+    fn test_write_batch_compat_same_version_mismatch() {
+        let schema = new_test_schema(None);
+        let mut batch = WriteBatch::new(schema, TEST_ROW_KEY_END);
+
+        // Same version but has one more column.
+        let schema_mismatch = new_test_schema(Some(None));
+        let err = batch.compat_write(&schema_mismatch).unwrap_err();
+        assert!(
+            matches!(err, Error::SchemaVersionMismatch { version: 0, .. }),
+            "err {err} is not SchemaVersionMismatch",
+        );
+        assert!(err.status_code().is_retryable());
+    }
+
     #[test]
     fn test_write_batch_compat_columns_not_in_schema() {
         let schema_has_column = new_test_schema(Some(None));
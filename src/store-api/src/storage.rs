@@ -36,8 +36,11 @@ pub use self::engine::{CreateOptions, EngineContext, OpenOptions, StorageEngine}
 pub use self::metadata::RegionMeta;
 pub use self::region::{FlushContext, Region, WriteContext};
 pub use self::requests::{
-    AddColumn, AlterOperation, AlterRequest, GetRequest, ScanRequest, WriteRequest,
+    AddColumn, AlterOperation, AlterRequest, DeleteRangeRequest, DeletionPacer, DeletionPacerRef,
+    GetRequest, OrphanGcRequest, ScanRequest, WriteRequest,
+};
+pub use self::responses::{
+    GetResponse, OrphanFile, OrphanGcResponse, OrphanKind, ScanResponse, WriteResponse,
 };
-pub use self::responses::{GetResponse, ScanResponse, WriteResponse};
 pub use self::snapshot::{ReadContext, Snapshot};
 pub use self::types::{OpType, SequenceNumber};
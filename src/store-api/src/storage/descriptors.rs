@@ -46,6 +46,16 @@ pub struct ColumnDescriptor {
     default_constraint: Option<ColumnDefaultConstraint>,
     #[builder(default, setter(into))]
     pub comment: String,
+    /// Encoding of the column in SST files, default is None, which means the default
+    /// encoding of the SST writer.
+    #[builder(default)]
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Compression of the column in SST files, default is None, which means the default
+    /// compression of the SST writer.
+    #[builder(default)]
+    #[serde(default)]
+    pub compression: Option<String>,
 }
 
 impl ColumnDescriptor {
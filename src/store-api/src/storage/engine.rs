@@ -89,6 +89,11 @@ pub struct CreateOptions {
     /// Region SST files TTL
     pub ttl: Option<Duration>,
     pub compaction_time_window: Option<i64>,
+    /// Whether writes to the region skip the WAL, losing unflushed data on crash.
+    pub wal_disabled: bool,
+    /// Timestamp column holding the expiry time of each row, whose expired rows are dropped
+    /// by compaction.
+    pub ttl_column: Option<String>,
 }
 
 /// Options to open a region.
@@ -101,4 +106,9 @@ pub struct OpenOptions {
     /// Region SST files TTL
     pub ttl: Option<Duration>,
     pub compaction_time_window: Option<i64>,
+    /// Whether writes to the region skip the WAL, losing unflushed data on crash.
+    pub wal_disabled: bool,
+    /// Timestamp column holding the expiry time of each row, whose expired rows are dropped
+    /// by compaction.
+    pub ttl_column: Option<String>,
 }
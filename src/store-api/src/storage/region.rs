@@ -37,10 +37,10 @@ use common_error::ext::ErrorExt;
 
 use crate::storage::engine::OpenOptions;
 use crate::storage::metadata::RegionMeta;
-use crate::storage::requests::{AlterRequest, WriteRequest};
-use crate::storage::responses::WriteResponse;
+use crate::storage::requests::{AlterRequest, DeleteRangeRequest, OrphanGcRequest, WriteRequest};
+use crate::storage::responses::{OrphanGcResponse, WriteResponse};
 use crate::storage::snapshot::{ReadContext, Snapshot};
-use crate::storage::RegionId;
+use crate::storage::{RegionId, SequenceNumber};
 
 /// Chunks of rows in storage engine.
 #[async_trait]
@@ -65,6 +65,11 @@ pub trait Region: Send + Sync + Clone + std::fmt::Debug + 'static {
         request: Self::WriteRequest,
     ) -> Result<WriteResponse, Self::Error>;
 
+    /// Deletes the rows matching `request` by a range tombstone, which hides the rows written
+    /// before it without reading them, until compactions drop these rows.
+    async fn delete_range(&self, request: DeleteRangeRequest)
+        -> Result<WriteResponse, Self::Error>;
+
     /// Create a snapshot for read.
     fn snapshot(&self, ctx: &ReadContext) -> Result<Self::Snapshot, Self::Error>;
 
@@ -77,8 +82,34 @@ pub trait Region: Send + Sync + Clone + std::fmt::Debug + 'static {
 
     fn disk_usage_bytes(&self) -> u64;
 
+    /// Returns bytes of the SSTs and the manifest of the region stored in the object store,
+    /// including the removed SSTs not purged yet.
+    fn storage_bytes(&self) -> u64;
+
+    /// Returns the approximate number of rows in the region, counting the rows in SSTs and
+    /// memtables, including the ones deleted or overwritten but not compacted yet.
+    fn approximate_rows(&self) -> u64;
+
+    /// Returns the exact number of rows visible in the region if it can tell without reading
+    /// them, `None` if it can't, e.g. some rows may be deleted or overwritten by others.
+    fn exact_rows(&self) -> Option<u64>;
+
+    /// Returns bytes allocated by the memtables of the region, which approximates the size of
+    /// the unflushed data in the WAL.
+    fn memtable_bytes(&self) -> u64;
+
+    /// Returns the sequence of the last write visible to reads.
+    fn committed_sequence(&self) -> SequenceNumber;
+
+    /// Returns the current flush threshold of the region in bytes, if any.
+    fn flush_threshold_bytes(&self) -> Option<u64>;
+
     /// Flush memtable of the region to disk.
     async fn flush(&self, ctx: &FlushContext) -> Result<(), Self::Error>;
+
+    /// Finds the objects under the storage of the region that the region no longer
+    /// references, and deletes them if the request asks to.
+    async fn gc_orphans(&self, request: &OrphanGcRequest) -> Result<OrphanGcResponse, Self::Error>;
 }
 
 /// Context for write operations.
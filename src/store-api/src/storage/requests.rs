@@ -13,9 +13,13 @@
 // limitations under the License.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use common_error::ext::ErrorExt;
 use common_query::logical_plan::Expr;
+use common_time::range::TimestampRange;
+use datatypes::value::Value;
 use datatypes::vectors::VectorRef;
 
 use crate::storage::{ColumnDescriptor, RegionDescriptor, SequenceNumber};
@@ -38,6 +42,17 @@ pub trait WriteRequest: Send {
     fn delete(&mut self, keys: HashMap<String, VectorRef>) -> Result<(), Self::Error>;
 }
 
+/// Request to delete the rows in a time range, whose row key columns equal the given values,
+/// without reading them.
+#[derive(Debug, Clone)]
+pub struct DeleteRangeRequest {
+    /// Names and values of the row key columns of the rows to delete, the rows of all keys in
+    /// the time range are deleted if empty. The timestamp column is not allowed.
+    pub keys: Vec<(String, Value)>,
+    /// Time range of the rows to delete.
+    pub time_range: TimestampRange,
+}
+
 #[derive(Default)]
 pub struct ScanRequest {
     /// Max sequence number to read, None for latest sequence.
@@ -49,6 +64,10 @@ pub struct ScanRequest {
     pub projection: Option<Vec<usize>>,
     /// Filters pushed down
     pub filters: Vec<Expr>,
+    /// Whether to dictionary encode string tag columns of returned chunks. The dictionary is
+    /// shared by all chunks of the scan, so tag values repeated across chunks are only stored
+    /// once.
+    pub dictionary_tags: bool,
 }
 
 #[derive(Debug)]
@@ -76,6 +95,15 @@ pub enum AlterOperation {
         /// Name of columns to drop.
         names: Vec<String>,
     },
+    /// Set the storage options of a column, which only apply to the SST files written after.
+    SetColumnOptions {
+        /// Name of the column.
+        name: String,
+        /// New encoding of the column, keeps the current one if `None`.
+        encoding: Option<String>,
+        /// New compression of the column, keeps the current one if `None`.
+        compression: Option<String>,
+    },
 }
 
 impl AlterOperation {
@@ -88,6 +116,13 @@ impl AlterOperation {
             AlterOperation::DropColumns { names } => {
                 Self::apply_drop(names, descriptor);
             }
+            AlterOperation::SetColumnOptions {
+                name,
+                encoding,
+                compression,
+            } => {
+                Self::apply_set_options(name, encoding, compression, descriptor);
+            }
         }
     }
 
@@ -119,6 +154,36 @@ impl AlterOperation {
             cf.columns.retain(|col| !name_set.contains(&col.name));
         }
     }
+
+    /// Set the storage options of the column named `name` in the [RegionDescriptor].
+    fn apply_set_options(
+        name: &str,
+        encoding: &Option<String>,
+        compression: &Option<String>,
+        descriptor: &mut RegionDescriptor,
+    ) {
+        let column = descriptor
+            .row_key
+            .columns
+            .iter_mut()
+            .chain(std::iter::once(&mut descriptor.row_key.timestamp))
+            .chain(descriptor.default_cf.columns.iter_mut())
+            .chain(
+                descriptor
+                    .extra_cfs
+                    .iter_mut()
+                    .flat_map(|cf| cf.columns.iter_mut()),
+            )
+            .find(|col| col.name == name);
+        if let Some(column) = column {
+            if encoding.is_some() {
+                column.encoding = encoding.clone();
+            }
+            if compression.is_some() {
+                column.compression = compression.clone();
+            }
+        }
+    }
 }
 
 /// Alter region request.
@@ -130,6 +195,51 @@ pub struct AlterRequest {
     pub version: u32,
 }
 
+/// Request to find the orphans of a region, the objects in the storage of the region that
+/// are referenced by neither its manifest nor its ongoing reads.
+#[derive(Debug, Clone)]
+pub struct OrphanGcRequest {
+    /// Whether to delete the orphans older than `safety_age`, the orphans are only
+    /// reported otherwise.
+    pub delete: bool,
+    /// Min age of the orphans to delete, so the files written by in-progress flushes and
+    /// compactions, which are not in the manifest yet, are spared.
+    pub safety_age: Duration,
+    /// Paces the deletions of the orphans, unlimited if `None`. The pacer is shared by the
+    /// requests to the regions so the rate of deletions is limited across them.
+    pub deletion_pacer: Option<DeletionPacerRef>,
+}
+
+/// Paces the deletions of orphans to a max number per second.
+#[derive(Debug)]
+pub struct DeletionPacer {
+    interval: Duration,
+    /// Time the next deletion is allowed, `None` if nothing is deleted yet.
+    next: Mutex<Option<Instant>>,
+}
+
+pub type DeletionPacerRef = Arc<DeletionPacer>;
+
+impl DeletionPacer {
+    /// Creates a pacer allowing `max_per_sec` deletions per second, which must be positive.
+    pub fn new(max_per_sec: u32) -> Self {
+        debug_assert!(max_per_sec > 0);
+        Self {
+            interval: Duration::from_secs(1) / max_per_sec,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Reserves the time of a deletion, returns how long to wait before deleting.
+    pub fn reserve(&self) -> Duration {
+        let now = Instant::now();
+        let mut next = self.next.lock().unwrap();
+        let at = next.map_or(now, |next| next.max(now));
+        *next = Some(at + self.interval);
+        at - now
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use datatypes::prelude::*;
@@ -206,5 +316,48 @@ mod tests {
         op.apply(&mut desc);
         assert_eq!(1, desc.row_key.columns.len());
         assert_eq!(1, desc.default_cf.columns.len());
+
+        let op = AlterOperation::SetColumnOptions {
+            name: String::from("4"),
+            encoding: Some(String::from("plain")),
+            compression: None,
+        };
+        op.apply(&mut desc);
+        assert_eq!(
+            Some("plain"),
+            desc.default_cf.columns[0].encoding.as_deref()
+        );
+        assert_eq!(None, desc.default_cf.columns[0].compression);
+        let op = AlterOperation::SetColumnOptions {
+            name: String::from("4"),
+            encoding: None,
+            compression: Some(String::from("zstd")),
+        };
+        op.apply(&mut desc);
+        assert_eq!(
+            Some("plain"),
+            desc.default_cf.columns[0].encoding.as_deref()
+        );
+        assert_eq!(
+            Some("zstd"),
+            desc.default_cf.columns[0].compression.as_deref()
+        );
+    }
+
+    #[test]
+    fn test_deletion_pacer() {
+        let pacer = DeletionPacer::new(10);
+        assert_eq!(Duration::ZERO, pacer.reserve());
+        // Deletions reserved back to back are 100ms apart.
+        let wait = pacer.reserve();
+        assert!(
+            wait > Duration::from_millis(90) && wait <= Duration::from_millis(100),
+            "{wait:?}"
+        );
+        let wait = pacer.reserve();
+        assert!(
+            wait > Duration::from_millis(190) && wait <= Duration::from_millis(200),
+            "{wait:?}"
+        );
     }
 }
@@ -12,8 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
+use crate::storage::SequenceNumber;
+
 #[derive(Debug)]
-pub struct WriteResponse {}
+pub struct WriteResponse {
+    /// Sequence of the write, readable once the committed sequence of the region reaches it.
+    pub sequence: SequenceNumber,
+}
 
 #[derive(Debug)]
 pub struct ScanResponse<R> {
@@ -23,3 +30,29 @@ pub struct ScanResponse<R> {
 
 #[derive(Debug)]
 pub struct GetResponse {}
+
+#[derive(Debug, Default)]
+pub struct OrphanGcResponse {
+    /// Orphans found in the storage of the region.
+    pub orphans: Vec<OrphanFile>,
+}
+
+/// An object in the storage of a region that the region no longer references.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanFile {
+    pub path: String,
+    pub kind: OrphanKind,
+    pub size: u64,
+    /// Time since the object was last modified, `None` if the object store doesn't tell.
+    pub age: Option<Duration>,
+    /// Whether the orphan is deleted by the request.
+    pub deleted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrphanKind {
+    /// A SST not in the manifest.
+    Sst,
+    /// A manifest file superseded by the last checkpoint.
+    Manifest,
+}
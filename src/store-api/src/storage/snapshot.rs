@@ -44,12 +44,16 @@ pub trait Snapshot: Send + Sync {
 pub struct ReadContext {
     /// Suggested batch size of chunk.
     pub batch_size: usize,
+    /// Reads the snapshot of the region as of this wall-clock time in millis if set, or the
+    /// latest snapshot otherwise.
+    pub as_of: Option<i64>,
 }
 
 impl Default for ReadContext {
     fn default() -> ReadContext {
         ReadContext {
             batch_size: consts::READ_BATCH_SIZE,
+            as_of: None,
         }
     }
 }
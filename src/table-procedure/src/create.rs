@@ -257,13 +257,13 @@ impl CreateTableProcedure {
             .map_err(Error::from_error_ext)?
             .unwrap();
 
-        let register_req = RegisterTableRequest {
-            catalog: self.data.request.catalog_name.clone(),
-            schema: self.data.request.schema_name.clone(),
-            table_name: self.data.request.table_name.clone(),
-            table_id: self.data.request.id,
+        let register_req = RegisterTableRequest::new(
+            &self.data.request.catalog_name,
+            &self.data.request.schema_name,
+            &self.data.request.table_name,
+            self.data.request.id,
             table,
-        };
+        );
         self.catalog_manager
             .register_table(register_req)
             .await
@@ -0,0 +1,144 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Limits of the columns of tables, which keep the schema evolution on insertion from growing
+//! tables without bound.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_MAX_COLUMNS: usize = 1024;
+pub const DEFAULT_MAX_ADDED_COLUMNS: usize = 256;
+
+/// A table having at least this ratio of its max columns is near the limit.
+const NEAR_LIMIT_RATIO: f64 = 0.8;
+
+/// Column limits of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnLimits {
+    /// Max number of columns of a table.
+    pub max_columns: usize,
+    /// Max number of columns added to a table by an insertion or an alteration.
+    pub max_added_columns: usize,
+}
+
+impl Default for ColumnLimits {
+    fn default() -> Self {
+        Self {
+            max_columns: DEFAULT_MAX_COLUMNS,
+            max_added_columns: DEFAULT_MAX_ADDED_COLUMNS,
+        }
+    }
+}
+
+impl ColumnLimits {
+    /// Returns whether a table of `num_columns` columns is near, or beyond, the max columns.
+    pub fn is_near_limit(&self, num_columns: usize) -> bool {
+        num_columns as f64 >= self.max_columns as f64 * NEAR_LIMIT_RATIO
+    }
+}
+
+pub type ColumnLimitsOptionsRef = Arc<ColumnLimitsOptions>;
+
+/// Options of the column limits of all the schemas, some of which may be overridden per
+/// schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnLimitsOptions {
+    pub max_columns: usize,
+    pub max_added_columns: usize,
+    /// Limits of the schemas by their names, the limits absent are the ones above.
+    pub schemas: HashMap<String, SchemaColumnLimits>,
+}
+
+impl Default for ColumnLimitsOptions {
+    fn default() -> Self {
+        Self {
+            max_columns: DEFAULT_MAX_COLUMNS,
+            max_added_columns: DEFAULT_MAX_ADDED_COLUMNS,
+            schemas: HashMap::new(),
+        }
+    }
+}
+
+impl ColumnLimitsOptions {
+    /// Returns the column limits of the tables in schema `schema_name`.
+    pub fn limits_of(&self, schema_name: &str) -> ColumnLimits {
+        let schema = self.schemas.get(schema_name);
+        ColumnLimits {
+            max_columns: schema
+                .and_then(|limits| limits.max_columns)
+                .unwrap_or(self.max_columns),
+            max_added_columns: schema
+                .and_then(|limits| limits.max_added_columns)
+                .unwrap_or(self.max_added_columns),
+        }
+    }
+}
+
+/// Column limits overriding the ones of all the schemas.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchemaColumnLimits {
+    pub max_columns: Option<usize>,
+    pub max_added_columns: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_of_schema() {
+        let options = ColumnLimitsOptions {
+            max_columns: 100,
+            max_added_columns: 10,
+            schemas: HashMap::from([(
+                "metrics".to_string(),
+                SchemaColumnLimits {
+                    max_columns: Some(500),
+                    max_added_columns: None,
+                },
+            )]),
+        };
+
+        assert_eq!(
+            ColumnLimits {
+                max_columns: 100,
+                max_added_columns: 10
+            },
+            options.limits_of("public")
+        );
+        assert_eq!(
+            ColumnLimits {
+                max_columns: 500,
+                max_added_columns: 10
+            },
+            options.limits_of("metrics")
+        );
+    }
+
+    #[test]
+    fn test_near_limit() {
+        let limits = ColumnLimits {
+            max_columns: 10,
+            max_added_columns: 10,
+        };
+        assert!(!limits.is_near_limit(7));
+        assert!(limits.is_near_limit(8));
+        assert!(limits.is_near_limit(12));
+    }
+}
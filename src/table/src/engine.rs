@@ -16,9 +16,9 @@ use std::fmt::{self, Display};
 use std::sync::Arc;
 
 use common_procedure::BoxedProcedure;
-use store_api::storage::RegionId;
+use store_api::storage::{RegionId, RegionNumber};
 
-use crate::error::Result;
+use crate::error::{Result, UnsupportedSnafu};
 use crate::metadata::TableId;
 use crate::requests::{AlterTableRequest, CreateTableRequest, DropTableRequest, OpenTableRequest};
 use crate::TableRef;
@@ -104,6 +104,37 @@ pub trait TableEngine: Send + Sync {
     /// Drops the given table. Return true if the table is dropped, or false if the table doesn't exist.
     async fn drop_table(&self, ctx: &EngineContext, request: DropTableRequest) -> Result<bool>;
 
+    /// Adds region `region_number` to the table, which the scans of the table don't read until
+    /// it's published by [`Table::publish_region`](crate::Table::publish_region). Does
+    /// nothing if the table has the region.
+    async fn create_region(
+        &self,
+        ctx: &EngineContext,
+        table_ref: &TableReference,
+        region_number: RegionNumber,
+    ) -> Result<()> {
+        let _ = (ctx, table_ref, region_number);
+        UnsupportedSnafu {
+            operation: "CREATE_REGION",
+        }
+        .fail()
+    }
+
+    /// Drops region `region_number` of the table. Returns false if the table doesn't have the
+    /// region.
+    async fn drop_region(
+        &self,
+        ctx: &EngineContext,
+        table_ref: &TableReference,
+        region_number: RegionNumber,
+    ) -> Result<bool> {
+        let _ = (ctx, table_ref, region_number);
+        UnsupportedSnafu {
+            operation: "DROP_REGION",
+        }
+        .fail()
+    }
+
     /// Close the table.
     async fn close(&self) -> Result<()>;
 }
@@ -151,6 +182,12 @@ pub fn region_id(table_id: TableId, n: u32) -> RegionId {
     (u64::from(table_id) << 32) | u64::from(n)
 }
 
+/// Splits the region id generated by [region_id] into the table id and region number.
+#[inline]
+pub fn split_region_id(region_id: RegionId) -> (TableId, u32) {
+    ((region_id >> 32) as TableId, region_id as u32)
+}
+
 #[inline]
 pub fn table_dir(catalog_name: &str, schema_name: &str, table_id: TableId) -> String {
     format!("{catalog_name}/{schema_name}/{table_id}/")
@@ -170,4 +207,11 @@ mod tests {
 
         assert_eq!("greptime.public.test", table_ref.to_string());
     }
+
+    #[test]
+    fn test_split_region_id() {
+        let id = region_id(1024, 3);
+        assert_eq!((1024, 3), split_region_id(id));
+        assert_eq!((TableId::MAX, u32::MAX), split_region_id(u64::MAX));
+    }
 }
@@ -13,12 +13,14 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::time::Duration;
 
 use common_error::prelude::*;
 use common_recordbatch::error::Error as RecordBatchError;
 use datafusion::error::DataFusionError;
 use datatypes::arrow::error::ArrowError;
 use snafu::Location;
+use store_api::storage::{RegionId, SequenceNumber};
 
 use crate::metadata::TableId;
 
@@ -96,6 +98,30 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Not allowed to remove the ttl column {} of table {}",
+        column_name,
+        table_name
+    ))]
+    RemoveTtlColumn {
+        column_name: String,
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Invalid storage options of column {} in table {}, source: {}",
+        column_name,
+        table_name,
+        source
+    ))]
+    InvalidColumnOptions {
+        column_name: String,
+        table_name: String,
+        #[snafu(backtrace)]
+        source: datatypes::error::Error,
+    },
+
     #[snafu(display(
         "Failed to build column descriptor for table: {}, column: {}, source: {}",
         table_name,
@@ -136,6 +162,60 @@ pub enum Error {
         table_name: String,
         location: Location,
     },
+
+    #[snafu(display("Table {} not found", table_name))]
+    TableNotFound {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Table {} already exists", table_name))]
+    TableExists {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Missing value of column {} in table {}", column_name, table_name))]
+    MissingColumnValue {
+        column_name: String,
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to build table info of {}, source: {}", table_name, source))]
+    BuildTableInfo {
+        source: crate::metadata::TableInfoBuilderError,
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to build table meta of {}, source: {}", table_name, source))]
+    BuildTableMeta {
+        source: crate::metadata::TableMetaBuilderError,
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Injected failure of {}", operation))]
+    InjectedFailure {
+        operation: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Timeout waiting for region {} to apply sequence {} after {:?}, committed sequence: {}",
+        region_id,
+        sequence,
+        timeout,
+        committed_sequence
+    ))]
+    WaitSequenceTimeout {
+        region_id: RegionId,
+        sequence: SequenceNumber,
+        committed_sequence: SequenceNumber,
+        timeout: Duration,
+        location: Location,
+    },
 }
 
 impl ErrorExt for Error {
@@ -145,15 +225,23 @@ impl ErrorExt for Error {
             | Error::PollStream { .. }
             | Error::SchemaConversion { .. }
             | Error::TableProjection { .. } => StatusCode::EngineExecuteQuery,
-            Error::RemoveColumnInIndex { .. } | Error::BuildColumnDescriptor { .. } => {
-                StatusCode::InvalidArguments
-            }
+            Error::RemoveColumnInIndex { .. }
+            | Error::RemoveTtlColumn { .. }
+            | Error::InvalidColumnOptions { .. }
+            | Error::BuildColumnDescriptor { .. } => StatusCode::InvalidArguments,
             Error::TablesRecordBatch { .. } => StatusCode::Unexpected,
             Error::ColumnExists { .. } => StatusCode::TableColumnExists,
             Error::SchemaBuild { source, .. } => source.status_code(),
             Error::TableOperation { source } => source.status_code(),
             Error::ColumnNotExists { .. } => StatusCode::TableColumnNotFound,
-            Error::RegionSchemaMismatch { .. } => StatusCode::StorageUnavailable,
+            Error::TableNotFound { .. } => StatusCode::TableNotFound,
+            Error::TableExists { .. } => StatusCode::TableAlreadyExists,
+            Error::MissingColumnValue { .. } => StatusCode::InvalidArguments,
+            Error::BuildTableInfo { .. } | Error::BuildTableMeta { .. } => StatusCode::Unexpected,
+            Error::InjectedFailure { .. } => StatusCode::Unexpected,
+            Error::RegionSchemaMismatch { .. } | Error::WaitSequenceTimeout { .. } => {
+                StatusCode::StorageUnavailable
+            }
             Error::Unsupported { .. } => StatusCode::Unsupported,
             Error::ParseTableOption { .. }
             | Error::EngineNotFound { .. }
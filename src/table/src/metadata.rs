@@ -22,11 +22,14 @@ pub use datatypes::error::{Error as ConvertError, Result as ConvertResult};
 use datatypes::schema::{ColumnSchema, RawSchema, Schema, SchemaBuilder, SchemaRef};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
-use snafu::{ensure, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 use store_api::storage::{ColumnDescriptor, ColumnDescriptorBuilder, ColumnId};
 
 use crate::error::{self, Result};
-use crate::requests::{AddColumnRequest, AlterKind, TableOptions};
+use crate::requests::{
+    AddColumnRequest, AlterKind, TableOptions, DEFAULT_OPTION_VALUES, IMMUTABLE_TABLE_META_KEY,
+    REGIONS_KEY,
+};
 
 pub type TableId = u32;
 pub type TableVersion = u64;
@@ -79,6 +82,17 @@ pub enum TableType {
     Temporary,
 }
 
+impl TableType {
+    /// Returns the type in the form of the `table_type` column of `information_schema.tables`.
+    pub fn as_sql_str(&self) -> &'static str {
+        match self {
+            TableType::Base => "BASE TABLE",
+            TableType::View => "VIEW",
+            TableType::Temporary => "LOCAL TEMPORARY",
+        }
+    }
+}
+
 /// Identifier of the table.
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Default)]
 pub struct TableIdent {
@@ -89,6 +103,15 @@ pub struct TableIdent {
     pub version: TableVersion,
 }
 
+/// An option of a table, with its value in the canonical format of `SHOW CREATE TABLE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableOptionValue {
+    pub key: String,
+    pub value: String,
+    /// Whether the table takes the default value as it doesn't set the option.
+    pub inherited: bool,
+}
+
 /// The table metadata
 /// Note: if you add new fields to this struct, please ensure 'new_meta_builder' function works.
 /// TODO(dennis): find a better way to ensure 'new_meta_builder' works when adding new fields.
@@ -160,6 +183,44 @@ impl TableMeta {
         })
     }
 
+    /// Returns the options of the table, the ones set on the table (including the number of
+    /// regions) followed by the defaults of the ones it doesn't set.
+    pub fn option_values(&self) -> Vec<TableOptionValue> {
+        let mut values = Vec::new();
+        if !self.region_numbers.is_empty() {
+            values.push((
+                REGIONS_KEY.to_string(),
+                self.region_numbers.len().to_string(),
+            ));
+        }
+        values.extend(
+            self.options
+                .option_values()
+                .into_iter()
+                // The files of immutable tables are their state rather than an option.
+                .filter(|(key, _)| key != IMMUTABLE_TABLE_META_KEY),
+        );
+
+        let mut values = values
+            .into_iter()
+            .map(|(key, value)| TableOptionValue {
+                inherited: self.options.inherited_keys.contains(&key),
+                key,
+                value,
+            })
+            .collect::<Vec<_>>();
+        for (key, value) in DEFAULT_OPTION_VALUES {
+            if values.iter().all(|v| v.key != key) {
+                values.push(TableOptionValue {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    inherited: true,
+                });
+            }
+        }
+        values
+    }
+
     /// Returns the new [TableMetaBuilder] after applying given `alter_kind`.
     ///
     /// The returned builder would derive the next column id of this meta.
@@ -171,6 +232,16 @@ impl TableMeta {
         match alter_kind {
             AlterKind::AddColumns { columns } => self.add_columns(table_name, columns),
             AlterKind::DropColumns { names } => self.remove_columns(table_name, names),
+            AlterKind::SetColumnOptions {
+                name,
+                encoding,
+                compression,
+            } => self.set_column_options(
+                table_name,
+                name,
+                encoding.as_deref(),
+                compression.as_deref(),
+            ),
             // No need to rebuild table meta when renaming tables.
             AlterKind::RenameTable { .. } => {
                 let mut meta_builder = TableMetaBuilder::default();
@@ -199,6 +270,8 @@ impl TableMeta {
         )
         .is_nullable(new_column.is_nullable())
         .default_constraint(new_column.default_constraint().cloned())
+        .encoding(new_column.encoding().map(String::from))
+        .compression(new_column.compression().map(String::from))
         .build()
         .context(error::BuildColumnDescriptorSnafu {
             table_name,
@@ -313,6 +386,14 @@ impl TableMeta {
                         }
                     );
                 }
+
+                ensure!(
+                    self.options.ttl_column.as_ref() != Some(*column_name),
+                    error::RemoveTtlColumnSnafu {
+                        column_name: *column_name,
+                        table_name,
+                    }
+                );
             } else {
                 return error::ColumnNotExistsSnafu {
                     column_name: *column_name,
@@ -358,6 +439,62 @@ impl TableMeta {
 
         Ok(meta_builder)
     }
+
+    fn set_column_options(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        encoding: Option<&str>,
+        compression: Option<&str>,
+    ) -> Result<TableMetaBuilder> {
+        let table_schema = &self.schema;
+        let mut meta_builder = self.new_meta_builder();
+
+        let index = table_schema.column_index_by_name(column_name).context(
+            error::ColumnNotExistsSnafu {
+                column_name,
+                table_name,
+            },
+        )?;
+        let mut columns = table_schema.column_schemas().to_vec();
+        let mut column_schema = columns[index].clone();
+        if let Some(encoding) = encoding {
+            column_schema = column_schema.with_encoding(encoding).context(
+                error::InvalidColumnOptionsSnafu {
+                    column_name,
+                    table_name,
+                },
+            )?;
+        }
+        if let Some(compression) = compression {
+            column_schema = column_schema.with_compression(compression).context(
+                error::InvalidColumnOptionsSnafu {
+                    column_name,
+                    table_name,
+                },
+            )?;
+        }
+        columns[index] = column_schema;
+
+        let mut builder = SchemaBuilder::try_from_columns(columns)
+            .with_context(|_| error::SchemaBuildSnafu {
+                msg: format!("Failed to convert column schemas into schema for table {table_name}"),
+            })?
+            // Also bump the schema version.
+            .version(table_schema.version() + 1);
+        for (k, v) in table_schema.metadata().iter() {
+            builder = builder.add_metadata(k, v);
+        }
+        let new_schema = builder.build().with_context(|_| error::SchemaBuildSnafu {
+            msg: format!("Table {table_name} cannot set options of column {column_name}"),
+        })?;
+
+        meta_builder
+            .schema(Arc::new(new_schema))
+            .primary_key_indices(self.primary_key_indices.clone());
+
+        Ok(meta_builder)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Builder)]
@@ -780,6 +917,100 @@ mod tests {
         assert_eq!(StatusCode::InvalidArguments, err.status_code());
     }
 
+    #[test]
+    fn test_remove_ttl_column() {
+        let schema = Arc::new(new_test_schema());
+        let meta = TableMetaBuilder::default()
+            .schema(schema)
+            .primary_key_indices(vec![0])
+            .engine("engine")
+            .next_column_id(3)
+            .options(TableOptions {
+                ttl_column: Some("col2".to_string()),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+
+        let alter_kind = AlterKind::DropColumns {
+            names: vec![String::from("col2")],
+        };
+        let err = meta
+            .builder_with_alter_kind("my_table", &alter_kind)
+            .err()
+            .unwrap();
+        assert!(
+            matches!(err, error::Error::RemoveTtlColumn { .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_set_column_options() {
+        let schema = Arc::new(new_test_schema());
+        let meta = TableMetaBuilder::default()
+            .schema(schema)
+            .primary_key_indices(vec![0])
+            .engine("engine")
+            .next_column_id(3)
+            .build()
+            .unwrap();
+
+        let alter_kind = AlterKind::SetColumnOptions {
+            name: String::from("col2"),
+            encoding: Some(String::from("delta_binary_packed")),
+            compression: Some(String::from("zstd")),
+        };
+        let new_meta = meta
+            .builder_with_alter_kind("my_table", &alter_kind)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(meta.schema.version() + 1, new_meta.schema.version());
+        assert_eq!(meta.primary_key_indices, new_meta.primary_key_indices);
+        assert_eq!(meta.next_column_id, new_meta.next_column_id);
+        let column_schema = new_meta.schema.column_schema_by_name("col2").unwrap();
+        assert_eq!(Some("delta_binary_packed"), column_schema.encoding());
+        assert_eq!(Some("zstd"), column_schema.compression());
+
+        // Only the options given are changed.
+        let alter_kind = AlterKind::SetColumnOptions {
+            name: String::from("col2"),
+            encoding: Some(String::from("plain")),
+            compression: None,
+        };
+        let new_meta = new_meta
+            .builder_with_alter_kind("my_table", &alter_kind)
+            .unwrap()
+            .build()
+            .unwrap();
+        let column_schema = new_meta.schema.column_schema_by_name("col2").unwrap();
+        assert_eq!(Some("plain"), column_schema.encoding());
+        assert_eq!(Some("zstd"), column_schema.compression());
+
+        let alter_kind = AlterKind::SetColumnOptions {
+            name: String::from("col2"),
+            encoding: Some(String::from("byte_stream_split")),
+            compression: None,
+        };
+        let err = meta
+            .builder_with_alter_kind("my_table", &alter_kind)
+            .err()
+            .unwrap();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+
+        let alter_kind = AlterKind::SetColumnOptions {
+            name: String::from("unknown"),
+            encoding: None,
+            compression: Some(String::from("zstd")),
+        };
+        let err = meta
+            .builder_with_alter_kind("my_table", &alter_kind)
+            .err()
+            .unwrap();
+        assert_eq!(StatusCode::TableColumnNotFound, err.status_code());
+    }
+
     #[test]
     fn test_alloc_new_column() {
         let schema = Arc::new(new_test_schema());
@@ -798,4 +1029,58 @@ mod tests {
         assert_eq!(4, meta.next_column_id);
         assert_eq!(column_schema.name, desc.name);
     }
+
+    #[test]
+    fn test_option_values() {
+        let meta = TableMetaBuilder::default()
+            .schema(Arc::new(new_test_schema()))
+            .primary_key_indices(vec![0])
+            .engine("engine")
+            .next_column_id(3)
+            .region_numbers(vec![0, 1])
+            .options(TableOptions {
+                ttl: Some(std::time::Duration::from_secs(7 * 86400)),
+                extra_options: HashMap::from([
+                    ("b".to_string(), "B".to_string()),
+                    ("a".to_string(), "A".to_string()),
+                ]),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let values = meta
+            .option_values()
+            .into_iter()
+            .map(|v| (v.key, v.value, v.inherited))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("regions", "2", false),
+            ("ttl", "7days", false),
+            ("a", "A", false),
+            ("b", "B", false),
+            ("wal", "enabled", true),
+        ]
+        .map(|(k, v, inherited)| (k.to_string(), v.to_string(), inherited));
+        assert_eq!(expected.to_vec(), values);
+
+        let meta = TableMetaBuilder::default()
+            .schema(Arc::new(new_test_schema()))
+            .primary_key_indices(vec![0])
+            .engine("engine")
+            .next_column_id(3)
+            .options(TableOptions {
+                wal_disabled: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        assert_eq!(
+            vec![TableOptionValue {
+                key: "wal".to_string(),
+                value: "disabled".to_string(),
+                inherited: false,
+            }],
+            meta.option_values()
+        );
+    }
 }
@@ -18,7 +18,7 @@ use common_time::range::TimestampRange;
 use common_time::Timestamp;
 use datafusion::parquet::file::metadata::RowGroupMetaData;
 use datafusion::physical_optimizer::pruning::PruningPredicate;
-use datafusion_common::ToDFSchema;
+use datafusion_common::{Column, ScalarValue, ToDFSchema};
 use datafusion_expr::{Between, BinaryExpr, Operator};
 use datafusion_physical_expr::create_physical_expr;
 use datafusion_physical_expr::execution_props::ExecutionProps;
@@ -29,6 +29,26 @@ use crate::predicate::stats::RowGroupPruningStatistics;
 
 mod stats;
 
+/// Max number of the disjoint time ranges, or the values of an `IN` list, predicates prune by.
+/// The time ranges beyond it are merged into the range covering them, and the regions aren't
+/// pruned by longer `IN` lists.
+pub const MAX_PRUNING_DISJUNCTS: usize = 64;
+
+/// Returns the column and the values of `expr` if it's a non-negated `IN` list of literals
+/// over a column.
+pub fn in_list_literals(expr: &DfExpr) -> Option<(&Column, Vec<&ScalarValue>)> {
+    let DfExpr::InList { expr, list, negated: false } = expr else { return None; };
+    let DfExpr::Column(column) = expr.as_ref() else { return None; };
+    let values = list
+        .iter()
+        .map(|expr| match expr {
+            DfExpr::Literal(scalar) => Some(scalar),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((column, values))
+}
+
 #[derive(Default, Clone)]
 pub struct Predicate {
     exprs: Vec<Expr>,
@@ -43,6 +63,11 @@ impl Predicate {
         Self { exprs: vec![] }
     }
 
+    /// Returns the conjunctive exprs of the predicate.
+    pub fn exprs(&self) -> &[Expr] {
+        &self.exprs
+    }
+
     pub fn prune_row_groups(
         &self,
         schema: SchemaRef,
@@ -106,20 +131,29 @@ impl<'a> TimeRangePredicateBuilder<'a> {
         }
     }
 
+    /// Builds the range covering the time ranges of the filters.
     pub fn build(&self) -> TimestampRange {
-        let mut res = TimestampRange::min_to_max();
+        self.build_ranges()
+            .iter()
+            .fold(TimestampRange::empty(), |res, range| res.or(range))
+    }
+
+    /// Builds the sorted disjoint time ranges the filters match, at most
+    /// [MAX_PRUNING_DISJUNCTS] of them. Nothing matches if the ranges are empty.
+    pub fn build_ranges(&self) -> Vec<TimestampRange> {
+        let mut res = vec![TimestampRange::min_to_max()];
         for expr in self.filters {
-            let range = self
-                .extract_time_range_from_expr(expr.df_expr())
-                .unwrap_or_else(TimestampRange::min_to_max);
-            res = res.and(&range);
+            let ranges = self
+                .extract_time_ranges_from_expr(expr.df_expr())
+                .unwrap_or_else(|| vec![TimestampRange::min_to_max()]);
+            res = intersect_ranges(&res, &ranges);
         }
         res
     }
 
-    /// Extract time range filter from `WHERE`/`IN (...)`/`BETWEEN` clauses.
+    /// Extract time ranges from `WHERE`/`IN (...)`/`BETWEEN` clauses.
     /// Return None if no time range can be found in expr.
-    fn extract_time_range_from_expr(&self, expr: &DfExpr) -> Option<TimestampRange> {
+    fn extract_time_ranges_from_expr(&self, expr: &DfExpr) -> Option<Vec<TimestampRange>> {
         match expr {
             DfExpr::BinaryExpr(BinaryExpr { left, op, right }) => {
                 self.extract_from_binary_expr(left, op, right)
@@ -129,12 +163,10 @@ impl<'a> TimeRangePredicateBuilder<'a> {
                 negated,
                 low,
                 high,
-            }) => self.extract_from_between_expr(expr, negated, low, high),
-            DfExpr::InList {
-                expr,
-                list,
-                negated,
-            } => self.extract_from_in_list_expr(expr, *negated, list),
+            }) => self
+                .extract_from_between_expr(expr, negated, low, high)
+                .map(|range| normalize_ranges(vec![range])),
+            DfExpr::InList { .. } => self.extract_from_in_list_expr(expr),
             _ => None,
         }
     }
@@ -144,8 +176,8 @@ impl<'a> TimeRangePredicateBuilder<'a> {
         left: &DfExpr,
         op: &Operator,
         right: &DfExpr,
-    ) -> Option<TimestampRange> {
-        match op {
+    ) -> Option<Vec<TimestampRange>> {
+        let range = match op {
             Operator::Eq => self
                 .get_timestamp_filter(left, right)
                 .map(TimestampRange::single),
@@ -165,17 +197,18 @@ impl<'a> TimeRangePredicateBuilder<'a> {
                 // instead of return none when failed to extract time range from left/right, we unwrap the none into
                 // `TimestampRange::min_to_max`.
                 let left = self
-                    .extract_time_range_from_expr(left)
-                    .unwrap_or_else(TimestampRange::min_to_max);
+                    .extract_time_ranges_from_expr(left)
+                    .unwrap_or_else(|| vec![TimestampRange::min_to_max()]);
                 let right = self
-                    .extract_time_range_from_expr(right)
-                    .unwrap_or_else(TimestampRange::min_to_max);
-                Some(left.and(&right))
+                    .extract_time_ranges_from_expr(right)
+                    .unwrap_or_else(|| vec![TimestampRange::min_to_max()]);
+                return Some(intersect_ranges(&left, &right));
             }
             Operator::Or => {
-                let left = self.extract_time_range_from_expr(left)?;
-                let right = self.extract_time_range_from_expr(right)?;
-                Some(left.or(&right))
+                let mut left = self.extract_time_ranges_from_expr(left)?;
+                let right = self.extract_time_ranges_from_expr(right)?;
+                left.extend(right);
+                return Some(normalize_ranges(left));
             }
             Operator::NotEq
             | Operator::Plus
@@ -195,7 +228,8 @@ impl<'a> TimeRangePredicateBuilder<'a> {
             | Operator::BitwiseShiftRight
             | Operator::BitwiseShiftLeft
             | Operator::StringConcat => None,
-        }
+        };
+        range.map(|range| normalize_ranges(vec![range]))
     }
 
     fn get_timestamp_filter(&self, left: &DfExpr, right: &DfExpr) -> Option<Timestamp> {
@@ -238,37 +272,66 @@ impl<'a> TimeRangePredicateBuilder<'a> {
         }
     }
 
-    /// Extract time range filter from `IN (...)` expr.
-    fn extract_from_in_list_expr(
-        &self,
-        expr: &DfExpr,
-        negated: bool,
-        list: &[DfExpr],
-    ) -> Option<TimestampRange> {
-        if negated {
-            return None;
-        }
-        let DfExpr::Column(col) = expr else { return None; };
+    /// Extract time ranges from `IN (...)` expr.
+    fn extract_from_in_list_expr(&self, expr: &DfExpr) -> Option<Vec<TimestampRange>> {
+        let (col, list) = in_list_literals(expr)?;
         if col.name != self.ts_col_name {
             return None;
         }
 
-        if list.is_empty() {
-            return Some(TimestampRange::empty());
-        }
-        let mut init_range = TimestampRange::empty();
-        for expr in list {
-            if let DfExpr::Literal(scalar) = expr {
-                if let Some(timestamp) = scalar_value_to_timestamp(scalar) {
-                    init_range = init_range.or(&TimestampRange::single(timestamp))
-                } else {
-                    // TODO(hl): maybe we should raise an error here since cannot parse
-                    // timestamp value from in list expr
-                    return None;
-                }
+        let mut ranges = Vec::with_capacity(list.len());
+        for scalar in list {
+            if let Some(timestamp) = scalar_value_to_timestamp(scalar) {
+                ranges.push(TimestampRange::single(timestamp));
+            } else {
+                // TODO(hl): maybe we should raise an error here since cannot parse
+                // timestamp value from in list expr
+                return None;
             }
         }
-        Some(init_range)
+        Some(normalize_ranges(ranges))
+    }
+}
+
+/// Returns the sorted disjoint ranges of the intersections of `left` and `right`.
+fn intersect_ranges(left: &[TimestampRange], right: &[TimestampRange]) -> Vec<TimestampRange> {
+    let ranges = left
+        .iter()
+        .flat_map(|l| right.iter().map(|r| l.and(r)))
+        .collect();
+    normalize_ranges(ranges)
+}
+
+/// Sorts the non-empty `ranges` and merges the overlapping or adjacent ones. Ranges more than
+/// [MAX_PRUNING_DISJUNCTS] are merged into the range covering them.
+fn normalize_ranges(mut ranges: Vec<TimestampRange>) -> Vec<TimestampRange> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_by(|a, b| a.start().cmp(b.start()));
+
+    let mut merged: Vec<TimestampRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            // Ranges are sorted by their start, so they overlap or are adjacent if the range
+            // starts before the end of the last one.
+            Some(last) if overlaps_or_adjacent(last, &range) => *last = last.or(&range),
+            _ => merged.push(range),
+        }
+    }
+
+    if merged.len() > MAX_PRUNING_DISJUNCTS {
+        let covering = merged
+            .iter()
+            .fold(TimestampRange::empty(), |res, range| res.or(range));
+        vec![covering]
+    } else {
+        merged
+    }
+}
+
+fn overlaps_or_adjacent(last: &TimestampRange, range: &TimestampRange) -> bool {
+    match (last.end(), range.start()) {
+        (Some(end), Some(start)) => start <= end,
+        _ => true,
     }
 }
 
@@ -14,14 +14,18 @@
 
 //! Table and TableEngine requests
 
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
 use std::str::FromStr;
 use std::time::Duration;
 
 use common_base::readable_size::ReadableSize;
-use datatypes::prelude::VectorRef;
+pub use common_base::write_mode::WriteMode;
+use common_time::range::TimestampRange;
+use datatypes::prelude::{Value, VectorRef};
 use datatypes::schema::{ColumnSchema, RawSchema};
 use serde::{Deserialize, Serialize};
+use snafu::ensure;
 use store_api::storage::RegionNumber;
 
 use crate::engine::TableReference;
@@ -33,11 +37,17 @@ pub const IMMUTABLE_TABLE_META_KEY: &str = "IMMUTABLE_TABLE_META";
 pub const IMMUTABLE_TABLE_LOCATION_KEY: &str = "LOCATION";
 pub const IMMUTABLE_TABLE_PATTERN_KEY: &str = "PATTERN";
 pub const IMMUTABLE_TABLE_FORMAT_KEY: &str = "FORMAT";
+/// How to handle the files of an immutable table that disappear: `error` (default) or `skip`.
+pub const IMMUTABLE_TABLE_ON_MISSING_FILE_KEY: &str = "ON_MISSING_FILE";
+/// Whether to check the files of an immutable table still exist on each scan.
+pub const IMMUTABLE_TABLE_REFRESH_FILES_KEY: &str = "REFRESH_FILES";
 
 #[derive(Debug, Clone)]
 pub struct CreateDatabaseRequest {
     pub db_name: String,
     pub create_if_not_exists: bool,
+    /// Default options of the tables created in the database.
+    pub table_options: TableOptions,
 }
 
 /// Create table request
@@ -78,12 +88,89 @@ pub struct TableOptions {
     pub extra_options: HashMap<String, String>,
     /// Time window for compaction
     pub compaction_time_window: Option<i64>,
+    /// Whether writes to the table skip the WAL. Unflushed data of such tables is lost on
+    /// crash, so it only suits tables that don't need durability, e.g. benchmark tables.
+    pub wal_disabled: bool,
+    /// Name of the timestamp column holding the expiry time of each row. Rows are invisible
+    /// to queries once expired, and dropped by compaction. Rows whose expiry time is null
+    /// never expire.
+    pub ttl_column: Option<String>,
+    /// Keys of the options the table takes from the default options of its schema rather than
+    /// sets itself, see [TableOptions::inherit].
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub inherited_keys: BTreeSet<String>,
 }
 
 pub const WRITE_BUFFER_SIZE_KEY: &str = "write_buffer_size";
 pub const TTL_KEY: &str = "ttl";
 pub const REGIONS_KEY: &str = "regions";
 pub const COMPACTION_TIME_WINDOW_KEY: &str = "compaction_time_window";
+/// Key of the WAL option, whose value is `enabled` (default) or `disabled`.
+pub const WAL_KEY: &str = "wal";
+pub const WAL_ENABLED: &str = "enabled";
+pub const WAL_DISABLED: &str = "disabled";
+pub const TTL_COLUMN_KEY: &str = "ttl_column";
+/// Values of the options the tables take if neither they nor their schemas set them, in the
+/// canonical format of [TableOptions::option_values].
+pub const DEFAULT_OPTION_VALUES: [(&str, &str); 1] = [(WAL_KEY, WAL_ENABLED)];
+/// Prefix of the table options carrying the storage options of columns in SST files, whose
+/// keys are `column.<column name>.encoding` and `column.<column name>.compression`. These
+/// options are moved to the column schemas on table creation.
+pub const COLUMN_OPTION_KEY_PREFIX: &str = "column.";
+const COLUMN_ENCODING: &str = "encoding";
+const COLUMN_COMPRESSION: &str = "compression";
+
+fn column_option_key(column_name: &str, option: &str) -> String {
+    format!("{COLUMN_OPTION_KEY_PREFIX}{column_name}.{option}")
+}
+
+/// Returns the storage options of the columns as table options.
+pub fn column_storage_options(column_schemas: &[ColumnSchema]) -> HashMap<String, String> {
+    let mut options = HashMap::new();
+    for column_schema in column_schemas {
+        if let Some(encoding) = column_schema.encoding() {
+            let _ = options.insert(
+                column_option_key(&column_schema.name, COLUMN_ENCODING),
+                encoding.to_string(),
+            );
+        }
+        if let Some(compression) = column_schema.compression() {
+            let _ = options.insert(
+                column_option_key(&column_schema.name, COLUMN_COMPRESSION),
+                compression.to_string(),
+            );
+        }
+    }
+    options
+}
+
+/// Sets the storage options of the column in the table `options` to the column schema.
+pub fn with_column_storage_options(
+    mut column_schema: ColumnSchema,
+    options: &HashMap<String, String>,
+) -> Result<ColumnSchema, error::Error> {
+    let key = column_option_key(&column_schema.name, COLUMN_ENCODING);
+    if let Some(encoding) = options.get(&key) {
+        column_schema = column_schema.with_encoding(encoding).map_err(|_| {
+            ParseTableOptionSnafu {
+                key,
+                value: encoding,
+            }
+            .build()
+        })?;
+    }
+    let key = column_option_key(&column_schema.name, COLUMN_COMPRESSION);
+    if let Some(compression) = options.get(&key) {
+        column_schema = column_schema.with_compression(compression).map_err(|_| {
+            ParseTableOptionSnafu {
+                key,
+                value: compression,
+            }
+            .build()
+        })?;
+    }
+    Ok(column_schema)
+}
 
 impl TryFrom<&HashMap<String, String>> for TableOptions {
     type Error = error::Error;
@@ -126,11 +213,37 @@ impl TryFrom<&HashMap<String, String>> for TableOptions {
                 }
             };
         }
+        if let Some(wal) = value.get(WAL_KEY) {
+            options.wal_disabled = match wal.to_lowercase().as_str() {
+                WAL_ENABLED => false,
+                WAL_DISABLED => true,
+                _ => {
+                    return ParseTableOptionSnafu {
+                        key: WAL_KEY,
+                        value: wal,
+                    }
+                    .fail()
+                }
+            };
+        }
+        if let Some(ttl_column) = value.get(TTL_COLUMN_KEY) {
+            ensure!(
+                !ttl_column.is_empty(),
+                ParseTableOptionSnafu {
+                    key: TTL_COLUMN_KEY,
+                    value: ttl_column,
+                }
+            );
+            options.ttl_column = Some(ttl_column.clone());
+        }
         options.extra_options = HashMap::from_iter(value.iter().filter_map(|(k, v)| {
             if k != WRITE_BUFFER_SIZE_KEY
                 && k != REGIONS_KEY
                 && k != TTL_KEY
                 && k != COMPACTION_TIME_WINDOW_KEY
+                && k != WAL_KEY
+                && k != TTL_COLUMN_KEY
+                && !k.starts_with(COLUMN_OPTION_KEY_PREFIX)
             {
                 Some((k.clone(), v.clone()))
             } else {
@@ -141,31 +254,77 @@ impl TryFrom<&HashMap<String, String>> for TableOptions {
     }
 }
 
-impl From<&TableOptions> for HashMap<String, String> {
-    fn from(opts: &TableOptions) -> Self {
-        let mut res = HashMap::with_capacity(2 + opts.extra_options.len());
-        if let Some(write_buffer_size) = opts.write_buffer_size {
-            res.insert(
+impl TableOptions {
+    /// Parses the default options of the tables of a schema. The WAL option is only set per
+    /// table, as a table can't tell setting `wal = 'enabled'` itself from taking the default.
+    pub fn try_from_schema_options(value: &HashMap<String, String>) -> Result<Self, error::Error> {
+        if let Some(wal) = value.get(WAL_KEY) {
+            return ParseTableOptionSnafu {
+                key: WAL_KEY,
+                value: wal,
+            }
+            .fail();
+        }
+        TableOptions::try_from(value)
+    }
+
+    /// Takes the options of `schema_options`, the default options of the schema of the table,
+    /// the table doesn't set itself, and records them as inherited.
+    pub fn inherit(&mut self, schema_options: &TableOptions) -> Result<(), error::Error> {
+        let mut values = self.option_values().into_iter().collect::<HashMap<_, _>>();
+        let mut inherited_keys = std::mem::take(&mut self.inherited_keys);
+        for (key, value) in schema_options.option_values() {
+            if let Entry::Vacant(entry) = values.entry(key) {
+                let _ = inherited_keys.insert(entry.key().clone());
+                let _ = entry.insert(value);
+            }
+        }
+        *self = TableOptions::try_from(&values)?;
+        self.inherited_keys = inherited_keys;
+        Ok(())
+    }
+
+    /// Returns the options set on the table as `(key, value)` pairs, with the values in the
+    /// canonical format, which is also the format of the options in `SHOW CREATE TABLE`. The
+    /// extra options come last, in ascending order of their keys.
+    pub fn option_values(&self) -> Vec<(String, String)> {
+        let mut values = Vec::with_capacity(5 + self.extra_options.len());
+        if let Some(write_buffer_size) = self.write_buffer_size {
+            values.push((
                 WRITE_BUFFER_SIZE_KEY.to_string(),
                 write_buffer_size.to_string(),
-            );
+            ));
         }
-        if let Some(ttl) = opts.ttl {
+        if let Some(ttl) = self.ttl {
             let ttl_str = humantime::format_duration(ttl).to_string();
-            res.insert(TTL_KEY.to_string(), ttl_str);
+            values.push((TTL_KEY.to_string(), ttl_str));
         }
-        if let Some(compaction_time_window) = opts.compaction_time_window {
-            res.insert(
+        if let Some(compaction_time_window) = self.compaction_time_window {
+            values.push((
                 COMPACTION_TIME_WINDOW_KEY.to_string(),
                 compaction_time_window.to_string(),
-            );
+            ));
         }
-        res.extend(
-            opts.extra_options
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone())),
-        );
-        res
+        if self.wal_disabled {
+            values.push((WAL_KEY.to_string(), WAL_DISABLED.to_string()));
+        }
+        if let Some(ttl_column) = &self.ttl_column {
+            values.push((TTL_COLUMN_KEY.to_string(), ttl_column.clone()));
+        }
+        let mut extra_options = self
+            .extra_options
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        extra_options.sort_unstable();
+        values.extend(extra_options);
+        values
+    }
+}
+
+impl From<&TableOptions> for HashMap<String, String> {
+    fn from(opts: &TableOptions) -> Self {
+        opts.option_values().into_iter().collect()
     }
 }
 
@@ -210,9 +369,22 @@ pub struct AddColumnRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AlterKind {
-    AddColumns { columns: Vec<AddColumnRequest> },
-    DropColumns { names: Vec<String> },
-    RenameTable { new_table_name: String },
+    AddColumns {
+        columns: Vec<AddColumnRequest>,
+    },
+    DropColumns {
+        names: Vec<String>,
+    },
+    RenameTable {
+        new_table_name: String,
+    },
+    /// Sets the storage options of a column, which only apply to the SSTs written after, the
+    /// absent options are kept.
+    SetColumnOptions {
+        name: String,
+        encoding: Option<String>,
+        compression: Option<String>,
+    },
 }
 
 /// Drop table request
@@ -233,6 +405,17 @@ impl DropTableRequest {
     }
 }
 
+/// Restore table request, restores the last dropped table named `table_name` from the
+/// recycle bin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreTableRequest {
+    pub catalog_name: String,
+    pub schema_name: String,
+    pub table_name: String,
+    /// Name to restore the table as, the table keeps its name if it's `None`.
+    pub new_table_name: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct InsertRequest {
     pub catalog_name: String,
@@ -240,6 +423,9 @@ pub struct InsertRequest {
     pub table_name: String,
     pub columns_values: HashMap<String, VectorRef>,
     pub region_number: RegionNumber,
+    /// How to write the rows whose primary key and timestamp already exist, engines not
+    /// supporting the mode reject the request.
+    pub write_mode: WriteMode,
 }
 
 /// Delete (by primary key) request
@@ -251,6 +437,16 @@ pub struct DeleteRequest {
     pub key_column_values: HashMap<String, VectorRef>,
 }
 
+/// Delete (by key and time range) request, deleting the rows without reading them.
+#[derive(Debug)]
+pub struct DeleteRangeRequest {
+    /// Values of the primary key columns of the rows to delete, the rows of all keys in the
+    /// time range are deleted if empty.
+    pub key_column_values: HashMap<String, Value>,
+    /// Time range of the rows to delete.
+    pub time_range: TimestampRange,
+}
+
 #[derive(Debug)]
 pub enum CopyDirection {
     Export,
@@ -280,6 +476,58 @@ pub struct FlushTableRequest {
     pub wait: Option<bool>,
 }
 
+/// Request to operate a region of a table on the datanode serving it, for changing the
+/// partitions of the table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionRequest {
+    pub catalog_name: String,
+    pub schema_name: String,
+    pub table_name: String,
+    /// Id of the table, the request is for another table if the table of the name has another
+    /// id.
+    pub table_id: TableId,
+    pub region_number: RegionNumber,
+    pub kind: RegionRequestKind,
+}
+
+impl RegionRequest {
+    pub fn table_ref(&self) -> TableReference {
+        TableReference {
+            catalog: &self.catalog_name,
+            schema: &self.schema_name,
+            table: &self.table_name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RegionRequestKind {
+    /// Creates the region, which the scans of the table don't read until it's published.
+    Create,
+    /// Copies the rows of the region in the key range to the region `target_region` of the
+    /// same table on the datanode at `target_addr`.
+    ///
+    /// The bounds are the JSON encoded `PartitionBound`s of the `partition_columns`, the same
+    /// as the `value_list` of a partition in the table route.
+    CopyTo {
+        target_addr: String,
+        target_region: RegionNumber,
+        partition_columns: Vec<String>,
+        /// Inclusive lower bound, `None` for no lower bound.
+        lower: Option<Vec<String>>,
+        /// Exclusive upper bound.
+        upper: Vec<String>,
+    },
+    /// Returns the watermark of the region, which changes whenever the region is written.
+    Watermark,
+    /// Pauses or resumes the writes to the region.
+    SetWritable { writable: bool },
+    /// Makes the scans of the table read the region.
+    Publish,
+    /// Drops the region, does nothing if the region doesn't exist.
+    Drop,
+}
+
 #[macro_export]
 macro_rules! meter_insert_request {
     ($req: expr) => {
@@ -295,6 +543,8 @@ macro_rules! meter_insert_request {
 
 #[cfg(test)]
 mod tests {
+    use datatypes::prelude::ConcreteDataType;
+
     use super::*;
 
     #[test]
@@ -304,6 +554,9 @@ mod tests {
             ttl: Some(Duration::from_secs(1000)),
             extra_options: HashMap::new(),
             compaction_time_window: Some(1677652502),
+            wal_disabled: true,
+            ttl_column: Some("expires_at".to_string()),
+            inherited_keys: BTreeSet::new(),
         };
         let serialized = serde_json::to_string(&options).unwrap();
         let deserialized: TableOptions = serde_json::from_str(&serialized).unwrap();
@@ -317,6 +570,9 @@ mod tests {
             ttl: Some(Duration::from_secs(1000)),
             extra_options: HashMap::new(),
             compaction_time_window: Some(1677652502),
+            wal_disabled: false,
+            ttl_column: None,
+            inherited_keys: BTreeSet::new(),
         };
         let serialized_map = HashMap::from(&options);
         let serialized = TableOptions::try_from(&serialized_map).unwrap();
@@ -327,6 +583,9 @@ mod tests {
             ttl: None,
             extra_options: HashMap::new(),
             compaction_time_window: None,
+            wal_disabled: true,
+            ttl_column: Some("expires_at".to_string()),
+            inherited_keys: BTreeSet::new(),
         };
         let serialized_map = HashMap::from(&options);
         let serialized = TableOptions::try_from(&serialized_map).unwrap();
@@ -337,9 +596,91 @@ mod tests {
             ttl: Some(Duration::from_secs(1000)),
             extra_options: HashMap::from([("a".to_string(), "A".to_string())]),
             compaction_time_window: Some(1677652502),
+            wal_disabled: false,
+            ttl_column: None,
+            inherited_keys: BTreeSet::new(),
         };
         let serialized_map = HashMap::from(&options);
         let serialized = TableOptions::try_from(&serialized_map).unwrap();
         assert_eq!(options, serialized);
     }
+
+    #[test]
+    fn test_parse_wal_option() {
+        let parse = |wal: &str| {
+            let map = HashMap::from([(WAL_KEY.to_string(), wal.to_string())]);
+            TableOptions::try_from(&map)
+        };
+        assert!(parse("disabled").unwrap().wal_disabled);
+        assert!(parse("DISABLED").unwrap().wal_disabled);
+        assert!(!parse("enabled").unwrap().wal_disabled);
+        assert!(parse("off").is_err());
+
+        // Tables created before the option are deserialized as WAL enabled.
+        let options: TableOptions = serde_json::from_str(r#"{"ttl":null}"#).unwrap();
+        assert!(!options.wal_disabled);
+    }
+
+    #[test]
+    fn test_inherit_schema_options() {
+        let schema_options = TableOptions::try_from_schema_options(&HashMap::from([
+            (TTL_KEY.to_string(), "7days".to_string()),
+            ("a".to_string(), "schema".to_string()),
+        ]))
+        .unwrap();
+        let mut options = TableOptions {
+            extra_options: HashMap::from([("a".to_string(), "table".to_string())]),
+            wal_disabled: true,
+            ..Default::default()
+        };
+        options.inherit(&schema_options).unwrap();
+
+        assert_eq!(Some(Duration::from_secs(7 * 24 * 3600)), options.ttl);
+        assert_eq!("table", options.extra_options["a"]);
+        assert!(options.wal_disabled);
+        assert_eq!(
+            BTreeSet::from([TTL_KEY.to_string()]),
+            options.inherited_keys
+        );
+
+        // The WAL option is only set per table.
+        let wal = HashMap::from([(WAL_KEY.to_string(), WAL_DISABLED.to_string())]);
+        assert!(TableOptions::try_from_schema_options(&wal).is_err());
+    }
+
+    #[test]
+    fn test_column_storage_options() {
+        let column_schemas = vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true)
+                .with_encoding("dictionary")
+                .unwrap(),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true)
+                .with_compression("zstd")
+                .unwrap(),
+        ];
+        let mut map = column_storage_options(&column_schemas);
+        assert_eq!("dictionary", map.get("column.host.encoding").unwrap());
+        assert_eq!("zstd", map.get("column.cpu.compression").unwrap());
+
+        // Column options are not table options.
+        let _ = map.insert(TTL_KEY.to_string(), "1h".to_string());
+        let options = TableOptions::try_from(&map).unwrap();
+        assert!(options.extra_options.is_empty());
+
+        for column_schema in column_schemas {
+            let plain =
+                ColumnSchema::new(&column_schema.name, column_schema.data_type.clone(), true);
+            assert_eq!(
+                column_schema,
+                with_column_storage_options(plain, &map).unwrap()
+            );
+        }
+
+        let _ = map.insert(
+            "column.cpu.encoding".to_string(),
+            "dictionary_x".to_string(),
+        );
+        let cpu = ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true);
+        assert!(with_column_storage_options(cpu, &map).is_err());
+    }
 }
@@ -13,21 +13,27 @@
 // limitations under the License.
 
 pub mod adapter;
+pub mod as_of;
 pub mod numbers;
+pub mod purge;
 pub mod scan;
 
 use std::any::Any;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use common_base::commit_token::CommitToken;
 use common_query::logical_plan::Expr;
 use common_query::physical_plan::PhysicalPlanRef;
+use common_recordbatch::SendableRecordBatchStream;
+use common_time::Timestamp;
 use datatypes::schema::SchemaRef;
-use store_api::storage::RegionNumber;
+use store_api::storage::{OrphanFile, OrphanGcRequest, RegionNumber, SequenceNumber};
 
 use crate::error::{Result, UnsupportedSnafu};
 use crate::metadata::{FilterPushDownType, TableId, TableInfoRef, TableType};
-use crate::requests::{AlterTableRequest, DeleteRequest, InsertRequest};
+use crate::requests::{AlterTableRequest, DeleteRangeRequest, DeleteRequest, InsertRequest};
 
 pub type AlterContext = anymap::Map<dyn Any + Send + Sync>;
 
@@ -41,6 +47,12 @@ pub trait Table: Send + Sync {
     /// Get a reference to the schema for this table
     fn schema(&self) -> SchemaRef;
 
+    /// Get the schema of the batches scanned from this table, which may encode some columns
+    /// differently from [Table::schema], e.g. the tags as dictionaries.
+    fn scan_schema(&self) -> SchemaRef {
+        self.schema()
+    }
+
     /// Get a reference to the table info.
     fn table_info(&self) -> TableInfoRef;
 
@@ -71,12 +83,82 @@ pub trait Table: Send + Sync {
         limit: Option<usize>,
     ) -> Result<PhysicalPlanRef>;
 
+    /// Whether the table keeps the versions of its rows, so [Table::scan_as_of] reads its
+    /// snapshots in the past. The tables without versions, like the system tables, are
+    /// always read as is.
+    fn supports_time_travel(&self) -> bool {
+        false
+    }
+
+    /// Scan the snapshot of the table as of the wall-clock time `as_of` in millis, which is
+    /// only readable within the time-travel retention of the table.
+    async fn scan_as_of(
+        &self,
+        _projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+        _as_of: i64,
+    ) -> Result<PhysicalPlanRef> {
+        UnsupportedSnafu {
+            operation: "TIME TRAVEL",
+        }
+        .fail()?
+    }
+
     /// Tests whether the table provider can make use of any or all filter expressions
     /// to optimise data retrieval.
     fn supports_filters_pushdown(&self, filters: &[&Expr]) -> Result<Vec<FilterPushDownType>> {
         Ok(vec![FilterPushDownType::Unsupported; filters.len()])
     }
 
+    /// Tests whether the table can evaluate the partial stage of a two-phase aggregation
+    /// next to where its data are stored, see [`Table::scan_partial_aggregate`].
+    fn supports_aggregate_pushdown(&self) -> bool {
+        false
+    }
+
+    /// Tests whether [`Table::scan_partial_aggregate`] can evaluate the partial stage of the
+    /// user-defined aggregate functions supporting distributed aggregation, which is planned
+    /// into an aggregate expression only known to the query engine planning it.
+    fn supports_udaf_pushdown(&self) -> bool {
+        false
+    }
+
+    /// Scan the table and evaluate the partial aggregation on the scanned rows. The output
+    /// contains partial aggregate states, which are to be merged by a final aggregation.
+    async fn scan_partial_aggregate(
+        &self,
+        _projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _aggregate: &PartialAggregate,
+    ) -> Result<PhysicalPlanRef> {
+        UnsupportedSnafu {
+            operation: "PARTIAL_AGGREGATE",
+        }
+        .fail()?
+    }
+
+    /// Tests whether the table can keep the top-k rows of the partial stage of a two-phase
+    /// top-k next to where its data are stored, see [`Table::scan_topk`].
+    fn supports_topk_pushdown(&self) -> bool {
+        false
+    }
+
+    /// Scan the table and keep the top-k rows of the scanned rows of each place the data are
+    /// stored. The output contains up to k rows of each place, which are to be merged into the
+    /// top-k rows of the whole table.
+    async fn scan_topk(
+        &self,
+        _projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _topk: &PartialTopK,
+    ) -> Result<PhysicalPlanRef> {
+        UnsupportedSnafu {
+            operation: "PARTIAL_TOPK",
+        }
+        .fail()?
+    }
+
     /// Alter table.
     async fn alter(&self, _context: AlterContext, _request: &AlterTableRequest) -> Result<()> {
         UnsupportedSnafu {
@@ -95,6 +177,21 @@ pub trait Table: Send + Sync {
         .fail()?
     }
 
+    /// Whether the table deletes rows by [Table::delete_range].
+    fn supports_delete_range(&self) -> bool {
+        false
+    }
+
+    /// Delete the rows of some keys in a time range without reading them.
+    ///
+    /// The number of deleted rows is unknown.
+    async fn delete_range(&self, _request: DeleteRangeRequest) -> Result<()> {
+        UnsupportedSnafu {
+            operation: "DELETE RANGE",
+        }
+        .fail()?
+    }
+
     /// Flush table.
     ///
     /// Options:
@@ -117,6 +214,112 @@ pub trait Table: Send + Sync {
         }
         .fail()?
     }
+
+    /// Returns bytes of the manifest of the table in the object store, excluding the
+    /// manifests of its regions counted by [`Table::region_stats`].
+    fn manifest_bytes(&self) -> u64 {
+        0
+    }
+
+    /// Returns the exact number of rows in the table if it can tell without scanning them,
+    /// `None` if it can't, e.g. some rows may be deleted or overwritten by others.
+    fn exact_row_count(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the commit token of the writes accepted by the table so far, see
+    /// [`CommitToken`].
+    fn commit_token(&self) -> Result<CommitToken> {
+        Ok(CommitToken::default())
+    }
+
+    /// Returns a view of the table for a session, whose reads wait for the writes in
+    /// `commit_token` and whose [`Table::commit_token`] records the writes made through the
+    /// view, for the tables whose regions are on other nodes.
+    ///
+    /// Returns `None` for the tables with local regions, which are waited for by
+    /// [`Table::wait_for_sequence`] instead.
+    fn with_commit_token(&self, commit_token: CommitToken) -> Option<TableRef> {
+        let _ = commit_token;
+        None
+    }
+
+    /// Waits until the region has applied the writes up to `sequence`, or fails with a
+    /// retryable error after `timeout`.
+    ///
+    /// Tables without regions always return immediately.
+    async fn wait_for_sequence(
+        &self,
+        region_number: RegionNumber,
+        sequence: SequenceNumber,
+        timeout: Duration,
+    ) -> Result<()> {
+        let _ = (region_number, sequence, timeout);
+        Ok(())
+    }
+
+    /// Finds the orphans in the storage of the regions of the table, the objects no region
+    /// references, and deletes them if the request asks to.
+    async fn gc_orphans(&self, request: &OrphanGcRequest) -> Result<Vec<OrphanFile>> {
+        let _ = request;
+        UnsupportedSnafu {
+            operation: "GC_ORPHANS",
+        }
+        .fail()?
+    }
+
+    /// Purges the rows whose time index is before `expire_before`, which are expired by the
+    /// `ttl` option of the table.
+    ///
+    /// Called periodically for the tables of engines relying on the catalog to expire their
+    /// rows. Deletes the expired rows found by a range scan by default, see
+    /// [delete_expired_rows](purge::delete_expired_rows), engines purging the expired rows by
+    /// themselves return an unsupported error instead.
+    async fn purge_expired(&self, expire_before: Timestamp) -> Result<()> {
+        let _ = purge::delete_expired_rows(self, expire_before).await?;
+        Ok(())
+    }
+
+    /// Scans all the rows of region `region_number`, including a region not published yet.
+    /// Returns the rows and the sequence of the region they are read up to at least.
+    async fn scan_region(
+        &self,
+        region_number: RegionNumber,
+    ) -> Result<(SendableRecordBatchStream, SequenceNumber)> {
+        let _ = region_number;
+        UnsupportedSnafu {
+            operation: "SCAN_REGION",
+        }
+        .fail()?
+    }
+
+    /// Returns the sequence of the last write to region `region_number` visible to reads.
+    fn committed_sequence(&self, region_number: RegionNumber) -> Result<SequenceNumber> {
+        let _ = region_number;
+        UnsupportedSnafu {
+            operation: "COMMITTED_SEQUENCE",
+        }
+        .fail()?
+    }
+
+    /// Pauses or resumes the writes to region `region_number`.
+    fn set_region_writable(&self, region_number: RegionNumber, writable: bool) -> Result<()> {
+        let _ = (region_number, writable);
+        UnsupportedSnafu {
+            operation: "SET_REGION_WRITABLE",
+        }
+        .fail()?
+    }
+
+    /// Makes the scans of the table read region `region_number`, which is added by
+    /// [`TableEngine::create_region`](crate::engine::TableEngine::create_region).
+    async fn publish_region(&self, region_number: RegionNumber) -> Result<()> {
+        let _ = region_number;
+        UnsupportedSnafu {
+            operation: "PUBLISH_REGION",
+        }
+        .fail()?
+    }
 }
 
 pub type TableRef = Arc<dyn Table>;
@@ -128,8 +331,56 @@ pub trait TableIdProvider {
 
 pub type TableIdProviderRef = Arc<dyn TableIdProvider + Send + Sync>;
 
+/// The partial stage of a two-phase aggregation that is pushed down to table scan.
+#[derive(Debug, Clone)]
+pub struct PartialAggregate {
+    /// Group by expressions, evaluated against the (projected and filtered) scanned rows.
+    pub group_expr: Vec<Expr>,
+    /// Aggregate expressions, each of which produces one partial state column.
+    pub aggr_expr: Vec<Expr>,
+    /// Output schema, group by columns followed by partial state columns.
+    pub schema: SchemaRef,
+}
+
+/// The partial stage of a two-phase top-k that is pushed down to table scan.
+#[derive(Debug, Clone)]
+pub struct PartialTopK {
+    /// Sort expressions, evaluated against the (projected and filtered) scanned rows.
+    pub sort_expr: Vec<Expr>,
+    /// Number of rows to keep.
+    pub fetch: usize,
+}
+
 #[derive(Default, Debug)]
 pub struct RegionStat {
     pub region_id: u64,
     pub disk_usage_bytes: u64,
+    /// Bytes of the SSTs and the manifest of the region in the object store, including the
+    /// SSTs removed by compaction but not purged yet.
+    pub storage_bytes: u64,
+    /// Max time index value written to the region since it was opened, in milliseconds.
+    pub max_timestamp_millis: Option<i64>,
+    /// Wall-clock time of the last successful write to the region since it was opened, in
+    /// milliseconds.
+    pub last_write_millis: Option<i64>,
+    /// Size of the memtables that triggers a flush of the region, which is adaptive to the
+    /// ingest rate if the table doesn't set `write_buffer_size`.
+    pub flush_threshold_bytes: Option<u64>,
+    /// Whether writes to the region skip the WAL, so its unflushed data is lost on crash.
+    pub wal_disabled: bool,
+    /// Approximate number of rows in the region, 0 if the table can't tell.
+    pub approximate_rows: u64,
+    /// Bytes of the memtables of the region, which approximates its unflushed data in the
+    /// WAL, 0 if the table can't tell.
+    pub memtable_bytes: u64,
+}
+
+/// Returns the seconds elapsed from the last write to any of the regions to `now_millis`, or
+/// `None` if none of the regions has been written since opened.
+pub fn write_freshness_secs(stats: &[RegionStat], now_millis: i64) -> Option<f64> {
+    stats
+        .iter()
+        .filter_map(|stat| stat.last_write_millis)
+        .max()
+        .map(|last_write_millis| (now_millis - last_write_millis).max(0) as f64 / 1000.0)
 }
@@ -55,7 +55,7 @@ impl TableProvider for DfTableProviderAdapter {
     }
 
     fn schema(&self) -> DfSchemaRef {
-        self.table.schema().arrow_schema().clone()
+        self.table.scan_schema().arrow_schema().clone()
     }
 
     fn table_type(&self) -> DfTableType {
@@ -0,0 +1,83 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+
+use async_trait::async_trait;
+use common_query::physical_plan::PhysicalPlanRef;
+use datatypes::schema::SchemaRef;
+
+use crate::error::Result;
+use crate::metadata::{FilterPushDownType, TableInfoRef, TableType};
+use crate::table::{Expr, Table, TableRef};
+
+/// Read-only view of the snapshot of a table as of a wall-clock time, whose scans are
+/// served by [Table::scan_as_of] of the table.
+pub struct AsOfTable {
+    table: TableRef,
+    /// Wall-clock time in millis.
+    as_of: i64,
+}
+
+impl AsOfTable {
+    pub fn new(table: TableRef, as_of: i64) -> Self {
+        Self { table, as_of }
+    }
+
+    pub fn table(&self) -> &TableRef {
+        &self.table
+    }
+
+    pub fn as_of(&self) -> i64 {
+        self.as_of
+    }
+}
+
+#[async_trait]
+impl Table for AsOfTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema()
+    }
+
+    fn scan_schema(&self) -> SchemaRef {
+        self.table.scan_schema()
+    }
+
+    fn table_info(&self) -> TableInfoRef {
+        self.table.table_info()
+    }
+
+    fn table_type(&self) -> TableType {
+        self.table.table_type()
+    }
+
+    async fn scan(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<PhysicalPlanRef> {
+        self.table
+            .scan_as_of(projection, filters, limit, self.as_of)
+            .await
+    }
+
+    fn supports_filters_pushdown(&self, filters: &[&Expr]) -> Result<Vec<FilterPushDownType>> {
+        self.table.supports_filters_pushdown(filters)
+    }
+}
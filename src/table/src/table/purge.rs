@@ -0,0 +1,112 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The default purging of expired rows, by deleting them with the keys found by a range scan.
+
+use std::collections::HashMap;
+
+use common_error::prelude::BoxedError;
+use common_query::logical_plan::Expr;
+use common_time::Timestamp;
+use datafusion::prelude::SessionContext;
+use datafusion_common::Column;
+use datafusion_expr::{lit, Expr as DfExpr};
+use datatypes::prelude::*;
+use datatypes::schema::ColumnSchema;
+use datatypes::vectors::BooleanVector;
+use futures::StreamExt;
+use snafu::{OptionExt, ResultExt};
+
+use crate::error::{Result, TableOperationSnafu, UnsupportedSnafu};
+use crate::requests::DeleteRequest;
+use crate::Table;
+
+/// Deletes the rows of the table whose time index is before `expire_before`, returns the
+/// number of rows deleted.
+///
+/// The rows are found by scanning the primary key and time index columns of the table, with
+/// a filter on the time index the table may use to skip the rows not expired. The rows the
+/// table returns anyway are filtered out here, so the table doesn't have to apply the filter.
+pub async fn delete_expired_rows<T: Table + ?Sized>(
+    table: &T,
+    expire_before: Timestamp,
+) -> Result<usize> {
+    let table_info = table.table_info();
+    let schema = &table_info.meta.schema;
+    // Rows without a time index never expire.
+    let ts_index = schema.timestamp_index().context(UnsupportedSnafu {
+        operation: "PURGE_EXPIRED without time index",
+    })?;
+    let ts_column = &schema.column_schemas()[ts_index];
+
+    let mut projection = table_info.meta.primary_key_indices.clone();
+    projection.push(ts_index);
+    let filters = expire_filter(ts_column, expire_before)
+        .into_iter()
+        .collect::<Vec<_>>();
+    let plan = table.scan(Some(&projection), &filters, None).await?;
+
+    let task_ctx = SessionContext::default().task_ctx();
+    let mut deleted = 0;
+    for partition in 0..plan.output_partitioning().partition_count() {
+        let mut stream = plan
+            .execute(partition, task_ctx.clone())
+            .map_err(BoxedError::new)
+            .context(TableOperationSnafu)?;
+        while let Some(batch) = stream.next().await {
+            let batch = batch
+                .map_err(BoxedError::new)
+                .context(TableOperationSnafu)?;
+            let ts_vector = batch.column(projection.len() - 1);
+            let expired = (0..batch.num_rows())
+                .map(|row| {
+                    let ts = ts_vector.get_ref(row).as_timestamp().ok().flatten();
+                    Some(matches!(ts, Some(ts) if ts < expire_before))
+                })
+                .collect::<Vec<_>>();
+            if !expired.contains(&Some(true)) {
+                continue;
+            }
+            let expired = BooleanVector::from(expired);
+
+            let key_column_values = batch
+                .schema
+                .column_schemas()
+                .iter()
+                .zip(batch.columns())
+                .map(|(column_schema, vector)| {
+                    let vector = vector
+                        .filter(&expired)
+                        .map_err(BoxedError::new)
+                        .context(TableOperationSnafu)?;
+                    Ok((column_schema.name.clone(), vector))
+                })
+                .collect::<Result<HashMap<_, _>>>()?;
+            deleted += table.delete(DeleteRequest { key_column_values }).await?;
+        }
+    }
+    Ok(deleted)
+}
+
+/// Returns the filter `ts_column < expire_before`, in the unit of the time index column.
+fn expire_filter(ts_column: &ColumnSchema, expire_before: Timestamp) -> Option<Expr> {
+    let ConcreteDataType::Timestamp(ts_type) = &ts_column.data_type else { return None };
+    // Rounds up, so no expired row is filtered out by the truncation.
+    let expire_before = expire_before.convert_to_ceil(ts_type.unit())?;
+    let scalar = Value::Timestamp(expire_before)
+        .try_to_scalar_value(&ts_column.data_type)
+        .ok()?;
+    let column = DfExpr::Column(Column::from_name(&ts_column.name));
+    Some(column.lt(lit(scalar)).into())
+}
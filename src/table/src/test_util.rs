@@ -13,9 +13,17 @@
 // limitations under the License.
 
 mod empty_table;
+#[cfg(any(test, feature = "test"))]
+mod memory_engine;
+#[cfg(any(test, feature = "test"))]
+mod memory_table;
 mod memtable;
 mod mock_engine;
 
 pub use empty_table::EmptyTable;
+#[cfg(any(test, feature = "test"))]
+pub use memory_engine::{CreateRequestBuilder, FaultInjector, MemoryTableEngine, MEMORY_ENGINE};
+#[cfg(any(test, feature = "test"))]
+pub use memory_table::MemoryTable;
 pub use memtable::MemTable;
 pub use mock_engine::MockTableEngine;
@@ -0,0 +1,724 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_procedure::{
+    BoxedProcedure, Context, Error as ProcedureError, LockKey, Procedure,
+    Result as ProcedureResult, Status,
+};
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, RawSchema, Schema};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::engine::{EngineContext, TableEngine, TableEngineProcedure, TableReference};
+use crate::error::{
+    BuildTableInfoSnafu, BuildTableMetaSnafu, InjectedFailureSnafu, Result, SchemaBuildSnafu,
+    TableExistsSnafu, TableNotFoundSnafu,
+};
+use crate::metadata::{TableInfoBuilder, TableMetaBuilder, TableType};
+use crate::requests::{
+    AlterKind, AlterTableRequest, CreateTableRequest, DropTableRequest, OpenTableRequest,
+    TableOptions,
+};
+use crate::test_util::MemoryTable;
+use crate::TableRef;
+
+/// Name of the [MemoryTableEngine].
+pub const MEMORY_ENGINE: &str = "memory";
+
+/// Failures and latencies injected into the operations of a [MemoryTableEngine] and its
+/// tables, counting both the DDLs of the engine and the reads and writes of the tables.
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    /// Number of the operations started so far.
+    operations: AtomicU64,
+    /// Sequence of the operation to fail, 0 to fail none.
+    fail_at: AtomicU64,
+    /// Latency added to each operation, in milliseconds.
+    latency_millis: AtomicU64,
+}
+
+impl FaultInjector {
+    /// Fails the `n`-th operation from now on, 1 for the next one.
+    pub fn fail_nth(&self, n: u64) {
+        let operations = self.operations.load(Ordering::Relaxed);
+        self.fail_at.store(operations + n, Ordering::Relaxed);
+    }
+
+    /// Delays every operation by the `latency`, e.g. to test timeouts.
+    pub fn set_latency(&self, latency: Duration) {
+        self.latency_millis
+            .store(latency.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the number of the operations started so far.
+    pub fn operations(&self) -> u64 {
+        self.operations.load(Ordering::Relaxed)
+    }
+
+    pub(crate) async fn check(&self, operation: &str) -> Result<()> {
+        let latency = self.latency_millis.load(Ordering::Relaxed);
+        if latency > 0 {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+
+        let sequence = self.operations.fetch_add(1, Ordering::Relaxed) + 1;
+        ensure!(
+            self.fail_at.load(Ordering::Relaxed) != sequence,
+            InjectedFailureSnafu { operation }
+        );
+        Ok(())
+    }
+}
+
+/// Table engine keeping its tables in memory, for tests of the catalog, the frontend and
+/// the query engine that don't care how the tables are stored.
+///
+/// The DDLs are executed by the procedures in one step, and the tables are lost once the
+/// engine is dropped.
+#[derive(Clone, Default)]
+pub struct MemoryTableEngine {
+    /// Keyed by the full table name.
+    tables: Arc<RwLock<HashMap<String, Arc<MemoryTable>>>>,
+    faults: Arc<FaultInjector>,
+}
+
+impl MemoryTableEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn faults(&self) -> &Arc<FaultInjector> {
+        &self.faults
+    }
+
+    fn table(&self, table_ref: &TableReference) -> Option<Arc<MemoryTable>> {
+        self.tables
+            .read()
+            .unwrap()
+            .get(&table_ref.to_string())
+            .cloned()
+    }
+}
+
+#[async_trait]
+impl TableEngine for MemoryTableEngine {
+    fn name(&self) -> &str {
+        MEMORY_ENGINE
+    }
+
+    async fn create_table(
+        &self,
+        _ctx: &EngineContext,
+        request: CreateTableRequest,
+    ) -> Result<TableRef> {
+        self.faults.check("CREATE TABLE").await?;
+
+        let table_name = request.table_ref().to_string();
+        if let Some(table) = self.table(&request.table_ref()) {
+            ensure!(
+                request.create_if_not_exists,
+                TableExistsSnafu { table_name }
+            );
+            return Ok(table);
+        }
+
+        let schema = Schema::try_from(request.schema.clone()).context(SchemaBuildSnafu {
+            msg: "failed to build table schema",
+        })?;
+        let next_column_id = schema.num_columns() as u32;
+        let meta = TableMetaBuilder::default()
+            .schema(Arc::new(schema))
+            .primary_key_indices(request.primary_key_indices)
+            .engine(request.engine)
+            .region_numbers(request.region_numbers)
+            .next_column_id(next_column_id)
+            .options(request.table_options)
+            .created_on(Default::default())
+            .build()
+            .context(BuildTableMetaSnafu {
+                table_name: &table_name,
+            })?;
+        let info = TableInfoBuilder::new(request.table_name, meta)
+            .table_id(request.id)
+            .catalog_name(request.catalog_name)
+            .schema_name(request.schema_name)
+            .desc(request.desc)
+            .table_type(TableType::Base)
+            .build()
+            .context(BuildTableInfoSnafu {
+                table_name: &table_name,
+            })?;
+
+        let table = Arc::new(MemoryTable::new(info, self.faults.clone()));
+        let mut tables = self.tables.write().unwrap();
+        // Another creation may win while building the table.
+        let table = tables.entry(table_name).or_insert(table).clone();
+        Ok(table)
+    }
+
+    async fn open_table(
+        &self,
+        _ctx: &EngineContext,
+        request: OpenTableRequest,
+    ) -> Result<Option<TableRef>> {
+        self.faults.check("OPEN TABLE").await?;
+
+        let table_ref = TableReference::full(
+            &request.catalog_name,
+            &request.schema_name,
+            &request.table_name,
+        );
+        Ok(self.table(&table_ref).map(|table| table as _))
+    }
+
+    async fn alter_table(
+        &self,
+        _ctx: &EngineContext,
+        request: AlterTableRequest,
+    ) -> Result<TableRef> {
+        self.faults.check("ALTER TABLE").await?;
+
+        let table_ref = request.table_ref();
+        let table = self.table(&table_ref).context(TableNotFoundSnafu {
+            table_name: table_ref.to_string(),
+        })?;
+        table.apply_alter(&request)?;
+
+        if let AlterKind::RenameTable { new_table_name } = &request.alter_kind {
+            let new_table_ref =
+                TableReference::full(&request.catalog_name, &request.schema_name, new_table_name);
+            let mut tables = self.tables.write().unwrap();
+            let _ = tables.remove(&table_ref.to_string());
+            let _ = tables.insert(new_table_ref.to_string(), table.clone());
+        }
+        Ok(table)
+    }
+
+    fn get_table(
+        &self,
+        _ctx: &EngineContext,
+        table_ref: &TableReference,
+    ) -> Result<Option<TableRef>> {
+        Ok(self.table(table_ref).map(|table| table as _))
+    }
+
+    fn table_exists(&self, _ctx: &EngineContext, table_ref: &TableReference) -> bool {
+        self.table(table_ref).is_some()
+    }
+
+    async fn drop_table(&self, _ctx: &EngineContext, request: DropTableRequest) -> Result<bool> {
+        self.faults.check("DROP TABLE").await?;
+
+        let table_name = request.table_ref().to_string();
+        Ok(self.tables.write().unwrap().remove(&table_name).is_some())
+    }
+
+    async fn close(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl TableEngineProcedure for MemoryTableEngine {
+    fn create_table_procedure(
+        &self,
+        _ctx: &EngineContext,
+        request: CreateTableRequest,
+    ) -> Result<BoxedProcedure> {
+        Ok(Box::new(MemoryDdlProcedure {
+            engine: self.clone(),
+            request: DdlRequest::Create(request),
+        }))
+    }
+
+    fn alter_table_procedure(
+        &self,
+        _ctx: &EngineContext,
+        request: AlterTableRequest,
+    ) -> Result<BoxedProcedure> {
+        Ok(Box::new(MemoryDdlProcedure {
+            engine: self.clone(),
+            request: DdlRequest::Alter(request),
+        }))
+    }
+
+    fn drop_table_procedure(
+        &self,
+        _ctx: &EngineContext,
+        request: DropTableRequest,
+    ) -> Result<BoxedProcedure> {
+        Ok(Box::new(MemoryDdlProcedure {
+            engine: self.clone(),
+            request: DdlRequest::Drop(request),
+        }))
+    }
+}
+
+#[derive(Debug)]
+enum DdlRequest {
+    Create(CreateTableRequest),
+    Alter(AlterTableRequest),
+    Drop(DropTableRequest),
+}
+
+/// Procedure executing a DDL of the [MemoryTableEngine] in one step. It's never persisted
+/// as the tables are lost on restart anyway.
+struct MemoryDdlProcedure {
+    engine: MemoryTableEngine,
+    request: DdlRequest,
+}
+
+#[async_trait]
+impl Procedure for MemoryDdlProcedure {
+    fn type_name(&self) -> &str {
+        "table::MemoryDdlProcedure"
+    }
+
+    async fn execute(&mut self, _ctx: &Context) -> ProcedureResult<Status> {
+        let ctx = EngineContext::default();
+        let result = match &self.request {
+            DdlRequest::Create(request) => {
+                let mut request = request.clone();
+                // The procedure may be retried after the table is created.
+                request.create_if_not_exists = true;
+                self.engine.create_table(&ctx, request).await.map(|_| ())
+            }
+            DdlRequest::Alter(request) => self
+                .engine
+                .alter_table(&ctx, request.clone())
+                .await
+                .map(|_| ()),
+            DdlRequest::Drop(request) => self
+                .engine
+                .drop_table(&ctx, request.clone())
+                .await
+                .map(|_| ()),
+        };
+        result.map_err(ProcedureError::from_error_ext)?;
+        Ok(Status::Done)
+    }
+
+    fn dump(&self) -> ProcedureResult<String> {
+        Ok(format!("{:?}", self.request))
+    }
+
+    fn lock_key(&self) -> LockKey {
+        let table_ref = match &self.request {
+            DdlRequest::Create(request) => request.table_ref(),
+            DdlRequest::Alter(request) => request.table_ref(),
+            DdlRequest::Drop(request) => request.table_ref(),
+        };
+        LockKey::single(table_ref.to_string())
+    }
+}
+
+/// Builds the [CreateTableRequest] of a table in few lines, adding the columns in order by
+/// [tag](Self::tag), [field](Self::field) and [time_index](Self::time_index).
+#[derive(Debug, Clone)]
+pub struct CreateRequestBuilder {
+    request: CreateTableRequest,
+    column_schemas: Vec<ColumnSchema>,
+}
+
+impl CreateRequestBuilder {
+    /// Creates the builder of a table of the [MemoryTableEngine] in the default schema.
+    pub fn new(table_name: impl Into<String>) -> Self {
+        Self {
+            request: CreateTableRequest {
+                id: 1024,
+                catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: table_name.into(),
+                desc: None,
+                schema: RawSchema::new(vec![]),
+                region_numbers: vec![0],
+                primary_key_indices: vec![],
+                create_if_not_exists: false,
+                table_options: TableOptions::default(),
+                engine: MEMORY_ENGINE.to_string(),
+            },
+            column_schemas: vec![],
+        }
+    }
+
+    pub fn table_id(mut self, id: u32) -> Self {
+        self.request.id = id;
+        self
+    }
+
+    pub fn schema_name(mut self, catalog_name: &str, schema_name: &str) -> Self {
+        self.request.catalog_name = catalog_name.to_string();
+        self.request.schema_name = schema_name.to_string();
+        self
+    }
+
+    pub fn engine(mut self, engine: &str) -> Self {
+        self.request.engine = engine.to_string();
+        self
+    }
+
+    pub fn table_options(mut self, table_options: TableOptions) -> Self {
+        self.request.table_options = table_options;
+        self
+    }
+
+    pub fn regions(mut self, region_numbers: Vec<u32>) -> Self {
+        self.request.region_numbers = region_numbers;
+        self
+    }
+
+    /// Adds a non-null column of the primary key.
+    pub fn tag(mut self, name: &str, data_type: ConcreteDataType) -> Self {
+        self.request
+            .primary_key_indices
+            .push(self.column_schemas.len());
+        self.column(ColumnSchema::new(name, data_type, false))
+    }
+
+    /// Adds a nullable value column.
+    pub fn field(self, name: &str, data_type: ConcreteDataType) -> Self {
+        self.column(ColumnSchema::new(name, data_type, true))
+    }
+
+    /// Adds the time index column in milliseconds.
+    pub fn time_index(self, name: &str) -> Self {
+        self.column(
+            ColumnSchema::new(
+                name,
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        )
+    }
+
+    pub fn column(mut self, column_schema: ColumnSchema) -> Self {
+        self.column_schemas.push(column_schema);
+        self
+    }
+
+    pub fn build(mut self) -> CreateTableRequest {
+        self.request.schema = RawSchema::new(self.column_schemas);
+        self.request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common_recordbatch::util;
+    use common_time::Timestamp;
+    use datafusion::prelude::SessionContext;
+    use datatypes::prelude::*;
+    use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+
+    use super::*;
+    use crate::requests::{AddColumnRequest, DeleteRequest, InsertRequest};
+    use crate::Table;
+
+    fn insert_request(hosts: Vec<&str>, cpus: Vec<f64>, ts: Vec<i64>) -> InsertRequest {
+        let columns_values = HashMap::from([
+            (
+                "host".to_string(),
+                Arc::new(StringVector::from(hosts)) as VectorRef,
+            ),
+            (
+                "cpu".to_string(),
+                Arc::new(Float64Vector::from_vec(cpus)) as _,
+            ),
+            (
+                "ts".to_string(),
+                Arc::new(TimestampMillisecondVector::from_vec(ts)) as _,
+            ),
+        ]);
+        InsertRequest {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "monitor".to_string(),
+            columns_values,
+            region_number: 0,
+            write_mode: Default::default(),
+        }
+    }
+
+    async fn create_monitor_table(engine: &MemoryTableEngine) -> TableRef {
+        let request = CreateRequestBuilder::new("monitor")
+            .tag("host", ConcreteDataType::string_datatype())
+            .field("cpu", ConcreteDataType::float64_datatype())
+            .time_index("ts")
+            .build();
+        engine
+            .create_table(&EngineContext::default(), request)
+            .await
+            .unwrap()
+    }
+
+    async fn scan_to_string(table: &TableRef) -> String {
+        let ctx = SessionContext::new();
+        let plan = table.scan(None, &[], None).await.unwrap();
+        let stream = plan.execute(0, ctx.task_ctx()).unwrap();
+        let batches = util::collect_batches(stream).await.unwrap();
+        batches.pretty_print().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_insert_scan_delete() {
+        let engine = MemoryTableEngine::new();
+        let table = create_monitor_table(&engine).await;
+        let ctx = EngineContext::default();
+        let table_ref = TableReference::bare("monitor");
+        assert!(engine.table_exists(&ctx, &table_ref));
+
+        let request = CreateRequestBuilder::new("monitor").build();
+        let err = engine.create_table(&ctx, request).await.unwrap_err();
+        assert!(
+            matches!(err, crate::error::Error::TableExists { .. }),
+            "{err}"
+        );
+
+        let rows = table
+            .insert(insert_request(
+                vec!["a", "b"],
+                vec![1.0, 2.0],
+                vec![1000, 2000],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(2, rows);
+        let expected = "\
++------+-----+---------------------+
+| host | cpu | ts                  |
++------+-----+---------------------+
+| a    | 1.0 | 1970-01-01T00:00:01 |
+| b    | 2.0 | 1970-01-01T00:00:02 |
++------+-----+---------------------+";
+        assert_eq!(expected, scan_to_string(&table).await);
+
+        let request = DeleteRequest {
+            key_column_values: HashMap::from([
+                (
+                    "host".to_string(),
+                    Arc::new(StringVector::from(vec!["a"])) as VectorRef,
+                ),
+                (
+                    "ts".to_string(),
+                    Arc::new(TimestampMillisecondVector::from_vec(vec![1000])) as _,
+                ),
+            ]),
+        };
+        assert_eq!(1, table.delete(request).await.unwrap());
+        let stats = table.region_stats().unwrap();
+        assert_eq!(1, stats.len());
+        assert!(stats[0].disk_usage_bytes > 0);
+
+        let request = DropTableRequest {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "monitor".to_string(),
+        };
+        assert!(engine.drop_table(&ctx, request.clone()).await.unwrap());
+        assert!(!engine.drop_table(&ctx, request).await.unwrap());
+        assert!(!engine.table_exists(&ctx, &table_ref));
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired() {
+        let engine = MemoryTableEngine::new();
+        let table = create_monitor_table(&engine).await;
+        let _ = table
+            .insert(insert_request(
+                vec!["a", "b", "a"],
+                vec![1.0, 2.0, 3.0],
+                vec![1000, 2000, 3000],
+            ))
+            .await
+            .unwrap();
+
+        // Deletes the expired rows by the default range deletion.
+        table.purge_expired(Timestamp::new_second(3)).await.unwrap();
+        let expected = "\
++------+-----+---------------------+
+| host | cpu | ts                  |
++------+-----+---------------------+
+| a    | 3.0 | 1970-01-01T00:00:03 |
++------+-----+---------------------+";
+        assert_eq!(expected, scan_to_string(&table).await);
+    }
+
+    #[tokio::test]
+    async fn test_schema_evolution() {
+        let engine = MemoryTableEngine::new();
+        let table = create_monitor_table(&engine).await;
+        let _ = table
+            .insert(insert_request(vec!["a"], vec![1.0], vec![1000]))
+            .await
+            .unwrap();
+
+        let ctx = EngineContext::default();
+        let request = AlterTableRequest {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "monitor".to_string(),
+            alter_kind: AlterKind::AddColumns {
+                columns: vec![AddColumnRequest {
+                    column_schema: ColumnSchema::new(
+                        "memory",
+                        ConcreteDataType::float64_datatype(),
+                        true,
+                    ),
+                    is_key: false,
+                }],
+            },
+        };
+        let table = engine.alter_table(&ctx, request).await.unwrap();
+        assert_eq!(1, table.table_info().ident.version);
+
+        // Rows written before the alteration get the default value of the new column.
+        let mut request = insert_request(vec!["b"], vec![2.0], vec![2000]);
+        let _ = request.columns_values.insert(
+            "memory".to_string(),
+            Arc::new(Float64Vector::from_vec(vec![20.0])),
+        );
+        let _ = table.insert(request).await.unwrap();
+        let expected = "\
++------+-----+---------------------+--------+
+| host | cpu | ts                  | memory |
++------+-----+---------------------+--------+
+| a    | 1.0 | 1970-01-01T00:00:01 |        |
+| b    | 2.0 | 1970-01-01T00:00:02 | 20.0   |
++------+-----+---------------------+--------+";
+        assert_eq!(expected, scan_to_string(&table).await);
+
+        let request = AlterTableRequest {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "monitor".to_string(),
+            alter_kind: AlterKind::DropColumns {
+                names: vec!["cpu".to_string()],
+            },
+        };
+        let table = engine.alter_table(&ctx, request).await.unwrap();
+        let expected = "\
++------+---------------------+--------+
+| host | ts                  | memory |
++------+---------------------+--------+
+| a    | 1970-01-01T00:00:01 |        |
+| b    | 1970-01-01T00:00:02 | 20.0   |
++------+---------------------+--------+";
+        assert_eq!(expected, scan_to_string(&table).await);
+
+        // Writes to the dropped column are rejected.
+        let err = table
+            .insert(insert_request(vec!["c"], vec![3.0], vec![3000]))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, crate::error::Error::ColumnNotExists { .. }),
+            "{err}"
+        );
+
+        let request = AlterTableRequest {
+            catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+            schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: "monitor".to_string(),
+            alter_kind: AlterKind::RenameTable {
+                new_table_name: "monitor_v2".to_string(),
+            },
+        };
+        let _ = engine.alter_table(&ctx, request).await.unwrap();
+        assert!(!engine.table_exists(&ctx, &TableReference::bare("monitor")));
+        let table = engine
+            .get_table(&ctx, &TableReference::bare("monitor_v2"))
+            .unwrap()
+            .unwrap();
+        assert_eq!("monitor_v2", table.table_info().name);
+        assert_eq!(3, table.table_info().ident.version);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_writes() {
+        let engine = MemoryTableEngine::new();
+        let table = create_monitor_table(&engine).await;
+
+        let handles = (0..8)
+            .map(|i| {
+                let table = table.clone();
+                tokio::spawn(async move {
+                    for j in 0..10 {
+                        let host = format!("host-{i}");
+                        let ts = i * 100 + j;
+                        let _ = table
+                            .insert(insert_request(vec![host.as_str()], vec![1.0], vec![ts]))
+                            .await
+                            .unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        futures::future::try_join_all(handles).await.unwrap();
+
+        let table = table.as_any().downcast_ref::<MemoryTable>().unwrap();
+        assert_eq!(80, table.num_rows());
+    }
+
+    #[tokio::test]
+    async fn test_inject_faults() {
+        let engine = MemoryTableEngine::new();
+        let table = create_monitor_table(&engine).await;
+
+        engine.faults().fail_nth(2);
+        let _ = table
+            .insert(insert_request(vec!["a"], vec![1.0], vec![1000]))
+            .await
+            .unwrap();
+        let err = table
+            .insert(insert_request(vec!["b"], vec![2.0], vec![2000]))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, crate::error::Error::InjectedFailure { .. }),
+            "{err}"
+        );
+        // Only the N-th operation fails.
+        let _ = table
+            .insert(insert_request(vec!["c"], vec![3.0], vec![3000]))
+            .await
+            .unwrap();
+        let table = table.as_any().downcast_ref::<MemoryTable>().unwrap();
+        assert_eq!(2, table.num_rows());
+
+        engine.faults().set_latency(Duration::from_millis(100));
+        let result = tokio::time::timeout(
+            Duration::from_millis(10),
+            engine.open_table(
+                &EngineContext::default(),
+                OpenTableRequest {
+                    catalog_name: DEFAULT_CATALOG_NAME.to_string(),
+                    schema_name: DEFAULT_SCHEMA_NAME.to_string(),
+                    table_name: "monitor".to_string(),
+                    table_id: 1024,
+                },
+            ),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
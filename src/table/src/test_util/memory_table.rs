@@ -0,0 +1,315 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use common_error::prelude::BoxedError;
+use common_query::physical_plan::PhysicalPlanRef;
+use common_query::prelude::Expr;
+use common_recordbatch::{RecordBatch, RecordBatches};
+use datatypes::prelude::*;
+use datatypes::schema::{Schema, SchemaRef};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::engine::region_id;
+use crate::error::{
+    BuildTableMetaSnafu, ColumnNotExistsSnafu, MissingColumnValueSnafu, Result, SchemaBuildSnafu,
+    TablesRecordBatchSnafu,
+};
+use crate::metadata::{TableInfo, TableInfoRef};
+use crate::requests::{AlterKind, AlterTableRequest, DeleteRequest, InsertRequest};
+use crate::table::scan::SimpleTableScan;
+use crate::table::{AlterContext, RegionStat};
+use crate::test_util::memory_engine::FaultInjector;
+use crate::Table;
+
+/// Table of the [MemoryTableEngine](crate::test_util::MemoryTableEngine), which keeps its rows
+/// in memory as record batches.
+pub struct MemoryTable {
+    info: RwLock<TableInfoRef>,
+    /// Rows of the table, all in the current schema of the table.
+    batches: RwLock<Vec<RecordBatch>>,
+    faults: Arc<FaultInjector>,
+}
+
+impl MemoryTable {
+    pub(crate) fn new(info: TableInfo, faults: Arc<FaultInjector>) -> Self {
+        Self {
+            info: RwLock::new(Arc::new(info)),
+            batches: RwLock::new(Vec::new()),
+            faults,
+        }
+    }
+
+    /// Returns the number of rows in the table.
+    pub fn num_rows(&self) -> usize {
+        self.batches
+            .read()
+            .unwrap()
+            .iter()
+            .map(|batch| batch.num_rows())
+            .sum()
+    }
+
+    /// Applies the alteration without injecting faults, the engine has done it.
+    pub(crate) fn apply_alter(&self, request: &AlterTableRequest) -> Result<()> {
+        let mut info = self.info.write().unwrap();
+        let table_name = &info.name;
+
+        let mut new_info = TableInfo::clone(&info);
+        match &request.alter_kind {
+            AlterKind::RenameTable { new_table_name } => {
+                new_info.name = new_table_name.clone();
+            }
+            AlterKind::AddColumns { .. }
+            | AlterKind::DropColumns { .. }
+            | AlterKind::SetColumnOptions { .. } => {
+                new_info.meta = info
+                    .meta
+                    .builder_with_alter_kind(table_name, &request.alter_kind)?
+                    .build()
+                    .context(BuildTableMetaSnafu { table_name })?;
+            }
+        }
+        new_info.ident.version = info.ident.version + 1;
+
+        // Rewrites the rows in the new schema, so they never need aligning on reading.
+        let schema = new_info.meta.schema.clone();
+        let mut batches = self.batches.write().unwrap();
+        *batches = batches
+            .iter()
+            .map(|batch| align_batch(batch, &schema))
+            .collect::<Result<_>>()?;
+        *info = Arc::new(new_info);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Table for MemoryTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table_info().meta.schema.clone()
+    }
+
+    fn table_info(&self) -> TableInfoRef {
+        self.info.read().unwrap().clone()
+    }
+
+    async fn insert(&self, request: InsertRequest) -> Result<usize> {
+        self.faults.check("INSERT").await?;
+
+        let info = self.table_info();
+        let schema = &info.meta.schema;
+        for column_name in request.columns_values.keys() {
+            ensure!(
+                schema.contains_column(column_name),
+                ColumnNotExistsSnafu {
+                    column_name,
+                    table_name: &info.name,
+                }
+            );
+        }
+        let Some(rows) = request.columns_values.values().next().map(|v| v.len()) else { return Ok(0) };
+
+        let columns = schema
+            .column_schemas()
+            .iter()
+            .map(|column_schema| match request.columns_values.get(&column_schema.name) {
+                Some(vector) => Ok(vector.clone()),
+                None => column_schema
+                    .create_default_vector(rows)
+                    .context(SchemaBuildSnafu {
+                        msg: "failed to create default vector",
+                    })?
+                    .context(MissingColumnValueSnafu {
+                        column_name: &column_schema.name,
+                        table_name: &info.name,
+                    }),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let batch = RecordBatch::new(schema.clone(), columns)
+            .map_err(BoxedError::new)
+            .context(TablesRecordBatchSnafu)?;
+
+        // The table may be altered while building the batch, holds the info so it can't be
+        // altered again until the batch is pushed.
+        let info = self.info.read().unwrap();
+        let batch = align_batch(&batch, &info.meta.schema)?;
+        self.batches.write().unwrap().push(batch);
+        Ok(rows)
+    }
+
+    async fn scan(
+        &self,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<PhysicalPlanRef> {
+        self.faults.check("SCAN").await?;
+
+        let (schema, batches) = {
+            let info = self.info.read().unwrap();
+            let batches = self.batches.read().unwrap();
+            (info.meta.schema.clone(), batches.clone())
+        };
+        let indices = match projection {
+            Some(indices) => indices.clone(),
+            None => (0..schema.num_columns()).collect(),
+        };
+        let schema = Arc::new(Schema::new(
+            indices
+                .iter()
+                .map(|i| schema.column_schemas()[*i].clone())
+                .collect(),
+        ));
+
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        let mut projected = Vec::with_capacity(batches.len());
+        for batch in batches {
+            if remaining == 0 {
+                break;
+            }
+            let rows = batch.num_rows().min(remaining);
+            remaining -= rows;
+            let columns = indices.iter().map(|i| batch.column(*i).slice(0, rows));
+            projected.push(RecordBatch::new(schema.clone(), columns));
+        }
+        let batches = projected
+            .into_iter()
+            .collect::<common_recordbatch::error::Result<Vec<_>>>()
+            .and_then(|batches| RecordBatches::try_new(schema, batches))
+            .map_err(BoxedError::new)
+            .context(TablesRecordBatchSnafu)?;
+        Ok(Arc::new(SimpleTableScan::new(batches.as_stream())))
+    }
+
+    async fn alter(&self, _context: AlterContext, request: &AlterTableRequest) -> Result<()> {
+        self.faults.check("ALTER TABLE").await?;
+        self.apply_alter(request)
+    }
+
+    async fn delete(&self, request: DeleteRequest) -> Result<usize> {
+        self.faults.check("DELETE").await?;
+
+        let info = self.table_info();
+        let mut key_columns = request.key_column_values.into_iter().collect::<Vec<_>>();
+        key_columns.sort_by(|a, b| a.0.cmp(&b.0));
+        let Some(keys_num) = key_columns.first().map(|(_, v)| v.len()) else { return Ok(0) };
+        let keys = (0..keys_num)
+            .map(|i| key_columns.iter().map(|(_, v)| v.get(i)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let mut batches = self.batches.write().unwrap();
+        let mut deleted = 0;
+        for batch in batches.iter_mut() {
+            let key_vectors = key_columns
+                .iter()
+                .map(|(name, _)| {
+                    batch.column_by_name(name).context(ColumnNotExistsSnafu {
+                        column_name: name,
+                        table_name: &info.name,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let kept = (0..batch.num_rows())
+                .filter(|&row| {
+                    let key = key_vectors.iter().map(|v| v.get(row)).collect::<Vec<_>>();
+                    !keys.contains(&key)
+                })
+                .collect::<Vec<_>>();
+            if kept.len() == batch.num_rows() {
+                continue;
+            }
+
+            deleted += batch.num_rows() - kept.len();
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|vector| {
+                    let mut builder = vector.data_type().create_mutable_vector(kept.len());
+                    for &row in &kept {
+                        builder.push_value_ref(vector.get_ref(row));
+                    }
+                    builder.to_vector()
+                })
+                .collect::<Vec<_>>();
+            *batch = RecordBatch::new(batch.schema.clone(), columns)
+                .map_err(BoxedError::new)
+                .context(TablesRecordBatchSnafu)?;
+        }
+        batches.retain(|batch| batch.num_rows() > 0);
+        Ok(deleted)
+    }
+
+    async fn flush(&self, _region_number: Option<u32>, _wait: Option<bool>) -> Result<()> {
+        self.faults.check("FLUSH").await
+    }
+
+    fn region_stats(&self) -> Result<Vec<RegionStat>> {
+        let info = self.table_info();
+        let bytes = self
+            .batches
+            .read()
+            .unwrap()
+            .iter()
+            .flat_map(|batch| batch.columns().iter().map(|v| v.memory_size() as u64))
+            .sum::<u64>();
+        let rows = self.num_rows() as u64;
+        let regions = &info.meta.region_numbers;
+        Ok(regions
+            .iter()
+            .enumerate()
+            .map(|(i, region_number)| RegionStat {
+                region_id: region_id(info.ident.table_id, *region_number),
+                // All rows are accounted to the first region.
+                disk_usage_bytes: if i == 0 { bytes } else { 0 },
+                approximate_rows: if i == 0 { rows } else { 0 },
+                wal_disabled: info.meta.options.wal_disabled,
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+/// Rebuilds the `batch` in the `schema`, filling the columns it doesn't have with their
+/// default values.
+fn align_batch(batch: &RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    if batch.schema == *schema {
+        return Ok(batch.clone());
+    }
+
+    let rows = batch.num_rows();
+    let columns = schema
+        .column_schemas()
+        .iter()
+        .map(|column_schema| match batch.column_by_name(&column_schema.name) {
+            Some(vector) => Ok(vector.clone()),
+            None => Ok(column_schema
+                .create_default_vector(rows)
+                .context(SchemaBuildSnafu {
+                    msg: "failed to create default vector",
+                })?
+                .unwrap_or_else(|| column_schema.create_default_vector_for_padding(rows))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    RecordBatch::new(schema.clone(), columns)
+        .map_err(BoxedError::new)
+        .context(TablesRecordBatchSnafu)
+}
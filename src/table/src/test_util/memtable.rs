@@ -32,8 +32,9 @@ use store_api::storage::RegionNumber;
 
 use crate::error::{Result, SchemaConversionSnafu, TableProjectionSnafu, TablesRecordBatchSnafu};
 use crate::metadata::{
-    TableId, TableInfoBuilder, TableInfoRef, TableMetaBuilder, TableType, TableVersion,
+    TableId, TableInfo, TableInfoBuilder, TableInfoRef, TableMetaBuilder, TableType, TableVersion,
 };
+use crate::requests::TableOptions;
 use crate::table::scan::SimpleTableScan;
 use crate::Table;
 
@@ -103,6 +104,22 @@ impl MemTable {
         Self { info, recordbatch }
     }
 
+    /// Sets the options of the table.
+    pub fn with_options(mut self, options: TableOptions) -> Self {
+        let mut info = TableInfo::clone(&self.info);
+        info.meta.options = options;
+        self.info = Arc::new(info);
+        self
+    }
+
+    /// Sets the comment of the table.
+    pub fn with_desc(mut self, desc: impl Into<String>) -> Self {
+        let mut info = TableInfo::clone(&self.info);
+        info.desc = Some(desc.into());
+        self.info = Arc::new(info);
+        self
+    }
+
     pub fn table_name(&self) -> &str {
         &self.info.name
     }
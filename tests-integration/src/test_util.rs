@@ -35,6 +35,7 @@ use datanode::sql::SqlHandler;
 use datatypes::data_type::ConcreteDataType;
 use datatypes::schema::{ColumnSchema, RawSchema};
 use frontend::instance::Instance as FeInstance;
+use frontend::script::ScriptOptions;
 use object_store::services::{Oss, S3};
 use object_store::test_util::TempFolder;
 use object_store::ObjectStore;
@@ -291,9 +292,10 @@ pub async fn setup_test_http_app(store_type: StorageType, name: &str) -> (Router
     )
     .await
     .unwrap();
-    let frontend_instance = FeInstance::try_new_standalone(instance.clone())
-        .await
-        .unwrap();
+    let frontend_instance =
+        FeInstance::try_new_standalone(instance.clone(), &ScriptOptions::default())
+            .await
+            .unwrap();
     instance.start().await.unwrap();
     let http_server = HttpServerBuilder::new(HttpOptions::default())
         .with_sql_handler(ServerSqlQueryHandlerAdaptor::arc(Arc::new(
@@ -311,7 +313,7 @@ pub async fn setup_test_http_app_with_frontend(
 ) -> (Router, TestGuard) {
     let (opts, guard) = create_tmp_dir_and_datanode_opts(store_type, name);
     let instance = Arc::new(Instance::with_mock_meta_client(&opts).await.unwrap());
-    let frontend = FeInstance::try_new_standalone(instance.clone())
+    let frontend = FeInstance::try_new_standalone(instance.clone(), &ScriptOptions::default())
         .await
         .unwrap();
     instance.start().await.unwrap();
@@ -326,6 +328,7 @@ pub async fn setup_test_http_app_with_frontend(
     let http_server = HttpServerBuilder::new(HttpOptions::default())
         .with_sql_handler(ServerSqlQueryHandlerAdaptor::arc(frontend_ref.clone()))
         .with_grpc_handler(ServerGrpcQueryHandlerAdaptor::arc(frontend_ref.clone()))
+        .with_prom_handler(frontend_ref.clone())
         .with_script_handler(frontend_ref)
         .build();
     let app = http_server.make_app();
@@ -338,7 +341,7 @@ pub async fn setup_test_prom_app_with_frontend(
 ) -> (Router, TestGuard) {
     let (opts, guard) = create_tmp_dir_and_datanode_opts(store_type, name);
     let instance = Arc::new(Instance::with_mock_meta_client(&opts).await.unwrap());
-    let frontend = FeInstance::try_new_standalone(instance.clone())
+    let frontend = FeInstance::try_new_standalone(instance.clone(), &ScriptOptions::default())
         .await
         .unwrap();
     instance.start().await.unwrap();
@@ -373,17 +376,20 @@ pub async fn setup_grpc_server(
 
     let fe_grpc_addr = format!("127.0.0.1:{}", get_port());
 
-    let fe_instance = FeInstance::try_new_standalone(instance.clone())
+    let fe_instance = FeInstance::try_new_standalone(instance.clone(), &ScriptOptions::default())
         .await
         .unwrap();
     instance.start().await.unwrap();
     let fe_instance_ref = Arc::new(fe_instance);
-    let fe_grpc_server = Arc::new(GrpcServer::new(
-        ServerGrpcQueryHandlerAdaptor::arc(fe_instance_ref.clone()),
-        Some(fe_instance_ref.clone()),
-        None,
-        runtime,
-    ));
+    let fe_grpc_server = Arc::new(
+        GrpcServer::new(
+            ServerGrpcQueryHandlerAdaptor::arc(fe_instance_ref.clone()),
+            Some(fe_instance_ref.clone()),
+            None,
+            runtime,
+        )
+        .with_ddl_batch_handler(fe_instance_ref.clone()),
+    );
     let grpc_server_clone = fe_grpc_server.clone();
 
     let fe_grpc_addr_clone = fe_grpc_addr.clone();
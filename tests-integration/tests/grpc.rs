@@ -15,13 +15,18 @@
 use api::v1::alter_expr::Kind;
 use api::v1::column::SemanticType;
 use api::v1::promql_request::Promql;
+use api::v1::row::value::ValueData;
+use api::v1::row::{ColumnSchema as RowColumnSchema, Row, RowInsertRequest, Value};
 use api::v1::{
     column, AddColumn, AddColumns, AlterExpr, Column, ColumnDataType, ColumnDef, CreateTableExpr,
     InsertRequest, PromInstantQuery, PromRangeQuery, PromqlRequest, RequestHeader, TableId,
 };
 use client::{Client, Database, DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_base::validation_mode::ValidationMode;
 use common_catalog::consts::{MIN_USER_TABLE_ID, MITO_ENGINE};
+use common_error::prelude::{ErrorExt, StatusCode};
 use common_query::Output;
+use serde_json::json;
 use servers::prom::{PromData, PromJsonResponse, PromSeries};
 use servers::server::Server;
 use tests_integration::test_util::{setup_grpc_server, StorageType};
@@ -59,9 +64,12 @@ macro_rules! grpc_tests {
                 test_invalid_dbname,
                 test_auto_create_table,
                 test_insert_and_select,
+                test_insert_invalid_rows,
                 test_dbname,
                 test_health_check,
                 test_prom_gateway_query,
+                test_create_tables,
+                test_row_insert,
             );
         )*
     };
@@ -214,6 +222,78 @@ pub async fn test_insert_and_select(store_type: StorageType) {
     guard.remove_all().await;
 }
 
+pub async fn test_insert_invalid_rows(store_type: StorageType) {
+    let (addr, mut guard, fe_grpc_server) = setup_grpc_server(store_type, "invalid_rows").await;
+
+    let grpc_client = Client::with_urls(vec![addr]);
+    let mut db = Database::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, grpc_client);
+    let result = db.create(testing_create_expr()).await.unwrap();
+    assert!(matches!(result, Output::AffectedRows(0)));
+
+    // The hosts of the rows 1, 4 and 7 are null, but the column is not null.
+    let host = Column {
+        column_name: "host".to_string(),
+        values: Some(column::Values {
+            string_values: vec!["host0", "host2", "host3", "host5", "host6"]
+                .into_iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ..Default::default()
+        }),
+        null_mask: vec![0b1001_0010],
+        semantic_type: SemanticType::Tag as i32,
+        datatype: ColumnDataType::String as i32,
+    };
+    let ts = Column {
+        column_name: "ts".to_string(),
+        values: Some(column::Values {
+            ts_millisecond_values: (0..8).collect(),
+            ..Default::default()
+        }),
+        semantic_type: SemanticType::Timestamp as i32,
+        datatype: ColumnDataType::TimestampMillisecond as i32,
+        ..Default::default()
+    };
+    let request = InsertRequest {
+        table_name: "demo".to_string(),
+        region_number: 0,
+        columns: vec![host, ts],
+        row_count: 8,
+    };
+    let row_error = |row| json!({"row": row, "column": "host", "value": null, "reason": "null value in not null column"});
+
+    for (mode, expected) in [
+        (
+            ValidationMode::Strict,
+            json!({"errors": [row_error(1)], "incomplete": false}),
+        ),
+        (
+            ValidationMode::Lenient,
+            json!({"errors": [row_error(1), row_error(4), row_error(7)], "incomplete": false}),
+        ),
+    ] {
+        db.set_validation_mode(mode);
+        let err = db.insert(request.clone()).await.unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        let row_errors = serde_json::to_value(err.row_errors().unwrap()).unwrap();
+        assert_eq!(expected, row_errors, "{mode}");
+    }
+
+    // The batch is rejected as a whole.
+    let result = db.sql("SELECT * FROM demo").await.unwrap();
+    let Output::RecordBatches(recordbatches) = result else { unreachable!() };
+    assert_eq!(
+        0,
+        recordbatches
+            .iter()
+            .map(|batch| batch.num_rows())
+            .sum::<usize>()
+    );
+
+    let _ = fe_grpc_server.shutdown().await;
+    guard.remove_all().await;
+}
+
 async fn insert_and_assert(db: &Database) {
     // testing data:
     let (expected_host_col, expected_cpu_col, expected_mem_col, expected_ts_col) = expect_data();
@@ -325,6 +405,114 @@ pub async fn test_health_check(store_type: StorageType) {
     guard.remove_all().await;
 }
 
+pub async fn test_create_tables(store_type: StorageType) {
+    let (addr, mut guard, fe_grpc_server) = setup_grpc_server(store_type, "create_tables").await;
+
+    let grpc_client = Client::with_urls(vec![addr]);
+    let db = Database::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, grpc_client);
+    let create_table = |name: &str| {
+        format!("CREATE TABLE {name} (host STRING, ts TIMESTAMP TIME INDEX, PRIMARY KEY(host));")
+    };
+
+    let sql = format!("{}{}", create_table("t1"), create_table("t2"));
+    let tables = db.create_tables(&sql).await.unwrap();
+    assert_eq!(
+        vec![
+            json!({"table": "greptime.public.t1", "status": "created"}),
+            json!({"table": "greptime.public.t2", "status": "created"}),
+        ],
+        tables
+    );
+
+    // `t1` already exists, so the batch fails without creating `t3`.
+    let sql = format!("{}{}", create_table("t3"), create_table("t1"));
+    let tables = db.create_tables(&sql).await.unwrap();
+    assert_eq!("skipped", tables[0]["status"]);
+    assert_eq!("failed", tables[1]["status"]);
+    assert!(db.sql("SELECT * FROM t3").await.is_err());
+
+    let _ = fe_grpc_server.shutdown().await;
+    guard.remove_all().await;
+}
+
+pub async fn test_row_insert(store_type: StorageType) {
+    let (addr, mut guard, fe_grpc_server) = setup_grpc_server(store_type, "row_insert").await;
+
+    let grpc_client = Client::with_urls(vec![addr]);
+    let db = Database::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, grpc_client);
+    let column_schema =
+        |name: &str, datatype: ColumnDataType, semantic_type: SemanticType| RowColumnSchema {
+            column_name: name.to_string(),
+            datatype: datatype as i32,
+            semantic_type: semantic_type as i32,
+        };
+    let row = |host: &str, cpu: Option<f64>, ts: i64| Row {
+        values: vec![
+            Value {
+                value_data: Some(ValueData::StringValue(host.to_string())),
+            },
+            Value {
+                value_data: cpu.map(ValueData::F64Value),
+            },
+            Value {
+                value_data: Some(ValueData::TsMillisecondValue(ts)),
+            },
+        ],
+    };
+    let request = RowInsertRequest {
+        table_name: "row_demo".to_string(),
+        schema: vec![
+            column_schema("host", ColumnDataType::String, SemanticType::Tag),
+            column_schema("cpu", ColumnDataType::Float64, SemanticType::Field),
+            column_schema(
+                "ts",
+                ColumnDataType::TimestampMillisecond,
+                SemanticType::Timestamp,
+            ),
+        ],
+        rows: vec![row("host1", Some(0.1), 1000), row("host2", None, 2000)],
+        region_number: 0,
+    };
+
+    // The table is created on demand.
+    assert_eq!(2, db.row_insert(vec![request]).await.unwrap());
+    let output = db
+        .sql("SELECT host, cpu, ts FROM row_demo ORDER BY ts")
+        .await
+        .unwrap();
+    let Output::RecordBatches(recordbatches) = output else { unreachable!() };
+    let expected = "\
++-------+-----+---------------------+
+| host  | cpu | ts                  |
++-------+-----+---------------------+
+| host1 | 0.1 | 1970-01-01T00:00:01 |
+| host2 |     | 1970-01-01T00:00:02 |
++-------+-----+---------------------+";
+    assert_eq!(recordbatches.pretty_print().unwrap(), expected);
+
+    // Rows not matching the schema are rejected.
+    let mut short_row = row("host3", None, 3000);
+    let _ = short_row.values.pop();
+    let request = RowInsertRequest {
+        table_name: "row_demo".to_string(),
+        schema: vec![
+            column_schema("host", ColumnDataType::String, SemanticType::Tag),
+            column_schema("cpu", ColumnDataType::Float64, SemanticType::Field),
+            column_schema(
+                "ts",
+                ColumnDataType::TimestampMillisecond,
+                SemanticType::Timestamp,
+            ),
+        ],
+        rows: vec![short_row],
+        region_number: 0,
+    };
+    assert!(db.row_insert(vec![request]).await.is_err());
+
+    let _ = fe_grpc_server.shutdown().await;
+    guard.remove_all().await;
+}
+
 pub async fn test_prom_gateway_query(store_type: StorageType) {
     // prepare connection
     let (addr, mut guard, fe_grpc_server) = setup_grpc_server(store_type, "prom_gateway").await;
@@ -12,12 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use api::prometheus::remote::WriteRequest;
 use axum::http::StatusCode;
 use axum_test_helper::TestClient;
 use common_error::status_code::StatusCode as ErrorCode;
+use prost::Message;
 use serde_json::json;
 use servers::http::handler::HealthResponse;
 use servers::http::{JsonOutput, JsonResponse};
+use servers::prometheus::{mock_timeseries, snappy_compress};
 use tests_integration::test_util::{
     setup_test_http_app, setup_test_http_app_with_frontend, setup_test_prom_app_with_frontend,
     StorageType,
@@ -53,6 +58,9 @@ macro_rules! http_tests {
                 $service,
 
                 test_sql_api,
+                test_nan_and_infinity,
+                test_insert_invalid_rows,
+                test_time_travel,
                 test_prometheus_promql_api,
                 test_prom_http_api,
                 test_metrics_api,
@@ -267,6 +275,172 @@ pub async fn test_sql_api(store_type: StorageType) {
     guard.remove_all().await;
 }
 
+pub async fn test_nan_and_infinity(store_type: StorageType) {
+    common_telemetry::init_default_ut_logging();
+    let (app, mut guard) = setup_test_http_app_with_frontend(store_type, "nan_and_infinity").await;
+    let client = TestClient::new(app);
+
+    // Ingests by sql.
+    let res = client
+        .get("/v1/sql?sql=insert into demo values('host', 'NaN'::double, '-Infinity', 0)")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = serde_json::from_str::<JsonResponse>(&res.text().await).unwrap();
+    assert!(body.success());
+
+    // Ingests by prometheus remote write, with and without dropping NaN samples.
+    let mut timeseries = mock_timeseries();
+    timeseries[0].samples[0].value = f64::NAN;
+    timeseries[1].samples[0].value = f64::NAN;
+    timeseries[1].samples[1].value = f64::INFINITY;
+    let write_request = WriteRequest {
+        timeseries: timeseries[..1].to_vec(),
+        ..Default::default()
+    };
+    let res = client
+        .post("/v1/prometheus/write")
+        .body(snappy_compress(&write_request.encode_to_vec()).unwrap())
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    let write_request = WriteRequest {
+        timeseries: timeseries[1..2].to_vec(),
+        ..Default::default()
+    };
+    let res = client
+        .post("/v1/prometheus/write?drop_nan=true")
+        .body(snappy_compress(&write_request.encode_to_vec()).unwrap())
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let queries = [
+        (
+            "select cpu, memory from demo",
+            json!([["NaN", "-Infinity"]]),
+        ),
+        (
+            "select greptime_value from metric1 order by greptime_timestamp",
+            json!([["NaN"], [2.0]]),
+        ),
+        (
+            "select greptime_value from metric2 order by greptime_timestamp",
+            json!([["Infinity"]]),
+        ),
+    ];
+    for (sql, expected_rows) in queries {
+        let res = client.get(&format!("/v1/sql?sql={sql}")).send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // Non-finite values keep the payload valid json.
+        let body = serde_json::from_str::<serde_json::Value>(&res.text().await).unwrap();
+        assert_eq!(body["code"], json!(0), "{sql}: {body}");
+        assert_eq!(body["output"][0]["records"]["rows"], expected_rows, "{sql}");
+    }
+
+    guard.remove_all().await;
+}
+
+pub async fn test_insert_invalid_rows(store_type: StorageType) {
+    common_telemetry::init_default_ut_logging();
+    let (app, mut guard) = setup_test_http_app_with_frontend(store_type, "invalid_rows").await;
+    let client = TestClient::new(app);
+
+    // The hosts of the rows 1, 4 and 7 are null, but the column is not null.
+    let values = (0..8)
+        .map(|i| {
+            let host = if i % 3 == 1 {
+                "NULL".to_string()
+            } else {
+                format!("'host{i}'")
+            };
+            format!("({host}, {i})")
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let insert = format!("insert into demo(host, ts) values {values}");
+    let row_error = |row| json!({"row": row, "column": "host", "value": null, "reason": "null value in not null column"});
+
+    for (mode, expected) in [
+        (
+            "strict",
+            json!({"errors": [row_error(1)], "incomplete": false}),
+        ),
+        (
+            "lenient",
+            json!({"errors": [row_error(1), row_error(4), row_error(7)], "incomplete": false}),
+        ),
+    ] {
+        let res = client
+            .get(&format!("/v1/sql?validation_mode={mode}&sql={insert}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = serde_json::from_str::<JsonResponse>(&res.text().await).unwrap();
+        assert_eq!(body.code(), ErrorCode::InvalidArguments as u32);
+        assert_eq!(body.detail(), Some(&expected), "{mode}");
+    }
+
+    // The batch is rejected as a whole.
+    let res = client.get("/v1/sql?sql=select * from demo").send().await;
+    let body = serde_json::from_str::<serde_json::Value>(&res.text().await).unwrap();
+    assert_eq!(body["output"][0]["records"]["rows"], json!([]));
+
+    guard.remove_all().await;
+}
+
+pub async fn test_time_travel(store_type: StorageType) {
+    common_telemetry::init_default_ut_logging();
+    let (app, mut guard) = setup_test_http_app_with_frontend(store_type, "time_travel").await;
+    let client = TestClient::new(app);
+
+    let now_millis = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64
+    };
+    let pause = Duration::from_millis(200);
+    let insert = |cpu| format!("/v1/sql?sql=insert into demo values('host', {cpu}, 1024, 0)");
+
+    let res = client.get(&insert(66.6)).send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    tokio::time::sleep(pause).await;
+    let before_overwrite = now_millis();
+    tokio::time::sleep(pause).await;
+    let res = client.get(&insert(88.8)).send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    tokio::time::sleep(pause).await;
+    let after_overwrite = now_millis();
+
+    let select_as_of = |as_of| format!("/v1/sql?as_of={as_of}&sql=select cpu from demo");
+    for (as_of, cpu) in [(before_overwrite, 66.6), (after_overwrite, 88.8)] {
+        let res = client.get(&select_as_of(as_of)).send().await;
+        let body = serde_json::from_str::<serde_json::Value>(&res.text().await).unwrap();
+        assert_eq!(body["output"][0]["records"]["rows"], json!([[cpu]]));
+    }
+
+    // Out of the default retention of 10 minutes.
+    let res = client
+        .get(&select_as_of(now_millis() - 2 * 60 * 60 * 1000))
+        .send()
+        .await;
+    let body = serde_json::from_str::<JsonResponse>(&res.text().await).unwrap();
+    assert_eq!(body.code(), ErrorCode::InvalidArguments as u32);
+    assert!(body
+        .error()
+        .unwrap()
+        .contains("is gone, it's out of the time travel retention 600s"));
+
+    // The latest snapshot is read without `as_of`.
+    let res = client.get("/v1/sql?sql=select cpu from demo").send().await;
+    let body = serde_json::from_str::<serde_json::Value>(&res.text().await).unwrap();
+    assert_eq!(body["output"][0]["records"]["rows"], json!([[88.8]]));
+
+    guard.remove_all().await;
+}
+
 pub async fn test_prometheus_promql_api(store_type: StorageType) {
     common_telemetry::init_default_ut_logging();
     let (app, mut guard) = setup_test_http_app_with_frontend(store_type, "sql_api").await;
@@ -400,7 +574,7 @@ pub async fn test_health_api(store_type: StorageType) {
     assert_eq!(body_text, "{}");
 
     let body = serde_json::from_str::<HealthResponse>(&body_text).unwrap();
-    assert_eq!(body, HealthResponse {});
+    assert_eq!(body, HealthResponse::default());
 }
 
 #[cfg(feature = "dashboard")]
@@ -0,0 +1,16 @@
+CREATE DATABASE ttl_db WITH (ttl='7days', write_buffer_size='16MB');
+
+CREATE DATABASE wal_db WITH (wal='disabled');
+
+USE ttl_db;
+
+CREATE TABLE foo (ts BIGINT TIME INDEX) WITH (write_buffer_size='8MB');
+
+SELECT option_name, option_value, is_inherited
+FROM information_schema.table_options
+WHERE table_schema = 'ttl_db' AND table_name = 'foo' AND option_name IN ('ttl', 'write_buffer_size')
+ORDER BY option_name;
+
+DROP TABLE foo;
+
+USE public;
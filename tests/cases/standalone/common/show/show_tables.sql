@@ -0,0 +1,17 @@
+CREATE SCHEMA show_tables_test;
+
+USE show_tables_test;
+
+CREATE TABLE t1(i BIGINT TIME INDEX);
+
+SHOW TABLES;
+
+SHOW FULL TABLES;
+
+SHOW FULL TABLES WITH COMMENT;
+
+SHOW FULL TABLES LIKE 'x%';
+
+DROP TABLE t1;
+
+USE public;